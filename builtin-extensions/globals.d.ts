@@ -21,6 +21,20 @@ interface ByRoleOptions {
     selected?: boolean;
 }
 
+interface ByTextOptions {
+    /** Match the whole text, case-sensitively, instead of a substring. */
+    exact?: boolean;
+}
+
+interface LocatorFilterOptions {
+    /** Keep matches whose text contains this (case-insensitive) or matches the RegExp. */
+    hasText?: string | RegExp;
+    hasNotText?: string | RegExp;
+    /** Keep matches with an element matching this locator inside. */
+    has?: Locator;
+    hasNot?: Locator;
+}
+
 type ScreenshotClip = {
     x: number;
     y: number;
@@ -91,16 +105,45 @@ type WaitForEventOptions<E extends keyof PageEventValueMap> = {
     predicate?: WaitForEventPredicate<E>;
 };
 
+/** A `<select>` option to pick; a bare string matches value or label. */
+type SelectOptionValue =
+    | string
+    | { value?: string; label?: string; index?: number };
+
+/** Options for actions that wait for their element to be actionable. */
+interface ActionOptions {
+    /** Milliseconds to wait (default 30000). */
+    timeout?: number;
+    /** Skip every actionability check except being attached. */
+    force?: boolean;
+}
+
 interface Locator {
     readonly selector: string;
     locator(selector: string): Locator;
     getByRole(role: string, options?: ByRoleOptions): Locator;
+    getByText(text: string | RegExp, options?: ByTextOptions): Locator;
+    getByLabel(text: string | RegExp, options?: ByTextOptions): Locator;
     first(): Locator;
     last(): Locator;
     nth(index: number): Locator;
+    filter(options: LocatorFilterOptions): Locator;
     count(): Promise<number>;
-    click(options?: { timeout?: number } | number): Promise<void>;
-    fill(value: string, options?: { timeout?: number } | number): Promise<void>;
+    all(): Promise<Locator[]>;
+    click(options?: ActionOptions | number): Promise<void>;
+    fill(value: string, options?: ActionOptions | number): Promise<void>;
+    /** Pick `<select>` options; resolves to the selected values. */
+    selectOption(
+        values: SelectOptionValue | SelectOptionValue[] | null,
+        options?: ActionOptions | number,
+    ): Promise<string[]>;
+    /** Click a checkbox or radio button unless it is already checked. */
+    check(options?: ActionOptions | number): Promise<void>;
+    uncheck(options?: ActionOptions | number): Promise<void>;
+    /** Move the mouse over the element, e.g. to open a hover menu. */
+    hover(options?: ActionOptions | number): Promise<void>;
+    focus(options?: ActionOptions | number): Promise<void>;
+    blur(options?: ActionOptions | number): Promise<void>;
     innerText(options?: { timeout?: number } | number): Promise<string>;
     textContent(options?: { timeout?: number } | number): Promise<string>;
     getAttribute(
@@ -110,6 +153,13 @@ interface Locator {
     inputValue(options?: { timeout?: number } | number): Promise<string>;
     isVisible(): Promise<boolean>;
     isEnabled(): Promise<boolean>;
+    /** Viewport coordinates of the element once visible, for `page.mouse`. */
+    boundingBox(): Promise<{
+        x: number;
+        y: number;
+        width: number;
+        height: number;
+    }>;
     screenshot(
         options?: Omit<ScreenshotOptions, 'fullPage' | 'clip'>,
     ): Promise<Uint8Array>;
@@ -139,11 +189,50 @@ interface ElementHandle extends JSHandle {
     $$(selector: string): Promise<ElementHandle[]>;
 }
 
+/** Element methods on a frame handle run in that frame, whatever
+ * `page.switchToFrame` was last given. */
 interface Frame {
     url(): Promise<string>;
     name(): Promise<string>;
     parentFrame(): Promise<Frame | null>;
     page(): PageApi;
+    locator(selector: string): Locator;
+    getByRole(role: string, options?: ByRoleOptions): Locator;
+    getByText(text: string | RegExp, options?: ByTextOptions): Locator;
+    getByLabel(text: string | RegExp, options?: ByTextOptions): Locator;
+    evaluate(expression: string): Promise<unknown>;
+    waitForSelector(selector: string, timeoutMs?: number): Promise<void>;
+    click(selector: string, options?: ActionOptions | number): Promise<void>;
+    type(
+        selector: string,
+        text: string,
+        options?: ActionOptions | number,
+    ): Promise<void>;
+    fill(
+        selector: string,
+        value: string,
+        options?: ActionOptions | number,
+    ): Promise<void>;
+    selectOption(
+        selector: string,
+        values: SelectOptionValue | SelectOptionValue[] | null,
+        options?: ActionOptions | number,
+    ): Promise<string[]>;
+}
+
+/** A frame looked up by reference each time one of its methods runs. */
+interface FrameLocator {
+    locator(selector: string): Locator;
+    getByRole(role: string, options?: ByRoleOptions): Locator;
+    getByText(text: string | RegExp, options?: ByTextOptions): Locator;
+    getByLabel(text: string | RegExp, options?: ByTextOptions): Locator;
+    waitForSelector(selector: string, timeoutMs?: number): Promise<void>;
+    click(selector: string, options?: ActionOptions | number): Promise<void>;
+    fill(
+        selector: string,
+        value: string,
+        options?: ActionOptions | number,
+    ): Promise<void>;
 }
 
 interface Request {
@@ -187,9 +276,70 @@ interface Response {
     securityDetails(): Promise<SecurityDetails | null>;
 }
 
+/** Key events for the focused element; see `page.keyboard`. */
+interface Keyboard {
+    press(keys: string, options?: { delay?: number }): Promise<void>;
+    down(key: string): Promise<void>;
+    up(key: string): Promise<void>;
+    type(text: string, options?: { delay?: number }): Promise<void>;
+    insertText(text: string): Promise<void>;
+}
+
+interface MouseOptions {
+    button?: 'left' | 'right' | 'middle';
+    clickCount?: number;
+}
+
+/** Mouse events at viewport coordinates, in CSS pixels. */
+interface Mouse {
+    move(x: number, y: number, options?: { steps?: number }): Promise<void>;
+    click(
+        x: number,
+        y: number,
+        options?: MouseOptions & { delay?: number },
+    ): Promise<void>;
+    down(options?: MouseOptions): Promise<void>;
+    up(options?: MouseOptions): Promise<void>;
+    wheel(deltaX: number, deltaY: number): Promise<void>;
+}
+
+/** A request paused by `page.route`; decide with one of continue/fulfill/abort. */
+interface Route {
+    request(): Request;
+    continue(options?: {
+        url?: string;
+        method?: string;
+        headers?: Record<string, string>;
+        postData?: string | Uint8Array;
+    }): Promise<void>;
+    fulfill(options?: {
+        status?: number;
+        headers?: Record<string, string>;
+        contentType?: string;
+        body?: string | Uint8Array;
+        json?: unknown;
+    }): Promise<void>;
+    abort(errorCode?: string): Promise<void>;
+    fallback(): void;
+}
+
+type RouteHandler = (route: Route, request: Request) => unknown;
+
+/** Result of `page.extractTable()` and `context.html.tables[]` in extractors. */
+interface ExtractedTable {
+    headers: string[];
+    rows: string[][];
+    /** `rows` keyed by header; empty when the table has no header row. */
+    records: Record<string, string>[];
+}
+
 interface PageApi {
+    readonly keyboard: Keyboard;
+    readonly mouse: Mouse;
     locator(selector: string): Locator;
     getByRole(role: string, options?: ByRoleOptions): Locator;
+    getByText(text: string | RegExp, options?: ByTextOptions): Locator;
+    getByLabel(text: string | RegExp, options?: ByTextOptions): Locator;
     goto(
         url: string,
         options?: {
@@ -202,6 +352,7 @@ interface PageApi {
     waitForSelector(selector: string, timeoutMs?: number): Promise<void>;
     waitForNavigation(timeoutMs?: number): Promise<void>;
     waitForURL(pattern: string, timeoutMs?: number): Promise<void>;
+    waitForFunction(expression: string, timeoutMs?: number): Promise<unknown>;
     waitForLoadState(
         state?: 'load' | 'domcontentloaded' | 'networkidle' | 'commit',
         timeoutMs?: number,
@@ -220,6 +371,16 @@ interface PageApi {
         urlOrPredicate: RequestUrlMatcher,
         options?: { timeout?: number } | number,
     ): Promise<Request>;
+    route(
+        urlOrPredicate: RequestUrlMatcher,
+        handler: RouteHandler,
+        options?: { times?: number },
+    ): Promise<void>;
+    unroute(
+        urlOrPredicate: RequestUrlMatcher,
+        handler?: RouteHandler,
+    ): Promise<void>;
+    unrouteAll(): Promise<void>;
     networkRequests(): Promise<string>;
     responsesReceived(): Promise<string>;
     clearNetworkRequests(): Promise<void>;
@@ -236,18 +397,51 @@ interface PageApi {
     /** @deprecated Removed. Use browser.pages() and direct Page handles. */
     selectTab(index: number): Promise<never>;
     frames(): Promise<string>;
+    frame(frameRef: string): Promise<Frame>;
+    frameLocator(frameRef: string): FrameLocator;
     switchToFrame(frameRef: string): Promise<void>;
     switchToMainFrame(): Promise<void>;
-    click(selector: string): Promise<void>;
-    type(selector: string, text: string): Promise<void>;
-    fill(selector: string, value: string): Promise<void>;
+    setStrictSelectors(enabled: boolean): Promise<void>;
+    dragAndDrop(
+        source: string,
+        target: string,
+        options?: ActionOptions & { steps?: number },
+    ): Promise<void>;
+    click(selector: string, options?: ActionOptions | number): Promise<void>;
+    type(
+        selector: string,
+        text: string,
+        options?: ActionOptions | number,
+    ): Promise<void>;
+    fill(
+        selector: string,
+        value: string,
+        options?: ActionOptions | number,
+    ): Promise<void>;
+    selectOption(
+        selector: string,
+        values: SelectOptionValue | SelectOptionValue[] | null,
+        options?: ActionOptions | number,
+    ): Promise<string[]>;
     innerHTML(selector: string): Promise<string>;
     innerText(selector: string): Promise<string>;
     textContent(selector: string): Promise<string>;
+    extractTable(
+        selector: string,
+        options?: { headerRows?: number; includeEmptyRows?: boolean },
+    ): Promise<ExtractedTable>;
     getAttribute(selector: string, name: string): Promise<string>;
     inputValue(selector: string): Promise<string>;
     isVisible(selector: string): Promise<boolean>;
     isEnabled(selector: string): Promise<boolean>;
+    localStorage(): Promise<Record<string, string>>;
+    localStorage(key: string): Promise<string | null>;
+    setLocalStorage(key: string, value: string | null): Promise<void>;
+    clearLocalStorage(): Promise<void>;
+    sessionStorage(): Promise<Record<string, string>>;
+    sessionStorage(key: string): Promise<string | null>;
+    setSessionStorage(key: string, value: string | null): Promise<void>;
+    clearSessionStorage(): Promise<void>;
     evaluate(expression: string): Promise<unknown>;
     evaluateHandle(expression: string): Promise<unknown>;
     frameEvaluate(frameRef: string, expression: string): Promise<unknown>;
@@ -262,6 +456,8 @@ interface PageApi {
     popupEvents(): Promise<string>;
     screenshot(options?: ScreenshotOptions): Promise<Uint8Array>;
     waitForDownload(timeoutMs?: number): Promise<PageDownload>;
+    close(): Promise<void>;
+    isClosed(): Promise<boolean>;
 }
 
 interface BrowserApi {
@@ -291,7 +487,118 @@ interface SessionMetadata {
     dateRangeEnd?: string;
 }
 
+/** One transaction's detail page, matched by `fitid` or else `date` and `amount`. */
+interface TransactionDetail {
+    fitid?: string;
+    date?: string;
+    amount?: string | number;
+    merchant?: string;
+    /** Address as shown on the page. */
+    location?: string;
+    city?: string;
+    /** State or province. */
+    region?: string;
+    postalCode?: string;
+    country?: string;
+    /** Decimal degrees. */
+    latitude?: string | number;
+    longitude?: string | number;
+    cardMember?: string;
+    tags?: Record<string, string>;
+    comment?: string;
+}
+
+/** What `refreshmint.tryExtract` found in a staged resource. */
+interface TryExtractResult {
+    count: number;
+    firstDate: string | null;
+    lastDate: string | null;
+    /** Why extraction failed; `count` is 0 then. */
+    error?: string;
+}
+
+interface LoginFlowCredentials {
+    /** Value for `page.fill`, usually a manifest secret name. */
+    username: string;
+    /** Value for `page.fill`, usually a manifest secret name. */
+    password: string;
+}
+
+interface LoginFlowSelectors {
+    username?: string[];
+    password?: string[];
+    next?: string[];
+    submit?: string[];
+    otp?: string[];
+    otpSubmit?: string[];
+    error?: string[];
+}
+
+interface LoginFlowOptions {
+    /** Wait for the widget to advance after each action. Default 60000. */
+    timeoutMs?: number;
+    /** Message for `refreshmint.prompt` when a verification code is needed. */
+    otpPrompt?: string;
+    /** Reject instead of prompting for a verification code. */
+    noMfa?: boolean;
+    /** Selectors tried before the built-in ones. */
+    selectors?: LoginFlowSelectors;
+}
+
+interface LoginFlowsApi {
+    okta(
+        login: LoginFlowCredentials,
+        options?: LoginFlowOptions,
+    ): Promise<void>;
+    forgerock(
+        login: LoginFlowCredentials,
+        options?: LoginFlowOptions,
+    ): Promise<void>;
+    /** Switch to the Plaid Link iframe with `page.switchToFrame` first. */
+    plaid(
+        login: LoginFlowCredentials,
+        options?: LoginFlowOptions,
+    ): Promise<void>;
+}
+
+interface StorageApi {
+    /** Resolves to `undefined` when `key` is unset. */
+    get(key: string): Promise<unknown>;
+    /** Persists a JSON value for this login+extension. Never store secrets. */
+    set(key: string, value: unknown): Promise<void>;
+    /** Resolves to whether `key` was set. */
+    delete(key: string): Promise<boolean>;
+}
+
+interface ExpectOptions {
+    /** Milliseconds to keep retrying before failing (default 5000). */
+    timeout?: number;
+}
+
+interface Expectation {
+    /** The same expectation with each matcher inverted. */
+    readonly not: Expectation;
+    toBeVisible(options?: ExpectOptions): Promise<void>;
+    /** Strings match the whole normalized text; RegExps may match part. */
+    toHaveText(
+        expected: string | RegExp,
+        options?: ExpectOptions,
+    ): Promise<void>;
+    /** Strings match the whole value exactly; RegExps may match part. */
+    toHaveValue(
+        expected: string | RegExp,
+        options?: ExpectOptions,
+    ): Promise<void>;
+}
+
 interface RefreshmintApi {
+    /** Rejects with captured page context unless `condition` is truthy. */
+    assert(condition: unknown, message?: string): Promise<void>;
+    expect(target: Locator | string): Expectation;
+    /** Shared sign-in flows for common identity-provider widgets. */
+    readonly flows: LoginFlowsApi;
+    /** Small key/value state kept between runs. */
+    readonly storage: StorageApi;
     saveResource(
         filename: string,
         data: string | Uint8Array | number[] | ArrayLike<number>,
@@ -302,6 +609,11 @@ interface RefreshmintApi {
         filename?: string,
         options?: SaveResourceOptions,
     ): Promise<void>;
+    /** Stages details that extraction merges into matching transactions as tags and comments. */
+    saveTransactionDetails(
+        details: TransactionDetail[],
+        options?: SaveResourceOptions,
+    ): Promise<void>;
     listAccountDocuments(
         filter?:
             | string
@@ -311,16 +623,27 @@ interface RefreshmintApi {
               },
     ): Promise<string>;
     setSessionMetadata(metadata: SessionMetadata): Promise<void>;
+    /** Runs the extractor on the latest staged resource named `filename` without finalizing it. */
+    tryExtract(filename: string): Promise<TryExtractResult>;
+    /** JS API version this app provides; see manifest `minApiVersion`. */
+    readonly apiVersion: number;
+    /** Called when a step lands on a manifest `loginWallUrls` page; the step is then repeated. */
+    setLoginHandler(login: (() => Promise<void>) | null): void;
     reportValue(key: string, value: string): void;
     log(message: string): void;
+    /** `message` may be a manifest `prompts` key; answers are validated. */
     prompt(message: string): Promise<string>;
     /** Returns CLI --option key/value pairs as a JS object. Returns {} when no options are supplied. */
     getOptions(): Record<string, unknown>;
+    /** Returns every value in `document` matching a JSONPath such as `$.data.items[*].amount`. Also available in extract scripts. */
+    extractJson(document: unknown, path: string): unknown[];
 }
 
 declare const page: PageApi;
 declare const browser: BrowserApi;
 declare const refreshmint: RefreshmintApi;
+/** The same as `refreshmint.expect`. */
+declare function expect(target: Locator | string): Expectation;
 
 declare module 'refreshmint:util' {
     interface InspectOptions {
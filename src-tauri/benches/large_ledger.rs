@@ -0,0 +1,225 @@
+//! Load benchmark over a synthetic large account journal.
+//!
+//! Run with `cargo bench --bench large_ledger --features perf-stats`. Each
+//! phase has a time budget; the run exits non-zero when a phase goes over,
+//! so CI can catch regressions. `REFRESHMINT_BENCH_ENTRIES` sets the journal
+//! size (default 100000) and `REFRESHMINT_BENCH_BUDGET_SCALE` scales every
+//! budget for slower machines.
+
+use app_lib::account_journal::{self, AccountEntry, EntryPosting, EntryStatus, SimpleAmount};
+use app_lib::dedup::{self, DedupConfig, DedupResult};
+use app_lib::extract::{ExtractedAmount, ExtractedPosting, ExtractedTransaction};
+use std::error::Error;
+use std::fs;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+const DEFAULT_ENTRIES: usize = 100_000;
+/// Rows in the re-extracted statement run through dedup.
+const STATEMENT_ROWS: usize = 500;
+const PAYEES: [&str; 8] = [
+    "GROCERY OUTLET",
+    "SHELL OIL 5742",
+    "NETFLIX.COM",
+    "PG&E WEB ONLINE",
+    "TRADER JOE'S #123",
+    "AMAZON MKTPLACE PMTS",
+    "PAYROLL DEPOSIT",
+    "CITY OF OAKLAND PARKING",
+];
+
+struct Phase {
+    name: &'static str,
+    elapsed: Duration,
+    budget: Duration,
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let entries_count =
+        env_number("REFRESHMINT_BENCH_ENTRIES").unwrap_or(DEFAULT_ENTRIES as f64) as usize;
+    let budget_scale = env_number("REFRESHMINT_BENCH_BUDGET_SCALE").unwrap_or(1.0);
+    let budget = |millis: f64| {
+        Duration::from_secs_f64(
+            millis * budget_scale * entries_count as f64 / DEFAULT_ENTRIES as f64 / 1000.0,
+        )
+    };
+
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH)?.as_nanos();
+    let root =
+        std::env::temp_dir().join(format!("refreshmint-bench-{}-{nanos}", std::process::id()));
+    fs::create_dir_all(&root)?;
+    let journal_path = root.join("account.journal");
+    let mut phases = Vec::new();
+
+    let started = Instant::now();
+    let entries: Vec<AccountEntry> = (0..entries_count).map(synthetic_entry).collect();
+    account_journal::write_journal_at_path(&journal_path, &entries)?;
+    phases.push(Phase {
+        name: "generate + write journal",
+        elapsed: started.elapsed(),
+        budget: budget(10_000.0),
+    });
+
+    let started = Instant::now();
+    let existing = account_journal::read_journal_at_path(&journal_path)?;
+    phases.push(Phase {
+        name: "read journal",
+        elapsed: started.elapsed(),
+        budget: budget(5_000.0),
+    });
+    if existing.len() != entries_count {
+        return Err(format!("read {} entries, wrote {entries_count}", existing.len()).into());
+    }
+
+    // Re-extract the newest statement: half its rows are already in the
+    // journal under the same evidence, half are new.
+    let statement: Vec<ExtractedTransaction> = (0..STATEMENT_ROWS)
+        .map(|row| {
+            if row % 2 == 0 {
+                extracted_from(&existing[entries_count - 1 - row])
+            } else {
+                synthetic_extracted(entries_count + row)
+            }
+        })
+        .collect();
+    let started = Instant::now();
+    let actions = dedup::run_dedup(
+        &existing,
+        &statement,
+        "statement-new.csv",
+        &DedupConfig::default(),
+    );
+    phases.push(Phase {
+        name: "dedup statement",
+        elapsed: started.elapsed(),
+        budget: budget(1_000.0),
+    });
+    let new_rows = actions
+        .iter()
+        .filter(|action| matches!(action.result, DedupResult::New))
+        .count();
+
+    let _ = fs::remove_dir_all(&root);
+
+    println!("{entries_count} entries, {STATEMENT_ROWS} statement rows ({new_rows} new)");
+    let mut over_budget = false;
+    for phase in &phases {
+        let over = phase.elapsed > phase.budget;
+        over_budget |= over;
+        println!(
+            "{:<26} {:>10.1} ms  budget {:>10.1} ms{}",
+            phase.name,
+            phase.elapsed.as_secs_f64() * 1000.0,
+            phase.budget.as_secs_f64() * 1000.0,
+            if over { "  OVER BUDGET" } else { "" }
+        );
+    }
+    let stats = app_lib::perf::performance_stats();
+    if stats.enabled {
+        println!();
+        for stat in stats.stats {
+            println!(
+                "{:<10} {:<16} {:>6} calls {:>10.1} ms total {:>10.1} ms max",
+                stat.category, stat.name, stat.count, stat.total_ms, stat.max_ms
+            );
+        }
+    }
+    if over_budget {
+        return Err("benchmark exceeded its time budget".into());
+    }
+    Ok(())
+}
+
+fn env_number(name: &str) -> Option<f64> {
+    std::env::var(name).ok()?.trim().parse().ok()
+}
+
+fn synthetic_entry(index: usize) -> AccountEntry {
+    let date = synthetic_date(index);
+    let mut entry = AccountEntry::new(
+        date.clone(),
+        EntryStatus::Cleared,
+        PAYEES[index % PAYEES.len()].to_string(),
+        vec![format!(
+            "statement-{}.csv:{}:1",
+            &date[..7],
+            index % 1000 + 1
+        )],
+        vec![
+            EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: synthetic_amount(index),
+                }),
+            },
+            EntryPosting {
+                account: "Equity:Staging:Checking".to_string(),
+                amount: None,
+            },
+        ],
+    );
+    entry
+        .tags
+        .push(("bankId".to_string(), format!("FIT{index:08}")));
+    entry
+}
+
+fn extracted_from(entry: &AccountEntry) -> ExtractedTransaction {
+    let amount = entry
+        .postings
+        .first()
+        .and_then(|posting| posting.amount.clone())
+        .map(|amount| ExtractedAmount {
+            acommodity: amount.commodity,
+            aquantity: amount.quantity,
+        });
+    let mut ttags: Vec<(String, String)> = entry
+        .evidence
+        .iter()
+        .map(|evidence| ("evidence".to_string(), evidence.clone()))
+        .collect();
+    ttags.extend(entry.tags.iter().cloned());
+    ExtractedTransaction {
+        tdate: entry.date.clone(),
+        tstatus: "Cleared".to_string(),
+        tdescription: entry.description.clone(),
+        tcomment: String::new(),
+        ttags,
+        tpostings: Some(vec![ExtractedPosting {
+            paccount: "Assets:Checking".to_string(),
+            pamount: amount.map(|amount| vec![amount]),
+        }]),
+    }
+}
+
+fn synthetic_extracted(index: usize) -> ExtractedTransaction {
+    let mut txn = extracted_from(&synthetic_entry(index));
+    txn.ttags = vec![
+        (
+            "evidence".to_string(),
+            format!("statement-new.csv:{index}:1"),
+        ),
+        ("bankId".to_string(), format!("FIT{index:08}")),
+    ];
+    txn
+}
+
+/// Dates spread evenly, about 90 entries a day.
+fn synthetic_date(index: usize) -> String {
+    let day = index / 90;
+    let year = 2015 + day / 360;
+    let month = day % 360 / 30 + 1;
+    let day_of_month = day % 30 + 1;
+    let day_of_month = if month == 2 {
+        day_of_month.min(28)
+    } else {
+        day_of_month
+    };
+    format!("{year:04}-{month:02}-{day_of_month:02}")
+}
+
+fn synthetic_amount(index: usize) -> String {
+    let cents = (index * 7919) % 50_000 + 100;
+    let sign = if index % PAYEES.len() == 6 { "" } else { "-" };
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
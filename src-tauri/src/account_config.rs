@@ -1,12 +1,13 @@
 use serde::{Deserialize, Serialize};
-use std::io::{self, Write};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Per-account configuration stored in `accounts/<name>/config.json`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
 pub struct AccountConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub extension: Option<String>,
 }
 
@@ -34,26 +35,6 @@ pub fn read_account_config(ledger_dir: &Path, account_name: &str) -> AccountConf
     }
 }
 
-/// Write the account config via temp-file + rename.
-///
-/// On Unix this is an atomic replace. On Windows we fall back to remove+rename
-/// when the destination already exists.
-fn replace_file(temp_path: &Path, path: &Path) -> std::io::Result<()> {
-    match std::fs::rename(temp_path, path) {
-        Ok(()) => Ok(()),
-        Err(err) => {
-            #[cfg(windows)]
-            {
-                if err.kind() == std::io::ErrorKind::AlreadyExists {
-                    std::fs::remove_file(path)?;
-                    return std::fs::rename(temp_path, path);
-                }
-            }
-            Err(err)
-        }
-    }
-}
-
 /// Write the account config via temp-file + rename.
 pub fn write_account_config(
     ledger_dir: &Path,
@@ -61,32 +42,8 @@ pub fn write_account_config(
     config: &AccountConfig,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let path = config_path(ledger_dir, account_name);
-    let parent = path
-        .parent()
-        .ok_or_else(|| std::io::Error::other("config path has no parent"))?;
-    std::fs::create_dir_all(parent)?;
-
     let json = serde_json::to_string_pretty(config)?;
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    let temp_path = parent.join(format!(".config.json.tmp-{}-{nanos}", std::process::id()));
-    {
-        let mut file = std::fs::OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&temp_path)?;
-        file.write_all(json.as_bytes())?;
-        file.sync_all()?;
-    }
-    if let Err(err) = replace_file(&temp_path, &path) {
-        let _ = std::fs::remove_file(&temp_path);
-        return Err(err.into());
-    }
-    if let Ok(dir) = std::fs::File::open(parent) {
-        let _ = dir.sync_all();
-    }
+    crate::atomic_file::write_atomic(&path, json.as_bytes())?;
     Ok(())
 }
 
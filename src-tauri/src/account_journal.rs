@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::fmt::Write as FmtWrite;
-use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
+use std::fs;
+use std::io;
 use std::path::{Path, PathBuf};
 
 /// Status of a transaction entry, matching hledger conventions.
@@ -22,6 +22,32 @@ impl EntryStatus {
     }
 }
 
+/// Whether the user has checked an entry, kept in a `review:` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts", rename = "EntryReviewState")]
+pub enum ReviewState {
+    Reviewed,
+    Flagged,
+}
+
+impl ReviewState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Reviewed => "reviewed",
+            Self::Flagged => "flagged",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "reviewed" => Some(Self::Reviewed),
+            "flagged" => Some(Self::Flagged),
+            _ => None,
+        }
+    }
+}
+
 /// A simple amount for account journal entries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SimpleAmount {
@@ -54,6 +80,11 @@ pub struct AccountEntry {
     pub posted: Option<String>,
     #[serde(default)]
     pub posted_postings: Vec<(usize, String)>,
+    /// Free-form user note, written as one `note:` tag per line.
+    #[serde(default)]
+    pub note: Option<String>,
+    #[serde(default)]
+    pub review: Option<ReviewState>,
 }
 
 impl AccountEntry {
@@ -77,6 +108,8 @@ impl AccountEntry {
             extracted_by: None,
             posted: None,
             posted_postings: Vec::new(),
+            note: None,
+            review: None,
         }
     }
 
@@ -98,6 +131,11 @@ impl AccountEntry {
         self.evidence.iter().any(|e| e == evidence_ref)
     }
 
+    /// Whether the entry, or any of its postings, is posted to the GL.
+    pub fn is_posted(&self) -> bool {
+        self.posted.is_some() || !self.posted_postings.is_empty()
+    }
+
     /// Add an evidence reference if not already present.
     pub fn add_evidence(&mut self, evidence_ref: String) {
         if !self.evidence.iter().any(|e| e == &evidence_ref) {
@@ -171,6 +209,20 @@ pub fn format_entry(entry: &AccountEntry) -> String {
         comments.push(format!("posted-posting-{idx}: {gl_ref}"));
     }
 
+    // `review:` and `note:` are set by the user, not by extraction.
+    if let Some(review) = entry.review {
+        comments.push(format!("review: {}", review.as_str()));
+    }
+    if let Some(note) = &entry.note {
+        for line in note.lines() {
+            if line.is_empty() {
+                comments.push("note:".to_string());
+            } else {
+                comments.push(format!("note: {line}"));
+            }
+        }
+    }
+
     // custom tags
     for (key, value) in &entry.tags {
         if key != "id"
@@ -178,6 +230,7 @@ pub fn format_entry(entry: &AccountEntry) -> String {
             && key != "extracted-by"
             && key != "posted"
             && !key.starts_with("posted-posting-")
+            && key != "note"
         {
             if value.is_empty() {
                 comments.push(format!("{key}:"));
@@ -187,9 +240,9 @@ pub fn format_entry(entry: &AccountEntry) -> String {
         }
     }
 
-    // Inline comment if not empty
+    // Plain comment lines come first; each gets its own `;` line.
     if !entry.comment.is_empty() {
-        comments.insert(0, entry.comment.clone());
+        comments.splice(0..0, entry.comment.lines().map(str::to_string));
     }
 
     // Write comment lines
@@ -247,7 +300,7 @@ pub fn write_journal_at_path(path: &Path, entries: &[AccountEntry]) -> io::Resul
     }
 
     let content = format_journal(entries);
-    atomic_write(path, content.as_bytes())
+    crate::atomic_file::write_atomic(path, content.as_bytes())
 }
 
 /// Append a single entry to the account journal.
@@ -262,13 +315,7 @@ pub fn append_entry_at_path(path: &Path, entry: &AccountEntry) -> io::Result<()>
         fs::create_dir_all(parent)?;
     }
 
-    let formatted = format_entry(entry);
-    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
-    if file.metadata()?.len() > 0 {
-        file.write_all(b"\n")?;
-    }
-    file.write_all(formatted.as_bytes())?;
-    Ok(())
+    crate::atomic_file::append_block(path, &format_entry(entry))
 }
 
 /// Read all entries from the account journal by parsing the file.
@@ -282,6 +329,7 @@ pub fn read_journal(ledger_dir: &Path, account_name: &str) -> io::Result<Vec<Acc
 
 /// Read all entries from a specific journal path.
 pub fn read_journal_at_path(path: &Path) -> io::Result<Vec<AccountEntry>> {
+    let _span = crate::perf::span("journal", "read");
     if !path.exists() {
         return Ok(Vec::new());
     }
@@ -344,6 +392,8 @@ pub fn parse_journal(content: &str) -> io::Result<Vec<AccountEntry>> {
         let mut extracted_by = None;
         let mut posted = None;
         let mut posted_postings = Vec::new();
+        let mut note_lines: Vec<&str> = Vec::new();
+        let mut review = None;
         let mut tags = Vec::new();
         let mut comment = String::new();
 
@@ -358,6 +408,13 @@ pub fn parse_journal(content: &str) -> io::Result<Vec<AccountEntry>> {
                 posted = Some(rest.trim().to_string());
             } else if let Some(rest) = strip_posted_posting_prefix(comment_line) {
                 posted_postings.push(rest);
+            } else if let Some(state) = comment_line
+                .strip_prefix("review:")
+                .and_then(|rest| ReviewState::parse(rest.trim()))
+            {
+                review = Some(state);
+            } else if let Some(rest) = comment_line.strip_prefix("note:") {
+                note_lines.push(rest.trim());
             } else if let Some((key, value)) = parse_tag_line(comment_line) {
                 tags.push((key, value));
             } else {
@@ -387,6 +444,8 @@ pub fn parse_journal(content: &str) -> io::Result<Vec<AccountEntry>> {
             extracted_by,
             posted,
             posted_postings,
+            note: (!note_lines.is_empty()).then(|| note_lines.join("\n")),
+            review,
         });
     }
 
@@ -453,19 +512,6 @@ fn parse_tag_line(line: &str) -> Option<(String, String)> {
     Some((key.to_string(), value.to_string()))
 }
 
-fn atomic_write(path: &Path, content: &[u8]) -> io::Result<()> {
-    let temp_path = path.with_extension("tmp");
-    let mut file = OpenOptions::new()
-        .create(true)
-        .write(true)
-        .truncate(true)
-        .open(&temp_path)?;
-    file.write_all(content)?;
-    file.flush()?;
-    fs::rename(&temp_path, path)?;
-    Ok(())
-}
-
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -514,6 +560,8 @@ mod tests {
             extracted_by: Some("chase-driver:1.0".to_string()),
             posted: None,
             posted_postings: Vec::new(),
+            note: None,
+            review: None,
         };
 
         let formatted = format_entry(&entry);
@@ -646,6 +694,46 @@ mod tests {
         assert!(formatted.contains("! Pending txn"));
     }
 
+    #[test]
+    fn note_and_review_round_trip_as_tags() {
+        let mut entry = AccountEntry::new(
+            "2024-01-01".to_string(),
+            EntryStatus::Cleared,
+            "Refund".to_string(),
+            vec![],
+            vec![EntryPosting {
+                account: "A".to_string(),
+                amount: None,
+            }],
+        );
+        entry.comment = "split bill\nsee receipt".to_string();
+        entry.note = Some("ask about fee\n\nsee email".to_string());
+        entry.review = Some(ReviewState::Flagged);
+        entry.tags = vec![("reviewer".to_string(), "sam".to_string())];
+
+        let formatted = format_entry(&entry);
+        assert!(formatted.contains("    ; review: flagged\n"));
+        assert!(
+            formatted.contains("    ; note: ask about fee\n    ; note:\n    ; note: see email\n")
+        );
+
+        let parsed = parse_journal(&formatted).unwrap();
+        assert_eq!(
+            parsed[0].note.as_deref(),
+            Some("ask about fee\n\nsee email")
+        );
+        assert_eq!(parsed[0].review, Some(ReviewState::Flagged));
+        assert_eq!(parsed[0].comment, "split bill\nsee receipt");
+        assert_eq!(parsed[0].tags, entry.tags);
+
+        let unknown = parse_journal("2024-01-01  X\n    ; review: later\n    A\n").unwrap();
+        assert_eq!(unknown[0].review, None);
+        assert_eq!(
+            unknown[0].tags,
+            vec![("review".to_string(), "later".to_string())]
+        );
+    }
+
     #[test]
     fn read_nonexistent_returns_empty() {
         let root = temp_dir("nonexist");
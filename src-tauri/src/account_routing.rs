@@ -0,0 +1,552 @@
+//! The account-number registry, and routing transactions from one document
+//! to several login accounts.
+//!
+//! Each label's `accountIds` config claims the institution's identifiers for
+//! that account: an OFX `ACCTID`, an IBAN, or a masked number such as
+//! `****1234`. Extractors tag each transaction with its account's identifier
+//! as `accountId`, and drivers may pass one as `accountId` metadata when they
+//! save a document. The registry is filled on first sight: a scrape registers
+//! an unknown identifier to the label its document was saved under, and an
+//! extraction queues unknown identifiers in `unknown-account-ids.json` until
+//! someone assigns them. Once registered, an identifier decides the label, so
+//! a bank renaming an account's nickname does not start a new label.
+//!
+//! Some exports cover more than one account: an OFX file with a statement
+//! per account, or an MT940 file with several `:25:` blocks. After a label's
+//! documents are extracted, transactions whose `accountId` another label
+//! claims move to that label, and each document they cite is copied into
+//! that label's documents directory with its sidecar. Their evidence refs
+//! then name a document of the journal they are written to, as refs always
+//! do, and dedup runs against that journal's own history.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::extract::ExtractedTransaction;
+use crate::login_config::LoginConfig;
+
+const UNKNOWN_ACCOUNT_IDS_FILE: &str = "unknown-account-ids.json";
+
+/// Characters institutions print in place of hidden digits, after
+/// [`normalize_account_id`] has uppercased them.
+const MASK_CHARS: &[char] = &['*', 'X', '\u{2022}', '\u{b7}', '.', '#', '-', '_'];
+
+/// Tag naming the institution's identifier for a transaction's account.
+pub const ACCOUNT_ID_TAG: &str = "accountId";
+
+/// Transactions and documents bound for one label.
+#[derive(Debug, Default)]
+pub struct Route {
+    pub label: String,
+    pub proposed_transactions: Vec<ExtractedTransaction>,
+    pub document_names: Vec<String>,
+}
+
+/// How one label's extraction splits across labels.
+#[derive(Debug, Default)]
+pub struct Routing {
+    pub routes: Vec<Route>,
+    /// Identifiers seen for the first time. Their transactions stayed with
+    /// the extracted label.
+    pub unknown_account_ids: Vec<UnknownAccountId>,
+}
+
+/// An identifier extraction found that no label claims.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct UnknownAccountId {
+    pub account_id: String,
+    pub login_name: String,
+    /// Label whose document the identifier was first seen in.
+    pub label: String,
+    pub document: String,
+    pub first_seen: String,
+}
+
+/// Identifiers compare without whitespace and case, so `DE89 3704 ...` in an
+/// export matches `de893704...` in the config.
+pub fn normalize_account_id(id: &str) -> String {
+    id.chars()
+        .filter(|c| !c.is_whitespace())
+        .collect::<String>()
+        .to_uppercase()
+}
+
+/// The visible digits of a masked identifier such as `****1234` or `X1234`.
+fn masked_tail(normalized: &str) -> Option<&str> {
+    let tail = normalized.trim_start_matches(MASK_CHARS);
+    (tail.len() < normalized.len() && tail.len() >= 4 && tail.chars().all(|c| c.is_ascii_digit()))
+        .then_some(tail)
+}
+
+/// Whether two identifiers can name the same account: equal once
+/// normalized, or a masked one whose visible digits end the other.
+pub fn account_ids_match(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_account_id(a), normalize_account_id(b));
+    if a == b {
+        return true;
+    }
+    match (masked_tail(&a), masked_tail(&b)) {
+        (Some(x), Some(y)) => x.ends_with(y) || y.ends_with(x),
+        (Some(tail), None) => b.ends_with(tail),
+        (None, Some(tail)) => a.ends_with(tail),
+        (None, None) => false,
+    }
+}
+
+/// The label claiming an identifier: the label with an exact claim, else the
+/// only label with a claim it matches once masks are considered.
+pub fn resolve_account_id<'a>(config: &'a LoginConfig, id: &str) -> Option<&'a str> {
+    let normalized = normalize_account_id(id);
+    let claims = || {
+        config.accounts.iter().flat_map(|(label, account)| {
+            account
+                .account_ids
+                .iter()
+                .map(move |claim| (label.as_str(), claim))
+        })
+    };
+    if let Some((label, _)) = claims().find(|(_, claim)| normalize_account_id(claim) == normalized)
+    {
+        return Some(label);
+    }
+    let mut labels: Vec<&str> = claims()
+        .filter(|(_, claim)| account_ids_match(claim, id))
+        .map(|(label, _)| label)
+        .collect();
+    labels.dedup();
+    match labels.as_slice() {
+        [label] => Some(label),
+        _ => None,
+    }
+}
+
+/// Split a label's extraction by `accountId`. The first route is always
+/// `label` itself with every extracted document, so a document that routes
+/// all of its transactions elsewhere is still recorded as extracted; the
+/// other routes follow in label order. Transactions without an `accountId`,
+/// or with one no label claims, stay with `label`; identifiers no label
+/// claims are queued for assignment.
+pub fn route_extraction(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    proposed_transactions: Vec<ExtractedTransaction>,
+    document_names: &[String],
+) -> io::Result<Routing> {
+    let config = crate::login_config::read_login_config(ledger_dir, login_name);
+
+    let mut source = Route {
+        label: label.to_string(),
+        proposed_transactions: Vec::new(),
+        document_names: document_names.to_vec(),
+    };
+    let mut routed: BTreeMap<&str, Vec<ExtractedTransaction>> = BTreeMap::new();
+    let mut unknown: Vec<(String, String)> = Vec::new();
+    for txn in proposed_transactions {
+        let account_id = txn
+            .ttags
+            .iter()
+            .find(|(key, _)| key == ACCOUNT_ID_TAG)
+            .map(|(_, id)| id.trim())
+            .filter(|id| !id.is_empty());
+        let claimant = account_id.and_then(|id| resolve_account_id(&config, id));
+        if let (Some(id), None) = (account_id, claimant) {
+            if !unknown
+                .iter()
+                .any(|(other, _)| account_ids_match(other, id))
+            {
+                let document = txn
+                    .evidence_refs()
+                    .first()
+                    .map(|e| crate::provenance::split_evidence_ref(e).0.to_string())
+                    .unwrap_or_default();
+                unknown.push((id.to_string(), document));
+            }
+        }
+        match claimant.filter(|target| *target != label) {
+            Some(target) => routed.entry(target).or_default().push(txn),
+            None => source.proposed_transactions.push(txn),
+        }
+    }
+
+    let mut routes = vec![source];
+    for (target, proposed_transactions) in routed {
+        let document_names: Vec<String> = document_names
+            .iter()
+            .filter(|doc_name| {
+                proposed_transactions.iter().any(|txn| {
+                    txn.evidence_refs()
+                        .iter()
+                        .any(|e| crate::extract::evidence_ref_cites_document(e, doc_name))
+                })
+            })
+            .cloned()
+            .collect();
+        for doc_name in &document_names {
+            copy_document(ledger_dir, login_name, label, target, doc_name)?;
+        }
+        routes.push(Route {
+            label: target.to_string(),
+            proposed_transactions,
+            document_names,
+        });
+    }
+    let unknown_account_ids = queue_unknown_account_ids(ledger_dir, login_name, label, unknown)?;
+    Ok(Routing {
+        routes,
+        unknown_account_ids,
+    })
+}
+
+pub fn unknown_account_ids_path(ledger_dir: &Path, login_name: &str) -> PathBuf {
+    ledger_dir
+        .join("logins")
+        .join(login_name)
+        .join(UNKNOWN_ACCOUNT_IDS_FILE)
+}
+
+/// Identifiers extraction has seen that no label claims, oldest first.
+pub fn list_unknown_account_ids(
+    ledger_dir: &Path,
+    login_name: &str,
+) -> io::Result<Vec<UnknownAccountId>> {
+    Ok(
+        crate::bookkeeping::read_optional_json(&unknown_account_ids_path(ledger_dir, login_name))?
+            .unwrap_or_default(),
+    )
+}
+
+/// Add `(identifier, document)` pairs not already queued. Returns the ones
+/// added.
+fn queue_unknown_account_ids(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    seen: Vec<(String, String)>,
+) -> io::Result<Vec<UnknownAccountId>> {
+    if seen.is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut queue = list_unknown_account_ids(ledger_dir, login_name)?;
+    let now = crate::operations::now_timestamp();
+    let mut added = Vec::new();
+    for (account_id, document) in seen {
+        if queue
+            .iter()
+            .any(|queued| account_ids_match(&queued.account_id, &account_id))
+        {
+            continue;
+        }
+        let unknown = UnknownAccountId {
+            account_id,
+            login_name: login_name.to_string(),
+            label: label.to_string(),
+            document,
+            first_seen: now.clone(),
+        };
+        queue.push(unknown.clone());
+        added.push(unknown);
+    }
+    if !added.is_empty() {
+        crate::bookkeeping::write_json(&unknown_account_ids_path(ledger_dir, login_name), &queue)?;
+    }
+    Ok(added)
+}
+
+/// Register an identifier to a label and drop it, and any masked form of it,
+/// from the unknown queue. Returns the label's identifiers. Transactions
+/// already extracted stay where they are; the next extraction routes by the
+/// new claim.
+pub fn assign_account_id(
+    ledger_dir: &Path,
+    login_name: &str,
+    account_id: &str,
+    label: &str,
+) -> Result<Vec<String>, String> {
+    let account_id = account_id.trim();
+    if account_id.is_empty() {
+        return Err("account id is required".to_string());
+    }
+    let config = crate::login_config::read_login_config(ledger_dir, login_name);
+    let mut ids = config
+        .accounts
+        .get(label)
+        .map(|account| account.account_ids.clone())
+        .unwrap_or_default();
+    ids.push(account_id.to_string());
+    let ids = set_login_account_ids(ledger_dir, login_name, label, ids)?;
+
+    let mut queue =
+        list_unknown_account_ids(ledger_dir, login_name).map_err(|err| err.to_string())?;
+    let before = queue.len();
+    queue.retain(|queued| !account_ids_match(&queued.account_id, account_id));
+    if queue.len() != before {
+        crate::bookkeeping::write_json(&unknown_account_ids_path(ledger_dir, login_name), &queue)
+            .map_err(|err| err.to_string())?;
+    }
+    Ok(ids)
+}
+
+/// The identifier a driver passed as `accountId` metadata when saving a
+/// document.
+pub fn metadata_account_id(metadata: &BTreeMap<String, serde_json::Value>) -> Option<String> {
+    let id = match metadata.get(ACCOUNT_ID_TAG)? {
+        serde_json::Value::String(id) => id.trim().to_string(),
+        serde_json::Value::Number(id) => id.to_string(),
+        _ => return None,
+    };
+    (!id.is_empty()).then_some(id)
+}
+
+/// Copy a document and its sidecar from one label to another under the same
+/// name. A copy already in place is kept; a different document by that name
+/// is an error, since the routed evidence refs would cite it.
+fn copy_document(
+    ledger_dir: &Path,
+    login_name: &str,
+    from_label: &str,
+    to_label: &str,
+    doc_name: &str,
+) -> io::Result<()> {
+    let source_dir =
+        crate::account_journal::login_account_documents_dir(ledger_dir, login_name, from_label);
+    let target_dir =
+        crate::account_journal::login_account_documents_dir(ledger_dir, login_name, to_label);
+    let bytes = fs::read(source_dir.join(doc_name))?;
+    let target = target_dir.join(doc_name);
+    if target.exists() {
+        if fs::read(&target)? == bytes {
+            return Ok(());
+        }
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("label '{to_label}' already has a different document named {doc_name}"),
+        ));
+    }
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    crate::atomic_file::write_atomic(&target, &bytes)?;
+
+    let sidecar_name = format!("{doc_name}-info.json");
+    let source_sidecar = source_dir.join(&sidecar_name);
+    if source_sidecar.exists() {
+        let text = fs::read_to_string(&source_sidecar)?;
+        let mut info: crate::scrape::DocumentInfo = serde_json::from_str(&text)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        info.label = to_label.to_string();
+        let json = serde_json::to_string_pretty(&info)
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        crate::atomic_file::write_atomic(&target_dir.join(sidecar_name), json.as_bytes())?;
+    }
+    Ok(())
+}
+
+/// Replace the identifiers a label claims. Each identifier may be claimed by
+/// only one label of the login.
+pub fn set_login_account_ids(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    account_ids: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut config = crate::login_config::read_login_config(ledger_dir, login_name);
+    let mut ids: Vec<String> = Vec::new();
+    for id in account_ids {
+        let id = id.trim().to_string();
+        if id.is_empty()
+            || ids
+                .iter()
+                .any(|other| normalize_account_id(other) == normalize_account_id(&id))
+        {
+            continue;
+        }
+        let claimant = config.accounts.iter().find(|(other, account)| {
+            other.as_str() != label
+                && account
+                    .account_ids
+                    .iter()
+                    .any(|other| normalize_account_id(other) == normalize_account_id(&id))
+        });
+        if let Some((other, _)) = claimant {
+            return Err(format!(
+                "account id '{id}' is already assigned to label '{other}'"
+            ));
+        }
+        ids.push(id);
+    }
+    config
+        .accounts
+        .entry(label.to_string())
+        .or_default()
+        .account_ids = ids.clone();
+    crate::login_config::write_login_config(ledger_dir, login_name, &config)
+        .map_err(|err| err.to_string())?;
+    Ok(ids)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn txn(date: &str, account_id: Option<&str>, evidence: &str) -> ExtractedTransaction {
+        let mut ttags = vec![("evidence".to_string(), evidence.to_string())];
+        if let Some(id) = account_id {
+            ttags.push((ACCOUNT_ID_TAG.to_string(), id.to_string()));
+        }
+        ExtractedTransaction {
+            tdate: date.to_string(),
+            tstatus: "Cleared".to_string(),
+            tdescription: "Coffee".to_string(),
+            tcomment: String::new(),
+            ttags,
+            tpostings: None,
+        }
+    }
+
+    #[test]
+    fn masked_identifiers_match_the_numbers_they_end() {
+        assert!(account_ids_match("DE89 3704 0044", "de8937040044"));
+        assert!(account_ids_match("****1234", "000991234"));
+        assert!(account_ids_match("x1234", "\u{2022}\u{2022}1234"));
+        assert!(account_ids_match("XXXX-1234", "...1234"));
+        assert!(!account_ids_match("****1234", "000995678"));
+        assert!(!account_ids_match("000991234", "000881234"));
+        // Too few visible digits to tell accounts apart.
+        assert!(!account_ids_match("**34", "1234"));
+
+        let mut config = LoginConfig::default();
+        for (label, ids) in [("checking", vec!["000991234"]), ("card", vec!["****5678"])] {
+            config.accounts.insert(
+                label.to_string(),
+                crate::login_config::LoginAccountConfig {
+                    account_ids: ids.into_iter().map(str::to_string).collect(),
+                    ..Default::default()
+                },
+            );
+        }
+        assert_eq!(resolve_account_id(&config, "XX1234"), Some("checking"));
+        assert_eq!(resolve_account_id(&config, "4400005678"), Some("card"));
+        assert_eq!(resolve_account_id(&config, "****9999"), None);
+    }
+
+    #[test]
+    fn transactions_follow_the_label_that_claims_their_account_id() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-account-routing-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        let login = "bank";
+        let docs = crate::account_journal::login_account_documents_dir(&dir, login, "checking");
+        fs::create_dir_all(&docs).unwrap();
+        fs::write(docs.join("2025-02-01-all.ofx"), b"ofx").unwrap();
+        fs::write(docs.join("2025-02-01-other.csv"), b"csv").unwrap();
+        let info = serde_json::json!({
+            "mimeType": "application/x-ofx",
+            "scrapedAt": "2025-02-01T00:00:00Z",
+            "extensionName": "bank",
+            "loginName": login,
+            "label": "checking",
+            "scrapeSessionId": "s1",
+            "coverageEndDate": "2025-01-31",
+        });
+        fs::write(
+            docs.join("2025-02-01-all.ofx-info.json"),
+            serde_json::to_string(&info).unwrap(),
+        )
+        .unwrap();
+        set_login_account_ids(&dir, login, "checking", vec!["111".to_string()]).unwrap();
+        assert_eq!(
+            set_login_account_ids(
+                &dir,
+                login,
+                "savings",
+                vec![" 22 2 ".to_string(), "222".to_string(), String::new()]
+            )
+            .unwrap(),
+            vec!["22 2".to_string()]
+        );
+        assert!(set_login_account_ids(&dir, login, "card", vec!["111".to_string()]).is_err());
+
+        let documents = vec![
+            "2025-02-01-all.ofx".to_string(),
+            "2025-02-01-other.csv".to_string(),
+        ];
+        let routing = route_extraction(
+            &dir,
+            login,
+            "checking",
+            vec![
+                txn("2025-01-02", Some("111"), "2025-02-01-all.ofx:3:1"),
+                txn("2025-01-03", Some("222"), "2025-02-01-all.ofx:9:1"),
+                txn("2025-01-04", Some("999"), "2025-02-01-all.ofx:15:1"),
+                txn("2025-01-05", None, "2025-02-01-other.csv:2:1"),
+            ],
+            &documents,
+        )
+        .unwrap();
+        let routes = routing.routes;
+
+        assert_eq!(routes.len(), 2);
+        assert_eq!(routes[0].label, "checking");
+        assert_eq!(routes[0].document_names, documents);
+        let dates = |route: &Route| -> Vec<String> {
+            route
+                .proposed_transactions
+                .iter()
+                .map(|txn| txn.tdate.clone())
+                .collect()
+        };
+        assert_eq!(
+            dates(&routes[0]),
+            ["2025-01-02", "2025-01-04", "2025-01-05"]
+        );
+        assert_eq!(routes[1].label, "savings");
+        assert_eq!(routes[1].document_names, ["2025-02-01-all.ofx"]);
+        assert_eq!(dates(&routes[1]), ["2025-01-03"]);
+        assert_eq!(routing.unknown_account_ids.len(), 1);
+        assert_eq!(routing.unknown_account_ids[0].account_id, "999");
+        assert_eq!(
+            routing.unknown_account_ids[0].document,
+            "2025-02-01-all.ofx"
+        );
+
+        let copied =
+            crate::extract::list_documents_for_login_account(&dir, login, "savings").unwrap();
+        assert_eq!(copied.len(), 1);
+        assert_eq!(copied[0].filename, "2025-02-01-all.ofx");
+        assert_eq!(copied[0].info.as_ref().unwrap().label, "savings");
+
+        // Extracting the same document again finds its copy in place and
+        // does not queue the unknown identifier twice.
+        let again = route_extraction(
+            &dir,
+            login,
+            "checking",
+            vec![
+                txn("2025-01-03", Some("222"), "2025-02-01-all.ofx:9:1"),
+                txn("2025-01-04", Some("999"), "2025-02-01-all.ofx:15:1"),
+            ],
+            &documents,
+        )
+        .unwrap();
+        assert!(again.unknown_account_ids.is_empty());
+        assert_eq!(list_unknown_account_ids(&dir, login).unwrap().len(), 1);
+
+        assert_eq!(
+            assign_account_id(&dir, login, "999", "card").unwrap(),
+            vec!["999".to_string()]
+        );
+        assert!(list_unknown_account_ids(&dir, login).unwrap().is_empty());
+        let config = crate::login_config::read_login_config(&dir, login);
+        assert_eq!(resolve_account_id(&config, "999"), Some("card"));
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
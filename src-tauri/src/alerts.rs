@@ -0,0 +1,558 @@
+//! Rule-driven alerts on newly extracted transactions.
+//!
+//! Rules are configured in `alert-rules.json` at the ledger root. After an
+//! extraction writes new entries to a login account journal, each enabled
+//! rule is evaluated against the new entries and any match is stored as
+//! `bookkeeping/alerts/<alert-id>.json`. An entry raises at most one alert
+//! per rule, so re-running extraction does not duplicate alerts.
+//!
+//! Coverage gap rules look at the account's documents instead of its
+//! entries; see [`evaluate_coverage_gaps`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::account_journal::AccountEntry;
+use crate::bookkeeping::{
+    bookkeeping_dir, json_bytes, read_json_objects_from_dir, read_optional_json, write_json,
+};
+
+const ALERT_RULES_FILE: &str = "alert-rules.json";
+const ALERTS_DIR: &str = "alerts";
+
+/// What a rule looks for.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
+pub enum AlertCondition {
+    /// An outflow larger than `threshold`, e.g. `"500"`.
+    LargeCharge { threshold: String },
+    /// The first transaction at a payee never seen before in the account.
+    NewPayee,
+    /// Same amount and a similar description within `withinDays` of an
+    /// earlier transaction in the account.
+    #[serde(rename_all = "camelCase")]
+    DuplicateCharge { within_days: u32 },
+    /// At least `minDays` days that no document of a series covers.
+    #[serde(rename_all = "camelCase")]
+    CoverageGap { min_days: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct AlertRule {
+    pub id: String,
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    pub condition: AlertCondition,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum AlertState {
+    Open,
+    Acknowledged,
+    Dismissed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct Alert {
+    pub id: String,
+    pub rule_id: String,
+    pub rule_name: String,
+    pub login_name: String,
+    pub label: String,
+    pub entry_id: String,
+    pub date: String,
+    pub description: String,
+    pub amount: Option<String>,
+    pub message: String,
+    pub state: AlertState,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+pub fn alert_rules_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(ALERT_RULES_FILE)
+}
+
+pub fn list_alert_rules(ledger_dir: &Path) -> io::Result<Vec<AlertRule>> {
+    Ok(read_optional_json(&alert_rules_path(ledger_dir))?.unwrap_or_default())
+}
+
+/// Replace the configured rules.
+pub fn save_alert_rules(ledger_dir: &Path, rules: Vec<AlertRule>) -> io::Result<Vec<AlertRule>> {
+    let mut seen = HashSet::new();
+    for rule in &rules {
+        if rule.id.trim().is_empty() || rule.name.trim().is_empty() {
+            return Err(invalid_input("alert rules need an id and a name"));
+        }
+        if !seen.insert(rule.id.as_str()) {
+            return Err(invalid_input(format!(
+                "duplicate alert rule id: {}",
+                rule.id
+            )));
+        }
+        if let AlertCondition::LargeCharge { threshold } = &rule.condition {
+            if threshold.trim().parse::<f64>().map_or(true, |t| t < 0.0) {
+                return Err(invalid_input(format!(
+                    "rule {}: threshold must be a non-negative amount",
+                    rule.id
+                )));
+            }
+        }
+    }
+    write_json(&alert_rules_path(ledger_dir), &rules)?;
+    Ok(rules)
+}
+
+/// List alerts, newest first. Closed (acknowledged or dismissed) alerts are
+/// included only when `include_closed` is set.
+pub fn list_alerts(ledger_dir: &Path, include_closed: bool) -> io::Result<Vec<Alert>> {
+    let mut alerts: Vec<Alert> = read_json_objects_from_dir(&alerts_dir(ledger_dir))?;
+    alerts.retain(|alert| include_closed || alert.state == AlertState::Open);
+    alerts.sort_by(|a, b| {
+        b.created_at
+            .cmp(&a.created_at)
+            .then_with(|| b.date.cmp(&a.date))
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(alerts)
+}
+
+pub fn set_alert_state(ledger_dir: &Path, id: &str, state: AlertState) -> io::Result<Alert> {
+    let path = alert_path(ledger_dir, id);
+    let mut alert: Alert = read_optional_json(&path)?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("alert not found: {id}")))?;
+    alert.state = state;
+    alert.updated_at = crate::operations::now_timestamp();
+    write_json(&path, &alert)?;
+    Ok(alert)
+}
+
+/// Evaluate the ledger's rules against entries newly added to a login
+/// account and store the resulting alerts. Returns only newly raised alerts.
+pub fn evaluate_new_entries(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    history: &[AccountEntry],
+    new_entries: &[AccountEntry],
+) -> io::Result<Vec<Alert>> {
+    let rules = list_alert_rules(ledger_dir)?;
+    if rules.is_empty() || new_entries.is_empty() {
+        return Ok(Vec::new());
+    }
+    let existing: HashSet<(String, String)> = list_alerts(ledger_dir, true)?
+        .into_iter()
+        .filter(|alert| alert.login_name == login_name && alert.label == label)
+        .map(|alert| (alert.rule_id, alert.entry_id))
+        .collect();
+
+    let now = crate::operations::now_timestamp();
+    let mut raised = Vec::new();
+    for (rule, entry, message) in matches(&rules, history, new_entries) {
+        if existing.contains(&(rule.id.clone(), entry.id.clone())) {
+            continue;
+        }
+        let alert = Alert {
+            id: uuid::Uuid::new_v4().to_string(),
+            rule_id: rule.id.clone(),
+            rule_name: rule.name.clone(),
+            login_name: login_name.to_string(),
+            label: label.to_string(),
+            entry_id: entry.id.clone(),
+            date: entry.date.clone(),
+            description: entry.description.clone(),
+            amount: entry_amount_string(entry),
+            message,
+            state: AlertState::Open,
+            created_at: now.clone(),
+            updated_at: now.clone(),
+        };
+        write_json(&alert_path(ledger_dir, &alert.id), &alert)?;
+        raised.push(alert);
+    }
+    Ok(raised)
+}
+
+/// Evaluate the ledger's coverage gap rules against a login account's
+/// documents and store the resulting alerts. A gap raises at most one alert
+/// per rule; its `entryId` names the series and the gap's first and last day.
+pub fn evaluate_coverage_gaps(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+) -> io::Result<Vec<Alert>> {
+    let all_rules = list_alert_rules(ledger_dir)?;
+    let rules: Vec<(&AlertRule, u32)> = all_rules
+        .iter()
+        .filter(|rule| rule.enabled)
+        .filter_map(|rule| match rule.condition {
+            AlertCondition::CoverageGap { min_days } => Some((rule, min_days)),
+            _ => None,
+        })
+        .collect();
+    if rules.is_empty() {
+        return Ok(Vec::new());
+    }
+    let existing: HashSet<(String, String)> = list_alerts(ledger_dir, true)?
+        .into_iter()
+        .filter(|alert| alert.login_name == login_name && alert.label == label)
+        .map(|alert| (alert.rule_id, alert.entry_id))
+        .collect();
+
+    let now = crate::operations::now_timestamp();
+    let mut raised = Vec::new();
+    for series in crate::coverage::login_account_coverage(ledger_dir, login_name, label)? {
+        let series_name = series
+            .document_type
+            .map_or("documents", |document_type| document_type.as_str());
+        let what = match series.document_type {
+            Some(document_type) => format!("{} documents", document_type.as_str()),
+            None => "documents".to_string(),
+        };
+        for gap in series
+            .issues
+            .iter()
+            .filter(|issue| issue.kind == crate::coverage::CoverageIssueKind::Gap)
+        {
+            let entry_id = format!("coverage-gap:{series_name}:{}:{}", gap.from, gap.to);
+            for (rule, min_days) in &rules {
+                if gap.days < i64::from(*min_days)
+                    || existing.contains(&(rule.id.clone(), entry_id.clone()))
+                {
+                    continue;
+                }
+                let about = if gap.estimated { "about " } else { "" };
+                let alert = Alert {
+                    id: uuid::Uuid::new_v4().to_string(),
+                    rule_id: rule.id.clone(),
+                    rule_name: rule.name.clone(),
+                    login_name: login_name.to_string(),
+                    label: label.to_string(),
+                    entry_id: entry_id.clone(),
+                    date: gap.from.clone(),
+                    description: format!("Missing {what}"),
+                    amount: None,
+                    message: format!(
+                        "No {what} cover {} to {} ({about}{} days), between {} and {}",
+                        gap.from, gap.to, gap.days, gap.before, gap.after
+                    ),
+                    state: AlertState::Open,
+                    created_at: now.clone(),
+                    updated_at: now.clone(),
+                };
+                write_json(&alert_path(ledger_dir, &alert.id), &alert)?;
+                raised.push(alert);
+            }
+        }
+    }
+    Ok(raised)
+}
+
+/// Rule matches for `new_entries`, as `(rule, entry, message)`. Each new
+/// entry is compared with `history` and with new entries before it.
+fn matches<'a>(
+    rules: &'a [AlertRule],
+    history: &[AccountEntry],
+    new_entries: &'a [AccountEntry],
+) -> Vec<(&'a AlertRule, &'a AccountEntry, String)> {
+    let mut seen_payees: HashSet<String> = history
+        .iter()
+        .map(|entry| payee_key(&entry.description))
+        .collect();
+    let mut earlier: Vec<&AccountEntry> = history.iter().collect();
+    let mut out = Vec::new();
+
+    for entry in new_entries {
+        let amount = entry_amount(entry);
+        let payee = payee_key(&entry.description);
+        let is_new_payee = !payee.is_empty() && !seen_payees.contains(&payee);
+
+        for rule in rules.iter().filter(|rule| rule.enabled) {
+            let message = match &rule.condition {
+                AlertCondition::LargeCharge { threshold } => {
+                    let threshold: f64 = threshold.trim().parse().unwrap_or(f64::INFINITY);
+                    amount
+                        .filter(|amount| *amount < 0.0 && -amount > threshold)
+                        .map(|amount| format!("Charge of {:.2} is over {threshold:.2}", -amount))
+                }
+                AlertCondition::NewPayee => {
+                    is_new_payee.then(|| format!("First transaction at {}", entry.description))
+                }
+                AlertCondition::DuplicateCharge { within_days } => amount
+                    .filter(|amount| *amount < 0.0)
+                    .and_then(|amount| {
+                        earlier.iter().find(|other| {
+                            other.id != entry.id
+                                && entry_amount(other)
+                                    .is_some_and(|other| (other - amount).abs() < 0.005)
+                                && crate::dedup::dates_within_tolerance(
+                                    &entry.date,
+                                    &other.date,
+                                    i64::from(*within_days),
+                                )
+                                && crate::dedup::descriptions_similar(
+                                    &entry.description,
+                                    &other.description,
+                                )
+                        })
+                    })
+                    .map(|other| {
+                        format!(
+                            "Looks like a duplicate of {} on {} ({})",
+                            other.description, other.date, other.id
+                        )
+                    }),
+                AlertCondition::CoverageGap { .. } => None,
+            };
+            if let Some(message) = message {
+                out.push((rule, entry, message));
+            }
+        }
+
+        seen_payees.insert(payee);
+        earlier.push(entry);
+    }
+    out
+}
+
+fn entry_amount(entry: &AccountEntry) -> Option<f64> {
+    entry
+        .postings
+        .first()?
+        .amount
+        .as_ref()?
+        .quantity
+        .parse()
+        .ok()
+}
+
+fn entry_amount_string(entry: &AccountEntry) -> Option<String> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    Some(format!("{} {}", amount.quantity, amount.commodity))
+}
+
+/// Normalized payee: the description without digits and punctuation, so
+/// store numbers and reference codes do not make a payee look new.
+pub(crate) fn payee_key(description: &str) -> String {
+    description
+        .to_ascii_uppercase()
+        .chars()
+        .map(|c| if c.is_alphabetic() { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Point alerts at renamed entry ids, given per login account locator.
+/// Returns the rewritten alert files without writing them.
+pub(crate) fn rename_alert_entry_ids(
+    ledger_dir: &Path,
+    renames: &HashMap<String, HashMap<String, String>>,
+) -> io::Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut staged = Vec::new();
+    for mut alert in list_alerts(ledger_dir, true)? {
+        let locator = format!("logins/{}/accounts/{}", alert.login_name, alert.label);
+        let Some(new_id) = renames.get(&locator).and_then(|r| r.get(&alert.entry_id)) else {
+            continue;
+        };
+        alert.entry_id = new_id.clone();
+        staged.push((alert_path(ledger_dir, &alert.id), json_bytes(&alert)?));
+    }
+    Ok(staged)
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+fn alerts_dir(ledger_dir: &Path) -> PathBuf {
+    bookkeeping_dir(ledger_dir).join(ALERTS_DIR)
+}
+
+fn alert_path(ledger_dir: &Path, id: &str) -> PathBuf {
+    alerts_dir(ledger_dir).join(format!("{id}.json"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+    use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_ledger_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-alerts-{prefix}-{}-{now}.refreshmint",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entry(date: &str, description: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![],
+            vec![EntryPosting {
+                account: "Liabilities:Card".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    fn rules() -> Vec<AlertRule> {
+        vec![
+            AlertRule {
+                id: "large".to_string(),
+                name: "Charge over $500".to_string(),
+                enabled: true,
+                condition: AlertCondition::LargeCharge {
+                    threshold: "500".to_string(),
+                },
+            },
+            AlertRule {
+                id: "payee".to_string(),
+                name: "New payee".to_string(),
+                enabled: true,
+                condition: AlertCondition::NewPayee,
+            },
+            AlertRule {
+                id: "dup".to_string(),
+                name: "Duplicate charge".to_string(),
+                enabled: true,
+                condition: AlertCondition::DuplicateCharge { within_days: 3 },
+            },
+        ]
+    }
+
+    #[test]
+    fn rules_match_large_new_and_duplicate_charges() {
+        let rules = rules();
+        let history = vec![entry("2026-03-01", "COFFEE SHOP #123", "-4.50")];
+        let new_entries = vec![
+            entry("2026-03-02", "COFFEE SHOP #456", "-4.50"),
+            entry("2026-03-03", "TV STORE", "-899.00"),
+            entry("2026-03-09", "COFFEE SHOP #123", "-4.50"),
+        ];
+
+        let found: Vec<(&str, &str)> = matches(&rules, &history, &new_entries)
+            .into_iter()
+            .map(|(rule, entry, _)| (rule.id.as_str(), entry.date.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("dup", "2026-03-02"),
+                ("large", "2026-03-03"),
+                ("payee", "2026-03-03"),
+            ]
+        );
+    }
+
+    #[test]
+    fn evaluate_stores_alerts_once_and_tracks_state() {
+        let root = temp_ledger_dir("evaluate");
+        save_alert_rules(&root, rules()).unwrap();
+        let new_entries = vec![entry("2026-03-03", "TV STORE", "-899.00")];
+
+        let raised = evaluate_new_entries(&root, "bank", "card", &[], &new_entries).unwrap();
+        assert_eq!(raised.len(), 2);
+        let again = evaluate_new_entries(&root, "bank", "card", &[], &new_entries).unwrap();
+        assert!(again.is_empty());
+
+        set_alert_state(&root, &raised[0].id, AlertState::Dismissed).unwrap();
+        assert_eq!(list_alerts(&root, false).unwrap().len(), 1);
+        assert_eq!(list_alerts(&root, true).unwrap().len(), 2);
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn coverage_gap_rules_alert_once_per_gap() {
+        let root = temp_ledger_dir("coverage");
+        save_alert_rules(
+            &root,
+            vec![AlertRule {
+                id: "gaps".to_string(),
+                name: "Missing statements".to_string(),
+                enabled: true,
+                condition: AlertCondition::CoverageGap { min_days: 5 },
+            }],
+        )
+        .unwrap();
+        let docs = crate::account_journal::login_account_documents_dir(&root, "bank", "card");
+        fs::create_dir_all(&docs).unwrap();
+        for (name, start, end) in [
+            ("jan.pdf", "2026-01-01", "2026-01-31"),
+            ("feb.pdf", "2026-02-03", "2026-02-28"),
+            ("apr.pdf", "2026-04-01", "2026-04-30"),
+        ] {
+            fs::write(docs.join(name), b"pdf").unwrap();
+            let info = serde_json::json!({
+                "mimeType": "application/pdf",
+                "scrapedAt": "2026-05-01T00:00:00Z",
+                "extensionName": "bank",
+                "loginName": "bank",
+                "label": "card",
+                "scrapeSessionId": name,
+                "coverageEndDate": end,
+                "dateRangeStart": start,
+                "documentType": "statement",
+            });
+            fs::write(
+                docs.join(format!("{name}-info.json")),
+                serde_json::to_string(&info).unwrap(),
+            )
+            .unwrap();
+        }
+
+        let raised = evaluate_coverage_gaps(&root, "bank", "card").unwrap();
+        assert_eq!(raised.len(), 1);
+        assert_eq!(
+            raised[0].entry_id,
+            "coverage-gap:statement:2026-03-01:2026-03-31"
+        );
+        assert_eq!(
+            raised[0].message,
+            "No statement documents cover 2026-03-01 to 2026-03-31 (31 days), \
+             between feb.pdf and apr.pdf"
+        );
+        assert!(evaluate_coverage_gaps(&root, "bank", "card")
+            .unwrap()
+            .is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn save_rejects_duplicate_rule_ids() {
+        let root = temp_ledger_dir("rules");
+        let mut rules = rules();
+        rules.push(rules[0].clone());
+        assert!(save_alert_rules(&root, rules).is_err());
+        let _ = fs::remove_dir_all(&root);
+    }
+}
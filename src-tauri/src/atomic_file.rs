@@ -0,0 +1,242 @@
+//! Crash-safe file writes.
+//!
+//! Rewrites go through a temporary file in the target's directory that is
+//! fsynced and then renamed over the target; the directory is fsynced too so
+//! the rename survives a power loss. A crash leaves either the old or the new
+//! contents, plus at worst an orphaned temp file, which
+//! [`remove_orphaned_temp_files`] deletes when the ledger is opened.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Temp files are named `.<file name>.tmp-<pid>-<nanos>`.
+const TEMP_MARKER: &str = ".tmp-";
+/// Temp files younger than this may belong to a write in progress in
+/// another process, so recovery leaves them alone.
+const ORPHAN_MIN_AGE: Duration = Duration::from_secs(5 * 60);
+
+fn temp_path_for(path: &Path) -> io::Result<PathBuf> {
+    let parent = parent_dir(path)?;
+    let name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("file");
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    Ok(parent.join(format!(
+        ".{name}{TEMP_MARKER}{}-{nanos}",
+        std::process::id()
+    )))
+}
+
+fn parent_dir(path: &Path) -> io::Result<&Path> {
+    path.parent().ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("path has no parent: {}", path.display()),
+        )
+    })
+}
+
+/// Replace `path` with `content`.
+pub(crate) fn write_atomic(path: &Path, content: &[u8]) -> io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(content))
+}
+
+/// Replace `path` with whatever `write` writes into the temp file.
+pub(crate) fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> io::Result<()>,
+) -> io::Result<()> {
+    let parent = parent_dir(path)?;
+    fs::create_dir_all(parent)?;
+    let temp_path = temp_path_for(path)?;
+    let result = (|| {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp_path)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        drop(file);
+        replace_file(&temp_path, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result?;
+    sync_dir(parent);
+    Ok(())
+}
+
+/// Copy `from` to `to` so that `to` never exists half-written.
+pub(crate) fn copy_atomic(from: &Path, to: &Path) -> io::Result<()> {
+    let mut source = File::open(from)?;
+    write_atomic_with(to, |file| io::copy(&mut source, file).map(|_| ()))
+}
+
+/// Append `text` to `path` as a new block, separated from existing content
+/// by a blank line, and fsync before returning.
+pub(crate) fn append_block(path: &Path, text: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() > 0 {
+        file.write_all(b"\n")?;
+    }
+    file.write_all(text.as_bytes())?;
+    file.sync_all()
+}
+
+/// Rename over an existing file, with a remove-then-rename fallback for
+/// Windows.
+fn replace_file(temp_path: &Path, path: &Path) -> io::Result<()> {
+    match fs::rename(temp_path, path) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            #[cfg(windows)]
+            {
+                if err.kind() == io::ErrorKind::AlreadyExists {
+                    fs::remove_file(path)?;
+                    return fs::rename(temp_path, path);
+                }
+            }
+            Err(err)
+        }
+    }
+}
+
+/// Best effort: directories cannot be opened for sync on every platform.
+fn sync_dir(dir: &Path) {
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+}
+
+/// Delete temp files left behind by interrupted writes anywhere in the
+/// ledger, skipping `.git`. Returns the removed paths.
+pub(crate) fn remove_orphaned_temp_files(ledger_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    remove_orphaned_temp_files_older_than(ledger_dir, ORPHAN_MIN_AGE)
+}
+
+fn remove_orphaned_temp_files_older_than(
+    ledger_dir: &Path,
+    min_age: Duration,
+) -> io::Result<Vec<PathBuf>> {
+    let mut removed = Vec::new();
+    let mut pending = vec![ledger_dir.to_path_buf()];
+    while let Some(dir) = pending.pop() {
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => continue,
+            Err(err) => return Err(err),
+        };
+        for entry in entries {
+            let entry = entry?;
+            let file_type = entry.file_type()?;
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            if file_type.is_dir() {
+                if name != ".git" {
+                    pending.push(entry.path());
+                }
+                continue;
+            }
+            if !file_type.is_file() || !is_temp_file_name(&dir, &name) {
+                continue;
+            }
+            let age = entry
+                .metadata()?
+                .modified()
+                .ok()
+                .and_then(|modified| modified.elapsed().ok())
+                .unwrap_or_default();
+            if age >= min_age {
+                fs::remove_file(entry.path())?;
+                removed.push(entry.path());
+            }
+        }
+    }
+    removed.sort();
+    Ok(removed)
+}
+
+/// Temp files from [`write_atomic`] and the config writers, plus the
+/// `<stem>.tmp` files older versions wrote next to `<stem>.journal`.
+fn is_temp_file_name(dir: &Path, name: &str) -> bool {
+    if name.starts_with('.') && name.contains(TEMP_MARKER) {
+        return true;
+    }
+    name.strip_suffix(".tmp")
+        .is_some_and(|stem| dir.join(format!("{stem}.journal")).is_file())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-{prefix}-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn atomic_writes_replace_content_without_leaving_temp_files() {
+        let dir = temp_dir("atomic-write");
+        let path = dir.join("nested").join("general.journal");
+        write_atomic(&path, b"first\n").unwrap();
+        write_atomic(&path, b"second\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+
+        let failed = write_atomic_with(&path, |file| {
+            file.write_all(b"partial")?;
+            Err(io::Error::other("interrupted"))
+        });
+        assert!(failed.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n");
+
+        append_block(&path, "third\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\n\nthird\n");
+
+        let copy = dir.join("documents").join("statement.csv");
+        copy_atomic(&path, &copy).unwrap();
+        assert_eq!(fs::read_to_string(&copy).unwrap(), "second\n\nthird\n");
+
+        let leftovers = remove_orphaned_temp_files_older_than(&dir, Duration::ZERO).unwrap();
+        assert!(leftovers.is_empty(), "{leftovers:?}");
+        let _ = fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn recovery_removes_only_orphaned_temp_files() {
+        let dir = temp_dir("atomic-recover");
+        let account_dir = dir.join("accounts").join("checking");
+        fs::create_dir_all(&account_dir).unwrap();
+        fs::create_dir_all(dir.join(".git")).unwrap();
+        let orphan = dir.join(".general.journal.tmp-123-456");
+        let legacy = account_dir.join("account.tmp");
+        let git_file = dir.join(".git").join(".index.tmp-1-2");
+        let unrelated = account_dir.join("notes.tmp");
+        for path in [&orphan, &legacy, &git_file, &unrelated] {
+            fs::write(path, "x").unwrap();
+        }
+        fs::write(account_dir.join("account.journal"), "").unwrap();
+
+        assert!(remove_orphaned_temp_files(&dir).unwrap().is_empty());
+        let removed = remove_orphaned_temp_files_older_than(&dir, Duration::ZERO).unwrap();
+        let mut expected = vec![legacy.clone(), orphan.clone()];
+        expected.sort();
+        assert_eq!(removed, expected);
+        assert!(git_file.exists());
+        assert!(unrelated.exists());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,338 @@
+//! Scraped balance and holdings snapshots.
+//!
+//! Drivers report balances with `refreshmint.reportBalance(...)`. Snapshots
+//! are appended to `logins/<login>/accounts/<label>/balances.csv` when the
+//! scrape finishes, one row per cash balance or brokerage position.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::OpenOptions;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::account_journal;
+
+/// What a snapshot row measures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SnapshotKind {
+    /// Total account balance (cash balance, or market value for brokerages).
+    Balance,
+    /// A single holding in a brokerage account.
+    Position,
+}
+
+/// One row of `balances.csv`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BalanceSnapshot {
+    pub date: String,
+    pub kind: SnapshotKind,
+    /// Ticker or other security identifier; empty for balances.
+    #[serde(default)]
+    pub symbol: Option<String>,
+    #[serde(default)]
+    pub quantity: Option<String>,
+    #[serde(default)]
+    pub price: Option<String>,
+    /// Balance, or market value of a position.
+    pub amount: String,
+    pub commodity: String,
+    pub scrape_session_id: String,
+    pub scraped_at: String,
+}
+
+/// Return the path to `logins/<login_name>/accounts/<label>/balances.csv`.
+pub fn login_account_balances_path(ledger_dir: &Path, login_name: &str, label: &str) -> PathBuf {
+    ledger_dir
+        .join("logins")
+        .join(login_name)
+        .join("accounts")
+        .join(label)
+        .join("balances.csv")
+}
+
+/// Append snapshots to a balances file, writing the CSV header for a new file.
+pub fn append_snapshots(path: &Path, snapshots: &[BalanceSnapshot]) -> io::Result<()> {
+    if snapshots.is_empty() {
+        return Ok(());
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let is_new = std::fs::metadata(path)
+        .map(|m| m.len() == 0)
+        .unwrap_or(true);
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let mut writer = csv::WriterBuilder::new()
+        .has_headers(is_new)
+        .from_writer(file);
+    for snapshot in snapshots {
+        writer.serialize(snapshot).map_err(io::Error::other)?;
+    }
+    writer.flush()
+}
+
+/// Read all snapshots from a balances file. A missing file has no snapshots.
+pub fn read_snapshots(path: &Path) -> io::Result<Vec<BalanceSnapshot>> {
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let mut reader = csv::Reader::from_path(path).map_err(io::Error::other)?;
+    reader
+        .deserialize()
+        .map(|row| row.map_err(io::Error::other))
+        .collect()
+}
+
+/// Bucket size for [`balance_history`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BalancePeriod {
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl BalancePeriod {
+    pub fn parse(value: &str) -> Result<Self, String> {
+        match value.trim() {
+            "" | "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            other => Err(format!(
+                "period: expected one of (daily|weekly|monthly), got {other}"
+            )),
+        }
+    }
+
+    /// First day of the period containing `date`.
+    pub(crate) fn bucket(self, date: chrono::NaiveDate) -> chrono::NaiveDate {
+        use chrono::Datelike;
+        match self {
+            Self::Daily => date,
+            Self::Weekly => {
+                date - chrono::Duration::days(i64::from(date.weekday().num_days_from_monday()))
+            }
+            Self::Monthly => date.with_day(1).unwrap_or(date),
+        }
+    }
+}
+
+/// A brokerage position reported alongside a balance.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "BalancePosition")]
+pub struct PositionPoint {
+    pub symbol: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub quantity: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub price: Option<String>,
+    pub amount: String,
+    pub commodity: String,
+}
+
+/// The last reported balance in a period, compared with the balance computed
+/// from the login account journal.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct BalanceHistoryPoint {
+    /// First day of the period.
+    pub period_start: String,
+    /// Date of the snapshot used for this period.
+    pub date: String,
+    pub commodity: String,
+    pub reported: f64,
+    /// Sum of the account's own postings dated on or before `date`.
+    pub computed: f64,
+    /// `reported - computed`. A constant drift is an opening balance; a
+    /// changing drift means transactions are missing or duplicated.
+    pub drift: f64,
+    pub positions: Vec<PositionPoint>,
+}
+
+/// Build a balance history for a login account, one point per period and
+/// commodity.
+pub fn balance_history(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    period: BalancePeriod,
+) -> io::Result<Vec<BalanceHistoryPoint>> {
+    let snapshots = read_snapshots(&login_account_balances_path(ledger_dir, login_name, label))?;
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+    Ok(history_from_snapshots(&snapshots, &entries, period))
+}
+
+fn history_from_snapshots(
+    snapshots: &[BalanceSnapshot],
+    entries: &[account_journal::AccountEntry],
+    period: BalancePeriod,
+) -> Vec<BalanceHistoryPoint> {
+    // Last balance per (period, commodity); rows are appended in scrape order,
+    // so later rows for the same date win.
+    let mut latest: BTreeMap<(chrono::NaiveDate, String), &BalanceSnapshot> = BTreeMap::new();
+    for snapshot in snapshots {
+        if snapshot.kind != SnapshotKind::Balance {
+            continue;
+        }
+        let Ok(date) = chrono::NaiveDate::parse_from_str(&snapshot.date, "%Y-%m-%d") else {
+            continue;
+        };
+        let key = (period.bucket(date), snapshot.commodity.clone());
+        let replace = latest
+            .get(&key)
+            .map_or(true, |existing| existing.date <= snapshot.date);
+        if replace {
+            latest.insert(key, snapshot);
+        }
+    }
+
+    latest
+        .into_iter()
+        .filter_map(|((period_start, commodity), snapshot)| {
+            let reported: f64 = snapshot.amount.parse().ok()?;
+            let computed = computed_balance(entries, &commodity, &snapshot.date);
+            let positions = snapshots
+                .iter()
+                .filter(|s| {
+                    s.kind == SnapshotKind::Position
+                        && s.scrape_session_id == snapshot.scrape_session_id
+                })
+                .map(|s| PositionPoint {
+                    symbol: s.symbol.clone().unwrap_or_default(),
+                    quantity: s.quantity.clone(),
+                    price: s.price.clone(),
+                    amount: s.amount.clone(),
+                    commodity: s.commodity.clone(),
+                })
+                .collect();
+            Some(BalanceHistoryPoint {
+                period_start: period_start.format("%Y-%m-%d").to_string(),
+                date: snapshot.date.clone(),
+                commodity,
+                reported,
+                computed,
+                drift: reported - computed,
+                positions,
+            })
+        })
+        .collect()
+}
+
+/// Sum the first posting (the account's own side) of every entry in
+/// `commodity` dated on or before `date`.
+pub(crate) fn computed_balance(
+    entries: &[account_journal::AccountEntry],
+    commodity: &str,
+    date: &str,
+) -> f64 {
+    entries
+        .iter()
+        .filter(|entry| entry.date.as_str() <= date)
+        .filter_map(|entry| entry.postings.first()?.amount.as_ref())
+        .filter(|amount| amount.commodity == commodity)
+        .filter_map(|amount| amount.quantity.parse::<f64>().ok())
+        .sum()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{AccountEntry, EntryPosting, EntryStatus, SimpleAmount};
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time should be after unix epoch")
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn balance(date: &str, amount: &str, session: &str) -> BalanceSnapshot {
+        BalanceSnapshot {
+            date: date.to_string(),
+            kind: SnapshotKind::Balance,
+            symbol: None,
+            quantity: None,
+            price: None,
+            amount: amount.to_string(),
+            commodity: "USD".to_string(),
+            scrape_session_id: session.to_string(),
+            scraped_at: format!("{date}T12:00:00Z"),
+        }
+    }
+
+    fn entry(date: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            "txn".to_string(),
+            vec![],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    #[test]
+    fn append_and_read_round_trip() {
+        let dir = temp_dir("balances-round-trip");
+        let path = dir.join("balances.csv");
+        let position = BalanceSnapshot {
+            kind: SnapshotKind::Position,
+            symbol: Some("VTI".to_string()),
+            quantity: Some("10".to_string()),
+            price: Some("250.00".to_string()),
+            ..balance("2026-01-31", "2500.00", "s1")
+        };
+        append_snapshots(&path, &[balance("2026-01-31", "2500.00", "s1")]).unwrap();
+        append_snapshots(&path, std::slice::from_ref(&position)).unwrap();
+
+        let rows = read_snapshots(&path).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[1], position);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn history_keeps_last_snapshot_per_period_and_computes_drift() {
+        let mut position = balance("2026-02-20", "300", "s3");
+        position.kind = SnapshotKind::Position;
+        position.symbol = Some("VTI".to_string());
+        let snapshots = vec![
+            balance("2026-01-10", "100", "s1"),
+            balance("2026-01-31", "150", "s2"),
+            balance("2026-02-20", "300", "s3"),
+            position,
+        ];
+        let entries = vec![entry("2026-01-05", "100"), entry("2026-01-20", "40")];
+
+        let points = history_from_snapshots(&snapshots, &entries, BalancePeriod::Monthly);
+        assert_eq!(points.len(), 2);
+        assert_eq!(points[0].period_start, "2026-01-01");
+        assert_eq!(points[0].date, "2026-01-31");
+        assert_eq!(points[0].computed, 140.0);
+        assert_eq!(points[0].drift, 10.0);
+        assert!(points[0].positions.is_empty());
+        assert_eq!(points[1].positions.len(), 1);
+        assert_eq!(points[1].positions[0].symbol, "VTI");
+    }
+
+    #[test]
+    fn parse_period_rejects_unknown() {
+        assert_eq!(BalancePeriod::parse("weekly"), Ok(BalancePeriod::Weekly));
+        assert!(BalancePeriod::parse("yearly").is_err());
+    }
+}
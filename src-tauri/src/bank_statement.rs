@@ -0,0 +1,652 @@
+//! SWIFT MT940 and ISO 20022 CAMT.053 bank statement parsing.
+//!
+//! European banks export account statements in these formats. Each booked
+//! (or pending) statement line becomes a [`StatementEntry`] with a signed
+//! amount, the counterparty, the remittance text, and structured details
+//! (counterparty IBAN and BIC, SEPA end-to-end ID, mandate ID, creditor ID,
+//! creditor reference) as tags. The statement's own account number is tagged
+//! `accountId`, so a file covering several accounts can be routed by
+//! [`crate::account_routing`].
+//!
+//! MT940 `:86:` information is read in the common layouts: German
+//! `?NN` subfields (with SEPA `EREF+` / `SVWZ+` keywords), Dutch
+//! `/KEY/value/` pairs, and free text.
+
+use std::io;
+
+use crate::xml_tree::{parse_xml, XmlElement};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StatementFormat {
+    Mt940,
+    Camt053,
+}
+
+impl StatementFormat {
+    /// Parse a format name as written after `builtin:` in `manifest.json`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "mt940" => Some(Self::Mt940),
+            "camt.053" | "camt053" => Some(Self::Camt053),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StatementEntry {
+    /// 1-based position in the document: the line of the `:61:` field for
+    /// MT940, the entry number for CAMT.053.
+    pub position: usize,
+    pub booking_date: String,
+    pub value_date: Option<String>,
+    /// Signed decimal amount; debits are negative.
+    pub amount: String,
+    pub currency: String,
+    pub pending: bool,
+    pub counterparty_name: Option<String>,
+    pub remittance: Option<String>,
+    /// The bank's own reference for the entry.
+    pub bank_reference: Option<String>,
+    /// Structured details such as `counterpartyIban` and `endToEndId`.
+    pub tags: Vec<(String, String)>,
+}
+
+impl StatementEntry {
+    fn push_tag(&mut self, key: &str, value: &str) {
+        let value = value.trim();
+        if !value.is_empty() && !value.eq_ignore_ascii_case("NOTPROVIDED") {
+            self.tags.push((key.to_string(), value.to_string()));
+        }
+    }
+}
+
+/// Parse a statement document in `format`.
+pub fn parse(format: StatementFormat, text: &str) -> io::Result<Vec<StatementEntry>> {
+    match format {
+        StatementFormat::Mt940 => parse_mt940(text),
+        StatementFormat::Camt053 => parse_camt053(text),
+    }
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+// --- MT940 ---
+
+/// SEPA keywords inside German `?20`–`?29` remittance subfields.
+const SEPA_KEYWORDS: &[&str] = &[
+    "EREF+", "KREF+", "MREF+", "CRED+", "DEBT+", "SVWZ+", "ABWA+", "ABWE+", "IBAN+", "BIC+",
+];
+
+/// Keys of the `/KEY/value/` `:86:` layout.
+const SLASH_KEYS: &[&str] = &[
+    "TRTP", "IBAN", "BIC", "NAME", "REMI", "EREF", "MARF", "CSID", "ORDP", "BENM", "ADDR", "RTRN",
+    "PREF", "ULTC", "ULTD", "PURP", "ISDT",
+];
+
+pub fn parse_mt940(text: &str) -> io::Result<Vec<StatementEntry>> {
+    // (tag, value, line) with continuation lines joined by '\n'.
+    let mut fields: Vec<(String, String, usize)> = Vec::new();
+    for (index, raw_line) in text.lines().enumerate() {
+        let mut line = raw_line.trim_end_matches('\r');
+        if line.starts_with('{') {
+            // SWIFT envelope: `{1:...}{2:...}{4:` precedes the text block.
+            match line.find("{4:") {
+                Some(start) => line = &line[start + 3..],
+                None => continue,
+            }
+        }
+        if line.trim() == "-}" || line.trim() == "-" {
+            continue;
+        }
+        if let Some((tag, value)) = mt940_field_start(line) {
+            fields.push((tag.to_string(), value.to_string(), index + 1));
+        } else if let Some((_, value, _)) = fields.last_mut() {
+            value.push('\n');
+            value.push_str(line);
+        }
+    }
+    if !fields.iter().any(|(tag, _, _)| tag == "20" || tag == "61") {
+        return Err(invalid(
+            "not an MT940 statement: no :20: or :61: fields".to_string(),
+        ));
+    }
+
+    let mut entries: Vec<StatementEntry> = Vec::new();
+    let mut currency = String::new();
+    let mut account = String::new();
+    let mut last_tag = String::new();
+    for (tag, value, line) in &fields {
+        match tag.as_str() {
+            "25" => {
+                account = value.trim().to_string();
+            }
+            "60F" | "60M" => {
+                currency = value.get(7..10).unwrap_or_default().to_string();
+            }
+            "61" => {
+                let mut entry = parse_mt940_statement_line(value, &currency, *line)
+                    .ok_or_else(|| invalid(format!("invalid :61: field on line {line}")))?;
+                entry.push_tag("accountId", &account);
+                entries.push(entry);
+            }
+            "86" if last_tag == "61" => {
+                if let Some(entry) = entries.last_mut() {
+                    parse_mt940_information(value, entry);
+                }
+            }
+            _ => {}
+        }
+        last_tag.clone_from(tag);
+    }
+    Ok(entries)
+}
+
+/// Split `:61:value` into its tag and value.
+fn mt940_field_start(line: &str) -> Option<(&str, &str)> {
+    let rest = line.strip_prefix(':')?;
+    let end = rest.find(':')?;
+    let tag = &rest[..end];
+    let valid = tag.len() >= 2
+        && tag.len() <= 3
+        && tag.bytes().take(2).all(|b| b.is_ascii_digit())
+        && tag.bytes().skip(2).all(|b| b.is_ascii_uppercase());
+    valid.then(|| (tag, &rest[end + 1..]))
+}
+
+/// Parse a `:61:` statement line:
+/// `YYMMDD[MMDD]<mark>[funds code]<amount><type><customer ref>[//bank ref]`.
+fn parse_mt940_statement_line(value: &str, currency: &str, line: usize) -> Option<StatementEntry> {
+    let (first, supplementary) = match value.split_once('\n') {
+        Some((first, rest)) => (first, Some(rest.trim())),
+        None => (value, None),
+    };
+    let value_date = parse_yymmdd(first.get(0..6)?)?;
+    let mut rest = &first[6..];
+    let mut booking_date = value_date;
+    if rest.len() >= 4 && rest.as_bytes()[..4].iter().all(u8::is_ascii_digit) {
+        let month: u32 = rest[0..2].parse().ok()?;
+        let day: u32 = rest[2..4].parse().ok()?;
+        use chrono::Datelike;
+        let year = match (month, value_date.month()) {
+            (12, 1) => value_date.year() - 1,
+            (1, 12) => value_date.year() + 1,
+            _ => value_date.year(),
+        };
+        booking_date = chrono::NaiveDate::from_ymd_opt(year, month, day)?;
+        rest = &rest[4..];
+    }
+    let mark_len = if ["RC", "RD", "EC", "ED"].iter().any(|m| rest.starts_with(m)) {
+        2
+    } else {
+        1
+    };
+    let mark = rest.get(..mark_len)?;
+    rest = &rest[mark_len..];
+    if rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        rest = &rest[1..]; // funds code
+    }
+    let amount_len = rest
+        .find(|c: char| !c.is_ascii_digit() && c != ',')
+        .unwrap_or(rest.len());
+    let amount = rest[..amount_len].trim_end_matches(',').replace(',', ".");
+    if amount.is_empty() {
+        return None;
+    }
+    rest = &rest[amount_len..];
+    let transaction_type = rest.get(..4).unwrap_or(rest);
+    rest = rest.get(4..).unwrap_or_default();
+    let (customer_reference, bank_reference) = match rest.split_once("//") {
+        Some((customer, bank)) => (customer, Some(bank)),
+        None => (rest, None),
+    };
+
+    let negative = matches!(mark, "D" | "RC" | "ED");
+    let mut entry = StatementEntry {
+        position: line,
+        booking_date: booking_date.format("%Y-%m-%d").to_string(),
+        value_date: Some(value_date.format("%Y-%m-%d").to_string()),
+        amount: if negative {
+            format!("-{amount}")
+        } else {
+            amount
+        },
+        currency: currency.to_string(),
+        pending: mark.starts_with('E'),
+        bank_reference: bank_reference
+            .map(str::trim)
+            .filter(|r| !r.is_empty() && !r.eq_ignore_ascii_case("NONREF"))
+            .map(str::to_string),
+        ..StatementEntry::default()
+    };
+    entry.push_tag("transactionCode", transaction_type);
+    if !customer_reference.trim().eq_ignore_ascii_case("NONREF") {
+        entry.push_tag("customerReference", customer_reference);
+    }
+    if let Some(details) = supplementary.filter(|d| !d.is_empty()) {
+        entry.remittance = Some(details.to_string());
+    }
+    Some(entry)
+}
+
+fn parse_yymmdd(text: &str) -> Option<chrono::NaiveDate> {
+    let yy: i32 = text.get(0..2)?.parse().ok()?;
+    let month: u32 = text.get(2..4)?.parse().ok()?;
+    let day: u32 = text.get(4..6)?.parse().ok()?;
+    let year = if yy < 80 { 2000 + yy } else { 1900 + yy };
+    chrono::NaiveDate::from_ymd_opt(year, month, day)
+}
+
+/// Apply a `:86:` information field to `entry`.
+fn parse_mt940_information(value: &str, entry: &mut StatementEntry) {
+    // SWIFT wraps lines at 65 characters; continuation lines carry on the text.
+    let joined: String = value.lines().collect();
+    let joined = joined.trim();
+    let is_subfields = joined.len() > 4
+        && joined.as_bytes()[..3].iter().all(u8::is_ascii_digit)
+        && joined.as_bytes()[3] == b'?';
+    if is_subfields {
+        let mut remittance = String::new();
+        let mut name = String::new();
+        for (code, text) in question_mark_subfields(&joined[3..]) {
+            match code {
+                20..=29 | 60..=63 => remittance.push_str(text),
+                30 => entry.push_tag("counterpartyBic", text),
+                31 => entry.push_tag("counterpartyIban", text),
+                32 | 33 => name.push_str(text),
+                _ => {}
+            }
+        }
+        if !name.trim().is_empty() {
+            entry.counterparty_name = Some(name.trim().to_string());
+        }
+        apply_sepa_keywords(&remittance, entry);
+    } else if joined.starts_with('/') {
+        for (key, text) in slash_pairs(joined) {
+            match key {
+                "IBAN" => entry.push_tag("counterpartyIban", text),
+                "BIC" => entry.push_tag("counterpartyBic", text),
+                "NAME" => {
+                    entry.counterparty_name =
+                        Some(text.trim().to_string()).filter(|n| !n.is_empty())
+                }
+                "EREF" => entry.push_tag("endToEndId", text),
+                "MARF" => entry.push_tag("mandateId", text),
+                "CSID" => entry.push_tag("creditorId", text),
+                "REMI" => {
+                    if let Some(reference) = text
+                        .strip_prefix("STRD/CUR/")
+                        .or_else(|| text.strip_prefix("STRD/ISO/"))
+                    {
+                        entry.push_tag("creditorReference", reference);
+                    } else {
+                        let text = text.strip_prefix("USTD//").unwrap_or(text).trim();
+                        if !text.is_empty() {
+                            entry.remittance = Some(text.to_string());
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+    } else if !joined.is_empty() {
+        apply_sepa_keywords(joined, entry);
+    }
+}
+
+/// `?NN` subfields in order. The separator is conventionally `?`.
+fn question_mark_subfields(text: &str) -> Vec<(u32, &str)> {
+    let bytes = text.as_bytes();
+    let starts: Vec<usize> = (0..bytes.len())
+        .filter(|&i| {
+            bytes[i] == b'?'
+                && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)
+                && bytes.get(i + 2).is_some_and(u8::is_ascii_digit)
+        })
+        .collect();
+    starts
+        .iter()
+        .enumerate()
+        .filter_map(|(n, &start)| {
+            let end = starts.get(n + 1).copied().unwrap_or(text.len());
+            let code = text.get(start + 1..start + 3)?.parse().ok()?;
+            Some((code, text.get(start + 3..end)?))
+        })
+        .collect()
+}
+
+/// Split German SEPA remittance text on `EREF+`, `SVWZ+`, ... keywords.
+/// Text without keywords is taken as the remittance.
+fn apply_sepa_keywords(text: &str, entry: &mut StatementEntry) {
+    let mut found: Vec<(usize, &str)> = SEPA_KEYWORDS
+        .iter()
+        .filter_map(|keyword| text.find(keyword).map(|pos| (pos, *keyword)))
+        .collect();
+    found.sort_unstable();
+    if found.is_empty() {
+        if !text.trim().is_empty() {
+            entry.remittance = Some(text.trim().to_string());
+        }
+        return;
+    }
+    let leading = text[..found[0].0].trim();
+    let mut remittance = (!leading.is_empty()).then(|| leading.to_string());
+    for (n, (pos, keyword)) in found.iter().enumerate() {
+        let end = found.get(n + 1).map_or(text.len(), |(next, _)| *next);
+        let value = text[pos + keyword.len()..end].trim();
+        match *keyword {
+            "EREF+" => entry.push_tag("endToEndId", value),
+            "MREF+" => entry.push_tag("mandateId", value),
+            "CRED+" => entry.push_tag("creditorId", value),
+            "IBAN+" => entry.push_tag("counterpartyIban", value),
+            "BIC+" => entry.push_tag("counterpartyBic", value),
+            "SVWZ+" if !value.is_empty() => remittance = Some(value.to_string()),
+            _ => {}
+        }
+    }
+    if remittance.is_some() {
+        entry.remittance = remittance;
+    }
+}
+
+/// `/KEY/value/KEY/value` pairs. Values may contain `/`; a value runs until
+/// the next known key.
+fn slash_pairs(text: &str) -> Vec<(&str, &str)> {
+    let mut starts: Vec<(usize, &str)> = Vec::new();
+    let mut offset = 0;
+    while let Some(found) = text[offset..].find('/') {
+        let pos = offset + found;
+        if let Some(key) = SLASH_KEYS.iter().find(|key| {
+            text[pos + 1..].starts_with(**key) && text[pos + 1 + key.len()..].starts_with('/')
+        }) {
+            starts.push((pos, key));
+            offset = pos + key.len() + 2;
+        } else {
+            offset = pos + 1;
+        }
+    }
+    starts
+        .iter()
+        .enumerate()
+        .map(|(n, &(pos, key))| {
+            let end = starts.get(n + 1).map_or(text.len(), |(next, _)| *next);
+            let value = &text[(pos + key.len() + 2).min(end)..end];
+            (key, value.trim_end_matches('/'))
+        })
+        .collect()
+}
+
+// --- CAMT.053 ---
+
+pub fn parse_camt053(text: &str) -> io::Result<Vec<StatementEntry>> {
+    let document = parse_xml(text)?;
+    let statements = document
+        .find(&["BkToCstmrStmt"])
+        .ok_or_else(|| invalid("not a CAMT.053 statement: missing BkToCstmrStmt".to_string()))?;
+    let mut entries = Vec::new();
+    let mut position = 0;
+    for statement in statements.children_named("Stmt") {
+        let account_currency = statement.text_at(&["Acct", "Ccy"]).unwrap_or_default();
+        let account_id = statement
+            .text_at(&["Acct", "Id", "IBAN"])
+            .or_else(|| statement.text_at(&["Acct", "Id", "Othr", "Id"]))
+            .unwrap_or_default();
+        for ntry in statement.children_named("Ntry") {
+            position += 1;
+            if let Some(mut entry) = parse_camt_entry(ntry, account_currency, position)? {
+                entry.push_tag("accountId", account_id);
+                entries.push(entry);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+fn parse_camt_entry(
+    ntry: &XmlElement,
+    account_currency: &str,
+    position: usize,
+) -> io::Result<Option<StatementEntry>> {
+    let status = ntry
+        .text_at(&["Sts", "Cd"])
+        .or_else(|| ntry.text_at(&["Sts"]))
+        .unwrap_or("BOOK");
+    if status == "INFO" {
+        return Ok(None);
+    }
+    let amount_element = ntry
+        .find(&["Amt"])
+        .ok_or_else(|| invalid(format!("CAMT.053 entry {position} has no Amt")))?;
+    let amount = amount_element.text.trim().to_string();
+    let debit = ntry.text_at(&["CdtDbtInd"]) == Some("DBIT");
+    let date = |path: &str| {
+        ntry.text_at(&[path, "Dt"])
+            .or_else(|| ntry.text_at(&[path, "DtTm"]).and_then(|t| t.get(..10)))
+            .map(str::to_string)
+    };
+    let booking_date = date("BookgDt")
+        .or_else(|| date("ValDt"))
+        .ok_or_else(|| invalid(format!("CAMT.053 entry {position} has no booking date")))?;
+
+    let mut entry = StatementEntry {
+        position,
+        booking_date,
+        value_date: date("ValDt"),
+        amount: if debit { format!("-{amount}") } else { amount },
+        currency: amount_element
+            .attr("Ccy")
+            .unwrap_or(account_currency)
+            .to_string(),
+        pending: status == "PDNG",
+        bank_reference: ntry.text_at(&["AcctSvcrRef"]).map(str::to_string),
+        ..StatementEntry::default()
+    };
+
+    let details: Vec<&XmlElement> = ntry
+        .children_named("NtryDtls")
+        .flat_map(|d| d.children_named("TxDtls"))
+        .collect();
+    if let [tx] = details.as_slice() {
+        apply_camt_transaction_details(tx, debit, &mut entry);
+    } else if details.len() > 1 {
+        entry.push_tag("batchCount", &details.len().to_string());
+    }
+    if entry.remittance.is_none() {
+        entry.remittance = ntry.text_at(&["AddtlNtryInf"]).map(str::to_string);
+    }
+    Ok(Some(entry))
+}
+
+fn apply_camt_transaction_details(tx: &XmlElement, debit: bool, entry: &mut StatementEntry) {
+    if entry.bank_reference.is_none() {
+        entry.bank_reference = tx.text_at(&["Refs", "AcctSvcrRef"]).map(str::to_string);
+    }
+    if let Some(id) = tx.text_at(&["Refs", "EndToEndId"]) {
+        entry.push_tag("endToEndId", id);
+    }
+    if let Some(id) = tx.text_at(&["Refs", "MndtId"]) {
+        entry.push_tag("mandateId", id);
+    }
+
+    // The counterparty is the creditor of a debit and the debtor of a credit.
+    let (party, account, agent) = if debit {
+        ("Cdtr", "CdtrAcct", "CdtrAgt")
+    } else {
+        ("Dbtr", "DbtrAcct", "DbtrAgt")
+    };
+    if let Some(parties) = tx.find(&["RltdPties"]) {
+        entry.counterparty_name = parties
+            .text_at(&[party, "Nm"])
+            .or_else(|| parties.text_at(&[party, "Pty", "Nm"]))
+            .map(str::to_string);
+        if let Some(iban) = parties.text_at(&[account, "Id", "IBAN"]) {
+            entry.push_tag("counterpartyIban", iban);
+        }
+        if debit {
+            if let Some(id) = parties.text_at(&["Cdtr", "Id", "PrvtId", "Othr", "Id"]) {
+                entry.push_tag("creditorId", id);
+            }
+        }
+    }
+    if let Some(bic) = tx
+        .text_at(&["RltdAgts", agent, "FinInstnId", "BIC"])
+        .or_else(|| tx.text_at(&["RltdAgts", agent, "FinInstnId", "BICFI"]))
+    {
+        entry.push_tag("counterpartyBic", bic);
+    }
+
+    if let Some(remittance) = tx.find(&["RmtInf"]) {
+        let unstructured: Vec<&str> = remittance
+            .children_named("Ustrd")
+            .map(|u| u.text.trim())
+            .filter(|u| !u.is_empty())
+            .collect();
+        if !unstructured.is_empty() {
+            entry.remittance = Some(unstructured.join(" "));
+        }
+        for structured in remittance.children_named("Strd") {
+            if let Some(reference) = structured.text_at(&["CdtrRefInf", "Ref"]) {
+                entry.push_tag("creditorReference", reference);
+            }
+        }
+    }
+    if entry.remittance.is_none() {
+        entry.remittance = tx.text_at(&["AddtlTxInf"]).map(str::to_string);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn tag<'a>(entry: &'a StatementEntry, key: &str) -> Option<&'a str> {
+        entry
+            .tags
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    #[test]
+    fn parses_mt940_with_german_and_dutch_information() {
+        let text = "{1:F01BANKDEFFXXXX0000000000}{2:I940BANKDEFFXXXXN}{4:\r\n\
+:20:STARTUMS\r\n\
+:25:DE89370400440532013000\r\n\
+:28C:1/1\r\n\
+:60F:C241231EUR1000,00\r\n\
+:61:2412310102DR12,50NDDTMREF-77//BANKREF1\r\n\
+:86:105?00SEPA-LASTSCHRIFT?20EREF+E2E-001 MREF+MREF-77 CRED+DE98ZZZ09999999999 SV\r\n\
+?21WZ+Strom Januar?30COBADEFFXXX?31DE02120300000000202051?32Stadtwerke?33 Berlin\r\n\
+:61:250103C2000,NTRFNONREF\r\n\
+:86:/TRTP/SEPA OVERBOEKING/IBAN/NL91ABNA0417164300/BIC/ABNANL2A/NAME/Employer B.V./REMI/USTD//Salary 01/2025/EREF/NOTPROVIDED\r\n\
+:62F:C250103EUR2987,50\r\n\
+-}\r\n";
+        let entries = parse_mt940(text).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let debit = &entries[0];
+        assert_eq!(debit.position, 6);
+        assert_eq!(debit.booking_date, "2025-01-02");
+        assert_eq!(debit.value_date.as_deref(), Some("2024-12-31"));
+        assert_eq!(debit.amount, "-12.50");
+        assert_eq!(debit.currency, "EUR");
+        assert_eq!(debit.bank_reference.as_deref(), Some("BANKREF1"));
+        assert_eq!(
+            debit.counterparty_name.as_deref(),
+            Some("Stadtwerke Berlin")
+        );
+        assert_eq!(debit.remittance.as_deref(), Some("Strom Januar"));
+        assert_eq!(tag(debit, "endToEndId"), Some("E2E-001"));
+        assert_eq!(tag(debit, "mandateId"), Some("MREF-77"));
+        assert_eq!(tag(debit, "creditorId"), Some("DE98ZZZ09999999999"));
+        assert_eq!(
+            tag(debit, "counterpartyIban"),
+            Some("DE02120300000000202051")
+        );
+        assert_eq!(tag(debit, "customerReference"), Some("MREF-77"));
+        assert_eq!(tag(debit, "accountId"), Some("DE89370400440532013000"));
+
+        let credit = &entries[1];
+        assert_eq!(credit.amount, "2000");
+        assert_eq!(credit.booking_date, "2025-01-03");
+        assert_eq!(credit.bank_reference, None);
+        assert_eq!(credit.counterparty_name.as_deref(), Some("Employer B.V."));
+        assert_eq!(credit.remittance.as_deref(), Some("Salary 01/2025"));
+        assert_eq!(tag(credit, "counterpartyIban"), Some("NL91ABNA0417164300"));
+        assert_eq!(tag(credit, "counterpartyBic"), Some("ABNANL2A"));
+        assert_eq!(tag(credit, "endToEndId"), None);
+
+        assert!(parse_mt940("Date,Amount\n2025-01-01,5\n").is_err());
+    }
+
+    #[test]
+    fn parses_camt053_entries() {
+        let text = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Document xmlns="urn:iso:std:iso:20022:tech:xsd:camt.053.001.02">
+  <BkToCstmrStmt>
+    <Stmt>
+      <Acct><Id><IBAN>DE89370400440532013000</IBAN></Id><Ccy>EUR</Ccy></Acct>
+      <Ntry>
+        <Amt Ccy="EUR">42.00</Amt>
+        <CdtDbtInd>DBIT</CdtDbtInd>
+        <Sts>BOOK</Sts>
+        <BookgDt><Dt>2025-01-05</Dt></BookgDt>
+        <ValDt><Dt>2025-01-06</Dt></ValDt>
+        <AcctSvcrRef>2025010500001</AcctSvcrRef>
+        <NtryDtls><TxDtls>
+          <Refs><EndToEndId>INV-2025-7</EndToEndId></Refs>
+          <RltdPties>
+            <Cdtr><Nm>Acme GmbH &amp; Co</Nm></Cdtr>
+            <CdtrAcct><Id><IBAN>DE02100100109307118603</IBAN></Id></CdtrAcct>
+          </RltdPties>
+          <RltdAgts><CdtrAgt><FinInstnId><BIC>PBNKDEFFXXX</BIC></FinInstnId></CdtrAgt></RltdAgts>
+          <RmtInf><Strd><CdtrRefInf><Ref>RF18539007547034</Ref></CdtrRefInf></Strd></RmtInf>
+        </TxDtls></NtryDtls>
+        <AddtlNtryInf>SEPA Credit Transfer</AddtlNtryInf>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">5.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts><Cd>PDNG</Cd></Sts>
+        <BookgDt><DtTm>2025-01-07T09:30:00</DtTm></BookgDt>
+      </Ntry>
+      <Ntry>
+        <Amt Ccy="EUR">1.00</Amt>
+        <CdtDbtInd>CRDT</CdtDbtInd>
+        <Sts>INFO</Sts>
+        <BookgDt><Dt>2025-01-08</Dt></BookgDt>
+      </Ntry>
+    </Stmt>
+  </BkToCstmrStmt>
+</Document>"#;
+        let entries = parse_camt053(text).unwrap();
+        assert_eq!(entries.len(), 2);
+
+        let debit = &entries[0];
+        assert_eq!(debit.position, 1);
+        assert_eq!(debit.amount, "-42.00");
+        assert_eq!(debit.booking_date, "2025-01-05");
+        assert_eq!(debit.value_date.as_deref(), Some("2025-01-06"));
+        assert_eq!(debit.bank_reference.as_deref(), Some("2025010500001"));
+        assert_eq!(debit.counterparty_name.as_deref(), Some("Acme GmbH & Co"));
+        assert_eq!(debit.remittance.as_deref(), Some("SEPA Credit Transfer"));
+        assert_eq!(
+            tag(debit, "counterpartyIban"),
+            Some("DE02100100109307118603")
+        );
+        assert_eq!(tag(debit, "counterpartyBic"), Some("PBNKDEFFXXX"));
+        assert_eq!(tag(debit, "endToEndId"), Some("INV-2025-7"));
+        assert_eq!(tag(debit, "creditorReference"), Some("RF18539007547034"));
+        assert_eq!(tag(debit, "accountId"), Some("DE89370400440532013000"));
+
+        let pending = &entries[1];
+        assert!(pending.pending);
+        assert_eq!(pending.booking_date, "2025-01-07");
+        assert_eq!(pending.amount, "5.00");
+
+        assert!(parse_camt053("<Document><Other/></Document>").is_err());
+    }
+}
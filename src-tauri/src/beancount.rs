@@ -0,0 +1,890 @@
+//! Beancount file reading for [`crate::book_import`].
+//!
+//! Covers the directives that carry ledger data: `open`/`close` (the chart of
+//! accounts), `price`, transactions with metadata, tags and links, `balance`
+//! (as hledger balance assertions), `pad` (as generated transactions), and
+//! `include`. Postings without an amount are filled in from the others.
+//! Lot reductions with an empty cost (`{}`) are booked first-in-first-out.
+//! Plugins are not run, and `note`, `document`, `event`, `query`, and
+//! `custom` directives are listed in the report instead.
+
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+
+use crate::book_import::{
+    AccountKind, Book, BookAccount, BookAmount, BookPosting, BookPrice, BookTransaction, Decimal,
+    PostingCost,
+};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+/// Read the Beancount file at `path` and the files it includes.
+pub fn read_book(path: &Path) -> Result<Book, BoxError> {
+    let mut reader = Reader::default();
+    reader.read_file(path)?;
+    Ok(reader.finish())
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Word(String),
+    Text(String),
+}
+
+#[derive(Debug, Clone)]
+enum CostSpec {
+    Unit(BookAmount),
+    Total(BookAmount),
+    /// `{}` or a date/label-only spec: reduce whatever lots are held.
+    Lot,
+}
+
+#[derive(Debug, Clone)]
+struct RawPosting {
+    account: String,
+    status: Option<char>,
+    amount: Option<BookAmount>,
+    cost: Option<CostSpec>,
+    price: Option<PostingCost>,
+    tags: Vec<(String, String)>,
+}
+
+#[derive(Debug)]
+enum Entry {
+    Transaction {
+        txn: BookTransaction,
+        postings: Vec<RawPosting>,
+    },
+    Pad {
+        date: String,
+        account: String,
+        source: String,
+    },
+    Balance {
+        date: String,
+        account: String,
+        amount: BookAmount,
+    },
+}
+
+impl Entry {
+    fn date(&self) -> &str {
+        match self {
+            Self::Transaction { txn, .. } => &txn.date,
+            Self::Pad { date, .. } | Self::Balance { date, .. } => date,
+        }
+    }
+}
+
+/// Where indented lines (postings, metadata) attach.
+#[derive(Debug, Clone)]
+enum Context {
+    None,
+    Transaction {
+        entry: usize,
+        posting_indent: Option<usize>,
+    },
+    Account(String),
+}
+
+#[derive(Debug)]
+struct Reader {
+    entries: Vec<Entry>,
+    accounts: BTreeMap<String, BookAccount>,
+    prices: Vec<BookPrice>,
+    root_names: HashMap<String, AccountKind>,
+    tag_stack: Vec<String>,
+    skipped: BTreeMap<String, usize>,
+    unrepresented: Vec<String>,
+    read_files: HashSet<PathBuf>,
+}
+
+impl Default for Reader {
+    fn default() -> Self {
+        Self {
+            entries: Vec::new(),
+            accounts: BTreeMap::new(),
+            prices: Vec::new(),
+            root_names: HashMap::from([
+                ("Assets".to_string(), AccountKind::Asset),
+                ("Liabilities".to_string(), AccountKind::Liability),
+                ("Equity".to_string(), AccountKind::Equity),
+                ("Income".to_string(), AccountKind::Revenue),
+                ("Expenses".to_string(), AccountKind::Expense),
+            ]),
+            tag_stack: Vec::new(),
+            skipped: BTreeMap::new(),
+            unrepresented: Vec::new(),
+            read_files: HashSet::new(),
+        }
+    }
+}
+
+impl Reader {
+    fn read_file(&mut self, path: &Path) -> Result<(), BoxError> {
+        let canonical = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        if !self.read_files.insert(canonical) {
+            return Ok(());
+        }
+        let text = std::fs::read_to_string(path)
+            .map_err(|err| std::io::Error::other(format!("{}: {err}", path.display())))?;
+        let mut context = Context::None;
+        for (index, line) in text.lines().enumerate() {
+            let located = |message: String| -> BoxError {
+                std::io::Error::other(format!("{}:{}: {message}", path.display(), index + 1)).into()
+            };
+            let content = strip_comment(line);
+            if content.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with(char::is_whitespace) {
+                let indent = content.len() - content.trim_start().len();
+                self.read_indented(content.trim(), indent, &mut context)
+                    .map_err(located)?;
+                continue;
+            }
+            let tokens = tokenize(content).map_err(located)?;
+            if let [Token::Word(keyword), Token::Text(target), ..] = tokens.as_slice() {
+                if keyword == "include" {
+                    let base = path.parent().unwrap_or_else(|| Path::new("."));
+                    self.read_file(&base.join(target))?;
+                    context = Context::None;
+                    continue;
+                }
+            }
+            context = self.read_directive(&tokens, content).map_err(located)?;
+        }
+        Ok(())
+    }
+
+    fn read_directive(&mut self, tokens: &[Token], line: &str) -> Result<Context, String> {
+        let word = |index: usize| match tokens.get(index) {
+            Some(Token::Word(word)) => Ok(word.as_str()),
+            _ => Err(format!("expected a word in: {}", line.trim())),
+        };
+        let Some(date) = word(0).ok().and_then(parse_date) else {
+            match (word(0).unwrap_or_default(), tokens.get(1), tokens.get(2)) {
+                ("option", Some(Token::Text(name)), Some(Token::Text(value))) => {
+                    self.set_option(name, value)
+                }
+                ("plugin", Some(Token::Text(name)), _) => self
+                    .unrepresented
+                    .push(format!("plugin {name} was not run")),
+                ("pushtag", Some(Token::Word(tag)), _) => {
+                    self.tag_stack.push(tag.trim_start_matches('#').to_string())
+                }
+                ("poptag", Some(Token::Word(tag)), _) => {
+                    let tag = tag.trim_start_matches('#');
+                    if let Some(position) = self.tag_stack.iter().rposition(|t| t == tag) {
+                        self.tag_stack.remove(position);
+                    }
+                }
+                ("pushmeta" | "popmeta", _, _) => count(&mut self.skipped, "pushmeta directives"),
+                // Org-mode headings and other undated lines carry no data.
+                _ => {}
+            }
+            return Ok(Context::None);
+        };
+
+        let keyword = word(1)?;
+        match keyword {
+            "open" => {
+                let account = word(2)?.to_string();
+                self.account(&account).opened = Some(date);
+                Ok(Context::Account(account))
+            }
+            "close" => {
+                let account = word(2)?.to_string();
+                self.account(&account).closed = Some(date);
+                Ok(Context::Account(account))
+            }
+            "commodity" => Ok(Context::None),
+            "price" => {
+                let commodity = word(2)?.to_string();
+                let price = parse_amount(&format!("{} {}", word(3)?, word(4)?))?;
+                self.prices.push(BookPrice {
+                    date,
+                    commodity,
+                    price,
+                });
+                Ok(Context::None)
+            }
+            "balance" => {
+                let account = word(2)?.to_string();
+                self.account(&account);
+                // A tolerance (`100.00 ~ 0.01 USD`) isn't kept.
+                let words: Vec<&str> = tokens[3..]
+                    .iter()
+                    .filter_map(|token| match token {
+                        Token::Word(word) if word != "~" => Some(word.as_str()),
+                        _ => None,
+                    })
+                    .collect();
+                let (Some(number), Some(commodity)) = (words.first(), words.last()) else {
+                    return Err(format!("balance needs an amount: {}", line.trim()));
+                };
+                let amount = parse_amount(&format!("{number} {commodity}"))?;
+                self.entries.push(Entry::Balance {
+                    date,
+                    account,
+                    amount,
+                });
+                Ok(Context::None)
+            }
+            "pad" => {
+                let account = word(2)?.to_string();
+                let source = word(3)?.to_string();
+                self.account(&account);
+                self.account(&source);
+                self.entries.push(Entry::Pad {
+                    date,
+                    account,
+                    source,
+                });
+                Ok(Context::None)
+            }
+            "note" | "document" | "event" | "query" | "custom" => {
+                count(&mut self.skipped, &format!("{keyword} directives"));
+                Ok(Context::None)
+            }
+            flag if flag == "txn" || flag.chars().count() == 1 => {
+                let mut txn = BookTransaction {
+                    date,
+                    status: match flag {
+                        "*" | "txn" => Some('*'),
+                        "!" => Some('!'),
+                        _ => None,
+                    },
+                    ..BookTransaction::default()
+                };
+                let mut strings = Vec::new();
+                for token in &tokens[2..] {
+                    match token {
+                        Token::Text(text) => strings.push(text.as_str()),
+                        Token::Word(word) => {
+                            if let Some(tag) = word.strip_prefix('#') {
+                                txn.tags.push((tag.to_string(), String::new()));
+                            } else if let Some(link) = word.strip_prefix('^') {
+                                txn.tags.push(("link".to_string(), link.to_string()));
+                            }
+                        }
+                    }
+                }
+                for tag in &self.tag_stack {
+                    if !txn.tags.iter().any(|(key, _)| key == tag) {
+                        txn.tags.push((tag.clone(), String::new()));
+                    }
+                }
+                txn.description = match strings.as_slice() {
+                    [] => String::new(),
+                    [narration] => narration.to_string(),
+                    ["", narration, ..] => narration.to_string(),
+                    [payee, narration, ..] => format!("{payee} | {narration}"),
+                };
+                self.entries.push(Entry::Transaction {
+                    txn,
+                    postings: Vec::new(),
+                });
+                Ok(Context::Transaction {
+                    entry: self.entries.len() - 1,
+                    posting_indent: None,
+                })
+            }
+            other => Err(format!("unknown directive: {other}")),
+        }
+    }
+
+    fn read_indented(
+        &mut self,
+        content: &str,
+        indent: usize,
+        context: &mut Context,
+    ) -> Result<(), String> {
+        let metadata = parse_metadata(content);
+        match context {
+            Context::None => Ok(()),
+            Context::Account(account) => {
+                if let Some((key, value)) = metadata {
+                    if key == "description" {
+                        let account = account.clone();
+                        self.account(&account).description = Some(value);
+                    }
+                }
+                Ok(())
+            }
+            Context::Transaction {
+                entry,
+                posting_indent,
+            } => {
+                let Some(Entry::Transaction { txn, postings }) = self.entries.get_mut(*entry)
+                else {
+                    return Ok(());
+                };
+                if let Some((key, value)) = metadata {
+                    match (postings.last_mut(), *posting_indent) {
+                        (Some(posting), Some(posting_indent)) if indent > posting_indent => {
+                            posting.tags.push((key, value))
+                        }
+                        _ => txn.tags.push((key, value)),
+                    }
+                    return Ok(());
+                }
+                let posting = parse_posting(content)?;
+                let account = posting.account.clone();
+                postings.push(posting);
+                *posting_indent = Some(indent);
+                self.account(&account);
+                Ok(())
+            }
+        }
+    }
+
+    fn set_option(&mut self, name: &str, value: &str) {
+        let kind = match name {
+            "name_assets" => AccountKind::Asset,
+            "name_liabilities" => AccountKind::Liability,
+            "name_equity" => AccountKind::Equity,
+            "name_income" => AccountKind::Revenue,
+            "name_expenses" => AccountKind::Expense,
+            _ => return,
+        };
+        self.root_names.retain(|_, existing| *existing != kind);
+        self.root_names.insert(value.to_string(), kind);
+    }
+
+    fn account(&mut self, name: &str) -> &mut BookAccount {
+        self.accounts
+            .entry(name.to_string())
+            .or_insert_with(|| BookAccount {
+                name: name.to_string(),
+                kind: None,
+                description: None,
+                opened: None,
+                closed: None,
+            })
+    }
+
+    /// Resolve inferred amounts, lots, pads, and balances in date order.
+    fn finish(mut self) -> Book {
+        let mut entries = std::mem::take(&mut self.entries);
+        // Balances are checked at the start of their day.
+        entries.sort_by(|a, b| {
+            let rank = |entry: &Entry| u8::from(!matches!(entry, Entry::Balance { .. }));
+            a.date().cmp(b.date()).then(rank(a).cmp(&rank(b)))
+        });
+
+        let mut book = Book::default();
+        let mut assertions = Vec::new();
+        let mut balances: HashMap<(String, String), Decimal> = HashMap::new();
+        let mut pads: HashMap<String, (String, String)> = HashMap::new();
+        let mut lots: HashMap<(String, String), VecDeque<(Decimal, BookAmount)>> = HashMap::new();
+        let mut fifo_reductions = 0usize;
+        let mut unmatched_reductions = 0usize;
+        let mut parent_assertions = 0usize;
+
+        for entry in entries {
+            match entry {
+                Entry::Transaction { mut txn, postings } => {
+                    let mut residual: BTreeMap<String, Decimal> = BTreeMap::new();
+                    let mut blank = None;
+                    for raw in postings {
+                        let Some(amount) = raw.amount.clone() else {
+                            blank.get_or_insert(raw);
+                            continue;
+                        };
+                        let mut tags = raw.tags;
+                        let cost = match raw.cost {
+                            None => raw.price,
+                            Some(spec) => {
+                                match &raw.price {
+                                    Some(PostingCost::Unit(price)) => {
+                                        tags.push(("price".to_string(), amount_text(price)))
+                                    }
+                                    Some(PostingCost::Total(price)) => {
+                                        tags.push(("price-total".to_string(), amount_text(price)))
+                                    }
+                                    None => {}
+                                }
+                                let held = lots
+                                    .entry((raw.account.clone(), amount.commodity.clone()))
+                                    .or_default();
+                                if amount.quantity.is_negative() {
+                                    let booked = reduce_lots(held, amount.quantity.abs());
+                                    match spec {
+                                        CostSpec::Unit(unit) => Some(PostingCost::Unit(unit)),
+                                        CostSpec::Total(total) => Some(PostingCost::Total(total)),
+                                        CostSpec::Lot => match booked {
+                                            Some(total) => {
+                                                fifo_reductions += 1;
+                                                Some(PostingCost::Total(total))
+                                            }
+                                            None => {
+                                                unmatched_reductions += 1;
+                                                raw.price
+                                            }
+                                        },
+                                    }
+                                } else {
+                                    match spec {
+                                        CostSpec::Unit(unit) => {
+                                            held.push_back((amount.quantity, unit.clone()));
+                                            Some(PostingCost::Unit(unit))
+                                        }
+                                        CostSpec::Total(total) => Some(PostingCost::Total(total)),
+                                        CostSpec::Lot => raw.price,
+                                    }
+                                }
+                            }
+                        };
+                        let weight = amount.weight(cost.as_ref());
+                        let sum = residual.entry(weight.commodity).or_default();
+                        *sum += weight.quantity;
+                        txn.postings.push(BookPosting {
+                            account: raw.account,
+                            status: raw.status,
+                            amount,
+                            cost,
+                            assertion: None,
+                            comment: None,
+                            tags,
+                        });
+                    }
+                    if let Some(blank) = blank {
+                        for (commodity, sum) in residual {
+                            if !sum.is_zero() {
+                                txn.postings.push(BookPosting {
+                                    account: blank.account.clone(),
+                                    status: blank.status,
+                                    amount: BookAmount::new(-sum, &commodity),
+                                    cost: None,
+                                    assertion: None,
+                                    comment: None,
+                                    tags: blank.tags.clone(),
+                                });
+                            }
+                        }
+                    }
+                    for posting in &txn.postings {
+                        let balance = balances
+                            .entry((posting.account.clone(), posting.amount.commodity.clone()))
+                            .or_default();
+                        *balance += posting.amount.quantity;
+                    }
+                    book.transactions.push(txn);
+                }
+                Entry::Pad {
+                    date,
+                    account,
+                    source,
+                } => {
+                    pads.insert(account, (date, source));
+                }
+                Entry::Balance {
+                    date,
+                    account,
+                    amount,
+                } => {
+                    let key = (account.clone(), amount.commodity.clone());
+                    if let Some((pad_date, source)) = pads.remove(&account) {
+                        let current = balances.get(&key).copied().unwrap_or_default();
+                        let difference = amount.quantity - current;
+                        if !difference.is_zero() {
+                            book.transactions.push(BookTransaction {
+                                date: pad_date,
+                                description: format!(
+                                    "Padding inserted for balance of {} for difference {}",
+                                    amount_text(&amount),
+                                    amount_text(&BookAmount::new(difference, &amount.commodity))
+                                ),
+                                postings: vec![
+                                    crate::book_import::simple_posting(
+                                        &account,
+                                        BookAmount::new(difference, &amount.commodity),
+                                    ),
+                                    crate::book_import::simple_posting(
+                                        &source,
+                                        BookAmount::new(-difference, &amount.commodity),
+                                    ),
+                                ],
+                                ..BookTransaction::default()
+                            });
+                            balances.insert(key, amount.quantity);
+                        }
+                    }
+                    let prefix = format!("{account}:");
+                    if self.accounts.keys().any(|name| name.starts_with(&prefix)) {
+                        parent_assertions += 1;
+                    }
+                    let Some(day_before) = chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                        .ok()
+                        .and_then(|day| day.pred_opt())
+                    else {
+                        continue;
+                    };
+                    let mut posting = crate::book_import::simple_posting(
+                        &account,
+                        BookAmount::new(Decimal::default(), &amount.commodity),
+                    );
+                    posting.assertion = Some(amount);
+                    assertions.push(BookTransaction {
+                        date: day_before.format("%Y-%m-%d").to_string(),
+                        description: "Balance assertion".to_string(),
+                        postings: vec![posting],
+                        ..BookTransaction::default()
+                    });
+                }
+            }
+        }
+        // After every transaction of the same day, so they see its balance.
+        book.transactions.extend(assertions);
+
+        for (name, mut account) in std::mem::take(&mut self.accounts) {
+            let root = name.split(':').next().unwrap_or_default();
+            account.kind = self.root_names.get(root).copied();
+            book.accounts.push(account);
+        }
+        book.prices = std::mem::take(&mut self.prices);
+        book.prices.sort_by(|a, b| a.date.cmp(&b.date));
+
+        book.unrepresented = std::mem::take(&mut self.unrepresented);
+        if fifo_reductions > 0 {
+            book.unrepresented.push(format!(
+                "{fifo_reductions} lot reduction(s) with an empty cost booked first-in-first-out"
+            ));
+        }
+        if unmatched_reductions > 0 {
+            book.unrepresented.push(format!(
+                "{unmatched_reductions} lot reduction(s) didn't match held lots; their price was used as cost"
+            ));
+        }
+        if parent_assertions > 0 {
+            book.unrepresented.push(format!(
+                "{parent_assertions} balance assertion(s) on parent accounts check only the parent's own postings"
+            ));
+        }
+        for (what, n) in self.skipped {
+            book.unrepresented.push(format!("{n} {what} not imported"));
+        }
+        book
+    }
+}
+
+/// Take `quantity` from the oldest lots; the total cost when they cover it
+/// in one currency.
+fn reduce_lots(
+    lots: &mut VecDeque<(Decimal, BookAmount)>,
+    quantity: Decimal,
+) -> Option<BookAmount> {
+    let mut remaining = quantity;
+    let mut total: Option<BookAmount> = None;
+    while !remaining.is_zero() {
+        let (held, unit) = lots.front_mut()?;
+        let take = if (*held - remaining).is_negative() {
+            *held
+        } else {
+            remaining
+        };
+        let cost = take * unit.quantity;
+        match &mut total {
+            Some(total) if total.commodity != unit.commodity => return None,
+            Some(total) => total.quantity += cost,
+            None => total = Some(BookAmount::new(cost, &unit.commodity)),
+        }
+        *held -= take;
+        if held.is_zero() {
+            lots.pop_front();
+        }
+        remaining -= take;
+    }
+    total
+}
+
+fn parse_posting(content: &str) -> Result<RawPosting, String> {
+    let mut words = content.split_whitespace();
+    let mut first = words.next().unwrap_or_default();
+    let mut status = None;
+    if first.chars().count() == 1 {
+        status = match first {
+            "*" => Some('*'),
+            "!" => Some('!'),
+            _ => None,
+        };
+        first = words.next().unwrap_or_default();
+    }
+    if !first.contains(':') {
+        return Err(format!("expected a posting: {content}"));
+    }
+    let account = first.to_string();
+    let rest = content[content.find(first).unwrap_or_default() + first.len()..].trim();
+
+    let (rest, cost) = match (rest.find('{'), rest.rfind('}')) {
+        (Some(start), Some(end)) if start < end => {
+            let spec = &rest[start..=end];
+            let (inner, total) = match spec
+                .strip_prefix("{{")
+                .and_then(|spec| spec.strip_suffix("}}"))
+            {
+                Some(inner) => (inner, true),
+                None => (&spec[1..spec.len() - 1], false),
+            };
+            let amount = inner
+                .split(',')
+                .find_map(|component| parse_amount(component).ok());
+            let cost = match amount {
+                Some(amount) if total => CostSpec::Total(amount),
+                Some(amount) => CostSpec::Unit(amount),
+                None => CostSpec::Lot,
+            };
+            (
+                format!("{} {}", &rest[..start], &rest[end + 1..]),
+                Some(cost),
+            )
+        }
+        _ => (rest.to_string(), None),
+    };
+
+    let (amount_text, price) = match rest.split_once('@') {
+        Some((amount_text, price_text)) => {
+            let price = match price_text.strip_prefix('@') {
+                Some(total) => PostingCost::Total(parse_amount(total)?),
+                None => PostingCost::Unit(parse_amount(price_text)?),
+            };
+            (amount_text.to_string(), Some(price))
+        }
+        None => (rest, None),
+    };
+    let amount = if amount_text.trim().is_empty() {
+        None
+    } else {
+        Some(parse_amount(&amount_text)?)
+    };
+    Ok(RawPosting {
+        account,
+        status,
+        amount,
+        cost,
+        price,
+        tags: Vec::new(),
+    })
+}
+
+/// `-1,234.56 USD`. Arithmetic expressions aren't supported.
+fn parse_amount(text: &str) -> Result<BookAmount, String> {
+    let words: Vec<&str> = text.split_whitespace().collect();
+    match words.as_slice() {
+        [number, commodity] => Decimal::parse(number)
+            .map(|quantity| BookAmount::new(quantity, commodity))
+            .ok_or_else(|| format!("unsupported amount: {}", text.trim())),
+        _ => Err(format!("expected an amount and commodity: {}", text.trim())),
+    }
+}
+
+/// `key: value` metadata; keys start with a lowercase letter.
+fn parse_metadata(content: &str) -> Option<(String, String)> {
+    let (key, value) = content.split_once(':')?;
+    let mut chars = key.chars();
+    if !chars.next()?.is_ascii_lowercase()
+        || !chars.all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        || !(value.is_empty() || value.starts_with(char::is_whitespace))
+    {
+        return None;
+    }
+    let value = value.trim();
+    let value = value
+        .strip_prefix('"')
+        .and_then(|value| value.strip_suffix('"'))
+        .unwrap_or(value);
+    Some((key.to_string(), value.to_string()))
+}
+
+fn parse_date(word: &str) -> Option<String> {
+    chrono::NaiveDate::parse_from_str(&word.replace('/', "-"), "%Y-%m-%d")
+        .ok()
+        .map(|date| date.format("%Y-%m-%d").to_string())
+}
+
+/// Drop a `;` comment that isn't inside a string.
+fn strip_comment(line: &str) -> &str {
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if in_string => escaped = true,
+            '"' => in_string = !in_string,
+            ';' if !in_string => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn tokenize(line: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = line.trim().chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut text = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => text.extend(chars.next()),
+                    Some(c) => text.push(c),
+                    None => return Err(format!("unterminated string: {}", line.trim())),
+                }
+            }
+            tokens.push(Token::Text(text));
+        } else {
+            let mut word = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() || c == '"' {
+                    break;
+                }
+                word.push(c);
+                chars.next();
+            }
+            tokens.push(Token::Word(word));
+        }
+    }
+    Ok(tokens)
+}
+
+fn amount_text(amount: &BookAmount) -> String {
+    format!("{} {}", amount.quantity, amount.commodity)
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, what: &str) {
+    *counts.entry(what.to_string()).or_default() += 1;
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::book_import::render_journal;
+
+    fn temp_dir() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-beancount-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reads_transactions_pads_balances_and_includes() {
+        let dir = temp_dir();
+        std::fs::write(
+            dir.join("main.beancount"),
+            r#"option "title" "Household"
+plugin "beancount.plugins.auto_accounts"
+* Accounts
+2020-01-01 open Assets:Checking USD
+  description: "Joint checking"
+2020-01-01 open Equity:Opening-Balances
+include "food.beancount"
+
+2023-12-31 pad Assets:Checking Equity:Opening-Balances
+2024-01-01 balance Assets:Checking 1000.00 USD
+2024-01-02 note Assets:Checking "Called the bank"
+"#,
+        )
+        .unwrap();
+        std::fs::write(
+            dir.join("food.beancount"),
+            r#"pushtag #household
+2024-01-01 * "Grocer" "Weekly shop" ^receipt-1 ; a comment
+  category: "food"
+  Expenses:Food   45.50 USD
+    aisle: "produce"
+  ! Assets:Checking
+poptag #household
+"#,
+        )
+        .unwrap();
+
+        let book = read_book(&dir.join("main.beancount")).unwrap();
+        let journal = render_journal(&book);
+        assert!(
+            journal.contains("account Assets:Checking  ; type: A, opened: 2020-01-01, description: Joint checking\n"),
+            "{journal}"
+        );
+        assert!(
+            journal.contains("account Expenses:Food  ; type: X\n"),
+            "{journal}"
+        );
+        assert!(
+            journal.contains(
+                "2023-12-31 Padding inserted for balance of 1000.00 USD for difference 1000.00 USD\n    Assets:Checking  1000.00 USD\n    Equity:Opening-Balances  -1000.00 USD\n"
+            ),
+            "{journal}"
+        );
+        assert!(
+            journal.contains(
+                "2023-12-31 Balance assertion\n    Assets:Checking  0 USD = 1000.00 USD\n"
+            ),
+            "{journal}"
+        );
+        assert!(
+            journal.contains(
+                "2024-01-01 * Grocer | Weekly shop  ; link: receipt-1, household:, category: food\n    Expenses:Food  45.50 USD  ; aisle: produce\n    ! Assets:Checking  -45.50 USD\n"
+            ),
+            "{journal}"
+        );
+        assert_eq!(
+            book.unrepresented,
+            vec![
+                "plugin beancount.plugins.auto_accounts was not run",
+                "1 note directives not imported",
+            ]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn books_empty_cost_reductions_first_in_first_out() {
+        let dir = temp_dir();
+        std::fs::write(
+            dir.join("main.beancount"),
+            r#"2024-01-01 * "Buy"
+  Assets:Brokerage  10 VTI {200 USD}
+  Assets:Cash
+2024-02-01 * "Buy"
+  Assets:Brokerage  10 VTI {250 USD}
+  Assets:Cash
+2024-03-01 * "Sell"
+  Assets:Brokerage  -15 VTI {} @ 300 USD
+  Assets:Cash  4500 USD
+  Income:Gains
+"#,
+        )
+        .unwrap();
+
+        let book = read_book(&dir.join("main.beancount")).unwrap();
+        let sale = &book.transactions[2];
+        assert_eq!(
+            sale.postings[0].cost,
+            Some(PostingCost::Total(BookAmount::new(
+                Decimal::parse("3250").unwrap(),
+                "USD"
+            )))
+        );
+        assert_eq!(
+            sale.postings[2].amount,
+            BookAmount::new(Decimal::parse("-1250").unwrap(), "USD")
+        );
+        assert_eq!(
+            book.unrepresented,
+            vec!["1 lot reduction(s) with an empty cost booked first-in-first-out"]
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,811 @@
+//! Import an existing GnuCash book or Beancount file into a new ledger's
+//! `general.journal`.
+//!
+//! Both formats are read into a [`Book`]: the chart of accounts (written as
+//! `account` directives with hledger account types), commodity prices (`P`
+//! directives), and every historical transaction. With a start date, history
+//! before it is collapsed into one opening-balances transaction. Anything the
+//! journal can't represent is listed in the [`ImportReport`].
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// Account receiving the other side of summarized opening balances.
+pub const OPENING_BALANCES_ACCOUNT: &str = "Equity:Opening Balances";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum BookFormat {
+    Gnucash,
+    Beancount,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct BookImportOptions {
+    /// Collapse transactions dated before this ISO date into opening balances.
+    pub start_date: Option<String>,
+    /// Report what would be imported without writing the ledger.
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ImportReport {
+    pub format: BookFormat,
+    pub dry_run: bool,
+    pub accounts: usize,
+    pub prices: usize,
+    pub transactions: usize,
+    /// Transactions folded into the opening-balances transaction.
+    pub summarized_transactions: usize,
+    pub opening_balance_date: Option<String>,
+    /// Source data that was dropped or approximated.
+    pub unrepresented: Vec<String>,
+}
+
+/// hledger account types, written as `type:` tags on account directives.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountKind {
+    Asset,
+    Cash,
+    Liability,
+    Equity,
+    Revenue,
+    Expense,
+}
+
+impl AccountKind {
+    fn code(self) -> &'static str {
+        match self {
+            Self::Asset => "A",
+            Self::Cash => "C",
+            Self::Liability => "L",
+            Self::Equity => "E",
+            Self::Revenue => "R",
+            Self::Expense => "X",
+        }
+    }
+
+    fn is_balance_sheet(self) -> bool {
+        !matches!(self, Self::Revenue | Self::Expense)
+    }
+
+    /// The kind implied by a conventional top-level account name.
+    pub fn from_top_level(name: &str) -> Option<Self> {
+        let top = name.split(':').next().unwrap_or_default();
+        match top.to_ascii_lowercase().as_str() {
+            "assets" | "asset" => Some(Self::Asset),
+            "liabilities" | "liability" => Some(Self::Liability),
+            "equity" => Some(Self::Equity),
+            "income" | "revenue" | "revenues" => Some(Self::Revenue),
+            "expenses" | "expense" => Some(Self::Expense),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Book {
+    pub accounts: Vec<BookAccount>,
+    pub prices: Vec<BookPrice>,
+    pub transactions: Vec<BookTransaction>,
+    pub unrepresented: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookAccount {
+    pub name: String,
+    pub kind: Option<AccountKind>,
+    pub description: Option<String>,
+    pub opened: Option<String>,
+    pub closed: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookPrice {
+    pub date: String,
+    pub commodity: String,
+    pub price: BookAmount,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookTransaction {
+    pub date: String,
+    /// `*` (cleared), `!` (pending), or `None`.
+    pub status: Option<char>,
+    pub code: Option<String>,
+    pub description: String,
+    pub comment: Option<String>,
+    pub tags: Vec<(String, String)>,
+    pub postings: Vec<BookPosting>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookPosting {
+    pub account: String,
+    pub status: Option<char>,
+    pub amount: BookAmount,
+    pub cost: Option<PostingCost>,
+    /// Balance the account must have, in this commodity, after the posting.
+    pub assertion: Option<BookAmount>,
+    pub comment: Option<String>,
+    pub tags: Vec<(String, String)>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum PostingCost {
+    Unit(BookAmount),
+    Total(BookAmount),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct BookAmount {
+    pub quantity: Decimal,
+    pub commodity: String,
+}
+
+impl BookAmount {
+    pub fn new(quantity: Decimal, commodity: &str) -> Self {
+        Self {
+            quantity,
+            commodity: commodity.to_string(),
+        }
+    }
+
+    /// The amount this posting contributes to the transaction's balance.
+    pub(crate) fn weight(&self, cost: Option<&PostingCost>) -> BookAmount {
+        match cost {
+            Some(PostingCost::Unit(unit)) => BookAmount {
+                quantity: self.quantity * unit.quantity,
+                commodity: unit.commodity.clone(),
+            },
+            Some(PostingCost::Total(total)) => BookAmount {
+                quantity: if self.quantity.is_negative() {
+                    -total.quantity.abs()
+                } else {
+                    total.quantity.abs()
+                },
+                commodity: total.commodity.clone(),
+            },
+            None => self.clone(),
+        }
+    }
+}
+
+/// A fixed-point decimal, `mantissa / 10^scale`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+impl Decimal {
+    /// Parse `-1,234.56`; grouping commas and a leading `+` are accepted.
+    pub fn parse(text: &str) -> Option<Self> {
+        let cleaned: String = text.trim().chars().filter(|c| *c != ',').collect();
+        let (negative, digits) = match cleaned.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, cleaned.strip_prefix('+').unwrap_or(&cleaned)),
+        };
+        let (int_part, frac_part) = digits.split_once('.').unwrap_or((digits, ""));
+        if (int_part.is_empty() && frac_part.is_empty())
+            || !int_part.chars().all(|c| c.is_ascii_digit())
+            || !frac_part.chars().all(|c| c.is_ascii_digit())
+        {
+            return None;
+        }
+        let mantissa: i128 = format!("{int_part}{frac_part}").parse().ok()?;
+        Some(Self {
+            mantissa: if negative { -mantissa } else { mantissa },
+            scale: u32::try_from(frac_part.len()).ok()?,
+        })
+    }
+
+    /// `numerator / denominator` when it has a finite decimal expansion, as
+    /// GnuCash's rational amounts (`-12345/100`) do.
+    pub fn from_ratio(numerator: i64, denominator: i64) -> Option<Self> {
+        if denominator == 0 {
+            return None;
+        }
+        let (numerator, denominator) = if denominator < 0 {
+            (-i128::from(numerator), -i128::from(denominator))
+        } else {
+            (i128::from(numerator), i128::from(denominator))
+        };
+        for scale in 0..=18u32 {
+            let scaled = numerator.checked_mul(10_i128.checked_pow(scale)?)?;
+            if scaled % denominator == 0 {
+                return Some(Self {
+                    mantissa: scaled / denominator,
+                    scale,
+                });
+            }
+        }
+        None
+    }
+
+    pub fn is_zero(self) -> bool {
+        self.mantissa == 0
+    }
+
+    pub fn is_negative(self) -> bool {
+        self.mantissa < 0
+    }
+
+    pub fn abs(self) -> Self {
+        Self {
+            mantissa: self.mantissa.abs(),
+            scale: self.scale,
+        }
+    }
+
+    /// `mantissa / 10^scale`, for quantities that arrive already split,
+    /// such as hledger's JSON decimals.
+    pub fn from_parts(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Compare numeric values, ignoring trailing-zero differences in scale.
+    pub fn cmp_value(self, other: Self) -> std::cmp::Ordering {
+        let scale = self.scale.max(other.scale);
+        self.rescaled(scale).cmp(&other.rescaled(scale))
+    }
+
+    /// `self / divisor` rounded half away from zero to `scale` places.
+    pub fn div_rounded(self, divisor: Self, scale: u32) -> Option<Self> {
+        if divisor.is_zero() {
+            return None;
+        }
+        // self.m / 10^a  /  (d.m / 10^b)  =  self.m * 10^(b + scale - a) / d.m  /  10^scale
+        let numerator = self
+            .mantissa
+            .checked_mul(10_i128.checked_pow(divisor.scale + scale)?)?;
+        let denominator = divisor
+            .mantissa
+            .checked_mul(10_i128.checked_pow(self.scale)?)?;
+        let quotient = numerator / denominator;
+        let remainder = numerator % denominator;
+        let round_away = remainder.checked_mul(2)?.abs() >= denominator.abs();
+        let mantissa = if !round_away {
+            quotient
+        } else if (numerator < 0) != (denominator < 0) {
+            quotient - 1
+        } else {
+            quotient + 1
+        };
+        Some(Self { mantissa, scale })
+    }
+
+    pub fn scale(self) -> u32 {
+        self.scale
+    }
+
+    fn rescaled(self, scale: u32) -> i128 {
+        self.mantissa * 10_i128.pow(scale - self.scale)
+    }
+}
+
+impl std::ops::Neg for Decimal {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            mantissa: -self.mantissa,
+            scale: self.scale,
+        }
+    }
+}
+
+impl std::ops::Add for Decimal {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let scale = self.scale.max(other.scale);
+        Self {
+            mantissa: self.rescaled(scale) + other.rescaled(scale),
+            scale,
+        }
+    }
+}
+
+impl std::ops::Sub for Decimal {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl std::ops::Mul for Decimal {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        Self {
+            mantissa: self.mantissa * other.mantissa,
+            scale: self.scale + other.scale,
+        }
+    }
+}
+
+impl std::ops::AddAssign for Decimal {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl std::ops::SubAssign for Decimal {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl std::fmt::Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.mantissa.abs().to_string();
+        let scale = self.scale as usize;
+        let digits = if digits.len() <= scale {
+            format!("{}{digits}", "0".repeat(scale + 1 - digits.len()))
+        } else {
+            digits
+        };
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+        if scale == 0 {
+            write!(f, "{sign}{digits}")
+        } else {
+            let (int_part, frac_part) = digits.split_at(digits.len() - scale);
+            write!(f, "{sign}{int_part}.{frac_part}")
+        }
+    }
+}
+
+/// Read the book at `path`.
+pub fn read_book(
+    format: BookFormat,
+    path: &Path,
+) -> Result<Book, Box<dyn std::error::Error + Send + Sync>> {
+    match format {
+        BookFormat::Gnucash => crate::gnucash::read_book(path),
+        BookFormat::Beancount => crate::beancount::read_book(path),
+    }
+}
+
+/// Import the book at `path` into the ledger's `general.journal`, which must
+/// not have transactions yet. The journal is checked with hledger and
+/// committed.
+pub fn import_book(
+    ledger_dir: &Path,
+    format: BookFormat,
+    path: &Path,
+    options: &BookImportOptions,
+    job: &crate::jobs::JobHandle<'_>,
+) -> Result<ImportReport, Box<dyn std::error::Error + Send + Sync>> {
+    job.progress(0, 3, format!("Reading {}", path.display()));
+    let mut book = read_book(format, path)?;
+    let summarized = match &options.start_date {
+        Some(start_date) => summarize_before(&mut book, start_date)?,
+        None => 0,
+    };
+    let report = ImportReport {
+        format,
+        dry_run: options.dry_run,
+        accounts: book.accounts.len(),
+        prices: book.prices.len(),
+        transactions: book.transactions.len(),
+        summarized_transactions: summarized,
+        opening_balance_date: options.start_date.clone(),
+        unrepresented: book.unrepresented.clone(),
+    };
+    if options.dry_run {
+        return Ok(report);
+    }
+
+    let journal_path = ledger_dir.join("general.journal");
+    let existing = std::fs::read_to_string(&journal_path)?;
+    if existing
+        .lines()
+        .any(|line| line.starts_with(|c: char| c.is_ascii_digit()))
+    {
+        return Err(std::io::Error::other(
+            "general.journal already has transactions; import into a new ledger",
+        )
+        .into());
+    }
+
+    job.check_cancelled()?;
+    job.progress(1, 3, "Checking the journal with hledger");
+    let (rendered, _) = crate::gl_journal::ensure_journal_has_ids(&render_journal(&book));
+    let content = if existing.trim().is_empty() {
+        rendered
+    } else {
+        format!("{}\n\n{rendered}", existing.trim_end())
+    };
+    crate::ledger_add::run_hledger_check(&content, &[], "imported book")?;
+    job.check_cancelled()?;
+    job.progress(2, 3, "Writing general.journal");
+    crate::atomic_file::write_atomic(&journal_path, content.as_bytes())?;
+    let source = match format {
+        BookFormat::Gnucash => "GnuCash book",
+        BookFormat::Beancount => "Beancount file",
+    };
+    crate::ledger::commit_general_journal(ledger_dir, &format!("Import {source}"))?;
+    Ok(report)
+}
+
+/// Replace transactions dated before `start_date` with one transaction on
+/// `start_date` carrying each balance-sheet account's balance. Returns the
+/// number of transactions replaced.
+pub fn summarize_before(
+    book: &mut Book,
+    start_date: &str,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    if chrono::NaiveDate::parse_from_str(start_date, "%Y-%m-%d").is_err() {
+        return Err(
+            std::io::Error::other(format!("start date must be YYYY-MM-DD: {start_date}")).into(),
+        );
+    }
+    let kinds: BTreeMap<String, Option<AccountKind>> = book
+        .accounts
+        .iter()
+        .map(|account| (account.name.clone(), account.kind))
+        .collect();
+    let is_balance_sheet = |account: &str| {
+        kinds
+            .get(account)
+            .copied()
+            .flatten()
+            .or_else(|| AccountKind::from_top_level(account))
+            .map_or(true, AccountKind::is_balance_sheet)
+    };
+
+    let (before, after): (Vec<_>, Vec<_>) = std::mem::take(&mut book.transactions)
+        .into_iter()
+        .partition(|txn| txn.date.as_str() < start_date);
+    let mut balances: BTreeMap<(String, String), Decimal> = BTreeMap::new();
+    for posting in before.iter().flat_map(|txn| &txn.postings) {
+        if is_balance_sheet(&posting.account) {
+            let balance = balances
+                .entry((posting.account.clone(), posting.amount.commodity.clone()))
+                .or_default();
+            *balance += posting.amount.quantity;
+        }
+    }
+
+    let mut postings = Vec::new();
+    let mut equity: BTreeMap<String, Decimal> = BTreeMap::new();
+    for ((account, commodity), quantity) in balances {
+        if quantity.is_zero() {
+            continue;
+        }
+        let total = equity.entry(commodity.clone()).or_default();
+        *total -= quantity;
+        postings.push(simple_posting(
+            &account,
+            BookAmount::new(quantity, &commodity),
+        ));
+    }
+    for (commodity, quantity) in equity {
+        if !quantity.is_zero() {
+            postings.push(simple_posting(
+                OPENING_BALANCES_ACCOUNT,
+                BookAmount::new(quantity, &commodity),
+            ));
+        }
+    }
+
+    let mut transactions = Vec::with_capacity(after.len() + 1);
+    if !postings.is_empty() {
+        if !book
+            .accounts
+            .iter()
+            .any(|account| account.name == OPENING_BALANCES_ACCOUNT)
+        {
+            book.accounts.push(BookAccount {
+                name: OPENING_BALANCES_ACCOUNT.to_string(),
+                kind: Some(AccountKind::Equity),
+                description: None,
+                opened: None,
+                closed: None,
+            });
+        }
+        transactions.push(BookTransaction {
+            date: start_date.to_string(),
+            status: Some('*'),
+            description: "Opening balances".to_string(),
+            postings,
+            ..BookTransaction::default()
+        });
+        if before.iter().any(|txn| {
+            txn.postings
+                .iter()
+                .any(|p| p.cost.is_some() && is_balance_sheet(&p.account))
+        }) {
+            book.unrepresented.push(format!(
+                "cost basis of holdings bought before {start_date} is not kept in the opening balances"
+            ));
+        }
+    }
+    transactions.extend(after);
+    book.transactions = transactions;
+    Ok(before.len())
+}
+
+pub(crate) fn simple_posting(account: &str, amount: BookAmount) -> BookPosting {
+    BookPosting {
+        account: account.to_string(),
+        status: None,
+        amount,
+        cost: None,
+        assertion: None,
+        comment: None,
+        tags: Vec::new(),
+    }
+}
+
+/// Render `book` as hledger journal text: account directives, prices, then
+/// transactions in date order.
+pub fn render_journal(book: &Book) -> String {
+    let mut out = String::new();
+    for account in &book.accounts {
+        out.push_str(&format!("account {}", account_name(&account.name)));
+        let mut tags = Vec::new();
+        if let Some(kind) = account.kind {
+            tags.push(format!("type: {}", kind.code()));
+        }
+        if let Some(opened) = &account.opened {
+            tags.push(format!("opened: {opened}"));
+        }
+        if let Some(closed) = &account.closed {
+            tags.push(format!("closed: {closed}"));
+        }
+        if let Some(description) = &account.description {
+            tags.push(format!("description: {}", single_line(description)));
+        }
+        if !tags.is_empty() {
+            out.push_str(&format!("  ; {}", tags.join(", ")));
+        }
+        out.push('\n');
+    }
+
+    if !book.prices.is_empty() {
+        out.push('\n');
+        for price in &book.prices {
+            out.push_str(&format!(
+                "P {} {} {}\n",
+                price.date,
+                commodity_symbol(&price.commodity),
+                format_amount(&price.price)
+            ));
+        }
+    }
+
+    let mut transactions: Vec<&BookTransaction> = book.transactions.iter().collect();
+    transactions.sort_by(|a, b| a.date.cmp(&b.date));
+    for txn in transactions {
+        out.push('\n');
+        out.push_str(&render_transaction(txn));
+    }
+    out
+}
+
+fn render_transaction(txn: &BookTransaction) -> String {
+    let mut header = txn.date.clone();
+    if let Some(status) = txn.status {
+        header.push_str(&format!(" {status}"));
+    }
+    if let Some(code) = txn.code.as_deref().filter(|c| !c.trim().is_empty()) {
+        header.push_str(&format!(" ({})", single_line(code).replace(')', "")));
+    }
+    let description = single_line(&txn.description);
+    if !description.is_empty() {
+        header.push_str(&format!(" {description}"));
+    }
+    let comment = comment_text(txn.comment.as_deref(), &txn.tags);
+    if !comment.is_empty() {
+        header.push_str(&format!("  ; {comment}"));
+    }
+    let mut lines = vec![header];
+    for posting in &txn.postings {
+        let mut line = String::from("    ");
+        if let Some(status) = posting.status {
+            line.push_str(&format!("{status} "));
+        }
+        line.push_str(&account_name(&posting.account));
+        line.push_str(&format!("  {}", format_amount(&posting.amount)));
+        match &posting.cost {
+            Some(PostingCost::Unit(unit)) => line.push_str(&format!(" @ {}", format_amount(unit))),
+            Some(PostingCost::Total(total)) => line.push_str(&format!(
+                " @@ {}",
+                format_amount(&BookAmount::new(total.quantity.abs(), &total.commodity))
+            )),
+            None => {}
+        }
+        if let Some(assertion) = &posting.assertion {
+            line.push_str(&format!(" = {}", format_amount(assertion)));
+        }
+        let comment = comment_text(posting.comment.as_deref(), &posting.tags);
+        if !comment.is_empty() {
+            line.push_str(&format!("  ; {comment}"));
+        }
+        lines.push(line);
+    }
+    let mut rendered = lines.join("\n");
+    rendered.push('\n');
+    rendered
+}
+
+fn comment_text(comment: Option<&str>, tags: &[(String, String)]) -> String {
+    let mut parts: Vec<String> = Vec::new();
+    if let Some(comment) = comment.map(single_line).filter(|c| !c.is_empty()) {
+        parts.push(comment);
+    }
+    for (key, value) in tags {
+        let value = single_line(value).replace(',', ";");
+        if value.is_empty() {
+            parts.push(format!("{key}:"));
+        } else {
+            parts.push(format!("{key}: {value}"));
+        }
+    }
+    parts.join(", ")
+}
+
+fn format_amount(amount: &BookAmount) -> String {
+    format!(
+        "{} {}",
+        amount.quantity,
+        commodity_symbol(&amount.commodity)
+    )
+}
+
+/// Quote commodity symbols that hledger would not read bare.
+fn commodity_symbol(commodity: &str) -> String {
+    if !commodity.is_empty() && commodity.chars().all(char::is_alphabetic) {
+        commodity.to_string()
+    } else {
+        format!("\"{}\"", commodity.replace('"', ""))
+    }
+}
+
+/// hledger ends an account name at two spaces, so collapse runs of spaces.
+fn account_name(name: &str) -> String {
+    name.split(' ')
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Comments and descriptions are one line, and `;` would start a comment.
+fn single_line(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .replace(';', ",")
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn amount(quantity: &str, commodity: &str) -> BookAmount {
+        BookAmount::new(Decimal::parse(quantity).unwrap(), commodity)
+    }
+
+    #[test]
+    fn decimals_parse_add_and_convert_ratios() {
+        assert_eq!(Decimal::parse("-1,234.50").unwrap().to_string(), "-1234.50");
+        assert_eq!(
+            (Decimal::parse("10.5").unwrap() + Decimal::parse("-0.25").unwrap()).to_string(),
+            "10.25"
+        );
+        assert_eq!(
+            Decimal::from_ratio(-12345, 100).unwrap().to_string(),
+            "-123.45"
+        );
+        assert_eq!(Decimal::from_ratio(1, 8).unwrap().to_string(), "0.125");
+        assert!(Decimal::from_ratio(1, 3).is_none());
+        assert_eq!(Decimal::parse(".5").unwrap().to_string(), "0.5");
+        assert!(Decimal::parse("1e5").is_none());
+        let third = |n: &str| {
+            Decimal::parse(n)
+                .unwrap()
+                .div_rounded(Decimal::parse("3").unwrap(), 2)
+        };
+        assert_eq!(third("10").unwrap().to_string(), "3.33");
+        assert_eq!(third("-2").unwrap().to_string(), "-0.67");
+        assert_eq!(
+            Decimal::parse("1.50")
+                .unwrap()
+                .cmp_value(Decimal::parse("1.5").unwrap()),
+            std::cmp::Ordering::Equal
+        );
+    }
+
+    #[test]
+    fn summarizes_history_into_opening_balances_and_renders_journal() {
+        let mut book = Book {
+            accounts: vec![
+                BookAccount {
+                    name: "Assets:Checking".to_string(),
+                    kind: Some(AccountKind::Cash),
+                    description: None,
+                    opened: Some("2020-01-01".to_string()),
+                    closed: None,
+                },
+                BookAccount {
+                    name: "Expenses:Dining  Out".to_string(),
+                    kind: Some(AccountKind::Expense),
+                    description: None,
+                    opened: None,
+                    closed: None,
+                },
+            ],
+            prices: vec![BookPrice {
+                date: "2024-01-31".to_string(),
+                commodity: "VTI2".to_string(),
+                price: amount("250.10", "USD"),
+            }],
+            transactions: vec![
+                BookTransaction {
+                    date: "2024-02-01".to_string(),
+                    status: Some('*'),
+                    code: Some("1002".to_string()),
+                    description: "Cafe; downtown".to_string(),
+                    postings: vec![
+                        simple_posting("Expenses:Dining  Out", amount("4.50", "USD")),
+                        simple_posting("Assets:Checking", amount("-4.50", "USD")),
+                    ],
+                    ..BookTransaction::default()
+                },
+                BookTransaction {
+                    date: "2023-12-01".to_string(),
+                    description: "Paycheck".to_string(),
+                    postings: vec![
+                        simple_posting("Assets:Checking", amount("1000", "USD")),
+                        simple_posting("Income:Salary", amount("-1000", "USD")),
+                    ],
+                    ..BookTransaction::default()
+                },
+                BookTransaction {
+                    date: "2023-12-02".to_string(),
+                    description: "Dinner".to_string(),
+                    postings: vec![
+                        simple_posting("Expenses:Dining  Out", amount("40", "USD")),
+                        simple_posting("Assets:Checking", amount("-40", "USD")),
+                    ],
+                    ..BookTransaction::default()
+                },
+            ],
+            unrepresented: Vec::new(),
+        };
+
+        assert_eq!(summarize_before(&mut book, "2024-01-01").unwrap(), 2);
+        let opening = &book.transactions[0];
+        assert_eq!(opening.date, "2024-01-01");
+        assert_eq!(
+            opening.postings,
+            vec![
+                simple_posting("Assets:Checking", amount("960", "USD")),
+                simple_posting(OPENING_BALANCES_ACCOUNT, amount("-960", "USD")),
+            ]
+        );
+
+        let journal = render_journal(&book);
+        assert_eq!(
+            journal,
+            "account Assets:Checking  ; type: C, opened: 2020-01-01\n\
+account Expenses:Dining Out  ; type: X\n\
+account Equity:Opening Balances  ; type: E\n\
+\n\
+P 2024-01-31 \"VTI2\" 250.10 USD\n\
+\n\
+2024-01-01 * Opening balances\n    Assets:Checking  960 USD\n    Equity:Opening Balances  -960 USD\n\
+\n\
+2024-02-01 * (1002) Cafe, downtown\n    Expenses:Dining Out  4.50 USD\n    Assets:Checking  -4.50 USD\n"
+        );
+        assert!(summarize_before(&mut book, "2024/01/01").is_err());
+    }
+}
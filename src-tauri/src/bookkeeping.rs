@@ -9,8 +9,9 @@ const RECONCILIATION_SESSIONS_DIR: &str = "reconciliation-sessions";
 const LINKS_DIR: &str = "links";
 const PERIOD_CLOSES_DIR: &str = "period-closes";
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct ReconciliationSession {
     pub id: String,
     pub gl_account: String,
@@ -26,16 +27,18 @@ pub struct ReconciliationSession {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "lowercase")]
+#[ts(export_to = "index.ts")]
 pub enum ReconciliationSessionStatus {
     Draft,
     Finalized,
     Reopened,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct NewReconciliationSessionInput {
     pub gl_account: String,
     pub statement_start_date: Option<String>,
@@ -47,8 +50,9 @@ pub struct NewReconciliationSessionInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct UpdateReconciliationSessionInput {
     pub id: String,
     pub gl_account: String,
@@ -61,8 +65,9 @@ pub struct UpdateReconciliationSessionInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct LinkRecord {
     pub id: String,
     pub kind: LinkKind,
@@ -74,29 +79,37 @@ pub struct LinkRecord {
     pub updated_at: String,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
 pub enum LinkKind {
     EvidenceLink,
     SettlementLink,
     SourceLink,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct TypedRef {
     pub kind: TypedRefKind,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub locator: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub entry_id: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub login_name: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub label: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub filename: Option<String>,
 }
 
@@ -109,16 +122,18 @@ impl TypedRef {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
 pub enum TypedRefKind {
     GlTxn,
     LoginEntry,
     Document,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct NewLinkRecordInput {
     pub kind: LinkKind,
     pub left_ref: TypedRef,
@@ -127,8 +142,9 @@ pub struct NewLinkRecordInput {
     pub notes: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct PeriodClose {
     pub period_id: String,
     pub status: PeriodCloseStatus,
@@ -139,16 +155,18 @@ pub struct PeriodClose {
     pub adjustment_txn_ids: Vec<String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
 pub enum PeriodCloseStatus {
     Draft,
     SoftClosed,
     Reopened,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct UpsertPeriodCloseInput {
     pub period_id: String,
     pub status: PeriodCloseStatus,
@@ -574,6 +592,34 @@ fn reconciliation_sessions_dir(ledger_dir: &Path) -> PathBuf {
     bookkeeping_dir(ledger_dir).join(RECONCILIATION_SESSIONS_DIR)
 }
 
+/// Point login-entry link refs at renamed entry ids, given per login
+/// account locator. Returns the rewritten link files without writing them.
+pub(crate) fn rename_login_entry_refs(
+    ledger_dir: &Path,
+    renames: &HashMap<String, HashMap<String, String>>,
+) -> io::Result<Vec<(PathBuf, Vec<u8>)>> {
+    let mut staged = Vec::new();
+    for mut link in list_links(ledger_dir)? {
+        let mut touched = false;
+        for typed_ref in [&mut link.left_ref, &mut link.right_ref] {
+            if !matches!(typed_ref.kind, TypedRefKind::LoginEntry) {
+                continue;
+            }
+            let Some(renames) = typed_ref.locator.as_deref().and_then(|l| renames.get(l)) else {
+                continue;
+            };
+            if let Some(new_id) = typed_ref.entry_id.as_ref().and_then(|id| renames.get(id)) {
+                typed_ref.entry_id = Some(new_id.clone());
+                touched = true;
+            }
+        }
+        if touched {
+            staged.push((link_path(ledger_dir, &link.id), json_bytes(&link)?));
+        }
+    }
+    Ok(staged)
+}
+
 fn links_dir(ledger_dir: &Path) -> PathBuf {
     bookkeeping_dir(ledger_dir).join(LINKS_DIR)
 }
@@ -594,7 +640,7 @@ fn period_close_path(ledger_dir: &Path, period_id: &str) -> PathBuf {
     period_closes_dir(ledger_dir).join(format!("{period_id}.json"))
 }
 
-fn require_non_empty(field_name: &str, value: String) -> io::Result<String> {
+pub(crate) fn require_non_empty(field_name: &str, value: String) -> io::Result<String> {
     let trimmed = value.trim();
     if trimmed.is_empty() {
         return Err(io::Error::new(
@@ -605,7 +651,7 @@ fn require_non_empty(field_name: &str, value: String) -> io::Result<String> {
     Ok(trimmed.to_string())
 }
 
-fn normalize_optional_string(value: Option<String>) -> Option<String> {
+pub(crate) fn normalize_optional_string(value: Option<String>) -> Option<String> {
     value.and_then(|value| {
         let trimmed = value.trim();
         if trimmed.is_empty() {
@@ -616,7 +662,7 @@ fn normalize_optional_string(value: Option<String>) -> Option<String> {
     })
 }
 
-fn require_date(field_name: &str, value: String) -> io::Result<String> {
+pub(crate) fn require_date(field_name: &str, value: String) -> io::Result<String> {
     let value = require_non_empty(field_name, value)?;
     chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d").map_err(|err| {
         io::Error::new(
@@ -953,37 +999,18 @@ fn apply_bookkeeping_updates(ledger_dir: &Path, updates: &[BookkeepingUpdate]) -
     Ok(())
 }
 
-fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
-    let parent = path.parent().ok_or_else(|| {
-        io::Error::new(
-            io::ErrorKind::InvalidInput,
-            format!("path has no parent: {}", path.display()),
-        )
-    })?;
-    fs::create_dir_all(parent)?;
-    let nanos = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .map_err(io::Error::other)?
-        .as_nanos();
-    let temp_path = parent.join(format!(
-        ".{}.tmp-{}-{nanos}",
-        path.file_name()
-            .and_then(|name| name.to_str())
-            .unwrap_or("bookkeeping.json"),
-        std::process::id()
-    ));
-
-    {
-        let mut file = OpenOptions::new()
-            .create_new(true)
-            .write(true)
-            .open(&temp_path)?;
-        serde_json::to_writer_pretty(&mut file, value).map_err(io::Error::other)?;
-        file.write_all(b"\n")?;
-    }
+/// `value` as [`write_json`] writes it.
+pub(crate) fn json_bytes<T: Serialize>(value: &T) -> io::Result<Vec<u8>> {
+    let mut bytes = serde_json::to_vec_pretty(value).map_err(io::Error::other)?;
+    bytes.push(b'\n');
+    Ok(bytes)
+}
 
-    fs::rename(temp_path, path)?;
-    Ok(())
+pub(crate) fn write_json<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
+    crate::atomic_file::write_atomic_with(path, |file| {
+        serde_json::to_writer_pretty(&mut *file, value).map_err(io::Error::other)?;
+        file.write_all(b"\n")
+    })
 }
 
 fn read_required_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<T> {
@@ -995,7 +1022,9 @@ fn read_required_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<T
     })
 }
 
-fn read_optional_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<Option<T>> {
+pub(crate) fn read_optional_json<T: for<'de> Deserialize<'de>>(
+    path: &Path,
+) -> io::Result<Option<T>> {
     if !path.exists() {
         return Ok(None);
     }
@@ -1004,7 +1033,9 @@ fn read_optional_json<T: for<'de> Deserialize<'de>>(path: &Path) -> io::Result<O
     Ok(Some(value))
 }
 
-fn read_json_objects_from_dir<T: for<'de> Deserialize<'de>>(dir: &Path) -> io::Result<Vec<T>> {
+pub(crate) fn read_json_objects_from_dir<T: for<'de> Deserialize<'de>>(
+    dir: &Path,
+) -> io::Result<Vec<T>> {
     if !dir.exists() {
         return Ok(Vec::new());
     }
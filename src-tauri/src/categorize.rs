@@ -6,9 +6,17 @@
 //!
 //! Also detects amount/status drift for already-posted entries and performs
 //! rule-based transfer auto-matching across login accounts.
+//!
+//! Each suggestion carries an explanation: the categorization rule behind
+//! it, how often the entry's payee went to the suggested account before, and
+//! the classifier's confidence. Accepting or correcting a suggestion with
+//! `record_category_feedback` saves the entry's tokens and chosen account to
+//! `category-feedback.json`, where they become training examples like rules.
 
 use std::collections::HashMap;
-use std::path::Path;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 use crate::account_journal;
 use crate::hledger;
@@ -21,12 +29,15 @@ use crate::transfer_detector;
 // ---------------------------------------------------------------------------
 
 /// Per-entry result from `suggest_categories`.
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct CategoryResult {
     /// Suggested counterpart account (only for unposted entries without a
     /// unique transfer match, and only when confidence ≥ 0.5).
     pub suggested: Option<String>,
+    /// Class probability of `suggested`, between 0.5 and 1.
+    pub confidence: Option<f64>,
     /// `true` if the entry's posting amount differs from the GL transaction amount.
     pub amount_changed: bool,
     /// `true` if the entry's status differs from the GL transaction status.
@@ -34,11 +45,77 @@ pub struct CategoryResult {
     /// Auto-detected transfer match (only set when a unique opposite-amount
     /// unposted entry exists within ±3 days across other login accounts).
     pub transfer_match: Option<TransferMatch>,
+    /// Principal/interest/escrow split when the entry is a payment on a
+    /// configured loan (unposted entries without a transfer match only).
+    pub loan_split: Option<crate::loans::LoanSplitSuggestion>,
+    /// Gross-to-net split when the entry is a deposit matching a pay stub's
+    /// net pay (unposted entries without a transfer match or loan split).
+    pub paycheck_split: Option<crate::paychecks::PaycheckSplitSuggestion>,
+    /// Open invoice this deposit appears to pay (unposted entries without a
+    /// transfer match, loan split, or paycheck split).
+    pub invoice_match: Option<crate::invoices::InvoiceMatch>,
+    /// Why `suggested` was chosen; set whenever it is.
+    pub explanation: Option<CategoryExplanation>,
+}
+
+/// The evidence behind a category suggestion.
+#[derive(Debug, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct CategoryExplanation {
+    /// A categorization rule whose keyword is in the entry and that points at
+    /// the suggested account.
+    pub matched_rule: Option<CategorizationRule>,
+    /// Posted entries with the same payee, if there are any.
+    pub payee_history: Option<PayeeHistory>,
+    /// Class probability of the suggestion, combining both classifiers.
+    pub classifier_confidence: f64,
+    /// Weight of this login account's own history against the whole
+    /// ledger's, from 0 until it has enough posted entries, up to 1.
+    pub account_weight: f64,
+    /// Times the suggested account was chosen for this payee through
+    /// `record_category_feedback`.
+    pub feedback_count: usize,
+    /// The entry's merchant category code and the category it hints at.
+    pub mcc: Option<crate::mcc::MccHint>,
+}
+
+/// How a payee's earlier entries were categorized.
+#[derive(Debug, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PayeeHistory {
+    /// Normalized payee key, e.g. `TRADER JOE S`.
+    pub payee: String,
+    /// Posted entries from this payee that went to the suggested account.
+    pub suggested_count: usize,
+    /// All posted entries from this payee.
+    pub total_count: usize,
+}
+
+/// The account chosen for an entry after seeing its suggestion, kept as a
+/// training example.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct CategoryFeedback {
+    pub login_name: String,
+    pub label: String,
+    pub entry_id: String,
+    /// Normalized payee key of the entry's description.
+    pub payee: String,
+    pub tokens: Vec<String>,
+    /// What was suggested, if anything; equal to `account` when the
+    /// suggestion was accepted.
+    pub suggested: Option<String>,
+    pub account: String,
+    pub recorded_at: String,
 }
 
 /// A uniquely matched transfer entry from another login account.
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct TransferMatch {
     pub account_locator: String,
     pub entry_id: String,
@@ -46,8 +123,9 @@ pub struct TransferMatch {
 }
 
 /// Per-GL-transaction result from `suggest_gl_categories`.
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct GlCategoryResult {
     /// ML-suggested replacement account for `Expenses:Unknown`, or `None` if
     /// confidence < 0.5 or a transfer match was found.
@@ -58,8 +136,9 @@ pub struct GlCategoryResult {
 }
 
 /// A matching `Expenses:Unknown` GL transaction that forms a transfer pair.
-#[derive(Debug, serde::Serialize)]
+#[derive(Debug, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct GlTransferMatch {
     pub txn_id: String,
     pub description: String,
@@ -67,10 +146,25 @@ pub struct GlTransferMatch {
     pub matched_amount: String,
 }
 
+/// A keyword that suggests a counterpart account, from
+/// `categorization-rules.json`. Rules are extra training examples, so
+/// posting history can still outvote them.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct CategorizationRule {
+    pub keyword: String,
+    pub account: String,
+}
+
 // ---------------------------------------------------------------------------
 // Constants
 // ---------------------------------------------------------------------------
 
+const CATEGORIZATION_RULES_FILE: &str = "categorization-rules.json";
+
+const CATEGORY_FEEDBACK_FILE: &str = "category-feedback.json";
+
 /// Class probability threshold below which the classifier abstains.
 const CONFIDENCE_THRESHOLD: f64 = 0.5;
 
@@ -114,8 +208,14 @@ pub fn suggest_categories(
 
     // Build MNB training data from user history.
     let source_locator = format!("logins/{login_name}/accounts/{label}");
-    let (global_examples, account_examples) =
-        build_training_examples(ledger_dir, &gl_txns, &source_locator)?;
+    let rules = list_categorization_rules(ledger_dir)?;
+    let feedback = list_category_feedback(ledger_dir)?;
+    let history = build_training_examples(ledger_dir, &gl_txns, &source_locator)?;
+    let mut global_examples = history.global;
+    let mut account_examples = history.account_specific;
+    global_examples.extend(rule_examples(&rules));
+    global_examples.extend(feedback_examples(&feedback, None));
+    account_examples.extend(feedback_examples(&feedback, Some((login_name, label))));
 
     // Fit global and per-account classifiers.
     let global_model = MnbModel::fit(&global_examples, 1.0);
@@ -125,10 +225,14 @@ pub fn suggest_categories(
     // Collect unposted transfer candidates from other login accounts.
     let transfer_candidates = collect_transfer_candidates(ledger_dir, login_name, label)?;
 
+    let loans = crate::loans::list_loans(ledger_dir)?;
+    let paystubs = crate::paychecks::list_paystubs(ledger_dir)?;
+    let open_invoices = crate::invoices::open_invoices(ledger_dir, &gl_txns)?;
+
     // Process each entry.
     let mut results = HashMap::new();
     for entry in &entries {
-        let result = process_entry(
+        let mut result = process_entry(
             entry,
             &gl_by_id,
             &source_locator,
@@ -137,6 +241,27 @@ pub fn suggest_categories(
             account_sample_count,
             &transfer_candidates,
         );
+        if let (Some(suggested), Some(confidence)) = (&result.suggested, result.confidence) {
+            result.explanation = Some(explain_suggestion(
+                entry,
+                suggested,
+                confidence,
+                account_sample_count,
+                &rules,
+                &history.payee_accounts,
+                &feedback,
+            ));
+        }
+        if entry.posted.is_none() && result.transfer_match.is_none() {
+            result.loan_split = crate::loans::suggest_loan_split(&loans, entry);
+            if result.loan_split.is_none() {
+                result.paycheck_split = crate::paychecks::suggest_paycheck_split(&paystubs, entry);
+            }
+            if result.loan_split.is_none() && result.paycheck_split.is_none() {
+                result.invoice_match =
+                    crate::invoices::suggest_invoice_match(&open_invoices, entry);
+            }
+        }
         results.insert(entry.id.clone(), result);
     }
 
@@ -171,7 +296,12 @@ pub fn suggest_gl_categories(
     }
 
     // Build ML model from GL transactions that already have real categories.
-    let training_examples = build_gl_training_examples(&gl_txns);
+    let mut training_examples = build_gl_training_examples(&gl_txns);
+    training_examples.extend(rule_examples(&list_categorization_rules(ledger_dir)?));
+    training_examples.extend(feedback_examples(
+        &list_category_feedback(ledger_dir)?,
+        None,
+    ));
     let global_model = MnbModel::fit(&training_examples, 1.0);
 
     // Build transfer candidates from the Expenses:Unknown set.
@@ -362,19 +492,29 @@ fn find_gl_transfer_match(
 /// A training example: `(tokens, counterpart_account)`.
 type TrainingExample = (Vec<String>, String);
 
+/// Training data from posting history.
+struct PostingHistory {
+    /// Seed vocabulary plus every posted entry.
+    global: Vec<TrainingExample>,
+    /// Posted entries of the caller's login account only.
+    account_specific: Vec<TrainingExample>,
+    /// Payee key → counterpart account → posted entries.
+    payee_accounts: HashMap<String, HashMap<String, usize>>,
+}
+
 /// Build `(tokens, counterpart_account)` training examples from GL history.
 ///
-/// Returns `(global_examples, per_account_examples)`.  Both start with the
-/// compile-time seed vocabulary; the per-account set is then filtered to only
-/// examples whose source locator matches the caller's login account.
+/// The global set starts with the compile-time seed vocabulary; the
+/// per-account set holds only examples whose source locator matches the
+/// caller's login account.
 fn build_training_examples(
     ledger_dir: &Path,
     gl_txns: &[hledger::Transaction],
     source_locator: &str,
-) -> Result<(Vec<TrainingExample>, Vec<TrainingExample>), Box<dyn std::error::Error + Send + Sync>>
-{
+) -> Result<PostingHistory, Box<dyn std::error::Error + Send + Sync>> {
     let mut global = seed_examples();
     let mut account_specific: Vec<TrainingExample> = Vec::new();
+    let mut payee_accounts: HashMap<String, HashMap<String, usize>> = HashMap::new();
 
     // Pre-load all login account journals into (locator, entry_id) → AccountEntry.
     let mut entry_map: HashMap<(String, String), account_journal::AccountEntry> = HashMap::new();
@@ -441,6 +581,11 @@ fn build_training_examples(
             continue;
         }
 
+        *payee_accounts
+            .entry(crate::alerts::payee_key(&entry.description))
+            .or_default()
+            .entry(counterpart_account.clone())
+            .or_default() += 1;
         let tokens = tokenize_entry(entry);
         let example = (tokens, counterpart_account);
         if locator == source_locator {
@@ -449,7 +594,120 @@ fn build_training_examples(
         global.push(example);
     }
 
-    Ok((global, account_specific))
+    Ok(PostingHistory {
+        global,
+        account_specific,
+        payee_accounts,
+    })
+}
+
+pub fn categorization_rules_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(CATEGORIZATION_RULES_FILE)
+}
+
+pub fn list_categorization_rules(ledger_dir: &Path) -> io::Result<Vec<CategorizationRule>> {
+    Ok(
+        crate::bookkeeping::read_optional_json(&categorization_rules_path(ledger_dir))?
+            .unwrap_or_default(),
+    )
+}
+
+pub fn category_feedback_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(CATEGORY_FEEDBACK_FILE)
+}
+
+pub fn list_category_feedback(ledger_dir: &Path) -> io::Result<Vec<CategoryFeedback>> {
+    Ok(
+        crate::bookkeeping::read_optional_json(&category_feedback_path(ledger_dir))?
+            .unwrap_or_default(),
+    )
+}
+
+/// Point category feedback at renamed entry ids, given per login account
+/// locator. Returns how many records changed and, when any did, the
+/// rewritten feedback file without writing it.
+pub(crate) fn rename_feedback_entry_ids(
+    ledger_dir: &Path,
+    renames: &HashMap<String, HashMap<String, String>>,
+) -> io::Result<(usize, Option<(PathBuf, Vec<u8>)>)> {
+    let mut feedback = list_category_feedback(ledger_dir)?;
+    let mut changed = 0;
+    for record in &mut feedback {
+        let locator = format!("logins/{}/accounts/{}", record.login_name, record.label);
+        if let Some(new_id) = renames.get(&locator).and_then(|r| r.get(&record.entry_id)) {
+            record.entry_id = new_id.clone();
+            changed += 1;
+        }
+    }
+    if changed == 0 {
+        return Ok((0, None));
+    }
+    let bytes = crate::bookkeeping::json_bytes(&feedback)?;
+    Ok((changed, Some((category_feedback_path(ledger_dir), bytes))))
+}
+
+/// Save the account chosen for an unposted entry after seeing `suggested`,
+/// replacing earlier feedback on the same entry. Later suggestions train on
+/// it.
+pub fn record_category_feedback(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    entry_id: &str,
+    suggested: Option<String>,
+    account: &str,
+) -> Result<CategoryFeedback, Box<dyn std::error::Error + Send + Sync>> {
+    let account = account.trim();
+    if account.is_empty() {
+        return Err("account is required".into());
+    }
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+    let entry = entries
+        .iter()
+        .find(|entry| entry.id == entry_id)
+        .ok_or_else(|| format!("entry {entry_id} not found in {login_name}/{label}"))?;
+    let record = CategoryFeedback {
+        login_name: login_name.to_string(),
+        label: label.to_string(),
+        entry_id: entry_id.to_string(),
+        payee: crate::alerts::payee_key(&entry.description),
+        tokens: tokenize_entry(entry),
+        suggested: suggested.filter(|s| !s.trim().is_empty()),
+        account: account.to_string(),
+        recorded_at: chrono::Utc::now().to_rfc3339(),
+    };
+
+    let mut feedback = list_category_feedback(ledger_dir)?;
+    feedback
+        .retain(|f| !(f.login_name == login_name && f.label == label && f.entry_id == entry_id));
+    feedback.push(record.clone());
+    crate::bookkeeping::write_json(&category_feedback_path(ledger_dir), &feedback)?;
+    Ok(record)
+}
+
+/// Feedback as training examples, optionally only one login account's.
+fn feedback_examples(
+    feedback: &[CategoryFeedback],
+    login_account: Option<(&str, &str)>,
+) -> Vec<TrainingExample> {
+    feedback
+        .iter()
+        .filter(|f| match login_account {
+            Some((login, label)) => f.login_name == login && f.label == label,
+            None => true,
+        })
+        .filter(|f| !f.tokens.is_empty())
+        .map(|f| (f.tokens.clone(), f.account.clone()))
+        .collect()
+}
+
+fn rule_examples(rules: &[CategorizationRule]) -> Vec<TrainingExample> {
+    rules
+        .iter()
+        .map(|rule| (tokenize_text(&rule.keyword), rule.account.clone()))
+        .filter(|(tokens, account)| !tokens.is_empty() && !account.is_empty())
+        .collect()
 }
 
 /// Compile-time seed vocabulary: common merchant keywords and bank category tags.
@@ -490,9 +748,18 @@ fn seed_examples() -> Vec<(Vec<String>, String)> {
         ("PAYROLL", "Income:Salary"),
         ("DEPOSIT", "Income:Salary"),
     ];
-    raw.iter()
+    let mut seeds: Vec<_> = raw
+        .iter()
         .map(|(token, account)| (vec![token.to_string()], account.to_string()))
-        .collect()
+        .collect();
+    // Merchant category code hints
+    seeds.extend(crate::mcc::categories().into_iter().map(|category| {
+        (
+            vec![format!("mccCategory:{category}")],
+            format!("Expenses:{category}"),
+        )
+    }));
+    seeds
 }
 
 // ---------------------------------------------------------------------------
@@ -500,7 +767,8 @@ fn seed_examples() -> Vec<(Vec<String>, String)> {
 // ---------------------------------------------------------------------------
 
 /// Tokenise an account journal entry into uppercase alphabetic words plus
-/// `"key:value"` strings for each entry tag.
+/// `"key:value"` strings for each entry tag, and `"mccCategory:<category>"`
+/// when its merchant category code is in the bundled table.
 pub(crate) fn tokenize_entry(entry: &account_journal::AccountEntry) -> Vec<String> {
     let mut tokens = tokenize_text(&entry.description);
     for (k, v) in &entry.tags {
@@ -510,6 +778,9 @@ pub(crate) fn tokenize_entry(entry: &account_journal::AccountEntry) -> Vec<Strin
             tokens.push(format!("{k}:{v}"));
         }
     }
+    if let Some(hint) = crate::mcc::entry_hint(&entry.tags) {
+        tokens.push(format!("mccCategory:{}", hint.category));
+    }
     tokens
 }
 
@@ -532,6 +803,8 @@ struct TransferCandidate {
     date: String,
     amount_f64: f64,
     commodity: String,
+    /// Institution metadata when the candidate is in a card account.
+    card: Option<Rc<login_config::InstitutionInfo>>,
 }
 
 fn collect_transfer_candidates(
@@ -543,10 +816,14 @@ fn collect_transfer_candidates(
     let logins = login_config::list_logins(ledger_dir)?;
     for login in &logins {
         let cfg = login_config::read_login_config(ledger_dir, login);
-        for lbl in cfg.accounts.keys() {
+        let institution = cfg.institution.clone().map(Rc::new);
+        for (lbl, acct_config) in &cfg.accounts {
             if login == exclude_login && lbl == exclude_label {
                 continue;
             }
+            let card = institution
+                .clone()
+                .filter(|_| acct_config.account_type == Some(login_config::AccountType::Card));
             let jpath = account_journal::login_account_journal_path(ledger_dir, login, lbl);
             let locator = format!("logins/{login}/accounts/{lbl}");
             if let Ok(entries) = account_journal::read_journal_at_path(&jpath) {
@@ -567,6 +844,7 @@ fn collect_transfer_candidates(
                         date: e.date.clone(),
                         amount_f64,
                         commodity: amt.commodity.clone(),
+                        card: card.clone(),
                     });
                 }
             }
@@ -579,6 +857,7 @@ fn collect_transfer_candidates(
 ///
 /// Returns `Some(TransferMatch)` only when EXACTLY ONE candidate has the
 /// opposite amount (sum ≈ 0), same commodity, and a date within ±3 days.
+/// Ties between card accounts are broken by `narrow_card_payment_matches`.
 /// Returns `None` when there are 0 or 2+ matches.
 fn find_transfer_match(
     entry: &account_journal::AccountEntry,
@@ -603,6 +882,11 @@ fn find_transfer_match(
                     .unwrap_or(false)
         })
         .collect();
+    let matches = if matches.len() > 1 {
+        narrow_card_payment_matches(entry, entry_date, matches)
+    } else {
+        matches
+    };
 
     if matches.len() == 1 {
         let m = matches[0];
@@ -616,6 +900,53 @@ fn find_transfer_match(
     }
 }
 
+/// Prefer card candidates whose last four digits appear in the payment's
+/// description, then candidates whose statement did not close between the
+/// two dates: a payment and its credit normally land in the same cycle.
+fn narrow_card_payment_matches<'a>(
+    entry: &account_journal::AccountEntry,
+    entry_date: chrono::NaiveDate,
+    matches: Vec<&'a TransferCandidate>,
+) -> Vec<&'a TransferCandidate> {
+    if matches.iter().all(|c| c.card.is_none()) {
+        return matches;
+    }
+    let by_last4: Vec<&TransferCandidate> = matches
+        .iter()
+        .copied()
+        .filter(|c| {
+            c.card.as_ref().is_some_and(|card| {
+                card.card_last4
+                    .iter()
+                    .any(|digits| entry.description.contains(digits.as_str()))
+            })
+        })
+        .collect();
+    let matches = if by_last4.is_empty() {
+        matches
+    } else {
+        by_last4
+    };
+    if matches.len() < 2 {
+        return matches;
+    }
+    let same_cycle: Vec<&TransferCandidate> = matches
+        .iter()
+        .copied()
+        .filter(|c| {
+            let (Some(card), Some(date)) = (&c.card, parse_date(&c.date)) else {
+                return true;
+            };
+            !card.statement_closes_between(entry_date.min(date), entry_date.max(date))
+        })
+        .collect();
+    if same_cycle.is_empty() {
+        matches
+    } else {
+        same_cycle
+    }
+}
+
 fn parse_date(s: &str) -> Option<chrono::NaiveDate> {
     chrono::NaiveDate::parse_from_str(s.trim(), "%Y-%m-%d").ok()
 }
@@ -693,7 +1024,7 @@ fn process_entry(
     };
 
     // --- Transfer detection + category suggestion (unposted entries only) ---
-    let (transfer_match, suggested) = if entry.posted.is_none() {
+    let (transfer_match, suggestion) = if entry.posted.is_none() {
         let is_probable_transfer = transfer_detector::is_probable_transfer(&entry.description)
             || entry
                 .tags
@@ -706,22 +1037,66 @@ fn process_entry(
             None
         };
 
-        let suggested = if transfer_match.is_none() {
+        let suggestion = if transfer_match.is_none() {
             suggest_category(entry, global_model, account_model, account_sample_count)
         } else {
             None
         };
 
-        (transfer_match, suggested)
+        (transfer_match, suggestion)
     } else {
         (None, None)
     };
+    let (suggested, confidence) = suggestion.unzip();
 
     CategoryResult {
         suggested,
+        confidence,
         amount_changed,
         status_changed,
         transfer_match,
+        loan_split: None,
+        paycheck_split: None,
+        invoice_match: None,
+        explanation: None,
+    }
+}
+
+fn explain_suggestion(
+    entry: &account_journal::AccountEntry,
+    suggested: &str,
+    confidence: f64,
+    account_sample_count: usize,
+    rules: &[CategorizationRule],
+    payee_accounts: &HashMap<String, HashMap<String, usize>>,
+    feedback: &[CategoryFeedback],
+) -> CategoryExplanation {
+    let tokens = tokenize_entry(entry);
+    let matched_rule = rules
+        .iter()
+        .filter(|rule| rule.account == suggested)
+        .find(|rule| {
+            let keywords = tokenize_text(&rule.keyword);
+            !keywords.is_empty() && keywords.iter().all(|k| tokens.contains(k))
+        })
+        .cloned();
+    let payee = crate::alerts::payee_key(&entry.description);
+    let payee_history = payee_accounts.get(&payee).map(|accounts| PayeeHistory {
+        suggested_count: accounts.get(suggested).copied().unwrap_or(0),
+        total_count: accounts.values().sum(),
+        payee: payee.clone(),
+    });
+    let feedback_count = feedback
+        .iter()
+        .filter(|f| f.payee == payee && f.account == suggested)
+        .count();
+    CategoryExplanation {
+        matched_rule,
+        payee_history,
+        classifier_confidence: confidence,
+        account_weight: (account_sample_count as f64 / ACCOUNT_WARMUP_SIZE).min(1.0),
+        feedback_count,
+        mcc: crate::mcc::entry_hint(&entry.tags),
     }
 }
 
@@ -730,7 +1105,7 @@ fn suggest_category(
     global_model: Option<&MnbModel>,
     account_model: Option<&MnbModel>,
     account_sample_count: usize,
-) -> Option<String> {
+) -> Option<(String, f64)> {
     let tokens = tokenize_entry(entry);
     let global_proba = global_model?.predict_proba(&tokens);
     let alpha = (account_sample_count as f64 / ACCOUNT_WARMUP_SIZE).min(1.0);
@@ -757,8 +1132,9 @@ fn suggest_category(
         .into_iter()
         .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap_or(std::cmp::Ordering::Equal))
         .and_then(|(class, prob)| {
-            if prob / total >= CONFIDENCE_THRESHOLD {
-                Some(class.to_string())
+            let confidence = prob / total;
+            if confidence >= CONFIDENCE_THRESHOLD {
+                Some((class.to_string(), confidence))
             } else {
                 None
             }
@@ -910,6 +1286,8 @@ mod tests {
             extracted_by: None,
             posted: None,
             posted_postings: vec![],
+            note: None,
+            review: None,
         }
     }
 
@@ -965,6 +1343,24 @@ mod tests {
         assert_eq!(best_class, "Expenses:Groceries");
     }
 
+    #[test]
+    fn mcc_hint_suggests_category_for_unknown_merchant() {
+        let entry = make_entry(
+            "e1",
+            "ZZMYSTERYMERCHANT",
+            vec![("mcc".to_string(), "5812".to_string())],
+        );
+        let tokens = tokenize_entry(&entry);
+        assert!(tokens.contains(&"mccCategory:Dining".to_string()));
+
+        let model = MnbModel::fit(&seed_examples(), 1.0).unwrap();
+        let best = model
+            .predict_proba(&tokens)
+            .into_iter()
+            .max_by(|a, b| a.0.partial_cmp(&b.0).unwrap());
+        assert_eq!(best.unwrap().1, "Expenses:Dining");
+    }
+
     #[test]
     fn mnb_abstains_on_unknown_token() {
         // With only seed examples, an unknown token should spread probability
@@ -997,6 +1393,7 @@ mod tests {
             date: date.to_string(),
             amount_f64: amount,
             commodity: commodity.to_string(),
+            card: None,
         }
     }
 
@@ -1065,6 +1462,43 @@ mod tests {
         assert!(find_transfer_match(&entry, &candidates).is_none());
     }
 
+    #[test]
+    fn card_payment_ties_break_on_last4_then_statement_cycle() {
+        let card = |last4: &str, cycle_day: u32| {
+            Some(Rc::new(login_config::InstitutionInfo {
+                statement_cycle_day: Some(cycle_day),
+                card_last4: vec![last4.to_string()],
+                ..Default::default()
+            }))
+        };
+        let mut sapphire = make_candidate(
+            "logins/chase/accounts/sapphire",
+            "s",
+            "2024-01-14",
+            21.32,
+            "USD",
+        );
+        sapphire.card = card("1111", 15);
+        let mut freedom = make_candidate(
+            "logins/chase/accounts/freedom",
+            "f",
+            "2024-01-14",
+            21.32,
+            "USD",
+        );
+        freedom.card = card("2222", 28);
+        let candidates = vec![sapphire, freedom];
+
+        let by_digits = make_entry("e1", "CHASE CREDIT CRD AUTOPAY 2222", vec![]);
+        let m = find_transfer_match(&by_digits, &candidates).unwrap();
+        assert_eq!(m.entry_id, "f");
+
+        // Sapphire's statement closed on the 15th, between the two dates.
+        let by_cycle = make_entry("e1", "CREDIT CARD PAYMENT", vec![]);
+        let m = find_transfer_match(&by_cycle, &candidates).unwrap();
+        assert_eq!(m.entry_id, "f");
+    }
+
     // --- suggest_category integration ---
 
     #[test]
@@ -1082,8 +1516,9 @@ mod tests {
         }
         let model = MnbModel::fit(&examples, 1.0).unwrap();
         let entry = make_entry("e1", "SAFEWAY #123", vec![]);
-        let result = suggest_category(&entry, Some(&model), None, 0);
-        assert_eq!(result.as_deref(), Some("Expenses:Groceries"));
+        let (account, confidence) = suggest_category(&entry, Some(&model), None, 0).unwrap();
+        assert_eq!(account, "Expenses:Groceries");
+        assert!((0.5..=1.0).contains(&confidence), "{confidence}");
     }
 
     #[test]
@@ -1095,4 +1530,110 @@ mod tests {
         // Should abstain when confidence is low.
         assert!(result.is_none(), "expected None, got {result:?}");
     }
+
+    // --- Explanations and feedback ---
+
+    #[test]
+    fn explanation_names_rule_payee_history_and_feedback() {
+        let entry = make_entry("e1", "TRADER JOE'S #552", vec![]);
+        let rules = vec![
+            CategorizationRule {
+                keyword: "trader joe".to_string(),
+                account: "Expenses:Groceries".to_string(),
+            },
+            CategorizationRule {
+                keyword: "joe".to_string(),
+                account: "Expenses:Dining".to_string(),
+            },
+        ];
+        let payee_accounts = HashMap::from([(
+            "TRADER JOE S".to_string(),
+            HashMap::from([
+                ("Expenses:Groceries".to_string(), 4),
+                ("Expenses:Dining".to_string(), 1),
+            ]),
+        )]);
+        let feedback = vec![CategoryFeedback {
+            login_name: "bank".to_string(),
+            label: "checking".to_string(),
+            entry_id: "e0".to_string(),
+            payee: "TRADER JOE S".to_string(),
+            tokens: vec!["TRADER".to_string(), "JOE".to_string()],
+            suggested: None,
+            account: "Expenses:Groceries".to_string(),
+            recorded_at: "2025-01-01T00:00:00Z".to_string(),
+        }];
+        let explanation = explain_suggestion(
+            &entry,
+            "Expenses:Groceries",
+            0.8,
+            10,
+            &rules,
+            &payee_accounts,
+            &feedback,
+        );
+        assert_eq!(explanation.matched_rule, Some(rules[0].clone()));
+        assert_eq!(
+            explanation.payee_history,
+            Some(PayeeHistory {
+                payee: "TRADER JOE S".to_string(),
+                suggested_count: 4,
+                total_count: 5,
+            })
+        );
+        assert_eq!(explanation.account_weight, 0.5);
+        assert_eq!(explanation.feedback_count, 1);
+        assert_eq!(
+            feedback_examples(&feedback, Some(("bank", "savings"))),
+            Vec::<TrainingExample>::new()
+        );
+    }
+
+    #[test]
+    fn recorded_feedback_replaces_earlier_feedback_on_the_entry() {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let ledger = std::env::temp_dir().join(format!(
+            "refreshmint-category-feedback-{}-{now}",
+            std::process::id()
+        ));
+        let journal = account_journal::login_account_journal_path(&ledger, "bank", "checking");
+        std::fs::create_dir_all(journal.parent().unwrap()).unwrap();
+        account_journal::write_journal_at_path(
+            &journal,
+            &[make_entry("e1", "Blue Bottle", vec![])],
+        )
+        .unwrap();
+
+        record_category_feedback(
+            &ledger,
+            "bank",
+            "checking",
+            "e1",
+            Some("Expenses:Shopping".to_string()),
+            "Expenses:Groceries",
+        )
+        .unwrap();
+        let record = record_category_feedback(
+            &ledger,
+            "bank",
+            "checking",
+            "e1",
+            Some("Expenses:Shopping".to_string()),
+            "Expenses:Dining",
+        )
+        .unwrap();
+        assert_eq!(record.payee, "BLUE BOTTLE");
+        assert_eq!(list_category_feedback(&ledger).unwrap(), vec![record]);
+        assert_eq!(
+            feedback_examples(&list_category_feedback(&ledger).unwrap(), None),
+            vec![(
+                vec!["BLUE".to_string(), "BOTTLE".to_string()],
+                "Expenses:Dining".to_string()
+            )]
+        );
+        assert!(record_category_feedback(&ledger, "bank", "checking", "nope", None, "X").is_err());
+    }
 }
@@ -0,0 +1,396 @@
+//! Keyboard-driven triage of unposted login account entries.
+//!
+//! The queue joins every unposted entry with its category suggestion,
+//! transfer match, and possible duplicates, highest confidence first, so a
+//! UI can accept, skip, or pair one entry per keystroke. Skipped entries are
+//! flagged for review and move to the end of the queue.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry, ReviewState};
+use crate::categorize::{self, TransferMatch};
+use crate::dedup;
+use crate::entry_review;
+use crate::login_config;
+use crate::post;
+
+/// Days apart two same-amount entries can be and still look like duplicates.
+const DUPLICATE_DATE_TOLERANCE_DAYS: i64 = 3;
+
+/// Identifies one entry in a login account journal.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EntryRef {
+    pub login_name: String,
+    pub label: String,
+    pub entry_id: String,
+}
+
+/// Another entry in the same account with the same amount, a similar
+/// description, and a nearby date.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct DuplicateWarning {
+    pub entry_id: String,
+    pub date: String,
+    pub description: String,
+    pub posted: bool,
+}
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "ClassificationQueueItem")]
+pub struct QueueItem {
+    #[serde(flatten)]
+    pub entry: EntryRef,
+    pub date: String,
+    pub description: String,
+    pub amount: Option<String>,
+    pub suggested: Option<String>,
+    pub transfer_match: Option<TransferMatch>,
+    pub duplicates: Vec<DuplicateWarning>,
+    /// 1 for a transfer match, the category probability for a suggestion,
+    /// and 0 when there is neither.
+    pub confidence: f64,
+    /// Set once the entry has been skipped.
+    pub review: Option<ReviewState>,
+}
+
+/// What to do with the entry at the head of the queue.
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(tag = "action", rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub enum ClassifyDecision {
+    /// Post with `account` as the counterpart, or the suggested category.
+    Post {
+        #[serde(default)]
+        #[ts(optional = nullable)]
+        account: Option<String>,
+    },
+    /// Flag the entry for review and move on.
+    Skip,
+    /// Post as a transfer with `counterpart`, or the detected transfer match.
+    Transfer {
+        #[serde(default)]
+        #[ts(optional = nullable)]
+        counterpart: Option<EntryRef>,
+    },
+}
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ClassifyOutcome {
+    /// GL transaction written by a post or transfer decision.
+    pub gl_txn_id: Option<String>,
+    /// The new head of the queue.
+    pub next: Option<QueueItem>,
+}
+
+/// Every unposted entry in login accounts mapped to a GL account: unskipped
+/// entries first, then by descending confidence and date.
+pub fn classification_queue(
+    ledger_dir: &Path,
+) -> Result<Vec<QueueItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let mut queue = Vec::new();
+    for login in login_config::list_logins(ledger_dir)? {
+        let config = login_config::read_login_config(ledger_dir, &login);
+        for (label, account_config) in &config.accounts {
+            let mapped = account_config
+                .gl_account
+                .as_deref()
+                .is_some_and(|gl_account| !gl_account.trim().is_empty());
+            if !mapped {
+                continue;
+            }
+            queue.extend(account_queue(ledger_dir, &login, label)?);
+        }
+    }
+    queue.sort_by(|a, b| {
+        a.review
+            .is_some()
+            .cmp(&b.review.is_some())
+            .then_with(|| b.confidence.total_cmp(&a.confidence))
+            .then_with(|| a.date.cmp(&b.date))
+            .then_with(|| a.entry.entry_id.cmp(&b.entry.entry_id))
+    });
+    Ok(queue)
+}
+
+fn account_queue(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+) -> Result<Vec<QueueItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+    let unposted: Vec<usize> = (0..entries.len())
+        .filter(|&i| entries[i].posted.is_none() && entries[i].posted_postings.is_empty())
+        .collect();
+    if unposted.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut suggestions = categorize::suggest_categories(ledger_dir, login_name, label)?;
+    Ok(unposted
+        .into_iter()
+        .map(|index| {
+            let entry = &entries[index];
+            let result = suggestions.remove(&entry.id);
+            let (suggested, transfer_match, confidence) = match result {
+                Some(result) if result.transfer_match.is_some() => {
+                    (None, result.transfer_match, 1.0)
+                }
+                Some(result) => {
+                    let confidence = result.confidence.unwrap_or(0.0);
+                    (result.suggested, None, confidence)
+                }
+                None => (None, None, 0.0),
+            };
+            QueueItem {
+                entry: EntryRef {
+                    login_name: login_name.to_string(),
+                    label: label.to_string(),
+                    entry_id: entry.id.clone(),
+                },
+                date: entry.date.clone(),
+                description: entry.description.clone(),
+                amount: entry_amount_string(entry),
+                suggested,
+                transfer_match,
+                duplicates: duplicate_warnings(&entries, index),
+                confidence,
+                review: entry.review,
+            }
+        })
+        .collect())
+}
+
+/// Apply `decision` to `entry` and return the refreshed head of the queue.
+pub fn classify_next(
+    ledger_dir: &Path,
+    entry: &EntryRef,
+    decision: &ClassifyDecision,
+    lock_owner: &str,
+) -> Result<ClassifyOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let gl_txn_id = match decision {
+        ClassifyDecision::Post { account } => {
+            let account = match account.as_deref().map(str::trim) {
+                Some(account) if !account.is_empty() => account.to_string(),
+                _ => suggestion_for(ledger_dir, entry)?.0.ok_or_else(|| {
+                    format!(
+                        "entry '{}' has no suggested category; choose an account",
+                        entry.entry_id
+                    )
+                })?,
+            };
+            Some(post::post_login_account_entry(
+                ledger_dir,
+                &entry.login_name,
+                &entry.label,
+                &entry.entry_id,
+                &account,
+                None,
+                lock_owner,
+            )?)
+        }
+        ClassifyDecision::Skip => {
+            entry_review::set_entry_review_state(
+                ledger_dir,
+                &entry.login_name,
+                &entry.label,
+                &entry.entry_id,
+                Some(ReviewState::Flagged),
+                lock_owner,
+            )?;
+            None
+        }
+        ClassifyDecision::Transfer { counterpart } => {
+            let counterpart = match counterpart {
+                Some(counterpart) => counterpart.clone(),
+                None => {
+                    let transfer_match = suggestion_for(ledger_dir, entry)?.1.ok_or_else(|| {
+                        format!(
+                            "entry '{}' has no transfer match; choose the other entry",
+                            entry.entry_id
+                        )
+                    })?;
+                    transfer_match_ref(&transfer_match)?
+                }
+            };
+            Some(post::post_login_account_transfer(
+                ledger_dir,
+                &entry.login_name,
+                &entry.label,
+                &entry.entry_id,
+                &counterpart.login_name,
+                &counterpart.label,
+                &counterpart.entry_id,
+                lock_owner,
+            )?)
+        }
+    };
+    let next = classification_queue(ledger_dir)?.into_iter().next();
+    Ok(ClassifyOutcome { gl_txn_id, next })
+}
+
+fn suggestion_for(
+    ledger_dir: &Path,
+    entry: &EntryRef,
+) -> Result<(Option<String>, Option<TransferMatch>), Box<dyn std::error::Error + Send + Sync>> {
+    let result = categorize::suggest_categories(ledger_dir, &entry.login_name, &entry.label)?
+        .remove(&entry.entry_id)
+        .ok_or_else(|| format!("entry not found: {}", entry.entry_id))?;
+    Ok((result.suggested, result.transfer_match))
+}
+
+/// Parse the `logins/<login>/accounts/<label>` locator of a transfer match.
+fn transfer_match_ref(transfer_match: &TransferMatch) -> Result<EntryRef, String> {
+    transfer_match
+        .account_locator
+        .strip_prefix("logins/")
+        .and_then(|rest| rest.split_once("/accounts/"))
+        .map(|(login_name, label)| EntryRef {
+            login_name: login_name.to_string(),
+            label: label.to_string(),
+            entry_id: transfer_match.entry_id.clone(),
+        })
+        .ok_or_else(|| {
+            format!(
+                "unexpected transfer match locator: {}",
+                transfer_match.account_locator
+            )
+        })
+}
+
+/// Other entries that share `entries[index]`'s amount and commodity, fall
+/// within a few days of it, and have a similar description.
+fn duplicate_warnings(entries: &[AccountEntry], index: usize) -> Vec<DuplicateWarning> {
+    let entry = &entries[index];
+    let Some(amount) = primary_amount(entry) else {
+        return Vec::new();
+    };
+    entries
+        .iter()
+        .enumerate()
+        .filter(|&(other_index, other)| {
+            other_index != index
+                && primary_amount(other).is_some_and(|(quantity, commodity)| {
+                    commodity == amount.1 && (quantity - amount.0).abs() < 0.005
+                })
+                && dedup::dates_within_tolerance(
+                    &entry.date,
+                    &other.date,
+                    DUPLICATE_DATE_TOLERANCE_DAYS,
+                )
+                && dedup::descriptions_similar(&entry.description, &other.description)
+        })
+        .map(|(_, other)| DuplicateWarning {
+            entry_id: other.id.clone(),
+            date: other.date.clone(),
+            description: other.description.clone(),
+            posted: other.posted.is_some() || !other.posted_postings.is_empty(),
+        })
+        .collect()
+}
+
+fn primary_amount(entry: &AccountEntry) -> Option<(f64, &str)> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    let quantity = amount.quantity.trim().parse().ok()?;
+    Some((quantity, amount.commodity.as_str()))
+}
+
+fn entry_amount_string(entry: &AccountEntry) -> Option<String> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    Some(format!("{} {}", amount.quantity, amount.commodity))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn entry(date: &str, description: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![format!("{date}.csv:1:1")],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    #[test]
+    fn duplicate_warnings_need_amount_date_and_description() {
+        let mut posted = entry("2025-03-02", "COFFEE SHOP #12", "-4.50");
+        posted.posted = Some("general.journal:abc".to_string());
+        let entries = vec![
+            entry("2025-03-01", "COFFEE SHOP #12", "-4.50"),
+            posted,
+            entry("2025-03-01", "COFFEE SHOP #12", "-5.50"),
+            entry("2025-03-09", "COFFEE SHOP #12", "-4.50"),
+            entry("2025-03-01", "HARDWARE STORE", "-4.50"),
+        ];
+
+        let warnings = duplicate_warnings(&entries, 0);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].entry_id, entries[1].id);
+        assert!(warnings[0].posted);
+        assert!(duplicate_warnings(&entries, 2).is_empty());
+    }
+
+    #[test]
+    fn transfer_match_locator_becomes_entry_ref() {
+        let transfer_match = TransferMatch {
+            account_locator: "logins/bank/accounts/savings".to_string(),
+            entry_id: "e-1".to_string(),
+            matched_amount: "100 USD".to_string(),
+        };
+        assert_eq!(
+            transfer_match_ref(&transfer_match).unwrap(),
+            EntryRef {
+                login_name: "bank".to_string(),
+                label: "savings".to_string(),
+                entry_id: "e-1".to_string(),
+            }
+        );
+
+        let bad = TransferMatch {
+            account_locator: "accounts/savings".to_string(),
+            ..transfer_match
+        };
+        assert!(transfer_match_ref(&bad).is_err());
+    }
+
+    #[test]
+    fn decisions_deserialize_from_action_tag() {
+        let post: ClassifyDecision = serde_json::from_str(r#"{"action":"post"}"#).unwrap();
+        assert!(matches!(post, ClassifyDecision::Post { account: None }));
+
+        let transfer: ClassifyDecision = serde_json::from_str(
+            r#"{"action":"transfer","counterpart":{"loginName":"bank","label":"savings","entryId":"e-1"}}"#,
+        )
+        .unwrap();
+        assert!(matches!(
+            transfer,
+            ClassifyDecision::Transfer {
+                counterpart: Some(ref counterpart)
+            } if counterpart.entry_id == "e-1"
+        ));
+
+        assert!(matches!(
+            serde_json::from_str::<ClassifyDecision>(r#"{"action":"skip"}"#).unwrap(),
+            ClassifyDecision::Skip
+        ));
+    }
+}
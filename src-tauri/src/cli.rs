@@ -1,4 +1,4 @@
-use clap::{Args, Parser, Subcommand};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use std::error::Error;
 use std::io::Read;
 use std::path::{Path, PathBuf};
@@ -22,12 +22,17 @@ enum Commands {
     Secret(SecretArgs),
     Scrape(ScrapeArgs),
     Account(AccountArgs),
+    Import(ImportArgs),
 }
 
 #[derive(Args)]
 struct NewArgs {
     #[arg(long)]
     ledger: Option<PathBuf>,
+    /// Seed the ledger from a starter template: personal-us, personal-eu,
+    /// or freelancer.
+    #[arg(long)]
+    template: Option<String>,
 }
 
 #[derive(Args)]
@@ -39,6 +44,27 @@ struct GlArgs {
 #[derive(Subcommand)]
 enum GlCommand {
     Add(AddArgs),
+    /// Move GL transactions into a single journal or per-year or per-month
+    /// include files, and post to that layout from now on.
+    Layout(GlLayoutArgs),
+}
+
+#[derive(Args)]
+struct GlLayoutArgs {
+    #[arg(value_enum)]
+    layout: GlLayoutArg,
+    #[arg(long)]
+    ledger: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GlLayoutArg {
+    /// Every transaction in general.journal.
+    Single,
+    /// One gl/<year>.journal per year.
+    Yearly,
+    /// One gl/<year>/<month>.journal per month.
+    Monthly,
 }
 
 #[derive(Args)]
@@ -79,6 +105,7 @@ enum LoginCommand {
     DeleteAccount(LoginDeleteAccountArgs),
     #[command(alias = "clear-chrome-profile")]
     ClearProfile(LoginClearProfileArgs),
+    Storage(LoginStorageArgs),
 }
 
 #[derive(Args)]
@@ -123,6 +150,18 @@ struct LoginSetAccountArgs {
     label: String,
     #[arg(long = "gl-account", value_name = "ACCOUNT")]
     gl_account: Option<String>,
+    #[arg(
+        long = "account-type",
+        value_name = "TYPE",
+        help = "Account type: bank, card, brokerage, retirement, or benefits."
+    )]
+    account_type: Option<crate::login_config::AccountType>,
+    #[arg(
+        long = "account-id",
+        value_name = "ID",
+        help = "Institution account number or masked number to route to this label; repeatable."
+    )]
+    account_ids: Vec<String>,
     #[arg(long)]
     ledger: Option<PathBuf>,
 }
@@ -145,10 +184,32 @@ struct LoginClearProfileArgs {
     ledger: Option<PathBuf>,
 }
 
+#[derive(Args)]
+struct LoginStorageArgs {
+    #[arg(long, value_name = "NAME")]
+    name: String,
+    #[arg(long, help = "Only show or clear this extension's storage.")]
+    extension: Option<String>,
+    #[arg(
+        long,
+        requires = "clear",
+        help = "Clear only this key (requires --extension)."
+    )]
+    key: Option<String>,
+    #[arg(long, help = "Clear the selected storage instead of printing it.")]
+    clear: bool,
+    #[arg(long)]
+    ledger: Option<PathBuf>,
+}
+
 #[derive(Args)]
 struct MigrateArgs {
     #[arg(long)]
     dry_run: bool,
+    /// Replace random entry ids with derived ones instead of migrating the
+    /// ledger layout.
+    #[arg(long)]
+    entry_ids: bool,
     #[arg(long)]
     ledger: Option<PathBuf>,
 }
@@ -163,6 +224,7 @@ struct DebugArgs {
 enum DebugCommand {
     Start(DebugStartArgs),
     Exec(DebugExecArgs),
+    Attach(DebugAttachArgs),
     Stop(DebugStopArgs),
 }
 
@@ -202,9 +264,37 @@ struct DebugExecArgs {
     extension_dir: Option<PathBuf>,
     #[arg(
         long,
-        value_name = "MESSAGE=VALUE",
+        value_name = "KEY=VALUE",
         action = clap::ArgAction::Append,
-        help = "Answer override for refreshmint.prompt(message). Repeat for multiple prompts."
+        help = "Answer for refreshmint.prompt(...), by manifest prompt key or exact message. \
+                Repeat for multiple prompts."
+    )]
+    prompt: Vec<String>,
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        action = clap::ArgAction::Append,
+        help = "Key/value option for refreshmint.getOptions(). VALUE is parsed as JSON; \
+                falls back to string. Repeat for multiple options."
+    )]
+    option: Vec<String>,
+}
+
+#[derive(Args)]
+struct DebugAttachArgs {
+    #[arg(help = "Login whose running debug session to attach to.")]
+    login: String,
+    #[arg(
+        long,
+        help = "Socket of the session (defaults to the newest session started for LOGIN)."
+    )]
+    socket: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "KEY=VALUE",
+        action = clap::ArgAction::Append,
+        help = "Answer for refreshmint.prompt(...), by manifest prompt key or exact message. \
+                Repeat for multiple prompts."
     )]
     prompt: Vec<String>,
     #[arg(
@@ -289,11 +379,18 @@ struct ScrapeArgs {
     headless: bool,
     #[arg(
         long,
-        value_name = "MESSAGE=VALUE",
+        value_name = "KEY=VALUE",
         action = clap::ArgAction::Append,
-        help = "Answer override for refreshmint.prompt(message). Repeat for multiple prompts."
+        help = "Answer for refreshmint.prompt(...), by manifest prompt key or exact message. \
+                Repeat for multiple prompts."
     )]
     prompt: Vec<String>,
+    #[arg(
+        long,
+        value_name = "YEAR",
+        help = "Run the extension's taxDocuments entry point for this tax year."
+    )]
+    tax_year: Option<i32>,
 }
 
 #[derive(Args)]
@@ -338,6 +435,11 @@ struct AccountExtractArgs {
         help = "Document filename to extract. Repeat for multiple files. Defaults to all account documents."
     )]
     document: Vec<String>,
+    #[arg(
+        long,
+        help = "Also extract documents marked suspect at download (error pages, empty exports)."
+    )]
+    include_suspect: bool,
 }
 
 #[derive(Args)]
@@ -430,8 +532,62 @@ struct AddArgs {
     posting: Vec<String>,
 }
 
+#[derive(Args)]
+struct ImportArgs {
+    #[arg(long, value_enum)]
+    format: ImportFormat,
+    #[arg(value_name = "FILE")]
+    file: PathBuf,
+    #[arg(
+        long,
+        alias = "account",
+        help = "Login whose account receives QIF entries."
+    )]
+    login: Option<String>,
+    #[arg(long, help = "Login account label for QIF entries.")]
+    label: Option<String>,
+    #[arg(long)]
+    ledger: Option<PathBuf>,
+    #[arg(
+        long,
+        value_name = "NAME",
+        help = "QIF !Account to import when the file holds several accounts."
+    )]
+    qif_account: Option<String>,
+    #[arg(
+        long,
+        default_value = "USD",
+        help = "Commodity for amounts in formats that don't record one."
+    )]
+    commodity: String,
+    #[arg(long, help = "Read dates as day/month/year.")]
+    day_first: bool,
+    #[arg(
+        long,
+        value_name = "YYYY-MM-DD",
+        help = "Summarize book history before this date into opening balances."
+    )]
+    start_date: Option<String>,
+    #[arg(
+        long,
+        help = "Report what a book import would write without writing it."
+    )]
+    dry_run: bool,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ImportFormat {
+    /// Quicken Interchange Format export of one login account.
+    Qif,
+    /// GnuCash XML or SQLite book, imported into general.journal.
+    Gnucash,
+    /// Beancount file, imported into general.journal.
+    Beancount,
+}
+
 pub fn run(context: tauri::Context<tauri::Wry>) -> Result<(), Box<dyn Error>> {
     let cli = Cli::parse();
+    crate::logging::init();
     match cli.command {
         Some(Commands::New(args)) => run_new(args, context),
         Some(Commands::Gl(args)) => run_gl(args, context),
@@ -442,33 +598,72 @@ pub fn run(context: tauri::Context<tauri::Wry>) -> Result<(), Box<dyn Error>> {
         Some(Commands::Secret(args)) => run_secret(args),
         Some(Commands::Scrape(args)) => run_scrape(args, context),
         Some(Commands::Account(args)) => run_account(args, context),
+        Some(Commands::Import(args)) => run_import(args, context),
         None => crate::run_with_context(context),
     }
 }
 
 fn run_new(args: NewArgs, context: tauri::Context<tauri::Wry>) -> Result<(), Box<dyn Error>> {
+    let template = args
+        .template
+        .map(|template| {
+            crate::onboarding::LedgerTemplate::parse(&template).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unknown ledger template: {template}"),
+                )
+            })
+        })
+        .transpose()?;
     match args.ledger {
-        Some(path) => run_new_with_ledger_path(path),
+        Some(path) => run_new_with_ledger_path(path, template),
         None => {
             let target_dir = default_ledger_dir(context)?;
-            crate::ledger::new_ledger_at_dir(&target_dir)?;
+            crate::ledger::new_ledger_at_dir(&target_dir, template)?;
             Ok(())
         }
     }
 }
 
-fn run_new_with_ledger_path(path: PathBuf) -> Result<(), Box<dyn Error>> {
+fn run_new_with_ledger_path(
+    path: PathBuf,
+    template: Option<crate::onboarding::LedgerTemplate>,
+) -> Result<(), Box<dyn Error>> {
     let target_dir = crate::ledger::ensure_refreshmint_extension(path)?;
-    crate::ledger::new_ledger_at_dir(&target_dir)?;
+    crate::ledger::new_ledger_at_dir(&target_dir, template)?;
     Ok(())
 }
 
 fn run_gl(args: GlArgs, context: tauri::Context<tauri::Wry>) -> Result<(), Box<dyn Error>> {
     match args.command {
         GlCommand::Add(add_args) => run_gl_add(add_args, context),
+        GlCommand::Layout(layout_args) => run_gl_layout(layout_args, context),
     }
 }
 
+fn run_gl_layout(
+    args: GlLayoutArgs,
+    context: tauri::Context<tauri::Wry>,
+) -> Result<(), Box<dyn Error>> {
+    let ledger_dir = resolve_cli_ledger_dir(args.ledger, context)?;
+    let layout = match args.layout {
+        GlLayoutArg::Single => crate::gl_layout::GlLayout::Single,
+        GlLayoutArg::Yearly => crate::gl_layout::GlLayout::Yearly,
+        GlLayoutArg::Monthly => crate::gl_layout::GlLayout::Monthly,
+    };
+    let conversion = crate::gl_layout::convert_gl_layout(&ledger_dir, layout, "cli")
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    println!(
+        "Moved {} transaction(s); the GL is now in {} file(s):",
+        conversion.moved,
+        conversion.files.len()
+    );
+    for file in conversion.files {
+        println!("  {file}");
+    }
+    Ok(())
+}
+
 fn run_extension(
     args: ExtensionArgs,
     context: tauri::Context<tauri::Wry>,
@@ -491,6 +686,7 @@ fn run_login(args: LoginArgs, context: tauri::Context<tauri::Wry>) -> Result<(),
         LoginCommand::ClearProfile(clear_profile_args) => {
             run_login_clear_profile(clear_profile_args, context)
         }
+        LoginCommand::Storage(storage_args) => run_login_storage(storage_args, context),
     }
 }
 
@@ -498,6 +694,7 @@ fn run_debug(args: DebugArgs, context: tauri::Context<tauri::Wry>) -> Result<(),
     match args.command {
         DebugCommand::Start(start_args) => run_debug_start(start_args, context),
         DebugCommand::Exec(exec_args) => run_debug_exec(exec_args),
+        DebugCommand::Attach(attach_args) => run_debug_attach(attach_args),
         DebugCommand::Stop(stop_args) => run_debug_stop(stop_args),
     }
 }
@@ -793,6 +990,19 @@ fn run_debug_exec(args: DebugExecArgs) -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
+fn run_debug_attach(args: DebugAttachArgs) -> Result<(), Box<dyn Error>> {
+    let login_name = require_cli_login_name("login", &args.login)?;
+    let socket_path = match args.socket {
+        Some(path) => path,
+        None => crate::scrape::debug::find_debug_socket(&login_name)?,
+    };
+    crate::scrape::debug_attach::run_attach(crate::scrape::debug_attach::AttachOptions {
+        socket_path,
+        prompt_overrides: parse_prompt_overrides(&args.prompt)?,
+        script_options: parse_script_options(&args.option)?,
+    })
+}
+
 fn run_debug_stop(args: DebugStopArgs) -> Result<(), Box<dyn Error>> {
     crate::scrape::debug::stop_debug_session(&args.socket)?;
     println!("Debug session stopped.");
@@ -847,6 +1057,8 @@ fn run_login_create(
     let config = crate::login_config::LoginConfig {
         extension: extension.map(ToOwned::to_owned),
         accounts: std::collections::BTreeMap::new(),
+        institution: None,
+        label_rules: Vec::new(),
     };
     crate::login_config::write_login_config(&ledger_dir, &login_name, &config)
         .map_err(std::io::Error::other)?;
@@ -932,12 +1144,17 @@ fn run_login_set_account(
     }
 
     let mut config = crate::login_config::read_login_config(&ledger_dir, &login_name);
-    config.accounts.insert(
-        label.clone(),
-        crate::login_config::LoginAccountConfig { gl_account },
-    );
+    let account_config = config.accounts.entry(label.clone()).or_default();
+    account_config.gl_account = gl_account;
+    if let Some(account_type) = args.account_type {
+        account_config.account_type = Some(account_type);
+    }
     crate::login_config::write_login_config(&ledger_dir, &login_name, &config)
         .map_err(std::io::Error::other)?;
+    for account_id in &args.account_ids {
+        crate::account_routing::assign_account_id(&ledger_dir, &login_name, account_id, &label)
+            .map_err(std::io::Error::other)?;
+    }
     println!("Updated label '{label}' for login '{login_name}'.");
     Ok(())
 }
@@ -986,14 +1203,62 @@ fn run_login_clear_profile(
     Ok(())
 }
 
+fn run_login_storage(
+    args: LoginStorageArgs,
+    context: tauri::Context<tauri::Wry>,
+) -> Result<(), Box<dyn Error>> {
+    let ledger_dir = resolve_cli_ledger_dir(args.ledger, context)?;
+    crate::ledger::require_refreshmint_extension(&ledger_dir)?;
+    let login_name = require_cli_login_name("name", &args.name)?;
+    require_cli_existing_login(&ledger_dir, &login_name)?;
+
+    if !args.clear {
+        let mut stores = crate::extension_storage::list_storage(&ledger_dir, &login_name)?;
+        if let Some(extension) = &args.extension {
+            stores.retain(|name, _| name == extension);
+        }
+        println!("{}", serde_json::to_string_pretty(&stores)?);
+        return Ok(());
+    }
+
+    let _lock = crate::login_config::acquire_login_lock_with_metadata(
+        &ledger_dir,
+        &login_name,
+        "cli",
+        "clear-login-storage",
+    )
+    .map_err(std::io::Error::other)?;
+    let removed = crate::extension_storage::clear_storage(
+        &ledger_dir,
+        &login_name,
+        args.extension.as_deref(),
+        args.key.as_deref(),
+    )?;
+    println!(
+        "Removed {removed} storage entr{} for login '{login_name}'.",
+        if removed == 1 { "y" } else { "ies" }
+    );
+    Ok(())
+}
+
 fn run_migrate(
     args: MigrateArgs,
     context: tauri::Context<tauri::Wry>,
 ) -> Result<(), Box<dyn Error>> {
     let ledger_dir = resolve_cli_ledger_dir(args.ledger, context)?;
     crate::ledger::require_refreshmint_extension(&ledger_dir)?;
-    let outcome = crate::migration::migrate_ledger(&ledger_dir, args.dry_run)
-        .map_err(|err| std::io::Error::other(err.to_string()))?;
+    if args.entry_ids {
+        let outcome = crate::migration::migrate_entry_ids(&ledger_dir, args.dry_run, "cli")
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+        println!("{}", serde_json::to_string_pretty(&outcome)?);
+        return Ok(());
+    }
+    let outcome = crate::migration::migrate_ledger(
+        &ledger_dir,
+        args.dry_run,
+        &crate::jobs::JobHandle::detached(),
+    )
+    .map_err(|err| std::io::Error::other(err.to_string()))?;
     println!("{}", serde_json::to_string_pretty(&outcome)?);
     Ok(())
 }
@@ -1013,6 +1278,7 @@ fn run_scrape(args: ScrapeArgs, context: tauri::Context<tauri::Wry>) -> Result<(
 
     let login_name_str = login_name.clone();
     let ledger_dir_clone = ledger_dir.clone();
+    let report = crate::scrape::ScrapeReportSlot::default();
 
     let config = crate::scrape::ScrapeConfig {
         login_name,
@@ -1023,20 +1289,48 @@ fn run_scrape(args: ScrapeArgs, context: tauri::Context<tauri::Wry>) -> Result<(
         prompt_overrides,
         prompt_requires_override: true,
         prompt_ui_handler: None,
+        mode: match args.tax_year {
+            Some(year) => crate::scrape::ScrapeMode::TaxDocuments { year },
+            None => crate::scrape::ScrapeMode::Accounts,
+        },
+        report: Some(report.clone()),
     };
 
     let timestamp = crate::operations::now_timestamp();
+    let started = std::time::Instant::now();
     let result = crate::scrape::run_scrape(config);
+    let report = std::mem::take(&mut *report.lock().unwrap_or_else(|err| err.into_inner()));
     let entry = crate::operations::ScrapeLogEntry {
         login_name: login_name_str,
         timestamp,
         success: result.is_ok(),
         error: result.as_ref().err().map(|e| e.to_string()),
-        source: "manual".to_string(),
+        source: if args.tax_year.is_some() {
+            "tax-documents".to_string()
+        } else {
+            "manual".to_string()
+        },
+        failure: result.as_ref().err().and_then(|e| {
+            e.downcast_ref::<crate::scrape::assertions::AssertionError>()
+                .map(|assertion| assertion.0.clone())
+        }),
+        network: report.network,
+        prompts_asked: report.prompts_asked,
+        session_metadata: report.session_metadata,
+        suspect_documents: report.suspect_documents,
+        scrape_session_id: report.scrape_session_id,
     };
     if let Err(e) = crate::operations::append_scrape_log_entry(&ledger_dir_clone, &entry) {
         eprintln!("warning: failed to write scrape log: {e}");
     }
+    if let Err(e) = crate::scrape_artifacts::write_session_report(&ledger_dir_clone, &entry) {
+        eprintln!("warning: failed to write scrape report: {e}");
+    }
+    if let Err(e) =
+        crate::scrape_stats::record_scrape_run(&ledger_dir_clone, &entry, started.elapsed())
+    {
+        eprintln!("warning: failed to update scrape stats: {e}");
+    }
     result
 }
 
@@ -1104,29 +1398,212 @@ fn run_account_extract(
         Vec::new()
     };
     let document_names = resolve_extraction_document_names(&args.document, listed_documents)?;
+    let document_names = if args.include_suspect {
+        document_names
+    } else {
+        let documents_dir =
+            crate::account_journal::login_account_documents_dir(&ledger_dir, &login_name, &label);
+        let (kept, suspect) =
+            crate::extract::partition_suspect_documents(&documents_dir, &document_names);
+        for name in &suspect {
+            eprintln!("Skipping suspect document {name} (use --include-suspect to extract it).");
+        }
+        kept
+    };
 
     if document_names.is_empty() {
         println!("No documents found for login '{login_name}' label '{label}'.");
         return Ok(());
     }
 
-    let doc_count = document_names.len();
+    let (new_count, raised_alerts) = apply_cli_extraction(
+        &ledger_dir,
+        &login_name,
+        &label,
+        &gl_account,
+        &format!("{extension_name}:latest"),
+        document_names.len(),
+        || {
+            crate::extract::run_extraction_for_login_account(
+                &ledger_dir,
+                &login_name,
+                &label,
+                &gl_account,
+                &extension_name,
+                &document_names,
+            )
+            .map_err(|err| std::io::Error::other(err.to_string()).into())
+        },
+    )?;
+    println!("Extraction complete. Added {new_count} new transaction(s).");
+    for alert in &raised_alerts {
+        println!(
+            "Alert [{}] {} {}: {}",
+            alert.rule_name, alert.date, alert.description, alert.message
+        );
+    }
+    Ok(())
+}
+
+fn run_import(args: ImportArgs, context: tauri::Context<tauri::Wry>) -> Result<(), Box<dyn Error>> {
+    let book_format = match args.format {
+        ImportFormat::Qif => None,
+        ImportFormat::Gnucash => Some(crate::book_import::BookFormat::Gnucash),
+        ImportFormat::Beancount => Some(crate::book_import::BookFormat::Beancount),
+    };
+    let ledger_dir = resolve_cli_ledger_dir(args.ledger, context)?;
+    crate::ledger::require_refreshmint_extension(&ledger_dir)?;
+    if let Some(format) = book_format {
+        let options = crate::book_import::BookImportOptions {
+            start_date: args.start_date,
+            dry_run: args.dry_run,
+        };
+        let report = crate::book_import::import_book(
+            &ledger_dir,
+            format,
+            &args.file,
+            &options,
+            &crate::jobs::JobHandle::detached(),
+        )
+        .map_err(|err| std::io::Error::other(err.to_string()))?;
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    let login_name = require_cli_login_name(
+        "login",
+        args.login
+            .as_deref()
+            .ok_or("--login is required for QIF imports")?,
+    )?;
+    require_cli_existing_login(&ledger_dir, &login_name)?;
+    let label = require_cli_label(
+        args.label
+            .as_deref()
+            .ok_or("--label is required for QIF imports")?,
+    )?;
+    let gl_account = resolve_login_account_gl_account_cli(&ledger_dir, &login_name, &label)?;
+
+    let original_name = args
+        .file
+        .file_name()
+        .and_then(|name| name.to_str())
+        .ok_or_else(|| {
+            std::io::Error::other(format!("invalid import file: {}", args.file.display()))
+        })?
+        .to_string();
+    let bytes = std::fs::read(&args.file)?;
+    let text = String::from_utf8_lossy(&bytes);
+
+    // The imported file is kept as the evidence document for its entries.
+    let documents_dir =
+        crate::account_journal::login_account_documents_dir(&ledger_dir, &login_name, &label);
+    std::fs::create_dir_all(&documents_dir)?;
+    let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let doc_name = crate::scrape::date_prefixed_filename(&today, &original_name, &documents_dir);
+
+    // QIF is the only statement format; books were handled above.
+    let format_name = "qif";
+    let options = crate::qif::QifOptions {
+        day_first: args.day_first,
+        account: args.qif_account,
+        commodity: args.commodity,
+    };
+    let proposed =
+        crate::extract::extract_qif(&text, &doc_name, &options).map_err(std::io::Error::other)?;
+    if proposed.is_empty() {
+        println!("No transactions found in {}.", args.file.display());
+        return Ok(());
+    }
+
+    let mut login_config = crate::login_config::read_login_config(&ledger_dir, &login_name);
+    if !login_config.accounts.contains_key(&label) {
+        login_config.accounts.insert(
+            label.clone(),
+            crate::login_config::LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
+        );
+        crate::login_config::write_login_config(&ledger_dir, &login_name, &login_config)
+            .map_err(|err| std::io::Error::other(err.to_string()))?;
+    }
+
+    let extracted_by = format!("import:{format_name}");
+    let first_date = proposed.iter().map(|t| t.tdate.clone()).min();
+    let last_date = proposed.iter().map(|t| t.tdate.clone()).max();
+    crate::atomic_file::write_atomic(&documents_dir.join(&doc_name), &bytes)?;
+    let info = crate::scrape::DocumentInfo {
+        mime_type: crate::scrape::guess_mime_type(&original_name),
+        original_url: None,
+        scraped_at: crate::operations::now_timestamp(),
+        extension_name: extracted_by.clone(),
+        login_name: login_name.clone(),
+        label: label.clone(),
+        scrape_session_id: String::new(),
+        coverage_end_date: last_date.clone().unwrap_or(today),
+        date_range_start: first_date,
+        date_range_end: last_date,
+        session_date_range_start: None,
+        session_date_range_end: None,
+        suspect: None,
+        document_type: None,
+        metadata: Default::default(),
+    };
+    crate::atomic_file::write_atomic(
+        &documents_dir.join(format!("{doc_name}-info.json")),
+        serde_json::to_string_pretty(&info)?.as_bytes(),
+    )?;
+
+    let (new_count, raised_alerts) = apply_cli_extraction(
+        &ledger_dir,
+        &login_name,
+        &label,
+        &gl_account,
+        &extracted_by,
+        1,
+        || {
+            Ok(crate::extract::ExtractionResult {
+                proposed_transactions: proposed,
+                document_names: vec![doc_name.clone()],
+                console_logs: Vec::new(),
+            })
+        },
+    )?;
+    println!("Imported {doc_name}. Added {new_count} new transaction(s).");
+    for alert in &raised_alerts {
+        println!(
+            "Alert [{}] {} {}: {}",
+            alert.rule_name, alert.date, alert.description, alert.message
+        );
+    }
+    Ok(())
+}
 
+/// Dedup the extracted transactions into the login account's journal and
+/// record the run in the extract log, whether or not it succeeded. Returns
+/// the number of new entries and the alerts they raised.
+fn apply_cli_extraction(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    gl_account: &str,
+    extracted_by: &str,
+    doc_count: usize,
+    extract: impl FnOnce() -> Result<crate::extract::ExtractionResult, Box<dyn Error>>,
+) -> Result<(usize, Vec<crate::alerts::Alert>), Box<dyn Error>> {
     // Run extraction + dedup + journal write, capturing any error so we can
     // always flush the extract log (including console logs) even on failure.
     let mut console_logs: Vec<crate::operations::ExtractConsoleLogLine> = Vec::new();
     let mut new_count = 0usize;
+    let mut raised_alerts: Vec<crate::alerts::Alert> = Vec::new();
 
+    let source_label = label;
     let outcome: Result<(), Box<dyn Error>> = (|| {
-        let extraction = crate::extract::run_extraction_for_login_account(
-            &ledger_dir,
-            &login_name,
-            &label,
-            &gl_account,
-            &extension_name,
-            &document_names,
-        )
-        .map_err(|err| std::io::Error::other(err.to_string()))?;
+        let extraction = extract()?;
 
         console_logs = extraction
             .console_logs
@@ -1138,76 +1615,128 @@ fn run_account_extract(
             })
             .collect();
 
-        let journal_path =
-            crate::account_journal::login_account_journal_path(&ledger_dir, &login_name, &label);
-        let existing_entries = crate::account_journal::read_journal_at_path(&journal_path)?;
-
-        let config = crate::dedup::DedupConfig::default();
-        let mut all_updated = existing_entries;
+        // A document covering several accounts can route transactions to
+        // other labels of the login; each label's journal is merged in turn.
+        let routing = crate::account_routing::route_extraction(
+            ledger_dir,
+            login_name,
+            label,
+            extraction.proposed_transactions,
+            &extraction.document_names,
+        )?;
+        for unknown in &routing.unknown_account_ids {
+            println!(
+                "Unknown account id {} in {}; its transactions stay in '{label}'. \
+                 Assign it with `login set-account --name {login_name} --label <LABEL> \
+                 --account-id {}`.",
+                unknown.account_id, unknown.document, unknown.account_id
+            );
+        }
+        for route in &routing.routes {
+            let label = route.label.as_str();
+            let gl_account = if label == source_label {
+                gl_account.to_string()
+            } else {
+                crate::login_config::read_login_config(ledger_dir, login_name)
+                    .accounts
+                    .get(label)
+                    .and_then(|account| account.gl_account.as_deref())
+                    .map(str::trim)
+                    .unwrap_or_default()
+                    .to_string()
+            };
+            let journal_path =
+                crate::account_journal::login_account_journal_path(ledger_dir, login_name, label);
+            let existing_entries = crate::account_journal::read_journal_at_path(&journal_path)?;
 
-        for doc_name in &extraction.document_names {
-            let doc_txns: Vec<_> = extraction
-                .proposed_transactions
-                .iter()
-                .filter(|t| {
-                    t.evidence_refs()
-                        .iter()
-                        .any(|e| evidence_ref_matches_document(e, doc_name))
-                })
-                .cloned()
-                .collect();
-            if doc_txns.is_empty() {
-                continue;
-            }
+            let config = crate::dedup::DedupConfig::for_account_type(
+                crate::login_config::login_account_type(ledger_dir, login_name, label),
+            );
+            let history = existing_entries.clone();
+            let mut all_updated = existing_entries;
+
+            for doc_name in &route.document_names {
+                let doc_txns: Vec<_> = route
+                    .proposed_transactions
+                    .iter()
+                    .filter(|t| {
+                        t.evidence_refs()
+                            .iter()
+                            .any(|e| evidence_ref_matches_document(e, doc_name))
+                    })
+                    .cloned()
+                    .collect();
+                if doc_txns.is_empty() {
+                    continue;
+                }
 
-            let actions = crate::dedup::run_dedup(&all_updated, &doc_txns, doc_name, &config);
-            new_count += actions
-                .iter()
-                .filter(|a| matches!(a.result, crate::dedup::DedupResult::New))
-                .count();
-
-            let default_account = all_updated
-                .first()
-                .and_then(|e| e.postings.first())
-                .map(|p| p.account.clone())
-                .unwrap_or_else(|| gl_account.clone());
-            if default_account.is_empty() {
-                let has_implicit = doc_txns.iter().any(|t| t.tpostings.is_none());
-                if has_implicit {
-                    return Err(std::io::Error::other(format!(
-                        "login '{login_name}' label '{label}': extractor produced a \
-                         transaction without explicit tpostings but no glAccount is \
-                         configured; set a GL account or fix the extractor"
-                    ))
-                    .into());
+                let actions = crate::dedup::run_dedup(&all_updated, &doc_txns, doc_name, &config);
+                new_count += actions
+                    .iter()
+                    .filter(|a| matches!(a.result, crate::dedup::DedupResult::New))
+                    .count();
+
+                let default_account = all_updated
+                    .first()
+                    .and_then(|e| e.postings.first())
+                    .map(|p| p.account.clone())
+                    .unwrap_or_else(|| gl_account.clone());
+                if default_account.is_empty() {
+                    let has_implicit = doc_txns.iter().any(|t| t.tpostings.is_none());
+                    if has_implicit {
+                        return Err(std::io::Error::other(format!(
+                            "login '{login_name}' label '{label}': extractor produced a \
+                             transaction without explicit tpostings but no glAccount is \
+                             configured; set a GL account or fix the extractor"
+                        ))
+                        .into());
+                    }
                 }
+                let staging_account =
+                    crate::staging::login_account_counterpart(ledger_dir, login_name, label);
+
+                all_updated = crate::dedup::apply_dedup_actions_for_login_account(
+                    ledger_dir,
+                    (login_name, label),
+                    all_updated,
+                    &actions,
+                    &default_account,
+                    &staging_account,
+                    Some(extracted_by),
+                )
+                .map_err(|err| std::io::Error::other(err.to_string()))?;
             }
-            let staging_account =
-                crate::staging::canonical_staging_account(&format!("{login_name}:{label}"));
 
-            all_updated = crate::dedup::apply_dedup_actions_for_login_account(
-                &ledger_dir,
-                (&login_name, &label),
-                all_updated,
-                &actions,
-                &default_account,
-                &staging_account,
-                Some(&format!("{extension_name}:latest")),
-            )
-            .map_err(|err| std::io::Error::other(err.to_string()))?;
-        }
+            crate::posted_changes::write_journal_keeping_posted(&journal_path, &all_updated)?;
 
-        crate::account_journal::write_journal_at_path(&journal_path, &all_updated)?;
+            let known_ids: std::collections::HashSet<&str> =
+                history.iter().map(|entry| entry.id.as_str()).collect();
+            let new_entries: Vec<_> = all_updated
+                .iter()
+                .filter(|entry| !known_ids.contains(entry.id.as_str()))
+                .cloned()
+                .collect();
+            raised_alerts.extend(crate::alerts::evaluate_new_entries(
+                ledger_dir,
+                login_name,
+                label,
+                &history,
+                &new_entries,
+            )?);
+            raised_alerts.extend(crate::alerts::evaluate_coverage_gaps(
+                ledger_dir, login_name, label,
+            )?);
+        }
         Ok(())
     })();
 
     // Write extract log regardless of success/failure so console logs and errors
     // are always persisted for later review.
     let _ = crate::operations::append_extract_log_entry(
-        &ledger_dir,
+        ledger_dir,
         &crate::operations::ExtractLogEntry {
-            login_name: login_name.clone(),
-            label: label.clone(),
+            login_name: login_name.to_string(),
+            label: label.to_string(),
             timestamp: crate::operations::now_timestamp(),
             success: outcome.is_ok(),
             error: outcome.as_ref().err().map(|e| e.to_string()),
@@ -1218,8 +1747,7 @@ fn run_account_extract(
     );
 
     outcome?;
-    println!("Extraction complete. Added {new_count} new transaction(s).");
-    Ok(())
+    Ok((new_count, raised_alerts))
 }
 
 fn run_account_journal(
@@ -1485,7 +2013,7 @@ fn parse_prompt_overrides(
         let Some((message, value)) = entry.split_once('=') else {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                format!("invalid --prompt value '{entry}', expected MESSAGE=VALUE"),
+                format!("invalid --prompt value '{entry}', expected KEY=VALUE"),
             )
             .into());
         };
@@ -1493,7 +2021,7 @@ fn parse_prompt_overrides(
         if message.is_empty() {
             return Err(std::io::Error::new(
                 std::io::ErrorKind::InvalidInput,
-                format!("invalid --prompt value '{entry}', MESSAGE cannot be empty"),
+                format!("invalid --prompt value '{entry}', KEY cannot be empty"),
             )
             .into());
         }
@@ -1544,8 +2072,8 @@ mod tests {
         evidence_ref_matches_document, parse_prompt_overrides, require_cli_existing_login,
         require_cli_label, require_cli_login_name, resolve_extraction_document_names,
         run_extension_load_with_dir, run_gl_add_with_dir, run_new_with_ledger_path, run_secret,
-        AccountCommand, AddArgs, Cli, Commands, ExtensionLoadArgs, LoginCommand, SecretAddArgs,
-        SecretArgs, SecretCommand, SecretListArgs, SecretRemoveArgs,
+        AccountCommand, AddArgs, Cli, Commands, ExtensionLoadArgs, ImportFormat, LoginCommand,
+        SecretAddArgs, SecretArgs, SecretCommand, SecretListArgs, SecretRemoveArgs,
     };
     use crate::ledger::ensure_refreshmint_extension;
     use clap::Parser;
@@ -1679,6 +2207,60 @@ mod tests {
         }
     }
 
+    #[test]
+    fn import_subcommand_parses_qif_options() {
+        let cli = Cli::try_parse_from([
+            "refreshmint",
+            "import",
+            "--format",
+            "qif",
+            "--login",
+            "old-bank",
+            "--label",
+            "checking",
+            "--qif-account",
+            "Checking",
+            "--day-first",
+            "export.qif",
+        ])
+        .unwrap_or_else(|err| panic!("failed to parse import command: {err}"));
+        match cli.command {
+            Some(Commands::Import(args)) => {
+                assert!(matches!(args.format, ImportFormat::Qif));
+                assert_eq!(args.file, PathBuf::from("export.qif"));
+                assert_eq!(args.login.as_deref(), Some("old-bank"));
+                assert_eq!(args.qif_account.as_deref(), Some("Checking"));
+                assert_eq!(args.commodity, "USD");
+                assert!(args.day_first);
+            }
+            _ => panic!("expected import command"),
+        }
+        assert!(
+            Cli::try_parse_from(["refreshmint", "import", "--format", "ofx", "x.ofx"]).is_err()
+        );
+
+        let cli = Cli::try_parse_from([
+            "refreshmint",
+            "import",
+            "--format",
+            "beancount",
+            "--start-date",
+            "2024-01-01",
+            "--dry-run",
+            "main.beancount",
+        ])
+        .unwrap_or_else(|err| panic!("failed to parse book import: {err}"));
+        match cli.command {
+            Some(Commands::Import(args)) => {
+                assert!(matches!(args.format, ImportFormat::Beancount));
+                assert_eq!(args.login, None);
+                assert_eq!(args.start_date.as_deref(), Some("2024-01-01"));
+                assert!(args.dry_run);
+            }
+            _ => panic!("expected import command"),
+        }
+    }
+
     #[test]
     fn account_post_subcommand_parses_posting_index() {
         let cli = Cli::try_parse_from([
@@ -1812,7 +2394,7 @@ mod tests {
         let base_dir = create_temp_dir();
         let ledger_path = base_dir.join("ledger.journal");
 
-        if let Err(err) = run_new_with_ledger_path(ledger_path) {
+        if let Err(err) = run_new_with_ledger_path(ledger_path, None) {
             panic!("run_new_with_ledger_path failed: {err}");
         }
 
@@ -1928,7 +2510,7 @@ mod tests {
         let base_dir = create_temp_dir();
         let ledger_path = base_dir.join("ledger.refreshmint");
 
-        if let Err(err) = run_new_with_ledger_path(ledger_path.clone()) {
+        if let Err(err) = run_new_with_ledger_path(ledger_path.clone(), None) {
             panic!("run_new_with_ledger_path failed: {err}");
         }
 
@@ -1987,7 +2569,7 @@ mod tests {
         let base_dir = create_temp_dir();
         let ledger_path = base_dir.join("ledger.refreshmint");
 
-        if let Err(err) = run_new_with_ledger_path(ledger_path.clone()) {
+        if let Err(err) = run_new_with_ledger_path(ledger_path.clone(), None) {
             panic!("run_new_with_ledger_path failed: {err}");
         }
 
@@ -2143,6 +2725,8 @@ mod tests {
         let config = crate::login_config::LoginConfig {
             extension: Some("chase-driver".to_string()),
             accounts: std::collections::BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         if let Err(err) = crate::login_config::write_login_config(&dir, "chase", &config) {
             panic!("failed to write login config: {err}");
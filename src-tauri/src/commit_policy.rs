@@ -0,0 +1,343 @@
+//! When ledger changes are committed to git.
+//!
+//! By default every operation is its own commit. A ledger that posts many
+//! entries can batch them instead: each operation stages the files it wrote
+//! and its commit message is remembered in `.git/refreshmint-pending.json`.
+//! Under the batched policy one commit takes them all once the oldest pending
+//! change is `minutes` old; there is no timer, so that happens at the next
+//! operation after the interval or when the user saves with
+//! `commit_pending_changes`. Under the manual policy only
+//! `commit_pending_changes` commits.
+//!
+//! The policy is stored in `commit-policy.json` at the ledger root.
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const COMMIT_POLICY_FILE: &str = "commit-policy.json";
+/// Lives in the git directory so it is never committed itself.
+const PENDING_FILE: &str = "refreshmint-pending.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase", tag = "mode")]
+#[ts(export, export_to = "index.ts")]
+pub enum CommitPolicy {
+    /// Commit after every operation.
+    #[default]
+    PerOperation,
+    /// Commit pending operations together once the oldest is `minutes` old.
+    Batched { minutes: u32 },
+    /// Commit only when asked to.
+    Manual,
+}
+
+/// Operations staged since the last commit.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PendingCommit {
+    /// When the oldest pending operation ran, in RFC 3339.
+    since: Option<String>,
+    /// Commit messages of the pending operations, oldest first.
+    messages: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct UncommittedChanges {
+    pub policy: CommitPolicy,
+    /// Summaries of operations that are staged but not committed, oldest first.
+    pub operations: Vec<String>,
+    /// Ledger-relative paths whose staged content differs from the last commit.
+    pub paths: Vec<String>,
+    /// When the oldest pending operation ran.
+    pub since: Option<String>,
+}
+
+pub fn commit_policy_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(COMMIT_POLICY_FILE)
+}
+
+/// The ledger's commit policy. A missing file commits every operation.
+pub fn get_commit_policy(ledger_dir: &Path) -> io::Result<CommitPolicy> {
+    Ok(
+        crate::bookkeeping::read_optional_json(&commit_policy_path(ledger_dir))?
+            .unwrap_or_default(),
+    )
+}
+
+/// Save the policy. Switching back to per-operation commits saves anything
+/// still pending so it is not left behind.
+pub fn save_commit_policy(ledger_dir: &Path, policy: CommitPolicy) -> io::Result<CommitPolicy> {
+    if policy == (CommitPolicy::Batched { minutes: 0 }) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "batched commits need an interval of at least one minute",
+        ));
+    }
+    crate::bookkeeping::write_json(&commit_policy_path(ledger_dir), &policy)?;
+    if policy == CommitPolicy::PerOperation {
+        let repo = open_repo(ledger_dir)?;
+        if !read_pending(&repo)?.messages.is_empty() {
+            commit_pending(&repo, None)?;
+        }
+    }
+    Ok(policy)
+}
+
+/// Called after an operation has staged the files it wrote.
+pub(crate) fn record_operation(
+    ledger_dir: &Path,
+    repo: &git2::Repository,
+    message: &str,
+) -> io::Result<()> {
+    record_operation_at(ledger_dir, repo, message, Utc::now())
+}
+
+fn record_operation_at(
+    ledger_dir: &Path,
+    repo: &git2::Repository,
+    message: &str,
+    now: DateTime<Utc>,
+) -> io::Result<()> {
+    let policy = get_commit_policy(ledger_dir)?;
+    let mut pending = read_pending(repo)?;
+    if policy == CommitPolicy::PerOperation && pending.messages.is_empty() {
+        return crate::ledger::commit_index(repo, message);
+    }
+    let since = match pending.since.as_deref().map(DateTime::parse_from_rfc3339) {
+        Some(Ok(since)) => since.with_timezone(&Utc),
+        _ => now,
+    };
+    pending.since = Some(since.to_rfc3339_opts(chrono::SecondsFormat::Millis, true));
+    pending.messages.push(message.to_string());
+    write_pending(repo, &pending)?;
+    let due = match policy {
+        CommitPolicy::PerOperation => true,
+        CommitPolicy::Batched { minutes } => {
+            now - since >= chrono::Duration::minutes(i64::from(minutes))
+        }
+        CommitPolicy::Manual => false,
+    };
+    if due {
+        commit_pending(repo, None)?;
+    }
+    Ok(())
+}
+
+/// What a "save" would commit.
+pub fn get_uncommitted_changes(ledger_dir: &Path) -> io::Result<UncommittedChanges> {
+    let repo = open_repo(ledger_dir)?;
+    let pending = read_pending(&repo)?;
+    Ok(UncommittedChanges {
+        policy: get_commit_policy(ledger_dir)?,
+        operations: pending
+            .messages
+            .iter()
+            .map(|message| summary_line(message).to_string())
+            .collect(),
+        paths: staged_paths(&repo)?,
+        since: pending.since,
+    })
+}
+
+/// Commit everything staged by pending operations. A blank `message` gets one
+/// that counts the operations; the commit body lists them either way.
+pub fn commit_pending_changes(ledger_dir: &Path, message: &str) -> io::Result<UncommittedChanges> {
+    let repo = open_repo(ledger_dir)?;
+    let message = message.trim();
+    commit_pending(&repo, (!message.is_empty()).then_some(message))?;
+    get_uncommitted_changes(ledger_dir)
+}
+
+fn commit_pending(repo: &git2::Repository, subject: Option<&str>) -> io::Result<()> {
+    let pending = read_pending(repo)?;
+    if !staged_paths(repo)?.is_empty() {
+        crate::ledger::commit_index(repo, &pending_commit_message(&pending.messages, subject))?;
+    }
+    let path = pending_path(repo);
+    if path.exists() {
+        std::fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+fn pending_commit_message(messages: &[String], subject: Option<&str>) -> String {
+    match (subject, messages) {
+        (None, [only]) => only.clone(),
+        (subject, messages) => {
+            let subject = match subject {
+                Some(subject) => subject.to_string(),
+                None if messages.is_empty() => "Save changes".to_string(),
+                None => format!("Save {} changes", messages.len()),
+            };
+            let body: Vec<String> = messages
+                .iter()
+                .map(|message| format!("- {}", summary_line(message)))
+                .collect();
+            if body.is_empty() {
+                subject
+            } else {
+                format!("{subject}\n\n{}", body.join("\n"))
+            }
+        }
+    }
+}
+
+fn summary_line(message: &str) -> &str {
+    message.lines().next().unwrap_or("").trim()
+}
+
+fn staged_paths(repo: &git2::Repository) -> io::Result<Vec<String>> {
+    let head_tree = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let diff = repo
+        .diff_tree_to_index(Some(&head_tree), None, None)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| {
+            delta
+                .new_file()
+                .path()
+                .or_else(|| delta.old_file().path())
+                .map(|path| path.to_string_lossy().into_owned())
+        })
+        .collect())
+}
+
+fn open_repo(ledger_dir: &Path) -> io::Result<git2::Repository> {
+    git2::Repository::open(ledger_dir).map_err(|e| io::Error::other(e.to_string()))
+}
+
+fn pending_path(repo: &git2::Repository) -> PathBuf {
+    repo.path().join(PENDING_FILE)
+}
+
+fn read_pending(repo: &git2::Repository) -> io::Result<PendingCommit> {
+    Ok(crate::bookkeeping::read_optional_json(&pending_path(repo))?.unwrap_or_default())
+}
+
+fn write_pending(repo: &git2::Repository, pending: &PendingCommit) -> io::Result<()> {
+    crate::bookkeeping::write_json(&pending_path(repo), pending)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_ledger(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-commit-policy-{name}-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&dir, None).unwrap();
+        dir
+    }
+
+    fn commit_count(dir: &Path) -> usize {
+        let repo = git2::Repository::open(dir).unwrap();
+        let mut walk = repo.revwalk().unwrap();
+        walk.push_head().unwrap();
+        walk.count()
+    }
+
+    fn head_message(dir: &Path) -> String {
+        let repo = git2::Repository::open(dir).unwrap();
+        let commit = repo.head().unwrap().peel_to_commit().unwrap();
+        commit.message().unwrap().to_string()
+    }
+
+    fn post(dir: &Path, text: &str, message: &str, now: DateTime<Utc>) {
+        fs::write(dir.join("general.journal"), text).unwrap();
+        let repo = git2::Repository::open(dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("general.journal")).unwrap();
+        index.write().unwrap();
+        record_operation_at(dir, &repo, message, now).unwrap();
+    }
+
+    fn at(minute: u32) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(&format!("2026-10-16T12:{minute:02}:00Z"))
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn per_operation_commits_every_change() {
+        let dir = temp_ledger("per-operation");
+        let base = commit_count(&dir);
+        post(&dir, "; a\n", "Post a", at(0));
+        post(&dir, "; b\n", "Post b", at(0));
+        assert_eq!(commit_count(&dir), base + 2);
+        assert_eq!(head_message(&dir), "Post b");
+        assert!(get_uncommitted_changes(&dir).unwrap().paths.is_empty());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn batched_changes_commit_together_after_the_interval() {
+        let dir = temp_ledger("batched");
+        save_commit_policy(&dir, CommitPolicy::Batched { minutes: 5 }).unwrap();
+        let base = commit_count(&dir);
+        post(&dir, "; a\n", "Post a", at(0));
+        post(&dir, "; b\n", "Post b\n\ndetails", at(3));
+
+        let status = get_uncommitted_changes(&dir).unwrap();
+        assert_eq!(status.operations, vec!["Post a", "Post b"]);
+        assert!(status.paths.contains(&"general.journal".to_string()));
+        assert_eq!(status.since.as_deref(), Some("2026-10-16T12:00:00.000Z"));
+        assert_eq!(commit_count(&dir), base);
+
+        post(&dir, "; c\n", "Post c", at(5));
+        assert_eq!(commit_count(&dir), base + 1);
+        assert_eq!(
+            head_message(&dir),
+            "Save 3 changes\n\n- Post a\n- Post b\n- Post c"
+        );
+        let status = get_uncommitted_changes(&dir).unwrap();
+        assert!(status.operations.is_empty());
+        assert!(status.paths.is_empty());
+        assert_eq!(status.since, None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn manual_changes_wait_for_a_save() {
+        let dir = temp_ledger("manual");
+        save_commit_policy(&dir, CommitPolicy::Manual).unwrap();
+        let base = commit_count(&dir);
+        post(&dir, "; a\n", "Post a", at(0));
+        post(&dir, "; b\n", "Post b", at(59));
+        assert_eq!(commit_count(&dir), base);
+
+        let status = commit_pending_changes(&dir, "Review October").unwrap();
+        assert!(status.operations.is_empty());
+        assert_eq!(commit_count(&dir), base + 1);
+        assert_eq!(head_message(&dir), "Review October\n\n- Post a\n- Post b");
+
+        // Nothing pending: saving again makes no empty commit.
+        commit_pending_changes(&dir, "").unwrap();
+        assert_eq!(commit_count(&dir), base + 1);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn switching_back_to_per_operation_saves_pending_changes() {
+        let dir = temp_ledger("switch");
+        save_commit_policy(&dir, CommitPolicy::Manual).unwrap();
+        let base = commit_count(&dir);
+        post(&dir, "; a\n", "Post a", at(0));
+        save_commit_policy(&dir, CommitPolicy::PerOperation).unwrap();
+        assert_eq!(commit_count(&dir), base + 1);
+        assert_eq!(head_message(&dir), "Post a");
+        assert!(save_commit_policy(&dir, CommitPolicy::Batched { minutes: 0 }).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,630 @@
+//! Optional GPG or SSH signatures on ledger commits.
+//!
+//! Signing is configured in `commit-signing.json` at the ledger root; without
+//! it commits are unsigned as before. Signatures are made by the `gpg` or
+//! `ssh-keygen` executable, so keys held by gpg-agent or ssh-agent work
+//! without refreshmint seeing them. `verify_ledger_history` checks that every
+//! commit since the first trusted signature is signed by a trusted key.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const COMMIT_SIGNING_FILE: &str = "commit-signing.json";
+/// Namespace ssh-keygen signs git commits under, as git itself does.
+const SSH_NAMESPACE: &str = "git";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum SigningFormat {
+    Gpg,
+    Ssh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct CommitSigning {
+    pub format: SigningFormat,
+    /// A GPG key id or fingerprint, or an SSH public key given literally
+    /// (`ssh-ed25519 AAAA...`) or as a path relative to the ledger. Literal
+    /// SSH keys and public key files sign through ssh-agent.
+    pub signing_key: String,
+    /// Other keys whose signatures `verify_ledger_history` accepts, such as
+    /// the key of another machine that commits to this ledger.
+    #[serde(default)]
+    pub trusted_keys: Vec<String>,
+    /// The `gpg` or `ssh-keygen` executable to run instead of the one on PATH.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub program: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub enum CommitSignatureStatus {
+    /// No signature, on a commit made after signing began.
+    Unsigned,
+    /// A valid signature by a key that is not trusted.
+    Foreign,
+    /// A signature that does not verify, e.g. because the commit was altered.
+    Invalid,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct FlaggedCommit {
+    pub id: String,
+    pub summary: String,
+    pub time: String,
+    pub status: CommitSignatureStatus,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LedgerHistoryVerification {
+    pub commits_checked: usize,
+    pub trusted_commits: usize,
+    /// Unsigned commits made before the first trusted signature.
+    pub unsigned_before_signing: usize,
+    /// The oldest commit with a trusted signature.
+    pub signing_began: Option<String>,
+    /// Commits that break signature continuity, newest first.
+    pub flagged: Vec<FlaggedCommit>,
+    /// True when nothing was flagged.
+    pub ok: bool,
+}
+
+/// What checking one commit signature found.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SignatureCheck {
+    Trusted,
+    Foreign(String),
+    Invalid(String),
+}
+
+pub fn commit_signing_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(COMMIT_SIGNING_FILE)
+}
+
+/// The ledger's signing configuration, or `None` when commits are unsigned.
+pub fn get_commit_signing(ledger_dir: &Path) -> io::Result<Option<CommitSigning>> {
+    crate::bookkeeping::read_optional_json(&commit_signing_path(ledger_dir))
+}
+
+/// Save the signing configuration; `None` turns signing off.
+pub fn save_commit_signing(
+    ledger_dir: &Path,
+    signing: Option<CommitSigning>,
+) -> io::Result<Option<CommitSigning>> {
+    let path = commit_signing_path(ledger_dir);
+    let Some(mut signing) = signing else {
+        if path.exists() {
+            std::fs::remove_file(&path)?;
+        }
+        return Ok(None);
+    };
+    signing.signing_key = signing.signing_key.trim().to_string();
+    if signing.signing_key.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "commit signing needs a signing key",
+        ));
+    }
+    signing.trusted_keys = signing
+        .trusted_keys
+        .iter()
+        .map(|key| key.trim().to_string())
+        .filter(|key| !key.is_empty())
+        .collect();
+    signing.program = signing
+        .program
+        .map(|program| program.trim().to_string())
+        .filter(|program| !program.is_empty());
+    crate::bookkeeping::write_json(&path, &signing)?;
+    Ok(Some(signing))
+}
+
+impl CommitSigning {
+    fn program(&self) -> &str {
+        self.program.as_deref().unwrap_or(match self.format {
+            SigningFormat::Gpg => "gpg",
+            SigningFormat::Ssh => "ssh-keygen",
+        })
+    }
+
+    /// Sign a commit buffer and return the armored signature.
+    pub(crate) fn sign(&self, ledger_dir: &Path, content: &str) -> io::Result<String> {
+        let mut command = Command::new(self.program());
+        let mut key_file = None;
+        match self.format {
+            SigningFormat::Gpg => {
+                command.args(["--status-fd=2", "-bsau", &self.signing_key]);
+            }
+            SigningFormat::Ssh => {
+                command.args(["-Y", "sign", "-n", SSH_NAMESPACE, "-f"]);
+                if is_literal_ssh_key(&self.signing_key) {
+                    let file = TempFile::write(&self.signing_key)?;
+                    command.arg(&file.0).arg("-U");
+                    key_file = Some(file);
+                } else {
+                    command.arg(resolve_key_path(ledger_dir, &self.signing_key));
+                }
+            }
+        }
+        let output = crate::extract::run_with_stdin(command, content.as_bytes().to_vec())
+            .map_err(|err| io::Error::other(format!("failed to run {}: {err}", self.program())))?;
+        drop(key_file);
+        if !output.status.success() {
+            return Err(io::Error::other(format!(
+                "{} failed to sign the commit: {}",
+                self.program(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8(output.stdout)
+            .map_err(|_| io::Error::other("commit signature is not valid UTF-8"))
+    }
+
+    /// The configured keys as they appear in signatures: public key text for
+    /// SSH, key ids and fingerprints for GPG.
+    fn trusted_keys(&self, ledger_dir: &Path) -> Vec<String> {
+        std::iter::once(&self.signing_key)
+            .chain(&self.trusted_keys)
+            .filter_map(|key| match self.format {
+                SigningFormat::Gpg => Some(key.clone()),
+                SigningFormat::Ssh => ssh_public_key(ledger_dir, key),
+            })
+            .collect()
+    }
+
+    fn check_signature(
+        &self,
+        ledger_dir: &Path,
+        signature: &str,
+        signed_data: &[u8],
+    ) -> io::Result<SignatureCheck> {
+        let signature_file = TempFile::write(signature)?;
+        match self.format {
+            SigningFormat::Gpg => {
+                let mut command = Command::new(self.program());
+                command
+                    .args(["--status-fd=1", "--verify"])
+                    .arg(&signature_file.0)
+                    .arg("-");
+                let output = crate::extract::run_with_stdin(command, signed_data.to_vec())?;
+                let status = String::from_utf8_lossy(&output.stdout);
+                Ok(classify_gpg_status(
+                    &status,
+                    &self.trusted_keys(ledger_dir),
+                    &String::from_utf8_lossy(&output.stderr),
+                ))
+            }
+            SigningFormat::Ssh => {
+                let allowed: String = self
+                    .trusted_keys(ledger_dir)
+                    .iter()
+                    .enumerate()
+                    .map(|(i, key)| format!("key{i} {key}\n"))
+                    .collect();
+                let allowed_file = TempFile::write(&allowed)?;
+                let found = Command::new(self.program())
+                    .args(["-Y", "find-principals", "-f"])
+                    .arg(&allowed_file.0)
+                    .arg("-s")
+                    .arg(&signature_file.0)
+                    .output()?;
+                let principal = String::from_utf8_lossy(&found.stdout)
+                    .lines()
+                    .next()
+                    .map(str::to_string)
+                    .filter(|_| found.status.success());
+                let mut command = Command::new(self.program());
+                match &principal {
+                    Some(principal) => {
+                        command
+                            .args(["-Y", "verify", "-f"])
+                            .arg(&allowed_file.0)
+                            .args(["-I", principal]);
+                    }
+                    None => {
+                        command.args(["-Y", "check-novalidate"]);
+                    }
+                }
+                command
+                    .args(["-n", SSH_NAMESPACE, "-s"])
+                    .arg(&signature_file.0);
+                let output = crate::extract::run_with_stdin(command, signed_data.to_vec())?;
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                Ok(match (output.status.success(), principal) {
+                    (true, Some(_)) => SignatureCheck::Trusted,
+                    (true, None) => {
+                        SignatureCheck::Foreign("signed by an untrusted SSH key".into())
+                    }
+                    (false, _) => SignatureCheck::Invalid(stderr),
+                })
+            }
+        }
+    }
+}
+
+/// Check every commit reachable from HEAD against the configured keys.
+pub fn verify_ledger_history(ledger_dir: &Path) -> io::Result<LedgerHistoryVerification> {
+    let signing = get_commit_signing(ledger_dir)?.ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "commit signing is not configured for this ledger",
+        )
+    })?;
+    let repo = git2::Repository::open(ledger_dir).map_err(|e| io::Error::other(e.to_string()))?;
+    verify_with(&repo, |signature, data| {
+        signing
+            .check_signature(ledger_dir, signature, data)
+            .unwrap_or_else(|err| SignatureCheck::Invalid(err.to_string()))
+    })
+}
+
+fn verify_with(
+    repo: &git2::Repository,
+    check: impl Fn(&str, &[u8]) -> SignatureCheck,
+) -> io::Result<LedgerHistoryVerification> {
+    let mut walk = repo
+        .revwalk()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    walk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    walk.push_head()
+        .map_err(|e| io::Error::other(e.to_string()))?;
+
+    // Oldest first, so the first trusted commit is where signing began.
+    let mut checked = Vec::new();
+    for oid in walk {
+        let oid = oid.map_err(|e| io::Error::other(e.to_string()))?;
+        let result = match repo.extract_signature(&oid, None) {
+            Ok((signature, data)) => Some(match signature.as_str() {
+                Some(signature) => check(signature, &data),
+                None => SignatureCheck::Invalid("signature is not valid UTF-8".to_string()),
+            }),
+            Err(err) if err.code() == git2::ErrorCode::NotFound => None,
+            Err(err) => return Err(io::Error::other(err.to_string())),
+        };
+        checked.push((oid, result));
+    }
+
+    let signing_began = checked
+        .iter()
+        .find(|(_, result)| result.as_ref() == Some(&SignatureCheck::Trusted))
+        .map(|(oid, _)| *oid);
+    let mut trusted_commits = 0;
+    let mut unsigned_before_signing = 0;
+    let mut flagged = Vec::new();
+    for (oid, result) in &checked {
+        let (status, detail) = match result {
+            Some(SignatureCheck::Trusted) => {
+                trusted_commits += 1;
+                continue;
+            }
+            Some(SignatureCheck::Foreign(detail)) => {
+                (CommitSignatureStatus::Foreign, Some(detail.clone()))
+            }
+            Some(SignatureCheck::Invalid(detail)) => {
+                (CommitSignatureStatus::Invalid, Some(detail.clone()))
+            }
+            None => {
+                let before_signing = match signing_began {
+                    Some(began) => repo
+                        .graph_descendant_of(began, *oid)
+                        .map_err(|e| io::Error::other(e.to_string()))?,
+                    None => true,
+                };
+                if before_signing {
+                    unsigned_before_signing += 1;
+                    continue;
+                }
+                (CommitSignatureStatus::Unsigned, None)
+            }
+        };
+        let commit = repo
+            .find_commit(*oid)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        flagged.push(FlaggedCommit {
+            id: oid.to_string(),
+            summary: commit.summary().unwrap_or("").to_string(),
+            time: chrono::DateTime::from_timestamp(commit.time().seconds(), 0)
+                .map(|time| time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+                .unwrap_or_default(),
+            status,
+            detail,
+        });
+    }
+    flagged.reverse();
+
+    Ok(LedgerHistoryVerification {
+        commits_checked: checked.len(),
+        trusted_commits,
+        unsigned_before_signing,
+        signing_began: signing_began.map(|oid| oid.to_string()),
+        ok: flagged.is_empty(),
+        flagged,
+    })
+}
+
+/// Classify `gpg --status-fd` output. A key is trusted when one of `trusted`
+/// is a suffix of the signing key's or primary key's fingerprint, so long key
+/// ids match too.
+fn classify_gpg_status(status: &str, trusted: &[String], stderr: &str) -> SignatureCheck {
+    let normalize = |key: &str| {
+        key.trim()
+            .trim_start_matches("0x")
+            .replace(' ', "")
+            .to_ascii_uppercase()
+    };
+    let trusted: Vec<String> = trusted.iter().map(|key| normalize(key)).collect();
+    for line in status.lines() {
+        let mut fields = line.split_whitespace();
+        if fields.next() != Some("[GNUPG:]") || fields.next() != Some("VALIDSIG") {
+            continue;
+        }
+        let fields: Vec<&str> = fields.collect();
+        let fingerprints = [fields.first(), fields.last()];
+        let is_trusted = fingerprints.iter().flatten().any(|fingerprint| {
+            let fingerprint = normalize(fingerprint);
+            trusted
+                .iter()
+                .any(|key| !key.is_empty() && fingerprint.ends_with(key.as_str()))
+        });
+        let fingerprint = fields.first().copied().unwrap_or("");
+        return if is_trusted {
+            SignatureCheck::Trusted
+        } else {
+            SignatureCheck::Foreign(format!("signed by untrusted GPG key {fingerprint}"))
+        };
+    }
+    SignatureCheck::Invalid(stderr.trim().to_string())
+}
+
+fn is_literal_ssh_key(key: &str) -> bool {
+    key.starts_with("ssh-") || key.starts_with("ecdsa-") || key.starts_with("sk-")
+}
+
+fn resolve_key_path(ledger_dir: &Path, key: &str) -> PathBuf {
+    let path = match key.strip_prefix("~/") {
+        Some(rest) => std::env::var_os("HOME")
+            .map(PathBuf::from)
+            .unwrap_or_default()
+            .join(rest),
+        None => PathBuf::from(key),
+    };
+    ledger_dir.join(path)
+}
+
+/// The public key text for a literal key or a key file. A private key path
+/// falls back to the `.pub` file beside it.
+fn ssh_public_key(ledger_dir: &Path, key: &str) -> Option<String> {
+    if is_literal_ssh_key(key) {
+        return Some(key.to_string());
+    }
+    let path = resolve_key_path(ledger_dir, key);
+    let mut candidates = vec![path.clone()];
+    let mut public = path.into_os_string();
+    public.push(".pub");
+    candidates.push(PathBuf::from(public));
+    candidates
+        .iter()
+        .filter_map(|path| std::fs::read_to_string(path).ok())
+        .map(|text| text.trim().to_string())
+        .find(|text| is_literal_ssh_key(text))
+}
+
+/// A file in the temp directory that is removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn write(contents: &str) -> io::Result<Self> {
+        let path =
+            std::env::temp_dir().join(format!("refreshmint-signing-{}", uuid::Uuid::new_v4()));
+        std::fs::write(&path, contents)?;
+        Ok(Self(path))
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.0);
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_ledger(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-commit-signing-{name}-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&dir, None).unwrap();
+        dir
+    }
+
+    /// Commit a change to general.journal with a fake armored signature.
+    fn commit(dir: &Path, text: &str, signer: Option<&str>) {
+        fs::write(dir.join("general.journal"), text).unwrap();
+        let repo = git2::Repository::open(dir).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("general.journal")).unwrap();
+        index.write().unwrap();
+        let sign = signer.map(|signer| {
+            move |_: &str| {
+                Ok(format!(
+                    "-----BEGIN FAKE SIGNATURE-----\n{signer}\n-----END FAKE SIGNATURE-----"
+                ))
+            }
+        });
+        crate::ledger::commit_index_with(
+            &repo,
+            text,
+            sign.as_ref()
+                .map(|sign| sign as crate::ledger::CommitSigner<'_>),
+        )
+        .unwrap();
+    }
+
+    fn fake_check(signature: &str, _: &[u8]) -> SignatureCheck {
+        if signature.contains("mine") {
+            SignatureCheck::Trusted
+        } else if signature.contains("tampered") {
+            SignatureCheck::Invalid("bad signature".to_string())
+        } else {
+            SignatureCheck::Foreign("untrusted".to_string())
+        }
+    }
+
+    #[test]
+    fn unsigned_and_foreign_commits_after_signing_began_are_flagged() {
+        let dir = temp_ledger("continuity");
+        commit(&dir, "; before\n", None);
+        commit(&dir, "; first signed\n", Some("mine"));
+        commit(&dir, "; gap\n", None);
+        commit(&dir, "; other\n", Some("theirs"));
+        commit(&dir, "; altered\n", Some("tampered"));
+        commit(&dir, "; last\n", Some("mine"));
+
+        let repo = git2::Repository::open(&dir).unwrap();
+        let report = verify_with(&repo, fake_check).unwrap();
+        assert!(!report.ok);
+        // The initial ledger commit and "; before" predate signing.
+        assert_eq!(report.unsigned_before_signing, 2);
+        assert_eq!(report.commits_checked, 7);
+        assert_eq!(report.trusted_commits, 2);
+        let flagged: Vec<(&str, CommitSignatureStatus)> = report
+            .flagged
+            .iter()
+            .map(|commit| (commit.summary.as_str(), commit.status))
+            .collect();
+        assert_eq!(
+            flagged,
+            vec![
+                ("; altered", CommitSignatureStatus::Invalid),
+                ("; other", CommitSignatureStatus::Foreign),
+                ("; gap", CommitSignatureStatus::Unsigned),
+            ]
+        );
+        let began = repo
+            .find_commit(git2::Oid::from_str(report.signing_began.as_deref().unwrap()).unwrap())
+            .unwrap();
+        assert_eq!(began.summary(), Some("; first signed"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn unsigned_history_without_trusted_signatures_is_not_flagged() {
+        let dir = temp_ledger("unsigned");
+        commit(&dir, "; a\n", None);
+        let repo = git2::Repository::open(&dir).unwrap();
+        let report = verify_with(&repo, fake_check).unwrap();
+        assert!(report.ok);
+        assert_eq!(report.signing_began, None);
+        assert_eq!(report.unsigned_before_signing, 2);
+        assert!(verify_ledger_history(&dir).is_err());
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn ssh_signed_commits_verify_end_to_end() {
+        if Command::new("ssh-keygen").arg("-?").output().is_err() {
+            return;
+        }
+        let dir = temp_ledger("ssh");
+        let keys = std::env::temp_dir().join(format!(
+            "refreshmint-commit-signing-keys-{}",
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&keys).unwrap();
+        for name in ["mine", "theirs"] {
+            let status = Command::new("ssh-keygen")
+                .args(["-q", "-t", "ed25519", "-N", "", "-C", name, "-f"])
+                .arg(keys.join(name))
+                .status()
+                .unwrap();
+            assert!(status.success());
+        }
+        let signing = |name: &str| CommitSigning {
+            format: SigningFormat::Ssh,
+            signing_key: keys.join(name).to_string_lossy().into_owned(),
+            trusted_keys: Vec::new(),
+            program: None,
+        };
+
+        save_commit_signing(&dir, Some(signing("mine"))).unwrap();
+        fs::write(dir.join("general.journal"), "; signed\n").unwrap();
+        crate::ledger::commit_general_journal(&dir, "Signed").unwrap();
+        let report = verify_ledger_history(&dir).unwrap();
+        assert!(report.ok, "{report:?}");
+        assert_eq!(report.trusted_commits, 1);
+
+        save_commit_signing(&dir, Some(signing("theirs"))).unwrap();
+        fs::write(dir.join("general.journal"), "; foreign\n").unwrap();
+        crate::ledger::commit_general_journal(&dir, "Foreign").unwrap();
+        save_commit_signing(&dir, Some(signing("mine"))).unwrap();
+        let report = verify_ledger_history(&dir).unwrap();
+        assert_eq!(report.flagged.len(), 1);
+        assert_eq!(report.flagged[0].summary, "Foreign");
+        assert_eq!(report.flagged[0].status, CommitSignatureStatus::Foreign);
+
+        let _ = fs::remove_dir_all(&dir);
+        let _ = fs::remove_dir_all(&keys);
+    }
+
+    #[test]
+    fn gpg_status_matches_trusted_fingerprints_and_key_ids() {
+        let status = "[GNUPG:] NEWSIG\n[GNUPG:] GOODSIG 1234567890ABCDEF Me\n[GNUPG:] VALIDSIG AAAA1111BBBB2222CCCC3333DDDD4444EEEE5555 2026-10-16 0 4 0 22 10 00 FFFF1111BBBB2222CCCC3333DDDD4444EEEE9999\n";
+        assert_eq!(
+            classify_gpg_status(status, &["0xdddd4444eeee5555".to_string()], ""),
+            SignatureCheck::Trusted
+        );
+        assert_eq!(
+            classify_gpg_status(status, &["DDDD 4444 EEEE 9999".to_string()], ""),
+            SignatureCheck::Trusted
+        );
+        assert!(matches!(
+            classify_gpg_status(status, &["0123".to_string()], ""),
+            SignatureCheck::Foreign(_)
+        ));
+        assert_eq!(
+            classify_gpg_status("[GNUPG:] BADSIG 1234 Me\n", &[], "BAD signature"),
+            SignatureCheck::Invalid("BAD signature".to_string())
+        );
+    }
+
+    #[test]
+    fn save_commit_signing_trims_and_clears() {
+        let dir = temp_ledger("save");
+        let saved = save_commit_signing(
+            &dir,
+            Some(CommitSigning {
+                format: SigningFormat::Ssh,
+                signing_key: " ssh-ed25519 AAAA me ".to_string(),
+                trusted_keys: vec![" ".to_string(), "ssh-ed25519 BBBB laptop".to_string()],
+                program: Some(" ".to_string()),
+            }),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(saved.signing_key, "ssh-ed25519 AAAA me");
+        assert_eq!(saved.trusted_keys, vec!["ssh-ed25519 BBBB laptop"]);
+        assert_eq!(saved.program, None);
+        assert_eq!(get_commit_signing(&dir).unwrap(), Some(saved));
+        assert_eq!(save_commit_signing(&dir, None).unwrap(), None);
+        assert_eq!(get_commit_signing(&dir).unwrap(), None);
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
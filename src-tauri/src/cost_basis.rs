@@ -0,0 +1,555 @@
+//! Per-lot cost basis and realized gains for commodities held at cost.
+//!
+//! An asset posting in the general journal that carries a cost
+//! (`0.5 BTC @@ 20010.00 USD`, usually from an exchange entry tagged `cost:`
+//! or `price:`) opens a lot when its quantity is positive and disposes of
+//! lots when it is negative. Postings without a cost, such as a move from an
+//! exchange to a wallet, leave the lots alone, so lots are tracked per
+//! commodity across all accounts. Buys should include fees in their cost and
+//! sells should net fees out of their proceeds.
+//!
+//! Lots are matched first-in first-out or highest-cost first, configured in
+//! `bookkeeping/cost-basis.json` with optional per-commodity overrides.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Months, NaiveDate};
+
+use crate::book_import::Decimal;
+use crate::bookkeeping::{bookkeeping_dir, read_optional_json, write_json};
+
+const COST_BASIS_FILE: &str = "cost-basis.json";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum LotMethod {
+    /// Oldest lots first.
+    #[default]
+    Fifo,
+    /// Lots with the highest unit cost first.
+    Hifo,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct CostBasisConfig {
+    #[serde(default)]
+    pub method: LotMethod,
+    /// Methods for individual commodities, e.g. `{"ETH": "hifo"}`.
+    #[serde(default)]
+    pub overrides: BTreeMap<String, LotMethod>,
+}
+
+impl CostBasisConfig {
+    fn method_for(&self, commodity: &str) -> LotMethod {
+        self.overrides
+            .get(commodity)
+            .copied()
+            .unwrap_or(self.method)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum HoldingTerm {
+    Short,
+    Long,
+}
+
+/// The part of one sale matched against one lot.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct Disposal {
+    pub date: String,
+    /// Acquisition date of the matched lot; `None` when the sale exceeded
+    /// the lots on hand and this part has no known basis.
+    pub acquired: Option<String>,
+    pub gl_txn_id: Option<String>,
+    pub description: String,
+    pub account: String,
+    pub commodity: String,
+    pub quantity: String,
+    pub quote_commodity: String,
+    pub proceeds: String,
+    pub cost_basis: String,
+    pub gain: String,
+    pub term: HoldingTerm,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct OpenLot {
+    pub acquired: String,
+    pub gl_txn_id: Option<String>,
+    pub commodity: String,
+    pub quantity: String,
+    pub quote_commodity: String,
+    pub cost_basis: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct GainTotal {
+    pub quote_commodity: String,
+    pub term: HoldingTerm,
+    pub proceeds: String,
+    pub cost_basis: String,
+    pub gain: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct RealizedGainsReport {
+    pub year: Option<i32>,
+    pub config: CostBasisConfig,
+    pub disposals: Vec<Disposal>,
+    /// Lots still held at the end of the year (or today without a year).
+    pub open_lots: Vec<OpenLot>,
+    pub totals: Vec<GainTotal>,
+    pub warnings: Vec<String>,
+}
+
+/// One GL asset posting held at cost.
+#[derive(Debug, Clone)]
+struct Trade {
+    date: NaiveDate,
+    gl_txn_id: Option<String>,
+    description: String,
+    account: String,
+    commodity: String,
+    /// Positive for acquisitions, negative for disposals.
+    quantity: Decimal,
+    quote_commodity: String,
+    /// Total cost or proceeds in the quote commodity, always positive.
+    total: Decimal,
+}
+
+#[derive(Debug, Clone)]
+struct Lot {
+    acquired: NaiveDate,
+    gl_txn_id: Option<String>,
+    quantity: Decimal,
+    quote_commodity: String,
+    cost: Decimal,
+}
+
+pub fn get_cost_basis_config(ledger_dir: &Path) -> io::Result<CostBasisConfig> {
+    Ok(read_optional_json(&config_path(ledger_dir))?.unwrap_or_default())
+}
+
+pub fn save_cost_basis_config(
+    ledger_dir: &Path,
+    config: CostBasisConfig,
+) -> io::Result<CostBasisConfig> {
+    if config.overrides.keys().any(|c| c.trim().is_empty()) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "cost basis overrides need a commodity",
+        ));
+    }
+    write_json(&config_path(ledger_dir), &config)?;
+    Ok(config)
+}
+
+/// Match disposals against lots across the whole general journal and report
+/// those in `year` (all years when `None`).
+pub fn realized_gains_report(
+    ledger_dir: &Path,
+    year: Option<i32>,
+) -> io::Result<RealizedGainsReport> {
+    let config = get_cost_basis_config(ledger_dir)?;
+    let gl_path = ledger_dir.join("general.journal");
+    let trades = if gl_path.exists() {
+        trades_from_transactions(&crate::ledger_open::run_hledger_print(&gl_path)?)
+    } else {
+        Vec::new()
+    };
+    Ok(build_report(&trades, config, year))
+}
+
+fn build_report(
+    trades: &[Trade],
+    config: CostBasisConfig,
+    year: Option<i32>,
+) -> RealizedGainsReport {
+    let mut lots: BTreeMap<String, Vec<Lot>> = BTreeMap::new();
+    let mut disposals = Vec::new();
+    let mut warnings = Vec::new();
+
+    for trade in trades {
+        if year.is_some_and(|y| trade.date.year() > y) {
+            break;
+        }
+        let held = lots.entry(trade.commodity.clone()).or_default();
+        if !trade.quantity.is_negative() {
+            held.push(Lot {
+                acquired: trade.date,
+                gl_txn_id: trade.gl_txn_id.clone(),
+                quantity: trade.quantity,
+                quote_commodity: trade.quote_commodity.clone(),
+                cost: trade.total,
+            });
+            continue;
+        }
+        let mut trade_warnings = Vec::new();
+        let matched = dispose(
+            held,
+            trade,
+            config.method_for(&trade.commodity),
+            &mut trade_warnings,
+        );
+        if year.map_or(true, |y| trade.date.year() == y) {
+            disposals.extend(matched);
+            warnings.extend(trade_warnings);
+        }
+    }
+
+    let open_lots = lots
+        .into_iter()
+        .flat_map(|(commodity, held)| {
+            held.into_iter().map(move |lot| OpenLot {
+                acquired: format_date(lot.acquired),
+                gl_txn_id: lot.gl_txn_id,
+                commodity: commodity.clone(),
+                quantity: lot.quantity.to_string(),
+                quote_commodity: lot.quote_commodity,
+                cost_basis: lot.cost.to_string(),
+            })
+        })
+        .collect();
+
+    RealizedGainsReport {
+        year,
+        config,
+        totals: totals(&disposals),
+        disposals,
+        open_lots,
+        warnings,
+    }
+}
+
+/// Remove the sold quantity from `held`, splitting the sale's proceeds
+/// across the matched lots in proportion to quantity.
+fn dispose(
+    held: &mut Vec<Lot>,
+    trade: &Trade,
+    method: LotMethod,
+    warnings: &mut Vec<String>,
+) -> Vec<Disposal> {
+    let sold = trade.quantity.abs();
+    let mut remaining = sold;
+    let mut proceeds_left = trade.total;
+    let mut matched = Vec::new();
+
+    while !remaining.is_zero() {
+        let Some(idx) = next_lot(held, method) else {
+            warnings.push(format!(
+                "{}: sold {remaining} {} more than the lots on hand; that part has no cost basis",
+                format_date(trade.date),
+                trade.commodity
+            ));
+            matched.push(disposal(
+                trade,
+                None,
+                remaining,
+                proceeds_left,
+                Decimal::default(),
+            ));
+            break;
+        };
+        let lot = &mut held[idx];
+        if lot.quote_commodity != trade.quote_commodity {
+            warnings.push(format!(
+                "{}: {} lot from {} cost {} but was sold for {}",
+                format_date(trade.date),
+                trade.commodity,
+                format_date(lot.acquired),
+                lot.quote_commodity,
+                trade.quote_commodity
+            ));
+        }
+        let take = if lot.quantity.cmp_value(remaining).is_le() {
+            lot.quantity
+        } else {
+            remaining
+        };
+        let cost = if take == lot.quantity {
+            lot.cost
+        } else {
+            (lot.cost * take)
+                .div_rounded(lot.quantity, lot.cost.scale())
+                .unwrap_or_default()
+        };
+        remaining -= take;
+        let proceeds = if remaining.is_zero() {
+            proceeds_left
+        } else {
+            (trade.total * take)
+                .div_rounded(sold, trade.total.scale())
+                .unwrap_or_default()
+        };
+        proceeds_left -= proceeds;
+        lot.quantity -= take;
+        lot.cost -= cost;
+        let acquired = lot.acquired;
+        if lot.quantity.is_zero() {
+            held.remove(idx);
+        }
+        matched.push(disposal(trade, Some(acquired), take, proceeds, cost));
+    }
+    matched
+}
+
+fn next_lot(held: &[Lot], method: LotMethod) -> Option<usize> {
+    match method {
+        LotMethod::Fifo => (!held.is_empty()).then_some(0),
+        // Compare unit costs by cross-multiplying to avoid rounding.
+        LotMethod::Hifo => (0..held.len()).reduce(|best, idx| {
+            let (a, b) = (&held[idx], &held[best]);
+            if (a.cost * b.quantity).cmp_value(b.cost * a.quantity).is_gt() {
+                idx
+            } else {
+                best
+            }
+        }),
+    }
+}
+
+fn disposal(
+    trade: &Trade,
+    acquired: Option<NaiveDate>,
+    quantity: Decimal,
+    proceeds: Decimal,
+    cost: Decimal,
+) -> Disposal {
+    let long_term = acquired
+        .and_then(|date| date.checked_add_months(Months::new(12)))
+        .is_some_and(|anniversary| trade.date > anniversary);
+    Disposal {
+        date: format_date(trade.date),
+        acquired: acquired.map(format_date),
+        gl_txn_id: trade.gl_txn_id.clone(),
+        description: trade.description.clone(),
+        account: trade.account.clone(),
+        commodity: trade.commodity.clone(),
+        quantity: quantity.to_string(),
+        quote_commodity: trade.quote_commodity.clone(),
+        proceeds: proceeds.to_string(),
+        cost_basis: cost.to_string(),
+        gain: (proceeds - cost).to_string(),
+        term: if long_term {
+            HoldingTerm::Long
+        } else {
+            HoldingTerm::Short
+        },
+    }
+}
+
+fn totals(disposals: &[Disposal]) -> Vec<GainTotal> {
+    let mut sums: BTreeMap<(String, bool), (Decimal, Decimal)> = BTreeMap::new();
+    for d in disposals {
+        let key = (d.quote_commodity.clone(), d.term == HoldingTerm::Long);
+        let sum = sums.entry(key).or_default();
+        sum.0 += Decimal::parse(&d.proceeds).unwrap_or_default();
+        sum.1 += Decimal::parse(&d.cost_basis).unwrap_or_default();
+    }
+    sums.into_iter()
+        .map(|((quote_commodity, long), (proceeds, cost))| GainTotal {
+            quote_commodity,
+            term: if long {
+                HoldingTerm::Long
+            } else {
+                HoldingTerm::Short
+            },
+            proceeds: proceeds.to_string(),
+            cost_basis: cost.to_string(),
+            gain: (proceeds - cost).to_string(),
+        })
+        .collect()
+}
+
+fn trades_from_transactions(txns: &[crate::hledger::Transaction]) -> Vec<Trade> {
+    use crate::hledger::AmountCost;
+
+    let mut trades = Vec::new();
+    for txn in txns {
+        let gl_txn_id = txn
+            .ttags
+            .iter()
+            .find(|(key, _)| key == "id")
+            .map(|(_, value)| value.clone());
+        for posting in &txn.tpostings {
+            if posting.paccount != "Assets" && !posting.paccount.starts_with("Assets:") {
+                continue;
+            }
+            let date = posting.pdate.as_deref().unwrap_or(&txn.tdate);
+            let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                continue;
+            };
+            for amount in &posting.pamount {
+                let (Some(quantity), Some(cost)) = (decimal(amount), amount.acost.as_ref()) else {
+                    continue;
+                };
+                let (cost_amount, total) = match cost {
+                    AmountCost::TotalCost(c) => (c, decimal(c).map(Decimal::abs)),
+                    AmountCost::UnitCost(c) => (c, decimal(c).map(|u| (u * quantity).abs())),
+                };
+                let Some(total) = total else {
+                    continue;
+                };
+                if quantity.is_zero() || cost_amount.acommodity == amount.acommodity {
+                    continue;
+                }
+                trades.push(Trade {
+                    date,
+                    gl_txn_id: gl_txn_id.clone(),
+                    description: txn.tdescription.clone(),
+                    account: posting.paccount.clone(),
+                    commodity: amount.acommodity.clone(),
+                    quantity,
+                    quote_commodity: cost_amount.acommodity.clone(),
+                    total,
+                });
+            }
+        }
+    }
+    // Acquisitions before disposals on the same day, so a same-day buy and
+    // sell match.
+    trades.sort_by_key(|t| (t.date, t.quantity.is_negative()));
+    trades
+}
+
+fn decimal(amount: &crate::hledger::Amount) -> Option<Decimal> {
+    let mantissa = amount
+        .aquantity
+        .decimal_mantissa
+        .to_string()
+        .parse::<i128>()
+        .ok()?;
+    Some(Decimal::from_parts(
+        mantissa,
+        amount.aquantity.decimal_places,
+    ))
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y-%m-%d").to_string()
+}
+
+fn config_path(ledger_dir: &Path) -> PathBuf {
+    bookkeeping_dir(ledger_dir).join(COST_BASIS_FILE)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn trade(date: &str, commodity: &str, quantity: &str, total: &str) -> Trade {
+        Trade {
+            date: NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap(),
+            gl_txn_id: Some(format!("{date}-{quantity}")),
+            description: "Exchange".to_string(),
+            account: "Assets:Exchange:BTC".to_string(),
+            commodity: commodity.to_string(),
+            quantity: Decimal::parse(quantity).unwrap(),
+            quote_commodity: "USD".to_string(),
+            total: Decimal::parse(total).unwrap(),
+        }
+    }
+
+    fn history() -> Vec<Trade> {
+        vec![
+            trade("2022-03-01", "BTC", "1.0", "25000.00"),
+            trade("2023-06-01", "BTC", "1.0", "40000.00"),
+            trade("2023-09-01", "BTC", "-1.5", "39000.00"),
+        ]
+    }
+
+    #[test]
+    fn fifo_splits_a_sale_across_lots_and_terms() {
+        let report = build_report(&history(), CostBasisConfig::default(), Some(2023));
+
+        let rows: Vec<_> = report
+            .disposals
+            .iter()
+            .map(|d| {
+                (
+                    d.acquired.as_deref().unwrap(),
+                    d.quantity.as_str(),
+                    d.proceeds.as_str(),
+                    d.cost_basis.as_str(),
+                    d.gain.as_str(),
+                    d.term,
+                )
+            })
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                (
+                    "2022-03-01",
+                    "1.0",
+                    "26000.00",
+                    "25000.00",
+                    "1000.00",
+                    HoldingTerm::Long
+                ),
+                (
+                    "2023-06-01",
+                    "0.5",
+                    "13000.00",
+                    "20000.00",
+                    "-7000.00",
+                    HoldingTerm::Short
+                ),
+            ]
+        );
+        assert_eq!(report.open_lots.len(), 1);
+        assert_eq!(report.open_lots[0].quantity, "0.5");
+        assert_eq!(report.open_lots[0].cost_basis, "20000.00");
+        assert!(report.warnings.is_empty());
+    }
+
+    #[test]
+    fn hifo_override_takes_the_costliest_lot_and_flags_oversold_quantity() {
+        let config = CostBasisConfig {
+            method: LotMethod::Fifo,
+            overrides: BTreeMap::from([("BTC".to_string(), LotMethod::Hifo)]),
+        };
+        let mut trades = history();
+        trades.push(trade("2024-01-10", "BTC", "-1.0", "45000.00"));
+
+        let report = build_report(&trades, config.clone(), Some(2023));
+        let acquired: Vec<_> = report
+            .disposals
+            .iter()
+            .map(|d| (d.acquired.as_deref().unwrap(), d.cost_basis.as_str()))
+            .collect();
+        assert_eq!(
+            acquired,
+            vec![("2023-06-01", "40000.00"), ("2022-03-01", "12500.00")]
+        );
+        assert_eq!(report.totals[0].term, HoldingTerm::Short);
+        assert_eq!(report.totals[0].gain, "-14000.00");
+        assert_eq!(report.totals[1].gain, "500.00");
+
+        let report = build_report(&trades, config, Some(2024));
+        assert_eq!(report.disposals.len(), 2);
+        assert_eq!(report.disposals[0].proceeds, "22500.00");
+        assert_eq!(report.disposals[1].acquired, None);
+        assert_eq!(report.disposals[1].gain, "22500.00");
+        assert_eq!(report.warnings.len(), 1);
+        assert!(report.open_lots.is_empty());
+    }
+}
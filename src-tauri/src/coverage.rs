@@ -0,0 +1,371 @@
+//! Gaps and overlaps in the periods a login account's documents cover.
+//!
+//! A scrape that misses a month leaves no error behind, only a hole in the
+//! documents. Each login account's documents are split into series by
+//! document type, since monthly statements and rolling CSV exports cover time
+//! differently, and each series is ordered by `coverageEndDate`.
+//!
+//! A document starts where its sidecar's `dateRangeStart` says. Drivers often
+//! set that to the scrape window rather than the document's own period, so a
+//! start equal to `sessionDateRangeStart` is ignored when the session saved
+//! several documents of the type, as is a start shared by documents with
+//! different end dates. Without a start, the series' cadence (the lower
+//! median of the intervals between end dates) stands in: an interval more
+//! than half again as long as the cadence is reported as an estimated gap.
+//! Overlaps are reported only between known starts. For an account with an
+//! opening balance, days between its `coverageStart` and the first
+//! document's stated start are a gap too.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::Path;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::scrape::DocumentType;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export_to = "index.ts")]
+pub enum CoverageIssueKind {
+    Gap,
+    Overlap,
+}
+
+/// Days no document covers, or days two documents both cover.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct CoverageIssue {
+    pub kind: CoverageIssueKind,
+    /// First and last day of the issue, inclusive.
+    pub from: String,
+    pub to: String,
+    #[ts(type = "number")]
+    pub days: i64,
+    /// The documents on either side.
+    pub before: String,
+    pub after: String,
+    /// Whether the range relies on the series' cadence rather than on a
+    /// stated start date.
+    pub estimated: bool,
+}
+
+/// One series of a login account's documents.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct AccountCoverage {
+    pub login_name: String,
+    pub label: String,
+    /// `None` for documents the driver saved without a type.
+    pub document_type: Option<DocumentType>,
+    pub documents: usize,
+    pub first_end: Option<String>,
+    pub last_end: Option<String>,
+    /// Typical days between end dates, once there are enough documents.
+    #[ts(type = "number | null")]
+    pub cadence_days: Option<i64>,
+    /// The login account's `coverageStart`, when it has an opening balance.
+    pub coverage_start: Option<String>,
+    pub issues: Vec<CoverageIssue>,
+}
+
+/// One document's place in a series.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Period {
+    document: String,
+    start: Option<NaiveDate>,
+    end: NaiveDate,
+}
+
+/// Coverage of every login account, or only of `login_name`'s.
+pub fn report_coverage_gaps(
+    ledger_dir: &Path,
+    login_name: Option<&str>,
+) -> io::Result<Vec<AccountCoverage>> {
+    let logins = match login_name {
+        Some(login) => vec![login.to_string()],
+        None => crate::login_config::list_logins(ledger_dir)?,
+    };
+    let mut report = Vec::new();
+    for login in logins {
+        for label in crate::login_config::list_login_labels(ledger_dir, &login)? {
+            report.extend(login_account_coverage(ledger_dir, &login, &label)?);
+        }
+    }
+    Ok(report)
+}
+
+/// Coverage of one login account, a series per document type.
+pub fn login_account_coverage(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+) -> io::Result<Vec<AccountCoverage>> {
+    let documents: Vec<(String, crate::scrape::DocumentInfo)> =
+        crate::extract::list_documents_for_login_account(ledger_dir, login_name, label)?
+            .into_iter()
+            .filter_map(|doc| Some((doc.filename, doc.info?)))
+            .filter(|(_, info)| {
+                // One-off documents and enrichment, not a sequence.
+                !matches!(
+                    info.document_type,
+                    Some(
+                        DocumentType::Confirmation
+                            | DocumentType::TaxForm
+                            | DocumentType::TransactionDetails
+                    )
+                )
+            })
+            .collect();
+    let mut per_session: BTreeMap<(Option<DocumentType>, &str), usize> = BTreeMap::new();
+    for (_, info) in &documents {
+        *per_session
+            .entry((info.document_type, info.scrape_session_id.as_str()))
+            .or_default() += 1;
+    }
+    let mut series: BTreeMap<Option<DocumentType>, Vec<Period>> = BTreeMap::new();
+    for (filename, info) in &documents {
+        let Some(end) = parse_date(&info.coverage_end_date) else {
+            continue;
+        };
+        let session_key = (info.document_type, info.scrape_session_id.as_str());
+        let session_documents = per_session[&session_key];
+        series.entry(info.document_type).or_default().push(Period {
+            document: filename.clone(),
+            start: stated_start(info, session_documents),
+            end,
+        });
+    }
+    let coverage_start = crate::login_config::read_login_config(ledger_dir, login_name)
+        .accounts
+        .get(label)
+        .and_then(|account| account.coverage_start.clone());
+    Ok(series
+        .into_iter()
+        .map(|(document_type, periods)| {
+            let documents = periods.len();
+            let (periods, cadence, mut issues) = analyze(periods);
+            if let Some(gap) = coverage_start
+                .as_deref()
+                .and_then(parse_date)
+                .zip(periods.first())
+                .and_then(|(start, first)| gap_before_first(start, first))
+            {
+                issues.insert(0, gap);
+            }
+            AccountCoverage {
+                login_name: login_name.to_string(),
+                label: label.to_string(),
+                document_type,
+                documents,
+                first_end: periods.first().map(|p| p.end.to_string()),
+                last_end: periods.last().map(|p| p.end.to_string()),
+                cadence_days: cadence,
+                coverage_start: coverage_start.clone(),
+                issues,
+            }
+        })
+        .collect())
+}
+
+/// Order a series, collapse documents with the same end date, and find its
+/// cadence and issues.
+fn analyze(mut periods: Vec<Period>) -> (Vec<Period>, Option<i64>, Vec<CoverageIssue>) {
+    let mut ends_by_start: HashMap<NaiveDate, Vec<NaiveDate>> = HashMap::new();
+    for period in &periods {
+        if let Some(start) = period.start {
+            ends_by_start.entry(start).or_default().push(period.end);
+        }
+    }
+    for period in &mut periods {
+        let shared = period
+            .start
+            .is_some_and(|start| ends_by_start[&start].iter().any(|end| *end != period.end));
+        if shared || period.start.is_some_and(|start| start > period.end) {
+            period.start = None;
+        }
+    }
+
+    periods.sort_by(|a, b| a.end.cmp(&b.end).then_with(|| a.start.cmp(&b.start)));
+    periods.dedup_by(|later, earlier| {
+        if later.end != earlier.end {
+            return false;
+        }
+        earlier.start = match (earlier.start, later.start) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        };
+        true
+    });
+
+    let mut intervals: Vec<i64> = periods
+        .windows(2)
+        .map(|pair| (pair[1].end - pair[0].end).num_days())
+        .collect();
+    intervals.sort_unstable();
+    let cadence = (intervals.len() >= 2).then(|| intervals[(intervals.len() - 1) / 2]);
+
+    let mut issues = Vec::new();
+    for pair in periods.windows(2) {
+        let (before, after) = (&pair[0], &pair[1]);
+        let day_after = before.end + Duration::days(1);
+        let issue = |kind, from: NaiveDate, to: NaiveDate, estimated| CoverageIssue {
+            kind,
+            from: from.to_string(),
+            to: to.to_string(),
+            days: (to - from).num_days() + 1,
+            before: before.document.clone(),
+            after: after.document.clone(),
+            estimated,
+        };
+        match (after.start, cadence) {
+            (Some(start), _) if start > day_after => {
+                issues.push(issue(
+                    CoverageIssueKind::Gap,
+                    day_after,
+                    start - Duration::days(1),
+                    false,
+                ));
+            }
+            (Some(start), _) if start <= before.end => {
+                issues.push(issue(CoverageIssueKind::Overlap, start, before.end, false));
+            }
+            (Some(_), _) => {}
+            (None, Some(cadence)) => {
+                let interval = (after.end - before.end).num_days();
+                if interval * 2 > cadence * 3 {
+                    issues.push(issue(
+                        CoverageIssueKind::Gap,
+                        day_after,
+                        after.end - Duration::days(cadence),
+                        true,
+                    ));
+                }
+            }
+            (None, None) => {}
+        }
+    }
+    (periods, cadence, issues)
+}
+
+/// Days between the coverage start and the first document's stated start.
+fn gap_before_first(coverage_start: NaiveDate, first: &Period) -> Option<CoverageIssue> {
+    let start = first.start.filter(|start| *start > coverage_start)?;
+    let to = start - Duration::days(1);
+    Some(CoverageIssue {
+        kind: CoverageIssueKind::Gap,
+        from: coverage_start.to_string(),
+        to: to.to_string(),
+        days: (to - coverage_start).num_days() + 1,
+        before: "coverage start".to_string(),
+        after: first.document.clone(),
+        estimated: false,
+    })
+}
+
+/// The start a sidecar states for its document. A start copied from the
+/// session's window covers the window, not the document, unless the session
+/// saved only this document of its type.
+fn stated_start(info: &crate::scrape::DocumentInfo, session_documents: usize) -> Option<NaiveDate> {
+    let start = info.date_range_start.as_deref()?;
+    let from_session = info.session_date_range_start.as_deref() == Some(start);
+    if from_session && session_documents > 1 {
+        return None;
+    }
+    parse_date(start)
+}
+
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn period(document: &str, start: Option<&str>, end: &str) -> Period {
+        Period {
+            document: document.to_string(),
+            start: start.map(|start| parse_date(start).unwrap()),
+            end: parse_date(end).unwrap(),
+        }
+    }
+
+    fn summary(issues: &[CoverageIssue]) -> Vec<(CoverageIssueKind, &str, &str, bool)> {
+        issues
+            .iter()
+            .map(|i| (i.kind, i.from.as_str(), i.to.as_str(), i.estimated))
+            .collect()
+    }
+
+    #[test]
+    fn stated_starts_show_gaps_and_overlaps() {
+        let (periods, _, issues) = analyze(vec![
+            period("mar.pdf", Some("2025-03-05"), "2025-03-31"),
+            period("jan.pdf", Some("2025-01-01"), "2025-01-31"),
+            period("feb.pdf", Some("2025-01-25"), "2025-02-28"),
+            period("feb-copy.pdf", Some("2025-02-01"), "2025-02-28"),
+        ]);
+        assert_eq!(periods.len(), 3);
+        assert_eq!(
+            summary(&issues),
+            vec![
+                (
+                    CoverageIssueKind::Overlap,
+                    "2025-01-25",
+                    "2025-01-31",
+                    false
+                ),
+                (CoverageIssueKind::Gap, "2025-03-01", "2025-03-04", false),
+            ]
+        );
+        assert_eq!(issues[1].days, 4);
+        assert_eq!(issues[1].before, "feb.pdf");
+        assert_eq!(issues[1].after, "mar.pdf");
+    }
+
+    #[test]
+    fn missing_months_are_estimated_from_the_cadence() {
+        let (_, cadence, issues) = analyze(vec![
+            period("jan.pdf", None, "2025-01-31"),
+            period("feb.pdf", None, "2025-02-28"),
+            period("mar.pdf", None, "2025-03-31"),
+            period("may.pdf", None, "2025-05-31"),
+            period("jun.pdf", None, "2025-06-30"),
+        ]);
+        assert_eq!(cadence, Some(30));
+        assert_eq!(
+            summary(&issues),
+            vec![(CoverageIssueKind::Gap, "2025-04-01", "2025-05-01", true)]
+        );
+    }
+
+    #[test]
+    fn a_start_shared_across_end_dates_is_a_scrape_window() {
+        let (_, cadence, issues) = analyze(vec![
+            period("jan.pdf", Some("2024-06-01"), "2025-01-31"),
+            period("feb.pdf", Some("2024-06-01"), "2025-02-28"),
+        ]);
+        assert_eq!(cadence, None);
+        assert!(issues.is_empty());
+    }
+
+    #[test]
+    fn days_after_the_coverage_start_before_the_first_document_are_a_gap() {
+        let first = period("feb.pdf", Some("2025-02-01"), "2025-02-28");
+        let gap = gap_before_first(parse_date("2025-01-15").unwrap(), &first).unwrap();
+        assert_eq!(
+            (gap.from.as_str(), gap.to.as_str(), gap.days),
+            ("2025-01-15", "2025-01-31", 17)
+        );
+        assert_eq!(gap.before, "coverage start");
+        assert_eq!(
+            gap_before_first(parse_date("2025-02-01").unwrap(), &first),
+            None
+        );
+    }
+}
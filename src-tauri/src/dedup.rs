@@ -2,7 +2,7 @@ use crate::account_journal::{AccountEntry, EntryStatus, SimpleAmount};
 use crate::extract::ExtractedTransaction;
 use crate::operations;
 
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
 use std::path::Path;
 
 #[derive(Default)]
@@ -40,7 +40,7 @@ fn build_attachment_index_for_account(ledger_dir: &Path, account_name: &str) ->
     match crate::extract::list_documents(ledger_dir, account_name) {
         Ok(docs) => attachment_index_from_documents(&docs),
         Err(err) => {
-            eprintln!("warning: failed to index account attachments: {err}");
+            tracing::warn!("failed to index account attachments: {err}");
             AttachmentIndex::default()
         }
     }
@@ -54,7 +54,7 @@ fn build_attachment_index_for_login_account(
     match crate::extract::list_documents_for_login_account(ledger_dir, login_name, label) {
         Ok(docs) => attachment_index_from_documents(&docs),
         Err(err) => {
-            eprintln!("warning: failed to index login account attachments: {err}");
+            tracing::warn!("failed to index login account attachments: {err}");
             AttachmentIndex::default()
         }
     }
@@ -158,6 +158,23 @@ impl Default for DedupConfig {
     }
 }
 
+impl DedupConfig {
+    /// Tolerances for a login account of the given type. Brokerage and plan
+    /// portals post final amounts but may date an entry by trade or
+    /// settlement day, so they match exact amounts over a wider date window.
+    pub fn for_account_type(account_type: Option<crate::login_config::AccountType>) -> Self {
+        match account_type {
+            Some(t) if !t.settles_with_amount_changes() => Self {
+                date_tolerance_days: 3,
+                pending_finalized_amount_abs: 0.0,
+                pending_finalized_amount_pct: 0.0,
+                ..Self::default()
+            },
+            _ => Self::default(),
+        }
+    }
+}
+
 /// Run dedup on a set of proposed transactions against existing account journal entries.
 ///
 /// Returns a list of `DedupAction` describing what to do for each proposed transaction.
@@ -167,12 +184,21 @@ pub fn run_dedup(
     source_document: &str,
     config: &DedupConfig,
 ) -> Vec<DedupAction> {
+    let _span = crate::perf::span("dedup", "run_dedup");
     let mut actions = Vec::new();
     // Track which existing entries have been matched (one-time consumption).
     let mut matched_existing: Vec<bool> = vec![false; existing.len()];
+    let index = ExistingIndex::new(existing);
 
     for txn in proposed {
-        let result = match_proposed(existing, txn, source_document, config, &matched_existing);
+        let result = match_proposed(
+            existing,
+            &index,
+            txn,
+            source_document,
+            config,
+            &matched_existing,
+        );
         match &result {
             DedupResult::SameEvidence { existing_index, .. }
             | DedupResult::BankIdMatch { existing_index }
@@ -210,6 +236,7 @@ pub fn apply_dedup_actions(
     extracted_by: Option<&str>,
 ) -> Result<Vec<AccountEntry>, Box<dyn std::error::Error + Send + Sync>> {
     let attachment_index = build_attachment_index_for_account(ledger_dir, account_name);
+    let recorded = operations::read_account_operations(ledger_dir, account_name)?;
     apply_dedup_actions_with_logger(
         entries,
         actions,
@@ -217,6 +244,7 @@ pub fn apply_dedup_actions(
         staging_account,
         extracted_by,
         Some(&attachment_index),
+        recorded,
         |op| operations::append_account_operation(ledger_dir, account_name, op),
     )
 }
@@ -233,6 +261,7 @@ pub fn apply_dedup_actions_for_login_account(
 ) -> Result<Vec<AccountEntry>, Box<dyn std::error::Error + Send + Sync>> {
     let (login_name, label) = login_account;
     let attachment_index = build_attachment_index_for_login_account(ledger_dir, login_name, label);
+    let recorded = operations::read_login_account_operations(ledger_dir, login_name, label)?;
     apply_dedup_actions_with_logger(
         entries,
         actions,
@@ -240,6 +269,7 @@ pub fn apply_dedup_actions_for_login_account(
         staging_account,
         extracted_by,
         Some(&attachment_index),
+        recorded,
         |op| operations::append_login_account_operation(ledger_dir, login_name, label, op),
     )
 }
@@ -251,19 +281,38 @@ fn apply_dedup_actions_with_logger<F>(
     staging_account: &str,
     extracted_by: Option<&str>,
     attachment_index: Option<&AttachmentIndex>,
+    mut recorded: Vec<operations::AccountOperation>,
     mut log_operation: F,
 ) -> Result<Vec<AccountEntry>, Box<dyn std::error::Error + Send + Sync>>
 where
     F: FnMut(&operations::AccountOperation) -> std::io::Result<()>,
 {
+    let mut taken_ids: HashSet<String> = entries.iter().map(|entry| entry.id.clone()).collect();
     for action in actions {
+        let matched_index = match &action.result {
+            DedupResult::SameEvidence { existing_index, .. }
+            | DedupResult::BankIdMatch { existing_index }
+            | DedupResult::FuzzyMatch { existing_index }
+            | DedupResult::PendingToFinalized { existing_index } => Some(*existing_index),
+            DedupResult::New | DedupResult::Ambiguous { .. } => None,
+        };
+        // Posted entries only gain evidence here; see `posted_changes`.
+        let posted_before = matched_index
+            .filter(|&i| entries[i].is_posted())
+            .map(|i| entries[i].clone());
         match &action.result {
             DedupResult::SameEvidence {
                 existing_index,
                 updated,
             } => {
-                if *updated {
-                    update_entry_from_proposed(&mut entries[*existing_index], &action.proposed);
+                if *updated && is_split_part(&entries[*existing_index]) {
+                    entries[*existing_index].status = action.proposed.status();
+                } else if *updated {
+                    update_entry_from_proposed(
+                        &mut entries[*existing_index],
+                        &action.proposed,
+                        staging_account,
+                    );
                 }
                 for ev in action.proposed.evidence_refs() {
                     entries[*existing_index].add_evidence(ev);
@@ -296,19 +345,28 @@ where
                 {
                     entries[*existing_index].comment = action.proposed.tcomment.clone();
                 }
-                if !amounts_equal(
-                    &entry_primary_amount(&entries[*existing_index]),
-                    &txn_primary_amount(&action.proposed),
-                ) {
+                if !is_split_part(&entries[*existing_index])
+                    && !amounts_equal(
+                        &entry_primary_amount(&entries[*existing_index]),
+                        &txn_primary_amount(&action.proposed),
+                    )
+                {
                     update_entry_amount_from_proposed(
                         &mut entries[*existing_index],
                         &action.proposed,
+                        staging_account,
                     );
                 }
             }
             DedupResult::PendingToFinalized { existing_index } => {
                 entries[*existing_index].status = EntryStatus::Cleared;
-                update_entry_from_proposed(&mut entries[*existing_index], &action.proposed);
+                if !is_split_part(&entries[*existing_index]) {
+                    update_entry_from_proposed(
+                        &mut entries[*existing_index],
+                        &action.proposed,
+                        staging_account,
+                    );
+                }
                 for ev in action.proposed.evidence_refs() {
                     entries[*existing_index].add_evidence(ev);
                 }
@@ -327,6 +385,7 @@ where
                 if let Some(eb) = extracted_by {
                     entry.extracted_by = Some(eb.to_string());
                 }
+                crate::entry_id::assign_entry_id(&mut entry, &mut taken_ids);
                 if let Some(index) = attachment_index {
                     add_attachment_evidence_refs(&mut entry, &action.proposed, index);
                 }
@@ -348,19 +407,128 @@ where
                 entries.push(entry);
             }
             DedupResult::Ambiguous { .. } => {
-                eprintln!(
+                tracing::warn!(
                     "Ambiguous match for transaction: {} {}",
-                    action.proposed.tdate, action.proposed.tdescription
+                    action.proposed.tdate,
+                    action.proposed.tdescription
                 );
             }
         }
+        if let (Some(i), Some(before)) = (matched_index, posted_before) {
+            let entry = &mut entries[i];
+            let changes = crate::posted_changes::keep_posted_content(entry, before);
+            let posted = crate::posted_changes::gl_ref(entry);
+            // A conflict leaves the entry as it was, so every extraction of
+            // the same document finds it again; record it once.
+            if !changes.is_empty()
+                && !crate::posted_changes::is_recorded_conflict(
+                    &recorded, &entry.id, &posted, &changes,
+                )
+            {
+                tracing::warn!(
+                    "Not applying extracted changes to posted entry {} ({}): {} {}",
+                    entry.id,
+                    posted,
+                    action.proposed.tdate,
+                    action.proposed.tdescription
+                );
+                let op = operations::AccountOperation::PostedEntryConflict {
+                    entry_id: entry.id.clone(),
+                    posted,
+                    proposed_evidence: action.proposed.evidence_refs(),
+                    changes,
+                    timestamp: operations::now_timestamp(),
+                };
+                log_operation(&op)?;
+                recorded.push(op);
+            }
+        }
     }
 
     Ok(entries)
 }
 
+/// Lookup tables over existing entries so each proposed transaction only
+/// visits entries that can match it, rather than the whole journal.
+struct ExistingIndex<'a> {
+    by_evidence: HashMap<&'a str, Vec<usize>>,
+    by_bank_id: HashMap<&'a str, Vec<usize>>,
+    /// Entries with a parseable date; others can never be within tolerance.
+    by_date: BTreeMap<chrono::NaiveDate, Vec<usize>>,
+    /// Summed amount of each split's parts, by the original entry id.
+    split_totals: HashMap<&'a str, f64>,
+}
+
+impl<'a> ExistingIndex<'a> {
+    fn new(existing: &'a [AccountEntry]) -> Self {
+        let mut index = Self {
+            by_evidence: HashMap::new(),
+            by_bank_id: HashMap::new(),
+            by_date: BTreeMap::new(),
+            split_totals: HashMap::new(),
+        };
+        for (i, entry) in existing.iter().enumerate() {
+            for ev in &entry.evidence {
+                let indices = index.by_evidence.entry(ev.as_str()).or_default();
+                if indices.last() != Some(&i) {
+                    indices.push(i);
+                }
+            }
+            if let Some(bank_id) = entry.bank_id() {
+                index.by_bank_id.entry(bank_id).or_default().push(i);
+            }
+            if let Some(date) = parse_date(&entry.date) {
+                index.by_date.entry(date).or_default().push(i);
+            }
+            if let Some(split_from) = entry.tag_value(crate::entry_split::SPLIT_FROM_TAG) {
+                *index.split_totals.entry(split_from).or_default() +=
+                    entry_primary_amount(entry).unwrap_or(0.0);
+            }
+        }
+        index
+    }
+
+    /// The amount a source row must have to be `entry`: for a split part,
+    /// the amount of the entry it was split from.
+    fn match_amount(&self, entry: &AccountEntry) -> Option<f64> {
+        match entry.tag_value(crate::entry_split::SPLIT_FROM_TAG) {
+            Some(split_from) => self.split_totals.get(split_from).copied(),
+            None => entry_primary_amount(entry),
+        }
+    }
+
+    /// Indices of entries dated within `tolerance_days` of `date`, ascending.
+    fn within_days(&self, date: &str, tolerance_days: i64) -> Vec<usize> {
+        let Some(date) = parse_date(date) else {
+            return Vec::new();
+        };
+        if tolerance_days < 0 {
+            return Vec::new();
+        }
+        let tolerance = chrono::TimeDelta::try_days(tolerance_days);
+        let start = tolerance
+            .and_then(|tolerance| date.checked_sub_signed(tolerance))
+            .unwrap_or(chrono::NaiveDate::MIN);
+        let end = tolerance
+            .and_then(|tolerance| date.checked_add_signed(tolerance))
+            .unwrap_or(chrono::NaiveDate::MAX);
+        let mut indices: Vec<usize> = self
+            .by_date
+            .range(start..=end)
+            .flat_map(|(_, indices)| indices.iter().copied())
+            .collect();
+        indices.sort_unstable();
+        indices
+    }
+}
+
+fn parse_date(date: &str) -> Option<chrono::NaiveDate> {
+    chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()
+}
+
 fn match_proposed(
     existing: &[AccountEntry],
+    index: &ExistingIndex<'_>,
     txn: &ExtractedTransaction,
     source_document: &str,
     config: &DedupConfig,
@@ -368,37 +536,31 @@ fn match_proposed(
 ) -> DedupResult {
     let evidence_refs = txn.evidence_refs();
 
-    // Step 1: Same-evidence match
-    for (i, entry) in existing.iter().enumerate() {
-        if matched[i] {
-            continue;
-        }
-        for ev in &evidence_refs {
-            if entry.has_evidence(ev) {
-                let updated = has_content_changed(entry, txn);
-                return DedupResult::SameEvidence {
-                    existing_index: i,
-                    updated,
-                };
-            }
-        }
+    // Step 1: Same-evidence match (the first unmatched entry carrying any of
+    // the proposed evidence refs)
+    let same_evidence = evidence_refs
+        .iter()
+        .filter_map(|ev| index.by_evidence.get(ev.as_str()))
+        .filter_map(|indices| indices.iter().copied().find(|&i| !matched[i]))
+        .min();
+    if let Some(i) = same_evidence {
+        return DedupResult::SameEvidence {
+            existing_index: i,
+            updated: has_content_changed(&existing[i], txn),
+        };
     }
 
     // Step 2: Exact match by bankId (across other documents)
     if let Some(bank_id) = txn.bank_id() {
-        let mut candidates = Vec::new();
-        for (i, entry) in existing.iter().enumerate() {
-            if matched[i] {
-                continue;
-            }
-            // Only match across different documents
-            if entry_is_from_same_document(entry, source_document) {
-                continue;
-            }
-            if entry.bank_id() == Some(bank_id) {
-                candidates.push(i);
-            }
-        }
+        let candidates: Vec<usize> = index
+            .by_bank_id
+            .get(bank_id)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&i| !matched[i] && !entry_is_from_same_document(&existing[i], source_document))
+            .collect();
+        let candidates = one_per_split(existing, candidates);
         if candidates.len() == 1 {
             return DedupResult::BankIdMatch {
                 existing_index: candidates[0],
@@ -412,26 +574,20 @@ fn match_proposed(
     }
 
     // Step 3: Fuzzy match (across other documents)
-    let mut fuzzy_candidates = Vec::new();
     let txn_amount = txn_primary_amount(txn);
-
-    for (i, entry) in existing.iter().enumerate() {
-        if matched[i] {
-            continue;
-        }
-        if entry_is_from_same_document(entry, source_document) {
-            continue;
-        }
-        if !dates_within_tolerance(&entry.date, &txn.tdate, config.date_tolerance_days) {
-            continue;
-        }
-        let entry_amount = entry_primary_amount(entry);
-        if amounts_equal(&entry_amount, &txn_amount)
-            && descriptions_similar(&entry.description, &txn.tdescription)
-        {
-            fuzzy_candidates.push(i);
-        }
-    }
+    let fuzzy_candidates: Vec<usize> = index
+        .within_days(&txn.tdate, config.date_tolerance_days)
+        .into_iter()
+        .filter(|&i| {
+            let entry = &existing[i];
+            !matched[i]
+                && !entry_is_from_same_document(entry, source_document)
+                && account_ids_compatible(entry, txn)
+                && amounts_equal(&index.match_amount(entry), &txn_amount)
+                && descriptions_similar(entry_raw_description(entry), txn_raw_description(txn))
+        })
+        .collect();
+    let fuzzy_candidates = one_per_split(existing, fuzzy_candidates);
 
     if fuzzy_candidates.len() == 1 {
         return DedupResult::FuzzyMatch {
@@ -441,29 +597,23 @@ fn match_proposed(
 
     // Step 4: Pending→finalized
     if txn.status() == EntryStatus::Cleared {
-        let mut pending_candidates = Vec::new();
-        for (i, entry) in existing.iter().enumerate() {
-            if matched[i] {
-                continue;
-            }
-            if entry.status != EntryStatus::Pending {
-                continue;
-            }
-            if entry_is_from_same_document(entry, source_document) {
-                continue;
-            }
-            if !dates_within_tolerance(&entry.date, &txn.tdate, config.pending_finalized_days) {
-                continue;
-            }
-            if amounts_within_tolerance(
-                &entry_primary_amount(entry),
-                &txn_amount,
-                config.pending_finalized_amount_abs,
-                config.pending_finalized_amount_pct,
-            ) {
-                pending_candidates.push(i);
-            }
-        }
+        let pending_candidates: Vec<usize> = index
+            .within_days(&txn.tdate, config.pending_finalized_days)
+            .into_iter()
+            .filter(|&i| {
+                let entry = &existing[i];
+                !matched[i]
+                    && entry.status == EntryStatus::Pending
+                    && !entry_is_from_same_document(entry, source_document)
+                    && account_ids_compatible(entry, txn)
+                    && amounts_within_tolerance(
+                        &index.match_amount(entry),
+                        &txn_amount,
+                        config.pending_finalized_amount_abs,
+                        config.pending_finalized_amount_pct,
+                    )
+            })
+            .collect();
         if pending_candidates.len() == 1 {
             return DedupResult::PendingToFinalized {
                 existing_index: pending_candidates[0],
@@ -482,6 +632,35 @@ fn match_proposed(
     DedupResult::New
 }
 
+/// Transactions tagged with different institution account ids are never
+/// the same transaction, however alike they look; a masked id matches the
+/// full number it ends.
+fn account_ids_compatible(entry: &AccountEntry, txn: &ExtractedTransaction) -> bool {
+    let tag = crate::account_routing::ACCOUNT_ID_TAG;
+    let proposed = txn.ttags.iter().find(|(k, _)| k == tag).map(|(_, v)| v);
+    match (entry.tag_value(tag), proposed) {
+        (Some(existing), Some(proposed)) => {
+            crate::account_routing::account_ids_match(existing, proposed)
+        }
+        _ => true,
+    }
+}
+
+/// The description an entry had before description cleaning, which dedup
+/// compares so that changing the cleaning steps does not create duplicates.
+fn entry_raw_description(entry: &AccountEntry) -> &str {
+    entry
+        .tag_value(crate::description_cleaning::RAW_DESCRIPTION_TAG)
+        .unwrap_or(&entry.description)
+}
+
+fn txn_raw_description(txn: &ExtractedTransaction) -> &str {
+    txn.ttags
+        .iter()
+        .find(|(key, _)| key == crate::description_cleaning::RAW_DESCRIPTION_TAG)
+        .map_or(&txn.tdescription, |(_, value)| value)
+}
+
 fn entry_is_from_same_document(entry: &AccountEntry, source_document: &str) -> bool {
     entry.evidence.iter().any(|ev| {
         ev.starts_with(source_document)
@@ -492,7 +671,33 @@ fn entry_is_from_same_document(entry: &AccountEntry, source_document: &str) -> b
     })
 }
 
+/// Keep only the first part of each split, so the parts count as one
+/// candidate.
+fn one_per_split(existing: &[AccountEntry], candidates: Vec<usize>) -> Vec<usize> {
+    let mut seen_splits = HashSet::new();
+    candidates
+        .into_iter()
+        .filter(
+            |&i| match existing[i].tag_value(crate::entry_split::SPLIT_FROM_TAG) {
+                Some(split_from) => seen_splits.insert(split_from),
+                None => true,
+            },
+        )
+        .collect()
+}
+
+fn is_split_part(entry: &AccountEntry) -> bool {
+    entry
+        .tag_value(crate::entry_split::SPLIT_FROM_TAG)
+        .is_some()
+}
+
+/// Whether the source row differs from `entry`. Split parts only track the
+/// row's status; their descriptions and amounts are the user's.
 fn has_content_changed(entry: &AccountEntry, txn: &ExtractedTransaction) -> bool {
+    if is_split_part(entry) {
+        return entry.status != txn.status();
+    }
     if entry.description != txn.tdescription {
         return true;
     }
@@ -516,16 +721,29 @@ fn is_more_finalized(new_status: &EntryStatus, old_status: &EntryStatus) -> bool
     )
 }
 
-fn update_entry_from_proposed(entry: &mut AccountEntry, txn: &ExtractedTransaction) {
+fn update_entry_from_proposed(
+    entry: &mut AccountEntry,
+    txn: &ExtractedTransaction,
+    staging_account: &str,
+) {
     entry.description = txn.tdescription.clone();
+    let raw_tag = crate::description_cleaning::RAW_DESCRIPTION_TAG;
+    entry.tags.retain(|(key, _)| key != raw_tag);
+    if let Some(raw) = txn.ttags.iter().find(|(key, _)| key == raw_tag) {
+        entry.tags.push(raw.clone());
+    }
     entry.status = txn.status();
     if !txn.tcomment.is_empty() {
         entry.comment = txn.tcomment.clone();
     }
-    update_entry_amount_from_proposed(entry, txn);
+    update_entry_amount_from_proposed(entry, txn, staging_account);
 }
 
-fn update_entry_amount_from_proposed(entry: &mut AccountEntry, txn: &ExtractedTransaction) {
+fn update_entry_amount_from_proposed(
+    entry: &mut AccountEntry,
+    txn: &ExtractedTransaction,
+    staging_account: &str,
+) {
     if let Some(ref postings) = txn.tpostings {
         for (entry_posting, proposed_posting) in entry.postings.iter_mut().zip(postings.iter()) {
             entry_posting.amount = proposed_posting
@@ -547,7 +765,9 @@ fn update_entry_amount_from_proposed(entry: &mut AccountEntry, txn: &ExtractedTr
     if let Some(first) = entry.postings.first_mut() {
         first.amount = Some(primary_amount.clone());
     }
-    if entry.postings.len() == 2 && crate::staging::is_staging_account(&entry.postings[1].account) {
+    if entry.postings.len() == 2
+        && crate::staging::is_counterpart_account(&entry.postings[1].account, staging_account)
+    {
         let negated = SimpleAmount {
             commodity: primary_amount.commodity,
             quantity: negate_quantity(&primary_amount.quantity),
@@ -605,7 +825,7 @@ pub(crate) fn dates_within_tolerance(date_a: &str, date_b: &str, tolerance_days:
     diff <= tolerance_days
 }
 
-fn txn_primary_amount(txn: &ExtractedTransaction) -> Option<f64> {
+pub(crate) fn txn_primary_amount(txn: &ExtractedTransaction) -> Option<f64> {
     // Try explicit postings first
     if let Some(ref postings) = txn.tpostings {
         if let Some(first) = postings.first() {
@@ -699,6 +919,7 @@ fn normalize_description(desc: &str) -> String {
 mod tests {
     use super::*;
     use crate::account_journal::{EntryPosting, SimpleAmount};
+    use crate::login_config::AccountType;
     use std::fs;
     use std::path::PathBuf;
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -735,6 +956,8 @@ mod tests {
             extracted_by: None,
             posted: None,
             posted_postings: Vec::new(),
+            note: None,
+            review: None,
         }
     }
 
@@ -762,6 +985,219 @@ mod tests {
         dir
     }
 
+    /// The original scan over every existing entry, kept as a reference for
+    /// the indexed lookup.
+    fn match_proposed_linear(
+        existing: &[AccountEntry],
+        txn: &ExtractedTransaction,
+        source_document: &str,
+        config: &DedupConfig,
+        matched: &[bool],
+    ) -> DedupResult {
+        let evidence_refs = txn.evidence_refs();
+
+        // Step 1: Same-evidence match
+        for (i, entry) in existing.iter().enumerate() {
+            if matched[i] {
+                continue;
+            }
+            for ev in &evidence_refs {
+                if entry.has_evidence(ev) {
+                    let updated = has_content_changed(entry, txn);
+                    return DedupResult::SameEvidence {
+                        existing_index: i,
+                        updated,
+                    };
+                }
+            }
+        }
+
+        // Step 2: Exact match by bankId (across other documents)
+        if let Some(bank_id) = txn.bank_id() {
+            let mut candidates = Vec::new();
+            for (i, entry) in existing.iter().enumerate() {
+                if matched[i] {
+                    continue;
+                }
+                // Only match across different documents
+                if entry_is_from_same_document(entry, source_document) {
+                    continue;
+                }
+                if entry.bank_id() == Some(bank_id) {
+                    candidates.push(i);
+                }
+            }
+            if candidates.len() == 1 {
+                return DedupResult::BankIdMatch {
+                    existing_index: candidates[0],
+                };
+            }
+            if candidates.len() > 1 {
+                return DedupResult::Ambiguous {
+                    candidate_indices: candidates,
+                };
+            }
+        }
+
+        // Step 3: Fuzzy match (across other documents)
+        let mut fuzzy_candidates = Vec::new();
+        let txn_amount = txn_primary_amount(txn);
+
+        for (i, entry) in existing.iter().enumerate() {
+            if matched[i] {
+                continue;
+            }
+            if entry_is_from_same_document(entry, source_document) {
+                continue;
+            }
+            if !dates_within_tolerance(&entry.date, &txn.tdate, config.date_tolerance_days)
+                || !account_ids_compatible(entry, txn)
+            {
+                continue;
+            }
+            let entry_amount = entry_primary_amount(entry);
+            if amounts_equal(&entry_amount, &txn_amount)
+                && descriptions_similar(entry_raw_description(entry), txn_raw_description(txn))
+            {
+                fuzzy_candidates.push(i);
+            }
+        }
+
+        if fuzzy_candidates.len() == 1 {
+            return DedupResult::FuzzyMatch {
+                existing_index: fuzzy_candidates[0],
+            };
+        }
+
+        // Step 4: Pending→finalized
+        if txn.status() == EntryStatus::Cleared {
+            let mut pending_candidates = Vec::new();
+            for (i, entry) in existing.iter().enumerate() {
+                if matched[i] {
+                    continue;
+                }
+                if entry.status != EntryStatus::Pending {
+                    continue;
+                }
+                if entry_is_from_same_document(entry, source_document) {
+                    continue;
+                }
+                if !dates_within_tolerance(&entry.date, &txn.tdate, config.pending_finalized_days)
+                    || !account_ids_compatible(entry, txn)
+                {
+                    continue;
+                }
+                if amounts_within_tolerance(
+                    &entry_primary_amount(entry),
+                    &txn_amount,
+                    config.pending_finalized_amount_abs,
+                    config.pending_finalized_amount_pct,
+                ) {
+                    pending_candidates.push(i);
+                }
+            }
+            if pending_candidates.len() == 1 {
+                return DedupResult::PendingToFinalized {
+                    existing_index: pending_candidates[0],
+                };
+            }
+        }
+
+        // Step 5: Ambiguous (multiple fuzzy candidates)
+        if fuzzy_candidates.len() > 1 {
+            return DedupResult::Ambiguous {
+                candidate_indices: fuzzy_candidates,
+            };
+        }
+
+        // Step 6: New transaction
+        DedupResult::New
+    }
+
+    #[test]
+    fn indexed_lookup_matches_linear_scan() {
+        let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+        let mut next = |modulus: u64| {
+            seed ^= seed << 13;
+            seed ^= seed >> 7;
+            seed ^= seed << 17;
+            seed % modulus
+        };
+        let descriptions = [
+            "COFFEE SHOP",
+            "Coffee Shop #12",
+            "GROCERY",
+            "RENT",
+            "PAYROLL",
+        ];
+        let amounts = ["-4.50", "-4.75", "-120.00", "-1800.00", "2500.00"];
+        let statuses = [
+            EntryStatus::Cleared,
+            EntryStatus::Pending,
+            EntryStatus::Unmarked,
+        ];
+        let random_entry = |n: usize, next: &mut dyn FnMut(u64) -> u64| {
+            let date = format!("2024-01-{:02}", next(28) + 1);
+            let doc = ["doc-a.csv", "doc-b.csv", "doc-c.csv"][next(3) as usize];
+            let mut entry = make_entry(
+                &format!("e{n}"),
+                if next(50) == 0 { "not-a-date" } else { &date },
+                descriptions[next(5) as usize],
+                statuses[next(3) as usize].clone(),
+                amounts[next(5) as usize],
+                &[&format!("{doc}:{}:1", next(40))],
+            );
+            if next(3) == 0 {
+                entry
+                    .tags
+                    .push(("bankId".to_string(), format!("B{}", next(30))));
+            }
+            entry
+        };
+        let existing: Vec<AccountEntry> = (0..300).map(|n| random_entry(n, &mut next)).collect();
+        let proposed: Vec<ExtractedTransaction> = (0..200)
+            .map(|n| {
+                let entry = random_entry(n, &mut next);
+                let mut txn = make_txn(
+                    &entry.date,
+                    &entry.description,
+                    ["Cleared", "Pending", "Unmarked"][next(3) as usize],
+                    &entry.evidence[0],
+                );
+                txn.ttags.extend(entry.tags.iter().cloned());
+                txn.ttags.push((
+                    "amount".to_string(),
+                    format!("{} USD", amounts[next(5) as usize]),
+                ));
+                txn
+            })
+            .collect();
+
+        for config in [
+            DedupConfig::default(),
+            DedupConfig::for_account_type(Some(AccountType::Brokerage)),
+        ] {
+            for document in ["doc-a.csv", "doc-b.csv", "doc-new.csv"] {
+                let actions = run_dedup(&existing, &proposed, document, &config);
+                let mut matched = vec![false; existing.len()];
+                for (txn, action) in proposed.iter().zip(&actions) {
+                    let expected =
+                        match_proposed_linear(&existing, txn, document, &config, &matched);
+                    assert_eq!(format!("{:?}", action.result), format!("{expected:?}"));
+                    match expected {
+                        DedupResult::SameEvidence { existing_index, .. }
+                        | DedupResult::BankIdMatch { existing_index }
+                        | DedupResult::FuzzyMatch { existing_index }
+                        | DedupResult::PendingToFinalized { existing_index } => {
+                            matched[existing_index] = true;
+                        }
+                        DedupResult::New | DedupResult::Ambiguous { .. } => {}
+                    }
+                }
+            }
+        }
+    }
+
     #[test]
     fn same_evidence_matches() {
         let existing = vec![make_entry(
@@ -785,6 +1221,60 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn split_parts_match_their_source_row_as_one_entry() {
+        let split_part = |id: &str, amount: &str| {
+            let mut entry = make_entry(
+                id,
+                "2024-01-05",
+                "ATM WITHDRAWAL",
+                EntryStatus::Cleared,
+                amount,
+                &["doc-a.csv:3:1"],
+            );
+            entry.tags.push((
+                crate::entry_split::SPLIT_FROM_TAG.to_string(),
+                "e1".to_string(),
+            ));
+            entry
+        };
+        let existing = vec![
+            split_part("e1-part1", "-60.00"),
+            split_part("e1-part2", "-40.00"),
+        ];
+        let with_amount = |evidence: &str| {
+            let mut txn = make_txn("2024-01-05", "ATM WITHDRAWAL", "Cleared", evidence);
+            txn.ttags
+                .push(("amount".to_string(), "-100.00 USD".to_string()));
+            txn
+        };
+
+        let actions = run_dedup(
+            &existing,
+            &[with_amount("doc-a.csv:3:1")],
+            "doc-a.csv",
+            &DedupConfig::default(),
+        );
+        assert!(matches!(
+            actions[0].result,
+            DedupResult::SameEvidence {
+                existing_index: 0,
+                updated: false
+            }
+        ));
+
+        let actions = run_dedup(
+            &existing,
+            &[with_amount("doc-b.csv:1:1")],
+            "doc-b.csv",
+            &DedupConfig::default(),
+        );
+        assert!(matches!(
+            actions[0].result,
+            DedupResult::FuzzyMatch { existing_index: 0 }
+        ));
+    }
+
     #[test]
     fn new_transaction_when_no_match() {
         let existing = vec![make_entry(
@@ -807,6 +1297,45 @@ mod tests {
         assert!(matches!(actions[0].result, DedupResult::New));
     }
 
+    #[test]
+    fn fuzzy_match_requires_compatible_account_ids() {
+        let mut existing = make_entry(
+            "e1",
+            "2024-01-01",
+            "SHELL OIL 12345",
+            EntryStatus::Cleared,
+            "-21.32",
+            &["doc-a.ofx:1:1"],
+        );
+        existing
+            .tags
+            .push(("accountId".to_string(), "000991234".to_string()));
+        let proposed = |account_id: &str| {
+            let mut txn = make_txn("2024-01-01", "SHELL OIL 12345", "Cleared", "doc-b.csv:1:1");
+            txn.ttags
+                .push(("amount".to_string(), "-21.32 USD".to_string()));
+            txn.ttags
+                .push(("accountId".to_string(), account_id.to_string()));
+            txn
+        };
+        let result = |account_id: &str| {
+            run_dedup(
+                std::slice::from_ref(&existing),
+                &[proposed(account_id)],
+                "doc-b.csv",
+                &DedupConfig::default(),
+            )
+            .remove(0)
+            .result
+        };
+
+        assert!(matches!(
+            result("****1234"),
+            DedupResult::FuzzyMatch { existing_index: 0 }
+        ));
+        assert!(matches!(result("000995678"), DedupResult::New));
+    }
+
     #[test]
     fn cross_document_fuzzy_match() {
         let existing = vec![make_entry(
@@ -830,6 +1359,86 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn fuzzy_match_compares_raw_descriptions() {
+        let mut existing = vec![make_entry(
+            "e1",
+            "2024-01-01",
+            "Shell Oil",
+            EntryStatus::Cleared,
+            "-21.32",
+            &["doc-a.csv:1:1"],
+        )];
+        existing[0]
+            .tags
+            .push(("raw_description".to_string(), "SHELL OIL 57444".to_string()));
+
+        // The cleaning steps changed since e1 was extracted.
+        let mut txn = make_txn(
+            "2024-01-01",
+            "Shell Gas Station",
+            "Cleared",
+            "doc-b.csv:1:1",
+        );
+        txn.ttags.extend([
+            ("amount".to_string(), "-21.32 USD".to_string()),
+            ("raw_description".to_string(), "SHELL OIL 57444".to_string()),
+        ]);
+
+        let actions = run_dedup(&existing, &[txn], "doc-b.csv", &DedupConfig::default());
+        assert!(matches!(
+            actions[0].result,
+            DedupResult::FuzzyMatch { existing_index: 0 }
+        ));
+    }
+
+    #[test]
+    fn plan_accounts_match_settlement_dates_but_not_changed_amounts() {
+        let existing = vec![
+            make_entry(
+                "e1",
+                "2024-01-02",
+                "Contribution",
+                EntryStatus::Cleared,
+                "500.00",
+                &["doc-a.csv:1:1"],
+            ),
+            make_entry(
+                "e2",
+                "2024-01-05",
+                "Exchange to Bond Fund",
+                EntryStatus::Pending,
+                "-100.00",
+                &["doc-a.csv:2:1"],
+            ),
+        ];
+        let mut contribution = make_txn("2024-01-04", "Contribution", "Cleared", "doc-b.csv:1:1");
+        contribution
+            .ttags
+            .push(("amount".to_string(), "500.00 USD".to_string()));
+        let mut exchange = make_txn("2024-01-09", "Fund exchange", "Cleared", "doc-b.csv:2:1");
+        exchange
+            .ttags
+            .push(("amount".to_string(), "-103.00 USD".to_string()));
+        let proposed = vec![contribution, exchange];
+
+        let retirement = DedupConfig::for_account_type(Some(AccountType::Retirement));
+        let actions = run_dedup(&existing, &proposed, "doc-b.csv", &retirement);
+        assert!(matches!(
+            actions[0].result,
+            DedupResult::FuzzyMatch { existing_index: 0 }
+        ));
+        assert!(matches!(actions[1].result, DedupResult::New));
+
+        let card = DedupConfig::for_account_type(Some(AccountType::Card));
+        let actions = run_dedup(&existing, &proposed, "doc-b.csv", &card);
+        assert!(matches!(actions[0].result, DedupResult::New));
+        assert!(matches!(
+            actions[1].result,
+            DedupResult::PendingToFinalized { existing_index: 1 }
+        ));
+    }
+
     #[test]
     fn no_within_document_merging() {
         // Two identical transactions from the same document should both be New
@@ -930,6 +1539,10 @@ mod tests {
             coverage_end_date: "2026-02-01".to_string(),
             date_range_start: None,
             date_range_end: None,
+            session_date_range_start: None,
+            session_date_range_end: None,
+            suspect: None,
+            document_type: None,
             metadata: std::collections::BTreeMap::from([(
                 "attachmentKey".to_string(),
                 serde_json::Value::String("check:123|2026-02-01|-25.00".to_string()),
@@ -995,6 +1608,10 @@ mod tests {
             coverage_end_date: "2026-02-01".to_string(),
             date_range_start: None,
             date_range_end: None,
+            session_date_range_start: None,
+            session_date_range_end: None,
+            suspect: None,
+            document_type: None,
             metadata: std::collections::BTreeMap::from([(
                 "attachmentKey".to_string(),
                 serde_json::Value::String("check:123|2026-02-01|25.00".to_string()),
@@ -1108,4 +1725,86 @@ mod tests {
 
         let _ = fs::remove_dir_all(&root);
     }
+
+    #[test]
+    fn apply_dedup_actions_leaves_posted_entries_unchanged() {
+        let root = temp_dir("dedup-posted-guard");
+
+        let mut existing = vec![make_entry(
+            "e1",
+            "2024-01-01",
+            "Shell Oil",
+            EntryStatus::Pending,
+            "-21.32",
+            &["doc-a.csv:1:1"],
+        )];
+        existing[0].posted = Some("general.journal:gl-1".to_string());
+
+        let mut txn = make_txn("2024-01-01", "Shell Oil", "Cleared", "doc-b.json:1001");
+        txn.ttags
+            .push(("amount".to_string(), "-25.00 USD".to_string()));
+
+        let actions = run_dedup(
+            &existing,
+            &[txn.clone()],
+            "doc-b.json",
+            &DedupConfig::default(),
+        );
+        assert!(matches!(
+            actions[0].result,
+            DedupResult::PendingToFinalized { existing_index: 0 }
+        ));
+        let updated = apply_dedup_actions(
+            &root,
+            "test-acct",
+            existing,
+            &actions,
+            "Assets:Checking",
+            "Equity:Staging:Checking",
+            Some("test:latest"),
+        )
+        .expect("apply_dedup_actions");
+
+        assert_eq!(updated[0].status, EntryStatus::Pending);
+        assert_eq!(entry_primary_amount(&updated[0]), Some(-21.32));
+        assert!(updated[0].has_evidence("doc-b.json:1001"));
+
+        let ops = operations::read_account_operations(&root, "test-acct").unwrap();
+        let Some(operations::AccountOperation::PostedEntryConflict {
+            entry_id, changes, ..
+        }) = ops.last()
+        else {
+            panic!("expected a posted-entry-conflict operation, got {ops:?}");
+        };
+        assert_eq!(entry_id, "e1");
+        let fields: Vec<_> = changes.iter().map(|change| change.field).collect();
+        assert_eq!(
+            fields,
+            [
+                operations::PostedEntryField::Status,
+                operations::PostedEntryField::Amount
+            ]
+        );
+
+        // Extracting the same document again finds the same conflict.
+        let actions = run_dedup(&updated, &[txn], "doc-b.json", &DedupConfig::default());
+        apply_dedup_actions(
+            &root,
+            "test-acct",
+            updated,
+            &actions,
+            "Assets:Checking",
+            "Equity:Staging:Checking",
+            Some("test:latest"),
+        )
+        .expect("apply_dedup_actions again");
+        let conflicts = operations::read_account_operations(&root, "test-acct")
+            .unwrap()
+            .into_iter()
+            .filter(|op| matches!(op, operations::AccountOperation::PostedEntryConflict { .. }))
+            .count();
+        assert_eq!(conflicts, 1);
+
+        let _ = fs::remove_dir_all(&root);
+    }
 }
@@ -0,0 +1,346 @@
+//! Per-ledger description cleaning, applied at extraction time.
+//!
+//! Bank descriptions often arrive in capitals with store numbers and
+//! reference ids, e.g. `SHELL OIL 57444 #0412`. The steps in
+//! `description-cleaning.json` at the ledger root rewrite each extracted
+//! description in order. When they change it, the bank's text is kept in a
+//! `raw_description:` tag; dedup compares that instead of the cleaned
+//! description, so editing the steps does not turn old entries into
+//! duplicates.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::extract::ExtractedTransaction;
+
+const DESCRIPTION_CLEANING_FILE: &str = "description-cleaning.json";
+
+/// Tag holding the description as the bank sent it, before cleaning.
+pub const RAW_DESCRIPTION_TAG: &str = "raw_description";
+
+/// Lowercase words left lowercase by title-casing unless they come first.
+const MINOR_WORDS: &[&str] = &["a", "an", "and", "at", "for", "in", "of", "on", "the", "to"];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(tag = "type", rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub enum CleaningStep {
+    /// Remove every match of a regular expression.
+    Strip { pattern: String },
+    /// Replace every match of a regular expression; `$1` refers to a group.
+    Replace {
+        pattern: String,
+        replacement: String,
+    },
+    /// Remove trailing store numbers and reference ids, such as `57444`,
+    /// `#0412`, or the `*2K4AB1C3` in `AMZN MKTP US*2K4AB1C3`.
+    RemoveTrailingIds,
+    /// Title-case words written in capitals, leaving abbreviations such as
+    /// `CVS` or `US` and words with digits as they are.
+    TitleCase,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct DescriptionCleaning {
+    #[serde(default)]
+    pub steps: Vec<CleaningStep>,
+}
+
+enum CompiledStep {
+    Replace(regex::Regex, String),
+    RemoveTrailingIds,
+    TitleCase,
+}
+
+/// Cleaning steps with their patterns compiled.
+pub struct DescriptionCleaner {
+    steps: Vec<CompiledStep>,
+}
+
+impl DescriptionCleaning {
+    /// Compile the steps; `Err` names the first invalid pattern.
+    pub fn compile(&self) -> Result<DescriptionCleaner, String> {
+        let compile = |pattern: &str| {
+            regex::Regex::new(pattern)
+                .map_err(|err| format!("invalid cleaning pattern {pattern:?}: {err}"))
+        };
+        let steps = self
+            .steps
+            .iter()
+            .map(|step| {
+                Ok(match step {
+                    CleaningStep::Strip { pattern } => {
+                        CompiledStep::Replace(compile(pattern)?, String::new())
+                    }
+                    CleaningStep::Replace {
+                        pattern,
+                        replacement,
+                    } => CompiledStep::Replace(compile(pattern)?, replacement.clone()),
+                    CleaningStep::RemoveTrailingIds => CompiledStep::RemoveTrailingIds,
+                    CleaningStep::TitleCase => CompiledStep::TitleCase,
+                })
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(DescriptionCleaner { steps })
+    }
+}
+
+impl DescriptionCleaner {
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Run every step on `description`, then collapse whitespace. Returns
+    /// the description unchanged if the steps would leave it empty.
+    pub fn clean(&self, description: &str) -> String {
+        let mut text = description.to_string();
+        for step in &self.steps {
+            text = match step {
+                CompiledStep::Replace(pattern, replacement) => pattern
+                    .replace_all(&text, replacement.as_str())
+                    .into_owned(),
+                CompiledStep::RemoveTrailingIds => remove_trailing_ids(&text),
+                CompiledStep::TitleCase => title_case(&text),
+            };
+        }
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        if text.is_empty() {
+            description.to_string()
+        } else {
+            text
+        }
+    }
+}
+
+fn description_cleaning_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(DESCRIPTION_CLEANING_FILE)
+}
+
+/// The ledger's cleaning steps. A missing file has none.
+pub fn load_description_cleaning(ledger_dir: &Path) -> io::Result<DescriptionCleaning> {
+    Ok(
+        crate::bookkeeping::read_optional_json(&description_cleaning_path(ledger_dir))?
+            .unwrap_or_default(),
+    )
+}
+
+/// Replace the ledger's cleaning steps after checking that their patterns
+/// compile.
+pub fn save_description_cleaning(
+    ledger_dir: &Path,
+    cleaning: &DescriptionCleaning,
+) -> io::Result<()> {
+    cleaning
+        .compile()
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))?;
+    crate::bookkeeping::write_json(&description_cleaning_path(ledger_dir), cleaning)
+}
+
+/// Clean the description of each transaction with the ledger's steps,
+/// keeping the original in a `raw_description:` tag when it changes.
+/// Unreadable or invalid steps are skipped with a warning.
+pub fn clean_transactions(ledger_dir: &Path, transactions: &mut [ExtractedTransaction]) {
+    let cleaner = match load_description_cleaning(ledger_dir)
+        .map_err(|err| err.to_string())
+        .and_then(|cleaning| cleaning.compile())
+    {
+        Ok(cleaner) if !cleaner.is_empty() => cleaner,
+        Ok(_) => return,
+        Err(err) => {
+            tracing::warn!("skipping description cleaning: {err}");
+            return;
+        }
+    };
+    for txn in transactions {
+        let cleaned = cleaner.clean(&txn.tdescription);
+        if cleaned == txn.tdescription {
+            continue;
+        }
+        let raw = std::mem::replace(&mut txn.tdescription, cleaned);
+        if !txn.ttags.iter().any(|(key, _)| key == RAW_DESCRIPTION_TAG) {
+            txn.ttags.push((RAW_DESCRIPTION_TAG.to_string(), raw));
+        }
+    }
+}
+
+/// Whether `token` looks like a store number or reference id rather than a
+/// word: `#0412`, three or more digits, or five or more characters with at
+/// least two digits (`2K4AB1C3`, `800-555-1212`). `76` and `7-ELEVEN` are
+/// words.
+fn is_reference_id(token: &str) -> bool {
+    let digits = token.chars().filter(char::is_ascii_digit).count();
+    if token.len() > 1 && token.starts_with('#') {
+        return true;
+    }
+    if digits == token.len() {
+        return digits >= 3;
+    }
+    token.chars().count() >= 5 && digits >= 2
+}
+
+fn remove_trailing_ids(text: &str) -> String {
+    let mut tokens: Vec<&str> = text.split_whitespace().collect();
+    while let Some(last) = tokens.last_mut() {
+        let token: &str = last;
+        if let Some((head, id)) = token.rsplit_once('*') {
+            if !head.is_empty() && id.chars().any(|c| c.is_ascii_digit()) {
+                *last = head;
+                continue;
+            }
+        }
+        if tokens.len() > 1 && is_reference_id(tokens[tokens.len() - 1]) {
+            tokens.pop();
+        } else {
+            break;
+        }
+    }
+    tokens.join(" ")
+}
+
+fn title_case(text: &str) -> String {
+    text.split(' ')
+        .enumerate()
+        .map(|(i, word)| {
+            let lower = word.to_lowercase();
+            if i > 0 && is_shouting(word) && MINOR_WORDS.contains(&lower.as_str()) {
+                return lower;
+            }
+            // `CVS/PHARMACY` becomes `CVS/Pharmacy` and `7-ELEVEN` `7-Eleven`.
+            word.split_inclusive(['/', '-'])
+                .map(title_case_part)
+                .collect::<String>()
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn is_shouting(word: &str) -> bool {
+    word.chars().any(char::is_alphabetic)
+        && !word.chars().any(char::is_lowercase)
+        && !word.chars().any(|c| c.is_ascii_digit())
+}
+
+/// Title-case one part of a word in capitals unless it looks like an
+/// abbreviation: no vowels (`CVS`), two letters or fewer (`US`), or an `&`
+/// (`AT&T`). Only the first letter is capitalized, so `JOE'S` becomes
+/// `Joe's`.
+fn title_case_part(part: &str) -> String {
+    let letters = part.chars().filter(|c| c.is_alphabetic()).count();
+    let has_vowel = part
+        .chars()
+        .any(|c| matches!(c, 'A' | 'E' | 'I' | 'O' | 'U' | 'Y'));
+    if !is_shouting(part) || !has_vowel || letters <= 2 || part.contains('&') {
+        return part.to_string();
+    }
+    let mut out = String::with_capacity(part.len());
+    let mut capitalized = false;
+    for c in part.chars().flat_map(char::to_lowercase) {
+        if !capitalized && c.is_alphabetic() {
+            out.extend(c.to_uppercase());
+            capitalized = true;
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_ledger_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-description-cleaning-{prefix}-{}-{now}.refreshmint",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn cleaner(steps: Vec<CleaningStep>) -> DescriptionCleaner {
+        DescriptionCleaning { steps }.compile().unwrap()
+    }
+
+    #[test]
+    fn cleans_shouting_descriptions_with_ids() {
+        let cleaner = cleaner(vec![
+            CleaningStep::Strip {
+                pattern: r"^(SQ|TST)\s*\*\s*".to_string(),
+            },
+            CleaningStep::RemoveTrailingIds,
+            CleaningStep::TitleCase,
+        ]);
+        assert_eq!(
+            cleaner.clean("SQ *BLUE BOTTLE COFFEE 0412"),
+            "Blue Bottle Coffee"
+        );
+        assert_eq!(cleaner.clean("SHELL OIL 57444 #0412"), "Shell Oil");
+        assert_eq!(cleaner.clean("AMZN MKTP US*2K4AB1C3"), "Amzn MKTP US");
+        assert_eq!(cleaner.clean("TRADER JOE'S #552"), "Trader Joe's");
+        assert_eq!(cleaner.clean("BANK OF AMERICA"), "Bank of America");
+        assert_eq!(cleaner.clean("CVS/PHARMACY 08812"), "CVS/Pharmacy");
+        assert_eq!(cleaner.clean("UNION 76"), "Union 76");
+        assert_eq!(cleaner.clean("7-ELEVEN"), "7-Eleven");
+        assert_eq!(cleaner.clean("AT&T BILL PAYMENT"), "AT&T Bill Payment");
+        assert_eq!(cleaner.clean("12345"), "12345");
+        assert_eq!(cleaner.clean("Blue Bottle"), "Blue Bottle");
+
+        let invalid = DescriptionCleaning {
+            steps: vec![CleaningStep::Strip {
+                pattern: "(".to_string(),
+            }],
+        };
+        assert!(invalid.compile().is_err());
+    }
+
+    #[test]
+    fn keeps_raw_description_when_cleaning_changes_it() {
+        let root = temp_ledger_dir("raw");
+        save_description_cleaning(
+            &root,
+            &DescriptionCleaning {
+                steps: vec![
+                    CleaningStep::Replace {
+                        pattern: r"^PAYPAL \*(\w+)".to_string(),
+                        replacement: "$1 via PayPal".to_string(),
+                    },
+                    CleaningStep::TitleCase,
+                ],
+            },
+        )
+        .unwrap();
+        let txn = |description: &str| ExtractedTransaction {
+            tdate: "2025-03-01".to_string(),
+            tstatus: "Cleared".to_string(),
+            tdescription: description.to_string(),
+            tcomment: String::new(),
+            ttags: vec![],
+            tpostings: None,
+        };
+        let mut transactions = vec![txn("PAYPAL *NETFLIX"), txn("Already Clean")];
+        clean_transactions(&root, &mut transactions);
+
+        assert_eq!(transactions[0].tdescription, "Netflix via PayPal");
+        assert_eq!(
+            transactions[0].ttags,
+            vec![(
+                RAW_DESCRIPTION_TAG.to_string(),
+                "PAYPAL *NETFLIX".to_string()
+            )]
+        );
+        assert_eq!(transactions[1].tdescription, "Already Clean");
+        assert!(transactions[1].ttags.is_empty());
+
+        let _ = std::fs::remove_dir_all(&root);
+    }
+}
@@ -0,0 +1,430 @@
+//! Weekly and monthly spending digests.
+//!
+//! A digest summarizes one completed period of the general journal: top
+//! expense categories, the largest expenses, categories spending well above
+//! their trailing average, and the unposted backlog in login account
+//! journals. Digests are stored as `bookkeeping/digests/<id>.json`, where the
+//! id names the cadence and period start, e.g. `monthly-2025-02-01`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::{Datelike, Duration, NaiveDate};
+
+use crate::bookkeeping::{bookkeeping_dir, read_json_objects_from_dir, write_json};
+
+const DIGESTS_DIR: &str = "digests";
+/// Entries kept in the top-category and largest-transaction lists.
+const TOP_N: usize = 5;
+/// Earlier periods averaged to judge whether a category's spend is unusual.
+const TRAILING_PERIODS: i32 = 3;
+/// Spend at least this multiple of the trailing average is unusual.
+const UNUSUAL_RATIO: f64 = 1.5;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum DigestCadence {
+    Weekly,
+    Monthly,
+}
+
+impl DigestCadence {
+    fn as_str(self) -> &'static str {
+        match self {
+            DigestCadence::Weekly => "weekly",
+            DigestCadence::Monthly => "monthly",
+        }
+    }
+
+    /// Start (inclusive) and end (exclusive) of the period containing `date`.
+    fn period_containing(self, date: NaiveDate) -> (NaiveDate, NaiveDate) {
+        match self {
+            DigestCadence::Weekly => {
+                let start = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+                (start, start + Duration::days(7))
+            }
+            DigestCadence::Monthly => {
+                let start = date.with_day(1).unwrap_or(date);
+                (start, add_months(start, 1))
+            }
+        }
+    }
+
+    /// The period `offset` periods after the one starting at `start`.
+    fn shift(self, start: NaiveDate, offset: i32) -> (NaiveDate, NaiveDate) {
+        let shifted = match self {
+            DigestCadence::Weekly => start + Duration::weeks(i64::from(offset)),
+            DigestCadence::Monthly => add_months(start, offset),
+        };
+        self.period_containing(shifted)
+    }
+}
+
+impl std::str::FromStr for DigestCadence {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "weekly" => Ok(DigestCadence::Weekly),
+            "monthly" => Ok(DigestCadence::Monthly),
+            other => Err(format!(
+                "unknown digest cadence '{other}'; expected weekly or monthly"
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct CategoryTotal {
+    pub account: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct DigestTransaction {
+    pub date: String,
+    pub description: String,
+    pub account: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct UnusualSpend {
+    pub account: String,
+    pub amount: f64,
+    pub trailing_average: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct UnpostedBacklog {
+    pub login_name: String,
+    pub label: String,
+    pub count: usize,
+    pub oldest_date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct Digest {
+    pub id: String,
+    pub cadence: DigestCadence,
+    pub period_start: String,
+    /// Exclusive end date.
+    pub period_end: String,
+    pub commodity: String,
+    pub total_spending: f64,
+    pub top_categories: Vec<CategoryTotal>,
+    pub largest_transactions: Vec<DigestTransaction>,
+    pub unusual_spending: Vec<UnusualSpend>,
+    pub unposted: Vec<UnpostedBacklog>,
+    pub generated_at: String,
+}
+
+/// An expense posting in the digest commodity.
+#[derive(Debug, Clone)]
+struct Expense {
+    date: NaiveDate,
+    description: String,
+    account: String,
+    amount: f64,
+}
+
+/// Stored digests, newest period first.
+pub fn list_digests(ledger_dir: &Path) -> io::Result<Vec<Digest>> {
+    let mut digests: Vec<Digest> = read_json_objects_from_dir(&digests_dir(ledger_dir))?;
+    digests.sort_by(|a, b| {
+        b.period_start
+            .cmp(&a.period_start)
+            .then_with(|| a.id.cmp(&b.id))
+    });
+    Ok(digests)
+}
+
+/// Generate and store the digest for the last complete period before
+/// `as_of`, replacing any stored digest for that period.
+pub fn generate_digest(
+    ledger_dir: &Path,
+    cadence: DigestCadence,
+    as_of: NaiveDate,
+    commodity: &str,
+) -> io::Result<Digest> {
+    let (current_start, _) = cadence.period_containing(as_of);
+    let (start, end) = cadence.shift(current_start, -1);
+
+    let gl_path = ledger_dir.join("general.journal");
+    let expenses = if gl_path.exists() {
+        expenses_from_transactions(&crate::ledger_open::run_hledger_print(&gl_path)?, commodity)
+    } else {
+        Vec::new()
+    };
+    let mut digest = build_digest(cadence, start, end, commodity, &expenses);
+    digest.unposted = unposted_backlog(ledger_dir)?;
+    write_json(&digest_path(ledger_dir, &digest.id), &digest)?;
+    Ok(digest)
+}
+
+/// Generate weekly and monthly digests for the last complete periods before
+/// `as_of` that have not been generated yet. Returns the new digests.
+pub fn generate_due_digests(
+    ledger_dir: &Path,
+    as_of: NaiveDate,
+    commodity: &str,
+) -> io::Result<Vec<Digest>> {
+    let mut generated = Vec::new();
+    for cadence in [DigestCadence::Weekly, DigestCadence::Monthly] {
+        let (current_start, _) = cadence.period_containing(as_of);
+        let (start, _) = cadence.shift(current_start, -1);
+        if digest_path(ledger_dir, &digest_id(cadence, start)).exists() {
+            continue;
+        }
+        generated.push(generate_digest(ledger_dir, cadence, as_of, commodity)?);
+    }
+    Ok(generated)
+}
+
+fn build_digest(
+    cadence: DigestCadence,
+    start: NaiveDate,
+    end: NaiveDate,
+    commodity: &str,
+    expenses: &[Expense],
+) -> Digest {
+    let in_period: Vec<&Expense> = expenses
+        .iter()
+        .filter(|e| e.date >= start && e.date < end)
+        .collect();
+    let totals = totals_by_account(in_period.iter().copied());
+
+    let mut top_categories: Vec<CategoryTotal> = totals
+        .iter()
+        .filter(|(_, amount)| **amount > 0.0)
+        .map(|(account, amount)| CategoryTotal {
+            account: account.clone(),
+            amount: *amount,
+        })
+        .collect();
+    top_categories.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+    top_categories.truncate(TOP_N);
+
+    let mut largest: Vec<&Expense> = in_period.clone();
+    largest.sort_by(|a, b| b.amount.total_cmp(&a.amount));
+    let largest_transactions = largest
+        .into_iter()
+        .take(TOP_N)
+        .map(|e| DigestTransaction {
+            date: e.date.format("%Y-%m-%d").to_string(),
+            description: e.description.clone(),
+            account: e.account.clone(),
+            amount: e.amount,
+        })
+        .collect();
+
+    let mut trailing: BTreeMap<String, f64> = BTreeMap::new();
+    for offset in 1..=TRAILING_PERIODS {
+        let (s, e) = cadence.shift(start, -offset);
+        let period = expenses.iter().filter(|x| x.date >= s && x.date < e);
+        for (account, amount) in totals_by_account(period) {
+            *trailing.entry(account).or_default() += amount / f64::from(TRAILING_PERIODS);
+        }
+    }
+    let mut unusual_spending: Vec<UnusualSpend> = totals
+        .iter()
+        .filter_map(|(account, amount)| {
+            let average = trailing.get(account).copied().unwrap_or(0.0);
+            (average > 0.0 && *amount >= average * UNUSUAL_RATIO).then(|| UnusualSpend {
+                account: account.clone(),
+                amount: *amount,
+                trailing_average: average,
+            })
+        })
+        .collect();
+    unusual_spending.sort_by(|a, b| {
+        (b.amount - b.trailing_average).total_cmp(&(a.amount - a.trailing_average))
+    });
+
+    Digest {
+        id: digest_id(cadence, start),
+        cadence,
+        period_start: start.format("%Y-%m-%d").to_string(),
+        period_end: end.format("%Y-%m-%d").to_string(),
+        commodity: commodity.to_string(),
+        total_spending: totals.values().sum(),
+        top_categories,
+        largest_transactions,
+        unusual_spending,
+        unposted: Vec::new(),
+        generated_at: crate::operations::now_timestamp(),
+    }
+}
+
+fn totals_by_account<'a>(expenses: impl Iterator<Item = &'a Expense>) -> BTreeMap<String, f64> {
+    let mut totals = BTreeMap::new();
+    for expense in expenses {
+        *totals.entry(expense.account.clone()).or_default() += expense.amount;
+    }
+    totals
+}
+
+fn expenses_from_transactions(
+    txns: &[crate::hledger::Transaction],
+    commodity: &str,
+) -> Vec<Expense> {
+    let mut expenses = Vec::new();
+    for txn in txns {
+        for posting in &txn.tpostings {
+            if posting.paccount != "Expenses" && !posting.paccount.starts_with("Expenses:") {
+                continue;
+            }
+            let date = posting.pdate.as_deref().unwrap_or(&txn.tdate);
+            let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                continue;
+            };
+            for amount in posting.pamount.iter().filter(|a| a.acommodity == commodity) {
+                expenses.push(Expense {
+                    date,
+                    description: txn.tdescription.clone(),
+                    account: posting.paccount.clone(),
+                    amount: amount.aquantity.floating_point,
+                });
+            }
+        }
+    }
+    expenses
+}
+
+fn unposted_backlog(ledger_dir: &Path) -> io::Result<Vec<UnpostedBacklog>> {
+    let mut backlog = Vec::new();
+    for login in crate::login_config::list_logins(ledger_dir)? {
+        let config = crate::login_config::read_login_config(ledger_dir, &login);
+        for label in config.accounts.keys() {
+            let entries = crate::post::get_unposted_login_account(ledger_dir, &login, label)
+                .map_err(|err| io::Error::other(err.to_string()))?;
+            if entries.is_empty() {
+                continue;
+            }
+            backlog.push(UnpostedBacklog {
+                login_name: login.clone(),
+                label: label.clone(),
+                count: entries.len(),
+                oldest_date: entries.iter().map(|e| e.date.clone()).min(),
+            });
+        }
+    }
+    Ok(backlog)
+}
+
+fn add_months(date: NaiveDate, months: i32) -> NaiveDate {
+    let total = date.year() * 12 + date.month0() as i32 + months;
+    NaiveDate::from_ymd_opt(total.div_euclid(12), total.rem_euclid(12) as u32 + 1, 1)
+        .unwrap_or(date)
+}
+
+fn digest_id(cadence: DigestCadence, start: NaiveDate) -> String {
+    format!("{}-{}", cadence.as_str(), start.format("%Y-%m-%d"))
+}
+
+fn digests_dir(ledger_dir: &Path) -> PathBuf {
+    bookkeeping_dir(ledger_dir).join(DIGESTS_DIR)
+}
+
+fn digest_path(ledger_dir: &Path, id: &str) -> PathBuf {
+    digests_dir(ledger_dir).join(format!("{id}.json"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn expense(day: &str, description: &str, account: &str, amount: f64) -> Expense {
+        Expense {
+            date: date(day),
+            description: description.to_string(),
+            account: account.to_string(),
+            amount,
+        }
+    }
+
+    #[test]
+    fn periods_cover_the_last_complete_week_and_month() {
+        let as_of = date("2025-03-05");
+        let (start, _) = DigestCadence::Weekly.period_containing(as_of);
+        assert_eq!(
+            DigestCadence::Weekly.shift(start, -1),
+            (date("2025-02-24"), date("2025-03-03"))
+        );
+        let (start, _) = DigestCadence::Monthly.period_containing(as_of);
+        assert_eq!(
+            DigestCadence::Monthly.shift(start, -1),
+            (date("2025-02-01"), date("2025-03-01"))
+        );
+        assert_eq!(add_months(date("2025-01-01"), -13), date("2023-12-01"));
+    }
+
+    #[test]
+    fn digest_ranks_categories_and_flags_unusual_spend() {
+        let mut expenses = Vec::new();
+        for month in ["2024-11", "2024-12", "2025-01"] {
+            expenses.push(expense(
+                &format!("{month}-10"),
+                "GROCER",
+                "Expenses:Groceries",
+                300.0,
+            ));
+            expenses.push(expense(
+                &format!("{month}-12"),
+                "CAFE",
+                "Expenses:Dining",
+                60.0,
+            ));
+        }
+        expenses.push(expense("2025-02-03", "GROCER", "Expenses:Groceries", 310.0));
+        expenses.push(expense(
+            "2025-02-14",
+            "STEAKHOUSE",
+            "Expenses:Dining",
+            180.0,
+        ));
+        expenses.push(expense("2025-02-20", "HARDWARE", "Expenses:Home", 45.0));
+        expenses.push(expense("2025-03-01", "GROCER", "Expenses:Groceries", 99.0));
+
+        let digest = build_digest(
+            DigestCadence::Monthly,
+            date("2025-02-01"),
+            date("2025-03-01"),
+            "USD",
+            &expenses,
+        );
+        assert_eq!(digest.id, "monthly-2025-02-01");
+        assert_eq!(digest.total_spending, 535.0);
+        let accounts: Vec<&str> = digest
+            .top_categories
+            .iter()
+            .map(|c| c.account.as_str())
+            .collect();
+        assert_eq!(
+            accounts,
+            vec!["Expenses:Groceries", "Expenses:Dining", "Expenses:Home"]
+        );
+        assert_eq!(digest.largest_transactions[0].description, "GROCER");
+        assert_eq!(digest.unusual_spending.len(), 1);
+        assert_eq!(digest.unusual_spending[0].account, "Expenses:Dining");
+        assert_eq!(digest.unusual_spending[0].trailing_average, 60.0);
+    }
+}
@@ -0,0 +1,440 @@
+//! Rules that give unlabeled documents a login account label.
+//!
+//! A driver that calls `saveResource` without a `label` leaves the document
+//! under `_default`, where extraction cannot route it to an account. A
+//! login's `labelRules` are tried in order when such documents are finalized,
+//! and the first rule whose matchers all match names the label.
+//! [`relabel_documents`] applies the same rules to documents already filed
+//! under `_default`, moving each with its sidecar.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+
+use regex::Regex;
+
+/// Label of documents saved without one.
+pub const DEFAULT_LABEL: &str = "_default";
+
+/// Give documents matching every matcher the rule's label.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LabelRule {
+    pub label: String,
+    /// Regex searched for in the document's file name.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub filename: Option<String>,
+    /// Regexes searched for in sidecar fields, keyed by `originalUrl`,
+    /// `mimeType`, `documentType`, or a key of the driver's metadata.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// A document moved out of `_default` by [`relabel_documents`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct RelabeledDocument {
+    /// Name relative to the documents directory, the same under both labels.
+    pub document: String,
+    pub label: String,
+}
+
+/// A document a rule matched but that stayed under `_default`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct SkippedRelabel {
+    pub document: String,
+    pub label: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct RelabelReport {
+    pub moved: Vec<RelabeledDocument>,
+    pub skipped: Vec<SkippedRelabel>,
+}
+
+/// A login's rules with their regexes compiled.
+#[derive(Default)]
+pub struct LabelRules {
+    rules: Vec<CompiledRule>,
+}
+
+struct CompiledRule {
+    label: String,
+    filename: Option<Regex>,
+    metadata: Vec<(String, Regex)>,
+}
+
+impl LabelRules {
+    pub fn compile(rules: &[LabelRule]) -> Result<Self, String> {
+        let compile = |pattern: &str, what: &str| {
+            Regex::new(pattern).map_err(|err| format!("invalid {what} pattern '{pattern}': {err}"))
+        };
+        let mut compiled = Vec::new();
+        for rule in rules {
+            compiled.push(CompiledRule {
+                label: rule.label.clone(),
+                filename: rule
+                    .filename
+                    .as_deref()
+                    .map(|pattern| compile(pattern, "filename"))
+                    .transpose()?,
+                metadata: rule
+                    .metadata
+                    .iter()
+                    .map(|(key, pattern)| Ok((key.clone(), compile(pattern, key)?)))
+                    .collect::<Result<_, String>>()?,
+            });
+        }
+        Ok(Self { rules: compiled })
+    }
+
+    /// Label of the first rule matching a document, given its file name and
+    /// the fields from [`document_fields`].
+    pub fn label_for(&self, filename: &str, fields: &BTreeMap<String, String>) -> Option<&str> {
+        self.rules
+            .iter()
+            .find(|rule| {
+                rule.filename
+                    .as_ref()
+                    .map_or(true, |pattern| pattern.is_match(filename))
+                    && rule.metadata.iter().all(|(key, pattern)| {
+                        fields.get(key).is_some_and(|value| pattern.is_match(value))
+                    })
+            })
+            .map(|rule| rule.label.as_str())
+    }
+}
+
+/// Sidecar fields label rules can match, as text.
+pub fn document_fields(
+    original_url: Option<&str>,
+    mime_type: Option<&str>,
+    document_type: Option<crate::scrape::DocumentType>,
+    metadata: &BTreeMap<String, serde_json::Value>,
+) -> BTreeMap<String, String> {
+    let mut fields: BTreeMap<String, String> = metadata
+        .iter()
+        .filter_map(|(key, value)| {
+            let text = match value {
+                serde_json::Value::Null => return None,
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            Some((key.clone(), text))
+        })
+        .collect();
+    if let Some(url) = original_url {
+        fields.insert("originalUrl".to_string(), url.to_string());
+    }
+    if let Some(mime) = mime_type {
+        fields.insert("mimeType".to_string(), mime.to_string());
+    }
+    if let Some(document_type) = document_type {
+        fields.insert(
+            "documentType".to_string(),
+            document_type.as_str().to_string(),
+        );
+    }
+    fields
+}
+
+/// Trim and check rules before they are saved: each needs a valid label
+/// other than `_default`, at least one matcher, and patterns that compile.
+pub fn normalize_label_rules(rules: Vec<LabelRule>) -> Result<Vec<LabelRule>, String> {
+    let mut normalized = Vec::new();
+    for rule in rules {
+        let label = rule.label.trim().to_string();
+        crate::login_config::validate_label(&label)?;
+        if label == DEFAULT_LABEL {
+            return Err(format!("a label rule cannot assign '{DEFAULT_LABEL}'"));
+        }
+        let filename = rule
+            .filename
+            .map(|pattern| pattern.trim().to_string())
+            .filter(|pattern| !pattern.is_empty());
+        let metadata: BTreeMap<String, String> = rule
+            .metadata
+            .into_iter()
+            .map(|(key, pattern)| (key.trim().to_string(), pattern.trim().to_string()))
+            .filter(|(key, pattern)| !key.is_empty() && !pattern.is_empty())
+            .collect();
+        if filename.is_none() && metadata.is_empty() {
+            return Err(format!(
+                "the rule for '{label}' needs a filename or metadata pattern"
+            ));
+        }
+        normalized.push(LabelRule {
+            label,
+            filename,
+            metadata,
+        });
+    }
+    LabelRules::compile(&normalized)?;
+    Ok(normalized)
+}
+
+/// Replace a login's label rules.
+pub fn set_login_label_rules(
+    ledger_dir: &Path,
+    login_name: &str,
+    rules: Vec<LabelRule>,
+) -> Result<Vec<LabelRule>, Box<dyn std::error::Error + Send + Sync>> {
+    let rules = normalize_label_rules(rules)?;
+    let mut config = crate::login_config::read_login_config(ledger_dir, login_name);
+    config.label_rules = rules.clone();
+    crate::login_config::write_login_config(ledger_dir, login_name, &config)?;
+    Ok(rules)
+}
+
+/// Move documents under a login's `_default` label that its rules now label.
+///
+/// A document keeps its name, including the date prefix and type folder, and
+/// its sidecar's `label` is updated. Documents cited as evidence by the
+/// `_default` account journal stay put, since moving them would break the
+/// entries extracted from them; so do documents whose name is already taken
+/// under the new label. When `dry_run` is set nothing is moved.
+pub fn relabel_documents(
+    ledger_dir: &Path,
+    login_name: &str,
+    dry_run: bool,
+) -> Result<RelabelReport, Box<dyn std::error::Error + Send + Sync>> {
+    let mut config = crate::login_config::read_login_config(ledger_dir, login_name);
+    let rules = LabelRules::compile(&config.label_rules)?;
+    let source_dir =
+        crate::account_journal::login_account_documents_dir(ledger_dir, login_name, DEFAULT_LABEL);
+    let journal_path =
+        crate::account_journal::login_account_journal_path(ledger_dir, login_name, DEFAULT_LABEL);
+    let entries = crate::account_journal::read_journal_at_path(&journal_path)?;
+
+    let mut report = RelabelReport::default();
+    for doc in
+        crate::extract::list_documents_for_login_account(ledger_dir, login_name, DEFAULT_LABEL)?
+    {
+        let fields = doc.info.as_ref().map_or_else(BTreeMap::new, |info| {
+            document_fields(
+                info.original_url.as_deref(),
+                Some(&info.mime_type),
+                info.document_type,
+                &info.metadata,
+            )
+        });
+        let name = doc.filename.rsplit('/').next().unwrap_or(&doc.filename);
+        let Some(label) = rules.label_for(name, &fields) else {
+            continue;
+        };
+        let label = label.to_string();
+        let cited = entries.iter().any(|entry| {
+            entry
+                .evidence
+                .iter()
+                .any(|e| crate::extract::evidence_ref_cites_document(e, &doc.filename))
+        });
+        let target_dir =
+            crate::account_journal::login_account_documents_dir(ledger_dir, login_name, &label);
+        let target = target_dir.join(&doc.filename);
+        let reason = if cited {
+            Some(format!("cited by the {DEFAULT_LABEL} account journal"))
+        } else if target.exists() {
+            Some(format!("{label} already has a document with this name"))
+        } else {
+            None
+        };
+        if let Some(reason) = reason {
+            report.skipped.push(SkippedRelabel {
+                document: doc.filename,
+                label,
+                reason,
+            });
+            continue;
+        }
+
+        if !dry_run {
+            if let Some(parent) = target.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::rename(source_dir.join(&doc.filename), &target)?;
+            let source_sidecar = source_dir.join(format!("{}-info.json", doc.filename));
+            if let Some(mut info) = doc.info {
+                info.label = label.clone();
+                let sidecar = target_dir.join(format!("{}-info.json", doc.filename));
+                crate::atomic_file::write_atomic(
+                    &sidecar,
+                    serde_json::to_string_pretty(&info)?.as_bytes(),
+                )?;
+                fs::remove_file(&source_sidecar)?;
+            } else if source_sidecar.exists() {
+                // An unreadable sidecar still belongs with its document.
+                fs::rename(
+                    &source_sidecar,
+                    target_dir.join(format!("{}-info.json", doc.filename)),
+                )?;
+            }
+            config.accounts.entry(label.clone()).or_insert_with(|| {
+                crate::login_config::LoginAccountConfig {
+                    gl_account: None,
+                    account_type: None,
+                    account_ids: Vec::new(),
+                    counterpart_account: None,
+                    coverage_start: None,
+                }
+            });
+        }
+        report.moved.push(RelabeledDocument {
+            document: doc.filename,
+            label,
+        });
+    }
+    if !dry_run && !report.moved.is_empty() {
+        crate::login_config::write_login_config(ledger_dir, login_name, &config)?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn rule(label: &str, filename: Option<&str>, metadata: &[(&str, &str)]) -> LabelRule {
+        LabelRule {
+            label: label.to_string(),
+            filename: filename.map(str::to_string),
+            metadata: metadata
+                .iter()
+                .map(|(key, pattern)| (key.to_string(), pattern.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn first_rule_with_all_matchers_matching_wins() {
+        let rules = LabelRules::compile(&[
+            rule(
+                "visa",
+                Some(r"(?i)visa"),
+                &[("documentType", "^statement$")],
+            ),
+            rule("checking", Some(r"^chk-"), &[]),
+            rule("savings", None, &[("accountId", "^9")]),
+        ])
+        .unwrap();
+        let fields = |document_type, account: Option<&str>| {
+            let mut metadata = BTreeMap::new();
+            if let Some(account) = account {
+                metadata.insert("accountId".to_string(), serde_json::json!(account));
+            }
+            document_fields(None, Some("application/pdf"), document_type, &metadata)
+        };
+        let statement = Some(crate::scrape::DocumentType::Statement);
+
+        assert_eq!(
+            rules.label_for("VISA-jan.pdf", &fields(statement, None)),
+            Some("visa")
+        );
+        assert_eq!(rules.label_for("VISA-jan.csv", &fields(None, None)), None);
+        assert_eq!(
+            rules.label_for("chk-jan.csv", &fields(None, None)),
+            Some("checking")
+        );
+        assert_eq!(
+            rules.label_for("jan.pdf", &fields(None, Some("9123"))),
+            Some("savings")
+        );
+        assert_eq!(
+            rules.label_for("jan.pdf", &fields(None, Some("1123"))),
+            None
+        );
+    }
+
+    #[test]
+    fn rules_are_checked_before_saving() {
+        let rules =
+            normalize_label_rules(vec![rule(" visa ", Some(" visa "), &[("", "x")])]).unwrap();
+        assert_eq!(rules, vec![rule("visa", Some("visa"), &[])]);
+        assert!(normalize_label_rules(vec![rule("visa", None, &[])]).is_err());
+        assert!(normalize_label_rules(vec![rule("_default", Some("x"), &[])]).is_err());
+        assert!(normalize_label_rules(vec![rule("a/b", Some("x"), &[])]).is_err());
+        assert!(normalize_label_rules(vec![rule("visa", Some("("), &[])]).is_err());
+    }
+
+    #[test]
+    fn relabel_moves_matching_documents_that_are_not_cited() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-document-labels-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        let login = "bank";
+        let source =
+            crate::account_journal::login_account_documents_dir(&dir, login, DEFAULT_LABEL);
+        fs::create_dir_all(source.join("statements")).unwrap();
+        for name in [
+            "statements/2025-01-31-visa.pdf",
+            "2025-02-01-visa.csv",
+            "2025-02-01-other.csv",
+        ] {
+            fs::write(source.join(name), b"doc").unwrap();
+        }
+        let info = serde_json::json!({
+            "mimeType": "application/pdf",
+            "scrapedAt": "2025-02-01T00:00:00Z",
+            "extensionName": "bank",
+            "loginName": login,
+            "label": DEFAULT_LABEL,
+            "scrapeSessionId": "s1",
+            "coverageEndDate": "2025-01-31",
+            "documentType": "statement",
+        });
+        fs::write(
+            source.join("statements/2025-01-31-visa.pdf-info.json"),
+            serde_json::to_string_pretty(&info).unwrap(),
+        )
+        .unwrap();
+        let journal =
+            crate::account_journal::login_account_journal_path(&dir, login, DEFAULT_LABEL);
+        fs::write(
+            &journal,
+            "2025-02-01 Coffee\n    ; id: e1\n    ; evidence: 2025-02-01-visa.csv:2:1\n    \
+             Equity:Unreconciled  -3.00 USD\n\n",
+        )
+        .unwrap();
+        set_login_label_rules(&dir, login, vec![rule("visa", Some("visa"), &[])]).unwrap();
+
+        let dry_run = relabel_documents(&dir, login, true).unwrap();
+        assert_eq!(dry_run.moved.len(), 1);
+        assert!(source.join("statements/2025-01-31-visa.pdf").exists());
+
+        let report = relabel_documents(&dir, login, false).unwrap();
+        assert_eq!(
+            report.moved,
+            vec![RelabeledDocument {
+                document: "statements/2025-01-31-visa.pdf".to_string(),
+                label: "visa".to_string(),
+            }]
+        );
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].document, "2025-02-01-visa.csv");
+
+        let target = crate::account_journal::login_account_documents_dir(&dir, login, "visa");
+        assert!(target.join("statements/2025-01-31-visa.pdf").exists());
+        assert!(!source.join("statements/2025-01-31-visa.pdf").exists());
+        assert!(!source
+            .join("statements/2025-01-31-visa.pdf-info.json")
+            .exists());
+        let moved = crate::extract::list_documents_for_login_account(&dir, login, "visa").unwrap();
+        assert_eq!(moved[0].info.as_ref().unwrap().label, "visa");
+        let config = crate::login_config::read_login_config(&dir, login);
+        assert!(config.accounts.contains_key("visa"));
+        assert!(source.join("2025-02-01-other.csv").exists());
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,146 @@
+//! Deterministic ids for extracted account journal entries.
+//!
+//! An entry's id is derived from what identifies the transaction at its
+//! source, so extracting the same documents into an empty journal yields the
+//! same ids, and git diffs and posted `source:` refs stay meaningful:
+//!
+//! - with a `bankId` tag (an OFX FITID or the driver's equivalent): the
+//!   string `bankId` and the bank id
+//! - otherwise: the first evidence ref (document and row), the date, and the
+//!   first posting's amount and commodity
+//!
+//! The fields are joined with U+001F, hashed with 64-bit FNV-1a, and written
+//! as 16 lowercase hex digits. When an id is already taken in the journal,
+//! `-2`, `-3`, ... is appended.
+
+use std::collections::HashSet;
+
+use crate::account_journal::AccountEntry;
+
+const FIELD_SEPARATOR: char = '\u{1f}';
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// The id `entry` derives before collision suffixing.
+pub fn derive_entry_id(entry: &AccountEntry) -> String {
+    let fields: Vec<&str> = match entry.bank_id() {
+        Some(bank_id) => vec!["bankId", bank_id],
+        None => {
+            let (quantity, commodity) = entry
+                .postings
+                .first()
+                .and_then(|posting| posting.amount.as_ref())
+                .map_or(("", ""), |amount| {
+                    (amount.quantity.trim(), amount.commodity.as_str())
+                });
+            vec![
+                entry.evidence.first().map_or("", String::as_str),
+                &entry.date,
+                quantity,
+                commodity,
+            ]
+        }
+    };
+    let material = fields.join(&FIELD_SEPARATOR.to_string());
+    format!("{:016x}", fnv1a_64(material.as_bytes()))
+}
+
+/// `base`, or `base` with the first free `-N` suffix when `base` is taken.
+pub fn unique_entry_id(base: String, taken: &HashSet<String>) -> String {
+    if !taken.contains(&base) {
+        return base;
+    }
+    let mut suffix = 2;
+    loop {
+        let candidate = format!("{base}-{suffix}");
+        if !taken.contains(&candidate) {
+            tracing::warn!("entry id {base} is already taken; using {candidate}");
+            return candidate;
+        }
+        suffix += 1;
+    }
+}
+
+/// Derive a unique id for `entry` and reserve it in `taken`.
+pub fn assign_entry_id(entry: &mut AccountEntry, taken: &mut HashSet<String>) {
+    entry.id = unique_entry_id(derive_entry_id(entry), taken);
+    taken.insert(entry.id.clone());
+}
+
+/// Whether `id` is a random id from before ids were derived.
+pub fn is_random_entry_id(id: &str) -> bool {
+    uuid::Uuid::parse_str(id).is_ok()
+}
+
+fn fnv1a_64(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(FNV_OFFSET_BASIS, |hash, byte| {
+        (hash ^ u64::from(*byte)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn entry(evidence: &str, date: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            "COFFEE".to_string(),
+            vec![evidence.to_string()],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    #[test]
+    fn fnv1a_matches_reference_vectors() {
+        assert_eq!(fnv1a_64(b""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(fnv1a_64(b"a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(fnv1a_64(b"foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn ids_depend_on_source_fields_only() {
+        let a = entry("2025-01.csv:3:1", "2025-01-03", "-4.50");
+        let mut same = entry("2025-01.csv:3:1", "2025-01-03", "-4.50");
+        same.description = "COFFEE SHOP".to_string();
+        assert_eq!(derive_entry_id(&a), derive_entry_id(&same));
+        assert_eq!(derive_entry_id(&a).len(), 16);
+
+        let other_row = entry("2025-01.csv:4:1", "2025-01-03", "-4.50");
+        assert_ne!(derive_entry_id(&a), derive_entry_id(&other_row));
+
+        let mut with_fitid = entry("2025-01.ofx:3:1", "2025-01-03", "-4.50");
+        with_fitid
+            .tags
+            .push(("bankId".to_string(), "FIT123".to_string()));
+        let mut refetched = entry("2025-02.ofx:9:1", "2025-01-04", "-4.50");
+        refetched
+            .tags
+            .push(("bankId".to_string(), "FIT123".to_string()));
+        assert_eq!(derive_entry_id(&with_fitid), derive_entry_id(&refetched));
+    }
+
+    #[test]
+    fn collisions_get_numbered_suffixes() {
+        let mut taken = HashSet::new();
+        let mut first = entry("a.csv:1:1", "2025-01-01", "1");
+        let mut second = first.clone();
+        let mut third = first.clone();
+        assign_entry_id(&mut first, &mut taken);
+        assign_entry_id(&mut second, &mut taken);
+        assign_entry_id(&mut third, &mut taken);
+        assert_eq!(second.id, format!("{}-2", first.id));
+        assert_eq!(third.id, format!("{}-3", first.id));
+        assert!(!is_random_entry_id(&first.id));
+        assert!(is_random_entry_id(&uuid::Uuid::new_v4().to_string()));
+    }
+}
@@ -0,0 +1,579 @@
+//! Notes, review state, and tags on login account entries.
+//!
+//! Notes and review state live on the entry in its account journal as
+//! `note:` and `review:` tags, so hledger queries see them too (e.g.
+//! `tag:review=flagged`). [`bulk_update_entries`] applies tag, review, and
+//! comment changes to many entries with one journal rewrite and one git
+//! commit. Unlike posting, none of this touches the general journal.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry, ReviewState};
+use crate::login_config;
+
+/// Review state accepted by the journal listing filters; `unreviewed`
+/// matches entries with no `review:` tag.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export_to = "index.ts")]
+pub enum ReviewFilter {
+    Unreviewed,
+    Reviewed,
+    Flagged,
+}
+
+/// Optional filter for the journal listing commands; unset fields match
+/// every entry.
+#[derive(Debug, Clone, Default, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EntryFilter {
+    #[serde(default)]
+    #[ts(optional)]
+    pub review: Option<ReviewFilter>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub has_note: Option<bool>,
+}
+
+impl EntryFilter {
+    pub fn matches(&self, entry: &AccountEntry) -> bool {
+        let review_matches = match self.review {
+            None => true,
+            Some(ReviewFilter::Unreviewed) => entry.review.is_none(),
+            Some(ReviewFilter::Reviewed) => entry.review == Some(ReviewState::Reviewed),
+            Some(ReviewFilter::Flagged) => entry.review == Some(ReviewState::Flagged),
+        };
+        review_matches
+            && self
+                .has_note
+                .map_or(true, |has_note| entry.note.is_some() == has_note)
+    }
+}
+
+/// Parse a review state from a command argument; an empty value clears it.
+pub fn parse_review_state(value: &str) -> Result<Option<ReviewState>, String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Ok(None);
+    }
+    ReviewState::parse(value)
+        .map(Some)
+        .ok_or_else(|| format!("state: expected one of (reviewed|flagged), got {value}"))
+}
+
+/// Trim each line and drop leading and trailing blank lines; `None` when
+/// nothing is left.
+pub fn normalize_note(note: &str) -> Option<String> {
+    let lines: Vec<&str> = note.lines().map(str::trim).collect();
+    let start = lines.iter().position(|line| !line.is_empty())?;
+    let end = lines.iter().rposition(|line| !line.is_empty())?;
+    Some(lines[start..=end].join("\n"))
+}
+
+/// Set or clear (`None` or blank) the note on a login account entry.
+pub fn set_entry_note(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    entry_id: &str,
+    note: Option<&str>,
+    lock_owner: &str,
+) -> Result<AccountEntry, Box<dyn std::error::Error + Send + Sync>> {
+    let note = note.and_then(normalize_note);
+    update_entry(
+        ledger_dir,
+        (login_name, label),
+        entry_id,
+        lock_owner,
+        "set-entry-note",
+        |entry| entry.note = note,
+    )
+}
+
+/// Set or clear the review state of a login account entry.
+pub fn set_entry_review_state(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    entry_id: &str,
+    state: Option<ReviewState>,
+    lock_owner: &str,
+) -> Result<AccountEntry, Box<dyn std::error::Error + Send + Sync>> {
+    update_entry(
+        ledger_dir,
+        (login_name, label),
+        entry_id,
+        lock_owner,
+        "set-entry-review-state",
+        |entry| entry.review = state,
+    )
+}
+
+fn update_entry(
+    ledger_dir: &Path,
+    (login_name, label): (&str, &str),
+    entry_id: &str,
+    lock_owner: &str,
+    purpose: &str,
+    update: impl FnOnce(&mut AccountEntry),
+) -> Result<AccountEntry, Box<dyn std::error::Error + Send + Sync>> {
+    let _login_lock = login_config::acquire_login_lock_with_metadata(
+        ledger_dir, login_name, lock_owner, purpose,
+    )?;
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let mut entries = account_journal::read_journal_at_path(&journal_path)?;
+    let entry = entries
+        .iter_mut()
+        .find(|e| e.id == entry_id)
+        .ok_or_else(|| format!("entry not found: {entry_id}"))?;
+    update(entry);
+    let updated = entry.clone();
+    crate::posted_changes::write_journal_keeping_posted(&journal_path, &entries)?;
+    crate::ledger::commit_login_account_journal_or_warn(
+        ledger_dir,
+        login_name,
+        label,
+        &format!("{purpose}: {entry_id}"),
+    );
+    Ok(updated)
+}
+
+/// Entries a bulk update applies to: a JSON array of entry ids, or a query
+/// object.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum EntrySelection {
+    Ids(Vec<String>),
+    Query(EntryQuery),
+}
+
+/// Unset fields match every entry.
+#[derive(Debug, Clone, Default, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EntryQuery {
+    /// Case-insensitive substring of the description or comment.
+    #[serde(default)]
+    #[ts(optional)]
+    pub text: Option<String>,
+    /// Inclusive `YYYY-MM-DD` bounds.
+    #[serde(default)]
+    #[ts(optional)]
+    pub date_from: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub date_to: Option<String>,
+    /// Only entries carrying this tag key.
+    #[serde(default)]
+    #[ts(optional)]
+    pub tag: Option<String>,
+    #[serde(flatten)]
+    pub filter: EntryFilter,
+}
+
+impl EntryQuery {
+    pub fn matches(&self, entry: &AccountEntry) -> bool {
+        let text_matches = self.text.as_deref().map_or(true, |text| {
+            let text = text.to_lowercase();
+            entry.description.to_lowercase().contains(&text)
+                || entry.comment.to_lowercase().contains(&text)
+        });
+        text_matches
+            && self
+                .date_from
+                .as_deref()
+                .map_or(true, |from| entry.date.as_str() >= from)
+            && self
+                .date_to
+                .as_deref()
+                .map_or(true, |to| entry.date.as_str() <= to)
+            && self
+                .tag
+                .as_deref()
+                .map_or(true, |tag| entry.tag_value(tag).is_some())
+            && self.filter.matches(entry)
+    }
+}
+
+/// One change applied to every selected entry.
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(tag = "op", rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "BulkEntryOperation")]
+pub enum BulkOperation {
+    /// Add a tag, replacing the value of an existing tag with that key.
+    AddTag {
+        key: String,
+        #[serde(default)]
+        #[ts(as = "Option<String>", optional)]
+        value: String,
+    },
+    RemoveTag {
+        key: String,
+    },
+    SetReviewState {
+        state: Option<ReviewState>,
+    },
+    /// Replace the plain comment; blank clears it.
+    SetComment {
+        comment: String,
+    },
+}
+
+impl BulkOperation {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::AddTag { key, value } => {
+                validate_tag_key(key)?;
+                if value.contains('\n') {
+                    return Err(format!("tag '{key}': value must be a single line"));
+                }
+                Ok(())
+            }
+            Self::RemoveTag { key } => validate_tag_key(key),
+            Self::SetReviewState { .. } | Self::SetComment { .. } => Ok(()),
+        }
+    }
+
+    fn apply(&self, entry: &mut AccountEntry) {
+        match self {
+            Self::AddTag { key, value } => {
+                let value = value.trim().to_string();
+                match entry.tags.iter_mut().find(|(k, _)| k == key) {
+                    Some((_, existing)) => *existing = value,
+                    None => entry.tags.push((key.clone(), value)),
+                }
+            }
+            Self::RemoveTag { key } => entry.tags.retain(|(k, _)| k != key),
+            Self::SetReviewState { state } => entry.review = *state,
+            Self::SetComment { comment } => {
+                entry.comment = normalize_note(comment).unwrap_or_default();
+            }
+        }
+    }
+}
+
+/// Tag keys must survive a round trip through the journal and must not
+/// shadow the tags that hold entry fields.
+fn validate_tag_key(key: &str) -> Result<(), String> {
+    if key.is_empty() || key.contains(|c: char| c == ':' || c.is_whitespace()) {
+        return Err(format!(
+            "invalid tag key '{key}'; use a single word without ':'"
+        ));
+    }
+    let reserved = matches!(
+        key,
+        "id" | "evidence" | "extracted-by" | "posted" | "note" | "review"
+    ) || key.starts_with("posted-posting-");
+    if reserved {
+        return Err(format!("tag '{key}' is managed by refreshmint"));
+    }
+    Ok(())
+}
+
+/// Outcome of [`bulk_update_entries`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct BulkUpdateResult {
+    pub dry_run: bool,
+    /// Entries the selection matched.
+    pub matched: usize,
+    /// Matched entries the operations changed (or would change).
+    pub changed: usize,
+    pub changed_ids: Vec<String>,
+}
+
+/// Apply `operations` to the selected entries of one login account. Ids
+/// that are not in the journal fail the whole update. With `dry_run` the
+/// journal is left alone and only the counts are returned.
+pub fn bulk_update_entries(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    selection: &EntrySelection,
+    operations: &[BulkOperation],
+    dry_run: bool,
+    lock_owner: &str,
+) -> Result<BulkUpdateResult, Box<dyn std::error::Error + Send + Sync>> {
+    if operations.is_empty() {
+        return Err("operations: at least one operation is required".into());
+    }
+    for operation in operations {
+        operation.validate()?;
+    }
+
+    let _login_lock = login_config::acquire_login_lock_with_metadata(
+        ledger_dir,
+        login_name,
+        lock_owner,
+        "bulk-update-entries",
+    )?;
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let mut entries = account_journal::read_journal_at_path(&journal_path)?;
+
+    let selected: Vec<usize> = match selection {
+        EntrySelection::Ids(ids) => ids
+            .iter()
+            .map(|id| {
+                entries
+                    .iter()
+                    .position(|e| &e.id == id)
+                    .ok_or_else(|| format!("entry not found: {id}"))
+            })
+            .collect::<Result<std::collections::BTreeSet<_>, _>>()?
+            .into_iter()
+            .collect(),
+        EntrySelection::Query(query) => (0..entries.len())
+            .filter(|&idx| query.matches(&entries[idx]))
+            .collect(),
+    };
+
+    let mut changed_ids = Vec::new();
+    for &idx in &selected {
+        let entry = &mut entries[idx];
+        let before = account_journal::format_entry(entry);
+        for operation in operations {
+            operation.apply(entry);
+        }
+        if account_journal::format_entry(entry) != before {
+            changed_ids.push(entry.id.clone());
+        }
+    }
+
+    if !dry_run && !changed_ids.is_empty() {
+        crate::posted_changes::write_journal_keeping_posted(&journal_path, &entries)?;
+        crate::ledger::commit_login_account_journal_or_warn(
+            ledger_dir,
+            login_name,
+            label,
+            &format!(
+                "bulk-update: {} entries in {login_name}/{label}",
+                changed_ids.len()
+            ),
+        );
+    }
+
+    Ok(BulkUpdateResult {
+        dry_run,
+        matched: selected.len(),
+        changed: changed_ids.len(),
+        changed_ids,
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus};
+
+    fn temp_dir(prefix: &str) -> std::path::PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time should be after unix epoch")
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn entry(description: &str) -> AccountEntry {
+        AccountEntry::new(
+            "2024-01-01".to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: None,
+            }],
+        )
+    }
+
+    #[test]
+    fn normalize_note_trims_lines_and_clears_blank_notes() {
+        assert_eq!(
+            normalize_note("\n  call bank  \r\n\n re: fee \n\n").as_deref(),
+            Some("call bank\n\nre: fee")
+        );
+        assert_eq!(normalize_note(" \n \n"), None);
+        assert_eq!(
+            parse_review_state(" flagged ").unwrap(),
+            Some(ReviewState::Flagged)
+        );
+        assert_eq!(parse_review_state("").unwrap(), None);
+        assert!(parse_review_state("done").is_err());
+    }
+
+    #[test]
+    fn filter_matches_review_state_and_note_presence() {
+        let plain = entry("plain");
+        let mut flagged = entry("flagged");
+        flagged.review = Some(ReviewState::Flagged);
+        flagged.note = Some("why?".to_string());
+
+        let filter: EntryFilter = serde_json::from_str(r#"{"review":"unreviewed"}"#).unwrap();
+        assert!(filter.matches(&plain));
+        assert!(!filter.matches(&flagged));
+        let filter: EntryFilter =
+            serde_json::from_str(r#"{"review":"flagged","hasNote":true}"#).unwrap();
+        assert!(!filter.matches(&plain));
+        assert!(filter.matches(&flagged));
+        assert!(EntryFilter::default().matches(&plain));
+    }
+
+    #[test]
+    fn bulk_update_applies_operations_to_query_matches() {
+        let root = temp_dir("entry-bulk");
+        let journal_path = account_journal::login_account_journal_path(&root, "chase", "checking");
+        let mut hotel = entry("HOTEL LISBOA");
+        hotel.date = "2024-06-03".to_string();
+        let mut cafe = entry("Cafe Lisboa");
+        cafe.date = "2024-06-04".to_string();
+        cafe.tags = vec![("vacation".to_string(), "spain".to_string())];
+        let mut rent = entry("Rent");
+        rent.date = "2024-06-01".to_string();
+        account_journal::write_journal_at_path(&journal_path, &[hotel, cafe, rent.clone()])
+            .unwrap();
+
+        let selection: EntrySelection =
+            serde_json::from_str(r#"{"text":"lisboa","dateFrom":"2024-06-02"}"#).unwrap();
+        let operations: Vec<BulkOperation> = serde_json::from_str(
+            r#"[{"op":"addTag","key":"vacation","value":"portugal"},
+                {"op":"setReviewState","state":"reviewed"}]"#,
+        )
+        .unwrap();
+
+        let preview = bulk_update_entries(
+            &root,
+            "chase",
+            "checking",
+            &selection,
+            &operations,
+            true,
+            "test",
+        )
+        .unwrap();
+        assert_eq!((preview.matched, preview.changed), (2, 2));
+        let untouched = account_journal::read_journal_at_path(&journal_path).unwrap();
+        assert!(untouched.iter().all(|e| e.review.is_none()));
+
+        bulk_update_entries(
+            &root,
+            "chase",
+            "checking",
+            &selection,
+            &operations,
+            false,
+            "test",
+        )
+        .unwrap();
+        let entries = account_journal::read_journal_at_path(&journal_path).unwrap();
+        for entry in &entries[..2] {
+            assert_eq!(entry.tag_value("vacation"), Some("portugal"));
+            assert_eq!(entry.review, Some(ReviewState::Reviewed));
+        }
+        assert_eq!(entries[2].tag_value("vacation"), None);
+
+        let again = bulk_update_entries(
+            &root,
+            "chase",
+            "checking",
+            &selection,
+            &operations,
+            false,
+            "test",
+        )
+        .unwrap();
+        assert_eq!((again.matched, again.changed), (2, 0));
+
+        let by_id = EntrySelection::Ids(vec![rent.id.clone(), "missing".to_string()]);
+        assert!(bulk_update_entries(
+            &root,
+            "chase",
+            "checking",
+            &by_id,
+            &operations,
+            false,
+            "test"
+        )
+        .is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn bulk_operations_reject_reserved_and_malformed_tags() {
+        for key in [
+            "posted",
+            "review",
+            "posted-posting-0",
+            "two words",
+            "a:b",
+            "",
+        ] {
+            let operation = BulkOperation::RemoveTag {
+                key: key.to_string(),
+            };
+            assert!(operation.validate().is_err(), "{key}");
+        }
+        let multi_line = BulkOperation::AddTag {
+            key: "trip".to_string(),
+            value: "a\nb".to_string(),
+        };
+        assert!(multi_line.validate().is_err());
+
+        let mut target = entry("x");
+        target.comment = "old".to_string();
+        BulkOperation::SetComment {
+            comment: "  ".to_string(),
+        }
+        .apply(&mut target);
+        assert_eq!(target.comment, "");
+    }
+
+    #[test]
+    fn set_note_and_review_state_rewrite_the_journal() {
+        let root = temp_dir("entry-review");
+        let journal_path = account_journal::login_account_journal_path(&root, "chase", "checking");
+        let target = entry("target");
+        let other = entry("other");
+        account_journal::write_journal_at_path(&journal_path, &[target.clone(), other.clone()])
+            .unwrap();
+
+        set_entry_note(
+            &root,
+            "chase",
+            "checking",
+            &target.id,
+            Some("split with Sam"),
+            "test",
+        )
+        .unwrap();
+        let updated = set_entry_review_state(
+            &root,
+            "chase",
+            "checking",
+            &target.id,
+            Some(ReviewState::Reviewed),
+            "test",
+        )
+        .unwrap();
+        assert_eq!(updated.note.as_deref(), Some("split with Sam"));
+
+        let entries = account_journal::read_journal_at_path(&journal_path).unwrap();
+        assert_eq!(entries[0].note.as_deref(), Some("split with Sam"));
+        assert_eq!(entries[0].review, Some(ReviewState::Reviewed));
+        assert_eq!((entries[1].note.as_ref(), entries[1].review), (None, None));
+
+        set_entry_note(&root, "chase", "checking", &target.id, Some("  "), "test").unwrap();
+        let entries = account_journal::read_journal_at_path(&journal_path).unwrap();
+        assert_eq!(entries[0].note, None);
+        assert!(set_entry_note(&root, "chase", "checking", "missing", None, "test").is_err());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
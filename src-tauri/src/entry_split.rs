@@ -0,0 +1,435 @@
+//! Splitting one login account entry into several.
+//!
+//! A single source row sometimes covers more than one real transaction,
+//! like an ATM withdrawal that paid for two different things. Splitting
+//! replaces the entry in its account journal with parts whose amounts sum
+//! to the original. Each part keeps the original's date, evidence, and tags,
+//! and gets a `splitFrom:` tag naming the original entry id, so each one can
+//! be posted, categorized, and reviewed on its own. Merging removes the parts
+//! and restores the original entry under its old id. Both are recorded in
+//! the account operations log.
+//!
+//! Dedup treats the parts of a split as one entry, so re-extracting the
+//! source row does not overwrite a part's amount or add the row again.
+
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry, SimpleAmount};
+use crate::loans::{format_cents, parse_cents};
+use crate::login_config;
+use crate::operations::{self, AccountOperation, SplitPartRecord};
+
+/// Tag on each part of a split entry, naming the entry it was split from.
+pub const SPLIT_FROM_TAG: &str = "splitFrom";
+
+/// One part requested by [`split_entry`].
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SplitPart {
+    /// Amount in the entry's commodity, with the same sign as the original.
+    pub amount: String,
+    /// Description for this part; defaults to the original description.
+    #[serde(default)]
+    #[ts(optional)]
+    pub description: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub note: Option<String>,
+}
+
+/// Replace an unposted login account entry with `parts`, returning the new
+/// entries in order.
+pub fn split_entry(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    entry_id: &str,
+    parts: &[SplitPart],
+    lock_owner: &str,
+) -> Result<Vec<AccountEntry>, Box<dyn std::error::Error + Send + Sync>> {
+    let _login_lock = login_config::acquire_login_lock_with_metadata(
+        ledger_dir,
+        login_name,
+        lock_owner,
+        "split-entry",
+    )?;
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let mut entries = account_journal::read_journal_at_path(&journal_path)?;
+    let index = entries
+        .iter()
+        .position(|e| e.id == entry_id)
+        .ok_or_else(|| format!("entry not found: {entry_id}"))?;
+    let taken: HashSet<String> = entries.iter().map(|e| e.id.clone()).collect();
+    let counterpart = crate::staging::login_account_counterpart(ledger_dir, login_name, label);
+    let children = build_split_parts(&entries[index], parts, &taken, &counterpart)?;
+
+    let original = entries.remove(index);
+    entries.splice(index..index, children.clone());
+    crate::posted_changes::write_journal_keeping_posted(&journal_path, &entries)?;
+
+    let op = AccountOperation::EntrySplit {
+        entry_id: entry_id.to_string(),
+        description: original.description.clone(),
+        amount: primary_amount(&original)
+            .map(|a| a.quantity.clone())
+            .unwrap_or_default(),
+        parts: children
+            .iter()
+            .map(|child| SplitPartRecord {
+                entry_id: child.id.clone(),
+                amount: primary_amount(child)
+                    .map(|a| a.quantity.clone())
+                    .unwrap_or_default(),
+            })
+            .collect(),
+        timestamp: operations::now_timestamp(),
+    };
+    operations::append_login_account_operation(ledger_dir, login_name, label, &op)?;
+    crate::ledger::commit_login_account_journal_or_warn(
+        ledger_dir,
+        login_name,
+        label,
+        &format!("split-entry: {entry_id}"),
+    );
+    Ok(children)
+}
+
+/// Remove the parts of a split and restore the original entry `entry_id`.
+pub fn merge_split_entry(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    entry_id: &str,
+    lock_owner: &str,
+) -> Result<AccountEntry, Box<dyn std::error::Error + Send + Sync>> {
+    let _login_lock = login_config::acquire_login_lock_with_metadata(
+        ledger_dir,
+        login_name,
+        lock_owner,
+        "merge-split-entry",
+    )?;
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let mut entries = account_journal::read_journal_at_path(&journal_path)?;
+    let description = operations::read_login_account_operations(ledger_dir, login_name, label)?
+        .into_iter()
+        .rev()
+        .find_map(|op| match op {
+            AccountOperation::EntrySplit {
+                entry_id: split_id,
+                description,
+                ..
+            } if split_id == entry_id => Some(description),
+            _ => None,
+        });
+
+    let counterpart = crate::staging::login_account_counterpart(ledger_dir, login_name, label);
+    let merged = merge_parts(&entries, entry_id, description, &counterpart)?;
+    let first = entries
+        .iter()
+        .position(|e| e.tag_value(SPLIT_FROM_TAG) == Some(entry_id))
+        .ok_or_else(|| format!("entry {entry_id} has no split parts"))?;
+    let part_ids: Vec<String> = entries
+        .iter()
+        .filter(|e| e.tag_value(SPLIT_FROM_TAG) == Some(entry_id))
+        .map(|e| e.id.clone())
+        .collect();
+    entries.retain(|e| e.tag_value(SPLIT_FROM_TAG) != Some(entry_id));
+    entries.insert(first, merged.clone());
+    crate::posted_changes::write_journal_keeping_posted(&journal_path, &entries)?;
+
+    let op = AccountOperation::EntryMerge {
+        entry_id: entry_id.to_string(),
+        parts: part_ids,
+        timestamp: operations::now_timestamp(),
+    };
+    operations::append_login_account_operation(ledger_dir, login_name, label, &op)?;
+    crate::ledger::commit_login_account_journal_or_warn(
+        ledger_dir,
+        login_name,
+        label,
+        &format!("merge-split-entry: {entry_id}"),
+    );
+    Ok(merged)
+}
+
+fn build_split_parts(
+    original: &AccountEntry,
+    parts: &[SplitPart],
+    taken: &HashSet<String>,
+    counterpart: &str,
+) -> Result<Vec<AccountEntry>, String> {
+    let entry_id = &original.id;
+    if parts.len() < 2 {
+        return Err("split requires at least 2 parts".to_string());
+    }
+    if original.is_posted() {
+        return Err(format!(
+            "entry {entry_id} is posted; unpost it before splitting"
+        ));
+    }
+    if original.tag_value(SPLIT_FROM_TAG).is_some() {
+        return Err(format!(
+            "entry {entry_id} is already part of a split; merge it first"
+        ));
+    }
+    let total = primary_amount(original)
+        .and_then(|amount| parse_cents(&amount.quantity))
+        .ok_or_else(|| format!("entry {entry_id} has no amount to split"))?;
+
+    let mut amounts = Vec::with_capacity(parts.len());
+    for part in parts {
+        let cents = parse_cents(&part.amount)
+            .ok_or_else(|| format!("invalid split amount: {}", part.amount))?;
+        if cents == 0 || cents.signum() != total.signum() {
+            return Err(format!(
+                "split amount {} must be non-zero with the same sign as {}",
+                part.amount,
+                format_cents(total)
+            ));
+        }
+        amounts.push(cents);
+    }
+    let sum: i64 = amounts.iter().sum();
+    if sum != total {
+        return Err(format!(
+            "split amounts sum to {}, not the entry amount {}",
+            format_cents(sum),
+            format_cents(total)
+        ));
+    }
+
+    let mut taken = taken.clone();
+    let mut children = Vec::with_capacity(parts.len());
+    for (n, (part, cents)) in parts.iter().zip(amounts).enumerate() {
+        let mut child = original.clone();
+        child.id = crate::entry_id::unique_entry_id(format!("{entry_id}-part{}", n + 1), &taken);
+        taken.insert(child.id.clone());
+        set_primary_amount(&mut child, &format_cents(cents), counterpart);
+        if let Some(description) = part.description.as_deref().map(str::trim) {
+            if !description.is_empty() {
+                child.description = description.to_string();
+            }
+        }
+        child.note = part
+            .note
+            .as_deref()
+            .and_then(crate::entry_review::normalize_note);
+        child.review = None;
+        child
+            .tags
+            .push((SPLIT_FROM_TAG.to_string(), entry_id.clone()));
+        children.push(child);
+    }
+    Ok(children)
+}
+
+fn merge_parts(
+    entries: &[AccountEntry],
+    entry_id: &str,
+    description: Option<String>,
+    counterpart: &str,
+) -> Result<AccountEntry, String> {
+    let parts: Vec<&AccountEntry> = entries
+        .iter()
+        .filter(|e| e.tag_value(SPLIT_FROM_TAG) == Some(entry_id))
+        .collect();
+    let Some(first) = parts.first() else {
+        return Err(format!("entry {entry_id} has no split parts"));
+    };
+    if let Some(posted) = parts.iter().find(|part| part.is_posted()) {
+        return Err(format!(
+            "split part {} is posted; unpost it before merging",
+            posted.id
+        ));
+    }
+    let mut total = 0;
+    for part in &parts {
+        total += primary_amount(part)
+            .and_then(|amount| parse_cents(&amount.quantity))
+            .ok_or_else(|| format!("split part {} has no amount", part.id))?;
+    }
+
+    let mut merged = (*first).clone();
+    merged.id = entry_id.to_string();
+    merged.tags.retain(|(key, _)| key != SPLIT_FROM_TAG);
+    set_primary_amount(&mut merged, &format_cents(total), counterpart);
+    if let Some(description) = description {
+        merged.description = description;
+    }
+    for part in &parts[1..] {
+        for evidence in &part.evidence {
+            merged.add_evidence(evidence.clone());
+        }
+    }
+    merged.note = None;
+    merged.review = None;
+    Ok(merged)
+}
+
+/// The entry `entry_id` was split from, as far as its derived id goes: the
+/// first part with the parts' summed amount. `None` without parts, or when a
+/// part has no amount.
+pub(crate) fn split_original(entries: &[AccountEntry], entry_id: &str) -> Option<AccountEntry> {
+    let parts: Vec<&AccountEntry> = entries
+        .iter()
+        .filter(|e| e.tag_value(SPLIT_FROM_TAG) == Some(entry_id))
+        .collect();
+    let mut total = 0;
+    for part in &parts {
+        total += primary_amount(part).and_then(|amount| parse_cents(&amount.quantity))?;
+    }
+    let mut original = (*parts.first()?).clone();
+    original.id = entry_id.to_string();
+    original.tags.retain(|(key, _)| key != SPLIT_FROM_TAG);
+    if let Some(amount) = original
+        .postings
+        .first_mut()
+        .and_then(|p| p.amount.as_mut())
+    {
+        amount.quantity = format_cents(total);
+    }
+    Some(original)
+}
+
+fn primary_amount(entry: &AccountEntry) -> Option<&SimpleAmount> {
+    entry.postings.first().and_then(|p| p.amount.as_ref())
+}
+
+/// Set the first posting's quantity, and the balancing counterpart
+/// posting's when the entry has one.
+pub(crate) fn set_primary_amount(entry: &mut AccountEntry, quantity: &str, counterpart: &str) {
+    let Some(first) = entry.postings.first_mut().and_then(|p| p.amount.as_mut()) else {
+        return;
+    };
+    first.quantity = quantity.to_string();
+    if entry.postings.len() == 2
+        && crate::staging::is_counterpart_account(&entry.postings[1].account, counterpart)
+    {
+        if let Some(second) = entry.postings[1].amount.as_mut() {
+            second.quantity = negate(quantity);
+        }
+    }
+}
+
+fn negate(quantity: &str) -> String {
+    match quantity.strip_prefix('-') {
+        Some(positive) => positive.to_string(),
+        None => format!("-{quantity}"),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus};
+
+    const STAGING: &str = "Equity:Staging:chase:checking";
+
+    fn withdrawal() -> AccountEntry {
+        let mut entry = AccountEntry::new(
+            "2025-03-02".to_string(),
+            EntryStatus::Cleared,
+            "ATM WITHDRAWAL".to_string(),
+            vec!["statement.pdf:3:1".to_string()],
+            vec![
+                EntryPosting {
+                    account: "Assets:Checking".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: "-100.00".to_string(),
+                    }),
+                },
+                EntryPosting {
+                    account: "Equity:Staging:chase:checking".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: "100.00".to_string(),
+                    }),
+                },
+            ],
+        );
+        entry.id = "abc".to_string();
+        entry.tags.push(("bankId".to_string(), "T1".to_string()));
+        entry
+    }
+
+    fn part(amount: &str, description: Option<&str>) -> SplitPart {
+        SplitPart {
+            amount: amount.to_string(),
+            description: description.map(str::to_string),
+            note: None,
+        }
+    }
+
+    #[test]
+    fn split_parts_sum_to_original_and_merge_back() {
+        let original = withdrawal();
+        let parts = build_split_parts(
+            &original,
+            &[part("-60", Some("Farmers market")), part("-40.00", None)],
+            &HashSet::from(["abc".to_string()]),
+            STAGING,
+        )
+        .unwrap();
+        assert_eq!(parts.len(), 2);
+        assert_eq!(parts[0].id, "abc-part1");
+        assert_eq!(parts[0].description, "Farmers market");
+        assert_eq!(parts[1].description, "ATM WITHDRAWAL");
+        assert_eq!(primary_amount(&parts[0]).unwrap().quantity, "-60.00");
+        assert_eq!(
+            parts[0].postings[1].amount.as_ref().unwrap().quantity,
+            "60.00"
+        );
+        assert_eq!(parts[1].evidence, original.evidence);
+        assert_eq!(parts[1].tag_value(SPLIT_FROM_TAG), Some("abc"));
+        assert_eq!(parts[1].bank_id(), Some("T1"));
+
+        let merged =
+            merge_parts(&parts, "abc", Some("ATM WITHDRAWAL".to_string()), STAGING).unwrap();
+        assert_eq!(merged.id, "abc");
+        assert_eq!(merged.description, "ATM WITHDRAWAL");
+        assert_eq!(primary_amount(&merged).unwrap().quantity, "-100.00");
+        assert_eq!(
+            merged.postings[1].amount.as_ref().unwrap().quantity,
+            "100.00"
+        );
+        assert_eq!(merged.tag_value(SPLIT_FROM_TAG), None);
+    }
+
+    #[test]
+    fn split_rejects_mismatched_or_posted_entries() {
+        let original = withdrawal();
+        let taken = HashSet::new();
+        let err = build_split_parts(
+            &original,
+            &[part("-60", None), part("-30", None)],
+            &taken,
+            STAGING,
+        )
+        .unwrap_err();
+        assert!(err.contains("sum to -90.00"), "{err}");
+        let err = build_split_parts(
+            &original,
+            &[part("-110", None), part("10", None)],
+            &taken,
+            STAGING,
+        )
+        .unwrap_err();
+        assert!(err.contains("same sign"), "{err}");
+
+        let mut posted = original;
+        posted.posted = Some("general.journal:xyz".to_string());
+        let err = build_split_parts(
+            &posted,
+            &[part("-60", None), part("-40", None)],
+            &taken,
+            STAGING,
+        )
+        .unwrap_err();
+        assert!(err.contains("unpost"), "{err}");
+    }
+}
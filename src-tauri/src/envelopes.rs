@@ -0,0 +1,699 @@
+//! Envelope (zero-based) budgeting.
+//!
+//! Envelopes live in `envelopes.journal` at the ledger root, next to
+//! `general.journal` but not included by it, so ordinary reports are
+//! unaffected. Each envelope is an `account Envelopes:<name>` directive whose
+//! tags name the GL categories it pays for:
+//!
+//! ```text
+//! account Envelopes:Groceries  ; category: Expenses:Food, group: Essentials, rollover: true
+//! ```
+//!
+//! Allocations and transfers are balanced virtual transactions tagged
+//! `envelope: allocate` or `envelope: transfer`. Spending is never written
+//! here: it is read from posted GL transactions in the envelope's categories.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::report_groups::{self, ReportGroup};
+
+const ENVELOPES_JOURNAL: &str = "envelopes.journal";
+const ENVELOPE_PREFIX: &str = "Envelopes:";
+/// Counter-account for allocations; the pool of income not yet assigned.
+const UNALLOCATED: &str = "Unallocated";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EnvelopeDefinition {
+    pub name: String,
+    /// GL accounts whose postings are deducted from this envelope.
+    /// Subaccounts are included.
+    #[serde(default)]
+    pub categories: Vec<String>,
+    /// Report groups whose accounts are deducted from this envelope.
+    #[serde(default)]
+    pub groups: Vec<String>,
+    /// Carry unspent money into the next month. Without rollover the
+    /// surplus returns to the unallocated pool; overspending always carries.
+    #[serde(default)]
+    pub rollover: bool,
+}
+
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct AllocateEnvelopeInput {
+    pub envelope: String,
+    /// Amount to move, e.g. `"250.00"`. Negative amounts return money.
+    pub amount: String,
+    pub commodity: Option<String>,
+    /// Defaults to today.
+    pub date: Option<String>,
+    /// Move money from another envelope instead of from income.
+    pub from_envelope: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EnvelopeStatus {
+    pub name: String,
+    pub categories: Vec<String>,
+    pub groups: Vec<String>,
+    pub rollover: bool,
+    pub carried_in: f64,
+    pub allocated: f64,
+    pub transferred: f64,
+    pub spent: f64,
+    pub available: f64,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EnvelopeReport {
+    /// `YYYY-MM`.
+    pub month: String,
+    pub commodity: String,
+    /// Income received during the month.
+    pub income: f64,
+    /// Allocations from income made during the month.
+    pub allocated: f64,
+    /// Income received through the end of the month that is not assigned
+    /// to any envelope.
+    pub to_be_budgeted: f64,
+    pub envelopes: Vec<EnvelopeStatus>,
+}
+
+pub fn envelopes_journal_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(ENVELOPES_JOURNAL)
+}
+
+pub fn list_envelope_definitions(ledger_dir: &Path) -> io::Result<Vec<EnvelopeDefinition>> {
+    let path = envelopes_journal_path(ledger_dir);
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    Ok(parse_definitions(&fs::read_to_string(path)?))
+}
+
+/// Create or replace an envelope's account directive.
+pub fn define_envelope(
+    ledger_dir: &Path,
+    definition: EnvelopeDefinition,
+    lock_owner: &str,
+) -> Result<EnvelopeDefinition, Box<dyn std::error::Error + Send + Sync>> {
+    let definition = normalize_definition(definition)?;
+    let _lock = crate::login_config::acquire_gl_lock_with_metadata(
+        ledger_dir,
+        lock_owner,
+        "define-envelope",
+    )?;
+    let path = envelopes_journal_path(ledger_dir);
+    let content = if path.exists() {
+        fs::read_to_string(&path)?
+    } else {
+        String::new()
+    };
+    crate::atomic_file::write_atomic(
+        &path,
+        upsert_definition_line(&content, &definition).as_bytes(),
+    )?;
+
+    let commit_msg = format!("envelope: define {}", definition.name);
+    if let Err(err) = crate::ledger::commit_envelopes_journal(ledger_dir, &commit_msg) {
+        tracing::warn!("git commit failed after defining envelope: {err}");
+    }
+    Ok(definition)
+}
+
+/// Record an allocation from income, or a transfer between envelopes.
+pub fn allocate_envelope(
+    ledger_dir: &Path,
+    input: AllocateEnvelopeInput,
+    lock_owner: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    let definitions = list_envelope_definitions(ledger_dir)?;
+    let require_defined = |name: &str| -> Result<String, String> {
+        let name = name.trim();
+        if definitions.iter().any(|d| d.name == name) {
+            Ok(name.to_string())
+        } else {
+            Err(format!("unknown envelope: {name}"))
+        }
+    };
+    let envelope = require_defined(&input.envelope)?;
+    let from_envelope = match input.from_envelope.as_deref().map(str::trim) {
+        Some(from) if !from.is_empty() => Some(require_defined(from)?),
+        _ => None,
+    };
+    if from_envelope.as_deref() == Some(envelope.as_str()) {
+        return Err("cannot transfer an envelope to itself".into());
+    }
+    let amount = input.amount.trim().to_string();
+    if amount
+        .parse::<f64>()
+        .map_or(true, |v| !v.is_finite() || v == 0.0)
+    {
+        return Err(format!("amount must be a non-zero number, got {amount}").into());
+    }
+    let commodity = input
+        .commodity
+        .map(|c| c.trim().to_string())
+        .filter(|c| !c.is_empty())
+        .unwrap_or_else(|| "USD".to_string());
+    let date = match input.date.map(|d| d.trim().to_string()) {
+        Some(date) if !date.is_empty() => {
+            chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")
+                .map_err(|err| format!("date must be YYYY-MM-DD: {err}"))?;
+            date
+        }
+        _ => chrono::Local::now()
+            .date_naive()
+            .format("%Y-%m-%d")
+            .to_string(),
+    };
+
+    let text = format_allocation(
+        &date,
+        &envelope,
+        from_envelope.as_deref(),
+        &amount,
+        &commodity,
+    );
+    let _lock = crate::login_config::acquire_gl_lock_with_metadata(
+        ledger_dir,
+        lock_owner,
+        "allocate-envelope",
+    )?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(envelopes_journal_path(ledger_dir))?;
+    file.write_all(text.as_bytes())?;
+
+    let commit_msg = match &from_envelope {
+        Some(from) => format!("envelope: move {amount} {commodity} {from} → {envelope}"),
+        None => format!("envelope: allocate {amount} {commodity} → {envelope}"),
+    };
+    if let Err(err) = crate::ledger::commit_envelopes_journal(ledger_dir, &commit_msg) {
+        tracing::warn!("git commit failed after envelope allocation: {err}");
+    }
+    Ok(())
+}
+
+/// Build the envelope report for `month` (`YYYY-MM`) in one commodity.
+pub fn get_envelopes(
+    ledger_dir: &Path,
+    month: &str,
+    commodity: &str,
+) -> io::Result<EnvelopeReport> {
+    let definitions = list_envelope_definitions(ledger_dir)?;
+    let groups = report_groups::list_report_groups(ledger_dir)?;
+
+    let gl_path = ledger_dir.join("general.journal");
+    let gl_flows = if gl_path.exists() {
+        flows_from_transactions(&crate::ledger_open::run_hledger_print(&gl_path)?, commodity)
+    } else {
+        Vec::new()
+    };
+    let envelopes_path = envelopes_journal_path(ledger_dir);
+    let envelope_txns = if envelopes_path.exists() {
+        crate::ledger_open::run_hledger_print(&envelopes_path)?
+    } else {
+        Vec::new()
+    };
+    let envelope_flows = envelope_flows_from_transactions(&envelope_txns, commodity);
+
+    Ok(build_report(
+        &definitions,
+        &groups,
+        &gl_flows,
+        &envelope_flows,
+        month,
+        commodity,
+    ))
+}
+
+/// A GL posting amount in the report commodity.
+#[derive(Debug, Clone)]
+struct Flow {
+    date: String,
+    account: String,
+    amount: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EnvelopeFlowKind {
+    Allocate,
+    Transfer,
+}
+
+#[derive(Debug, Clone)]
+struct EnvelopeFlow {
+    date: String,
+    envelope: String,
+    kind: EnvelopeFlowKind,
+    amount: f64,
+}
+
+fn flows_from_transactions(txns: &[crate::hledger::Transaction], commodity: &str) -> Vec<Flow> {
+    let mut flows = Vec::new();
+    for txn in txns {
+        for posting in &txn.tpostings {
+            for amount in posting.pamount.iter().filter(|a| a.acommodity == commodity) {
+                flows.push(Flow {
+                    date: posting.pdate.clone().unwrap_or_else(|| txn.tdate.clone()),
+                    account: posting.paccount.clone(),
+                    amount: amount.aquantity.floating_point,
+                });
+            }
+        }
+    }
+    flows
+}
+
+fn envelope_flows_from_transactions(
+    txns: &[crate::hledger::Transaction],
+    commodity: &str,
+) -> Vec<EnvelopeFlow> {
+    let mut flows = Vec::new();
+    for txn in txns {
+        let kind = match txn
+            .ttags
+            .iter()
+            .find(|(k, _)| k == "envelope")
+            .map(|(_, v)| v.trim())
+        {
+            Some("allocate") => EnvelopeFlowKind::Allocate,
+            Some("transfer") => EnvelopeFlowKind::Transfer,
+            _ => continue,
+        };
+        for flow in flows_from_transactions(std::slice::from_ref(txn), commodity) {
+            let Some(envelope) = flow.account.strip_prefix(ENVELOPE_PREFIX) else {
+                continue;
+            };
+            if envelope == UNALLOCATED {
+                continue;
+            }
+            flows.push(EnvelopeFlow {
+                date: flow.date,
+                envelope: envelope.to_string(),
+                kind,
+                amount: flow.amount,
+            });
+        }
+    }
+    flows
+}
+
+fn month_of(date: &str) -> &str {
+    date.get(..7).unwrap_or(date)
+}
+
+fn is_income_account(account: &str) -> bool {
+    ["Income", "Revenue"].iter().any(|top| {
+        account == *top
+            || account
+                .strip_prefix(top)
+                .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
+
+/// The first envelope whose categories or groups cover `account`.
+fn envelope_for_account<'a>(
+    definitions: &'a [EnvelopeDefinition],
+    groups: &[ReportGroup],
+    account: &str,
+) -> Option<&'a EnvelopeDefinition> {
+    let group = report_groups::group_for_account(groups, account).map(|g| g.name.as_str());
+    definitions.iter().find(|definition| {
+        definition.categories.iter().any(|category| {
+            account == category
+                || account
+                    .strip_prefix(category.as_str())
+                    .is_some_and(|rest| rest.starts_with(':'))
+        }) || group.is_some_and(|group| definition.groups.iter().any(|g| g == group))
+    })
+}
+
+fn build_report(
+    definitions: &[EnvelopeDefinition],
+    groups: &[ReportGroup],
+    gl_flows: &[Flow],
+    envelope_flows: &[EnvelopeFlow],
+    month: &str,
+    commodity: &str,
+) -> EnvelopeReport {
+    // (month, envelope) → (allocated, transferred, spent)
+    let mut activity: BTreeMap<(String, String), (f64, f64, f64)> = BTreeMap::new();
+    let mut income_by_month: BTreeMap<String, f64> = BTreeMap::new();
+    let mut months: BTreeSet<String> = BTreeSet::new();
+    months.insert(month.to_string());
+
+    for flow in gl_flows {
+        let flow_month = month_of(&flow.date);
+        if flow_month > month {
+            continue;
+        }
+        months.insert(flow_month.to_string());
+        if is_income_account(&flow.account) {
+            *income_by_month.entry(flow_month.to_string()).or_default() -= flow.amount;
+        } else if let Some(definition) = envelope_for_account(definitions, groups, &flow.account) {
+            activity
+                .entry((flow_month.to_string(), definition.name.clone()))
+                .or_default()
+                .2 += flow.amount;
+        }
+    }
+    let mut allocated_by_month: BTreeMap<String, f64> = BTreeMap::new();
+    for flow in envelope_flows {
+        let flow_month = month_of(&flow.date);
+        if flow_month > month {
+            continue;
+        }
+        months.insert(flow_month.to_string());
+        let entry = activity
+            .entry((flow_month.to_string(), flow.envelope.clone()))
+            .or_default();
+        match flow.kind {
+            EnvelopeFlowKind::Allocate => {
+                entry.0 += flow.amount;
+                *allocated_by_month
+                    .entry(flow_month.to_string())
+                    .or_default() += flow.amount;
+            }
+            EnvelopeFlowKind::Transfer => entry.1 += flow.amount,
+        }
+    }
+
+    let mut carry: BTreeMap<&str, f64> = BTreeMap::new();
+    let mut swept = 0.0;
+    let mut statuses = Vec::new();
+    for current in &months {
+        for definition in definitions {
+            let (allocated, transferred, spent) = activity
+                .get(&(current.clone(), definition.name.clone()))
+                .copied()
+                .unwrap_or_default();
+            let carried_in = carry.get(definition.name.as_str()).copied().unwrap_or(0.0);
+            let available = carried_in + allocated + transferred - spent;
+            if current == month {
+                statuses.push(EnvelopeStatus {
+                    name: definition.name.clone(),
+                    categories: definition.categories.clone(),
+                    groups: definition.groups.clone(),
+                    rollover: definition.rollover,
+                    carried_in,
+                    allocated,
+                    transferred,
+                    spent,
+                    available,
+                });
+            } else if !definition.rollover && available > 0.0 {
+                swept += available;
+            }
+            let carried_out = if definition.rollover {
+                available
+            } else {
+                available.min(0.0)
+            };
+            carry.insert(definition.name.as_str(), carried_out);
+        }
+    }
+
+    let total_income: f64 = income_by_month.values().sum();
+    let total_allocated: f64 = allocated_by_month.values().sum();
+    EnvelopeReport {
+        month: month.to_string(),
+        commodity: commodity.to_string(),
+        income: income_by_month.get(month).copied().unwrap_or(0.0),
+        allocated: allocated_by_month.get(month).copied().unwrap_or(0.0),
+        to_be_budgeted: total_income - total_allocated + swept,
+        envelopes: statuses,
+    }
+}
+
+fn normalize_definition(
+    definition: EnvelopeDefinition,
+) -> Result<EnvelopeDefinition, Box<dyn std::error::Error + Send + Sync>> {
+    let name = definition.name.trim().to_string();
+    if name.is_empty()
+        || name == UNALLOCATED
+        || name.contains([':', ',', ';', '\n'])
+        || name.contains("  ")
+    {
+        return Err(format!("invalid envelope name: {name:?}").into());
+    }
+    let clean = |values: Vec<String>| -> Result<Vec<String>, String> {
+        let mut out = Vec::new();
+        for value in values {
+            let value = value.trim().to_string();
+            if value.is_empty() {
+                continue;
+            }
+            if value.contains([',', ';', '\n']) {
+                return Err(format!("invalid envelope category or group: {value:?}"));
+            }
+            if !out.contains(&value) {
+                out.push(value);
+            }
+        }
+        Ok(out)
+    };
+    Ok(EnvelopeDefinition {
+        name,
+        categories: clean(definition.categories)?,
+        groups: clean(definition.groups)?,
+        rollover: definition.rollover,
+    })
+}
+
+fn parse_definitions(content: &str) -> Vec<EnvelopeDefinition> {
+    content
+        .lines()
+        .filter_map(|line| {
+            let rest = line.strip_prefix("account ")?.trim_start();
+            let (account, comment) = match rest.split_once(';') {
+                Some((account, comment)) => (account.trim(), comment),
+                None => (rest.trim(), ""),
+            };
+            let name = account.strip_prefix(ENVELOPE_PREFIX)?;
+            if name == UNALLOCATED {
+                return None;
+            }
+            let mut definition = EnvelopeDefinition {
+                name: name.to_string(),
+                categories: Vec::new(),
+                groups: Vec::new(),
+                rollover: false,
+            };
+            for tag in comment.split(',') {
+                let Some((key, value)) = tag.split_once(':') else {
+                    continue;
+                };
+                let value = value.trim().to_string();
+                match key.trim() {
+                    "category" => definition.categories.push(value),
+                    "group" => definition.groups.push(value),
+                    "rollover" => definition.rollover = value == "true",
+                    _ => {}
+                }
+            }
+            Some(definition)
+        })
+        .collect()
+}
+
+pub(crate) fn format_definition(definition: &EnvelopeDefinition) -> String {
+    let mut tags: Vec<String> = definition
+        .categories
+        .iter()
+        .map(|c| format!("category: {c}"))
+        .collect();
+    tags.extend(definition.groups.iter().map(|g| format!("group: {g}")));
+    tags.push(format!("rollover: {}", definition.rollover));
+    format!(
+        "account {ENVELOPE_PREFIX}{}  ; {}",
+        definition.name,
+        tags.join(", ")
+    )
+}
+
+/// Replace the directive for `definition` in place, or insert it after the
+/// last account directive.
+fn upsert_definition_line(content: &str, definition: &EnvelopeDefinition) -> String {
+    let account = format!("{ENVELOPE_PREFIX}{}", definition.name);
+    let is_directive_for = |line: &str| {
+        line.strip_prefix("account ")
+            .is_some_and(|rest| rest.split(';').next().map(str::trim) == Some(account.as_str()))
+    };
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+    let new_line = format_definition(definition);
+    if let Some(index) = lines.iter().position(|line| is_directive_for(line)) {
+        lines[index] = new_line;
+    } else {
+        let insert_at = lines
+            .iter()
+            .rposition(|line| line.starts_with("account "))
+            .map_or(0, |index| index + 1);
+        lines.insert(insert_at, new_line);
+    }
+    let mut out = lines.join("\n");
+    out.push('\n');
+    out
+}
+
+fn format_allocation(
+    date: &str,
+    envelope: &str,
+    from_envelope: Option<&str>,
+    amount: &str,
+    commodity: &str,
+) -> String {
+    let negated = match amount.strip_prefix('-') {
+        Some(positive) => positive.to_string(),
+        None => format!("-{amount}"),
+    };
+    let (description, kind, source) = match from_envelope {
+        Some(from) => (format!("Move {from} to {envelope}"), "transfer", from),
+        None => (format!("Allocate {envelope}"), "allocate", UNALLOCATED),
+    };
+    format!(
+        "\n{date} {description}  ; envelope: {kind}\n    [{ENVELOPE_PREFIX}{envelope}]  {amount} {commodity}\n    [{ENVELOPE_PREFIX}{source}]  {negated} {commodity}\n"
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn flow(date: &str, account: &str, amount: f64) -> Flow {
+        Flow {
+            date: date.to_string(),
+            account: account.to_string(),
+            amount,
+        }
+    }
+
+    fn allocation(date: &str, envelope: &str, kind: EnvelopeFlowKind, amount: f64) -> EnvelopeFlow {
+        EnvelopeFlow {
+            date: date.to_string(),
+            envelope: envelope.to_string(),
+            kind,
+            amount,
+        }
+    }
+
+    fn definition(name: &str, category: &str, rollover: bool) -> EnvelopeDefinition {
+        EnvelopeDefinition {
+            name: name.to_string(),
+            categories: vec![category.to_string()],
+            groups: Vec::new(),
+            rollover,
+        }
+    }
+
+    #[test]
+    fn definitions_round_trip_through_account_directives() {
+        let groceries = EnvelopeDefinition {
+            name: "Groceries".to_string(),
+            categories: vec!["Expenses:Food".to_string()],
+            groups: vec!["Essentials".to_string()],
+            rollover: true,
+        };
+        let content = upsert_definition_line("", &groceries);
+        assert_eq!(
+            content,
+            "account Envelopes:Groceries  ; category: Expenses:Food, group: Essentials, rollover: true\n"
+        );
+        let content = format!(
+            "{content}{}",
+            format_allocation("2026-03-01", "Groceries", None, "500", "USD")
+        );
+        let dining = definition("Dining", "Expenses:Dining", false);
+        let content = upsert_definition_line(&content, &dining);
+        let mut updated = groceries.clone();
+        updated.rollover = false;
+        let content = upsert_definition_line(&content, &updated);
+
+        assert_eq!(parse_definitions(&content), vec![updated, dining]);
+        assert!(content.contains(
+            "2026-03-01 Allocate Groceries  ; envelope: allocate\n    [Envelopes:Groceries]  500 USD\n    [Envelopes:Unallocated]  -500 USD\n"
+        ));
+    }
+
+    #[test]
+    fn report_applies_rollover_and_sweeps_unspent_without_it() {
+        let definitions = vec![
+            definition("Groceries", "Expenses:Food", true),
+            definition("Dining", "Expenses:Dining", false),
+        ];
+        let groups = Vec::new();
+        let gl_flows = vec![
+            flow("2026-01-01", "Income:Salary", -1000.0),
+            flow("2026-01-15", "Expenses:Food:Market", 300.0),
+            flow("2026-01-20", "Expenses:Dining", 50.0),
+            flow("2026-02-01", "Income:Salary", -1000.0),
+            flow("2026-02-10", "Expenses:Food", 100.0),
+            flow("2026-03-01", "Income:Salary", -1000.0),
+        ];
+        let envelope_flows = vec![
+            allocation("2026-01-02", "Groceries", EnvelopeFlowKind::Allocate, 400.0),
+            allocation("2026-01-02", "Dining", EnvelopeFlowKind::Allocate, 100.0),
+            allocation("2026-02-02", "Groceries", EnvelopeFlowKind::Allocate, 200.0),
+            allocation("2026-02-05", "Groceries", EnvelopeFlowKind::Transfer, -20.0),
+            allocation("2026-02-05", "Dining", EnvelopeFlowKind::Transfer, 20.0),
+        ];
+
+        let report = build_report(
+            &definitions,
+            &groups,
+            &gl_flows,
+            &envelope_flows,
+            "2026-02",
+            "USD",
+        );
+        assert_eq!(report.income, 1000.0);
+        assert_eq!(report.allocated, 200.0);
+        // 2000 income - 700 allocated + 50 Dining surplus swept in January.
+        assert_eq!(report.to_be_budgeted, 1350.0);
+        let groceries = &report.envelopes[0];
+        assert_eq!(groceries.carried_in, 100.0);
+        assert_eq!(groceries.spent, 100.0);
+        assert_eq!(groceries.transferred, -20.0);
+        assert_eq!(groceries.available, 180.0);
+        let dining = &report.envelopes[1];
+        assert_eq!(dining.carried_in, 0.0);
+        assert_eq!(dining.available, 20.0);
+    }
+
+    #[test]
+    fn group_membership_assigns_spending() {
+        let definitions = vec![EnvelopeDefinition {
+            name: "Fun".to_string(),
+            categories: Vec::new(),
+            groups: vec!["Fun".to_string()],
+            rollover: true,
+        }];
+        let groups = vec![ReportGroup {
+            name: "Fun".to_string(),
+            accounts: vec!["Expenses:Hobbies".to_string()],
+        }];
+        let report = build_report(
+            &definitions,
+            &groups,
+            &[flow("2026-01-05", "Expenses:Hobbies:Games", 60.0)],
+            &[],
+            "2026-01",
+            "USD",
+        );
+        assert_eq!(report.envelopes[0].spent, 60.0);
+        assert_eq!(report.envelopes[0].available, -60.0);
+    }
+}
@@ -0,0 +1,296 @@
+//! Small key/value state that drivers keep between runs
+//! (`refreshmint.storage`), such as device tokens, cursors, or dismissed
+//! banners.
+//!
+//! Each login+extension pair has its own JSON object at
+//! `logins/<login>/storage/<extension>.json`. The file lives in the ledger,
+//! which is usually committed to git, so it must never hold secrets; the
+//! driver API refuses values containing known keychain usernames.
+
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Longest accepted key, in bytes.
+pub const MAX_KEY_BYTES: usize = 256;
+/// Largest accepted value, in bytes of compact JSON.
+pub const MAX_VALUE_BYTES: usize = 16 * 1024;
+/// Largest accepted store for one login+extension, in bytes of compact JSON.
+pub const MAX_STORE_BYTES: usize = 256 * 1024;
+
+pub type StorageEntries = BTreeMap<String, serde_json::Value>;
+
+/// Return the path to `logins/<login_name>/storage/`.
+pub fn login_storage_dir(ledger_dir: &Path, login_name: &str) -> PathBuf {
+    ledger_dir.join("logins").join(login_name).join("storage")
+}
+
+/// Return the path to `logins/<login_name>/storage/<extension_name>.json`.
+pub fn storage_path(ledger_dir: &Path, login_name: &str, extension_name: &str) -> PathBuf {
+    login_storage_dir(ledger_dir, login_name).join(format!("{extension_name}.json"))
+}
+
+/// Entries stored for one login+extension; empty when nothing was stored.
+pub fn read_storage(
+    ledger_dir: &Path,
+    login_name: &str,
+    extension_name: &str,
+) -> io::Result<StorageEntries> {
+    validate_extension_name(extension_name)?;
+    let path = storage_path(ledger_dir, login_name, extension_name);
+    match std::fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).map_err(|err| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("failed to parse '{}': {err}", path.display()),
+            )
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(StorageEntries::new()),
+        Err(err) => Err(err),
+    }
+}
+
+pub fn get_value(
+    ledger_dir: &Path,
+    login_name: &str,
+    extension_name: &str,
+    key: &str,
+) -> io::Result<Option<serde_json::Value>> {
+    Ok(read_storage(ledger_dir, login_name, extension_name)?.remove(key))
+}
+
+/// Store `value` under `key`, enforcing the key, value, and store limits.
+pub fn set_value(
+    ledger_dir: &Path,
+    login_name: &str,
+    extension_name: &str,
+    key: &str,
+    value: serde_json::Value,
+) -> io::Result<()> {
+    validate_key(key)?;
+    let value_bytes = serde_json::to_string(&value)
+        .map_err(io::Error::other)?
+        .len();
+    if value_bytes > MAX_VALUE_BYTES {
+        return Err(invalid_input(format!(
+            "storage value for '{key}' is {value_bytes} bytes; the limit is {MAX_VALUE_BYTES}"
+        )));
+    }
+    let mut entries = read_storage(ledger_dir, login_name, extension_name)?;
+    entries.insert(key.to_string(), value);
+    let store_bytes = serde_json::to_string(&entries)
+        .map_err(io::Error::other)?
+        .len();
+    if store_bytes > MAX_STORE_BYTES {
+        return Err(invalid_input(format!(
+            "storage for extension '{extension_name}' would be {store_bytes} bytes; the limit is {MAX_STORE_BYTES}"
+        )));
+    }
+    write_storage(ledger_dir, login_name, extension_name, &entries)
+}
+
+/// Remove `key`; returns whether it was present.
+pub fn delete_value(
+    ledger_dir: &Path,
+    login_name: &str,
+    extension_name: &str,
+    key: &str,
+) -> io::Result<bool> {
+    let mut entries = read_storage(ledger_dir, login_name, extension_name)?;
+    if entries.remove(key).is_none() {
+        return Ok(false);
+    }
+    write_storage(ledger_dir, login_name, extension_name, &entries)?;
+    Ok(true)
+}
+
+/// Entries for every extension that stored something for the login, keyed
+/// by extension name.
+pub fn list_storage(
+    ledger_dir: &Path,
+    login_name: &str,
+) -> io::Result<BTreeMap<String, StorageEntries>> {
+    let dir = login_storage_dir(ledger_dir, login_name);
+    let entries = match std::fs::read_dir(&dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(BTreeMap::new()),
+        Err(err) => return Err(err),
+    };
+    let mut stores = BTreeMap::new();
+    for entry in entries {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let Some(extension_name) = name.strip_suffix(".json") else {
+            continue;
+        };
+        if name.starts_with('.') || !entry.file_type()?.is_file() {
+            continue;
+        }
+        stores.insert(
+            extension_name.to_string(),
+            read_storage(ledger_dir, login_name, extension_name)?,
+        );
+    }
+    Ok(stores)
+}
+
+/// Clear one key, one extension's store, or (with neither) every store for
+/// the login. Returns the number of entries removed.
+pub fn clear_storage(
+    ledger_dir: &Path,
+    login_name: &str,
+    extension_name: Option<&str>,
+    key: Option<&str>,
+) -> io::Result<usize> {
+    match (extension_name, key) {
+        (Some(extension_name), Some(key)) => Ok(usize::from(delete_value(
+            ledger_dir,
+            login_name,
+            extension_name,
+            key,
+        )?)),
+        (None, Some(_)) => Err(invalid_input(
+            "clearing a single storage key requires an extension".to_string(),
+        )),
+        (Some(extension_name), None) => {
+            let removed = read_storage(ledger_dir, login_name, extension_name)?.len();
+            remove_if_exists(&storage_path(ledger_dir, login_name, extension_name))?;
+            Ok(removed)
+        }
+        (None, None) => {
+            let mut removed = 0;
+            for (extension_name, entries) in list_storage(ledger_dir, login_name)? {
+                removed += entries.len();
+                remove_if_exists(&storage_path(ledger_dir, login_name, &extension_name))?;
+            }
+            Ok(removed)
+        }
+    }
+}
+
+fn write_storage(
+    ledger_dir: &Path,
+    login_name: &str,
+    extension_name: &str,
+    entries: &StorageEntries,
+) -> io::Result<()> {
+    let path = storage_path(ledger_dir, login_name, extension_name);
+    if entries.is_empty() {
+        return remove_if_exists(&path);
+    }
+    let json = serde_json::to_string_pretty(entries).map_err(io::Error::other)?;
+    crate::atomic_file::write_atomic(&path, format!("{json}\n").as_bytes())
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match std::fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+        _ => Ok(()),
+    }
+}
+
+fn validate_key(key: &str) -> io::Result<()> {
+    if key.is_empty() {
+        return Err(invalid_input("storage key must not be empty".to_string()));
+    }
+    if key.len() > MAX_KEY_BYTES {
+        return Err(invalid_input(format!(
+            "storage key is {} bytes; the limit is {MAX_KEY_BYTES}",
+            key.len()
+        )));
+    }
+    Ok(())
+}
+
+fn validate_extension_name(extension_name: &str) -> io::Result<()> {
+    crate::login_config::validate_label(extension_name)
+        .map_err(|err| invalid_input(format!("invalid extension name: {err}")))
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn temp_ledger() -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-storage-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn values_round_trip_per_login_and_extension() {
+        let ledger = temp_ledger();
+        set_value(&ledger, "chase", "chase-ext", "cursor", json!("2024-05-01")).unwrap();
+        set_value(
+            &ledger,
+            "chase",
+            "chase-ext",
+            "banner",
+            json!({ "dismissed": true }),
+        )
+        .unwrap();
+        set_value(&ledger, "chase", "other-ext", "cursor", json!(7)).unwrap();
+
+        assert_eq!(
+            get_value(&ledger, "chase", "chase-ext", "cursor").unwrap(),
+            Some(json!("2024-05-01"))
+        );
+        assert_eq!(
+            get_value(&ledger, "amex", "chase-ext", "cursor").unwrap(),
+            None
+        );
+        let stores = list_storage(&ledger, "chase").unwrap();
+        assert_eq!(stores.len(), 2);
+        assert_eq!(stores["chase-ext"].len(), 2);
+
+        assert!(delete_value(&ledger, "chase", "chase-ext", "cursor").unwrap());
+        assert!(!delete_value(&ledger, "chase", "chase-ext", "cursor").unwrap());
+        assert_eq!(clear_storage(&ledger, "chase", None, None).unwrap(), 2);
+        assert!(list_storage(&ledger, "chase").unwrap().is_empty());
+        let _ = std::fs::remove_dir_all(ledger);
+    }
+
+    #[test]
+    fn limits_and_names_are_enforced() {
+        let ledger = temp_ledger();
+        let big = "x".repeat(MAX_VALUE_BYTES);
+        assert!(set_value(&ledger, "chase", "ext", "big", json!(big)).is_err());
+        assert!(set_value(&ledger, "chase", "ext", "", json!(1)).is_err());
+        assert!(set_value(
+            &ledger,
+            "chase",
+            "ext",
+            &"k".repeat(MAX_KEY_BYTES + 1),
+            json!(1)
+        )
+        .is_err());
+        assert!(set_value(&ledger, "chase", "../escape", "k", json!(1)).is_err());
+
+        let chunk = "y".repeat(MAX_VALUE_BYTES - 16);
+        let mut stored = 0;
+        let err = loop {
+            match set_value(&ledger, "chase", "ext", &format!("k{stored}"), json!(chunk)) {
+                Ok(()) => stored += 1,
+                Err(err) => break err,
+            }
+        };
+        assert!(err.to_string().contains("limit"), "{err}");
+        assert_eq!(read_storage(&ledger, "chase", "ext").unwrap().len(), stored);
+        assert!(clear_storage(&ledger, "chase", None, Some("k0")).is_err());
+        assert_eq!(
+            clear_storage(&ledger, "chase", Some("ext"), Some("k0")).unwrap(),
+            1
+        );
+        let _ = std::fs::remove_dir_all(ledger);
+    }
+}
@@ -91,6 +91,27 @@ pub struct ExtractedTransaction {
     pub tpostings: Option<Vec<ExtractedPosting>>,
 }
 
+/// `refreshmint.extractJson(document, path)`: every value in `document`
+/// matching the JSONPath `path`, as an array.
+fn js_extract_json<'js>(
+    ctx: Ctx<'js>,
+    document: Value<'js>,
+    path: String,
+) -> rquickjs::Result<Value<'js>> {
+    let throw = |message: String| rquickjs::Exception::throw_message(&ctx, &message);
+    let text = match ctx.json_stringify(document)? {
+        Some(text) => text.to_string()?,
+        None => "null".to_string(),
+    };
+    let value: serde_json::Value =
+        serde_json::from_str(&text).map_err(|error| throw(format!("extractJson: {error}")))?;
+    let matches = crate::json_path::query(&value, &path)
+        .map_err(|error| throw(format!("extractJson: {error}")))?;
+    let json =
+        serde_json::to_string(&matches).map_err(|error| throw(format!("extractJson: {error}")))?;
+    ctx.json_parse(json)
+}
+
 fn default_status_string() -> String {
     "Unmarked".to_string()
 }
@@ -112,6 +133,10 @@ struct ExtractScriptContext {
     pdf: Option<PdfExtractContext>,
     #[serde(skip_serializing_if = "Option::is_none")]
     json: Option<serde_json::Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    html: Option<HtmlExtractContext>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    xlsx: Option<XlsxExtractContext>,
 }
 
 #[derive(Debug, Serialize)]
@@ -122,6 +147,18 @@ struct ExtractDocumentContext {
     format: String,
 }
 
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct HtmlExtractContext {
+    tables: Vec<crate::html_table::HtmlTable>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct XlsxExtractContext {
+    sheets: Vec<crate::xlsx::Sheet>,
+}
+
 #[derive(Debug, Serialize)]
 #[serde(rename_all = "camelCase")]
 struct PdfExtractContext {
@@ -153,6 +190,9 @@ enum DocumentFormat {
     Csv,
     Pdf,
     Json,
+    Html,
+    Xlsx,
+    Qif,
     Other,
 }
 
@@ -162,6 +202,9 @@ impl DocumentFormat {
             Self::Csv => "csv",
             Self::Pdf => "pdf",
             Self::Json => "json",
+            Self::Html => "html",
+            Self::Xlsx => "xlsx",
+            Self::Qif => "qif",
             Self::Other => "other",
         }
     }
@@ -352,6 +395,7 @@ pub fn validate_extracted_transaction(
 }
 
 /// Result of running extraction on a set of documents.
+#[derive(Default)]
 pub struct ExtractionResult {
     pub proposed_transactions: Vec<ExtractedTransaction>,
     pub document_names: Vec<String>,
@@ -359,6 +403,16 @@ pub struct ExtractionResult {
     pub console_logs: Vec<ConsoleLogLine>,
 }
 
+impl ExtractionResult {
+    /// Add the result of extracting more documents.
+    pub fn append(&mut self, other: ExtractionResult) {
+        self.proposed_transactions
+            .extend(other.proposed_transactions);
+        self.document_names.extend(other.document_names);
+        self.console_logs.extend(other.console_logs);
+    }
+}
+
 fn resolve_extraction_mode<'a>(
     extract: Option<&'a str>,
     rules: Option<&'a str>,
@@ -412,6 +466,26 @@ pub fn run_extraction_for_login_account(
     )
 }
 
+/// Run extraction on one file in `staging_dir`, such as a resource a driver
+/// has saved but the scrape has not finalized yet. Nothing is written.
+pub fn run_extraction_for_staged_file(
+    ledger_dir: &Path,
+    staging_dir: &Path,
+    doc_name: &str,
+    account_name: &str,
+    label: &str,
+    extension_name: &str,
+) -> Result<ExtractionResult, Box<dyn std::error::Error + Send + Sync>> {
+    run_extraction_with_documents_dir(
+        ledger_dir,
+        staging_dir,
+        account_name,
+        Some(label),
+        extension_name,
+        &[doc_name.to_string()],
+    )
+}
+
 fn run_extraction_with_documents_dir(
     ledger_dir: &Path,
     documents_dir: &Path,
@@ -431,6 +505,18 @@ fn run_extraction_with_documents_dir(
             },
         )?;
 
+    // Transaction details enrich the other documents' transactions instead
+    // of being extracted themselves.
+    let extracted_names: Vec<&String> = document_names
+        .iter()
+        .filter(|name| {
+            !read_document_info(documents_dir, name)
+                .ok()
+                .flatten()
+                .and_then(|info| info.document_type)
+                .is_some_and(|t| t == crate::scrape::DocumentType::TransactionDetails)
+        })
+        .collect();
     let mut all_proposed = Vec::new();
     let mut all_logs: Vec<ConsoleLogLine> = Vec::new();
 
@@ -441,7 +527,7 @@ fn run_extraction_with_documents_dir(
                 return Err(format!("extract script not found: {}", script_path.display()).into());
             }
 
-            for doc_name in document_names {
+            for &doc_name in &extracted_names {
                 let doc_path = documents_dir.join(doc_name);
                 if !doc_path.exists() {
                     return Err(format!("document not found: {}", doc_path.display()).into());
@@ -462,34 +548,71 @@ fn run_extraction_with_documents_dir(
             }
         }
         ExtractionMode::Rules(rules_rel_path) => {
+            let builtin = builtin_rules(rules_rel_path).map_err(io_error)?;
             let rules_path = extension_dir.join(rules_rel_path);
-            if !rules_path.exists() {
+            if builtin.is_none() && !rules_path.exists() {
                 return Err(format!("rules file not found: {}", rules_path.display()).into());
             }
+            let mapping =
+                if builtin.is_none() && rules_rel_path.to_ascii_lowercase().ends_with(".json") {
+                    Some(read_json_mapping(&rules_path)?)
+                } else {
+                    None
+                };
 
-            for doc_name in document_names {
+            for &doc_name in &extracted_names {
                 let doc_path = documents_dir.join(doc_name);
                 if !doc_path.exists() {
                     return Err(format!("document not found: {}", doc_path.display()).into());
                 }
-                if !doc_name.to_ascii_lowercase().ends_with(".csv") {
-                    return Err(format!(
-                        "rules extraction only supports CSV documents, got: {doc_name}"
-                    )
-                    .into());
-                }
-
-                let proposed = run_rules_extraction(
-                    &rules_path,
-                    &doc_path,
-                    doc_name,
-                    manifest.id_field.as_deref(),
-                )?;
+                let proposed = if let Some(BuiltinRules::Statement(format)) = builtin {
+                    run_statement_extraction(format, &doc_path, doc_name)?
+                } else if builtin == Some(BuiltinRules::Qif) {
+                    let bytes = std::fs::read(&doc_path)?;
+                    let text = String::from_utf8_lossy(&bytes);
+                    extract_qif(&text, doc_name, &crate::qif::QifOptions::default())
+                        .map_err(io_error)?
+                } else if let Some(mapping) = &mapping {
+                    let info = read_document_info(documents_dir, doc_name)?;
+                    if detect_document_format(doc_name, info.as_ref()) != DocumentFormat::Json {
+                        return Err(format!(
+                            "JSON mapping extraction only supports JSON documents, got: {doc_name}"
+                        )
+                        .into());
+                    }
+                    let bytes = std::fs::read(&doc_path)?;
+                    let document: serde_json::Value =
+                        serde_json::from_slice(&bytes).map_err(|error| {
+                            io_error(format!("invalid JSON in {doc_name}: {error}"))
+                        })?;
+                    run_json_mapping_extraction(mapping, &document, doc_name).map_err(io_error)?
+                } else {
+                    let lower_name = doc_name.to_ascii_lowercase();
+                    if !lower_name.ends_with(".csv") && !lower_name.ends_with(".xlsx") {
+                        return Err(format!(
+                            "rules extraction only supports CSV and XLSX documents, got: {doc_name}"
+                        )
+                        .into());
+                    }
+                    run_rules_extraction(
+                        &rules_path,
+                        &doc_path,
+                        doc_name,
+                        manifest.id_field.as_deref(),
+                        manifest.sheet.as_deref(),
+                    )?
+                };
                 all_proposed.extend(proposed);
             }
         }
     }
 
+    let details = crate::transaction_details::load_transaction_details(documents_dir);
+    crate::transaction_details::enrich_transactions(&mut all_proposed, &details);
+    crate::description_cleaning::clean_transactions(ledger_dir, &mut all_proposed);
+    crate::locations::normalize_transactions(&mut all_proposed);
+    crate::mcc::tag_transactions(&mut all_proposed);
+
     Ok(ExtractionResult {
         proposed_transactions: all_proposed,
         document_names: document_names.to_vec(),
@@ -601,18 +724,18 @@ async fn run_extract_script_async(
     let result_json: Result<String, String> = async_with!(context => |ctx| {
         init_quickjs_web_platform(&ctx)?;
 
-        // Install a collecting console global. Each method writes to stderr and
+        // Install a collecting console global. Each method logs an event and
         // appends to console_log. The formatter is non-throwing (no JSON.stringify).
         // Keep aligned with sandbox.rs which uses llrt_console::init instead.
         {
             let console_obj = Object::new(ctx.clone())
                 .map_err(|error| format!("failed to create console object: {error}"))?;
-            for &(method, level, to_stderr) in &[
-                ("log",   "log",   false),
-                ("info",  "info",  false),
-                ("warn",  "warn",  true),
-                ("error", "error", true),
-                ("debug", "debug", false),
+            for &(method, level) in &[
+                ("log",   "log"),
+                ("info",  "info"),
+                ("warn",  "warn"),
+                ("error", "error"),
+                ("debug", "debug"),
             ] {
                 let lb = Arc::clone(&console_log);
                 let doc = doc_name.to_string();
@@ -620,10 +743,11 @@ async fn run_extract_script_async(
                     ctx.clone(),
                     move |_ctx: Ctx<'_>, args: Rest<Value<'_>>| -> rquickjs::Result<()> {
                         let msg = format_console_args(&args);
-                        if to_stderr {
-                            eprintln!("[{level}] {msg}");
-                        } else {
-                            println!("[{level}] {msg}");
+                        match level {
+                            "warn" => tracing::warn!(document = %doc, "{msg}"),
+                            "error" => tracing::error!(document = %doc, "{msg}"),
+                            "debug" => tracing::debug!(document = %doc, "{msg}"),
+                            _ => tracing::info!(document = %doc, "{msg}"),
                         }
                         lb.lock().unwrap_or_else(|e| e.into_inner()).push(ConsoleLogLine {
                             level: level.to_string(),
@@ -644,6 +768,21 @@ async fn run_extract_script_async(
                 .catch(&ctx)
                 .map_err(|error| format!("failed to set console global: {error}"))?;
         }
+        // Install the `refreshmint` helper global for extractors.
+        {
+            let refreshmint_obj = Object::new(ctx.clone())
+                .map_err(|error| format!("failed to create refreshmint object: {error}"))?;
+            let extract_json = rquickjs::Function::new(ctx.clone(), js_extract_json)
+                .map_err(|error| format!("failed to create refreshmint.extractJson: {error}"))?;
+            refreshmint_obj
+                .set("extractJson", extract_json)
+                .catch(&ctx)
+                .map_err(|error| format!("failed to set refreshmint.extractJson: {error}"))?;
+            ctx.globals()
+                .set("refreshmint", refreshmint_obj)
+                .catch(&ctx)
+                .map_err(|error| format!("failed to set refreshmint global: {error}"))?;
+        }
         let module_namespace = Module::import(&ctx, module_specifier.as_str())
             .catch(&ctx)
             .map_err(|error| format!("failed to import {}: {error}", script_path.display()))?
@@ -796,8 +935,22 @@ fn build_extract_script_context(
     let document_info = read_document_info(documents_dir, doc_name)?;
     let format = detect_document_format(doc_name, document_info.as_ref());
 
-    let csv = match format {
-        DocumentFormat::Csv => Some(read_csv_rows(doc_path)?),
+    let xlsx = match format {
+        DocumentFormat::Xlsx => Some(XlsxExtractContext {
+            sheets: crate::xlsx::read_workbook(doc_path)?,
+        }),
+        _ => None,
+    };
+    let csv = match (format, &xlsx) {
+        (DocumentFormat::Csv, _) => Some(read_csv_rows(doc_path)?),
+        // The first sheet with rows, starting at its header, as if exported to CSV.
+        (DocumentFormat::Xlsx, Some(xlsx)) => Some(
+            xlsx.sheets
+                .iter()
+                .find(|sheet| !sheet.rows.is_empty())
+                .map(|sheet| sheet.rows[sheet.header_row..].to_vec())
+                .unwrap_or_default(),
+        ),
         _ => None,
     };
     let pdf = match format {
@@ -811,6 +964,10 @@ fn build_extract_script_context(
         }
         _ => None,
     };
+    let html = match format {
+        DocumentFormat::Html => Some(read_html_context(doc_path)?),
+        _ => None,
+    };
 
     Ok(ExtractScriptContext {
         ledger_dir: ledger_dir.display().to_string(),
@@ -826,6 +983,8 @@ fn build_extract_script_context(
         csv,
         pdf,
         json,
+        html,
+        xlsx,
     })
 }
 
@@ -862,6 +1021,15 @@ fn detect_document_format(
     if lower_name.ends_with(".json") {
         return DocumentFormat::Json;
     }
+    if lower_name.ends_with(".html") || lower_name.ends_with(".htm") {
+        return DocumentFormat::Html;
+    }
+    if lower_name.ends_with(".xlsx") {
+        return DocumentFormat::Xlsx;
+    }
+    if lower_name.ends_with(".qif") {
+        return DocumentFormat::Qif;
+    }
 
     if let Some(info) = document_info {
         let mime = info.mime_type.to_ascii_lowercase();
@@ -874,6 +1042,15 @@ fn detect_document_format(
         if mime.contains("json") {
             return DocumentFormat::Json;
         }
+        if mime.contains("html") {
+            return DocumentFormat::Html;
+        }
+        if mime.contains("spreadsheetml") {
+            return DocumentFormat::Xlsx;
+        }
+        if mime.contains("qif") {
+            return DocumentFormat::Qif;
+        }
     }
 
     DocumentFormat::Other
@@ -891,31 +1068,69 @@ fn guess_document_mime_type(doc_name: &str, format: &str) -> &'static str {
         "csv" => "text/csv",
         "pdf" => "application/pdf",
         "json" => "application/json",
+        "html" => "text/html",
+        "xlsx" => "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+        "qif" => "application/qif",
         _ => "application/octet-stream",
     }
 }
 
+/// Streams the rows of a CSV document one record at a time, so callers that
+/// only need part of a large file never hold all of it in memory.
+pub struct CsvRows {
+    path: std::path::PathBuf,
+    records: csv::StringRecordsIntoIter<std::fs::File>,
+}
+
+impl CsvRows {
+    pub fn open(path: &Path) -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        let reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .from_path(path)?;
+        Ok(Self {
+            path: path.to_path_buf(),
+            records: reader.into_records(),
+        })
+    }
+}
+
+impl Iterator for CsvRows {
+    type Item = Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let record = match self.records.next()? {
+            Ok(record) => record,
+            Err(err) if matches!(err.kind(), csv::ErrorKind::Utf8 { .. }) => {
+                return Some(Err(io_error(format!(
+                    "CSV document is not valid UTF-8: {}",
+                    self.path.display()
+                ))
+                .into()));
+            }
+            Err(err) => return Some(Err(err.into())),
+        };
+        Some(Ok(record
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect()))
+    }
+}
+
 fn read_csv_rows(
     doc_path: &Path,
 ) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
-    let bytes = std::fs::read(doc_path)?;
-    let text = std::str::from_utf8(&bytes).map_err(|_| {
-        io_error(format!(
-            "CSV document is not valid UTF-8: {}",
-            doc_path.display()
-        ))
-    })?;
-    let mut reader = csv::ReaderBuilder::new()
-        .has_headers(false)
-        .from_reader(text.as_bytes());
-
-    let mut rows = Vec::new();
-    for row in reader.records() {
-        let row = row?;
-        rows.push(row.iter().map(std::string::ToString::to_string).collect());
-    }
+    CsvRows::open(doc_path)?.collect()
+}
 
-    Ok(rows)
+fn read_html_context(
+    doc_path: &Path,
+) -> Result<HtmlExtractContext, Box<dyn std::error::Error + Send + Sync>> {
+    let bytes = std::fs::read(doc_path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let tables =
+        crate::html_table::extract_tables(&text, None, &crate::html_table::TableOptions::default())
+            .map_err(io_error)?;
+    Ok(HtmlExtractContext { tables })
 }
 
 fn read_pdf_context(
@@ -1018,25 +1233,49 @@ fn parse_rect(object: &lopdf::Object) -> Option<[f32; 4]> {
     Some([left, bottom, right, top])
 }
 
-/// Run hledger CSV rules-based extraction on a CSV document.
+/// Run hledger CSV rules-based extraction on a CSV or XLSX document.
+///
+/// XLSX documents are converted to CSV (worksheet `sheet`, or the first
+/// worksheet with rows, starting at its header) and piped to hledger.
 fn run_rules_extraction(
     rules_path: &Path,
     doc_path: &Path,
     doc_name: &str,
     id_field: Option<&str>,
+    sheet: Option<&str>,
 ) -> Result<Vec<ExtractedTransaction>, Box<dyn std::error::Error + Send + Sync>> {
+    let xlsx_csv = if detect_document_format(doc_name, None) == DocumentFormat::Xlsx {
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        for row in crate::xlsx::read_sheet_rows(doc_path, sheet)? {
+            writer.write_record(&row)?;
+        }
+        Some(
+            writer
+                .into_inner()
+                .map_err(|err| io_error(err.to_string()))?,
+        )
+    } else {
+        None
+    };
+
     // Use hledger to convert CSV to JSON using the rules file
-    let output = std::process::Command::new(crate::binpath::hledger_path())
-        .arg("print")
-        .arg("--output-format=json")
-        .arg("-f")
-        .arg(doc_path)
+    let mut command = std::process::Command::new(crate::binpath::hledger_path());
+    command.arg("print").arg("--output-format=json").arg("-f");
+    if xlsx_csv.is_some() {
+        command.arg("csv:-");
+    } else {
+        command.arg(doc_path);
+    }
+    command
         .arg("--rules-file")
         .arg(rules_path)
         .env("GIT_CONFIG_GLOBAL", crate::ledger::NULL_DEVICE)
         .env("GIT_CONFIG_SYSTEM", crate::ledger::NULL_DEVICE)
-        .env("GIT_CONFIG_NOSYSTEM", "1")
-        .output()?;
+        .env("GIT_CONFIG_NOSYSTEM", "1");
+    let output = crate::hledger_batch::timed("print-csv", || match xlsx_csv {
+        Some(csv_bytes) => run_with_stdin(command, csv_bytes),
+        None => command.output(),
+    })?;
 
     if !output.status.success() {
         return Err(format!(
@@ -1112,6 +1351,388 @@ fn run_rules_extraction(
     Ok(extracted)
 }
 
+/// Prefix of manifest `rules` values naming a built-in parser instead of a
+/// rules file, e.g. `builtin:mt940`.
+const BUILTIN_RULES_PREFIX: &str = "builtin:";
+
+/// A built-in parser named by a manifest `rules` value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BuiltinRules {
+    Statement(crate::bank_statement::StatementFormat),
+    Qif,
+}
+
+/// The built-in parser named by a manifest `rules` value, if any.
+fn builtin_rules(rules: &str) -> Result<Option<BuiltinRules>, String> {
+    let Some(name) = rules.strip_prefix(BUILTIN_RULES_PREFIX) else {
+        return Ok(None);
+    };
+    if name.trim().eq_ignore_ascii_case("qif") {
+        return Ok(Some(BuiltinRules::Qif));
+    }
+    crate::bank_statement::StatementFormat::from_name(name)
+        .map(|format| Some(BuiltinRules::Statement(format)))
+        .ok_or_else(|| {
+            format!(
+                "unknown built-in rules '{rules}' (expected builtin:mt940, builtin:camt.053, or builtin:qif)"
+            )
+        })
+}
+
+/// Whether a manifest `rules` value names a built-in parser or an existing
+/// rules file in `extension_dir`.
+pub fn rules_available(extension_dir: &Path, rules: &str) -> bool {
+    match builtin_rules(rules) {
+        Ok(Some(_)) => true,
+        Ok(None) => extension_dir.join(rules).exists(),
+        Err(_) => false,
+    }
+}
+
+/// Extract an MT940 or CAMT.053 statement. Each statement entry becomes a
+/// single-sided transaction whose tags carry the structured details.
+fn run_statement_extraction(
+    format: crate::bank_statement::StatementFormat,
+    doc_path: &Path,
+    doc_name: &str,
+) -> Result<Vec<ExtractedTransaction>, Box<dyn std::error::Error + Send + Sync>> {
+    let bytes = std::fs::read(doc_path)?;
+    let text = String::from_utf8_lossy(&bytes);
+    let entries = crate::bank_statement::parse(format, &text)
+        .map_err(|err| io_error(format!("failed to parse {doc_name}: {err}")))?;
+    Ok(entries
+        .into_iter()
+        .map(|entry| {
+            let mut ttags = vec![
+                (
+                    "evidence".to_string(),
+                    format!("{doc_name}:{}:1", entry.position),
+                ),
+                (
+                    "amount".to_string(),
+                    format!("{} {}", entry.amount, entry.currency)
+                        .trim()
+                        .to_string(),
+                ),
+            ];
+            if let Some(reference) = &entry.bank_reference {
+                ttags.push(("bankId".to_string(), reference.clone()));
+            }
+            if let Some(value_date) = entry
+                .value_date
+                .as_ref()
+                .filter(|date| **date != entry.booking_date)
+            {
+                ttags.push(("valueDate".to_string(), value_date.clone()));
+            }
+            if let Some(name) = &entry.counterparty_name {
+                ttags.push(("counterparty".to_string(), name.clone()));
+            }
+            ttags.extend(entry.tags);
+
+            let remittance = entry.remittance.unwrap_or_default();
+            let (tdescription, tcomment) = match entry.counterparty_name {
+                Some(name) => (name, remittance),
+                None => (remittance, String::new()),
+            };
+            ExtractedTransaction {
+                tdate: entry.booking_date,
+                tstatus: if entry.pending { "Pending" } else { "Cleared" }.to_string(),
+                tdescription,
+                tcomment,
+                ttags,
+                tpostings: None,
+            }
+        })
+        .collect())
+}
+
+/// Convert a QIF file to single-sided transactions. Categories, classes,
+/// transfer accounts, check numbers, splits, and investment details become
+/// tags; investment amounts are the action's effect on the account's cash
+/// (see [`crate::qif::QifInvestment::cash_sign`]).
+pub fn extract_qif(
+    text: &str,
+    doc_name: &str,
+    options: &crate::qif::QifOptions,
+) -> Result<Vec<ExtractedTransaction>, String> {
+    let transactions = crate::qif::parse(text, options)
+        .map_err(|err| format!("failed to parse {doc_name}: {err}"))?;
+    Ok(transactions
+        .into_iter()
+        .map(|txn| {
+            let mut amount = txn.amount.clone().unwrap_or_else(|| "0".to_string());
+            if let Some(investment) = &txn.investment {
+                let magnitude = amount.trim_start_matches('-').to_string();
+                amount = match investment.cash_sign() {
+                    0 => "0".to_string(),
+                    sign if sign < 0 => format!("-{magnitude}"),
+                    _ => magnitude,
+                };
+            }
+            let mut ttags = vec![
+                ("evidence".to_string(), format!("{doc_name}:{}:1", txn.line)),
+                (
+                    "amount".to_string(),
+                    format!("{amount} {}", options.commodity).trim().to_string(),
+                ),
+            ];
+            let mut push = |key: &str, value: &Option<String>| {
+                if let Some(value) = value {
+                    ttags.push((key.to_string(), value.clone()));
+                }
+            };
+            push("category", &txn.category);
+            push("class", &txn.class);
+            push("transfer", &txn.transfer);
+            push("checkNumber", &txn.number);
+            if let Some(investment) = &txn.investment {
+                push("action", &Some(investment.action.clone()));
+                push("security", &investment.security);
+                push("quantity", &investment.quantity);
+                push("price", &investment.price);
+                push("commission", &investment.commission);
+            }
+            for split in &txn.splits {
+                let target = match (&split.transfer, &split.category) {
+                    (Some(account), _) => format!("[{account}]"),
+                    (None, Some(category)) => category.clone(),
+                    (None, None) => String::new(),
+                };
+                let mut value = format!("{} {target}", split.amount.as_deref().unwrap_or("0"));
+                if let Some(memo) = &split.memo {
+                    value.push_str(&format!(" ({memo})"));
+                }
+                ttags.push(("split".to_string(), value.trim().to_string()));
+            }
+
+            let (tdescription, tcomment) = match (txn.payee, txn.memo) {
+                (Some(payee), memo) => (payee, memo.unwrap_or_default()),
+                (None, Some(memo)) => (memo, String::new()),
+                (None, None) => match &txn.investment {
+                    Some(investment) => (
+                        format!(
+                            "{} {}",
+                            investment.action,
+                            investment.security.as_deref().unwrap_or_default()
+                        )
+                        .trim()
+                        .to_string(),
+                        String::new(),
+                    ),
+                    None => (String::new(), String::new()),
+                },
+            };
+            let tstatus = match txn.cleared {
+                Some('*' | 'c' | 'C' | 'X' | 'x' | 'R' | 'r') => "Cleared",
+                _ => "Unmarked",
+            };
+            ExtractedTransaction {
+                tdate: txn.date,
+                tstatus: tstatus.to_string(),
+                tdescription,
+                tcomment,
+                ttags,
+                tpostings: None,
+            }
+        })
+        .collect())
+}
+
+/// Declarative mapping from a JSON document to transactions, used when the
+/// manifest `rules` file ends in `.json`.
+///
+/// Field values starting with `@` are JSONPath queries against each matched
+/// transaction item (`@` stands for the item), values starting with `$` are
+/// queries against the whole document, and anything else is a literal.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+struct JsonMapping {
+    /// JSONPath selecting the transaction items in the document.
+    transactions: String,
+    date: String,
+    /// chrono format for `date`. ISO 8601 dates and timestamps are accepted
+    /// when absent.
+    #[serde(default)]
+    date_format: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    amount: String,
+    #[serde(default)]
+    commodity: Option<String>,
+    /// Flip the sign of `amount`, for APIs that report charges as positive.
+    #[serde(default)]
+    negate: bool,
+    #[serde(default)]
+    status: Option<String>,
+    #[serde(default)]
+    comment: Option<String>,
+    #[serde(default)]
+    bank_id: Option<String>,
+    #[serde(default)]
+    tags: std::collections::BTreeMap<String, String>,
+}
+
+fn read_json_mapping(path: &Path) -> Result<JsonMapping, Box<dyn std::error::Error + Send + Sync>> {
+    let text = std::fs::read_to_string(path)?;
+    serde_json::from_str(&text).map_err(|error| {
+        io_error(format!("invalid JSON mapping {}: {error}", path.display())).into()
+    })
+}
+
+/// Map a JSON document to transactions with `mapping`. Evidence refers to
+/// the 1-based position of each item among the `transactions` matches.
+fn run_json_mapping_extraction(
+    mapping: &JsonMapping,
+    document: &serde_json::Value,
+    doc_name: &str,
+) -> Result<Vec<ExtractedTransaction>, String> {
+    let items = crate::json_path::query(document, &mapping.transactions)?;
+    let mut extracted = Vec::with_capacity(items.len());
+    for (index, item) in items.into_iter().enumerate() {
+        let position = index + 1;
+        let field = |spec: &str| resolve_json_mapping_field(spec, document, item);
+        let require = |name: &str, spec: &str| {
+            field(spec)?.ok_or_else(|| format!("{doc_name} item {position}: `{name}` is missing"))
+        };
+
+        let raw_date = require("date", &mapping.date)?;
+        let tdate = normalize_mapped_date(&raw_date, mapping.date_format.as_deref())
+            .ok_or_else(|| format!("{doc_name} item {position}: invalid date `{raw_date}`"))?;
+        let raw_amount = require("amount", &mapping.amount)?;
+        let mut quantity = normalize_mapped_amount(&raw_amount)
+            .ok_or_else(|| format!("{doc_name} item {position}: invalid amount `{raw_amount}`"))?;
+        if mapping.negate {
+            quantity = match quantity.strip_prefix('-') {
+                Some(positive) => positive.to_string(),
+                None => format!("-{quantity}"),
+            };
+        }
+        let commodity = match &mapping.commodity {
+            Some(spec) => field(spec)?.unwrap_or_default(),
+            None => String::new(),
+        };
+        let optional = |spec: &Option<String>| -> Result<String, String> {
+            Ok(match spec {
+                Some(spec) => field(spec)?.unwrap_or_default(),
+                None => String::new(),
+            })
+        };
+
+        let mut ttags = vec![
+            ("evidence".to_string(), format!("{doc_name}:{position}:1")),
+            (
+                "amount".to_string(),
+                if commodity.is_empty() {
+                    quantity
+                } else {
+                    format!("{quantity} {commodity}")
+                },
+            ),
+        ];
+        let bank_id = optional(&mapping.bank_id)?;
+        if !bank_id.is_empty() {
+            ttags.push(("bankId".to_string(), bank_id));
+        }
+        for (name, spec) in &mapping.tags {
+            if let Some(value) = field(spec)?.filter(|value| !value.is_empty()) {
+                ttags.push((name.clone(), value));
+            }
+        }
+        let status = optional(&mapping.status)?;
+
+        extracted.push(ExtractedTransaction {
+            tdate,
+            tstatus: if status.is_empty() {
+                default_status_string()
+            } else {
+                status
+            },
+            tdescription: optional(&mapping.description)?,
+            tcomment: optional(&mapping.comment)?,
+            ttags,
+            tpostings: None,
+        });
+    }
+    Ok(extracted)
+}
+
+/// Resolve one mapping value to text. Missing and `null` values are `None`.
+fn resolve_json_mapping_field(
+    spec: &str,
+    document: &serde_json::Value,
+    item: &serde_json::Value,
+) -> Result<Option<String>, String> {
+    let value = if let Some(relative) = spec.strip_prefix('@') {
+        crate::json_path::query_first(item, &format!("${relative}"))?
+    } else if spec.starts_with('$') {
+        crate::json_path::query_first(document, spec)?
+    } else {
+        return Ok(Some(spec.to_string()));
+    };
+    Ok(match value {
+        None | Some(serde_json::Value::Null) => None,
+        Some(serde_json::Value::String(text)) => Some(text.trim().to_string()),
+        Some(other) => Some(other.to_string()),
+    })
+}
+
+fn normalize_mapped_date(raw: &str, format: Option<&str>) -> Option<String> {
+    let date = match format {
+        Some(format) => chrono::NaiveDate::parse_from_str(raw, format)
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDateTime::parse_from_str(raw, format)
+                    .ok()
+                    .map(|datetime| datetime.date())
+            })?,
+        None => chrono::NaiveDate::parse_from_str(raw.get(..10)?, "%Y-%m-%d").ok()?,
+    };
+    Some(date.format("%Y-%m-%d").to_string())
+}
+
+/// Strip currency symbols, grouping commas, and accounting parentheses from
+/// an amount, returning a plain decimal string.
+fn normalize_mapped_amount(raw: &str) -> Option<String> {
+    let mut text: String = raw
+        .chars()
+        .filter(|c| !c.is_whitespace() && !matches!(c, ',' | '$' | '€' | '£' | '¥'))
+        .collect();
+    if let Some(inner) = text.strip_prefix('(').and_then(|t| t.strip_suffix(')')) {
+        text = format!("-{inner}");
+    }
+    if let Some(positive) = text.strip_prefix('+') {
+        text = positive.to_string();
+    }
+    text.parse::<f64>().ok()?.is_finite().then_some(text)
+}
+
+/// Run `command` with `input` on stdin and collect its output.
+pub(crate) fn run_with_stdin(
+    mut command: std::process::Command,
+    input: Vec<u8>,
+) -> io::Result<std::process::Output> {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    let mut stdin = child
+        .stdin
+        .take()
+        .ok_or_else(|| io_error("failed to open stdin"))?;
+    // Write from another thread so a full stdout pipe cannot deadlock us.
+    let writer = std::thread::spawn(move || stdin.write_all(&input));
+    let output = child.wait_with_output()?;
+    // A failed write (such as hledger exiting early) shows up in the exit
+    // status and stderr, which the caller reports.
+    let _ = writer.join();
+    Ok(output)
+}
+
 /// Format a DecimalRaw as a string quantity.
 fn format_decimal_raw(raw: &crate::hledger::DecimalRaw) -> String {
     let mantissa = raw.decimal_mantissa.as_i64().unwrap_or(0);
@@ -1159,16 +1780,223 @@ pub fn list_documents_for_login_account(
     list_documents_in_dir(&documents_dir)
 }
 
-/// Read raw CSV rows from a document in a login account's documents directory.
+/// Split `document_names` into those to extract and those whose sidecar
+/// marks them `suspect` (an error page or empty export saved as a document).
+pub fn partition_suspect_documents(
+    documents_dir: &Path,
+    document_names: &[String],
+) -> (Vec<String>, Vec<String>) {
+    document_names.iter().cloned().partition(|name| {
+        !read_document_info(documents_dir, name)
+            .ok()
+            .flatten()
+            .is_some_and(|info| info.suspect.is_some())
+    })
+}
+
+/// Keep only documents whose sidecar declares `document_type`.
+pub fn filter_documents_by_type(
+    documents: Vec<DocumentWithInfo>,
+    document_type: crate::scrape::DocumentType,
+) -> Vec<DocumentWithInfo> {
+    documents
+        .into_iter()
+        .filter(|doc| {
+            doc.info
+                .as_ref()
+                .and_then(|info| info.document_type)
+                .is_some_and(|t| t == document_type)
+        })
+        .collect()
+}
+
+/// Keep only documents whose period overlaps `from..=to` (either bound may be
+/// open). A document's period is its `dateRangeStart`/`dateRangeEnd`, else
+/// the scrape session's window, else just its `coverageEndDate`. Documents
+/// without a sidecar are dropped.
+pub fn filter_documents_by_date_range(
+    documents: Vec<DocumentWithInfo>,
+    from: Option<chrono::NaiveDate>,
+    to: Option<chrono::NaiveDate>,
+) -> Vec<DocumentWithInfo> {
+    let parse = |value: Option<&String>| {
+        value.and_then(|value| chrono::NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").ok())
+    };
+    documents
+        .into_iter()
+        .filter(|doc| {
+            let Some(info) = &doc.info else {
+                return false;
+            };
+            let coverage_end = parse(Some(&info.coverage_end_date));
+            let start = parse(info.date_range_start.as_ref())
+                .or_else(|| parse(info.session_date_range_start.as_ref()))
+                .or(coverage_end);
+            let end = parse(info.date_range_end.as_ref())
+                .or_else(|| parse(info.session_date_range_end.as_ref()))
+                .or(coverage_end);
+            let after_from = match (from, end) {
+                (Some(from), Some(end)) => end >= from,
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+            let before_to = match (to, start) {
+                (Some(to), Some(start)) => start <= to,
+                (Some(_), None) => false,
+                (None, _) => true,
+            };
+            after_from && before_to
+        })
+        .collect()
+}
+
+/// Remove login-account documents whose type's retention period has elapsed
+/// since their `coverageEndDate`. Documents cited as evidence by the account
+/// journal are always kept. Returns the (relative) names of pruned documents;
+/// when `dry_run` is set nothing is deleted.
+pub fn prune_expired_login_account_documents(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    today: chrono::NaiveDate,
+    dry_run: bool,
+) -> io::Result<Vec<String>> {
+    let documents_dir = account_journal::login_account_documents_dir(ledger_dir, login_name, label);
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+
+    let mut pruned = Vec::new();
+    for doc in list_documents_in_dir(&documents_dir)? {
+        let Some(info) = doc.info.as_ref() else {
+            continue;
+        };
+        let Some(retention_days) = info.document_type.and_then(|t| t.retention_days()) else {
+            continue;
+        };
+        let Ok(coverage_end) =
+            chrono::NaiveDate::parse_from_str(&info.coverage_end_date, "%Y-%m-%d")
+        else {
+            continue;
+        };
+        if (today - coverage_end).num_days() < retention_days {
+            continue;
+        }
+        let referenced = entries.iter().any(|entry| {
+            entry
+                .evidence
+                .iter()
+                .any(|e| evidence_ref_cites_document(e, &doc.filename))
+        });
+        if referenced {
+            continue;
+        }
+        if !dry_run {
+            std::fs::remove_file(documents_dir.join(&doc.filename))?;
+            let sidecar = documents_dir.join(format!("{}-info.json", doc.filename));
+            if sidecar.exists() {
+                std::fs::remove_file(sidecar)?;
+            }
+        }
+        pruned.push(doc.filename);
+    }
+    Ok(pruned)
+}
+
+pub(crate) fn evidence_ref_cites_document(evidence_ref: &str, document_name: &str) -> bool {
+    evidence_ref
+        .strip_prefix(document_name)
+        .is_some_and(|rest| rest.starts_with(':') || rest.starts_with('#'))
+}
+
+/// Rows returned by the document preview when no limit is given.
+pub const DEFAULT_DOCUMENT_ROWS_LIMIT: usize = 1000;
+
+/// Which rows and columns of a tabular document to read.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct DocumentRowsRequest {
+    /// Rows to skip from the start of the document.
+    #[serde(default)]
+    #[ts(as = "Option<usize>", optional)]
+    pub offset: usize,
+    /// Maximum rows to return; `None` reads to the end.
+    #[serde(default)]
+    #[ts(optional)]
+    pub limit: Option<usize>,
+    /// Zero-based column indexes to keep, in this order. Cells past the end
+    /// of a row come back empty.
+    #[serde(default)]
+    #[ts(optional)]
+    pub columns: Option<Vec<usize>>,
+}
+
+/// One page of a tabular document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct DocumentRowsPage {
+    /// Index of the first returned row in the document.
+    pub offset: usize,
+    pub rows: Vec<Vec<String>>,
+    /// Whether the document has rows after this page.
+    pub has_more: bool,
+}
+
+/// Take the requested page from a stream of rows. Rows after the page are not
+/// read beyond the one needed to tell whether more remain.
+fn page_rows<E>(
+    mut rows: impl Iterator<Item = Result<Vec<String>, E>>,
+    request: &DocumentRowsRequest,
+) -> Result<DocumentRowsPage, E> {
+    for _ in 0..request.offset {
+        match rows.next() {
+            Some(row) => {
+                row?;
+            }
+            None => break,
+        }
+    }
+    let limit = request.limit.unwrap_or(usize::MAX);
+    let mut page = Vec::new();
+    while page.len() < limit {
+        let Some(row) = rows.next() else {
+            break;
+        };
+        let row = row?;
+        page.push(match &request.columns {
+            Some(columns) => columns
+                .iter()
+                .map(|&column| row.get(column).cloned().unwrap_or_default())
+                .collect(),
+            None => row,
+        });
+    }
+    let has_more = page.len() == limit && rows.next().is_some();
+    Ok(DocumentRowsPage {
+        offset: request.offset,
+        rows: page,
+        has_more,
+    })
+}
+
+/// Read a page of raw rows from a CSV or XLSX document in a login account's
+/// documents directory. CSV files are streamed, so only the requested rows
+/// are kept in memory.
 pub fn read_login_account_document_csv_rows(
     ledger_dir: &Path,
     login_name: &str,
     label: &str,
     document_name: &str,
-) -> Result<Vec<Vec<String>>, Box<dyn std::error::Error + Send + Sync>> {
+    request: &DocumentRowsRequest,
+) -> Result<DocumentRowsPage, Box<dyn std::error::Error + Send + Sync>> {
     let documents_dir = account_journal::login_account_documents_dir(ledger_dir, login_name, label);
     let doc_path = documents_dir.join(document_name);
-    read_csv_rows(&doc_path)
+    if detect_document_format(document_name, None) == DocumentFormat::Xlsx {
+        let rows = crate::xlsx::read_sheet_rows(&doc_path, None)?;
+        return page_rows(rows.into_iter().map(Ok), request);
+    }
+    page_rows(CsvRows::open(&doc_path)?, request)
 }
 
 /// Read the raw bytes of a document in a login account's documents directory as a UTF-8 string.
@@ -1185,7 +2013,7 @@ pub fn read_login_account_document_text(
     Ok(String::from_utf8_lossy(&bytes).into_owned())
 }
 
-fn list_documents_in_dir(documents_dir: &Path) -> io::Result<Vec<DocumentWithInfo>> {
+pub(crate) fn list_documents_in_dir(documents_dir: &Path) -> io::Result<Vec<DocumentWithInfo>> {
     if !documents_dir.exists() {
         return Ok(Vec::new());
     }
@@ -1248,7 +2076,8 @@ fn collect_documents_in_dir(
 }
 
 /// A document file with its optional info sidecar.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
 pub struct DocumentWithInfo {
     pub filename: String,
     pub info: Option<crate::scrape::DocumentInfo>,
@@ -1489,6 +2318,36 @@ mod tests {
         assert!(err.contains("exactly one of `extract` or `rules`"));
     }
 
+    #[test]
+    fn build_extract_script_context_reads_html_tables() {
+        let root = temp_dir("extract-html-context");
+        let documents_dir = root.join("documents");
+        fs::create_dir_all(&documents_dir).expect("create docs dir");
+        let doc_name = "activity.html";
+        let doc_path = documents_dir.join(doc_name);
+        fs::write(
+            &doc_path,
+            "<table><tr><th>Date</th><th>Amount</th></tr><tr><td>2024-01-05</td><td>-4.50</td></tr></table>",
+        )
+        .expect("write html document");
+
+        let context = build_extract_script_context(
+            &doc_path,
+            doc_name,
+            &documents_dir,
+            &root,
+            "Assets:Checking",
+            None,
+            "example-extension",
+        )
+        .expect("build context");
+        assert_eq!(context.document.format, "html");
+        let tables = context.html.expect("html context").tables;
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].headers, vec!["Date", "Amount"]);
+        assert_eq!(tables[0].rows, vec![vec!["2024-01-05", "-4.50"]]);
+    }
+
     #[test]
     fn run_extract_script_executes_async_extract_function() {
         let root = temp_dir("extract-script-ok");
@@ -2092,4 +2951,386 @@ export function extract(context) {
         assert!(logs[0].message.contains("true"));
         assert!(logs[0].message.contains("42"));
     }
+
+    #[test]
+    fn prune_expired_documents_keeps_referenced_and_untyped() {
+        let root = temp_dir("prune-expired-documents");
+        let documents_dir =
+            account_journal::login_account_documents_dir(&root, "example-login", "checking");
+        fs::create_dir_all(documents_dir.join("confirmations")).expect("create documents dir");
+
+        let write_doc = |name: &str, document_type: Option<&str>, coverage_end: &str| {
+            fs::write(documents_dir.join(name), "x").expect("write document");
+            let type_field = document_type
+                .map(|t| format!(r#","documentType":"{t}""#))
+                .unwrap_or_default();
+            fs::write(
+                documents_dir.join(format!("{name}-info.json")),
+                format!(
+                    r#"{{"mimeType":"text/html","scrapedAt":"2022-01-01T00:00:00Z","extensionName":"example-extension","loginName":"example-login","label":"checking","scrapeSessionId":"session-1","coverageEndDate":"{coverage_end}"{type_field}}}"#
+                ),
+            )
+            .expect("write sidecar");
+        };
+        write_doc(
+            "confirmations/2022-01-01-old.html",
+            Some("confirmation"),
+            "2022-01-01",
+        );
+        write_doc(
+            "confirmations/2022-01-02-cited.html",
+            Some("confirmation"),
+            "2022-01-02",
+        );
+        write_doc(
+            "confirmations/2026-01-01-new.html",
+            Some("confirmation"),
+            "2026-01-01",
+        );
+        write_doc("2022-01-01-untyped.html", None, "2022-01-01");
+
+        let entry = AccountEntry::new(
+            "2022-01-02".to_string(),
+            EntryStatus::Cleared,
+            "Payment".to_string(),
+            vec!["confirmations/2022-01-02-cited.html#row:1".to_string()],
+            Vec::new(),
+        );
+        let journal_path =
+            account_journal::login_account_journal_path(&root, "example-login", "checking");
+        account_journal::write_journal_at_path(&journal_path, &[entry]).expect("write journal");
+
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).expect("valid date");
+        let dry =
+            prune_expired_login_account_documents(&root, "example-login", "checking", today, true)
+                .expect("dry-run prune");
+        assert_eq!(dry, vec!["confirmations/2022-01-01-old.html".to_string()]);
+        assert!(documents_dir
+            .join("confirmations/2022-01-01-old.html")
+            .exists());
+
+        let pruned =
+            prune_expired_login_account_documents(&root, "example-login", "checking", today, false)
+                .expect("prune");
+        assert_eq!(pruned, dry);
+        assert!(!documents_dir
+            .join("confirmations/2022-01-01-old.html")
+            .exists());
+        assert!(!documents_dir
+            .join("confirmations/2022-01-01-old.html-info.json")
+            .exists());
+
+        let remaining = list_documents_for_login_account(&root, "example-login", "checking")
+            .expect("list documents");
+        assert_eq!(remaining.len(), 3);
+        let confirmations =
+            filter_documents_by_type(remaining, crate::scrape::DocumentType::Confirmation);
+        assert_eq!(confirmations.len(), 2);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn date_range_filter_uses_document_then_session_periods() {
+        let doc = |filename: &str, fields: &str| {
+            DocumentWithInfo {
+            filename: filename.to_string(),
+            info: Some(
+                serde_json::from_str(&format!(
+                    r#"{{"mimeType":"text/csv","scrapedAt":"2025-04-01T00:00:00Z","extensionName":"example-extension","loginName":"example-login","scrapeSessionId":"session-1"{fields}}}"#
+                ))
+                .expect("parse sidecar"),
+            ),
+        }
+        };
+        let documents = vec![
+            doc(
+                "march.pdf",
+                r#","coverageEndDate":"2025-03-31","dateRangeStart":"2025-03-01","dateRangeEnd":"2025-03-31""#,
+            ),
+            doc(
+                "window.csv",
+                r#","coverageEndDate":"2025-04-01","sessionDateRangeStart":"2025-01-01","sessionDateRangeEnd":"2025-04-01""#,
+            ),
+            doc("january.pdf", r#","coverageEndDate":"2025-01-31""#),
+            DocumentWithInfo {
+                filename: "no-sidecar.pdf".to_string(),
+                info: None,
+            },
+        ];
+        let date = |value: &str| chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d").ok();
+        let names = |documents: Vec<DocumentWithInfo>| {
+            documents
+                .into_iter()
+                .map(|doc| doc.filename)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            names(filter_documents_by_date_range(
+                documents.clone(),
+                date("2025-02-01"),
+                date("2025-02-28"),
+            )),
+            vec!["window.csv"]
+        );
+        assert_eq!(
+            names(filter_documents_by_date_range(
+                documents,
+                None,
+                date("2025-01-31"),
+            )),
+            vec!["window.csv", "january.pdf"]
+        );
+    }
+
+    #[test]
+    fn json_mapping_maps_items_to_transactions() {
+        let mapping: JsonMapping = serde_json::from_str(
+            r#"{
+                "transactions": "$.data.items[*]",
+                "date": "@.postedAt",
+                "description": "@.merchant.name",
+                "amount": "@.amount",
+                "commodity": "$.account.currency",
+                "negate": true,
+                "status": "Cleared",
+                "bankId": "@.id",
+                "tags": { "category": "@.category" }
+            }"#,
+        )
+        .unwrap();
+        let document = serde_json::json!({
+            "account": { "currency": "USD" },
+            "data": { "items": [
+                { "id": "t1", "postedAt": "2025-01-02T10:00:00Z", "amount": 4.5,
+                  "merchant": { "name": "CAFE" }, "category": "dining" },
+                { "id": "t2", "postedAt": "2025-01-03", "amount": "(2,000.00)",
+                  "merchant": { "name": "PAYROLL" }, "category": null }
+            ] }
+        });
+
+        let txns = run_json_mapping_extraction(&mapping, &document, "activity.json").unwrap();
+        assert_eq!(txns.len(), 2);
+        assert_eq!(txns[0].tdate, "2025-01-02");
+        assert_eq!(txns[0].tdescription, "CAFE");
+        assert_eq!(txns[0].evidence_refs(), vec!["activity.json:1:1"]);
+        assert_eq!(txns[0].bank_id(), Some("t1"));
+        assert!(txns[0]
+            .ttags
+            .contains(&("amount".to_string(), "-4.5 USD".to_string())));
+        assert!(txns[0]
+            .ttags
+            .contains(&("category".to_string(), "dining".to_string())));
+        assert!(txns[1]
+            .ttags
+            .contains(&("amount".to_string(), "2000.00 USD".to_string())));
+        assert!(!txns[1].ttags.iter().any(|(key, _)| key == "category"));
+        assert!(validate_extracted_transaction(&txns[1], "activity.json").is_ok());
+
+        let entry = txns[0].to_account_entry("Liabilities:Card", "Equity:Staging");
+        assert_eq!(
+            entry.postings[0].amount.as_ref().unwrap().quantity,
+            "-4.5".to_string()
+        );
+
+        let missing = serde_json::json!({ "data": { "items": [{ "postedAt": "2025-01-02" }] } });
+        let err = run_json_mapping_extraction(&mapping, &missing, "activity.json").unwrap_err();
+        assert!(err.contains("`amount` is missing"), "{err}");
+    }
+
+    #[test]
+    fn extract_script_can_query_json_with_refreshmint_extract_json() {
+        let root = temp_dir("extract-script-extract-json");
+        let documents_dir = root.join("documents");
+        fs::create_dir_all(&documents_dir).expect("create docs dir");
+
+        let script_path = root.join("extract.mjs");
+        fs::write(
+            &script_path,
+            r#"
+export function extract(context) {
+  const ids = refreshmint.extractJson(context.json, "$.items[*].id");
+  return ids.map((id, i) => ({
+    tdate: "2025-01-02",
+    tstatus: "Cleared",
+    tdescription: id,
+    tcomment: "",
+    ttags: [["evidence", `${context.document.name}:${i + 1}:1`]],
+  }));
+}
+"#,
+        )
+        .expect("write extract script");
+
+        let doc_name = "activity.json";
+        let doc_path = documents_dir.join(doc_name);
+        fs::write(&doc_path, r#"{"items":[{"id":"a"},{"id":"b"}]}"#).expect("write document");
+
+        let (txns, _logs) = run_extract_script(
+            &root,
+            &script_path,
+            &doc_path,
+            doc_name,
+            &documents_dir,
+            &root,
+            "Assets:Checking",
+            None,
+            "example-extension",
+        )
+        .expect("extractJson should be available to extract scripts");
+        let descriptions: Vec<&str> = txns.iter().map(|t| t.tdescription.as_str()).collect();
+        assert_eq!(descriptions, vec!["a", "b"]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn statement_extraction_maps_entries_to_tagged_transactions() {
+        let root = temp_dir("statement-extraction");
+        let doc_path = root.join("2025-01.sta");
+        fs::write(
+            &doc_path,
+            ":20:STMT\n:25:DE89370400440532013000\n:60F:C241231EUR100,00\n\
+:61:2501020102D12,50NDDTNONREF//REF-1\n\
+:86:/IBAN/DE02120300000000202051/NAME/Stadtwerke/REMI/USTD//Strom Januar\n\
+:62F:C250102EUR87,50\n",
+        )
+        .expect("write statement");
+
+        let Some(BuiltinRules::Statement(format)) = builtin_rules("builtin:mt940").unwrap() else {
+            panic!("expected a statement format");
+        };
+        let txns = run_statement_extraction(format, &doc_path, "2025-01.sta").unwrap();
+        assert_eq!(txns.len(), 1);
+        let txn = &txns[0];
+        assert_eq!(txn.tdate, "2025-01-02");
+        assert_eq!(txn.tdescription, "Stadtwerke");
+        assert_eq!(txn.tcomment, "Strom Januar");
+        assert_eq!(txn.evidence_refs(), vec!["2025-01.sta:4:1"]);
+        assert_eq!(txn.bank_id(), Some("REF-1"));
+        assert!(txn
+            .ttags
+            .contains(&("amount".to_string(), "-12.50 EUR".to_string())));
+        assert!(txn.ttags.contains(&(
+            "counterpartyIban".to_string(),
+            "DE02120300000000202051".to_string()
+        )));
+
+        assert!(builtin_rules("account.rules").unwrap().is_none());
+        assert_eq!(
+            builtin_rules("builtin:QIF").unwrap(),
+            Some(BuiltinRules::Qif)
+        );
+        assert!(builtin_rules("builtin:ofx").is_err());
+        assert!(rules_available(&root, "builtin:camt.053"));
+        assert!(!rules_available(&root, "missing.rules"));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn qif_extraction_tags_categories_splits_and_investment_cash() {
+        let text = "!Type:Bank\nD1/3'25\nT-100.00\nC*\nPGROCER\nMWeekly shop\nSFood:Groceries\nEProduce\n$-60.00\nS[Savings]\n$-40.00\n^\n";
+        let txns = extract_qif(text, "2025-01.qif", &crate::qif::QifOptions::default()).unwrap();
+        assert_eq!(txns.len(), 1);
+        let txn = &txns[0];
+        assert_eq!(txn.tdate, "2025-01-03");
+        assert_eq!(txn.tstatus, "Cleared");
+        assert_eq!(txn.tdescription, "GROCER");
+        assert_eq!(txn.tcomment, "Weekly shop");
+        assert_eq!(txn.evidence_refs(), vec!["2025-01.qif:2:1"]);
+        let splits: Vec<&str> = txn
+            .ttags
+            .iter()
+            .filter(|(k, _)| k == "split")
+            .map(|(_, v)| v.as_str())
+            .collect();
+        assert_eq!(
+            splits,
+            vec!["-60.00 Food:Groceries (Produce)", "-40.00 [Savings]"]
+        );
+        assert!(txn
+            .ttags
+            .contains(&("amount".to_string(), "-100.00 USD".to_string())));
+
+        let text = "!Type:Invst\nD1/4'25\nNBuy\nYAAPL\nQ10\nT1505.00\n^\nD1/5'25\nNReinvDiv\nYAAPL\nQ0.1\nT15.00\n^\n";
+        let txns = extract_qif(text, "brokerage.qif", &crate::qif::QifOptions::default()).unwrap();
+        assert_eq!(txns[0].tdescription, "Buy AAPL");
+        assert!(txns[0]
+            .ttags
+            .contains(&("amount".to_string(), "-1505.00 USD".to_string())));
+        assert!(txns[1]
+            .ttags
+            .contains(&("amount".to_string(), "0 USD".to_string())));
+        assert!(txns[1]
+            .ttags
+            .contains(&("quantity".to_string(), "0.1".to_string())));
+    }
+
+    #[test]
+    fn document_rows_are_paged_and_projected() {
+        let ledger_dir = temp_dir("document-rows");
+        let documents_dir =
+            account_journal::login_account_documents_dir(&ledger_dir, "bank", "checking");
+        fs::create_dir_all(&documents_dir).unwrap();
+        let mut csv = String::from("Date,Description,Amount\n");
+        for row in 1..=10 {
+            csv.push_str(&format!("2025-01-{row:02},Row {row},{row}.00\n"));
+        }
+        fs::write(documents_dir.join("statement.csv"), csv).unwrap();
+
+        let read = |request: DocumentRowsRequest| {
+            read_login_account_document_csv_rows(
+                &ledger_dir,
+                "bank",
+                "checking",
+                "statement.csv",
+                &request,
+            )
+            .unwrap()
+        };
+
+        let all = read(DocumentRowsRequest::default());
+        assert_eq!(all.rows.len(), 11);
+        assert!(!all.has_more);
+
+        let page = read(DocumentRowsRequest {
+            offset: 3,
+            limit: Some(4),
+            columns: Some(vec![2, 0, 7]),
+        });
+        assert_eq!(page.offset, 3);
+        assert!(page.has_more);
+        assert_eq!(
+            page.rows,
+            (3..=6)
+                .map(|row| vec![
+                    format!("{row}.00"),
+                    format!("2025-01-{row:02}"),
+                    String::new()
+                ])
+                .collect::<Vec<_>>()
+        );
+
+        let last = read(DocumentRowsRequest {
+            offset: 9,
+            limit: Some(2),
+            columns: None,
+        });
+        assert_eq!(last.rows.len(), 2);
+        assert!(!last.has_more);
+
+        fs::write(documents_dir.join("bad.csv"), b"a,b\n\xff,c\n").unwrap();
+        let err = read_login_account_document_csv_rows(
+            &ledger_dir,
+            "bank",
+            "checking",
+            "bad.csv",
+            &DocumentRowsRequest::default(),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("not valid UTF-8"), "{err}");
+        let _ = fs::remove_dir_all(ledger_dir);
+    }
 }
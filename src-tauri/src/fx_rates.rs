@@ -0,0 +1,615 @@
+//! Historical exchange rates for foreign-currency accounts.
+//!
+//! [`backfill_prices`] fetches daily rates for a currency against the
+//! reporting currency and writes them as hledger `P` directives to
+//! `prices.journal`, which `general.journal` includes, so `hledger bal -X USD`
+//! values EUR postings at the rate of their day. The date range defaults to
+//! the span of GL postings in the currency.
+//!
+//! Rates come from a [`RateProvider`]; [`provider`] resolves the built-in
+//! ones by name. Fetched rates are cached per provider and currency pair in
+//! `cache/fx-rates/`, together with the date ranges already asked for, so a
+//! rerun only fetches days it has not asked for before (weekends and
+//! holidays have no rate and are not asked for again).
+//!
+//! Providers download with the system `curl`, as hledger is run as a
+//! separate program.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+pub const PRICES_JOURNAL: &str = "prices.journal";
+pub const DEFAULT_PROVIDER: &str = "ecb";
+/// Names accepted by [`provider`].
+pub const PROVIDERS: &[&str] = &["ecb", "frankfurter"];
+
+/// A source of historical daily exchange rates.
+pub trait RateProvider: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Units of `quote` per one unit of `base` for each day in
+    /// `start..=end` that the provider publishes a rate for. Currencies are
+    /// ISO 4217 codes.
+    fn fetch(
+        &self,
+        base: &str,
+        quote: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> io::Result<BTreeMap<NaiveDate, f64>>;
+}
+
+/// The built-in provider called `name`.
+pub fn provider(name: &str) -> Option<Box<dyn RateProvider>> {
+    match name {
+        "ecb" => Some(Box::new(EcbProvider)),
+        "frankfurter" => Some(Box::new(FrankfurterProvider)),
+        _ => None,
+    }
+}
+
+/// European Central Bank reference rates from its SDMX data API. Rates are
+/// published against EUR on TARGET business days; other pairs are crossed
+/// through EUR.
+pub struct EcbProvider;
+
+impl RateProvider for EcbProvider {
+    fn name(&self) -> &'static str {
+        "ecb"
+    }
+
+    fn fetch(
+        &self,
+        base: &str,
+        quote: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> io::Result<BTreeMap<NaiveDate, f64>> {
+        let base_per_eur = ecb_rates_per_eur(base, start, end)?;
+        let quote_per_eur = ecb_rates_per_eur(quote, start, end)?;
+        Ok(cross_rates(base_per_eur.as_ref(), quote_per_eur.as_ref()))
+    }
+}
+
+/// Units of `currency` per EUR, or `None` for EUR itself.
+fn ecb_rates_per_eur(
+    currency: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> io::Result<Option<BTreeMap<NaiveDate, f64>>> {
+    if currency == "EUR" {
+        return Ok(None);
+    }
+    let url = format!(
+        "https://data-api.ecb.europa.eu/service/data/EXR/D.{currency}.EUR.SP00.A\
+         ?startPeriod={start}&endPeriod={end}&format=csvdata"
+    );
+    let body = http_get(&url)?;
+    // The API answers an empty body when there are no observations.
+    if body.trim().is_empty() {
+        return Ok(Some(BTreeMap::new()));
+    }
+    parse_ecb_csv(&body).map(Some)
+}
+
+fn parse_ecb_csv(body: &str) -> io::Result<BTreeMap<NaiveDate, f64>> {
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    let headers = reader.headers().map_err(io::Error::other)?.clone();
+    let column = |name: &str| {
+        headers.iter().position(|h| h == name).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("ECB response has no {name} column"),
+            )
+        })
+    };
+    let (date_col, value_col) = (column("TIME_PERIOD")?, column("OBS_VALUE")?);
+    let mut rates = BTreeMap::new();
+    for record in reader.records() {
+        let record = record.map_err(io::Error::other)?;
+        let date = record.get(date_col).and_then(parse_day);
+        let value = record.get(value_col).and_then(|v| v.parse::<f64>().ok());
+        if let (Some(date), Some(value)) = (date, value) {
+            rates.insert(date, value);
+        }
+    }
+    Ok(rates)
+}
+
+/// `quote` per `base` from both currencies' rates per EUR (`None` is EUR).
+fn cross_rates(
+    base_per_eur: Option<&BTreeMap<NaiveDate, f64>>,
+    quote_per_eur: Option<&BTreeMap<NaiveDate, f64>>,
+) -> BTreeMap<NaiveDate, f64> {
+    match (base_per_eur, quote_per_eur) {
+        (None, None) => BTreeMap::new(),
+        (None, Some(quote)) => quote.clone(),
+        (Some(base), None) => base
+            .iter()
+            .filter(|(_, rate)| **rate != 0.0)
+            .map(|(date, rate)| (*date, 1.0 / rate))
+            .collect(),
+        (Some(base), Some(quote)) => base
+            .iter()
+            .filter(|(_, rate)| **rate != 0.0)
+            .filter_map(|(date, base_rate)| {
+                quote
+                    .get(date)
+                    .map(|quote_rate| (*date, quote_rate / base_rate))
+            })
+            .collect(),
+    }
+}
+
+/// The Frankfurter API (api.frankfurter.app), which republishes ECB rates
+/// for any pair of its currencies.
+pub struct FrankfurterProvider;
+
+#[derive(Deserialize)]
+struct FrankfurterResponse {
+    #[serde(default)]
+    rates: BTreeMap<String, BTreeMap<String, f64>>,
+}
+
+impl RateProvider for FrankfurterProvider {
+    fn name(&self) -> &'static str {
+        "frankfurter"
+    }
+
+    fn fetch(
+        &self,
+        base: &str,
+        quote: &str,
+        start: NaiveDate,
+        end: NaiveDate,
+    ) -> io::Result<BTreeMap<NaiveDate, f64>> {
+        let url = format!("https://api.frankfurter.app/{start}..{end}?from={base}&to={quote}");
+        let response: FrankfurterResponse =
+            serde_json::from_str(&http_get(&url)?).map_err(io::Error::other)?;
+        Ok(response
+            .rates
+            .into_iter()
+            .filter_map(|(date, rates)| Some((parse_day(&date)?, *rates.get(quote)?)))
+            .collect())
+    }
+}
+
+fn http_get(url: &str) -> io::Result<String> {
+    let output = Command::new("curl")
+        .args(["--silent", "--show-error", "--fail", "--location"])
+        .args(["--max-time", "60"])
+        .arg(url)
+        .output()
+        .map_err(|err| io::Error::new(err.kind(), format!("failed to run curl: {err}")))?;
+    if !output.status.success() {
+        return Err(io::Error::other(format!(
+            "GET {url} failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    String::from_utf8(output.stdout).map_err(io::Error::other)
+}
+
+/// What [`backfill_prices`] did.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PriceBackfillResult {
+    pub commodity: String,
+    pub quote: String,
+    pub provider: String,
+    /// The range covered; `None` when there were no postings in the
+    /// commodity and no range was given.
+    pub start: Option<String>,
+    pub end: Option<String>,
+    /// `P` directives written to `prices.journal`.
+    pub added: usize,
+    /// Days that already had a price for the pair.
+    pub existing: usize,
+}
+
+/// Write `P` directives for `commodity` in `quote` for each day in
+/// `start..=end` the provider has a rate for and `prices.journal` lacks.
+/// Missing bounds come from the GL postings in `commodity`.
+pub fn backfill_prices(
+    ledger_dir: &Path,
+    commodity: &str,
+    quote: &str,
+    start: Option<NaiveDate>,
+    end: Option<NaiveDate>,
+    rate_provider: &dyn RateProvider,
+) -> io::Result<PriceBackfillResult> {
+    let base_code = currency_code(commodity)?;
+    let quote_code = currency_code(quote)?;
+    if base_code == quote_code {
+        return Err(invalid_input(format!(
+            "{commodity} and {quote} are the same currency"
+        )));
+    }
+    let mut result = PriceBackfillResult {
+        commodity: commodity.to_string(),
+        quote: quote.to_string(),
+        provider: rate_provider.name().to_string(),
+        start: None,
+        end: None,
+        added: 0,
+        existing: 0,
+    };
+
+    let (start, end) = match (start, end) {
+        (Some(start), Some(end)) => (start, end),
+        (start, end) => match posting_date_range(ledger_dir, commodity)? {
+            Some((first, last)) => (start.unwrap_or(first), end.unwrap_or(last)),
+            None => return Ok(result),
+        },
+    };
+    let end = end.min(chrono::Local::now().date_naive());
+    if start > end {
+        return Err(invalid_input(format!("start {start} is after end {end}")));
+    }
+    result.start = Some(start.to_string());
+    result.end = Some(end.to_string());
+
+    let rates = cached_rates(
+        ledger_dir,
+        rate_provider,
+        &base_code,
+        &quote_code,
+        start,
+        end,
+    )?;
+
+    let prices_path = ledger_dir.join(PRICES_JOURNAL);
+    let existing_text = match fs::read_to_string(&prices_path) {
+        Ok(text) => text,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let priced_days = priced_days(&existing_text, commodity, quote);
+    let mut directives = String::new();
+    for (date, rate) in rates.range(start..=end) {
+        if priced_days.contains(date) {
+            result.existing += 1;
+            continue;
+        }
+        directives.push_str(&format_price_directive(*date, commodity, *rate, quote));
+        result.added += 1;
+    }
+    if result.added == 0 {
+        return Ok(result);
+    }
+
+    let mut text = existing_text;
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    text.push_str(&directives);
+    crate::atomic_file::write_atomic(&prices_path, text.as_bytes())?;
+    ensure_prices_included(ledger_dir)?;
+    if let Err(err) = crate::ledger::commit_prices_journal(
+        ledger_dir,
+        &format!("backfill {commodity} prices in {quote}"),
+    ) {
+        tracing::warn!("git commit failed after price backfill: {err}");
+    }
+    Ok(result)
+}
+
+/// First and last dates of GL postings in `commodity`.
+fn posting_date_range(
+    ledger_dir: &Path,
+    commodity: &str,
+) -> io::Result<Option<(NaiveDate, NaiveDate)>> {
+    let gl_path = ledger_dir.join("general.journal");
+    if !gl_path.exists() {
+        return Ok(None);
+    }
+    let query = format!("cur:^{}$", regex::escape(commodity));
+    let dates = crate::ledger_open::run_hledger_print_with_query(&gl_path, &[query])?
+        .into_iter()
+        .filter_map(|txn| parse_day(&txn.tdate));
+    Ok(dates.fold(None, |range, date| match range {
+        None => Some((date, date)),
+        Some((first, last)) => Some((first.min(date), last.max(date))),
+    }))
+}
+
+/// A provider's cached rates for one pair, and the ranges already fetched.
+/// Dates are `YYYY-MM-DD`.
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct RateCache {
+    fetched: Vec<(String, String)>,
+    rates: BTreeMap<String, f64>,
+}
+
+impl RateCache {
+    fn fetched_ranges(&self) -> Vec<(NaiveDate, NaiveDate)> {
+        self.fetched
+            .iter()
+            .filter_map(|(start, end)| Some((parse_day(start)?, parse_day(end)?)))
+            .collect()
+    }
+
+    fn rates(&self) -> BTreeMap<NaiveDate, f64> {
+        self.rates
+            .iter()
+            .filter_map(|(date, rate)| Some((parse_day(date)?, *rate)))
+            .collect()
+    }
+}
+
+fn parse_day(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+fn rate_cache_path(ledger_dir: &Path, provider: &str, base: &str, quote: &str) -> PathBuf {
+    ledger_dir
+        .join("cache")
+        .join("fx-rates")
+        .join(provider)
+        .join(format!("{base}-{quote}.json"))
+}
+
+fn cached_rates(
+    ledger_dir: &Path,
+    rate_provider: &dyn RateProvider,
+    base: &str,
+    quote: &str,
+    start: NaiveDate,
+    end: NaiveDate,
+) -> io::Result<BTreeMap<NaiveDate, f64>> {
+    let path = rate_cache_path(ledger_dir, rate_provider.name(), base, quote);
+    let mut cache: RateCache = match fs::read_to_string(&path) {
+        Ok(text) => serde_json::from_str(&text).unwrap_or_else(|err| {
+            tracing::warn!("ignoring unreadable FX cache {}: {err}", path.display());
+            RateCache::default()
+        }),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => RateCache::default(),
+        Err(err) => return Err(err),
+    };
+    let mut ranges = cache.fetched_ranges();
+    if let Some((fetch_start, fetch_end)) = uncovered_span(&ranges, start, end) {
+        let fetched = rate_provider.fetch(base, quote, fetch_start, fetch_end)?;
+        cache.rates.extend(
+            fetched
+                .into_iter()
+                .map(|(date, rate)| (date.to_string(), rate)),
+        );
+        ranges.push((fetch_start, fetch_end));
+        cache.fetched = merge_ranges(ranges)
+            .into_iter()
+            .map(|(start, end)| (start.to_string(), end.to_string()))
+            .collect();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let text = serde_json::to_string_pretty(&cache).map_err(io::Error::other)?;
+        crate::atomic_file::write_atomic(&path, text.as_bytes())?;
+    }
+    let mut rates = cache.rates();
+    rates.retain(|date, _| (start..=end).contains(date));
+    Ok(rates)
+}
+
+/// The smallest range covering every day of `start..=end` outside `fetched`.
+fn uncovered_span(
+    fetched: &[(NaiveDate, NaiveDate)],
+    start: NaiveDate,
+    end: NaiveDate,
+) -> Option<(NaiveDate, NaiveDate)> {
+    let covered = |day: &NaiveDate| fetched.iter().any(|(s, e)| s <= day && day <= e);
+    let mut days = start.iter_days().take_while(|day| *day <= end);
+    let first = days.find(|day| !covered(day))?;
+    let last = start
+        .iter_days()
+        .take_while(|day| *day <= end)
+        .filter(|day| !covered(day))
+        .last()
+        .unwrap_or(first);
+    Some((first, last))
+}
+
+/// Sort ranges and join the ones that overlap or touch.
+fn merge_ranges(mut ranges: Vec<(NaiveDate, NaiveDate)>) -> Vec<(NaiveDate, NaiveDate)> {
+    ranges.sort();
+    let mut merged: Vec<(NaiveDate, NaiveDate)> = Vec::with_capacity(ranges.len());
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some((_, last_end)) if start <= last_end.succ_opt().unwrap_or(*last_end) => {
+                *last_end = (*last_end).max(end);
+            }
+            _ => merged.push((start, end)),
+        }
+    }
+    merged
+}
+
+/// Days with a `P` directive for `commodity` in `quote`.
+fn priced_days(text: &str, commodity: &str, quote: &str) -> BTreeSet<NaiveDate> {
+    text.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            if fields.next()? != "P" {
+                return None;
+            }
+            let date = parse_day(fields.next()?)?;
+            if fields.next()?.trim_matches('"') != commodity {
+                return None;
+            }
+            let rest: Vec<&str> = fields.collect();
+            rest.iter()
+                .any(|field| field.trim_matches('"') == quote)
+                .then_some(date)
+        })
+        .collect()
+}
+
+fn format_price_directive(date: NaiveDate, commodity: &str, rate: f64, quote: &str) -> String {
+    format!(
+        "P {date} {} {rate:.6} {}\n",
+        quote_commodity(commodity),
+        quote_commodity(quote)
+    )
+}
+
+/// Quote commodity symbols that hledger would otherwise misread.
+fn quote_commodity(commodity: &str) -> String {
+    if commodity.chars().all(char::is_alphabetic) {
+        commodity.to_string()
+    } else {
+        format!("\"{commodity}\"")
+    }
+}
+
+/// The ISO 4217 code for a journal commodity: a three-letter code as is, or
+/// a common currency symbol.
+pub fn currency_code(commodity: &str) -> io::Result<String> {
+    let trimmed = commodity.trim();
+    let code = match trimmed {
+        "$" => "USD",
+        "€" => "EUR",
+        "£" => "GBP",
+        "¥" => "JPY",
+        "₹" => "INR",
+        "Fr" => "CHF",
+        other => other,
+    };
+    if code.len() == 3 && code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Ok(code.to_ascii_uppercase())
+    } else {
+        Err(invalid_input(format!(
+            "{commodity} is not a currency code or known currency symbol"
+        )))
+    }
+}
+
+/// Add `include prices.journal` to the top of `general.journal` if missing.
+fn ensure_prices_included(ledger_dir: &Path) -> io::Result<()> {
+    let general = ledger_dir.join("general.journal");
+    let content = match fs::read_to_string(&general) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let include = format!("include {PRICES_JOURNAL}");
+    if content.lines().any(|line| line.trim() == include) {
+        return Ok(());
+    }
+    let text = if content.is_empty() {
+        format!("{include}\n")
+    } else {
+        format!("{include}\n\n{content}")
+    };
+    crate::atomic_file::write_atomic(&general, text.as_bytes())
+}
+
+fn invalid_input(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn day(text: &str) -> NaiveDate {
+        parse_day(text).unwrap()
+    }
+
+    #[test]
+    fn ecb_csv_rates_cross_through_eur() {
+        let usd = parse_ecb_csv(
+            "KEY,FREQ,CURRENCY,CURRENCY_DENOM,EXR_TYPE,EXR_SUFFIX,TIME_PERIOD,OBS_VALUE\n\
+             EXR.D.USD.EUR.SP00.A,D,USD,EUR,SP00,A,2024-01-02,1.0956\n\
+             EXR.D.USD.EUR.SP00.A,D,USD,EUR,SP00,A,2024-01-03,1.0919\n",
+        )
+        .unwrap();
+        let gbp = BTreeMap::from([(day("2024-01-02"), 0.8653)]);
+
+        let eur_usd = cross_rates(None, Some(&usd));
+        assert_eq!(eur_usd.get(&day("2024-01-03")), Some(&1.0919));
+        let gbp_usd = cross_rates(Some(&gbp), Some(&usd));
+        assert_eq!(gbp_usd.len(), 1);
+        assert!((gbp_usd[&day("2024-01-02")] - 1.0956 / 0.8653).abs() < 1e-12);
+    }
+
+    #[test]
+    fn only_unfetched_days_are_fetched_again() {
+        let fetched = merge_ranges(vec![
+            (day("2024-02-01"), day("2024-02-10")),
+            (day("2024-01-01"), day("2024-01-31")),
+        ]);
+        assert_eq!(fetched, vec![(day("2024-01-01"), day("2024-02-10"))]);
+        assert_eq!(
+            uncovered_span(&fetched, day("2024-01-15"), day("2024-02-05")),
+            None
+        );
+        assert_eq!(
+            uncovered_span(&fetched, day("2023-12-30"), day("2024-02-12")),
+            Some((day("2023-12-30"), day("2024-02-12")))
+        );
+        assert_eq!(
+            uncovered_span(&fetched, day("2024-02-01"), day("2024-02-12")),
+            Some((day("2024-02-11"), day("2024-02-12")))
+        );
+    }
+
+    #[test]
+    fn existing_directives_are_recognized() {
+        let text = "P 2024-01-02 EUR 1.095600 USD\n\
+                    P 2024-01-03 \"€\" 1.091900 USD\n\
+                    P 2024-01-03 GBP 1.27 USD\n";
+        assert_eq!(
+            priced_days(text, "EUR", "USD"),
+            BTreeSet::from([day("2024-01-02")])
+        );
+        assert_eq!(
+            priced_days(text, "€", "USD"),
+            BTreeSet::from([day("2024-01-03")])
+        );
+        assert_eq!(
+            format_price_directive(day("2024-01-04"), "€", 1.09, "USD"),
+            "P 2024-01-04 \"€\" 1.090000 USD\n"
+        );
+        assert_eq!(currency_code("€").unwrap(), "EUR");
+        assert!(currency_code("VTI2").is_err());
+    }
+
+    #[test]
+    fn included_prices_journal_keeps_print_cache() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-fx-rates-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        let general = dir.join("general.journal");
+        fs::write(&general, "2024-01-02 coffee\n").unwrap();
+        fs::write(dir.join(PRICES_JOURNAL), "P 2024-01-02 EUR 1.095600 USD\n").unwrap();
+        ensure_prices_included(&dir).unwrap();
+
+        let runs = std::cell::Cell::new(0);
+        let run = || {
+            runs.set(runs.get() + 1);
+            Ok(Vec::new())
+        };
+        crate::hledger_batch::print_cached(&general, &[], run).unwrap();
+        crate::hledger_batch::print_cached(&general, &[], run).unwrap();
+        assert_eq!(runs.get(), 1);
+
+        fs::write(
+            dir.join(PRICES_JOURNAL),
+            "P 2024-01-02 EUR 1.095600 USD\nP 2024-01-03 EUR 1.091900 USD\n",
+        )
+        .unwrap();
+        crate::hledger_batch::print_cached(&general, &[], run).unwrap();
+        assert_eq!(runs.get(), 2);
+        let _ = fs::remove_dir_all(dir);
+    }
+}
@@ -62,6 +62,10 @@ pub fn ensure_journal_has_ids(content: &str) -> (String, Vec<String>) {
     let blocks: Vec<String> = split_journal_blocks(content)
         .into_iter()
         .map(|block| {
+            // A trailing comment would become part of the included path.
+            if block.starts_with("include ") {
+                return block;
+            }
             let (updated, id, inserted) = ensure_block_has_id(&block);
             if inserted {
                 inserted_ids.push(id);
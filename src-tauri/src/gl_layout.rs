@@ -0,0 +1,406 @@
+//! Which files hold the general ledger's transactions.
+//!
+//! By default every GL transaction lives in `general.journal`. A ledger with a
+//! long history can shard them by year (`gl/2024.journal`) or by month
+//! (`gl/2024/01.journal`) instead: `general.journal` keeps its directives and
+//! `include`s each shard, so hledger reads the same journal either way, while
+//! git diffs stay within one period's file and a post appends to a small one.
+//! GL refs stay `general.journal:<id>`; they name the ledger, not the file.
+//!
+//! The layout is stored in `gl-layout.json` at the ledger root. Changing it
+//! with [`convert_gl_layout`] moves existing transactions to match; without a
+//! conversion, new transactions follow the layout and old ones stay put.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const GL_LAYOUT_FILE: &str = "gl-layout.json";
+const GENERAL_JOURNAL: &str = "general.journal";
+/// Shards live under this ledger-relative directory.
+pub(crate) const SHARD_DIR: &str = "gl";
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub enum GlLayout {
+    /// Every transaction in `general.journal`.
+    #[default]
+    Single,
+    /// One `gl/<year>.journal` per year.
+    Yearly,
+    /// One `gl/<year>/<month>.journal` per month.
+    Monthly,
+}
+
+impl GlLayout {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            GlLayout::Single => "single",
+            GlLayout::Yearly => "yearly",
+            GlLayout::Monthly => "monthly",
+        }
+    }
+
+    /// The ledger-relative include path for a transaction dated `date`, or
+    /// `None` when it belongs in `general.journal`.
+    fn shard_for(self, date: NaiveDate) -> Option<String> {
+        match self {
+            GlLayout::Single => None,
+            GlLayout::Yearly => Some(format!("{SHARD_DIR}/{}.journal", date.format("%Y"))),
+            GlLayout::Monthly => Some(format!("{SHARD_DIR}/{}.journal", date.format("%Y/%m"))),
+        }
+    }
+}
+
+/// The outcome of [`convert_gl_layout`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct GlLayoutConversion {
+    pub layout: GlLayout,
+    /// Transactions that changed files.
+    pub moved: usize,
+    /// Ledger-relative GL files after the conversion, `general.journal` first.
+    pub files: Vec<String>,
+}
+
+pub fn gl_layout_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(GL_LAYOUT_FILE)
+}
+
+/// The ledger's GL layout. A missing file means a single journal.
+pub fn get_gl_layout(ledger_dir: &Path) -> io::Result<GlLayout> {
+    Ok(crate::bookkeeping::read_optional_json(&gl_layout_path(ledger_dir))?.unwrap_or_default())
+}
+
+/// `general.journal` followed by each shard it includes that exists.
+pub(crate) fn gl_journal_files(ledger_dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let general = ledger_dir.join(GENERAL_JOURNAL);
+    if !general.exists() {
+        return Ok(Vec::new());
+    }
+    let content = fs::read_to_string(&general)?;
+    let mut files = vec![general];
+    files.extend(
+        shard_includes(&content)
+            .into_iter()
+            .map(|rel| ledger_dir.join(rel))
+            .filter(|path| path.is_file()),
+    );
+    Ok(files)
+}
+
+/// The GL file holding the transaction tagged `id: <gl_txn_id>`, with that
+/// file's content.
+pub(crate) fn find_transaction_file(
+    ledger_dir: &Path,
+    gl_txn_id: &str,
+) -> io::Result<Option<(PathBuf, String)>> {
+    let marker = format!("id: {gl_txn_id}");
+    for path in gl_journal_files(ledger_dir)? {
+        let content = fs::read_to_string(&path)?;
+        if crate::gl_journal::split_journal_blocks(&content)
+            .iter()
+            .any(|block| block.contains(&marker))
+        {
+            return Ok(Some((path, content)));
+        }
+    }
+    Ok(None)
+}
+
+/// Append one transaction to the file its date belongs in.
+pub(crate) fn append_transaction(ledger_dir: &Path, text: &str) -> io::Result<()> {
+    append_transactions(ledger_dir, &[text])
+}
+
+/// Append transactions, each to the file its date belongs in, adding an
+/// `include` to `general.journal` for any new shard. Text without a dated
+/// header goes to `general.journal`.
+pub(crate) fn append_transactions(ledger_dir: &Path, transactions: &[&str]) -> io::Result<()> {
+    let layout = get_gl_layout(ledger_dir)?;
+    let mut by_file: BTreeMap<Option<String>, Vec<&str>> = BTreeMap::new();
+    for text in transactions {
+        let shard = transaction_date(text).and_then(|date| layout.shard_for(date));
+        by_file.entry(shard).or_default().push(text.trim_end());
+    }
+    let general = ledger_dir.join(GENERAL_JOURNAL);
+    for (shard, texts) in by_file {
+        let mut text = texts.join("\n\n");
+        text.push('\n');
+        match shard {
+            None => crate::atomic_file::append_block(&general, &text)?,
+            Some(rel) => {
+                let path = ledger_dir.join(&rel);
+                if !path.exists() {
+                    crate::atomic_file::write_atomic(&path, text.as_bytes())?;
+                } else {
+                    crate::atomic_file::append_block(&path, &text)?;
+                }
+                ensure_included(&general, &rel)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite a journal file from its blocks.
+pub(crate) fn write_blocks(path: &Path, blocks: &[String]) -> io::Result<()> {
+    crate::atomic_file::write_atomic(path, render_blocks(blocks).as_bytes())
+}
+
+/// Move every GL transaction into the file `layout` puts it in, rewrite
+/// `general.journal`'s shard includes, delete emptied shards, and save the
+/// layout. Directives and comments stay in `general.journal`.
+pub fn convert_gl_layout(
+    ledger_dir: &Path,
+    layout: GlLayout,
+    lock_owner: &str,
+) -> Result<GlLayoutConversion, Box<dyn std::error::Error + Send + Sync>> {
+    let _gl_lock =
+        crate::login_config::acquire_gl_lock_with_metadata(ledger_dir, lock_owner, "gl-layout")?;
+    let general = ledger_dir.join(GENERAL_JOURNAL);
+    let old_files = gl_journal_files(ledger_dir)?;
+    if old_files.is_empty() {
+        return Err(format!("{GENERAL_JOURNAL} not found").into());
+    }
+
+    let mut kept = Vec::new();
+    let mut shards: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut moved = 0;
+    for path in &old_files {
+        let rel = relative_path(ledger_dir, path);
+        for block in crate::gl_journal::split_journal_blocks(&fs::read_to_string(path)?) {
+            if shard_include(&block).is_some() {
+                continue;
+            }
+            let Some(date) = transaction_date(&block) else {
+                kept.push(block);
+                continue;
+            };
+            let target = layout.shard_for(date);
+            if target.as_deref().unwrap_or(GENERAL_JOURNAL) != rel {
+                moved += 1;
+            }
+            match target {
+                Some(shard) => shards.entry(shard).or_default().push(block),
+                None => kept.push(block),
+            }
+        }
+    }
+
+    for (rel, blocks) in &shards {
+        write_blocks(&ledger_dir.join(rel), blocks)?;
+    }
+    let includes: BTreeSet<String> = shards.keys().cloned().collect();
+    crate::atomic_file::write_atomic(&general, render_general(&kept, &includes).as_bytes())?;
+    for path in &old_files[1..] {
+        if !includes.contains(&relative_path(ledger_dir, path)) {
+            fs::remove_file(path)?;
+            remove_empty_shard_dirs(ledger_dir, path);
+        }
+    }
+    crate::bookkeeping::write_json(&gl_layout_path(ledger_dir), &layout)?;
+
+    let commit_msg = format!("Convert GL to the {} layout", layout.as_str());
+    if let Err(err) = crate::ledger::commit_gl_layout(ledger_dir, &commit_msg) {
+        tracing::warn!("git commit failed after GL layout conversion: {err}");
+    }
+
+    let mut files = vec![GENERAL_JOURNAL.to_string()];
+    files.extend(includes);
+    Ok(GlLayoutConversion {
+        layout,
+        moved,
+        files,
+    })
+}
+
+/// The date of a transaction block's header, if it starts with one.
+/// Secondary dates (`2024-01-05=2024-01-07`) are ignored.
+fn transaction_date(text: &str) -> Option<NaiveDate> {
+    let header = text
+        .lines()
+        .find(|line| !line.trim().is_empty() && !line.trim_start().starts_with(';'))?;
+    if !header.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+    let token = header.split_whitespace().next()?;
+    let primary = token.split('=').next()?.replace(['/', '.'], "-");
+    NaiveDate::parse_from_str(&primary, "%Y-%m-%d").ok()
+}
+
+/// The shard path of an `include gl/...` block.
+fn shard_include(block: &str) -> Option<&str> {
+    let path = block.trim().strip_prefix("include")?;
+    if !path.starts_with(char::is_whitespace) {
+        return None;
+    }
+    let path = path.trim();
+    path.starts_with(&format!("{SHARD_DIR}/")).then_some(path)
+}
+
+fn shard_includes(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .filter_map(shard_include)
+        .map(str::to_string)
+        .collect()
+}
+
+fn ensure_included(general: &Path, rel: &str) -> io::Result<()> {
+    let content = match fs::read_to_string(general) {
+        Ok(content) => content,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(err) => return Err(err),
+    };
+    let mut includes: BTreeSet<String> = shard_includes(&content).into_iter().collect();
+    if !includes.insert(rel.to_string()) {
+        return Ok(());
+    }
+    let kept: Vec<String> = crate::gl_journal::split_journal_blocks(&content)
+        .into_iter()
+        .filter(|block| shard_include(block).is_none())
+        .collect();
+    crate::atomic_file::write_atomic(general, render_general(&kept, &includes).as_bytes())
+}
+
+/// `general.journal`'s blocks with the shard includes last, in order.
+fn render_general(blocks: &[String], includes: &BTreeSet<String>) -> String {
+    let mut content = blocks.join("\n\n");
+    if !includes.is_empty() {
+        if !content.is_empty() {
+            content.push_str("\n\n");
+        }
+        let lines: Vec<String> = includes
+            .iter()
+            .map(|rel| format!("include {rel}"))
+            .collect();
+        content.push_str(&lines.join("\n"));
+    }
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+fn render_blocks(blocks: &[String]) -> String {
+    let mut content = blocks.join("\n\n");
+    if !content.is_empty() {
+        content.push('\n');
+    }
+    content
+}
+
+fn relative_path(ledger_dir: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(ledger_dir).unwrap_or(path);
+    rel.iter()
+        .map(|part| part.to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// Best effort: remove directories under `gl/` left empty by a deleted shard.
+fn remove_empty_shard_dirs(ledger_dir: &Path, shard: &Path) {
+    let root = ledger_dir.join(SHARD_DIR);
+    let mut dir = shard.parent();
+    while let Some(current) = dir {
+        if !current.starts_with(&root) || fs::remove_dir(current).is_err() {
+            break;
+        }
+        dir = current.parent();
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_ledger(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-gl-layout-{prefix}-{}-{now}.refreshmint",
+            std::process::id()
+        ));
+        crate::ledger::new_ledger_at_dir(&dir, None).unwrap();
+        dir
+    }
+
+    fn txn(date: &str, id: &str) -> String {
+        format!("{date} Coffee  ; id: {id}\n    Expenses:Food  3 USD\n    Assets:Cash\n")
+    }
+
+    #[test]
+    fn transactions_are_appended_to_their_shard() {
+        let dir = temp_ledger("append");
+        fs::write(dir.join(GENERAL_JOURNAL), "account Assets:Cash\n").unwrap();
+        crate::bookkeeping::write_json(&gl_layout_path(&dir), &GlLayout::Monthly).unwrap();
+
+        append_transaction(&dir, &txn("2024-02-03", "b")).unwrap();
+        append_transactions(&dir, &[&txn("2024/01/09", "a"), &txn("2024-02-20", "c")]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join(GENERAL_JOURNAL)).unwrap(),
+            "account Assets:Cash\n\ninclude gl/2024/01.journal\ninclude gl/2024/02.journal\n"
+        );
+        let february = fs::read_to_string(dir.join("gl/2024/02.journal")).unwrap();
+        assert!(february.contains("id: b") && february.contains("id: c"));
+        let files = gl_journal_files(&dir).unwrap();
+        assert_eq!(files.len(), 3);
+        let (path, _) = find_transaction_file(&dir, "a").unwrap().unwrap();
+        assert_eq!(path, dir.join("gl/2024/01.journal"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn conversion_splits_and_merges_transactions() {
+        let dir = temp_ledger("convert");
+        let general = format!(
+            "account Assets:Cash\n\n{}\n{}\n{}",
+            txn("2023-12-31", "a"),
+            txn("2024-01-02", "b"),
+            txn("2024-03-04", "c")
+        );
+        fs::write(dir.join(GENERAL_JOURNAL), general).unwrap();
+
+        let monthly = convert_gl_layout(&dir, GlLayout::Monthly, "test").unwrap();
+        assert_eq!(monthly.moved, 3);
+        assert_eq!(
+            monthly.files,
+            vec![
+                "general.journal",
+                "gl/2023/12.journal",
+                "gl/2024/01.journal",
+                "gl/2024/03.journal",
+            ]
+        );
+        assert_eq!(get_gl_layout(&dir).unwrap(), GlLayout::Monthly);
+
+        let yearly = convert_gl_layout(&dir, GlLayout::Yearly, "test").unwrap();
+        assert_eq!(
+            yearly.files,
+            vec!["general.journal", "gl/2023.journal", "gl/2024.journal"]
+        );
+        assert!(!dir.join("gl/2024").exists());
+
+        let single = convert_gl_layout(&dir, GlLayout::Single, "test").unwrap();
+        assert_eq!(single.moved, 3);
+        assert!(!dir.join(SHARD_DIR).exists());
+        let merged = fs::read_to_string(dir.join(GENERAL_JOURNAL)).unwrap();
+        assert!(merged.starts_with("account Assets:Cash\n\n2023-12-31"));
+        assert!(!merged.contains("include"));
+        assert_eq!(merged.matches("; id: ").count(), 3);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,617 @@
+//! GnuCash book reading for [`crate::book_import`].
+//!
+//! XML books (plain or gzip-compressed, as GnuCash saves them) are parsed
+//! directly. SQLite books are read with the `sqlite3` command-line tool.
+//!
+//! Split values are in the transaction's currency and quantities in the
+//! account's commodity; when they differ (securities, foreign-currency
+//! accounts) the posting is the quantity at a total cost of the value.
+//! Scheduled transactions, budgets, business objects (customers, invoices,
+//! ...), and lots are not imported and are listed in the report.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::io::Read;
+use std::path::Path;
+use std::process::Command;
+
+use crate::book_import::{
+    AccountKind, Book, BookAccount, BookAmount, BookPosting, BookPrice, BookTransaction, Decimal,
+    PostingCost,
+};
+use crate::xml_tree::{parse_xml, XmlElement};
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+const SQLITE_MAGIC: &[u8] = b"SQLite format 3\0";
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+
+#[derive(Debug, Default)]
+struct RawBook {
+    accounts: Vec<RawAccount>,
+    transactions: Vec<RawTransaction>,
+    prices: Vec<RawPrice>,
+    /// Things counted while reading, e.g. `("scheduled transactions", 3)`.
+    skipped: BTreeMap<String, usize>,
+}
+
+#[derive(Debug, Default)]
+struct RawAccount {
+    guid: String,
+    name: String,
+    kind: String,
+    parent: Option<String>,
+    commodity: Option<String>,
+    description: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct RawTransaction {
+    date: String,
+    currency: String,
+    num: Option<String>,
+    description: String,
+    notes: Option<String>,
+    voided: bool,
+    splits: Vec<RawSplit>,
+}
+
+#[derive(Debug, Default)]
+struct RawSplit {
+    account: String,
+    memo: Option<String>,
+    reconciled: char,
+    value: (i64, i64),
+    quantity: (i64, i64),
+}
+
+#[derive(Debug, Default)]
+struct RawPrice {
+    date: String,
+    commodity: String,
+    currency: String,
+    value: (i64, i64),
+}
+
+/// Read a GnuCash XML or SQLite book.
+pub fn read_book(path: &Path) -> Result<Book, BoxError> {
+    let bytes = std::fs::read(path)?;
+    let raw = if bytes.starts_with(SQLITE_MAGIC) {
+        read_sqlite_book(path)?
+    } else {
+        let text = if bytes.starts_with(GZIP_MAGIC) {
+            let mut text = String::new();
+            flate2::read::GzDecoder::new(bytes.as_slice()).read_to_string(&mut text)?;
+            text
+        } else {
+            String::from_utf8(bytes)
+                .map_err(|_| std::io::Error::other("GnuCash file is not valid UTF-8"))?
+        };
+        read_xml_book(&text)?
+    };
+    Ok(convert(raw))
+}
+
+fn read_xml_book(text: &str) -> Result<RawBook, BoxError> {
+    let root = parse_xml(text)?;
+    let book = root
+        .children_named("book")
+        .next()
+        .ok_or_else(|| std::io::Error::other("not a GnuCash XML book (no gnc:book element)"))?;
+    let mut raw = RawBook::default();
+    for child in &book.children {
+        match child.name.as_str() {
+            "account" => raw.accounts.push(RawAccount {
+                guid: child.text_at(&["id"]).unwrap_or_default().to_string(),
+                name: child.text_at(&["name"]).unwrap_or_default().to_string(),
+                kind: child.text_at(&["type"]).unwrap_or_default().to_string(),
+                parent: child.text_at(&["parent"]).map(str::to_string),
+                commodity: child.find(&["commodity"]).and_then(xml_commodity),
+                description: child.text_at(&["description"]).map(str::to_string),
+            }),
+            "transaction" => raw.transactions.push(xml_transaction(child)?),
+            "pricedb" => {
+                for price in child.children_named("price") {
+                    raw.prices.push(RawPrice {
+                        date: date_part(price.text_at(&["time", "date"]).unwrap_or_default()),
+                        commodity: price
+                            .find(&["commodity"])
+                            .and_then(xml_commodity)
+                            .unwrap_or_default(),
+                        currency: price
+                            .find(&["currency"])
+                            .and_then(xml_commodity)
+                            .unwrap_or_default(),
+                        value: parse_ratio(price.text_at(&["value"]).unwrap_or_default())?,
+                    });
+                }
+            }
+            "schedxaction" => count(&mut raw.skipped, "scheduled transactions"),
+            "budget" => count(&mut raw.skipped, "budgets"),
+            name if name.starts_with("Gnc") => {
+                count(&mut raw.skipped, &format!("business objects ({name})"))
+            }
+            _ => {}
+        }
+    }
+    Ok(raw)
+}
+
+fn xml_transaction(element: &XmlElement) -> Result<RawTransaction, BoxError> {
+    let slots = element.find(&["slots"]);
+    let mut splits = Vec::new();
+    for split in element
+        .find(&["splits"])
+        .map(|splits| splits.children_named("split").collect::<Vec<_>>())
+        .unwrap_or_default()
+    {
+        splits.push(RawSplit {
+            account: split.text_at(&["account"]).unwrap_or_default().to_string(),
+            memo: split.text_at(&["memo"]).map(str::to_string),
+            reconciled: split
+                .text_at(&["reconciled-state"])
+                .and_then(|state| state.chars().next())
+                .unwrap_or('n'),
+            value: parse_ratio(split.text_at(&["value"]).unwrap_or("0/1"))?,
+            quantity: parse_ratio(split.text_at(&["quantity"]).unwrap_or("0/1"))?,
+        });
+    }
+    Ok(RawTransaction {
+        date: date_part(
+            element
+                .text_at(&["date-posted", "date"])
+                .unwrap_or_default(),
+        ),
+        currency: element
+            .find(&["currency"])
+            .and_then(xml_commodity)
+            .unwrap_or_default(),
+        num: element.text_at(&["num"]).map(str::to_string),
+        description: element
+            .text_at(&["description"])
+            .unwrap_or_default()
+            .to_string(),
+        notes: slots.and_then(|slots| slot_value(slots, "notes")),
+        voided: slots.is_some_and(|slots| slot_value(slots, "void-reason").is_some()),
+        splits,
+    })
+}
+
+/// The commodity symbol of a `<cmdty:space>` / `<cmdty:id>` pair.
+fn xml_commodity(element: &XmlElement) -> Option<String> {
+    let space = element.text_at(&["space"]).unwrap_or_default();
+    if space.eq_ignore_ascii_case("template") {
+        return None;
+    }
+    element.text_at(&["id"]).map(str::to_string)
+}
+
+fn slot_value(slots: &XmlElement, key: &str) -> Option<String> {
+    slots
+        .children_named("slot")
+        .find(|slot| slot.text_at(&["key"]) == Some(key))
+        .and_then(|slot| slot.text_at(&["value"]))
+        .map(str::to_string)
+}
+
+fn read_sqlite_book(path: &Path) -> Result<RawBook, BoxError> {
+    let query = |sql: &str| sqlite_query(path, sql);
+    let text = |row: &serde_json::Value, key: &str| {
+        row.get(key)
+            .and_then(serde_json::Value::as_str)
+            .map(str::to_string)
+            .filter(|value| !value.is_empty())
+    };
+    let int = |row: &serde_json::Value, key: &str| {
+        row.get(key)
+            .and_then(serde_json::Value::as_i64)
+            .unwrap_or_default()
+    };
+
+    let mut commodities: HashMap<String, String> = HashMap::new();
+    for row in query("SELECT guid, namespace, mnemonic FROM commodities")? {
+        if text(&row, "namespace").as_deref() != Some("template") {
+            if let (Some(guid), Some(mnemonic)) = (text(&row, "guid"), text(&row, "mnemonic")) {
+                commodities.insert(guid, mnemonic);
+            }
+        }
+    }
+    let commodity = |guid: Option<String>| guid.and_then(|guid| commodities.get(&guid).cloned());
+
+    let template_root = query("SELECT root_template_guid FROM books")?
+        .first()
+        .and_then(|row| text(row, "root_template_guid"));
+    let mut raw = RawBook::default();
+    for row in query(
+        "SELECT guid, name, account_type, parent_guid, commodity_guid, description FROM accounts",
+    )? {
+        raw.accounts.push(RawAccount {
+            guid: text(&row, "guid").unwrap_or_default(),
+            name: text(&row, "name").unwrap_or_default(),
+            kind: text(&row, "account_type").unwrap_or_default(),
+            parent: text(&row, "parent_guid"),
+            commodity: commodity(text(&row, "commodity_guid")),
+            description: text(&row, "description"),
+        });
+    }
+    if let Some(template_root) = template_root {
+        // Template accounts hold scheduled-transaction templates.
+        let mut excluded: HashSet<String> = HashSet::from([template_root]);
+        loop {
+            let before = excluded.len();
+            for account in &raw.accounts {
+                if account
+                    .parent
+                    .as_ref()
+                    .is_some_and(|p| excluded.contains(p))
+                {
+                    excluded.insert(account.guid.clone());
+                }
+            }
+            if excluded.len() == before {
+                break;
+            }
+        }
+        raw.accounts
+            .retain(|account| !excluded.contains(&account.guid));
+    }
+
+    let mut slots: HashMap<(String, String), String> = HashMap::new();
+    for row in query(
+        "SELECT obj_guid, name, string_val FROM slots WHERE name IN ('notes', 'void-reason')",
+    )? {
+        if let (Some(guid), Some(name), Some(value)) = (
+            text(&row, "obj_guid"),
+            text(&row, "name"),
+            text(&row, "string_val"),
+        ) {
+            slots.insert((guid, name), value);
+        }
+    }
+
+    let mut splits: HashMap<String, Vec<RawSplit>> = HashMap::new();
+    for row in query(
+        "SELECT tx_guid, account_guid, memo, reconcile_state, value_num, value_denom, \
+         quantity_num, quantity_denom FROM splits",
+    )? {
+        splits
+            .entry(text(&row, "tx_guid").unwrap_or_default())
+            .or_default()
+            .push(RawSplit {
+                account: text(&row, "account_guid").unwrap_or_default(),
+                memo: text(&row, "memo"),
+                reconciled: text(&row, "reconcile_state")
+                    .and_then(|state| state.chars().next())
+                    .unwrap_or('n'),
+                value: (int(&row, "value_num"), int(&row, "value_denom")),
+                quantity: (int(&row, "quantity_num"), int(&row, "quantity_denom")),
+            });
+    }
+    for row in query("SELECT guid, currency_guid, num, post_date, description FROM transactions")? {
+        let guid = text(&row, "guid").unwrap_or_default();
+        raw.transactions.push(RawTransaction {
+            date: date_part(&text(&row, "post_date").unwrap_or_default()),
+            currency: commodity(text(&row, "currency_guid")).unwrap_or_default(),
+            num: text(&row, "num"),
+            description: text(&row, "description").unwrap_or_default(),
+            notes: slots.get(&(guid.clone(), "notes".to_string())).cloned(),
+            voided: slots.contains_key(&(guid.clone(), "void-reason".to_string())),
+            splits: splits.remove(&guid).unwrap_or_default(),
+        });
+    }
+    for row in
+        query("SELECT commodity_guid, currency_guid, date, value_num, value_denom FROM prices")?
+    {
+        raw.prices.push(RawPrice {
+            date: date_part(&text(&row, "date").unwrap_or_default()),
+            commodity: commodity(text(&row, "commodity_guid")).unwrap_or_default(),
+            currency: commodity(text(&row, "currency_guid")).unwrap_or_default(),
+            value: (int(&row, "value_num"), int(&row, "value_denom")),
+        });
+    }
+    for (table, label) in [
+        ("schedxactions", "scheduled transactions"),
+        ("budgets", "budgets"),
+        ("invoices", "business objects (invoices)"),
+        ("customers", "business objects (customers)"),
+        ("vendors", "business objects (vendors)"),
+    ] {
+        // Older books may lack some of these tables.
+        if let Ok(rows) = query(&format!("SELECT count(*) AS n FROM {table}")) {
+            let n = rows.first().map_or(0, |row| int(row, "n"));
+            if n > 0 {
+                raw.skipped.insert(label.to_string(), n as usize);
+            }
+        }
+    }
+    Ok(raw)
+}
+
+fn sqlite_query(path: &Path, sql: &str) -> Result<Vec<serde_json::Value>, BoxError> {
+    let output = Command::new("sqlite3")
+        .arg("-readonly")
+        .arg("-json")
+        .arg(path)
+        .arg(sql)
+        .output()
+        .map_err(|err| {
+            std::io::Error::other(format!(
+                "reading a GnuCash SQLite book needs the sqlite3 command-line tool: {err}"
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(std::io::Error::other(format!(
+            "sqlite3 failed: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+        .into());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    if stdout.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(serde_json::from_str(&stdout)?)
+}
+
+fn convert(raw: RawBook) -> Book {
+    let mut book = Book::default();
+    let by_guid: HashMap<&str, &RawAccount> = raw
+        .accounts
+        .iter()
+        .map(|account| (account.guid.as_str(), account))
+        .collect();
+    let mut names: HashMap<&str, String> = HashMap::new();
+    for account in &raw.accounts {
+        if account.kind == "ROOT" {
+            continue;
+        }
+        let mut parts = vec![account.name.as_str()];
+        let mut parent = account.parent.as_deref();
+        while let Some(parent_account) = parent.and_then(|guid| by_guid.get(guid)) {
+            if parent_account.kind == "ROOT" || parts.len() > by_guid.len() {
+                break;
+            }
+            parts.push(parent_account.name.as_str());
+            parent = parent_account.parent.as_deref();
+        }
+        parts.reverse();
+        names.insert(account.guid.as_str(), parts.join(":"));
+    }
+
+    let mut accounts: Vec<(&String, &RawAccount)> = raw
+        .accounts
+        .iter()
+        .filter_map(|account| names.get(account.guid.as_str()).map(|name| (name, account)))
+        .collect();
+    accounts.sort_by(|a, b| a.0.cmp(b.0));
+    for (name, account) in accounts {
+        book.accounts.push(BookAccount {
+            name: name.clone(),
+            kind: account_kind(&account.kind),
+            description: account.description.clone(),
+            opened: None,
+            closed: None,
+        });
+    }
+
+    let mut voided = 0usize;
+    let mut inexact = 0usize;
+    let mut undated = 0usize;
+    'transactions: for txn in &raw.transactions {
+        if txn.date.is_empty() {
+            undated += 1;
+            continue;
+        }
+        if txn.voided {
+            voided += 1;
+        }
+        let mut postings = Vec::new();
+        for split in &txn.splits {
+            let Some(account) = by_guid.get(split.account.as_str()) else {
+                continue 'transactions;
+            };
+            let Some(name) = names.get(split.account.as_str()) else {
+                continue 'transactions;
+            };
+            let (Some(value), Some(quantity)) = (
+                Decimal::from_ratio(split.value.0, split.value.1),
+                Decimal::from_ratio(split.quantity.0, split.quantity.1),
+            ) else {
+                inexact += 1;
+                continue 'transactions;
+            };
+            let commodity = account.commodity.as_deref().unwrap_or(&txn.currency);
+            let (amount, cost) = if commodity == txn.currency || quantity.is_zero() {
+                (BookAmount::new(value, &txn.currency), None)
+            } else {
+                (
+                    BookAmount::new(quantity, commodity),
+                    Some(PostingCost::Total(BookAmount::new(
+                        value.abs(),
+                        &txn.currency,
+                    ))),
+                )
+            };
+            postings.push(BookPosting {
+                account: name.clone(),
+                status: match split.reconciled {
+                    'y' | 'f' => Some('*'),
+                    'c' => Some('!'),
+                    _ => None,
+                },
+                amount,
+                cost,
+                assertion: None,
+                comment: split.memo.clone(),
+                tags: Vec::new(),
+            });
+        }
+        book.transactions.push(BookTransaction {
+            date: txn.date.clone(),
+            status: None,
+            code: txn.num.clone(),
+            description: txn.description.clone(),
+            comment: txn.notes.clone(),
+            tags: if txn.voided {
+                vec![("voided".to_string(), "true".to_string())]
+            } else {
+                Vec::new()
+            },
+            postings,
+        });
+    }
+
+    for price in &raw.prices {
+        match Decimal::from_ratio(price.value.0, price.value.1) {
+            Some(value) if !price.commodity.is_empty() && !price.date.is_empty() => {
+                book.prices.push(BookPrice {
+                    date: price.date.clone(),
+                    commodity: price.commodity.clone(),
+                    price: BookAmount::new(value, &price.currency),
+                })
+            }
+            _ => inexact += 1,
+        }
+    }
+    book.prices.sort_by(|a, b| a.date.cmp(&b.date));
+
+    if voided > 0 {
+        book.unrepresented.push(format!(
+            "{voided} voided transaction(s) kept with zero amounts and tagged voided"
+        ));
+    }
+    if inexact > 0 {
+        book.unrepresented.push(format!(
+            "{inexact} transaction(s) or price(s) skipped: amount has no exact decimal form"
+        ));
+    }
+    if undated > 0 {
+        book.unrepresented.push(format!(
+            "{undated} transaction(s) without a posted date skipped"
+        ));
+    }
+    for (what, n) in raw.skipped {
+        book.unrepresented.push(format!("{n} {what} not imported"));
+    }
+    book
+}
+
+fn account_kind(kind: &str) -> Option<AccountKind> {
+    match kind {
+        "BANK" | "CASH" => Some(AccountKind::Cash),
+        "ASSET" | "STOCK" | "MUTUAL" | "RECEIVABLE" | "CURRENCY" => Some(AccountKind::Asset),
+        "CREDIT" | "LIABILITY" | "PAYABLE" => Some(AccountKind::Liability),
+        "EQUITY" | "TRADING" => Some(AccountKind::Equity),
+        "INCOME" => Some(AccountKind::Revenue),
+        "EXPENSE" => Some(AccountKind::Expense),
+        _ => None,
+    }
+}
+
+fn count(counts: &mut BTreeMap<String, usize>, what: &str) {
+    *counts.entry(what.to_string()).or_default() += 1;
+}
+
+/// `2024-01-05 10:59:00 +0000` -> `2024-01-05`.
+fn date_part(timestamp: &str) -> String {
+    timestamp.trim().chars().take(10).collect()
+}
+
+/// `-12345/100` -> `(-12345, 100)`.
+fn parse_ratio(text: &str) -> Result<(i64, i64), BoxError> {
+    let invalid = || std::io::Error::other(format!("invalid GnuCash amount: {text}"));
+    let (numerator, denominator) = text.trim().split_once('/').unwrap_or((text.trim(), "1"));
+    Ok((
+        numerator.parse().map_err(|_| invalid())?,
+        denominator.parse().map_err(|_| invalid())?,
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::book_import::render_journal;
+    use std::io::Write;
+
+    const BOOK: &str = r#"<?xml version="1.0" encoding="utf-8" ?>
+<gnc-v2 xmlns:gnc="http://www.gnucash.org/XML/gnc" xmlns:act="http://www.gnucash.org/XML/act" xmlns:trn="http://www.gnucash.org/XML/trn" xmlns:split="http://www.gnucash.org/XML/split" xmlns:cmdty="http://www.gnucash.org/XML/cmdty" xmlns:ts="http://www.gnucash.org/XML/ts" xmlns:slot="http://www.gnucash.org/XML/slot" xmlns:price="http://www.gnucash.org/XML/price">
+<gnc:book version="2.0.0">
+<gnc:account version="2.0.0"><act:name>Root Account</act:name><act:id type="guid">root</act:id><act:type>ROOT</act:type></gnc:account>
+<gnc:account version="2.0.0"><act:name>Assets</act:name><act:id type="guid">assets</act:id><act:type>ASSET</act:type>
+  <act:commodity><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></act:commodity><act:parent type="guid">root</act:parent></gnc:account>
+<gnc:account version="2.0.0"><act:name>Checking</act:name><act:id type="guid">checking</act:id><act:type>BANK</act:type>
+  <act:commodity><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></act:commodity><act:parent type="guid">assets</act:parent></gnc:account>
+<gnc:account version="2.0.0"><act:name>Brokerage</act:name><act:id type="guid">vti</act:id><act:type>STOCK</act:type>
+  <act:commodity><cmdty:space>NYSEARCA</cmdty:space><cmdty:id>VTI</cmdty:id></act:commodity><act:parent type="guid">assets</act:parent></gnc:account>
+<gnc:account version="2.0.0"><act:name>Groceries</act:name><act:id type="guid">groceries</act:id><act:type>EXPENSE</act:type>
+  <act:commodity><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></act:commodity><act:parent type="guid">root</act:parent></gnc:account>
+<gnc:transaction version="2.0.0"><trn:id type="guid">t1</trn:id>
+  <trn:currency><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></trn:currency><trn:num>101</trn:num>
+  <trn:date-posted><ts:date>2024-01-05 10:59:00 +0000</ts:date></trn:date-posted><trn:description>Grocer</trn:description>
+  <trn:slots><slot><slot:key>notes</slot:key><slot:value type="string">weekly</slot:value></slot></trn:slots>
+  <trn:splits>
+    <trn:split><split:id type="guid">s1</split:id><split:reconciled-state>y</split:reconciled-state><split:value>-4550/100</split:value><split:quantity>-4550/100</split:quantity><split:account type="guid">checking</split:account></trn:split>
+    <trn:split><split:id type="guid">s2</split:id><split:memo>produce</split:memo><split:reconciled-state>n</split:reconciled-state><split:value>4550/100</split:value><split:quantity>4550/100</split:quantity><split:account type="guid">groceries</split:account></trn:split>
+  </trn:splits></gnc:transaction>
+<gnc:transaction version="2.0.0"><trn:id type="guid">t2</trn:id>
+  <trn:currency><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></trn:currency>
+  <trn:date-posted><ts:date>2024-01-06 10:59:00 +0000</ts:date></trn:date-posted><trn:description>Buy VTI</trn:description>
+  <trn:splits>
+    <trn:split><split:id type="guid">s3</split:id><split:reconciled-state>n</split:reconciled-state><split:value>-50000/100</split:value><split:quantity>-50000/100</split:quantity><split:account type="guid">checking</split:account></trn:split>
+    <trn:split><split:id type="guid">s4</split:id><split:reconciled-state>n</split:reconciled-state><split:value>50000/100</split:value><split:quantity>2/1</split:quantity><split:account type="guid">vti</split:account></trn:split>
+  </trn:splits></gnc:transaction>
+<gnc:pricedb version="1"><price><price:commodity><cmdty:space>NYSEARCA</cmdty:space><cmdty:id>VTI</cmdty:id></price:commodity>
+  <price:currency><cmdty:space>CURRENCY</cmdty:space><cmdty:id>USD</cmdty:id></price:currency>
+  <price:time><ts:date>2024-01-06 10:59:00 +0000</ts:date></price:time><price:value>25000/100</price:value></price></gnc:pricedb>
+<gnc:schedxaction version="2.0.0"><sx:name xmlns:sx="http://www.gnucash.org/XML/sx">Rent</sx:name></gnc:schedxaction>
+</gnc:book>
+</gnc-v2>
+"#;
+
+    #[test]
+    fn reads_gzipped_xml_book() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-gnucash-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .unwrap()
+                .as_nanos()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("book.gnucash");
+        let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+        encoder.write_all(BOOK.as_bytes()).unwrap();
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let book = read_book(&path).unwrap();
+        let names: Vec<&str> = book.accounts.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Assets", "Assets:Brokerage", "Assets:Checking", "Groceries"]
+        );
+        assert_eq!(book.accounts[2].kind, Some(AccountKind::Cash));
+        assert_eq!(
+            book.unrepresented,
+            vec!["1 scheduled transactions not imported"]
+        );
+
+        let journal = render_journal(&book);
+        assert!(
+            journal.contains("account Groceries  ; type: X\n"),
+            "{journal}"
+        );
+        assert!(journal.contains("P 2024-01-06 VTI 250 USD\n"), "{journal}");
+        assert!(
+            journal.contains(
+                "2024-01-05 (101) Grocer  ; weekly\n    * Assets:Checking  -45.5 USD\n    Groceries  45.5 USD  ; produce\n"
+            ),
+            "{journal}"
+        );
+        assert!(
+            journal.contains("    Assets:Brokerage  2 VTI @@ 500 USD\n"),
+            "{journal}"
+        );
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
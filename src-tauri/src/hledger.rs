@@ -195,7 +195,8 @@ pub struct AmountStyle {
     pub asrounding: Rounding,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export_to = "index.ts")]
 pub enum Side {
     L,
     R,
@@ -0,0 +1,361 @@
+//! Shared plumbing for hledger subprocesses.
+//!
+//! Spawning hledger dominates bulk workflows, so parsed `print` output is
+//! reused while the journal is unchanged, many transactions are validated in
+//! a single `check` run, and every invocation is timed for
+//! `get_hledger_timings`.
+
+use serde::Serialize;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::Instant;
+
+use crate::hledger::Transaction;
+
+/// Parsed `print` results kept at once; the cache is cleared when full.
+const PRINT_CACHE_CAPACITY: usize = 32;
+
+/// Aggregate timings for one hledger command.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct HledgerTiming {
+    pub command: String,
+    #[ts(type = "number")]
+    pub invocations: u64,
+    /// Calls answered from cached output without spawning hledger.
+    #[ts(type = "number")]
+    pub cache_hits: u64,
+    pub total_ms: f64,
+    pub max_ms: f64,
+}
+
+/// Journal path and query tokens.
+type PrintKey = (PathBuf, Vec<String>);
+
+struct CachedPrint {
+    content_hash: u64,
+    transactions: Vec<Transaction>,
+}
+
+fn timings() -> &'static Mutex<BTreeMap<String, HledgerTiming>> {
+    static TIMINGS: OnceLock<Mutex<BTreeMap<String, HledgerTiming>>> = OnceLock::new();
+    TIMINGS.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+fn print_cache() -> &'static Mutex<HashMap<PrintKey, CachedPrint>> {
+    static CACHE: OnceLock<Mutex<HashMap<PrintKey, CachedPrint>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Timings recorded since startup or the last reset, by command.
+pub fn hledger_timings() -> Vec<HledgerTiming> {
+    timings()
+        .lock()
+        .map(|timings| timings.values().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub fn reset_hledger_timings() {
+    if let Ok(mut timings) = timings().lock() {
+        timings.clear();
+    }
+}
+
+/// Run one hledger invocation and record how long it took.
+pub(crate) fn timed<T>(command: &str, run: impl FnOnce() -> io::Result<T>) -> io::Result<T> {
+    let _span = crate::perf::span("hledger", command);
+    let started = Instant::now();
+    let result = run();
+    let elapsed_ms = started.elapsed().as_secs_f64() * 1000.0;
+    tracing::debug!("hledger {command} took {elapsed_ms:.1} ms");
+    if let Ok(mut timings) = timings().lock() {
+        let timing = timings
+            .entry(command.to_string())
+            .or_insert_with(|| HledgerTiming {
+                command: command.to_string(),
+                ..HledgerTiming::default()
+            });
+        timing.invocations += 1;
+        timing.total_ms += elapsed_ms;
+        timing.max_ms = timing.max_ms.max(elapsed_ms);
+    }
+    result
+}
+
+fn record_cache_hit(command: &str) {
+    if let Ok(mut timings) = timings().lock() {
+        timings
+            .entry(command.to_string())
+            .or_insert_with(|| HledgerTiming {
+                command: command.to_string(),
+                ..HledgerTiming::default()
+            })
+            .cache_hits += 1;
+    }
+}
+
+/// `hledger print` for `journal_path`, reusing the previous result while
+/// the journal and every file it includes (GL shards, `prices.journal`) are
+/// unchanged. A journal with an include that can't be followed is always
+/// re-read.
+pub(crate) fn print_cached(
+    journal_path: &Path,
+    query_tokens: &[String],
+    run: impl FnOnce() -> io::Result<Vec<Transaction>>,
+) -> io::Result<Vec<Transaction>> {
+    let Some(content_hash) = journal_tree_hash(journal_path) else {
+        return timed("print", run);
+    };
+    let key = (
+        journal_path
+            .canonicalize()
+            .unwrap_or_else(|_| journal_path.to_path_buf()),
+        query_tokens.to_vec(),
+    );
+
+    if let Ok(cache) = print_cache().lock() {
+        if let Some(cached) = cache.get(&key) {
+            if cached.content_hash == content_hash {
+                record_cache_hit("print");
+                return Ok(cached.transactions.clone());
+            }
+        }
+    }
+
+    let transactions = timed("print", run)?;
+    if let Ok(mut cache) = print_cache().lock() {
+        if cache.len() >= PRINT_CACHE_CAPACITY && !cache.contains_key(&key) {
+            cache.clear();
+        }
+        cache.insert(
+            key,
+            CachedPrint {
+                content_hash,
+                transactions: transactions.clone(),
+            },
+        );
+    }
+    Ok(transactions)
+}
+
+/// Hash of `journal_path` and, recursively, each file it includes. `None`
+/// when an include names a glob, a `~` path, or a file that doesn't exist,
+/// since changes to those can't be detected.
+fn journal_tree_hash(journal_path: &Path) -> Option<u64> {
+    let mut hasher = DefaultHasher::new();
+    let mut visited = HashSet::new();
+    hash_journal_file(journal_path, &mut hasher, &mut visited)?;
+    Some(hasher.finish())
+}
+
+fn hash_journal_file(
+    path: &Path,
+    hasher: &mut DefaultHasher,
+    visited: &mut HashSet<PathBuf>,
+) -> Option<()> {
+    let path = path.canonicalize().ok()?;
+    if !visited.insert(path.clone()) {
+        return Some(());
+    }
+    let content = std::fs::read(&path).ok()?;
+    path.hash(hasher);
+    content.hash(hasher);
+    let dir = path.parent()?;
+    for include in include_paths(&content) {
+        if include.starts_with('~') || include.contains(['*', '?', '[']) {
+            return None;
+        }
+        hash_journal_file(&dir.join(include), hasher, visited)?;
+    }
+    Some(())
+}
+
+fn include_paths(content: &[u8]) -> Vec<String> {
+    String::from_utf8_lossy(content)
+        .lines()
+        .filter_map(|line| {
+            line.strip_prefix("include ")
+                .or_else(|| line.strip_prefix("!include "))
+        })
+        .map(|rest| rest.trim().to_string())
+        .filter(|rest| !rest.is_empty())
+        .collect()
+}
+
+/// Check many transactions with as few runs of `check` as possible: all of
+/// them at once, then each half of a failing batch until every failure is
+/// pinned to a single transaction. A failing batch whose halves both pass,
+/// such as two balance assertions that only conflict together, gives every
+/// transaction in it the batch's error. Returns one error message (or
+/// `None`) per transaction.
+pub(crate) fn check_transactions_batched(
+    transactions: &[String],
+    check: &dyn Fn(&str) -> io::Result<()>,
+) -> Vec<Option<String>> {
+    let mut results = vec![None; transactions.len()];
+    check_range(transactions, 0, check, &mut results);
+    results
+}
+
+fn check_range(
+    transactions: &[String],
+    offset: usize,
+    check: &dyn Fn(&str) -> io::Result<()>,
+    results: &mut [Option<String>],
+) {
+    if transactions.is_empty() {
+        return;
+    }
+    let Err(err) = check(&transactions.join("\n")) else {
+        return;
+    };
+    if transactions.len() == 1 {
+        results[offset] = Some(err.to_string());
+        return;
+    }
+    let mid = transactions.len() / 2;
+    check_range(&transactions[..mid], offset, check, results);
+    check_range(&transactions[mid..], offset + mid, check, results);
+    let range = offset..offset + transactions.len();
+    if results[range.clone()].iter().all(Option::is_none) {
+        let message = format!(
+            "fails together with transactions {}-{}: {err}",
+            range.start + 1,
+            range.end
+        );
+        for result in &mut results[range] {
+            *result = Some(message.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    #[test]
+    fn batched_check_runs_once_when_everything_passes() {
+        let transactions: Vec<String> = (0..100).map(|n| format!("txn {n}\n")).collect();
+        let calls = Cell::new(0);
+        let results = check_transactions_batched(&transactions, &|_| {
+            calls.set(calls.get() + 1);
+            Ok(())
+        });
+        assert_eq!(calls.get(), 1);
+        assert!(results.iter().all(Option::is_none));
+    }
+
+    #[test]
+    fn batched_check_pins_failures_to_single_transactions() {
+        let transactions: Vec<String> = (0..16).map(|n| format!("txn {n}\n")).collect();
+        let calls = Cell::new(0);
+        let results = check_transactions_batched(&transactions, &|text| {
+            calls.set(calls.get() + 1);
+            if text.contains("txn 5\n") || text.contains("txn 12\n") {
+                Err(io::Error::other("unbalanced"))
+            } else {
+                Ok(())
+            }
+        });
+        let failed: Vec<usize> = results
+            .iter()
+            .enumerate()
+            .filter_map(|(index, result)| result.as_ref().map(|_| index))
+            .collect();
+        assert_eq!(failed, vec![5, 12]);
+        assert!(calls.get() < transactions.len());
+    }
+
+    #[test]
+    fn batched_check_reports_failures_that_only_happen_together() {
+        let transactions: Vec<String> = (0..4).map(|n| format!("txn {n}\n")).collect();
+        let results = check_transactions_batched(&transactions, &|text| {
+            if text.contains("txn 0\n") && text.contains("txn 3\n") {
+                Err(io::Error::other("balance assertion failed"))
+            } else {
+                Ok(())
+            }
+        });
+        assert!(results.iter().all(|result| {
+            result.as_deref()
+                == Some("fails together with transactions 1-4: balance assertion failed")
+        }));
+    }
+
+    #[test]
+    fn print_output_is_reused_until_the_journal_changes() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-hledger-batch-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let journal = dir.join("general.journal");
+        std::fs::write(&journal, "2025-01-01 a\n").unwrap();
+
+        let runs = Cell::new(0);
+        let run = || {
+            runs.set(runs.get() + 1);
+            Ok(Vec::new())
+        };
+        print_cached(&journal, &[], run).unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        assert_eq!(runs.get(), 1);
+        print_cached(&journal, &["acct:Assets".to_string()], run).unwrap();
+        assert_eq!(runs.get(), 2);
+
+        std::fs::write(&journal, "2025-01-01 b\n").unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        assert_eq!(runs.get(), 3);
+
+        std::fs::write(&journal, "include other.journal\n").unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        assert_eq!(runs.get(), 5);
+
+        std::fs::write(&journal, "include *.journal\n").unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        assert_eq!(runs.get(), 6);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[test]
+    fn print_output_is_reused_across_gl_shards_until_one_changes() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-hledger-batch-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        std::fs::create_dir_all(dir.join("gl")).unwrap();
+        let journal = dir.join("general.journal");
+        std::fs::write(
+            &journal,
+            "include gl/2024.journal\ninclude gl/2025.journal\n",
+        )
+        .unwrap();
+        std::fs::write(dir.join("gl/2024.journal"), "2024-01-01 a\n").unwrap();
+        std::fs::write(dir.join("gl/2025.journal"), "2025-01-01 b\n").unwrap();
+
+        let runs = Cell::new(0);
+        let run = || {
+            runs.set(runs.get() + 1);
+            Ok(Vec::new())
+        };
+        print_cached(&journal, &[], run).unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        assert_eq!(runs.get(), 1);
+
+        std::fs::write(dir.join("gl/2024.journal"), "2024-01-01 c\n").unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        print_cached(&journal, &[], run).unwrap();
+        assert_eq!(runs.get(), 2);
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
@@ -0,0 +1,286 @@
+//! Structured rows from HTML `<table>` elements.
+//!
+//! Used by extraction for saved HTML documents. The rules match
+//! `page.extractTable()` in the scraper runtime so a driver can switch
+//! between scraping a table live and extracting it from a saved page:
+//!
+//! - cells spanning several columns or rows (`colspan` / `rowspan`) repeat
+//!   their text in every column and row they cover
+//! - cell text has non-breaking spaces replaced and whitespace collapsed
+//! - header rows are the leading rows inside `<thead>` or made only of
+//!   `<th>` cells, unless `headerRows` is given; several header rows are
+//!   joined per column with a space
+//! - rows whose cells are all empty are dropped
+
+use kuchikiki::traits::TendrilSink;
+use kuchikiki::NodeRef;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Upper bound on `colspan` / `rowspan`, as in browsers.
+const MAX_SPAN: usize = 1000;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TableOptions {
+    /// Number of leading rows to treat as headers. Detected when absent.
+    #[serde(default)]
+    pub header_rows: Option<usize>,
+    #[serde(default)]
+    pub include_empty_rows: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HtmlTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+    /// `rows` keyed by header. Empty when the table has no header row.
+    pub records: Vec<BTreeMap<String, String>>,
+}
+
+/// One `<tr>` before span expansion.
+struct RawRow {
+    cells: Vec<RawCell>,
+    in_thead: bool,
+}
+
+struct RawCell {
+    text: String,
+    is_header: bool,
+    colspan: usize,
+    rowspan: usize,
+}
+
+/// Extract every table matching `selector` (default `table`) in `html`.
+pub fn extract_tables(
+    html: &str,
+    selector: Option<&str>,
+    options: &TableOptions,
+) -> Result<Vec<HtmlTable>, String> {
+    let document = kuchikiki::parse_html().one(html).document_node;
+    let selector = selector.map(str::trim).filter(|s| !s.is_empty());
+    let matches = document
+        .select(selector.unwrap_or("table"))
+        .map_err(|()| format!("invalid table selector: {}", selector.unwrap_or("table")))?;
+    Ok(matches
+        .map(|table| build_table(raw_rows(table.as_node()), options))
+        .collect())
+}
+
+/// Rows of `table`, excluding rows of nested tables.
+fn raw_rows(table: &NodeRef) -> Vec<RawRow> {
+    let mut rows = Vec::new();
+    for child in table.children() {
+        match element_name(&child).as_deref() {
+            Some("tr") => rows.push(raw_row(&child, false)),
+            Some(section @ ("thead" | "tbody" | "tfoot")) => {
+                for row in child.children() {
+                    if element_name(&row).as_deref() == Some("tr") {
+                        rows.push(raw_row(&row, section == "thead"));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    rows
+}
+
+fn raw_row(row: &NodeRef, in_thead: bool) -> RawRow {
+    let cells = row
+        .children()
+        .filter_map(|cell| {
+            let name = element_name(&cell)?;
+            if name != "td" && name != "th" {
+                return None;
+            }
+            let span = |attr: &str| {
+                cell.as_element()
+                    .and_then(|e| e.attributes.borrow().get(attr).map(str::to_string))
+                    .and_then(|value| value.trim().parse::<usize>().ok())
+                    .map_or(1, |n| n.clamp(1, MAX_SPAN))
+            };
+            Some(RawCell {
+                text: normalize_cell_text(&cell.text_contents()),
+                is_header: name == "th",
+                colspan: span("colspan"),
+                rowspan: span("rowspan"),
+            })
+        })
+        .collect();
+    RawRow { cells, in_thead }
+}
+
+fn element_name(node: &NodeRef) -> Option<String> {
+    node.as_element()
+        .map(|e| e.name.local.to_string().to_ascii_lowercase())
+}
+
+/// Replace non-breaking spaces, collapse whitespace runs, and trim.
+pub fn normalize_cell_text(text: &str) -> String {
+    text.replace('\u{a0}', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn build_table(rows: Vec<RawRow>, options: &TableOptions) -> HtmlTable {
+    let header_count = options.header_rows.unwrap_or_else(|| {
+        rows.iter()
+            .take_while(|row| {
+                !row.cells.is_empty()
+                    && (row.in_thead || row.cells.iter().all(|cell| cell.is_header))
+            })
+            .count()
+    });
+    let grid = expand_spans(&rows);
+    let width = grid.iter().map(Vec::len).max().unwrap_or(0);
+    let mut grid: Vec<Vec<String>> = grid
+        .into_iter()
+        .map(|mut row| {
+            row.resize(width, String::new());
+            row
+        })
+        .collect();
+    let body = grid.split_off(header_count.min(grid.len()));
+
+    let headers: Vec<String> = (0..width)
+        .map(|col| {
+            let mut parts: Vec<&str> = Vec::new();
+            for row in &grid {
+                let text = row[col].as_str();
+                if !text.is_empty() && parts.last() != Some(&text) {
+                    parts.push(text);
+                }
+            }
+            parts.join(" ")
+        })
+        .collect();
+    let headers = if grid.is_empty() { Vec::new() } else { headers };
+
+    let rows: Vec<Vec<String>> = body
+        .into_iter()
+        .filter(|row| options.include_empty_rows || row.iter().any(|cell| !cell.is_empty()))
+        .collect();
+    let records = if headers.is_empty() {
+        Vec::new()
+    } else {
+        rows.iter()
+            .map(|row| {
+                headers
+                    .iter()
+                    .zip(row)
+                    .filter(|(header, _)| !header.is_empty())
+                    .map(|(header, cell)| (header.clone(), cell.clone()))
+                    .collect()
+            })
+            .collect()
+    };
+    HtmlTable {
+        headers,
+        rows,
+        records,
+    }
+}
+
+/// Lay cells out on a grid, repeating spanned cells.
+fn expand_spans(rows: &[RawRow]) -> Vec<Vec<String>> {
+    // Column -> (rows still covered, text) for rowspans from earlier rows.
+    let mut pending: BTreeMap<usize, (usize, String)> = BTreeMap::new();
+    let mut grid = Vec::with_capacity(rows.len());
+    for row in rows {
+        let mut out: Vec<String> = Vec::new();
+        for cell in &row.cells {
+            while let Some(text) = take_pending(&mut pending, out.len()) {
+                out.push(text);
+            }
+            for _ in 0..cell.colspan {
+                if cell.rowspan > 1 {
+                    pending.insert(out.len(), (cell.rowspan - 1, cell.text.clone()));
+                }
+                out.push(cell.text.clone());
+            }
+        }
+        // Rowspans at or right of the row's last cell still cover this row.
+        while let Some(col) = pending.range(out.len()..).next().map(|(col, _)| *col) {
+            out.resize(col, String::new());
+            if let Some(text) = take_pending(&mut pending, col) {
+                out.push(text);
+            }
+        }
+        grid.push(out);
+    }
+    grid
+}
+
+fn take_pending(pending: &mut BTreeMap<usize, (usize, String)>, col: usize) -> Option<String> {
+    let (remaining, text) = pending.get_mut(&col)?;
+    let text = text.clone();
+    *remaining -= 1;
+    if *remaining == 0 {
+        pending.remove(&col);
+    }
+    Some(text)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detects_headers_and_expands_spans() {
+        let html = r#"
+            <table id="activity">
+              <thead>
+                <tr><th rowspan="2">Date</th><th rowspan="2">Description</th><th colspan="2">Amount</th></tr>
+                <tr><th>Debit</th><th>Credit</th></tr>
+              </thead>
+              <tbody>
+                <tr><td>01/02/2025</td><td>  COFFEE&nbsp;&nbsp;SHOP
+                    #12 </td><td>4.50</td><td></td></tr>
+                <tr><td colspan="4"> </td></tr>
+                <tr><td rowspan="2">01/03/2025</td><td>PAYROLL</td><td></td><td>2000.00</td></tr>
+                <tr><td>GROCER</td><td>61.20</td><td></td></tr>
+              </tbody>
+            </table>
+            <table><tr><td>other</td></tr></table>
+        "#;
+        let tables = extract_tables(html, Some("#activity"), &TableOptions::default()).unwrap();
+        assert_eq!(tables.len(), 1);
+        let table = &tables[0];
+        assert_eq!(
+            table.headers,
+            vec!["Date", "Description", "Amount Debit", "Amount Credit"]
+        );
+        assert_eq!(
+            table.rows,
+            vec![
+                vec!["01/02/2025", "COFFEE SHOP #12", "4.50", ""],
+                vec!["01/03/2025", "PAYROLL", "", "2000.00"],
+                vec!["01/03/2025", "GROCER", "61.20", ""],
+            ]
+        );
+        assert_eq!(table.records[2]["Description"], "GROCER");
+        assert_eq!(table.records[0]["Amount Debit"], "4.50");
+    }
+
+    #[test]
+    fn header_rows_option_overrides_detection() {
+        let html = "<table><tr><td>Date</td><td>Amount</td></tr><tr><td>2025-01-01</td><td>5</td></tr></table>";
+        let detected = extract_tables(html, None, &TableOptions::default()).unwrap();
+        assert!(detected[0].headers.is_empty());
+        assert!(detected[0].records.is_empty());
+        assert_eq!(detected[0].rows.len(), 2);
+
+        let options = TableOptions {
+            header_rows: Some(1),
+            include_empty_rows: false,
+        };
+        let tables = extract_tables(html, None, &options).unwrap();
+        assert_eq!(tables[0].headers, vec!["Date", "Amount"]);
+        assert_eq!(tables[0].records[0]["Amount"], "5");
+        assert!(extract_tables(html, Some("table["), &options).is_err());
+    }
+}
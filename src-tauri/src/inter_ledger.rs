@@ -0,0 +1,392 @@
+//! Moving general journal entries between two refreshmint ledgers.
+//!
+//! When one card pays for both business and personal spending, the business
+//! entries are exported to the business ledger. The target ledger receives a
+//! copy of each entry's category postings funded by `Liabilities:Due To:<source>`.
+//! The source ledger keeps the original entry and gets a reclassifying entry
+//! that moves the same postings to `Assets:Due From:<target>`. Both ledgers'
+//! operations logs record which transactions were created for which source id.
+
+use std::collections::HashSet;
+use std::io;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::hledger::{Amount, Status, Transaction};
+use crate::operations::{self, ExportedEntry, GlOperation};
+
+const EXPORTED_ENTRY_TAG: &str = "exported-entry";
+const IMPORTED_ENTRY_TAG: &str = "imported-entry";
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LedgerExport {
+    pub target_ledger: String,
+    pub exported: Vec<ExportedEntry>,
+    pub skipped: Vec<SkippedExport>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SkippedExport {
+    pub gl_txn_id: String,
+    pub reason: String,
+}
+
+/// Copy the general journal entries matching `query` into `target_dir`,
+/// booking them against due-to/due-from accounts in both ledgers.
+pub fn export_entries_to_ledger(
+    ledger_dir: &Path,
+    query: &str,
+    target_dir: &Path,
+) -> Result<LedgerExport, Box<dyn std::error::Error>> {
+    crate::ledger::require_refreshmint_extension(target_dir)?;
+    if ledger_dir.canonicalize()? == target_dir.canonicalize()? {
+        return Err(invalid_input("target ledger must differ from the source ledger").into());
+    }
+    let query_tokens = crate::ledger_open::tokenize_query(query);
+    if query_tokens.is_empty() {
+        return Err(invalid_input("query is required").into());
+    }
+    let source_name = ledger_account_name(ledger_dir)?;
+    let target_name = ledger_account_name(target_dir)?;
+
+    let journal_path = ledger_dir.join("general.journal");
+    let already_exported: HashSet<String> = crate::ledger_open::run_hledger_print(&journal_path)?
+        .iter()
+        .filter_map(|txn| tag_value(txn, EXPORTED_ENTRY_TAG))
+        .map(str::to_string)
+        .collect();
+    let matches = crate::ledger_open::run_hledger_print_with_query(&journal_path, &query_tokens)?;
+
+    let mut exported = Vec::new();
+    let mut skipped = Vec::new();
+    let mut source_text = String::new();
+    let mut target_text = String::new();
+    for txn in &matches {
+        let Some(gl_txn_id) = crate::ledger_open::gl_transaction_id(txn) else {
+            continue;
+        };
+        let skip_reason = if tag_value(txn, EXPORTED_ENTRY_TAG).is_some()
+            || tag_value(txn, IMPORTED_ENTRY_TAG).is_some()
+        {
+            Some("entry was created by a ledger export")
+        } else if already_exported.contains(gl_txn_id) {
+            Some("entry was already exported")
+        } else {
+            None
+        };
+        if let Some(reason) = skip_reason {
+            skipped.push(SkippedExport {
+                gl_txn_id: gl_txn_id.to_string(),
+                reason: reason.to_string(),
+            });
+            continue;
+        }
+        let postings = match category_postings(txn) {
+            Ok(postings) if !postings.is_empty() => postings,
+            Ok(_) => {
+                skipped.push(SkippedExport {
+                    gl_txn_id: gl_txn_id.to_string(),
+                    reason: "entry has no income or expense postings".to_string(),
+                });
+                continue;
+            }
+            Err(reason) => {
+                skipped.push(SkippedExport {
+                    gl_txn_id: gl_txn_id.to_string(),
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let entry = ExportedEntry {
+            source_txn_id: gl_txn_id.to_string(),
+            target_txn_id: uuid::Uuid::new_v4().to_string(),
+            reclass_txn_id: uuid::Uuid::new_v4().to_string(),
+        };
+        target_text.push_str(&format_entry(
+            txn,
+            &entry.target_txn_id,
+            &[
+                (IMPORTED_ENTRY_TAG, gl_txn_id),
+                ("imported-from", &source_name),
+            ],
+            postings
+                .iter()
+                .map(|(account, amount)| (account.as_str(), amount.clone())),
+            &format!("Liabilities:Due To:{source_name}"),
+        ));
+        target_text.push('\n');
+        let reversed: Vec<(&str, Amount)> = postings
+            .iter()
+            .map(|(account, amount)| negate(amount).map(|amount| (account.as_str(), amount)))
+            .collect::<Option<_>>()
+            .ok_or_else(|| invalid_input(format!("cannot reverse amounts of {gl_txn_id}")))?;
+        source_text.push_str(&format_entry(
+            txn,
+            &entry.reclass_txn_id,
+            &[
+                (EXPORTED_ENTRY_TAG, gl_txn_id),
+                ("exported-to", &target_name),
+                ("target-txn", &entry.target_txn_id),
+            ],
+            reversed.into_iter(),
+            &format!("Assets:Due From:{target_name}"),
+        ));
+        source_text.push('\n');
+        exported.push(entry);
+    }
+
+    let result = LedgerExport {
+        target_ledger: target_dir.display().to_string(),
+        exported,
+        skipped,
+    };
+    if result.exported.is_empty() {
+        return Ok(result);
+    }
+
+    // Check the source side before writing anything so a bad source journal
+    // does not leave half an export in the target ledger.
+    crate::ledger_add::run_hledger_check(
+        &source_text,
+        &[&journal_path],
+        "journal-plus-transaction",
+    )?;
+    crate::ledger_add::add_transaction_text(target_dir, &target_text)?;
+    crate::ledger_add::add_transaction_text(ledger_dir, &source_text)?;
+
+    let timestamp = operations::now_timestamp();
+    operations::append_gl_operation(
+        target_dir,
+        &GlOperation::ImportEntries {
+            source_ledger: ledger_dir.display().to_string(),
+            entries: result.exported.clone(),
+            timestamp: timestamp.clone(),
+        },
+    )?;
+    operations::append_gl_operation(
+        ledger_dir,
+        &GlOperation::ExportEntries {
+            target_ledger: result.target_ledger.clone(),
+            query: query.to_string(),
+            entries: result.exported.clone(),
+            timestamp,
+        },
+    )?;
+    Ok(result)
+}
+
+/// Postings outside `Assets`, `Liabilities`, and `Equity`, which are what
+/// the entry paid for. Each must have a single amount without a cost.
+fn category_postings(txn: &Transaction) -> Result<Vec<(String, Amount)>, String> {
+    let mut postings = Vec::new();
+    for posting in &txn.tpostings {
+        let top = posting.paccount.split(':').next().unwrap_or_default();
+        if matches!(top, "Assets" | "Liabilities" | "Equity") {
+            continue;
+        }
+        match posting.pamount.as_slice() {
+            [amount] if amount.acost.is_none() => {
+                postings.push((posting.paccount.clone(), amount.clone()));
+            }
+            _ => {
+                return Err(format!(
+                    "posting to {} must have one amount without a cost",
+                    posting.paccount
+                ))
+            }
+        }
+    }
+    Ok(postings)
+}
+
+fn format_entry<'a>(
+    txn: &Transaction,
+    txn_id: &str,
+    tags: &[(&str, &str)],
+    postings: impl Iterator<Item = (&'a str, Amount)>,
+    balancing_account: &str,
+) -> String {
+    let status = match txn.tstatus {
+        Status::Cleared => "* ",
+        Status::Pending => "! ",
+        Status::Unmarked => "",
+    };
+    let mut text = format!(
+        "{}  {status}{}  ; id: {txn_id}\n",
+        txn.tdate,
+        txn.tdescription.replace('\n', " ")
+    );
+    for (name, value) in tags {
+        text.push_str(&format!("    ; {name}: {value}\n"));
+    }
+    for (account, amount) in postings {
+        let amount = crate::ledger_open::format_amount(&amount).unwrap_or_default();
+        text.push_str(&format!("    {account}  {amount}\n"));
+    }
+    text.push_str(&format!("    {balancing_account}\n"));
+    text
+}
+
+fn negate(amount: &Amount) -> Option<Amount> {
+    let mantissa = amount.aquantity.decimal_mantissa.as_i64()?;
+    let mut negated = amount.clone();
+    negated.aquantity.decimal_mantissa = serde_json::Number::from(mantissa.checked_neg()?);
+    negated.aquantity.floating_point = -amount.aquantity.floating_point;
+    Some(negated)
+}
+
+fn tag_value<'a>(txn: &'a Transaction, name: &str) -> Option<&'a str> {
+    txn.ttags
+        .iter()
+        .find(|(key, _)| key == name)
+        .map(|(_, value)| value.as_str())
+}
+
+/// The ledger's directory name without `.refreshmint`, usable as an account
+/// name component.
+fn ledger_account_name(ledger_dir: &Path) -> io::Result<String> {
+    let name = ledger_dir
+        .file_stem()
+        .and_then(|stem| stem.to_str())
+        .map(|stem| {
+            stem.replace([':', ';'], "-")
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .unwrap_or_default();
+    if name.is_empty() {
+        return Err(invalid_input(format!(
+            "cannot name ledger {}",
+            ledger_dir.display()
+        )));
+    }
+    Ok(name)
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::hledger::{DecimalRaw, Posting, PostingType, SourcePos, SourceSpan};
+
+    fn amount(mantissa: i64) -> Amount {
+        Amount {
+            acommodity: "USD".to_string(),
+            aquantity: DecimalRaw {
+                decimal_places: 2,
+                decimal_mantissa: serde_json::Number::from(mantissa),
+                floating_point: mantissa as f64 / 100.0,
+            },
+            astyle: None,
+            acost: None,
+            acostbasis: None,
+        }
+    }
+
+    fn posting(account: &str, amounts: Vec<Amount>) -> Posting {
+        Posting {
+            pdate: None,
+            pdate2: None,
+            pstatus: Status::Unmarked,
+            paccount: account.to_string(),
+            pamount: amounts,
+            pcomment: String::new(),
+            ptype: PostingType::RegularPosting,
+            ptags: vec![],
+            pbalanceassertion: None,
+            ptransaction_index: None,
+            poriginal: None,
+        }
+    }
+
+    fn txn(postings: Vec<Posting>) -> Transaction {
+        let pos = SourcePos {
+            source_name: String::new(),
+            source_line: 1,
+            source_column: 1,
+        };
+        Transaction {
+            tindex: 1,
+            tprecedingcomment: String::new(),
+            tsourcepos: SourceSpan(pos.clone(), pos),
+            tdate: "2025-03-04".to_string(),
+            tdate2: None,
+            tstatus: Status::Cleared,
+            tcode: String::new(),
+            tdescription: "Office Depot".to_string(),
+            tcomment: String::new(),
+            ttags: vec![("id".to_string(), "src-1".to_string())],
+            tpostings: postings,
+        }
+    }
+
+    #[test]
+    fn export_books_category_postings_against_due_to_and_due_from() {
+        let txn = txn(vec![
+            posting("Liabilities:Card", vec![amount(-8450)]),
+            posting("Expenses:Office", vec![amount(6000)]),
+            posting("Expenses:Software", vec![amount(2450)]),
+        ]);
+        let postings = category_postings(&txn).unwrap();
+        assert_eq!(postings.len(), 2);
+
+        let target = format_entry(
+            &txn,
+            "tgt-1",
+            &[(IMPORTED_ENTRY_TAG, "src-1")],
+            postings.iter().map(|(a, m)| (a.as_str(), m.clone())),
+            "Liabilities:Due To:Personal",
+        );
+        assert_eq!(
+            target,
+            "2025-03-04  * Office Depot  ; id: tgt-1\n    ; imported-entry: src-1\n    Expenses:Office  60.00 USD\n    Expenses:Software  24.50 USD\n    Liabilities:Due To:Personal\n"
+        );
+
+        let source = format_entry(
+            &txn,
+            "rcl-1",
+            &[(EXPORTED_ENTRY_TAG, "src-1")],
+            postings
+                .iter()
+                .map(|(a, m)| (a.as_str(), negate(m).unwrap())),
+            "Assets:Due From:Business",
+        );
+        assert!(source.contains("    Expenses:Office  -60.00 USD\n"));
+        assert!(source.ends_with("    Assets:Due From:Business\n"));
+    }
+
+    #[test]
+    fn postings_with_costs_or_multiple_amounts_are_rejected() {
+        let multi = txn(vec![
+            posting("Assets:Checking", vec![amount(-100)]),
+            posting("Expenses:Misc", vec![amount(50), amount(50)]),
+        ]);
+        assert!(category_postings(&multi).is_err());
+
+        let transfer = txn(vec![
+            posting("Assets:Checking", vec![amount(-100)]),
+            posting("Liabilities:Card", vec![amount(100)]),
+        ]);
+        assert!(category_postings(&transfer).unwrap().is_empty());
+    }
+
+    #[test]
+    fn ledger_names_become_account_components() {
+        assert_eq!(
+            ledger_account_name(Path::new("/tmp/My: Business.refreshmint")).unwrap(),
+            "My- Business"
+        );
+    }
+}
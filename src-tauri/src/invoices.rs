@@ -0,0 +1,590 @@
+//! Invoices and accounts receivable.
+//!
+//! An issued invoice is stored as `bookkeeping/invoices/<number>.json` and
+//! recorded in the general journal as a receivable: the client's receivable
+//! account (`Assets:Receivable:<client>` by default) is debited and an income
+//! account credited. Anything later credited to the receivable account, such
+//! as a posted client deposit, pays that client's invoices oldest first, so
+//! outstanding balances and aging always follow the general journal.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+
+use crate::account_journal::AccountEntry;
+use crate::bookkeeping::{
+    bookkeeping_dir, normalize_optional_string, read_json_objects_from_dir, read_optional_json,
+    require_date, require_non_empty, write_json,
+};
+use crate::loans::{format_cents, parse_cents};
+
+const INVOICES_DIR: &str = "invoices";
+const RECEIVABLE_PREFIX: &str = "Assets:Receivable";
+const DEFAULT_INCOME_ACCOUNT: &str = "Income:Consulting";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct Invoice {
+    pub number: String,
+    pub client: String,
+    /// Invoiced total, e.g. `"1500.00"`.
+    pub amount: String,
+    pub commodity: String,
+    pub issue_date: String,
+    pub due_date: String,
+    pub receivable_account: String,
+    pub income_account: String,
+    pub description: Option<String>,
+    /// Id of the general journal transaction that records the receivable.
+    pub gl_txn_id: String,
+    pub created_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct RecordInvoiceInput {
+    pub number: String,
+    pub client: String,
+    pub amount: String,
+    #[ts(optional = nullable)]
+    pub commodity: Option<String>,
+    pub issue_date: String,
+    pub due_date: String,
+    #[ts(optional = nullable)]
+    pub receivable_account: Option<String>,
+    #[ts(optional = nullable)]
+    pub income_account: Option<String>,
+    #[ts(optional = nullable)]
+    pub description: Option<String>,
+}
+
+/// An invoice with the payments applied to it as of the report date.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct InvoiceBalance {
+    pub invoice: Invoice,
+    pub paid: String,
+    pub outstanding: String,
+    /// Days past the due date; zero or negative when not yet due.
+    #[ts(type = "number")]
+    pub days_past_due: i64,
+}
+
+/// Outstanding amounts by days past due.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct AgingBuckets {
+    pub current: String,
+    pub days_1_to_30: String,
+    pub days_31_to_60: String,
+    pub days_61_to_90: String,
+    pub over_90_days: String,
+    pub total: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ClientReceivable {
+    pub client: String,
+    pub commodity: String,
+    pub aging: AgingBuckets,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ReceivablesReport {
+    pub as_of: String,
+    /// Invoices with an outstanding balance, oldest due first.
+    pub open_invoices: Vec<InvoiceBalance>,
+    pub clients: Vec<ClientReceivable>,
+    /// Aging across all clients, one entry per commodity.
+    pub totals: Vec<ClientReceivable>,
+    /// Receivable credits beyond what was invoiced, per account.
+    pub unapplied_credits: Vec<UnappliedCredit>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct UnappliedCredit {
+    pub receivable_account: String,
+    pub commodity: String,
+    pub amount: String,
+}
+
+/// An unposted deposit that looks like payment of an open invoice.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct InvoiceMatch {
+    pub number: String,
+    pub client: String,
+    pub receivable_account: String,
+    pub outstanding: String,
+}
+
+pub fn list_invoices(ledger_dir: &Path) -> io::Result<Vec<Invoice>> {
+    let mut invoices: Vec<Invoice> = read_json_objects_from_dir(&invoices_dir(ledger_dir))?;
+    invoices.sort_by(|a, b| {
+        a.issue_date
+            .cmp(&b.issue_date)
+            .then_with(|| a.number.cmp(&b.number))
+    });
+    Ok(invoices)
+}
+
+/// Store an invoice and add its receivable transaction to the general
+/// journal.
+pub fn record_invoice(
+    ledger_dir: &Path,
+    input: RecordInvoiceInput,
+) -> Result<Invoice, Box<dyn std::error::Error>> {
+    let number = require_non_empty("number", input.number)?;
+    if !number
+        .chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        || number.starts_with('.')
+    {
+        return Err(invalid_input(
+            "invoice number may only contain letters, digits, '-', '_', and '.'",
+        )
+        .into());
+    }
+    let client = require_non_empty("client", input.client)?;
+    if client.contains([':', ',', ';', '\n']) || client.contains("  ") {
+        return Err(
+            invalid_input("client name may not contain ':', ',', ';', or double spaces").into(),
+        );
+    }
+    let amount = require_non_empty("amount", input.amount)?;
+    if parse_cents(&amount).map_or(true, |cents| cents <= 0) {
+        return Err(invalid_input("amount must be a positive amount").into());
+    }
+    let issue_date = require_date("issue_date", input.issue_date)?;
+    let due_date = require_date("due_date", input.due_date)?;
+    if due_date < issue_date {
+        return Err(invalid_input("due_date must not be before issue_date").into());
+    }
+    if read_optional_json::<Invoice>(&invoice_path(ledger_dir, &number))?.is_some() {
+        return Err(invalid_input(format!("invoice {number} already exists")).into());
+    }
+
+    let invoice = Invoice {
+        receivable_account: normalize_optional_string(input.receivable_account)
+            .unwrap_or_else(|| format!("{RECEIVABLE_PREFIX}:{client}")),
+        income_account: normalize_optional_string(input.income_account)
+            .unwrap_or_else(|| DEFAULT_INCOME_ACCOUNT.to_string()),
+        commodity: normalize_optional_string(input.commodity).unwrap_or_else(|| "USD".to_string()),
+        description: normalize_optional_string(input.description),
+        gl_txn_id: uuid::Uuid::new_v4().to_string(),
+        created_at: crate::operations::now_timestamp(),
+        number,
+        client,
+        amount,
+        issue_date,
+        due_date,
+    };
+
+    let path = invoice_path(ledger_dir, &invoice.number);
+    write_json(&path, &invoice)?;
+    if let Err(err) =
+        crate::ledger_add::add_transaction_text(ledger_dir, &format_invoice_transaction(&invoice))
+    {
+        let _ = std::fs::remove_file(&path);
+        return Err(err);
+    }
+    Ok(invoice)
+}
+
+/// Outstanding invoices and aging as of `as_of`.
+pub fn report_accounts_receivable(
+    ledger_dir: &Path,
+    as_of: NaiveDate,
+) -> io::Result<ReceivablesReport> {
+    let invoices = list_invoices(ledger_dir)?;
+    let gl_path = ledger_dir.join("general.journal");
+    let txns = if gl_path.exists() && !invoices.is_empty() {
+        crate::ledger_open::run_hledger_print(&gl_path)?
+    } else {
+        Vec::new()
+    };
+    Ok(build_report(
+        &invoices,
+        &receivable_credits(&invoices, &txns, as_of),
+        as_of,
+    ))
+}
+
+/// Open invoices given the general journal, for matching deposits.
+pub fn open_invoices(
+    ledger_dir: &Path,
+    txns: &[crate::hledger::Transaction],
+) -> io::Result<Vec<InvoiceBalance>> {
+    let invoices = list_invoices(ledger_dir)?;
+    let as_of = chrono::Local::now().date_naive();
+    let credits = receivable_credits(&invoices, txns, as_of);
+    Ok(apply_credits(&invoices, &credits, as_of).0)
+}
+
+/// Match a deposit to an open invoice: one whose number appears in the
+/// description, otherwise the only one whose outstanding balance equals the
+/// deposit.
+pub fn suggest_invoice_match(
+    open: &[InvoiceBalance],
+    entry: &AccountEntry,
+) -> Option<InvoiceMatch> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    let deposit = parse_cents(&amount.quantity)?;
+    if deposit <= 0 {
+        return None;
+    }
+    let candidates: Vec<&InvoiceBalance> = open
+        .iter()
+        .filter(|b| b.invoice.commodity == amount.commodity && b.invoice.issue_date <= entry.date)
+        .collect();
+    let description = entry.description.to_ascii_lowercase();
+    let by_number = candidates.iter().find(|b| {
+        description
+            .split(|c: char| !c.is_ascii_alphanumeric() && c != '-')
+            .any(|word| word == b.invoice.number.to_ascii_lowercase())
+    });
+    let matched = match by_number {
+        Some(balance) => *balance,
+        None => {
+            let mut by_amount = candidates
+                .iter()
+                .filter(|b| parse_cents(&b.outstanding) == Some(deposit));
+            let first = by_amount.next()?;
+            if by_amount.next().is_some() {
+                return None;
+            }
+            *first
+        }
+    };
+    Some(InvoiceMatch {
+        number: matched.invoice.number.clone(),
+        client: matched.invoice.client.clone(),
+        receivable_account: matched.invoice.receivable_account.clone(),
+        outstanding: matched.outstanding.clone(),
+    })
+}
+
+/// Credits to each invoice receivable account up to `as_of`, keyed by
+/// account and commodity, in cents.
+fn receivable_credits(
+    invoices: &[Invoice],
+    txns: &[crate::hledger::Transaction],
+    as_of: NaiveDate,
+) -> BTreeMap<(String, String), i64> {
+    let mut credits = BTreeMap::new();
+    for invoice in invoices {
+        credits
+            .entry((
+                invoice.receivable_account.clone(),
+                invoice.commodity.clone(),
+            ))
+            .or_insert(0);
+    }
+    for txn in txns {
+        for posting in &txn.tpostings {
+            let date = posting.pdate.as_deref().unwrap_or(&txn.tdate);
+            if NaiveDate::parse_from_str(date, "%Y-%m-%d").map_or(true, |d| d > as_of) {
+                continue;
+            }
+            for amount in &posting.pamount {
+                let key = (posting.paccount.clone(), amount.acommodity.clone());
+                if let Some(total) = credits.get_mut(&key) {
+                    let cents = (amount.aquantity.floating_point * 100.0).round() as i64;
+                    if cents < 0 {
+                        *total -= cents;
+                    }
+                }
+            }
+        }
+    }
+    credits
+}
+
+/// Apply credits to invoices oldest first. Returns the open balances and the
+/// credits left over.
+fn apply_credits(
+    invoices: &[Invoice],
+    credits: &BTreeMap<(String, String), i64>,
+    as_of: NaiveDate,
+) -> (Vec<InvoiceBalance>, BTreeMap<(String, String), i64>) {
+    let mut remaining = credits.clone();
+    let mut open = Vec::new();
+    for invoice in invoices {
+        if invoice.issue_date > as_of.format("%Y-%m-%d").to_string() {
+            continue;
+        }
+        let amount = parse_cents(&invoice.amount).unwrap_or(0);
+        let credit = remaining
+            .entry((
+                invoice.receivable_account.clone(),
+                invoice.commodity.clone(),
+            ))
+            .or_insert(0);
+        let paid = amount.min(*credit);
+        *credit -= paid;
+        if paid == amount {
+            continue;
+        }
+        let days_past_due = NaiveDate::parse_from_str(&invoice.due_date, "%Y-%m-%d")
+            .map_or(0, |due| (as_of - due).num_days());
+        open.push(InvoiceBalance {
+            invoice: invoice.clone(),
+            paid: format_cents(paid),
+            outstanding: format_cents(amount - paid),
+            days_past_due,
+        });
+    }
+    open.sort_by(|a, b| {
+        a.invoice
+            .due_date
+            .cmp(&b.invoice.due_date)
+            .then_with(|| a.invoice.number.cmp(&b.invoice.number))
+    });
+    (open, remaining)
+}
+
+fn build_report(
+    invoices: &[Invoice],
+    credits: &BTreeMap<(String, String), i64>,
+    as_of: NaiveDate,
+) -> ReceivablesReport {
+    let (open_invoices, remaining) = apply_credits(invoices, credits, as_of);
+
+    let mut clients: BTreeMap<(String, String), [i64; 5]> = BTreeMap::new();
+    let mut totals: BTreeMap<String, [i64; 5]> = BTreeMap::new();
+    for balance in &open_invoices {
+        let bucket = match balance.days_past_due {
+            i64::MIN..=0 => 0,
+            1..=30 => 1,
+            31..=60 => 2,
+            61..=90 => 3,
+            _ => 4,
+        };
+        let cents = parse_cents(&balance.outstanding).unwrap_or(0);
+        let commodity = balance.invoice.commodity.clone();
+        clients
+            .entry((balance.invoice.client.clone(), commodity.clone()))
+            .or_default()[bucket] += cents;
+        totals.entry(commodity).or_default()[bucket] += cents;
+    }
+
+    ReceivablesReport {
+        as_of: as_of.format("%Y-%m-%d").to_string(),
+        clients: clients
+            .into_iter()
+            .map(|((client, commodity), buckets)| ClientReceivable {
+                client,
+                commodity,
+                aging: aging(buckets),
+            })
+            .collect(),
+        totals: totals
+            .into_iter()
+            .map(|(commodity, buckets)| ClientReceivable {
+                client: String::new(),
+                commodity,
+                aging: aging(buckets),
+            })
+            .collect(),
+        open_invoices,
+        unapplied_credits: remaining
+            .into_iter()
+            .filter(|(_, cents)| *cents > 0)
+            .map(|((receivable_account, commodity), cents)| UnappliedCredit {
+                receivable_account,
+                commodity,
+                amount: format_cents(cents),
+            })
+            .collect(),
+    }
+}
+
+fn aging(buckets: [i64; 5]) -> AgingBuckets {
+    AgingBuckets {
+        current: format_cents(buckets[0]),
+        days_1_to_30: format_cents(buckets[1]),
+        days_31_to_60: format_cents(buckets[2]),
+        days_61_to_90: format_cents(buckets[3]),
+        over_90_days: format_cents(buckets[4]),
+        total: format_cents(buckets.iter().sum()),
+    }
+}
+
+fn format_invoice_transaction(invoice: &Invoice) -> String {
+    let mut text = format!(
+        "{}  {} | Invoice {}  ; id: {}\n    ; generated-by: refreshmint-invoice\n    ; invoice: {}\n    ; client: {}\n    ; due: {}\n",
+        invoice.issue_date,
+        invoice.client,
+        invoice.number,
+        invoice.gl_txn_id,
+        invoice.number,
+        invoice.client,
+        invoice.due_date,
+    );
+    if let Some(description) = &invoice.description {
+        text.push_str(&format!("    ; {}\n", description.replace('\n', " ")));
+    }
+    text.push_str(&format!(
+        "    {}  {} {}\n    {}\n",
+        invoice.receivable_account, invoice.amount, invoice.commodity, invoice.income_account
+    ));
+    text
+}
+
+fn invoices_dir(ledger_dir: &Path) -> PathBuf {
+    bookkeeping_dir(ledger_dir).join(INVOICES_DIR)
+}
+
+fn invoice_path(ledger_dir: &Path, number: &str) -> PathBuf {
+    invoices_dir(ledger_dir).join(format!("{number}.json"))
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn invoice(number: &str, client: &str, amount: &str, issued: &str, due: &str) -> Invoice {
+        Invoice {
+            number: number.to_string(),
+            client: client.to_string(),
+            amount: amount.to_string(),
+            commodity: "USD".to_string(),
+            issue_date: issued.to_string(),
+            due_date: due.to_string(),
+            receivable_account: format!("{RECEIVABLE_PREFIX}:{client}"),
+            income_account: DEFAULT_INCOME_ACCOUNT.to_string(),
+            description: None,
+            gl_txn_id: format!("gl-{number}"),
+            created_at: "2025-01-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn deposit(description: &str, amount: &str) -> AccountEntry {
+        AccountEntry::new(
+            "2025-03-10".to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec!["bank.csv:1:1".to_string()],
+            vec![
+                EntryPosting {
+                    account: "Assets:Checking".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: amount.to_string(),
+                    }),
+                },
+                EntryPosting {
+                    account: "Equity:Staging:Checking".to_string(),
+                    amount: None,
+                },
+            ],
+        )
+    }
+
+    fn invoices() -> Vec<Invoice> {
+        vec![
+            invoice("1001", "Acme", "1000.00", "2024-12-01", "2024-12-31"),
+            invoice("1002", "Acme", "500.00", "2025-01-15", "2025-02-14"),
+            invoice("1003", "Globex", "750.00", "2025-02-20", "2025-03-22"),
+        ]
+    }
+
+    #[test]
+    fn payments_apply_oldest_first_and_age_the_rest() {
+        let credits = BTreeMap::from([
+            (
+                ("Assets:Receivable:Acme".to_string(), "USD".to_string()),
+                120000,
+            ),
+            (
+                ("Assets:Receivable:Globex".to_string(), "USD".to_string()),
+                0,
+            ),
+        ]);
+        let report = build_report(&invoices(), &credits, date("2025-03-10"));
+
+        let open: Vec<_> = report
+            .open_invoices
+            .iter()
+            .map(|b| {
+                (
+                    b.invoice.number.as_str(),
+                    b.outstanding.as_str(),
+                    b.days_past_due,
+                )
+            })
+            .collect();
+        assert_eq!(open, vec![("1002", "300.00", 24), ("1003", "750.00", -12)]);
+        assert_eq!(report.clients[0].client, "Acme");
+        assert_eq!(report.clients[0].aging.days_1_to_30, "300.00");
+        assert_eq!(report.totals[0].aging.current, "750.00");
+        assert_eq!(report.totals[0].aging.total, "1050.00");
+        assert!(report.unapplied_credits.is_empty());
+
+        let overpaid = BTreeMap::from([(
+            ("Assets:Receivable:Acme".to_string(), "USD".to_string()),
+            160000,
+        )]);
+        let report = build_report(&invoices(), &overpaid, date("2025-03-10"));
+        assert_eq!(report.unapplied_credits[0].amount, "100.00");
+    }
+
+    #[test]
+    fn deposits_match_by_invoice_number_or_unique_amount() {
+        let open = apply_credits(&invoices(), &BTreeMap::new(), date("2025-03-10")).0;
+
+        let matched = suggest_invoice_match(&open, &deposit("ACH ACME CORP INV 1002", "480.00"));
+        assert_eq!(matched.map(|m| m.number), Some("1002".to_string()));
+
+        let matched = suggest_invoice_match(&open, &deposit("GLOBEX PAYMENT", "750.00")).unwrap();
+        assert_eq!(matched.receivable_account, "Assets:Receivable:Globex");
+
+        assert_eq!(
+            suggest_invoice_match(&open, &deposit("Refund", "-750.00")),
+            None
+        );
+        assert_eq!(
+            suggest_invoice_match(&open, &deposit("Deposit", "42.00")),
+            None
+        );
+    }
+
+    #[test]
+    fn invoice_transaction_debits_the_receivable() {
+        let text = format_invoice_transaction(&invoice(
+            "1001",
+            "Acme",
+            "1000.00",
+            "2024-12-01",
+            "2024-12-31",
+        ));
+        assert_eq!(
+            text,
+            "2024-12-01  Acme | Invoice 1001  ; id: gl-1001\n    ; generated-by: refreshmint-invoice\n    ; invoice: 1001\n    ; client: Acme\n    ; due: 2024-12-31\n    Assets:Receivable:Acme  1000.00 USD\n    Income:Consulting\n"
+        );
+    }
+}
@@ -0,0 +1,404 @@
+//! Background jobs for commands that can run for minutes.
+//!
+//! A command that starts a job returns the job id at once and does the work
+//! on a worker thread. The work reports progress through its [`JobHandle`];
+//! the registry forwards every change to the UI as a
+//! `refreshmint://job-changed` event. `cancel_job` only sets a flag: the work
+//! stops the next time it calls [`JobHandle::check_cancelled`], so whatever
+//! it already wrote stays written.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock, PoisonError};
+
+/// Finished jobs kept for `list_jobs`; older ones are forgotten.
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// Error message of work that stopped because its job was cancelled.
+pub const CANCELLED: &str = "cancelled";
+
+/// Callback invoked with a job's new state whenever it changes.
+pub type JobListener = Arc<dyn Fn(&JobInfo) + Send + Sync + 'static>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
+pub enum JobKind {
+    Extraction,
+    Migration,
+    BookImport,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub enum JobState {
+    Running,
+    Succeeded,
+    Failed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct JobProgress {
+    pub done: usize,
+    pub total: usize,
+    pub message: String,
+}
+
+/// One running or finished job as reported to the UI.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct JobInfo {
+    #[ts(type = "number")]
+    pub id: u64,
+    pub kind: JobKind,
+    /// What the job works on, e.g. `chase/checking`.
+    pub title: String,
+    pub state: JobState,
+    pub progress: Option<JobProgress>,
+    /// The command's return value, once the job succeeded.
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+struct Job {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+struct RegistryState {
+    next_id: u64,
+    /// Oldest first.
+    jobs: Vec<Job>,
+}
+
+pub struct JobRegistry {
+    state: Mutex<RegistryState>,
+    listener: Mutex<Option<JobListener>>,
+}
+
+/// Given to a job's work to report progress and notice cancellation.
+pub struct JobHandle<'a> {
+    registry: Option<&'a JobRegistry>,
+    id: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+static GLOBAL_REGISTRY: OnceLock<JobRegistry> = OnceLock::new();
+
+impl JobRegistry {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(RegistryState {
+                next_id: 1,
+                jobs: Vec::new(),
+            }),
+            listener: Mutex::new(None),
+        }
+    }
+
+    /// The process-wide registry used by Tauri commands.
+    pub fn global() -> &'static JobRegistry {
+        GLOBAL_REGISTRY.get_or_init(JobRegistry::new)
+    }
+
+    pub fn set_listener(&self, listener: Option<JobListener>) {
+        *self.listener.lock().unwrap_or_else(PoisonError::into_inner) = listener;
+    }
+
+    /// Running and recently finished jobs, oldest first.
+    pub fn list(&self) -> Vec<JobInfo> {
+        self.lock_state()
+            .jobs
+            .iter()
+            .map(|job| job.info.clone())
+            .collect()
+    }
+
+    /// Ask a running job to stop.
+    pub fn cancel(&self, id: u64) -> Result<(), String> {
+        let state = self.lock_state();
+        let job = state
+            .jobs
+            .iter()
+            .find(|job| job.info.id == id)
+            .ok_or_else(|| format!("no job with id {id}"))?;
+        if job.info.state != JobState::Running {
+            return Err(format!("job {id} has already finished"));
+        }
+        job.cancelled.store(true, Ordering::SeqCst);
+        Ok(())
+    }
+
+    /// Run `work` on a new thread and return its job id.
+    pub fn spawn<T, F>(&'static self, kind: JobKind, title: String, work: F) -> Result<u64, String>
+    where
+        T: Serialize,
+        F: FnOnce(&JobHandle<'_>) -> Result<T, String> + Send + 'static,
+    {
+        let handle = self.start(kind, title);
+        let id = handle.id;
+        let spawned = std::thread::Builder::new()
+            .name(format!("job-{id}"))
+            .spawn(move || self.run(&handle, work));
+        if let Err(err) = spawned {
+            let message = format!("failed to start job thread: {err}");
+            self.finish(id, JobState::Failed, None, Some(message.clone()));
+            return Err(message);
+        }
+        Ok(id)
+    }
+
+    fn start(&self, kind: JobKind, title: String) -> JobHandle<'_> {
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let info = {
+            let mut state = self.lock_state();
+            let id = state.next_id;
+            state.next_id += 1;
+            let info = JobInfo {
+                id,
+                kind,
+                title,
+                state: JobState::Running,
+                progress: None,
+                result: None,
+                error: None,
+                started_at: crate::operations::now_timestamp(),
+                finished_at: None,
+            };
+            state.jobs.push(Job {
+                info: info.clone(),
+                cancelled: Arc::clone(&cancelled),
+            });
+            info
+        };
+        self.notify(&info);
+        JobHandle {
+            registry: Some(self),
+            id: info.id,
+            cancelled,
+        }
+    }
+
+    /// Run `work` for an already started job and record how it ended.
+    fn run<T, F>(&self, handle: &JobHandle<'_>, work: F)
+    where
+        T: Serialize,
+        F: FnOnce(&JobHandle<'_>) -> Result<T, String>,
+    {
+        let outcome = work(handle).and_then(|value| {
+            serde_json::to_value(value).map_err(|err| format!("failed to encode result: {err}"))
+        });
+        match outcome {
+            Ok(value) => self.finish(handle.id, JobState::Succeeded, Some(value), None),
+            Err(_) if handle.is_cancelled() => {
+                self.finish(handle.id, JobState::Cancelled, None, None);
+            }
+            Err(err) => self.finish(handle.id, JobState::Failed, None, Some(err)),
+        }
+    }
+
+    fn finish(
+        &self,
+        id: u64,
+        outcome: JobState,
+        result: Option<serde_json::Value>,
+        error: Option<String>,
+    ) {
+        let info = {
+            let mut state = self.lock_state();
+            let Some(job) = state.jobs.iter_mut().find(|job| job.info.id == id) else {
+                return;
+            };
+            job.info.state = outcome;
+            job.info.result = result;
+            job.info.error = error;
+            job.info.finished_at = Some(crate::operations::now_timestamp());
+            let info = job.info.clone();
+            forget_old_jobs(&mut state.jobs);
+            info
+        };
+        self.notify(&info);
+    }
+
+    fn set_progress(&self, id: u64, progress: JobProgress) {
+        let info = {
+            let mut state = self.lock_state();
+            let Some(job) = state.jobs.iter_mut().find(|job| job.info.id == id) else {
+                return;
+            };
+            job.info.progress = Some(progress);
+            job.info.clone()
+        };
+        self.notify(&info);
+    }
+
+    fn notify(&self, info: &JobInfo) {
+        let listener = self
+            .listener
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .clone();
+        if let Some(listener) = listener {
+            listener(info);
+        }
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, RegistryState> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Default for JobRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn forget_old_jobs(jobs: &mut Vec<Job>) {
+    let finished = jobs
+        .iter()
+        .filter(|job| job.info.state != JobState::Running)
+        .count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+    jobs.retain(|job| {
+        if excess > 0 && job.info.state != JobState::Running {
+            excess -= 1;
+            return false;
+        }
+        true
+    });
+}
+
+impl JobHandle<'static> {
+    /// A handle that reports nowhere and is never cancelled, for callers such
+    /// as the CLI that run the work directly.
+    pub fn detached() -> Self {
+        Self {
+            registry: None,
+            id: 0,
+            cancelled: Arc::new(AtomicBool::new(false)),
+        }
+    }
+}
+
+impl JobHandle<'_> {
+    /// Report that `done` of `total` steps are finished and what is next.
+    pub fn progress(&self, done: usize, total: usize, message: impl Into<String>) {
+        if let Some(registry) = self.registry {
+            registry.set_progress(
+                self.id,
+                JobProgress {
+                    done,
+                    total,
+                    message: message.into(),
+                },
+            );
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::SeqCst)
+    }
+
+    /// `Err(CANCELLED)` once the job has been cancelled. Call it between
+    /// steps that leave the ledger consistent.
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn state_of(registry: &JobRegistry, id: u64) -> JobInfo {
+        registry
+            .list()
+            .into_iter()
+            .find(|job| job.id == id)
+            .unwrap()
+    }
+
+    #[test]
+    fn finished_jobs_record_result_and_progress() {
+        let registry = JobRegistry::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = Arc::clone(&events);
+        registry.set_listener(Some(Arc::new(move |job: &JobInfo| {
+            sink.lock().unwrap().push(job.state);
+        })));
+
+        let handle = registry.start(JobKind::Extraction, "bank/checking".to_string());
+        let id = handle.id;
+        registry.run(&handle, |job| {
+            job.progress(1, 2, "Extracting a.csv");
+            Ok(7usize)
+        });
+
+        let info = state_of(&registry, id);
+        assert_eq!(info.state, JobState::Succeeded);
+        assert_eq!(info.result, Some(serde_json::json!(7)));
+        assert_eq!(info.progress.unwrap().message, "Extracting a.csv");
+        assert!(info.finished_at.is_some());
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![JobState::Running, JobState::Running, JobState::Succeeded]
+        );
+
+        let failed = registry.start(JobKind::Migration, "ledger".to_string());
+        let failed_id = failed.id;
+        registry.run(&failed, |_| Err::<(), _>("disk full".to_string()));
+        let info = state_of(&registry, failed_id);
+        assert_eq!(info.state, JobState::Failed);
+        assert_eq!(info.error.as_deref(), Some("disk full"));
+        assert!(registry.cancel(failed_id).is_err());
+    }
+
+    #[test]
+    fn cancelled_work_stops_at_the_next_check() {
+        let registry = JobRegistry::new();
+        let handle = registry.start(JobKind::Extraction, "bank/checking".to_string());
+        let id = handle.id;
+        let mut steps = 0;
+        registry.run(&handle, |job| {
+            for _ in 0..3 {
+                job.check_cancelled()?;
+                steps += 1;
+                registry.cancel(id)?;
+            }
+            Ok(())
+        });
+        assert_eq!(steps, 1);
+        let info = state_of(&registry, id);
+        assert_eq!(info.state, JobState::Cancelled);
+        assert_eq!(info.error, None);
+        assert!(registry.cancel(99).is_err());
+        assert!(JobHandle::detached().check_cancelled().is_ok());
+    }
+
+    #[test]
+    fn old_finished_jobs_are_forgotten() {
+        let registry = JobRegistry::new();
+        let running = registry.start(JobKind::BookImport, "book".to_string());
+        for _ in 0..MAX_FINISHED_JOBS + 5 {
+            let handle = registry.start(JobKind::Migration, "ledger".to_string());
+            registry.run(&handle, |_| Ok(()));
+        }
+        let jobs = registry.list();
+        assert_eq!(jobs.len(), MAX_FINISHED_JOBS + 1);
+        assert_eq!(jobs[0].id, running.id);
+        assert_eq!(jobs[1].id, running.id + 6);
+    }
+}
@@ -0,0 +1,392 @@
+//! CSV and JSON exports of one login account's journal.
+//!
+//! An export has one row per account journal entry: its date, bank status,
+//! description, amount, the account on the other side, the GL entries it was
+//! posted to, its tags and note, and the evidence refs behind it along with
+//! the document names they cite. It is meant for analyzing a single account
+//! in a spreadsheet or handing it to a tax preparer, so lists are flattened
+//! into `; `-separated cells in CSV and kept as arrays in JSON.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+
+use crate::account_journal::{self, AccountEntry, EntryStatus};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum JournalExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct JournalExportOptions {
+    /// First entry date to include, `YYYY-MM-DD`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub start: Option<String>,
+    /// Last entry date to include, inclusive.
+    #[serde(default)]
+    #[ts(optional)]
+    pub end: Option<String>,
+    /// Leave out entries not yet posted to the general journal.
+    #[serde(default)]
+    pub posted_only: bool,
+    /// File to write the export to.
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct JournalExport {
+    pub output: String,
+    pub entries: usize,
+    /// Distinct evidence documents the exported entries cite.
+    pub documents: usize,
+}
+
+/// One exported entry.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportRow {
+    id: String,
+    date: String,
+    /// `posted`, `pending`, or `unknown`, as the bank reported it.
+    bank_status: &'static str,
+    description: String,
+    amount: Option<String>,
+    commodity: Option<String>,
+    /// Account of the entry's second posting, e.g. `Expenses:Unknown`.
+    counter_account: Option<String>,
+    /// GL entry the whole entry was posted to.
+    posted: Option<String>,
+    /// GL entries individual postings were posted to.
+    posted_postings: Vec<String>,
+    documents: Vec<String>,
+    evidence: Vec<String>,
+    tags: Vec<String>,
+    note: Option<String>,
+    review: Option<account_journal::ReviewState>,
+    extracted_by: Option<String>,
+}
+
+impl ExportRow {
+    fn from_entry(entry: &AccountEntry) -> Self {
+        let bank_status = match entry.status {
+            EntryStatus::Cleared => "posted",
+            EntryStatus::Pending => "pending",
+            EntryStatus::Unmarked => "unknown",
+        };
+        let amount = entry
+            .postings
+            .first()
+            .and_then(|posting| posting.amount.as_ref());
+        let mut documents: Vec<String> = Vec::new();
+        for evidence in &entry.evidence {
+            let (document, _) = crate::provenance::split_evidence_ref(evidence);
+            if !documents.iter().any(|known| known == document) {
+                documents.push(document.to_string());
+            }
+        }
+        ExportRow {
+            id: entry.id.clone(),
+            date: entry.date.clone(),
+            bank_status,
+            description: entry.description.clone(),
+            amount: amount.map(|amount| amount.quantity.clone()),
+            commodity: amount.map(|amount| amount.commodity.clone()),
+            counter_account: entry.postings.get(1).map(|posting| posting.account.clone()),
+            posted: entry.posted.clone(),
+            posted_postings: entry
+                .posted_postings
+                .iter()
+                .map(|(_, posted)| posted.clone())
+                .collect(),
+            documents,
+            evidence: entry.evidence.clone(),
+            tags: entry
+                .tags
+                .iter()
+                .map(|(key, value)| format!("{key}:{value}"))
+                .collect(),
+            note: entry.note.clone(),
+            review: entry.review,
+            extracted_by: entry.extracted_by.clone(),
+        }
+    }
+}
+
+const CSV_HEADER: [&str; 15] = [
+    "id",
+    "date",
+    "bankStatus",
+    "description",
+    "amount",
+    "commodity",
+    "counterAccount",
+    "posted",
+    "postedPostings",
+    "documents",
+    "evidence",
+    "tags",
+    "note",
+    "review",
+    "extractedBy",
+];
+
+/// Write the journal of `login_name`/`label` to `options.output`.
+pub fn export_account_journal(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    format: JournalExportFormat,
+    options: &JournalExportOptions,
+) -> io::Result<JournalExport> {
+    let start = parse_bound("start", options.start.as_deref())?;
+    let end = parse_bound("end", options.end.as_deref())?;
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+    let rows: Vec<ExportRow> = entries
+        .iter()
+        .filter(|entry| in_period(&entry.date, start, end))
+        .filter(|entry| {
+            !options.posted_only || entry.posted.is_some() || !entry.posted_postings.is_empty()
+        })
+        .map(ExportRow::from_entry)
+        .collect();
+
+    let content = render(&rows, format)?;
+    let output = PathBuf::from(&options.output);
+    crate::atomic_file::write_atomic(&output, content.as_bytes())?;
+
+    let mut documents: Vec<&str> = rows
+        .iter()
+        .flat_map(|row| row.documents.iter().map(String::as_str))
+        .collect();
+    documents.sort_unstable();
+    documents.dedup();
+    Ok(JournalExport {
+        output: options.output.clone(),
+        entries: rows.len(),
+        documents: documents.len(),
+    })
+}
+
+/// Whether `date` falls in the period; an unparsable date only passes an
+/// unbounded one.
+fn in_period(date: &str, start: Option<NaiveDate>, end: Option<NaiveDate>) -> bool {
+    if start.is_none() && end.is_none() {
+        return true;
+    }
+    let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+        return false;
+    };
+    start.iter().all(|start| date >= *start) && end.iter().all(|end| date <= *end)
+}
+
+fn parse_bound(name: &str, value: Option<&str>) -> io::Result<Option<NaiveDate>> {
+    value
+        .map(str::trim)
+        .filter(|value| !value.is_empty())
+        .map(|value| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d").map_err(|err| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{name} must be YYYY-MM-DD: {err}"),
+                )
+            })
+        })
+        .transpose()
+}
+
+fn render(rows: &[ExportRow], format: JournalExportFormat) -> io::Result<String> {
+    match format {
+        JournalExportFormat::Json => {
+            let mut json = serde_json::to_string_pretty(rows).map_err(io::Error::other)?;
+            json.push('\n');
+            Ok(json)
+        }
+        JournalExportFormat::Csv => {
+            let mut writer = csv::Writer::from_writer(Vec::new());
+            writer.write_record(CSV_HEADER).map_err(io::Error::other)?;
+            for row in rows {
+                let review = row.review.map(|review| review.as_str().to_string());
+                writer
+                    .write_record([
+                        row.id.as_str(),
+                        row.date.as_str(),
+                        row.bank_status,
+                        row.description.as_str(),
+                        row.amount.as_deref().unwrap_or_default(),
+                        row.commodity.as_deref().unwrap_or_default(),
+                        row.counter_account.as_deref().unwrap_or_default(),
+                        row.posted.as_deref().unwrap_or_default(),
+                        &row.posted_postings.join("; "),
+                        &row.documents.join("; "),
+                        &row.evidence.join("; "),
+                        &row.tags.join("; "),
+                        row.note.as_deref().unwrap_or_default(),
+                        review.as_deref().unwrap_or_default(),
+                        row.extracted_by.as_deref().unwrap_or_default(),
+                    ])
+                    .map_err(io::Error::other)?;
+            }
+            let bytes = writer.into_inner().map_err(io::Error::other)?;
+            String::from_utf8(bytes).map_err(io::Error::other)
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, SimpleAmount};
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time should be after unix epoch")
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn entry(date: &str, description: &str, posted: Option<&str>) -> AccountEntry {
+        let mut entry = AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![
+                "2025-02-01-activity.csv:3:1".to_string(),
+                "2025-02-01-activity.csv:4:1".to_string(),
+            ],
+            vec![
+                EntryPosting {
+                    account: "Assets:Checking".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: "-4.50".to_string(),
+                    }),
+                },
+                EntryPosting {
+                    account: "Expenses:Unknown".to_string(),
+                    amount: None,
+                },
+            ],
+        );
+        entry.tags.push(("bankId".to_string(), "T1".to_string()));
+        entry.posted = posted.map(str::to_string);
+        entry
+    }
+
+    fn write_journal(ledger: &Path, entries: &[AccountEntry]) {
+        let path = account_journal::login_account_journal_path(ledger, "bank", "checking");
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        account_journal::write_journal_at_path(&path, entries).unwrap();
+    }
+
+    #[test]
+    fn csv_export_flattens_evidence_and_tags() {
+        let ledger = temp_dir("journal-export-csv");
+        write_journal(
+            &ledger,
+            &[
+                entry("2025-01-30", "COFFEE, INC", Some("general.journal:abc")),
+                entry("2025-02-03", "GROCER", None),
+            ],
+        );
+        let output = ledger.join("out").join("checking.csv");
+        let options = JournalExportOptions {
+            start: Some("2025-01-01".to_string()),
+            end: Some("2025-01-31".to_string()),
+            posted_only: false,
+            output: output.to_string_lossy().into_owned(),
+        };
+        let export = export_account_journal(
+            &ledger,
+            "bank",
+            "checking",
+            JournalExportFormat::Csv,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(export.entries, 1);
+        assert_eq!(export.documents, 1);
+
+        let mut reader = csv::Reader::from_path(&output).unwrap();
+        let headers = reader.headers().unwrap().clone();
+        assert_eq!(headers.iter().collect::<Vec<_>>(), CSV_HEADER);
+        let rows: Vec<csv::StringRecord> = reader.records().map(Result::unwrap).collect();
+        assert_eq!(rows.len(), 1);
+        let cell = |name: &str| {
+            let index = CSV_HEADER.iter().position(|h| *h == name).unwrap();
+            rows[0][index].to_string()
+        };
+        assert_eq!(cell("description"), "COFFEE, INC");
+        assert_eq!(cell("amount"), "-4.50");
+        assert_eq!(cell("counterAccount"), "Expenses:Unknown");
+        assert_eq!(cell("posted"), "general.journal:abc");
+        assert_eq!(cell("documents"), "2025-02-01-activity.csv");
+        assert_eq!(
+            cell("evidence"),
+            "2025-02-01-activity.csv:3:1; 2025-02-01-activity.csv:4:1"
+        );
+        assert_eq!(cell("tags"), "bankId:T1");
+    }
+
+    #[test]
+    fn json_export_keeps_lists_and_can_skip_unposted() {
+        let ledger = temp_dir("journal-export-json");
+        write_journal(
+            &ledger,
+            &[
+                entry("2025-01-30", "COFFEE", Some("general.journal:abc")),
+                entry("2025-02-03", "GROCER", None),
+            ],
+        );
+        let output = ledger.join("checking.json");
+        let options = JournalExportOptions {
+            posted_only: true,
+            output: output.to_string_lossy().into_owned(),
+            ..JournalExportOptions::default()
+        };
+        let export = export_account_journal(
+            &ledger,
+            "bank",
+            "checking",
+            JournalExportFormat::Json,
+            &options,
+        )
+        .unwrap();
+        assert_eq!(export.entries, 1);
+
+        let json: serde_json::Value =
+            serde_json::from_str(&std::fs::read_to_string(&output).unwrap()).unwrap();
+        assert_eq!(json[0]["description"], "COFFEE");
+        assert_eq!(json[0]["bankStatus"], "posted");
+        assert_eq!(json[0]["documents"][0], "2025-02-01-activity.csv");
+        assert_eq!(json[0]["tags"][0], "bankId:T1");
+
+        let bad = JournalExportOptions {
+            start: Some("Jan 1".to_string()),
+            ..options
+        };
+        let err =
+            export_account_journal(&ledger, "bank", "checking", JournalExportFormat::Json, &bad)
+                .unwrap_err();
+        assert!(
+            err.to_string().contains("start must be YYYY-MM-DD"),
+            "{err}"
+        );
+    }
+}
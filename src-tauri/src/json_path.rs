@@ -0,0 +1,318 @@
+//! A small JSONPath evaluator for extracting values from saved API responses.
+//!
+//! Supported syntax:
+//!
+//! - `$` — the document root
+//! - `.name` and `['name']` — an object member
+//! - `[0]`, `[-1]` — an array element, counting from the end when negative
+//! - `*`, `[*]` — every member or element
+//! - `[start:end:step]` — an array slice (step must be positive)
+//! - `[a,b]` — a union of names or indexes
+//! - `..name`, `..*`, `..[0]` — recursive descent
+//!
+//! Filter expressions (`[?(...)]`) are not supported.
+
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    Name(String),
+    Index(i64),
+    Wildcard,
+    Slice(Option<i64>, Option<i64>, i64),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Segment {
+    recursive: bool,
+    selectors: Vec<Selector>,
+}
+
+/// Evaluate `path` against `value`, returning every match in document order.
+pub fn query<'a>(value: &'a Value, path: &str) -> Result<Vec<&'a Value>, String> {
+    let segments = parse(path)?;
+    let mut current = vec![value];
+    for segment in &segments {
+        let mut next = Vec::new();
+        for node in current {
+            if segment.recursive {
+                let mut descendants = Vec::new();
+                collect_descendants(node, &mut descendants);
+                for candidate in descendants {
+                    apply_selectors(candidate, &segment.selectors, &mut next);
+                }
+            } else {
+                apply_selectors(node, &segment.selectors, &mut next);
+            }
+        }
+        current = next;
+    }
+    Ok(current)
+}
+
+/// The first match of `path`, if any.
+pub fn query_first<'a>(value: &'a Value, path: &str) -> Result<Option<&'a Value>, String> {
+    Ok(query(value, path)?.into_iter().next())
+}
+
+fn collect_descendants<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    out.push(value);
+    match value {
+        Value::Array(items) => items.iter().for_each(|item| collect_descendants(item, out)),
+        Value::Object(members) => members
+            .values()
+            .for_each(|member| collect_descendants(member, out)),
+        _ => {}
+    }
+}
+
+fn apply_selectors<'a>(value: &'a Value, selectors: &[Selector], out: &mut Vec<&'a Value>) {
+    for selector in selectors {
+        match (selector, value) {
+            (Selector::Name(name), Value::Object(members)) => out.extend(members.get(name)),
+            (Selector::Index(index), Value::Array(items)) => {
+                out.extend(resolve_index(*index, items.len()).and_then(|i| items.get(i)))
+            }
+            (Selector::Wildcard, Value::Array(items)) => out.extend(items),
+            (Selector::Wildcard, Value::Object(members)) => out.extend(members.values()),
+            (Selector::Slice(start, end, step), Value::Array(items)) => {
+                let len = items.len() as i64;
+                let clamp = |bound: i64| {
+                    if bound < 0 {
+                        (len + bound).max(0)
+                    } else {
+                        bound.min(len)
+                    }
+                };
+                let start = start.map_or(0, clamp);
+                let end = end.map_or(len, clamp);
+                let mut i = start;
+                while i < end {
+                    out.extend(items.get(i as usize));
+                    i += step;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn resolve_index(index: i64, len: usize) -> Option<usize> {
+    if index < 0 {
+        len.checked_sub(index.unsigned_abs() as usize)
+    } else {
+        Some(index as usize)
+    }
+}
+
+fn parse(path: &str) -> Result<Vec<Segment>, String> {
+    let path = path.trim();
+    let rest = path
+        .strip_prefix('$')
+        .ok_or_else(|| format!("JSONPath must start with `$`: {path}"))?;
+    let chars: Vec<char> = rest.chars().collect();
+    let mut segments = Vec::new();
+    let mut pos = 0;
+    while pos < chars.len() {
+        let recursive = chars[pos..].starts_with(&['.', '.']);
+        if recursive {
+            pos += 2;
+        } else if chars[pos] == '.' {
+            pos += 1;
+        } else if chars[pos] != '[' {
+            return Err(format!(
+                "unexpected `{}` at offset {} in JSONPath: {path}",
+                chars[pos],
+                pos + 1
+            ));
+        }
+
+        let selectors = if chars.get(pos) == Some(&'[') {
+            let (selectors, next) = parse_bracket(&chars, pos + 1, path)?;
+            pos = next;
+            selectors
+        } else if chars.get(pos) == Some(&'*') {
+            pos += 1;
+            vec![Selector::Wildcard]
+        } else {
+            let start = pos;
+            while pos < chars.len()
+                && (chars[pos].is_alphanumeric() || matches!(chars[pos], '_' | '-' | '$'))
+            {
+                pos += 1;
+            }
+            if start == pos {
+                return Err(format!("expected a member name in JSONPath: {path}"));
+            }
+            vec![Selector::Name(chars[start..pos].iter().collect())]
+        };
+        segments.push(Segment {
+            recursive,
+            selectors,
+        });
+    }
+    Ok(segments)
+}
+
+/// Parse the inside of `[...]` starting after the `[`. Returns the selectors
+/// and the position after the closing `]`.
+fn parse_bracket(
+    chars: &[char],
+    mut pos: usize,
+    path: &str,
+) -> Result<(Vec<Selector>, usize), String> {
+    let mut selectors = Vec::new();
+    loop {
+        while chars.get(pos).is_some_and(|c| c.is_whitespace()) {
+            pos += 1;
+        }
+        match chars.get(pos) {
+            None => return Err(format!("unterminated `[` in JSONPath: {path}")),
+            Some('?') => return Err(format!("filter expressions are not supported: {path}")),
+            Some(quote @ ('\'' | '"')) => {
+                let quote = *quote;
+                let mut name = String::new();
+                pos += 1;
+                loop {
+                    match chars.get(pos) {
+                        None => return Err(format!("unterminated string in JSONPath: {path}")),
+                        Some('\\') => {
+                            name.extend(chars.get(pos + 1));
+                            pos += 2;
+                        }
+                        Some(c) if *c == quote => {
+                            pos += 1;
+                            break;
+                        }
+                        Some(c) => {
+                            name.push(*c);
+                            pos += 1;
+                        }
+                    }
+                }
+                selectors.push(Selector::Name(name));
+            }
+            Some('*') => {
+                pos += 1;
+                selectors.push(Selector::Wildcard);
+            }
+            Some(_) => {
+                let start = pos;
+                while chars.get(pos).is_some_and(|c| !matches!(c, ',' | ']')) {
+                    pos += 1;
+                }
+                let text: String = chars[start..pos].iter().collect();
+                selectors.push(parse_index_or_slice(text.trim(), path)?);
+            }
+        }
+        while chars.get(pos).is_some_and(|c| c.is_whitespace()) {
+            pos += 1;
+        }
+        match chars.get(pos) {
+            Some(',') => pos += 1,
+            Some(']') => return Ok((selectors, pos + 1)),
+            _ => return Err(format!("expected `,` or `]` in JSONPath: {path}")),
+        }
+    }
+}
+
+fn parse_index_or_slice(text: &str, path: &str) -> Result<Selector, String> {
+    let parse_int = |part: &str| -> Result<Option<i64>, String> {
+        let part = part.trim();
+        if part.is_empty() {
+            return Ok(None);
+        }
+        part.parse::<i64>()
+            .map(Some)
+            .map_err(|_| format!("invalid index `{part}` in JSONPath: {path}"))
+    };
+    if !text.contains(':') {
+        return parse_int(text)?
+            .map(Selector::Index)
+            .ok_or_else(|| format!("empty brackets in JSONPath: {path}"));
+    }
+    let parts: Vec<&str> = text.split(':').collect();
+    if parts.len() > 3 {
+        return Err(format!("invalid slice `{text}` in JSONPath: {path}"));
+    }
+    let step = match parts.get(2) {
+        Some(part) => parse_int(part)?.unwrap_or(1),
+        None => 1,
+    };
+    if step <= 0 {
+        return Err(format!("slice step must be positive in JSONPath: {path}"));
+    }
+    Ok(Selector::Slice(
+        parse_int(parts[0])?,
+        parse_int(parts[1])?,
+        step,
+    ))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn doc() -> Value {
+        json!({
+            "account": { "id": "acct-1", "currency": "USD" },
+            "data": {
+                "transactions": [
+                    { "id": "t1", "amount": -4.5, "merchant": { "name": "CAFE" } },
+                    { "id": "t2", "amount": 2000, "merchant": { "name": "PAYROLL" } },
+                    { "id": "t3", "amount": -61.2, "merchant": { "name": "GROCER" } }
+                ]
+            }
+        })
+    }
+
+    fn ids(matches: &[&Value]) -> Vec<String> {
+        matches
+            .iter()
+            .map(|v| v.as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn evaluates_members_indexes_slices_and_descent() {
+        let doc = doc();
+        assert_eq!(query(&doc, "$").unwrap(), vec![&doc]);
+        assert_eq!(
+            ids(&query(&doc, "$.data.transactions[*].id").unwrap()),
+            vec!["t1", "t2", "t3"]
+        );
+        assert_eq!(
+            ids(&query(&doc, "$['data']['transactions'][-1].id").unwrap()),
+            vec!["t3"]
+        );
+        assert_eq!(
+            ids(&query(&doc, "$.data.transactions[0:3:2].id").unwrap()),
+            vec!["t1", "t3"]
+        );
+        assert_eq!(
+            ids(&query(&doc, "$.data.transactions[0,2].merchant.name").unwrap()),
+            vec!["CAFE", "GROCER"]
+        );
+        assert_eq!(
+            ids(&query(&doc, "$..merchant.name").unwrap()),
+            vec!["CAFE", "PAYROLL", "GROCER"]
+        );
+        assert_eq!(
+            query_first(&doc, "$.account.currency").unwrap(),
+            Some(&json!("USD"))
+        );
+        assert!(query(&doc, "$.missing.path").unwrap().is_empty());
+    }
+
+    #[test]
+    fn rejects_malformed_paths() {
+        let doc = doc();
+        assert!(query(&doc, "data.transactions").is_err());
+        assert!(query(&doc, "$.data[").is_err());
+        assert!(query(&doc, "$.data.transactions[?(@.amount < 0)]").is_err());
+        assert!(query(&doc, "$.data.transactions[::0]").is_err());
+        assert!(query(&doc, "$.data.transactions[x]").is_err());
+    }
+}
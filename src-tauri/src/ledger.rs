@@ -39,20 +39,57 @@ pub(crate) fn require_refreshmint_extension(path: &Path) -> io::Result<()> {
     }
 }
 
-pub fn new_ledger_at_dir(target_dir: &Path) -> io::Result<()> {
+/// Create a new ledger, seeded from `template` when one is given.
+pub fn new_ledger_at_dir(
+    target_dir: &Path,
+    template: Option<crate::onboarding::LedgerTemplate>,
+) -> io::Result<()> {
     create_ledger_dir(target_dir)?;
     enable_bundle_attr_if_supported(target_dir)?;
     write_refreshmint_json(target_dir)?;
     create_general_journal(target_dir)?;
     crate::bookkeeping::ensure_bookkeeping_layout(target_dir)?;
-    init_git_repo(target_dir)?;
+    let seeded = match template {
+        Some(template) => crate::onboarding::apply_template(target_dir, template)?,
+        None => Vec::new(),
+    };
+    init_git_repo(target_dir, &seeded)?;
     Ok(())
 }
 
+/// Make a directory that already holds ledger files into a ledger: mark it as
+/// a bundle and commit `files` with `general.journal` and `refreshmint.json`.
+pub(crate) fn init_ledger_repo(dir: &Path, files: &[PathBuf]) -> io::Result<()> {
+    enable_bundle_attr_if_supported(dir)?;
+    init_git_repo(dir, files)
+}
+
 pub(crate) fn commit_general_journal(dir: &Path, message: &str) -> io::Result<()> {
     commit_paths(dir, &[Path::new("general.journal")], message)
 }
 
+/// Commit a GL layout change: general.journal, its shards, and the layout.
+pub(crate) fn commit_gl_layout(dir: &Path, message: &str) -> io::Result<()> {
+    commit_paths(
+        dir,
+        &[Path::new("general.journal"), Path::new("gl-layout.json")],
+        message,
+    )
+}
+
+pub(crate) fn commit_envelopes_journal(dir: &Path, message: &str) -> io::Result<()> {
+    commit_paths(dir, &[Path::new("envelopes.journal")], message)
+}
+
+/// Commit prices.journal and the general.journal that includes it.
+pub(crate) fn commit_prices_journal(dir: &Path, message: &str) -> io::Result<()> {
+    commit_paths(
+        dir,
+        &[Path::new("general.journal"), Path::new("prices.journal")],
+        message,
+    )
+}
+
 /// Commit general.journal plus a login account journal after a single-entry post.
 pub(crate) fn commit_post_changes(
     dir: &Path,
@@ -68,6 +105,55 @@ pub(crate) fn commit_post_changes(
     commit_paths(dir, &[Path::new("general.journal"), &acct_rel], message)
 }
 
+/// Commit a login account journal after an edit that leaves the GL alone.
+pub(crate) fn commit_login_account_journal(
+    dir: &Path,
+    login_name: &str,
+    label: &str,
+    message: &str,
+) -> io::Result<()> {
+    let acct_rel = PathBuf::from("logins")
+        .join(login_name)
+        .join("accounts")
+        .join(label)
+        .join("account.journal");
+    commit_paths(dir, &[&acct_rel], message)
+}
+
+/// [`commit_login_account_journal`], logging a failed commit instead of
+/// returning it: the journal edit itself has already been written.
+pub(crate) fn commit_login_account_journal_or_warn(
+    dir: &Path,
+    login_name: &str,
+    label: &str,
+    message: &str,
+) {
+    if let Err(err) = commit_login_account_journal(dir, login_name, label, message) {
+        tracing::warn!("git commit failed after {message}: {err}");
+    }
+}
+
+/// Commit general.journal plus any number of login account journals.
+pub(crate) fn commit_gl_and_login_account_journals(
+    dir: &Path,
+    accounts: &[(String, String)],
+    message: &str,
+) -> io::Result<()> {
+    let acct_rels: Vec<PathBuf> = accounts
+        .iter()
+        .map(|(login_name, label)| {
+            PathBuf::from("logins")
+                .join(login_name)
+                .join("accounts")
+                .join(label)
+                .join("account.journal")
+        })
+        .collect();
+    let mut paths: Vec<&Path> = vec![Path::new("general.journal")];
+    paths.extend(acct_rels.iter().map(PathBuf::as_path));
+    commit_paths(dir, &paths, message)
+}
+
 /// Commit general.journal plus two login account journals after a transfer post.
 pub(crate) fn commit_transfer_changes(
     dir: &Path,
@@ -94,15 +180,53 @@ pub(crate) fn commit_transfer_changes(
     )
 }
 
+/// Stage `paths` and let the ledger's commit policy decide whether to commit
+/// now; see [`crate::commit_policy`]. Staging general.journal also stages the
+/// GL shards under `gl/`, including deleted ones; see [`crate::gl_layout`].
 fn commit_paths(dir: &Path, paths: &[&Path], message: &str) -> io::Result<()> {
     let repo = git2::Repository::open(dir).map_err(|e| io::Error::other(e.to_string()))?;
     let mut index = repo.index().map_err(|e| io::Error::other(e.to_string()))?;
+    if paths.contains(&Path::new("general.journal")) {
+        let shards = [crate::gl_layout::SHARD_DIR];
+        index
+            .add_all(shards, git2::IndexAddOption::DEFAULT, None)
+            .and_then(|()| index.update_all(shards, None))
+            .map_err(|e| io::Error::other(e.to_string()))?;
+    }
     for path in paths {
         index
             .add_path(path)
             .map_err(|e| io::Error::other(e.to_string()))?;
     }
     index.write().map_err(|e| io::Error::other(e.to_string()))?;
+    crate::commit_policy::record_operation(dir, &repo, message)
+}
+
+/// Commit the index as it stands on top of HEAD, signed when the ledger has
+/// commit signing configured; see [`crate::commit_signing`].
+pub(crate) fn commit_index(repo: &git2::Repository, message: &str) -> io::Result<()> {
+    let signing = match repo.workdir() {
+        Some(dir) => crate::commit_signing::get_commit_signing(dir)?.map(|signing| (dir, signing)),
+        None => None,
+    };
+    match signing {
+        Some((dir, signing)) => {
+            commit_index_with(repo, message, Some(&|content| signing.sign(dir, content)))
+        }
+        None => commit_index_with(repo, message, None),
+    }
+}
+
+/// Returns the armored signature for a commit buffer.
+pub(crate) type CommitSigner<'a> = &'a dyn Fn(&str) -> io::Result<String>;
+
+/// Commit the index, signing the commit with `sign` when given.
+pub(crate) fn commit_index_with(
+    repo: &git2::Repository,
+    message: &str,
+    sign: Option<CommitSigner<'_>>,
+) -> io::Result<()> {
+    let mut index = repo.index().map_err(|e| io::Error::other(e.to_string()))?;
     let tree_oid = index
         .write_tree()
         .map_err(|e| io::Error::other(e.to_string()))?;
@@ -111,11 +235,27 @@ fn commit_paths(dir: &Path, paths: &[&Path], message: &str) -> io::Result<()> {
         .map_err(|e| io::Error::other(e.to_string()))?;
     let sig = git2::Signature::now(GIT_USER_NAME, GIT_USER_EMAIL)
         .map_err(|e| io::Error::other(e.to_string()))?;
-    let head = repo.head().map_err(|e| io::Error::other(e.to_string()))?;
+    let mut head = repo.head().map_err(|e| io::Error::other(e.to_string()))?;
     let parent = head
         .peel_to_commit()
         .map_err(|e| io::Error::other(e.to_string()))?;
-    repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+    let Some(sign) = sign else {
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &[&parent])
+            .map_err(|e| io::Error::other(e.to_string()))?;
+        return Ok(());
+    };
+    let buffer = repo
+        .commit_create_buffer(&sig, &sig, message, &tree, &[&parent])
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let content = buffer
+        .as_str()
+        .ok_or_else(|| io::Error::other("commit is not valid UTF-8"))?;
+    let signature = sign(content)?;
+    let oid = repo
+        .commit_signed(content, &signature, None)
+        .map_err(|e| io::Error::other(e.to_string()))?;
+    let summary = message.lines().next().unwrap_or("");
+    head.set_target(oid, &format!("commit: {summary}"))
         .map_err(|e| io::Error::other(e.to_string()))?;
     Ok(())
 }
@@ -147,7 +287,7 @@ fn create_general_journal(dir: &Path) -> io::Result<()> {
     Ok(())
 }
 
-fn init_git_repo(dir: &Path) -> io::Result<()> {
+fn init_git_repo(dir: &Path, seeded: &[PathBuf]) -> io::Result<()> {
     let repo = git2::Repository::init(dir).map_err(|e| io::Error::other(e.to_string()))?;
 
     // Set default branch to main
@@ -165,6 +305,11 @@ fn init_git_repo(dir: &Path) -> io::Result<()> {
     index
         .add_path(Path::new("refreshmint.json"))
         .map_err(|e| io::Error::other(e.to_string()))?;
+    for path in seeded {
+        index
+            .add_path(path)
+            .map_err(|e| io::Error::other(e.to_string()))?;
+    }
     index.write().map_err(|e| io::Error::other(e.to_string()))?;
     let tree_oid = index
         .write_tree()
@@ -1,11 +1,11 @@
 use crate::ledger_open::LedgerView;
 use serde::Deserialize;
-use std::fs::OpenOptions;
 use std::io::{self, Write};
 use std::path::Path;
 use std::process::{Command, Stdio};
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts", rename = "NewTransactionInput")]
 pub struct NewTransaction {
     pub date: String,
     pub description: String,
@@ -13,7 +13,8 @@ pub struct NewTransaction {
     pub postings: Vec<NewPosting>,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts", rename = "NewPostingInput")]
 pub struct NewPosting {
     pub account: String,
     pub amount: Option<String>,
@@ -82,7 +83,7 @@ pub fn add_transaction_to_ledger(
     let commit_message = transaction_commit_message(&transaction.date, &transaction.description);
     run_hledger_check(&serialized, &[], "transaction-only")?;
     run_hledger_check(&serialized, &[&journal_path], "journal-plus-transaction")?;
-    append_transaction(&journal_path, &serialized)?;
+    crate::gl_layout::append_transaction(ledger_dir, &serialized)?;
     crate::ledger::commit_general_journal(ledger_dir, &commit_message)?;
     crate::ledger_open::open_ledger_dir(ledger_dir)
 }
@@ -96,7 +97,7 @@ pub fn add_transaction_text(
     let (serialized, _) = crate::gl_journal::ensure_journal_has_ids(&serialized);
     run_hledger_check(&serialized, &[], "transaction-only")?;
     run_hledger_check(&serialized, &[&journal_path], "journal-plus-transaction")?;
-    append_transaction(&journal_path, &serialized)?;
+    crate::gl_layout::append_transaction(ledger_dir, &serialized)?;
     let commit_message = transaction_commit_message_from_text(&serialized);
     crate::ledger::commit_general_journal(ledger_dir, &commit_message)?;
     crate::ledger_open::open_ledger_dir(ledger_dir)
@@ -112,6 +113,68 @@ pub fn validate_transaction_text(
     Ok(())
 }
 
+/// Validate many raw transactions with as few hledger runs as possible.
+/// Returns one error message (or `None`) per transaction.
+pub fn validate_transactions_text(
+    ledger_dir: &Path,
+    transactions: &[String],
+) -> Result<Vec<Option<String>>, Box<dyn std::error::Error>> {
+    prepare_ledger(ledger_dir)?;
+    let transactions: Vec<String> = transactions
+        .iter()
+        .map(|transaction| ensure_trailing_newline(transaction))
+        .collect();
+    Ok(crate::hledger_batch::check_transactions_batched(
+        &transactions,
+        &|text| run_hledger_check(text, &[], "transaction-only"),
+    ))
+}
+
+/// Append many raw transactions with one validation pass and one commit.
+/// Nothing is written if any transaction fails validation.
+pub fn add_transactions_text(
+    ledger_dir: &Path,
+    transactions: &[String],
+) -> Result<LedgerView, Box<dyn std::error::Error>> {
+    let journal_path = prepare_ledger(ledger_dir)?;
+    if transactions.is_empty() {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "no transactions to add").into());
+    }
+    let transactions: Vec<String> = transactions
+        .iter()
+        .map(|transaction| {
+            let (serialized, _) =
+                crate::gl_journal::ensure_journal_has_ids(&ensure_trailing_newline(transaction));
+            serialized
+        })
+        .collect();
+    let errors = crate::hledger_batch::check_transactions_batched(&transactions, &|text| {
+        run_hledger_check(text, &[&journal_path], "journal-plus-transaction")
+    });
+    let failures: Vec<String> = errors
+        .iter()
+        .enumerate()
+        .filter_map(|(index, error)| {
+            error
+                .as_ref()
+                .map(|error| format!("transaction {}: {error}", index + 1))
+        })
+        .collect();
+    if !failures.is_empty() {
+        return Err(io::Error::other(failures.join("\n")).into());
+    }
+
+    // With no failures the batch passed as a whole on the first check run;
+    // failures that only show up in combination are reported above.
+    let texts: Vec<&str> = transactions.iter().map(String::as_str).collect();
+    crate::gl_layout::append_transactions(ledger_dir, &texts)?;
+    crate::ledger::commit_general_journal(
+        ledger_dir,
+        &format!("Add {} transactions", transactions.len()),
+    )?;
+    crate::ledger_open::open_ledger_dir(ledger_dir)
+}
+
 pub fn validate_transaction_only(
     ledger_dir: &Path,
     transaction: NewTransaction,
@@ -290,7 +353,21 @@ fn transaction_commit_message_from_text(transaction: &str) -> String {
     "Add transaction".to_string()
 }
 
-fn run_hledger_check(transaction: &str, extra_files: &[&Path], context: &str) -> io::Result<()> {
+pub(crate) fn run_hledger_check(
+    transaction: &str,
+    extra_files: &[&Path],
+    context: &str,
+) -> io::Result<()> {
+    crate::hledger_batch::timed("check", || {
+        run_hledger_check_uncached(transaction, extra_files, context)
+    })
+}
+
+fn run_hledger_check_uncached(
+    transaction: &str,
+    extra_files: &[&Path],
+    context: &str,
+) -> io::Result<()> {
     let mut cmd = Command::new(crate::binpath::hledger_path());
     cmd.arg("check");
     cmd.arg("--color=never");
@@ -322,15 +399,6 @@ fn run_hledger_check(transaction: &str, extra_files: &[&Path], context: &str) ->
     }
 }
 
-fn append_transaction(journal_path: &Path, transaction: &str) -> io::Result<()> {
-    let mut file = OpenOptions::new().append(true).open(journal_path)?;
-    if file.metadata()?.len() > 0 {
-        file.write_all(b"\n")?;
-    }
-    file.write_all(transaction.as_bytes())?;
-    Ok(())
-}
-
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -348,7 +416,7 @@ mod tests {
             "refreshmint-ledger-add-{prefix}-{}-{now}.refreshmint",
             std::process::id()
         ));
-        crate::ledger::new_ledger_at_dir(&dir).unwrap();
+        crate::ledger::new_ledger_at_dir(&dir, None).unwrap();
         dir
     }
 
@@ -1,12 +1,21 @@
 use crate::hledger::{Amount, Posting, Side, Transaction};
-use serde::Serialize;
+use serde::de::{IgnoredAny, SeqAccess, Visitor};
+use serde::{Deserializer, Serialize};
 use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
-use std::io;
+use std::io::{self, BufReader, Read};
+use std::ops::Range;
 use std::path::Path;
-use std::process::Command;
+use std::process::{Command, Stdio};
 
-#[derive(Debug, Serialize)]
+/// Transactions a paged query will page through before asking for a
+/// narrower query.
+pub const DEFAULT_QUERY_ROW_CAP: usize = 5000;
+/// Rows per page when the caller doesn't say.
+pub const DEFAULT_QUERY_PAGE_SIZE: usize = 200;
+
+#[derive(Debug, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct LedgerView {
     pub path: String,
     pub accounts: Vec<AccountRow>,
@@ -14,8 +23,9 @@ pub struct LedgerView {
     pub gl_account_conflicts: Vec<crate::login_config::GlAccountConflict>,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct AccountRow {
     pub name: String,
     pub totals: Option<Vec<AmountTotal>>,
@@ -24,8 +34,9 @@ pub struct AccountRow {
     pub unposted_count: usize,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct TransactionRow {
     pub id: String,
     pub date: String,
@@ -39,8 +50,9 @@ pub struct TransactionRow {
     pub bookkeeping: TransactionBookkeeping,
 }
 
-#[derive(Debug, Clone, Default, Serialize)]
+#[derive(Debug, Clone, Default, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct TransactionBookkeeping {
     pub generated: bool,
     pub reconciled_session_ids: Vec<String>,
@@ -49,8 +61,9 @@ pub struct TransactionBookkeeping {
     pub soft_closed_period_id: Option<String>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct AmountTotal {
     pub commodity: String,
     pub mantissa: String,
@@ -58,15 +71,17 @@ pub struct AmountTotal {
     pub style: Option<AmountStyleHint>,
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct AmountStyleHint {
     pub side: Side,
     pub spaced: bool,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct PostingRow {
     pub account: String,
     pub amount: Option<String>,
@@ -88,6 +103,7 @@ struct CommodityTotal {
 }
 
 pub fn open_ledger_dir(path: &Path) -> Result<LedgerView, Box<dyn std::error::Error>> {
+    let _span = crate::perf::span("ledger", "open");
     crate::ledger::require_refreshmint_extension(path)?;
     if !path.is_dir() {
         return Err(io::Error::new(io::ErrorKind::NotFound, "ledger directory not found").into());
@@ -102,6 +118,18 @@ pub fn open_ledger_dir(path: &Path) -> Result<LedgerView, Box<dyn std::error::Er
         .into());
     }
 
+    match crate::atomic_file::remove_orphaned_temp_files(path) {
+        Ok(removed) => {
+            for temp_path in removed {
+                tracing::warn!(
+                    "removed temp file left by an interrupted write: {}",
+                    temp_path.display()
+                );
+            }
+        }
+        Err(err) => tracing::warn!("failed to clean up interrupted writes: {err}"),
+    }
+
     let journal_path = path.join("general.journal");
     if !journal_path.is_file() {
         return Err(io::Error::new(io::ErrorKind::NotFound, "general.journal not found").into());
@@ -128,6 +156,67 @@ pub(crate) fn run_hledger_print_with_query(
     journal_path: &Path,
     query_tokens: &[String],
 ) -> io::Result<Vec<Transaction>> {
+    crate::hledger_batch::print_cached(journal_path, query_tokens, || {
+        run_hledger_print_uncached(journal_path, query_tokens)
+    })
+}
+
+fn run_hledger_print_uncached(
+    journal_path: &Path,
+    query_tokens: &[String],
+) -> io::Result<Vec<Transaction>> {
+    stream_hledger_print(journal_path, query_tokens, 0..usize::MAX).map(|(txns, _)| txns)
+}
+
+/// One page of a GL query's results.
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TransactionPage {
+    pub rows: Vec<TransactionRow>,
+    pub offset: usize,
+    /// Transactions matching the query, including those past the cap.
+    pub total: usize,
+    /// True when more transactions match than the row cap allows; the
+    /// frontend should ask the user to refine the query.
+    pub capped: bool,
+    /// True when another page can be fetched after this one.
+    pub has_more: bool,
+}
+
+/// Rows `offset..offset + limit` of a GL query, never reaching past
+/// `row_cap`. hledger's output is parsed as it streams, so only the page is
+/// held in memory however many transactions match.
+pub(crate) fn query_transaction_page(
+    ledger_dir: &Path,
+    query: &str,
+    offset: usize,
+    limit: usize,
+    row_cap: usize,
+) -> io::Result<TransactionPage> {
+    let journal_path = ledger_dir.join("general.journal");
+    let tokens = tokenize_query(query);
+    let end = offset.saturating_add(limit).min(row_cap);
+    let (transactions, total) = crate::hledger_batch::timed("print", || {
+        stream_hledger_print(&journal_path, &tokens, offset..end)
+    })?;
+    Ok(TransactionPage {
+        rows: build_transaction_rows(ledger_dir, &transactions)?,
+        offset,
+        total,
+        capped: total > row_cap,
+        has_more: end < total.min(row_cap),
+    })
+}
+
+/// Run `hledger print` and parse its JSON array as it is read, keeping only
+/// the transactions whose positions fall in `keep`. Returns the kept
+/// transactions and how many were printed in all.
+fn stream_hledger_print(
+    journal_path: &Path,
+    query_tokens: &[String],
+    keep: Range<usize>,
+) -> io::Result<(Vec<Transaction>, usize)> {
     let mut cmd = Command::new(crate::binpath::hledger_path());
     cmd.arg("print")
         .arg("--output-format=json")
@@ -135,17 +224,81 @@ pub(crate) fn run_hledger_print_with_query(
         .arg(journal_path)
         .env("GIT_CONFIG_GLOBAL", crate::ledger::NULL_DEVICE)
         .env("GIT_CONFIG_SYSTEM", crate::ledger::NULL_DEVICE)
-        .env("GIT_CONFIG_NOSYSTEM", "1");
+        .env("GIT_CONFIG_NOSYSTEM", "1")
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
     for token in query_tokens {
         cmd.arg(token);
     }
-    let output = cmd.output()?;
-    if output.status.success() {
-        serde_json::from_slice(&output.stdout).map_err(io::Error::other)
-    } else {
-        Err(io::Error::other(
-            String::from_utf8_lossy(&output.stderr).to_string(),
-        ))
+    let mut child = cmd.spawn()?;
+    let stdout = child
+        .stdout
+        .take()
+        .ok_or_else(|| io::Error::other("hledger stdout was not captured"))?;
+    // Drain stderr alongside stdout so a chatty hledger can't block on a
+    // full pipe.
+    let mut stderr = child.stderr.take();
+    let stderr_reader = std::thread::spawn(move || {
+        let mut text = String::new();
+        if let Some(stderr) = stderr.as_mut() {
+            let _ = stderr.read_to_string(&mut text);
+        }
+        text
+    });
+
+    let parsed = parse_print_stream(BufReader::new(stdout), keep);
+    if parsed.is_err() {
+        // The rest of stdout was never read; stop hledger rather than wait
+        // on a child that may be blocked writing to a full pipe.
+        let _ = child.kill();
+    }
+    let status = child.wait()?;
+    let stderr = stderr_reader.join().unwrap_or_default();
+    if !status.success() {
+        return Err(io::Error::other(stderr));
+    }
+    parsed
+}
+
+fn parse_print_stream(
+    reader: impl Read,
+    keep: Range<usize>,
+) -> io::Result<(Vec<Transaction>, usize)> {
+    let mut deserializer = serde_json::Deserializer::from_reader(reader);
+    let parsed = (&mut deserializer)
+        .deserialize_seq(PrintVisitor { keep })
+        .map_err(io::Error::other)?;
+    deserializer.end().map_err(io::Error::other)?;
+    Ok(parsed)
+}
+
+struct PrintVisitor {
+    keep: Range<usize>,
+}
+
+impl<'de> Visitor<'de> for PrintVisitor {
+    type Value = (Vec<Transaction>, usize);
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("an array of hledger transactions")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut kept = Vec::new();
+        let mut total = 0;
+        loop {
+            // Transactions outside the page are skipped without being built.
+            if self.keep.contains(&total) {
+                match seq.next_element::<Transaction>()? {
+                    Some(txn) => kept.push(txn),
+                    None => break,
+                }
+            } else if seq.next_element::<IgnoredAny>()?.is_none() {
+                break;
+            }
+            total += 1;
+        }
+        Ok((kept, total))
     }
 }
 
@@ -439,7 +592,7 @@ fn posting_amount_text(posting: &Posting) -> Option<String> {
     format_amount(&posting.pamount[0])
 }
 
-fn format_amount(amount: &Amount) -> Option<String> {
+pub(crate) fn format_amount(amount: &Amount) -> Option<String> {
     if amount.acost.is_some() || amount.acostbasis.is_some() {
         return None;
     }
@@ -630,6 +783,23 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn print_stream_keeps_only_the_requested_page() {
+        let txns: Vec<Transaction> = (1..=5).map(|n| make_txn(n, vec![], "")).collect();
+        let json = serde_json::to_vec(&txns).unwrap();
+        let (page, total) = parse_print_stream(json.as_slice(), 1..3).unwrap();
+        assert_eq!(total, 5);
+        assert_eq!(
+            page.iter().map(|txn| txn.tindex).collect::<Vec<_>>(),
+            vec![2, 3]
+        );
+
+        let (all, total) = parse_print_stream(&b"[]"[..], 0..usize::MAX).unwrap();
+        assert!(all.is_empty());
+        assert_eq!(total, 0);
+        assert!(parse_print_stream(&b"[{"[..], 0..1).is_err());
+    }
+
     #[test]
     fn tokenize_simple() {
         assert_eq!(
@@ -5,28 +5,83 @@ pub mod secret;
 
 pub mod account_config;
 pub mod account_journal;
+pub mod account_routing;
+pub mod alerts;
+pub mod balances;
+pub mod bank_statement;
+pub mod beancount;
+pub mod book_import;
 pub mod bookkeeping;
 pub mod categorize;
+pub mod classification_queue;
+pub mod commit_policy;
+pub mod commit_signing;
+pub mod cost_basis;
+pub mod coverage;
 pub mod dedup;
+pub mod description_cleaning;
+pub mod digests;
+pub mod document_labels;
+pub mod entry_id;
+pub mod entry_review;
+pub mod entry_split;
+pub mod envelopes;
+pub mod extension_storage;
 pub mod extract;
+pub mod fx_rates;
+pub mod gl_layout;
+pub mod gnucash;
+pub mod html_table;
+pub mod inter_ledger;
+pub mod invoices;
+pub mod jobs;
+pub mod journal_export;
+pub mod json_path;
+pub mod lint;
+pub mod loans;
+pub mod locations;
+pub mod logging;
 pub mod login_config;
+pub mod mcc;
 pub mod migration;
+pub mod onboarding;
+pub mod opening_balance;
 pub mod operations;
+pub mod paper_trail;
+pub mod paychecks;
+pub mod payees;
+pub mod perf;
 pub mod post;
+pub mod posted_changes;
+pub mod provenance;
+pub mod qif;
+pub mod redacted_export;
 pub mod report;
+pub mod report_groups;
+pub mod scrape_artifacts;
+pub mod scrape_stats;
 pub mod staging;
+pub mod subscriptions;
+pub mod suspicious;
+pub mod tax_documents;
+pub mod transaction_details;
 pub mod transfer_detector;
+pub mod xlsx;
 
+mod atomic_file;
 mod binpath;
 mod builtin_extensions;
 mod extension;
 mod gl_journal;
+mod hledger_batch;
 mod js_module_loader;
 mod ledger;
 mod ledger_add;
 mod ledger_open;
+mod statement_period;
 mod ts_strip;
 mod version;
+mod xml_tree;
 
 use tauri::{Emitter, Manager};
 
@@ -41,8 +96,9 @@ struct LockMetadataWatcher {
 }
 
 /// Per-domain credential status returned by list/sync commands.
-#[derive(Clone, Debug, serde::Serialize)]
+#[derive(Clone, Debug, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 struct DomainSecretEntry {
     domain: String,
     has_username: bool,
@@ -51,8 +107,9 @@ struct DomainSecretEntry {
 
 /// Sync result: which domains are required by the manifest, which are missing
 /// credentials, and which are extra (stored but not required).
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 struct SecretSyncResult {
     /// All domains declared in the extension manifest.
     required: Vec<DomainSecretEntry>,
@@ -70,16 +127,19 @@ struct LockStatusChangedEvent {
     ledger_path: String,
 }
 
-#[derive(Clone, Debug, serde::Serialize)]
+#[derive(Clone, Debug, serde::Serialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
 struct LockStatusSnapshot {
     gl: login_config::LockStatus,
     logins: std::collections::BTreeMap<String, login_config::LockStatus>,
 }
 
-#[derive(Clone, Debug, serde::Serialize)]
+#[derive(Clone, Debug, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 struct LoginExtractionSupport {
     supported: bool,
+    #[ts(type = "'missing-extension' | 'missing-extractor' | 'broken-extractor' | null")]
     reason: Option<&'static str>,
 }
 
@@ -89,7 +149,9 @@ struct LoginExtractionSupport {
 /// to send `Some(answer)` for Submit or `None` for Cancel. Keep this aligned
 /// with the receiving half in `scrape/js_api.rs`.
 #[derive(Default)]
-pub struct PromptAnswerState(pub std::sync::Mutex<Option<std::sync::mpsc::Sender<Option<String>>>>);
+pub struct PromptAnswerState(
+    pub std::sync::Mutex<Option<std::sync::mpsc::Sender<Option<crate::secret::SecretString>>>>,
+);
 
 static UI_DEBUG_SESSION: std::sync::OnceLock<std::sync::Mutex<Option<UiDebugSession>>> =
     std::sync::OnceLock::new();
@@ -104,6 +166,17 @@ fn lock_metadata_watcher_state() -> &'static std::sync::Mutex<Option<LockMetadat
     LOCK_METADATA_WATCHER.get_or_init(|| std::sync::Mutex::new(None))
 }
 
+/// Wrap an invoke handler so each command's latency is recorded by `perf`.
+/// Synchronous commands are timed in full; async ones only until spawned.
+fn timed_invoke_handler<R: tauri::Runtime>(
+    handler: impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(tauri::ipc::Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let _span = perf::span("command", invoke.message.command());
+        handler(invoke)
+    }
+}
+
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() -> Result<(), Box<dyn std::error::Error>> {
     let context: tauri::Context<tauri::Wry> = tauri::generate_context!();
@@ -113,17 +186,30 @@ pub fn run() -> Result<(), Box<dyn std::error::Error>> {
 pub fn run_with_context(
     context: tauri::Context<tauri::Wry>,
 ) -> Result<(), Box<dyn std::error::Error>> {
+    logging::init();
     tauri::Builder::default()
         .plugin(tauri_plugin_dialog::init())
         .plugin(tauri_plugin_store::Builder::default().build())
         .manage(PromptAnswerState::default())
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(timed_invoke_handler(tauri::generate_handler![
             new_ledger,
+            list_ledger_templates,
+            get_onboarding_status,
             open_ledger,
             add_transaction,
             validate_transaction,
             add_transaction_text,
             validate_transaction_text,
+            add_transactions_text,
+            validate_transactions_text,
+            get_hledger_timings,
+            reset_hledger_timings,
+            get_performance_stats,
+            reset_performance_stats,
+            get_recent_logs,
+            get_log_levels,
+            set_log_levels,
+            get_js_api_version,
             list_scrape_extensions,
             load_scrape_extension,
             start_scrape_debug_session_for_login,
@@ -135,10 +221,77 @@ pub fn run_with_context(
             get_lock_status_snapshot,
             get_login_extraction_support,
             run_scrape_for_login,
+            verify_scrape_prerequisites,
             run_scrape,
+            collect_tax_documents,
+            list_tax_documents,
+            list_paystubs,
+            get_cost_basis_config,
+            save_cost_basis_config,
+            report_realized_gains,
+            list_invoices,
+            record_invoice,
+            report_accounts_receivable,
+            export_entries_to_ledger,
+            export_redacted_ledger,
+            write_paper_trail_report,
+            export_account_journal,
+            plan_opening_balance,
+            record_opening_balance,
+            report_balance_history,
+            list_loans,
+            upsert_loan,
+            delete_loan,
+            get_loan_schedule,
+            report_loan_balance,
+            list_report_groups,
+            upsert_report_group,
+            delete_report_group,
+            get_envelopes,
+            define_envelope,
+            allocate_envelope,
+            list_alert_rules,
+            save_alert_rules,
+            list_alerts,
+            acknowledge_alert,
+            dismiss_alert,
+            report_suspicious_activity,
+            report_subscription_changes,
+            report_spending_by_location,
+            get_description_cleaning,
+            set_description_cleaning,
+            preview_description_cleaning,
+            list_payees,
+            get_payee,
+            backfill_prices,
+            lint_ledger,
+            list_digests,
+            generate_digest,
+            generate_due_digests,
+            get_scrape_queue,
+            set_scrape_concurrency_limit,
+            list_jobs,
+            cancel_job,
+            get_commit_policy,
+            save_commit_policy,
+            get_staging_config,
+            save_staging_config,
+            get_uncommitted_changes,
+            commit_pending_changes,
+            get_gl_layout,
+            convert_gl_layout,
+            get_commit_signing,
+            save_commit_signing,
+            verify_ledger_history,
             get_scrape_log,
+            list_scrape_artifacts,
+            read_scrape_artifact,
+            report_scrape_reliability,
+            report_coverage_gaps,
             list_documents,
             list_login_account_documents,
+            prune_login_account_documents,
+            relabel_documents,
             read_login_account_document_rows,
             read_login_account_document_text,
             read_attachment_data_url,
@@ -148,6 +301,11 @@ pub fn run_with_context(
             get_login_account_journal,
             get_unposted,
             get_login_account_unposted,
+            set_entry_note,
+            set_entry_review_state,
+            bulk_update_entries,
+            split_login_account_entry,
+            merge_login_account_entry_split,
             list_reconciliation_sessions,
             query_reconciliation_candidates,
             create_reconciliation_session,
@@ -169,8 +327,14 @@ pub fn run_with_context(
             post_login_account_transfer,
             get_unposted_entries_for_transfer,
             sync_gl_transaction,
+            list_posted_entry_conflicts,
+            accept_posted_entry_changes,
             suggest_categories,
+            record_category_feedback,
             suggest_gl_categories,
+            get_classification_queue,
+            classify_next,
+            get_transaction_provenance,
             recategorize_gl_transaction,
             merge_gl_transfer,
             get_account_config,
@@ -181,6 +345,15 @@ pub fn run_with_context(
             set_login_extension,
             delete_login,
             set_login_account,
+            set_login_account_type,
+            set_login_account_counterpart,
+            set_login_account_ids,
+            list_unknown_account_ids,
+            assign_account_id,
+            get_login_institution,
+            set_login_institution,
+            set_login_label_rules,
+            suggest_login_account_gl_account,
             remove_login_account,
             delete_login_account,
             repair_login_account_labels,
@@ -193,20 +366,31 @@ pub fn run_with_context(
             get_login_username,
             migrate_login_secrets,
             clear_login_profile,
+            get_required_prompts,
+            get_login_storage,
+            clear_login_storage,
             migrate_ledger,
+            migrate_entry_ids,
+            import_book,
             query_transactions,
+            query_transactions_page,
             run_hledger_report,
             submit_prompt_answer,
-        ])
+        ]))
         .setup(|app| {
             binpath::init_from_app(app.handle());
-            if cfg!(debug_assertions) {
-                app.handle().plugin(
-                    tauri_plugin_log::Builder::default()
-                        .level(log::LevelFilter::Info)
-                        .build(),
-                )?;
-            }
+            let queue_app_handle = app.handle().clone();
+            scrape::queue::ScrapeQueue::global().set_listener(Some(std::sync::Arc::new(
+                move |snapshot: &scrape::queue::ScrapeQueueSnapshot| {
+                    let _ = queue_app_handle.emit("refreshmint://scrape-queue-changed", snapshot);
+                },
+            )))?;
+            let jobs_app_handle = app.handle().clone();
+            jobs::JobRegistry::global().set_listener(Some(std::sync::Arc::new(
+                move |job: &jobs::JobInfo| {
+                    let _ = jobs_app_handle.emit("refreshmint://job-changed", job);
+                },
+            )));
             Ok(())
         })
         .run(context)
@@ -214,7 +398,17 @@ pub fn run_with_context(
 }
 
 #[tauri::command]
-fn new_ledger(app: tauri::AppHandle, ledger: Option<String>) -> Result<(), String> {
+fn new_ledger(
+    app: tauri::AppHandle,
+    ledger: Option<String>,
+    template: Option<String>,
+) -> Result<(), String> {
+    let template = template
+        .map(|template| {
+            onboarding::LedgerTemplate::parse(&template)
+                .ok_or_else(|| format!("unknown ledger template: {template}"))
+        })
+        .transpose()?;
     let target_dir = match ledger {
         Some(path) => crate::ledger::ensure_refreshmint_extension(path.into())
             .map_err(|err| err.to_string())?,
@@ -224,7 +418,18 @@ fn new_ledger(app: tauri::AppHandle, ledger: Option<String>) -> Result<(), Strin
         }
     };
 
-    crate::ledger::new_ledger_at_dir(&target_dir).map_err(|err| err.to_string())
+    crate::ledger::new_ledger_at_dir(&target_dir, template).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn list_ledger_templates() -> Vec<onboarding::LedgerTemplateInfo> {
+    onboarding::list_ledger_templates()
+}
+
+#[tauri::command]
+fn get_onboarding_status(ledger: String) -> Result<onboarding::OnboardingStatus, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    onboarding::onboarding_status(&target_dir).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
@@ -266,6 +471,68 @@ fn validate_transaction_text(ledger: String, transaction: String) -> Result<(),
     ledger_add::validate_transaction_text(&target_dir, &transaction).map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+fn add_transactions_text(
+    ledger: String,
+    transactions: Vec<String>,
+) -> Result<ledger_open::LedgerView, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    ledger_add::add_transactions_text(&target_dir, &transactions).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn validate_transactions_text(
+    ledger: String,
+    transactions: Vec<String>,
+) -> Result<Vec<Option<String>>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    ledger_add::validate_transactions_text(&target_dir, &transactions)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_hledger_timings() -> Vec<hledger_batch::HledgerTiming> {
+    hledger_batch::hledger_timings()
+}
+
+#[tauri::command]
+fn reset_hledger_timings() {
+    hledger_batch::reset_hledger_timings();
+}
+
+#[tauri::command]
+fn get_performance_stats() -> perf::PerformanceStats {
+    perf::performance_stats()
+}
+
+#[tauri::command]
+fn reset_performance_stats() {
+    perf::reset_performance_stats();
+}
+
+#[tauri::command]
+fn get_recent_logs(
+    limit: Option<usize>,
+    min_level: Option<String>,
+) -> Result<Vec<logging::LogRecord>, String> {
+    logging::recent_logs(limit.unwrap_or(500), min_level.as_deref()).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_log_levels() -> Result<logging::LogLevels, String> {
+    logging::log_levels().map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn set_log_levels(levels: logging::LogLevels) -> Result<logging::LogLevels, String> {
+    logging::set_log_levels(levels).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_js_api_version() -> scrape::api_version::JsApiVersionInfo {
+    scrape::api_version::version_info()
+}
+
 #[tauri::command]
 fn list_scrape_extensions(ledger: String) -> Result<Vec<String>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
@@ -365,14 +632,17 @@ fn inspect_login_extraction_support(
                 Some("broken-extractor")
             },
         }),
-        (None, Some(path)) => Ok(LoginExtractionSupport {
-            supported: extension_dir.join(path).exists(),
-            reason: if extension_dir.join(path).exists() {
-                None
-            } else {
-                Some("broken-extractor")
-            },
-        }),
+        (None, Some(rules)) => {
+            let available = extract::rules_available(&extension_dir, rules);
+            Ok(LoginExtractionSupport {
+                supported: available,
+                reason: if available {
+                    None
+                } else {
+                    Some("broken-extractor")
+                },
+            })
+        }
         (Some(_), Some(_)) => Ok(LoginExtractionSupport {
             supported: false,
             reason: Some("broken-extractor"),
@@ -436,7 +706,7 @@ fn start_scrape_debug_session_for_login(
     let socket_for_thread = socket_path.clone();
     let join_handle = std::thread::spawn(move || {
         if let Err(err) = crate::scrape::debug::run_debug_session(config) {
-            eprintln!("debug session exited with error: {err}");
+            tracing::error!("debug session exited with error: {err}");
         }
     });
 
@@ -592,6 +862,7 @@ async fn run_scrape_for_login(
     login_name: String,
     source: String,
     headless: bool,
+    prompts: Option<scrape::js_api::PromptOverrides>,
 ) -> Result<(), String> {
     let login_name = require_login_name_input(login_name)?;
 
@@ -602,14 +873,59 @@ async fn run_scrape_for_login(
     require_existing_login(&target_dir, &login_name)?;
 
     // From here ledger and login are confirmed to exist; logging is safe.
+    scrape_login_and_log(
+        &app_handle,
+        target_dir,
+        login_name,
+        source,
+        headless,
+        scrape::ScrapeMode::Accounts,
+        prompts.unwrap_or_default(),
+    )
+    .await
+}
+
+#[tauri::command]
+async fn verify_scrape_prerequisites(
+    ledger: String,
+    login_name: String,
+) -> Result<scrape::preflight::ScrapePreflight, String> {
+    let login_name = require_login_name_input(login_name)?;
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    require_existing_login(&target_dir, &login_name)?;
+
+    // Running hledger and searching for Chrome block.
+    tokio::task::spawn_blocking(move || {
+        scrape::preflight::verify_scrape_prerequisites(&target_dir, &login_name)
+    })
+    .await
+    .map_err(|err| err.to_string())
+}
+
+/// Run one scrape for an existing login and append the outcome to its scrape log.
+async fn scrape_login_and_log(
+    app_handle: &tauri::AppHandle,
+    target_dir: std::path::PathBuf,
+    login_name: String,
+    source: String,
+    headless: bool,
+    mode: scrape::ScrapeMode,
+    prompt_overrides: scrape::js_api::PromptOverrides,
+) -> Result<(), String> {
     let timestamp = operations::now_timestamp();
+    let started = std::time::Instant::now();
 
+    let mut failure = None;
+    let report = scrape::ScrapeReportSlot::default();
     let result: Result<(), String> = async {
         let extension = login_config::resolve_login_extension(&target_dir, &login_name)
             .map_err(|err| err.to_string())?;
         let prompt_ui_handler = {
             let app_handle = app_handle.clone();
-            std::sync::Arc::new(move |message: String| request_prompt_answer(&app_handle, message))
+            std::sync::Arc::new(move |request: scrape::prompt_catalog::PromptRequest| {
+                request_prompt_answer(&app_handle, request)
+            })
         };
 
         let config = scrape::ScrapeConfig {
@@ -618,28 +934,52 @@ async fn run_scrape_for_login(
             ledger_dir: target_dir.clone(),
             profile_override: None,
             headless,
-            prompt_overrides: scrape::js_api::PromptOverrides::new(),
+            prompt_overrides,
             prompt_requires_override: false,
             prompt_ui_handler: Some(prompt_ui_handler),
+            mode,
+            report: Some(report.clone()),
         };
 
         tokio::task::spawn_blocking(move || {
-            scrape::run_scrape(config).map_err(|err| err.to_string())
+            scrape::run_scrape(config).map_err(|err| {
+                let assertion = err
+                    .downcast_ref::<scrape::assertions::AssertionError>()
+                    .map(|assertion| Box::new(assertion.0.clone()));
+                (err.to_string(), assertion)
+            })
         })
         .await
         .map_err(|err| err.to_string())?
+        .map_err(|(message, assertion)| {
+            failure = assertion.map(|assertion| *assertion);
+            message
+        })
     }
     .await;
 
+    let report = std::mem::take(&mut *report.lock().unwrap_or_else(|err| err.into_inner()));
     let entry = operations::ScrapeLogEntry {
         login_name: login_name.clone(),
         timestamp,
         success: result.is_ok(),
         error: result.as_ref().err().cloned(),
         source,
+        failure,
+        network: report.network,
+        prompts_asked: report.prompts_asked,
+        session_metadata: report.session_metadata,
+        suspect_documents: report.suspect_documents,
+        scrape_session_id: report.scrape_session_id,
     };
     if let Err(e) = operations::append_scrape_log_entry(&target_dir, &entry) {
-        eprintln!("warning: failed to write scrape log: {e}");
+        tracing::warn!("failed to write scrape log: {e}");
+    }
+    if let Err(e) = scrape_artifacts::write_session_report(&target_dir, &entry) {
+        tracing::warn!("failed to write scrape report: {e}");
+    }
+    if let Err(e) = scrape_stats::record_scrape_run(&target_dir, &entry, started.elapsed()) {
+        tracing::warn!("failed to update scrape stats: {e}");
     }
 
     result
@@ -652,124 +992,712 @@ async fn run_scrape(
     account: String,
 ) -> Result<(), String> {
     let login_name = require_non_empty_input("account", account)?;
-    run_scrape_for_login(app_handle, ledger, login_name, "manual".to_string(), false).await
+    run_scrape_for_login(
+        app_handle,
+        ledger,
+        login_name,
+        "manual".to_string(),
+        false,
+        None,
+    )
+    .await
 }
 
 #[tauri::command]
-fn get_scrape_log(
+async fn collect_tax_documents(
+    app_handle: tauri::AppHandle,
     ledger: String,
-    login_name: String,
-) -> Result<Vec<operations::ScrapeLogEntry>, String> {
-    let ledger_dir = std::path::PathBuf::from(&ledger);
-    crate::ledger::require_refreshmint_extension(&ledger_dir).map_err(|err| err.to_string())?;
-    let login_name = require_login_name_input(login_name)?;
-    require_existing_login(&ledger_dir, &login_name)?;
-    let mut entries =
-        operations::read_scrape_log(&ledger_dir, &login_name).map_err(|err| err.to_string())?;
-    entries.reverse(); // newest-first to match prior localStorage behaviour
-    Ok(entries)
+    year: i32,
+    headless: bool,
+) -> Result<tax_documents::TaxCollectionOutcome, String> {
+    let target_dir = std::path::PathBuf::from(&ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+
+    let attempted =
+        tax_documents::tax_document_logins(&target_dir).map_err(|err| err.to_string())?;
+    let mut errors = Vec::new();
+    for login_name in &attempted {
+        if let Err(error) = scrape_login_and_log(
+            &app_handle,
+            target_dir.clone(),
+            login_name.clone(),
+            "tax-documents".to_string(),
+            headless,
+            scrape::ScrapeMode::TaxDocuments { year },
+            scrape::js_api::PromptOverrides::new(),
+        )
+        .await
+        {
+            errors.push(tax_documents::TaxCollectionError {
+                login_name: login_name.clone(),
+                error,
+            });
+        }
+    }
+
+    let report =
+        tax_documents::list_tax_documents(&target_dir, year).map_err(|err| err.to_string())?;
+    Ok(tax_documents::TaxCollectionOutcome {
+        attempted,
+        errors,
+        report,
+    })
 }
 
 #[tauri::command]
-fn list_documents(
+fn list_tax_documents(
     ledger: String,
-    account_name: String,
-) -> Result<Vec<extract::DocumentWithInfo>, String> {
+    year: i32,
+) -> Result<tax_documents::TaxDocumentsReport, String> {
     let target_dir = std::path::PathBuf::from(ledger);
-    let account_name = require_non_empty_input("account_name", account_name)?;
-    extract::list_documents(&target_dir, &account_name).map_err(|err| err.to_string())
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    tax_documents::list_tax_documents(&target_dir, year).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn list_login_account_documents(
+fn list_paystubs(ledger: String) -> Result<Vec<paychecks::PaystubDocument>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    paychecks::list_paystubs(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_cost_basis_config(ledger: String) -> Result<cost_basis::CostBasisConfig, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    cost_basis::get_cost_basis_config(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn save_cost_basis_config(
+    ledger: String,
+    config: cost_basis::CostBasisConfig,
+) -> Result<cost_basis::CostBasisConfig, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    cost_basis::save_cost_basis_config(&target_dir, config).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn report_realized_gains(
+    ledger: String,
+    year: Option<i32>,
+) -> Result<cost_basis::RealizedGainsReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    cost_basis::realized_gains_report(&target_dir, year).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn list_invoices(ledger: String) -> Result<Vec<invoices::Invoice>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    invoices::list_invoices(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn record_invoice(
+    ledger: String,
+    invoice: invoices::RecordInvoiceInput,
+) -> Result<invoices::Invoice, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    invoices::record_invoice(&target_dir, invoice).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn report_accounts_receivable(
+    ledger: String,
+    as_of: Option<String>,
+) -> Result<invoices::ReceivablesReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let as_of = parse_digest_as_of(as_of)?;
+    invoices::report_accounts_receivable(&target_dir, as_of).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn export_entries_to_ledger(
+    ledger: String,
+    query: String,
+    target_ledger: String,
+) -> Result<inter_ledger::LedgerExport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let other_dir =
+        std::path::PathBuf::from(require_non_empty_input("targetLedger", target_ledger)?);
+    inter_ledger::export_entries_to_ledger(&target_dir, &query, &other_dir)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn export_redacted_ledger(
+    ledger: String,
+    options: redacted_export::RedactionOptions,
+) -> Result<redacted_export::RedactedExport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    require_non_empty_input("target", options.target.clone())?;
+    redacted_export::export_redacted_ledger(&target_dir, &options).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn write_paper_trail_report(
+    ledger: String,
+    options: paper_trail::PaperTrailOptions,
+) -> Result<paper_trail::PaperTrailReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    require_non_empty_input("output", options.output.clone())?;
+    paper_trail::write_paper_trail_report(&target_dir, &options).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn export_account_journal(
     ledger: String,
     login_name: String,
     label: String,
-) -> Result<Vec<extract::DocumentWithInfo>, String> {
+    format: journal_export::JournalExportFormat,
+    options: journal_export::JournalExportOptions,
+) -> Result<journal_export::JournalExport, String> {
     let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
     let login_name = require_login_name_input(login_name)?;
     let label = require_label_input(label)?;
-    extract::list_documents_for_login_account(&target_dir, &login_name, &label)
+    require_non_empty_input("output", options.output.clone())?;
+    journal_export::export_account_journal(&target_dir, &login_name, &label, format, &options)
         .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn read_login_account_document_rows(
+fn plan_opening_balance(
     ledger: String,
     login_name: String,
     label: String,
-    document_name: String,
-) -> Result<Vec<Vec<String>>, String> {
+    input: opening_balance::OpeningBalanceInput,
+) -> Result<opening_balance::OpeningBalance, String> {
     let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
     let login_name = require_login_name_input(login_name)?;
     let label = require_label_input(label)?;
-    extract::read_login_account_document_csv_rows(&target_dir, &login_name, &label, &document_name)
+    opening_balance::plan_opening_balance(&target_dir, &login_name, &label, &input)
         .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn read_login_account_document_text(
+fn record_opening_balance(
     ledger: String,
     login_name: String,
     label: String,
-    document_name: String,
-) -> Result<String, String> {
+    input: opening_balance::OpeningBalanceInput,
+) -> Result<opening_balance::OpeningBalance, String> {
     let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
     let login_name = require_login_name_input(login_name)?;
     let label = require_label_input(label)?;
-    extract::read_login_account_document_text(&target_dir, &login_name, &label, &document_name)
+    opening_balance::record_opening_balance(&target_dir, &login_name, &label, &input, "gui")
         .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn read_attachment_data_url(ledger: String, filename: String) -> Result<String, String> {
-    let ledger_dir = std::path::Path::new(&ledger);
-    extract::read_attachment_data_url(ledger_dir, &filename).map_err(|e| e.to_string())
+fn report_balance_history(
+    ledger: String,
+    account: String,
+    period: Option<String>,
+) -> Result<Vec<balances::BalanceHistoryPoint>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let account = require_non_empty_input("account", account)?;
+    let period = balances::BalancePeriod::parse(period.as_deref().unwrap_or_default())?;
+    let (login_name, label) =
+        login_config::find_login_account_for_gl_account(&target_dir, &account)
+            .ok_or_else(|| format!("no login account is mapped to GL account '{account}'"))?;
+    balances::balance_history(&target_dir, &login_name, &label, period)
+        .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn run_extraction(
-    ledger: String,
-    account_name: String,
-    document_names: Vec<String>,
-) -> Result<usize, String> {
+fn list_loans(ledger: String) -> Result<Vec<loans::Loan>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
-    let account_name = require_non_empty_input("account_name", account_name)?;
-    let extension_name = account_config::resolve_extension(&target_dir, &account_name, None)
-        .map_err(|err| err.to_string())?;
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    loans::list_loans(&target_dir).map_err(|err| err.to_string())
+}
 
-    let result =
-        extract::run_extraction(&target_dir, &account_name, &extension_name, &document_names)
-            .map_err(|err| err.to_string())?;
+#[tauri::command]
+fn upsert_loan(ledger: String, loan: loans::UpsertLoanInput) -> Result<loans::Loan, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    loans::upsert_loan(&target_dir, loan).map_err(|err| err.to_string())
+}
 
-    // Run dedup on extracted transactions
-    let existing_entries =
-        account_journal::read_journal(&target_dir, &account_name).map_err(|err| err.to_string())?;
+#[tauri::command]
+fn delete_loan(ledger: String, id: String) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let id = require_non_empty_input("id", id)?;
+    loans::delete_loan(&target_dir, &id).map_err(|err| err.to_string())
+}
 
-    let config = dedup::DedupConfig::default();
-    let mut all_updated = existing_entries;
-    let mut new_count = 0;
+#[tauri::command]
+fn get_loan_schedule(ledger: String, id: String) -> Result<Vec<loans::AmortizationRow>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let id = require_non_empty_input("id", id)?;
+    let loan = loans::get_loan(&target_dir, &id).map_err(|err| err.to_string())?;
+    loans::amortization_schedule(&loan).map_err(|err| err.to_string())
+}
 
-    // Process each document's transactions through dedup
-    for doc_name in &result.document_names {
-        let doc_txns: Vec<_> = result
-            .proposed_transactions
-            .iter()
-            .filter(|t| {
-                t.evidence_refs()
-                    .iter()
-                    .any(|e| evidence_ref_matches_document(e, doc_name))
-            })
-            .cloned()
-            .collect();
+#[tauri::command]
+fn report_loan_balance(
+    ledger: String,
+    id: String,
+    as_of: Option<String>,
+) -> Result<loans::LoanBalanceReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let id = require_non_empty_input("id", id)?;
+    let as_of = match as_of.as_deref().map(str::trim).filter(|v| !v.is_empty()) {
+        Some(value) => chrono::NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map_err(|err| format!("as_of must be YYYY-MM-DD: {err}"))?,
+        None => chrono::Local::now().date_naive(),
+    };
+    loans::loan_balance_report(&target_dir, &id, as_of).map_err(|err| err.to_string())
+}
 
-        if doc_txns.is_empty() {
-            continue;
-        }
+#[tauri::command]
+fn get_scrape_queue() -> Result<scrape::queue::ScrapeQueueSnapshot, String> {
+    scrape::queue::ScrapeQueue::global().snapshot()
+}
 
-        let actions = dedup::run_dedup(&all_updated, &doc_txns, doc_name, &config);
-        new_count += actions
-            .iter()
+#[tauri::command]
+fn set_scrape_concurrency_limit(limit: usize) -> Result<(), String> {
+    scrape::queue::ScrapeQueue::global().set_max_concurrent(limit)
+}
+
+#[tauri::command]
+fn list_jobs() -> Vec<jobs::JobInfo> {
+    jobs::JobRegistry::global().list()
+}
+
+#[tauri::command]
+fn cancel_job(id: u64) -> Result<(), String> {
+    jobs::JobRegistry::global().cancel(id)
+}
+
+#[tauri::command]
+fn get_commit_policy(ledger: String) -> Result<commit_policy::CommitPolicy, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_policy::get_commit_policy(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn save_commit_policy(
+    ledger: String,
+    policy: commit_policy::CommitPolicy,
+) -> Result<commit_policy::CommitPolicy, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_policy::save_commit_policy(&target_dir, policy).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_staging_config(ledger: String) -> Result<staging::StagingConfig, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    staging::get_staging_config(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn save_staging_config(
+    ledger: String,
+    config: staging::StagingConfig,
+) -> Result<staging::StagingConfig, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    staging::save_staging_config(&target_dir, config).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_gl_layout(ledger: String) -> Result<gl_layout::GlLayout, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    gl_layout::get_gl_layout(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn convert_gl_layout(
+    ledger: String,
+    layout: gl_layout::GlLayout,
+) -> Result<gl_layout::GlLayoutConversion, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    gl_layout::convert_gl_layout(&target_dir, layout, "gui").map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_uncommitted_changes(ledger: String) -> Result<commit_policy::UncommittedChanges, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_policy::get_uncommitted_changes(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn commit_pending_changes(
+    ledger: String,
+    message: String,
+) -> Result<commit_policy::UncommittedChanges, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_policy::commit_pending_changes(&target_dir, &message).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_commit_signing(ledger: String) -> Result<Option<commit_signing::CommitSigning>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_signing::get_commit_signing(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn save_commit_signing(
+    ledger: String,
+    signing: Option<commit_signing::CommitSigning>,
+) -> Result<Option<commit_signing::CommitSigning>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_signing::save_commit_signing(&target_dir, signing).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn verify_ledger_history(
+    ledger: String,
+) -> Result<commit_signing::LedgerHistoryVerification, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    commit_signing::verify_ledger_history(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_scrape_log(
+    ledger: String,
+    login_name: String,
+) -> Result<Vec<operations::ScrapeLogEntry>, String> {
+    let ledger_dir = std::path::PathBuf::from(&ledger);
+    crate::ledger::require_refreshmint_extension(&ledger_dir).map_err(|err| err.to_string())?;
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&ledger_dir, &login_name)?;
+    let mut entries =
+        operations::read_scrape_log(&ledger_dir, &login_name).map_err(|err| err.to_string())?;
+    entries.reverse(); // newest-first to match prior localStorage behaviour
+    Ok(entries)
+}
+
+#[tauri::command]
+fn list_scrape_artifacts(
+    ledger: String,
+    login_name: String,
+    session_id: Option<String>,
+) -> Result<Vec<scrape_artifacts::ScrapeArtifact>, String> {
+    let ledger_dir = std::path::PathBuf::from(&ledger);
+    crate::ledger::require_refreshmint_extension(&ledger_dir).map_err(|err| err.to_string())?;
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&ledger_dir, &login_name)?;
+    scrape_artifacts::list_scrape_artifacts(&ledger_dir, &login_name, session_id.as_deref())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn read_scrape_artifact(
+    ledger: String,
+    path: String,
+) -> Result<scrape_artifacts::ScrapeArtifactContent, String> {
+    let ledger_dir = std::path::PathBuf::from(&ledger);
+    crate::ledger::require_refreshmint_extension(&ledger_dir).map_err(|err| err.to_string())?;
+    let path = require_non_empty_input("path", path)?;
+    scrape_artifacts::read_scrape_artifact(&ledger_dir, &path)
+}
+
+#[tauri::command]
+fn report_scrape_reliability(
+    ledger: String,
+    period: Option<String>,
+    login_name: Option<String>,
+) -> Result<Vec<scrape_stats::LoginReliability>, String> {
+    let ledger_dir = std::path::PathBuf::from(&ledger);
+    crate::ledger::require_refreshmint_extension(&ledger_dir).map_err(|err| err.to_string())?;
+    let period = balances::BalancePeriod::parse(period.as_deref().unwrap_or_default())?;
+    let login_names = match login_name {
+        Some(login_name) => {
+            let login_name = require_login_name_input(login_name)?;
+            require_existing_login(&ledger_dir, &login_name)?;
+            vec![login_name]
+        }
+        None => login_config::list_logins(&ledger_dir).map_err(|err| err.to_string())?,
+    };
+    scrape_stats::reliability_report(&ledger_dir, &login_names, period)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn report_coverage_gaps(
+    ledger: String,
+    login_name: Option<String>,
+) -> Result<Vec<coverage::AccountCoverage>, String> {
+    let ledger_dir = std::path::PathBuf::from(&ledger);
+    crate::ledger::require_refreshmint_extension(&ledger_dir).map_err(|err| err.to_string())?;
+    let login_name = match login_name {
+        Some(login_name) => {
+            let login_name = require_login_name_input(login_name)?;
+            require_existing_login(&ledger_dir, &login_name)?;
+            Some(login_name)
+        }
+        None => None,
+    };
+    coverage::report_coverage_gaps(&ledger_dir, login_name.as_deref())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn list_documents(
+    ledger: String,
+    account_name: String,
+    document_type: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<Vec<extract::DocumentWithInfo>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let account_name = require_non_empty_input("account_name", account_name)?;
+    let documents =
+        extract::list_documents(&target_dir, &account_name).map_err(|err| err.to_string())?;
+    filter_documents_input(documents, document_type, date_from, date_to)
+}
+
+#[tauri::command]
+fn list_login_account_documents(
+    ledger: String,
+    login_name: String,
+    label: String,
+    document_type: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<Vec<extract::DocumentWithInfo>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let documents = extract::list_documents_for_login_account(&target_dir, &login_name, &label)
+        .map_err(|err| err.to_string())?;
+    filter_documents_input(documents, document_type, date_from, date_to)
+}
+
+fn filter_documents_input(
+    documents: Vec<extract::DocumentWithInfo>,
+    document_type: Option<String>,
+    date_from: Option<String>,
+    date_to: Option<String>,
+) -> Result<Vec<extract::DocumentWithInfo>, String> {
+    let documents = match document_type.filter(|value| !value.trim().is_empty()) {
+        Some(value) => {
+            let document_type = scrape::DocumentType::parse(&value)?;
+            extract::filter_documents_by_type(documents, document_type)
+        }
+        None => documents,
+    };
+    let parse_date = |name: &str, value: Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                    .map_err(|err| format!("{name} must be YYYY-MM-DD: {err}"))
+            })
+            .transpose()
+    };
+    let date_from = parse_date("date_from", date_from)?;
+    let date_to = parse_date("date_to", date_to)?;
+    if date_from.is_none() && date_to.is_none() {
+        return Ok(documents);
+    }
+    Ok(extract::filter_documents_by_date_range(
+        documents, date_from, date_to,
+    ))
+}
+
+#[tauri::command]
+fn prune_login_account_documents(
+    ledger: String,
+    login_name: String,
+    label: String,
+    dry_run: bool,
+) -> Result<Vec<String>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    let label = require_label_input(label)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "prune-documents",
+    )
+    .map_err(|err| err.to_string())?;
+
+    let today = chrono::Local::now().date_naive();
+    extract::prune_expired_login_account_documents(&target_dir, &login_name, &label, today, dry_run)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn relabel_documents(
+    ledger: String,
+    login_name: String,
+    dry_run: bool,
+) -> Result<document_labels::RelabelReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "relabel-documents",
+    )
+    .map_err(|err| err.to_string())?;
+
+    document_labels::relabel_documents(&target_dir, &login_name, dry_run)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn read_login_account_document_rows(
+    ledger: String,
+    login_name: String,
+    label: String,
+    document_name: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    columns: Option<Vec<usize>>,
+) -> Result<extract::DocumentRowsPage, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let request = extract::DocumentRowsRequest {
+        offset: offset.unwrap_or(0),
+        limit: Some(limit.unwrap_or(extract::DEFAULT_DOCUMENT_ROWS_LIMIT)),
+        columns,
+    };
+    extract::read_login_account_document_csv_rows(
+        &target_dir,
+        &login_name,
+        &label,
+        &document_name,
+        &request,
+    )
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn read_login_account_document_text(
+    ledger: String,
+    login_name: String,
+    label: String,
+    document_name: String,
+) -> Result<String, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    extract::read_login_account_document_text(&target_dir, &login_name, &label, &document_name)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn read_attachment_data_url(ledger: String, filename: String) -> Result<String, String> {
+    let ledger_dir = std::path::Path::new(&ledger);
+    extract::read_attachment_data_url(ledger_dir, &filename).map_err(|e| e.to_string())
+}
+
+/// Start extracting `document_names` into the account journal as a job whose
+/// result is the number of new entries.
+#[tauri::command]
+fn run_extraction(
+    ledger: String,
+    account_name: String,
+    document_names: Vec<String>,
+) -> Result<u64, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let account_name = require_non_empty_input("account_name", account_name)?;
+    jobs::JobRegistry::global().spawn(
+        jobs::JobKind::Extraction,
+        account_name.clone(),
+        move |job| extract_account(&target_dir, &account_name, &document_names, job),
+    )
+}
+
+fn extract_account(
+    target_dir: &std::path::Path,
+    account_name: &str,
+    document_names: &[String],
+    job: &jobs::JobHandle<'_>,
+) -> Result<usize, String> {
+    let extension_name = account_config::resolve_extension(target_dir, account_name, None)
+        .map_err(|err| err.to_string())?;
+
+    let mut result = extract::ExtractionResult::default();
+    for (index, document_name) in document_names.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(
+            index,
+            document_names.len(),
+            format!("Extracting {document_name}"),
+        );
+        result.append(
+            extract::run_extraction(
+                target_dir,
+                account_name,
+                &extension_name,
+                std::slice::from_ref(document_name),
+            )
+            .map_err(|err| err.to_string())?,
+        );
+    }
+    job.check_cancelled()?;
+    job.progress(
+        document_names.len(),
+        document_names.len(),
+        "Removing duplicates",
+    );
+
+    // Run dedup on extracted transactions
+    let existing_entries =
+        account_journal::read_journal(target_dir, account_name).map_err(|err| err.to_string())?;
+
+    let config = dedup::DedupConfig::default();
+    let mut all_updated = existing_entries;
+    let mut new_count = 0;
+
+    // Process each document's transactions through dedup
+    for doc_name in &result.document_names {
+        let doc_txns: Vec<_> = result
+            .proposed_transactions
+            .iter()
+            .filter(|t| {
+                t.evidence_refs()
+                    .iter()
+                    .any(|e| evidence_ref_matches_document(e, doc_name))
+            })
+            .cloned()
+            .collect();
+
+        if doc_txns.is_empty() {
+            continue;
+        }
+
+        let actions = dedup::run_dedup(&all_updated, &doc_txns, doc_name, &config);
+        new_count += actions
+            .iter()
             .filter(|a| matches!(a.result, dedup::DedupResult::New))
             .count();
 
@@ -779,11 +1707,11 @@ fn run_extraction(
             .and_then(|e| e.postings.first())
             .map(|p| p.account.clone())
             .unwrap_or_else(|| format!("Assets:{account_name}"));
-        let staging_account = crate::staging::canonical_staging_account(&account_name);
+        let staging_account = crate::staging::ledger_counterpart_account(target_dir, account_name);
 
         all_updated = dedup::apply_dedup_actions(
-            &target_dir,
-            &account_name,
+            target_dir,
+            account_name,
             all_updated,
             &actions,
             &default_account,
@@ -794,40 +1722,88 @@ fn run_extraction(
     }
 
     // Write updated journal
-    account_journal::write_journal(&target_dir, &account_name, &all_updated)
-        .map_err(|err| err.to_string())?;
+    posted_changes::write_journal_keeping_posted(
+        &account_journal::account_journal_path(target_dir, account_name),
+        &all_updated,
+    )
+    .map_err(|err| err.to_string())?;
 
     Ok(new_count)
 }
 
+/// Start extracting `document_names` into the login account's journal as a
+/// job whose result is the number of new entries. Documents marked suspect
+/// at download are skipped unless `include_suspect` is set.
 #[tauri::command]
 fn run_login_account_extraction(
+    app_handle: tauri::AppHandle,
     ledger: String,
     login_name: String,
     label: String,
     document_names: Vec<String>,
-) -> Result<usize, String> {
+    include_suspect: Option<bool>,
+) -> Result<u64, String> {
     let target_dir = std::path::PathBuf::from(ledger);
     let login_name = require_login_name_input(login_name)?;
     let label = require_label_input(label)?;
+    jobs::JobRegistry::global().spawn(
+        jobs::JobKind::Extraction,
+        format!("{login_name}/{label}"),
+        move |job| {
+            extract_login_account(
+                &app_handle,
+                &target_dir,
+                &login_name,
+                &label,
+                &document_names,
+                include_suspect.unwrap_or(false),
+                job,
+            )
+        },
+    )
+}
 
-    let extension_name = login_config::resolve_login_extension(&target_dir, &login_name)
+fn extract_login_account(
+    app_handle: &tauri::AppHandle,
+    target_dir: &std::path::Path,
+    login_name: &str,
+    label: &str,
+    document_names: &[String],
+    include_suspect: bool,
+    job: &jobs::JobHandle<'_>,
+) -> Result<usize, String> {
+    let documents_dir = account_journal::login_account_documents_dir(target_dir, login_name, label);
+    let document_names = if include_suspect {
+        document_names.to_vec()
+    } else {
+        let (kept, suspect) = extract::partition_suspect_documents(&documents_dir, document_names);
+        if !suspect.is_empty() {
+            tracing::warn!(
+                "skipping {} suspect document(s) of {login_name}/{label}: {}",
+                suspect.len(),
+                suspect.join(", ")
+            );
+        }
+        kept
+    };
+    let extension_name = login_config::resolve_login_extension(target_dir, login_name)
         .map_err(|err| err.to_string())?;
     // gl_account is optional for extraction: extensions that supply explicit
     // tpostings (e.g. the target extractor) do not need a pre-configured GL
     // account. The gl_account is still required by post_login_account_entry /
     // post_login_account_transfer at posting time.
-    let gl_account: String = {
-        let config = login_config::read_login_config(&target_dir, &login_name);
+    let gl_account_for = |label: &str| -> String {
+        let config = login_config::read_login_config(target_dir, login_name);
         config
             .accounts
-            .get(&*label)
+            .get(label)
             .and_then(|a| a.gl_account.as_deref())
             .map(str::trim)
             .filter(|s| !s.is_empty())
             .map(str::to_string)
             .unwrap_or_default()
     };
+    let gl_account = gl_account_for(label);
 
     let doc_count = document_names.len();
 
@@ -835,17 +1811,28 @@ fn run_login_account_extraction(
     // always flush the extract log (including console logs) even on failure.
     let mut console_logs: Vec<operations::ExtractConsoleLogLine> = Vec::new();
     let mut new_count = 0usize;
+    let mut raised_alerts: Vec<alerts::Alert> = Vec::new();
+    let mut unknown_account_ids: Vec<account_routing::UnknownAccountId> = Vec::new();
 
     let outcome: Result<(), String> = (|| {
-        let result = extract::run_extraction_for_login_account(
-            &target_dir,
-            &login_name,
-            &label,
-            &gl_account,
-            &extension_name,
-            &document_names,
-        )
-        .map_err(|err| err.to_string())?;
+        let mut result = extract::ExtractionResult::default();
+        for (index, document_name) in document_names.iter().enumerate() {
+            job.check_cancelled()?;
+            job.progress(index, doc_count, format!("Extracting {document_name}"));
+            result.append(
+                extract::run_extraction_for_login_account(
+                    target_dir,
+                    login_name,
+                    label,
+                    &gl_account,
+                    &extension_name,
+                    std::slice::from_ref(document_name),
+                )
+                .map_err(|err| err.to_string())?,
+            );
+        }
+        job.check_cancelled()?;
+        job.progress(doc_count, doc_count, "Removing duplicates");
 
         console_logs = result
             .console_logs
@@ -857,84 +1844,131 @@ fn run_login_account_extraction(
             })
             .collect();
 
-        let journal_path =
-            account_journal::login_account_journal_path(&target_dir, &login_name, &label);
-        let existing_entries =
-            account_journal::read_journal_at_path(&journal_path).map_err(|err| err.to_string())?;
+        // A document covering several accounts can route transactions to
+        // other labels of the login; each label's journal is merged in turn.
+        let routing = account_routing::route_extraction(
+            target_dir,
+            login_name,
+            label,
+            result.proposed_transactions,
+            &result.document_names,
+        )
+        .map_err(|err| err.to_string())?;
+        unknown_account_ids = routing.unknown_account_ids;
+        for route in &routing.routes {
+            let label = route.label.as_str();
+            let gl_account = gl_account_for(label);
+            let journal_path =
+                account_journal::login_account_journal_path(target_dir, login_name, label);
+            let existing_entries = account_journal::read_journal_at_path(&journal_path)
+                .map_err(|err| err.to_string())?;
+
+            let config = dedup::DedupConfig::for_account_type(login_config::login_account_type(
+                target_dir, login_name, label,
+            ));
+            let history = existing_entries.clone();
+            let mut all_updated = existing_entries;
 
-        let config = dedup::DedupConfig::default();
-        let mut all_updated = existing_entries;
+            for doc_name in &route.document_names {
+                let doc_txns: Vec<_> = route
+                    .proposed_transactions
+                    .iter()
+                    .filter(|t| {
+                        t.evidence_refs()
+                            .iter()
+                            .any(|e| evidence_ref_matches_document(e, doc_name))
+                    })
+                    .cloned()
+                    .collect();
+
+                if doc_txns.is_empty() {
+                    continue;
+                }
 
-        for doc_name in &result.document_names {
-            let doc_txns: Vec<_> = result
-                .proposed_transactions
+                let actions = dedup::run_dedup(&all_updated, &doc_txns, doc_name, &config);
+                new_count += actions
+                    .iter()
+                    .filter(|a| matches!(a.result, dedup::DedupResult::New))
+                    .count();
+
+                // When gl_account is empty (no glAccount configured), default_account
+                // falls back to "" on the very first extraction run (empty journal).
+                // This is safe only if every proposed transaction supplies explicit
+                // tpostings — if any transaction has tpostings: None, we fail loudly
+                // rather than silently writing blank-account journal entries.
+                let default_account = all_updated
+                    .first()
+                    .and_then(|e| e.postings.first())
+                    .map(|p| p.account.clone())
+                    .unwrap_or_else(|| gl_account.clone());
+                if default_account.is_empty() {
+                    let has_implicit = doc_txns.iter().any(|t| t.tpostings.is_none());
+                    if has_implicit {
+                        return Err(format!(
+                            "login '{login_name}' label '{label}': extractor produced a \
+                             transaction without explicit tpostings but no glAccount is \
+                             configured; set a GL account or fix the extractor"
+                        ));
+                    }
+                }
+                let staging_account =
+                    crate::staging::login_account_counterpart(target_dir, login_name, label);
+
+                all_updated = dedup::apply_dedup_actions_for_login_account(
+                    target_dir,
+                    (login_name, label),
+                    all_updated,
+                    &actions,
+                    &default_account,
+                    &staging_account,
+                    Some(&format!("{extension_name}:latest")),
+                )
+                .map_err(|err| err.to_string())?;
+            }
+
+            posted_changes::write_journal_keeping_posted(&journal_path, &all_updated)
+                .map_err(|err| err.to_string())?;
+
+            let known_ids: std::collections::HashSet<&str> =
+                history.iter().map(|entry| entry.id.as_str()).collect();
+            let new_entries: Vec<_> = all_updated
                 .iter()
-                .filter(|t| {
-                    t.evidence_refs()
-                        .iter()
-                        .any(|e| evidence_ref_matches_document(e, doc_name))
-                })
+                .filter(|entry| !known_ids.contains(entry.id.as_str()))
                 .cloned()
                 .collect();
-
-            if doc_txns.is_empty() {
-                continue;
+            match alerts::evaluate_new_entries(
+                target_dir,
+                login_name,
+                label,
+                &history,
+                &new_entries,
+            ) {
+                Ok(alerts) => raised_alerts.extend(alerts),
+                Err(err) => tracing::warn!("alert evaluation failed: {err}"),
             }
-
-            let actions = dedup::run_dedup(&all_updated, &doc_txns, doc_name, &config);
-            new_count += actions
-                .iter()
-                .filter(|a| matches!(a.result, dedup::DedupResult::New))
-                .count();
-
-            // When gl_account is empty (no glAccount configured), default_account
-            // falls back to "" on the very first extraction run (empty journal).
-            // This is safe only if every proposed transaction supplies explicit
-            // tpostings — if any transaction has tpostings: None, we fail loudly
-            // rather than silently writing blank-account journal entries.
-            let default_account = all_updated
-                .first()
-                .and_then(|e| e.postings.first())
-                .map(|p| p.account.clone())
-                .unwrap_or_else(|| gl_account.clone());
-            if default_account.is_empty() {
-                let has_implicit = doc_txns.iter().any(|t| t.tpostings.is_none());
-                if has_implicit {
-                    return Err(format!(
-                        "login '{login_name}' label '{label}': extractor produced a \
-                         transaction without explicit tpostings but no glAccount is \
-                         configured; set a GL account or fix the extractor"
-                    ));
-                }
+            match alerts::evaluate_coverage_gaps(target_dir, login_name, label) {
+                Ok(alerts) => raised_alerts.extend(alerts),
+                Err(err) => tracing::warn!("coverage gap evaluation failed: {err}"),
             }
-            let staging_account =
-                crate::staging::canonical_staging_account(&format!("{login_name}:{label}"));
-
-            all_updated = dedup::apply_dedup_actions_for_login_account(
-                &target_dir,
-                (&login_name, &label),
-                all_updated,
-                &actions,
-                &default_account,
-                &staging_account,
-                Some(&format!("{extension_name}:latest")),
-            )
-            .map_err(|err| err.to_string())?;
         }
 
-        account_journal::write_journal_at_path(&journal_path, &all_updated)
-            .map_err(|err| err.to_string())?;
-
         Ok(())
     })();
 
+    if !raised_alerts.is_empty() {
+        let _ = app_handle.emit("refreshmint://alerts-raised", &raised_alerts);
+    }
+    if !unknown_account_ids.is_empty() {
+        let _ = app_handle.emit("refreshmint://unknown-account-ids", &unknown_account_ids);
+    }
+
     // Write extract log regardless of success/failure so console logs and errors
     // are always persisted for later review.
     let _ = operations::append_extract_log_entry(
-        &target_dir,
+        target_dir,
         &operations::ExtractLogEntry {
-            login_name: login_name.clone(),
-            label: label.clone(),
+            login_name: login_name.to_string(),
+            label: label.to_string(),
             timestamp: operations::now_timestamp(),
             success: outcome.is_ok(),
             error: outcome.as_ref().err().cloned(),
@@ -1072,6 +2106,8 @@ fn create_login(ledger: String, login_name: String, extension: String) -> Result
     let config = login_config::LoginConfig {
         extension: ext_value,
         accounts: std::collections::BTreeMap::new(),
+        institution: None,
+        label_rules: Vec::new(),
     };
     login_config::write_login_config(&target_dir, &login_name, &config)
         .map_err(|err| err.to_string())
@@ -1147,14 +2183,210 @@ fn set_login_account(
         login_config::check_gl_account_uniqueness(&target_dir, &login_name, &label, gl)?;
     }
 
+    let mut config = login_config::read_login_config(&target_dir, &login_name);
+    config.accounts.entry(label).or_default().gl_account = gl_account;
+    login_config::write_login_config(&target_dir, &login_name, &config)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn set_login_account_type(
+    ledger: String,
+    login_name: String,
+    label: String,
+    account_type: Option<login_config::AccountType>,
+) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    let label = require_label_input(label)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "set-login-account-type",
+    )
+    .map_err(|err| err.to_string())?;
+
+    let mut config = login_config::read_login_config(&target_dir, &login_name);
+    config.accounts.entry(label).or_default().account_type = account_type;
+    login_config::write_login_config(&target_dir, &login_name, &config)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn set_login_account_counterpart(
+    ledger: String,
+    login_name: String,
+    label: String,
+    counterpart_account: Option<String>,
+) -> Result<String, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    let label = require_label_input(label)?;
+    let counterpart_account = staging::normalize_account_input(counterpart_account);
+    if let Some(account) = &counterpart_account {
+        staging::validate_counterpart_account(&target_dir, account)
+            .map_err(|err| err.to_string())?;
+    }
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "set-login-account-counterpart",
+    )
+    .map_err(|err| err.to_string())?;
+
     let mut config = login_config::read_login_config(&target_dir, &login_name);
     config
         .accounts
-        .insert(label, login_config::LoginAccountConfig { gl_account });
+        .entry(label.clone())
+        .or_default()
+        .counterpart_account = counterpart_account;
     login_config::write_login_config(&target_dir, &login_name, &config)
+        .map_err(|err| err.to_string())?;
+    Ok(staging::login_account_counterpart(
+        &target_dir,
+        &login_name,
+        &label,
+    ))
+}
+
+#[tauri::command]
+fn set_login_account_ids(
+    ledger: String,
+    login_name: String,
+    label: String,
+    account_ids: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    let label = require_label_input(label)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "set-login-account-ids",
+    )
+    .map_err(|err| err.to_string())?;
+
+    account_routing::set_login_account_ids(&target_dir, &login_name, &label, account_ids)
+}
+
+#[tauri::command]
+fn list_unknown_account_ids(
+    ledger: String,
+    login_name: String,
+) -> Result<Vec<account_routing::UnknownAccountId>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    account_routing::list_unknown_account_ids(&target_dir, &login_name)
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+fn assign_account_id(
+    ledger: String,
+    login_name: String,
+    account_id: String,
+    label: String,
+) -> Result<Vec<String>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    let label = require_label_input(label)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "assign-account-id",
+    )
+    .map_err(|err| err.to_string())?;
+
+    account_routing::assign_account_id(&target_dir, &login_name, &account_id, &label)
+}
+
+#[tauri::command]
+fn get_login_institution(
+    ledger: String,
+    login_name: String,
+) -> Result<Option<login_config::InstitutionInfo>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    Ok(login_config::read_login_config(&target_dir, &login_name).institution)
+}
+
+#[tauri::command]
+fn set_login_institution(
+    ledger: String,
+    login_name: String,
+    institution: Option<login_config::InstitutionInfo>,
+) -> Result<Option<login_config::InstitutionInfo>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "set-login-institution",
+    )
+    .map_err(|err| err.to_string())?;
+
+    login_config::set_login_institution(&target_dir, &login_name, institution)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn set_login_label_rules(
+    ledger: String,
+    login_name: String,
+    rules: Vec<document_labels::LabelRule>,
+) -> Result<Vec<document_labels::LabelRule>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "set-login-label-rules",
+    )
+    .map_err(|err| err.to_string())?;
+
+    document_labels::set_login_label_rules(&target_dir, &login_name, rules)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn suggest_login_account_gl_account(
+    ledger: String,
+    login_name: String,
+    label: String,
+) -> Result<String, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let config = login_config::read_login_config(&target_dir, &login_name);
+    let account_type = config
+        .accounts
+        .get(&label)
+        .and_then(|acct_config| acct_config.account_type);
+    Ok(config
+        .institution
+        .unwrap_or_default()
+        .suggested_gl_account(account_type, &label))
+}
+
 #[tauri::command]
 fn remove_login_account(ledger: String, login_name: String, label: String) -> Result<(), String> {
     let target_dir = std::path::PathBuf::from(ledger);
@@ -1368,8 +2600,10 @@ fn migrate_login_secrets(login_name: String) -> Result<Vec<String>, String> {
     }
 
     // Group by domain: collect all (domain, name, value) triples
-    let mut by_domain: std::collections::BTreeMap<String, Vec<(String, String)>> =
-        std::collections::BTreeMap::new();
+    let mut by_domain: std::collections::BTreeMap<
+        String,
+        Vec<(String, crate::secret::SecretString)>,
+    > = std::collections::BTreeMap::new();
     for (domain, name) in &legacy {
         let value = store
             .get_legacy_value(domain, name)
@@ -1380,99 +2614,588 @@ fn migrate_login_secrets(login_name: String) -> Result<Vec<String>, String> {
             .push((name.clone(), value));
     }
 
-    let mut migrated = Vec::new();
-    for (domain, name_values) in &by_domain {
-        // Heuristic: name containing "username"/"user"/"login" → username role, else password
-        let username = name_values
-            .iter()
-            .find(|(n, _)| {
-                n.to_ascii_lowercase().contains("username")
-                    || n.to_ascii_lowercase().contains("_user")
-            })
-            .map(|(_, v)| v.clone())
-            .unwrap_or_default();
-        let password = name_values
-            .iter()
-            .find(|(n, _)| {
-                n.to_ascii_lowercase().contains("password")
-                    || n.to_ascii_lowercase().contains("_pass")
-            })
-            .or_else(|| name_values.last())
-            .map(|(_, v)| v.clone())
-            .unwrap_or_default();
+    let mut migrated = Vec::new();
+    for (domain, name_values) in &by_domain {
+        // Heuristic: name containing "username"/"user"/"login" → username role, else password
+        let username = name_values
+            .iter()
+            .find(|(n, _)| {
+                n.to_ascii_lowercase().contains("username")
+                    || n.to_ascii_lowercase().contains("_user")
+            })
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default();
+        let password = name_values
+            .iter()
+            .find(|(n, _)| {
+                n.to_ascii_lowercase().contains("password")
+                    || n.to_ascii_lowercase().contains("_pass")
+            })
+            .or_else(|| name_values.last())
+            .map(|(_, v)| v.clone())
+            .unwrap_or_default();
+
+        store
+            .set_credentials(domain, username.expose_secret(), password.expose_secret())
+            .map_err(|err| format!("failed to migrate domain '{domain}': {err}"))?;
+
+        // Clean up legacy entries for this domain
+        for (name, _) in name_values {
+            let _ = store.delete_legacy_entry(domain, name);
+        }
+        migrated.push(domain.clone());
+    }
+
+    // Clean up legacy index if all entries were migrated
+    if migrated.len() == by_domain.len() {
+        let _ = store.delete_legacy_index();
+    }
+
+    Ok(migrated)
+}
+
+#[tauri::command]
+fn clear_login_profile(ledger: String, login_name: String) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+
+    let lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "clear-login-profile",
+    )
+    .map_err(|err| err.to_string())?;
+    scrape::profile::clear_login_profile(&target_dir, &login_name, &lock)
+        .map_err(|err| err.to_string())
+}
+
+/// Prompts the login's extension declares plus what its scrape log says about
+/// the saved session, so the UI can collect answers before starting a scrape.
+#[tauri::command]
+fn get_required_prompts(
+    ledger: String,
+    login_name: String,
+) -> Result<scrape::prompt_catalog::RequiredPrompts, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    let extension = login_config::resolve_login_extension(&target_dir, &login_name)
+        .map_err(|err| err.to_string())?;
+    let extension_dir = account_config::resolve_extension_dir(&target_dir, &extension);
+    let manifest = scrape::load_manifest(&extension_dir).map_err(|err| err.to_string())?;
+    let log =
+        operations::read_scrape_log(&target_dir, &login_name).map_err(|err| err.to_string())?;
+    Ok(scrape::prompt_catalog::required_prompts(
+        &manifest.prompts,
+        &log,
+    ))
+}
+
+#[tauri::command]
+fn get_login_storage(
+    ledger: String,
+    login_name: String,
+) -> Result<std::collections::BTreeMap<String, extension_storage::StorageEntries>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+    extension_storage::list_storage(&target_dir, &login_name).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn clear_login_storage(
+    ledger: String,
+    login_name: String,
+    extension: Option<String>,
+    key: Option<String>,
+) -> Result<usize, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    require_existing_login(&target_dir, &login_name)?;
+
+    let _lock = login_config::acquire_login_lock_with_metadata(
+        &target_dir,
+        &login_name,
+        "gui",
+        "clear-login-storage",
+    )
+    .map_err(|err| err.to_string())?;
+    extension_storage::clear_storage(
+        &target_dir,
+        &login_name,
+        extension.as_deref(),
+        key.as_deref(),
+    )
+    .map_err(|err| err.to_string())
+}
+
+/// Start migrating legacy `accounts/` into logins as a job whose result is
+/// the `MigrationOutcome`.
+#[tauri::command]
+fn migrate_ledger(ledger: String, dry_run: bool) -> Result<u64, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    jobs::JobRegistry::global().spawn(jobs::JobKind::Migration, "ledger".to_string(), move |job| {
+        migration::migrate_ledger(&target_dir, dry_run, job).map_err(|err| err.to_string())
+    })
+}
+
+/// Start importing a GnuCash book or Beancount file into the ledger as a job
+/// whose result is the `ImportReport`.
+#[tauri::command]
+fn import_book(
+    ledger: String,
+    format: book_import::BookFormat,
+    path: String,
+    start_date: Option<String>,
+    dry_run: bool,
+) -> Result<u64, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let path = std::path::PathBuf::from(require_non_empty_input("path", path)?);
+    let title = path.file_name().map_or_else(
+        || path.display().to_string(),
+        |name| name.to_string_lossy().into_owned(),
+    );
+    let options = book_import::BookImportOptions {
+        start_date,
+        dry_run,
+    };
+    jobs::JobRegistry::global().spawn(jobs::JobKind::BookImport, title, move |job| {
+        book_import::import_book(&target_dir, format, &path, &options, job)
+            .map_err(|err| err.to_string())
+    })
+}
+
+#[tauri::command]
+fn migrate_entry_ids(
+    ledger: String,
+    dry_run: Option<bool>,
+) -> Result<migration::EntryIdMigrationOutcome, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    migration::migrate_entry_ids(&target_dir, dry_run.unwrap_or(false), "gui")
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn query_transactions(
+    ledger: String,
+    query: String,
+) -> Result<Vec<ledger_open::TransactionRow>, String> {
+    let dir = std::path::PathBuf::from(&ledger);
+    let journal_path = dir.join("general.journal");
+    let tokens = ledger_open::tokenize_query(&query);
+    ledger_open::run_hledger_print_with_query(&journal_path, &tokens)
+        .and_then(|txns| ledger_open::build_transaction_rows(&dir, &txns))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn query_transactions_page(
+    ledger: String,
+    query: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    row_cap: Option<usize>,
+) -> Result<ledger_open::TransactionPage, String> {
+    let dir = std::path::PathBuf::from(&ledger);
+    let limit = limit.unwrap_or(ledger_open::DEFAULT_QUERY_PAGE_SIZE);
+    let row_cap = row_cap.unwrap_or(ledger_open::DEFAULT_QUERY_ROW_CAP);
+    if limit == 0 || row_cap == 0 {
+        return Err("limit and rowCap must be positive".to_string());
+    }
+    tokio::task::spawn_blocking(move || {
+        ledger_open::query_transaction_page(&dir, &query, offset.unwrap_or(0), limit, row_cap)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn run_hledger_report(
+    ledger: String,
+    command: String,
+    args: Vec<String>,
+    grouped: Option<bool>,
+) -> Result<report::ReportResult, String> {
+    let ledger_dir = std::path::PathBuf::from(&ledger);
+    let journal_path = ledger_dir.join("general.journal");
+    let args = if grouped.unwrap_or(false) {
+        let groups = report_groups::list_report_groups(&ledger_dir).map_err(|e| e.to_string())?;
+        let accounts = report::list_accounts(&journal_path).map_err(|e| e.to_string())?;
+        let mut grouped_args = report_groups::alias_args(&groups, &accounts);
+        grouped_args.extend(args);
+        grouped_args
+    } else {
+        args
+    };
+    let args = if command == "cashflow" {
+        let mut args = args;
+        args.extend(report::exclude_accounts_args(
+            &login_config::gl_accounts_excluded_from_cashflow(&ledger_dir),
+        ));
+        args
+    } else {
+        args
+    };
+    report::run_report(&journal_path, &command, &args).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_envelopes(
+    ledger: String,
+    month: Option<String>,
+    commodity: Option<String>,
+) -> Result<envelopes::EnvelopeReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let month = match month.as_deref().map(str::trim).filter(|m| !m.is_empty()) {
+        Some(month) => {
+            chrono::NaiveDate::parse_from_str(&format!("{month}-01"), "%Y-%m-%d")
+                .map_err(|err| format!("month must be YYYY-MM: {err}"))?;
+            month.to_string()
+        }
+        None => chrono::Local::now()
+            .date_naive()
+            .format("%Y-%m")
+            .to_string(),
+    };
+    let commodity = commodity
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .unwrap_or("USD");
+    envelopes::get_envelopes(&target_dir, &month, commodity).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn define_envelope(
+    ledger: String,
+    envelope: envelopes::EnvelopeDefinition,
+) -> Result<envelopes::EnvelopeDefinition, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    envelopes::define_envelope(&target_dir, envelope, "gui").map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn allocate_envelope(
+    ledger: String,
+    allocation: envelopes::AllocateEnvelopeInput,
+) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    envelopes::allocate_envelope(&target_dir, allocation, "gui").map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn report_suspicious_activity(
+    ledger: String,
+    since: Option<String>,
+) -> Result<suspicious::SuspiciousActivityReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let since = match since
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        Some(since) => since,
+        None => (chrono::Local::now().date_naive() - chrono::Duration::days(30))
+            .format("%Y-%m-%d")
+            .to_string(),
+    };
+    suspicious::suspicious_activity_report(&target_dir, &since).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn report_subscription_changes(
+    ledger: String,
+) -> Result<Vec<subscriptions::SubscriptionChange>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    subscriptions::report_subscription_changes(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_description_cleaning(
+    ledger: String,
+) -> Result<description_cleaning::DescriptionCleaning, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    description_cleaning::load_description_cleaning(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn set_description_cleaning(
+    ledger: String,
+    cleaning: description_cleaning::DescriptionCleaning,
+) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    description_cleaning::save_description_cleaning(&target_dir, &cleaning)
+        .map_err(|err| err.to_string())
+}
+
+/// Show what `cleaning` would make of `descriptions` without saving it.
+#[tauri::command]
+fn preview_description_cleaning(
+    cleaning: description_cleaning::DescriptionCleaning,
+    descriptions: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let cleaner = cleaning.compile()?;
+    Ok(descriptions
+        .iter()
+        .map(|description| cleaner.clean(description))
+        .collect())
+}
+
+#[tauri::command]
+fn report_spending_by_location(
+    ledger: String,
+    since: Option<String>,
+    until: Option<String>,
+) -> Result<Vec<locations::LocationSpending>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    locations::report_spending_by_location(&target_dir, since.as_deref(), until.as_deref())
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn list_payees(ledger: String, query: Option<String>) -> Result<Vec<payees::PayeeSummary>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    payees::list_payees(&target_dir, query.as_deref()).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_payee(ledger: String, payee: String) -> Result<payees::PayeeDetail, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    payees::get_payee(&target_dir, &payee).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn backfill_prices(
+    ledger: String,
+    commodity: String,
+    quote: Option<String>,
+    start: Option<String>,
+    end: Option<String>,
+    provider: Option<String>,
+) -> Result<fx_rates::PriceBackfillResult, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let commodity = require_non_empty_input("commodity", commodity)?;
+    let quote = quote
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty())
+        .unwrap_or_else(|| "USD".to_string());
+    let parse_date = |name: &str, value: Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(|v| {
+                chrono::NaiveDate::parse_from_str(v, "%Y-%m-%d")
+                    .map_err(|err| format!("{name} must be YYYY-MM-DD: {err}"))
+            })
+            .transpose()
+    };
+    let start = parse_date("start", start)?;
+    let end = parse_date("end", end)?;
+    let provider_name = provider
+        .as_deref()
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .unwrap_or(fx_rates::DEFAULT_PROVIDER)
+        .to_string();
+    let rate_provider = fx_rates::provider(&provider_name).ok_or_else(|| {
+        format!(
+            "provider: expected one of ({}), got {provider_name}",
+            fx_rates::PROVIDERS.join("|")
+        )
+    })?;
+
+    // Fetching rates and running hledger block.
+    tokio::task::spawn_blocking(move || {
+        fx_rates::backfill_prices(
+            &target_dir,
+            &commodity,
+            &quote,
+            start,
+            end,
+            rate_provider.as_ref(),
+        )
+    })
+    .await
+    .map_err(|err| err.to_string())?
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+async fn lint_ledger(
+    ledger: String,
+    options: lint::LintOptions,
+) -> Result<lint::LintReport, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    if options.stale_unposted_days.is_some_and(|days| days < 0) {
+        return Err("staleUnpostedDays must not be negative".to_string());
+    }
+    let today = chrono::Local::now().date_naive();
+    // Reading every journal and running hledger block.
+    tokio::task::spawn_blocking(move || lint::lint_ledger(&target_dir, &options, today))
+        .await
+        .map_err(|err| err.to_string())?
+        .map_err(|err| err.to_string())
+}
+
+fn parse_digest_as_of(as_of: Option<String>) -> Result<chrono::NaiveDate, String> {
+    match as_of.as_deref().map(str::trim).filter(|d| !d.is_empty()) {
+        Some(as_of) => chrono::NaiveDate::parse_from_str(as_of, "%Y-%m-%d")
+            .map_err(|err| format!("asOf must be YYYY-MM-DD: {err}")),
+        None => Ok(chrono::Local::now().date_naive()),
+    }
+}
+
+#[tauri::command]
+fn list_digests(ledger: String) -> Result<Vec<digests::Digest>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    digests::list_digests(&target_dir).map_err(|err| err.to_string())
+}
 
-        store
-            .set_credentials(domain, &username, &password)
-            .map_err(|err| format!("failed to migrate domain '{domain}': {err}"))?;
+#[tauri::command]
+fn generate_digest(
+    app_handle: tauri::AppHandle,
+    ledger: String,
+    cadence: String,
+    as_of: Option<String>,
+    commodity: Option<String>,
+) -> Result<digests::Digest, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let cadence: digests::DigestCadence = cadence.parse()?;
+    let as_of = parse_digest_as_of(as_of)?;
+    let commodity = commodity
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .unwrap_or("USD");
+    let digest = digests::generate_digest(&target_dir, cadence, as_of, commodity)
+        .map_err(|err| err.to_string())?;
+    let _ = app_handle.emit("refreshmint://digests-ready", [&digest]);
+    Ok(digest)
+}
 
-        // Clean up legacy entries for this domain
-        for (name, _) in name_values {
-            let _ = store.delete_legacy_entry(domain, name);
-        }
-        migrated.push(domain.clone());
+/// Generate any weekly or monthly digest that is due and announce new ones.
+#[tauri::command]
+fn generate_due_digests(
+    app_handle: tauri::AppHandle,
+    ledger: String,
+    commodity: Option<String>,
+) -> Result<Vec<digests::Digest>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let commodity = commodity
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty())
+        .unwrap_or("USD");
+    let generated =
+        digests::generate_due_digests(&target_dir, chrono::Local::now().date_naive(), commodity)
+            .map_err(|err| err.to_string())?;
+    if !generated.is_empty() {
+        let _ = app_handle.emit("refreshmint://digests-ready", &generated);
     }
+    Ok(generated)
+}
 
-    // Clean up legacy index if all entries were migrated
-    if migrated.len() == by_domain.len() {
-        let _ = store.delete_legacy_index();
-    }
+#[tauri::command]
+fn list_alert_rules(ledger: String) -> Result<Vec<alerts::AlertRule>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    alerts::list_alert_rules(&target_dir).map_err(|err| err.to_string())
+}
 
-    Ok(migrated)
+#[tauri::command]
+fn save_alert_rules(
+    ledger: String,
+    rules: Vec<alerts::AlertRule>,
+) -> Result<Vec<alerts::AlertRule>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    alerts::save_alert_rules(&target_dir, rules).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn clear_login_profile(ledger: String, login_name: String) -> Result<(), String> {
+fn list_alerts(ledger: String, include_closed: Option<bool>) -> Result<Vec<alerts::Alert>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
-    let login_name = require_login_name_input(login_name)?;
-    require_existing_login(&target_dir, &login_name)?;
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    alerts::list_alerts(&target_dir, include_closed.unwrap_or(false)).map_err(|err| err.to_string())
+}
 
-    let lock = login_config::acquire_login_lock_with_metadata(
-        &target_dir,
-        &login_name,
-        "gui",
-        "clear-login-profile",
-    )
-    .map_err(|err| err.to_string())?;
-    scrape::profile::clear_login_profile(&target_dir, &login_name, &lock)
+#[tauri::command]
+fn acknowledge_alert(ledger: String, id: String) -> Result<alerts::Alert, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let id = require_non_empty_input("id", id)?;
+    alerts::set_alert_state(&target_dir, &id, alerts::AlertState::Acknowledged)
         .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn migrate_ledger(ledger: String, dry_run: bool) -> Result<migration::MigrationOutcome, String> {
+fn dismiss_alert(ledger: String, id: String) -> Result<alerts::Alert, String> {
     let target_dir = std::path::PathBuf::from(ledger);
     crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
-    migration::migrate_ledger(&target_dir, dry_run).map_err(|err| err.to_string())
+    let id = require_non_empty_input("id", id)?;
+    alerts::set_alert_state(&target_dir, &id, alerts::AlertState::Dismissed)
+        .map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn query_transactions(
+fn list_report_groups(ledger: String) -> Result<Vec<report_groups::ReportGroup>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    report_groups::list_report_groups(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn upsert_report_group(
     ledger: String,
-    query: String,
-) -> Result<Vec<ledger_open::TransactionRow>, String> {
-    let dir = std::path::PathBuf::from(&ledger);
-    let journal_path = dir.join("general.journal");
-    let tokens = ledger_open::tokenize_query(&query);
-    ledger_open::run_hledger_print_with_query(&journal_path, &tokens)
-        .and_then(|txns| ledger_open::build_transaction_rows(&dir, &txns))
-        .map_err(|e| e.to_string())
+    name: String,
+    accounts: Vec<String>,
+) -> Result<Vec<report_groups::ReportGroup>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    report_groups::upsert_report_group(&target_dir, &name, accounts).map_err(|err| err.to_string())
 }
 
 #[tauri::command]
-fn run_hledger_report(
+fn delete_report_group(
     ledger: String,
-    command: String,
-    args: Vec<String>,
-) -> Result<report::ReportResult, String> {
-    let journal_path = std::path::PathBuf::from(&ledger).join("general.journal");
-    report::run_report(&journal_path, &command, &args).map_err(|e| e.to_string())
+    name: String,
+) -> Result<Vec<report_groups::ReportGroup>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let name = require_non_empty_input("name", name)?;
+    report_groups::delete_report_group(&target_dir, &name).map_err(|err| err.to_string())
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 struct AccountJournalEntry {
     id: String,
     date: String,
+    #[ts(type = "'pending' | 'posted' | 'unknown'")]
     bank_status: String,
+    #[ts(type = "'' | '!' | '*'")]
     status_marker: String,
     description: String,
     comment: String,
@@ -1483,18 +3206,21 @@ struct AccountJournalEntry {
     amount: Option<String>,
     /// All tags on the entry, as `(key, value)` pairs.
     tags: Vec<(String, String)>,
+    note: Option<String>,
+    review: Option<account_journal::ReviewState>,
 }
 
 #[tauri::command]
 fn get_account_journal(
     ledger: String,
     account_name: String,
+    filter: Option<entry_review::EntryFilter>,
 ) -> Result<Vec<AccountJournalEntry>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
     let account_name = require_non_empty_input("account_name", account_name)?;
     let entries =
         account_journal::read_journal(&target_dir, &account_name).map_err(|err| err.to_string())?;
-    Ok(map_account_journal_entries(entries))
+    Ok(map_account_journal_entries(filter_entries(entries, filter)))
 }
 
 #[tauri::command]
@@ -1502,6 +3228,7 @@ fn get_login_account_journal(
     ledger: String,
     login_name: String,
     label: String,
+    filter: Option<entry_review::EntryFilter>,
 ) -> Result<Vec<AccountJournalEntry>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
     let login_name = require_login_name_input(login_name)?;
@@ -1510,15 +3237,19 @@ fn get_login_account_journal(
         account_journal::login_account_journal_path(&target_dir, &login_name, &label);
     let entries =
         account_journal::read_journal_at_path(&journal_path).map_err(|err| err.to_string())?;
-    Ok(map_account_journal_entries(entries))
+    Ok(map_account_journal_entries(filter_entries(entries, filter)))
 }
 
 #[tauri::command]
-fn get_unposted(ledger: String, account_name: String) -> Result<Vec<AccountJournalEntry>, String> {
+fn get_unposted(
+    ledger: String,
+    account_name: String,
+    filter: Option<entry_review::EntryFilter>,
+) -> Result<Vec<AccountJournalEntry>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
     let account_name = require_non_empty_input("account_name", account_name)?;
     let entries = post::get_unposted(&target_dir, &account_name).map_err(|err| err.to_string())?;
-    Ok(map_account_journal_entries(entries))
+    Ok(map_account_journal_entries(filter_entries(entries, filter)))
 }
 
 #[tauri::command]
@@ -1526,13 +3257,14 @@ fn get_login_account_unposted(
     ledger: String,
     login_name: String,
     label: String,
+    filter: Option<entry_review::EntryFilter>,
 ) -> Result<Vec<AccountJournalEntry>, String> {
     let target_dir = std::path::PathBuf::from(ledger);
     let login_name = require_login_name_input(login_name)?;
     let label = require_label_input(label)?;
     let entries = post::get_unposted_login_account(&target_dir, &login_name, &label)
         .map_err(|err| err.to_string())?;
-    Ok(map_account_journal_entries(entries))
+    Ok(map_account_journal_entries(filter_entries(entries, filter)))
 }
 
 #[tauri::command]
@@ -1777,6 +3509,106 @@ fn unpost_login_account_entry(
     .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+fn set_entry_note(
+    ledger: String,
+    login_name: String,
+    label: String,
+    entry_id: String,
+    note: Option<String>,
+) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let entry_id = require_non_empty_input("entry_id", entry_id)?;
+    entry_review::set_entry_note(
+        &target_dir,
+        &login_name,
+        &label,
+        &entry_id,
+        note.as_deref(),
+        "gui",
+    )
+    .map(drop)
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn set_entry_review_state(
+    ledger: String,
+    login_name: String,
+    label: String,
+    entry_id: String,
+    state: Option<String>,
+) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let entry_id = require_non_empty_input("entry_id", entry_id)?;
+    let state = entry_review::parse_review_state(state.as_deref().unwrap_or_default())?;
+    entry_review::set_entry_review_state(&target_dir, &login_name, &label, &entry_id, state, "gui")
+        .map(drop)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn bulk_update_entries(
+    ledger: String,
+    login_name: String,
+    label: String,
+    query_or_ids: entry_review::EntrySelection,
+    operations: Vec<entry_review::BulkOperation>,
+    dry_run: Option<bool>,
+) -> Result<entry_review::BulkUpdateResult, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    entry_review::bulk_update_entries(
+        &target_dir,
+        &login_name,
+        &label,
+        &query_or_ids,
+        &operations,
+        dry_run.unwrap_or(false),
+        "gui",
+    )
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn split_login_account_entry(
+    ledger: String,
+    login_name: String,
+    label: String,
+    entry_id: String,
+    parts: Vec<entry_split::SplitPart>,
+) -> Result<Vec<AccountJournalEntry>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let entry_id = require_non_empty_input("entry_id", entry_id)?;
+    let parts =
+        entry_split::split_entry(&target_dir, &login_name, &label, &entry_id, &parts, "gui")
+            .map_err(|err| err.to_string())?;
+    Ok(map_account_journal_entries(parts))
+}
+
+#[tauri::command]
+fn merge_login_account_entry_split(
+    ledger: String,
+    login_name: String,
+    label: String,
+    entry_id: String,
+) -> Result<(), String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let entry_id = require_non_empty_input("entry_id", entry_id)?;
+    entry_split::merge_split_entry(&target_dir, &login_name, &label, &entry_id, "gui")
+        .map(drop)
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn post_transfer(
     ledger: String,
@@ -1795,7 +3627,8 @@ fn post_transfer(
         .map_err(|err| err.to_string())
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
 struct UnpostedTransferResult {
     #[serde(rename = "loginName")]
     login_name: String,
@@ -1883,6 +3716,35 @@ fn sync_gl_transaction(
         .map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+fn list_posted_entry_conflicts(
+    ledger: String,
+    login_name: String,
+    label: String,
+) -> Result<Vec<posted_changes::PostedEntryConflict>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    posted_changes::list_posted_entry_conflicts(&target_dir, &login_name, &label)
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn accept_posted_entry_changes(
+    ledger: String,
+    login_name: String,
+    label: String,
+    entry_id: String,
+) -> Result<String, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let entry_id = require_non_empty_input("entry_id", entry_id)?;
+
+    posted_changes::accept_posted_entry_changes(&target_dir, &login_name, &label, &entry_id, "gui")
+        .map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn suggest_categories(
     ledger: String,
@@ -1896,6 +3758,75 @@ fn suggest_categories(
     categorize::suggest_categories(&target_dir, &login_name, &label).map_err(|err| err.to_string())
 }
 
+#[tauri::command]
+fn record_category_feedback(
+    ledger: String,
+    login_name: String,
+    label: String,
+    entry_id: String,
+    suggested: Option<String>,
+    account: String,
+) -> Result<categorize::CategoryFeedback, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    crate::ledger::require_refreshmint_extension(&target_dir).map_err(|err| err.to_string())?;
+    let login_name = require_login_name_input(login_name)?;
+    let label = require_label_input(label)?;
+    let entry_id = require_non_empty_input("entry_id", entry_id)?;
+    categorize::record_category_feedback(
+        &target_dir,
+        &login_name,
+        &label,
+        &entry_id,
+        suggested,
+        &account,
+    )
+    .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_classification_queue(
+    ledger: String,
+) -> Result<Vec<classification_queue::QueueItem>, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    classification_queue::classification_queue(&target_dir).map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn classify_next(
+    ledger: String,
+    entry_ref: classification_queue::EntryRef,
+    decision: classification_queue::ClassifyDecision,
+) -> Result<classification_queue::ClassifyOutcome, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let entry_ref = classification_queue::EntryRef {
+        login_name: require_login_name_input(entry_ref.login_name)?,
+        label: require_label_input(entry_ref.label)?,
+        entry_id: require_non_empty_input("entry_id", entry_ref.entry_id)?,
+    };
+
+    // Same GL mapping check as post_login_account_entry.
+    if matches!(
+        decision,
+        classification_queue::ClassifyDecision::Post { .. }
+    ) {
+        let _ =
+            resolve_login_account_gl_account(&target_dir, &entry_ref.login_name, &entry_ref.label)?;
+    }
+
+    classification_queue::classify_next(&target_dir, &entry_ref, &decision, "gui")
+        .map_err(|err| err.to_string())
+}
+
+#[tauri::command]
+fn get_transaction_provenance(
+    ledger: String,
+    txn_id: String,
+) -> Result<provenance::TransactionProvenance, String> {
+    let target_dir = std::path::PathBuf::from(ledger);
+    let txn_id = require_non_empty_input("txn_id", txn_id)?;
+    provenance::transaction_provenance(&target_dir, &txn_id).map_err(|err| err.to_string())
+}
+
 #[tauri::command]
 fn suggest_gl_categories(
     ledger: String,
@@ -1926,6 +3857,16 @@ fn merge_gl_transfer(ledger: String, txn_id_1: String, txn_id_2: String) -> Resu
     post::merge_gl_transfer(&target_dir, &txn_id_1, &txn_id_2, "gui").map_err(|err| err.to_string())
 }
 
+fn filter_entries(
+    entries: Vec<account_journal::AccountEntry>,
+    filter: Option<entry_review::EntryFilter>,
+) -> Vec<account_journal::AccountEntry> {
+    match filter {
+        Some(filter) => entries.into_iter().filter(|e| filter.matches(e)).collect(),
+        None => entries,
+    }
+}
+
 fn map_account_journal_entries(
     entries: Vec<account_journal::AccountEntry>,
 ) -> Vec<AccountJournalEntry> {
@@ -1956,6 +3897,8 @@ fn map_account_journal_entries(
                 is_transfer,
                 amount,
                 tags,
+                note: e.note,
+                review: e.review,
             }
         })
         .collect()
@@ -1968,32 +3911,24 @@ fn send_prompt_answer(answer: Option<String>, state: &PromptAnswerState) -> Resu
     let mut guard = state.0.lock().map_err(|e| e.to_string())?;
     if let Some(sender) = guard.take() {
         // Ignore send errors: the scrape thread may have already timed out.
-        let _ = sender.send(answer);
+        let _ = sender.send(answer.map(crate::secret::SecretString::new));
     }
     Ok(())
 }
 
 fn request_prompt_answer(
     app_handle: &tauri::AppHandle,
-    message: String,
-) -> Result<Option<String>, String> {
-    let (tx, rx) = std::sync::mpsc::channel::<Option<String>>();
+    request: scrape::prompt_catalog::PromptRequest,
+) -> Result<Option<crate::secret::SecretString>, String> {
+    let (tx, rx) = std::sync::mpsc::channel::<Option<crate::secret::SecretString>>();
     {
         let state = app_handle.state::<PromptAnswerState>();
         let mut guard = state.0.lock().map_err(|e| e.to_string())?;
         *guard = Some(tx);
     }
 
-    #[derive(serde::Serialize, Clone)]
-    struct PromptRequestedPayload {
-        message: String,
-    }
-
     app_handle
-        .emit(
-            "refreshmint://prompt-requested",
-            PromptRequestedPayload { message },
-        )
+        .emit("refreshmint://prompt-requested", request)
         .map_err(|e| format!("prompt emit failed: {e}"))?;
 
     rx.recv().map_err(|_| "prompt cancelled".to_string())
@@ -2061,7 +3996,7 @@ mod tests {
         assert_eq!(
             rx.recv()
                 .unwrap_or_else(|err| panic!("failed to receive prompt answer: {err}")),
-            Some(String::new())
+            Some(crate::secret::SecretString::default())
         );
     }
 
@@ -2136,6 +4071,8 @@ mod tests {
         let config = crate::login_config::LoginConfig {
             extension: Some("chase-driver".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         if let Err(err) = crate::login_config::write_login_config(&dir, "chase", &config) {
             panic!("failed to write login config: {err}");
@@ -2259,11 +4196,17 @@ mod tests {
             "checking".to_string(),
             crate::login_config::LoginAccountConfig {
                 gl_account: Some("Assets:Chase:Checking".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
             },
         );
         let config = crate::login_config::LoginConfig {
             extension: Some("chase-driver".to_string()),
             accounts,
+            institution: None,
+            label_rules: Vec::new(),
         };
         if let Err(err) = crate::login_config::write_login_config(&dir, "chase-personal", &config) {
             panic!("failed to write login config: {err}");
@@ -2290,6 +4233,8 @@ mod tests {
         let config = crate::login_config::LoginConfig {
             extension: Some("chase-driver".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         if let Err(err) = crate::login_config::write_login_config(&dir, "chase-personal", &config) {
             panic!("failed to write login config: {err}");
@@ -0,0 +1,484 @@
+//! Data quality checks across the ledger's journals.
+//!
+//! Each rule looks for one kind of likely mistake in the login account
+//! journals or the general journal and reports findings with a severity and
+//! a suggested fix. Nothing is changed; the findings point at entries to
+//! review.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::io;
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry};
+use crate::hledger::Transaction;
+use crate::login_config;
+
+/// Unposted entries older than this many days are reported by default.
+pub const DEFAULT_STALE_UNPOSTED_DAYS: i64 = 30;
+/// Largest edit distance at which a single-use category suggests another.
+const TYPO_MAX_DISTANCE: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum LintSeverity {
+    Warning,
+    Info,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
+pub enum LintRule {
+    /// An entry or GL transaction with a blank description.
+    MissingDescription,
+    /// Dated after today.
+    FutureDate,
+    /// Every amount is zero.
+    ZeroAmount,
+    /// The same evidence ref on more than one entry of an account, which
+    /// usually means a row was extracted twice.
+    DuplicateEvidence,
+    /// A GL category posted to only once, often a misspelling.
+    SingleUseCategory,
+    /// An entry left unposted for longer than the stale threshold.
+    StaleUnposted,
+}
+
+impl LintRule {
+    fn severity(self) -> LintSeverity {
+        match self {
+            LintRule::MissingDescription
+            | LintRule::FutureDate
+            | LintRule::DuplicateEvidence
+            | LintRule::SingleUseCategory => LintSeverity::Warning,
+            LintRule::ZeroAmount | LintRule::StaleUnposted => LintSeverity::Info,
+        }
+    }
+}
+
+/// Where a finding is: a login account entry, a GL transaction, or a GL
+/// account.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LintFinding {
+    pub rule: LintRule,
+    pub severity: LintSeverity,
+    pub login_name: Option<String>,
+    pub label: Option<String>,
+    pub entry_id: Option<String>,
+    pub gl_txn_id: Option<String>,
+    pub account: Option<String>,
+    pub date: Option<String>,
+    pub message: String,
+    pub suggestion: String,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LintReport {
+    /// Most severe first, then by date.
+    pub findings: Vec<LintFinding>,
+    pub warnings: usize,
+    pub infos: usize,
+}
+
+#[derive(Debug, Clone, Default, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LintOptions {
+    /// Report unposted entries older than this many days (default 30).
+    #[serde(default)]
+    #[ts(optional)]
+    pub stale_unposted_days: Option<i64>,
+    /// Rules to skip.
+    #[serde(default)]
+    #[ts(optional)]
+    pub disabled_rules: Option<Vec<String>>,
+}
+
+/// Run every enabled rule over the login account journals and the general
+/// journal as of `today`.
+pub fn lint_ledger(
+    ledger_dir: &Path,
+    options: &LintOptions,
+    today: NaiveDate,
+) -> io::Result<LintReport> {
+    let stale_days = options
+        .stale_unposted_days
+        .unwrap_or(DEFAULT_STALE_UNPOSTED_DAYS);
+    let mut findings = Vec::new();
+    for login in login_config::list_logins(ledger_dir)? {
+        let config = login_config::read_login_config(ledger_dir, &login);
+        for label in config.accounts.keys() {
+            let journal_path =
+                account_journal::login_account_journal_path(ledger_dir, &login, label);
+            let entries = account_journal::read_journal_at_path(&journal_path)?;
+            findings.extend(lint_account_entries(
+                &login, label, &entries, today, stale_days,
+            ));
+        }
+    }
+    let gl_path = ledger_dir.join("general.journal");
+    if gl_path.exists() {
+        let transactions = crate::ledger_open::run_hledger_print(&gl_path)?;
+        findings.extend(lint_gl_transactions(&transactions, today));
+    }
+
+    let disabled: BTreeSet<&str> = options
+        .disabled_rules
+        .iter()
+        .flatten()
+        .map(String::as_str)
+        .collect();
+    findings.retain(|finding| !disabled.contains(rule_name(finding.rule).as_str()));
+    findings.sort_by(|a, b| {
+        a.severity
+            .cmp(&b.severity)
+            .then_with(|| b.date.cmp(&a.date))
+    });
+    let count = |severity: LintSeverity| findings.iter().filter(|f| f.severity == severity).count();
+    Ok(LintReport {
+        warnings: count(LintSeverity::Warning),
+        infos: count(LintSeverity::Info),
+        findings,
+    })
+}
+
+fn rule_name(rule: LintRule) -> String {
+    serde_json::to_value(rule)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn lint_account_entries(
+    login: &str,
+    label: &str,
+    entries: &[AccountEntry],
+    today: NaiveDate,
+    stale_days: i64,
+) -> Vec<LintFinding> {
+    let finding =
+        |rule: LintRule, entry: &AccountEntry, message: String, suggestion: &str| LintFinding {
+            rule,
+            severity: rule.severity(),
+            login_name: Some(login.to_string()),
+            label: Some(label.to_string()),
+            entry_id: Some(entry.id.clone()),
+            gl_txn_id: None,
+            account: None,
+            date: Some(entry.date.clone()),
+            message,
+            suggestion: suggestion.to_string(),
+        };
+    let mut findings = Vec::new();
+    for entry in entries {
+        let date = parse_day(&entry.date);
+        if entry.description.trim().is_empty() {
+            findings.push(finding(
+                LintRule::MissingDescription,
+                entry,
+                "Entry has no description".to_string(),
+                "Check the source document and add a description, or fix the extractor.",
+            ));
+        }
+        if date.is_some_and(|date| date > today) {
+            findings.push(finding(
+                LintRule::FutureDate,
+                entry,
+                format!("Entry is dated {}, after today", entry.date),
+                "Check the date format the extractor parses (day/month order, two-digit years).",
+            ));
+        }
+        let amounts: Vec<f64> = entry
+            .postings
+            .iter()
+            .filter_map(|p| p.amount.as_ref())
+            .filter_map(|a| a.quantity.replace(',', "").parse().ok())
+            .collect();
+        if !amounts.is_empty() && amounts.iter().all(|amount| *amount == 0.0) {
+            findings.push(finding(
+                LintRule::ZeroAmount,
+                entry,
+                "Entry amount is zero".to_string(),
+                "Zero-amount rows are often authorizations or memo lines; skip them in the extractor if they are not transactions.",
+            ));
+        }
+        let stale = date.is_some_and(|date| (today - date).num_days() > stale_days);
+        if stale && crate::post::has_unposted_portion(entry) {
+            findings.push(finding(
+                LintRule::StaleUnposted,
+                entry,
+                format!("Entry has been unposted for more than {stale_days} days"),
+                "Categorize and post it, or match it as a transfer.",
+            ));
+        }
+    }
+
+    let mut by_evidence: BTreeMap<&str, Vec<&AccountEntry>> = BTreeMap::new();
+    for entry in entries {
+        for evidence in &entry.evidence {
+            by_evidence
+                .entry(evidence.as_str())
+                .or_default()
+                .push(entry);
+        }
+    }
+    let mut reported = BTreeSet::new();
+    for (evidence, sharing) in by_evidence {
+        // Parts of one split share their original's evidence on purpose.
+        let splits: BTreeSet<Option<&str>> = sharing
+            .iter()
+            .map(|entry| entry.tag_value(crate::entry_split::SPLIT_FROM_TAG))
+            .collect();
+        if sharing.len() < 2 || (splits.len() == 1 && !splits.contains(&None)) {
+            continue;
+        }
+        for entry in &sharing[1..] {
+            if !reported.insert(entry.id.as_str()) {
+                continue;
+            }
+            findings.push(finding(
+                LintRule::DuplicateEvidence,
+                entry,
+                format!(
+                    "Evidence {evidence} is also on entry {}",
+                    sharing[0].id
+                ),
+                "If both entries are the same transaction, delete one; otherwise fix the evidence row numbers in the extractor.",
+            ));
+        }
+    }
+    findings
+}
+
+fn lint_gl_transactions(transactions: &[Transaction], today: NaiveDate) -> Vec<LintFinding> {
+    let finding =
+        |rule: LintRule, txn: &Transaction, message: String, suggestion: &str| LintFinding {
+            rule,
+            severity: rule.severity(),
+            login_name: None,
+            label: None,
+            entry_id: None,
+            gl_txn_id: gl_txn_id(txn),
+            account: None,
+            date: Some(txn.tdate.clone()),
+            message,
+            suggestion: suggestion.to_string(),
+        };
+    let mut findings = Vec::new();
+    let mut category_uses: BTreeMap<&str, Vec<&Transaction>> = BTreeMap::new();
+    for txn in transactions {
+        if txn.tdescription.trim().is_empty() {
+            findings.push(finding(
+                LintRule::MissingDescription,
+                txn,
+                "GL transaction has no description".to_string(),
+                "Add a description so the transaction can be found and categorized.",
+            ));
+        }
+        if parse_day(&txn.tdate).is_some_and(|date| date > today) {
+            findings.push(finding(
+                LintRule::FutureDate,
+                txn,
+                format!("GL transaction is dated {}, after today", txn.tdate),
+                "Fix the date, or tag it as scheduled if it is a planned transaction.",
+            ));
+        }
+        let amounts: Vec<f64> = txn
+            .tpostings
+            .iter()
+            .flat_map(|p| &p.pamount)
+            .map(|a| a.aquantity.floating_point)
+            .collect();
+        if !amounts.is_empty() && amounts.iter().all(|amount| *amount == 0.0) {
+            findings.push(finding(
+                LintRule::ZeroAmount,
+                txn,
+                "GL transaction amounts are all zero".to_string(),
+                "Delete it if it records nothing.",
+            ));
+        }
+        for posting in &txn.tpostings {
+            if is_category(&posting.paccount) {
+                category_uses
+                    .entry(posting.paccount.as_str())
+                    .or_default()
+                    .push(txn);
+            }
+        }
+    }
+
+    let common: Vec<&str> = category_uses
+        .iter()
+        .filter(|(_, uses)| uses.len() > 1)
+        .map(|(account, _)| *account)
+        .collect();
+    for (account, uses) in &category_uses {
+        if uses.len() != 1 {
+            continue;
+        }
+        let suggestion = match closest_category(account, &common) {
+            Some(closest) => format!("Did you mean {closest}? Recategorize the transaction."),
+            None => {
+                "If this is a typo, recategorize the transaction; otherwise ignore.".to_string()
+            }
+        };
+        let mut found = finding(
+            LintRule::SingleUseCategory,
+            uses[0],
+            format!("{account} is used by only one transaction"),
+            &suggestion,
+        );
+        found.account = Some((*account).to_string());
+        findings.push(found);
+    }
+    findings
+}
+
+fn gl_txn_id(txn: &Transaction) -> Option<String> {
+    txn.ttags
+        .iter()
+        .find(|(key, value)| key == "id" && !value.trim().is_empty())
+        .map(|(_, value)| value.clone())
+}
+
+fn is_category(account: &str) -> bool {
+    let top = account.split(':').next().unwrap_or(account);
+    !["Assets", "Liabilities", "Equity"].contains(&top)
+}
+
+/// The category nearest to `account` by edit distance, if close enough to be
+/// a misspelling.
+fn closest_category<'a>(account: &str, candidates: &[&'a str]) -> Option<&'a str> {
+    let lower = account.to_lowercase();
+    candidates
+        .iter()
+        .map(|candidate| (edit_distance(&lower, &candidate.to_lowercase()), *candidate))
+        .filter(|(distance, _)| *distance <= TYPO_MAX_DISTANCE)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != *cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+fn parse_day(text: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(text, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn entry(
+        id: &str,
+        date: &str,
+        description: &str,
+        amount: &str,
+        evidence: &str,
+    ) -> AccountEntry {
+        let mut entry = AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![evidence.to_string()],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: amount.to_string(),
+                }),
+            }],
+        );
+        entry.id = id.to_string();
+        entry
+    }
+
+    fn rules(findings: &[LintFinding], id: &str) -> Vec<LintRule> {
+        findings
+            .iter()
+            .filter(|f| f.entry_id.as_deref() == Some(id))
+            .map(|f| f.rule)
+            .collect()
+    }
+
+    #[test]
+    fn account_entry_rules() {
+        let today = parse_day("2025-06-30").unwrap();
+        let mut posted = entry("posted", "2025-01-01", "Rent", "-1500", "a.csv:1:1");
+        posted.posted = Some("general.journal:x".to_string());
+        let entries = vec![
+            posted,
+            entry("blank", "2025-06-29", " ", "-3.00", "a.csv:2:1"),
+            entry("future", "2026-06-01", "Coffee", "-4.00", "a.csv:3:1"),
+            entry("zero", "2025-06-20", "Auth hold", "0.00", "a.csv:4:1"),
+            entry("stale", "2025-04-01", "Gym", "-40", "a.csv:5:1"),
+            entry("dupe", "2025-06-28", "Gym", "-40", "a.csv:5:1"),
+        ];
+        let findings = lint_account_entries("bank", "checking", &entries, today, 30);
+        assert!(rules(&findings, "posted").is_empty());
+        assert_eq!(
+            rules(&findings, "blank"),
+            vec![LintRule::MissingDescription]
+        );
+        assert_eq!(rules(&findings, "future"), vec![LintRule::FutureDate]);
+        assert_eq!(rules(&findings, "zero"), vec![LintRule::ZeroAmount]);
+        assert_eq!(rules(&findings, "stale"), vec![LintRule::StaleUnposted]);
+        assert_eq!(rules(&findings, "dupe"), vec![LintRule::DuplicateEvidence]);
+    }
+
+    #[test]
+    fn split_parts_sharing_evidence_are_not_duplicates() {
+        let today = parse_day("2025-06-30").unwrap();
+        let mut parts = vec![
+            entry("abc-part1", "2025-06-28", "ATM", "-60", "a.csv:5:1"),
+            entry("abc-part2", "2025-06-28", "ATM", "-40", "a.csv:5:1"),
+        ];
+        for part in &mut parts {
+            part.tags.push((
+                crate::entry_split::SPLIT_FROM_TAG.to_string(),
+                "abc".to_string(),
+            ));
+        }
+        let findings = lint_account_entries("bank", "checking", &parts, today, 30);
+        assert!(findings.is_empty(), "{findings:?}");
+    }
+
+    #[test]
+    fn single_use_category_suggests_nearest_common_category() {
+        assert_eq!(
+            closest_category(
+                "Expenses:Grocereis",
+                &["Expenses:Groceries", "Expenses:Gas"]
+            ),
+            Some("Expenses:Groceries")
+        );
+        assert_eq!(
+            closest_category("Expenses:Vet", &["Expenses:Groceries"]),
+            None
+        );
+        assert_eq!(
+            rule_name(LintRule::SingleUseCategory),
+            "single-use-category"
+        );
+    }
+}
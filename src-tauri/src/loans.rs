@@ -0,0 +1,642 @@
+//! Loan and mortgage amortization.
+//!
+//! Each loan is stored as `bookkeeping/loans/<id>.json`. The amortization
+//! schedule is computed from the loan terms and is used to split a payment
+//! into principal, interest, and escrow, and to compare the expected
+//! remaining balance with the balance scraped from the servicer.
+
+use chrono::{Months, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::account_journal::AccountEntry;
+use crate::bookkeeping::{
+    bookkeeping_dir, normalize_optional_string, read_json_objects_from_dir, read_optional_json,
+    require_date, require_non_empty, write_json,
+};
+use crate::post::SplitCounterpart;
+
+const LOANS_DIR: &str = "loans";
+
+/// How far a payment may be from its due date and still be matched to it.
+const PAYMENT_WINDOW_DAYS: i64 = 20;
+
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct Loan {
+    pub id: String,
+    pub name: String,
+    /// Liability account that carries the outstanding principal.
+    pub gl_account: String,
+    pub interest_account: String,
+    pub escrow_account: Option<String>,
+    pub commodity: String,
+    /// Original principal, e.g. `"300000.00"`.
+    pub principal: String,
+    /// Annual interest rate in percent, e.g. `"6.25"`.
+    pub annual_rate: String,
+    pub term_months: u32,
+    /// Due date of the first payment; later payments fall on the same day of
+    /// each following month.
+    pub first_payment_date: String,
+    /// Principal and interest payment. Computed from the terms when unset.
+    pub payment: Option<String>,
+    /// Escrow collected with each payment.
+    pub escrow: Option<String>,
+    /// Case-insensitive substring of the payment entry description.
+    pub payee: Option<String>,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct UpsertLoanInput {
+    pub id: Option<String>,
+    pub name: String,
+    pub gl_account: String,
+    pub interest_account: String,
+    pub escrow_account: Option<String>,
+    pub commodity: Option<String>,
+    pub principal: String,
+    pub annual_rate: String,
+    pub term_months: u32,
+    pub first_payment_date: String,
+    pub payment: Option<String>,
+    pub escrow: Option<String>,
+    pub payee: Option<String>,
+}
+
+/// One scheduled payment. Amounts are in the loan's commodity.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct AmortizationRow {
+    pub number: u32,
+    pub due_date: String,
+    pub payment: String,
+    pub principal: String,
+    pub interest: String,
+    pub escrow: String,
+    /// Principal outstanding after this payment.
+    pub balance: String,
+}
+
+/// Suggested split for a loan payment entry.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LoanSplitSuggestion {
+    pub loan_id: String,
+    pub payment_number: u32,
+    /// Counterpart legs ready for `post_login_account_entry_split`.
+    pub counterparts: Vec<SplitCounterpart>,
+    /// Amount paid above the scheduled payment, applied to principal.
+    pub extra_principal: String,
+}
+
+/// The servicer's scraped balance for a loan.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct ReportedLoanBalance {
+    pub login_name: String,
+    pub label: String,
+    pub date: String,
+    pub amount: String,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LoanBalanceReport {
+    pub loan_id: String,
+    pub as_of: String,
+    pub commodity: String,
+    pub payments_due: u32,
+    /// Principal outstanding after the last payment due on or before `as_of`.
+    pub scheduled_balance: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub reported: Option<ReportedLoanBalance>,
+    /// `|reported| - scheduled_balance`. Negative after extra principal
+    /// payments; positive when a payment was missed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub drift: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub next_payment: Option<AmortizationRow>,
+}
+
+pub fn list_loans(ledger_dir: &Path) -> io::Result<Vec<Loan>> {
+    let mut loans: Vec<Loan> = read_json_objects_from_dir(&loans_dir(ledger_dir))?;
+    loans.sort_by(|a, b| a.name.cmp(&b.name).then_with(|| a.id.cmp(&b.id)));
+    Ok(loans)
+}
+
+pub fn get_loan(ledger_dir: &Path, id: &str) -> io::Result<Loan> {
+    read_optional_json(&loan_path(ledger_dir, id))?
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, format!("loan not found: {id}")))
+}
+
+pub fn upsert_loan(ledger_dir: &Path, input: UpsertLoanInput) -> io::Result<Loan> {
+    let name = require_non_empty("name", input.name)?;
+    let gl_account = require_non_empty("gl_account", input.gl_account)?;
+    let interest_account = require_non_empty("interest_account", input.interest_account)?;
+    let escrow_account = normalize_optional_string(input.escrow_account);
+    let commodity = normalize_optional_string(input.commodity).unwrap_or_else(|| "USD".to_string());
+    let principal = require_non_empty("principal", input.principal)?;
+    if parse_cents(&principal).map_or(true, |cents| cents <= 0) {
+        return Err(invalid_input("principal must be a positive amount"));
+    }
+    let annual_rate = require_non_empty("annual_rate", input.annual_rate)?;
+    if annual_rate
+        .parse::<f64>()
+        .map_or(true, |rate| !rate.is_finite() || rate < 0.0)
+    {
+        return Err(invalid_input(
+            "annual_rate must be a non-negative percentage",
+        ));
+    }
+    if input.term_months == 0 {
+        return Err(invalid_input("term_months must be at least 1"));
+    }
+    let first_payment_date = require_date("first_payment_date", input.first_payment_date)?;
+    let payment = normalize_optional_amount("payment", input.payment)?;
+    let escrow = normalize_optional_amount("escrow", input.escrow)?;
+    if escrow.is_some() && escrow_account.is_none() {
+        return Err(invalid_input(
+            "escrow_account is required when escrow is set",
+        ));
+    }
+
+    let now = crate::operations::now_timestamp();
+    let id = normalize_optional_string(input.id);
+    let existing = match &id {
+        Some(id) => Some(get_loan(ledger_dir, id)?),
+        None => None,
+    };
+    let loan = Loan {
+        id: id.unwrap_or_else(|| uuid::Uuid::new_v4().to_string()),
+        name,
+        gl_account,
+        interest_account,
+        escrow_account,
+        commodity,
+        principal,
+        annual_rate,
+        term_months: input.term_months,
+        first_payment_date,
+        payment,
+        escrow,
+        payee: normalize_optional_string(input.payee),
+        created_at: existing
+            .map(|loan| loan.created_at)
+            .unwrap_or_else(|| now.clone()),
+        updated_at: now,
+    };
+    write_json(&loan_path(ledger_dir, &loan.id), &loan)?;
+    Ok(loan)
+}
+
+pub fn delete_loan(ledger_dir: &Path, id: &str) -> io::Result<()> {
+    let path = loan_path(ledger_dir, id);
+    if path.exists() {
+        fs::remove_file(path)?;
+    }
+    Ok(())
+}
+
+/// Compute the full amortization schedule for a loan.
+///
+/// Interest accrues monthly at `annual_rate / 12` on the outstanding
+/// principal and is rounded to the cent; the final payment absorbs any
+/// rounding remainder.
+pub fn amortization_schedule(loan: &Loan) -> io::Result<Vec<AmortizationRow>> {
+    let terms = LoanTerms::from_loan(loan)?;
+    let mut rows = Vec::with_capacity(terms.term_months as usize);
+    let mut balance = terms.principal;
+    for number in 1..=terms.term_months {
+        if balance <= 0 {
+            break;
+        }
+        let due_date = terms
+            .first_payment_date
+            .checked_add_months(Months::new(number - 1))
+            .ok_or_else(|| invalid_input("loan schedule runs past the supported date range"))?;
+        let interest = (balance as f64 * terms.monthly_rate).round() as i64;
+        let mut principal = terms.payment - interest;
+        if number == terms.term_months || principal > balance {
+            principal = balance;
+        }
+        balance -= principal;
+        rows.push(AmortizationRow {
+            number,
+            due_date: due_date.format("%Y-%m-%d").to_string(),
+            payment: format_cents(principal + interest),
+            principal: format_cents(principal),
+            interest: format_cents(interest),
+            escrow: format_cents(terms.escrow),
+            balance: format_cents(balance),
+        });
+    }
+    Ok(rows)
+}
+
+/// Suggest a principal/interest/escrow split for an unposted entry.
+///
+/// The entry matches a loan when its description contains the loan's
+/// `payee`, or, for loans without a payee, when its amount equals the
+/// scheduled payment plus escrow. Any amount above the scheduled payment is
+/// treated as extra principal.
+pub fn suggest_loan_split(loans: &[Loan], entry: &AccountEntry) -> Option<LoanSplitSuggestion> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    let paid = parse_cents(&amount.quantity)?;
+    if paid == 0 {
+        return None;
+    }
+    let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok()?;
+    let description = entry.description.to_lowercase();
+    loans
+        .iter()
+        .filter(|loan| {
+            loan.commodity == amount.commodity && entry.postings[0].account != loan.gl_account
+        })
+        .find_map(|loan| {
+            let by_payee = loan
+                .payee
+                .as_deref()
+                .is_some_and(|payee| description.contains(&payee.to_lowercase()));
+            if loan.payee.is_some() && !by_payee {
+                return None;
+            }
+            let schedule = amortization_schedule(loan).ok()?;
+            let row = nearest_row(&schedule, date)?;
+            let interest = parse_cents(&row.interest)?;
+            let escrow = parse_cents(&row.escrow)?;
+            let scheduled = parse_cents(&row.payment)? + escrow;
+            let total = paid.abs();
+            if !by_payee && total != scheduled {
+                return None;
+            }
+            if total < interest + escrow {
+                return None;
+            }
+            let principal = total - interest - escrow;
+            // Legs offset the entry's own posting.
+            let sign = if paid < 0 { 1 } else { -1 };
+            let leg = |account: &str, cents: i64| SplitCounterpart {
+                account: account.to_string(),
+                amount: Some(format!("{} {}", format_cents(sign * cents), loan.commodity)),
+            };
+            let mut counterparts = vec![
+                leg(&loan.gl_account, principal),
+                leg(&loan.interest_account, interest),
+            ];
+            if let (Some(account), true) = (&loan.escrow_account, escrow > 0) {
+                counterparts.push(leg(account, escrow));
+            }
+            Some(LoanSplitSuggestion {
+                loan_id: loan.id.clone(),
+                payment_number: row.number,
+                counterparts,
+                extra_principal: format_cents((total - scheduled).max(0)),
+            })
+        })
+}
+
+/// Compare the scheduled remaining principal with the servicer's balance.
+///
+/// The reported balance is the latest `balances.csv` snapshot on or before
+/// `as_of` from the login account mapped to the loan's GL account.
+pub fn loan_balance_report(
+    ledger_dir: &Path,
+    loan_id: &str,
+    as_of: NaiveDate,
+) -> io::Result<LoanBalanceReport> {
+    let loan = get_loan(ledger_dir, loan_id)?;
+    let schedule = amortization_schedule(&loan)?;
+    let as_of_str = as_of.format("%Y-%m-%d").to_string();
+    let due: Vec<&AmortizationRow> = schedule
+        .iter()
+        .filter(|row| row.due_date <= as_of_str)
+        .collect();
+    let scheduled_balance = due
+        .last()
+        .map(|row| row.balance.clone())
+        .unwrap_or_else(|| format_cents(parse_cents(&loan.principal).unwrap_or(0)));
+    let next_payment = schedule
+        .iter()
+        .find(|row| row.due_date > as_of_str)
+        .cloned();
+
+    let reported = reported_loan_balance(ledger_dir, &loan, &as_of_str)?;
+    let drift = reported.as_ref().and_then(|reported| {
+        let reported = parse_cents(&reported.amount)?.abs();
+        Some(format_cents(reported - parse_cents(&scheduled_balance)?))
+    });
+
+    Ok(LoanBalanceReport {
+        loan_id: loan.id,
+        as_of: as_of_str,
+        commodity: loan.commodity,
+        payments_due: due.len() as u32,
+        scheduled_balance,
+        reported,
+        drift,
+        next_payment,
+    })
+}
+
+fn reported_loan_balance(
+    ledger_dir: &Path,
+    loan: &Loan,
+    as_of: &str,
+) -> io::Result<Option<ReportedLoanBalance>> {
+    let Some((login_name, label)) =
+        crate::login_config::find_login_account_for_gl_account(ledger_dir, &loan.gl_account)
+    else {
+        return Ok(None);
+    };
+    let snapshots = crate::balances::read_snapshots(
+        &crate::balances::login_account_balances_path(ledger_dir, &login_name, &label),
+    )?;
+    let latest = snapshots
+        .into_iter()
+        .filter(|s| {
+            s.kind == crate::balances::SnapshotKind::Balance
+                && s.commodity == loan.commodity
+                && s.date.as_str() <= as_of
+        })
+        .fold(
+            None,
+            |best: Option<crate::balances::BalanceSnapshot>, s| match best {
+                Some(best) if best.date > s.date => Some(best),
+                _ => Some(s),
+            },
+        );
+    Ok(latest.map(|snapshot| ReportedLoanBalance {
+        login_name,
+        label,
+        date: snapshot.date,
+        amount: snapshot.amount,
+    }))
+}
+
+struct LoanTerms {
+    principal: i64,
+    monthly_rate: f64,
+    term_months: u32,
+    first_payment_date: NaiveDate,
+    payment: i64,
+    escrow: i64,
+}
+
+impl LoanTerms {
+    fn from_loan(loan: &Loan) -> io::Result<Self> {
+        let principal = parse_cents(&loan.principal)
+            .ok_or_else(|| invalid_input(format!("invalid principal: {}", loan.principal)))?;
+        let annual_rate: f64 = loan
+            .annual_rate
+            .parse()
+            .map_err(|_| invalid_input(format!("invalid annual_rate: {}", loan.annual_rate)))?;
+        let monthly_rate = annual_rate / 100.0 / 12.0;
+        let first_payment_date = NaiveDate::parse_from_str(&loan.first_payment_date, "%Y-%m-%d")
+            .map_err(|err| invalid_input(format!("invalid first_payment_date: {err}")))?;
+        let payment = match &loan.payment {
+            Some(payment) => parse_cents(payment)
+                .ok_or_else(|| invalid_input(format!("invalid payment: {payment}")))?,
+            None => level_payment(principal, monthly_rate, loan.term_months),
+        };
+        let escrow = match &loan.escrow {
+            Some(escrow) => parse_cents(escrow)
+                .ok_or_else(|| invalid_input(format!("invalid escrow: {escrow}")))?,
+            None => 0,
+        };
+        Ok(Self {
+            principal,
+            monthly_rate,
+            term_months: loan.term_months,
+            first_payment_date,
+            payment,
+            escrow,
+        })
+    }
+}
+
+/// Level monthly payment that retires `principal` in `term_months`.
+fn level_payment(principal: i64, monthly_rate: f64, term_months: u32) -> i64 {
+    let n = f64::from(term_months.max(1));
+    let principal = principal as f64;
+    if monthly_rate == 0.0 {
+        return (principal / n).ceil() as i64;
+    }
+    let factor = (1.0 + monthly_rate).powf(n);
+    (principal * monthly_rate * factor / (factor - 1.0)).round() as i64
+}
+
+fn nearest_row(schedule: &[AmortizationRow], date: NaiveDate) -> Option<&AmortizationRow> {
+    schedule
+        .iter()
+        .filter_map(|row| {
+            let due = NaiveDate::parse_from_str(&row.due_date, "%Y-%m-%d").ok()?;
+            let distance = (date - due).num_days().abs();
+            (distance <= PAYMENT_WINDOW_DAYS).then_some((distance, row))
+        })
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, row)| row)
+}
+
+/// Parse a decimal amount into cents, ignoring thousands separators.
+pub(crate) fn parse_cents(value: &str) -> Option<i64> {
+    let value: f64 = value.trim().replace(',', "").parse().ok()?;
+    value.is_finite().then(|| (value * 100.0).round() as i64)
+}
+
+pub(crate) fn format_cents(cents: i64) -> String {
+    let sign = if cents < 0 { "-" } else { "" };
+    let cents = cents.unsigned_abs();
+    format!("{sign}{}.{:02}", cents / 100, cents % 100)
+}
+
+fn normalize_optional_amount(
+    field_name: &str,
+    value: Option<String>,
+) -> io::Result<Option<String>> {
+    match normalize_optional_string(value) {
+        Some(value) if parse_cents(&value).map_or(true, |cents| cents < 0) => Err(invalid_input(
+            format!("{field_name} must be a non-negative amount"),
+        )),
+        other => Ok(other),
+    }
+}
+
+fn invalid_input(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, message.into())
+}
+
+fn loans_dir(ledger_dir: &Path) -> PathBuf {
+    bookkeeping_dir(ledger_dir).join(LOANS_DIR)
+}
+
+fn loan_path(ledger_dir: &Path, id: &str) -> PathBuf {
+    loans_dir(ledger_dir).join(format!("{id}.json"))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_ledger_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-loans-{prefix}-{}-{now}.refreshmint",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn mortgage_input() -> UpsertLoanInput {
+        UpsertLoanInput {
+            id: None,
+            name: "Mortgage".to_string(),
+            gl_account: "Liabilities:Mortgage".to_string(),
+            interest_account: "Expenses:Mortgage Interest".to_string(),
+            escrow_account: Some("Expenses:Escrow".to_string()),
+            commodity: None,
+            principal: "200000".to_string(),
+            annual_rate: "6".to_string(),
+            term_months: 360,
+            first_payment_date: "2026-01-01".to_string(),
+            payment: None,
+            escrow: Some("400.00".to_string()),
+            payee: Some("loan servicing".to_string()),
+        }
+    }
+
+    fn payment_entry(date: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            "ACME LOAN SERVICING PMT".to_string(),
+            vec![],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    #[test]
+    fn schedule_retires_principal_with_level_payments() {
+        let root = temp_ledger_dir("schedule");
+        let loan = upsert_loan(&root, mortgage_input()).unwrap();
+        let schedule = amortization_schedule(&loan).unwrap();
+
+        assert_eq!(schedule.len(), 360);
+        assert_eq!(schedule[0].due_date, "2026-01-01");
+        assert_eq!(schedule[0].payment, "1199.10");
+        assert_eq!(schedule[0].interest, "1000.00");
+        assert_eq!(schedule[0].principal, "199.10");
+        assert_eq!(schedule[0].balance, "199800.90");
+        assert_eq!(schedule[1].due_date, "2026-02-01");
+        assert_eq!(schedule[359].balance, "0.00");
+
+        assert_eq!(list_loans(&root).unwrap().len(), 1);
+        delete_loan(&root, &loan.id).unwrap();
+        assert!(list_loans(&root).unwrap().is_empty());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn suggest_split_applies_extra_to_principal() {
+        let root = temp_ledger_dir("split");
+        let loan = upsert_loan(&root, mortgage_input()).unwrap();
+        let loans = vec![loan];
+
+        let suggestion =
+            suggest_loan_split(&loans, &payment_entry("2026-02-03", "-1699.10")).unwrap();
+        assert_eq!(suggestion.payment_number, 2);
+        assert_eq!(suggestion.extra_principal, "100.00");
+        let legs: Vec<(String, Option<String>)> = suggestion
+            .counterparts
+            .into_iter()
+            .map(|c| (c.account, c.amount))
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                (
+                    "Liabilities:Mortgage".to_string(),
+                    Some("300.10 USD".to_string())
+                ),
+                (
+                    "Expenses:Mortgage Interest".to_string(),
+                    Some("999.00 USD".to_string())
+                ),
+                (
+                    "Expenses:Escrow".to_string(),
+                    Some("400.00 USD".to_string())
+                ),
+            ]
+        );
+
+        let mut other = payment_entry("2026-02-03", "-1599.10");
+        other.description = "GROCERY".to_string();
+        assert!(suggest_loan_split(&loans, &other).is_none());
+        assert!(suggest_loan_split(&loans, &payment_entry("2025-11-01", "-1599.10")).is_none());
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn balance_report_compares_with_scraped_balance() {
+        let root = temp_ledger_dir("report");
+        let loan = upsert_loan(&root, mortgage_input()).unwrap();
+        fs::create_dir_all(root.join("logins").join("servicer")).unwrap();
+        fs::write(
+            crate::login_config::login_config_path(&root, "servicer"),
+            r#"{"accounts":{"mortgage":{"gl_account":"Liabilities:Mortgage"}}}"#,
+        )
+        .unwrap();
+        let snapshot = crate::balances::BalanceSnapshot {
+            date: "2026-02-15".to_string(),
+            kind: crate::balances::SnapshotKind::Balance,
+            symbol: None,
+            quantity: None,
+            price: None,
+            amount: "-199500.00".to_string(),
+            commodity: "USD".to_string(),
+            scrape_session_id: "s1".to_string(),
+            scraped_at: "2026-02-15T00:00:00Z".to_string(),
+        };
+        crate::balances::append_snapshots(
+            &crate::balances::login_account_balances_path(&root, "servicer", "mortgage"),
+            &[snapshot],
+        )
+        .unwrap();
+
+        let as_of = NaiveDate::from_ymd_opt(2026, 2, 20).unwrap();
+        let report = loan_balance_report(&root, &loan.id, as_of).unwrap();
+        assert_eq!(report.payments_due, 2);
+        assert_eq!(report.scheduled_balance, "199600.80");
+        assert_eq!(report.reported.as_ref().unwrap().date, "2026-02-15");
+        assert_eq!(report.drift.as_deref(), Some("-100.80"));
+        assert_eq!(report.next_payment.unwrap().number, 3);
+        let _ = fs::remove_dir_all(&root);
+    }
+}
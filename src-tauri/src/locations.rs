@@ -0,0 +1,485 @@
+//! Where transactions took place.
+//!
+//! Detail pages and some card exports give a merchant's address or
+//! coordinates. Extraction keeps them as entry tags with the schema below,
+//! posting copies those tags to the GL transaction, and
+//! [`report_spending_by_location`] totals spending per place from the GL so
+//! the UI can plot it on a map.
+//!
+//! hledger ends a tag value at a comma, so commas in GL tag values are
+//! written as semicolons and read back as commas.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::extract::ExtractedTransaction;
+use crate::hledger::Transaction;
+
+/// Tag holding the free-text address, such as `1 Main St, Seattle, WA`.
+pub const LOCATION_TAG: &str = "location";
+pub const CITY_TAG: &str = "city";
+/// State, province, or other subdivision.
+pub const REGION_TAG: &str = "region";
+pub const POSTAL_CODE_TAG: &str = "postalCode";
+pub const COUNTRY_TAG: &str = "country";
+/// Decimal degrees, -90 to 90.
+pub const LATITUDE_TAG: &str = "latitude";
+/// Decimal degrees, -180 to 180.
+pub const LONGITUDE_TAG: &str = "longitude";
+
+/// Every location tag, in the order they are written to the GL.
+pub const LOCATION_TAGS: [&str; 7] = [
+    LOCATION_TAG,
+    CITY_TAG,
+    REGION_TAG,
+    POSTAL_CODE_TAG,
+    COUNTRY_TAG,
+    LATITUDE_TAG,
+    LONGITUDE_TAG,
+];
+
+/// Other tag keys extractors use for location fields, compared in lowercase
+/// without spaces, `_`, or `-`.
+const TAG_ALIASES: &[(&str, &str)] = &[
+    ("address", LOCATION_TAG),
+    ("merchantaddress", LOCATION_TAG),
+    ("merchantcity", CITY_TAG),
+    ("state", REGION_TAG),
+    ("province", REGION_TAG),
+    ("merchantstate", REGION_TAG),
+    ("zip", POSTAL_CODE_TAG),
+    ("zipcode", POSTAL_CODE_TAG),
+    ("postcode", POSTAL_CODE_TAG),
+    ("merchantzip", POSTAL_CODE_TAG),
+    ("merchantcountry", COUNTRY_TAG),
+    ("lat", LATITUDE_TAG),
+    ("lng", LONGITUDE_TAG),
+    ("lon", LONGITUDE_TAG),
+];
+
+/// An entry's location tags.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TransactionLocation {
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub region: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
+    pub latitude: Option<f64>,
+    pub longitude: Option<f64>,
+}
+
+impl TransactionLocation {
+    /// The location in `tags`, or `None` when no location tag is set.
+    /// Coordinates count only as a valid latitude and longitude pair.
+    pub fn from_tags(tags: &[(String, String)]) -> Option<Self> {
+        let tag = |key: &str| {
+            tags.iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.trim().replace(';', ","))
+                .filter(|value| !value.is_empty())
+        };
+        let (latitude, longitude) = match (tag(LATITUDE_TAG), tag(LONGITUDE_TAG)) {
+            (Some(lat), Some(lon)) => match (parse_latitude(&lat), parse_longitude(&lon)) {
+                (Some(lat), Some(lon)) => (Some(lat), Some(lon)),
+                _ => (None, None),
+            },
+            _ => (None, None),
+        };
+        let location = Self {
+            address: tag(LOCATION_TAG),
+            city: tag(CITY_TAG),
+            region: tag(REGION_TAG),
+            postal_code: tag(POSTAL_CODE_TAG),
+            country: tag(COUNTRY_TAG),
+            latitude,
+            longitude,
+        };
+        (location != Self::default()).then_some(location)
+    }
+
+    /// A short name for the place: city, region, and country when known,
+    /// else the address, else the coordinates.
+    pub fn label(&self) -> String {
+        let parts: Vec<&str> = [&self.city, &self.region, &self.country]
+            .into_iter()
+            .filter_map(|part| part.as_deref())
+            .collect();
+        if !parts.is_empty() {
+            return parts.join(", ");
+        }
+        if let Some(address) = &self.address {
+            return address.clone();
+        }
+        match (self.latitude, self.longitude) {
+            (Some(lat), Some(lon)) => format!("{lat:.4}, {lon:.4}"),
+            _ => String::new(),
+        }
+    }
+
+    /// Key grouping transactions at the same place: coordinates rounded to
+    /// about 100 m, else the lowercased label.
+    fn group_key(&self) -> String {
+        match (self.latitude, self.longitude) {
+            (Some(lat), Some(lon)) => format!("{lat:.3},{lon:.3}"),
+            _ => self.label().to_lowercase(),
+        }
+    }
+}
+
+fn parse_latitude(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|lat| lat.is_finite() && lat.abs() <= 90.0)
+}
+
+fn parse_longitude(value: &str) -> Option<f64> {
+    value
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .filter(|lon| lon.is_finite() && lon.abs() <= 180.0)
+}
+
+/// Check a coordinate tag's value; `Err` says why it is unusable.
+pub fn validate_coordinate(key: &str, value: &str) -> Result<(), String> {
+    let valid = match key {
+        LATITUDE_TAG => parse_latitude(value).is_some(),
+        LONGITUDE_TAG => parse_longitude(value).is_some(),
+        _ => true,
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!(
+            "{key} is not a coordinate in decimal degrees: {value}"
+        ))
+    }
+}
+
+fn canonical_key(key: &str) -> Option<&'static str> {
+    let normalized: String = key
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    LOCATION_TAGS
+        .iter()
+        .find(|tag| tag.to_lowercase() == normalized)
+        .copied()
+        .or_else(|| {
+            TAG_ALIASES
+                .iter()
+                .find(|(alias, _)| *alias == normalized)
+                .map(|(_, tag)| *tag)
+        })
+}
+
+/// Rename location tags extractors set under other names (`zip`, `lat`,
+/// `Merchant City`, ...) to the schema's keys, trim their values, and drop
+/// empty values and coordinates outside decimal-degree ranges.
+pub fn normalize_transactions(transactions: &mut [ExtractedTransaction]) {
+    for txn in transactions {
+        let mut tags = Vec::with_capacity(txn.ttags.len());
+        for (key, value) in std::mem::take(&mut txn.ttags) {
+            let Some(canonical) = canonical_key(&key) else {
+                tags.push((key, value));
+                continue;
+            };
+            let value = value.trim().to_string();
+            if value.is_empty() || tags.iter().any(|(k, _)| k == canonical) {
+                continue;
+            }
+            if let Err(err) = validate_coordinate(canonical, &value) {
+                tracing::warn!("dropping location tag on {}: {err}", txn.tdescription);
+                continue;
+            }
+            tags.push((canonical.to_string(), value));
+        }
+        txn.ttags = tags;
+    }
+}
+
+/// GL comment lines carrying the location tags in `tags`.
+pub fn gl_comment_lines(tags: &[(String, String)]) -> Vec<String> {
+    LOCATION_TAGS
+        .iter()
+        .filter_map(|key| {
+            let value = tags.iter().find(|(k, _)| k == key)?.1.trim();
+            (!value.is_empty()).then(|| format!("    ; {key}: {}", value.replace(',', ";")))
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct LocationTotal {
+    pub commodity: String,
+    /// Sum of the transactions' expense postings, so spending is positive.
+    pub amount: f64,
+}
+
+/// Spending at one place.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LocationSpending {
+    pub key: String,
+    pub label: String,
+    /// The location as tagged on the place's most recent transaction.
+    pub location: TransactionLocation,
+    pub transaction_count: usize,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub totals: Vec<LocationTotal>,
+}
+
+/// Spending per place in the general journal between `since` and `until`
+/// (inclusive `YYYY-MM-DD` dates, both optional), most transactions first.
+/// Transactions without location tags are left out.
+pub fn report_spending_by_location(
+    ledger_dir: &Path,
+    since: Option<&str>,
+    until: Option<&str>,
+) -> io::Result<Vec<LocationSpending>> {
+    let gl_path = ledger_dir.join("general.journal");
+    if !gl_path.exists() {
+        return Ok(Vec::new());
+    }
+    let transactions = crate::ledger_open::run_hledger_print(&gl_path)?;
+    Ok(summarize_spending(&transactions, since, until))
+}
+
+fn summarize_spending(
+    transactions: &[Transaction],
+    since: Option<&str>,
+    until: Option<&str>,
+) -> Vec<LocationSpending> {
+    let mut places: BTreeMap<String, LocationSpending> = BTreeMap::new();
+    let mut totals: BTreeMap<String, BTreeMap<String, f64>> = BTreeMap::new();
+    for txn in transactions {
+        let date = txn.tdate.as_str();
+        if since.is_some_and(|since| date < since) || until.is_some_and(|until| date > until) {
+            continue;
+        }
+        let Some(location) = TransactionLocation::from_tags(&txn.ttags) else {
+            continue;
+        };
+        let key = location.group_key();
+        let place = places
+            .entry(key.clone())
+            .or_insert_with(|| LocationSpending {
+                key: key.clone(),
+                label: location.label(),
+                location: location.clone(),
+                transaction_count: 0,
+                first_seen: date.to_string(),
+                last_seen: date.to_string(),
+                totals: Vec::new(),
+            });
+        place.transaction_count += 1;
+        if date < place.first_seen.as_str() {
+            place.first_seen = date.to_string();
+        }
+        if date >= place.last_seen.as_str() {
+            place.last_seen = date.to_string();
+            place.label = location.label();
+            place.location = location;
+        }
+        let place_totals = totals.entry(key).or_default();
+        for posting in &txn.tpostings {
+            if posting.paccount != "Expenses" && !posting.paccount.starts_with("Expenses:") {
+                continue;
+            }
+            for amount in &posting.pamount {
+                *place_totals.entry(amount.acommodity.clone()).or_default() +=
+                    amount.aquantity.floating_point;
+            }
+        }
+    }
+
+    let mut spending: Vec<LocationSpending> = places
+        .into_iter()
+        .map(|(key, mut place)| {
+            place.totals = totals
+                .remove(&key)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(commodity, amount)| LocationTotal { commodity, amount })
+                .collect();
+            place
+        })
+        .collect();
+    spending.sort_by(|a, b| {
+        b.transaction_count
+            .cmp(&a.transaction_count)
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    spending
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::hledger::{Amount, DecimalRaw, Posting, PostingType, SourcePos, SourceSpan, Status};
+
+    fn tags(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn normalizes_extractor_tags_and_round_trips_through_gl_comments() {
+        let mut transactions = vec![ExtractedTransaction {
+            tdate: "2025-03-01".to_string(),
+            tstatus: "Cleared".to_string(),
+            tdescription: "SQ *BLUE BOTTLE".to_string(),
+            tcomment: String::new(),
+            ttags: tags(&[
+                ("Merchant City", " Oakland "),
+                ("state", "CA"),
+                ("zip", ""),
+                ("address", "300 Webster St, Oakland, CA"),
+                ("lat", "37.80"),
+                ("lng", "-222.27"),
+                ("bankId", "T1"),
+            ]),
+            tpostings: None,
+        }];
+        normalize_transactions(&mut transactions);
+        assert_eq!(
+            transactions[0].ttags,
+            tags(&[
+                ("city", "Oakland"),
+                ("region", "CA"),
+                ("location", "300 Webster St, Oakland, CA"),
+                ("latitude", "37.80"),
+                ("bankId", "T1"),
+            ])
+        );
+
+        let lines = gl_comment_lines(&transactions[0].ttags);
+        assert_eq!(
+            lines,
+            [
+                "    ; location: 300 Webster St; Oakland; CA",
+                "    ; city: Oakland",
+                "    ; region: CA",
+                "    ; latitude: 37.80",
+            ]
+        );
+        let gl_tags: Vec<(String, String)> = lines
+            .iter()
+            .filter_map(|line| line.trim().strip_prefix("; ")?.split_once(": "))
+            .map(|(k, v)| (k.to_string(), v.to_string()))
+            .collect();
+        let location = TransactionLocation::from_tags(&gl_tags).unwrap();
+        assert_eq!(
+            location.address.as_deref(),
+            Some("300 Webster St, Oakland, CA")
+        );
+        assert_eq!(location.latitude, None);
+        assert_eq!(location.label(), "Oakland, CA");
+        assert!(TransactionLocation::from_tags(&tags(&[("mcc", "5814")])).is_none());
+    }
+
+    fn txn(date: &str, ttags: &[(&str, &str)], account: &str, cents: i64) -> Transaction {
+        let pos = SourcePos {
+            source_name: String::new(),
+            source_line: 1,
+            source_column: 1,
+        };
+        let posting = |account: &str, cents: i64| Posting {
+            pdate: None,
+            pdate2: None,
+            pstatus: Status::Unmarked,
+            paccount: account.to_string(),
+            pamount: vec![Amount {
+                acommodity: "USD".to_string(),
+                aquantity: DecimalRaw {
+                    decimal_places: 2,
+                    decimal_mantissa: serde_json::Number::from(cents),
+                    floating_point: cents as f64 / 100.0,
+                },
+                astyle: None,
+                acost: None,
+                acostbasis: None,
+            }],
+            pcomment: String::new(),
+            ptype: PostingType::RegularPosting,
+            ptags: vec![],
+            pbalanceassertion: None,
+            ptransaction_index: None,
+            poriginal: None,
+        };
+        Transaction {
+            tindex: 1,
+            tprecedingcomment: String::new(),
+            tsourcepos: SourceSpan(pos.clone(), pos),
+            tdate: date.to_string(),
+            tdate2: None,
+            tstatus: Status::Cleared,
+            tcode: String::new(),
+            tdescription: "CARD PURCHASE".to_string(),
+            tcomment: String::new(),
+            ttags: tags(ttags),
+            tpostings: vec![posting("Liabilities:Card", -cents), posting(account, cents)],
+        }
+    }
+
+    #[test]
+    fn sums_expenses_per_place_within_dates() {
+        let cafe = [("latitude", "37.80441"), ("longitude", "-122.27112")];
+        let transactions = vec![
+            txn("2025-03-01", &cafe, "Expenses:Dining", 600),
+            txn(
+                "2025-03-08",
+                &[
+                    ("latitude", "37.80402"),
+                    ("longitude", "-122.27149"),
+                    ("city", "Oakland"),
+                ],
+                "Expenses:Dining",
+                450,
+            ),
+            txn(
+                "2025-03-05",
+                &[("city", "Portland"), ("region", "OR")],
+                "Expenses:Travel",
+                20000,
+            ),
+            txn("2025-03-06", &[], "Expenses:Groceries", 3000),
+            txn("2025-01-15", &cafe, "Expenses:Dining", 999),
+        ];
+        let spending = summarize_spending(&transactions, Some("2025-03-01"), None);
+        assert_eq!(spending.len(), 2);
+
+        let cafe = &spending[0];
+        assert_eq!(cafe.key, "37.804,-122.271");
+        assert_eq!(cafe.label, "Oakland");
+        assert_eq!(cafe.transaction_count, 2);
+        assert_eq!(
+            (cafe.first_seen.as_str(), cafe.last_seen.as_str()),
+            ("2025-03-01", "2025-03-08")
+        );
+        assert_eq!(
+            cafe.totals,
+            vec![LocationTotal {
+                commodity: "USD".to_string(),
+                amount: 10.5,
+            }]
+        );
+        assert_eq!(spending[1].label, "Portland, OR");
+        assert_eq!(spending[1].totals[0].amount, 200.0);
+    }
+}
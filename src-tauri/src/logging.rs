@@ -0,0 +1,326 @@
+//! Structured logging for the app and the CLI.
+//!
+//! Events from `tracing` (and from the `log` crate, via its bridge) go to
+//! stderr, to a daily-rotated file under the app data directory, and to an
+//! in-memory ring that backs `get_recent_logs`. Levels are configured per
+//! subsystem and saved next to the log files; `REFRESHMINT_LOG` (in
+//! `EnvFilter` syntax) overrides the saved levels at startup.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use std::io;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock};
+use tracing::field::{Field, Visit};
+use tracing::level_filters::LevelFilter;
+use tracing::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Layer, Registry};
+
+/// Subsystems with their own level, and the module each one covers.
+pub const SUBSYSTEMS: [(&str, &str); 4] = [
+    ("scrape", "app_lib::scrape"),
+    ("extract", "app_lib::extract"),
+    ("post", "app_lib::post"),
+    ("dedup", "app_lib::dedup"),
+];
+
+/// Events kept in memory for `get_recent_logs`.
+const RECENT_LOGS_CAPACITY: usize = 2000;
+/// Rotated log files kept on disk.
+const MAX_LOG_FILES: usize = 14;
+const LEVELS_FILE: &str = "log-levels.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LogLevels {
+    /// Level for everything without a subsystem override.
+    pub default: String,
+    /// Subsystem name to level, e.g. `"scrape": "debug"`.
+    #[serde(default)]
+    pub subsystems: BTreeMap<String, String>,
+}
+
+impl Default for LogLevels {
+    fn default() -> Self {
+        Self {
+            default: "info".to_string(),
+            subsystems: BTreeMap::new(),
+        }
+    }
+}
+
+/// One captured event.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LogRecord {
+    /// RFC 3339 local time.
+    pub timestamp: String,
+    pub level: String,
+    /// Subsystem the event belongs to, if any.
+    pub subsystem: Option<String>,
+    pub target: String,
+    pub message: String,
+    /// Structured fields other than the message.
+    pub fields: BTreeMap<String, String>,
+}
+
+type RecentLogs = Arc<Mutex<VecDeque<LogRecord>>>;
+
+fn recent_logs_buffer() -> &'static RecentLogs {
+    static RECENT: OnceLock<RecentLogs> = OnceLock::new();
+    RECENT.get_or_init(|| Arc::new(Mutex::new(VecDeque::with_capacity(RECENT_LOGS_CAPACITY))))
+}
+
+static FILTER_HANDLE: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// `<data dir>/refreshmint/logs`, alongside the browser profiles.
+pub fn log_dir() -> io::Result<PathBuf> {
+    let data_dir =
+        dirs::data_dir().ok_or_else(|| io::Error::other("could not determine data directory"))?;
+    Ok(data_dir.join("refreshmint").join("logs"))
+}
+
+/// Install the global subscriber. Later calls do nothing, so both the CLI
+/// entry point and the app can call it.
+pub fn init() {
+    if FILTER_HANDLE.get().is_some() {
+        return;
+    }
+    let dir = log_dir().ok();
+    let filter = match std::env::var("REFRESHMINT_LOG") {
+        Ok(directives) if !directives.trim().is_empty() => EnvFilter::new(directives),
+        _ => {
+            let levels = dir
+                .as_ref()
+                .and_then(|dir| read_levels(dir).ok())
+                .unwrap_or_default();
+            EnvFilter::new(filter_directives(&levels).unwrap_or_else(|_| "info".to_string()))
+        }
+    };
+    let (filter, handle) = reload::Layer::new(filter);
+
+    let file_layer = dir.and_then(|dir| {
+        tracing_appender::rolling::RollingFileAppender::builder()
+            .rotation(tracing_appender::rolling::Rotation::DAILY)
+            .filename_prefix("refreshmint")
+            .filename_suffix("log")
+            .max_log_files(MAX_LOG_FILES)
+            .build(dir)
+            .ok()
+            .map(|appender| {
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(appender)
+            })
+    });
+
+    let installed = tracing_subscriber::registry()
+        .with(filter)
+        .with(tracing_subscriber::fmt::layer().with_writer(io::stderr))
+        .with(file_layer)
+        .with(RecentLogsLayer {
+            buffer: recent_logs_buffer().clone(),
+            capacity: RECENT_LOGS_CAPACITY,
+        })
+        .try_init()
+        .is_ok();
+    if installed {
+        let _ = FILTER_HANDLE.set(handle);
+    }
+}
+
+/// Saved levels, or the defaults when none have been saved.
+pub fn log_levels() -> io::Result<LogLevels> {
+    read_levels(&log_dir()?)
+}
+
+/// Validate, save, and apply new levels.
+pub fn set_log_levels(levels: LogLevels) -> io::Result<LogLevels> {
+    let directives = filter_directives(&levels)?;
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let json = serde_json::to_string_pretty(&levels).map_err(io::Error::other)?;
+    std::fs::write(dir.join(LEVELS_FILE), json)?;
+    if let Some(handle) = FILTER_HANDLE.get() {
+        handle
+            .reload(EnvFilter::new(directives))
+            .map_err(io::Error::other)?;
+    }
+    Ok(levels)
+}
+
+/// The newest `limit` captured events at or above `min_level`, oldest
+/// first.
+pub fn recent_logs(limit: usize, min_level: Option<&str>) -> io::Result<Vec<LogRecord>> {
+    let min_level = match min_level {
+        Some(level) => Some(parse_level(level)?),
+        None => None,
+    };
+    let buffer = recent_logs_buffer()
+        .lock()
+        .map_err(|_| io::Error::other("recent log buffer is poisoned"))?;
+    let mut records: Vec<LogRecord> = buffer
+        .iter()
+        .rev()
+        .filter(|record| {
+            min_level.map_or(true, |min| {
+                tracing::Level::from_str(&record.level).is_ok_and(|level| min >= level)
+            })
+        })
+        .take(limit)
+        .cloned()
+        .collect();
+    records.reverse();
+    Ok(records)
+}
+
+fn read_levels(dir: &std::path::Path) -> io::Result<LogLevels> {
+    match std::fs::read_to_string(dir.join(LEVELS_FILE)) {
+        Ok(text) => serde_json::from_str(&text).map_err(io::Error::other),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(LogLevels::default()),
+        Err(err) => Err(err),
+    }
+}
+
+fn parse_level(level: &str) -> io::Result<LevelFilter> {
+    LevelFilter::from_str(level.trim())
+        .map_err(|_| io::Error::other(format!("unknown log level: {level}")))
+}
+
+/// `EnvFilter` directives for `levels`, e.g. `info,app_lib::scrape=debug`.
+fn filter_directives(levels: &LogLevels) -> io::Result<String> {
+    let mut directives = vec![parse_level(&levels.default)?.to_string()];
+    for (subsystem, level) in &levels.subsystems {
+        let module = SUBSYSTEMS
+            .iter()
+            .find(|(name, _)| name == subsystem)
+            .map(|(_, module)| *module)
+            .ok_or_else(|| io::Error::other(format!("unknown log subsystem: {subsystem}")))?;
+        directives.push(format!("{module}={}", parse_level(level)?));
+    }
+    Ok(directives.join(","))
+}
+
+fn subsystem_of(target: &str) -> Option<String> {
+    SUBSYSTEMS
+        .iter()
+        .find(|(_, module)| {
+            target
+                .strip_prefix(module)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+        .map(|(name, _)| (*name).to_string())
+}
+
+/// Keeps the newest events in memory.
+struct RecentLogsLayer {
+    buffer: RecentLogs,
+    capacity: usize,
+}
+
+impl<S: Subscriber> Layer<S> for RecentLogsLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        let record = LogRecord {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            level: metadata.level().to_string(),
+            subsystem: subsystem_of(metadata.target()),
+            target: metadata.target().to_string(),
+            message: visitor.message,
+            fields: visitor.fields,
+        };
+        if let Ok(mut buffer) = self.buffer.lock() {
+            if buffer.len() >= self.capacity {
+                buffer.pop_front();
+            }
+            buffer.push_back(record);
+        }
+    }
+}
+
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: BTreeMap<String, String>,
+}
+
+impl Visit for FieldVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields
+                .insert(field.name().to_string(), value.to_string());
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{value:?}");
+        } else if !field.name().starts_with("log.") {
+            self.fields
+                .insert(field.name().to_string(), format!("{value:?}"));
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn directives_map_subsystems_to_modules() {
+        let mut levels = LogLevels::default();
+        levels
+            .subsystems
+            .insert("scrape".to_string(), "debug".to_string());
+        levels
+            .subsystems
+            .insert("dedup".to_string(), "WARN".to_string());
+        assert_eq!(
+            filter_directives(&levels).unwrap(),
+            "info,app_lib::dedup=warn,app_lib::scrape=debug"
+        );
+
+        levels
+            .subsystems
+            .insert("network".to_string(), "debug".to_string());
+        assert!(filter_directives(&levels).is_err());
+        let levels = LogLevels {
+            default: "loud".to_string(),
+            subsystems: BTreeMap::new(),
+        };
+        assert!(filter_directives(&levels).is_err());
+    }
+
+    #[test]
+    fn ring_keeps_newest_events_with_fields() {
+        let buffer = RecentLogs::default();
+        let subscriber = tracing_subscriber::registry().with(RecentLogsLayer {
+            buffer: buffer.clone(),
+            capacity: 3,
+        });
+        tracing::subscriber::with_default(subscriber, || {
+            for row in 0..5 {
+                tracing::info!(target: "app_lib::extract", row, "extracted row");
+            }
+            tracing::warn!(target: "app_lib::scrape::browser", "handler error");
+        });
+        let records: Vec<LogRecord> = buffer.lock().unwrap().iter().cloned().collect();
+        assert_eq!(records.len(), 3);
+        assert_eq!(records[0].fields.get("row").map(String::as_str), Some("3"));
+        assert_eq!(records[0].message, "extracted row");
+        assert_eq!(records[0].subsystem.as_deref(), Some("extract"));
+        assert_eq!(records[2].level, "WARN");
+        assert_eq!(records[2].subsystem.as_deref(), Some("scrape"));
+        assert_eq!(subsystem_of("app_lib::scraper"), None);
+    }
+}
@@ -1,25 +1,248 @@
+use chrono::{Datelike, NaiveDate};
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
+use std::io;
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 
 /// Per-login-account configuration: maps a label to a GL account.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct LoginAccountConfig {
     #[serde(default, skip_serializing_if = "Option::is_none", alias = "gl_account")]
     pub gl_account: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account_type: Option<AccountType>,
+    /// The institution's identifiers for this account, such as an OFX
+    /// `ACCTID` or an IBAN. Transactions tagged with one of them are routed
+    /// here even when extracted from another label's document.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    #[ts(as = "Option<Vec<String>>", optional)]
+    pub account_ids: Vec<String>,
+    /// Account for the counterpart posting of extracted single-sided
+    /// entries, replacing the ledger's `Equity:Staging:<login>:<label>`
+    /// default.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub counterpart_account: Option<String>,
+    /// First day (`YYYY-MM-DD`) the ledger's history of this account covers.
+    /// Everything before it is carried in an opening-balance transaction.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub coverage_start: Option<String>,
+}
+
+/// What kind of account a login label is. The type tunes how extracted
+/// entries are deduplicated and how the account is treated in reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum AccountType {
+    Bank,
+    Card,
+    Brokerage,
+    /// Employer retirement plans such as a 401(k) or 403(b), and IRAs.
+    Retirement,
+    /// HSA and FSA accounts from benefits portals.
+    Benefits,
+}
+
+impl AccountType {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            AccountType::Bank => "bank",
+            AccountType::Card => "card",
+            AccountType::Brokerage => "brokerage",
+            AccountType::Retirement => "retirement",
+            AccountType::Benefits => "benefits",
+        }
+    }
+
+    /// Whether the account's balance counts as cash in cash-flow reports.
+    /// Retirement and benefits balances can't be spent freely.
+    pub fn in_cashflow(self) -> bool {
+        !matches!(self, AccountType::Retirement | AccountType::Benefits)
+    }
+
+    /// Whether entries can appear pending and settle later at a different
+    /// amount (tips, holds). Plan and brokerage portals post final amounts,
+    /// but trades and contributions may be dated by trade or settlement day.
+    pub fn settles_with_amount_changes(self) -> bool {
+        matches!(self, AccountType::Bank | AccountType::Card)
+    }
+}
+
+impl std::str::FromStr for AccountType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value.trim() {
+            "bank" => Ok(AccountType::Bank),
+            "card" => Ok(AccountType::Card),
+            "brokerage" => Ok(AccountType::Brokerage),
+            "retirement" => Ok(AccountType::Retirement),
+            "benefits" => Ok(AccountType::Benefits),
+            other => Err(format!(
+                "unknown account type '{other}'; expected bank, card, brokerage, retirement, or benefits"
+            )),
+        }
+    }
 }
 
 /// Per-login configuration stored in `logins/<login_name>/config.json`.
-#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
 pub struct LoginConfig {
     #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub extension: Option<String>,
     #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
     pub accounts: BTreeMap<String, LoginAccountConfig>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub institution: Option<InstitutionInfo>,
+    /// Rules that label documents the driver saved without one.
+    #[serde(default, rename = "labelRules", skip_serializing_if = "Vec::is_empty")]
+    pub label_rules: Vec<crate::document_labels::LabelRule>,
+}
+
+/// What the user knows about the institution behind a login.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct InstitutionInfo {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub display_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub website: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub support_phone: Option<String>,
+    /// Day of the month the card statement closes (1-31). Months shorter
+    /// than the day close on their last day.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub statement_cycle_day: Option<u32>,
+    /// Last four digits of each card on the login.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub card_last4: Vec<String>,
+    /// Free-form notes on how to get in, such as who gets the 2FA codes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub access_notes: Option<String>,
+}
+
+impl InstitutionInfo {
+    /// Trim the fields, drop blank ones, and check the cycle day and card
+    /// digits.
+    pub fn normalized(self) -> Result<Self, String> {
+        let clean = |value: Option<String>| {
+            value
+                .map(|value| value.trim().to_string())
+                .filter(|value| !value.is_empty())
+        };
+        if let Some(day) = self.statement_cycle_day {
+            if !(1..=31).contains(&day) {
+                return Err(format!(
+                    "statement cycle day must be between 1 and 31 (got {day})"
+                ));
+            }
+        }
+        let mut card_last4 = Vec::new();
+        for digits in self.card_last4 {
+            let digits = digits.trim().to_string();
+            if digits.len() != 4 || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+                return Err(format!("card last-4 must be four digits (got '{digits}')"));
+            }
+            if !card_last4.contains(&digits) {
+                card_last4.push(digits);
+            }
+        }
+        Ok(Self {
+            display_name: clean(self.display_name),
+            website: clean(self.website),
+            support_phone: clean(self.support_phone),
+            statement_cycle_day: self.statement_cycle_day,
+            card_last4,
+            access_notes: clean(self.access_notes),
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// GL account to propose for one of the login's labels, named after the
+    /// institution and, for a single card, its last four digits.
+    pub fn suggested_gl_account(&self, account_type: Option<AccountType>, label: &str) -> String {
+        let root = match account_type {
+            Some(AccountType::Card) => "Liabilities:Credit Cards",
+            Some(AccountType::Brokerage) => "Assets:Investments",
+            Some(AccountType::Retirement) => "Assets:Retirement",
+            Some(AccountType::Benefits) => "Assets:Benefits",
+            Some(AccountType::Bank) | None => "Assets:Bank",
+        };
+        let institution = self
+            .display_name
+            .as_deref()
+            .map(|name| name.replace(':', " "))
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        let leaf = match (account_type, self.card_last4.as_slice()) {
+            (Some(AccountType::Card), [digits]) => format!("{} {digits}", title_case(label)),
+            _ => title_case(label),
+        };
+        match institution {
+            Some(institution) => format!("{root}:{institution}:{leaf}"),
+            None => format!("{root}:{leaf}"),
+        }
+    }
+
+    /// Whether a statement closes after `from` and on or before `to`.
+    pub fn statement_closes_between(&self, from: NaiveDate, to: NaiveDate) -> bool {
+        let Some(day) = self.statement_cycle_day else {
+            return false;
+        };
+        let mut date = from;
+        while date < to {
+            date = match date.succ_opt() {
+                Some(next) => next,
+                None => return false,
+            };
+            if date.day() == day.min(days_in_month(date)) {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+fn days_in_month(date: NaiveDate) -> u32 {
+    let (year, month) = if date.month() == 12 {
+        (date.year() + 1, 1)
+    } else {
+        (date.year(), date.month() + 1)
+    };
+    NaiveDate::from_ymd_opt(year, month, 1)
+        .and_then(|first| first.pred_opt())
+        .map_or(31, |last| last.day())
+}
+
+fn title_case(label: &str) -> String {
+    label
+        .split(['-', '_', '.'])
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            chars.next().map_or_else(String::new, |first| {
+                first.to_uppercase().chain(chars).collect()
+            })
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
 }
 
 /// Validate a label used as a sub-account directory name.
@@ -123,52 +346,11 @@ pub fn write_login_config(
     config: &LoginConfig,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let path = login_config_path(ledger_dir, login_name);
-    let parent = path
-        .parent()
-        .ok_or_else(|| std::io::Error::other("config path has no parent"))?;
-    std::fs::create_dir_all(parent)?;
-
     let json = serde_json::to_string_pretty(config)?;
-    let nanos = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_nanos();
-    let temp_path = parent.join(format!(".config.json.tmp-{}-{nanos}", std::process::id()));
-    {
-        let mut file = std::fs::OpenOptions::new()
-            .write(true)
-            .create_new(true)
-            .open(&temp_path)?;
-        file.write_all(json.as_bytes())?;
-        file.sync_all()?;
-    }
-    if let Err(err) = replace_file(&temp_path, &path) {
-        let _ = std::fs::remove_file(&temp_path);
-        return Err(err.into());
-    }
-    if let Ok(dir) = std::fs::File::open(parent) {
-        let _ = dir.sync_all();
-    }
+    crate::atomic_file::write_atomic(&path, json.as_bytes())?;
     Ok(())
 }
 
-/// Atomically replace a file via rename, with a Windows fallback.
-fn replace_file(temp_path: &Path, path: &Path) -> std::io::Result<()> {
-    match std::fs::rename(temp_path, path) {
-        Ok(()) => Ok(()),
-        Err(err) => {
-            #[cfg(windows)]
-            {
-                if err.kind() == std::io::ErrorKind::AlreadyExists {
-                    std::fs::remove_file(path)?;
-                    return std::fs::rename(temp_path, path);
-                }
-            }
-            Err(err)
-        }
-    }
-}
-
 /// List all login names by scanning the `logins/` directory.
 pub fn list_logins(ledger_dir: &Path) -> io::Result<Vec<String>> {
     let logins_dir = ledger_dir.join("logins");
@@ -195,17 +377,39 @@ pub fn list_logins(ledger_dir: &Path) -> io::Result<Vec<String>> {
     Ok(names)
 }
 
+/// List the labels under `logins/<login>/accounts/`.
+pub fn list_login_labels(ledger_dir: &Path, login_name: &str) -> io::Result<Vec<String>> {
+    let accounts_dir = ledger_dir.join("logins").join(login_name).join("accounts");
+    if !accounts_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let mut labels = Vec::new();
+    for entry in std::fs::read_dir(&accounts_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some(name) = entry.file_name().to_str() {
+            labels.push(name.to_string());
+        }
+    }
+    labels.sort();
+    Ok(labels)
+}
+
 /// A conflict entry for GL account uniqueness violations.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct GlAccountConflictEntry {
     pub login_name: String,
     pub label: String,
 }
 
 /// A GL account conflict: multiple login accounts map to the same GL account.
-#[derive(Debug, Clone, serde::Serialize)]
+#[derive(Debug, Clone, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct GlAccountConflict {
     pub gl_account: String,
     pub entries: Vec<GlAccountConflictEntry>,
@@ -240,6 +444,61 @@ pub fn check_gl_account_uniqueness(
     Ok(())
 }
 
+/// Find the `(login, label)` pair mapped to `gl_account`, if any.
+pub fn find_login_account_for_gl_account(
+    ledger_dir: &Path,
+    gl_account: &str,
+) -> Option<(String, String)> {
+    let logins = list_logins(ledger_dir).unwrap_or_default();
+    logins.into_iter().find_map(|login| {
+        let config = read_login_config(ledger_dir, &login);
+        config
+            .accounts
+            .into_iter()
+            .find(|(_, acct_config)| acct_config.gl_account.as_deref() == Some(gl_account))
+            .map(|(label, _)| (login, label))
+    })
+}
+
+/// The account type configured for a login label, if any.
+pub fn login_account_type(ledger_dir: &Path, login_name: &str, label: &str) -> Option<AccountType> {
+    read_login_config(ledger_dir, login_name)
+        .accounts
+        .get(label)
+        .and_then(|acct_config| acct_config.account_type)
+}
+
+/// Store institution metadata for a login, or clear it with `None`.
+pub fn set_login_institution(
+    ledger_dir: &Path,
+    login_name: &str,
+    institution: Option<InstitutionInfo>,
+) -> Result<Option<InstitutionInfo>, Box<dyn std::error::Error + Send + Sync>> {
+    let institution = institution
+        .map(InstitutionInfo::normalized)
+        .transpose()?
+        .filter(|info| !info.is_empty());
+    let mut config = read_login_config(ledger_dir, login_name);
+    config.institution = institution.clone();
+    write_login_config(ledger_dir, login_name, &config)?;
+    Ok(institution)
+}
+
+/// GL accounts mapped by login labels whose type is kept out of cash-flow
+/// reports.
+pub fn gl_accounts_excluded_from_cashflow(ledger_dir: &Path) -> Vec<String> {
+    let logins = list_logins(ledger_dir).unwrap_or_default();
+    let mut accounts: Vec<String> = logins
+        .iter()
+        .flat_map(|login| read_login_config(ledger_dir, login).accounts.into_values())
+        .filter(|acct_config| acct_config.account_type.is_some_and(|t| !t.in_cashflow()))
+        .filter_map(|acct_config| acct_config.gl_account)
+        .collect();
+    accounts.sort();
+    accounts.dedup();
+    accounts
+}
+
 /// Scan all login configs and return a list of GL account conflicts.
 pub fn find_gl_account_conflicts(ledger_dir: &Path) -> Vec<GlAccountConflict> {
     let logins = list_logins(ledger_dir).unwrap_or_default();
@@ -270,21 +529,24 @@ pub fn find_gl_account_conflicts(ledger_dir: &Path) -> Vec<GlAccountConflict> {
         .collect()
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ts_rs::TS)]
 #[serde(tag = "kind", rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "LockMetadataResource")]
 pub enum LockResource {
     Login { login_name: String },
     Gl,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct LockMetadata {
     pub version: u8,
     pub owner: String,
     pub purpose: String,
     pub started_at: String,
     #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional = nullable)]
     pub pid: Option<u32>,
     pub resource: LockResource,
 }
@@ -315,8 +577,9 @@ impl LockMetadata {
     }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct LockStatus {
     pub locked: bool,
     pub metadata: Option<LockMetadata>,
@@ -528,6 +791,7 @@ pub fn resolve_login_extension(ledger_dir: &Path, login_name: &str) -> Result<St
 mod tests {
     use super::*;
     use std::fs;
+    use std::time::{SystemTime, UNIX_EPOCH};
 
     fn create_temp_dir(prefix: &str) -> PathBuf {
         let now = SystemTime::now()
@@ -540,6 +804,48 @@ mod tests {
         dir
     }
 
+    #[test]
+    fn institution_info_normalizes_and_names_gl_accounts() {
+        let info = InstitutionInfo {
+            display_name: Some(" Chase ".to_string()),
+            website: Some("  ".to_string()),
+            card_last4: vec!["1234".to_string(), " 1234".to_string()],
+            statement_cycle_day: Some(31),
+            ..Default::default()
+        }
+        .normalized()
+        .unwrap();
+        assert_eq!(info.display_name.as_deref(), Some("Chase"));
+        assert_eq!(info.website, None);
+        assert_eq!(info.card_last4, vec!["1234".to_string()]);
+        assert_eq!(
+            info.suggested_gl_account(Some(AccountType::Card), "sapphire-reserve"),
+            "Liabilities:Credit Cards:Chase:Sapphire Reserve 1234"
+        );
+        assert_eq!(
+            info.suggested_gl_account(None, "checking"),
+            "Assets:Bank:Chase:Checking"
+        );
+
+        // A day-31 cycle closes on the last day of shorter months.
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        assert!(info.statement_closes_between(date("2025-02-27"), date("2025-03-01")));
+        assert!(!info.statement_closes_between(date("2025-03-01"), date("2025-03-30")));
+
+        assert!(InstitutionInfo {
+            statement_cycle_day: Some(0),
+            ..Default::default()
+        }
+        .normalized()
+        .is_err());
+        assert!(InstitutionInfo {
+            card_last4: vec!["12a4".to_string()],
+            ..Default::default()
+        }
+        .normalized()
+        .is_err());
+    }
+
     #[test]
     fn validate_label_accepts_valid_names() {
         assert!(validate_label("checking").is_ok());
@@ -603,12 +909,27 @@ mod tests {
             "checking".to_string(),
             LoginAccountConfig {
                 gl_account: Some("Assets:Chase:Checking".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
+        );
+        accounts.insert(
+            "cc".to_string(),
+            LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
             },
         );
-        accounts.insert("cc".to_string(), LoginAccountConfig { gl_account: None });
         let config = LoginConfig {
             extension: Some("chase-driver".to_string()),
             accounts,
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase-personal", &config).unwrap();
         let loaded = read_login_config(&dir, "chase-personal");
@@ -654,10 +975,16 @@ mod tests {
                     "checking".to_string(),
                     LoginAccountConfig {
                         gl_account: Some("Assets:Chase:Checking".to_string()),
+                        account_type: None,
+                        account_ids: Vec::new(),
+                        counterpart_account: None,
+                        coverage_start: None,
                     },
                 );
                 m
             },
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase", &config).unwrap();
 
@@ -667,6 +994,54 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn retirement_and_benefits_accounts_are_excluded_from_cashflow() {
+        let dir = create_temp_dir("login-account-types");
+        let account = |gl: &str, account_type| LoginAccountConfig {
+            gl_account: Some(gl.to_string()),
+            account_type,
+            account_ids: Vec::new(),
+            counterpart_account: None,
+            coverage_start: None,
+        };
+        let config = LoginConfig {
+            extension: Some("fidelity-netbenefits".to_string()),
+            accounts: BTreeMap::from([
+                (
+                    "401k".to_string(),
+                    account(
+                        "Assets:Retirement:Fidelity 401k",
+                        Some(AccountType::Retirement),
+                    ),
+                ),
+                (
+                    "hsa".to_string(),
+                    account("Assets:Benefits:HSA", Some(AccountType::Benefits)),
+                ),
+                (
+                    "cash".to_string(),
+                    account("Assets:Fidelity:Cash", Some(AccountType::Brokerage)),
+                ),
+                ("other".to_string(), account("Assets:Fidelity:Other", None)),
+            ]),
+            institution: None,
+            label_rules: Vec::new(),
+        };
+        write_login_config(&dir, "fidelity", &config).unwrap();
+
+        assert_eq!(
+            gl_accounts_excluded_from_cashflow(&dir),
+            vec!["Assets:Benefits:HSA", "Assets:Retirement:Fidelity 401k"]
+        );
+        assert_eq!(
+            login_account_type(&dir, "fidelity", "401k"),
+            Some(AccountType::Retirement)
+        );
+        assert_eq!(login_account_type(&dir, "fidelity", "other"), None);
+        assert_eq!("benefits".parse::<AccountType>(), Ok(AccountType::Benefits));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn gl_account_uniqueness_allows_self_update() {
         let dir = create_temp_dir("login-gl-self");
@@ -678,10 +1053,16 @@ mod tests {
                     "checking".to_string(),
                     LoginAccountConfig {
                         gl_account: Some("Assets:Chase:Checking".to_string()),
+                        account_type: None,
+                        account_ids: Vec::new(),
+                        counterpart_account: None,
+                        coverage_start: None,
                     },
                 );
                 m
             },
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase", &config).unwrap();
 
@@ -700,9 +1081,20 @@ mod tests {
             extension: Some("chase-driver".to_string()),
             accounts: {
                 let mut m = BTreeMap::new();
-                m.insert("cc".to_string(), LoginAccountConfig { gl_account: None });
+                m.insert(
+                    "cc".to_string(),
+                    LoginAccountConfig {
+                        gl_account: None,
+                        account_type: None,
+                        account_ids: Vec::new(),
+                        counterpart_account: None,
+                        coverage_start: None,
+                    },
+                );
                 m
             },
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase", &config).unwrap();
 
@@ -722,10 +1114,16 @@ mod tests {
                     "checking".to_string(),
                     LoginAccountConfig {
                         gl_account: Some("Assets:Checking".to_string()),
+                        account_type: None,
+                        account_ids: Vec::new(),
+                        counterpart_account: None,
+                        coverage_start: None,
                     },
                 );
                 m
             },
+            institution: None,
+            label_rules: Vec::new(),
         };
         let config2 = LoginConfig {
             extension: Some("other-driver".to_string()),
@@ -735,10 +1133,16 @@ mod tests {
                     "main".to_string(),
                     LoginAccountConfig {
                         gl_account: Some("Assets:Checking".to_string()),
+                        account_type: None,
+                        account_ids: Vec::new(),
+                        counterpart_account: None,
+                        coverage_start: None,
                     },
                 );
                 m
             },
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase", &config1).unwrap();
         write_login_config(&dir, "other", &config2).unwrap();
@@ -750,6 +1154,41 @@ mod tests {
         let _ = fs::remove_dir_all(&dir);
     }
 
+    #[test]
+    fn find_login_account_for_gl_account_returns_mapping() {
+        let dir = create_temp_dir("login-gl-lookup");
+        let config = LoginConfig {
+            extension: None,
+            accounts: {
+                let mut m = BTreeMap::new();
+                m.insert(
+                    "brokerage".to_string(),
+                    LoginAccountConfig {
+                        gl_account: Some("Assets:Brokerage".to_string()),
+                        account_type: None,
+                        account_ids: Vec::new(),
+                        counterpart_account: None,
+                        coverage_start: None,
+                    },
+                );
+                m
+            },
+            institution: None,
+            label_rules: Vec::new(),
+        };
+        write_login_config(&dir, "broker", &config).unwrap();
+
+        assert_eq!(
+            find_login_account_for_gl_account(&dir, "Assets:Brokerage"),
+            Some(("broker".to_string(), "brokerage".to_string()))
+        );
+        assert_eq!(
+            find_login_account_for_gl_account(&dir, "Assets:Other"),
+            None
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+
     #[test]
     fn acquire_login_lock_succeeds() {
         let dir = create_temp_dir("login-lock");
@@ -873,6 +1312,8 @@ mod tests {
         let config = LoginConfig {
             extension: Some("saved-ext".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase", &config).unwrap();
 
@@ -912,6 +1353,8 @@ mod tests {
         let config = LoginConfig {
             extension: Some("chase-driver".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         write_login_config(&dir, "chase", &config).unwrap();
 
@@ -0,0 +1,214 @@
+//! Merchant category codes (MCCs), the four-digit codes card networks
+//! assign to merchants.
+//!
+//! Card exports often carry an MCC column. Extraction normalizes it into an
+//! `mcc:` tag on the entry, posting copies the tag to the GL transaction so
+//! reports can break spending down by MCC (`hledger bal --pivot mcc`), and
+//! categorization adds a `mccCategory:<hint>` token from the bundled table
+//! below, so a merchant seen for the first time can still get a suggestion.
+
+use crate::extract::ExtractedTransaction;
+
+/// Tag holding an entry's normalized four-digit MCC.
+pub const MCC_TAG: &str = "mcc";
+
+/// Tag keys extractors use for an MCC, compared in lowercase without spaces,
+/// `_`, or `-`.
+const MCC_KEYS: &[&str] = &["mcc", "mcccode", "merchantcategorycode", "merchantcategory"];
+
+/// MCC ranges and the spending category each hints at. Each category is
+/// seeded as `Expenses:<category>` in the classifier.
+const MCC_HINTS: &[(u16, u16, &str)] = &[
+    (742, 742, "Pets"),
+    (3000, 3299, "Travel"),
+    (3351, 3441, "Travel"),
+    (3501, 3999, "Travel"),
+    (4111, 4112, "Transportation"),
+    (4121, 4121, "Transportation"),
+    (4131, 4131, "Transportation"),
+    (4511, 4511, "Travel"),
+    (4722, 4722, "Travel"),
+    (4784, 4784, "Transportation"),
+    (4812, 4814, "Utilities"),
+    (4899, 4899, "Entertainment"),
+    (4900, 4900, "Utilities"),
+    (5200, 5200, "Shopping"),
+    (5251, 5251, "Shopping"),
+    (5300, 5311, "Shopping"),
+    (5411, 5411, "Groceries"),
+    (5422, 5422, "Groceries"),
+    (5441, 5462, "Groceries"),
+    (5499, 5499, "Groceries"),
+    (5541, 5542, "Gas"),
+    (5552, 5552, "Gas"),
+    (5651, 5651, "Shopping"),
+    (5691, 5699, "Shopping"),
+    (5732, 5735, "Shopping"),
+    (5812, 5814, "Dining"),
+    (5912, 5912, "Healthcare"),
+    (5942, 5942, "Shopping"),
+    (5945, 5945, "Shopping"),
+    (5960, 5960, "Insurance"),
+    (5995, 5995, "Pets"),
+    (5999, 5999, "Shopping"),
+    (6300, 6300, "Insurance"),
+    (6513, 6513, "Rent"),
+    (7011, 7011, "Travel"),
+    (7230, 7230, "Personal Care"),
+    (7298, 7298, "Personal Care"),
+    (7512, 7512, "Travel"),
+    (7523, 7523, "Transportation"),
+    (7832, 7841, "Entertainment"),
+    (7922, 7922, "Entertainment"),
+    (7941, 7941, "Entertainment"),
+    (7991, 7999, "Entertainment"),
+    (8011, 8099, "Healthcare"),
+    (8211, 8299, "Education"),
+    (9311, 9311, "Taxes"),
+];
+
+/// An entry's MCC and the category the bundled table gives it.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct MccHint {
+    pub code: String,
+    pub category: String,
+}
+
+/// The four-digit MCC in `raw`, such as `5411` from `5411`, `MCC 5411`, or
+/// `5411 - Grocery Stores`. Shorter codes are zero-padded (`742` is
+/// `0742`).
+pub fn normalize_mcc(raw: &str) -> Option<String> {
+    let raw = raw.trim();
+    let rest = match raw.get(..3) {
+        Some(prefix) if prefix.eq_ignore_ascii_case("mcc") => {
+            raw[3..].trim_start_matches(|c: char| matches!(c, ' ' | ':' | '#' | '-'))
+        }
+        _ => raw,
+    };
+    let digits: String = rest.chars().take_while(char::is_ascii_digit).collect();
+    if digits.is_empty() || digits.len() > 4 {
+        return None;
+    }
+    Some(format!("{digits:0>4}"))
+}
+
+/// The category the bundled table gives a normalized MCC.
+pub fn mcc_category(code: &str) -> Option<&'static str> {
+    let code: u16 = code.parse().ok()?;
+    MCC_HINTS
+        .iter()
+        .find(|(first, last, _)| (*first..=*last).contains(&code))
+        .map(|(_, _, category)| *category)
+}
+
+/// The hint for an entry tagged `mcc:` with a code the table knows.
+pub fn entry_hint(tags: &[(String, String)]) -> Option<MccHint> {
+    let code = tags
+        .iter()
+        .find(|(key, _)| key == MCC_TAG)
+        .map(|(_, value)| value)?;
+    mcc_category(code).map(|category| MccHint {
+        code: code.clone(),
+        category: category.to_string(),
+    })
+}
+
+/// Every category in the table, once each.
+pub fn categories() -> Vec<&'static str> {
+    let mut categories: Vec<&str> = MCC_HINTS.iter().map(|(_, _, c)| *c).collect();
+    categories.sort_unstable();
+    categories.dedup();
+    categories
+}
+
+fn is_mcc_key(key: &str) -> bool {
+    let key: String = key
+        .chars()
+        .filter(|c| !matches!(c, ' ' | '_' | '-'))
+        .flat_map(char::to_lowercase)
+        .collect();
+    MCC_KEYS.contains(&key.as_str())
+}
+
+/// Give each transaction that carries an MCC under any of the usual tag
+/// names an `mcc:` tag holding the normalized code.
+pub fn tag_transactions(transactions: &mut [ExtractedTransaction]) {
+    for txn in transactions {
+        let Some(code) = txn
+            .ttags
+            .iter()
+            .filter(|(key, _)| is_mcc_key(key))
+            .find_map(|(_, value)| normalize_mcc(value))
+        else {
+            continue;
+        };
+        txn.ttags.retain(|(key, _)| key != MCC_TAG);
+        txn.ttags.push((MCC_TAG.to_string(), code));
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalizes_codes_and_looks_up_categories() {
+        assert_eq!(normalize_mcc("5411").as_deref(), Some("5411"));
+        assert_eq!(normalize_mcc(" MCC 5812 ").as_deref(), Some("5812"));
+        assert_eq!(
+            normalize_mcc("5541 - Service Stations").as_deref(),
+            Some("5541")
+        );
+        assert_eq!(normalize_mcc("742").as_deref(), Some("0742"));
+        assert_eq!(normalize_mcc("541100"), None);
+        assert_eq!(normalize_mcc("Groceries"), None);
+
+        assert_eq!(mcc_category("5411"), Some("Groceries"));
+        assert_eq!(mcc_category("3058"), Some("Travel"));
+        assert_eq!(mcc_category("0742"), Some("Pets"));
+        assert_eq!(mcc_category("6011"), None);
+        assert!(categories().contains(&"Dining"));
+    }
+
+    #[test]
+    fn tags_transactions_from_extractor_columns() {
+        let txn = |tags: &[(&str, &str)]| ExtractedTransaction {
+            tdate: "2025-03-01".to_string(),
+            tstatus: "Cleared".to_string(),
+            tdescription: "SQ *BLUE BOTTLE".to_string(),
+            tcomment: String::new(),
+            ttags: tags
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect(),
+            tpostings: None,
+        };
+        let mut transactions = vec![
+            txn(&[("Merchant Category Code", "5814 Fast Food")]),
+            txn(&[("mcc", "MCC 5411")]),
+            txn(&[("category", "Dining")]),
+        ];
+        tag_transactions(&mut transactions);
+
+        let mcc = |txn: &ExtractedTransaction| {
+            txn.ttags
+                .iter()
+                .filter(|(k, _)| k == MCC_TAG)
+                .map(|(_, v)| v.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(mcc(&transactions[0]), ["5814"]);
+        assert_eq!(mcc(&transactions[1]), ["5411"]);
+        assert!(mcc(&transactions[2]).is_empty());
+        assert_eq!(
+            entry_hint(&transactions[0].ttags),
+            Some(MccHint {
+                code: "5814".to_string(),
+                category: "Dining".to_string(),
+            })
+        );
+    }
+}
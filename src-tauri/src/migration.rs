@@ -1,18 +1,22 @@
-use std::collections::{BTreeMap, BTreeSet};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::fs;
 use std::io;
 use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, serde::Serialize)]
+use crate::secret::SecretString;
+
+#[derive(Debug, Clone, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct MigratedAccount {
     pub account_name: String,
     pub login_name: String,
     pub label: String,
 }
 
-#[derive(Debug, Clone, Default, serde::Serialize)]
+#[derive(Debug, Clone, Default, serde::Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct MigrationOutcome {
     pub dry_run: bool,
     pub migrated: Vec<MigratedAccount>,
@@ -23,11 +27,13 @@ pub struct MigrationOutcome {
 pub fn migrate_ledger(
     ledger_dir: &Path,
     dry_run: bool,
+    job: &crate::jobs::JobHandle<'_>,
 ) -> Result<MigrationOutcome, Box<dyn std::error::Error + Send + Sync>> {
     let mut outcome = MigrationOutcome {
         dry_run,
         ..MigrationOutcome::default()
     };
+    job.progress(0, 1, "Renaming staging counterpart accounts");
     migrate_staging_account_names(ledger_dir, dry_run, &mut outcome)?;
 
     let accounts_dir = ledger_dir.join("accounts");
@@ -59,7 +65,16 @@ pub fn migrate_ledger(
         groups.entry(extension).or_default().push(account_name);
     }
 
-    for (extension, mut account_group) in groups {
+    let group_count = groups.len();
+    for (index, (extension, mut account_group)) in groups.into_iter().enumerate() {
+        // Each group is migrated under its own login lock, so stopping
+        // between groups leaves the rest for the next run.
+        job.check_cancelled()?;
+        job.progress(
+            index,
+            group_count,
+            format!("Migrating {extension} accounts"),
+        );
         account_group.sort();
         let login_name = derive_login_name(&extension);
         if let Err(err) = crate::login_config::validate_label(&login_name) {
@@ -95,6 +110,10 @@ pub fn migrate_ledger(
                 label,
                 crate::login_config::LoginAccountConfig {
                     gl_account: Some(account_name.clone()),
+                    account_type: None,
+                    account_ids: Vec::new(),
+                    counterpart_account: None,
+                    coverage_start: None,
                 },
             );
         }
@@ -162,16 +181,34 @@ fn migrate_staging_account_names(
         changed_paths.push("general.journal".to_string());
     }
 
+    let mut counterpart_paths = Vec::new();
     for journal_path in walk_account_journals(ledger_dir)? {
-        if rewrite_account_journal_staging_accounts(&journal_path, dry_run)? {
-            let rel = journal_path
-                .strip_prefix(ledger_dir)
-                .unwrap_or(&journal_path)
-                .display()
-                .to_string();
-            changed_paths.push(rel);
+        let counterpart = configured_counterpart(ledger_dir, &journal_path);
+        let (renamed_legacy, moved_to_counterpart) = rewrite_account_journal_staging_accounts(
+            &journal_path,
+            counterpart.as_deref(),
+            dry_run,
+        )?;
+        let rel = journal_path
+            .strip_prefix(ledger_dir)
+            .unwrap_or(&journal_path)
+            .display()
+            .to_string();
+        if renamed_legacy {
+            changed_paths.push(rel.clone());
+        }
+        if moved_to_counterpart {
+            counterpart_paths.push(rel);
         }
     }
+    if !counterpart_paths.is_empty() {
+        let action = if dry_run { "would move" } else { "moved" };
+        outcome.warnings.push(format!(
+            "{action} staging counterpart postings to the configured counterpart accounts in {} file(s): {}",
+            counterpart_paths.len(),
+            counterpart_paths.join(", ")
+        ));
+    }
 
     if !changed_paths.is_empty() {
         let action = if dry_run { "would rename" } else { "renamed" };
@@ -482,22 +519,62 @@ fn rewrite_file_string(path: &Path, dry_run: bool) -> io::Result<bool> {
     Ok(true)
 }
 
-fn rewrite_account_journal_staging_accounts(path: &Path, dry_run: bool) -> io::Result<bool> {
+/// The counterpart configured for the login account owning `journal_path`,
+/// when its login account or the ledger sets one.
+fn configured_counterpart(ledger_dir: &Path, journal_path: &Path) -> Option<String> {
+    let rel = journal_path.strip_prefix(ledger_dir).ok()?;
+    let parts: Vec<&str> = rel.iter().filter_map(std::ffi::OsStr::to_str).collect();
+    let ["logins", login_name, "accounts", label, "account.journal"] = parts.as_slice() else {
+        return None;
+    };
+    let config = crate::login_config::read_login_config(ledger_dir, login_name);
+    let account_override = config
+        .accounts
+        .get(*label)
+        .is_some_and(|account| account.counterpart_account.is_some());
+    let ledger_prefix = crate::staging::get_staging_config(ledger_dir)
+        .ok()
+        .is_some_and(|config| config.counterpart_prefix.is_some());
+    (account_override || ledger_prefix)
+        .then(|| crate::staging::login_account_counterpart(ledger_dir, login_name, label))
+}
+
+/// Rename legacy `Equity:Unreconciled` postings to `Equity:Staging`, and move
+/// the staging counterpart of single-sided entries to `counterpart` when one
+/// is configured. Returns whether each kind of change was made.
+fn rewrite_account_journal_staging_accounts(
+    path: &Path,
+    counterpart: Option<&str>,
+    dry_run: bool,
+) -> io::Result<(bool, bool)> {
     let mut entries = crate::account_journal::read_journal_at_path(path)?;
-    let mut changed = false;
+    let mut renamed_legacy = false;
+    let mut moved_to_counterpart = false;
     for entry in &mut entries {
-        for posting in &mut entry.postings {
+        let single_sided = entry.postings.len() == 2;
+        for (index, posting) in entry.postings.iter_mut().enumerate() {
+            if let Some(counterpart) = counterpart {
+                if single_sided
+                    && index == 1
+                    && crate::staging::is_staging_account(&posting.account)
+                    && posting.account != counterpart
+                {
+                    posting.account = counterpart.to_string();
+                    moved_to_counterpart = true;
+                    continue;
+                }
+            }
             let canonical = crate::staging::canonicalize_account_name(&posting.account);
             if canonical != posting.account {
                 posting.account = canonical;
-                changed = true;
+                renamed_legacy = true;
             }
         }
     }
-    if changed && !dry_run {
+    if (renamed_legacy || moved_to_counterpart) && !dry_run {
         crate::account_journal::write_journal_at_path(path, &entries)?;
     }
-    Ok(changed)
+    Ok((renamed_legacy, moved_to_counterpart))
 }
 
 fn move_file_if_exists(source: &Path, target: &Path) -> io::Result<()> {
@@ -697,7 +774,7 @@ fn copy_login_secrets_from_account(account_name: &str, login_name: &str) -> Resu
                     .map_err(|err| err.to_string())?;
             }
             target
-                .set_password(&domain, &password)
+                .set_password(&domain, password.expose_secret())
                 .map_err(|err| err.to_string())?;
         }
     }
@@ -706,7 +783,7 @@ fn copy_login_secrets_from_account(account_name: &str, login_name: &str) -> Resu
     let legacy = source
         .list_legacy_entries()
         .map_err(|err| err.to_string())?;
-    let mut by_domain: BTreeMap<String, Vec<(String, String)>> = BTreeMap::new();
+    let mut by_domain: BTreeMap<String, Vec<(String, SecretString)>> = BTreeMap::new();
     for (domain, name) in legacy {
         let value = source
             .get_legacy_value(&domain, &name)
@@ -714,8 +791,8 @@ fn copy_login_secrets_from_account(account_name: &str, login_name: &str) -> Resu
         by_domain.entry(domain).or_default().push((name, value));
     }
     for (domain, pairs) in by_domain {
-        let mut username: Option<String> = None;
-        let mut password: Option<String> = None;
+        let mut username: Option<SecretString> = None;
+        let mut password: Option<SecretString> = None;
         for (name, value) in &pairs {
             match infer_role(name) {
                 "username" => {
@@ -730,13 +807,223 @@ fn copy_login_secrets_from_account(account_name: &str, login_name: &str) -> Resu
         let password = password.unwrap_or_default();
         if !username.is_empty() || !password.is_empty() {
             target
-                .set_credentials(&domain, &username, &password)
+                .set_credentials(&domain, username.expose_secret(), password.expose_secret())
                 .map_err(|err| err.to_string())?;
         }
     }
     Ok(())
 }
 
+#[derive(Debug, Clone, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct RenamedEntryId {
+    pub login_name: String,
+    pub label: String,
+    pub old_id: String,
+    pub new_id: String,
+}
+
+#[derive(Debug, Clone, Default, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct EntryIdMigrationOutcome {
+    pub dry_run: bool,
+    pub renamed: Vec<RenamedEntryId>,
+    /// `source:` refs rewritten in general.journal and its shards.
+    pub gl_refs: usize,
+    /// Bookkeeping links and alerts rewritten.
+    pub bookkeeping_refs: usize,
+    /// Entry ids rewritten in the GL and login account operations logs.
+    pub operation_refs: usize,
+    /// Category feedback records rewritten.
+    pub feedback_refs: usize,
+}
+
+/// Replace random (UUID) entry ids in login account journals with derived
+/// ids (see `entry_id`), and rewrite everything that points at them: the
+/// `source:` refs in every GL file, `splitFrom:` tags, operations logs,
+/// bookkeeping links, alerts, and category feedback. Every file is read and
+/// rewritten in memory first and only written once all of them are ready.
+pub fn migrate_entry_ids(
+    ledger_dir: &Path,
+    dry_run: bool,
+    lock_owner: &str,
+) -> Result<EntryIdMigrationOutcome, Box<dyn std::error::Error + Send + Sync>> {
+    let mut outcome = EntryIdMigrationOutcome {
+        dry_run,
+        ..EntryIdMigrationOutcome::default()
+    };
+    let _gl_lock = crate::login_config::acquire_gl_lock_with_metadata(
+        ledger_dir,
+        lock_owner,
+        "migrate-entry-ids",
+    )?;
+    let mut gl_files = Vec::new();
+    for path in crate::gl_layout::gl_journal_files(ledger_dir)? {
+        let content = fs::read_to_string(&path)?;
+        gl_files.push((path, content, false));
+    }
+    let mut staged: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+    let mut all_renames = Vec::new();
+    let mut renames_by_locator: HashMap<String, HashMap<String, String>> = HashMap::new();
+    let mut changed_accounts = Vec::new();
+    // Held until the migration is committed.
+    let mut login_locks = Vec::new();
+
+    for login_name in crate::login_config::list_logins(ledger_dir)? {
+        login_locks.push(crate::login_config::acquire_login_lock_with_metadata(
+            ledger_dir,
+            &login_name,
+            lock_owner,
+            "migrate-entry-ids",
+        )?);
+        let config = crate::login_config::read_login_config(ledger_dir, &login_name);
+        for label in config.accounts.keys() {
+            let journal_path =
+                crate::account_journal::login_account_journal_path(ledger_dir, &login_name, label);
+            let mut entries = crate::account_journal::read_journal_at_path(&journal_path)?;
+            let renames = rename_random_entry_ids(&mut entries);
+            if renames.is_empty() {
+                continue;
+            }
+
+            let locator = format!("logins/{login_name}/accounts/{label}");
+            for (old_id, new_id) in &renames {
+                let old_ref = format!("{locator}:{old_id}");
+                for (_, content, changed) in &mut gl_files {
+                    let count = content.matches(&old_ref).count();
+                    if count > 0 {
+                        outcome.gl_refs += count;
+                        *content = content.replace(&old_ref, &format!("{locator}:{new_id}"));
+                        *changed = true;
+                    }
+                }
+                outcome.renamed.push(RenamedEntryId {
+                    login_name: login_name.clone(),
+                    label: label.clone(),
+                    old_id: old_id.clone(),
+                    new_id: new_id.clone(),
+                });
+            }
+            staged.push((
+                journal_path,
+                crate::account_journal::format_journal(&entries).into_bytes(),
+            ));
+            let ops_path =
+                crate::operations::login_account_operations_path(ledger_dir, &login_name, label);
+            if let Some((count, content)) =
+                crate::operations::rename_entry_ids_in_log(&ops_path, &renames)?
+            {
+                outcome.operation_refs += count;
+                staged.push((ops_path, content.into_bytes()));
+            }
+            all_renames.extend(renames.iter().cloned());
+            renames_by_locator.insert(locator, renames.into_iter().collect());
+            changed_accounts.push((login_name.clone(), label.clone()));
+        }
+    }
+    if changed_accounts.is_empty() {
+        return Ok(outcome);
+    }
+
+    let gl_ops_path = crate::operations::gl_operations_path(ledger_dir);
+    if let Some((count, content)) =
+        crate::operations::rename_entry_ids_in_log(&gl_ops_path, &all_renames)?
+    {
+        outcome.operation_refs += count;
+        staged.push((gl_ops_path, content.into_bytes()));
+    }
+    let links = crate::bookkeeping::rename_login_entry_refs(ledger_dir, &renames_by_locator)?;
+    let alerts = crate::alerts::rename_alert_entry_ids(ledger_dir, &renames_by_locator)?;
+    outcome.bookkeeping_refs = links.len() + alerts.len();
+    staged.extend(links);
+    staged.extend(alerts);
+    let (feedback_refs, feedback) =
+        crate::categorize::rename_feedback_entry_ids(ledger_dir, &renames_by_locator)?;
+    outcome.feedback_refs = feedback_refs;
+    staged.extend(feedback);
+    for (path, content, changed) in gl_files {
+        if changed {
+            staged.push((path, content.into_bytes()));
+        }
+    }
+    if dry_run {
+        return Ok(outcome);
+    }
+
+    for (path, bytes) in &staged {
+        crate::atomic_file::write_atomic(path, bytes)?;
+    }
+    let message = format!("migrate: derived ids for {} entries", outcome.renamed.len());
+    if let Err(err) =
+        crate::ledger::commit_gl_and_login_account_journals(ledger_dir, &changed_accounts, &message)
+    {
+        tracing::warn!("git commit failed after entry id migration: {err}");
+    }
+    Ok(outcome)
+}
+
+/// Give every entry with a random id its derived id, in journal order, then
+/// do the same for each split original with a random id: its parts'
+/// `splitFrom:` tags take the original's derived id and the parts become
+/// `<id>-part1`, `<id>-part2`, ... Returns `(old id, new id)` pairs, with a
+/// split's parts before its original so that rewriting `<locator>:<old id>`
+/// refs in order never touches a part's ref by its prefix.
+fn rename_random_entry_ids(
+    entries: &mut [crate::account_journal::AccountEntry],
+) -> Vec<(String, String)> {
+    use crate::entry_split::SPLIT_FROM_TAG;
+
+    let mut taken: std::collections::HashSet<String> = entries
+        .iter()
+        .filter(|entry| !crate::entry_id::is_random_entry_id(&entry.id))
+        .map(|entry| entry.id.clone())
+        .collect();
+    let mut renames = Vec::new();
+    for entry in entries
+        .iter_mut()
+        .filter(|entry| crate::entry_id::is_random_entry_id(&entry.id))
+    {
+        let old_id = entry.id.clone();
+        crate::entry_id::assign_entry_id(entry, &mut taken);
+        renames.push((old_id, entry.id.clone()));
+    }
+
+    let mut split_ids: Vec<String> = Vec::new();
+    for entry in entries.iter() {
+        if let Some(id) = entry.tag_value(SPLIT_FROM_TAG) {
+            if crate::entry_id::is_random_entry_id(id) && !split_ids.iter().any(|s| s == id) {
+                split_ids.push(id.to_string());
+            }
+        }
+    }
+    for old_id in split_ids {
+        let Some(mut original) = crate::entry_split::split_original(entries, &old_id) else {
+            continue;
+        };
+        crate::entry_id::assign_entry_id(&mut original, &mut taken);
+        let new_id = original.id;
+        let parts = entries
+            .iter_mut()
+            .filter(|entry| entry.tag_value(SPLIT_FROM_TAG) == Some(old_id.as_str()));
+        for (n, part) in parts.enumerate() {
+            for (key, value) in &mut part.tags {
+                if key == SPLIT_FROM_TAG {
+                    *value = new_id.clone();
+                }
+            }
+            taken.remove(&part.id);
+            let part_id =
+                crate::entry_id::unique_entry_id(format!("{new_id}-part{}", n + 1), &taken);
+            taken.insert(part_id.clone());
+            renames.push((std::mem::replace(&mut part.id, part_id.clone()), part_id));
+        }
+        renames.push((old_id, new_id));
+    }
+    renames
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -795,7 +1082,8 @@ mod tests {
         )
         .unwrap();
 
-        let outcome = migrate_ledger(&ledger_dir, false).unwrap();
+        let outcome =
+            migrate_ledger(&ledger_dir, false, &crate::jobs::JobHandle::detached()).unwrap();
         assert_eq!(outcome.migrated.len(), 1);
         assert_eq!(outcome.migrated[0].login_name, "chase-driver");
         assert_eq!(outcome.migrated[0].label, "checking");
@@ -836,7 +1124,8 @@ mod tests {
         )
         .unwrap();
 
-        let outcome = migrate_ledger(&ledger_dir, true).unwrap();
+        let outcome =
+            migrate_ledger(&ledger_dir, true, &crate::jobs::JobHandle::detached()).unwrap();
         assert_eq!(outcome.migrated.len(), 1);
         assert!(ledger_dir.join("accounts").join(account_name).exists());
         assert!(!ledger_dir.join("logins").join("chase-driver").exists());
@@ -844,6 +1133,43 @@ mod tests {
         let _ = fs::remove_dir_all(&ledger_dir);
     }
 
+    #[test]
+    fn migrate_moves_staging_postings_to_configured_counterpart() {
+        let ledger_dir = temp_dir("counterpart");
+        let mut config = crate::login_config::LoginConfig::default();
+        config.accounts.insert(
+            "checking".to_string(),
+            crate::login_config::LoginAccountConfig {
+                gl_account: Some("Assets:Checking".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: Some("Expenses:Unknown".to_string()),
+                coverage_start: None,
+            },
+        );
+        crate::login_config::write_login_config(&ledger_dir, "bank", &config).unwrap();
+        let journal_path =
+            crate::account_journal::login_account_journal_path(&ledger_dir, "bank", "checking");
+        fs::create_dir_all(journal_path.parent().unwrap()).unwrap();
+        fs::write(
+            &journal_path,
+            "2026-01-01 Coffee\n    Assets:Checking  -4 USD\n    Equity:Unreconciled:bank:checking  4 USD\n\n2026-01-02 Split\n    Assets:Checking  -5 USD\n    Expenses:Fees  5 USD\n",
+        )
+        .unwrap();
+
+        let outcome =
+            migrate_ledger(&ledger_dir, false, &crate::jobs::JobHandle::detached()).unwrap();
+        assert!(outcome
+            .warnings
+            .iter()
+            .any(|warning| warning.contains("configured counterpart")));
+        let entries = crate::account_journal::read_journal_at_path(&journal_path).unwrap();
+        assert_eq!(entries[0].postings[1].account, "Expenses:Unknown");
+        assert_eq!(entries[1].postings[1].account, "Expenses:Fees");
+
+        let _ = fs::remove_dir_all(&ledger_dir);
+    }
+
     #[test]
     fn migrate_backfills_missing_general_journal_ids() {
         let ledger_dir = temp_dir("general-journal-ids");
@@ -853,7 +1179,8 @@ mod tests {
         )
         .unwrap();
 
-        let outcome = migrate_ledger(&ledger_dir, false).unwrap();
+        let outcome =
+            migrate_ledger(&ledger_dir, false, &crate::jobs::JobHandle::detached()).unwrap();
         assert!(outcome
             .warnings
             .iter()
@@ -873,15 +1200,27 @@ mod tests {
         let mut config = crate::login_config::LoginConfig {
             extension: Some("providentcu".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         config.accounts.insert(
             "4569_signature_cash_back".to_string(),
-            crate::login_config::LoginAccountConfig { gl_account: None },
+            crate::login_config::LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
         );
         config.accounts.insert(
             "signature_cash_back_4569".to_string(),
             crate::login_config::LoginAccountConfig {
                 gl_account: Some("Liabilities:Provident:Visa".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
             },
         );
         crate::login_config::write_login_config(&ledger_dir, login_name, &config).unwrap();
@@ -933,15 +1272,27 @@ mod tests {
         let mut config = crate::login_config::LoginConfig {
             extension: Some("providentcu".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         config.accounts.insert(
             "4569_signature_cash_back".to_string(),
-            crate::login_config::LoginAccountConfig { gl_account: None },
+            crate::login_config::LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
         );
         config.accounts.insert(
             "signature_cash_back_4569".to_string(),
             crate::login_config::LoginAccountConfig {
                 gl_account: Some("Liabilities:Provident:Visa".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
             },
         );
         crate::login_config::write_login_config(&ledger_dir, login_name, &config).unwrap();
@@ -981,15 +1332,27 @@ mod tests {
         let mut config = crate::login_config::LoginConfig {
             extension: Some("bankofamerica".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         config.accounts.insert(
             "_default".to_string(),
-            crate::login_config::LoginAccountConfig { gl_account: None },
+            crate::login_config::LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
         );
         config.accounts.insert(
             "bankofamerica".to_string(),
             crate::login_config::LoginAccountConfig {
                 gl_account: Some("Bankofamerica".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
             },
         );
         crate::login_config::write_login_config(&ledger_dir, login_name, &config).unwrap();
@@ -1037,15 +1400,27 @@ mod tests {
         let mut config = crate::login_config::LoginConfig {
             extension: Some("providentcu".to_string()),
             accounts: BTreeMap::new(),
+            institution: None,
+            label_rules: Vec::new(),
         };
         config.accounts.insert(
             "4569_signature_cash_back".to_string(),
-            crate::login_config::LoginAccountConfig { gl_account: None },
+            crate::login_config::LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
         );
         config.accounts.insert(
             "signature_cash_back_4569".to_string(),
             crate::login_config::LoginAccountConfig {
                 gl_account: Some("Liabilities:Card:Provident".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
             },
         );
         crate::login_config::write_login_config(&ledger_dir, login_name, &config).unwrap();
@@ -1064,4 +1439,164 @@ mod tests {
 
         let _ = fs::remove_dir_all(&ledger_dir);
     }
+
+    #[test]
+    fn migrate_entry_ids_rewrites_journal_gl_links_splits_and_feedback() {
+        use crate::account_journal::{AccountEntry, EntryPosting, EntryStatus, SimpleAmount};
+
+        let ledger_dir = temp_dir("entry-ids");
+        let mut config = crate::login_config::LoginConfig::default();
+        config.accounts.insert(
+            "checking".to_string(),
+            crate::login_config::LoginAccountConfig {
+                gl_account: Some("Assets:Checking".to_string()),
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            },
+        );
+        crate::login_config::write_login_config(&ledger_dir, "bank", &config).unwrap();
+        let entry = |row: usize| {
+            AccountEntry::new(
+                "2025-01-02".to_string(),
+                EntryStatus::Cleared,
+                "COFFEE".to_string(),
+                vec![format!("jan.csv:{row}:1")],
+                vec![EntryPosting {
+                    account: "Assets:Checking".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: "-4.50".to_string(),
+                    }),
+                }],
+            )
+        };
+        let entries = vec![entry(1), entry(2), entry(3)];
+        let old_id = entries[0].id.clone();
+        let split_id = entries[2].id.clone();
+        let journal_path =
+            crate::account_journal::login_account_journal_path(&ledger_dir, "bank", "checking");
+        fs::create_dir_all(journal_path.parent().unwrap()).unwrap();
+        crate::account_journal::write_journal_at_path(&journal_path, &entries).unwrap();
+        let part = |amount: &str| crate::entry_split::SplitPart {
+            amount: amount.to_string(),
+            description: None,
+            note: None,
+        };
+        crate::entry_split::split_entry(
+            &ledger_dir,
+            "bank",
+            "checking",
+            &split_id,
+            &[part("-3.00"), part("-1.50")],
+            "test",
+        )
+        .unwrap();
+        crate::categorize::record_category_feedback(
+            &ledger_dir,
+            "bank",
+            "checking",
+            &entries[1].id,
+            None,
+            "Expenses:Dining",
+        )
+        .unwrap();
+        let ops_path =
+            crate::operations::login_account_operations_path(&ledger_dir, "bank", "checking");
+        let gl_txn = |gl_id: &str, entry_id: &str| {
+            format!(
+                "2025-01-02 COFFEE  ; id: {gl_id}\n    ; source: logins/bank/accounts/checking:{entry_id}\n    Assets:Checking  -4.50 USD\n    Expenses:Dining\n"
+            )
+        };
+        // One reference in general.journal and one in a yearly shard it includes.
+        let gl = format!("{}\ninclude gl/2025.journal\n", gl_txn("gl-1", &old_id));
+        let shard = gl_txn("gl-2", &entries[1].id);
+        fs::write(ledger_dir.join("general.journal"), &gl).unwrap();
+        fs::create_dir_all(ledger_dir.join("gl")).unwrap();
+        fs::write(ledger_dir.join("gl/2025.journal"), &shard).unwrap();
+        let login_ref = |entry_id: &str| crate::bookkeeping::TypedRef {
+            kind: crate::bookkeeping::TypedRefKind::LoginEntry,
+            id: None,
+            locator: Some("logins/bank/accounts/checking".to_string()),
+            entry_id: Some(entry_id.to_string()),
+            login_name: None,
+            label: None,
+            filename: None,
+        };
+        crate::bookkeeping::create_link(
+            &ledger_dir,
+            crate::bookkeeping::NewLinkRecordInput {
+                kind: crate::bookkeeping::LinkKind::SourceLink,
+                left_ref: login_ref(&old_id),
+                right_ref: login_ref(&entries[1].id),
+                amount: None,
+                notes: None,
+            },
+        )
+        .unwrap();
+
+        let preview = migrate_entry_ids(&ledger_dir, true, "test").unwrap();
+        // Two entries, two split parts, and the split original.
+        assert_eq!(preview.renamed.len(), 5);
+        assert_eq!(preview.gl_refs, 2);
+        assert_eq!(preview.feedback_refs, 1);
+        assert!(fs::read_to_string(&ops_path).unwrap().contains(&split_id));
+        assert_eq!(
+            fs::read_to_string(ledger_dir.join("general.journal")).unwrap(),
+            gl
+        );
+        assert_eq!(
+            fs::read_to_string(ledger_dir.join("gl/2025.journal")).unwrap(),
+            shard
+        );
+
+        let outcome = migrate_entry_ids(&ledger_dir, false, "test").unwrap();
+        let new_id = crate::entry_id::derive_entry_id(&entries[0]);
+        assert_eq!(outcome.renamed[0].new_id, new_id);
+        assert_eq!(outcome.bookkeeping_refs, 1);
+        let migrated = crate::account_journal::read_journal_at_path(&journal_path).unwrap();
+        assert_eq!(migrated[0].id, new_id);
+        assert_ne!(migrated[1].id, new_id);
+        let original_id = migrated[2]
+            .tag_value(crate::entry_split::SPLIT_FROM_TAG)
+            .unwrap()
+            .to_string();
+        assert!(!crate::entry_id::is_random_entry_id(&original_id));
+        assert_eq!(migrated[2].id, format!("{original_id}-part1"));
+        assert_eq!(migrated[3].id, format!("{original_id}-part2"));
+        assert_eq!(
+            migrated[3].tag_value(crate::entry_split::SPLIT_FROM_TAG),
+            Some(original_id.as_str())
+        );
+        let ops = fs::read_to_string(&ops_path).unwrap();
+        assert!(!ops.contains(&split_id));
+        assert!(ops.contains(&format!("\"{original_id}-part2\"")));
+        assert!(outcome.operation_refs >= 3);
+        let feedback = crate::categorize::list_category_feedback(&ledger_dir).unwrap();
+        assert_eq!(feedback[0].entry_id, migrated[1].id);
+        // The split merges back under the migrated id.
+        let merged = crate::entry_split::merge_split_entry(
+            &ledger_dir,
+            "bank",
+            "checking",
+            &original_id,
+            "test",
+        )
+        .unwrap();
+        assert_eq!(merged.id, original_id);
+        assert!(fs::read_to_string(ledger_dir.join("general.journal"))
+            .unwrap()
+            .contains(&format!("logins/bank/accounts/checking:{new_id}")));
+        assert!(fs::read_to_string(ledger_dir.join("gl/2025.journal"))
+            .unwrap()
+            .contains(&format!("logins/bank/accounts/checking:{}", migrated[1].id)));
+        let links = crate::bookkeeping::list_links(&ledger_dir).unwrap();
+        assert_eq!(links[0].left_ref.entry_id.as_deref(), Some(new_id.as_str()));
+
+        let again = migrate_entry_ids(&ledger_dir, false, "test").unwrap();
+        assert!(again.renamed.is_empty());
+
+        let _ = fs::remove_dir_all(&ledger_dir);
+    }
 }
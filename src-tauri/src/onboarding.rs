@@ -0,0 +1,475 @@
+//! Starter ledger templates and first-run setup status.
+//!
+//! A template seeds a new ledger with a chart of accounts and commodity
+//! directive in `general.journal`, keyword rules in
+//! `categorization-rules.json`, and example envelopes in
+//! `envelopes.journal`. `onboarding_status` reports which setup steps are
+//! still missing so the UI can walk a new user through them.
+
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::categorize::{self, CategorizationRule};
+use crate::envelopes::{self, EnvelopeDefinition};
+use crate::login_config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts", rename = "LedgerTemplateId")]
+pub enum LedgerTemplate {
+    PersonalUs,
+    PersonalEu,
+    Freelancer,
+}
+
+struct TemplateSpec {
+    description: &'static str,
+    /// Amount style for the `commodity` directive. Amounts keep `.` as the
+    /// decimal mark because that is how postings are written.
+    commodity: &'static str,
+    /// `(account, hledger type code)`.
+    accounts: &'static [(&'static str, &'static str)],
+    /// `(keyword, account)`.
+    rules: &'static [(&'static str, &'static str)],
+    /// `(envelope, categories)`.
+    envelopes: &'static [(&'static str, &'static [&'static str])],
+}
+
+const PERSONAL_US: TemplateSpec = TemplateSpec {
+    description: "Personal finances in US dollars",
+    commodity: "$1,000.00",
+    accounts: &[
+        ("Assets:Checking", "C"),
+        ("Assets:Savings", "C"),
+        ("Assets:Retirement:401k", "A"),
+        ("Liabilities:Credit Card", "L"),
+        ("Liabilities:Mortgage", "L"),
+        ("Equity:Opening Balances", "E"),
+        ("Income:Salary", "R"),
+        ("Income:Interest", "R"),
+        ("Expenses:Groceries", "X"),
+        ("Expenses:Dining", "X"),
+        ("Expenses:Gas", "X"),
+        ("Expenses:Shopping", "X"),
+        ("Expenses:Entertainment", "X"),
+        ("Expenses:Travel", "X"),
+        ("Expenses:Healthcare", "X"),
+        ("Expenses:Utilities", "X"),
+        ("Expenses:Rent", "X"),
+        ("Expenses:Insurance", "X"),
+        ("Expenses:Taxes:Federal", "X"),
+        ("Expenses:Taxes:State", "X"),
+        ("Expenses:Unknown", "X"),
+    ],
+    rules: &[
+        ("PG&E", "Expenses:Utilities"),
+        ("COMCAST", "Expenses:Utilities"),
+        ("VERIZON", "Expenses:Utilities"),
+        ("GEICO", "Expenses:Insurance"),
+        ("CVS", "Expenses:Healthcare"),
+        ("WALGREENS", "Expenses:Healthcare"),
+        ("UNITED AIRLINES", "Expenses:Travel"),
+        ("IRS USATAXPYMT", "Expenses:Taxes:Federal"),
+        ("INTEREST PAYMENT", "Income:Interest"),
+    ],
+    envelopes: &[
+        ("Groceries", &["Expenses:Groceries"]),
+        ("Dining", &["Expenses:Dining"]),
+        ("Housing", &["Expenses:Rent", "Expenses:Utilities"]),
+        ("Transportation", &["Expenses:Gas"]),
+        ("Fun", &["Expenses:Entertainment", "Expenses:Shopping"]),
+    ],
+};
+
+const PERSONAL_EU: TemplateSpec = TemplateSpec {
+    description: "Personal finances in euros",
+    commodity: "1000.00 EUR",
+    accounts: &[
+        ("Assets:Current Account", "C"),
+        ("Assets:Savings", "C"),
+        ("Assets:Pension", "A"),
+        ("Liabilities:Credit Card", "L"),
+        ("Liabilities:Mortgage", "L"),
+        ("Equity:Opening Balances", "E"),
+        ("Income:Salary", "R"),
+        ("Income:Interest", "R"),
+        ("Income:Child Benefit", "R"),
+        ("Expenses:Groceries", "X"),
+        ("Expenses:Dining", "X"),
+        ("Expenses:Transport", "X"),
+        ("Expenses:Shopping", "X"),
+        ("Expenses:Entertainment", "X"),
+        ("Expenses:Travel", "X"),
+        ("Expenses:Healthcare", "X"),
+        ("Expenses:Utilities", "X"),
+        ("Expenses:Rent", "X"),
+        ("Expenses:Insurance", "X"),
+        ("Expenses:Broadcasting Fee", "X"),
+        ("Expenses:Unknown", "X"),
+    ],
+    rules: &[
+        ("ALDI", "Expenses:Groceries"),
+        ("LIDL", "Expenses:Groceries"),
+        ("CARREFOUR", "Expenses:Groceries"),
+        ("REWE", "Expenses:Groceries"),
+        ("DEUTSCHE BAHN", "Expenses:Transport"),
+        ("SNCF", "Expenses:Transport"),
+        ("RYANAIR", "Expenses:Travel"),
+        ("IKEA", "Expenses:Shopping"),
+        ("APOTHEKE", "Expenses:Healthcare"),
+        ("PHARMACIE", "Expenses:Healthcare"),
+    ],
+    envelopes: &[
+        ("Groceries", &["Expenses:Groceries"]),
+        ("Housing", &["Expenses:Rent", "Expenses:Utilities"]),
+        ("Transport", &["Expenses:Transport"]),
+        ("Fun", &["Expenses:Dining", "Expenses:Entertainment"]),
+    ],
+};
+
+const FREELANCER: TemplateSpec = TemplateSpec {
+    description: "Self-employed work alongside personal finances, in US dollars",
+    commodity: "$1,000.00",
+    accounts: &[
+        ("Assets:Checking", "C"),
+        ("Assets:Business Checking", "C"),
+        ("Assets:Savings", "C"),
+        ("Assets:Receivable", "A"),
+        ("Liabilities:Credit Card", "L"),
+        ("Equity:Opening Balances", "E"),
+        ("Equity:Owner Draws", "E"),
+        ("Income:Consulting", "R"),
+        ("Income:Sales", "R"),
+        ("Income:Interest", "R"),
+        ("Expenses:Business:Software", "X"),
+        ("Expenses:Business:Office", "X"),
+        ("Expenses:Business:Equipment", "X"),
+        ("Expenses:Business:Travel", "X"),
+        ("Expenses:Business:Fees", "X"),
+        ("Expenses:Groceries", "X"),
+        ("Expenses:Dining", "X"),
+        ("Expenses:Rent", "X"),
+        ("Expenses:Utilities", "X"),
+        ("Expenses:Healthcare", "X"),
+        ("Expenses:Taxes:Estimated", "X"),
+        ("Expenses:Unknown", "X"),
+    ],
+    rules: &[
+        ("GITHUB", "Expenses:Business:Software"),
+        ("ADOBE", "Expenses:Business:Software"),
+        ("ZOOM", "Expenses:Business:Software"),
+        ("WEWORK", "Expenses:Business:Office"),
+        ("STAPLES", "Expenses:Business:Office"),
+        ("STRIPE", "Expenses:Business:Fees"),
+        ("PAYPAL FEE", "Expenses:Business:Fees"),
+        ("IRS USATAXPYMT", "Expenses:Taxes:Estimated"),
+    ],
+    envelopes: &[
+        ("Taxes", &["Expenses:Taxes:Estimated"]),
+        ("Business", &["Expenses:Business"]),
+        (
+            "Household",
+            &["Expenses:Groceries", "Expenses:Rent", "Expenses:Utilities"],
+        ),
+    ],
+};
+
+impl LedgerTemplate {
+    pub const ALL: [LedgerTemplate; 3] = [
+        LedgerTemplate::PersonalUs,
+        LedgerTemplate::PersonalEu,
+        LedgerTemplate::Freelancer,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LedgerTemplate::PersonalUs => "personal-us",
+            LedgerTemplate::PersonalEu => "personal-eu",
+            LedgerTemplate::Freelancer => "freelancer",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|template| template.as_str() == value.trim())
+    }
+
+    fn spec(self) -> &'static TemplateSpec {
+        match self {
+            LedgerTemplate::PersonalUs => &PERSONAL_US,
+            LedgerTemplate::PersonalEu => &PERSONAL_EU,
+            LedgerTemplate::Freelancer => &FREELANCER,
+        }
+    }
+}
+
+/// Returned by `list_ledger_templates`.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LedgerTemplateInfo {
+    pub id: LedgerTemplate,
+    pub description: String,
+    pub commodity: String,
+    pub accounts: Vec<String>,
+    pub envelopes: Vec<String>,
+}
+
+pub fn list_ledger_templates() -> Vec<LedgerTemplateInfo> {
+    LedgerTemplate::ALL
+        .into_iter()
+        .map(|template| {
+            let spec = template.spec();
+            LedgerTemplateInfo {
+                id: template,
+                description: spec.description.to_string(),
+                commodity: spec.commodity.to_string(),
+                accounts: spec.accounts.iter().map(|(a, _)| a.to_string()).collect(),
+                envelopes: spec.envelopes.iter().map(|(e, _)| e.to_string()).collect(),
+            }
+        })
+        .collect()
+}
+
+/// Write `template`'s files into a freshly created ledger. Returns the
+/// ledger-relative paths it added besides `general.journal`, for the
+/// initial commit.
+pub(crate) fn apply_template(
+    ledger_dir: &Path,
+    template: LedgerTemplate,
+) -> io::Result<Vec<PathBuf>> {
+    let spec = template.spec();
+    fs::write(
+        ledger_dir.join("general.journal"),
+        render_chart_of_accounts(template),
+    )?;
+
+    let rules: Vec<CategorizationRule> = spec
+        .rules
+        .iter()
+        .map(|(keyword, account)| CategorizationRule {
+            keyword: keyword.to_string(),
+            account: account.to_string(),
+        })
+        .collect();
+    crate::bookkeeping::write_json(&categorize::categorization_rules_path(ledger_dir), &rules)?;
+
+    let mut envelopes_journal = String::new();
+    for (name, categories) in spec.envelopes {
+        envelopes_journal.push_str(&envelopes::format_definition(&EnvelopeDefinition {
+            name: name.to_string(),
+            categories: categories.iter().map(|c| c.to_string()).collect(),
+            groups: Vec::new(),
+            rollover: true,
+        }));
+        envelopes_journal.push('\n');
+    }
+    fs::write(
+        envelopes::envelopes_journal_path(ledger_dir),
+        envelopes_journal,
+    )?;
+
+    Ok(vec![
+        PathBuf::from("categorization-rules.json"),
+        PathBuf::from("envelopes.journal"),
+    ])
+}
+
+fn render_chart_of_accounts(template: LedgerTemplate) -> String {
+    let spec = template.spec();
+    let mut out = format!(
+        "; Chart of accounts from the {} template.\ncommodity {}\n\n",
+        template.as_str(),
+        spec.commodity
+    );
+    for (account, kind) in spec.accounts {
+        out.push_str(&format!("account {account}  ; type: {kind}\n"));
+    }
+    out
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
+pub enum OnboardingStepKind {
+    /// hledger runs.
+    Hledger,
+    /// `general.journal` declares at least one account.
+    ChartOfAccounts,
+    /// At least one login exists.
+    Login,
+    /// Every login account is mapped to a GL account.
+    GlMapping,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct OnboardingStep {
+    pub step: OnboardingStepKind,
+    pub done: bool,
+    /// What is missing, for steps that are not done.
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct OnboardingStatus {
+    pub complete: bool,
+    pub steps: Vec<OnboardingStep>,
+}
+
+pub fn onboarding_status(ledger_dir: &Path) -> io::Result<OnboardingStatus> {
+    let hledger_available = std::process::Command::new(crate::binpath::hledger_path())
+        .arg("--version")
+        .output()
+        .is_ok_and(|output| output.status.success());
+    onboarding_status_with(ledger_dir, hledger_available)
+}
+
+fn onboarding_status_with(
+    ledger_dir: &Path,
+    hledger_available: bool,
+) -> io::Result<OnboardingStatus> {
+    let mut steps = vec![step(
+        OnboardingStepKind::Hledger,
+        (!hledger_available).then(|| "hledger was not found or did not run".to_string()),
+    )];
+
+    let general_journal = ledger_dir.join("general.journal");
+    let declares_accounts = general_journal.exists()
+        && fs::read_to_string(&general_journal)?
+            .lines()
+            .any(|line| line.starts_with("account "));
+    steps.push(step(
+        OnboardingStepKind::ChartOfAccounts,
+        (!declares_accounts).then(|| "general.journal declares no accounts".to_string()),
+    ));
+
+    let logins = login_config::list_logins(ledger_dir)?;
+    steps.push(step(
+        OnboardingStepKind::Login,
+        logins
+            .is_empty()
+            .then(|| "no logins have been added".to_string()),
+    ));
+
+    let mut login_accounts = 0;
+    let mut unmapped = Vec::new();
+    for login in &logins {
+        let config = login_config::read_login_config(ledger_dir, login);
+        for (label, account) in &config.accounts {
+            login_accounts += 1;
+            let mapped = account
+                .gl_account
+                .as_deref()
+                .is_some_and(|gl_account| !gl_account.trim().is_empty());
+            if !mapped {
+                unmapped.push(format!("{login}/{label}"));
+            }
+        }
+    }
+    let mapping_detail = if login_accounts == 0 {
+        Some("no login accounts to map yet".to_string())
+    } else if unmapped.is_empty() {
+        None
+    } else {
+        Some(format!("no GL account for {}", unmapped.join(", ")))
+    };
+    steps.push(step(OnboardingStepKind::GlMapping, mapping_detail));
+
+    Ok(OnboardingStatus {
+        complete: steps.iter().all(|step| step.done),
+        steps,
+    })
+}
+
+fn step(kind: OnboardingStepKind, missing: Option<String>) -> OnboardingStep {
+    OnboardingStep {
+        step: kind,
+        done: missing.is_none(),
+        detail: missing,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn temp_ledger(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-onboarding-{name}-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&dir, None).unwrap();
+        dir
+    }
+
+    #[test]
+    fn templates_parse_by_id_and_declare_unique_accounts() {
+        for template in LedgerTemplate::ALL {
+            assert_eq!(LedgerTemplate::parse(template.as_str()), Some(template));
+            let spec = template.spec();
+            let mut names: Vec<&str> = spec.accounts.iter().map(|(a, _)| *a).collect();
+            names.sort_unstable();
+            names.dedup();
+            assert_eq!(names.len(), spec.accounts.len(), "{}", template.as_str());
+            for (_, account) in spec.rules {
+                assert!(names.contains(account), "{account}");
+            }
+        }
+        assert_eq!(LedgerTemplate::parse("corporate"), None);
+    }
+
+    #[test]
+    fn template_seeds_rules_envelopes_and_chart_of_accounts() {
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-onboarding-template-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&dir, Some(LedgerTemplate::Freelancer)).unwrap();
+
+        let journal = fs::read_to_string(dir.join("general.journal")).unwrap();
+        assert!(journal.contains("commodity $1,000.00\n"));
+        assert!(journal.contains("account Income:Consulting  ; type: R\n"));
+        assert_eq!(
+            categorize::list_categorization_rules(&dir).unwrap().len(),
+            FREELANCER.rules.len()
+        );
+        let envelopes = envelopes::list_envelope_definitions(&dir).unwrap();
+        assert_eq!(envelopes.len(), FREELANCER.envelopes.len());
+        assert_eq!(envelopes[0].categories, vec!["Expenses:Taxes:Estimated"]);
+
+        let status = onboarding_status_with(&dir, true).unwrap();
+        assert!(status.steps[1].done);
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn status_reports_missing_steps() {
+        let dir = temp_ledger("status");
+        let status = onboarding_status_with(&dir, false).unwrap();
+        assert!(!status.complete);
+        let missing: Vec<OnboardingStepKind> = status
+            .steps
+            .iter()
+            .filter(|step| !step.done)
+            .map(|step| step.step)
+            .collect();
+        assert_eq!(
+            missing,
+            vec![
+                OnboardingStepKind::Hledger,
+                OnboardingStepKind::ChartOfAccounts,
+                OnboardingStepKind::Login,
+                OnboardingStepKind::GlMapping,
+            ]
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
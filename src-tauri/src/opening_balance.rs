@@ -0,0 +1,396 @@
+//! Opening balances for accounts connected partway through their life.
+//!
+//! A login account's journal starts with its first scraped document, so
+//! everything before is missing and its balance is off by that much. The
+//! opening balance is the account's balance at the start of the first
+//! covered day, entered by hand or derived from a scraped balance snapshot by
+//! backing out the journal's postings between the coverage start and the
+//! snapshot. Recording it appends an `Equity:Opening Balances` transaction
+//! dated the day before coverage starts and sets the login account's
+//! `coverageStart`, from which the coverage report measures its history.
+
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+use chrono::{Duration, NaiveDate};
+
+use crate::account_journal;
+use crate::balances::{self, SnapshotKind};
+use crate::login_config;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+#[derive(Debug, Clone, Default, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct OpeningBalanceInput {
+    /// First day the ledger's history of the account covers, `YYYY-MM-DD`.
+    pub coverage_start: String,
+    /// Balance at the start of that day. Without it, the balance is derived
+    /// from the scraped balance snapshot nearest the coverage start.
+    #[serde(default)]
+    #[ts(optional)]
+    pub amount: Option<String>,
+    /// Needed when the snapshots or journal hold more than one commodity.
+    #[serde(default)]
+    #[ts(optional)]
+    pub commodity: Option<String>,
+    /// Defaults to `Equity:Opening Balances`.
+    #[serde(default)]
+    #[ts(optional)]
+    pub equity_account: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export_to = "index.ts", rename = "OpeningBalanceSource")]
+pub enum BalanceSource {
+    Manual,
+    Scraped,
+}
+
+/// The opening-balance transaction for a login account.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct OpeningBalance {
+    pub login_name: String,
+    pub label: String,
+    pub gl_account: String,
+    pub equity_account: String,
+    pub coverage_start: String,
+    /// Date of the transaction, the day before `coverage_start`.
+    pub date: String,
+    pub amount: String,
+    pub commodity: String,
+    pub source: BalanceSource,
+    /// Date of the scraped snapshot the amount was derived from.
+    pub snapshot_date: Option<String>,
+    /// The transaction as it is written to the general journal.
+    pub transaction: String,
+}
+
+/// Work out the opening balance for a login account without writing it.
+pub fn plan_opening_balance(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    input: &OpeningBalanceInput,
+) -> Result<OpeningBalance, BoxError> {
+    let coverage_start = NaiveDate::parse_from_str(input.coverage_start.trim(), "%Y-%m-%d")
+        .map_err(|err| format!("coverageStart must be YYYY-MM-DD: {err}"))?;
+    let config = login_config::read_login_config(ledger_dir, login_name);
+    let account = config
+        .accounts
+        .get(label)
+        .ok_or_else(|| format!("login account {login_name}/{label} does not exist"))?;
+    let gl_account = account
+        .gl_account
+        .clone()
+        .ok_or_else(|| format!("map {login_name}/{label} to a GL account first"))?;
+    if let Some(existing) = &account.coverage_start {
+        return Err(format!(
+            "{login_name}/{label} already has an opening balance as of {existing}"
+        )
+        .into());
+    }
+
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+    let opening_day = coverage_start - Duration::days(1);
+    let opening_date = opening_day.format("%Y-%m-%d").to_string();
+    let commodity_filter = input
+        .commodity
+        .as_deref()
+        .map(str::trim)
+        .filter(|c| !c.is_empty());
+
+    let (amount, commodity, source, snapshot_date) = match input
+        .amount
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+    {
+        Some(amount) => {
+            amount
+                .parse::<f64>()
+                .map_err(|_| format!("amount must be a number, got {amount:?}"))?;
+            let commodity = match commodity_filter {
+                Some(commodity) => commodity.to_string(),
+                None => only_commodity(
+                    entries
+                        .iter()
+                        .filter_map(|entry| entry.postings.first()?.amount.as_ref())
+                        .map(|amount| amount.commodity.as_str()),
+                    "the account journal",
+                )?,
+            };
+            (amount.to_string(), commodity, BalanceSource::Manual, None)
+        }
+        None => {
+            let snapshots = balances::read_snapshots(&balances::login_account_balances_path(
+                ledger_dir, login_name, label,
+            ))?;
+            let snapshots: Vec<_> = snapshots
+                .into_iter()
+                .filter(|s| s.kind == SnapshotKind::Balance)
+                .filter(|s| {
+                    commodity_filter.is_none() || commodity_filter == Some(s.commodity.as_str())
+                })
+                .filter_map(|s| {
+                    let date = NaiveDate::parse_from_str(&s.date, "%Y-%m-%d").ok()?;
+                    Some((date, s))
+                })
+                .collect();
+            if snapshots.is_empty() {
+                return Err(format!(
+                    "no scraped balance for {login_name}/{label}; enter the amount"
+                )
+                .into());
+            }
+            let commodity = match commodity_filter {
+                Some(commodity) => commodity.to_string(),
+                None => only_commodity(
+                    snapshots.iter().map(|(_, s)| s.commodity.as_str()),
+                    "the scraped balances",
+                )?,
+            };
+            // The nearest snapshot needs the fewest postings backed out; on a
+            // tie, prefer the one after the opening day.
+            let (date, snapshot) = snapshots
+                .iter()
+                .min_by_key(|(date, _)| {
+                    ((*date - opening_day).num_days().abs(), *date < opening_day)
+                })
+                .ok_or_else(|| format!("no scraped balance in {commodity}"))?;
+            let reported: f64 = snapshot
+                .amount
+                .parse()
+                .map_err(|_| format!("scraped balance {:?} is not a number", snapshot.amount))?;
+            let snapshot_date = date.format("%Y-%m-%d").to_string();
+            let opening = reported
+                - (balances::computed_balance(&entries, &commodity, &snapshot_date)
+                    - balances::computed_balance(&entries, &commodity, &opening_date));
+            let places = entries
+                .iter()
+                .filter_map(|entry| entry.postings.first()?.amount.as_ref())
+                .map(|amount| decimal_places(&amount.quantity))
+                .chain([decimal_places(&snapshot.amount)])
+                .max()
+                .unwrap_or(2);
+            (
+                format!("{opening:.places$}"),
+                commodity,
+                BalanceSource::Scraped,
+                Some(snapshot_date),
+            )
+        }
+    };
+
+    let equity_account = input
+        .equity_account
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .unwrap_or(crate::book_import::OPENING_BALANCES_ACCOUNT)
+        .to_string();
+    let transaction = format!(
+        "{opening_date} * Opening balance  ; opening-balance: {login_name}/{label}\n    {gl_account}  {amount} {}\n    {equity_account}\n",
+        crate::post::commodity_symbol(&commodity)
+    );
+    Ok(OpeningBalance {
+        login_name: login_name.to_string(),
+        label: label.to_string(),
+        gl_account,
+        equity_account,
+        coverage_start: coverage_start.format("%Y-%m-%d").to_string(),
+        date: opening_date,
+        amount,
+        commodity,
+        source,
+        snapshot_date,
+        transaction,
+    })
+}
+
+/// Append the opening-balance transaction to the general journal and set
+/// the login account's coverage start.
+pub fn record_opening_balance(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    input: &OpeningBalanceInput,
+    lock_owner: &str,
+) -> Result<OpeningBalance, BoxError> {
+    let _gl_lock =
+        login_config::acquire_gl_lock_with_metadata(ledger_dir, lock_owner, "opening-balance")?;
+    let _login_lock = login_config::acquire_login_lock_with_metadata(
+        ledger_dir,
+        login_name,
+        lock_owner,
+        "opening-balance",
+    )?;
+    let opening = plan_opening_balance(ledger_dir, login_name, label, input)?;
+    crate::ledger_add::add_transaction_text(ledger_dir, &opening.transaction)
+        .map_err(|err| err.to_string())?;
+
+    let mut config = login_config::read_login_config(ledger_dir, login_name);
+    config
+        .accounts
+        .entry(label.to_string())
+        .or_default()
+        .coverage_start = Some(opening.coverage_start.clone());
+    login_config::write_login_config(ledger_dir, login_name, &config)?;
+    Ok(opening)
+}
+
+/// The one commodity in `commodities`, or an error asking for one.
+fn only_commodity<'a>(
+    commodities: impl Iterator<Item = &'a str>,
+    source: &str,
+) -> Result<String, String> {
+    let mut found: Vec<&str> = commodities.collect();
+    found.sort_unstable();
+    found.dedup();
+    match found.as_slice() {
+        [one] => Ok(one.to_string()),
+        [] => Err(format!("{source} has no amounts; enter the commodity")),
+        many => Err(format!(
+            "{source} has several commodities ({}); choose one",
+            many.join(", ")
+        )),
+    }
+}
+
+fn decimal_places(quantity: &str) -> usize {
+    quantity
+        .split_once('.')
+        .map_or(0, |(_, fraction)| fraction.len())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{AccountEntry, EntryPosting, EntryStatus, SimpleAmount};
+    use crate::balances::BalanceSnapshot;
+    use std::path::PathBuf;
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time should be after unix epoch")
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn entry(date: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            "purchase".to_string(),
+            Vec::new(),
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    fn snapshot(date: &str, amount: &str) -> BalanceSnapshot {
+        BalanceSnapshot {
+            date: date.to_string(),
+            kind: SnapshotKind::Balance,
+            symbol: None,
+            quantity: None,
+            price: None,
+            amount: amount.to_string(),
+            commodity: "USD".to_string(),
+            scrape_session_id: "s".to_string(),
+            scraped_at: "2025-03-01T00:00:00Z".to_string(),
+        }
+    }
+
+    fn setup(prefix: &str) -> PathBuf {
+        let ledger = temp_dir(prefix);
+        let mut config = login_config::LoginConfig::default();
+        config.accounts.insert(
+            "checking".to_string(),
+            login_config::LoginAccountConfig {
+                gl_account: Some("Assets:Bank:Checking".to_string()),
+                ..Default::default()
+            },
+        );
+        std::fs::create_dir_all(
+            login_config::login_config_path(&ledger, "bank")
+                .parent()
+                .unwrap(),
+        )
+        .unwrap();
+        login_config::write_login_config(&ledger, "bank", &config).unwrap();
+        let journal = account_journal::login_account_journal_path(&ledger, "bank", "checking");
+        std::fs::create_dir_all(journal.parent().unwrap()).unwrap();
+        account_journal::write_journal_at_path(
+            &journal,
+            &[entry("2025-02-03", "-40.00"), entry("2025-02-20", "-10.00")],
+        )
+        .unwrap();
+        ledger
+    }
+
+    #[test]
+    fn scraped_balance_backs_out_postings_since_coverage_start() {
+        let ledger = setup("opening-balance-scraped");
+        balances::append_snapshots(
+            &balances::login_account_balances_path(&ledger, "bank", "checking"),
+            &[
+                snapshot("2025-02-10", "950.00"),
+                snapshot("2025-03-01", "940.5"),
+            ],
+        )
+        .unwrap();
+        let input = OpeningBalanceInput {
+            coverage_start: "2025-02-01".to_string(),
+            ..Default::default()
+        };
+        let opening = plan_opening_balance(&ledger, "bank", "checking", &input).unwrap();
+        assert_eq!(opening.date, "2025-01-31");
+        assert_eq!(opening.amount, "990.00");
+        assert_eq!(opening.commodity, "USD");
+        assert_eq!(opening.source, BalanceSource::Scraped);
+        assert_eq!(opening.snapshot_date.as_deref(), Some("2025-02-10"));
+        assert_eq!(
+            opening.transaction,
+            "2025-01-31 * Opening balance  ; opening-balance: bank/checking\n    Assets:Bank:Checking  990.00 USD\n    Equity:Opening Balances\n"
+        );
+    }
+
+    #[test]
+    fn manual_amount_takes_the_journal_commodity() {
+        let ledger = setup("opening-balance-manual");
+        let input = OpeningBalanceInput {
+            coverage_start: "2025-02-01".to_string(),
+            amount: Some("-1200.50".to_string()),
+            equity_account: Some("Equity:Starting".to_string()),
+            ..Default::default()
+        };
+        let opening = plan_opening_balance(&ledger, "bank", "checking", &input).unwrap();
+        assert_eq!(opening.amount, "-1200.50");
+        assert_eq!(opening.commodity, "USD");
+        assert_eq!(opening.source, BalanceSource::Manual);
+        assert_eq!(opening.equity_account, "Equity:Starting");
+
+        let without_balance = OpeningBalanceInput {
+            coverage_start: "2025-02-01".to_string(),
+            ..Default::default()
+        };
+        let err = plan_opening_balance(&ledger, "bank", "checking", &without_balance).unwrap_err();
+        assert!(err.to_string().contains("no scraped balance"), "{err}");
+    }
+}
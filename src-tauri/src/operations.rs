@@ -7,8 +7,9 @@ use std::path::{Path, PathBuf};
 pub type Tag = (String, String);
 
 /// An operation in the per-account operations log.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(tag = "type")]
+#[ts(export_to = "index.ts")]
 pub enum AccountOperation {
     /// Records the creation of an account journal entry (for ID stability during re-derivation).
     #[serde(rename = "entry-created")]
@@ -51,19 +52,86 @@ pub enum AccountOperation {
         scrape_session_id: String,
         timestamp: String,
     },
+
+    /// An entry replaced by parts whose amounts sum to it.
+    #[serde(rename = "entry-split")]
+    EntrySplit {
+        #[serde(rename = "entryId")]
+        entry_id: String,
+        /// The original description, restored when the parts are merged.
+        description: String,
+        amount: String,
+        parts: Vec<SplitPartRecord>,
+        timestamp: String,
+    },
+
+    /// The parts of a split merged back into the original entry.
+    #[serde(rename = "entry-merge")]
+    EntryMerge {
+        #[serde(rename = "entryId")]
+        entry_id: String,
+        /// Ids of the removed parts.
+        parts: Vec<String>,
+        timestamp: String,
+    },
+
+    /// Changes extraction found for a posted entry and left unapplied.
+    #[serde(rename = "posted-entry-conflict")]
+    PostedEntryConflict {
+        #[serde(rename = "entryId")]
+        entry_id: String,
+        /// The entry's GL ref when the conflict was found.
+        posted: String,
+        #[serde(rename = "proposedEvidence")]
+        proposed_evidence: Vec<String>,
+        changes: Vec<PostedEntryChange>,
+        timestamp: String,
+    },
+}
+
+/// One part recorded in an `EntrySplit` operation.
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export_to = "index.ts")]
+pub struct SplitPartRecord {
+    #[serde(rename = "entryId")]
+    pub entry_id: String,
+    pub amount: String,
+}
+
+/// One field of a posted entry that extraction would have changed.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export_to = "index.ts")]
+pub struct PostedEntryChange {
+    pub field: PostedEntryField,
+    pub current: String,
+    pub proposed: String,
+}
+
+/// The fields of a posted entry that extraction can change; the amount is
+/// the first posting's quantity.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export_to = "index.ts")]
+pub enum PostedEntryField {
+    Description,
+    Status,
+    Comment,
+    Amount,
 }
 
 /// Dedup override action: force two entries to match, or prevent them from matching.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "kebab-case")]
+#[ts(export_to = "index.ts")]
 pub enum DedupOverrideAction {
     ForceMatch,
     PreventMatch,
 }
 
 /// An operation in the GL-level operations log.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(tag = "type")]
+#[ts(export_to = "index.ts")]
 pub enum GlOperation {
     /// Post an account journal entry to the GL with a counterpart account.
     #[serde(rename = "post")]
@@ -75,6 +143,9 @@ pub enum GlOperation {
         counterpart_account: String,
         #[serde(rename = "postingIndex")]
         posting_index: Option<usize>,
+        /// Lock owner that performed the post (`gui`, `cli`, ...), when known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        operator: Option<String>,
         timestamp: String,
     },
 
@@ -86,6 +157,9 @@ pub enum GlOperation {
         entry_id: String,
         #[serde(rename = "counterpartAccounts")]
         counterpart_accounts: Vec<String>,
+        /// Lock owner that performed the post (`gui`, `cli`, ...), when known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        operator: Option<String>,
         timestamp: String,
     },
 
@@ -93,6 +167,9 @@ pub enum GlOperation {
     #[serde(rename = "transfer-match")]
     TransferMatch {
         entries: Vec<TransferMatchEntry>,
+        /// Lock owner that performed the post (`gui`, `cli`, ...), when known.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        operator: Option<String>,
         timestamp: String,
     },
 
@@ -119,10 +196,30 @@ pub enum GlOperation {
         sources: Vec<SyncSource>,
         timestamp: String,
     },
+
+    /// Export GL transactions matching a query to another ledger.
+    #[serde(rename = "export-entries")]
+    ExportEntries {
+        #[serde(rename = "targetLedger")]
+        target_ledger: String,
+        query: String,
+        entries: Vec<ExportedEntry>,
+        timestamp: String,
+    },
+
+    /// Import GL transactions exported from another ledger.
+    #[serde(rename = "import-entries")]
+    ImportEntries {
+        #[serde(rename = "sourceLedger")]
+        source_ledger: String,
+        entries: Vec<ExportedEntry>,
+        timestamp: String,
+    },
 }
 
 /// A source-entry snapshot recorded inside a `SyncTransaction` operation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export_to = "index.ts")]
 pub struct SyncSource {
     pub account: String,
     #[serde(rename = "entryId")]
@@ -133,8 +230,24 @@ pub struct SyncSource {
     pub status: String,
 }
 
+/// Cross-reference for one GL transaction exported to another ledger.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
+pub struct ExportedEntry {
+    /// The exported transaction in the source ledger.
+    #[serde(rename = "sourceTxnId")]
+    pub source_txn_id: String,
+    /// The copy booked against `Liabilities:Due To:<source>` in the target ledger.
+    #[serde(rename = "targetTxnId")]
+    pub target_txn_id: String,
+    /// The source-ledger transaction moving the postings to `Assets:Due From:<target>`.
+    #[serde(rename = "reclassTxnId")]
+    pub reclass_txn_id: String,
+}
+
 /// An entry in a transfer-match operation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[ts(export_to = "index.ts")]
 pub struct TransferMatchEntry {
     pub account: String,
     #[serde(rename = "entryId")]
@@ -227,6 +340,24 @@ pub struct ScrapeLogEntry {
     pub error: Option<String>,
     /// `"manual"` for user-triggered runs, `"auto"` for auto-scrape runs.
     pub source: String,
+    /// Page context captured when the driver failed an assertion.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub failure: Option<crate::scrape::assertions::AssertionFailure>,
+    /// Allowlist and domains the driver contacted.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub network: Option<crate::scrape::network_policy::NetworkReport>,
+    /// Prompts the driver asked for, without their answers.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub prompts_asked: Vec<crate::scrape::prompt_catalog::PromptRequest>,
+    /// The window the driver declared with `setSessionMetadata`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub session_metadata: Option<crate::scrape::js_api::SessionMetadata>,
+    /// Saved resources that look like error pages or empty exports.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub suspect_documents: Vec<crate::scrape::download_check::SuspectDownload>,
+    /// Names this run's files under `cache/diagnostics/<login>/`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scrape_session_id: Option<String>,
 }
 
 /// Returns the path to the per-login scrape log.
@@ -299,6 +430,29 @@ pub fn now_timestamp() -> String {
     chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Millis, true)
 }
 
+/// Replace renamed entry ids wherever they appear as a JSON string in the
+/// log at `path`, leaving every other byte as it is. Returns how many ids
+/// were replaced and the new content, or `None` when there were none.
+pub(crate) fn rename_entry_ids_in_log(
+    path: &Path,
+    renames: &[(String, String)],
+) -> io::Result<Option<(usize, String)>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let mut content = fs::read_to_string(path)?;
+    let mut replaced = 0;
+    for (old_id, new_id) in renames {
+        let old = format!("\"{old_id}\"");
+        let count = content.matches(&old).count();
+        if count > 0 {
+            replaced += count;
+            content = content.replace(&old, &format!("\"{new_id}\""));
+        }
+    }
+    Ok((replaced > 0).then_some((replaced, content)))
+}
+
 fn append_jsonl<T: Serialize>(path: &Path, value: &T) -> io::Result<()> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -393,6 +547,7 @@ mod tests {
             entry_id: "txn-abc123".to_string(),
             counterpart_account: "Expenses:Food".to_string(),
             posting_index: None,
+            operator: Some("cli".to_string()),
             timestamp: now_timestamp(),
         };
 
@@ -454,6 +609,12 @@ mod tests {
             success: false,
             error: Some("no progress in last 3 steps".to_string()),
             source: "auto".to_string(),
+            failure: None,
+            network: None,
+            prompts_asked: Vec::new(),
+            session_metadata: None,
+            suspect_documents: Vec::new(),
+            scrape_session_id: None,
         };
         let e2 = ScrapeLogEntry {
             login_name: "bankofamerica".to_string(),
@@ -461,6 +622,12 @@ mod tests {
             success: true,
             error: None,
             source: "manual".to_string(),
+            failure: None,
+            network: None,
+            prompts_asked: Vec::new(),
+            session_metadata: None,
+            suspect_documents: Vec::new(),
+            scrape_session_id: None,
         };
         // Create the login dir so append_scrape_log_entry can write.
         fs::create_dir_all(root.join("logins").join("bankofamerica")).unwrap();
@@ -0,0 +1,1191 @@
+//! Printable account statements and net-worth summaries.
+//!
+//! A paper-trail report covers one period of the general journal, for
+//! lenders and others who want statements rather than a ledger. Each
+//! requested account gets an activity statement: opening balance, every
+//! posting in the period with a running balance, and closing balance. The
+//! report can add a net-worth summary of assets and liabilities at the start
+//! and end of the period, and an appendix listing the source documents that
+//! the statements' `evidence:` tags cite.
+//!
+//! The report is laid out once as a list of blocks and then rendered either
+//! as self-contained HTML for printing from a browser or as a PDF set in
+//! Courier, whose fixed width keeps the columns aligned without font metrics.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use chrono::NaiveDate;
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Object, Stream};
+
+/// Letter paper, in points.
+const PAGE_WIDTH: i64 = 612;
+const PAGE_HEIGHT: i64 = 792;
+const PAGE_MARGIN: i64 = 54;
+const BODY_SIZE: i64 = 9;
+const HEADING_SIZE: i64 = 11;
+const TITLE_SIZE: i64 = 14;
+const FOOTER_SIZE: i64 = 8;
+/// Space between PDF table columns, in characters.
+const COLUMN_GAP: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export_to = "index.ts")]
+pub enum PaperTrailFormat {
+    Html,
+    Pdf,
+}
+
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PaperTrailOptions {
+    /// First day of the period, `YYYY-MM-DD`.
+    pub start: String,
+    /// Last day of the period, inclusive.
+    pub end: String,
+    /// GL accounts to print statements for; each includes its subaccounts.
+    pub accounts: Vec<String>,
+    pub net_worth: bool,
+    pub evidence_appendix: bool,
+    pub format: PaperTrailFormat,
+    /// File to write the report to.
+    pub output: String,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PaperTrailReport {
+    pub output: String,
+    pub statements: usize,
+    /// Statement rows, one per posting in the period.
+    pub transactions: usize,
+    /// Source documents listed in the evidence appendix.
+    pub documents: usize,
+}
+
+/// One GL posting, reduced to what a report prints.
+#[derive(Debug, Clone, PartialEq)]
+struct Line {
+    date: NaiveDate,
+    description: String,
+    account: String,
+    commodity: String,
+    amount: f64,
+    /// Decimal places the journal writes this amount with.
+    decimals: u32,
+    /// Evidence refs of the posting and its transaction.
+    evidence: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Period {
+    start: NaiveDate,
+    /// Inclusive.
+    end: NaiveDate,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct StatementRow {
+    date: NaiveDate,
+    description: String,
+    commodity: String,
+    amount: f64,
+    /// Running balance of the row's commodity after this posting.
+    balance: f64,
+    /// Appendix numbers of the documents this row cites.
+    notes: Vec<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Statement {
+    account: String,
+    opening: BTreeMap<String, f64>,
+    rows: Vec<StatementRow>,
+    closing: BTreeMap<String, f64>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct NetWorthRow {
+    /// Account at depth two, e.g. `Assets:Checking`.
+    account: String,
+    commodity: String,
+    start: f64,
+    end: f64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct AppendixEntry {
+    number: usize,
+    document: String,
+    /// Rows, pages, or other locations cited within the document.
+    locations: Vec<String>,
+    /// Where the document is stored, relative to the ledger.
+    stored_at: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Report {
+    period: Period,
+    statements: Vec<Statement>,
+    net_worth: Option<Vec<NetWorthRow>>,
+    appendix: Option<Vec<AppendixEntry>>,
+    /// Decimal places to print per commodity.
+    decimals: BTreeMap<String, u32>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Column {
+    title: &'static str,
+    numeric: bool,
+}
+
+impl Column {
+    fn text(title: &'static str) -> Self {
+        Self {
+            title,
+            numeric: false,
+        }
+    }
+
+    fn number(title: &'static str) -> Self {
+        Self {
+            title,
+            numeric: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Block {
+    Title(String),
+    Heading(String),
+    Text(String),
+    Table {
+        columns: Vec<Column>,
+        rows: Vec<Vec<String>>,
+    },
+}
+
+/// Render the report described by `options` from the general journal of
+/// `ledger_dir` and write it to `options.output`.
+pub fn write_paper_trail_report(
+    ledger_dir: &Path,
+    options: &PaperTrailOptions,
+) -> io::Result<PaperTrailReport> {
+    let period = parse_period(&options.start, &options.end)?;
+    let accounts: Vec<String> = options
+        .accounts
+        .iter()
+        .map(|account| account.trim().to_string())
+        .filter(|account| !account.is_empty())
+        .collect();
+    if accounts.is_empty() && !options.net_worth {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "choose at least one account or include the net-worth summary",
+        ));
+    }
+    let output = PathBuf::from(options.output.trim());
+    if output.as_os_str().is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "output file is required",
+        ));
+    }
+
+    let gl_path = ledger_dir.join("general.journal");
+    let lines = lines_from_transactions(&crate::ledger_open::run_hledger_print(&gl_path)?);
+    let mut report = build_report(
+        &lines,
+        period,
+        &accounts,
+        options.net_worth,
+        options.evidence_appendix,
+    );
+    for entry in report.appendix.iter_mut().flatten() {
+        entry.stored_at = crate::extract::find_attachment_path(ledger_dir, &entry.document)
+            .and_then(|path| {
+                path.strip_prefix(ledger_dir)
+                    .ok()
+                    .map(|relative| relative.to_string_lossy().into_owned())
+            });
+    }
+
+    let name = ledger_dir
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let blocks = report_blocks(&report, &name, chrono::Local::now().date_naive());
+    let content = match options.format {
+        PaperTrailFormat::Html => render_html(&blocks).into_bytes(),
+        PaperTrailFormat::Pdf => render_pdf(&blocks)?,
+    };
+    crate::atomic_file::write_atomic(&output, &content)?;
+
+    Ok(PaperTrailReport {
+        output: output.to_string_lossy().into_owned(),
+        statements: report.statements.len(),
+        transactions: report.statements.iter().map(|s| s.rows.len()).sum(),
+        documents: report.appendix.as_ref().map_or(0, Vec::len),
+    })
+}
+
+fn parse_period(start: &str, end: &str) -> io::Result<Period> {
+    let parse = |value: &str, name: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d").map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{name} date '{value}' is not YYYY-MM-DD"),
+            )
+        })
+    };
+    let period = Period {
+        start: parse(start, "start")?,
+        end: parse(end, "end")?,
+    };
+    if period.end < period.start {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "end date is before start date",
+        ));
+    }
+    Ok(period)
+}
+
+fn lines_from_transactions(txns: &[crate::hledger::Transaction]) -> Vec<Line> {
+    let mut lines = Vec::new();
+    for txn in txns {
+        let txn_evidence = evidence_tags(&txn.ttags);
+        for posting in &txn.tpostings {
+            let date = posting.pdate.as_deref().unwrap_or(&txn.tdate);
+            let Ok(date) = NaiveDate::parse_from_str(date, "%Y-%m-%d") else {
+                continue;
+            };
+            let mut evidence = txn_evidence.clone();
+            for reference in evidence_tags(&posting.ptags) {
+                if !evidence.contains(&reference) {
+                    evidence.push(reference);
+                }
+            }
+            for amount in &posting.pamount {
+                lines.push(Line {
+                    date,
+                    description: txn.tdescription.clone(),
+                    account: posting.paccount.clone(),
+                    commodity: amount.acommodity.clone(),
+                    amount: amount.aquantity.floating_point,
+                    decimals: amount.aquantity.decimal_places,
+                    evidence: evidence.clone(),
+                });
+            }
+        }
+    }
+    lines
+}
+
+fn evidence_tags(tags: &[crate::hledger::HledgerTag]) -> Vec<String> {
+    tags.iter()
+        .filter(|(name, value)| name == "evidence" && !value.trim().is_empty())
+        .map(|(_, value)| value.trim().to_string())
+        .collect()
+}
+
+fn build_report(
+    lines: &[Line],
+    period: Period,
+    accounts: &[String],
+    net_worth: bool,
+    evidence_appendix: bool,
+) -> Report {
+    let mut sorted: Vec<&Line> = lines.iter().collect();
+    sorted.sort_by_key(|line| line.date);
+
+    let mut decimals = BTreeMap::new();
+    for line in &sorted {
+        let places: &mut u32 = decimals.entry(line.commodity.clone()).or_default();
+        *places = (*places).max(line.decimals);
+    }
+
+    let mut appendix: Vec<(String, Vec<String>)> = Vec::new();
+    let mut statements = Vec::new();
+    for account in accounts {
+        let mut statement = Statement {
+            account: account.clone(),
+            opening: BTreeMap::new(),
+            rows: Vec::new(),
+            closing: BTreeMap::new(),
+        };
+        let mut running = BTreeMap::new();
+        for line in sorted.iter().filter(|line| within(&line.account, account)) {
+            if line.date > period.end {
+                break;
+            }
+            let balance: &mut f64 = running.entry(line.commodity.clone()).or_default();
+            *balance += line.amount;
+            if line.date < period.start {
+                continue;
+            }
+            let description = match line.account.strip_prefix(&format!("{account}:")) {
+                Some(subaccount) => format!("{} ({subaccount})", line.description),
+                None => line.description.clone(),
+            };
+            let notes = if evidence_appendix {
+                line.evidence
+                    .iter()
+                    .map(|reference| cite(&mut appendix, reference))
+                    .fold(Vec::new(), |mut notes, number| {
+                        if !notes.contains(&number) {
+                            notes.push(number);
+                        }
+                        notes
+                    })
+            } else {
+                Vec::new()
+            };
+            statement.rows.push(StatementRow {
+                date: line.date,
+                description,
+                commodity: line.commodity.clone(),
+                amount: line.amount,
+                balance: *balance,
+                notes,
+            });
+        }
+        for row in &statement.rows {
+            // The first row of each commodity shows what it started from.
+            statement
+                .opening
+                .entry(row.commodity.clone())
+                .or_insert(row.balance - row.amount);
+        }
+        for (commodity, balance) in &running {
+            statement
+                .opening
+                .entry(commodity.clone())
+                .or_insert(*balance);
+        }
+        statement.closing = running;
+        statements.push(statement);
+    }
+
+    let net_worth = net_worth.then(|| net_worth_rows(&sorted, period));
+    let appendix = evidence_appendix.then(|| {
+        appendix
+            .into_iter()
+            .enumerate()
+            .map(|(index, (document, locations))| AppendixEntry {
+                number: index + 1,
+                document,
+                locations,
+                stored_at: None,
+            })
+            .collect()
+    });
+
+    Report {
+        period,
+        statements,
+        net_worth,
+        appendix,
+        decimals,
+    }
+}
+
+/// Whether `account` is `parent` or one of its subaccounts.
+fn within(account: &str, parent: &str) -> bool {
+    account == parent
+        || account
+            .strip_prefix(parent)
+            .is_some_and(|rest| rest.starts_with(':'))
+}
+
+/// Number the document `reference` points into, adding it to the appendix
+/// the first time it is cited.
+fn cite(appendix: &mut Vec<(String, Vec<String>)>, reference: &str) -> usize {
+    let (document, location) = crate::provenance::split_evidence_ref(reference);
+    let index = match appendix.iter().position(|(name, _)| name == document) {
+        Some(index) => index,
+        None => {
+            appendix.push((document.to_string(), Vec::new()));
+            appendix.len() - 1
+        }
+    };
+    if let Some(location) = location {
+        let locations = &mut appendix[index].1;
+        if !locations.iter().any(|l| l == location) {
+            locations.push(location.to_string());
+        }
+    }
+    index + 1
+}
+
+fn net_worth_rows(lines: &[&Line], period: Period) -> Vec<NetWorthRow> {
+    let mut balances: BTreeMap<(String, String), (f64, f64)> = BTreeMap::new();
+    for line in lines {
+        if line.date > period.end
+            || !(within(&line.account, "Assets") || within(&line.account, "Liabilities"))
+        {
+            continue;
+        }
+        let account = line
+            .account
+            .split(':')
+            .take(2)
+            .collect::<Vec<_>>()
+            .join(":");
+        let (start, end) = balances
+            .entry((account, line.commodity.clone()))
+            .or_default();
+        if line.date < period.start {
+            *start += line.amount;
+        }
+        *end += line.amount;
+    }
+    balances
+        .into_iter()
+        .map(|((account, commodity), (start, end))| NetWorthRow {
+            account,
+            commodity,
+            start,
+            end,
+        })
+        .collect()
+}
+
+fn report_blocks(report: &Report, ledger_name: &str, generated: NaiveDate) -> Vec<Block> {
+    let amount = |value: f64, commodity: &str| {
+        format_amount(
+            value,
+            commodity,
+            report.decimals.get(commodity).copied().unwrap_or(2),
+        )
+    };
+    let mut blocks = vec![
+        Block::Title(if ledger_name.is_empty() {
+            "Account statements".to_string()
+        } else {
+            format!("{ledger_name}: account statements")
+        }),
+        Block::Text(format!(
+            "Period {} to {}. Prepared {} from the general journal.",
+            report.period.start, report.period.end, generated
+        )),
+    ];
+
+    for statement in &report.statements {
+        blocks.push(Block::Heading(statement.account.clone()));
+        for (commodity, balance) in &statement.opening {
+            blocks.push(Block::Text(format!(
+                "Opening balance {}: {}",
+                report.period.start,
+                amount(*balance, commodity)
+            )));
+        }
+        if statement.rows.is_empty() {
+            blocks.push(Block::Text("No activity in this period.".to_string()));
+        } else {
+            let mut money_in: BTreeMap<&str, f64> = BTreeMap::new();
+            let mut money_out: BTreeMap<&str, f64> = BTreeMap::new();
+            for row in &statement.rows {
+                let totals = if row.amount >= 0.0 {
+                    &mut money_in
+                } else {
+                    &mut money_out
+                };
+                *totals.entry(&row.commodity).or_default() += row.amount;
+            }
+            let with_notes = report.appendix.is_some();
+            let mut columns = vec![
+                Column::text("Date"),
+                Column::text("Description"),
+                Column::number("Amount"),
+                Column::number("Balance"),
+            ];
+            if with_notes {
+                columns.push(Column::text("Source"));
+            }
+            let rows = statement
+                .rows
+                .iter()
+                .map(|row| {
+                    let mut cells = vec![
+                        row.date.to_string(),
+                        row.description.clone(),
+                        amount(row.amount, &row.commodity),
+                        amount(row.balance, &row.commodity),
+                    ];
+                    if with_notes {
+                        cells.push(
+                            row.notes
+                                .iter()
+                                .map(|number| format!("[{number}]"))
+                                .collect::<Vec<_>>()
+                                .join(" "),
+                        );
+                    }
+                    cells
+                })
+                .collect();
+            blocks.push(Block::Table { columns, rows });
+            for (commodity, total) in &money_in {
+                blocks.push(Block::Text(format!(
+                    "Money in: {}",
+                    amount(*total, commodity)
+                )));
+            }
+            for (commodity, total) in &money_out {
+                blocks.push(Block::Text(format!(
+                    "Money out: {}",
+                    amount(*total, commodity)
+                )));
+            }
+        }
+        for (commodity, balance) in &statement.closing {
+            blocks.push(Block::Text(format!(
+                "Closing balance {}: {}",
+                report.period.end,
+                amount(*balance, commodity)
+            )));
+        }
+    }
+
+    if let Some(net_worth) = &report.net_worth {
+        blocks.push(Block::Heading("Net worth".to_string()));
+        if net_worth.is_empty() {
+            blocks.push(Block::Text(
+                "No asset or liability accounts have postings by the end of the period."
+                    .to_string(),
+            ));
+        } else {
+            let mut totals: BTreeMap<&str, (f64, f64)> = BTreeMap::new();
+            let mut rows: Vec<Vec<String>> = net_worth
+                .iter()
+                .map(|row| {
+                    let total = totals.entry(&row.commodity).or_default();
+                    total.0 += row.start;
+                    total.1 += row.end;
+                    vec![
+                        row.account.clone(),
+                        amount(row.start, &row.commodity),
+                        amount(row.end, &row.commodity),
+                    ]
+                })
+                .collect();
+            for (commodity, (start, end)) in totals {
+                rows.push(vec![
+                    "Net worth".to_string(),
+                    amount(start, commodity),
+                    amount(end, commodity),
+                ]);
+            }
+            blocks.push(Block::Text(
+                "Balances at the start of the first day and the end of the last day.".to_string(),
+            ));
+            blocks.push(Block::Table {
+                columns: vec![
+                    Column::text("Account"),
+                    Column::number("Start"),
+                    Column::number("End"),
+                ],
+                rows,
+            });
+        }
+    }
+
+    if let Some(appendix) = &report.appendix {
+        blocks.push(Block::Heading("Evidence appendix".to_string()));
+        if appendix.is_empty() {
+            blocks.push(Block::Text(
+                "No statement row cites a source document.".to_string(),
+            ));
+        } else {
+            let rows = appendix
+                .iter()
+                .map(|entry| {
+                    vec![
+                        format!("[{}]", entry.number),
+                        entry.document.clone(),
+                        entry.locations.join(", "),
+                        entry
+                            .stored_at
+                            .clone()
+                            .unwrap_or_else(|| "not in ledger".to_string()),
+                    ]
+                })
+                .collect();
+            blocks.push(Block::Table {
+                columns: vec![
+                    Column::text("Ref"),
+                    Column::text("Document"),
+                    Column::text("Cited at"),
+                    Column::text("Stored at"),
+                ],
+                rows,
+            });
+        }
+    }
+    blocks
+}
+
+/// `$1,234.50`, `-$3.00`, or `12.5000 AAPL`.
+fn format_amount(value: f64, commodity: &str, decimals: u32) -> String {
+    let places = decimals as usize;
+    let rounded = format!("{:.places$}", value.abs());
+    let negative = value < 0.0 && rounded.chars().any(|c| c.is_ascii_digit() && c != '0');
+    let (whole, fraction) = match rounded.split_once('.') {
+        Some((whole, fraction)) => (whole, Some(fraction)),
+        None => (rounded.as_str(), None),
+    };
+    let mut grouped = String::new();
+    for (index, digit) in whole.chars().enumerate() {
+        if index > 0 && (whole.len() - index) % 3 == 0 {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    if let Some(fraction) = fraction {
+        grouped.push('.');
+        grouped.push_str(fraction);
+    }
+    let sign = if negative { "-" } else { "" };
+    if commodity.is_empty() {
+        format!("{sign}{grouped}")
+    } else if commodity.chars().any(char::is_alphabetic) {
+        format!("{sign}{grouped} {commodity}")
+    } else {
+        format!("{sign}{commodity}{grouped}")
+    }
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: Helvetica, Arial, sans-serif; font-size: 10pt; color: #000; margin: 2em; }
+h1 { font-size: 16pt; margin-bottom: 0.3em; }
+h2 { font-size: 12pt; margin-top: 1.6em; break-after: avoid; }
+p { margin: 0.3em 0; }
+table { border-collapse: collapse; width: 100%; margin: 0.5em 0; }
+th, td { padding: 2px 6px; border-bottom: 1px solid #ccc; text-align: left; vertical-align: top; }
+th { border-bottom: 1px solid #000; }
+.num { text-align: right; white-space: nowrap; font-variant-numeric: tabular-nums; }
+thead { display: table-header-group; }
+tr { break-inside: avoid; }
+@page { size: letter; margin: 0.75in; }
+@media print { body { margin: 0; } }
+";
+
+fn render_html(blocks: &[Block]) -> String {
+    let title = blocks
+        .iter()
+        .find_map(|block| match block {
+            Block::Title(title) => Some(title.as_str()),
+            _ => None,
+        })
+        .unwrap_or("Account statements");
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>{}</title>\n<style>\n{HTML_STYLE}</style>\n</head>\n<body>\n",
+        escape_html(title)
+    );
+    for block in blocks {
+        match block {
+            Block::Title(text) => html.push_str(&format!("<h1>{}</h1>\n", escape_html(text))),
+            Block::Heading(text) => html.push_str(&format!("<h2>{}</h2>\n", escape_html(text))),
+            Block::Text(text) => html.push_str(&format!("<p>{}</p>\n", escape_html(text))),
+            Block::Table { columns, rows } => {
+                let class = |column: &Column| if column.numeric { " class=\"num\"" } else { "" };
+                html.push_str("<table>\n<thead><tr>");
+                for column in columns {
+                    html.push_str(&format!(
+                        "<th{}>{}</th>",
+                        class(column),
+                        escape_html(column.title)
+                    ));
+                }
+                html.push_str("</tr></thead>\n<tbody>\n");
+                for row in rows {
+                    html.push_str("<tr>");
+                    for (column, cell) in columns.iter().zip(row) {
+                        html.push_str(&format!("<td{}>{}</td>", class(column), escape_html(cell)));
+                    }
+                    html.push_str("</tr>\n");
+                }
+                html.push_str("</tbody>\n</table>\n");
+            }
+        }
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// One line of PDF text.
+#[derive(Debug, Clone, PartialEq)]
+struct PdfLine {
+    bold: bool,
+    size: i64,
+    text: String,
+}
+
+/// Courier glyphs are 0.6 em wide, so this many fit across the page.
+fn chars_per_line(size: i64) -> usize {
+    ((PAGE_WIDTH - 2 * PAGE_MARGIN) * 10 / (6 * size)) as usize
+}
+
+fn pdf_lines(blocks: &[Block]) -> Vec<PdfLine> {
+    let mut lines = Vec::new();
+    for block in blocks {
+        match block {
+            Block::Title(text) => push_wrapped(&mut lines, true, TITLE_SIZE, text),
+            Block::Heading(text) => {
+                push_wrapped(&mut lines, false, BODY_SIZE, "");
+                push_wrapped(&mut lines, true, HEADING_SIZE, text);
+            }
+            Block::Text(text) => push_wrapped(&mut lines, false, BODY_SIZE, text),
+            Block::Table { columns, rows } => {
+                let widths = column_widths(columns, rows, chars_per_line(BODY_SIZE));
+                let titles: Vec<String> = columns.iter().map(|c| c.title.to_string()).collect();
+                lines.push(PdfLine {
+                    bold: true,
+                    size: BODY_SIZE,
+                    text: table_line(columns, &widths, &titles),
+                });
+                for row in rows {
+                    lines.push(PdfLine {
+                        bold: false,
+                        size: BODY_SIZE,
+                        text: table_line(columns, &widths, row),
+                    });
+                }
+            }
+        }
+    }
+    lines
+}
+
+fn push_wrapped(lines: &mut Vec<PdfLine>, bold: bool, size: i64, text: &str) {
+    for text in wrap(text, chars_per_line(size)) {
+        lines.push(PdfLine { bold, size, text });
+    }
+}
+
+/// Widths of the columns of a table, shrinking the widest text column when
+/// the table would not fit in `available` characters.
+fn column_widths(columns: &[Column], rows: &[Vec<String>], available: usize) -> Vec<usize> {
+    let mut widths: Vec<usize> = columns.iter().map(|c| c.title.chars().count()).collect();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+    let total = widths.iter().sum::<usize>() + COLUMN_GAP * widths.len().saturating_sub(1);
+    if total > available {
+        let widest_text = (0..columns.len())
+            .filter(|&index| !columns[index].numeric)
+            .max_by_key(|&index| widths[index]);
+        if let Some(index) = widest_text {
+            let excess = total - available;
+            widths[index] = widths[index]
+                .saturating_sub(excess)
+                .max(columns[index].title.len());
+        }
+    }
+    widths
+}
+
+fn table_line(columns: &[Column], widths: &[usize], cells: &[String]) -> String {
+    let mut line = String::new();
+    for (index, (column, width)) in columns.iter().zip(widths).enumerate() {
+        if index > 0 {
+            line.push_str(&" ".repeat(COLUMN_GAP));
+        }
+        let cell = cells.get(index).map(String::as_str).unwrap_or_default();
+        let cell = truncate(cell, *width);
+        let padding = " ".repeat(width.saturating_sub(cell.chars().count()));
+        if column.numeric {
+            line.push_str(&padding);
+            line.push_str(&cell);
+        } else {
+            line.push_str(&cell);
+            line.push_str(&padding);
+        }
+    }
+    line.trim_end().to_string()
+}
+
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+    format!("{kept}...")
+}
+
+/// Break `text` at spaces into lines of at most `width` characters; words
+/// longer than a line are split.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    for word in text.split(' ') {
+        let mut word: Vec<char> = word.chars().collect();
+        loop {
+            let used = current.chars().count();
+            let needed = if used == 0 {
+                word.len()
+            } else {
+                used + 1 + word.len()
+            };
+            if needed <= width {
+                if used > 0 {
+                    current.push(' ');
+                }
+                current.extend(word);
+                break;
+            }
+            if used > 0 {
+                lines.push(std::mem::take(&mut current));
+                continue;
+            }
+            let rest = word.split_off(width.min(word.len()));
+            lines.push(word.into_iter().collect());
+            word = rest;
+            if word.is_empty() {
+                break;
+            }
+        }
+    }
+    lines.push(current);
+    lines
+}
+
+/// Encode `text` for a font using WinAnsiEncoding. Latin-1 characters map to
+/// themselves; anything else prints as `?`.
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            '\u{20}'..='\u{7e}' | '\u{a0}'..='\u{ff}' => c as u8,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201c}' => 0x93,
+            '\u{201d}' => 0x94,
+            '\u{2026}' => 0x85,
+            '\u{20ac}' => 0x80,
+            _ => b'?',
+        })
+        .collect()
+}
+
+fn render_pdf(blocks: &[Block]) -> io::Result<Vec<u8>> {
+    let pdf_error = |err: lopdf::Error| io::Error::other(format!("failed to write PDF: {err}"));
+
+    let mut pages: Vec<Vec<(i64, &PdfLine)>> = vec![Vec::new()];
+    let lines = pdf_lines(blocks);
+    let bottom = PAGE_MARGIN + FOOTER_SIZE * 2;
+    let mut y = PAGE_HEIGHT - PAGE_MARGIN;
+    for line in &lines {
+        let leading = line.size + 3;
+        if y - leading < bottom {
+            pages.push(Vec::new());
+            y = PAGE_HEIGHT - PAGE_MARGIN;
+        }
+        y -= leading;
+        if let Some(page) = pages.last_mut() {
+            page.push((y, line));
+        }
+    }
+
+    let mut doc = lopdf::Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font = |name: &str| {
+        dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => name,
+            "Encoding" => "WinAnsiEncoding",
+        }
+    };
+    let regular_id = doc.add_object(font("Courier"));
+    let bold_id = doc.add_object(font("Courier-Bold"));
+    let resources_id = doc.add_object(dictionary! {
+        "Font" => dictionary! {
+            "F1" => regular_id,
+            "F2" => bold_id,
+        },
+    });
+
+    let page_count = pages.len();
+    let mut kids = Vec::new();
+    for (index, page) in pages.iter().enumerate() {
+        let mut operations = Vec::new();
+        let mut text = |font: &str, size: i64, x: i64, y: i64, text: &str| {
+            operations.push(Operation::new("BT", vec![]));
+            operations.push(Operation::new("Tf", vec![font.into(), size.into()]));
+            operations.push(Operation::new("Td", vec![x.into(), y.into()]));
+            operations.push(Operation::new(
+                "Tj",
+                vec![Object::string_literal(win_ansi(text))],
+            ));
+            operations.push(Operation::new("ET", vec![]));
+        };
+        for (y, line) in page {
+            if line.text.is_empty() {
+                continue;
+            }
+            let font = if line.bold { "F2" } else { "F1" };
+            text(font, line.size, PAGE_MARGIN, *y, &line.text);
+        }
+        text(
+            "F1",
+            FOOTER_SIZE,
+            PAGE_MARGIN,
+            PAGE_MARGIN,
+            &format!("Page {} of {page_count}", index + 1),
+        );
+        let content = Content { operations }.encode().map_err(pdf_error)?;
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+        let page_id = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+        });
+        kids.push(Object::from(page_id));
+    }
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Kids" => kids,
+            "Count" => page_count as i64,
+            "Resources" => resources_id,
+            "MediaBox" => vec![
+                Object::from(0),
+                Object::from(0),
+                Object::from(PAGE_WIDTH),
+                Object::from(PAGE_HEIGHT),
+            ],
+        }),
+    );
+    let catalog_id = doc.add_object(dictionary! {
+        "Type" => "Catalog",
+        "Pages" => pages_id,
+    });
+    doc.trailer.set("Root", catalog_id);
+    doc.compress();
+
+    let mut bytes = Vec::new();
+    doc.save_to(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    fn line(day: &str, description: &str, account: &str, amount: f64, evidence: &[&str]) -> Line {
+        Line {
+            date: date(day),
+            description: description.to_string(),
+            account: account.to_string(),
+            commodity: "$".to_string(),
+            amount,
+            decimals: 2,
+            evidence: evidence.iter().map(|e| e.to_string()).collect(),
+        }
+    }
+
+    fn march() -> Period {
+        Period {
+            start: date("2025-03-01"),
+            end: date("2025-03-31"),
+        }
+    }
+
+    #[test]
+    fn statements_run_from_opening_to_closing_balance() {
+        let lines = vec![
+            line(
+                "2025-03-05",
+                "Grocer",
+                "Assets:Checking",
+                -40.0,
+                &["march.csv:4:1"],
+            ),
+            line("2025-02-20", "Paycheck", "Assets:Checking", 1000.0, &[]),
+            line(
+                "2025-03-01",
+                "Paycheck",
+                "Assets:Checking",
+                1000.0,
+                &["march.csv:2:1"],
+            ),
+            line(
+                "2025-03-10",
+                "Interest",
+                "Assets:Checking:Savings",
+                1.5,
+                &["stmt.pdf#page=2"],
+            ),
+            line(
+                "2025-03-12",
+                "Grocer",
+                "Expenses:Food",
+                40.0,
+                &["march.csv:4:1"],
+            ),
+            line("2025-04-01", "Rent", "Assets:Checking", -900.0, &[]),
+            line("2025-03-15", "Elsewhere", "Assets:CheckingOld", 5.0, &[]),
+        ];
+        let report = build_report(
+            &lines,
+            march(),
+            &["Assets:Checking".to_string()],
+            false,
+            true,
+        );
+
+        let statement = &report.statements[0];
+        assert_eq!(statement.opening["$"], 1000.0);
+        assert_eq!(statement.closing["$"], 1961.5);
+        let rows: Vec<(&str, f64, f64)> = statement
+            .rows
+            .iter()
+            .map(|row| (row.description.as_str(), row.amount, row.balance))
+            .collect();
+        assert_eq!(
+            rows,
+            vec![
+                ("Paycheck", 1000.0, 2000.0),
+                ("Grocer", -40.0, 1960.0),
+                ("Interest (Savings)", 1.5, 1961.5),
+            ]
+        );
+        assert_eq!(statement.rows[1].notes, vec![1]);
+        assert_eq!(statement.rows[0].notes, vec![1]);
+        assert_eq!(statement.rows[2].notes, vec![2]);
+
+        let appendix = report.appendix.unwrap();
+        assert_eq!(appendix[0].document, "march.csv");
+        assert_eq!(appendix[0].locations, vec!["2:1", "4:1"]);
+        assert_eq!(appendix[1].document, "stmt.pdf");
+        assert_eq!(appendix[1].locations, vec!["#page=2"]);
+        assert_eq!(report.net_worth, None);
+    }
+
+    #[test]
+    fn net_worth_compares_the_start_and_end_of_the_period() {
+        let lines = vec![
+            line("2025-02-01", "Opening", "Assets:Checking", 500.0, &[]),
+            line(
+                "2025-02-01",
+                "Opening",
+                "Liabilities:Card:Visa",
+                -200.0,
+                &[],
+            ),
+            line("2025-03-10", "Payment", "Assets:Checking", -100.0, &[]),
+            line("2025-03-10", "Payment", "Liabilities:Card:Visa", 100.0, &[]),
+            line("2025-03-11", "Lunch", "Expenses:Food", 12.0, &[]),
+            line("2025-04-02", "Payday", "Assets:Checking", 900.0, &[]),
+        ];
+        let report = build_report(&lines, march(), &[], true, false);
+        assert_eq!(
+            report.net_worth.unwrap(),
+            vec![
+                NetWorthRow {
+                    account: "Assets:Checking".to_string(),
+                    commodity: "$".to_string(),
+                    start: 500.0,
+                    end: 400.0,
+                },
+                NetWorthRow {
+                    account: "Liabilities:Card".to_string(),
+                    commodity: "$".to_string(),
+                    start: -200.0,
+                    end: -100.0,
+                },
+            ]
+        );
+        assert!(report.statements.is_empty());
+        assert_eq!(report.appendix, None);
+    }
+
+    #[test]
+    fn amounts_are_grouped_and_signed() {
+        assert_eq!(format_amount(1234567.5, "$", 2), "$1,234,567.50");
+        assert_eq!(format_amount(-3.0, "$", 2), "-$3.00");
+        assert_eq!(format_amount(-0.001, "$", 2), "$0.00");
+        assert_eq!(format_amount(12.5, "AAPL", 4), "12.5000 AAPL");
+        assert_eq!(wrap("one two three", 7), vec!["one two", "three"]);
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+    }
+
+    #[test]
+    fn renders_escaped_html_and_a_readable_pdf() {
+        let lines: Vec<Line> = (1..=28)
+            .flat_map(|day| {
+                let day = format!("2025-03-{day:02}");
+                vec![
+                    line(
+                        &day,
+                        "Coffee <& tea>",
+                        "Assets:Checking",
+                        -4.25,
+                        &["a.csv:1:1"],
+                    ),
+                    line(&day, "Refund", "Assets:Checking", 1.0, &[]),
+                ]
+            })
+            .collect();
+        let report = build_report(
+            &lines,
+            march(),
+            &["Assets:Checking".to_string()],
+            true,
+            true,
+        );
+        let blocks = report_blocks(&report, "Household", date("2025-04-02"));
+
+        let html = render_html(&blocks);
+        assert!(html.contains("<title>Household: account statements</title>"));
+        assert!(html.contains("<td>Coffee &lt;&amp; tea&gt;</td>"));
+        assert!(html.contains("<td class=\"num\">-$91.00</td>"));
+        assert!(html.contains("Closing balance 2025-03-31: -$91.00"));
+        assert!(!html.contains("<& tea>"));
+
+        let pdf = render_pdf(&blocks).unwrap();
+        let doc = lopdf::Document::load_mem(&pdf).unwrap();
+        let pages = doc.get_pages();
+        assert!(pages.len() > 1);
+        let text = doc.extract_text(&[1]).unwrap();
+        assert!(text.contains("Household: account statements"));
+        assert!(text.contains("Coffee <& tea>"));
+        let last = doc.extract_text(&[pages.len() as u32]).unwrap();
+        assert!(last.contains("Evidence appendix"));
+        assert!(last.contains(&format!("Page {} of {}", pages.len(), pages.len())));
+    }
+}
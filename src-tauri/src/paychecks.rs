@@ -0,0 +1,412 @@
+//! Pay stub parsing and gross-to-net paycheck splits.
+//!
+//! Payroll extensions (ADP, Gusto, Workday, ...) save each pay stub as a JSON
+//! document with `documentType: "paystub"`. A stub lists earnings, taxes,
+//! and deductions; the net pay is what lands in the bank. When an unposted
+//! bank deposit matches a stub's net pay, the deposit is split into the
+//! stub's gross income, tax, and deduction postings.
+
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::Path;
+
+use crate::account_journal::AccountEntry;
+use crate::loans::{format_cents, parse_cents};
+use crate::post::SplitCounterpart;
+use crate::scrape::DocumentType;
+
+/// How far a deposit may be from the pay date and still match the stub.
+const DEPOSIT_WINDOW_DAYS: i64 = 5;
+
+/// A pay stub document as saved by a payroll extension.
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct Paystub {
+    pub pay_date: String,
+    #[serde(default)]
+    #[ts(optional)]
+    pub employer: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub period_start: Option<String>,
+    #[serde(default)]
+    #[ts(optional)]
+    pub period_end: Option<String>,
+    #[serde(default = "default_commodity")]
+    pub commodity: String,
+    pub earnings: Vec<PaystubLine>,
+    #[serde(default)]
+    pub taxes: Vec<PaystubLine>,
+    #[serde(default)]
+    pub deductions: Vec<PaystubLine>,
+    /// Net pay. Computed from the lines when omitted.
+    #[serde(default)]
+    #[ts(optional)]
+    pub net: Option<String>,
+}
+
+/// One earnings, tax, or deduction line, e.g. `{ "name": "401(k)",
+/// "amount": "200.00", "kind": "retirement" }`.
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PaystubLine {
+    pub name: String,
+    pub amount: String,
+    /// Deductions only; decides the default account.
+    #[serde(default)]
+    #[ts(optional)]
+    pub kind: Option<DeductionKind>,
+    /// GL account overriding the default for this line.
+    #[serde(default)]
+    #[ts(optional)]
+    pub account: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export_to = "index.ts")]
+pub enum DeductionKind {
+    Retirement,
+    Insurance,
+    Other,
+}
+
+/// A parsed pay stub and where it was found.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PaystubDocument {
+    pub login_name: String,
+    pub label: String,
+    pub filename: String,
+    pub paystub: Paystub,
+    pub gross: String,
+    pub total_taxes: String,
+    pub total_deductions: String,
+    pub net: String,
+}
+
+/// Suggested gross-to-net split for a paycheck deposit entry.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PaycheckSplitSuggestion {
+    pub login_name: String,
+    pub label: String,
+    /// The pay stub document the split comes from.
+    pub filename: String,
+    pub pay_date: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub employer: Option<String>,
+    pub gross: String,
+    /// Counterpart legs ready for `post_login_account_entry_split`.
+    pub counterparts: Vec<SplitCounterpart>,
+}
+
+impl PaystubDocument {
+    fn new(login_name: &str, label: &str, filename: String, paystub: Paystub) -> Self {
+        Self {
+            login_name: login_name.to_string(),
+            label: label.to_string(),
+            filename,
+            gross: format_cents(line_total(&paystub.earnings)),
+            total_taxes: format_cents(line_total(&paystub.taxes)),
+            total_deductions: format_cents(line_total(&paystub.deductions)),
+            net: format_cents(computed_net_cents(&paystub)),
+            paystub,
+        }
+    }
+}
+
+fn default_commodity() -> String {
+    "USD".to_string()
+}
+
+/// Parse and check a pay stub: amounts must be non-negative decimals and
+/// net pay, when given, must equal gross less taxes and deductions.
+pub fn parse_paystub(text: &str) -> io::Result<Paystub> {
+    let paystub: Paystub = serde_json::from_str(text)
+        .map_err(|err| invalid_data(format!("invalid pay stub: {err}")))?;
+    NaiveDate::parse_from_str(&paystub.pay_date, "%Y-%m-%d")
+        .map_err(|_| invalid_data(format!("payDate must be YYYY-MM-DD: {}", paystub.pay_date)))?;
+    if paystub.earnings.is_empty() {
+        return Err(invalid_data("pay stub has no earnings"));
+    }
+    for line in paystub
+        .earnings
+        .iter()
+        .chain(&paystub.taxes)
+        .chain(&paystub.deductions)
+    {
+        if line.name.trim().is_empty() {
+            return Err(invalid_data("pay stub line has no name"));
+        }
+        if parse_cents(&line.amount).map_or(true, |cents| cents < 0) {
+            return Err(invalid_data(format!(
+                "{}: amount must be a non-negative decimal, got {}",
+                line.name, line.amount
+            )));
+        }
+    }
+    let computed = computed_net_cents(&paystub);
+    if let Some(net) = &paystub.net {
+        if parse_cents(net) != Some(computed) {
+            return Err(invalid_data(format!(
+                "net pay {net} does not equal gross less taxes and deductions ({})",
+                format_cents(computed)
+            )));
+        }
+    }
+    Ok(paystub)
+}
+
+fn line_total(lines: &[PaystubLine]) -> i64 {
+    lines
+        .iter()
+        .filter_map(|line| parse_cents(&line.amount))
+        .sum()
+}
+
+fn computed_net_cents(paystub: &Paystub) -> i64 {
+    line_total(&paystub.earnings) - line_total(&paystub.taxes) - line_total(&paystub.deductions)
+}
+
+/// Read every pay stub document in the ledger. Stubs that fail to parse are
+/// skipped with a warning.
+pub fn list_paystubs(ledger_dir: &Path) -> io::Result<Vec<PaystubDocument>> {
+    let mut paystubs = Vec::new();
+    for login_name in crate::login_config::list_logins(ledger_dir)? {
+        for label in crate::login_config::list_login_labels(ledger_dir, &login_name)? {
+            let documents =
+                crate::extract::list_documents_for_login_account(ledger_dir, &login_name, &label)?;
+            let documents_dir = crate::account_journal::login_account_documents_dir(
+                ledger_dir,
+                &login_name,
+                &label,
+            );
+            for doc in crate::extract::filter_documents_by_type(documents, DocumentType::Paystub) {
+                let parsed = std::fs::read_to_string(documents_dir.join(&doc.filename))
+                    .and_then(|text| parse_paystub(&text));
+                match parsed {
+                    Ok(paystub) => paystubs.push(PaystubDocument::new(
+                        &login_name,
+                        &label,
+                        doc.filename,
+                        paystub,
+                    )),
+                    Err(err) => eprintln!(
+                        "warning: skipping pay stub {login_name}/{label}/{}: {err}",
+                        doc.filename
+                    ),
+                }
+            }
+        }
+    }
+    paystubs.sort_by(|a, b| a.paystub.pay_date.cmp(&b.paystub.pay_date));
+    Ok(paystubs)
+}
+
+/// Suggest a gross-to-net split for an unposted deposit.
+///
+/// The entry matches the pay stub whose net pay equals the deposit, in the
+/// same commodity, with the closest pay date within a few days.
+pub fn suggest_paycheck_split(
+    paystubs: &[PaystubDocument],
+    entry: &AccountEntry,
+) -> Option<PaycheckSplitSuggestion> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    let deposit = parse_cents(&amount.quantity)?;
+    if deposit <= 0 {
+        return None;
+    }
+    let date = NaiveDate::parse_from_str(&entry.date, "%Y-%m-%d").ok()?;
+    let (_, stub) = paystubs
+        .iter()
+        .filter(|stub| {
+            stub.paystub.commodity == amount.commodity && parse_cents(&stub.net) == Some(deposit)
+        })
+        .filter_map(|stub| {
+            let pay_date = NaiveDate::parse_from_str(&stub.paystub.pay_date, "%Y-%m-%d").ok()?;
+            let distance = (date - pay_date).num_days().abs();
+            (distance <= DEPOSIT_WINDOW_DAYS).then_some((distance, stub))
+        })
+        .min_by_key(|(distance, _)| *distance)?;
+    Some(PaycheckSplitSuggestion {
+        login_name: stub.login_name.clone(),
+        label: stub.label.clone(),
+        filename: stub.filename.clone(),
+        pay_date: stub.paystub.pay_date.clone(),
+        employer: stub.paystub.employer.clone(),
+        gross: stub.gross.clone(),
+        counterparts: paycheck_counterparts(&stub.paystub),
+    })
+}
+
+/// Legs offsetting a net pay deposit: income is credited with each earnings
+/// line and each tax and deduction is debited. Lines sharing an account are
+/// combined.
+pub fn paycheck_counterparts(paystub: &Paystub) -> Vec<SplitCounterpart> {
+    let mut legs: Vec<(String, i64)> = Vec::new();
+    let mut add = |account: String, cents: i64| {
+        if cents == 0 {
+            return;
+        }
+        match legs.iter_mut().find(|(existing, _)| *existing == account) {
+            Some((_, total)) => *total += cents,
+            None => legs.push((account, cents)),
+        }
+    };
+    for line in &paystub.earnings {
+        let account = line_account(line, "Income:Salary");
+        add(account, -parse_cents(&line.amount).unwrap_or_default());
+    }
+    for line in &paystub.taxes {
+        let account = line_account(line, "Expenses:Taxes");
+        add(account, parse_cents(&line.amount).unwrap_or_default());
+    }
+    for line in &paystub.deductions {
+        let parent = match line.kind {
+            Some(DeductionKind::Retirement) => "Assets:Retirement",
+            Some(DeductionKind::Insurance) => "Expenses:Insurance",
+            Some(DeductionKind::Other) | None => "Expenses:Payroll Deductions",
+        };
+        let account = line_account(line, parent);
+        add(account, parse_cents(&line.amount).unwrap_or_default());
+    }
+    legs.into_iter()
+        .map(|(account, cents)| SplitCounterpart {
+            account,
+            amount: Some(format!("{} {}", format_cents(cents), paystub.commodity)),
+        })
+        .collect()
+}
+
+/// The line's own account, or `<parent>:<line name>`.
+fn line_account(line: &PaystubLine, parent: &str) -> String {
+    if let Some(account) = line
+        .account
+        .as_deref()
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+    {
+        return account.to_string();
+    }
+    let name = line
+        .name
+        .replace(':', " ")
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("{parent}:{name}")
+}
+
+fn invalid_data(message: impl Into<String>) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.into())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    const STUB: &str = r#"{
+        "payDate": "2024-01-15",
+        "employer": "Acme Corp",
+        "earnings": [
+            { "name": "Regular", "amount": "4,000.00" },
+            { "name": "Bonus", "amount": "500.00", "account": "Income:Bonus" }
+        ],
+        "taxes": [
+            { "name": "Federal Income Tax", "amount": "600.00" },
+            { "name": "Social Security", "amount": "279.00" }
+        ],
+        "deductions": [
+            { "name": "401(k)", "amount": "450.00", "kind": "retirement" },
+            { "name": "Medical", "amount": "120.50", "kind": "insurance" }
+        ],
+        "net": "3050.50"
+    }"#;
+
+    fn deposit(date: &str, amount: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            "ACME CORP PAYROLL".to_string(),
+            Vec::new(),
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: amount.to_string(),
+                }),
+            }],
+        )
+    }
+
+    fn stub_document() -> PaystubDocument {
+        PaystubDocument::new(
+            "adp",
+            "acme",
+            "paystubs/2024-01-15-stub.json".to_string(),
+            parse_paystub(STUB).unwrap(),
+        )
+    }
+
+    #[test]
+    fn parse_paystub_checks_net_pay() {
+        let stub = stub_document();
+        assert_eq!(stub.gross, "4500.00");
+        assert_eq!(stub.net, "3050.50");
+        let wrong = STUB.replace("3050.50", "3000.00");
+        let err = parse_paystub(&wrong).unwrap_err();
+        assert!(err.to_string().contains("does not equal"), "{err}");
+        assert!(parse_paystub(r#"{ "payDate": "2024-01-15", "earnings": [] }"#).is_err());
+    }
+
+    #[test]
+    fn deposit_matching_net_pay_gets_gross_to_net_split() {
+        let stubs = vec![stub_document()];
+        let suggestion = suggest_paycheck_split(&stubs, &deposit("2024-01-16", "3050.50")).unwrap();
+        assert_eq!(suggestion.filename, "paystubs/2024-01-15-stub.json");
+        let legs: Vec<(String, String)> = suggestion
+            .counterparts
+            .into_iter()
+            .map(|c| (c.account, c.amount.unwrap()))
+            .collect();
+        assert_eq!(
+            legs,
+            vec![
+                (
+                    "Income:Salary:Regular".to_string(),
+                    "-4000.00 USD".to_string()
+                ),
+                ("Income:Bonus".to_string(), "-500.00 USD".to_string()),
+                (
+                    "Expenses:Taxes:Federal Income Tax".to_string(),
+                    "600.00 USD".to_string()
+                ),
+                (
+                    "Expenses:Taxes:Social Security".to_string(),
+                    "279.00 USD".to_string()
+                ),
+                (
+                    "Assets:Retirement:401(k)".to_string(),
+                    "450.00 USD".to_string()
+                ),
+                (
+                    "Expenses:Insurance:Medical".to_string(),
+                    "120.50 USD".to_string()
+                ),
+            ]
+        );
+
+        assert!(suggest_paycheck_split(&stubs, &deposit("2024-01-25", "3050.50")).is_none());
+        assert!(suggest_paycheck_split(&stubs, &deposit("2024-01-15", "3050.49")).is_none());
+        assert!(suggest_paycheck_split(&stubs, &deposit("2024-01-15", "-3050.50")).is_none());
+    }
+}
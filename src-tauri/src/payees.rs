@@ -0,0 +1,297 @@
+//! Payee directory built from the general journal.
+//!
+//! GL transactions are grouped by normalized payee (the alert payee key:
+//! uppercase letters only, so store numbers and punctuation don't split a
+//! merchant). Each payee reports when it was first and last seen, how many
+//! transactions it has, their net-worth effect per commodity, and the
+//! category account most of its transactions post to. The directory feeds
+//! payee autocompletion in manual entry and the payee detail view.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::hledger::Transaction;
+use crate::ledger_open::TransactionRow;
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct PayeeTotal {
+    pub commodity: String,
+    /// Sum of the transactions' Assets and Liabilities postings, so spending
+    /// is negative.
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PayeeSummary {
+    /// Normalized payee key.
+    pub key: String,
+    /// The description used most often for this payee, for display and
+    /// autocompletion.
+    pub name: String,
+    pub first_seen: String,
+    pub last_seen: String,
+    pub transaction_count: usize,
+    pub totals: Vec<PayeeTotal>,
+    /// The category account used by the most transactions, if any.
+    pub dominant_category: Option<String>,
+}
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PayeeDetail {
+    pub payee: PayeeSummary,
+    /// The payee's transactions, newest first.
+    pub transactions: Vec<TransactionRow>,
+}
+
+/// Payees in the general journal, most transactions first. With `query`,
+/// only payees whose name or key contains it (case-insensitively) are
+/// returned, and those starting with it come first.
+pub fn list_payees(ledger_dir: &Path, query: Option<&str>) -> io::Result<Vec<PayeeSummary>> {
+    let transactions = gl_transactions(ledger_dir)?;
+    let mut payees = summarize_payees(&transactions);
+    if let Some(query) = query.map(str::trim).filter(|q| !q.is_empty()) {
+        let query = query.to_uppercase();
+        let query_key = crate::alerts::payee_key(&query);
+        payees.retain(|payee| {
+            payee.name.to_uppercase().contains(&query)
+                || (!query_key.is_empty() && payee.key.contains(&query_key))
+        });
+        payees.sort_by_key(|payee| {
+            let prefix = payee.name.to_uppercase().starts_with(&query)
+                || (!query_key.is_empty() && payee.key.starts_with(&query_key));
+            !prefix
+        });
+    }
+    Ok(payees)
+}
+
+/// One payee's summary and transaction history. `payee` may be a key or any
+/// description that normalizes to one.
+pub fn get_payee(ledger_dir: &Path, payee: &str) -> io::Result<PayeeDetail> {
+    let key = crate::alerts::payee_key(payee);
+    let transactions: Vec<Transaction> = gl_transactions(ledger_dir)?
+        .into_iter()
+        .filter(|txn| crate::alerts::payee_key(&txn.tdescription) == key)
+        .collect();
+    let summary = summarize_payees(&transactions)
+        .into_iter()
+        .next()
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("no transactions for payee '{payee}'"),
+            )
+        })?;
+    let mut rows = crate::ledger_open::build_transaction_rows(ledger_dir, &transactions)?;
+    rows.sort_by(|a, b| b.date.cmp(&a.date).then_with(|| b.id.cmp(&a.id)));
+    Ok(PayeeDetail {
+        payee: summary,
+        transactions: rows,
+    })
+}
+
+fn gl_transactions(ledger_dir: &Path) -> io::Result<Vec<Transaction>> {
+    let gl_path = ledger_dir.join("general.journal");
+    if !gl_path.exists() {
+        return Ok(Vec::new());
+    }
+    crate::ledger_open::run_hledger_print(&gl_path)
+}
+
+#[derive(Default)]
+struct PayeeStats<'a> {
+    names: BTreeMap<&'a str, usize>,
+    first_seen: Option<&'a str>,
+    last_seen: Option<&'a str>,
+    count: usize,
+    totals: BTreeMap<String, f64>,
+    categories: BTreeMap<&'a str, usize>,
+}
+
+fn summarize_payees(transactions: &[Transaction]) -> Vec<PayeeSummary> {
+    let mut stats: BTreeMap<String, PayeeStats> = BTreeMap::new();
+    for txn in transactions {
+        let key = crate::alerts::payee_key(&txn.tdescription);
+        if key.is_empty() {
+            continue;
+        }
+        let entry = stats.entry(key).or_default();
+        *entry.names.entry(txn.tdescription.trim()).or_default() += 1;
+        let date = txn.tdate.as_str();
+        entry.first_seen = Some(entry.first_seen.map_or(date, |first| first.min(date)));
+        entry.last_seen = Some(entry.last_seen.map_or(date, |last| last.max(date)));
+        entry.count += 1;
+
+        let mut categories = Vec::new();
+        for posting in &txn.tpostings {
+            if is_balance_sheet_account(&posting.paccount) {
+                for amount in &posting.pamount {
+                    *entry.totals.entry(amount.acommodity.clone()).or_default() +=
+                        amount.aquantity.floating_point;
+                }
+            } else if !categories.contains(&posting.paccount.as_str()) {
+                categories.push(posting.paccount.as_str());
+            }
+        }
+        for category in categories {
+            *entry.categories.entry(category).or_default() += 1;
+        }
+    }
+
+    let mut payees: Vec<PayeeSummary> = stats
+        .into_iter()
+        .map(|(key, stats)| PayeeSummary {
+            name: most_common(&stats.names).unwrap_or(&key).to_string(),
+            key,
+            first_seen: stats.first_seen.unwrap_or_default().to_string(),
+            last_seen: stats.last_seen.unwrap_or_default().to_string(),
+            transaction_count: stats.count,
+            totals: stats
+                .totals
+                .into_iter()
+                .map(|(commodity, amount)| PayeeTotal { commodity, amount })
+                .collect(),
+            dominant_category: most_common(&stats.categories).map(ToOwned::to_owned),
+        })
+        .collect();
+    payees.sort_by(|a, b| {
+        b.transaction_count
+            .cmp(&a.transaction_count)
+            .then_with(|| b.last_seen.cmp(&a.last_seen))
+            .then_with(|| a.key.cmp(&b.key))
+    });
+    payees
+}
+
+/// The most frequent value; ties go to the alphabetically first.
+fn most_common<'a>(counts: &BTreeMap<&'a str, usize>) -> Option<&'a str> {
+    counts
+        .iter()
+        .rev()
+        .max_by_key(|(_, count)| **count)
+        .map(|(value, _)| *value)
+}
+
+fn is_balance_sheet_account(account: &str) -> bool {
+    ["Assets", "Liabilities", "Equity"].iter().any(|top| {
+        account == *top
+            || account
+                .strip_prefix(top)
+                .is_some_and(|rest| rest.starts_with(':'))
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::hledger::{Amount, DecimalRaw, Posting, PostingType, SourcePos, SourceSpan, Status};
+
+    fn posting(account: &str, cents: i64) -> Posting {
+        Posting {
+            pdate: None,
+            pdate2: None,
+            pstatus: Status::Unmarked,
+            paccount: account.to_string(),
+            pamount: vec![Amount {
+                acommodity: "USD".to_string(),
+                aquantity: DecimalRaw {
+                    decimal_places: 2,
+                    decimal_mantissa: serde_json::Number::from(cents),
+                    floating_point: cents as f64 / 100.0,
+                },
+                astyle: None,
+                acost: None,
+                acostbasis: None,
+            }],
+            pcomment: String::new(),
+            ptype: PostingType::RegularPosting,
+            ptags: vec![],
+            pbalanceassertion: None,
+            ptransaction_index: None,
+            poriginal: None,
+        }
+    }
+
+    fn txn(date: &str, description: &str, category: &str, cents: i64) -> Transaction {
+        let pos = SourcePos {
+            source_name: String::new(),
+            source_line: 1,
+            source_column: 1,
+        };
+        Transaction {
+            tindex: 1,
+            tprecedingcomment: String::new(),
+            tsourcepos: SourceSpan(pos.clone(), pos),
+            tdate: date.to_string(),
+            tdate2: None,
+            tstatus: Status::Cleared,
+            tcode: String::new(),
+            tdescription: description.to_string(),
+            tcomment: String::new(),
+            ttags: vec![],
+            tpostings: vec![
+                posting("Liabilities:Card", -cents),
+                posting(category, cents),
+            ],
+        }
+    }
+
+    #[test]
+    fn payees_group_by_normalized_description() {
+        let transactions = vec![
+            txn(
+                "2025-01-04",
+                "Trader Joe's #123",
+                "Expenses:Groceries",
+                4500,
+            ),
+            txn("2025-02-09", "TRADER JOE'S 456", "Expenses:Groceries", 3000),
+            txn(
+                "2025-03-01",
+                "Trader Joe's #123",
+                "Expenses:Household",
+                1250,
+            ),
+            txn("2025-02-15", "Blue Bottle", "Expenses:Dining", 600),
+            txn("2025-02-16", "1234", "Expenses:Misc", 100),
+        ];
+        let payees = summarize_payees(&transactions);
+        assert_eq!(payees.len(), 2);
+
+        let grocer = &payees[0];
+        assert_eq!(grocer.key, "TRADER JOE S");
+        assert_eq!(grocer.name, "Trader Joe's #123");
+        assert_eq!(grocer.first_seen, "2025-01-04");
+        assert_eq!(grocer.last_seen, "2025-03-01");
+        assert_eq!(grocer.transaction_count, 3);
+        assert_eq!(
+            grocer.totals,
+            vec![PayeeTotal {
+                commodity: "USD".to_string(),
+                amount: -87.5,
+            }]
+        );
+        assert_eq!(
+            grocer.dominant_category.as_deref(),
+            Some("Expenses:Groceries")
+        );
+        assert_eq!(payees[1].name, "Blue Bottle");
+    }
+
+    #[test]
+    fn transfers_have_no_dominant_category() {
+        let transactions = vec![txn("2025-01-04", "Card payment", "Assets:Checking", 20000)];
+        let payees = summarize_payees(&transactions);
+        assert_eq!(payees[0].dominant_category, None);
+        assert_eq!(payees[0].totals[0].amount, 0.0);
+    }
+}
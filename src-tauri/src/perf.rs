@@ -0,0 +1,137 @@
+//! Timing instrumentation for profiling large ledgers.
+//!
+//! Built with the `perf-stats` feature, every [`span`] records how long it
+//! was alive under its category and name: journal reads, dedup passes,
+//! hledger calls, and IPC command latencies. Without the feature spans are
+//! empty and cost nothing. `get_performance_stats` returns the breakdown.
+
+use serde::Serialize;
+
+#[cfg(feature = "perf-stats")]
+use std::collections::BTreeMap;
+#[cfg(feature = "perf-stats")]
+use std::sync::{Mutex, OnceLock};
+#[cfg(feature = "perf-stats")]
+use std::time::Instant;
+
+/// Aggregate timing for one category and name.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PerfStat {
+    pub category: String,
+    pub name: String,
+    #[ts(type = "number")]
+    pub count: u64,
+    pub total_ms: f64,
+    pub mean_ms: f64,
+    pub max_ms: f64,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PerformanceStats {
+    /// Whether this build records timings (the `perf-stats` feature).
+    pub enabled: bool,
+    /// Slowest total first.
+    pub stats: Vec<PerfStat>,
+}
+
+/// Records its lifetime when dropped.
+#[must_use = "a span records its timing when dropped"]
+pub struct Span {
+    #[cfg(feature = "perf-stats")]
+    started: Instant,
+    #[cfg(feature = "perf-stats")]
+    key: (&'static str, String),
+}
+
+#[cfg(feature = "perf-stats")]
+fn registry() -> &'static Mutex<BTreeMap<(&'static str, String), PerfStat>> {
+    static REGISTRY: OnceLock<Mutex<BTreeMap<(&'static str, String), PerfStat>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(BTreeMap::new()))
+}
+
+/// Start timing `name` within `category`.
+#[cfg(feature = "perf-stats")]
+pub fn span(category: &'static str, name: &str) -> Span {
+    Span {
+        started: Instant::now(),
+        key: (category, name.to_string()),
+    }
+}
+
+/// Start timing `name` within `category`.
+#[cfg(not(feature = "perf-stats"))]
+pub fn span(_category: &'static str, _name: &str) -> Span {
+    Span {}
+}
+
+#[cfg(feature = "perf-stats")]
+impl Drop for Span {
+    fn drop(&mut self) {
+        let elapsed_ms = self.started.elapsed().as_secs_f64() * 1000.0;
+        if let Ok(mut registry) = registry().lock() {
+            let (category, name) = &self.key;
+            let stat = registry
+                .entry(self.key.clone())
+                .or_insert_with(|| PerfStat {
+                    category: category.to_string(),
+                    name: name.clone(),
+                    ..PerfStat::default()
+                });
+            stat.count += 1;
+            stat.total_ms += elapsed_ms;
+            stat.max_ms = stat.max_ms.max(elapsed_ms);
+            stat.mean_ms = stat.total_ms / stat.count as f64;
+        }
+    }
+}
+
+/// Timings recorded since startup or the last reset.
+pub fn performance_stats() -> PerformanceStats {
+    #[cfg(feature = "perf-stats")]
+    {
+        let mut stats: Vec<PerfStat> = registry()
+            .lock()
+            .map(|registry| registry.values().cloned().collect())
+            .unwrap_or_default();
+        stats.sort_by(|a, b| b.total_ms.total_cmp(&a.total_ms));
+        PerformanceStats {
+            enabled: true,
+            stats,
+        }
+    }
+    #[cfg(not(feature = "perf-stats"))]
+    PerformanceStats::default()
+}
+
+pub fn reset_performance_stats() {
+    #[cfg(feature = "perf-stats")]
+    if let Ok(mut registry) = registry().lock() {
+        registry.clear();
+    }
+}
+
+#[cfg(all(test, feature = "perf-stats"))]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn spans_accumulate_per_category_and_name() {
+        for _ in 0..3 {
+            let _span = span("test", "spans_accumulate");
+        }
+        let stats = performance_stats();
+        assert!(stats.enabled);
+        let stat = stats
+            .stats
+            .iter()
+            .find(|stat| stat.category == "test" && stat.name == "spans_accumulate")
+            .unwrap();
+        assert_eq!(stat.count, 3);
+        assert!(stat.max_ms <= stat.total_ms);
+    }
+}
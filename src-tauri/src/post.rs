@@ -1,16 +1,17 @@
-use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
+use std::fs;
+use std::io;
 use std::path::Path;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::account_journal::{self, AccountEntry};
 use crate::login_config;
 use crate::operations;
 
 /// One leg of a split posting supplied by the caller.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
 pub struct SplitCounterpart {
     pub account: String,
     /// Explicit amount string (e.g. `"100.00 USD"`).  The last leg may omit
@@ -94,9 +95,8 @@ pub fn post_entry(
     // Write updated account journal first. If this fails, nothing else was mutated.
     account_journal::write_journal(ledger_dir, account_name, &entries)?;
 
-    // Append to general.journal; rollback account journal on failure.
-    let journal_path = ledger_dir.join("general.journal");
-    if let Err(err) = append_to_journal(&journal_path, &gl_text) {
+    // Append to the GL; rollback account journal on failure.
+    if let Err(err) = crate::gl_layout::append_transaction(ledger_dir, &gl_text) {
         let _ = account_journal::write_journal(ledger_dir, account_name, &original_entries);
         return Err(err.into());
     }
@@ -107,6 +107,7 @@ pub fn post_entry(
         entry_id: entry_id.to_string(),
         counterpart_account: counterpart_account.to_string(),
         posting_index,
+        operator: None,
         timestamp: operations::now_timestamp(),
     };
     if let Err(err) = operations::append_gl_operation(ledger_dir, &op) {
@@ -193,8 +194,7 @@ pub fn post_login_account_entry(
 
     account_journal::write_journal_at_path(&journal_path, &entries)?;
 
-    let gl_journal_path = ledger_dir.join("general.journal");
-    if let Err(err) = append_to_journal(&gl_journal_path, &gl_text) {
+    if let Err(err) = crate::gl_layout::append_transaction(ledger_dir, &gl_text) {
         let _ = account_journal::write_journal_at_path(&journal_path, &original_entries);
         return Err(err.into());
     }
@@ -204,6 +204,7 @@ pub fn post_login_account_entry(
         entry_id: entry_id.to_string(),
         counterpart_account: counterpart_account.to_string(),
         posting_index,
+        operator: Some(lock_owner.to_string()),
         timestamp: operations::now_timestamp(),
     };
     if let Err(err) = operations::append_gl_operation(ledger_dir, &op) {
@@ -215,7 +216,7 @@ pub fn post_login_account_entry(
     let commit_msg = format!("post: {entry_id} → {counterpart_account}");
     if let Err(err) = crate::ledger::commit_post_changes(ledger_dir, login_name, label, &commit_msg)
     {
-        eprintln!("warning: git commit failed after post: {err}");
+        tracing::warn!("git commit failed after post: {err}");
     }
 
     Ok(gl_txn_id)
@@ -272,8 +273,7 @@ pub fn post_login_account_entry_split(
 
     account_journal::write_journal_at_path(&journal_path, &entries)?;
 
-    let gl_journal_path = ledger_dir.join("general.journal");
-    if let Err(err) = append_to_journal(&gl_journal_path, &gl_text) {
+    if let Err(err) = crate::gl_layout::append_transaction(ledger_dir, &gl_text) {
         let _ = account_journal::write_journal_at_path(&journal_path, &original_entries);
         return Err(err.into());
     }
@@ -284,6 +284,7 @@ pub fn post_login_account_entry_split(
         account: source_locator,
         entry_id: entry_id.to_string(),
         counterpart_accounts,
+        operator: Some(lock_owner.to_string()),
         timestamp: operations::now_timestamp(),
     };
     if let Err(err) = operations::append_gl_operation(ledger_dir, &op) {
@@ -300,7 +301,7 @@ pub fn post_login_account_entry_split(
     let commit_msg = format!("post: {entry_id} → {counterpart_summary}");
     if let Err(err) = crate::ledger::commit_post_changes(ledger_dir, login_name, label, &commit_msg)
     {
-        eprintln!("warning: git commit failed after split post: {err}");
+        tracing::warn!("git commit failed after split post: {err}");
     }
 
     Ok(gl_txn_id)
@@ -312,11 +313,9 @@ pub fn post_login_account_entry_split(
 
 /// Find a GL block by its id tag without removing it.
 fn find_gl_block(ledger_dir: &Path, gl_txn_id: &str) -> io::Result<Option<String>> {
-    let journal_path = ledger_dir.join("general.journal");
-    if !journal_path.exists() {
+    let Some((_, content)) = crate::gl_layout::find_transaction_file(ledger_dir, gl_txn_id)? else {
         return Ok(None);
-    }
-    let content = fs::read_to_string(&journal_path)?;
+    };
     let marker = format!("id: {gl_txn_id}");
     Ok(crate::gl_journal::split_journal_blocks(&content)
         .into_iter()
@@ -416,7 +415,7 @@ fn write_other_sides(
         if let Err(err) = account_journal::write_journal_at_path(&side.path, &side.updated) {
             // Best-effort rollback
             if let Some(ref removed) = removed_gl_block {
-                let _ = append_to_journal(&ledger_dir.join("general.journal"), removed);
+                let _ = crate::gl_layout::append_transaction(ledger_dir, removed);
             }
             for prev in other_sides.iter().take(i) {
                 let _ = account_journal::write_journal_at_path(&prev.path, &prev.original);
@@ -489,8 +488,7 @@ pub fn unpost_entry(
     // Write updated account journal.
     if let Err(err) = account_journal::write_journal(ledger_dir, account_name, &entries) {
         if let Some(removed) = &removed_gl_txn {
-            let journal_path = ledger_dir.join("general.journal");
-            let _ = append_to_journal(&journal_path, removed);
+            let _ = crate::gl_layout::append_transaction(ledger_dir, removed);
         }
         for side in &other_sides {
             let _ = account_journal::write_journal_at_path(&side.path, &side.original);
@@ -511,8 +509,7 @@ pub fn unpost_entry(
             let _ = account_journal::write_journal_at_path(&side.path, &side.original);
         }
         if let Some(removed) = removed_gl_txn {
-            let journal_path = ledger_dir.join("general.journal");
-            let _ = append_to_journal(&journal_path, &removed);
+            let _ = crate::gl_layout::append_transaction(ledger_dir, &removed);
         }
         return Err(err.into());
     }
@@ -599,8 +596,7 @@ pub fn unpost_login_account_entry(
 
     if let Err(err) = account_journal::write_journal_at_path(&journal_path, &entries) {
         if let Some(removed) = &removed_gl_txn {
-            let gl_journal_path = ledger_dir.join("general.journal");
-            let _ = append_to_journal(&gl_journal_path, removed);
+            let _ = crate::gl_layout::append_transaction(ledger_dir, removed);
         }
         for side in &other_sides {
             let _ = account_journal::write_journal_at_path(&side.path, &side.original);
@@ -620,8 +616,7 @@ pub fn unpost_login_account_entry(
             let _ = account_journal::write_journal_at_path(&side.path, &side.original);
         }
         if let Some(removed) = removed_gl_txn {
-            let gl_journal_path = ledger_dir.join("general.journal");
-            let _ = append_to_journal(&gl_journal_path, &removed);
+            let _ = crate::gl_layout::append_transaction(ledger_dir, &removed);
         }
         return Err(err.into());
     }
@@ -705,8 +700,7 @@ pub fn post_login_account_transfer(
         return Err(err.into());
     }
 
-    let journal_path = ledger_dir.join("general.journal");
-    if let Err(err) = append_to_journal(&journal_path, &gl_text) {
+    if let Err(err) = crate::gl_layout::append_transaction(ledger_dir, &gl_text) {
         let _ = account_journal::write_journal_at_path(&journal_path1, &original_entries1);
         let _ = account_journal::write_journal_at_path(&journal_path2, &original_entries2);
         return Err(err.into());
@@ -723,6 +717,7 @@ pub fn post_login_account_transfer(
                 entry_id: entry_id2.to_string(),
             },
         ],
+        operator: Some(lock_owner.to_string()),
         timestamp: operations::now_timestamp(),
     };
     if let Err(err) = operations::append_gl_operation(ledger_dir, &op) {
@@ -741,7 +736,7 @@ pub fn post_login_account_transfer(
         label2,
         &commit_msg,
     ) {
-        eprintln!("warning: git commit failed after transfer post: {err}");
+        tracing::warn!("git commit failed after transfer post: {err}");
     }
 
     Ok(gl_txn_id)
@@ -908,9 +903,8 @@ pub fn post_transfer(
         return Err(err.into());
     }
 
-    // Append to general.journal
-    let journal_path = ledger_dir.join("general.journal");
-    if let Err(err) = append_to_journal(&journal_path, &gl_text) {
+    // Append to the GL
+    if let Err(err) = crate::gl_layout::append_transaction(ledger_dir, &gl_text) {
         let _ = account_journal::write_journal(ledger_dir, account1, &original_entries1);
         let _ = account_journal::write_journal(ledger_dir, account2, &original_entries2);
         return Err(err.into());
@@ -928,6 +922,7 @@ pub fn post_transfer(
                 entry_id: entry_id2.to_string(),
             },
         ],
+        operator: None,
         timestamp: operations::now_timestamp(),
     };
     if let Err(err) = operations::append_gl_operation(ledger_dir, &op) {
@@ -960,7 +955,7 @@ pub fn get_unposted_login_account(
     Ok(entries.into_iter().filter(has_unposted_portion).collect())
 }
 
-fn has_unposted_portion(entry: &AccountEntry) -> bool {
+pub(crate) fn has_unposted_portion(entry: &AccountEntry) -> bool {
     if entry.posted.is_some() {
         return false;
     }
@@ -1003,7 +998,7 @@ fn format_gl_transaction(
         let amount = posting
             .amount
             .as_ref()
-            .map(|a| format!("{} {}", a.quantity, a.commodity))
+            .map(|a| format_gl_amount(entry, a))
             .unwrap_or_default();
         (posting.account.clone(), amount)
     } else {
@@ -1011,7 +1006,7 @@ fn format_gl_transaction(
         let amount = first_posting
             .amount
             .as_ref()
-            .map(|a| format!("{} {}", a.quantity, a.commodity))
+            .map(|a| format_gl_amount(entry, a))
             .unwrap_or_default();
         (first_posting.account.clone(), amount)
     };
@@ -1024,6 +1019,10 @@ fn format_gl_transaction(
     for evidence_ref in collect_unique_evidence_refs([entry]) {
         comment_lines.push(format!("    ; evidence: {evidence_ref}"));
     }
+    if let Some(mcc) = entry.tag_value(crate::mcc::MCC_TAG) {
+        comment_lines.push(format!("    ; mcc: {mcc}"));
+    }
+    comment_lines.extend(crate::locations::gl_comment_lines(&entry.tags));
     let comment_block = comment_lines.join("\n");
 
     format!(
@@ -1032,6 +1031,50 @@ fn format_gl_transaction(
     )
 }
 
+/// Format a source posting's amount for the GL.
+///
+/// An entry tagged `cost:` (total, e.g. `20010.00 USD`) or `price:` (per
+/// unit) in a different commodity, such as an exchange buy or sell, is
+/// posted at that cost so hledger balances the counterpart in the quote
+/// commodity and the cost-basis report can track the lot.
+fn format_gl_amount(entry: &AccountEntry, amount: &account_journal::SimpleAmount) -> String {
+    let mut text = format!(
+        "{} {}",
+        amount.quantity,
+        commodity_symbol(&amount.commodity)
+    );
+    for (key, marker) in [("cost", "@@"), ("price", "@")] {
+        let Some((_, value)) = entry.tags.iter().find(|(k, _)| k == key) else {
+            continue;
+        };
+        let Some((quantity, commodity)) = value.trim().split_once(' ') else {
+            continue;
+        };
+        let commodity = commodity.trim();
+        if commodity != amount.commodity {
+            text.push_str(&format!(
+                " {marker} {} {}",
+                quantity.trim().trim_start_matches('-'),
+                commodity_symbol(commodity)
+            ));
+        }
+        break;
+    }
+    text
+}
+
+/// Quote commodity symbols hledger would misread bare, such as `1INCH`.
+pub(crate) fn commodity_symbol(commodity: &str) -> String {
+    if commodity
+        .chars()
+        .any(|c| c.is_ascii_digit() || c.is_whitespace() || "-+.,;@*\"".contains(c))
+    {
+        format!("\"{}\"", commodity.replace('"', ""))
+    } else {
+        commodity.to_string()
+    }
+}
+
 /// Format a GL transaction that splits one bank entry across multiple counterpart accounts.
 fn format_gl_split_transaction(
     entry: &AccountEntry,
@@ -1046,7 +1089,7 @@ fn format_gl_split_transaction(
     let amount_str = first_posting
         .amount
         .as_ref()
-        .map(|a| format!("{} {}", a.quantity, a.commodity))
+        .map(|a| format_gl_amount(entry, a))
         .unwrap_or_default();
 
     let status_marker = entry.status.hledger_marker();
@@ -1057,6 +1100,10 @@ fn format_gl_split_transaction(
     for evidence_ref in collect_unique_evidence_refs([entry]) {
         comment_lines.push(format!("    ; evidence: {evidence_ref}"));
     }
+    if let Some(mcc) = entry.tag_value(crate::mcc::MCC_TAG) {
+        comment_lines.push(format!("    ; mcc: {mcc}"));
+    }
+    comment_lines.extend(crate::locations::gl_comment_lines(&entry.tags));
     let comment_block = comment_lines.join("\n");
 
     let mut counterpart_lines = String::new();
@@ -1146,18 +1193,6 @@ fn collect_unique_evidence_refs<'a>(
     refs.into_iter().collect()
 }
 
-fn append_to_journal(journal_path: &Path, text: &str) -> io::Result<()> {
-    let mut file = OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open(journal_path)?;
-    if file.metadata()?.len() > 0 {
-        file.write_all(b"\n")?;
-    }
-    file.write_all(text.as_bytes())?;
-    Ok(())
-}
-
 /// Parse a `logins/{login}/accounts/{label}` locator into `(login, label)`.
 fn locator_to_login_label(locator: &str) -> Option<(&str, &str)> {
     let rest = locator.strip_prefix("logins/")?;
@@ -1209,19 +1244,19 @@ fn acquire_login_locks_for_names(
     Ok(locks)
 }
 
-/// Remove a GL transaction from general.journal by its ID.
+/// Remove a GL transaction by its ID.
 ///
-/// Finds the transaction with `; id: <gl_txn_id>` and removes it.
+/// Finds the transaction with `; id: <gl_txn_id>` in whichever GL file holds
+/// it and removes it.
 fn remove_gl_transaction(
     ledger_dir: &Path,
     gl_txn_id: &str,
 ) -> Result<Option<String>, Box<dyn std::error::Error + Send + Sync>> {
-    let journal_path = ledger_dir.join("general.journal");
-    if !journal_path.exists() {
+    let Some((journal_path, content)) =
+        crate::gl_layout::find_transaction_file(ledger_dir, gl_txn_id)?
+    else {
         return Ok(None);
-    }
-
-    let content = fs::read_to_string(&journal_path)?;
+    };
     let marker = format!("id: {gl_txn_id}");
     let mut kept_blocks = Vec::new();
     let mut removed_block = None;
@@ -1234,20 +1269,17 @@ fn remove_gl_transaction(
         }
     }
 
-    let mut final_content = kept_blocks.join("\n\n");
-    if !final_content.is_empty() {
-        final_content.push('\n');
-    }
-    fs::write(&journal_path, final_content)?;
+    crate::gl_layout::write_blocks(&journal_path, &kept_blocks)?;
     Ok(removed_block)
 }
 
-/// Replace a GL block in general.journal in-place.
+/// Replace a GL block in-place.
 ///
 /// Finds the block with `id: <gl_txn_id>` and replaces it with `new_block`.
 fn replace_gl_block(ledger_dir: &Path, gl_txn_id: &str, new_block: &str) -> io::Result<()> {
-    let journal_path = ledger_dir.join("general.journal");
-    let content = fs::read_to_string(&journal_path)?;
+    let (journal_path, content) =
+        crate::gl_layout::find_transaction_file(ledger_dir, gl_txn_id)?
+            .ok_or_else(|| io::Error::other(format!("GL transaction not found: {gl_txn_id}")))?;
     let marker = format!("id: {gl_txn_id}");
     let mut replaced = false;
     let blocks: Vec<String> = crate::gl_journal::split_journal_blocks(&content)
@@ -1263,14 +1295,10 @@ fn replace_gl_block(ledger_dir: &Path, gl_txn_id: &str, new_block: &str) -> io::
         .collect();
     if !replaced {
         return Err(io::Error::other(format!(
-            "GL transaction not found in general.journal: {gl_txn_id}"
+            "GL transaction not found: {gl_txn_id}"
         )));
     }
-    let mut final_content = blocks.join("\n\n");
-    if !final_content.is_empty() {
-        final_content.push('\n');
-    }
-    fs::write(&journal_path, final_content)
+    crate::gl_layout::write_blocks(&journal_path, &blocks)
 }
 
 /// Extract the counterpart account (last indented non-comment posting line) from a GL block.
@@ -1459,8 +1487,8 @@ pub fn recategorize_gl_transaction(
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
     let _gl_lock =
         login_config::acquire_gl_lock_with_metadata(ledger_dir, lock_owner, "recategorize-gl")?;
-    let journal_path = ledger_dir.join("general.journal");
-    let content = fs::read_to_string(&journal_path)?;
+    let (journal_path, content) = crate::gl_layout::find_transaction_file(ledger_dir, txn_id)?
+        .ok_or_else(|| format!("GL transaction not found: {txn_id}"))?;
     let marker = format!("id: {txn_id}");
     let mut found = false;
     let mut replaced_any = false;
@@ -1513,15 +1541,11 @@ pub fn recategorize_gl_transaction(
         return Err(format!("GL posting index out of bounds: {posting_index}").into());
     }
 
-    let mut final_content = blocks.join("\n\n");
-    if !final_content.is_empty() {
-        final_content.push('\n');
-    }
-    fs::write(&journal_path, final_content)?;
+    crate::gl_layout::write_blocks(&journal_path, &blocks)?;
 
     let commit_msg = format!("recategorize: {txn_id} → {new_account}");
     if let Err(err) = crate::ledger::commit_general_journal(ledger_dir, &commit_msg) {
-        eprintln!("warning: git commit failed after recategorize: {err}");
+        tracing::warn!("git commit failed after recategorize: {err}");
     }
 
     Ok(())
@@ -1626,20 +1650,30 @@ pub fn merge_gl_transfer(
         &new_uuid,
     );
 
-    // 6. Compute new GL content: remove both old blocks, append merged.
-    let gl_journal_path = ledger_dir.join("general.journal");
-    let original_gl_content = fs::read_to_string(&gl_journal_path)?;
+    // 6. Compute new GL content: remove both old blocks from the files that
+    // hold them; the merged block is appended afterwards.
     let marker1 = format!("id: {txn_id_1}");
     let marker2 = format!("id: {txn_id_2}");
-    let kept_blocks: Vec<String> = crate::gl_journal::split_journal_blocks(&original_gl_content)
-        .into_iter()
-        .filter(|block| !block.contains(&marker1) && !block.contains(&marker2))
-        .collect();
-    let mut new_gl_content = kept_blocks.join("\n\n");
-    if !new_gl_content.is_empty() {
-        new_gl_content.push_str("\n\n");
+    let mut original_gl_files = Vec::new();
+    let mut new_gl_files = Vec::new();
+    for path in crate::gl_layout::gl_journal_files(ledger_dir)? {
+        let content = fs::read_to_string(&path)?;
+        let blocks = crate::gl_journal::split_journal_blocks(&content);
+        let kept_blocks: Vec<String> = blocks
+            .iter()
+            .filter(|block| !block.contains(&marker1) && !block.contains(&marker2))
+            .cloned()
+            .collect();
+        if kept_blocks.len() != blocks.len() {
+            new_gl_files.push((path.clone(), kept_blocks));
+            original_gl_files.push((path, content));
+        }
     }
-    new_gl_content.push_str(&gl_text);
+    let restore_gl = || {
+        for (path, content) in &original_gl_files {
+            let _ = crate::atomic_file::write_atomic(path, content.as_bytes());
+        }
+    };
 
     // 7. Update posted refs in account entries.
     let new_gl_ref = format!("general.journal:{new_uuid}");
@@ -1650,7 +1684,7 @@ pub fn merge_gl_transfer(
         entries2[idx2].posted = Some(new_gl_ref);
     }
 
-    // 8. Write account journals first, then general.journal.
+    // 8. Write account journals first, then the GL.
     account_journal::write_journal_at_path(&path1, &entries1)?;
     if !same_file {
         if let Err(err) = account_journal::write_journal_at_path(&path2, &entries2) {
@@ -1658,7 +1692,13 @@ pub fn merge_gl_transfer(
             return Err(err.into());
         }
     }
-    if let Err(err) = fs::write(&gl_journal_path, &new_gl_content) {
+    let gl_result = new_gl_files
+        .iter()
+        .try_for_each(|(path, blocks)| crate::gl_layout::write_blocks(path, blocks))
+        .and_then(|()| crate::gl_layout::append_transaction(ledger_dir, &gl_text));
+    if let Err(err) = gl_result {
+        let _ = remove_gl_transaction(ledger_dir, &new_uuid);
+        restore_gl();
         let _ = account_journal::write_journal_at_path(&path1, &original_entries1);
         if !same_file {
             let _ = account_journal::write_journal_at_path(&path2, &original_entries2);
@@ -1670,7 +1710,8 @@ pub fn merge_gl_transfer(
         &[txn_id_1, txn_id_2],
         &new_uuid,
     ) {
-        let _ = fs::write(&gl_journal_path, &original_gl_content);
+        let _ = remove_gl_transaction(ledger_dir, &new_uuid);
+        restore_gl();
         let _ = account_journal::write_journal_at_path(&path1, &original_entries1);
         if !same_file {
             let _ = account_journal::write_journal_at_path(&path2, &original_entries2);
@@ -1693,7 +1734,7 @@ pub fn merge_gl_transfer(
         _ => crate::ledger::commit_general_journal(ledger_dir, &commit_msg),
     };
     if let Err(err) = commit_result {
-        eprintln!("warning: git commit failed after merge_gl_transfer: {err}");
+        tracing::warn!("git commit failed after merge_gl_transfer: {err}");
     }
 
     Ok(new_uuid)
@@ -1716,7 +1757,7 @@ mod tests {
             "refreshmint-rec-{prefix}-{}-{now}.refreshmint",
             std::process::id()
         ));
-        crate::ledger::new_ledger_at_dir(&dir).unwrap();
+        crate::ledger::new_ledger_at_dir(&dir, None).unwrap();
         dir
     }
 
@@ -1745,6 +1786,8 @@ mod tests {
             extracted_by: None,
             posted: None,
             posted_postings: Vec::new(),
+            note: None,
+            review: None,
         }
     }
 
@@ -1927,6 +1970,8 @@ mod tests {
             extracted_by: None,
             posted: None,
             posted_postings: Vec::new(),
+            note: None,
+            review: None,
         };
         account_journal::write_journal(&root, "chase", &[entry]).unwrap();
 
@@ -1963,6 +2008,53 @@ mod tests {
         assert!(!text.contains("! Shell Oil"));
     }
 
+    #[test]
+    fn format_gl_transaction_posts_crypto_trades_at_cost() {
+        let mut entry = make_entry("txn-1", "2024-01-15", "Buy BTC", "0.50000000");
+        entry.postings[0].amount = Some(SimpleAmount {
+            commodity: "BTC".to_string(),
+            quantity: "0.50000000".to_string(),
+        });
+        entry.tags = vec![("cost".to_string(), "20010.00 USD".to_string())];
+        let text =
+            format_gl_transaction(&entry, "accounts/ex", "Assets:Exchange:USD", "gl-id", None);
+        assert!(text.contains("    Assets:Checking  0.50000000 BTC @@ 20010.00 USD\n"));
+
+        entry.postings[0].amount = Some(SimpleAmount {
+            commodity: "1INCH".to_string(),
+            quantity: "-12".to_string(),
+        });
+        entry.tags = vec![("price".to_string(), "0.41 USD".to_string())];
+        let text =
+            format_gl_transaction(&entry, "accounts/ex", "Assets:Exchange:USD", "gl-id", None);
+        assert!(text.contains("    Assets:Checking  -12 \"1INCH\" @ 0.41 USD\n"));
+    }
+
+    #[test]
+    fn format_gl_transaction_carries_merchant_category_code() {
+        let mut entry = make_entry("txn-1", "2024-01-15", "Shell Oil", "-21.32");
+        entry.tags = vec![("mcc".to_string(), "5541".to_string())];
+        let text = format_gl_transaction(&entry, "accounts/chase", "Expenses:Gas", "gl-id", None);
+        assert!(text.contains("\n    ; mcc: 5541\n"), "{text}");
+    }
+
+    #[test]
+    fn format_gl_transaction_carries_location_tags() {
+        let mut entry = make_entry("txn-1", "2024-01-15", "Shell Oil", "-21.32");
+        entry.tags = vec![
+            ("location".to_string(), "1 Main St, Seattle, WA".to_string()),
+            ("latitude".to_string(), "47.6062".to_string()),
+            ("longitude".to_string(), "-122.3321".to_string()),
+        ];
+        let text = format_gl_transaction(&entry, "accounts/chase", "Expenses:Gas", "gl-id", None);
+        assert!(
+            text.contains(
+                "\n    ; location: 1 Main St; Seattle; WA\n    ; latitude: 47.6062\n    ; longitude: -122.3321\n"
+            ),
+            "{text}"
+        );
+    }
+
     #[test]
     fn format_transfer_gl_transaction_both_cleared_gets_star() {
         let e1 = make_entry("txn-1", "2024-01-15", "Transfer", "-100.00");
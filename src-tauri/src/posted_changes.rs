@@ -0,0 +1,506 @@
+//! Write protection for account journal entries that are posted to the GL.
+//!
+//! A posted entry is the source of a GL transaction, so rewriting it during
+//! extraction would leave the GL out of date without anyone noticing. Dedup
+//! therefore keeps a posted entry's description, status, comment, and
+//! postings as they are (it still adds evidence refs, which the GL does not
+//! carry) and records what it would have changed as a
+//! `posted-entry-conflict` operation. [`list_posted_entry_conflicts`]
+//! reports the changes still outstanding, and [`accept_posted_entry_changes`]
+//! applies them and syncs the GL transaction. Other writers go through
+//! [`write_journal_keeping_posted`], which refuses such edits outright.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry, EntryStatus};
+use crate::login_config;
+use crate::operations::{self, AccountOperation, PostedEntryChange, PostedEntryField};
+
+/// Outstanding changes for one posted entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PostedEntryConflict {
+    pub entry_id: String,
+    pub date: String,
+    pub description: String,
+    pub posted: String,
+    pub changes: Vec<PostedEntryChange>,
+    /// When extraction last found the changes.
+    pub timestamp: String,
+}
+
+/// The GL ref of a posted entry; the first posting's for a per-posting post.
+pub fn gl_ref(entry: &AccountEntry) -> String {
+    entry
+        .posted
+        .clone()
+        .or_else(|| entry.posted_postings.first().map(|(_, r)| r.clone()))
+        .unwrap_or_default()
+}
+
+fn status_str(status: &EntryStatus) -> &'static str {
+    match status {
+        EntryStatus::Cleared => "cleared",
+        EntryStatus::Pending => "pending",
+        EntryStatus::Unmarked => "unmarked",
+    }
+}
+
+fn parse_status(value: &str) -> Option<EntryStatus> {
+    match value {
+        "cleared" => Some(EntryStatus::Cleared),
+        "pending" => Some(EntryStatus::Pending),
+        "unmarked" => Some(EntryStatus::Unmarked),
+        _ => None,
+    }
+}
+
+fn primary_quantity(entry: &AccountEntry) -> &str {
+    entry
+        .postings
+        .first()
+        .and_then(|p| p.amount.as_ref())
+        .map_or("", |a| a.quantity.as_str())
+}
+
+fn field_value(entry: &AccountEntry, field: PostedEntryField) -> &str {
+    match field {
+        PostedEntryField::Description => &entry.description,
+        PostedEntryField::Status => status_str(&entry.status),
+        PostedEntryField::Comment => &entry.comment,
+        PostedEntryField::Amount => primary_quantity(entry),
+    }
+}
+
+/// Whether `proposed` differs from the entry's value of `field`; amounts
+/// compare as numbers so `5.0` and `5.00` are the same.
+fn differs(entry: &AccountEntry, field: PostedEntryField, proposed: &str) -> bool {
+    let current = field_value(entry, field);
+    if field == PostedEntryField::Amount {
+        if let (Ok(current), Ok(proposed)) = (current.parse::<f64>(), proposed.parse::<f64>()) {
+            return (current - proposed).abs() >= 0.005;
+        }
+    }
+    current != proposed
+}
+
+/// Put back the content dedup changed on a posted entry, keeping any
+/// evidence it added, and return the changes that were undone.
+pub fn keep_posted_content(
+    entry: &mut AccountEntry,
+    before: AccountEntry,
+) -> Vec<PostedEntryChange> {
+    let changes = [
+        PostedEntryField::Description,
+        PostedEntryField::Status,
+        PostedEntryField::Comment,
+        PostedEntryField::Amount,
+    ]
+    .into_iter()
+    .filter(|&field| differs(&before, field, field_value(entry, field)))
+    .map(|field| PostedEntryChange {
+        field,
+        current: field_value(&before, field).to_string(),
+        proposed: field_value(entry, field).to_string(),
+    })
+    .collect();
+    entry.description = before.description;
+    entry.status = before.status;
+    entry.comment = before.comment;
+    entry.postings = before.postings;
+    entry.tags = before.tags;
+    changes
+}
+
+/// Whether the last conflict recorded for `entry_id` already holds
+/// `changes` against the same GL ref.
+pub fn is_recorded_conflict(
+    ops: &[AccountOperation],
+    entry_id: &str,
+    posted: &str,
+    changes: &[PostedEntryChange],
+) -> bool {
+    ops.iter()
+        .rev()
+        .find_map(|op| match op {
+            AccountOperation::PostedEntryConflict {
+                entry_id: id,
+                posted: recorded_posted,
+                changes: recorded_changes,
+                ..
+            } if id == entry_id => Some(recorded_posted == posted && recorded_changes == changes),
+            _ => None,
+        })
+        .unwrap_or(false)
+}
+
+/// The parts of a posted entry that [`keep_posted_content`] protects.
+type PostedContent<'a> = (
+    &'a str,
+    &'static str,
+    &'a str,
+    Vec<(&'a str, Option<(&'a str, &'a str)>)>,
+    &'a [(String, String)],
+);
+
+fn posted_content(entry: &AccountEntry) -> PostedContent<'_> {
+    (
+        entry.description.as_str(),
+        status_str(&entry.status),
+        entry.comment.as_str(),
+        entry
+            .postings
+            .iter()
+            .map(|p| {
+                (
+                    p.account.as_str(),
+                    p.amount
+                        .as_ref()
+                        .map(|a| (a.commodity.as_str(), a.quantity.as_str())),
+                )
+            })
+            .collect(),
+        &entry.tags,
+    )
+}
+
+/// Write an account journal, refusing to drop or change the content of an
+/// entry that is still posted to the same GL transaction. Every writer except
+/// posting and [`accept_posted_entry_changes`], which rebuild the GL
+/// transaction alongside the entry, goes through here.
+pub fn write_journal_keeping_posted(path: &Path, entries: &[AccountEntry]) -> std::io::Result<()> {
+    let on_disk = account_journal::read_journal_at_path(path)?;
+    let after: HashMap<&str, &AccountEntry> = entries
+        .iter()
+        .map(|entry| (entry.id.as_str(), entry))
+        .collect();
+    for old in on_disk.iter().filter(|entry| entry.is_posted()) {
+        let allowed = after.get(old.id.as_str()).is_some_and(|entry| {
+            old.posted != entry.posted
+                || old.posted_postings != entry.posted_postings
+                || posted_content(old) == posted_content(entry)
+        });
+        if !allowed {
+            return Err(std::io::Error::other(format!(
+                "entry {} is posted to {}; unpost it before changing it",
+                old.id,
+                gl_ref(old)
+            )));
+        }
+    }
+    account_journal::write_journal_at_path(path, entries)
+}
+
+/// The latest conflict recorded for each posted entry in the login
+/// account, narrowed to the changes the entry does not have yet. Conflicts
+/// for entries since unposted or posted again elsewhere are dropped.
+pub fn list_posted_entry_conflicts(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+) -> Result<Vec<PostedEntryConflict>, Box<dyn std::error::Error + Send + Sync>> {
+    let journal_path = account_journal::login_account_journal_path(ledger_dir, login_name, label);
+    let entries = account_journal::read_journal_at_path(&journal_path)?;
+    let ops = operations::read_login_account_operations(ledger_dir, login_name, label)?;
+    Ok(outstanding_conflicts(&entries, &ops))
+}
+
+fn outstanding_conflicts(
+    entries: &[AccountEntry],
+    ops: &[AccountOperation],
+) -> Vec<PostedEntryConflict> {
+    let mut latest: HashMap<&str, (&str, &[PostedEntryChange], &str)> = HashMap::new();
+    for op in ops {
+        if let AccountOperation::PostedEntryConflict {
+            entry_id,
+            posted,
+            changes,
+            timestamp,
+            ..
+        } = op
+        {
+            latest.insert(
+                entry_id.as_str(),
+                (posted.as_str(), changes.as_slice(), timestamp.as_str()),
+            );
+        }
+    }
+    entries
+        .iter()
+        .filter(|entry| entry.is_posted())
+        .filter_map(|entry| {
+            let (posted, changes, timestamp) = latest.get(entry.id.as_str())?;
+            let posted_now = gl_ref(entry);
+            if *posted != posted_now {
+                return None;
+            }
+            let changes: Vec<PostedEntryChange> = changes
+                .iter()
+                .filter(|change| differs(entry, change.field, &change.proposed))
+                .map(|change| PostedEntryChange {
+                    field: change.field,
+                    current: field_value(entry, change.field).to_string(),
+                    proposed: change.proposed.clone(),
+                })
+                .collect();
+            if changes.is_empty() {
+                return None;
+            }
+            Some(PostedEntryConflict {
+                entry_id: entry.id.clone(),
+                date: entry.date.clone(),
+                description: entry.description.clone(),
+                posted: posted_now,
+                changes,
+                timestamp: timestamp.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn apply_changes(entry: &mut AccountEntry, changes: &[PostedEntryChange], counterpart: &str) {
+    for change in changes {
+        match change.field {
+            PostedEntryField::Description => entry.description = change.proposed.clone(),
+            PostedEntryField::Status => {
+                if let Some(status) = parse_status(&change.proposed) {
+                    entry.status = status;
+                }
+            }
+            PostedEntryField::Comment => entry.comment = change.proposed.clone(),
+            PostedEntryField::Amount => {
+                crate::entry_split::set_primary_amount(entry, &change.proposed, counterpart);
+            }
+        }
+    }
+}
+
+/// Apply the outstanding changes extraction found for a posted entry, then
+/// rebuild its GL transaction from the updated entry.
+///
+/// Returns the GL transaction UUID.
+pub fn accept_posted_entry_changes(
+    ledger_dir: &Path,
+    login_name: &str,
+    label: &str,
+    entry_id: &str,
+    lock_owner: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    {
+        let _login_lock = login_config::acquire_login_lock_with_metadata(
+            ledger_dir,
+            login_name,
+            lock_owner,
+            "accept-posted-entry-changes",
+        )?;
+        let conflict = list_posted_entry_conflicts(ledger_dir, login_name, label)?
+            .into_iter()
+            .find(|conflict| conflict.entry_id == entry_id)
+            .ok_or_else(|| format!("no outstanding changes for entry {entry_id}"))?;
+        let journal_path =
+            account_journal::login_account_journal_path(ledger_dir, login_name, label);
+        let mut entries = account_journal::read_journal_at_path(&journal_path)?;
+        let entry = entries
+            .iter_mut()
+            .find(|e| e.id == entry_id)
+            .ok_or_else(|| format!("entry not found: {entry_id}"))?;
+        let counterpart = crate::staging::login_account_counterpart(ledger_dir, login_name, label);
+        apply_changes(entry, &conflict.changes, &counterpart);
+        account_journal::write_journal_at_path(&journal_path, &entries)?;
+        let message = format!("accept-posted-entry-changes: {entry_id}");
+        crate::ledger::commit_login_account_journal_or_warn(
+            ledger_dir, login_name, label, &message,
+        );
+    }
+    // The GL sync takes the login lock itself.
+    crate::post::sync_gl_transaction(ledger_dir, login_name, label, entry_id, lock_owner)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, SimpleAmount};
+
+    fn posted_entry() -> AccountEntry {
+        let mut entry = AccountEntry::new(
+            "2025-03-01".to_string(),
+            EntryStatus::Pending,
+            "COFFEE SHOP".to_string(),
+            vec!["a.csv:1:1".to_string()],
+            vec![
+                EntryPosting {
+                    account: "Liabilities:Card".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: "-4.50".to_string(),
+                    }),
+                },
+                EntryPosting {
+                    account: "Equity:Staging:Card".to_string(),
+                    amount: Some(SimpleAmount {
+                        commodity: "USD".to_string(),
+                        quantity: "4.50".to_string(),
+                    }),
+                },
+            ],
+        );
+        entry.posted = Some("general.journal:txn-1".to_string());
+        entry
+    }
+
+    #[test]
+    fn keeps_posted_content_and_reports_changes() {
+        let before = posted_entry();
+        let mut entry = before.clone();
+        entry.description = "Coffee Shop".to_string();
+        entry.status = EntryStatus::Cleared;
+        entry.postings[0].amount.as_mut().unwrap().quantity = "-5.50".to_string();
+        entry.postings[1].amount.as_mut().unwrap().quantity = "5.50".to_string();
+        entry.add_evidence("b.csv:1:1".to_string());
+
+        let changes = keep_posted_content(&mut entry, before);
+
+        assert_eq!(
+            changes,
+            vec![
+                PostedEntryChange {
+                    field: PostedEntryField::Description,
+                    current: "COFFEE SHOP".to_string(),
+                    proposed: "Coffee Shop".to_string(),
+                },
+                PostedEntryChange {
+                    field: PostedEntryField::Status,
+                    current: "pending".to_string(),
+                    proposed: "cleared".to_string(),
+                },
+                PostedEntryChange {
+                    field: PostedEntryField::Amount,
+                    current: "-4.50".to_string(),
+                    proposed: "-5.50".to_string(),
+                },
+            ]
+        );
+        assert_eq!(entry.description, "COFFEE SHOP");
+        assert_eq!(entry.status, EntryStatus::Pending);
+        assert_eq!(primary_quantity(&entry), "-4.50");
+        assert_eq!(entry.evidence, ["a.csv:1:1", "b.csv:1:1"]);
+    }
+
+    #[test]
+    fn outstanding_conflicts_drop_applied_and_unposted() {
+        let entry = posted_entry();
+        let mut unposted = posted_entry();
+        unposted.posted = None;
+        let conflict = |entry_id: &str, changes: Vec<PostedEntryChange>| {
+            AccountOperation::PostedEntryConflict {
+                entry_id: entry_id.to_string(),
+                posted: "general.journal:txn-1".to_string(),
+                proposed_evidence: vec!["b.csv:1:1".to_string()],
+                changes,
+                timestamp: "2025-03-02T00:00:00.000Z".to_string(),
+            }
+        };
+        let status = PostedEntryChange {
+            field: PostedEntryField::Status,
+            current: "pending".to_string(),
+            proposed: "cleared".to_string(),
+        };
+        let same_amount = PostedEntryChange {
+            field: PostedEntryField::Amount,
+            current: "-4.5".to_string(),
+            proposed: "-4.50".to_string(),
+        };
+        let ops = vec![
+            conflict(&entry.id, vec![status.clone(), same_amount]),
+            conflict(&unposted.id, vec![status.clone()]),
+        ];
+
+        let conflicts = outstanding_conflicts(&[entry.clone(), unposted], &ops);
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].entry_id, entry.id);
+        assert_eq!(conflicts[0].changes, vec![status]);
+
+        let mut accepted = entry;
+        apply_changes(&mut accepted, &conflicts[0].changes, "Equity:Staging:Card");
+        assert_eq!(accepted.status, EntryStatus::Cleared);
+        assert!(outstanding_conflicts(&[accepted], &ops).is_empty());
+    }
+
+    #[test]
+    fn journal_writes_keep_posted_entries() {
+        let root = std::env::temp_dir().join(format!(
+            "refreshmint-posted-guard-{}-{}",
+            std::process::id(),
+            uuid::Uuid::new_v4()
+        ));
+        let path = account_journal::login_account_journal_path(&root, "bank", "card");
+        let entry = posted_entry();
+        write_journal_keeping_posted(&path, std::slice::from_ref(&entry)).unwrap();
+
+        let mut commented = entry.clone();
+        commented.comment = "latte".to_string();
+        assert!(write_journal_keeping_posted(&path, &[commented.clone()]).is_err());
+        assert!(write_journal_keeping_posted(&path, &[]).is_err());
+
+        let mut noted = entry.clone();
+        noted.note = Some("work trip".to_string());
+        noted.add_evidence("b.csv:1:1".to_string());
+        write_journal_keeping_posted(&path, &[noted]).unwrap();
+
+        commented.posted = None;
+        write_journal_keeping_posted(&path, &[commented]).unwrap();
+        let entries = account_journal::read_journal_at_path(&path).unwrap();
+        assert_eq!(entries[0].comment, "latte");
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn recorded_conflict_matches_only_the_latest_for_the_entry() {
+        let status = PostedEntryChange {
+            field: PostedEntryField::Status,
+            current: "pending".to_string(),
+            proposed: "cleared".to_string(),
+        };
+        let op = |entry_id: &str, posted: &str| AccountOperation::PostedEntryConflict {
+            entry_id: entry_id.to_string(),
+            posted: posted.to_string(),
+            proposed_evidence: Vec::new(),
+            changes: vec![status.clone()],
+            timestamp: "2025-03-02T00:00:00.000Z".to_string(),
+        };
+        let ops = vec![
+            op("e1", "general.journal:txn-1"),
+            op("e2", "general.journal:txn-2"),
+        ];
+
+        assert!(is_recorded_conflict(
+            &ops,
+            "e1",
+            "general.journal:txn-1",
+            &[status.clone()]
+        ));
+        assert!(!is_recorded_conflict(
+            &ops,
+            "e1",
+            "general.journal:txn-3",
+            &[status.clone()]
+        ));
+        assert!(!is_recorded_conflict(
+            &ops,
+            "e1",
+            "general.journal:txn-1",
+            &[]
+        ));
+        assert!(!is_recorded_conflict(
+            &ops,
+            "e3",
+            "general.journal:txn-1",
+            &[status]
+        ));
+    }
+}
@@ -0,0 +1,493 @@
+//! Where a GL transaction came from.
+//!
+//! A posted GL transaction carries `source:` tags naming the account journal
+//! entries it was posted from. Each entry cites its document rows in
+//! `evidence:` refs and the extension that extracted it in `extracted-by:`.
+//! Each document has an `-info.json` sidecar with the scrape session that
+//! saved it. The operations logs record when each entry was created and who
+//! posted it. `transaction_provenance` follows all of these links and returns
+//! them as one response.
+
+use serde::Serialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::account_journal::{self, AccountEntry};
+use crate::gl_journal;
+use crate::operations::{self, AccountOperation, GlOperation};
+use crate::scrape::DocumentInfo;
+
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TransactionProvenance {
+    pub txn_id: String,
+    pub date: String,
+    pub description: String,
+    /// Lock owner of the operation that posted the transaction (`gui`,
+    /// `cli`, ...). Missing for posts made before operators were recorded.
+    pub posted_by: Option<String>,
+    pub posted_at: Option<String>,
+    pub sources: Vec<SourceProvenance>,
+    /// GL operations that touched the transaction or its source entries,
+    /// oldest first.
+    pub gl_operations: Vec<GlOperation>,
+}
+
+/// One `source:` tag of the transaction.
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SourceProvenance {
+    /// `logins/<login>/accounts/<label>` or `accounts/<name>`.
+    pub locator: String,
+    pub entry_id: String,
+    pub posting_index: Option<usize>,
+    /// Whether the entry is still in its account journal.
+    pub found: bool,
+    /// The entry's `extracted-by:` tag, `<extension>:<version>`.
+    pub extracted_by: Option<String>,
+    pub extension: Option<String>,
+    pub extension_version: Option<String>,
+    pub documents: Vec<DocumentProvenance>,
+    /// Account operations for the entry, such as its creation.
+    pub operations: Vec<AccountOperation>,
+}
+
+/// One `evidence:` ref of a source entry.
+#[derive(Debug, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct DocumentProvenance {
+    pub evidence: String,
+    pub document: String,
+    /// The part of the ref after the document name, such as `3:1` for a
+    /// CSV row and column or `#page=2`.
+    pub location: Option<String>,
+    /// The document's sidecar, with its scrape session and extension.
+    pub info: Option<DocumentInfo>,
+}
+
+/// Collect the provenance of GL transaction `txn_id`.
+pub fn transaction_provenance(
+    ledger_dir: &Path,
+    txn_id: &str,
+) -> Result<TransactionProvenance, Box<dyn std::error::Error + Send + Sync>> {
+    let content = crate::gl_layout::find_transaction_file(ledger_dir, txn_id)?
+        .map(|(_, content)| content)
+        .unwrap_or_default();
+    let block = gl_journal::split_journal_blocks(&content)
+        .into_iter()
+        .find(|block| gl_journal::block_transaction_id(block).as_deref() == Some(txn_id))
+        .ok_or_else(|| format!("GL transaction not found: {txn_id}"))?;
+    let (date, description) = parse_header(&block);
+
+    let gl_ops = operations::read_gl_operations(ledger_dir)?;
+    let sources = parse_source_tags(&block)
+        .into_iter()
+        .map(|(locator, entry_id, posting_index)| {
+            source_provenance(ledger_dir, locator, entry_id, posting_index)
+        })
+        .collect::<io::Result<Vec<_>>>()?;
+
+    let gl_operations: Vec<GlOperation> = gl_ops
+        .into_iter()
+        .filter(|op| gl_operation_concerns(op, txn_id, &sources))
+        .collect();
+    let post = gl_operations.iter().rev().find_map(|op| match op {
+        GlOperation::Post {
+            operator,
+            timestamp,
+            ..
+        }
+        | GlOperation::PostSplit {
+            operator,
+            timestamp,
+            ..
+        }
+        | GlOperation::TransferMatch {
+            operator,
+            timestamp,
+            ..
+        } => Some((operator.clone(), timestamp.clone())),
+        _ => None,
+    });
+    let (posted_by, posted_at) = match post {
+        Some((operator, timestamp)) => (operator, Some(timestamp)),
+        None => (None, None),
+    };
+
+    Ok(TransactionProvenance {
+        txn_id: txn_id.to_string(),
+        date,
+        description,
+        posted_by,
+        posted_at,
+        sources,
+        gl_operations,
+    })
+}
+
+fn source_provenance(
+    ledger_dir: &Path,
+    locator: String,
+    entry_id: String,
+    posting_index: Option<usize>,
+) -> io::Result<SourceProvenance> {
+    let mut source = SourceProvenance {
+        locator,
+        entry_id,
+        posting_index,
+        found: false,
+        extracted_by: None,
+        extension: None,
+        extension_version: None,
+        documents: Vec::new(),
+        operations: Vec::new(),
+    };
+    let Some(paths) = LocatorPaths::resolve(ledger_dir, &source.locator) else {
+        return Ok(source);
+    };
+
+    let entries = if paths.journal.exists() {
+        account_journal::read_journal_at_path(&paths.journal)?
+    } else {
+        Vec::new()
+    };
+    if let Some(entry) = entries.iter().find(|entry| entry.id == source.entry_id) {
+        source.found = true;
+        fill_entry_provenance(&mut source, entry, &paths.documents_dir)?;
+    }
+
+    source.operations = paths
+        .read_operations(ledger_dir)?
+        .into_iter()
+        .filter(|op| account_operation_concerns(op, &source.entry_id))
+        .collect();
+    Ok(source)
+}
+
+fn fill_entry_provenance(
+    source: &mut SourceProvenance,
+    entry: &AccountEntry,
+    documents_dir: &Path,
+) -> io::Result<()> {
+    if let Some(extracted_by) = &entry.extracted_by {
+        let (extension, version) = match extracted_by.rsplit_once(':') {
+            Some((extension, version)) => (extension.to_string(), Some(version.to_string())),
+            None => (extracted_by.clone(), None),
+        };
+        source.extension = Some(extension);
+        source.extension_version = version;
+        source.extracted_by = Some(extracted_by.clone());
+    }
+    for evidence in &entry.evidence {
+        let (document, location) = split_evidence_ref(evidence);
+        let info = read_sidecar(documents_dir, document)?;
+        source.documents.push(DocumentProvenance {
+            evidence: evidence.clone(),
+            document: document.to_string(),
+            location: location.map(str::to_string),
+            info,
+        });
+    }
+    Ok(())
+}
+
+/// Journal, documents, and operations log of a source locator.
+struct LocatorPaths {
+    journal: PathBuf,
+    documents_dir: PathBuf,
+    login: Option<(String, String)>,
+    account: Option<String>,
+}
+
+impl LocatorPaths {
+    fn resolve(ledger_dir: &Path, locator: &str) -> Option<Self> {
+        if let Some(rest) = locator.strip_prefix("logins/") {
+            let (login, label) = rest.split_once("/accounts/")?;
+            return Some(Self {
+                journal: account_journal::login_account_journal_path(ledger_dir, login, label),
+                documents_dir: account_journal::login_account_documents_dir(
+                    ledger_dir, login, label,
+                ),
+                login: Some((login.to_string(), label.to_string())),
+                account: None,
+            });
+        }
+        let account = locator.strip_prefix("accounts/")?;
+        Some(Self {
+            journal: account_journal::account_journal_path(ledger_dir, account),
+            documents_dir: account_journal::account_documents_dir(ledger_dir, account),
+            login: None,
+            account: Some(account.to_string()),
+        })
+    }
+
+    fn read_operations(&self, ledger_dir: &Path) -> io::Result<Vec<AccountOperation>> {
+        match (&self.login, &self.account) {
+            (Some((login, label)), _) => {
+                operations::read_login_account_operations(ledger_dir, login, label)
+            }
+            (None, Some(account)) => operations::read_account_operations(ledger_dir, account),
+            (None, None) => Ok(Vec::new()),
+        }
+    }
+}
+
+/// Date and description from the header line of a GL block.
+fn parse_header(block: &str) -> (String, String) {
+    let header = block.lines().next().unwrap_or_default();
+    let header = header.split_once("  ;").map_or(header, |(text, _)| text);
+    let (date, rest) = header
+        .split_once(char::is_whitespace)
+        .unwrap_or((header, ""));
+    let rest = rest.trim();
+    let description = rest
+        .strip_prefix("* ")
+        .or_else(|| rest.strip_prefix("! "))
+        .unwrap_or(rest);
+    (date.to_string(), description.trim().to_string())
+}
+
+/// `(locator, entry_id, posting_index)` for each `; source:` line.
+fn parse_source_tags(block: &str) -> Vec<(String, String, Option<usize>)> {
+    let mut sources = Vec::new();
+    for line in block.lines() {
+        let Some(rest) = line.trim().strip_prefix("; source: ") else {
+            continue;
+        };
+        let rest = rest.trim();
+        let (rest, posting_index) = match rest.rsplit_once(":posting:") {
+            Some((head, index)) => match index.parse::<usize>() {
+                Ok(index) => (head, Some(index)),
+                Err(_) => (rest, None),
+            },
+            None => (rest, None),
+        };
+        if let Some((locator, entry_id)) = rest.rsplit_once(':') {
+            if !locator.is_empty() && !entry_id.is_empty() {
+                sources.push((locator.to_string(), entry_id.to_string(), posting_index));
+            }
+        }
+    }
+    sources
+}
+
+/// Split an evidence ref into its document name and the location within it.
+pub(crate) fn split_evidence_ref(evidence: &str) -> (&str, Option<&str>) {
+    match evidence.find([':', '#']) {
+        Some(pos) => {
+            let location = evidence[pos..].trim_start_matches(':');
+            (&evidence[..pos], Some(location).filter(|l| !l.is_empty()))
+        }
+        None => (evidence, None),
+    }
+}
+
+fn read_sidecar(documents_dir: &Path, document: &str) -> io::Result<Option<DocumentInfo>> {
+    let path = documents_dir.join(format!("{document}-info.json"));
+    if !path.exists() {
+        return Ok(None);
+    }
+    let text = fs::read_to_string(&path)?;
+    // A damaged sidecar should not hide the rest of the chain.
+    match serde_json::from_str(&text) {
+        Ok(info) => Ok(Some(info)),
+        Err(err) => {
+            tracing::warn!("invalid document sidecar {}: {err}", path.display());
+            Ok(None)
+        }
+    }
+}
+
+/// Whether `op` is about `entry_id`; splits and merges concern both the
+/// original entry and its parts.
+fn account_operation_concerns(op: &AccountOperation, entry_id: &str) -> bool {
+    match op {
+        AccountOperation::EntryCreated { entry_id: id, .. }
+        | AccountOperation::ManualAdd { entry_id: id, .. }
+        | AccountOperation::DedupOverride { entry_id: id, .. }
+        | AccountOperation::PostedEntryConflict { entry_id: id, .. } => id == entry_id,
+        AccountOperation::EntrySplit {
+            entry_id: id,
+            parts,
+            ..
+        } => id == entry_id || parts.iter().any(|part| part.entry_id == entry_id),
+        AccountOperation::EntryMerge {
+            entry_id: id,
+            parts,
+            ..
+        } => id == entry_id || parts.iter().any(|part| part == entry_id),
+        AccountOperation::RemoveScrape { .. } => false,
+    }
+}
+
+fn gl_operation_concerns(op: &GlOperation, txn_id: &str, sources: &[SourceProvenance]) -> bool {
+    let is_source = |account: &str, entry_id: &str| {
+        sources.iter().any(|source| {
+            source.entry_id == entry_id
+                && (source.locator == account
+                    || source.locator.strip_prefix("accounts/") == Some(account))
+        })
+    };
+    match op {
+        GlOperation::Post {
+            account, entry_id, ..
+        }
+        | GlOperation::PostSplit {
+            account, entry_id, ..
+        }
+        | GlOperation::UndoPost {
+            account, entry_id, ..
+        } => is_source(account, entry_id),
+        GlOperation::TransferMatch { entries, .. } => entries
+            .iter()
+            .any(|entry| is_source(&entry.account, &entry.entry_id)),
+        GlOperation::SyncTransaction { gl_txn_id, .. } => gl_txn_id == txn_id,
+        GlOperation::ExportEntries { entries, .. } | GlOperation::ImportEntries { entries, .. } => {
+            entries.iter().any(|entry| {
+                entry.source_txn_id == txn_id
+                    || entry.target_txn_id == txn_id
+                    || entry.reclass_txn_id == txn_id
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-provenance-{prefix}-{}-{now}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn parses_header_and_source_tags() {
+        let block = "2025-01-03 * COFFEE SHOP  ; id: gl-1\n    ; source: logins/chase/accounts/checking:e-1\n    ; source: logins/chase/accounts/checking:e-2:posting:1\n    Assets:Checking  -4.50 USD\n    Expenses:Food";
+        assert_eq!(
+            parse_header(block),
+            ("2025-01-03".to_string(), "COFFEE SHOP".to_string())
+        );
+        assert_eq!(
+            parse_source_tags(block),
+            vec![
+                (
+                    "logins/chase/accounts/checking".to_string(),
+                    "e-1".to_string(),
+                    None
+                ),
+                (
+                    "logins/chase/accounts/checking".to_string(),
+                    "e-2".to_string(),
+                    Some(1)
+                ),
+            ]
+        );
+        assert_eq!(
+            split_evidence_ref("2025-01.csv:3:1"),
+            ("2025-01.csv", Some("3:1"))
+        );
+        assert_eq!(
+            split_evidence_ref("stmt.pdf#page=2"),
+            ("stmt.pdf", Some("#page=2"))
+        );
+        assert_eq!(split_evidence_ref("receipt.png"), ("receipt.png", None));
+    }
+
+    #[test]
+    fn follows_source_evidence_sidecar_and_operations() {
+        let ledger = temp_dir("chain");
+        let mut entry = AccountEntry::new(
+            "2025-01-03".to_string(),
+            EntryStatus::Cleared,
+            "COFFEE SHOP".to_string(),
+            vec!["2025-01.csv:3:1".to_string()],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: "-4.50".to_string(),
+                }),
+            }],
+        );
+        entry.id = "e-1".to_string();
+        entry.extracted_by = Some("chase-driver:1.2".to_string());
+        entry.posted = Some("general.journal:gl-1".to_string());
+        let journal = account_journal::login_account_journal_path(&ledger, "chase", "checking");
+        fs::create_dir_all(journal.parent().unwrap()).unwrap();
+        account_journal::write_journal_at_path(&journal, &[entry]).unwrap();
+
+        let documents = account_journal::login_account_documents_dir(&ledger, "chase", "checking");
+        fs::create_dir_all(&documents).unwrap();
+        fs::write(
+            documents.join("2025-01.csv-info.json"),
+            r#"{"mimeType":"text/csv","scrapedAt":"2025-02-01T00:00:00Z","extensionName":"chase-driver","loginName":"chase","label":"checking","scrapeSessionId":"session-7","coverageEndDate":"2025-01-31"}"#,
+        )
+        .unwrap();
+
+        operations::append_login_account_operation(
+            &ledger,
+            "chase",
+            "checking",
+            &AccountOperation::EntryCreated {
+                entry_id: "e-1".to_string(),
+                evidence: vec!["2025-01.csv:3:1".to_string()],
+                date: "2025-01-03".to_string(),
+                amount: "-4.50 USD".to_string(),
+                tags: Vec::new(),
+                timestamp: "2025-02-01T00:00:01Z".to_string(),
+            },
+        )
+        .unwrap();
+        let post = |entry_id: &str| GlOperation::Post {
+            account: "logins/chase/accounts/checking".to_string(),
+            entry_id: entry_id.to_string(),
+            counterpart_account: "Expenses:Food".to_string(),
+            posting_index: None,
+            operator: Some("gui".to_string()),
+            timestamp: "2025-02-02T00:00:00Z".to_string(),
+        };
+        operations::append_gl_operation(&ledger, &post("e-1")).unwrap();
+        operations::append_gl_operation(&ledger, &post("e-9")).unwrap();
+
+        fs::write(
+            ledger.join("general.journal"),
+            "2025-01-03 * COFFEE SHOP  ; id: gl-1\n    ; generated-by: refreshmint-post\n    ; source: logins/chase/accounts/checking:e-1\n    ; evidence: 2025-01.csv:3:1\n    Assets:Checking  -4.50 USD\n    Expenses:Food\n",
+        )
+        .unwrap();
+
+        let provenance = transaction_provenance(&ledger, "gl-1").unwrap();
+        assert_eq!(provenance.description, "COFFEE SHOP");
+        assert_eq!(provenance.posted_by.as_deref(), Some("gui"));
+        assert_eq!(provenance.gl_operations.len(), 1);
+        let source = &provenance.sources[0];
+        assert!(source.found);
+        assert_eq!(source.extension.as_deref(), Some("chase-driver"));
+        assert_eq!(source.extension_version.as_deref(), Some("1.2"));
+        assert_eq!(source.operations.len(), 1);
+        let document = &source.documents[0];
+        assert_eq!(document.document, "2025-01.csv");
+        assert_eq!(document.location.as_deref(), Some("3:1"));
+        assert_eq!(
+            document.info.as_ref().unwrap().scrape_session_id,
+            "session-7"
+        );
+
+        assert!(transaction_provenance(&ledger, "missing").is_err());
+        let _ = fs::remove_dir_all(&ledger);
+    }
+}
@@ -0,0 +1,475 @@
+//! Quicken Interchange Format (QIF) parsing.
+//!
+//! QIF files are line-oriented: a `!Type:` header starts a block of records,
+//! each field is one line whose first character names it, and `^` ends a
+//! record. Supported:
+//!
+//! - banking blocks (`Bank`, `Cash`, `CCard`, `Oth A`, `Oth L`) with
+//!   categories (`Category/Class`), transfers (`[Account]`), and splits
+//! - investment blocks (`Invst`) with actions, securities, prices, share
+//!   quantities, and commissions
+//! - `!Account` blocks naming the account the following records belong to,
+//!   including multi-account exports wrapped in `!Option:AutoSwitch`
+//!
+//! Lists (`Cat`, `Class`, `Memorized`, `Security`, `Prices`, ...) are skipped.
+
+use std::io;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QifOptions {
+    /// Read dates as day/month/year instead of Quicken's month/day/year.
+    pub day_first: bool,
+    /// Only import records of this `!Account`. Required when the file holds
+    /// several accounts.
+    pub account: Option<String>,
+    /// Commodity for amounts; QIF does not record one.
+    pub commodity: String,
+}
+
+impl Default for QifOptions {
+    fn default() -> Self {
+        Self {
+            day_first: false,
+            account: None,
+            commodity: "USD".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QifTransaction {
+    /// 1-based line of the record's first field.
+    pub line: usize,
+    /// Name from the most recent `!Account` block, if any.
+    pub account: Option<String>,
+    /// The block type, e.g. `Bank` or `Invst`.
+    pub kind: String,
+    /// ISO 8601 date.
+    pub date: String,
+    /// Signed decimal amount as written (`T`, or `U` when `T` is absent).
+    pub amount: Option<String>,
+    /// Cleared flag: `*` or `c` (cleared), `X` or `R` (reconciled).
+    pub cleared: Option<char>,
+    /// Check number (`N` in banking blocks).
+    pub number: Option<String>,
+    pub payee: Option<String>,
+    pub memo: Option<String>,
+    pub category: Option<String>,
+    pub class: Option<String>,
+    /// Account named by a `[Account]` category.
+    pub transfer: Option<String>,
+    pub splits: Vec<QifSplit>,
+    pub investment: Option<QifInvestment>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QifSplit {
+    pub category: Option<String>,
+    pub class: Option<String>,
+    pub transfer: Option<String>,
+    pub memo: Option<String>,
+    pub amount: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QifInvestment {
+    /// Action such as `Buy`, `Sell`, `Div`, or `ReinvDiv`.
+    pub action: String,
+    pub security: Option<String>,
+    pub price: Option<String>,
+    pub quantity: Option<String>,
+    pub commission: Option<String>,
+    /// Amount moved to or from the transfer account (`$`).
+    pub transfer_amount: Option<String>,
+}
+
+impl QifInvestment {
+    /// Direction of the action's effect on the account's cash: `1` for cash
+    /// in, `-1` for cash out, `0` for actions that only move shares. Actions
+    /// ending in `X` take cash from or send it to the `L` account, so they
+    /// leave this account's cash unchanged.
+    pub fn cash_sign(&self) -> i32 {
+        let action = self.action.trim().to_ascii_lowercase();
+        match action.as_str() {
+            "xin" | "contribx" => return 1,
+            "xout" | "withdrwx" => return -1,
+            _ => {}
+        }
+        if action.len() > 1 && action.ends_with('x') {
+            return 0;
+        }
+        match action.as_str() {
+            "buy" | "miscexp" | "margint" | "cvrshrt" => -1,
+            "reinvdiv" | "reinvint" | "reinvlg" | "reinvmd" | "reinvsh" | "shrsin" | "shrsout"
+            | "stksplit" | "grant" | "vest" | "exercise" | "expire" | "reminder" => 0,
+            _ => 1,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    Accounts,
+    Transactions,
+    Skipped,
+}
+
+/// Parse a QIF file into transactions in file order.
+pub fn parse(text: &str, options: &QifOptions) -> io::Result<Vec<QifTransaction>> {
+    let mut section = Section::Skipped;
+    let mut kind = String::new();
+    let mut autoswitch = false;
+    let mut current_account: Option<String> = None;
+    let mut pending_account: Option<String> = None;
+    let mut record: Option<QifTransaction> = None;
+    let mut transactions = Vec::new();
+
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    for (index, raw_line) in text.lines().enumerate() {
+        let line_number = index + 1;
+        let line = raw_line.trim_end_matches('\r');
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        if let Some(header) = line.strip_prefix('!') {
+            if let Some(record) = record.take() {
+                transactions.push(finish_record(record)?);
+            }
+            let header = header.trim();
+            let lower = header.to_ascii_lowercase();
+            if lower == "option:autoswitch" {
+                autoswitch = true;
+            } else if lower == "clear:autoswitch" {
+                autoswitch = false;
+            } else if lower == "account" {
+                section = Section::Accounts;
+            } else if let Some(block) = lower.strip_prefix("type:") {
+                kind = header["type:".len()..].trim().to_string();
+                section = match block.trim() {
+                    "bank" | "cash" | "ccard" | "oth a" | "oth l" | "invst" => {
+                        Section::Transactions
+                    }
+                    _ => Section::Skipped,
+                };
+            } else {
+                section = Section::Skipped;
+            }
+            continue;
+        }
+
+        let (code, value) = split_field(line);
+        match section {
+            Section::Skipped => {}
+            Section::Accounts => match code {
+                'N' => pending_account = Some(value.to_string()),
+                '^' => {
+                    if let Some(name) = pending_account.take() {
+                        if !autoswitch {
+                            current_account = Some(name);
+                        }
+                    }
+                }
+                _ => {}
+            },
+            Section::Transactions => {
+                if code == '^' {
+                    if let Some(record) = record.take() {
+                        transactions.push(finish_record(record)?);
+                    }
+                    continue;
+                }
+                let record = record.get_or_insert_with(|| QifTransaction {
+                    line: line_number,
+                    account: current_account.clone(),
+                    kind: kind.clone(),
+                    ..QifTransaction::default()
+                });
+                apply_field(record, code, value, line_number, options)?;
+            }
+        }
+    }
+    if let Some(record) = record.take() {
+        transactions.push(finish_record(record)?);
+    }
+
+    select_account(transactions, options.account.as_deref())
+}
+
+fn split_field(line: &str) -> (char, &str) {
+    let mut chars = line.chars();
+    let code = chars.next().unwrap_or('^');
+    (code, chars.as_str().trim())
+}
+
+fn apply_field(
+    record: &mut QifTransaction,
+    code: char,
+    value: &str,
+    line_number: usize,
+    options: &QifOptions,
+) -> io::Result<()> {
+    let invalid = |what: &str| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line {line_number}: invalid QIF {what}: {value}"),
+        )
+    };
+    let amount = || normalize_amount(value).ok_or_else(|| invalid("amount"));
+    let text = || Some(value.to_string()).filter(|v| !v.is_empty());
+    let investing = record.kind.eq_ignore_ascii_case("invst");
+
+    match code {
+        'D' => record.date = parse_date(value, options.day_first).ok_or_else(|| invalid("date"))?,
+        'T' => record.amount = Some(amount()?),
+        'U' if record.amount.is_none() => record.amount = Some(amount()?),
+        'C' => record.cleared = value.chars().next(),
+        'P' => record.payee = text(),
+        'M' => record.memo = text(),
+        'L' => {
+            let (category, class, transfer) = parse_category(value);
+            record.category = category;
+            record.class = class;
+            record.transfer = transfer;
+        }
+        'N' if investing => investment(record).action = value.to_string(),
+        'N' => record.number = text(),
+        'Y' => investment(record).security = text(),
+        'I' => investment(record).price = Some(amount()?),
+        'Q' => investment(record).quantity = Some(amount()?),
+        'O' => investment(record).commission = Some(amount()?),
+        '$' if investing => investment(record).transfer_amount = Some(amount()?),
+        'S' => {
+            let (category, class, transfer) = parse_category(value);
+            record.splits.push(QifSplit {
+                category,
+                class,
+                transfer,
+                ..QifSplit::default()
+            });
+        }
+        'E' => last_split(record).memo = text(),
+        '$' => last_split(record).amount = Some(amount()?),
+        // Address lines, split percentages, and fields we don't use.
+        _ => {}
+    }
+    Ok(())
+}
+
+fn investment(record: &mut QifTransaction) -> &mut QifInvestment {
+    record.investment.get_or_insert_with(QifInvestment::default)
+}
+
+/// The split an `E` or `$` field belongs to. Some exporters omit `S` for
+/// uncategorized splits, so a missing split is started here.
+fn last_split(record: &mut QifTransaction) -> &mut QifSplit {
+    if record
+        .splits
+        .last()
+        .map_or(true, |split| split.amount.is_some())
+    {
+        record.splits.push(QifSplit::default());
+    }
+    let index = record.splits.len() - 1;
+    &mut record.splits[index]
+}
+
+fn finish_record(record: QifTransaction) -> io::Result<QifTransaction> {
+    if record.date.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("line {}: QIF record has no date (D) field", record.line),
+        ));
+    }
+    Ok(record)
+}
+
+/// Keep the records of `account`, or fail when the file mixes accounts and
+/// none was chosen.
+fn select_account(
+    transactions: Vec<QifTransaction>,
+    account: Option<&str>,
+) -> io::Result<Vec<QifTransaction>> {
+    let mut names: Vec<&str> = Vec::new();
+    for name in transactions.iter().filter_map(|t| t.account.as_deref()) {
+        if !names.contains(&name) {
+            names.push(name);
+        }
+    }
+    match account {
+        Some(account) => {
+            if !names.contains(&account) {
+                return Err(io::Error::new(
+                    io::ErrorKind::NotFound,
+                    format!(
+                        "QIF file has no account '{account}' (found: {})",
+                        names.join(", ")
+                    ),
+                ));
+            }
+            Ok(transactions
+                .into_iter()
+                .filter(|t| t.account.as_deref() == Some(account))
+                .collect())
+        }
+        None if names.len() > 1 => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "QIF file has several accounts ({}); choose one to import",
+                names.join(", ")
+            ),
+        )),
+        None => Ok(transactions),
+    }
+}
+
+/// Split an `L` or `S` value into category, class, and transfer account:
+/// `Food:Groceries/Household`, `[Savings]`, or `[Savings]/Household`.
+fn parse_category(value: &str) -> (Option<String>, Option<String>, Option<String>) {
+    let value = value.trim();
+    // Account names may contain `/`, so only look for a class after `]`.
+    let (account, rest) = match value
+        .strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+    {
+        Some((account, rest)) => (Some(account.trim().to_string()), rest),
+        None => (None, value),
+    };
+    let (category, class) = match rest.split_once('/') {
+        Some((category, class)) => (category, Some(class)),
+        None => (rest, None),
+    };
+    let non_empty = |s: &str| Some(s.trim().to_string()).filter(|s| !s.is_empty());
+    let class = class.and_then(non_empty);
+    match account {
+        Some(account) => (None, class, Some(account)),
+        None => (non_empty(category), class, None),
+    }
+}
+
+/// Normalize `1,234.56` or `-$4.50` to a plain signed decimal.
+fn normalize_amount(value: &str) -> Option<String> {
+    let cleaned: String = value
+        .chars()
+        .filter(|c| !matches!(c, ',' | '$' | ' '))
+        .collect();
+    let digits = cleaned
+        .strip_prefix('-')
+        .or_else(|| cleaned.strip_prefix('+'))
+        .unwrap_or(&cleaned);
+    let valid = !digits.is_empty()
+        && digits.chars().any(|c| c.is_ascii_digit())
+        && digits.chars().all(|c| c.is_ascii_digit() || c == '.')
+        && digits.matches('.').count() <= 1;
+    if !valid {
+        return None;
+    }
+    Some(cleaned.trim_start_matches('+').to_string())
+}
+
+/// Parse a QIF date. Quicken writes `1/2/98` and `1/2'05`, where `'` marks
+/// a year in the 2000s; other tools write four-digit years, `-` or `.`
+/// separators, or ISO 8601. Two-digit years without `'` below 70 are read as
+/// 20xx.
+fn parse_date(value: &str, day_first: bool) -> Option<String> {
+    let value: String = value.chars().filter(|c| !c.is_whitespace()).collect();
+    if let Ok(date) = chrono::NaiveDate::parse_from_str(&value, "%Y-%m-%d") {
+        return Some(date.format("%Y-%m-%d").to_string());
+    }
+    let apostrophe = value.contains('\'');
+    let parts: Vec<&str> = value.split(['/', '\'', '-', '.']).collect();
+    let [first, second, year] = parts.as_slice() else {
+        return None;
+    };
+    let (month, day) = if day_first {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    let month: u32 = month.parse().ok()?;
+    let day: u32 = day.parse().ok()?;
+    let mut year: i32 = year.parse().ok()?;
+    if year < 100 {
+        year += if apostrophe || year < 70 { 2000 } else { 1900 };
+    }
+    chrono::NaiveDate::from_ymd_opt(year, month, day).map(|d| d.format("%Y-%m-%d").to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    const BANK: &str = "!Account\nNChecking\nTBank\n^\n!Type:Bank\nD1/2'25\nT-1,234.56\nCX\nN1001\nPLANDLORD\nMJanuary rent\nLHousing:Rent/Home\n^\nD01/03/2025\nT-100.00\nPGROCER\nLFood\nSFood:Groceries\nEProduce\n$-60.00\nS[Savings]\n$-40.00\n^\n!Account\nNBrokerage\nTInvst\n^\n!Type:Invst\nD1/4'25\nNBuy\nYAAPL\nI150.00\nQ10\nO5.00\nT1505.00\n^\nD1/5'25\nNDivX\nYAAPL\nT12.34\nL[Checking]\n$12.34\n^\n";
+
+    #[test]
+    fn parses_banking_records_with_categories_and_splits() {
+        let options = QifOptions {
+            account: Some("Checking".to_string()),
+            ..QifOptions::default()
+        };
+        let transactions = parse(BANK, &options).unwrap();
+        assert_eq!(transactions.len(), 2);
+
+        let rent = &transactions[0];
+        assert_eq!(rent.line, 6);
+        assert_eq!(rent.kind, "Bank");
+        assert_eq!(rent.date, "2025-01-02");
+        assert_eq!(rent.amount.as_deref(), Some("-1234.56"));
+        assert_eq!(rent.cleared, Some('X'));
+        assert_eq!(rent.number.as_deref(), Some("1001"));
+        assert_eq!(rent.category.as_deref(), Some("Housing:Rent"));
+        assert_eq!(rent.class.as_deref(), Some("Home"));
+
+        let groceries = &transactions[1];
+        assert_eq!(groceries.date, "2025-01-03");
+        assert_eq!(
+            groceries.splits,
+            vec![
+                QifSplit {
+                    category: Some("Food:Groceries".to_string()),
+                    memo: Some("Produce".to_string()),
+                    amount: Some("-60.00".to_string()),
+                    ..QifSplit::default()
+                },
+                QifSplit {
+                    transfer: Some("Savings".to_string()),
+                    amount: Some("-40.00".to_string()),
+                    ..QifSplit::default()
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_investment_actions_and_requires_an_account_choice() {
+        let err = parse(BANK, &QifOptions::default()).unwrap_err();
+        assert!(err.to_string().contains("Checking, Brokerage"), "{err}");
+
+        let options = QifOptions {
+            account: Some("Brokerage".to_string()),
+            ..QifOptions::default()
+        };
+        let transactions = parse(BANK, &options).unwrap();
+        assert_eq!(transactions.len(), 2);
+        let buy = transactions[0].investment.as_ref().unwrap();
+        assert_eq!(buy.action, "Buy");
+        assert_eq!(buy.security.as_deref(), Some("AAPL"));
+        assert_eq!(buy.quantity.as_deref(), Some("10"));
+        assert_eq!(buy.commission.as_deref(), Some("5.00"));
+        assert_eq!(buy.cash_sign(), -1);
+
+        let dividend = &transactions[1];
+        assert_eq!(dividend.transfer.as_deref(), Some("Checking"));
+        let dividend = dividend.investment.as_ref().unwrap();
+        assert_eq!(dividend.transfer_amount.as_deref(), Some("12.34"));
+        assert_eq!(dividend.cash_sign(), 0);
+
+        assert_eq!(
+            parse_date("31.12.1999", true).as_deref(),
+            Some("1999-12-31")
+        );
+        assert_eq!(parse_date(" 1/ 2/98", false).as_deref(), Some("1998-01-02"));
+        assert!(parse_date("13/13/2025", false).is_none());
+    }
+}
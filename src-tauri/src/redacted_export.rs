@@ -0,0 +1,990 @@
+//! Copy a ledger with its amounts, payees, and documents redacted.
+//!
+//! The copy keeps the shape of the books: every transaction, date, account,
+//! and login mapping is still there, so an advisor can review the category
+//! structure. Only files known to be safe once redacted are exported: journals
+//! (rewritten), login configs (cut down to the extension and each label's GL
+//! account and type), `refreshmint.json`, report groups, and the GL layout.
+//! Bookkeeping state, operation logs, and other JSON files can hold amounts
+//! or notes and are left out, as is git history.
+//!
+//! In journals, amounts are multiplied by a scale factor, with the largest
+//! posting of a balanced transaction absorbing the rounding so hledger still
+//! accepts it. Balance assertions and transaction codes are dropped. Payees
+//! are replaced by a keyed hash that is consistent within one export but
+//! cannot be reversed, and comments are dropped except for the tags that tie
+//! entries together (`id:`, `posted:`, `source:`, ...). Entry ids derive from
+//! amounts and dates, so the ids and document names in those tags go through
+//! the same keyed hash, and copied documents are renamed to match, so links
+//! still resolve within the copy.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Comment tags kept in redacted journals because they link entries, not
+/// because they describe them.
+const STRUCTURAL_TAGS: &[&str] = &[
+    "id",
+    "posted",
+    "source",
+    "generated-by",
+    "extracted-by",
+    "review",
+    "evidence",
+];
+
+/// Ledger-root files copied as they are.
+const COPIED_ROOT_FILES: &[&str] = &["gl-layout.json", "refreshmint.json", "report-groups.json"];
+
+#[derive(Debug, Clone, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct RedactionOptions {
+    /// Directory to create for the copy; it must not exist yet.
+    pub target: String,
+    /// Every amount is multiplied by this. 1 keeps amounts, 0 zeroes them.
+    pub amount_scale: f64,
+    pub hash_payees: bool,
+    pub drop_documents: bool,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct RedactedExport {
+    pub target: String,
+    pub journals: usize,
+    pub transactions: usize,
+    /// Distinct payees replaced by a hash.
+    pub payees: usize,
+    /// Documents copied, or dropped when `dropDocuments` was set.
+    pub documents: usize,
+    /// Other files left out of the copy.
+    pub skipped_files: usize,
+}
+
+/// Write a redacted copy of `ledger_dir` as a new ledger with a fresh git
+/// repository.
+pub fn export_redacted_ledger(
+    ledger_dir: &Path,
+    options: &RedactionOptions,
+) -> io::Result<RedactedExport> {
+    if !options.amount_scale.is_finite() || options.amount_scale < 0.0 {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "amount scale must be a number of at least 0",
+        ));
+    }
+    let target = crate::ledger::ensure_refreshmint_extension(PathBuf::from(options.target.trim()))?;
+    if target.exists() {
+        return Err(io::Error::new(
+            io::ErrorKind::AlreadyExists,
+            format!("{} already exists", target.display()),
+        ));
+    }
+    if target.starts_with(ledger_dir) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "the redacted copy cannot be inside the ledger",
+        ));
+    }
+
+    let mut redactor = Redactor::new(options);
+    let mut report = RedactedExport {
+        target: target.to_string_lossy().into_owned(),
+        journals: 0,
+        transactions: 0,
+        payees: 0,
+        documents: 0,
+        skipped_files: 0,
+    };
+    let mut files = Vec::new();
+    collect_files(ledger_dir, Path::new(""), &mut files)?;
+    fs::create_dir(&target)?;
+    let mut exported = Vec::new();
+    for rel in files {
+        let source = ledger_dir.join(&rel);
+        let destination = target.join(&rel);
+        match classify(&rel) {
+            FileKind::Journal => {
+                let text = fs::read_to_string(&source)?;
+                let (redacted, transactions) = redactor.redact_journal(&text);
+                write_file(&destination, redacted.as_bytes())?;
+                report.journals += 1;
+                report.transactions += transactions;
+            }
+            FileKind::Copied => write_file(&destination, &fs::read(&source)?)?,
+            FileKind::LoginConfig => {
+                let config = redact_login_config(&fs::read_to_string(&source)?)?;
+                write_file(&destination, config.as_bytes())?;
+            }
+            FileKind::Document => {
+                report.documents += 1;
+                if options.drop_documents {
+                    continue;
+                }
+                let rel = redactor.redact_document_path(&rel);
+                write_file(&target.join(&rel), &fs::read(&source)?)?;
+                exported.push(rel);
+                continue;
+            }
+            FileKind::Skipped => {
+                report.skipped_files += 1;
+                continue;
+            }
+        }
+        exported.push(rel);
+    }
+    if !target.join("general.journal").exists() {
+        write_file(&target.join("general.journal"), b"")?;
+    }
+    exported.retain(|rel| rel != Path::new("general.journal"));
+    crate::ledger::init_ledger_repo(&target, &exported)?;
+    report.payees = redactor.payees.len();
+    Ok(report)
+}
+
+enum FileKind {
+    Journal,
+    Copied,
+    LoginConfig,
+    Document,
+    Skipped,
+}
+
+fn classify(rel: &Path) -> FileKind {
+    let parts: Vec<&str> = rel.iter().filter_map(|part| part.to_str()).collect();
+    if parts.contains(&"documents") {
+        return FileKind::Document;
+    }
+    match parts.as_slice() {
+        [name] if COPIED_ROOT_FILES.contains(name) => FileKind::Copied,
+        ["logins", _, "config.json"] => FileKind::LoginConfig,
+        [.., name] if name.ends_with(".journal") => FileKind::Journal,
+        _ => FileKind::Skipped,
+    }
+}
+
+/// A login config with only the extension and each label's GL account and
+/// account type. Institution details, label rules, and account numbers are
+/// dropped.
+fn redact_login_config(text: &str) -> io::Result<String> {
+    let config: crate::login_config::LoginConfig = serde_json::from_str(text)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+    let redacted = crate::login_config::LoginConfig {
+        extension: config.extension,
+        accounts: config
+            .accounts
+            .into_iter()
+            .map(|(label, account)| {
+                let account = crate::login_config::LoginAccountConfig {
+                    gl_account: account.gl_account,
+                    account_type: account.account_type,
+                    ..Default::default()
+                };
+                (label, account)
+            })
+            .collect(),
+        ..Default::default()
+    };
+    serde_json::to_string_pretty(&redacted)
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))
+}
+
+/// Ledger-relative paths of every file outside `.git`, in a stable order.
+fn collect_files(root: &Path, rel: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries: Vec<fs::DirEntry> = fs::read_dir(root.join(rel))?.collect::<Result<_, _>>()?;
+    entries.sort_by_key(fs::DirEntry::file_name);
+    for entry in entries {
+        let child = rel.join(entry.file_name());
+        if child == Path::new(".git") {
+            continue;
+        }
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() {
+            collect_files(root, &child, files)?;
+        } else if file_type.is_file() {
+            files.push(child);
+        }
+    }
+    Ok(())
+}
+
+fn write_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, contents)
+}
+
+struct Redactor {
+    scale: f64,
+    hash_payees: bool,
+    /// Keyed with a random key per export, so hashes cannot be looked up.
+    hasher: std::collections::hash_map::RandomState,
+    payees: HashSet<String>,
+}
+
+/// A posting amount split around its number: `-$12.50` has prefix `-$` and
+/// no suffix, `3 USD` has no prefix and suffix ` USD`.
+struct ParsedAmount {
+    prefix: String,
+    units: i128,
+    decimals: usize,
+    suffix: String,
+    /// Text after the amount that is kept as is, such as a unit price.
+    rest: String,
+}
+
+impl ParsedAmount {
+    fn commodity(&self) -> String {
+        format!(
+            "{}|{}",
+            self.prefix.replace('-', "").trim(),
+            self.suffix.trim()
+        )
+    }
+
+    fn render(&self) -> String {
+        let sign = if self.units < 0 { "-" } else { "" };
+        let digits = self.units.unsigned_abs().to_string();
+        let number = if self.decimals == 0 {
+            digits
+        } else {
+            let padded = format!("{digits:0>width$}", width = self.decimals + 1);
+            let (int_part, frac_part) = padded.split_at(padded.len() - self.decimals);
+            format!("{int_part}.{frac_part}")
+        };
+        format!(
+            "{sign}{}{number}{}{}",
+            self.prefix.replace('-', ""),
+            self.suffix,
+            self.rest
+        )
+    }
+}
+
+/// A posting line of the transaction being redacted.
+struct PendingPosting {
+    /// Index into the transaction's output lines.
+    line: usize,
+    indent: String,
+    account: String,
+    comment: String,
+    amount: Option<ParsedAmount>,
+    /// The original amount in units, before scaling.
+    original: i128,
+}
+
+impl Redactor {
+    fn new(options: &RedactionOptions) -> Self {
+        Self {
+            scale: options.amount_scale,
+            hash_payees: options.hash_payees,
+            hasher: std::collections::hash_map::RandomState::new(),
+            payees: HashSet::new(),
+        }
+    }
+
+    /// Redact a journal and return it with its transaction count.
+    fn redact_journal(&mut self, text: &str) -> (String, usize) {
+        let mut out: Vec<String> = Vec::new();
+        let mut transaction: Option<(Vec<String>, Vec<PendingPosting>, bool)> = None;
+        let mut transactions = 0;
+        let mut in_comment_block = false;
+
+        for line in text.lines() {
+            let trimmed = line.trim();
+            if in_comment_block {
+                in_comment_block = trimmed != "end comment";
+                continue;
+            }
+            let indented = line.starts_with(' ') || line.starts_with('\t');
+            if indented && !trimmed.is_empty() {
+                match &mut transaction {
+                    Some((lines, postings, has_cost)) => {
+                        self.redact_transaction_line(line, lines, postings, has_cost);
+                    }
+                    // Subdirectives of `account` and similar directives.
+                    None => out.push(line.to_string()),
+                }
+                continue;
+            }
+            if let Some((lines, postings, has_cost)) = transaction.take() {
+                out.extend(finish_transaction(lines, postings, has_cost));
+            }
+            if trimmed.is_empty() {
+                out.push(String::new());
+            } else if trimmed == "comment" {
+                in_comment_block = true;
+            } else if trimmed.starts_with([';', '#', '*']) {
+                // Top-level comments are free text.
+            } else if line.starts_with(|c: char| c.is_ascii_digit()) {
+                transactions += 1;
+                transaction = Some((vec![self.redact_header(line)], Vec::new(), false));
+            } else {
+                out.push(line.to_string());
+            }
+        }
+        if let Some((lines, postings, has_cost)) = transaction.take() {
+            out.extend(finish_transaction(lines, postings, has_cost));
+        }
+        let mut redacted = out.join("\n");
+        if text.ends_with('\n') {
+            redacted.push('\n');
+        }
+        (redacted, transactions)
+    }
+
+    /// `date  [status] [(code)] description  ; comment`: the code is dropped,
+    /// the description hashed, and the comment kept only if structural.
+    fn redact_header(&mut self, line: &str) -> String {
+        let (body, comment) = split_comment(line);
+        let comment = comment.and_then(|comment| self.redact_tag(comment));
+        let date_end = body.find(char::is_whitespace).unwrap_or(body.len());
+        let (date, rest) = body.split_at(date_end);
+        let gap_end = rest.len() - rest.trim_start().len();
+        let (gap, mut rest) = rest.split_at(gap_end);
+        let mut header = format!("{date}{gap}");
+        for marker in ["* ", "! "] {
+            if let Some(after) = rest.strip_prefix(marker) {
+                header.push_str(marker);
+                rest = after.trim_start();
+            }
+        }
+        if rest.starts_with('(') {
+            if let Some(close) = rest.find(')') {
+                rest = rest[close + 1..].trim_start();
+            }
+        }
+        header.push_str(&self.redact_payee(rest.trim_end()));
+        let header = header.trim_end().to_string();
+        match comment {
+            Some(comment) => format!("{header}  ; {comment}"),
+            None => header,
+        }
+    }
+
+    /// A structural tag with its ids and document names replaced by
+    /// pseudonyms, or `None` for any other comment.
+    fn redact_tag(&self, comment: &str) -> Option<String> {
+        if !is_structural(comment) {
+            return None;
+        }
+        let (key, value) = comment.split_once(':')?;
+        let (key, value) = (key.trim(), value.trim());
+        let value = match key {
+            "id" => self.pseudonym(value),
+            "posted" | "source" => self.redact_ref(value),
+            "evidence" => self.redact_evidence(value),
+            _ if key.starts_with("posted-posting-") => self.redact_ref(value),
+            _ => value.to_string(),
+        };
+        Some(format!("{key}: {value}"))
+    }
+
+    /// `<locator>:<id>` with an optional `:posting:<n>`: the locator (a
+    /// journal path) is kept and the id replaced.
+    fn redact_ref(&self, value: &str) -> String {
+        let (head, posting) = match value.rsplit_once(":posting:") {
+            Some((head, index)) if index.parse::<usize>().is_ok() => (head, &value[head.len()..]),
+            _ => (value, ""),
+        };
+        match head.rsplit_once(':') {
+            Some((locator, id)) => format!("{locator}:{}{posting}", self.pseudonym(id)),
+            None => format!("{}{posting}", self.pseudonym(head)),
+        }
+    }
+
+    /// `<document>:<row>:<col>` or `<document>#page=<n>`: the document name
+    /// is replaced, the location within it kept.
+    fn redact_evidence(&self, value: &str) -> String {
+        let (document, _) = crate::provenance::split_evidence_ref(value);
+        format!(
+            "{}{}",
+            self.redact_document_name(document),
+            &value[document.len()..]
+        )
+    }
+
+    /// A document name (relative to its `documents/` directory) with the
+    /// file name replaced and its folder and extension kept, e.g.
+    /// `statements/2024-01 Chase.pdf` becomes `statements/doc-<hash>.pdf`.
+    fn redact_document_name(&self, document: &str) -> String {
+        let (folder, name) = match document.rsplit_once('/') {
+            Some((folder, name)) => (format!("{folder}/"), name),
+            None => (String::new(), document),
+        };
+        let extension = match name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => format!(".{extension}"),
+            _ => String::new(),
+        };
+        format!("{folder}doc-{}{extension}", self.pseudonym(document))
+    }
+
+    /// Where a copied document (or its `-info.json` sidecar) goes, named as
+    /// the redacted evidence refs name it.
+    fn redact_document_path(&self, rel: &Path) -> PathBuf {
+        let parts: Vec<String> = rel
+            .iter()
+            .map(|part| part.to_string_lossy().into_owned())
+            .collect();
+        let Some(at) = parts.iter().position(|part| part == "documents") else {
+            return rel.to_path_buf();
+        };
+        let document = parts[at + 1..].join("/");
+        let redacted = match document.strip_suffix("-info.json") {
+            Some(document) => format!("{}-info.json", self.redact_document_name(document)),
+            None => self.redact_document_name(&document),
+        };
+        parts[..=at].iter().collect::<PathBuf>().join(redacted)
+    }
+
+    /// Keyed hash of an id or document name, the same for the same input
+    /// within one export.
+    fn pseudonym(&self, value: &str) -> String {
+        let mut hasher = self.hasher.build_hasher();
+        hasher.write(b"ref\x1f");
+        hasher.write(value.as_bytes());
+        format!("{:016x}", hasher.finish())
+    }
+
+    fn redact_payee(&mut self, description: &str) -> String {
+        if !self.hash_payees || description.is_empty() {
+            return description.to_string();
+        }
+        self.payees.insert(description.to_string());
+        let mut hasher = self.hasher.build_hasher();
+        hasher.write(description.as_bytes());
+        format!("Payee {:08x}", hasher.finish() as u32)
+    }
+
+    fn redact_transaction_line(
+        &self,
+        line: &str,
+        lines: &mut Vec<String>,
+        postings: &mut Vec<PendingPosting>,
+        has_cost: &mut bool,
+    ) {
+        let indent = &line[..line.len() - line.trim_start().len()];
+        let trimmed = line.trim();
+        if let Some(comment) = trimmed.strip_prefix(';') {
+            if let Some(tag) = self.redact_tag(comment.trim()) {
+                lines.push(format!("{indent}; {tag}"));
+            }
+            return;
+        }
+        let (body, comment) = split_comment(trimmed);
+        let comment = comment
+            .and_then(|comment| self.redact_tag(comment))
+            .map(|comment| format!("  ; {comment}"))
+            .unwrap_or_default();
+        let (account, amount) = split_posting(body);
+        let amount = amount.and_then(|text| self.scale_amount(text));
+        *has_cost |= amount
+            .as_ref()
+            .is_some_and(|amount| amount.rest.contains('@'));
+        let original = amount.as_ref().map_or(0, |amount| amount.units);
+        let amount = amount.map(|mut amount| {
+            amount.units = scale_units(amount.units, self.scale);
+            amount
+        });
+        postings.push(PendingPosting {
+            line: lines.len(),
+            indent: indent.to_string(),
+            account: account.to_string(),
+            comment,
+            amount,
+            original,
+        });
+        lines.push(String::new());
+    }
+
+    /// Parse a posting amount and scale a total cost (`@@`). A unit cost
+    /// (`@`) stays, so the scaled quantity scales the cost with it. A balance
+    /// assertion would no longer hold and is dropped.
+    fn scale_amount(&self, text: &str) -> Option<ParsedAmount> {
+        let text = match text.find(" =").or_else(|| text.find("\t=")) {
+            Some(at) => text[..at].trim_end(),
+            None => text,
+        };
+        let (amount, cost) = match text.find(" @") {
+            Some(at) => (&text[..at], &text[at..]),
+            None => (text, ""),
+        };
+        let mut parsed = parse_amount(amount)?;
+        parsed.rest = match cost.trim_start().strip_prefix("@@") {
+            Some(total) => match parse_amount(total.trim()) {
+                Some(mut total) => {
+                    total.units = scale_units(total.units, self.scale);
+                    format!(" @@ {}", total.render())
+                }
+                None => cost.to_string(),
+            },
+            None => cost.to_string(),
+        };
+        Some(parsed)
+    }
+}
+
+/// Render the postings of a finished transaction. If it balanced before
+/// scaling, its largest posting in each commodity takes the rounding
+/// difference so it still balances.
+fn finish_transaction(
+    mut lines: Vec<String>,
+    mut postings: Vec<PendingPosting>,
+    has_cost: bool,
+) -> Vec<String> {
+    let all_explicit = postings.iter().all(|posting| posting.amount.is_some());
+    if all_explicit && !has_cost {
+        let mut by_commodity: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+        for (i, posting) in postings.iter().enumerate() {
+            if let Some(amount) = &posting.amount {
+                by_commodity.entry(amount.commodity()).or_default().push(i);
+            }
+        }
+        for members in by_commodity.values() {
+            balance_rounding(&mut postings, members);
+        }
+    }
+    for posting in postings {
+        let line = match &posting.amount {
+            Some(amount) => format!(
+                "{}{}  {}{}",
+                posting.indent,
+                posting.account,
+                amount.render(),
+                posting.comment
+            ),
+            None => format!("{}{}{}", posting.indent, posting.account, posting.comment),
+        };
+        lines[posting.line] = line;
+    }
+    lines
+}
+
+fn balance_rounding(postings: &mut [PendingPosting], members: &[usize]) {
+    let decimals = members
+        .iter()
+        .filter_map(|&i| postings[i].amount.as_ref())
+        .map(|amount| amount.decimals)
+        .max()
+        .unwrap_or(0);
+    let widen = |units: i128, from: usize| units * 10i128.pow((decimals - from) as u32);
+    let mut original_sum = 0;
+    let mut scaled_sum = 0;
+    for &i in members {
+        if let Some(amount) = &postings[i].amount {
+            original_sum += widen(postings[i].original, amount.decimals);
+            scaled_sum += widen(amount.units, amount.decimals);
+        }
+    }
+    if original_sum != 0 || scaled_sum == 0 {
+        return;
+    }
+    let largest = members
+        .iter()
+        .copied()
+        .filter(|&i| {
+            postings[i]
+                .amount
+                .as_ref()
+                .is_some_and(|amount| amount.decimals == decimals)
+        })
+        .max_by_key(|&i| postings[i].amount.as_ref().map_or(0, |a| a.units.abs()));
+    if let Some(amount) = largest.and_then(|i| postings[i].amount.as_mut()) {
+        amount.units -= scaled_sum;
+    }
+}
+
+fn scale_units(units: i128, scale: f64) -> i128 {
+    (units as f64 * scale).round() as i128
+}
+
+/// Split `account  amount` on the first run of two spaces or a tab.
+fn split_posting(body: &str) -> (&str, Option<&str>) {
+    let gap = [body.find("  "), body.find('\t')]
+        .into_iter()
+        .flatten()
+        .min();
+    match gap {
+        Some(at) => {
+            let amount = body[at..].trim();
+            (&body[..at], (!amount.is_empty()).then_some(amount))
+        }
+        None => (body, None),
+    }
+}
+
+/// Split `text ; comment` at the first `;`.
+fn split_comment(line: &str) -> (&str, Option<&str>) {
+    match line.find(';') {
+        Some(at) => (line[..at].trim_end(), Some(line[at + 1..].trim())),
+        None => (line.trim_end(), None),
+    }
+}
+
+fn is_structural(comment: &str) -> bool {
+    let Some((key, _)) = comment.split_once(':') else {
+        return false;
+    };
+    let key = key.trim();
+    STRUCTURAL_TAGS.contains(&key) || key.starts_with("posted-posting-")
+}
+
+/// Parse one amount such as `-12.50 USD`, `$1,000`, or `3 "ABC 1"`. The
+/// number is the first run of digits outside double quotes.
+fn parse_amount(text: &str) -> Option<ParsedAmount> {
+    let mut in_quotes = false;
+    let mut start = None;
+    for (i, c) in text.char_indices() {
+        if c == '"' {
+            in_quotes = !in_quotes;
+        } else if !in_quotes && c.is_ascii_digit() {
+            start = Some(i);
+            break;
+        }
+    }
+    let start = start?;
+    let end = text[start..]
+        .find(|c: char| !(c.is_ascii_digit() || c == ',' || c == '.'))
+        .map_or(text.len(), |len| start + len);
+    let number = text[start..end].trim_end_matches([',', '.']);
+    let end = start + number.len();
+    let (whole, fraction) = match number.rsplit_once('.') {
+        Some((whole, fraction)) => (whole, fraction),
+        None => (number, ""),
+    };
+    let digits: String = whole.chars().filter(char::is_ascii_digit).collect();
+    let mut units: i128 = format!("{digits}{fraction}").parse().ok()?;
+    let prefix = &text[..start];
+    if prefix.contains('-') {
+        units = -units;
+    }
+    Some(ParsedAmount {
+        prefix: prefix.to_string(),
+        units,
+        decimals: fraction.len(),
+        suffix: text[end..].to_string(),
+        rest: String::new(),
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn redactor(scale: f64) -> Redactor {
+        Redactor::new(&RedactionOptions {
+            target: String::new(),
+            amount_scale: scale,
+            hash_payees: true,
+            drop_documents: true,
+        })
+    }
+
+    #[test]
+    fn journal_amounts_scale_and_payees_hash() {
+        let journal = "\
+; opening notes about my accounts
+account Assets:Checking
+
+2024-02-15  * (1042) SHELL OIL 12345  ; id: gl-1
+    ; generated-by: refreshmint-post
+    ; note: gas for the road trip
+    Assets:Checking  -21.32 USD = 1000.00 USD
+    Expenses:Auto  21.32 USD  ; bankId: FIT123
+
+2024-02-16 Split
+    Assets:Checking  $0.01
+    Assets:Savings  $0.01
+    Income:Salary  $-0.02
+";
+        let mut redactor = redactor(0.5);
+        let (redacted, transactions) = redactor.redact_journal(journal);
+        assert_eq!(transactions, 2);
+        let lines: Vec<&str> = redacted.lines().collect();
+        assert_eq!(lines[0], "account Assets:Checking");
+        assert!(lines[2].starts_with("2024-02-15  * Payee "), "{}", lines[2]);
+        assert!(lines[2].contains("  ; id: "), "{}", lines[2]);
+        assert!(!redacted.contains("gl-1"));
+        assert!(!redacted.contains("SHELL"));
+        assert!(!redacted.contains("1042"));
+        assert!(!redacted.contains("road trip"));
+        assert!(!redacted.contains("FIT123"));
+        assert_eq!(lines[3], "    ; generated-by: refreshmint-post");
+        assert_eq!(lines[4], "    Assets:Checking  -10.66 USD");
+        assert_eq!(lines[5], "    Expenses:Auto  10.66 USD");
+        // Both halves of a cent round up; the largest posting takes the
+        // difference so the transaction still balances.
+        assert_eq!(lines[8], "    Assets:Checking  $0.01");
+        assert_eq!(lines[9], "    Assets:Savings  $0.01");
+        assert_eq!(lines[10], "    Income:Salary  -$0.02");
+        assert_eq!(redactor.payees.len(), 2);
+    }
+
+    #[test]
+    fn costs_and_quoted_commodities_keep_their_shape() {
+        let journal = "\
+2024-03-01 Buy
+    Assets:Brokerage  2 \"ABC 1\" @ 50.00 USD
+    Assets:Brokerage  0.5 BTC @@ 20000.00 USD
+    Assets:Cash
+";
+        let (redacted, _) = redactor(2.0).redact_journal(journal);
+        let lines: Vec<&str> = redacted.lines().collect();
+        assert_eq!(lines[1], "    Assets:Brokerage  4 \"ABC 1\" @ 50.00 USD");
+        assert_eq!(lines[2], "    Assets:Brokerage  1.0 BTC @@ 40000.00 USD");
+        assert_eq!(lines[3], "    Assets:Cash");
+    }
+
+    #[test]
+    fn export_copies_structure_but_not_history_or_state() {
+        let ledger = std::env::temp_dir().join(format!(
+            "refreshmint-redact-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&ledger, None).unwrap();
+        fs::write(
+            ledger.join("general.journal"),
+            "2024-01-02 Coffee Shop\n    Expenses:Food  4.50 USD\n    Assets:Cash\n",
+        )
+        .unwrap();
+        let account = ledger.join("logins/bank/accounts/checking");
+        fs::create_dir_all(account.join("documents")).unwrap();
+        fs::write(account.join("documents/statement.csv"), "secret").unwrap();
+        fs::write(account.join("account.journal"), "").unwrap();
+        fs::write(ledger.join("logins/bank/config.json"), "{}").unwrap();
+        fs::write(ledger.join("alert-rules.json"), "[]").unwrap();
+
+        let target = std::env::temp_dir().join(format!(
+            "refreshmint-redact-out-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        let report = export_redacted_ledger(
+            &ledger,
+            &RedactionOptions {
+                target: target.to_string_lossy().into_owned(),
+                amount_scale: 1.0,
+                hash_payees: true,
+                drop_documents: true,
+            },
+        )
+        .unwrap();
+        assert_eq!(report.journals, 2);
+        assert_eq!(report.transactions, 1);
+        assert_eq!(report.payees, 1);
+        assert_eq!(report.documents, 1);
+        assert!(report.skipped_files >= 1);
+
+        let general = fs::read_to_string(target.join("general.journal")).unwrap();
+        assert!(!general.contains("Coffee"));
+        assert!(general.contains("Expenses:Food  4.50 USD"));
+        assert!(target.join("logins/bank/config.json").exists());
+        assert!(target.join("refreshmint.json").exists());
+        assert!(!target.join("alert-rules.json").exists());
+        assert!(!target
+            .join("logins/bank/accounts/checking/documents")
+            .exists());
+
+        let repo = git2::Repository::open(&target).unwrap();
+        let head = repo.head().unwrap().peel_to_commit().unwrap();
+        assert_eq!(head.parent_count(), 0);
+        assert!(head
+            .tree()
+            .unwrap()
+            .get_path(Path::new("logins/bank/config.json"))
+            .is_ok());
+
+        assert!(export_redacted_ledger(
+            &ledger,
+            &RedactionOptions {
+                target: target.to_string_lossy().into_owned(),
+                amount_scale: 1.0,
+                hash_payees: false,
+                drop_documents: false,
+            },
+        )
+        .is_err());
+        let _ = fs::remove_dir_all(&ledger);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn export_replaces_ids_and_evidence_refs_consistently() {
+        let ledger = std::env::temp_dir().join(format!(
+            "refreshmint-redact-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&ledger, None).unwrap();
+        fs::write(
+            ledger.join("general.journal"),
+            "2024-01-02 Coffee Shop  ; id: gl-7d1e\n    \
+             ; source: logins/bank/accounts/checking:3f9a0c1d2e4b5a69\n    \
+             Expenses:Food  4.50 USD\n    Assets:Checking\n",
+        )
+        .unwrap();
+        let account = ledger.join("logins/bank/accounts/checking");
+        fs::create_dir_all(account.join("documents")).unwrap();
+        fs::write(account.join("documents/2024-01 statement.csv"), "row").unwrap();
+        fs::write(
+            account.join("documents/2024-01 statement.csv-info.json"),
+            "{}",
+        )
+        .unwrap();
+        fs::write(
+            account.join("account.journal"),
+            "2024-01-02 * COFFEE SHOP\n    \
+             ; id: 3f9a0c1d2e4b5a69\n    \
+             ; evidence: 2024-01 statement.csv:3:1\n    \
+             ; posted: general.journal:gl-7d1e\n    \
+             Assets:Checking  -4.50 USD\n    \
+             Equity:Staging:bank:checking  4.50 USD\n",
+        )
+        .unwrap();
+
+        let target = std::env::temp_dir().join(format!(
+            "refreshmint-redact-out-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        export_redacted_ledger(
+            &ledger,
+            &RedactionOptions {
+                target: target.to_string_lossy().into_owned(),
+                amount_scale: 0.5,
+                hash_payees: true,
+                drop_documents: false,
+            },
+        )
+        .unwrap();
+
+        let mut files = Vec::new();
+        collect_files(&target, Path::new(""), &mut files).unwrap();
+        for rel in &files {
+            let text = String::from_utf8_lossy(&fs::read(target.join(rel)).unwrap()).into_owned();
+            for original in ["3f9a0c1d2e4b5a69", "gl-7d1e", "2024-01 statement"] {
+                assert!(
+                    !text.contains(original),
+                    "{original} kept in {}",
+                    rel.display()
+                );
+                assert!(
+                    !rel.to_string_lossy().contains(original),
+                    "{original} kept in the name {}",
+                    rel.display()
+                );
+            }
+        }
+
+        // The replaced ids and document names still link up.
+        let tag = |text: &str, key: &str| -> String {
+            let start = text.find(&format!("; {key}: ")).unwrap() + key.len() + 4;
+            text[start..].lines().next().unwrap().to_string()
+        };
+        let general = fs::read_to_string(target.join("general.journal")).unwrap();
+        let account_out = target.join("logins/bank/accounts/checking");
+        let journal = fs::read_to_string(account_out.join("account.journal")).unwrap();
+        let entry_id = tag(&journal, "id");
+        assert_eq!(
+            tag(&general, "source"),
+            format!("logins/bank/accounts/checking:{entry_id}")
+        );
+        assert_eq!(
+            tag(&journal, "posted"),
+            format!("general.journal:{}", tag(&general, "id"))
+        );
+        let evidence = tag(&journal, "evidence");
+        let document = evidence.strip_suffix(":3:1").unwrap();
+        assert!(document.starts_with("doc-") && document.ends_with(".csv"));
+        assert!(account_out.join("documents").join(document).is_file());
+        assert!(account_out
+            .join("documents")
+            .join(format!("{document}-info.json"))
+            .is_file());
+        let _ = fs::remove_dir_all(&ledger);
+        let _ = fs::remove_dir_all(&target);
+    }
+
+    #[test]
+    fn export_strips_sensitive_login_config_fields() {
+        let ledger = std::env::temp_dir().join(format!(
+            "refreshmint-redact-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        crate::ledger::new_ledger_at_dir(&ledger, None).unwrap();
+        fs::create_dir_all(ledger.join("logins/bank")).unwrap();
+        fs::write(
+            ledger.join("logins/bank/config.json"),
+            r#"{
+  "extension": "bank-driver",
+  "accounts": {
+    "checking": {
+      "glAccount": "Assets:Checking",
+      "accountType": "bank",
+      "accountIds": ["DE89370400440532013000"]
+    }
+  },
+  "institution": {
+    "supportPhone": "800-555-0100",
+    "cardLast4": ["4242"],
+    "accessNotes": "2FA codes go to Alice's phone"
+  }
+}"#,
+        )
+        .unwrap();
+
+        let target = std::env::temp_dir().join(format!(
+            "refreshmint-redact-out-{}.refreshmint",
+            uuid::Uuid::new_v4()
+        ));
+        export_redacted_ledger(
+            &ledger,
+            &RedactionOptions {
+                target: target.to_string_lossy().into_owned(),
+                amount_scale: 1.0,
+                hash_payees: true,
+                drop_documents: true,
+            },
+        )
+        .unwrap();
+
+        let config: crate::login_config::LoginConfig = serde_json::from_str(
+            &fs::read_to_string(target.join("logins/bank/config.json")).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(config.extension.as_deref(), Some("bank-driver"));
+        let checking = &config.accounts["checking"];
+        assert_eq!(checking.gl_account.as_deref(), Some("Assets:Checking"));
+        assert_eq!(
+            checking.account_type,
+            Some(crate::login_config::AccountType::Bank)
+        );
+
+        // Everything outside `.git`; the commit holds the same contents.
+        let mut files = Vec::new();
+        collect_files(&target, Path::new(""), &mut files).unwrap();
+        assert!(!files.is_empty());
+        for rel in files {
+            let text = String::from_utf8_lossy(&fs::read(target.join(&rel)).unwrap()).into_owned();
+            for secret in [
+                "2FA codes",
+                "DE89370400440532013000",
+                "800-555-0100",
+                "4242",
+            ] {
+                assert!(
+                    !text.contains(secret),
+                    "{secret} leaked into {}",
+                    rel.display()
+                );
+            }
+        }
+        let _ = fs::remove_dir_all(&ledger);
+        let _ = fs::remove_dir_all(&target);
+    }
+}
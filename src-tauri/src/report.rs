@@ -20,8 +20,9 @@ const ALLOWED_COMMANDS: &[&str] = &[
 // Flags that control file I/O — must not be passed by the frontend
 const BLOCKED_FLAG_PREFIXES: &[&str] = &["-f", "--file", "-o", "--output-file", "--output-format"];
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Serialize, ts_rs::TS)]
 #[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "HledgerReportResult")]
 pub struct ReportResult {
     /// Populated for CSV-output commands (all except stats/activity)
     pub rows: Vec<Vec<String>>,
@@ -86,7 +87,7 @@ pub fn run_report(journal_path: &Path, command: &str, args: &[String]) -> io::Re
         cmd.arg(arg);
     }
 
-    let output = cmd.output()?;
+    let output = crate::hledger_batch::timed(command, || cmd.output())?;
     if !output.status.success() {
         return Err(io::Error::other(
             String::from_utf8_lossy(&output.stderr).to_string(),
@@ -106,6 +107,39 @@ pub fn run_report(journal_path: &Path, command: &str, args: &[String]) -> io::Re
     })
 }
 
+/// Query terms that leave `accounts` and their subaccounts out of a report.
+pub fn exclude_accounts_args(accounts: &[String]) -> Vec<String> {
+    accounts
+        .iter()
+        .map(|account| format!("not:acct:^{}(:|$)", regex::escape(account)))
+        .collect()
+}
+
+/// List every account name used in the journal.
+pub fn list_accounts(journal_path: &Path) -> io::Result<Vec<String>> {
+    let output = crate::hledger_batch::timed("accounts", || {
+        Command::new(crate::binpath::hledger_path())
+            .arg("accounts")
+            .arg("-f")
+            .arg(journal_path)
+            .env("GIT_CONFIG_GLOBAL", crate::ledger::NULL_DEVICE)
+            .env("GIT_CONFIG_SYSTEM", crate::ledger::NULL_DEVICE)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .output()
+    })?;
+    if !output.status.success() {
+        return Err(io::Error::other(
+            String::from_utf8_lossy(&output.stderr).to_string(),
+        ));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect())
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used, clippy::expect_used)]
 mod tests {
@@ -216,6 +250,16 @@ mod tests {
         assert_eq!(rows[1], vec!["Expenses:Food", "10", "20", "30"]);
     }
 
+    // --- exclude_accounts_args ---
+
+    #[test]
+    fn excluded_accounts_match_subaccounts_literally() {
+        assert_eq!(
+            exclude_accounts_args(&args(&["Assets:Retirement:401(k)"])),
+            vec![r"not:acct:^Assets:Retirement:401\(k\)(:|$)"]
+        );
+    }
+
     // -------------------------------------------------------------------------
     // Integration tests — require hledger on PATH.
     // Run with: cargo test report -- --ignored
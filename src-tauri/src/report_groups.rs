@@ -0,0 +1,215 @@
+//! Reporting groups that cut across the GL account hierarchy.
+//!
+//! Groups are stored in `report-groups.json` at the ledger root. Each group
+//! names a set of GL accounts; an account belongs to the group that lists it
+//! or its nearest listed ancestor. Grouped reports rewrite account names to
+//! `<top-level>:<group>` with hledger `--alias` flags, so income statement
+//! and balance sheet classification still works.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
+const REPORT_GROUPS_FILE: &str = "report-groups.json";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ReportGroup {
+    pub name: String,
+    /// GL accounts in this group. Subaccounts are included unless they are
+    /// listed by another group.
+    pub accounts: Vec<String>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ReportGroupsFile {
+    #[serde(default)]
+    groups: Vec<ReportGroup>,
+}
+
+pub fn report_groups_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(REPORT_GROUPS_FILE)
+}
+
+/// Load configured groups in display order. A missing file has no groups.
+pub fn list_report_groups(ledger_dir: &Path) -> io::Result<Vec<ReportGroup>> {
+    let file: Option<ReportGroupsFile> =
+        crate::bookkeeping::read_optional_json(&report_groups_path(ledger_dir))?;
+    Ok(file.unwrap_or_default().groups)
+}
+
+/// Create or replace a group. New groups are appended; existing groups keep
+/// their position.
+pub fn upsert_report_group(
+    ledger_dir: &Path,
+    name: &str,
+    accounts: Vec<String>,
+) -> io::Result<Vec<ReportGroup>> {
+    let name = require_group_name(name)?;
+    let mut accounts: Vec<String> = accounts
+        .into_iter()
+        .map(|account| account.trim().to_string())
+        .filter(|account| !account.is_empty())
+        .collect();
+    accounts.sort();
+    accounts.dedup();
+
+    let mut groups = list_report_groups(ledger_dir)?;
+    for group in groups.iter().filter(|group| group.name != name) {
+        if let Some(account) = accounts.iter().find(|a| group.accounts.contains(a)) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{account} is already in report group '{}'", group.name),
+            ));
+        }
+    }
+    match groups.iter_mut().find(|group| group.name == name) {
+        Some(group) => group.accounts = accounts,
+        None => groups.push(ReportGroup { name, accounts }),
+    }
+    save_report_groups(ledger_dir, &groups)?;
+    Ok(groups)
+}
+
+pub fn delete_report_group(ledger_dir: &Path, name: &str) -> io::Result<Vec<ReportGroup>> {
+    let mut groups = list_report_groups(ledger_dir)?;
+    groups.retain(|group| group.name != name.trim());
+    save_report_groups(ledger_dir, &groups)?;
+    Ok(groups)
+}
+
+fn save_report_groups(ledger_dir: &Path, groups: &[ReportGroup]) -> io::Result<()> {
+    crate::bookkeeping::write_json(
+        &report_groups_path(ledger_dir),
+        &ReportGroupsFile {
+            groups: groups.to_vec(),
+        },
+    )
+}
+
+/// Return the group for `account`: the group listing the account itself or
+/// its longest listed ancestor.
+pub fn group_for_account<'a>(groups: &'a [ReportGroup], account: &str) -> Option<&'a ReportGroup> {
+    groups
+        .iter()
+        .flat_map(|group| group.accounts.iter().map(move |listed| (listed, group)))
+        .filter(|(listed, _)| {
+            account == listed.as_str()
+                || account
+                    .strip_prefix(listed.as_str())
+                    .is_some_and(|rest| rest.starts_with(':'))
+        })
+        .max_by_key(|(listed, _)| listed.len())
+        .map(|(_, group)| group)
+}
+
+/// The account name a grouped report shows for `account`, e.g.
+/// `Expenses:Groceries` in group `Essentials` becomes `Expenses:Essentials`.
+pub fn grouped_account_name(groups: &[ReportGroup], account: &str) -> Option<String> {
+    let group = group_for_account(groups, account)?;
+    let top = account.split(':').next().unwrap_or(account);
+    Some(format!("{top}:{}", group.name))
+}
+
+/// hledger `--alias` flags that rename every grouped account in `accounts`.
+///
+/// One exact-match alias is emitted per account so the result does not
+/// depend on the order hledger applies aliases in.
+pub fn alias_args(groups: &[ReportGroup], accounts: &[String]) -> Vec<String> {
+    accounts
+        .iter()
+        .filter_map(|account| {
+            let grouped = grouped_account_name(groups, account)?;
+            Some(format!("--alias=/^{}$/={grouped}", regex::escape(account)))
+        })
+        .collect()
+}
+
+fn require_group_name(name: &str) -> io::Result<String> {
+    let name = name.trim();
+    if name.is_empty() || name.contains(':') || name.contains('/') {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "report group name must be non-empty and contain no ':' or '/'",
+        ));
+    }
+    Ok(name.to_string())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_ledger_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-report-groups-{prefix}-{}-{now}.refreshmint",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn upsert_rejects_account_claimed_by_other_group() {
+        let root = temp_ledger_dir("upsert");
+        upsert_report_group(&root, "Essentials", strings(&["Expenses:Rent"])).unwrap();
+        upsert_report_group(&root, "Fun", strings(&["Expenses:Dining"])).unwrap();
+        let groups = upsert_report_group(
+            &root,
+            "Essentials",
+            strings(&["Expenses:Food", "Expenses:Rent"]),
+        )
+        .unwrap();
+        assert_eq!(groups[0].name, "Essentials");
+        assert_eq!(groups[0].accounts.len(), 2);
+
+        let err = upsert_report_group(&root, "Fun", strings(&["Expenses:Rent"])).unwrap_err();
+        assert!(err.to_string().contains("Essentials"));
+        assert!(upsert_report_group(&root, "A:B", vec![]).is_err());
+
+        let groups = delete_report_group(&root, "Fun").unwrap();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(list_report_groups(&root).unwrap(), groups);
+        let _ = std::fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn nearest_listed_ancestor_wins() {
+        let groups = vec![
+            ReportGroup {
+                name: "Fun".to_string(),
+                accounts: strings(&["Expenses:Food"]),
+            },
+            ReportGroup {
+                name: "Essentials".to_string(),
+                accounts: strings(&["Expenses:Food:Groceries"]),
+            },
+        ];
+        assert_eq!(
+            grouped_account_name(&groups, "Expenses:Food:Groceries:Costco").as_deref(),
+            Some("Expenses:Essentials")
+        );
+        assert_eq!(
+            grouped_account_name(&groups, "Expenses:Food:Dining").as_deref(),
+            Some("Expenses:Fun")
+        );
+        assert_eq!(grouped_account_name(&groups, "Expenses:Foodstuff"), None);
+
+        let args = alias_args(
+            &groups,
+            &strings(&["Expenses:Food:Dining", "Assets:Checking"]),
+        );
+        assert_eq!(args, vec!["--alias=/^Expenses:Food:Dining$/=Expenses:Fun"]);
+    }
+}
@@ -1,9 +1,21 @@
+pub mod api_version;
+pub mod assertions;
 pub mod browser;
 pub mod debug;
+pub mod debug_attach;
+pub mod download_check;
+pub mod flows;
+pub mod input;
 pub mod js_api;
 pub mod locator;
+pub mod login_wall;
+pub mod network_policy;
+pub mod preflight;
 pub mod profile;
+pub mod prompt_catalog;
+pub mod queue;
 pub mod sandbox;
+pub mod storage;
 
 use serde::Deserialize;
 use std::path::Path;
@@ -11,7 +23,7 @@ use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 
-use crate::secret::SecretStore;
+use crate::secret::{SecretCache, SecretStore};
 
 /// Configuration for a scrape run.
 pub struct ScrapeConfig {
@@ -25,6 +37,48 @@ pub struct ScrapeConfig {
     /// When set, `refreshmint.prompt()` asks the host app for a response
     /// rather than reading from stdin.
     pub prompt_ui_handler: Option<js_api::PromptUiHandler>,
+    pub mode: ScrapeMode,
+    /// Receives what the session observed, for the scrape log.
+    pub report: Option<ScrapeReportSlot>,
+}
+
+/// Session details recorded in the scrape log, filled when a scrape ends
+/// whether or not it succeeded.
+#[derive(Debug, Default)]
+pub struct ScrapeReport {
+    /// Allowlist and domains the driver contacted.
+    pub network: Option<network_policy::NetworkReport>,
+    /// Prompts the driver asked for, without their answers.
+    pub prompts_asked: Vec<prompt_catalog::PromptRequest>,
+    /// The window the driver declared with `setSessionMetadata`.
+    pub session_metadata: Option<js_api::SessionMetadata>,
+    /// Saved resources that look like error pages or empty exports.
+    pub suspect_documents: Vec<download_check::SuspectDownload>,
+    /// The session's ID, set as soon as the scrape starts.
+    pub scrape_session_id: Option<String>,
+}
+
+pub type ScrapeReportSlot = Arc<std::sync::Mutex<ScrapeReport>>;
+
+/// Which entry point of the extension a scrape runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScrapeMode {
+    /// The regular `driver` that downloads account documents.
+    Accounts,
+    /// The manifest `taxDocuments` entry point, collecting forms for a tax year.
+    TaxDocuments { year: i32 },
+}
+
+impl ScrapeMode {
+    /// Options exposed to the driver via `refreshmint.getOptions()`.
+    fn script_options(self) -> js_api::ScriptOptions {
+        let mut options = js_api::ScriptOptions::new();
+        if let ScrapeMode::TaxDocuments { year } = self {
+            options.insert("mode".to_string(), "tax-documents".into());
+            options.insert("taxYear".to_string(), year.into());
+        }
+        options
+    }
 }
 
 /// The value type for a domain entry in `manifest.json` `secrets` field.
@@ -55,8 +109,36 @@ struct ExtensionManifest {
     rules: Option<String>,
     #[serde(default, rename = "idField")]
     id_field: Option<String>,
+    #[serde(default)]
+    sheet: Option<String>,
     #[serde(default, rename = "autoExtract")]
     auto_extract: Option<bool>,
+    #[serde(default, rename = "taxDocuments")]
+    tax_documents: Option<TaxDocumentsEntry>,
+    #[serde(default, rename = "allowedDomains")]
+    allowed_domains: Option<Vec<String>>,
+    #[serde(default)]
+    prompts: prompt_catalog::PromptCatalog,
+    #[serde(default, rename = "browserProfile")]
+    browser_profile: profile::ProfileMode,
+    #[serde(default, rename = "loginWallUrls")]
+    login_wall_urls: Vec<String>,
+    #[serde(default, rename = "minApiVersion")]
+    min_api_version: Option<u32>,
+}
+
+/// Manifest `taxDocuments` entry point.
+///
+/// `driver` defaults to the regular driver, which can branch on
+/// `refreshmint.getOptions().mode === "tax-documents"`.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct TaxDocumentsEntry {
+    #[serde(default)]
+    pub driver: Option<String>,
+    /// Form types the institution issues every year (e.g. `1099-INT`),
+    /// used to report missing forms.
+    #[serde(default, rename = "expectedForms")]
+    pub expected_forms: Vec<String>,
 }
 
 /// Parsed extension manifest with all fields.
@@ -66,7 +148,21 @@ pub struct ParsedManifest {
     pub extract: Option<String>,
     pub rules: Option<String>,
     pub id_field: Option<String>,
+    /// Worksheet used when `rules` extracts XLSX documents.
+    pub sheet: Option<String>,
     pub auto_extract: bool,
+    pub tax_documents: Option<TaxDocumentsEntry>,
+    /// Hosts (and their subdomains) the driver may navigate to and fetch
+    /// from; `None` leaves the network unrestricted.
+    pub allowed_domains: Option<Vec<String>>,
+    /// Named prompts drivers pass to `refreshmint.prompt` by key.
+    pub prompts: prompt_catalog::PromptCatalog,
+    /// Whether browser state persists between runs of a login.
+    pub browser_profile: profile::ProfileMode,
+    /// URL globs of pages that mean the session expired.
+    pub login_wall_urls: Vec<String>,
+    /// JS API version the extension was written against.
+    pub min_api_version: Option<u32>,
 }
 
 /// Load and parse the full extension manifest.
@@ -145,13 +241,38 @@ pub fn load_manifest(
         declared.insert(domain, creds);
     }
 
+    let allowed_domains = manifest
+        .allowed_domains
+        .as_deref()
+        .map(network_policy::normalize_allowed_domains)
+        .transpose()
+        .map_err(|err| format!("{err} in {}", manifest_path.display()))?;
+    prompt_catalog::validate_catalog(&manifest.prompts)
+        .map_err(|err| format!("{err} in {}", manifest_path.display()))?;
+    let login_wall_urls = login_wall::normalize_login_wall_patterns(&manifest.login_wall_urls)
+        .map_err(|err| format!("{err} in {}", manifest_path.display()))?;
+    if manifest.min_api_version == Some(0) {
+        return Err(format!(
+            "invalid minApiVersion 0 in {}; versions start at 1",
+            manifest_path.display()
+        )
+        .into());
+    }
+
     Ok(ParsedManifest {
         secrets: declared,
         driver: manifest.driver,
         extract: manifest.extract,
         rules: manifest.rules,
         id_field: manifest.id_field,
+        sheet: manifest.sheet,
         auto_extract: manifest.auto_extract.unwrap_or(true),
+        tax_documents: manifest.tax_documents,
+        allowed_domains,
+        prompts: manifest.prompts,
+        browser_profile: manifest.browser_profile,
+        login_wall_urls,
+        min_api_version: manifest.min_api_version,
     })
 }
 
@@ -163,17 +284,69 @@ pub fn resolve_driver_script_path(extension_dir: &Path, manifest: &ParsedManifes
     extension_dir.join(manifest.driver.as_deref().unwrap_or("driver.mjs"))
 }
 
+/// Resolve the driver script for `mode`. Fails when the extension does not
+/// declare the requested entry point.
+pub fn resolve_mode_driver_script_path(
+    extension_dir: &Path,
+    manifest: &ParsedManifest,
+    mode: ScrapeMode,
+) -> Result<PathBuf, String> {
+    match mode {
+        ScrapeMode::Accounts => Ok(resolve_driver_script_path(extension_dir, manifest)),
+        ScrapeMode::TaxDocuments { .. } => {
+            let entry = manifest.tax_documents.as_ref().ok_or_else(|| {
+                "extension does not declare a taxDocuments entry point".to_string()
+            })?;
+            Ok(match entry.driver.as_deref() {
+                Some(driver) => extension_dir.join(driver),
+                None => resolve_driver_script_path(extension_dir, manifest),
+            })
+        }
+    }
+}
+
+/// In tax-documents mode, resources saved without a `documentType` are tax
+/// forms, and every form is tagged with the requested `taxYear`.
+fn apply_tax_document_defaults(resources: &mut [js_api::StagedResource], year: i32) {
+    for resource in resources {
+        resource.document_type.get_or_insert(DocumentType::TaxForm);
+        resource
+            .metadata
+            .entry("taxYear".to_string())
+            .or_insert_with(|| year.into());
+    }
+}
+
+/// The latest window end recorded in the login's document sidecars, so a
+/// driver can start where its previous scrape stopped. Sidecars written
+/// before `sessionDateRangeEnd` existed fall back to `dateRangeEnd`.
+pub fn last_session_date_range_end(ledger_dir: &Path, login_name: &str) -> Option<String> {
+    let labels = crate::login_config::list_login_labels(ledger_dir, login_name).ok()?;
+    labels
+        .iter()
+        .filter_map(|label| {
+            crate::extract::list_documents_for_login_account(ledger_dir, login_name, label).ok()
+        })
+        .flatten()
+        .filter_map(|doc| doc.info)
+        .filter_map(|info| info.session_date_range_end.or(info.date_range_end))
+        .filter(|end| chrono::NaiveDate::parse_from_str(end, "%Y-%m-%d").is_ok())
+        .max()
+}
+
 /// Generate a scrape session ID from the current timestamp.
 pub fn generate_scrape_session_id() -> String {
     chrono::Local::now().format("%Y%m%d-%H%M%S").to_string()
 }
 
 /// Document info sidecar written alongside each evidence document.
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
 pub struct DocumentInfo {
     #[serde(rename = "mimeType")]
     pub mime_type: String,
     #[serde(rename = "originalUrl", skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub original_url: Option<String>,
     #[serde(rename = "scrapedAt")]
     pub scraped_at: String,
@@ -188,17 +361,131 @@ pub struct DocumentInfo {
     #[serde(rename = "coverageEndDate")]
     pub coverage_end_date: String,
     #[serde(rename = "dateRangeStart", skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub date_range_start: Option<String>,
     #[serde(rename = "dateRangeEnd", skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
     pub date_range_end: Option<String>,
+    /// The window the driver declared with `setSessionMetadata`. Unlike
+    /// `dateRangeStart`/`dateRangeEnd`, never an inferred document period.
+    #[serde(
+        rename = "sessionDateRangeStart",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub session_date_range_start: Option<String>,
+    #[serde(
+        rename = "sessionDateRangeEnd",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    #[ts(optional)]
+    pub session_date_range_end: Option<String>,
+    #[serde(
+        rename = "documentType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub document_type: Option<DocumentType>,
     #[serde(default, skip_serializing_if = "std::collections::BTreeMap::is_empty")]
     pub metadata: std::collections::BTreeMap<String, serde_json::Value>,
+    /// Why the saved bytes look like an error page or an empty export.
+    /// Extraction skips suspect documents unless asked to include them.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub suspect: Option<String>,
 }
 
 fn default_document_label() -> String {
     "_default".to_string()
 }
 
+/// Kind of evidence document, declared by the driver via
+/// `saveResource(..., { documentType })`.
+///
+/// Typed documents are stored in a per-type subfolder of the label's
+/// documents directory and follow the type's retention rule.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    serde::Serialize,
+    serde::Deserialize,
+    ts_rs::TS,
+)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
+pub enum DocumentType {
+    Statement,
+    TransactionsCsv,
+    TaxForm,
+    Confirmation,
+    Paystub,
+    TransactionDetails,
+}
+
+impl DocumentType {
+    pub const ALL: [DocumentType; 6] = [
+        DocumentType::Statement,
+        DocumentType::TransactionsCsv,
+        DocumentType::TaxForm,
+        DocumentType::Confirmation,
+        DocumentType::Paystub,
+        DocumentType::TransactionDetails,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            DocumentType::Statement => "statement",
+            DocumentType::TransactionsCsv => "transactions-csv",
+            DocumentType::TaxForm => "tax-form",
+            DocumentType::Confirmation => "confirmation",
+            DocumentType::Paystub => "paystub",
+            DocumentType::TransactionDetails => "transaction-details",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, String> {
+        Self::ALL
+            .into_iter()
+            .find(|t| t.as_str() == value.trim())
+            .ok_or_else(|| {
+                let expected = Self::ALL.map(DocumentType::as_str).join("|");
+                format!("documentType: expected one of ({expected}), got {value}")
+            })
+    }
+
+    /// Subfolder of `documents/` holding documents of this type.
+    pub fn folder(self) -> &'static str {
+        match self {
+            DocumentType::Statement => "statements",
+            DocumentType::TransactionsCsv => "transactions",
+            DocumentType::TaxForm => "tax-forms",
+            DocumentType::Confirmation => "confirmations",
+            DocumentType::Paystub => "paystubs",
+            DocumentType::TransactionDetails => "transaction-details",
+        }
+    }
+
+    /// Days after `coverageEndDate` that an unreferenced document may be
+    /// pruned. `None` keeps the document forever.
+    pub fn retention_days(self) -> Option<i64> {
+        match self {
+            DocumentType::Statement
+            | DocumentType::TransactionsCsv
+            | DocumentType::TaxForm
+            | DocumentType::Paystub
+            | DocumentType::TransactionDetails => None,
+            DocumentType::Confirmation => Some(2 * 365),
+        }
+    }
+}
+
 /// Finalize staged resources: move them to `logins/<login>/accounts/<label>/documents/`
 /// with date-prefixed filenames and write `-info.json` sidecars.
 pub fn finalize_staged_resources(
@@ -209,7 +496,16 @@ pub fn finalize_staged_resources(
     let mut finalized_names = Vec::new();
     let mut labels_seen = std::collections::BTreeSet::new();
     let mut resources_with_labels = Vec::new();
+    let mut login_config =
+        crate::login_config::read_login_config(&inner.ledger_dir, &inner.login_name);
+    // A hand-edited rule that no longer compiles should not fail the scrape.
+    let label_rules = crate::document_labels::LabelRules::compile(&login_config.label_rules)
+        .unwrap_or_else(|err| {
+            tracing::warn!("ignoring label rules of login {}: {err}", inner.login_name);
+            crate::document_labels::LabelRules::default()
+        });
 
+    let mut login_config_changed = false;
     for resource in &inner.staged_resources {
         let label = if let Some(raw) = resource.label.as_ref() {
             crate::login_config::validate_label(raw).map_err(|err| {
@@ -217,21 +513,65 @@ pub fn finalize_staged_resources(
             })?;
             raw.clone()
         } else {
-            "_default".to_string()
+            let mime = resource
+                .mime_type
+                .clone()
+                .unwrap_or_else(|| guess_mime_type(&resource.filename));
+            let fields = crate::document_labels::document_fields(
+                resource.original_url.as_deref(),
+                Some(&mime),
+                resource.document_type,
+                &resource.metadata,
+            );
+            let name = resource
+                .filename
+                .rsplit('/')
+                .next()
+                .unwrap_or(&resource.filename);
+            label_rules
+                .label_for(name, &fields)
+                .unwrap_or(crate::document_labels::DEFAULT_LABEL)
+                .to_string()
+        };
+        // A registered account number names the label, so a renamed
+        // nickname does not start a new one; an unknown number is registered
+        // to the label the driver or a rule gave.
+        let label = match crate::account_routing::metadata_account_id(&resource.metadata) {
+            Some(account_id) => {
+                match crate::account_routing::resolve_account_id(&login_config, &account_id) {
+                    Some(known) => known.to_string(),
+                    None => {
+                        if label != crate::document_labels::DEFAULT_LABEL {
+                            login_config
+                                .accounts
+                                .entry(label.clone())
+                                .or_default()
+                                .account_ids
+                                .push(account_id);
+                            login_config_changed = true;
+                        }
+                        label
+                    }
+                }
+            }
+            None => label,
         };
 
         labels_seen.insert(label.clone());
         resources_with_labels.push((resource, label));
     }
 
-    let mut login_config =
-        crate::login_config::read_login_config(&inner.ledger_dir, &inner.login_name);
-    let mut login_config_changed = false;
     for label in labels_seen {
         if let std::collections::btree_map::Entry::Vacant(entry) =
             login_config.accounts.entry(label)
         {
-            entry.insert(crate::login_config::LoginAccountConfig { gl_account: None });
+            entry.insert(crate::login_config::LoginAccountConfig {
+                gl_account: None,
+                account_type: None,
+                account_ids: Vec::new(),
+                counterpart_account: None,
+                coverage_start: None,
+            });
             login_config_changed = true;
         }
     }
@@ -244,9 +584,25 @@ pub fn finalize_staged_resources(
     }
 
     for (resource, label) in resources_with_labels {
+        let mime = resource
+            .mime_type
+            .clone()
+            .unwrap_or_else(|| guess_mime_type(&resource.filename));
+        // Without a driver-supplied date, the document's own period beats
+        // the scrape date for incremental windows and retention.
+        let inferred = match resource.coverage_end_date {
+            Some(_) => None,
+            None => crate::statement_period::infer_document_period(
+                &resource.staging_path,
+                &resource.filename,
+                &mime,
+            ),
+        };
+        let inferred_end = inferred.map(|period| period.end.format("%Y-%m-%d").to_string());
         let coverage_date = resource
             .coverage_end_date
             .as_deref()
+            .or(inferred_end.as_deref())
             .unwrap_or(&fallback_date);
         let documents_dir = crate::login_config::login_account_documents_dir(
             &inner.ledger_dir,
@@ -255,15 +611,25 @@ pub fn finalize_staged_resources(
         );
         std::fs::create_dir_all(&documents_dir)?;
 
-        let final_filename =
-            date_prefixed_filename(coverage_date, &resource.filename, &documents_dir);
+        let final_filename = match resource.document_type {
+            Some(document_type) => {
+                let folder = document_type.folder();
+                let name = date_prefixed_filename(
+                    coverage_date,
+                    &resource.filename,
+                    &documents_dir.join(folder),
+                );
+                format!("{folder}/{name}")
+            }
+            None => date_prefixed_filename(coverage_date, &resource.filename, &documents_dir),
+        };
         let final_path = documents_dir.join(&final_filename);
         if let Some(parent) = final_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
         // Copy from staging to documents dir
-        std::fs::copy(&resource.staging_path, &final_path).map_err(|e| {
+        crate::atomic_file::copy_atomic(&resource.staging_path, &final_path).map_err(|e| {
             format!(
                 "failed to copy {} to {}: {e}",
                 resource.staging_path.display(),
@@ -271,11 +637,21 @@ pub fn finalize_staged_resources(
             )
         })?;
 
-        // Guess MIME type from extension
-        let mime = resource
-            .mime_type
-            .clone()
-            .unwrap_or_else(|| guess_mime_type(&resource.filename));
+        // The scrape window, or the document's own period when the session
+        // has none.
+        let session = &inner.session_metadata;
+        let (date_range_start, date_range_end) = match inferred {
+            Some(period) if session.is_empty() => (
+                period
+                    .start
+                    .map(|start| start.format("%Y-%m-%d").to_string()),
+                inferred_end.clone(),
+            ),
+            _ => (
+                session.date_range_start.clone(),
+                session.date_range_end.clone(),
+            ),
+        };
 
         // Write sidecar
         let info = DocumentInfo {
@@ -287,9 +663,13 @@ pub fn finalize_staged_resources(
             label: label.clone(),
             scrape_session_id: inner.scrape_session_id.clone(),
             coverage_end_date: coverage_date.to_string(),
-            date_range_start: inner.session_metadata.date_range_start.clone(),
-            date_range_end: inner.session_metadata.date_range_end.clone(),
+            date_range_start,
+            date_range_end,
+            session_date_range_start: session.date_range_start.clone(),
+            session_date_range_end: session.date_range_end.clone(),
+            document_type: resource.document_type,
             metadata: resource.metadata.clone(),
+            suspect: resource.suspect.clone(),
         };
 
         let sidecar_path = documents_dir.join(format!("{final_filename}-info.json"));
@@ -297,7 +677,7 @@ pub fn finalize_staged_resources(
             std::fs::create_dir_all(parent)?;
         }
         let sidecar_json = serde_json::to_string_pretty(&info)?;
-        std::fs::write(&sidecar_path, sidecar_json)?;
+        crate::atomic_file::write_atomic(&sidecar_path, sidecar_json.as_bytes())?;
 
         finalized_names.push(final_filename);
     }
@@ -305,8 +685,43 @@ pub fn finalize_staged_resources(
     Ok(finalized_names)
 }
 
+/// Append balances staged by `reportBalance` to each label's `balances.csv`.
+/// Returns the number of rows written.
+pub fn finalize_staged_balances(
+    inner: &js_api::RefreshmintInner,
+) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
+    let mut by_label: std::collections::BTreeMap<String, Vec<crate::balances::BalanceSnapshot>> =
+        std::collections::BTreeMap::new();
+    for staged in &inner.staged_balances {
+        let label = match staged.label.as_ref() {
+            Some(raw) => {
+                crate::login_config::validate_label(raw)
+                    .map_err(|err| format!("invalid label '{raw}' for reportBalance: {err}"))?;
+                raw.clone()
+            }
+            None => "_default".to_string(),
+        };
+        by_label
+            .entry(label)
+            .or_default()
+            .push(staged.snapshot.clone());
+    }
+
+    let mut written = 0;
+    for (label, snapshots) in by_label {
+        let path = crate::balances::login_account_balances_path(
+            &inner.ledger_dir,
+            &inner.login_name,
+            &label,
+        );
+        crate::balances::append_snapshots(&path, &snapshots)?;
+        written += snapshots.len();
+    }
+    Ok(written)
+}
+
 /// Generate a date-prefixed filename, handling collisions with incrementing suffix.
-fn date_prefixed_filename(date: &str, original: &str, dir: &Path) -> String {
+pub(crate) fn date_prefixed_filename(date: &str, original: &str, dir: &Path) -> String {
     let candidate = format!("{date}-{original}");
     if !dir.join(&candidate).exists() {
         return candidate;
@@ -331,7 +746,7 @@ fn date_prefixed_filename(date: &str, original: &str, dir: &Path) -> String {
 }
 
 /// Guess MIME type from file extension.
-fn guess_mime_type(filename: &str) -> String {
+pub(crate) fn guess_mime_type(filename: &str) -> String {
     let ext = filename
         .rsplit('.')
         .next()
@@ -345,6 +760,7 @@ fn guess_mime_type(filename: &str) -> String {
         "txt" => "text/plain",
         "xml" => "application/xml",
         "ofx" | "qfx" => "application/x-ofx",
+        "qif" => "application/qif",
         _ => "application/octet-stream",
     }
     .to_string()
@@ -457,26 +873,43 @@ pub async fn run_scrape_async(
         crate::account_config::resolve_extension_dir(&config.ledger_dir, &config.extension_name);
     // 1. Load full manifest and locate the declared driver script.
     let manifest = load_manifest(&extension_dir)?;
-    let driver_path = resolve_driver_script_path(&extension_dir, &manifest);
+    for warning in
+        api_version::check_compatibility(&config.extension_name, manifest.min_api_version)?
+    {
+        tracing::warn!("{warning}");
+    }
+    let driver_path = resolve_mode_driver_script_path(&extension_dir, &manifest, config.mode)?;
     if !driver_path.exists() {
         return Err(format!("driver script not found: {}", driver_path.display()).into());
     }
     let declared_secrets = manifest.secrets;
+    let prompt_catalog = manifest.prompts;
+    let network_policy = Arc::new(network_policy::NetworkPolicy::new(manifest.allowed_domains));
+    let login_wall = Arc::new(login_wall::LoginWall::new(&manifest.login_wall_urls));
 
     // Generate scrape session ID
     let scrape_session_id = generate_scrape_session_id();
-    eprintln!("Scrape session: {scrape_session_id}");
+    tracing::info!("Scrape session: {scrape_session_id}");
+    if let Some(slot) = &config.report {
+        slot.lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .scrape_session_id = Some(scrape_session_id.clone());
+    }
 
-    // 2. Create secret store for the login
-    let secret_store = SecretStore::new(format!("login/{login_name}"));
+    // 2. Create the session's secret cache for the login. Dropping it at the
+    // end of the scrape zeroizes every value it read.
+    let secrets = SecretCache::new(SecretStore::new(format!("login/{login_name}")));
 
-    // 3. Resolve browser profile directory
-    let profile_dir = profile::resolve_profile_dir(
+    // 3. Resolve browser profile directory. An ephemeral profile is deleted
+    // when `session_profile` drops, after the browser has closed.
+    let session_profile = profile::resolve_session_profile(
         &config.ledger_dir,
         &login_name,
         config.profile_override.as_deref(),
+        manifest.browser_profile,
     )
     .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { e.to_string().into() })?;
+    let profile_dir = session_profile.dir();
 
     // 4. Resolve download directory
     let download_dir =
@@ -487,24 +920,32 @@ pub async fn run_scrape_async(
     // 5. Find and launch browser
     let chrome_path = browser::find_chrome_binary()
         .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { e.to_string().into() })?;
-    eprintln!("Using browser: {}", chrome_path.display());
-    eprintln!("Profile dir: {}", profile_dir.display());
+    tracing::info!("Using browser: {}", chrome_path.display());
+    tracing::info!(
+        "Profile dir: {}{}",
+        profile_dir.display(),
+        if session_profile.is_ephemeral() {
+            " (ephemeral)"
+        } else {
+            ""
+        }
+    );
 
-    eprintln!("Launching browser...");
+    tracing::info!("Launching browser...");
     let (browser_instance, handler_handle) =
-        browser::launch_browser(&chrome_path, &profile_dir, config.headless)
+        browser::launch_browser(&chrome_path, profile_dir, config.headless)
             .await
             .map_err(|e| -> Box<dyn std::error::Error + Send + Sync> { e.to_string().into() })?;
-    eprintln!("Browser launched.");
+    tracing::info!("Browser launched.");
     let browser = Arc::new(Mutex::new(browser_instance));
 
     // 6. Open a new page
-    eprintln!("Opening new page...");
+    tracing::info!("Opening new page...");
     let page = {
         let mut guard = browser.lock().await;
         browser::open_start_page(&mut guard).await?
     };
-    eprintln!("Page opened.");
+    tracing::info!("Page opened.");
 
     // 7. Set up shared state
     let ext_cache_key = std::path::Path::new(&config.extension_name)
@@ -522,34 +963,53 @@ pub async fn run_scrape_async(
     // directory, so it cannot detect these orphans; re-downloading is correct.
     clear_staged_output_dir(&output_dir)?;
 
+    let mut script_options = config.mode.script_options();
+    if config.mode == ScrapeMode::Accounts {
+        if let Some(end) = last_session_date_range_end(&config.ledger_dir, &login_name) {
+            script_options.insert("lastDateRangeEnd".to_string(), end.into());
+        }
+    }
+
+    network_policy.install(&page).await?;
     let page_inner = Arc::new(Mutex::new(js_api::PageInner {
         target_id: page.target_id().as_ref().to_string(),
         page,
         browser: browser.clone(),
-        secret_store: Arc::new(secret_store),
+        secrets: Arc::new(secrets),
         declared_secrets: Arc::new(declared_secrets),
+        download_root: download_dir.clone(),
         download_dir,
         target_frame_id: None,
+        network_policy: network_policy.clone(),
+        strict_selectors: true,
+        handlers: js_api::PageHandlers::default(),
+        closed: false,
+        login_wall,
+        input: Default::default(),
     }));
 
     let refreshmint_inner = Arc::new(Mutex::new(js_api::RefreshmintInner {
         output_dir,
         prompt_overrides: config.prompt_overrides.clone(),
         prompt_requires_override: config.prompt_requires_override,
-        script_options: js_api::ScriptOptions::new(),
+        prompt_catalog,
+        script_options,
         debug_output_sink: None,
         session_metadata: js_api::SessionMetadata::default(),
         staged_resources: Vec::new(),
+        staged_balances: Vec::new(),
         scrape_session_id: scrape_session_id.clone(),
         extension_name: config.extension_name.clone(),
         account_name: login_name.clone(),
         login_name: login_name.clone(),
         ledger_dir: config.ledger_dir.clone(),
         prompt_ui_handler: config.prompt_ui_handler.clone(),
+        assertion_failure: None,
+        prompts_asked: Vec::new(),
     }));
 
     // 8. Run the driver script in the sandbox
-    eprintln!("Running driver: {}", driver_path.display());
+    tracing::info!("Running driver: {}", driver_path.display());
     let mut result = sandbox::run_driver(
         &extension_dir,
         &driver_path,
@@ -557,20 +1017,30 @@ pub async fn run_scrape_async(
         refreshmint_inner.clone(),
     )
     .await;
-    eprintln!("Driver finished: {result:?}");
+    tracing::info!("Driver finished: {result:?}");
+    if result.is_err() {
+        // Report a failed assertion with its captured page context rather
+        // than the bare JS exception it was thrown as.
+        if let Some(failure) = refreshmint_inner.lock().await.assertion_failure.take() {
+            result = Err(Box::new(assertions::AssertionError(failure)));
+        }
+    }
 
     // 9. Finalize staged resources (move to accounts/<name>/documents/)
     if result.is_ok() {
-        let inner = refreshmint_inner.lock().await;
+        let mut inner = refreshmint_inner.lock().await;
+        if let ScrapeMode::TaxDocuments { year } = config.mode {
+            apply_tax_document_defaults(&mut inner.staged_resources, year);
+        }
         if !inner.staged_resources.is_empty() {
-            eprintln!(
+            tracing::info!(
                 "Finalizing {} staged resources...",
                 inner.staged_resources.len()
             );
             match finalize_staged_resources(&inner) {
                 Ok(names) => {
                     for name in &names {
-                        eprintln!("  -> {name}");
+                        tracing::info!("finalized {name}");
                     }
                 }
                 Err(e) => {
@@ -578,6 +1048,11 @@ pub async fn run_scrape_async(
                 }
             }
         }
+        if !inner.staged_balances.is_empty() {
+            if let Err(e) = finalize_staged_balances(&inner) {
+                result = Err(format!("failed to finalize staged balances: {e}").into());
+            }
+        }
     }
 
     // 10. Auto-save extension in login config if not already set
@@ -594,13 +1069,39 @@ pub async fn run_scrape_async(
             if let Err(e) =
                 crate::login_config::write_login_config(&config.ledger_dir, &login_name, &existing)
             {
-                eprintln!("Warning: failed to save login config: {e}");
+                tracing::warn!("failed to save login config: {e}");
             }
         }
     }
 
+    if let Some(slot) = &config.report {
+        let (prompts_asked, session_metadata, suspect_documents) = {
+            let mut inner = refreshmint_inner.lock().await;
+            let suspect_documents = inner
+                .staged_resources
+                .iter()
+                .filter_map(|resource| {
+                    Some(download_check::SuspectDownload {
+                        filename: resource.filename.clone(),
+                        reason: resource.suspect.clone()?,
+                    })
+                })
+                .collect();
+            (
+                std::mem::take(&mut inner.prompts_asked),
+                inner.session_metadata.clone(),
+                suspect_documents,
+            )
+        };
+        let mut report = slot.lock().unwrap_or_else(|err| err.into_inner());
+        report.network = Some(network_policy.report());
+        report.prompts_asked = prompts_asked;
+        report.session_metadata = Some(session_metadata).filter(|session| !session.is_empty());
+        report.suspect_documents = suspect_documents;
+    }
+
     // 11. Close browser
-    eprintln!("Closing browser...");
+    tracing::info!("Closing browser...");
     {
         let guard = browser.lock().await;
         let _ = guard.close().await;
@@ -608,13 +1109,24 @@ pub async fn run_scrape_async(
     drop(browser);
     // Wait briefly for handler to clean up, but don't block indefinitely
     let _ = tokio::time::timeout(std::time::Duration::from_secs(5), handler_handle).await;
-    eprintln!("Done.");
+    drop(session_profile);
+    tracing::info!("Done.");
 
     result
 }
 
 /// Synchronous entry point that creates a tokio runtime and runs the scrape.
+///
+/// Blocks in the process-wide [`queue::ScrapeQueue`] until the concurrency
+/// limit and per-domain serialization allow this login to start.
 pub fn run_scrape(config: ScrapeConfig) -> Result<(), Box<dyn std::error::Error>> {
+    let extension_dir =
+        crate::account_config::resolve_extension_dir(&config.ledger_dir, &config.extension_name);
+    let declared_secrets = load_manifest_secret_declarations(&extension_dir)
+        .map_err(|e| -> Box<dyn std::error::Error> { e.to_string().into() })?;
+    let domains = queue::scrape_domains(&declared_secrets, &config.extension_name);
+    let _slot = queue::ScrapeQueue::global().acquire(&config.login_name, domains)?;
+
     let rt = tokio::runtime::Runtime::new()?;
     rt.block_on(run_scrape_async(config))
         .map_err(|e| -> Box<dyn std::error::Error> { e })?;
@@ -626,15 +1138,15 @@ mod tests {
     use super::{
         clear_staged_output_dir, finalize_staged_resources, list_runnable_extensions,
         load_manifest, load_manifest_secret_declarations, normalize_manifest_domain,
-        resolve_driver_script_path,
+        resolve_driver_script_path, resolve_mode_driver_script_path, ScrapeMode,
     };
     use crate::login_config::login_account_documents_dir;
     use crate::scrape::js_api::{
         PageInner, PromptOverrides, RefreshmintInner, ScriptOptions, SessionMetadata,
         StagedResource,
     };
-    use crate::scrape::{browser, sandbox};
-    use crate::secret::SecretStore;
+    use crate::scrape::{browser, profile, sandbox};
+    use crate::secret::{SecretCache, SecretStore};
     use std::fs;
     use std::path::PathBuf;
     use std::sync::Arc;
@@ -760,6 +1272,84 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn load_manifest_reads_browser_profile_mode() {
+        let root = create_temp_dir("scrape-browser-profile");
+        fs::write(root.join("manifest.json"), r#"{"name":"demo"}"#)
+            .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        let manifest = load_manifest(&root).unwrap_or_else(|err| panic!("load failed: {err}"));
+        assert_eq!(manifest.browser_profile, profile::ProfileMode::Persistent);
+
+        fs::write(
+            root.join("manifest.json"),
+            r#"{"name":"demo","browserProfile":"ephemeral"}"#,
+        )
+        .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        let manifest = load_manifest(&root).unwrap_or_else(|err| panic!("load failed: {err}"));
+        assert_eq!(manifest.browser_profile, profile::ProfileMode::Ephemeral);
+
+        fs::write(
+            root.join("manifest.json"),
+            r#"{"name":"demo","browserProfile":"shared"}"#,
+        )
+        .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        assert!(load_manifest(&root).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn load_manifest_reads_min_api_version() {
+        let root = create_temp_dir("scrape-min-api-version");
+        fs::write(
+            root.join("manifest.json"),
+            r#"{"name":"demo","minApiVersion":5}"#,
+        )
+        .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        let manifest = load_manifest(&root).unwrap_or_else(|err| panic!("load failed: {err}"));
+        assert_eq!(manifest.min_api_version, Some(5));
+
+        fs::write(
+            root.join("manifest.json"),
+            r#"{"name":"demo","minApiVersion":0}"#,
+        )
+        .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        assert!(load_manifest(&root).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn resolve_mode_driver_script_path_requires_tax_documents_entry() {
+        let root = create_temp_dir("scrape-tax-driver-path");
+        fs::write(
+            root.join("manifest.json"),
+            r#"{"name":"demo","taxDocuments":{"driver":"tax.mjs","expectedForms":["1099-INT"]}}"#,
+        )
+        .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        let manifest = load_manifest(&root).unwrap_or_else(|err| panic!("load failed: {err}"));
+        let tax_mode = ScrapeMode::TaxDocuments { year: 2025 };
+        assert_eq!(
+            resolve_mode_driver_script_path(&root, &manifest, tax_mode),
+            Ok(root.join("tax.mjs"))
+        );
+        assert_eq!(
+            resolve_mode_driver_script_path(&root, &manifest, ScrapeMode::Accounts),
+            Ok(root.join("driver.mjs"))
+        );
+        assert_eq!(
+            tax_mode.script_options().get("taxYear"),
+            Some(&serde_json::json!(2025))
+        );
+
+        fs::write(root.join("manifest.json"), r#"{"name":"demo"}"#)
+            .unwrap_or_else(|err| panic!("failed to write manifest: {err}"));
+        let manifest = load_manifest(&root).unwrap_or_else(|err| panic!("load failed: {err}"));
+        assert!(resolve_mode_driver_script_path(&root, &manifest, tax_mode).is_err());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     fn normalize_manifest_domain_accepts_host_or_url() {
         assert_eq!(normalize_manifest_domain("example.com"), "example.com");
@@ -841,6 +1431,7 @@ mod tests {
             output_dir: root.join("output"),
             prompt_overrides: PromptOverrides::new(),
             prompt_requires_override: false,
+            prompt_catalog: crate::scrape::prompt_catalog::PromptCatalog::new(),
             script_options: ScriptOptions::new(),
             debug_output_sink: None,
             session_metadata: SessionMetadata::default(),
@@ -851,14 +1442,19 @@ mod tests {
                 original_url: Some("https://example.com/export".to_string()),
                 mime_type: Some("application/pdf".to_string()),
                 label: Some("checking".to_string()),
+                document_type: None,
                 metadata: std::collections::BTreeMap::new(),
+                suspect: None,
             }],
+            staged_balances: Vec::new(),
             scrape_session_id: "nested-test".to_string(),
             extension_name: "nested-ext".to_string(),
             account_name: login_name.clone(),
             login_name: login_name.clone(),
             ledger_dir: ledger_dir.clone(),
             prompt_ui_handler: None,
+            assertion_failure: None,
+            prompts_asked: Vec::new(),
         };
 
         let finalized = finalize_staged_resources(&inner).unwrap_or_else(|err| {
@@ -936,6 +1532,7 @@ mod tests {
             output_dir: root.join("output"),
             prompt_overrides: PromptOverrides::new(),
             prompt_requires_override: false,
+            prompt_catalog: crate::scrape::prompt_catalog::PromptCatalog::new(),
             script_options: ScriptOptions::new(),
             debug_output_sink: None,
             session_metadata: SessionMetadata::default(),
@@ -946,14 +1543,19 @@ mod tests {
                 original_url: None,
                 mime_type: Some("application/pdf".to_string()),
                 label: Some("bad/label".to_string()),
+                document_type: None,
                 metadata: std::collections::BTreeMap::new(),
+                suspect: None,
             }],
+            staged_balances: Vec::new(),
             scrape_session_id: "invalid-label-test".to_string(),
             extension_name: "nested-ext".to_string(),
             account_name: "chase-personal".to_string(),
             login_name: "chase-personal".to_string(),
             ledger_dir: ledger_dir.clone(),
             prompt_ui_handler: None,
+            assertion_failure: None,
+            prompts_asked: Vec::new(),
         };
 
         let err = finalize_staged_resources(&inner)
@@ -966,6 +1568,125 @@ mod tests {
         let _ = fs::remove_dir_all(&root);
     }
 
+    #[test]
+    fn finalize_staged_resources_infers_missing_coverage_from_contents() {
+        let root = create_temp_dir("scrape-finalize-infer-coverage");
+        let ledger_dir = root.join("ledger.refreshmint");
+        fs::create_dir_all(&ledger_dir).unwrap_or_else(|err| {
+            panic!("failed to create ledger dir: {err}");
+        });
+        let staged_path = root.join("activity.csv");
+        fs::write(
+            &staged_path,
+            "Date,Description,Amount\n02/14/2026,Coffee,-4.00\n02/01/2026,Payroll,900\n",
+        )
+        .unwrap_or_else(|err| panic!("failed to write staged file: {err}"));
+
+        let inner = RefreshmintInner {
+            output_dir: root.join("output"),
+            prompt_overrides: PromptOverrides::new(),
+            prompt_requires_override: false,
+            prompt_catalog: crate::scrape::prompt_catalog::PromptCatalog::new(),
+            script_options: ScriptOptions::new(),
+            debug_output_sink: None,
+            session_metadata: SessionMetadata::default(),
+            staged_resources: vec![StagedResource {
+                filename: "activity.csv".to_string(),
+                staging_path: staged_path,
+                coverage_end_date: None,
+                original_url: None,
+                mime_type: None,
+                label: Some("checking".to_string()),
+                document_type: None,
+                metadata: std::collections::BTreeMap::new(),
+                suspect: None,
+            }],
+            staged_balances: Vec::new(),
+            scrape_session_id: "infer-coverage-test".to_string(),
+            extension_name: "nested-ext".to_string(),
+            account_name: "chase-personal".to_string(),
+            login_name: "chase-personal".to_string(),
+            ledger_dir: ledger_dir.clone(),
+            prompt_ui_handler: None,
+            assertion_failure: None,
+            prompts_asked: Vec::new(),
+        };
+
+        let finalized = finalize_staged_resources(&inner).unwrap_or_else(|err| {
+            panic!("finalize_staged_resources failed: {err}");
+        });
+        assert_eq!(finalized, vec!["2026-02-14-activity.csv"]);
+
+        let documents_dir = login_account_documents_dir(&ledger_dir, "chase-personal", "checking");
+        let sidecar = fs::read_to_string(documents_dir.join("2026-02-14-activity.csv-info.json"))
+            .unwrap_or_else(|err| panic!("failed to read sidecar file: {err}"));
+        assert!(sidecar.contains("\"coverageEndDate\": \"2026-02-14\""));
+        assert!(sidecar.contains("\"dateRangeStart\": \"2026-02-01\""));
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
+    #[test]
+    fn finalize_staged_resources_keeps_known_account_ids_under_their_label() {
+        let root = create_temp_dir("scrape-finalize-account-ids");
+        let ledger_dir = root.join("ledger.refreshmint");
+        fs::create_dir_all(&ledger_dir).unwrap_or_else(|err| {
+            panic!("failed to create ledger dir: {err}");
+        });
+        let resource = |filename: &str, label: &str| {
+            let staging_path = root.join(filename);
+            fs::write(&staging_path, "Date,Amount\n2026-02-14,-4.00\n")
+                .unwrap_or_else(|err| panic!("failed to write staged file: {err}"));
+            StagedResource {
+                filename: filename.to_string(),
+                staging_path,
+                coverage_end_date: Some("2026-02-14".to_string()),
+                original_url: None,
+                mime_type: None,
+                label: Some(label.to_string()),
+                document_type: None,
+                metadata: std::collections::BTreeMap::from([(
+                    "accountId".to_string(),
+                    serde_json::json!("****1234"),
+                )]),
+                suspect: None,
+            }
+        };
+        let inner = |resources: Vec<StagedResource>| RefreshmintInner {
+            output_dir: root.join("output"),
+            prompt_overrides: PromptOverrides::new(),
+            prompt_requires_override: false,
+            prompt_catalog: crate::scrape::prompt_catalog::PromptCatalog::new(),
+            script_options: ScriptOptions::new(),
+            debug_output_sink: None,
+            session_metadata: SessionMetadata::default(),
+            staged_resources: resources,
+            staged_balances: Vec::new(),
+            scrape_session_id: "account-ids-test".to_string(),
+            extension_name: "nested-ext".to_string(),
+            account_name: "chase-personal".to_string(),
+            login_name: "chase-personal".to_string(),
+            ledger_dir: ledger_dir.clone(),
+            prompt_ui_handler: None,
+            assertion_failure: None,
+            prompts_asked: Vec::new(),
+        };
+
+        finalize_staged_resources(&inner(vec![resource("jan.csv", "checking")]))
+            .unwrap_or_else(|err| panic!("finalize_staged_resources failed: {err}"));
+        // The bank renamed the account; its number still names the label.
+        finalize_staged_resources(&inner(vec![resource("feb.csv", "everyday-checking")]))
+            .unwrap_or_else(|err| panic!("finalize_staged_resources failed: {err}"));
+
+        let config = crate::login_config::read_login_config(&ledger_dir, "chase-personal");
+        assert_eq!(config.accounts.keys().collect::<Vec<_>>(), vec!["checking"]);
+        assert_eq!(config.accounts["checking"].account_ids, vec!["****1234"]);
+        let documents_dir = login_account_documents_dir(&ledger_dir, "chase-personal", "checking");
+        assert!(documents_dir.join("2026-02-14-feb.csv").exists());
+
+        let _ = fs::remove_dir_all(&root);
+    }
+
     #[test]
     #[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
     fn network_waiter_rejects_when_browser_disconnects() {
@@ -1006,28 +1727,39 @@ mod tests {
                 target_id: page.target_id().as_ref().to_string(),
                 page,
                 browser: browser.clone(),
-                secret_store: Arc::new(SecretStore::new(
+                secrets: Arc::new(SecretCache::new(SecretStore::new(
                     "login/test-browser-disconnect".to_string(),
-                )),
+                ))),
                 declared_secrets: Arc::new(crate::scrape::js_api::SecretDeclarations::new()),
+                download_root: download_dir.clone(),
                 download_dir,
                 target_frame_id: None,
+                network_policy: Arc::new(crate::scrape::network_policy::NetworkPolicy::default()),
+                strict_selectors: true,
+                handlers: crate::scrape::js_api::PageHandlers::default(),
+                closed: false,
+                login_wall: Arc::new(crate::scrape::login_wall::LoginWall::default()),
+                input: Default::default(),
             }));
 
             let refreshmint_inner = Arc::new(Mutex::new(RefreshmintInner {
                 output_dir,
                 prompt_overrides: PromptOverrides::new(),
                 prompt_requires_override: false,
+                prompt_catalog: crate::scrape::prompt_catalog::PromptCatalog::new(),
                 script_options: ScriptOptions::new(),
                 debug_output_sink: None,
                 session_metadata: SessionMetadata::default(),
                 staged_resources: Vec::new(),
+                staged_balances: Vec::new(),
                 scrape_session_id: "browser-disconnect-test".to_string(),
                 extension_name: "smoke".to_string(),
                 account_name: "smoke-account".to_string(),
                 login_name: "smoke-account".to_string(),
                 ledger_dir: root.join("ledger.refreshmint"),
                 prompt_ui_handler: None,
+                assertion_failure: None,
+                prompts_asked: Vec::new(),
             }));
 
             let browser_for_close = browser.clone();
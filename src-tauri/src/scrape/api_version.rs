@@ -0,0 +1,259 @@
+//! Version of the JS API drivers are written against.
+//!
+//! [`JS_API_VERSION`] goes up by one whenever drivers gain or change an API.
+//! A manifest's `minApiVersion` names the version the extension was written
+//! and tested against: an older app refuses to run it, and a newer app warns
+//! about each breaking change made since.
+
+use serde::Serialize;
+
+/// The JS API version this build provides; `refreshmint.apiVersion` in
+/// drivers.
+pub const JS_API_VERSION: u32 = 30;
+
+/// What changed for drivers in one API version.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "JsApiChange")]
+pub struct ApiChange {
+    pub version: u32,
+    /// Whether drivers written for an earlier version may behave
+    /// differently.
+    pub breaking: bool,
+    pub summary: &'static str,
+}
+
+/// Oldest first; the last entry is [`JS_API_VERSION`].
+pub const API_CHANGES: &[ApiChange] = &[
+    ApiChange {
+        version: 1,
+        breaking: false,
+        summary: "page, browser, and refreshmint globals with saveResource, prompt, and getOptions",
+    },
+    ApiChange {
+        version: 2,
+        breaking: false,
+        summary: "tax-documents mode in getOptions and manifest taxDocuments; reportBalance",
+    },
+    ApiChange {
+        version: 3,
+        breaking: false,
+        summary: "page.extractTable and refreshmint.extractJson",
+    },
+    ApiChange {
+        version: 4,
+        breaking: false,
+        summary: "refreshmint.flows sign-in helpers and refreshmint.storage",
+    },
+    ApiChange {
+        version: 5,
+        breaking: false,
+        summary: "refreshmint.assert/expect and manifest allowedDomains",
+    },
+    ApiChange {
+        version: 6,
+        breaking: false,
+        summary: "manifest prompts catalog; refreshmint.prompt accepts catalog keys",
+    },
+    ApiChange {
+        version: 7,
+        breaking: true,
+        summary: "click, fill, and type wait for the element to be actionable; locators fail on ambiguous selectors",
+    },
+    ApiChange {
+        version: 8,
+        breaking: false,
+        summary: "text=, regex, and xpath= selector engines; getByText",
+    },
+    ApiChange {
+        version: 9,
+        breaking: false,
+        summary: "page.frame(), page.close(), and page.isClosed(); new tabs get downloads and handlers",
+    },
+    ApiChange {
+        version: 10,
+        breaking: false,
+        summary: "manifest browserProfile and loginWallUrls; refreshmint.setLoginHandler",
+    },
+    ApiChange {
+        version: 11,
+        breaking: false,
+        summary: "refreshmint.apiVersion and manifest minApiVersion",
+    },
+    ApiChange {
+        version: 12,
+        breaking: false,
+        summary: "page.route, page.unroute, and page.unrouteAll request interception",
+    },
+    ApiChange {
+        version: 13,
+        breaking: false,
+        summary: "page.localStorage, page.sessionStorage, and their set and clear helpers",
+    },
+    ApiChange {
+        version: 14,
+        breaking: false,
+        summary: "page.keyboard with press, down, up, type, and insertText",
+    },
+    ApiChange {
+        version: 15,
+        breaking: false,
+        summary: "refreshmint.tryExtract runs the extractor on a staged resource",
+    },
+    ApiChange {
+        version: 16,
+        breaking: false,
+        summary: "page.mouse, page.dragAndDrop, and locator.boundingBox",
+    },
+    ApiChange {
+        version: 17,
+        breaking: false,
+        summary: "locator.hover, locator.focus, and locator.blur",
+    },
+    ApiChange {
+        version: 18,
+        breaking: false,
+        summary: "page.selectOption and locator.selectOption",
+    },
+    ApiChange {
+        version: 19,
+        breaking: false,
+        summary: "locator.check and locator.uncheck",
+    },
+    ApiChange {
+        version: 20,
+        breaking: false,
+        summary: "getByLabel and label= selectors",
+    },
+    ApiChange {
+        version: 21,
+        breaking: false,
+        summary: "locator.filter",
+    },
+    ApiChange {
+        version: 22,
+        breaking: false,
+        summary: "refreshmint.saveTransactionDetails",
+    },
+    ApiChange {
+        version: 23,
+        breaking: false,
+        summary: "page.frameLocator",
+    },
+    ApiChange {
+        version: 24,
+        breaking: false,
+        summary: ">> selector chains that pierce shadow roots",
+    },
+    ApiChange {
+        version: 25,
+        breaking: false,
+        summary: "location fields in saveTransactionDetails",
+    },
+    ApiChange {
+        version: 26,
+        breaking: false,
+        summary: "selector engines in $, $$, frameFill, and extractTable",
+    },
+    ApiChange {
+        version: 27,
+        breaking: false,
+        summary: ":has-text() in CSS selectors",
+    },
+    ApiChange {
+        version: 28,
+        breaking: false,
+        summary: "page.waitForFunction",
+    },
+    ApiChange {
+        version: 29,
+        breaking: false,
+        summary: "global expect and toHaveValue",
+    },
+    ApiChange {
+        version: 30,
+        breaking: true,
+        summary: "page-level selector methods fail on ambiguous selectors unless setStrictSelectors(false)",
+    },
+];
+
+/// Returned by `get_js_api_version`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct JsApiVersionInfo {
+    pub version: u32,
+    /// Newest first.
+    pub changes: Vec<ApiChange>,
+}
+
+pub fn version_info() -> JsApiVersionInfo {
+    JsApiVersionInfo {
+        version: JS_API_VERSION,
+        changes: API_CHANGES.iter().rev().cloned().collect(),
+    }
+}
+
+/// Check an extension's `minApiVersion` against this build. Fails when the
+/// extension needs a newer app; otherwise returns one warning per breaking
+/// change since the declared version. Extensions that declare no version
+/// are not checked.
+pub fn check_compatibility(
+    extension_name: &str,
+    min_api_version: Option<u32>,
+) -> Result<Vec<String>, String> {
+    let Some(required) = min_api_version else {
+        return Ok(Vec::new());
+    };
+    if required > JS_API_VERSION {
+        return Err(format!(
+            "extension '{extension_name}' requires JS API version {required}, but this Refreshmint \
+             provides version {JS_API_VERSION}; update Refreshmint to run it"
+        ));
+    }
+    Ok(API_CHANGES
+        .iter()
+        .filter(|change| change.breaking && change.version > required)
+        .map(|change| {
+            format!(
+                "extension '{extension_name}' was written for JS API version {required}; \
+                 version {} changed: {}",
+                change.version, change.summary
+            )
+        })
+        .collect())
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn changes_are_consecutive_and_end_at_current_version() {
+        for (index, change) in API_CHANGES.iter().enumerate() {
+            assert_eq!(change.version as usize, index + 1);
+        }
+        assert_eq!(API_CHANGES.last().map(|c| c.version), Some(JS_API_VERSION));
+        assert_eq!(version_info().changes[0].version, JS_API_VERSION);
+    }
+
+    #[test]
+    fn check_compatibility_refuses_newer_and_warns_about_breaking_changes() {
+        assert_eq!(
+            check_compatibility("bank", None).unwrap(),
+            Vec::<String>::new()
+        );
+        assert!(check_compatibility("bank", Some(JS_API_VERSION))
+            .unwrap()
+            .is_empty());
+
+        let err = check_compatibility("bank", Some(JS_API_VERSION + 1)).unwrap_err();
+        assert!(err.contains("update Refreshmint"), "{err}");
+
+        let warnings = check_compatibility("bank", Some(6)).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].contains("version 7 changed"), "{}", warnings[0]);
+        assert!(check_compatibility("bank", Some(7)).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,471 @@
+//! Scrape-time assertions: `refreshmint.assert(condition, message)` and
+//! `refreshmint.expect(locator)`, which is also the global `expect`.
+//!
+//! A failed assertion captures the page URL, a screenshot, and an excerpt of
+//! `page.snapshot()` before throwing, and keeps them on the session so the
+//! scrape log entry for the run carries them as `failure`. Screenshots go to
+//! `cache/diagnostics/<login>/`, which is never committed.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rquickjs::convert::Coerced;
+use rquickjs::{class::Trace, function::Opt, Class, JsLifetime, Result as JsResult, Value};
+use tokio::sync::Mutex;
+
+use super::js_api::{
+    js_err, run_screenshot_capture, PageApi, PageInner, ParsedScreenshotOptions, RefreshmintInner,
+};
+use super::locator::Locator;
+
+/// How long `expect(...)` matchers retry before failing.
+pub const DEFAULT_EXPECT_TIMEOUT_MS: u64 = 5_000;
+const POLL_INTERVAL_MS: u64 = 100;
+/// Longest snapshot excerpt kept in a failure, in characters.
+const SNAPSHOT_EXCERPT_CHARS: usize = 2_000;
+
+/// Context captured when a driver assertion fails.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssertionFailure {
+    pub message: String,
+    /// Locator selector the assertion was about, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expected: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub actual: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Ledger-relative path of the screenshot taken at failure.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub screenshot: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub snapshot_excerpt: Option<String>,
+}
+
+/// Error for a scrape whose driver failed an assertion; callers downcast to
+/// it to record the captured context.
+#[derive(Debug)]
+pub struct AssertionError(pub AssertionFailure);
+
+impl std::fmt::Display for AssertionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let failure = &self.0;
+        write!(f, "assertion failed: {}", failure.message)?;
+        if let Some(target) = &failure.target {
+            write!(f, " [{target}]")?;
+        }
+        match (&failure.expected, &failure.actual) {
+            (Some(expected), Some(actual)) => write!(f, "; expected {expected}, got {actual}")?,
+            (Some(expected), None) => write!(f, "; expected {expected}")?,
+            _ => {}
+        }
+        if let Some(url) = &failure.url {
+            write!(f, " (at {url})")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for AssertionError {}
+
+/// The start of `snapshot`, cut at a character boundary.
+fn snapshot_excerpt(snapshot: &str) -> String {
+    match snapshot.char_indices().nth(SNAPSHOT_EXCERPT_CHARS) {
+        Some((end, _)) => format!("{}…", &snapshot[..end]),
+        None => snapshot.to_string(),
+    }
+}
+
+/// Collapse runs of whitespace, as `toHaveText` compares visible text.
+fn normalize_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Expected text for `toHaveText` and `toHaveValue`: a string (the whole
+/// text, after whitespace normalization for `toHaveText`) or a RegExp.
+#[derive(Debug)]
+enum TextMatcher {
+    Exact(String),
+    Normalized(String),
+    Pattern {
+        regex: regex::Regex,
+        display: String,
+    },
+}
+
+impl TextMatcher {
+    fn from_js(value: &Value<'_>, method: &str, normalize: bool) -> JsResult<Self> {
+        if let Some(text) = value.as_string() {
+            let text = text.to_string()?;
+            return Ok(if normalize {
+                Self::Normalized(normalize_whitespace(&text))
+            } else {
+                Self::Exact(text)
+            });
+        }
+        if let Some(object) = value.as_object() {
+            let source: Option<String> = object.get("source")?;
+            let flags: Option<String> = object.get("flags")?;
+            if let Some(source) = source {
+                return Self::pattern(&source, flags.as_deref().unwrap_or_default())
+                    .map_err(|err| js_err(format!("{method}: {err}")));
+            }
+        }
+        Err(js_err(format!(
+            "{method}: expected text must be a string or RegExp"
+        )))
+    }
+
+    /// Translate a JS RegExp; `i`, `m`, and `s` carry over and the
+    /// stateful/unicode flags do not change matching here.
+    fn pattern(source: &str, flags: &str) -> Result<Self, String> {
+        let inline: String = flags.chars().filter(|flag| "ims".contains(*flag)).collect();
+        let pattern = if inline.is_empty() {
+            source.to_string()
+        } else {
+            format!("(?{inline}){source}")
+        };
+        let regex = regex::Regex::new(&pattern)
+            .map_err(|err| format!("unsupported RegExp /{source}/{flags}: {err}"))?;
+        Ok(Self::Pattern {
+            regex,
+            display: format!("/{source}/{flags}"),
+        })
+    }
+
+    fn matches(&self, text: &str) -> bool {
+        match self {
+            Self::Exact(expected) => text == expected,
+            Self::Normalized(expected) => normalize_whitespace(text) == *expected,
+            Self::Pattern { regex, .. } => regex.is_match(text),
+        }
+    }
+
+    /// `noun` names what is compared, such as `text` or `value`.
+    fn describe(&self, noun: &str) -> String {
+        match self {
+            Self::Exact(expected) | Self::Normalized(expected) => format!("{noun} {expected:?}"),
+            Self::Pattern { display, .. } => format!("{noun} matching {display}"),
+        }
+    }
+}
+
+fn parse_expect_timeout(options: Option<&Value<'_>>) -> JsResult<u64> {
+    let Some(object) = options.and_then(Value::as_object) else {
+        return Ok(DEFAULT_EXPECT_TIMEOUT_MS);
+    };
+    Ok(object
+        .get::<_, Option<u64>>("timeout")?
+        .unwrap_or(DEFAULT_EXPECT_TIMEOUT_MS))
+}
+
+/// Shared state for recording a failure.
+#[derive(Clone)]
+struct FailureRecorder {
+    page: PageApi,
+    page_inner: Arc<Mutex<PageInner>>,
+    refreshmint: Arc<Mutex<RefreshmintInner>>,
+}
+
+impl FailureRecorder {
+    /// Capture context for `failure`, keep it on the session, and return the
+    /// error to throw.
+    async fn fail(&self, mut failure: AssertionFailure) -> rquickjs::Error {
+        failure.url = self.page.url().await.ok();
+        failure.snapshot_excerpt = self
+            .page
+            .snapshot(Opt(None))
+            .await
+            .ok()
+            .map(|snapshot| snapshot_excerpt(&snapshot));
+        failure.screenshot = self.capture_screenshot().await;
+
+        let mut inner = self.refreshmint.lock().await;
+        inner.assertion_failure = Some(failure.clone());
+        js_err(AssertionError(failure).to_string())
+    }
+
+    async fn capture_screenshot(&self) -> Option<String> {
+        let (ledger_dir, login_name, session_id) = {
+            let inner = self.refreshmint.lock().await;
+            (
+                inner.ledger_dir.clone(),
+                inner.login_name.clone(),
+                inner.scrape_session_id.clone(),
+            )
+        };
+        let relative = crate::scrape_artifacts::login_diagnostics_dir(&login_name).join(format!(
+            "{session_id}-{}.png",
+            chrono::Utc::now().format("%H%M%S%3f")
+        ));
+        let result = run_screenshot_capture(
+            self.page_inner.clone(),
+            &ParsedScreenshotOptions::default(),
+            None,
+            &[],
+            Some(ledger_dir.join(&relative)),
+        )
+        .await;
+        match result {
+            Ok(_) => Some(relative.to_string_lossy().replace('\\', "/")),
+            Err(err) => {
+                tracing::warn!("assertion screenshot failed: {err}");
+                None
+            }
+        }
+    }
+}
+
+/// Backs `refreshmint.assert` and `refreshmint.expect`, which
+/// `register_globals` binds onto the `refreshmint` object.
+#[rquickjs::class(rename = "Assertions")]
+#[derive(Trace)]
+pub struct AssertionsApi {
+    #[qjs(skip_trace)]
+    recorder: FailureRecorder,
+}
+
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for AssertionsApi {
+    type Changed<'to> = AssertionsApi;
+}
+
+impl AssertionsApi {
+    pub fn new(
+        page: PageApi,
+        page_inner: Arc<Mutex<PageInner>>,
+        refreshmint: Arc<Mutex<RefreshmintInner>>,
+    ) -> Self {
+        Self {
+            recorder: FailureRecorder {
+                page,
+                page_inner,
+                refreshmint,
+            },
+        }
+    }
+}
+
+#[rquickjs::methods]
+impl AssertionsApi {
+    /// Throw with captured page context unless `condition` is truthy.
+    pub async fn assert(&self, condition: Coerced<bool>, message: Opt<String>) -> JsResult<()> {
+        if condition.0 {
+            return Ok(());
+        }
+        let message = message
+            .0
+            .unwrap_or_else(|| "condition was falsy".to_string());
+        Err(self
+            .recorder
+            .fail(AssertionFailure {
+                message,
+                target: None,
+                expected: None,
+                actual: None,
+                url: None,
+                screenshot: None,
+                snapshot_excerpt: None,
+            })
+            .await)
+    }
+
+    /// Start an expectation about a locator or CSS selector.
+    pub fn expect<'js>(&self, target: Value<'js>) -> JsResult<Expectation> {
+        let locator = if let Some(selector) = target.as_string() {
+            let selector = selector.to_string()?;
+            Locator::new(self.recorder.page_inner.clone(), selector)
+        } else if let Ok(locator) = Class::<Locator>::from_value(&target) {
+            locator.borrow().clone()
+        } else {
+            return Err(js_err(
+                "refreshmint.expect: target must be a Locator or selector string".to_string(),
+            ));
+        };
+        Ok(Expectation {
+            recorder: self.recorder.clone(),
+            locator,
+            negated: false,
+        })
+    }
+}
+
+/// JS-visible result of `refreshmint.expect(...)`.
+#[rquickjs::class(rename = "Expectation")]
+#[derive(Trace)]
+pub struct Expectation {
+    #[qjs(skip_trace)]
+    recorder: FailureRecorder,
+    #[qjs(skip_trace)]
+    locator: Locator,
+    #[qjs(skip_trace)]
+    negated: bool,
+}
+
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for Expectation {
+    type Changed<'to> = Expectation;
+}
+
+impl Expectation {
+    /// Poll `check` until it reports `!negated` or the timeout passes, then
+    /// fail with the last observed value.
+    async fn poll<F, Fut>(&self, timeout_ms: u64, expected: String, mut check: F) -> JsResult<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = (bool, String)>,
+    {
+        let deadline = tokio::time::Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            let (matched, actual) = check().await;
+            if matched != self.negated {
+                return Ok(());
+            }
+            if tokio::time::Instant::now() >= deadline {
+                let expected = if self.negated {
+                    format!("not {expected}")
+                } else {
+                    expected
+                };
+                return Err(self
+                    .recorder
+                    .fail(AssertionFailure {
+                        message: format!("expect(locator) timed out after {timeout_ms}ms"),
+                        target: Some(self.locator.selector()),
+                        expected: Some(expected),
+                        actual: Some(actual),
+                        url: None,
+                        screenshot: None,
+                        snapshot_excerpt: None,
+                    })
+                    .await);
+            }
+            tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+}
+
+#[rquickjs::methods]
+impl Expectation {
+    /// The same expectation with every matcher inverted.
+    #[qjs(get, rename = "not")]
+    pub fn negated(&self) -> Expectation {
+        Expectation {
+            recorder: self.recorder.clone(),
+            locator: self.locator.clone(),
+            negated: !self.negated,
+        }
+    }
+
+    #[qjs(rename = "toBeVisible")]
+    pub async fn to_be_visible(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        let timeout_ms = parse_expect_timeout(options.0.as_ref())?;
+        self.poll(timeout_ms, "visible".to_string(), || async {
+            match self.locator.is_visible().await {
+                Ok(true) => (true, "visible".to_string()),
+                Ok(false) => (false, "hidden or missing".to_string()),
+                Err(err) => (false, err.to_string()),
+            }
+        })
+        .await
+    }
+
+    #[qjs(rename = "toHaveText")]
+    pub async fn to_have_text<'js>(
+        &self,
+        expected: Value<'js>,
+        options: Opt<Value<'js>>,
+    ) -> JsResult<()> {
+        let matcher = TextMatcher::from_js(&expected, "toHaveText", true)?;
+        let timeout_ms = parse_expect_timeout(options.0.as_ref())?;
+        self.poll(timeout_ms, matcher.describe("text"), || async {
+            match self.locator.get_property("innerText", 0).await {
+                Ok(text) => (
+                    matcher.matches(&text),
+                    format!("{:?}", normalize_whitespace(&text)),
+                ),
+                Err(err) => (false, err.to_string()),
+            }
+        })
+        .await
+    }
+
+    /// Wait for an input, select, or textarea to hold `expected`.
+    #[qjs(rename = "toHaveValue")]
+    pub async fn to_have_value<'js>(
+        &self,
+        expected: Value<'js>,
+        options: Opt<Value<'js>>,
+    ) -> JsResult<()> {
+        let matcher = TextMatcher::from_js(&expected, "toHaveValue", false)?;
+        let timeout_ms = parse_expect_timeout(options.0.as_ref())?;
+        self.poll(timeout_ms, matcher.describe("value"), || async {
+            match self.locator.get_property("value", 0).await {
+                Ok(value) => (matcher.matches(&value), format!("{value:?}")),
+                Err(err) => (false, err.to_string()),
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn failure_message_includes_expectation_and_url() {
+        let failure = AssertionFailure {
+            message: "expect(locator) timed out after 5000ms".to_string(),
+            target: Some("#balance".to_string()),
+            expected: Some("visible".to_string()),
+            actual: Some("hidden or missing".to_string()),
+            url: Some("https://bank.example/accounts".to_string()),
+            screenshot: Some("cache/diagnostics/bank/s-1.png".to_string()),
+            snapshot_excerpt: None,
+        };
+        assert_eq!(
+            AssertionError(failure.clone()).to_string(),
+            "assertion failed: expect(locator) timed out after 5000ms [#balance]; \
+             expected visible, got hidden or missing (at https://bank.example/accounts)"
+        );
+        let json = serde_json::to_value(&failure).unwrap();
+        assert_eq!(json["snapshotExcerpt"], serde_json::Value::Null);
+        assert!(json.get("snapshotExcerpt").is_none());
+        assert_eq!(
+            serde_json::from_value::<AssertionFailure>(json).unwrap(),
+            failure
+        );
+    }
+
+    #[test]
+    fn text_matchers_normalize_whitespace_and_translate_flags() {
+        let normalized = TextMatcher::Normalized(normalize_whitespace("  Total\n balance "));
+        assert!(normalized.matches("Total balance"));
+        assert!(!normalized.matches("Total balance due"));
+        let exact = TextMatcher::Exact("1,234.50".to_string());
+        assert!(exact.matches("1,234.50"));
+        assert!(!exact.matches(" 1,234.50"));
+        assert_eq!(exact.describe("value"), "value \"1,234.50\"");
+
+        let pattern = TextMatcher::pattern(r"^\$[\d,]+\.\d{2}$", "").unwrap();
+        assert!(pattern.matches("$1,234.56"));
+        let insensitive = TextMatcher::pattern("welcome", "gi").unwrap();
+        assert!(insensitive.matches("WELCOME back"));
+        assert_eq!(insensitive.describe("text"), "text matching /welcome/gi");
+        assert!(TextMatcher::pattern("(?<=a)b", "").is_err());
+    }
+
+    #[test]
+    fn snapshot_excerpt_truncates_on_char_boundary() {
+        let long = "é".repeat(SNAPSHOT_EXCERPT_CHARS + 5);
+        let excerpt = snapshot_excerpt(&long);
+        assert_eq!(excerpt.chars().count(), SNAPSHOT_EXCERPT_CHARS + 1);
+        assert!(excerpt.ends_with('…'));
+        assert_eq!(snapshot_excerpt("[]"), "[]");
+    }
+}
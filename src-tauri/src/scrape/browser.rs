@@ -14,14 +14,11 @@ pub fn find_chrome_binary() -> Result<PathBuf, Box<dyn Error>> {
         if let Some(path) = std::env::var_os(env_name) {
             let candidate = PathBuf::from(path);
             if candidate.exists() {
-                eprintln!(
-                    "[browser] Using browser from ${env_name}: {}",
-                    candidate.display()
-                );
+                tracing::info!("Using browser from ${env_name}: {}", candidate.display());
                 return Ok(candidate);
             }
-            eprintln!(
-                "[browser] Ignoring browser path from ${env_name} because it does not exist: {}",
+            tracing::warn!(
+                "Ignoring browser path from ${env_name} because it does not exist: {}",
                 candidate.display()
             );
         }
@@ -29,43 +26,43 @@ pub fn find_chrome_binary() -> Result<PathBuf, Box<dyn Error>> {
 
     // Prefer PATH before hard-coded locations so workflow-provided shims win.
     if let Ok(path) = which::which("google-chrome") {
-        eprintln!(
-            "[browser] Using browser from PATH lookup google-chrome: {}",
+        tracing::info!(
+            "Using browser from PATH lookup google-chrome: {}",
             path.display()
         );
         return Ok(path);
     }
     if let Ok(path) = which::which("google-chrome-stable") {
-        eprintln!(
-            "[browser] Using browser from PATH lookup google-chrome-stable: {}",
+        tracing::info!(
+            "Using browser from PATH lookup google-chrome-stable: {}",
             path.display()
         );
         return Ok(path);
     }
     if let Ok(path) = which::which("google-chrome-beta") {
-        eprintln!(
-            "[browser] Using browser from PATH lookup google-chrome-beta: {}",
+        tracing::info!(
+            "Using browser from PATH lookup google-chrome-beta: {}",
             path.display()
         );
         return Ok(path);
     }
     if let Ok(path) = which::which("chromium") {
-        eprintln!(
-            "[browser] Using browser from PATH lookup chromium: {}",
+        tracing::info!(
+            "Using browser from PATH lookup chromium: {}",
             path.display()
         );
         return Ok(path);
     }
     if let Ok(path) = which::which("chromium-browser") {
-        eprintln!(
-            "[browser] Using browser from PATH lookup chromium-browser: {}",
+        tracing::info!(
+            "Using browser from PATH lookup chromium-browser: {}",
             path.display()
         );
         return Ok(path);
     }
     if let Ok(path) = which::which("microsoft-edge") {
-        eprintln!(
-            "[browser] Using browser from PATH lookup microsoft-edge: {}",
+        tracing::info!(
+            "Using browser from PATH lookup microsoft-edge: {}",
             path.display()
         );
         return Ok(path);
@@ -74,8 +71,8 @@ pub fn find_chrome_binary() -> Result<PathBuf, Box<dyn Error>> {
     // Fallback to well-known installation paths.
     for candidate in chrome_candidates() {
         if candidate.exists() {
-            eprintln!(
-                "[browser] Using browser from well-known path: {}",
+            tracing::info!(
+                "Using browser from well-known path: {}",
                 candidate.display()
             );
             return Ok(candidate);
@@ -140,20 +137,20 @@ pub async fn launch_browser(
     let force_headless = headless || std::env::var_os("REFRESHMINT_BROWSER_HEADLESS").is_some();
     let is_linux_ci = cfg!(target_os = "linux") && std::env::var_os("CI").is_some();
     let use_headless = force_headless || is_linux_ci;
-    eprintln!(
-        "[browser] Launch config: chrome={}, profile={}, linux_ci={is_linux_ci}, force_headless={force_headless}",
+    tracing::info!(
+        "Launch config: chrome={}, profile={}, linux_ci={is_linux_ci}, force_headless={force_headless}",
         chrome_path.display(),
         profile_dir.display()
     );
     if use_headless {
-        eprintln!("[browser] Launch mode: headless=old");
+        tracing::info!("Launch mode: headless=old");
         builder = builder.headless_mode(HeadlessMode::True);
         if cfg!(target_os = "linux") {
-            eprintln!("[browser] Launch flags: --no-sandbox --disable-dev-shm-usage");
+            tracing::debug!("Launch flags: --no-sandbox --disable-dev-shm-usage");
             builder = builder.no_sandbox().arg("--disable-dev-shm-usage");
         }
     } else {
-        eprintln!("[browser] Launch mode: headed");
+        tracing::info!("Launch mode: headed");
         builder = builder.with_head();
     }
 
@@ -164,7 +161,7 @@ pub async fn launch_browser(
     let (browser, mut handler) = Browser::launch(config).await?;
 
     let handle = tokio::spawn(async move {
-        eprintln!("[browser] Handler loop starting...");
+        tracing::debug!("Handler loop starting...");
         while let Some(result) = handler.next().await {
             if let Err(err) = result {
                 match &err {
@@ -175,18 +172,18 @@ pub async fn launch_browser(
                     | CdpError::LaunchExit(_, _)
                     | CdpError::LaunchTimeout(_)
                     | CdpError::LaunchIo(_, _) => {
-                        eprintln!("[browser] Fatal handler error: {err}");
+                        tracing::error!("Fatal handler error: {err}");
                         return;
                     }
                     // Non-fatal: a single malformed/unexpected CDP message.
                     // Log and keep processing so the session stays alive.
                     _ => {
-                        eprintln!("[browser] Non-fatal handler error (continuing): {err}");
+                        tracing::warn!("Non-fatal handler error (continuing): {err}");
                     }
                 }
             }
         }
-        eprintln!("[browser] Handler loop ended.");
+        tracing::debug!("Handler loop ended.");
     });
 
     Ok((browser, handle))
@@ -201,23 +198,23 @@ pub async fn open_start_page(
 ) -> Result<chromiumoxide::Page, Box<dyn Error + Send + Sync>> {
     let create_timeout = std::time::Duration::from_secs(30);
     for attempt in 1..=2 {
-        eprintln!("[browser] Creating initial about:blank page (attempt {attempt}/2)");
+        tracing::debug!("Creating initial about:blank page (attempt {attempt}/2)");
         match tokio::time::timeout(create_timeout, browser.new_page("about:blank")).await {
             Ok(Ok(page)) => {
-                eprintln!("[browser] Created initial about:blank page on attempt {attempt}");
+                tracing::debug!("Created initial about:blank page on attempt {attempt}");
                 return Ok(page);
             }
             Ok(Err(err)) => {
-                eprintln!(
-                    "[browser] Failed to create initial about:blank page on attempt {attempt}: {err}"
+                tracing::warn!(
+                    "Failed to create initial about:blank page on attempt {attempt}: {err}"
                 );
                 if attempt == 2 {
                     return Err(format!("failed to create initial page: {err}").into());
                 }
             }
             Err(_) => {
-                eprintln!(
-                    "[browser] Timed out creating about:blank after {}s on attempt {attempt}",
+                tracing::warn!(
+                    "Timed out creating about:blank after {}s on attempt {attempt}",
                     create_timeout.as_secs()
                 );
                 if attempt == 2 {
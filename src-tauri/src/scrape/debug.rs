@@ -18,11 +18,7 @@ pub fn default_debug_socket_path(login_name: &str) -> Result<PathBuf, Box<dyn Er
         use std::os::unix::ffi::OsStrExt;
 
         let account_sanitized = sanitize_segment(login_name);
-        let preferred_base = dirs::cache_dir()
-            .unwrap_or_else(std::env::temp_dir)
-            .join("refreshmint")
-            .join("debug");
-        let preferred = preferred_base.join(format!(
+        let preferred = debug_socket_dir().join(format!(
             "rm-{}-{}.sock",
             std::process::id(),
             account_sanitized
@@ -48,6 +44,83 @@ pub fn default_debug_socket_path(login_name: &str) -> Result<PathBuf, Box<dyn Er
     }
 }
 
+#[cfg(unix)]
+fn debug_socket_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(std::env::temp_dir)
+        .join("refreshmint")
+        .join("debug")
+}
+
+/// Find the socket of a running debug session for `login_name` started with
+/// the default socket path. When several exist, the newest one that accepts
+/// a connection wins.
+pub fn find_debug_socket(login_name: &str) -> Result<PathBuf, Box<dyn Error>> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::UnixStream;
+
+        let account_sanitized = sanitize_segment(login_name);
+        let mut candidates = Vec::new();
+        for dir in [debug_socket_dir(), std::env::temp_dir()] {
+            let Ok(entries) = std::fs::read_dir(&dir) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                if !socket_name_matches_login(&name, &account_sanitized) {
+                    continue;
+                }
+                let Ok(metadata) = entry.metadata() else {
+                    continue;
+                };
+                if metadata.file_type().is_socket() {
+                    let modified = metadata.modified().unwrap_or(std::time::UNIX_EPOCH);
+                    candidates.push((modified, entry.path()));
+                }
+            }
+        }
+        candidates.sort();
+        candidates
+            .into_iter()
+            .rev()
+            .map(|(_, path)| path)
+            .find(|path| UnixStream::connect(path).is_ok())
+            .ok_or_else(|| {
+                format!(
+                    "no running debug session found for login '{login_name}'; \
+                     start one with `debug start` or pass --socket"
+                )
+                .into()
+            })
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = login_name;
+        Err("debug sockets are currently supported only on unix platforms".into())
+    }
+}
+
+/// Whether `name` is a default socket name (`rm-<pid>-<login>.sock` or the
+/// `rm-debug-<pid>-<login>.sock` fallback) for the sanitized login.
+#[cfg(any(unix, test))]
+fn socket_name_matches_login(name: &str, account_sanitized: &str) -> bool {
+    let Some(rest) = name
+        .strip_prefix("rm-debug-")
+        .or_else(|| name.strip_prefix("rm-"))
+        .and_then(|rest| rest.strip_suffix(".sock"))
+    else {
+        return false;
+    };
+    rest.split_once('-').is_some_and(|(pid, login)| {
+        !pid.is_empty()
+            && pid.bytes().all(|byte| byte.is_ascii_digit())
+            && login == account_sanitized
+    })
+}
+
 pub fn run_debug_session(config: DebugStartConfig) -> Result<(), Box<dyn Error>> {
     #[cfg(unix)]
     {
@@ -136,6 +209,42 @@ pub fn exec_debug_entry_module_with_options(
     }
 }
 
+/// Run `script_source` in the session and hand each output line to
+/// `on_output` instead of printing it.
+pub fn exec_debug_script_with_output(
+    socket_path: &Path,
+    script_source: &str,
+    prompt_overrides: Option<super::js_api::PromptOverrides>,
+    script_options: Option<super::js_api::ScriptOptions>,
+    on_output: &mut dyn FnMut(super::js_api::DebugOutputStream, &str),
+) -> Result<(), Box<dyn Error>> {
+    #[cfg(unix)]
+    {
+        let request = Request::Exec {
+            script: Some(script_source.to_string()),
+            entry_root: None,
+            entry_path: None,
+            declared_secrets: None,
+            prompt_overrides,
+            prompt_requires_override: Some(true),
+            script_options,
+        };
+        exec_debug_request_unix(socket_path, request, on_output)
+    }
+
+    #[cfg(not(unix))]
+    {
+        let _ = (
+            socket_path,
+            script_source,
+            prompt_overrides,
+            script_options,
+            on_output,
+        );
+        Err("debug sockets are currently supported only on unix platforms".into())
+    }
+}
+
 pub fn stop_debug_session(socket_path: &Path) -> Result<(), Box<dyn Error>> {
     let response = send_request(socket_path, Request::Stop)?;
     if response.ok {
@@ -166,7 +275,7 @@ fn exec_debug_script_with_options_unix(
         script_options,
     };
 
-    exec_debug_request_unix(socket_path, request)
+    exec_debug_request_unix(socket_path, request, &mut print_exec_output)
 }
 
 #[cfg(unix)]
@@ -189,11 +298,15 @@ fn exec_debug_entry_module_with_options_unix(
         script_options,
     };
 
-    exec_debug_request_unix(socket_path, request)
+    exec_debug_request_unix(socket_path, request, &mut print_exec_output)
 }
 
 #[cfg(unix)]
-fn exec_debug_request_unix(socket_path: &Path, request: Request) -> Result<(), Box<dyn Error>> {
+fn exec_debug_request_unix(
+    socket_path: &Path,
+    request: Request,
+    on_output: &mut dyn FnMut(super::js_api::DebugOutputStream, &str),
+) -> Result<(), Box<dyn Error>> {
     use std::io::{BufRead, BufReader, Write};
     use std::os::unix::net::UnixStream;
 
@@ -218,14 +331,7 @@ fn exec_debug_request_unix(socket_path: &Path, request: Request) -> Result<(), B
 
         if let Ok(frame) = serde_json::from_str::<ExecStreamFrame>(trimmed) {
             match frame {
-                ExecStreamFrame::Output {
-                    stream: ExecOutputStream::Stdout,
-                    line,
-                } => println!("{line}"),
-                ExecStreamFrame::Output {
-                    stream: ExecOutputStream::Stderr,
-                    line,
-                } => eprintln!("{line}"),
+                ExecStreamFrame::Output { stream, line } => on_output(stream.into(), &line),
                 ExecStreamFrame::Result { ok, error } => {
                     if ok {
                         return Ok(());
@@ -251,6 +357,14 @@ fn exec_debug_request_unix(socket_path: &Path, request: Request) -> Result<(), B
     }
 }
 
+#[cfg(unix)]
+fn print_exec_output(stream: super::js_api::DebugOutputStream, line: &str) {
+    match stream {
+        super::js_api::DebugOutputStream::Stdout => println!("{line}"),
+        super::js_api::DebugOutputStream::Stderr => eprintln!("{line}"),
+    }
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(tag = "command", rename_all = "snake_case")]
 enum Request {
@@ -287,6 +401,16 @@ enum ExecOutputStream {
     Stderr,
 }
 
+#[cfg(unix)]
+impl From<ExecOutputStream> for super::js_api::DebugOutputStream {
+    fn from(value: ExecOutputStream) -> Self {
+        match value {
+            ExecOutputStream::Stdout => Self::Stdout,
+            ExecOutputStream::Stderr => Self::Stderr,
+        }
+    }
+}
+
 #[cfg(any(unix, test))]
 impl From<super::js_api::DebugOutputStream> for ExecOutputStream {
     fn from(value: super::js_api::DebugOutputStream) -> Self {
@@ -315,18 +439,22 @@ enum ExecStreamFrame {
 fn finalize_debug_exec_resources(
     refreshmint: &mut super::js_api::RefreshmintInner,
 ) -> Result<Vec<String>, String> {
+    if !refreshmint.staged_balances.is_empty() {
+        super::finalize_staged_balances(refreshmint).map_err(|err| err.to_string())?;
+        refreshmint.staged_balances.clear();
+    }
     if refreshmint.staged_resources.is_empty() {
         return Ok(Vec::new());
     }
 
-    eprintln!(
+    tracing::info!(
         "Finalizing {} staged resources from debug exec...",
         refreshmint.staged_resources.len()
     );
     let names = super::finalize_staged_resources(refreshmint).map_err(|err| err.to_string())?;
     refreshmint.staged_resources.clear();
     for name in &names {
-        eprintln!("  -> {name}");
+        tracing::info!("finalized {name}");
     }
     Ok(names)
 }
@@ -345,6 +473,7 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
         tokio::task::JoinHandle<()>,
         Arc<Mutex<super::js_api::PageInner>>,
         Arc<Mutex<super::js_api::RefreshmintInner>>,
+        super::profile::SessionProfile,
     );
 
     let _login_lock = crate::login_config::acquire_login_lock_with_metadata(
@@ -379,16 +508,32 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
     };
 
     let rt = tokio::runtime::Runtime::new()?;
-    let (browser_instance, handler_handle, page_inner, refreshmint_inner): DebugRuntimeState =
-        rt.block_on(async {
-            let secret_store =
-                crate::secret::SecretStore::new(format!("login/{}", config.login_name));
-            let profile_dir = super::profile::resolve_profile_dir(
+    // `_session_profile` lives until the browser is closed below; an
+    // ephemeral profile is deleted when it drops.
+    let (browser_instance, handler_handle, page_inner, refreshmint_inner, _session_profile) = rt
+        .block_on(async {
+            let secrets = crate::secret::SecretCache::new(crate::secret::SecretStore::new(
+                format!("login/{}", config.login_name),
+            ));
+            let extension_dir = crate::account_config::resolve_extension_dir(
+                &config.ledger_dir,
+                &config.extension_name,
+            );
+            let manifest = super::load_manifest(&extension_dir).map_err(|err| err.to_string())?;
+            for warning in super::api_version::check_compatibility(
+                &config.extension_name,
+                manifest.min_api_version,
+            )? {
+                tracing::warn!("{warning}");
+            }
+            let session_profile = super::profile::resolve_session_profile(
                 &config.ledger_dir,
                 &config.login_name,
                 config.profile_override.as_deref(),
+                manifest.browser_profile,
             )
             .map_err(|err| err.to_string())?;
+            let profile_dir = session_profile.dir();
             let download_dir = super::profile::resolve_download_dir(
                 &config.extension_name,
                 config.profile_override.as_deref(),
@@ -396,12 +541,9 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
             .map_err(|err| err.to_string())?;
             std::fs::create_dir_all(&download_dir).map_err(|err| err.to_string())?;
 
-            let extension_dir = crate::account_config::resolve_extension_dir(
-                &config.ledger_dir,
-                &config.extension_name,
-            );
-            let declared_secrets = super::load_manifest_secret_declarations(&extension_dir)
-                .map_err(|err| err.to_string())?;
+            let network_policy = Arc::new(super::network_policy::NetworkPolicy::new(
+                manifest.allowed_domains,
+            ));
             let ext_cache_key = std::path::Path::new(&config.extension_name)
                 .file_name()
                 .and_then(|n| n.to_str())
@@ -416,11 +558,11 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
 
             let chrome_path =
                 super::browser::find_chrome_binary().map_err(|err| err.to_string())?;
-            eprintln!("Using browser: {}", chrome_path.display());
-            eprintln!("Profile dir: {}", profile_dir.display());
+            tracing::info!("Using browser: {}", chrome_path.display());
+            tracing::info!("Profile dir: {}", profile_dir.display());
 
             let (browser_instance, handler) =
-                super::browser::launch_browser(&chrome_path, &profile_dir, config.headless)
+                super::browser::launch_browser(&chrome_path, profile_dir, config.headless)
                     .await
                     .map_err(|err| err.to_string())?;
             let browser = Arc::new(Mutex::new(browser_instance));
@@ -431,31 +573,49 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
                     .map_err(|err| err.to_string())?
             };
 
+            network_policy.install(&page).await?;
             let page_inner = Arc::new(Mutex::new(super::js_api::PageInner {
                 target_id: page.target_id().as_ref().to_string(),
                 page,
                 browser: browser.clone(),
-                secret_store: Arc::new(secret_store),
-                declared_secrets: Arc::new(declared_secrets),
+                secrets: Arc::new(secrets),
+                declared_secrets: Arc::new(manifest.secrets),
+                download_root: download_dir.clone(),
                 download_dir,
                 target_frame_id: None,
+                network_policy,
+                strict_selectors: true,
+                handlers: super::js_api::PageHandlers::default(),
+                closed: false,
+                login_wall: Arc::new(super::login_wall::LoginWall::new(&manifest.login_wall_urls)),
+                input: Default::default(),
             }));
             let refreshmint_inner = Arc::new(Mutex::new(super::js_api::RefreshmintInner {
                 output_dir,
                 prompt_overrides: super::js_api::PromptOverrides::new(),
                 prompt_requires_override: config.prompt_requires_override,
+                prompt_catalog: manifest.prompts,
                 script_options: super::js_api::ScriptOptions::new(),
                 debug_output_sink: None,
                 session_metadata: super::js_api::SessionMetadata::default(),
                 staged_resources: Vec::new(),
+                staged_balances: Vec::new(),
                 scrape_session_id: String::new(),
                 extension_name: config.extension_name.clone(),
                 account_name: config.login_name.clone(),
                 login_name: config.login_name.clone(),
                 ledger_dir: config.ledger_dir.clone(),
                 prompt_ui_handler: None,
+                assertion_failure: None,
+                prompts_asked: Vec::new(),
             }));
-            Ok::<_, Box<dyn Error>>((browser, handler, page_inner, refreshmint_inner))
+            Ok::<DebugRuntimeState, Box<dyn Error>>((
+                browser,
+                handler,
+                page_inner,
+                refreshmint_inner,
+                session_profile,
+            ))
         })?;
 
     rt.block_on(async move {
@@ -486,7 +646,7 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
                                 error: Some("failed to read request: empty request".to_string()),
                             };
                             if let Err(err) = write_response_async(&mut stream, &response).await {
-                                eprintln!("failed to write debug response: {err}");
+                                tracing::warn!("failed to write debug response: {err}");
                             }
                         }
                         Ok(_) => match serde_json::from_str::<Request>(body.trim()) {
@@ -513,7 +673,7 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
                                 )
                                 .await
                                 {
-                                    eprintln!("failed to write debug exec stream: {err}");
+                                    tracing::warn!("failed to write debug exec stream: {err}");
                                 }
                             }
                             Ok(Request::Stop) => {
@@ -524,7 +684,7 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
                                 };
                                 if let Err(err) = write_response_async(&mut stream, &response).await
                                 {
-                                    eprintln!("failed to write debug response: {err}");
+                                    tracing::warn!("failed to write debug response: {err}");
                                 }
                             }
                             Err(err) => {
@@ -534,7 +694,7 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
                                 };
                                 if let Err(err) = write_response_async(&mut stream, &response).await
                                 {
-                                    eprintln!("failed to write debug response: {err}");
+                                    tracing::warn!("failed to write debug response: {err}");
                                 }
                             }
                         },
@@ -544,7 +704,7 @@ fn run_debug_session_unix(config: DebugStartConfig) -> Result<(), Box<dyn Error>
                                 error: Some(format!("failed to read request: {err}")),
                             };
                             if let Err(err) = write_response_async(&mut stream, &response).await {
-                                eprintln!("failed to write debug response: {err}");
+                                tracing::warn!("failed to write debug response: {err}");
                             }
                         }
                     }
@@ -888,7 +1048,8 @@ fn resolve_socket_bind_path(requested_path: &Path) -> PathBuf {
 #[cfg(test)]
 mod tests {
     use super::{
-        finalize_debug_exec_resources, sanitize_segment, ExecOutputStream, ExecStreamFrame,
+        finalize_debug_exec_resources, sanitize_segment, socket_name_matches_login,
+        ExecOutputStream, ExecStreamFrame,
     };
     use crate::login_config::login_account_documents_dir;
     use crate::scrape::js_api::{
@@ -921,6 +1082,18 @@ mod tests {
         assert_eq!(sanitize_segment("a/b:c"), "a_b_c");
     }
 
+    #[test]
+    fn socket_names_match_only_the_same_login() {
+        assert!(socket_name_matches_login("rm-4242-chase.sock", "chase"));
+        assert!(socket_name_matches_login("rm-debug-7-chase.sock", "chase"));
+        assert!(!socket_name_matches_login("rm-4242-chase-2.sock", "chase"));
+        assert!(!socket_name_matches_login("rm-abc-chase.sock", "chase"));
+        assert!(!socket_name_matches_login(
+            "rm-4242-chase.sock.lock",
+            "chase"
+        ));
+    }
+
     #[test]
     fn exec_stream_output_frame_roundtrip_json() {
         let frame = ExecStreamFrame::Output {
@@ -963,6 +1136,7 @@ mod tests {
             output_dir: root.join("output"),
             prompt_overrides: PromptOverrides::new(),
             prompt_requires_override: false,
+            prompt_catalog: crate::scrape::prompt_catalog::PromptCatalog::new(),
             script_options: ScriptOptions::new(),
             debug_output_sink: None,
             session_metadata: SessionMetadata::default(),
@@ -973,14 +1147,19 @@ mod tests {
                 original_url: Some("https://example.com/export".to_string()),
                 mime_type: Some("application/octet-stream".to_string()),
                 label: Some("checking".to_string()),
+                document_type: None,
                 metadata: std::collections::BTreeMap::new(),
+                suspect: None,
             }],
+            staged_balances: Vec::new(),
             scrape_session_id: "debug-session".to_string(),
             extension_name: "smoke-ext".to_string(),
             account_name: login_name.clone(),
             login_name: login_name.clone(),
             ledger_dir: ledger_dir.clone(),
             prompt_ui_handler: None,
+            assertion_failure: None,
+            prompts_asked: Vec::new(),
         };
 
         let finalized =
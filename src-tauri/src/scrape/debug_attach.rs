@@ -0,0 +1,462 @@
+//! Interactive client for a running debug session (`debug attach`).
+//!
+//! Each input is sent to the session as its own script, so values only
+//! survive between inputs when stored on `globalThis`. Single expressions are
+//! evaluated and their value is printed; output lines that hold JSON (for
+//! example `page.snapshot()` or `page.networkRequests()`) are pretty-printed.
+
+use std::error::Error;
+use std::path::{Path, PathBuf};
+
+use base64::Engine;
+use rustyline::error::ReadlineError;
+use rustyline::DefaultEditor;
+
+use super::js_api::{DebugOutputStream, PromptOverrides, ScriptOptions};
+
+const PROMPT: &str = "rm> ";
+const CONTINUATION_PROMPT: &str = "... ";
+const HISTORY_FILE: &str = "attach-history";
+/// Prefix of the output line that carries a base64 screenshot back to the
+/// client.
+const SCREENSHOT_MARKER: &str = "__refreshmint_screenshot__:";
+
+const HELP: &str = "\
+Enter JavaScript to run against the session (`page`, `browser`, `refreshmint`).
+A single expression prints its value. Lines ending in `\\` or with unclosed
+brackets continue on the next line. Values do not persist between inputs
+unless stored on `globalThis`.
+
+Meta-commands:
+  :screenshot [PATH]  Save a PNG of the current page (default: screenshot-<time>.png)
+  :pages              List open pages with their URLs
+  :reload             Reload the current page
+  :help               Show this help
+  :quit               Detach (the session keeps running)";
+
+pub struct AttachOptions {
+    pub socket_path: PathBuf,
+    pub prompt_overrides: PromptOverrides,
+    pub script_options: ScriptOptions,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum MetaCommand {
+    Screenshot(Option<String>),
+    Pages,
+    Reload,
+    Help,
+    Quit,
+}
+
+/// Run the prompt until `:quit` or end of input.
+pub fn run_attach(options: AttachOptions) -> Result<(), Box<dyn Error>> {
+    let mut editor = DefaultEditor::new()?;
+    let history_path = history_path();
+    if let Some(path) = &history_path {
+        let _ = editor.load_history(path);
+    }
+
+    println!(
+        "Attached to {}. Type :help for commands.",
+        options.socket_path.display()
+    );
+    let result = repl(&mut editor, &options);
+
+    if let Some(path) = &history_path {
+        if let Some(parent) = path.parent() {
+            let _ = std::fs::create_dir_all(parent);
+        }
+        if let Err(err) = editor.save_history(path) {
+            tracing::warn!("failed to save debug attach history: {err}");
+        }
+    }
+    result
+}
+
+fn repl(editor: &mut DefaultEditor, options: &AttachOptions) -> Result<(), Box<dyn Error>> {
+    loop {
+        let Some(input) = read_input(editor)? else {
+            return Ok(());
+        };
+        let trimmed = input.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        let _ = editor.add_history_entry(trimmed);
+
+        let outcome = match parse_meta_command(trimmed) {
+            Some(Ok(MetaCommand::Quit)) => return Ok(()),
+            Some(Ok(MetaCommand::Help)) => {
+                println!("{HELP}");
+                continue;
+            }
+            Some(Ok(MetaCommand::Screenshot(path))) => take_screenshot(options, path),
+            Some(Ok(MetaCommand::Pages)) => run_script(options, PAGES_SCRIPT),
+            Some(Ok(MetaCommand::Reload)) => run_script(options, RELOAD_SCRIPT),
+            Some(Err(message)) => {
+                eprintln!("{message}");
+                continue;
+            }
+            None => run_script(options, &script_for_input(trimmed)),
+        };
+        if let Err(err) = outcome {
+            // Losing the socket ends the session; script errors do not.
+            if err.downcast_ref::<std::io::Error>().is_some() {
+                return Err(format!("debug session connection failed: {err}").into());
+            }
+            eprintln!("error: {err}");
+        }
+    }
+}
+
+/// Read one complete input, prompting for continuation lines. `None` means
+/// end of input.
+fn read_input(editor: &mut DefaultEditor) -> Result<Option<String>, Box<dyn Error>> {
+    let mut buffer = String::new();
+    loop {
+        let prompt = if buffer.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        };
+        match editor.readline(prompt) {
+            Ok(line) => {
+                if let Some(stripped) = line.strip_suffix('\\') {
+                    buffer.push_str(stripped);
+                    buffer.push('\n');
+                    continue;
+                }
+                buffer.push_str(&line);
+                if buffer.starts_with(':') || input_is_complete(&buffer) {
+                    return Ok(Some(buffer));
+                }
+                buffer.push('\n');
+            }
+            Err(ReadlineError::Interrupted) => {
+                // Ctrl-C discards the current input, like a shell.
+                buffer.clear();
+            }
+            Err(ReadlineError::Eof) => {
+                return Ok((!buffer.trim().is_empty()).then_some(buffer));
+            }
+            Err(err) => return Err(err.into()),
+        }
+    }
+}
+
+fn run_script(options: &AttachOptions, script: &str) -> Result<(), Box<dyn Error>> {
+    super::debug::exec_debug_script_with_output(
+        &options.socket_path,
+        script,
+        Some(options.prompt_overrides.clone()),
+        Some(options.script_options.clone()),
+        &mut |stream, line| match stream {
+            DebugOutputStream::Stdout => println!("{}", format_output_line(line)),
+            DebugOutputStream::Stderr => eprintln!("{}", format_output_line(line)),
+        },
+    )
+}
+
+fn take_screenshot(options: &AttachOptions, path: Option<String>) -> Result<(), Box<dyn Error>> {
+    let path = PathBuf::from(path.unwrap_or_else(|| {
+        format!(
+            "screenshot-{}.png",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        )
+    }));
+    let mut encoded = None;
+    super::debug::exec_debug_script_with_output(
+        &options.socket_path,
+        &screenshot_script(),
+        Some(options.prompt_overrides.clone()),
+        Some(options.script_options.clone()),
+        &mut |stream, line| match line.strip_prefix(SCREENSHOT_MARKER) {
+            Some(data) => encoded = Some(data.to_string()),
+            None if stream == DebugOutputStream::Stdout => println!("{line}"),
+            None => eprintln!("{line}"),
+        },
+    )?;
+    let encoded = encoded.ok_or("screenshot produced no image data")?;
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|err| format!("screenshot data is not valid base64: {err}"))?;
+    write_screenshot(&path, &bytes)
+        .map_err(|err| format!("failed to write {}: {err}", path.display()))?;
+    println!("Saved screenshot to {}", path.display());
+    Ok(())
+}
+
+fn write_screenshot(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(path, bytes)
+}
+
+fn history_path() -> Option<PathBuf> {
+    dirs::cache_dir().map(|dir| dir.join("refreshmint").join("debug").join(HISTORY_FILE))
+}
+
+/// `Some` when `input` is a meta-command, with an error for unknown ones.
+fn parse_meta_command(input: &str) -> Option<Result<MetaCommand, String>> {
+    let rest = input.strip_prefix(':')?;
+    let mut words = rest.split_whitespace();
+    let name = words.next().unwrap_or_default();
+    let argument = words.next().map(str::to_string);
+    let extra = words.next().is_some();
+    let command = match (name, argument, extra) {
+        ("screenshot", path, false) => MetaCommand::Screenshot(path),
+        ("pages", None, false) => MetaCommand::Pages,
+        ("reload", None, false) => MetaCommand::Reload,
+        ("help", None, false) => MetaCommand::Help,
+        ("quit" | "exit", None, false) => MetaCommand::Quit,
+        ("screenshot" | "pages" | "reload" | "help" | "quit" | "exit", _, _) => {
+            return Some(Err(format!(
+                "too many arguments for :{name}; type :help for usage"
+            )))
+        }
+        _ => {
+            return Some(Err(format!(
+                "unknown command :{name}; type :help for commands"
+            )))
+        }
+    };
+    Some(Ok(command))
+}
+
+/// Whether every bracket, string, and block comment in `source` is closed.
+fn input_is_complete(source: &str) -> bool {
+    let mut depth = 0i32;
+    let mut chars = source.chars().peekable();
+    while let Some(ch) = chars.next() {
+        match ch {
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            '\'' | '"' | '`' => {
+                let mut closed = false;
+                while let Some(next) = chars.next() {
+                    if next == '\\' {
+                        chars.next();
+                    } else if next == ch {
+                        closed = true;
+                        break;
+                    } else if next == '\n' && ch != '`' {
+                        // An unterminated plain string is a syntax error;
+                        // let the session report it.
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    return false;
+                }
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for next in chars.by_ref() {
+                    if next == '\n' {
+                        break;
+                    }
+                }
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut closed = false;
+                while let Some(next) = chars.next() {
+                    if next == '*' && chars.peek() == Some(&'/') {
+                        chars.next();
+                        closed = true;
+                        break;
+                    }
+                }
+                if !closed {
+                    return false;
+                }
+            }
+            _ => {}
+        }
+    }
+    depth <= 0
+}
+
+/// Wrap a single expression so its value is printed; statements run as-is.
+fn script_for_input(input: &str) -> String {
+    if !looks_like_expression(input) {
+        return input.to_string();
+    }
+    let expression = input.trim().trim_end_matches(';');
+    format!(
+        "const __refreshmintValue = await (\n{expression}\n);\n\
+         if (__refreshmintValue !== undefined) {{\n\
+         \x20   refreshmint.log(typeof __refreshmintValue === 'string'\n\
+         \x20       ? __refreshmintValue\n\
+         \x20       : JSON.stringify(__refreshmintValue));\n\
+         }}\n"
+    )
+}
+
+fn looks_like_expression(input: &str) -> bool {
+    const STATEMENT_KEYWORDS: [&str; 16] = [
+        "const",
+        "let",
+        "var",
+        "if",
+        "for",
+        "while",
+        "do",
+        "function",
+        "class",
+        "return",
+        "throw",
+        "try",
+        "switch",
+        "import",
+        "export",
+        "async function",
+    ];
+    let trimmed = input.trim();
+    if trimmed.starts_with('{') {
+        return false;
+    }
+    if STATEMENT_KEYWORDS.iter().any(|keyword| {
+        trimmed.strip_prefix(keyword).is_some_and(|rest| {
+            rest.starts_with(|ch: char| ch.is_whitespace() || ch == '(' || ch == '{')
+        })
+    }) {
+        return false;
+    }
+    !has_top_level_semicolon(trimmed.trim_end_matches(';'))
+}
+
+fn has_top_level_semicolon(source: &str) -> bool {
+    let mut depth = 0i32;
+    let mut quote = None;
+    let mut escaped = false;
+    for ch in source.chars() {
+        if let Some(open) = quote {
+            if escaped {
+                escaped = false;
+            } else if ch == '\\' {
+                escaped = true;
+            } else if ch == open {
+                quote = None;
+            }
+            continue;
+        }
+        match ch {
+            '\'' | '"' | '`' => quote = Some(ch),
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            ';' if depth == 0 => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Pretty-print lines that are JSON objects or arrays.
+fn format_output_line(line: &str) -> String {
+    let trimmed = line.trim();
+    if !(trimmed.starts_with('{') || trimmed.starts_with('[')) {
+        return line.to_string();
+    }
+    serde_json::from_str::<serde_json::Value>(trimmed)
+        .ok()
+        .and_then(|value| serde_json::to_string_pretty(&value).ok())
+        .unwrap_or_else(|| line.to_string())
+}
+
+const PAGES_SCRIPT: &str = "\
+const pages = await browser.pages();
+for (let i = 0; i < pages.length; i++) {
+    refreshmint.log(`${i}: ${await pages[i].url()}`);
+}
+";
+
+const RELOAD_SCRIPT: &str = "\
+await page.reload();
+refreshmint.log(`reloaded ${await page.url()}`);
+";
+
+fn screenshot_script() -> String {
+    format!(
+        "const bytes = await page.screenshot();
+const alphabet = 'ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/';
+const parts = [];
+for (let i = 0; i < bytes.length; i += 3) {{
+    const n = (bytes[i] << 16) | ((bytes[i + 1] ?? 0) << 8) | (bytes[i + 2] ?? 0);
+    parts.push(
+        alphabet[(n >> 18) & 63] + alphabet[(n >> 12) & 63] +
+        (i + 1 < bytes.length ? alphabet[(n >> 6) & 63] : '=') +
+        (i + 2 < bytes.length ? alphabet[n & 63] : '='));
+}}
+refreshmint.log('{SCREENSHOT_MARKER}' + parts.join(''));
+"
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn meta_commands_parse_with_arguments() {
+        assert_eq!(
+            parse_meta_command(":screenshot out/page.png"),
+            Some(Ok(MetaCommand::Screenshot(Some(
+                "out/page.png".to_string()
+            ))))
+        );
+        assert_eq!(
+            parse_meta_command(":screenshot"),
+            Some(Ok(MetaCommand::Screenshot(None)))
+        );
+        assert_eq!(parse_meta_command(":pages"), Some(Ok(MetaCommand::Pages)));
+        assert_eq!(parse_meta_command(":exit"), Some(Ok(MetaCommand::Quit)));
+        assert!(matches!(parse_meta_command(":reload now"), Some(Err(_))));
+        assert!(matches!(parse_meta_command(":nope"), Some(Err(_))));
+        assert_eq!(parse_meta_command("page.url()"), None);
+    }
+
+    #[test]
+    fn incomplete_input_waits_for_closing_brackets_and_strings() {
+        assert!(input_is_complete("await page.url()"));
+        assert!(!input_is_complete("for (const p of pages) {"));
+        assert!(!input_is_complete("const s = `line one"));
+        assert!(input_is_complete("const s = '{'"));
+        assert!(input_is_complete("page.url() // trailing ("));
+        assert!(!input_is_complete("/* still"));
+    }
+
+    #[test]
+    fn expressions_are_wrapped_and_statements_are_not() {
+        assert!(looks_like_expression("await page.url()"));
+        assert!(looks_like_expression("page.snapshot();"));
+        assert!(looks_like_expression("'a;b'"));
+        assert!(!looks_like_expression("const url = await page.url()"));
+        assert!(!looks_like_expression(
+            "await page.reload(); await page.url()"
+        ));
+        assert!(!looks_like_expression("if (x) { y(); }"));
+        assert!(looks_like_expression("constants.value"));
+
+        let script = script_for_input("await page.url();");
+        assert!(script.contains("await (\nawait page.url()\n)"));
+        assert_eq!(script_for_input("let x = 1"), "let x = 1");
+    }
+
+    #[test]
+    fn json_output_is_pretty_printed() {
+        assert_eq!(
+            format_output_line(r#"{"role":"button","name":"Sign in"}"#),
+            "{\n  \"name\": \"Sign in\",\n  \"role\": \"button\"\n}"
+        );
+        assert_eq!(format_output_line("[1,2]"), "[\n  1,\n  2\n]");
+        assert_eq!(format_output_line("{not json"), "{not json");
+        assert_eq!(format_output_line("plain text"), "plain text");
+    }
+}
@@ -0,0 +1,200 @@
+//! Checks on saved resources that catch a bank's error page or an empty
+//! export before it becomes evidence.
+//!
+//! Banks sometimes answer an export request with an HTML error or login page
+//! under a `.csv` name, or with a CSV holding only its header. A resource
+//! whose bytes do not match the kind its mime type or filename declares, that
+//! has no data rows, or that reads like a known error page is marked suspect:
+//! its sidecar says why, extraction skips it unless asked, and the scrape log
+//! lists it.
+
+use serde::{Deserialize, Serialize};
+
+/// How much of a file is searched for error-page phrases.
+const SIGNATURE_SCAN_BYTES: usize = 64 * 1024;
+
+/// Text files smaller than this are checked for error phrases even when they
+/// are not HTML, since a real export is rarely this short.
+const SMALL_TEXT_BYTES: usize = 4 * 1024;
+
+/// Phrases of session-expired, maintenance, and server error pages.
+const ERROR_PAGE_SIGNATURES: &[&str] = &[
+    "session has expired",
+    "session expired",
+    "session has timed out",
+    "session timed out",
+    "please sign in again",
+    "please log in again",
+    "access denied",
+    "temporarily unavailable",
+    "service unavailable",
+    "scheduled maintenance",
+    "an unexpected error",
+    "an error has occurred",
+    "internal server error",
+    "page not found",
+    "404 not found",
+    "request could not be processed",
+];
+
+/// A saved resource marked suspect, for the scrape log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SuspectDownload {
+    pub filename: String,
+    pub reason: String,
+}
+
+/// The kind of file a resource claims to be.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeclaredKind {
+    Csv,
+    Pdf,
+    Ofx,
+    Json,
+    Xlsx,
+    Html,
+    Other,
+}
+
+fn declared_kind(filename: &str, mime_type: &str) -> DeclaredKind {
+    let mime = mime_type.to_ascii_lowercase();
+    let lower = filename.to_ascii_lowercase();
+    let extension = lower.rsplit_once('.').map_or("", |(_, ext)| ext);
+    match (mime.as_str(), extension) {
+        ("text/csv", _) | (_, "csv") => DeclaredKind::Csv,
+        ("application/pdf", _) | (_, "pdf") => DeclaredKind::Pdf,
+        ("application/x-ofx", _) | (_, "ofx" | "qfx") => DeclaredKind::Ofx,
+        ("application/json", _) | (_, "json") => DeclaredKind::Json,
+        (_, "xlsx") => DeclaredKind::Xlsx,
+        ("text/html", _) | (_, "html" | "htm") => DeclaredKind::Html,
+        _ => DeclaredKind::Other,
+    }
+}
+
+/// Why `data` saved as `filename` looks like something other than the
+/// document it claims to be, or `None` when it looks fine.
+pub fn check_download(filename: &str, mime_type: &str, data: &[u8]) -> Option<String> {
+    let kind = declared_kind(filename, mime_type);
+    let start = trim_leading(data);
+    if start.is_empty() {
+        return Some("file is empty".to_string());
+    }
+    let looks_like_html = is_html(start);
+    let mismatch = match kind {
+        DeclaredKind::Pdf if !start.starts_with(b"%PDF") => Some("is not a PDF"),
+        DeclaredKind::Xlsx if !start.starts_with(b"PK") => Some("is not an XLSX workbook"),
+        DeclaredKind::Csv | DeclaredKind::Ofx | DeclaredKind::Json if looks_like_html => {
+            Some("is an HTML page")
+        }
+        _ => None,
+    };
+    if let Some(mismatch) = mismatch {
+        return Some(match error_signature(start) {
+            Some(signature) => format!("{mismatch} mentioning \"{signature}\""),
+            None => mismatch.to_string(),
+        });
+    }
+
+    let is_text = std::str::from_utf8(&start[..start.len().min(SIGNATURE_SCAN_BYTES)]).is_ok()
+        || looks_like_html;
+    if looks_like_html || (is_text && data.len() < SMALL_TEXT_BYTES) {
+        if let Some(signature) = error_signature(start) {
+            return Some(format!("reads like an error page (\"{signature}\")"));
+        }
+    }
+
+    if kind == DeclaredKind::Csv && csv_data_rows(start) == 0 {
+        return Some("CSV has no data rows".to_string());
+    }
+    None
+}
+
+/// Skip a UTF-8 byte order mark and leading whitespace.
+fn trim_leading(data: &[u8]) -> &[u8] {
+    let data = data.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(data);
+    let start = data
+        .iter()
+        .position(|b| !b.is_ascii_whitespace())
+        .unwrap_or(data.len());
+    &data[start..]
+}
+
+fn is_html(start: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&start[..start.len().min(512)]).to_ascii_lowercase();
+    [
+        "<!doctype html",
+        "<html",
+        "<head",
+        "<body",
+        "<script",
+        "<meta",
+    ]
+    .iter()
+    .any(|tag| head.starts_with(tag))
+}
+
+fn error_signature(data: &[u8]) -> Option<&'static str> {
+    let text =
+        String::from_utf8_lossy(&data[..data.len().min(SIGNATURE_SCAN_BYTES)]).to_lowercase();
+    ERROR_PAGE_SIGNATURES
+        .iter()
+        .copied()
+        .find(|signature| text.contains(signature))
+}
+
+/// Non-blank lines after the header.
+fn csv_data_rows(data: &[u8]) -> usize {
+    String::from_utf8_lossy(data)
+        .lines()
+        .filter(|line| !line.trim().trim_matches(',').is_empty())
+        .count()
+        .saturating_sub(1)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn html_error_pages_saved_as_exports_are_suspect() {
+        let page = b"\n<!DOCTYPE html><html><body><h1>Your session has expired</h1></body></html>";
+        assert_eq!(
+            check_download("activity.csv", "text/csv", page).as_deref(),
+            Some("is an HTML page mentioning \"session has expired\"")
+        );
+        assert_eq!(
+            check_download("statement.pdf", "application/pdf", b"<html></html>").as_deref(),
+            Some("is not a PDF")
+        );
+        assert_eq!(
+            check_download("statement.html", "text/html", page).as_deref(),
+            Some("reads like an error page (\"session has expired\")")
+        );
+        assert_eq!(
+            check_download("statement.html", "text/html", b"<html>Statement</html>"),
+            None
+        );
+    }
+
+    #[test]
+    fn empty_and_header_only_csvs_are_suspect() {
+        assert_eq!(
+            check_download("a.csv", "text/csv", b" \n").as_deref(),
+            Some("file is empty")
+        );
+        assert_eq!(
+            check_download("a.csv", "text/csv", b"\xEF\xBB\xBFDate,Amount\n,\n").as_deref(),
+            Some("CSV has no data rows")
+        );
+        assert_eq!(
+            check_download("a.csv", "text/csv", b"Date,Amount\n2025-01-02,-4.50\n"),
+            None
+        );
+        assert_eq!(
+            check_download("s.pdf", "application/pdf", b"%PDF-1.7\n..."),
+            None
+        );
+    }
+}
@@ -0,0 +1,539 @@
+//! Reusable login flows for hosted sign-in widgets (`refreshmint.flows`).
+//!
+//! Many institutions embed the same identity provider widget, so the
+//! selectors and the username/password/verification-code state machine live
+//! here once instead of in every driver. Each flow polls the active frame,
+//! fills whatever step the widget is showing, and returns once the widget is
+//! gone after a submit. Values passed as `username`/`password` go through
+//! `page.fill`, so secret names declared in the manifest are resolved the
+//! same way.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use rquickjs::{class::Trace, function::Opt, JsLifetime, Result as JsResult, Value};
+use tokio::sync::Mutex;
+use tokio::time::Instant;
+
+use super::js_api::{js_err, rquickjs_value_to_json, PageApi, RefreshmintApi, RefreshmintInner};
+use super::locator::ActionOptions;
+
+const DEFAULT_TIMEOUT_MS: u64 = 60_000;
+const POLL_INTERVAL_MS: u64 = 250;
+
+/// Built-in selectors for one identity provider widget. Selectors are tried
+/// in order; the first visible match wins.
+struct FlowDefinition {
+    name: &'static str,
+    username: &'static [&'static str],
+    password: &'static [&'static str],
+    /// Button that advances an identifier-first form to the password step.
+    next: &'static [&'static str],
+    submit: &'static [&'static str],
+    otp: &'static [&'static str],
+    otp_submit: &'static [&'static str],
+    error: &'static [&'static str],
+}
+
+const OKTA: FlowDefinition = FlowDefinition {
+    name: "Okta",
+    username: &[
+        "input[name=\"identifier\"]",
+        "#okta-signin-username",
+        "input[name=\"username\"]",
+    ],
+    password: &[
+        "input[name=\"credentials.passcode\"][type=\"password\"]",
+        "#okta-signin-password",
+        "input[name=\"password\"]",
+    ],
+    next: &[
+        "input[type=\"submit\"][value=\"Next\"]",
+        "#okta-signin-submit",
+    ],
+    submit: &[
+        "input[type=\"submit\"][value=\"Verify\"]",
+        "#okta-signin-submit",
+        "input[type=\"submit\"]",
+    ],
+    otp: &[
+        "input[name=\"credentials.passcode\"]:not([type=\"password\"])",
+        "input[name=\"passCode\"]",
+        "input[name=\"answer\"]",
+    ],
+    otp_submit: &[
+        "input[type=\"submit\"][value=\"Verify\"]",
+        "input[type=\"submit\"]",
+    ],
+    error: &[
+        ".o-form-error-container .okta-form-infobox-error",
+        ".o-form-error-container .infobox-error",
+        ".okta-form-input-error",
+    ],
+};
+
+const FORGEROCK: FlowDefinition = FlowDefinition {
+    name: "ForgeRock",
+    username: &["#idToken1", "input[name=\"callback_0\"]"],
+    password: &[
+        "#idToken2",
+        "input[type=\"password\"][name^=\"callback_\"]",
+        "input[type=\"password\"][name^=\"IDToken\"]",
+    ],
+    next: &["#loginButton_0", "button[type=\"submit\"]"],
+    submit: &[
+        "#loginButton_0",
+        "button[type=\"submit\"]",
+        "input[type=\"submit\"]",
+    ],
+    otp: &[
+        "input[autocomplete=\"one-time-code\"]",
+        "input[name^=\"IDToken\"][type=\"tel\"]",
+    ],
+    otp_submit: &[
+        "#loginButton_0",
+        "button[type=\"submit\"]",
+        "input[type=\"submit\"]",
+    ],
+    error: &["#loginError", ".alert-danger"],
+};
+
+const PLAID: FlowDefinition = FlowDefinition {
+    name: "Plaid",
+    username: &[
+        "input#username",
+        "input[name=\"username\"]",
+        "input[autocomplete=\"username\"]",
+    ],
+    password: &[
+        "input#password",
+        "input[name=\"password\"]",
+        "input[type=\"password\"]",
+    ],
+    next: &["button[type=\"submit\"]"],
+    submit: &["button[type=\"submit\"]"],
+    otp: &[
+        "input[autocomplete=\"one-time-code\"]",
+        "input#code",
+        "input[name=\"code\"]",
+    ],
+    otp_submit: &["button[type=\"submit\"]"],
+    error: &["[role=\"alert\"]"],
+};
+
+/// `login` argument: the values to fill, usually manifest secret names.
+#[derive(Debug, serde::Deserialize)]
+struct FlowLogin {
+    username: String,
+    password: String,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct FlowOptions {
+    /// How long to wait for the widget to advance after each action.
+    timeout_ms: Option<u64>,
+    /// Message for `refreshmint.prompt` when a verification code is needed.
+    otp_prompt: Option<String>,
+    /// Fail instead of prompting when a verification code is requested.
+    no_mfa: bool,
+    /// Extra selectors tried before the built-in ones.
+    selectors: SelectorOverrides,
+}
+
+#[derive(Debug, Default, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+struct SelectorOverrides {
+    username: Vec<String>,
+    password: Vec<String>,
+    next: Vec<String>,
+    submit: Vec<String>,
+    otp: Vec<String>,
+    otp_submit: Vec<String>,
+    error: Vec<String>,
+}
+
+#[derive(Debug)]
+struct FlowSelectors {
+    username: Vec<String>,
+    password: Vec<String>,
+    next: Vec<String>,
+    submit: Vec<String>,
+    otp: Vec<String>,
+    otp_submit: Vec<String>,
+    error: Vec<String>,
+}
+
+impl FlowDefinition {
+    fn selectors(&self, overrides: SelectorOverrides) -> FlowSelectors {
+        fn merge(custom: Vec<String>, builtin: &[&str]) -> Vec<String> {
+            let mut merged = custom;
+            merged.extend(builtin.iter().map(|selector| (*selector).to_string()));
+            merged
+        }
+        FlowSelectors {
+            username: merge(overrides.username, self.username),
+            password: merge(overrides.password, self.password),
+            next: merge(overrides.next, self.next),
+            submit: merge(overrides.submit, self.submit),
+            otp: merge(overrides.otp, self.otp),
+            otp_submit: merge(overrides.otp_submit, self.otp_submit),
+            error: merge(overrides.error, self.error),
+        }
+    }
+}
+
+/// First visible selector of each kind at one poll.
+#[derive(Debug, Default)]
+struct Observed {
+    username: Option<String>,
+    password: Option<String>,
+    otp: Option<String>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+struct Progress {
+    username_filled: bool,
+    password_filled: bool,
+    otp_submitted: bool,
+    submitted: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Step {
+    FillUsername(String),
+    FillPassword(String),
+    EnterOtp(String),
+    Wait,
+    Done,
+    Fail(String),
+}
+
+/// Decide what to do with the widget as currently observed.
+fn next_step(flow: &str, observed: &Observed, progress: &Progress) -> Step {
+    if progress.submitted {
+        if let Some(error) = &observed.error {
+            return Step::Fail(format!("{flow} sign-in failed: {error}"));
+        }
+    }
+    if let Some(otp) = &observed.otp {
+        // A submitted code either clears the field or shows an error; until
+        // then the field may linger while the widget verifies it.
+        if progress.otp_submitted {
+            return Step::Wait;
+        }
+        return Step::EnterOtp(otp.clone());
+    }
+    if let Some(username) = &observed.username {
+        if !progress.username_filled {
+            return Step::FillUsername(username.clone());
+        }
+    }
+    if let Some(password) = &observed.password {
+        if !progress.password_filled {
+            return Step::FillPassword(password.clone());
+        }
+    }
+    if progress.submitted
+        && observed.username.is_none()
+        && observed.password.is_none()
+        && observed.otp.is_none()
+    {
+        return Step::Done;
+    }
+    Step::Wait
+}
+
+/// JS-visible `refreshmint.flows` namespace.
+#[rquickjs::class(rename = "Flows")]
+#[derive(Trace)]
+pub struct FlowsApi {
+    #[qjs(skip_trace)]
+    page: PageApi,
+    #[qjs(skip_trace)]
+    refreshmint: Arc<Mutex<RefreshmintInner>>,
+}
+
+// Safety: FlowsApi only contains Arc<Mutex<...>> state which is 'static.
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for FlowsApi {
+    type Changed<'to> = FlowsApi;
+}
+
+impl FlowsApi {
+    pub fn new(page: PageApi, refreshmint: Arc<Mutex<RefreshmintInner>>) -> Self {
+        Self { page, refreshmint }
+    }
+
+    async fn run(
+        &self,
+        definition: &FlowDefinition,
+        login: Value<'_>,
+        options: Option<Value<'_>>,
+    ) -> JsResult<()> {
+        let flow = definition.name;
+        let login: FlowLogin = parse_argument(&login, flow, "login")?;
+        let options: FlowOptions = match options {
+            Some(options) if !options.is_undefined() && !options.is_null() => {
+                parse_argument(&options, flow, "options")?
+            }
+            _ => FlowOptions::default(),
+        };
+        let timeout = Duration::from_millis(options.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS));
+        let otp_prompt = options
+            .otp_prompt
+            .clone()
+            .unwrap_or_else(|| format!("{flow} verification code"));
+        let selectors = definition.selectors(options.selectors);
+
+        let mut progress = Progress::default();
+        let mut deadline = Instant::now() + timeout;
+        loop {
+            let observed = self.observe(&selectors).await?;
+            let step = next_step(flow, &observed, &progress);
+            tracing::debug!(flow, ?step, "login flow step");
+            match step {
+                Step::Done => return Ok(()),
+                Step::Fail(message) => return Err(js_err(message)),
+                Step::Wait => {
+                    if Instant::now() >= deadline {
+                        return Err(js_err(if progress.submitted {
+                            format!("{flow} sign-in did not finish within {timeout:?}")
+                        } else {
+                            format!("{flow} sign-in form not found within {timeout:?}")
+                        }));
+                    }
+                    tokio::time::sleep(Duration::from_millis(POLL_INTERVAL_MS)).await;
+                    continue;
+                }
+                Step::FillUsername(selector) => {
+                    self.page
+                        .fill(selector, login.username.clone(), Opt(None))
+                        .await?;
+                    progress.username_filled = true;
+                    // Identifier-first forms only show the password after
+                    // the username is submitted.
+                    if self.first_visible(&selectors.password).await?.is_none() {
+                        self.click_first_visible(flow, &selectors.next).await?;
+                    }
+                }
+                Step::FillPassword(selector) => {
+                    self.page
+                        .fill(selector, login.password.clone(), Opt(None))
+                        .await?;
+                    progress.password_filled = true;
+                    self.click_first_visible(flow, &selectors.submit).await?;
+                    progress.submitted = true;
+                }
+                Step::EnterOtp(selector) => {
+                    if options.no_mfa {
+                        return Err(js_err(format!(
+                            "{flow} sign-in requested a verification code and noMfa is set"
+                        )));
+                    }
+                    let code =
+                        RefreshmintApi::new(self.refreshmint.clone()).prompt(otp_prompt.clone())?;
+                    self.page
+                        .fill(selector, code.trim().to_string(), Opt(None))
+                        .await?;
+                    progress.otp_submitted = true;
+                    self.click_first_visible(flow, &selectors.otp_submit)
+                        .await?;
+                    progress.submitted = true;
+                }
+            }
+            deadline = Instant::now() + timeout;
+        }
+    }
+
+    async fn observe(&self, selectors: &FlowSelectors) -> JsResult<Observed> {
+        let error = match self.first_visible(&selectors.error).await? {
+            Some(selector) => {
+                let text = self.page.js_inner_text(selector).await?;
+                let text = text.trim();
+                (!text.is_empty()).then(|| text.to_string())
+            }
+            None => None,
+        };
+        Ok(Observed {
+            username: self.first_visible(&selectors.username).await?,
+            password: self.first_visible(&selectors.password).await?,
+            otp: self.first_visible(&selectors.otp).await?,
+            error,
+        })
+    }
+
+    async fn first_visible(&self, selectors: &[String]) -> JsResult<Option<String>> {
+        for selector in selectors {
+            if self.page.js_is_visible(selector.clone()).await? {
+                return Ok(Some(selector.clone()));
+            }
+        }
+        Ok(None)
+    }
+
+    async fn click_first_visible(&self, flow: &str, selectors: &[String]) -> JsResult<()> {
+        let selector = self.first_visible(selectors).await?.ok_or_else(|| {
+            js_err(format!(
+                "{flow} sign-in: no visible button among {selectors:?}"
+            ))
+        })?;
+        self.page
+            .click_selector(selector, ActionOptions::default())
+            .await
+    }
+}
+
+fn parse_argument<T: serde::de::DeserializeOwned>(
+    value: &Value<'_>,
+    flow: &str,
+    name: &str,
+) -> JsResult<T> {
+    let json = rquickjs_value_to_json(value).map_err(|err| {
+        js_err(format!(
+            "flows.{}: invalid {name}: {err}",
+            flow.to_lowercase()
+        ))
+    })?;
+    serde_json::from_value(json).map_err(|err| {
+        js_err(format!(
+            "flows.{}: invalid {name}: {err}",
+            flow.to_lowercase()
+        ))
+    })
+}
+
+#[rquickjs::methods]
+impl FlowsApi {
+    /// Sign in through an Okta Sign-In Widget (classic or Identity Engine).
+    pub async fn okta<'js>(&self, login: Value<'js>, options: Opt<Value<'js>>) -> JsResult<()> {
+        self.run(&OKTA, login, options.0).await
+    }
+
+    /// Sign in through a ForgeRock Access Management login page.
+    pub async fn forgerock<'js>(
+        &self,
+        login: Value<'js>,
+        options: Opt<Value<'js>>,
+    ) -> JsResult<()> {
+        self.run(&FORGEROCK, login, options.0).await
+    }
+
+    /// Sign in to an institution inside Plaid Link. Switch to the Link frame
+    /// with `page.switchToFrame` first.
+    pub async fn plaid<'js>(&self, login: Value<'js>, options: Opt<Value<'js>>) -> JsResult<()> {
+        self.run(&PLAID, login, options.0).await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn seen(username: bool, password: bool, otp: bool, error: Option<&str>) -> Observed {
+        Observed {
+            username: username.then(|| "#user".to_string()),
+            password: password.then(|| "#pass".to_string()),
+            otp: otp.then(|| "#otp".to_string()),
+            error: error.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn identifier_first_flow_fills_username_then_password() {
+        let mut progress = Progress::default();
+        assert_eq!(
+            next_step("Okta", &seen(false, false, false, None), &progress),
+            Step::Wait
+        );
+        assert_eq!(
+            next_step("Okta", &seen(true, false, false, None), &progress),
+            Step::FillUsername("#user".to_string())
+        );
+        progress.username_filled = true;
+        assert_eq!(
+            next_step("Okta", &seen(true, false, false, None), &progress),
+            Step::Wait
+        );
+        assert_eq!(
+            next_step("Okta", &seen(false, true, false, None), &progress),
+            Step::FillPassword("#pass".to_string())
+        );
+        progress.password_filled = true;
+        progress.submitted = true;
+        assert_eq!(
+            next_step("Okta", &seen(false, true, false, None), &progress),
+            Step::Wait
+        );
+        assert_eq!(
+            next_step("Okta", &seen(false, false, false, None), &progress),
+            Step::Done
+        );
+    }
+
+    #[test]
+    fn errors_after_submit_fail_and_otp_is_entered_once() {
+        let mut progress = Progress::default();
+        // Errors left over from an earlier visit do not stop a fresh attempt.
+        assert_eq!(
+            next_step(
+                "Plaid",
+                &seen(true, true, false, Some("Try again")),
+                &progress
+            ),
+            Step::FillUsername("#user".to_string())
+        );
+        progress.username_filled = true;
+        progress.password_filled = true;
+        progress.submitted = true;
+        assert_eq!(
+            next_step(
+                "Plaid",
+                &seen(false, true, false, Some("Wrong password")),
+                &progress
+            ),
+            Step::Fail("Plaid sign-in failed: Wrong password".to_string())
+        );
+        assert_eq!(
+            next_step("Plaid", &seen(false, false, true, None), &progress),
+            Step::EnterOtp("#otp".to_string())
+        );
+        progress.otp_submitted = true;
+        assert_eq!(
+            next_step("Plaid", &seen(false, false, true, None), &progress),
+            Step::Wait
+        );
+        assert_eq!(
+            next_step(
+                "Plaid",
+                &seen(false, false, true, Some("Invalid code")),
+                &progress
+            ),
+            Step::Fail("Plaid sign-in failed: Invalid code".to_string())
+        );
+    }
+
+    #[test]
+    fn custom_selectors_are_tried_before_builtin_ones() {
+        let overrides: SelectorOverrides = serde_json::from_value(serde_json::json!({
+            "username": ["#bank-user"],
+            "otpSubmit": ["#verify"],
+        }))
+        .unwrap();
+        let selectors = OKTA.selectors(overrides);
+        assert_eq!(selectors.username[0], "#bank-user");
+        assert_eq!(selectors.username[1], OKTA.username[0]);
+        assert_eq!(selectors.otp_submit[0], "#verify");
+        assert_eq!(selectors.password.len(), OKTA.password.len());
+
+        let options: FlowOptions = serde_json::from_value(serde_json::json!({
+            "timeoutMs": 5000,
+            "noMfa": true,
+        }))
+        .unwrap();
+        assert_eq!(options.timeout_ms, Some(5000));
+        assert!(options.no_mfa);
+    }
+}
@@ -0,0 +1,569 @@
+//! JS-visible `page.keyboard` and `page.mouse`: individual key and mouse
+//! events sent through CDP `Input.dispatchKeyEvent` and
+//! `Input.dispatchMouseEvent`, for widgets that only react to real input,
+//! such as canvas charts and range sliders.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use chromiumoxide::cdp::browser_protocol::input::{
+    DispatchKeyEventParams, DispatchKeyEventType, DispatchMouseEventParams, DispatchMouseEventType,
+    InsertTextParams, MouseButton,
+};
+use rquickjs::{class::Trace, function::Opt, JsLifetime, Result as JsResult, Value};
+use tokio::sync::Mutex;
+
+use super::js_api::{js_err, resolve_secret_if_applicable, PageInner};
+
+/// CDP modifier bits.
+const MODIFIER_ALT: i64 = 1;
+const MODIFIER_CONTROL: i64 = 2;
+const MODIFIER_META: i64 = 4;
+const MODIFIER_SHIFT: i64 = 8;
+
+/// Input state of one tab: the modifiers held by `keyboard.down`, and the
+/// mouse position and buttons held by `mouse`.
+#[derive(Debug, Clone, Default)]
+pub struct InputState {
+    pub modifiers: i64,
+    pub mouse_x: f64,
+    pub mouse_y: f64,
+    pub mouse_buttons: i64,
+}
+
+/// What one key sends: its DOM `key` and `code`, Windows virtual key code,
+/// the text it types (if any), and the modifier bit it sets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct KeyDefinition {
+    pub key: String,
+    pub code: String,
+    pub key_code: i64,
+    pub text: Option<String>,
+    pub modifier: i64,
+}
+
+#[rquickjs::class(rename = "Keyboard")]
+#[derive(Trace)]
+pub struct KeyboardApi {
+    #[qjs(skip_trace)]
+    inner: Arc<Mutex<PageInner>>,
+}
+
+// Safety: KeyboardApi only contains Arc<Mutex<...>> which is 'static.
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for KeyboardApi {
+    type Changed<'to> = KeyboardApi;
+}
+
+impl KeyboardApi {
+    pub fn new(inner: Arc<Mutex<PageInner>>) -> Self {
+        Self { inner }
+    }
+
+    async fn key_down(&self, key: &KeyDefinition) -> JsResult<()> {
+        let (page, modifiers) = {
+            let mut inner = self.inner.lock().await;
+            inner.input.modifiers |= key.modifier;
+            (inner.page.clone(), inner.input.modifiers)
+        };
+        // Text is only typed while no modifier other than Shift is held, so
+        // "Control+A" selects rather than inserting an "a".
+        let text = key
+            .text
+            .as_ref()
+            .filter(|_| modifiers & !MODIFIER_SHIFT == 0)
+            .map(|text| {
+                if modifiers & MODIFIER_SHIFT != 0 {
+                    text.to_uppercase()
+                } else {
+                    text.clone()
+                }
+            });
+        let mut params = DispatchKeyEventParams::builder()
+            .r#type(if text.is_some() {
+                DispatchKeyEventType::KeyDown
+            } else {
+                DispatchKeyEventType::RawKeyDown
+            })
+            .modifiers(modifiers)
+            .key(key.key.clone())
+            .code(key.code.clone())
+            .windows_virtual_key_code(key.key_code)
+            .native_virtual_key_code(key.key_code);
+        if let Some(text) = text {
+            params = params.text(text.clone()).unmodified_text(text);
+        }
+        let params = params
+            .build()
+            .map_err(|e| js_err(format!("keyboard.down invalid params: {e}")))?;
+        page.execute(params)
+            .await
+            .map_err(|e| js_err(format!("keyboard.down failed: {e}")))?;
+        Ok(())
+    }
+
+    async fn key_up(&self, key: &KeyDefinition) -> JsResult<()> {
+        let (page, modifiers) = {
+            let mut inner = self.inner.lock().await;
+            inner.input.modifiers &= !key.modifier;
+            (inner.page.clone(), inner.input.modifiers)
+        };
+        let params = DispatchKeyEventParams::builder()
+            .r#type(DispatchKeyEventType::KeyUp)
+            .modifiers(modifiers)
+            .key(key.key.clone())
+            .code(key.code.clone())
+            .windows_virtual_key_code(key.key_code)
+            .native_virtual_key_code(key.key_code)
+            .build()
+            .map_err(|e| js_err(format!("keyboard.up invalid params: {e}")))?;
+        page.execute(params)
+            .await
+            .map_err(|e| js_err(format!("keyboard.up failed: {e}")))?;
+        Ok(())
+    }
+
+    async fn insert_text(&self, text: &str) -> JsResult<()> {
+        let page = self.inner.lock().await.page.clone();
+        page.execute(InsertTextParams::new(text))
+            .await
+            .map_err(|e| js_err(format!("keyboard.insertText failed: {e}")))?;
+        Ok(())
+    }
+}
+
+#[rquickjs::methods]
+impl KeyboardApi {
+    /// Press and hold `key`. Modifiers stay held for later keys until
+    /// `up` releases them.
+    pub async fn down(&self, key: String) -> JsResult<()> {
+        self.key_down(&require_key(&key, "keyboard.down")?).await
+    }
+
+    /// Release `key`.
+    pub async fn up(&self, key: String) -> JsResult<()> {
+        self.key_up(&require_key(&key, "keyboard.up")?).await
+    }
+
+    /// Press a key or a `+`-joined combination such as `Control+A` or
+    /// `Shift+Tab`: every key goes down in order and comes up in reverse.
+    /// `options.delay` waits that many milliseconds between down and up.
+    pub async fn press(&self, keys: String, options: Opt<Value<'_>>) -> JsResult<()> {
+        let delay = parse_delay(options.0);
+        let keys = split_key_combo(&keys)
+            .into_iter()
+            .map(|key| require_key(key, "keyboard.press"))
+            .collect::<JsResult<Vec<_>>>()?;
+        for key in &keys {
+            self.key_down(key).await?;
+        }
+        if delay > 0 {
+            tokio::time::sleep(Duration::from_millis(delay)).await;
+        }
+        for key in keys.iter().rev() {
+            self.key_up(key).await?;
+        }
+        Ok(())
+    }
+
+    /// Type `text` into the focused element one key at a time. Characters
+    /// without a key are inserted as text. A declared secret name types the
+    /// secret. `options.delay` waits between keys.
+    #[qjs(rename = "type")]
+    pub async fn js_type(&self, text: String, options: Opt<Value<'_>>) -> JsResult<()> {
+        let delay = parse_delay(options.0);
+        let text = {
+            let inner = self.inner.lock().await;
+            resolve_secret_if_applicable(&inner, &text).await?
+        };
+        for ch in text.expose_secret().chars() {
+            match key_definition(&ch.to_string()).filter(|key| key.text.is_some()) {
+                Some(key) => {
+                    self.key_down(&key).await?;
+                    self.key_up(&key).await?;
+                }
+                None => self.insert_text(&ch.to_string()).await?,
+            }
+            if delay > 0 {
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+            }
+        }
+        Ok(())
+    }
+
+    /// Insert `text` into the focused element without key events.
+    #[qjs(rename = "insertText")]
+    pub async fn js_insert_text(&self, text: String) -> JsResult<()> {
+        self.insert_text(&text).await
+    }
+}
+
+#[rquickjs::class(rename = "Mouse")]
+#[derive(Trace)]
+pub struct MouseApi {
+    #[qjs(skip_trace)]
+    inner: Arc<Mutex<PageInner>>,
+}
+
+// Safety: MouseApi only contains Arc<Mutex<...>> which is 'static.
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for MouseApi {
+    type Changed<'to> = MouseApi;
+}
+
+/// `button` and `clickCount` of a mouse option object.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct MouseOptions {
+    pub button: MouseButtonChoice,
+    pub click_count: i64,
+    pub delay_ms: u64,
+    pub steps: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum MouseButtonChoice {
+    Left,
+    Right,
+    Middle,
+}
+
+impl MouseButtonChoice {
+    fn cdp(self) -> MouseButton {
+        match self {
+            MouseButtonChoice::Left => MouseButton::Left,
+            MouseButtonChoice::Right => MouseButton::Right,
+            MouseButtonChoice::Middle => MouseButton::Middle,
+        }
+    }
+
+    /// The bit this button sets in CDP `buttons`.
+    fn bit(self) -> i64 {
+        match self {
+            MouseButtonChoice::Left => 1,
+            MouseButtonChoice::Right => 2,
+            MouseButtonChoice::Middle => 4,
+        }
+    }
+}
+
+impl MouseApi {
+    pub fn new(inner: Arc<Mutex<PageInner>>) -> Self {
+        Self { inner }
+    }
+
+    async fn dispatch(
+        &self,
+        kind: DispatchMouseEventType,
+        button: Option<MouseButtonChoice>,
+        click_count: i64,
+        update: impl FnOnce(&mut InputState),
+    ) -> JsResult<()> {
+        let (page, state) = {
+            let mut inner = self.inner.lock().await;
+            update(&mut inner.input);
+            (inner.page.clone(), inner.input.clone())
+        };
+        let mut params = DispatchMouseEventParams::builder()
+            .r#type(kind)
+            .x(state.mouse_x)
+            .y(state.mouse_y)
+            .modifiers(state.modifiers)
+            .buttons(state.mouse_buttons)
+            .button(button.map_or(MouseButton::None, MouseButtonChoice::cdp));
+        if button.is_some() {
+            params = params.click_count(click_count);
+        }
+        let params = params
+            .build()
+            .map_err(|e| js_err(format!("mouse event invalid params: {e}")))?;
+        page.execute(params)
+            .await
+            .map_err(|e| js_err(format!("mouse event failed: {e}")))?;
+        Ok(())
+    }
+
+    /// Move to `(x, y)` in `steps` intermediate moves, so drag handlers see
+    /// the pointer travel.
+    pub(crate) async fn move_to(&self, x: f64, y: f64, steps: u32) -> JsResult<()> {
+        let from = {
+            let inner = self.inner.lock().await;
+            (inner.input.mouse_x, inner.input.mouse_y)
+        };
+        for (x, y) in move_path(from, (x, y), steps) {
+            self.dispatch(DispatchMouseEventType::MouseMoved, None, 0, |state| {
+                state.mouse_x = x;
+                state.mouse_y = y;
+            })
+            .await?;
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn down(&self, options: MouseOptions) -> JsResult<()> {
+        let bit = options.button.bit();
+        self.dispatch(
+            DispatchMouseEventType::MousePressed,
+            Some(options.button),
+            options.click_count,
+            |state| state.mouse_buttons |= bit,
+        )
+        .await
+    }
+
+    pub(crate) async fn up(&self, options: MouseOptions) -> JsResult<()> {
+        let bit = options.button.bit();
+        self.dispatch(
+            DispatchMouseEventType::MouseReleased,
+            Some(options.button),
+            options.click_count,
+            |state| state.mouse_buttons &= !bit,
+        )
+        .await
+    }
+}
+
+#[rquickjs::methods]
+impl MouseApi {
+    /// Move the pointer to viewport coordinates `(x, y)`. `options.steps`
+    /// splits the move into that many events.
+    #[qjs(rename = "move")]
+    pub async fn js_move(&self, x: f64, y: f64, options: Opt<Value<'_>>) -> JsResult<()> {
+        let options = parse_mouse_options(options.0)?;
+        self.move_to(x, y, options.steps).await
+    }
+
+    /// Press a button where the pointer is. `options.button` is `left`,
+    /// `right`, or `middle`; `options.clickCount` defaults to 1.
+    #[qjs(rename = "down")]
+    pub async fn js_down(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.down(parse_mouse_options(options.0)?).await
+    }
+
+    /// Release a button where the pointer is.
+    #[qjs(rename = "up")]
+    pub async fn js_up(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.up(parse_mouse_options(options.0)?).await
+    }
+
+    /// Move to `(x, y)` and click there, `options.clickCount` times
+    /// (2 for a double click). `options.delay` waits between down and up.
+    #[qjs(rename = "click")]
+    pub async fn js_click(&self, x: f64, y: f64, options: Opt<Value<'_>>) -> JsResult<()> {
+        let options = parse_mouse_options(options.0)?;
+        self.move_to(x, y, 1).await?;
+        for count in 1..=options.click_count {
+            let options = MouseOptions {
+                click_count: count,
+                ..options
+            };
+            self.down(options).await?;
+            if options.delay_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(options.delay_ms)).await;
+            }
+            self.up(options).await?;
+        }
+        Ok(())
+    }
+
+    /// Scroll by `(deltaX, deltaY)` pixels where the pointer is, for
+    /// canvas charts and lists that listen to wheel events.
+    #[qjs(rename = "wheel")]
+    pub async fn js_wheel(&self, delta_x: f64, delta_y: f64) -> JsResult<()> {
+        let (page, state) = {
+            let inner = self.inner.lock().await;
+            (inner.page.clone(), inner.input.clone())
+        };
+        let params = DispatchMouseEventParams::builder()
+            .r#type(DispatchMouseEventType::MouseWheel)
+            .x(state.mouse_x)
+            .y(state.mouse_y)
+            .modifiers(state.modifiers)
+            .delta_x(delta_x)
+            .delta_y(delta_y)
+            .build()
+            .map_err(|e| js_err(format!("mouse.wheel invalid params: {e}")))?;
+        page.execute(params)
+            .await
+            .map_err(|e| js_err(format!("mouse.wheel failed: {e}")))?;
+        Ok(())
+    }
+}
+
+/// The points a move from `from` to `to` in `steps` events passes through,
+/// ending at `to`.
+fn move_path(from: (f64, f64), to: (f64, f64), steps: u32) -> Vec<(f64, f64)> {
+    let steps = steps.max(1);
+    (1..=steps)
+        .map(|step| {
+            let t = f64::from(step) / f64::from(steps);
+            (from.0 + (to.0 - from.0) * t, from.1 + (to.1 - from.1) * t)
+        })
+        .collect()
+}
+
+pub(crate) fn parse_mouse_options(options: Option<Value<'_>>) -> JsResult<MouseOptions> {
+    let obj = options.as_ref().and_then(Value::as_object);
+    let get_f64 = |key: &str| obj.and_then(|obj| obj.get::<_, Option<f64>>(key).ok().flatten());
+    let button = match obj
+        .and_then(|obj| obj.get::<_, Option<String>>("button").ok().flatten())
+        .as_deref()
+    {
+        None | Some("left") => MouseButtonChoice::Left,
+        Some("right") => MouseButtonChoice::Right,
+        Some("middle") => MouseButtonChoice::Middle,
+        Some(other) => {
+            return Err(js_err(format!(
+                "mouse: button must be left, right, or middle, got \"{other}\""
+            )))
+        }
+    };
+    Ok(MouseOptions {
+        button,
+        click_count: get_f64("clickCount").map_or(1, |n| (n as i64).max(1)),
+        delay_ms: get_f64("delay")
+            .filter(|d| *d > 0.0)
+            .map_or(0, |d| d as u64),
+        steps: get_f64("steps").map_or(1, |n| (n as u32).max(1)),
+    })
+}
+
+fn require_key(key: &str, method: &str) -> JsResult<KeyDefinition> {
+    key_definition(key).ok_or_else(|| js_err(format!("{method}: unknown key \"{key}\"")))
+}
+
+fn parse_delay(options: Option<Value<'_>>) -> u64 {
+    options
+        .as_ref()
+        .and_then(Value::as_object)
+        .and_then(|obj| obj.get::<_, Option<f64>>("delay").ok().flatten())
+        .filter(|delay| *delay > 0.0)
+        .map_or(0, |delay| delay as u64)
+}
+
+/// Split `Control+Shift+A` into its keys. A `+` on its own or at the end
+/// (`Control++`) is the plus key.
+fn split_key_combo(combo: &str) -> Vec<&str> {
+    let mut keys = Vec::new();
+    let mut rest = combo;
+    while !rest.is_empty() {
+        let first = rest.chars().next().map_or(1, char::len_utf8);
+        match rest[first..].find('+').map(|i| i + first) {
+            Some(i) => {
+                keys.push(&rest[..i]);
+                rest = &rest[i + 1..];
+            }
+            None => {
+                keys.push(rest);
+                break;
+            }
+        }
+    }
+    keys
+}
+
+/// Key definitions following the DOM `key` names Playwright accepts:
+/// modifiers, editing and navigation keys, `F1`–`F12`, and single
+/// characters.
+pub(crate) fn key_definition(key: &str) -> Option<KeyDefinition> {
+    let define = |key: &str, code: &str, key_code: i64, text: Option<&str>, modifier: i64| {
+        Some(KeyDefinition {
+            key: key.to_string(),
+            code: code.to_string(),
+            key_code,
+            text: text.map(str::to_string),
+            modifier,
+        })
+    };
+    match key {
+        "Shift" => define("Shift", "ShiftLeft", 16, None, MODIFIER_SHIFT),
+        "Control" => define("Control", "ControlLeft", 17, None, MODIFIER_CONTROL),
+        "Alt" => define("Alt", "AltLeft", 18, None, MODIFIER_ALT),
+        "Meta" => define("Meta", "MetaLeft", 91, None, MODIFIER_META),
+        "ControlOrMeta" if cfg!(target_os = "macos") => key_definition("Meta"),
+        "ControlOrMeta" => key_definition("Control"),
+        "Enter" => define("Enter", "Enter", 13, Some("\r"), 0),
+        "Tab" => define("Tab", "Tab", 9, None, 0),
+        "Backspace" => define("Backspace", "Backspace", 8, None, 0),
+        "Delete" => define("Delete", "Delete", 46, None, 0),
+        "Escape" => define("Escape", "Escape", 27, None, 0),
+        "Insert" => define("Insert", "Insert", 45, None, 0),
+        "Home" => define("Home", "Home", 36, None, 0),
+        "End" => define("End", "End", 35, None, 0),
+        "PageUp" => define("PageUp", "PageUp", 33, None, 0),
+        "PageDown" => define("PageDown", "PageDown", 34, None, 0),
+        "ArrowLeft" => define("ArrowLeft", "ArrowLeft", 37, None, 0),
+        "ArrowUp" => define("ArrowUp", "ArrowUp", 38, None, 0),
+        "ArrowRight" => define("ArrowRight", "ArrowRight", 39, None, 0),
+        "ArrowDown" => define("ArrowDown", "ArrowDown", 40, None, 0),
+        "Space" | " " => define(" ", "Space", 32, Some(" "), 0),
+        "\n" => key_definition("Enter"),
+        "\t" => key_definition("Tab"),
+        _ => {
+            if let Some(n) = key
+                .strip_prefix('F')
+                .and_then(|n| n.parse::<i64>().ok())
+                .filter(|n| (1..=12).contains(n))
+            {
+                return define(key, key, 111 + n, None, 0);
+            }
+            let mut chars = key.chars();
+            let (Some(ch), None) = (chars.next(), chars.next()) else {
+                return None;
+            };
+            let text = ch.to_string();
+            if ch.is_ascii_alphabetic() {
+                let upper = ch.to_ascii_uppercase();
+                define(&text, &format!("Key{upper}"), upper as i64, Some(&text), 0)
+            } else if ch.is_ascii_digit() {
+                define(&text, &format!("Digit{ch}"), ch as i64, Some(&text), 0)
+            } else {
+                define(&text, "", 0, Some(&text), 0)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn key_combos_split_on_plus_but_keep_the_plus_key() {
+        assert_eq!(split_key_combo("Control+A"), vec!["Control", "A"]);
+        assert_eq!(
+            split_key_combo("Control+Shift+Tab"),
+            vec!["Control", "Shift", "Tab"]
+        );
+        assert_eq!(split_key_combo("Control++"), vec!["Control", "+"]);
+        assert_eq!(split_key_combo("+"), vec!["+"]);
+        assert_eq!(split_key_combo("Enter"), vec!["Enter"]);
+    }
+
+    #[test]
+    fn key_definitions_cover_named_keys_and_characters() {
+        let enter = key_definition("Enter").unwrap();
+        assert_eq!((enter.key_code, enter.text.as_deref()), (13, Some("\r")));
+        let a = key_definition("a").unwrap();
+        assert_eq!((a.code.as_str(), a.key_code), ("KeyA", 65));
+        assert_eq!(key_definition("7").unwrap().code, "Digit7");
+        assert_eq!(key_definition("F5").unwrap().key_code, 116);
+        assert_eq!(
+            key_definition("Control").unwrap().modifier,
+            MODIFIER_CONTROL
+        );
+        assert_eq!(key_definition("/").unwrap().text.as_deref(), Some("/"));
+        assert!(key_definition("Hyper").is_none());
+        assert!(key_definition("F13").is_none());
+    }
+
+    #[test]
+    fn mouse_moves_step_evenly_and_end_on_target() {
+        assert_eq!(move_path((0.0, 0.0), (10.0, 20.0), 1), vec![(10.0, 20.0)]);
+        assert_eq!(
+            move_path((0.0, 0.0), (10.0, 20.0), 2),
+            vec![(5.0, 10.0), (10.0, 20.0)]
+        );
+        assert_eq!(move_path((4.0, 4.0), (8.0, 8.0), 0), vec![(8.0, 8.0)]);
+    }
+}
@@ -1,20 +1,25 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::fs;
 use std::path::{Path, PathBuf};
-use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::Arc;
 
 use base64::Engine;
 use rquickjs::class::Trace;
 use rquickjs::promise::MaybePromise;
 use rquickjs::{
-    function::Opt, Class, Ctx, FromJs, Function, IntoJs, JsLifetime, Object, Persistent,
+    function::Opt, Class, Ctx, FromJs, Function, IntoJs, JsLifetime, Object, Persistent, Promise,
     Result as JsResult, TypedArray, Value,
 };
-use tokio::sync::{oneshot, Mutex};
+use tokio::sync::{mpsc, oneshot, Mutex};
 
-use super::locator::{build_role_selector, Locator};
-use crate::secret::SecretStore;
+use super::locator::{
+    build_label_selector, build_role_selector, build_text_selector, parse_action_options,
+    parse_select_targets, selector_steps_json, Action, ActionOptions, Locator, RESOLVER_JS,
+};
+use super::prompt_catalog::{PromptCatalog, PromptRequest};
+use crate::secret::{SecretCache, SecretString};
+use zeroize::Zeroizing;
 
 pub(crate) fn js_err(msg: String) -> rquickjs::Error {
     rquickjs::Error::new_from_js_message("Error", "Error", msg)
@@ -81,6 +86,9 @@ impl Default for ParsedScreenshotOptions {
     }
 }
 
+/// Global holding the function passed to `refreshmint.setLoginHandler`.
+const LOGIN_HANDLER_GLOBAL: &str = "__refreshmint_login_handler__";
+
 #[derive(Debug, Clone)]
 struct GotoOptions {
     wait_until: String,
@@ -262,6 +270,7 @@ enum RequestLifecycleEvent {
     Failed,
 }
 
+#[derive(Clone)]
 enum JsNetworkMatcher {
     String(String),
     RegExp(Persistent<Value<'static>>),
@@ -273,6 +282,34 @@ struct EventWaitOptions {
     predicate: Option<Persistent<Function<'static>>>,
 }
 
+/// One `page.route` registration.
+struct RouteEntry {
+    id: u64,
+    matcher: JsNetworkMatcher,
+    /// The `url` argument as given, so `unroute` can find the route.
+    url: Persistent<Value<'static>>,
+    handler: Persistent<Function<'static>>,
+    /// Calls left before the route removes itself (`times` option).
+    remaining: Option<u32>,
+}
+
+/// Changes `route.continue()` makes to the request.
+#[derive(Debug, Default)]
+struct RouteContinueOverrides {
+    url: Option<String>,
+    method: Option<String>,
+    headers: Option<BTreeMap<String, String>>,
+    post_data: Option<Vec<u8>>,
+}
+
+/// The response `route.fulfill()` sends instead of contacting the server.
+#[derive(Debug)]
+struct RouteFulfillResponse {
+    status: i64,
+    headers: BTreeMap<String, String>,
+    body: Vec<u8>,
+}
+
 #[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize, PartialEq)]
 #[serde(rename_all = "camelCase")]
 struct RequestTiming {
@@ -459,10 +496,62 @@ pub struct PageInner {
     pub page: chromiumoxide::Page,
     pub target_id: String,
     pub browser: Arc<Mutex<chromiumoxide::browser::Browser>>,
-    pub secret_store: Arc<SecretStore>,
+    /// Session-scoped keychain reads; zeroized when the scrape ends.
+    pub secrets: Arc<SecretCache>,
     pub declared_secrets: Arc<SecretDeclarations>,
     pub download_dir: PathBuf,
     pub target_frame_id: Option<chromiumoxide::cdp::browser_protocol::page::FrameId>,
+    /// Manifest `allowedDomains` enforcement and observed-domain counts,
+    /// shared by every page of the session.
+    pub network_policy: Arc<super::network_policy::NetworkPolicy>,
+    /// When set (the default), page-level selector methods fail on ambiguous
+    /// selectors instead of using the first match (`page.setStrictSelectors`).
+    pub strict_selectors: bool,
+    /// Session download directory. Pages opened later download into
+    /// `pages/<target id>` below it, so their `waitForDownload` calls do not
+    /// pick up each other's files.
+    pub download_root: PathBuf,
+    /// Dialog and popup handlers set on this page; pages it opens inherit them.
+    pub handlers: PageHandlers,
+    /// Set once `page.close()` has closed the tab.
+    pub closed: bool,
+    /// Manifest `loginWallUrls`, shared by every page of the session.
+    pub login_wall: Arc<super::login_wall::LoginWall>,
+    /// Keys held down through `page.keyboard`.
+    pub input: super::input::InputState,
+}
+
+/// Modes from `page.setDialogHandler` and `page.setPopupHandler`.
+#[derive(Debug, Clone, Default)]
+pub struct PageHandlers {
+    /// Mode and prompt text.
+    pub dialog: Option<(String, Option<String>)>,
+    pub popup: Option<String>,
+    /// Tabs already set up by `prepare_new_page`; shared by every page of
+    /// the session so listing pages again does not stack more listeners.
+    prepared_targets: Arc<std::sync::Mutex<BTreeSet<String>>>,
+}
+
+impl PageHandlers {
+    /// Record `target_id` as set up; false if it already was.
+    fn mark_prepared(&self, target_id: &str) -> bool {
+        self.prepared_targets
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(target_id.to_string())
+    }
+
+    /// Scripts that install the handlers in a document.
+    fn scripts(&self) -> Vec<String> {
+        let mut scripts = Vec::new();
+        if let Some((mode, prompt_text)) = &self.dialog {
+            scripts.push(dialog_handler_script(mode, prompt_text.as_deref()));
+        }
+        if let Some(mode) = &self.popup {
+            scripts.push(popup_handler_script(mode));
+        }
+        scripts
+    }
 }
 
 /// JS-visible `page` object with Playwright-like API.
@@ -504,6 +593,9 @@ pub struct PageApi {
     raw_request_current_ids: Arc<std::sync::Mutex<BTreeMap<String, String>>>,
     #[qjs(skip_trace)]
     next_request_id: Arc<AtomicU64>,
+    /// `page.route` handlers, oldest first.
+    #[qjs(skip_trace)]
+    routes: Arc<Mutex<Vec<RouteEntry>>>,
 }
 
 // Safety: PageApi only contains Arc<Mutex<...>> which is 'static and has no JS lifetimes.
@@ -1210,7 +1302,11 @@ impl PageApi {
                 let candidate = build_page_api_from_template(&self.inner, tab.page).await;
                 if page_matches_event_predicate(ctx, options.predicate.as_ref(), &candidate).await?
                 {
-                    return Ok(candidate);
+                    return candidate
+                        .ensure_popup_allowed()
+                        .await?
+                        .prepare_new_page()
+                        .await;
                 }
             }
 
@@ -1619,6 +1715,7 @@ impl PageApi {
             request_timings: Arc::new(std::sync::Mutex::new(BTreeMap::new())),
             raw_request_current_ids: Arc::new(std::sync::Mutex::new(BTreeMap::new())),
             next_request_id: Arc::new(AtomicU64::new(1)),
+            routes: Arc::new(Mutex::new(Vec::new())),
         }
     }
 }
@@ -1687,7 +1784,7 @@ impl JsHandle {
         .map_err(|e| js_err(format!("JSHandle.jsonValue failed: {e}")))?;
         let mut text =
             stringify_evaluation_result(result.value.as_ref(), result.description.as_deref());
-        scrub_known_secrets(&inner.secret_store, &mut text);
+        scrub_known_secrets(&inner.secrets, &mut text);
         Ok(text)
     }
 }
@@ -1747,7 +1844,7 @@ impl ElementHandle {
         .map_err(|e| js_err(format!("ElementHandle.jsonValue failed: {e}")))?;
         let mut text =
             stringify_evaluation_result(result.value.as_ref(), result.description.as_deref());
-        scrub_known_secrets(&inner.secret_store, &mut text);
+        scrub_known_secrets(&inner.secrets, &mut text);
         Ok(text)
     }
 
@@ -1777,7 +1874,9 @@ impl ElementHandle {
         };
         let inner = self.page_inner.lock().await;
         let value_arg = CallArgument {
-            value: Some(serde_json::Value::String(actual_value)),
+            value: Some(serde_json::Value::String(
+                actual_value.expose_secret().to_string(),
+            )),
             unserializable_value: None,
             object_id: None,
         };
@@ -1913,19 +2012,17 @@ impl ElementHandle {
         use chromiumoxide::cdp::js_protocol::runtime::{CallArgument, RemoteObjectSubtype};
         let inner = self.page_inner.lock().await;
         let sel_arg = CallArgument {
-            value: Some(serde_json::Value::String(selector.clone())),
+            value: serde_json::from_str(&selector_steps_json(&selector)).ok(),
             unserializable_value: None,
             object_id: None,
         };
-        let result = call_function_on_handle(
-            &inner.page,
-            &self.object_id,
-            "function(sel) { return this.querySelector(sel); }",
-            &[sel_arg],
-            false,
-        )
-        .await
-        .map_err(|e| js_err(format!("ElementHandle.$({selector}) failed: {e}")))?;
+        let function = format!(
+            "async function(steps) {{ {RESOLVER_JS} return (await resolveLocator(steps, [this]))[0] ?? null; }}"
+        );
+        let result =
+            call_function_on_handle(&inner.page, &self.object_id, &function, &[sel_arg], false)
+                .await
+                .map_err(|e| js_err(format!("ElementHandle.$({selector}) failed: {e}")))?;
         let Some(object_id) = result.object_id else {
             return Ok(None);
         };
@@ -1945,19 +2042,17 @@ impl ElementHandle {
         use chromiumoxide::cdp::js_protocol::runtime::CallArgument;
         let inner = self.page_inner.lock().await;
         let sel_arg = CallArgument {
-            value: Some(serde_json::Value::String(selector.clone())),
+            value: serde_json::from_str(&selector_steps_json(&selector)).ok(),
             unserializable_value: None,
             object_id: None,
         };
-        let array_result = call_function_on_handle(
-            &inner.page,
-            &self.object_id,
-            "function(sel) { return Array.from(this.querySelectorAll(sel)); }",
-            &[sel_arg],
-            false,
-        )
-        .await
-        .map_err(|e| js_err(format!("ElementHandle.$$({selector}) failed: {e}")))?;
+        let function = format!(
+            "async function(steps) {{ {RESOLVER_JS} return resolveLocator(steps, [this]); }}"
+        );
+        let array_result =
+            call_function_on_handle(&inner.page, &self.object_id, &function, &[sel_arg], false)
+                .await
+                .map_err(|e| js_err(format!("ElementHandle.$$({selector}) failed: {e}")))?;
         let array_id = match array_result.object_id {
             Some(id) => id,
             None => return Ok(vec![]),
@@ -1981,6 +2076,21 @@ unsafe impl<'js> JsLifetime<'js> for FrameApi {
     type Changed<'to> = FrameApi;
 }
 
+/// `page.frameLocator(frameRef)`: locators and element methods scoped to a
+/// frame that is looked up when each action runs, not when it is created.
+#[rquickjs::class(rename = "FrameLocator")]
+#[derive(Trace, Clone)]
+pub struct FrameLocatorApi {
+    frame_ref: String,
+    #[qjs(skip_trace)]
+    page_inner: Arc<Mutex<PageInner>>,
+}
+
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for FrameLocatorApi {
+    type Changed<'to> = FrameLocatorApi;
+}
+
 #[rquickjs::class(rename = "Request")]
 #[derive(Trace, Clone)]
 pub struct RequestApi {
@@ -2036,6 +2146,31 @@ unsafe impl<'js> JsLifetime<'js> for ResponseApi {
     type Changed<'to> = ResponseApi;
 }
 
+/// A request intercepted by `page.route`, passed to the route handler.
+///
+/// The handler decides with exactly one of `continue`, `fulfill`, or
+/// `abort`; `fallback` (or returning without deciding) hands the request to
+/// the next matching route, and the request continues unchanged when no
+/// route decides.
+#[rquickjs::class(rename = "Route")]
+#[derive(Trace, Clone)]
+pub struct RouteApi {
+    #[qjs(skip_trace)]
+    request: RequestApi,
+    #[qjs(skip_trace)]
+    paused_id: chromiumoxide::cdp::browser_protocol::fetch::RequestId,
+    #[qjs(skip_trace)]
+    page: chromiumoxide::Page,
+    /// Set once a handler has continued, fulfilled, or aborted the request.
+    #[qjs(skip_trace)]
+    handled: Arc<AtomicBool>,
+}
+
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for RouteApi {
+    type Changed<'to> = RouteApi;
+}
+
 /// Return value from `evaluate` / `evaluateHandle` / `callFunction`.
 ///
 /// Serialisable primitives and JSON-safe objects are returned as their native
@@ -2152,6 +2287,186 @@ impl FrameApi {
     pub fn page(&self) -> PageApi {
         PageApi::new(self.page_inner.clone())
     }
+
+    /// Create a locator that resolves inside this frame.
+    pub fn locator(&self, selector: String) -> Locator {
+        Locator::new(self.page_inner.clone(), selector).in_frame(self.cdp_frame_id())
+    }
+
+    #[qjs(rename = "getByRole")]
+    pub fn get_by_role(&self, role: String, options: Opt<rquickjs::Value<'_>>) -> Locator {
+        self.locator(build_role_selector(&role, options.0))
+    }
+
+    #[qjs(rename = "getByText")]
+    pub fn get_by_text(
+        &self,
+        text: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> Locator {
+        self.locator(build_text_selector(text, options.0))
+    }
+
+    #[qjs(rename = "getByLabel")]
+    pub fn get_by_label(
+        &self,
+        text: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> Locator {
+        self.locator(build_label_selector(text, options.0))
+    }
+
+    pub async fn evaluate(&self, expression: String) -> JsResult<JsEvalResult> {
+        self.page()
+            .evaluate_in_frame(Some(self.cdp_frame_id()), expression)
+            .await
+    }
+
+    #[qjs(rename = "waitForSelector")]
+    pub async fn js_wait_for_selector(
+        &self,
+        selector: String,
+        timeout_ms: Option<u64>,
+    ) -> JsResult<()> {
+        self.page()
+            .wait_for_selector_in(Some(self.cdp_frame_id()), selector, timeout_ms)
+            .await
+    }
+
+    pub async fn click(&self, selector: String, options: Opt<rquickjs::Value<'_>>) -> JsResult<()> {
+        self.selector_locator(selector)
+            .await
+            .click_with_options(parse_action_options(options.0))
+            .await
+    }
+
+    pub async fn fill(
+        &self,
+        selector: String,
+        value: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        self.selector_locator(selector)
+            .await
+            .fill_with_options(value, parse_action_options(options.0))
+            .await
+    }
+
+    #[qjs(rename = "selectOption")]
+    pub async fn select_option(
+        &self,
+        selector: String,
+        values: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<Vec<String>> {
+        let targets = parse_select_targets(values)?;
+        self.selector_locator(selector)
+            .await
+            .select_option_with_options(&targets, parse_action_options(options.0))
+            .await
+    }
+
+    #[qjs(rename = "type")]
+    pub async fn js_type(
+        &self,
+        selector: String,
+        text: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        let locator = self.selector_locator(selector).await;
+        self.page()
+            .type_with_locator(locator, text, parse_action_options(options.0))
+            .await
+    }
+}
+
+impl FrameApi {
+    fn cdp_frame_id(&self) -> chromiumoxide::cdp::browser_protocol::page::FrameId {
+        self.frame_id.clone().into()
+    }
+
+    /// Like `PageApi::selector_locator`, pinned to this frame.
+    async fn selector_locator(&self, selector: String) -> Locator {
+        self.page()
+            .selector_locator(selector)
+            .await
+            .in_frame(self.cdp_frame_id())
+    }
+}
+
+#[rquickjs::methods]
+impl FrameLocatorApi {
+    /// Create a locator that resolves inside the frame.
+    pub fn locator(&self, selector: String) -> Locator {
+        Locator::new(self.page_inner.clone(), selector).in_frame_ref(self.frame_ref.clone())
+    }
+
+    #[qjs(rename = "getByRole")]
+    pub fn get_by_role(&self, role: String, options: Opt<rquickjs::Value<'_>>) -> Locator {
+        self.locator(build_role_selector(&role, options.0))
+    }
+
+    #[qjs(rename = "getByText")]
+    pub fn get_by_text(
+        &self,
+        text: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> Locator {
+        self.locator(build_text_selector(text, options.0))
+    }
+
+    #[qjs(rename = "getByLabel")]
+    pub fn get_by_label(
+        &self,
+        text: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> Locator {
+        self.locator(build_label_selector(text, options.0))
+    }
+
+    #[qjs(rename = "waitForSelector")]
+    pub async fn js_wait_for_selector(
+        &self,
+        selector: String,
+        timeout_ms: Option<u64>,
+    ) -> JsResult<()> {
+        let page = self.page_inner.lock().await.page.clone();
+        let frame_id = wait_for_frame_ref(&page, &self.frame_ref)
+            .await
+            .map_err(|e| js_err(format!("waitForSelector failed: {e}")))?;
+        PageApi::new(self.page_inner.clone())
+            .wait_for_selector_in(Some(frame_id), selector, timeout_ms)
+            .await
+    }
+
+    pub async fn click(&self, selector: String, options: Opt<rquickjs::Value<'_>>) -> JsResult<()> {
+        self.selector_locator(selector)
+            .await
+            .click_with_options(parse_action_options(options.0))
+            .await
+    }
+
+    pub async fn fill(
+        &self,
+        selector: String,
+        value: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        self.selector_locator(selector)
+            .await
+            .fill_with_options(value, parse_action_options(options.0))
+            .await
+    }
+}
+
+impl FrameLocatorApi {
+    /// Like `PageApi::selector_locator`, scoped to the frame.
+    async fn selector_locator(&self, selector: String) -> Locator {
+        PageApi::new(self.page_inner.clone())
+            .selector_locator(selector)
+            .await
+            .in_frame_ref(self.frame_ref.clone())
+    }
 }
 
 #[rquickjs::methods]
@@ -2538,6 +2853,66 @@ impl ResponseApi {
     }
 }
 
+#[rquickjs::methods]
+impl RouteApi {
+    pub fn request(&self) -> RequestApi {
+        self.request.clone()
+    }
+
+    /// Send the request on, optionally with a different `url`, `method`,
+    /// `headers` (replacing all of them), or `postData`.
+    #[qjs(rename = "continue")]
+    pub async fn js_continue(&self, options: Opt<rquickjs::Value<'_>>) -> JsResult<()> {
+        let overrides = parse_route_continue_options(options.0.as_ref())?;
+        self.claim()?;
+        continue_paused_request(&self.page, self.paused_id.clone(), overrides)
+            .await
+            .map_err(js_err)
+    }
+
+    /// Answer the request without contacting the server. Options: `status`
+    /// (default 200), `headers`, `contentType`, and `body` (string or
+    /// Uint8Array) or `json`.
+    pub async fn fulfill(&self, options: Opt<rquickjs::Value<'_>>) -> JsResult<()> {
+        let response = parse_route_fulfill_options(options.0.as_ref())?;
+        self.claim()?;
+        fulfill_paused_request(&self.page, self.paused_id.clone(), response)
+            .await
+            .map_err(js_err)
+    }
+
+    /// Fail the request with a Playwright error code (default `failed`).
+    pub async fn abort(&self, error_code: Opt<String>) -> JsResult<()> {
+        let code = error_code.0.unwrap_or_else(|| "failed".to_string());
+        let reason = route_abort_reason(&code)
+            .ok_or_else(|| js_err(format!("route.abort: unknown error code '{code}'")))?;
+        self.claim()?;
+        use chromiumoxide::cdp::browser_protocol::fetch::FailRequestParams;
+        self.page
+            .execute(FailRequestParams::new(self.paused_id.clone(), reason))
+            .await
+            .map(drop)
+            .map_err(|e| js_err(format!("route.abort failed: {e}")))
+    }
+
+    /// Leave the request to the next matching route.
+    pub fn fallback(&self) -> JsResult<()> {
+        if self.handled.load(Ordering::SeqCst) {
+            return Err(js_err("Route is already handled!".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl RouteApi {
+    fn claim(&self) -> JsResult<()> {
+        if self.handled.swap(true, Ordering::SeqCst) {
+            return Err(js_err("Route is already handled!".to_string()));
+        }
+        Ok(())
+    }
+}
+
 #[rquickjs::methods]
 impl PageApi {
     /// Wait for a response matching `url_pattern` and return its body as a string.
@@ -2623,128 +2998,91 @@ impl PageApi {
         Locator::new(self.inner.clone(), selector)
     }
 
+    /// Create a locator for elements containing the given text or matching a RegExp.
+    #[qjs(rename = "getByText")]
+    pub fn get_by_text(
+        &self,
+        text: rquickjs::Value<'_>,
+        options: rquickjs::function::Opt<rquickjs::Value<'_>>,
+    ) -> Locator {
+        let selector = build_text_selector(text, options.0);
+        Locator::new(self.inner.clone(), selector)
+    }
+
+    /// Create a locator for form controls whose label matches the given text or RegExp.
+    #[qjs(rename = "getByLabel")]
+    pub fn get_by_label(
+        &self,
+        text: rquickjs::Value<'_>,
+        options: rquickjs::function::Opt<rquickjs::Value<'_>>,
+    ) -> Locator {
+        let selector = build_label_selector(text, options.0);
+        Locator::new(self.inner.clone(), selector)
+    }
+
     /// Navigate to a URL.
     #[qjs(rename = "goto")]
-    pub async fn js_goto(&self, url: String, options: Opt<rquickjs::Value<'_>>) -> JsResult<()> {
-        let GotoOptions {
-            wait_until,
-            timeout_ms,
-        } = parse_goto_options(options.0)?;
-        let deadline = goto_deadline(timeout_ms);
-        let current_url = self.current_url().await?;
+    pub async fn js_goto<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        url: String,
+        options: Opt<rquickjs::Value<'js>>,
+    ) -> JsResult<()> {
+        let options = parse_goto_options(options.0)?;
+        self.goto_with_options(url.clone(), options.clone()).await?;
+        // Going to the login page on purpose is not an expired session.
+        let wall = self.inner.lock().await.login_wall.clone();
+        if wall.is_enabled() && !wall.matches(&url) && self.recover_from_login_wall(&ctx).await? {
+            self.goto_with_options(url, options).await?;
+            self.ensure_past_login_wall().await?;
+        }
+        Ok(())
+    }
+
+    /// Get the current page URL.
+    pub async fn url(&self) -> JsResult<String> {
         let page = {
             let inner = self.inner.lock().await;
             inner.page.clone()
         };
-        if current_url == url {
-            if let Some(remaining) = goto_remaining(deadline, timeout_ms, &url)? {
-                tokio::time::timeout(remaining, page.reload())
-                    .await
-                    .map_err(|_| goto_timeout_err(timeout_ms, &url))?
-                    .map_err(|e| js_err(format!("goto failed (same-url reload): {e}")))?;
-            } else {
-                page.reload()
+        let url = match page.url().await {
+            Ok(url) => url.unwrap_or_default(),
+            Err(err) => {
+                let err_text = err.to_string();
+                if !is_transport_disconnected_error(&err_text) {
+                    return Err(js_err(format_browser_error("url() failed", &err_text)));
+                }
+                let refreshed = self.refresh_page_handle().await.map_err(|refresh_err| {
+                    js_err(format_browser_error(
+                        "url() page refresh failed",
+                        &refresh_err,
+                    ))
+                })?;
+                refreshed
+                    .url()
                     .await
-                    .map_err(|e| js_err(format!("goto failed (same-url reload): {e}")))?;
+                    .map_err(|retry_err| {
+                        js_err(format_browser_error("url() failed", &retry_err.to_string()))
+                    })?
+                    .unwrap_or_default()
             }
-            self.wait_for_goto_wait_until(&wait_until, deadline, timeout_ms, &url)
-                .await?;
-            self.ensure_not_browser_error_page(&url).await?;
-            return Ok(());
-        }
-
-        use chromiumoxide::cdp::browser_protocol::page::NavigateParams;
-        let params = NavigateParams::builder()
-            .url(url.clone())
-            .build()
-            .map_err(|e| js_err(format!("goto build failed: {e}")))?;
-        let nav_outcome = if let Some(remaining) = goto_remaining(deadline, timeout_ms, &url)? {
-            tokio::time::timeout(remaining, page.execute(params))
-                .await
-                .ok()
-        } else {
-            Some(page.execute(params).await)
         };
-
-        if let Some(nav_result) = nav_outcome {
-            match nav_result {
-                Ok(nav_result) => {
-                    if let Some(error_text) = nav_result.result.error_text {
-                        return Err(js_err(format!("goto failed: {error_text} at {url}")));
-                    }
-                }
-                Err(err) => {
-                    let err_text = err.to_string();
-                    if is_cdp_request_timeout(&err_text) {
-                        // Chromiumoxide wraps Page.navigate as a navigation request and can
-                        // surface a timeout before our explicit waitUntil completes.
-                        // Keep observing URL/lifecycle up to the caller's timeout.
-                    } else {
-                        return Err(js_err(format!("goto failed: {err}")));
-                    }
-                }
-            }
-        }
-
-        loop {
-            let observed = self.current_url().await?;
-            if observed != current_url {
-                break;
-            }
-            if let Some(limit) = deadline {
-                if tokio::time::Instant::now() >= limit {
-                    return Err(goto_timeout_err(timeout_ms, &url));
-                }
-            }
-            tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
-        }
-        self.wait_for_goto_wait_until(&wait_until, deadline, timeout_ms, &url)
-            .await?;
-        self.ensure_not_browser_error_page(&url).await?;
-        Ok(())
-    }
-
-    /// Get the current page URL.
-    pub async fn url(&self) -> JsResult<String> {
-        let page = {
-            let inner = self.inner.lock().await;
-            inner.page.clone()
-        };
-        let url = match page.url().await {
-            Ok(url) => url.unwrap_or_default(),
-            Err(err) => {
-                let err_text = err.to_string();
-                if !is_transport_disconnected_error(&err_text) {
-                    return Err(js_err(format_browser_error("url() failed", &err_text)));
-                }
-                let refreshed = self.refresh_page_handle().await.map_err(|refresh_err| {
-                    js_err(format_browser_error(
-                        "url() page refresh failed",
-                        &refresh_err,
-                    ))
-                })?;
-                refreshed
-                    .url()
-                    .await
-                    .map_err(|retry_err| {
-                        js_err(format_browser_error("url() failed", &retry_err.to_string()))
-                    })?
-                    .unwrap_or_default()
-            }
-        };
-        Ok(url.to_string())
-    }
+        Ok(url.to_string())
+    }
 
     /// Reload the current page.
-    pub async fn reload(&self) -> JsResult<()> {
-        let inner = self.inner.lock().await;
-        use chromiumoxide::cdp::browser_protocol::page::ReloadParams;
-        inner
-            .page
-            .execute(ReloadParams::default())
-            .await
-            .map_err(|e| js_err(format!("reload failed: {e}")))?;
-        Ok(())
+    pub async fn reload<'js>(&self, ctx: Ctx<'js>) -> JsResult<()> {
+        self.with_login_wall_recovery(&ctx, || async move {
+            let inner = self.inner.lock().await;
+            use chromiumoxide::cdp::browser_protocol::page::ReloadParams;
+            inner
+                .page
+                .execute(ReloadParams::default())
+                .await
+                .map_err(|e| js_err(format!("reload failed: {e}")))?;
+            Ok(())
+        })
+        .await
     }
 
     /// List all frames in the page as a JSON string.
@@ -2782,6 +3120,35 @@ impl PageApi {
         serde_json::to_string(&out).map_err(|e| js_err(format!("frames serialization failed: {e}")))
     }
 
+    /// Return a `Frame` handle whose element methods run in the given frame,
+    /// independent of `switchToFrame`.
+    ///
+    /// `frame_ref` may be a frame id, frame name, or frame URL substring.
+    pub async fn frame(&self, frame_ref: String) -> JsResult<FrameApi> {
+        let frame_id = self
+            .resolve_frame_id_live(&frame_ref)
+            .await
+            .map_err(|e| js_err(format!("frame failed: {e}")))?;
+        Ok(FrameApi {
+            frame_id: frame_id.as_ref().to_string(),
+            page_inner: self.inner.clone(),
+        })
+    }
+
+    /// Return a `FrameLocator` whose locators and element methods run in the
+    /// given frame, looked up again on each action. Unlike `switchToFrame` it
+    /// leaves other element methods on the page's current target, and unlike
+    /// `frame` the frame need not exist yet.
+    ///
+    /// `frame_ref` may be a frame id, frame name, or frame URL substring.
+    #[qjs(rename = "frameLocator")]
+    pub fn frame_locator(&self, frame_ref: String) -> FrameLocatorApi {
+        FrameLocatorApi {
+            frame_ref,
+            page_inner: self.inner.clone(),
+        }
+    }
+
     /// Switch subsequent element interactions to the given frame.
     ///
     /// `frame_ref` may be a frame id, frame name, or frame URL substring.
@@ -2804,52 +3171,16 @@ impl PageApi {
         Ok(())
     }
 
-    /// Wait for a CSS selector to appear in the DOM.
+    /// Wait for a selector (CSS, `role=`, `text=`, or `xpath=`) to appear in the DOM.
     #[qjs(rename = "waitForSelector")]
     pub async fn js_wait_for_selector(
         &self,
         selector: String,
         timeout_ms: Option<u64>,
     ) -> JsResult<()> {
-        let timeout_ms = timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
-        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
-        let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
-        let probe = format!(
-            r#"(() => {{
-                try {{
-                    return !!document.querySelector({selector_json});
-                }} catch (err) {{
-                    return {{ __refreshmintSelectorError: String(err) }};
-                }}
-            }})()"#
-        );
-
-        loop {
-            let res = self
-                .eval_string(probe.clone(), "waitForSelector")
-                .await
-                .map_err(|e| js_err(format!("waitForSelector failed: {e}")))?;
-            if res == "true" {
-                return Ok(());
-            }
-            if res.contains("__refreshmintSelectorError") {
-                let val: serde_json::Value = serde_json::from_str(&res).unwrap_or_default();
-                if let Some(selector_error) = val
-                    .get("__refreshmintSelectorError")
-                    .and_then(serde_json::Value::as_str)
-                {
-                    return Err(js_err(format!(
-                        "waitForSelector(\"{selector}\") failed: {selector_error}"
-                    )));
-                }
-            }
-            if tokio::time::Instant::now() >= deadline {
-                return Err(js_err(format!(
-                    "TimeoutError: waiting for selector \"{selector}\" failed: timeout {timeout_ms}ms exceeded"
-                )));
-            }
-            tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
-        }
+        let frame_id = self.inner.lock().await.target_frame_id.clone();
+        self.wait_for_selector_in(frame_id, selector, timeout_ms)
+            .await
     }
 
     /// Wait for the next navigation.
@@ -2893,6 +3224,44 @@ impl PageApi {
         }
     }
 
+    /// Poll a JS expression in the active frame until it is truthy, and
+    /// return its value. A function expression is called on each poll.
+    #[qjs(rename = "waitForFunction")]
+    pub async fn js_wait_for_function(
+        &self,
+        expression: String,
+        timeout_ms: Option<u64>,
+    ) -> JsResult<JsEvalResult> {
+        let timeout_ms = timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+        // Falsy values come back as `undefined`, which means keep polling.
+        let probe = format!(
+            r#"(async () => {{
+                let value = await (
+{expression}
+                );
+                if (typeof value === 'function') value = await value();
+                return value || undefined;
+            }})()"#
+        );
+
+        loop {
+            let result = self
+                .evaluate_in_active_context(probe.clone())
+                .await
+                .map_err(|e| js_err(format!("waitForFunction failed: {e}")))?;
+            if !matches!(result, JsEvalResult::Undefined) {
+                return Ok(result);
+            }
+            if tokio::time::Instant::now() >= deadline {
+                return Err(js_err(format!(
+                    "TimeoutError: waiting for function failed: timeout {timeout_ms}ms exceeded"
+                )));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
     /// Wait for a page load state (`load`, `domcontentloaded`, or `networkidle`).
     #[qjs(rename = "waitForLoadState")]
     pub async fn js_wait_for_load_state(
@@ -3035,6 +3404,70 @@ impl PageApi {
         }
     }
 
+    /// Intercept requests whose URL matches `url` (glob string, RegExp, or
+    /// predicate receiving the Request) and call `handler(route, request)`
+    /// for each. The most recently added matching route runs first.
+    /// `options.times` removes the route after that many calls. Routes last
+    /// until `unroute` or the end of the script.
+    pub async fn route<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        url: Value<'js>,
+        handler: Function<'js>,
+        options: Opt<rquickjs::Value<'js>>,
+    ) -> JsResult<()> {
+        let matcher = parse_wait_for_network_matcher(&ctx, url.clone(), "route")?;
+        let remaining = parse_route_times(options.0.as_ref())?;
+        if self.routes.lock().await.is_empty() {
+            self.start_route_dispatcher(&ctx).await?;
+        }
+        let entry = RouteEntry {
+            id: self.allocate_waiter_id(),
+            matcher,
+            url: Persistent::save(&ctx, url),
+            handler: Persistent::save(&ctx, handler),
+            remaining,
+        };
+        self.routes.lock().await.push(entry);
+        Ok(())
+    }
+
+    /// Remove routes added with the same `url` (and `handler`, if given).
+    pub async fn unroute<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        url: Value<'js>,
+        handler: Opt<Function<'js>>,
+    ) -> JsResult<()> {
+        let mut routes = self.routes.lock().await;
+        let mut kept = Vec::with_capacity(routes.len());
+        for entry in routes.drain(..) {
+            let same_url = route_urls_equal(&ctx, &entry.url.clone().restore(&ctx)?, &url)?;
+            let same_handler = match &handler.0 {
+                Some(handler) => entry.handler.clone().restore(&ctx)? == *handler,
+                None => true,
+            };
+            if !(same_url && same_handler) {
+                kept.push(entry);
+            }
+        }
+        *routes = kept;
+        let empty = routes.is_empty();
+        drop(routes);
+        if empty {
+            self.stop_route_dispatcher().await;
+        }
+        Ok(())
+    }
+
+    /// Remove every route on this page.
+    #[qjs(rename = "unrouteAll")]
+    pub async fn js_unroute_all(&self) -> JsResult<()> {
+        self.routes.lock().await.clear();
+        self.stop_route_dispatcher().await;
+        Ok(())
+    }
+
     /// List captured network requests as JSON.
     #[qjs(rename = "networkRequests")]
     pub async fn js_network_requests(&self) -> JsResult<String> {
@@ -3072,52 +3505,9 @@ impl PageApi {
                 "setDialogHandler mode must be accept, dismiss, or none (got {mode})"
             )));
         }
-        let mode_json =
-            serde_json::to_string(&normalized_mode).unwrap_or_else(|_| "\"none\"".to_string());
-        let prompt_json =
-            serde_json::to_string(&prompt_text).unwrap_or_else(|_| "null".to_string());
-        let script = format!(
-            r#"(() => {{
-                const mode = {mode_json};
-                const promptText = {prompt_json};
-                const state = window.__refreshmintDialogState || {{
-                    events: [],
-                    lastEvent: null,
-                    originalAlert: window.alert,
-                    originalConfirm: window.confirm,
-                    originalPrompt: window.prompt,
-                }};
-                state.mode = mode;
-                state.promptText = promptText;
-                const pushEvent = (kind, message) => {{
-                    const evt = {{ type: kind, message: String(message ?? ''), ts: Date.now() }};
-                    state.lastEvent = evt;
-                    state.events.push(evt);
-                    if (state.events.length > 500) state.events.shift();
-                }};
-                window.alert = (message) => {{
-                    pushEvent('alert', message);
-                    return undefined;
-                }};
-                window.confirm = (message) => {{
-                    pushEvent('confirm', message);
-                    if (state.mode === 'dismiss') return false;
-                    if (state.mode === 'none') return state.originalConfirm(message);
-                    return true;
-                }};
-                window.prompt = (message, defaultValue) => {{
-                    pushEvent('prompt', message);
-                    if (state.mode === 'dismiss') return null;
-                    if (state.mode === 'none') return state.originalPrompt(message, defaultValue);
-                    if (typeof state.promptText === 'string') return state.promptText;
-                    if (typeof defaultValue === 'string') return defaultValue;
-                    return '';
-                }};
-                window.__refreshmintDialogState = state;
-                return true;
-            }})()"#
-        );
-        let inner = self.inner.lock().await;
+        let script = dialog_handler_script(&normalized_mode, prompt_text.as_deref());
+        let mut inner = self.inner.lock().await;
+        inner.handlers.dialog = Some((normalized_mode, prompt_text));
         inner
             .page
             .evaluate(script)
@@ -3157,41 +3547,9 @@ impl PageApi {
                 "setPopupHandler mode must be ignore or same_tab (got {mode})"
             )));
         }
-        let mode_json =
-            serde_json::to_string(&normalized_mode).unwrap_or_else(|_| "\"ignore\"".to_string());
-        let script = format!(
-            r#"(() => {{
-                const mode = {mode_json};
-                const state = window.__refreshmintPopupState || {{
-                    events: [],
-                    originalOpen: window.open,
-                }};
-                state.mode = mode;
-                window.open = function(url, target, features) {{
-                    const popupEvent = {{
-                        url: String(url ?? ''),
-                        target: String(target ?? ''),
-                        ts: Date.now(),
-                    }};
-                    state.events.push(popupEvent);
-                    if (state.events.length > 500) state.events.shift();
-                    if (state.mode === 'same_tab' && popupEvent.url.length > 0) {{
-                        window.location.href = popupEvent.url;
-                        return null;
-                    }}
-                    if (state.mode === 'ignore') {{
-                        return null;
-                    }}
-                    if (typeof state.originalOpen === 'function') {{
-                        return state.originalOpen.call(window, url, target, features);
-                    }}
-                    return null;
-                }};
-                window.__refreshmintPopupState = state;
-                return true;
-            }})()"#
-        );
-        let inner = self.inner.lock().await;
+        let script = popup_handler_script(&normalized_mode);
+        let mut inner = self.inner.lock().await;
+        inner.handlers.popup = Some(normalized_mode);
         inner
             .page
             .evaluate(script)
@@ -3280,149 +3638,126 @@ impl PageApi {
         }
     }
 
-    /// Click an element matching the CSS selector.
-    pub async fn click(&self, selector: String) -> JsResult<()> {
-        let inner = self.inner.lock().await;
-        if let Some(frame_id) = &inner.target_frame_id {
-            // Frame context: evaluate JS click inside the frame's execution context.
-            let (context_id, session_id) =
-                wait_for_frame_execution_target(&inner.page, frame_id.clone())
-                    .await
-                    .map_err(|e| js_err(format!("click failed to get frame target: {e}")))?;
-            let selector_json = serde_json::to_string(&selector).unwrap_or_default();
-            let js = format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
-                    if (!el) throw new Error('click: element not found: ' + {selector_json});
-                    if (!el.isConnected) throw new Error('click: element is detached');
-                    el.scrollIntoView({{ block: 'center', inline: 'center', behavior: 'instant' }});
-                    el.click();
-                }})()"#
-            );
-            use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
-            let eval = EvaluateParams::builder()
-                .expression(js)
-                .context_id(context_id)
-                .await_promise(true)
-                .return_by_value(true)
-                .build()
-                .map_err(|e| js_err(format!("click invalid params: {e}")))?;
-            inner
-                .page
-                .evaluate_expression_with_session(eval, session_id)
-                .await
-                .map_err(|e| js_err(format!("click failed: {e}")))?;
-        } else {
-            drop(inner);
-            Locator::new(self.inner.clone(), selector)
-                .click_with_timeout(DEFAULT_TIMEOUT_MS)
-                .await?;
-            return Ok(());
-        }
+    /// Whether page-level selector methods (`click`, `fill`, `type`,
+    /// `innerText`, ...) fail when a selector matches more than one element.
+    /// On by default; `false` makes them use the first match instead.
+    /// Locators are always strict.
+    #[qjs(rename = "setStrictSelectors")]
+    pub async fn js_set_strict_selectors(&self, enabled: bool) -> JsResult<()> {
+        self.inner.lock().await.strict_selectors = enabled;
         Ok(())
     }
 
+    /// Click the element matching the selector with a trusted mouse click,
+    /// waiting until it is visible, stable, enabled, and not covered.
+    pub async fn click<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        selector: String,
+        options: Opt<rquickjs::Value<'js>>,
+    ) -> JsResult<()> {
+        let options = parse_action_options(options.0);
+        self.with_login_wall_recovery(&ctx, || self.click_selector(selector.clone(), options))
+            .await
+    }
+
     /// Type text into an element, character by character.
     #[qjs(rename = "type")]
-    pub async fn js_type(&self, selector: String, text: String) -> JsResult<()> {
-        let actual_text = {
-            let inner = self.inner.lock().await;
-            resolve_secret_if_applicable(&inner, &text).await?
-        };
+    pub async fn js_type(
+        &self,
+        selector: String,
+        text: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        let locator = self.selector_locator(selector).await;
+        self.type_with_locator(locator, text, parse_action_options(options.0))
+            .await
+    }
 
-        let inner = self.inner.lock().await;
-        if let Some(frame_id) = &inner.target_frame_id {
-            // Frame context: focus element via JS, then dispatch CDP key events
-            // (Input.dispatchKeyEvent is global and targets the focused element).
-            let (context_id, session_id) =
-                wait_for_frame_execution_target(&inner.page, frame_id.clone())
-                    .await
-                    .map_err(|e| js_err(format!("type failed to get frame target: {e}")))?;
-            let selector_json = serde_json::to_string(&selector).unwrap_or_default();
-            let js = format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
-                    if (!el) throw new Error('type: element not found: ' + {selector_json});
-                    el.focus();
-                    el.click();
-                }})()"#
-            );
-            use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
-            let eval = EvaluateParams::builder()
-                .expression(js)
-                .context_id(context_id)
-                .await_promise(true)
-                .return_by_value(true)
-                .build()
-                .map_err(|e| js_err(format!("type invalid params: {e}")))?;
-            inner
-                .page
-                .evaluate_expression_with_session(eval, session_id)
-                .await
-                .map_err(|e| js_err(format!("type failed: {e}")))?;
-            inner
-                .page
-                .type_str(&actual_text)
-                .await
-                .map_err(|e| js_err(format!("type failed: {e}")))?;
-        } else {
-            // Main frame: use CDP element interaction for reliable key events.
-            let element = inner
-                .page
-                .find_element(selector)
-                .await
-                .map_err(|e| js_err(format!("type find failed: {e}")))?;
-            ensure_element_receives_pointer_events(&element)
-                .await
-                .map_err(|e| js_err(format!("type click failed: {e}")))?;
-            element
-                .click()
-                .await
-                .map_err(|e| js_err(format!("type click failed: {e}")))?;
-            element
-                .type_str(&actual_text)
-                .await
-                .map_err(|e| js_err(format!("type failed: {e}")))?;
-        }
-        Ok(())
+    /// Individual key events for the focused element (`page.keyboard`).
+    #[qjs(get)]
+    pub fn keyboard(&self) -> super::input::KeyboardApi {
+        super::input::KeyboardApi::new(self.inner.clone())
     }
 
-    /// Fill an input element's value.
-    ///
-    /// If `value` matches a manifest-declared secret name for the current
-    /// top-level domain, the real secret is resolved from keychain and injected via CDP.
-    /// The JS sandbox only ever sees the placeholder name.
-    pub async fn fill(&self, selector: String, value: String) -> JsResult<()> {
-        let actual_value = {
-            let inner = self.inner.lock().await;
-            resolve_secret_if_applicable(&inner, &value).await?
-        };
-        let selector_json = serde_json::to_string(&selector).unwrap_or_default();
-        let value_json = serde_json::to_string(&actual_value).unwrap_or_default();
-        let js = format!(
-            r#"(() => {{
-                const el = document.querySelector({selector_json});
-                if (!el) throw new Error('fill: element not found: ' + {selector_json});
-                el.focus();
-                el.value = {value_json};
-                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
-                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
-            }})()"#,
-        );
-        self.evaluate_in_active_context(js)
+    /// Mouse events at viewport coordinates (`page.mouse`).
+    #[qjs(get)]
+    pub fn mouse(&self) -> super::input::MouseApi {
+        super::input::MouseApi::new(self.inner.clone())
+    }
+
+    /// Press the mouse on `source`, move to `target`, and release, for
+    /// sliders and drag-to-reorder lists. `options.steps` sets how many
+    /// moves the drag takes; `timeout` and `force` apply to both elements.
+    #[qjs(rename = "dragAndDrop")]
+    pub async fn drag_and_drop(
+        &self,
+        source: String,
+        target: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        let action_options = parse_action_options(options.0.clone());
+        let mouse_options = super::input::parse_mouse_options(options.0)?;
+        let mouse = super::input::MouseApi::new(self.inner.clone());
+        let (x, y) = self
+            .selector_locator(source)
+            .await
+            .action_point(Action::DragAndDrop, action_options)
+            .await?;
+        mouse.move_to(x, y, 1).await?;
+        mouse.down(mouse_options).await?;
+        let (x, y) = self
+            .selector_locator(target)
+            .await
+            .action_point(Action::DragAndDrop, action_options)
+            .await?;
+        mouse.move_to(x, y, mouse_options.steps).await?;
+        mouse.up(mouse_options).await
+    }
+
+    /// Fill an input element's value once it is visible, enabled, and
+    /// editable.
+    ///
+    /// If `value` matches a manifest-declared secret name for the current
+    /// top-level domain, the real secret is resolved from keychain and injected via CDP.
+    /// The JS sandbox only ever sees the placeholder name.
+    pub async fn fill(
+        &self,
+        selector: String,
+        value: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        self.selector_locator(selector)
+            .await
+            .fill_with_options(value, parse_action_options(options.0))
+            .await
+    }
+
+    /// Pick options of a `<select>` by value, label, or index, firing
+    /// `input` and `change`, and return the selected values.
+    #[qjs(rename = "selectOption")]
+    pub async fn select_option(
+        &self,
+        selector: String,
+        values: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<Vec<String>> {
+        let targets = parse_select_targets(values)?;
+        self.selector_locator(selector)
+            .await
+            .select_option_with_options(&targets, parse_action_options(options.0))
             .await
-            .map_err(|e| js_err(format!("fill failed: {e}")))?;
-        Ok(())
     }
 
     /// Get an element's innerHTML.
     #[qjs(rename = "innerHTML")]
     pub async fn js_inner_html(&self, selector: String) -> JsResult<String> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "innerHTML").await;
         self.eval_string(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) throw new Error('innerHTML: element not found: ' + {selector_json});
                     return el.innerHTML;
                 }})()"#
@@ -3436,10 +3771,11 @@ impl PageApi {
     #[qjs(rename = "innerText")]
     pub async fn js_inner_text(&self, selector: String) -> JsResult<String> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "innerText").await;
         self.eval_string(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) throw new Error('innerText: element not found: ' + {selector_json});
                     return el.innerText;
                 }})()"#
@@ -3453,10 +3789,11 @@ impl PageApi {
     #[qjs(rename = "textContent")]
     pub async fn js_text_content(&self, selector: String) -> JsResult<String> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "textContent").await;
         self.eval_string(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) throw new Error('textContent: element not found: ' + {selector_json});
                     return el.textContent ?? '';
                 }})()"#
@@ -3466,15 +3803,139 @@ impl PageApi {
         .await
     }
 
+    /// Read a `<table>` into `{ headers, rows, records }`.
+    ///
+    /// Uses the same rules as extraction of saved HTML documents: spanned
+    /// cells are repeated, cell text is whitespace-normalized, and header rows
+    /// are detected unless `options.headerRows` is given. Empty rows are
+    /// dropped unless `options.includeEmptyRows` is true.
+    #[qjs(rename = "extractTable")]
+    pub async fn js_extract_table(
+        &self,
+        selector: String,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<JsEvalResult> {
+        let options = parse_extract_table_options(options.0)?;
+        let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let options_json = serde_json::to_string(&options).unwrap_or_else(|_| "{}".to_string());
+        let steps_json = selector_steps_json(&selector);
+        self.evaluate_in_active_context(format!(
+            r#"(async () => {{
+                {RESOLVER_JS}
+                const selector = {selector_json};
+                const options = {options_json};
+                const table = (await resolveLocator({steps_json}))[0];
+                if (!table) throw new Error('extractTable: element not found: ' + selector);
+                const normalize = (text) => (text || '').replace(/\u00a0/g, ' ').replace(/\s+/g, ' ').trim();
+                const span = (cell, attr) => {{
+                    const n = parseInt((cell.getAttribute(attr) || '').trim(), 10);
+                    return Number.isFinite(n) ? Math.min(Math.max(n, 1), 1000) : 1;
+                }};
+                const rawRows = [];
+                const addRow = (row, inThead) => rawRows.push({{
+                    inThead,
+                    cells: Array.from(row.children)
+                        .filter((cell) => ['td', 'th'].includes(cell.tagName.toLowerCase()))
+                        .map((cell) => ({{
+                            text: normalize(cell.textContent),
+                            isHeader: cell.tagName.toLowerCase() === 'th',
+                            colspan: span(cell, 'colspan'),
+                            rowspan: span(cell, 'rowspan'),
+                        }})),
+                }});
+                for (const child of Array.from(table.children)) {{
+                    const tag = child.tagName.toLowerCase();
+                    if (tag === 'tr') {{
+                        addRow(child, false);
+                    }} else if (tag === 'thead' || tag === 'tbody' || tag === 'tfoot') {{
+                        for (const row of Array.from(child.children)) {{
+                            if (row.tagName.toLowerCase() === 'tr') addRow(row, tag === 'thead');
+                        }}
+                    }}
+                }}
+
+                let headerCount = options.headerRows;
+                if (headerCount === null || headerCount === undefined) {{
+                    headerCount = 0;
+                    while (
+                        headerCount < rawRows.length &&
+                        rawRows[headerCount].cells.length > 0 &&
+                        (rawRows[headerCount].inThead ||
+                            rawRows[headerCount].cells.every((cell) => cell.isHeader))
+                    ) {{
+                        headerCount++;
+                    }}
+                }}
+
+                const pending = new Map();
+                const take = (col) => {{
+                    const entry = pending.get(col);
+                    if (!entry) return null;
+                    entry.remaining--;
+                    if (entry.remaining === 0) pending.delete(col);
+                    return entry.text;
+                }};
+                let grid = rawRows.map(({{ cells }}) => {{
+                    const out = [];
+                    for (const cell of cells) {{
+                        for (let text = take(out.length); text !== null; text = take(out.length)) {{
+                            out.push(text);
+                        }}
+                        for (let i = 0; i < cell.colspan; i++) {{
+                            if (cell.rowspan > 1) {{
+                                pending.set(out.length, {{ remaining: cell.rowspan - 1, text: cell.text }});
+                            }}
+                            out.push(cell.text);
+                        }}
+                    }}
+                    for (;;) {{
+                        const cols = Array.from(pending.keys())
+                            .filter((col) => col >= out.length)
+                            .sort((a, b) => a - b);
+                        if (cols.length === 0) break;
+                        while (out.length < cols[0]) out.push('');
+                        out.push(take(cols[0]));
+                    }}
+                    return out;
+                }});
+                const width = grid.reduce((max, row) => Math.max(max, row.length), 0);
+                grid = grid.map((row) => row.concat(Array(width - row.length).fill('')));
+                const headerGrid = grid.slice(0, Math.min(headerCount, grid.length));
+                const headers = headerGrid.length === 0 ? [] : Array.from({{ length: width }}, (_, col) => {{
+                    const parts = [];
+                    for (const row of headerGrid) {{
+                        const text = row[col];
+                        if (text !== '' && parts[parts.length - 1] !== text) parts.push(text);
+                    }}
+                    return parts.join(' ');
+                }});
+                const rows = grid
+                    .slice(headerGrid.length)
+                    .filter((row) => options.includeEmptyRows || row.some((cell) => cell !== ''));
+                const records = headers.length === 0 ? [] : rows.map((row) => {{
+                    const record = {{}};
+                    headers.forEach((header, col) => {{
+                        if (header !== '') record[header] = row[col];
+                    }});
+                    return record;
+                }});
+                return {{ headers, rows, records }};
+            }})()"#
+        ))
+        .await
+        .map_err(|e| js_err(format!("extractTable failed: {e}")))
+    }
+
     /// Get an element attribute. Returns empty string if attribute is missing.
     #[qjs(rename = "getAttribute")]
     pub async fn js_get_attribute(&self, selector: String, name: String) -> JsResult<String> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "getAttribute").await;
         let name_json = serde_json::to_string(&name).unwrap_or_else(|_| "\"\"".to_string());
         self.eval_string(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) throw new Error('getAttribute: element not found: ' + {selector_json});
                     return el.getAttribute({name_json}) ?? '';
                 }})()"#
@@ -3488,10 +3949,11 @@ impl PageApi {
     #[qjs(rename = "inputValue")]
     pub async fn js_input_value(&self, selector: String) -> JsResult<String> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "inputValue").await;
         self.eval_string(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) throw new Error('inputValue: element not found: ' + {selector_json});
                     if (!('value' in el)) throw new Error('inputValue: element has no value property: ' + {selector_json});
                     return String(el.value ?? '');
@@ -3506,10 +3968,11 @@ impl PageApi {
     #[qjs(rename = "isVisible")]
     pub async fn js_is_visible(&self, selector: String) -> JsResult<bool> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "isVisible").await;
         self.eval_bool(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) return false;
                     const style = window.getComputedStyle(el);
                     if (!style) return false;
@@ -3527,10 +3990,11 @@ impl PageApi {
     #[qjs(rename = "isEnabled")]
     pub async fn js_is_enabled(&self, selector: String) -> JsResult<bool> {
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
+        let select = self.select_element_js(&selector, "isEnabled").await;
         self.eval_bool(
             format!(
-                r#"(() => {{
-                    const el = document.querySelector({selector_json});
+                r#"(async () => {{
+                    {select}
                     if (!el) return false;
                     return !el.disabled;
                 }})()"#
@@ -3540,6 +4004,62 @@ impl PageApi {
         .await
     }
 
+    /// Read the page origin's `localStorage`: the value for `key` (or
+    /// `null`), or every entry as an object when `key` is omitted.
+    #[qjs(rename = "localStorage")]
+    pub async fn js_local_storage(&self, key: Opt<String>) -> JsResult<JsEvalResult> {
+        self.evaluate_in_active_context(web_storage_read_js("localStorage", key.0.as_deref()))
+            .await
+    }
+
+    /// Set `key` in the page origin's `localStorage`; a `null` value removes
+    /// it.
+    #[qjs(rename = "setLocalStorage")]
+    pub async fn js_set_local_storage(&self, key: String, value: Option<String>) -> JsResult<()> {
+        self.evaluate_in_active_context(web_storage_write_js(
+            "localStorage",
+            &key,
+            value.as_deref(),
+        ))
+        .await
+        .map(|_| ())
+    }
+
+    /// Remove every entry from the page origin's `localStorage`.
+    #[qjs(rename = "clearLocalStorage")]
+    pub async fn js_clear_local_storage(&self) -> JsResult<()> {
+        self.evaluate_in_active_context("(() => { localStorage.clear(); })()".to_string())
+            .await
+            .map(|_| ())
+    }
+
+    /// Like `localStorage`, for the tab's `sessionStorage`.
+    #[qjs(rename = "sessionStorage")]
+    pub async fn js_session_storage(&self, key: Opt<String>) -> JsResult<JsEvalResult> {
+        self.evaluate_in_active_context(web_storage_read_js("sessionStorage", key.0.as_deref()))
+            .await
+    }
+
+    /// Like `setLocalStorage`, for the tab's `sessionStorage`.
+    #[qjs(rename = "setSessionStorage")]
+    pub async fn js_set_session_storage(&self, key: String, value: Option<String>) -> JsResult<()> {
+        self.evaluate_in_active_context(web_storage_write_js(
+            "sessionStorage",
+            &key,
+            value.as_deref(),
+        ))
+        .await
+        .map(|_| ())
+    }
+
+    /// Remove every entry from the tab's `sessionStorage`.
+    #[qjs(rename = "clearSessionStorage")]
+    pub async fn js_clear_session_storage(&self) -> JsResult<()> {
+        self.evaluate_in_active_context("(() => { sessionStorage.clear(); })()".to_string())
+            .await
+            .map(|_| ())
+    }
+
     /// Evaluate a JS expression inside a frame execution context.
     ///
     /// `frame_ref` may be a frame id, frame name, or frame URL.
@@ -3574,7 +4094,7 @@ impl PageApi {
             .map_err(|e| js_err(format!("frameEvaluate failed: {e}")))?;
         let mut eval_result = remote_object_to_eval_result(result.object().clone(), page_inner_arc);
         if let JsEvalResult::Str(ref mut s) = eval_result {
-            scrub_known_secrets(&inner.secret_store, s);
+            scrub_known_secrets(&inner.secrets, s);
         }
         Ok(eval_result)
     }
@@ -3599,12 +4119,14 @@ impl PageApi {
             .await
             .map_err(|e| js_err(format!("frameFill failed: {e}")))?;
         let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
-        let value_json =
-            serde_json::to_string(&actual_value).unwrap_or_else(|_| "\"\"".to_string());
+        let value_json = serde_json::to_string(actual_value.expose_secret())
+            .unwrap_or_else(|_| "\"\"".to_string());
         use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
+        let steps_json = selector_steps_json(&selector);
         let script = format!(
-            r#"(() => {{
-                const el = document.querySelector({selector_json});
+            r#"(async () => {{
+                {RESOLVER_JS}
+                const el = (await resolveLocator({steps_json}))[0];
                 if (!el) throw new Error('frameFill: element not found: ' + {selector_json});
                 el.focus();
                 el.value = {value_json};
@@ -3658,10 +4180,33 @@ impl PageApi {
                         if (tag === 'summary') return 'button';
                         return '';
                     };
+                    // Elements without an id or name get an XPath from the
+                    // nearest ancestor with an id, e.g. `xpath=//*[@id="tx"]/tr[3]/td[2]`.
+                    const xpathHint = (el) => {
+                        // Elements inside shadow roots cannot be reached by XPath.
+                        if (el.getRootNode() !== document) return (el.tagName || '').toLowerCase();
+                        const parts = [];
+                        let node = el;
+                        while (node && node.nodeType === Node.ELEMENT_NODE) {
+                            if (node.id && !node.id.includes('"')) {
+                                parts.unshift('//*[@id="' + node.id + '"]');
+                                return 'xpath=' + parts.join('/');
+                            }
+                            const tag = (node.tagName || '').toLowerCase();
+                            let nth = 1;
+                            let sib = node;
+                            while ((sib = sib.previousElementSibling)) {
+                                if ((sib.tagName || '').toLowerCase() === tag) nth++;
+                            }
+                            parts.unshift(tag + '[' + nth + ']');
+                            node = node.parentElement;
+                        }
+                        return 'xpath=/' + parts.join('/');
+                    };
                     const selectorHint = (el) => {
                         if (el.id) return '#' + el.id;
                         if (el.getAttribute('name')) return '[name="' + el.getAttribute('name') + '"]';
-                        return (el.tagName || '').toLowerCase();
+                        return xpathHint(el);
                     };
                     const domPath = (el) => {
                         const parts = [];
@@ -3928,19 +4473,22 @@ impl PageApi {
         }
         let mut eval_result = remote_object_to_eval_result(response.result.result, page_inner_arc);
         if let JsEvalResult::Str(ref mut s) = eval_result {
-            scrub_known_secrets(&inner.secret_store, s);
+            scrub_known_secrets(&inner.secrets, s);
         }
         Ok(eval_result)
     }
 
     /// Return the first element in the document matching `selector`, or `null`.
     ///
-    /// Equivalent to `document.querySelector(selector)`.
+    /// Like `document.querySelector(selector)`, but `selector` may also use
+    /// the `xpath=`, `text=`, `role=`, and `label=` engines.
     #[qjs(rename = "$")]
     pub async fn js_query_selector(&self, selector: String) -> JsResult<Option<ElementHandle>> {
         use chromiumoxide::cdp::js_protocol::runtime::RemoteObjectSubtype;
-        let selector_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
-        let js = format!("document.querySelector({selector_json})");
+        let steps_json = selector_steps_json(&selector);
+        let js = format!(
+            "(async () => {{ {RESOLVER_JS} return (await resolveLocator({steps_json}))[0] ?? null; }})()"
+        );
         let result = self.evaluate_in_active_context(js).await?;
         match result {
             JsEvalResult::ElementHandleResult(eh) => Ok(Some(eh)),
@@ -3958,17 +4506,19 @@ impl PageApi {
 
     /// Return all elements in the document matching `selector`.
     ///
-    /// Equivalent to `Array.from(document.querySelectorAll(selector))`.
+    /// Like `Array.from(document.querySelectorAll(selector))`, but `selector`
+    /// may also use the `xpath=`, `text=`, `role=`, and `label=` engines.
     #[qjs(rename = "$$")]
     pub async fn js_query_selector_all(&self, selector: String) -> JsResult<Vec<ElementHandle>> {
         use chromiumoxide::cdp::js_protocol::runtime::{CallArgument, EvaluateParams};
         let inner = self.inner.lock().await;
         let page_inner_arc = self.inner.clone();
 
-        // Evaluate `Array.from(document.querySelectorAll(sel))` with
-        // returnByValue:false so we get the array as a remote object.
-        let sel_json = serde_json::to_string(&selector).unwrap_or_else(|_| "\"\"".to_string());
-        let expr = format!("Array.from(document.querySelectorAll({sel_json}))");
+        // Resolve the selector with returnByValue:false so we get the array
+        // as a remote object.
+        let steps_json = selector_steps_json(&selector);
+        let expr =
+            format!("(async () => {{ {RESOLVER_JS} return resolveLocator({steps_json}); }})()");
 
         let (array_obj, session_id_opt) = if let Some(frame_id) = &inner.target_frame_id {
             let (ctx_id, session_id) =
@@ -3978,7 +4528,7 @@ impl PageApi {
             let eval = EvaluateParams::builder()
                 .expression(expr)
                 .context_id(ctx_id)
-                .await_promise(false)
+                .await_promise(true)
                 .return_by_value(false)
                 .build()
                 .map_err(|e| js_err(format!("$$({selector}) params: {e}")))?;
@@ -3991,7 +4541,7 @@ impl PageApi {
         } else {
             let eval = EvaluateParams::builder()
                 .expression(expr)
-                .await_promise(false)
+                .await_promise(true)
                 .return_by_value(false)
                 .build()
                 .map_err(|e| js_err(format!("$$({selector}) params: {e}")))?;
@@ -4043,6 +4593,36 @@ impl PageApi {
         TypedArray::new_copy(ctx, bytes).map_err(|e| js_err(format!("Page.screenshot failed: {e}")))
     }
 
+    /// Close this tab.
+    pub async fn close(&self) -> JsResult<()> {
+        let mut inner = self.inner.lock().await;
+        if inner.closed {
+            return Ok(());
+        }
+        inner
+            .page
+            .clone()
+            .close()
+            .await
+            .map_err(|e| js_err(format!("close failed: {e}")))?;
+        inner.closed = true;
+        Ok(())
+    }
+
+    /// Whether the tab is gone, closed by `close()`, the site, or the user.
+    #[qjs(rename = "isClosed")]
+    pub async fn js_is_closed(&self) -> JsResult<bool> {
+        let target_id = {
+            let inner = self.inner.lock().await;
+            if inner.closed {
+                return Ok(true);
+            }
+            inner.target_id.clone()
+        };
+        let tabs = self.fetch_open_tabs().await?;
+        Ok(!tabs.iter().any(|tab| tab.target_id == target_id))
+    }
+
     /// Wait for the next download to complete and return its info.
     #[qjs(rename = "waitForDownload")]
     pub async fn js_wait_for_download(&self, timeout_ms: Option<u64>) -> JsResult<DownloadInfo> {
@@ -4124,9 +4704,16 @@ impl BrowserApi {
     pub async fn pages(&self) -> JsResult<Vec<PageApi>> {
         let page = PageApi::new(self.page_inner.clone());
         let tabs = page.fetch_open_tabs().await?;
+        let own_target = self.page_inner.lock().await.target_id.clone();
         let mut out = Vec::with_capacity(tabs.len());
         for tab in tabs {
-            out.push(build_page_api_from_template(&self.page_inner, tab.page).await);
+            let is_own_tab = tab.target_id == own_target;
+            let page = build_page_api_from_template(&self.page_inner, tab.page).await;
+            out.push(if is_own_tab {
+                page
+            } else {
+                page.prepare_new_page().await?
+            });
         }
         Ok(out)
     }
@@ -4157,20 +4744,171 @@ impl BrowserApi {
 }
 
 impl PageApi {
+    /// Start handing this page's requests to its routes. The dispatcher runs
+    /// on the script's runtime until routing stops.
+    async fn start_route_dispatcher<'js>(&self, ctx: &Ctx<'js>) -> JsResult<()> {
+        let (page, policy) = {
+            let inner = self.inner.lock().await;
+            (inner.page.clone(), inner.network_policy.clone())
+        };
+        let receiver = policy
+            .start_routing(&page)
+            .await
+            .map_err(|e| js_err(format!("route: {e}")))?;
+        let page_api = self.clone();
+        let dispatch_ctx = ctx.clone();
+        ctx.spawn(async move {
+            page_api.dispatch_routes(dispatch_ctx, receiver).await;
+        });
+        Ok(())
+    }
+
+    async fn stop_route_dispatcher(&self) {
+        let (target_id, policy) = {
+            let inner = self.inner.lock().await;
+            (inner.target_id.clone(), inner.network_policy.clone())
+        };
+        policy.stop_routing(&target_id).await;
+    }
+
+    async fn dispatch_routes<'js>(
+        self,
+        ctx: Ctx<'js>,
+        mut receiver: mpsc::UnboundedReceiver<super::network_policy::PausedRequest>,
+    ) {
+        while let Some(paused) = receiver.recv().await {
+            if let Err(err) = self.route_paused_request(&ctx, &paused).await {
+                tracing::warn!(
+                    "page.route handler for {} failed: {err}",
+                    paused.request.url
+                );
+            }
+        }
+    }
+
+    /// Run the matching routes for one paused request, newest first, until
+    /// one of them decides; continue it unchanged if none does.
+    async fn route_paused_request<'js>(
+        &self,
+        ctx: &Ctx<'js>,
+        paused: &super::network_policy::PausedRequest,
+    ) -> JsResult<()> {
+        let request = self.request_api_from_paused(paused);
+        let route = RouteApi {
+            request: request.clone(),
+            paused_id: paused.request_id.clone(),
+            page: self.inner.lock().await.page.clone(),
+            handled: Arc::new(AtomicBool::new(false)),
+        };
+        let candidates: Vec<(u64, JsNetworkMatcher, Persistent<Function<'static>>)> = self
+            .routes
+            .lock()
+            .await
+            .iter()
+            .rev()
+            .map(|entry| (entry.id, entry.matcher.clone(), entry.handler.clone()))
+            .collect();
+
+        let mut outcome = Ok(());
+        for (id, matcher, handler) in candidates {
+            match request_matches_js_matcher(ctx, &matcher, &request).await {
+                Ok(true) => {}
+                Ok(false) => continue,
+                Err(err) => {
+                    outcome = Err(err);
+                    break;
+                }
+            }
+            self.count_route_call(id).await;
+            if let Err(err) = call_route_handler(ctx, handler, &route, &request).await {
+                outcome = Err(err);
+                break;
+            }
+            if route.handled.load(Ordering::SeqCst) {
+                return Ok(());
+            }
+        }
+        if !route.handled.swap(true, Ordering::SeqCst) {
+            continue_paused_request(
+                &route.page,
+                route.paused_id.clone(),
+                RouteContinueOverrides::default(),
+            )
+            .await
+            .map_err(js_err)?;
+        }
+        outcome
+    }
+
+    /// Use up one call of a `times`-limited route.
+    async fn count_route_call(&self, id: u64) {
+        let mut routes = self.routes.lock().await;
+        let Some(index) = routes.iter().position(|entry| entry.id == id) else {
+            return;
+        };
+        let Some(remaining) = routes[index].remaining.as_mut() else {
+            return;
+        };
+        *remaining = remaining.saturating_sub(1);
+        if *remaining > 0 {
+            return;
+        }
+        routes.remove(index);
+        let empty = routes.is_empty();
+        drop(routes);
+        if empty {
+            self.stop_route_dispatcher().await;
+        }
+    }
+
+    fn request_api_from_paused(
+        &self,
+        paused: &chromiumoxide::cdp::browser_protocol::fetch::EventRequestPaused,
+    ) -> RequestApi {
+        let raw_request_id = paused
+            .network_id
+            .as_ref()
+            .map(|id| id.as_ref().to_string())
+            .unwrap_or_else(|| paused.request_id.as_ref().to_string());
+        let resource_type = paused.resource_type.as_ref().to_ascii_lowercase();
+        RequestApi {
+            request_id: raw_request_id.clone(),
+            raw_request_id,
+            url: paused.request.url.clone(),
+            method: paused.request.method.clone(),
+            is_navigation_request: resource_type == "document",
+            resource_type,
+            headers: headers_to_map(Some(&paused.request.headers)),
+            frame_id: Some(paused.frame_id.as_ref().to_string()),
+            post_data: paused.request.post_data.clone(),
+            redirected_from: None,
+            error: None,
+            finished: false,
+            timing: RequestTiming::default_playwright(),
+            page_api: self.clone(),
+        }
+    }
+
     /// Evaluate `expression` in the active frame context (or the main frame if none is set).
     ///
     /// Uses `returnByValue: false` so non-serialisable results (DOM nodes, functions, …)
     /// come back as remote-object handles rather than `undefined`.
     /// Secret string values in the result are scrubbed to `[REDACTED]`.
     async fn evaluate_in_active_context(&self, expression: String) -> JsResult<JsEvalResult> {
+        let frame_id = self.inner.lock().await.target_frame_id.clone();
+        self.evaluate_in_frame(frame_id, expression).await
+    }
+
+    /// Evaluate in `frame_id`'s execution context, or the main frame's.
+    async fn evaluate_in_frame(
+        &self,
+        frame_id: Option<chromiumoxide::cdp::browser_protocol::page::FrameId>,
+        expression: String,
+    ) -> JsResult<JsEvalResult> {
         use chromiumoxide::cdp::js_protocol::runtime::EvaluateParams;
-        let (page, frame_id, secret_store) = {
+        let (page, secrets) = {
             let inner = self.inner.lock().await;
-            (
-                inner.page.clone(),
-                inner.target_frame_id.clone(),
-                inner.secret_store.clone(),
-            )
+            (inner.page.clone(), inner.secrets.clone())
         };
         let page_inner_arc = self.inner.clone();
         if let Some(frame_id) = frame_id {
@@ -4229,7 +4967,7 @@ impl PageApi {
             let mut eval_result =
                 remote_object_to_eval_result(result.object().clone(), page_inner_arc);
             if let JsEvalResult::Str(ref mut s) = eval_result {
-                scrub_known_secrets(&secret_store, s);
+                scrub_known_secrets(&secrets, s);
             }
             Ok(eval_result)
         } else {
@@ -4272,7 +5010,7 @@ impl PageApi {
             let mut eval_result =
                 remote_object_to_eval_result(result.object().clone(), page_inner_arc);
             if let JsEvalResult::Str(ref mut s) = eval_result {
-                scrub_known_secrets(&secret_store, s);
+                scrub_known_secrets(&secrets, s);
             }
             Ok(eval_result)
         }
@@ -4302,7 +5040,7 @@ impl PageApi {
                         &err_text,
                     )));
                 }
-                eprintln!(
+                tracing::warn!(
                     "tab sync failed to fetch targets: {err}; falling back to current page handle"
                 );
                 return Ok(vec![OpenTab {
@@ -4315,7 +5053,7 @@ impl PageApi {
                 }]);
             }
             Err(_) => {
-                eprintln!(
+                tracing::warn!(
                     "tab sync timed out fetching targets after {}ms; falling back to current page handle",
                     TAB_QUERY_TIMEOUT_MS
                 );
@@ -4349,7 +5087,7 @@ impl PageApi {
                         &err_text,
                     )));
                 }
-                eprintln!(
+                tracing::warn!(
                     "tab sync failed to list pages: {err}; falling back to current page handle"
                 );
                 return Ok(vec![OpenTab {
@@ -4362,7 +5100,7 @@ impl PageApi {
                 }]);
             }
             Err(_) => {
-                eprintln!(
+                tracing::warn!(
                     "tab sync timed out listing pages after {}ms; falling back to current page handle",
                     TAB_QUERY_TIMEOUT_MS
                 );
@@ -4430,122 +5168,459 @@ impl PageApi {
         };
 
         loop {
-            let tabs = self.fetch_open_tabs().await?;
-            if !tabs.iter().any(|tab| tab.target_id == opener_target) {
-                return Err(js_err(
-                    "TargetClosedError: page was closed while waiting for popup".to_string(),
-                ));
-            }
-            if let Some(popup_tab) = tabs.iter().find(|tab| {
-                tab.target_id != opener_target
-                    && tab.opener_target_id.as_deref() == Some(opener_target.as_str())
-            }) {
-                return Ok(build_page_api_from_template(&self.inner, popup_tab.page.clone()).await);
+            let tabs = self.fetch_open_tabs().await?;
+            if !tabs.iter().any(|tab| tab.target_id == opener_target) {
+                return Err(js_err(
+                    "TargetClosedError: page was closed while waiting for popup".to_string(),
+                ));
+            }
+            if let Some(popup_tab) = tabs.iter().find(|tab| {
+                tab.target_id != opener_target
+                    && tab.opener_target_id.as_deref() == Some(opener_target.as_str())
+            }) {
+                return build_page_api_from_template(&self.inner, popup_tab.page.clone())
+                    .await
+                    .ensure_popup_allowed()
+                    .await?
+                    .prepare_new_page()
+                    .await;
+            }
+            if let Some(popup_tab) = tabs.iter().find(|tab| tab.target_id != opener_target) {
+                return build_page_api_from_template(&self.inner, popup_tab.page.clone())
+                    .await
+                    .ensure_popup_allowed()
+                    .await?
+                    .prepare_new_page()
+                    .await;
+            }
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(js_err(format!(
+                    "TimeoutError: waitForPopup timed out after {timeout_ms}ms (no popup opened by current page)"
+                )));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    async fn current_url(&self) -> JsResult<String> {
+        let page = {
+            let inner = self.inner.lock().await;
+            inner.page.clone()
+        };
+
+        let (cdp_url, cdp_error) = match page.url().await {
+            Ok(url) => (url.map(|value| value.to_string()), None),
+            Err(error) => (None, Some(format!("{error}"))),
+        };
+
+        let runtime_url = match page
+            .evaluate(
+                "(() => { try { return String(window.location.href || ''); } catch (_) { return ''; } })()",
+            )
+            .await
+        {
+            Ok(result) => parse_runtime_location_href(result.value()),
+            Err(_) => None,
+        };
+
+        if let Some(runtime_url) = runtime_url {
+            return Ok(runtime_url);
+        }
+        if let Some(cdp_url) = cdp_url {
+            return Ok(cdp_url);
+        }
+        if let Some(cdp_error) = cdp_error {
+            return Err(js_err(format_browser_error("url() failed", &cdp_error)));
+        }
+
+        Ok(String::new())
+    }
+
+    async fn wait_for_goto_wait_until(
+        &self,
+        wait_until: &str,
+        deadline: Option<tokio::time::Instant>,
+        timeout_ms: u64,
+        url: &str,
+    ) -> JsResult<()> {
+        match wait_until {
+            "commit" => Ok(()),
+            "networkidle" => {
+                let page = {
+                    let inner = self.inner.lock().await;
+                    inner.page.clone()
+                };
+                if let Some(remaining) = goto_remaining(deadline, timeout_ms, url)? {
+                    tokio::time::timeout(remaining, page.wait_for_network_idle())
+                        .await
+                        .map_err(|_| goto_timeout_err(timeout_ms, url))?
+                        .map(|_| ())
+                        .map_err(|e| js_err(format!("waitForLoadState(networkidle) failed: {e}")))
+                } else {
+                    page.wait_for_network_idle()
+                        .await
+                        .map(|_| ())
+                        .map_err(|e| js_err(format!("waitForLoadState(networkidle) failed: {e}")))
+                }
+            }
+            "load" | "domcontentloaded" => loop {
+                let ready = match wait_until {
+                    "load" => self.ready_state_is_complete().await?,
+                    "domcontentloaded" => self.ready_state_is_interactive_or_complete().await?,
+                    _ => false,
+                };
+                if ready {
+                    return Ok(());
+                }
+                if let Some(limit) = deadline {
+                    if tokio::time::Instant::now() >= limit {
+                        return Err(goto_timeout_err(timeout_ms, url));
+                    }
+                }
+                tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+            },
+            _ => Err(js_err(format!(
+                "waitUntil: expected one of (load|domcontentloaded|networkidle|commit), got {wait_until}"
+            ))),
+        }
+    }
+
+    /// Reject `goto` to a host outside the manifest `allowedDomains`.
+    async fn ensure_navigation_allowed(&self, url: &str) -> JsResult<()> {
+        let policy = self.inner.lock().await.network_policy.clone();
+        policy.check_navigation(url, "goto").map_err(js_err)
+    }
+
+    /// Close a popup that opened outside the manifest `allowedDomains`.
+    async fn ensure_popup_allowed(self) -> JsResult<PageApi> {
+        let url = self.current_url().await?;
+        let (policy, page) = {
+            let inner = self.inner.lock().await;
+            (inner.network_policy.clone(), inner.page.clone())
+        };
+        if let Err(err) = policy.check_navigation(&url, "popup") {
+            if let Err(close_err) = page.close().await {
+                tracing::warn!("failed to close blocked popup: {close_err}");
+            }
+            return Err(js_err(err));
+        }
+        Ok(self)
+    }
+
+    /// `page.goto` without the login wall check.
+    async fn goto_with_options(&self, url: String, options: GotoOptions) -> JsResult<()> {
+        let GotoOptions {
+            wait_until,
+            timeout_ms,
+        } = options;
+        self.ensure_navigation_allowed(&url).await?;
+        let deadline = goto_deadline(timeout_ms);
+        let current_url = self.current_url().await?;
+        let page = {
+            let inner = self.inner.lock().await;
+            inner.page.clone()
+        };
+        if current_url == url {
+            if let Some(remaining) = goto_remaining(deadline, timeout_ms, &url)? {
+                tokio::time::timeout(remaining, page.reload())
+                    .await
+                    .map_err(|_| goto_timeout_err(timeout_ms, &url))?
+                    .map_err(|e| js_err(format!("goto failed (same-url reload): {e}")))?;
+            } else {
+                page.reload()
+                    .await
+                    .map_err(|e| js_err(format!("goto failed (same-url reload): {e}")))?;
+            }
+            self.wait_for_goto_wait_until(&wait_until, deadline, timeout_ms, &url)
+                .await?;
+            self.ensure_not_browser_error_page(&url).await?;
+            return Ok(());
+        }
+
+        use chromiumoxide::cdp::browser_protocol::page::NavigateParams;
+        let params = NavigateParams::builder()
+            .url(url.clone())
+            .build()
+            .map_err(|e| js_err(format!("goto build failed: {e}")))?;
+        let nav_outcome = if let Some(remaining) = goto_remaining(deadline, timeout_ms, &url)? {
+            tokio::time::timeout(remaining, page.execute(params))
+                .await
+                .ok()
+        } else {
+            Some(page.execute(params).await)
+        };
+
+        if let Some(nav_result) = nav_outcome {
+            match nav_result {
+                Ok(nav_result) => {
+                    if let Some(error_text) = nav_result.result.error_text {
+                        return Err(js_err(format!("goto failed: {error_text} at {url}")));
+                    }
+                }
+                Err(err) => {
+                    let err_text = err.to_string();
+                    if is_cdp_request_timeout(&err_text) {
+                        // Chromiumoxide wraps Page.navigate as a navigation request and can
+                        // surface a timeout before our explicit waitUntil completes.
+                        // Keep observing URL/lifecycle up to the caller's timeout.
+                    } else {
+                        return Err(js_err(format!("goto failed: {err}")));
+                    }
+                }
+            }
+        }
+
+        loop {
+            let observed = self.current_url().await?;
+            if observed != current_url {
+                break;
+            }
+            if let Some(limit) = deadline {
+                if tokio::time::Instant::now() >= limit {
+                    return Err(goto_timeout_err(timeout_ms, &url));
+                }
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+        self.wait_for_goto_wait_until(&wait_until, deadline, timeout_ms, &url)
+            .await?;
+        self.ensure_not_browser_error_page(&url).await?;
+        Ok(())
+    }
+
+    /// `page.click` without the login wall check.
+    pub(crate) async fn click_selector(
+        &self,
+        selector: String,
+        options: ActionOptions,
+    ) -> JsResult<()> {
+        self.selector_locator(selector)
+            .await
+            .click_with_options(options)
+            .await
+    }
+
+    /// Run a step that may navigate. If it ends on a login wall, run the
+    /// login handler, go back to the URL the step started from, and repeat
+    /// the step once. Steps that start on a login wall are the driver
+    /// working through the login page and are left alone.
+    pub(crate) async fn with_login_wall_recovery<'js, F, Fut>(
+        &self,
+        ctx: &Ctx<'js>,
+        step: F,
+    ) -> JsResult<()>
+    where
+        F: Fn() -> Fut,
+        Fut: std::future::Future<Output = JsResult<()>>,
+    {
+        let wall = self.inner.lock().await.login_wall.clone();
+        if !wall.is_enabled() {
+            return step().await;
+        }
+        let url_before = self.current_url().await?;
+        step().await?;
+        if wall.matches(&url_before) || !self.recover_from_login_wall(ctx).await? {
+            return Ok(());
+        }
+        self.goto_with_options(url_before, parse_goto_options(None)?)
+            .await?;
+        step().await?;
+        self.ensure_past_login_wall().await
+    }
+
+    /// After a step that may have navigated: if the page landed on a
+    /// manifest login wall and the driver registered a login handler, run it
+    /// and return true so the caller goes back to where the step started and
+    /// repeats it.
+    async fn recover_from_login_wall<'js>(&self, ctx: &Ctx<'js>) -> JsResult<bool> {
+        let wall = self.inner.lock().await.login_wall.clone();
+        let url = self.current_url().await?;
+        if !wall.blocks(&url) {
+            return Ok(false);
+        }
+        let handler: Option<Function<'js>> = ctx.globals().get(LOGIN_HANDLER_GLOBAL)?;
+        let Some(handler) = handler else {
+            tracing::debug!("on login wall {url} but no login handler is registered");
+            return Ok(false);
+        };
+        let Some(_guard) = wall.begin_login() else {
+            return Ok(false);
+        };
+        tracing::info!(
+            "session expired (landed on {url}); running the driver's login handler (re-login {})",
+            wall.relogins()
+        );
+        let result: MaybePromise<'js> = handler
+            .call(())
+            .map_err(|e| js_err(format!("login handler threw: {e}")))?;
+        result
+            .into_future::<Value<'js>>()
+            .await
+            .map_err(|e| js_err(format!("login handler failed: {e}")))?;
+        Ok(true)
+    }
+
+    /// Fail a repeated step that still ended on a login wall.
+    async fn ensure_past_login_wall(&self) -> JsResult<()> {
+        let wall = self.inner.lock().await.login_wall.clone();
+        let url = self.current_url().await?;
+        if wall.blocks(&url) {
+            return Err(js_err(format!(
+                "SessionExpiredError: still on login wall {url} after the login handler ran"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Set up a tab the driver did not open itself: start capturing its
+    /// requests and responses, and install the opener's dialog and popup
+    /// handlers in the document it is loading and in later ones.
+    /// Each tab is set up once per session.
+    async fn prepare_new_page(self) -> JsResult<PageApi> {
+        use chromiumoxide::cdp::browser_protocol::page::AddScriptToEvaluateOnNewDocumentParams;
+
+        let (page, scripts) = {
+            let inner = self.inner.lock().await;
+            if !inner.handlers.mark_prepared(&inner.target_id) {
+                return Ok(self.clone());
+            }
+            (inner.page.clone(), inner.handlers.scripts())
+        };
+        self.ensure_request_capture().await?;
+        self.ensure_response_capture().await?;
+        for script in scripts {
+            page.execute(AddScriptToEvaluateOnNewDocumentParams::new(script.clone()))
+                .await
+                .map_err(|e| js_err(format!("failed to install page handlers: {e}")))?;
+            if let Err(err) = page.evaluate(script).await {
+                // The tab may still be between documents; the new-document
+                // script covers the next one.
+                tracing::debug!("installing page handlers in current document failed: {err}");
+            }
+        }
+        Ok(self)
+    }
+
+    async fn ensure_not_browser_error_page(&self, requested_url: &str) -> JsResult<()> {
+        let observed = self.current_url().await?;
+        if is_browser_error_url(&observed) {
+            return Err(js_err(format!(
+                "goto failed: navigation to \"{requested_url}\" failed (landed on {observed})"
+            )));
+        }
+        Ok(())
+    }
+
+    /// Page-side statements binding `el` to the element `selector_json`
+    /// matches: the first match, or with strict selectors on, an error
+    /// listing every match when there is more than one.
+    async fn select_element_js(&self, selector: &str, method: &str) -> String {
+        let steps_json = selector_steps_json(selector);
+        let pick = if self.inner.lock().await.strict_selectors {
+            format!(
+                "if (matches.length > 1) throw new Error('{method}: ' + strictModeViolation(matches));"
+            )
+        } else {
+            String::new()
+        };
+        format!(
+            r#"{RESOLVER_JS}
+                    const matches = await resolveLocator({steps_json});
+                    {pick}
+                    const el = matches[0] ?? null;"#
+        )
+    }
+
+    /// Poll until `selector` matches in `frame_id` (or the main frame).
+    async fn wait_for_selector_in(
+        &self,
+        frame_id: Option<chromiumoxide::cdp::browser_protocol::page::FrameId>,
+        selector: String,
+        timeout_ms: Option<u64>,
+    ) -> JsResult<()> {
+        let timeout_ms = timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS);
+        let deadline = tokio::time::Instant::now() + std::time::Duration::from_millis(timeout_ms);
+        let steps_json = selector_steps_json(&selector);
+        let probe = format!(
+            r#"(async () => {{
+                {RESOLVER_JS}
+                try {{
+                    return (await resolveLocator({steps_json})).length > 0;
+                }} catch (err) {{
+                    return {{ __refreshmintSelectorError: String(err) }};
+                }}
+            }})()"#
+        );
+
+        loop {
+            let res = self
+                .evaluate_in_frame(frame_id.clone(), probe.clone())
+                .await
+                .map_err(|e| js_err(format!("waitForSelector failed: {e}")))?
+                .into_string_repr();
+            if res == "true" {
+                return Ok(());
             }
-            if let Some(popup_tab) = tabs.iter().find(|tab| tab.target_id != opener_target) {
-                return Ok(build_page_api_from_template(&self.inner, popup_tab.page.clone()).await);
+            if res.contains("__refreshmintSelectorError") {
+                let val: serde_json::Value = serde_json::from_str(&res).unwrap_or_default();
+                if let Some(selector_error) = val
+                    .get("__refreshmintSelectorError")
+                    .and_then(serde_json::Value::as_str)
+                {
+                    return Err(js_err(format!(
+                        "waitForSelector(\"{selector}\") failed: {selector_error}"
+                    )));
+                }
             }
-
             if tokio::time::Instant::now() >= deadline {
                 return Err(js_err(format!(
-                    "TimeoutError: waitForPopup timed out after {timeout_ms}ms (no popup opened by current page)"
+                    "TimeoutError: waiting for selector \"{selector}\" failed: timeout {timeout_ms}ms exceeded"
                 )));
             }
             tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
         }
     }
 
-    async fn current_url(&self) -> JsResult<String> {
-        let page = {
+    /// Click `locator` once it is editable, then type `text` with key events.
+    async fn type_with_locator(
+        &self,
+        locator: Locator,
+        text: String,
+        options: ActionOptions,
+    ) -> JsResult<()> {
+        locator.wait_for_actionable(Action::Type, options).await?;
+        let actual_text = {
             let inner = self.inner.lock().await;
-            inner.page.clone()
-        };
-
-        let (cdp_url, cdp_error) = match page.url().await {
-            Ok(url) => (url.map(|value| value.to_string()), None),
-            Err(error) => (None, Some(format!("{error}"))),
+            resolve_secret_if_applicable(&inner, &text).await?
         };
 
-        let runtime_url = match page
-            .evaluate(
-                "(() => { try { return String(window.location.href || ''); } catch (_) { return ''; } })()",
-            )
+        // Focus the element with a real click; Input.dispatchKeyEvent then
+        // targets the focused element in whichever frame holds it.
+        locator.click_with_options(options).await?;
+        let inner = self.inner.lock().await;
+        inner
+            .page
+            .type_str(actual_text.expose_secret())
             .await
-        {
-            Ok(result) => parse_runtime_location_href(result.value()),
-            Err(_) => None,
-        };
-
-        if let Some(runtime_url) = runtime_url {
-            return Ok(runtime_url);
-        }
-        if let Some(cdp_url) = cdp_url {
-            return Ok(cdp_url);
-        }
-        if let Some(cdp_error) = cdp_error {
-            return Err(js_err(format_browser_error("url() failed", &cdp_error)));
-        }
-
-        Ok(String::new())
+            .map_err(|e| js_err(format!("type failed: {e}")))?;
+        Ok(())
     }
 
-    async fn wait_for_goto_wait_until(
-        &self,
-        wait_until: &str,
-        deadline: Option<tokio::time::Instant>,
-        timeout_ms: u64,
-        url: &str,
-    ) -> JsResult<()> {
-        match wait_until {
-            "commit" => Ok(()),
-            "networkidle" => {
-                let page = {
-                    let inner = self.inner.lock().await;
-                    inner.page.clone()
-                };
-                if let Some(remaining) = goto_remaining(deadline, timeout_ms, url)? {
-                    tokio::time::timeout(remaining, page.wait_for_network_idle())
-                        .await
-                        .map_err(|_| goto_timeout_err(timeout_ms, url))?
-                        .map(|_| ())
-                        .map_err(|e| js_err(format!("waitForLoadState(networkidle) failed: {e}")))
-                } else {
-                    page.wait_for_network_idle()
-                        .await
-                        .map(|_| ())
-                        .map_err(|e| js_err(format!("waitForLoadState(networkidle) failed: {e}")))
-                }
-            }
-            "load" | "domcontentloaded" => loop {
-                let ready = match wait_until {
-                    "load" => self.ready_state_is_complete().await?,
-                    "domcontentloaded" => self.ready_state_is_interactive_or_complete().await?,
-                    _ => false,
-                };
-                if ready {
-                    return Ok(());
-                }
-                if let Some(limit) = deadline {
-                    if tokio::time::Instant::now() >= limit {
-                        return Err(goto_timeout_err(timeout_ms, url));
-                    }
-                }
-                tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
-            },
-            _ => Err(js_err(format!(
-                "waitUntil: expected one of (load|domcontentloaded|networkidle|commit), got {wait_until}"
-            ))),
+    /// Locator for a page-level selector method: strict unless the driver
+    /// called `setStrictSelectors(false)`, in which case it acts on the first
+    /// match and logs a warning listing every match.
+    async fn selector_locator(&self, selector: String) -> Locator {
+        let locator = Locator::new(self.inner.clone(), selector.clone());
+        if self.inner.lock().await.strict_selectors {
+            return locator;
         }
-    }
-
-    async fn ensure_not_browser_error_page(&self, requested_url: &str) -> JsResult<()> {
-        let observed = self.current_url().await?;
-        if is_browser_error_url(&observed) {
-            return Err(js_err(format!(
-                "goto failed: navigation to \"{requested_url}\" failed (landed on {observed})"
-            )));
+        if let Ok(Some(violation)) = locator.strict_mode_violation().await {
+            tracing::warn!(
+                "selector \"{selector}\" is ambiguous, using the first match: {violation}"
+            );
         }
-        Ok(())
+        locator.first()
     }
 
     async fn eval_string(&self, expression: String, _method_name: &str) -> JsResult<String> {
@@ -5284,7 +6359,7 @@ impl BrowserApi {
                 let candidate = build_page_api_from_template(&self.page_inner, tab.page).await;
                 if page_matches_event_predicate(ctx, options.predicate.as_ref(), &candidate).await?
                 {
-                    return Ok(candidate);
+                    return candidate.prepare_new_page().await;
                 }
             }
 
@@ -5294,20 +6369,122 @@ impl BrowserApi {
     }
 }
 
+/// Script that wraps `alert`/`confirm`/`prompt` for `setDialogHandler`.
+/// Safe to run more than once per document.
+fn dialog_handler_script(mode: &str, prompt_text: Option<&str>) -> String {
+    let mode_json = serde_json::to_string(mode).unwrap_or_else(|_| "\"none\"".to_string());
+    let prompt_json = serde_json::to_string(&prompt_text).unwrap_or_else(|_| "null".to_string());
+    format!(
+        r#"(() => {{
+            const mode = {mode_json};
+            const promptText = {prompt_json};
+            const state = window.__refreshmintDialogState || {{
+                events: [],
+                lastEvent: null,
+                originalAlert: window.alert,
+                originalConfirm: window.confirm,
+                originalPrompt: window.prompt,
+            }};
+            state.mode = mode;
+            state.promptText = promptText;
+            const pushEvent = (kind, message) => {{
+                const evt = {{ type: kind, message: String(message ?? ''), ts: Date.now() }};
+                state.lastEvent = evt;
+                state.events.push(evt);
+                if (state.events.length > 500) state.events.shift();
+            }};
+            window.alert = (message) => {{
+                pushEvent('alert', message);
+                return undefined;
+            }};
+            window.confirm = (message) => {{
+                pushEvent('confirm', message);
+                if (state.mode === 'dismiss') return false;
+                if (state.mode === 'none') return state.originalConfirm(message);
+                return true;
+            }};
+            window.prompt = (message, defaultValue) => {{
+                pushEvent('prompt', message);
+                if (state.mode === 'dismiss') return null;
+                if (state.mode === 'none') return state.originalPrompt(message, defaultValue);
+                if (typeof state.promptText === 'string') return state.promptText;
+                if (typeof defaultValue === 'string') return defaultValue;
+                return '';
+            }};
+            window.__refreshmintDialogState = state;
+            return true;
+        }})()"#
+    )
+}
+
+/// Script that wraps `window.open` for `setPopupHandler`.
+fn popup_handler_script(mode: &str) -> String {
+    let mode_json = serde_json::to_string(mode).unwrap_or_else(|_| "\"ignore\"".to_string());
+    format!(
+        r#"(() => {{
+            const mode = {mode_json};
+            const state = window.__refreshmintPopupState || {{
+                events: [],
+                originalOpen: window.open,
+            }};
+            state.mode = mode;
+            window.open = function(url, target, features) {{
+                const popupEvent = {{
+                    url: String(url ?? ''),
+                    target: String(target ?? ''),
+                    ts: Date.now(),
+                }};
+                state.events.push(popupEvent);
+                if (state.events.length > 500) state.events.shift();
+                if (state.mode === 'same_tab' && popupEvent.url.length > 0) {{
+                    window.location.href = popupEvent.url;
+                    return null;
+                }}
+                if (state.mode === 'ignore') {{
+                    return null;
+                }}
+                if (typeof state.originalOpen === 'function') {{
+                    return state.originalOpen.call(window, url, target, features);
+                }}
+                return null;
+            }};
+            window.__refreshmintPopupState = state;
+            return true;
+        }})()"#
+    )
+}
+
 async fn build_page_api_from_template(
     template: &Arc<Mutex<PageInner>>,
     page: chromiumoxide::Page,
 ) -> PageApi {
     let template = template.lock().await;
+    let target_id = page.target_id().as_ref().to_string();
+    let download_dir = if target_id == template.target_id {
+        template.download_dir.clone()
+    } else {
+        template.download_root.join("pages").join(&target_id)
+    };
     let page_inner = PageInner {
-        target_id: page.target_id().as_ref().to_string(),
+        target_id,
         page,
         browser: template.browser.clone(),
-        secret_store: template.secret_store.clone(),
+        secrets: template.secrets.clone(),
         declared_secrets: template.declared_secrets.clone(),
-        download_dir: template.download_dir.clone(),
+        download_dir,
         target_frame_id: None,
+        network_policy: template.network_policy.clone(),
+        strict_selectors: template.strict_selectors,
+        download_root: template.download_root.clone(),
+        handlers: template.handlers.clone(),
+        closed: false,
+        login_wall: template.login_wall.clone(),
+        input: Default::default(),
     };
+    drop(template);
+    if let Err(err) = page_inner.network_policy.install(&page_inner.page).await {
+        tracing::warn!("failed to watch network requests of new page: {err}");
+    }
     PageApi::new(Arc::new(Mutex::new(page_inner)))
 }
 
@@ -5460,16 +6637,12 @@ pub(crate) fn stringify_evaluation_result(
     }
 }
 
-pub(crate) fn scrub_known_secrets(secret_store: &SecretStore, text: &mut String) {
+pub(crate) fn scrub_known_secrets(secrets: &SecretCache, text: &mut String) {
     // Usernames are readable without biometric and are the most likely to
-    // appear in page-evaluation results.  Passwords are typed into form
-    // fields and rarely returned by JS evaluation.
-    if let Ok(usernames) = secret_store.all_usernames() {
-        for username in &usernames {
-            if !username.is_empty() {
-                *text = text.replace(username.as_str(), "[REDACTED]");
-            }
-        }
+    // appear in page-evaluation results.  Passwords are only scrubbed once a
+    // `page.fill` has resolved them, so scrubbing never prompts.
+    for value in secrets.scrub_values() {
+        *text = text.replace(value.expose_secret(), "[REDACTED]");
     }
 }
 
@@ -5741,7 +6914,7 @@ fn url_waiter_matches(url: &str, matcher: &UrlWaiterMatcher) -> bool {
 
 // Keep string-glob semantics aligned with Playwright's
 // packages/playwright-core/src/utils/isomorphic/urlMatch.ts `globToRegexPattern`.
-fn glob_to_regex_pattern(glob: &str) -> String {
+pub(crate) fn glob_to_regex_pattern(glob: &str) -> String {
     let mut tokens = String::from("^");
     let mut in_group = false;
     let chars = glob.chars().collect::<Vec<_>>();
@@ -6446,35 +7619,333 @@ pub(crate) async fn run_screenshot_capture(
         }
         Ok::<Vec<u8>, rquickjs::Error>(bytes)
     }
-    .await;
+    .await;
+
+    if background_overridden {
+        let _ = page
+            .execute(SetDefaultBackgroundColorOverrideParams::builder().build())
+            .await;
+    }
+    let _ = clear_screenshot_overrides(&page).await;
+    capture_result
+}
+
+fn headers_to_map(
+    headers: Option<&chromiumoxide::cdp::browser_protocol::network::Headers>,
+) -> BTreeMap<String, String> {
+    let Some(headers) = headers else {
+        return BTreeMap::new();
+    };
+    let Some(map) = headers.inner().as_object() else {
+        return BTreeMap::new();
+    };
+    map.iter()
+        .map(|(name, value)| {
+            let rendered = value
+                .as_str()
+                .map(str::to_string)
+                .unwrap_or_else(|| value.to_string());
+            (name.to_ascii_lowercase(), rendered)
+        })
+        .collect()
+}
+
+async fn call_route_handler<'js>(
+    ctx: &Ctx<'js>,
+    handler: Persistent<Function<'static>>,
+    route: &RouteApi,
+    request: &RequestApi,
+) -> JsResult<()> {
+    let handler = handler
+        .restore(ctx)
+        .map_err(|e| js_err(format!("failed to restore route handler: {e}")))?;
+    let route_value = Class::instance(ctx.clone(), route.clone())
+        .map(|instance| instance.into_value())
+        .map_err(|e| js_err(format!("failed to materialize route: {e}")))?;
+    let request_value = Class::instance(ctx.clone(), request.clone())
+        .map(|instance| instance.into_value())
+        .map_err(|e| js_err(format!("failed to materialize request for route: {e}")))?;
+    let result: MaybePromise<'js> = handler
+        .call((route_value, request_value))
+        .map_err(|e| js_err(format!("route handler threw: {e}")))?;
+    result
+        .into_future::<Value<'js>>()
+        .await
+        .map_err(|e| js_err(format!("route handler rejected: {e}")))?;
+    Ok(())
+}
+
+/// Whether two `page.route` url arguments name the same route: equal
+/// strings, RegExps with the same source and flags, or the same function.
+fn route_urls_equal<'js>(ctx: &Ctx<'js>, a: &Value<'js>, b: &Value<'js>) -> JsResult<bool> {
+    let compare: Function<'js> = ctx
+        .eval(
+            "(a, b) => a === b || (a instanceof RegExp && b instanceof RegExp \
+             && a.source === b.source && a.flags === b.flags)",
+        )
+        .map_err(|e| js_err(format!("failed to build route url comparison: {e}")))?;
+    compare
+        .call((a.clone(), b.clone()))
+        .map_err(|e| js_err(format!("failed to compare route urls: {e}")))
+}
+
+fn parse_route_times(option: Option<&Value<'_>>) -> JsResult<Option<u32>> {
+    let Some(option) = option else {
+        return Ok(None);
+    };
+    if option.is_undefined() || option.is_null() {
+        return Ok(None);
+    }
+    let object = Object::from_value(option.clone())
+        .map_err(|_| js_err("route options must be an object".to_string()))?;
+    let times = object
+        .get::<_, Option<i32>>("times")
+        .map_err(|e| js_err(format!("invalid route times option: {e}")))?;
+    match times {
+        Some(times) if times < 1 => Err(js_err(format!(
+            "route times must be at least 1, got {times}"
+        ))),
+        Some(times) => Ok(Some(times as u32)),
+        None => Ok(None),
+    }
+}
+
+fn parse_route_continue_options(option: Option<&Value<'_>>) -> JsResult<RouteContinueOverrides> {
+    let Some(option) = option else {
+        return Ok(RouteContinueOverrides::default());
+    };
+    if option.is_undefined() || option.is_null() {
+        return Ok(RouteContinueOverrides::default());
+    }
+    let object = Object::from_value(option.clone())
+        .map_err(|_| js_err("route.continue options must be an object".to_string()))?;
+    let post_data = object
+        .get::<_, Option<Value<'_>>>("postData")
+        .map_err(|e| js_err(format!("invalid route.continue postData: {e}")))?
+        .filter(|value| !value.is_undefined() && !value.is_null())
+        .map(|value| route_body_bytes(&value, "route.continue postData"))
+        .transpose()?;
+    Ok(RouteContinueOverrides {
+        url: object
+            .get::<_, Option<String>>("url")
+            .map_err(|e| js_err(format!("invalid route.continue url: {e}")))?,
+        method: object
+            .get::<_, Option<String>>("method")
+            .map_err(|e| js_err(format!("invalid route.continue method: {e}")))?,
+        headers: object
+            .get::<_, Option<BTreeMap<String, String>>>("headers")
+            .map_err(|e| js_err(format!("invalid route.continue headers: {e}")))?,
+        post_data,
+    })
+}
+
+fn parse_route_fulfill_options(option: Option<&Value<'_>>) -> JsResult<RouteFulfillResponse> {
+    let mut response = RouteFulfillResponse {
+        status: 200,
+        headers: BTreeMap::new(),
+        body: Vec::new(),
+    };
+    let Some(option) = option else {
+        return Ok(response);
+    };
+    if option.is_undefined() || option.is_null() {
+        return Ok(response);
+    }
+    let object = Object::from_value(option.clone())
+        .map_err(|_| js_err("route.fulfill options must be an object".to_string()))?;
+    if let Some(status) = object
+        .get::<_, Option<i64>>("status")
+        .map_err(|e| js_err(format!("invalid route.fulfill status: {e}")))?
+    {
+        response.status = status;
+    }
+    if let Some(headers) = object
+        .get::<_, Option<BTreeMap<String, String>>>("headers")
+        .map_err(|e| js_err(format!("invalid route.fulfill headers: {e}")))?
+    {
+        response.headers = headers
+            .into_iter()
+            .map(|(name, value)| (name.to_ascii_lowercase(), value))
+            .collect();
+    }
+    let json = object
+        .get::<_, Option<Value<'_>>>("json")
+        .map_err(|e| js_err(format!("invalid route.fulfill json: {e}")))?
+        .filter(|value| !value.is_undefined());
+    if let Some(json) = json {
+        let text = match option.ctx().json_stringify(json)? {
+            Some(text) => text.to_string()?,
+            None => "null".to_string(),
+        };
+        response.body = text.into_bytes();
+        response
+            .headers
+            .entry("content-type".to_string())
+            .or_insert_with(|| "application/json".to_string());
+    } else if let Some(body) = object
+        .get::<_, Option<Value<'_>>>("body")
+        .map_err(|e| js_err(format!("invalid route.fulfill body: {e}")))?
+        .filter(|value| !value.is_undefined() && !value.is_null())
+    {
+        response.body = route_body_bytes(&body, "route.fulfill body")?;
+    }
+    if let Some(content_type) = object
+        .get::<_, Option<String>>("contentType")
+        .map_err(|e| js_err(format!("invalid route.fulfill contentType: {e}")))?
+    {
+        response
+            .headers
+            .insert("content-type".to_string(), content_type);
+    }
+    Ok(response)
+}
+
+/// Bytes of a string or Uint8Array body.
+fn route_body_bytes(value: &Value<'_>, what: &str) -> JsResult<Vec<u8>> {
+    if let Some(text) = value.as_string() {
+        return Ok(text.to_string()?.into_bytes());
+    }
+    if let Ok(array) = TypedArray::<u8>::from_js(value.ctx(), value.clone()) {
+        if let Some(bytes) = array.as_bytes() {
+            return Ok(bytes.to_vec());
+        }
+    }
+    Err(js_err(format!("{what} must be a string or Uint8Array")))
+}
+
+fn route_header_entries(
+    headers: &BTreeMap<String, String>,
+) -> Vec<chromiumoxide::cdp::browser_protocol::fetch::HeaderEntry> {
+    headers
+        .iter()
+        .map(|(name, value)| {
+            chromiumoxide::cdp::browser_protocol::fetch::HeaderEntry::new(
+                name.clone(),
+                value.clone(),
+            )
+        })
+        .collect()
+}
+
+async fn continue_paused_request(
+    page: &chromiumoxide::Page,
+    request_id: chromiumoxide::cdp::browser_protocol::fetch::RequestId,
+    overrides: RouteContinueOverrides,
+) -> Result<(), String> {
+    use chromiumoxide::cdp::browser_protocol::fetch::ContinueRequestParams;
+    let mut builder = ContinueRequestParams::builder().request_id(request_id);
+    if let Some(url) = overrides.url {
+        builder = builder.url(url);
+    }
+    if let Some(method) = overrides.method {
+        builder = builder.method(method);
+    }
+    if let Some(headers) = &overrides.headers {
+        builder = builder.headers(route_header_entries(headers));
+    }
+    if let Some(post_data) = overrides.post_data {
+        builder = builder.post_data(chromiumoxide::Binary::from(
+            base64::engine::general_purpose::STANDARD.encode(post_data),
+        ));
+    }
+    let params = builder
+        .build()
+        .map_err(|e| format!("route.continue params failed: {e}"))?;
+    page.execute(params)
+        .await
+        .map(drop)
+        .map_err(|e| format!("route.continue failed: {e}"))
+}
 
-    if background_overridden {
-        let _ = page
-            .execute(SetDefaultBackgroundColorOverrideParams::builder().build())
-            .await;
+async fn fulfill_paused_request(
+    page: &chromiumoxide::Page,
+    request_id: chromiumoxide::cdp::browser_protocol::fetch::RequestId,
+    response: RouteFulfillResponse,
+) -> Result<(), String> {
+    use chromiumoxide::cdp::browser_protocol::fetch::FulfillRequestParams;
+    let params = FulfillRequestParams::builder()
+        .request_id(request_id)
+        .response_code(response.status)
+        .response_headers(route_header_entries(&response.headers))
+        .body(chromiumoxide::Binary::from(
+            base64::engine::general_purpose::STANDARD.encode(&response.body),
+        ))
+        .build()
+        .map_err(|e| format!("route.fulfill params failed: {e}"))?;
+    page.execute(params)
+        .await
+        .map(drop)
+        .map_err(|e| format!("route.fulfill failed: {e}"))
+}
+
+/// Expression reading `area` (`localStorage` or `sessionStorage`): one key's
+/// value, or all entries as an object.
+fn web_storage_read_js(area: &str, key: Option<&str>) -> String {
+    match key {
+        Some(key) => {
+            let key_json = serde_json::to_string(key).unwrap_or_else(|_| "\"\"".to_string());
+            format!("(() => {area}.getItem({key_json}))()")
+        }
+        None => format!("(() => Object.fromEntries(Object.entries({area})))()"),
     }
-    let _ = clear_screenshot_overrides(&page).await;
-    capture_result
 }
 
-fn headers_to_map(
-    headers: Option<&chromiumoxide::cdp::browser_protocol::network::Headers>,
-) -> BTreeMap<String, String> {
-    let Some(headers) = headers else {
-        return BTreeMap::new();
-    };
-    let Some(map) = headers.inner().as_object() else {
-        return BTreeMap::new();
-    };
-    map.iter()
-        .map(|(name, value)| {
-            let rendered = value
-                .as_str()
-                .map(str::to_string)
-                .unwrap_or_else(|| value.to_string());
-            (name.to_ascii_lowercase(), rendered)
-        })
-        .collect()
+/// Expression setting `key` in `area`, or removing it when `value` is `None`.
+fn web_storage_write_js(area: &str, key: &str, value: Option<&str>) -> String {
+    let key_json = serde_json::to_string(key).unwrap_or_else(|_| "\"\"".to_string());
+    match value {
+        Some(value) => {
+            let value_json = serde_json::to_string(value).unwrap_or_else(|_| "\"\"".to_string());
+            format!("(() => {{ {area}.setItem({key_json}, {value_json}); }})()")
+        }
+        None => format!("(() => {{ {area}.removeItem({key_json}); }})()"),
+    }
+}
+
+/// The network error for a Playwright `route.abort` error code.
+fn route_abort_reason(
+    code: &str,
+) -> Option<chromiumoxide::cdp::browser_protocol::network::ErrorReason> {
+    use chromiumoxide::cdp::browser_protocol::network::ErrorReason;
+    Some(match code.to_ascii_lowercase().as_str() {
+        "aborted" => ErrorReason::Aborted,
+        "accessdenied" => ErrorReason::AccessDenied,
+        "addressunreachable" => ErrorReason::AddressUnreachable,
+        "blockedbyclient" => ErrorReason::BlockedByClient,
+        "blockedbyresponse" => ErrorReason::BlockedByResponse,
+        "connectionaborted" => ErrorReason::ConnectionAborted,
+        "connectionclosed" => ErrorReason::ConnectionClosed,
+        "connectionfailed" => ErrorReason::ConnectionFailed,
+        "connectionrefused" => ErrorReason::ConnectionRefused,
+        "connectionreset" => ErrorReason::ConnectionReset,
+        "internetdisconnected" => ErrorReason::InternetDisconnected,
+        "namenotresolved" => ErrorReason::NameNotResolved,
+        "timedout" => ErrorReason::TimedOut,
+        "failed" => ErrorReason::Failed,
+        _ => return None,
+    })
+}
+
+/// Chain `promise` so every `page.route` stops when it settles. Otherwise
+/// the route dispatchers would keep the runtime alive and intercepted
+/// requests would wait on handlers that can no longer run.
+pub(crate) fn stop_routes_when_settled<'js>(
+    ctx: &Ctx<'js>,
+    page_inner: Arc<Mutex<PageInner>>,
+    promise: Promise<'js>,
+) -> JsResult<Promise<'js>> {
+    let release = Function::new(
+        ctx.clone(),
+        rquickjs::function::Async(move || {
+            let page_inner = page_inner.clone();
+            async move {
+                let policy = page_inner.lock().await.network_policy.clone();
+                policy.stop_all_routing().await;
+            }
+        }),
+    )?;
+    let finally: Function<'js> = promise.get("finally")?;
+    finally.call((rquickjs::function::This(promise), release))
 }
 
 fn header_value(headers: &BTreeMap<String, String>, name: &str) -> Option<String> {
@@ -7039,72 +8510,58 @@ pub(crate) async fn wait_for_frame_execution_target(
     }
 }
 
-async fn ensure_element_receives_pointer_events(
-    element: &chromiumoxide::Element,
-) -> Result<(), String> {
-    let check = element
-        .call_js_fn(
-            r#"function() {
-                if (!this.isConnected) return 'Node is detached from document';
-                if (this.nodeType !== Node.ELEMENT_NODE) return 'Node is not of type HTMLElement';
-                this.scrollIntoView({ block: 'center', inline: 'center', behavior: 'instant' });
-                const rect = this.getBoundingClientRect();
-                if (rect.width <= 0 || rect.height <= 0) return 'Element is not visible';
-                const x = rect.left + rect.width / 2;
-                const y = rect.top + rect.height / 2;
-                if (x < 0 || y < 0 || x > window.innerWidth || y > window.innerHeight) {
-                    return 'Element is outside of the viewport';
-                }
-                const hit = document.elementFromPoint(x, y);
-                if (!hit) return 'Element is outside of the viewport';
-                const containsComposed = (target, hit) => {
-                    // 1. Walk up the normal DOM and open shadow roots
-                    let cur = hit;
-                    while (cur) {
-                        if (cur === target) return true;
-                        cur = cur.parentNode || (cur instanceof ShadowRoot ? cur.host : null);
-                    }
-                    
-                    // 2. If the hit element has a closed shadow root, check if target is inside.
-                    const checkDeepContains = (parent, node) => {
-                        if (parent === node) return true;
-                        const root = parent.shadowRoot || parent.openOrClosedShadowRoot;
-                        if (root && checkDeepContains(root, node)) return true;
-                        for (const child of parent.children || []) {
-                            if (checkDeepContains(child, node)) return true;
-                        }
-                        return false;
-                    };
-                    
-                    return checkDeepContains(hit, target);
-                };
-                if (containsComposed(this, hit)) return '';
-                const describe = el => {
-                    if (!(el instanceof Element)) return 'Another node';
-                    let out = el.tagName.toLowerCase();
-                    if (el.id) out += '#' + el.id;
-                    if (el.classList && el.classList.length) {
-                        out += '.' + Array.from(el.classList).slice(0, 3).join('.');
-                    }
-                    return out;
-                };
-                return describe(hit) + ' intercepts pointer events';
-            }"#,
-            false,
-        )
-        .await
-        .map_err(|e| format!("pointer actionability check failed: {e}"))?;
+/// Find the frame `frame_ref` names, waiting for it to attach. As with
+/// `page.frame`, the reference may be a frame id, frame name, or frame URL
+/// substring; empty or `main` is the main frame.
+pub(crate) async fn wait_for_frame_ref(
+    page: &chromiumoxide::Page,
+    frame_ref: &str,
+) -> Result<chromiumoxide::cdp::browser_protocol::page::FrameId, String> {
+    let wanted = frame_ref.trim();
+    let deadline =
+        tokio::time::Instant::now() + std::time::Duration::from_millis(DEFAULT_TIMEOUT_MS);
 
-    let message = check
-        .result
-        .value
-        .as_ref()
-        .and_then(serde_json::Value::as_str)
-        .unwrap_or_default();
-    if message.is_empty() {
-        Ok(())
-    } else {
-        Err(message.to_string())
+    loop {
+        if wanted.is_empty() || wanted.eq_ignore_ascii_case("main") {
+            if let Some(main) = page
+                .mainframe()
+                .await
+                .map_err(|e| format!("failed to resolve main frame: {e}"))?
+            {
+                return Ok(main);
+            }
+        } else {
+            let mut frames = Vec::new();
+            for frame_id in page
+                .frames()
+                .await
+                .map_err(|e| format!("failed to list live frames: {e}"))?
+            {
+                let name = page
+                    .frame_name(frame_id.clone())
+                    .await
+                    .map_err(|e| format!("failed to query frame name: {e}"))?
+                    .unwrap_or_default();
+                let url = page
+                    .frame_url(frame_id.clone())
+                    .await
+                    .map_err(|e| format!("failed to query frame url: {e}"))?
+                    .unwrap_or_default();
+                frames.push((frame_id, name, url));
+            }
+            let found = frames
+                .iter()
+                .find(|(id, _, _)| id.as_ref() == wanted)
+                .or_else(|| frames.iter().find(|(_, name, _)| name == wanted))
+                .or_else(|| frames.iter().find(|(_, _, url)| url.contains(wanted)));
+            if let Some((frame_id, _, _)) = found {
+                return Ok(frame_id.clone());
+            }
+        }
+        if tokio::time::Instant::now() >= deadline {
+            return Err(format!("frame not found for reference '{wanted}'"));
+        }
+        tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
     }
 }
 
@@ -7120,23 +8577,23 @@ async fn ensure_element_receives_pointer_events(
 pub(crate) async fn resolve_secret_if_applicable(
     inner: &PageInner,
     value: &str,
-) -> JsResult<String> {
+) -> JsResult<SecretString> {
     let referenced_name = value.trim();
     if referenced_name.is_empty() {
-        return Ok(value.to_string());
+        return Ok(SecretString::new(value.to_string()));
     }
 
     let declared_domains = declared_domains_for_secret(&inner.declared_secrets, referenced_name);
     // Also check legacy store for unconfigured-but-stored names when fallback
     // is enabled during migration rollout.
     let legacy_known = if ENABLE_LEGACY_SECRET_FALLBACK {
-        inner.secret_store.list_legacy_entries().unwrap_or_default()
+        inner.secrets.list_legacy_entries().unwrap_or_default()
     } else {
         Vec::new()
     };
     let configured_legacy = legacy_known.iter().any(|(_, name)| name == referenced_name);
     if declared_domains.is_empty() && !configured_legacy {
-        return Ok(value.to_string());
+        return Ok(SecretString::new(value.to_string()));
     }
 
     let current_url = inner.page.url().await.ok().flatten().unwrap_or_default();
@@ -7163,10 +8620,10 @@ pub(crate) async fn resolve_secret_if_applicable(
     let username_role =
         is_username_role(&inner.declared_secrets, &top_level_domain, referenced_name);
     if username_role {
-        if let Ok(v) = inner.secret_store.get_username(&top_level_domain) {
+        if let Ok(v) = inner.secrets.get_username(&top_level_domain) {
             return Ok(v);
         }
-    } else if let Ok(v) = inner.secret_store.get_password(&top_level_domain) {
+    } else if let Ok(v) = inner.secrets.get_password(&top_level_domain) {
         return Ok(v);
     }
 
@@ -7174,14 +8631,11 @@ pub(crate) async fn resolve_secret_if_applicable(
     if ENABLE_LEGACY_SECRET_FALLBACK {
         for (domain, name) in &legacy_known {
             if name == referenced_name && domain.eq_ignore_ascii_case(&top_level_domain) {
-                return inner
-                    .secret_store
-                    .get_legacy_value(domain, name)
-                    .map_err(|e| {
-                        js_err(format!(
-                            "failed to read secret '{name}' for domain '{domain}': {e}"
-                        ))
-                    });
+                return inner.secrets.get_legacy_value(domain, name).map_err(|e| {
+                    js_err(format!(
+                        "failed to read secret '{name}' for domain '{domain}': {e}"
+                    ))
+                });
             }
         }
     }
@@ -7271,6 +8725,12 @@ pub struct SessionMetadata {
     pub date_range_end: Option<String>,
 }
 
+impl SessionMetadata {
+    pub fn is_empty(&self) -> bool {
+        self.date_range_start.is_none() && self.date_range_end.is_none()
+    }
+}
+
 /// A staged resource from `saveResource`, pending finalization.
 #[derive(Debug, Clone)]
 pub struct StagedResource {
@@ -7280,21 +8740,35 @@ pub struct StagedResource {
     pub original_url: Option<String>,
     pub mime_type: Option<String>,
     pub label: Option<String>,
+    pub document_type: Option<crate::scrape::DocumentType>,
     pub metadata: std::collections::BTreeMap<String, serde_json::Value>,
+    /// Why the saved bytes look like an error page or an empty export.
+    pub suspect: Option<String>,
+}
+
+/// A balance or holding from `reportBalance`, appended to the label's
+/// `balances.csv` when the scrape finishes.
+#[derive(Debug, Clone)]
+pub struct StagedBalance {
+    pub label: Option<String>,
+    pub snapshot: crate::balances::BalanceSnapshot,
 }
 
 /// Shared state backing the `refreshmint` JS namespace.
 pub type PromptUiHandler =
-    Arc<dyn Fn(String) -> Result<Option<String>, String> + Send + Sync + 'static>;
+    Arc<dyn Fn(PromptRequest) -> Result<Option<SecretString>, String> + Send + Sync + 'static>;
 
 pub struct RefreshmintInner {
     pub output_dir: PathBuf,
     pub prompt_overrides: PromptOverrides,
     pub prompt_requires_override: bool,
+    /// Manifest `prompts`, which `prompt()` resolves keys against.
+    pub prompt_catalog: PromptCatalog,
     pub script_options: ScriptOptions,
     pub debug_output_sink: Option<tokio::sync::mpsc::UnboundedSender<DebugOutputEvent>>,
     pub session_metadata: SessionMetadata,
     pub staged_resources: Vec<StagedResource>,
+    pub staged_balances: Vec<StagedBalance>,
     pub scrape_session_id: String,
     pub extension_name: String,
     pub account_name: String,
@@ -7303,9 +8777,13 @@ pub struct RefreshmintInner {
     /// When set, `prompt()` asks the host app for a response instead of
     /// reading from stdin.
     pub prompt_ui_handler: Option<PromptUiHandler>,
+    /// Context captured by the latest failed `refreshmint.assert`/`expect`.
+    pub assertion_failure: Option<super::assertions::AssertionFailure>,
+    /// Prompts the driver asked for, in order, for the scrape log.
+    pub prompts_asked: Vec<PromptRequest>,
 }
 
-fn resolve_prompt_response(response: Option<String>) -> JsResult<String> {
+fn resolve_prompt_response(response: Option<SecretString>) -> JsResult<SecretString> {
     match response {
         Some(answer) => Ok(answer),
         None => Err(js_err("prompt cancelled".to_string())),
@@ -7339,12 +8817,6 @@ struct AccountDocumentSummary {
     metadata: std::collections::BTreeMap<String, serde_json::Value>,
 }
 
-fn missing_prompt_override_error(message: &str) -> String {
-    format!(
-        "missing prompt value for refreshmint.prompt(\"{message}\"); supply --prompt \"{message}=VALUE\""
-    )
-}
-
 fn parse_document_filter(
     filter: Option<rquickjs::Value<'_>>,
 ) -> std::collections::BTreeMap<String, serde_json::Value> {
@@ -7396,6 +8868,9 @@ fn matches_filter(
                 .map(|s| serde_json::Value::String(s.clone())),
             "extensionName" => Some(serde_json::Value::String(info.extension_name.clone())),
             "scrapedAt" => Some(serde_json::Value::String(info.scraped_at.clone())),
+            "documentType" => info
+                .document_type
+                .map(|t| serde_json::Value::String(t.as_str().to_string())),
             _ => info.metadata.get(key).cloned(),
         };
 
@@ -7483,6 +8958,12 @@ fn collect_account_documents_in_dir(
                 if let Some(url) = info.original_url {
                     metadata.insert("originalUrl".to_string(), serde_json::Value::String(url));
                 }
+                if let Some(document_type) = info.document_type {
+                    metadata.insert(
+                        "documentType".to_string(),
+                        serde_json::Value::String(document_type.as_str().to_string()),
+                    );
+                }
                 docs.push(AccountDocumentSummary {
                     filename: relative,
                     metadata,
@@ -7503,11 +8984,43 @@ fn collect_account_documents_in_dir(
     Ok(())
 }
 
+/// What `tryExtract` found in a staged resource.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TryExtractSummary {
+    count: usize,
+    first_date: Option<String>,
+    last_date: Option<String>,
+    /// Why extraction failed; `count` is 0 then.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+impl TryExtractSummary {
+    fn from_transactions(transactions: &[crate::extract::ExtractedTransaction]) -> Self {
+        let dates = || transactions.iter().map(|txn| txn.tdate.clone());
+        Self {
+            count: transactions.len(),
+            first_date: dates().min(),
+            last_date: dates().max(),
+            error: None,
+        }
+    }
+
+    fn failed(error: String) -> Self {
+        Self {
+            error: Some(error),
+            ..Self::default()
+        }
+    }
+}
+
 struct SaveResourceOptions {
     coverage_end_date: Option<String>,
     original_url: Option<String>,
     mime_type: Option<String>,
     label: Option<String>,
+    document_type: Option<String>,
     metadata: BTreeMap<String, serde_json::Value>,
 }
 
@@ -7517,6 +9030,7 @@ fn parse_save_resource_options(options: Option<rquickjs::Value<'_>>) -> SaveReso
         original_url: None,
         mime_type: None,
         label: None,
+        document_type: None,
         metadata: BTreeMap::new(),
     };
     if let Some(opts) = options {
@@ -7535,6 +9049,9 @@ fn parse_save_resource_options(options: Option<rquickjs::Value<'_>>) -> SaveReso
                     "label" => {
                         result.label = v.as_string().and_then(|s| s.to_string().ok());
                     }
+                    "documentType" => {
+                        result.document_type = v.as_string().and_then(|s| s.to_string().ok());
+                    }
                     _ => {
                         let json_val = if v.is_null() || v.is_undefined() {
                             serde_json::Value::Null
@@ -7562,6 +9079,31 @@ fn parse_save_resource_options(options: Option<rquickjs::Value<'_>>) -> SaveReso
     result
 }
 
+fn parse_extract_table_options(
+    options: Option<rquickjs::Value<'_>>,
+) -> JsResult<crate::html_table::TableOptions> {
+    let mut result = crate::html_table::TableOptions::default();
+    if let Some(opts) = options {
+        let Some(obj) = opts.as_object() else {
+            return Err(js_err(
+                "extractTable options must be an object when provided".to_string(),
+            ));
+        };
+        if let Ok(Some(header_rows)) = obj.get::<_, Option<f64>>("headerRows") {
+            if header_rows.is_sign_negative() || header_rows.fract() != 0.0 {
+                return Err(js_err(
+                    "extractTable headerRows must be a non-negative integer".to_string(),
+                ));
+            }
+            result.header_rows = Some(header_rows as usize);
+        }
+        if let Ok(val) = obj.get::<_, Option<bool>>("includeEmptyRows") {
+            result.include_empty_rows = val.unwrap_or(false);
+        }
+    }
+    Ok(result)
+}
+
 fn parse_snapshot_options(options: Option<rquickjs::Value<'_>>) -> JsResult<SnapshotOptions> {
     let mut result = SnapshotOptions::default();
     if let Some(opts) = options {
@@ -7797,9 +9339,10 @@ impl RefreshmintApi {
 
     /// Save binary data to a file in the extension output directory.
     ///
-    /// Accepts an optional third argument: an options object with `coverageEndDate`.
-    /// Files are staged during scraping and moved to their final location
-    /// after extraction determines the coverage date.
+    /// Accepts an optional third argument: an options object with `coverageEndDate`,
+    /// `label`, and `documentType` (`statement`, `transactions-csv`, `tax-form`,
+    /// or `confirmation`). Files are staged during scraping and moved to their
+    /// final location after extraction determines the coverage date.
     #[qjs(rename = "saveResource")]
     pub async fn js_save_resource(
         &self,
@@ -7815,8 +9358,14 @@ impl RefreshmintApi {
             original_url,
             mime_type,
             label,
+            document_type,
             metadata,
         } = parse_save_resource_options(options.0);
+        let document_type = document_type
+            .as_deref()
+            .map(crate::scrape::DocumentType::parse)
+            .transpose()
+            .map_err(|e| js_err(format!("saveResource: {e}")))?;
 
         // Always save to the legacy output dir for backward compatibility
         let path = unique_output_path(&inner.output_dir, &filename);
@@ -7827,6 +9376,14 @@ impl RefreshmintApi {
         std::fs::write(&path, &data)
             .map_err(|e| js_err(format!("saveResource write failed: {e}")))?;
 
+        let declared_mime = mime_type
+            .clone()
+            .unwrap_or_else(|| crate::scrape::guess_mime_type(&filename));
+        let suspect = super::download_check::check_download(&filename, &declared_mime, &data);
+        if let Some(reason) = &suspect {
+            tracing::warn!("saveResource: {filename} looks suspect: {reason}");
+        }
+
         // Also stage the resource for the new evidence pipeline
         inner.staged_resources.push(StagedResource {
             filename: filename.clone(),
@@ -7835,7 +9392,9 @@ impl RefreshmintApi {
             original_url,
             mime_type,
             label,
+            document_type,
             metadata,
+            suspect,
         });
 
         Ok(())
@@ -7870,6 +9429,134 @@ impl RefreshmintApi {
         self.js_save_resource(final_name, data, options).await
     }
 
+    /// Save details scraped from transaction detail pages as a
+    /// `transaction-details` document. Extraction adds each record's merchant,
+    /// location, card member, tags, and comment to the transaction it matches.
+    ///
+    /// `details` is an array of `{ fitid?, date?, amount?, merchant?,
+    /// location?, cardMember?, tags?, comment? }`, each matched by `fitid` or
+    /// else by `date` and `amount`. `options` takes the `label`,
+    /// `coverageEndDate`, and `originalUrl` of `saveResource`; the coverage
+    /// end date defaults to the latest record date.
+    #[qjs(rename = "saveTransactionDetails")]
+    pub async fn js_save_transaction_details(
+        &self,
+        details: rquickjs::Value<'_>,
+        options: Opt<rquickjs::Value<'_>>,
+    ) -> JsResult<()> {
+        let json = rquickjs_value_to_json(&details)
+            .map_err(|e| js_err(format!("saveTransactionDetails: {e}")))?;
+        let details = crate::transaction_details::parse_transaction_details(&json)
+            .map_err(|e| js_err(format!("saveTransactionDetails: {e}")))?;
+        let SaveResourceOptions {
+            coverage_end_date,
+            original_url,
+            label,
+            metadata,
+            ..
+        } = parse_save_resource_options(options.0);
+        let coverage_end_date =
+            coverage_end_date.or_else(|| details.iter().filter_map(|d| d.date.clone()).max());
+        let data = serde_json::to_vec_pretty(&details)
+            .map_err(|e| js_err(format!("saveTransactionDetails: {e}")))?;
+
+        let mut inner = self.inner.lock().await;
+        let filename = "transaction-details.json".to_string();
+        let path = unique_output_path(&inner.output_dir, &filename);
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| js_err(format!("saveTransactionDetails mkdir failed: {e}")))?;
+        }
+        std::fs::write(&path, &data)
+            .map_err(|e| js_err(format!("saveTransactionDetails write failed: {e}")))?;
+        inner.staged_resources.push(StagedResource {
+            filename,
+            staging_path: path,
+            coverage_end_date,
+            original_url,
+            mime_type: Some("application/json".to_string()),
+            label,
+            document_type: Some(crate::scrape::DocumentType::TransactionDetails),
+            metadata,
+            suspect: None,
+        });
+        Ok(())
+    }
+
+    /// Run the extension's extractor on the most recent staged resource
+    /// named `filename`, without finalizing it, and return
+    /// `{ count, firstDate, lastDate, error? }`. Lets a driver notice an
+    /// error page or empty export and download again.
+    #[qjs(rename = "tryExtract")]
+    pub async fn js_try_extract(&self, filename: String) -> JsResult<JsEvalResult> {
+        let (ledger_dir, output_dir, login_name, extension_name, resource) = {
+            let inner = self.inner.lock().await;
+            let resource = inner
+                .staged_resources
+                .iter()
+                .rev()
+                .find(|resource| resource.filename == filename)
+                .cloned()
+                .ok_or_else(|| js_err(format!("tryExtract: no staged resource \"{filename}\"")))?;
+            (
+                inner.ledger_dir.clone(),
+                inner.output_dir.clone(),
+                inner.login_name.clone(),
+                inner.extension_name.clone(),
+                resource,
+            )
+        };
+        let doc_name = resource
+            .staging_path
+            .strip_prefix(&output_dir)
+            .map_err(|_| js_err(format!("tryExtract: \"{filename}\" is not staged")))?
+            .to_string_lossy()
+            .replace('\\', "/");
+        let label = resource
+            .label
+            .unwrap_or_else(|| crate::document_labels::DEFAULT_LABEL.to_string());
+        let gl_account = crate::login_config::read_login_config(&ledger_dir, &login_name)
+            .accounts
+            .get(&label)
+            .and_then(|account| account.gl_account.clone())
+            .unwrap_or_default();
+        let summary = tokio::task::spawn_blocking(move || {
+            crate::extract::run_extraction_for_staged_file(
+                &ledger_dir,
+                &output_dir,
+                &doc_name,
+                &gl_account,
+                &label,
+                &extension_name,
+            )
+            .map(|result| TryExtractSummary::from_transactions(&result.proposed_transactions))
+            .unwrap_or_else(|e| TryExtractSummary::failed(e.to_string()))
+        })
+        .await
+        .map_err(|e| js_err(format!("tryExtract failed: {e}")))?;
+        let json = serde_json::to_string(&summary)
+            .map_err(|e| js_err(format!("tryExtract serialization: {e}")))?;
+        Ok(JsEvalResult::Json(format!("({json})")))
+    }
+
+    /// The JS API version this app provides (see manifest `minApiVersion`).
+    #[qjs(get, rename = "apiVersion")]
+    pub fn api_version(&self) -> u32 {
+        super::api_version::JS_API_VERSION
+    }
+
+    /// Register the driver's login entry point. When a navigation lands on
+    /// a manifest `loginWallUrls` page, the runner awaits it and repeats the
+    /// interrupted step.
+    #[qjs(rename = "setLoginHandler")]
+    pub fn js_set_login_handler<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        handler: Option<Function<'js>>,
+    ) -> JsResult<()> {
+        ctx.globals().set(LOGIN_HANDLER_GLOBAL, handler)
+    }
+
     /// Set session-level metadata (dateRangeStart, dateRangeEnd).
     #[qjs(rename = "setSessionMetadata")]
     pub async fn js_set_session_metadata(&self, metadata: rquickjs::Value<'_>) -> JsResult<()> {
@@ -7885,6 +9572,24 @@ impl RefreshmintApi {
         Ok(())
     }
 
+    /// Stage a balance snapshot and optional brokerage holdings.
+    ///
+    /// `report` is `{ balance, currency?, date?, label?, holdings? }` where each
+    /// holding is `{ symbol, quantity?, price?, value, currency? }`. Amounts may
+    /// be numbers or decimal strings; `date` defaults to today.
+    #[qjs(rename = "reportBalance")]
+    pub async fn js_report_balance(&self, report: rquickjs::Value<'_>) -> JsResult<()> {
+        let json =
+            rquickjs_value_to_json(&report).map_err(|e| js_err(format!("reportBalance: {e}")))?;
+        let mut inner = self.inner.lock().await;
+        let today = chrono::Local::now().format("%Y-%m-%d").to_string();
+        let scraped_at = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+        let staged = parse_balance_report(&json, &today, &inner.scrape_session_id, &scraped_at)
+            .map_err(|e| js_err(format!("reportBalance: {e}")))?;
+        inner.staged_balances.extend(staged);
+        Ok(())
+    }
+
     /// Report a key-value pair to stdout.
     #[qjs(rename = "reportValue")]
     pub fn js_report_value(&self, key: String, value: String) -> JsResult<()> {
@@ -7895,61 +9600,66 @@ impl RefreshmintApi {
         Ok(())
     }
 
-    /// Log a message to stderr.
+    /// Log a message as an info event.
     pub fn log(&self, message: String) -> JsResult<()> {
         if !self.emit_debug_output(DebugOutputStream::Stderr, message.clone()) {
-            eprintln!("{message}");
+            tracing::info!("{message}");
         }
         Ok(())
     }
 
     /// Prompt the user: use CLI-provided override when available.
     ///
+    /// `message` may be the key of a manifest `prompts` entry, in which case
+    /// overrides match the key and the answer is validated for its type.
     /// In the Tauri UI context (`prompt_ui_handler` is set), asks the host app
     /// for a response and blocks until it returns one. In CLI context, reads
     /// from stdin as before.
     pub fn prompt(&self, message: String) -> JsResult<String> {
-        let (override_value, require_override, prompt_ui_handler) = {
-            let inner = self
+        let (request, override_value, require_override, prompt_ui_handler) = {
+            let mut inner = self
                 .inner
                 .try_lock()
                 .map_err(|_| js_err("prompt unavailable: prompt state is busy".to_string()))?;
+            let request = PromptRequest::resolve(&inner.prompt_catalog, &message);
+            let override_value = request
+                .find_override(&inner.prompt_overrides)
+                .map(|value| SecretString::new(value.to_string()));
+            inner.prompts_asked.push(request.clone());
             (
-                inner.prompt_overrides.get(&message).cloned().or_else(|| {
-                    let trimmed = message.trim();
-                    if trimmed == message {
-                        None
-                    } else {
-                        inner.prompt_overrides.get(trimmed).cloned()
-                    }
-                }),
+                request,
+                override_value,
                 inner.prompt_requires_override,
                 inner.prompt_ui_handler.clone(),
             )
         };
 
         if let Some(value) = override_value {
-            return Ok(value);
+            return request
+                .validate_answer(value.expose_secret())
+                .map_err(js_err);
         }
 
         if require_override {
-            return Err(js_err(missing_prompt_override_error(&message)));
+            return Err(js_err(request.missing_override_error()));
         }
 
         // UI context: ask the host app to collect a response. `prompt()`
         // runs on a spawn_blocking thread so a blocking callback is safe.
         if let Some(prompt_ui_handler) = prompt_ui_handler {
-            let response = prompt_ui_handler(message).map_err(js_err)?;
-            return resolve_prompt_response(response);
+            let response = prompt_ui_handler(request.clone()).map_err(js_err)?;
+            return request
+                .validate_answer(resolve_prompt_response(response)?.expose_secret())
+                .map_err(js_err);
         }
 
         // CLI context: read from stdin.
-        eprint!("{message} ");
-        let mut line = String::new();
+        eprint!("{} ", request.terminal_message());
+        let mut line = Zeroizing::new(String::new());
         std::io::stdin()
             .read_line(&mut line)
             .map_err(|e| js_err(format!("prompt read failed: {e}")))?;
-        Ok(line.trim_end().to_string())
+        request.validate_answer(line.trim_end()).map_err(js_err)
     }
 
     /// Return CLI `--option` key/value pairs as a native JS object.
@@ -7965,6 +9675,28 @@ impl RefreshmintApi {
         // Wrap in parens so `{}` is parsed as an object literal, not a block statement.
         Ok(JsEvalResult::Json(format!("({json})")))
     }
+
+    /// Return every value in `document` matching the JSONPath `path`, as an
+    /// array. Extract scripts get the same function on their `refreshmint`.
+    #[qjs(rename = "extractJson")]
+    pub fn js_extract_json<'js>(
+        &self,
+        ctx: Ctx<'js>,
+        document: Value<'js>,
+        path: String,
+    ) -> JsResult<JsEvalResult> {
+        let text = match ctx.json_stringify(document)? {
+            Some(text) => text.to_string()?,
+            None => "null".to_string(),
+        };
+        let value: serde_json::Value =
+            serde_json::from_str(&text).map_err(|e| js_err(format!("extractJson: {e}")))?;
+        let matches = crate::json_path::query(&value, &path)
+            .map_err(|e| js_err(format!("extractJson: {e}")))?;
+        let json = serde_json::to_string(&matches)
+            .map_err(|e| js_err(format!("extractJson serialization: {e}")))?;
+        Ok(JsEvalResult::Json(format!("({json})")))
+    }
 }
 
 impl RefreshmintApi {
@@ -8039,8 +9771,79 @@ fn js_value_to_call_args(
     Ok(vec![single_arg(val)?])
 }
 
+/// Parse a `reportBalance` argument into staged snapshot rows.
+fn parse_balance_report(
+    report: &serde_json::Value,
+    today: &str,
+    scrape_session_id: &str,
+    scraped_at: &str,
+) -> Result<Vec<StagedBalance>, String> {
+    use crate::balances::{BalanceSnapshot, SnapshotKind};
+
+    fn text(value: Option<&serde_json::Value>) -> Option<String> {
+        match value? {
+            serde_json::Value::String(s) if !s.trim().is_empty() => Some(s.trim().to_string()),
+            serde_json::Value::Number(n) => Some(n.to_string()),
+            _ => None,
+        }
+    }
+
+    let obj = report
+        .as_object()
+        .ok_or_else(|| "expected an object".to_string())?;
+    let label = text(obj.get("label"));
+    let date = text(obj.get("date")).unwrap_or_else(|| today.to_string());
+    if chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d").is_err() {
+        return Err(format!("date must be YYYY-MM-DD, got {date}"));
+    }
+    let currency = text(obj.get("currency")).unwrap_or_else(|| "USD".to_string());
+    let row = |kind, amount: String, commodity: String| BalanceSnapshot {
+        date: date.clone(),
+        kind,
+        symbol: None,
+        quantity: None,
+        price: None,
+        amount,
+        commodity,
+        scrape_session_id: scrape_session_id.to_string(),
+        scraped_at: scraped_at.to_string(),
+    };
+
+    let mut rows = Vec::new();
+    if let Some(balance) = text(obj.get("balance")) {
+        rows.push(row(SnapshotKind::Balance, balance, currency.clone()));
+    }
+    if let Some(holdings) = obj.get("holdings").and_then(|v| v.as_array()) {
+        for (i, holding) in holdings.iter().enumerate() {
+            let symbol = text(holding.get("symbol"))
+                .ok_or_else(|| format!("holdings[{i}].symbol is required"))?;
+            let value = text(holding.get("value"))
+                .ok_or_else(|| format!("holdings[{i}].value is required"))?;
+            let commodity = text(holding.get("currency")).unwrap_or_else(|| currency.clone());
+            rows.push(BalanceSnapshot {
+                symbol: Some(symbol),
+                quantity: text(holding.get("quantity")),
+                price: text(holding.get("price")),
+                ..row(SnapshotKind::Position, value, commodity)
+            });
+        }
+    }
+    if rows.is_empty() {
+        return Err("expected balance or holdings".to_string());
+    }
+    Ok(rows
+        .into_iter()
+        .map(|snapshot| StagedBalance {
+            label: label.clone(),
+            snapshot,
+        })
+        .collect())
+}
+
 /// Best-effort serialisation of a `rquickjs::Value` to `serde_json::Value`.
-fn rquickjs_value_to_json(val: &rquickjs::Value<'_>) -> Result<serde_json::Value, String> {
+pub(crate) fn rquickjs_value_to_json(
+    val: &rquickjs::Value<'_>,
+) -> Result<serde_json::Value, String> {
     if val.is_null() || val.is_undefined() {
         return Ok(serde_json::Value::Null);
     }
@@ -8094,21 +9897,133 @@ pub fn register_globals(
     let globals = ctx.globals();
 
     let page = PageApi::new(page_inner.clone());
-    globals.set("page", page)?;
+    globals.set("page", page.clone())?;
 
-    let browser = BrowserApi::new(page_inner);
+    let browser = BrowserApi::new(page_inner.clone());
     globals.set("browser", browser)?;
 
-    let rm = RefreshmintApi::new(refreshmint_inner);
+    let rm = Class::instance(ctx.clone(), RefreshmintApi::new(refreshmint_inner.clone()))?;
+    // `assert`/`expect` live on a separate class so they can capture page
+    // context.
+    let assertions = Class::instance(
+        ctx.clone(),
+        super::assertions::AssertionsApi::new(
+            page.clone(),
+            page_inner.clone(),
+            refreshmint_inner.clone(),
+        ),
+    )?;
+    expose_assertions(ctx, &assertions, &rm)?;
+    rm.set(
+        "flows",
+        super::flows::FlowsApi::new(page, refreshmint_inner.clone()),
+    )?;
+    rm.set(
+        "storage",
+        super::storage::StorageApi::new(page_inner, refreshmint_inner),
+    )?;
     globals.set("refreshmint", rm)?;
 
     Ok(())
 }
 
+/// Set `assert` and `expect` of `assertions` on `refreshmint` as functions
+/// bound to it, and `expect` as a global too, as in Playwright tests. The
+/// global is the same function object as `refreshmint.expect`.
+fn expose_assertions<'js>(
+    ctx: &Ctx<'js>,
+    assertions: &Object<'js>,
+    refreshmint: &Object<'js>,
+) -> JsResult<()> {
+    for name in ["assert", "expect"] {
+        let method: Function<'js> = assertions.get(name)?;
+        let bind: Function<'js> = method.get("bind")?;
+        let bound: Function<'js> =
+            bind.call((rquickjs::function::This(method), assertions.clone()))?;
+        if name == "expect" {
+            ctx.globals().set(name, bound.clone())?;
+        }
+        refreshmint.set(name, bound)?;
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn route_abort_reason_maps_playwright_error_codes() {
+        use chromiumoxide::cdp::browser_protocol::network::ErrorReason;
+        assert_eq!(route_abort_reason("failed"), Some(ErrorReason::Failed));
+        assert_eq!(
+            route_abort_reason("blockedbyclient"),
+            Some(ErrorReason::BlockedByClient)
+        );
+        assert_eq!(route_abort_reason("TimedOut"), Some(ErrorReason::TimedOut));
+        assert_eq!(route_abort_reason("nope"), None);
+    }
+
+    #[test]
+    fn global_expect_is_refreshmint_expect() {
+        let runtime = rquickjs::Runtime::new().unwrap();
+        let context = rquickjs::Context::full(&runtime).unwrap();
+        context.with(|ctx| {
+            let assertions: Object<'_> = ctx
+                .eval(
+                    "({ tag: 'page', assert() {}, \
+                     expect(target) { return this.tag + ':' + target; } })",
+                )
+                .unwrap();
+            let refreshmint = Object::new(ctx.clone()).unwrap();
+            expose_assertions(&ctx, &assertions, &refreshmint).unwrap();
+            ctx.globals().set("refreshmint", refreshmint).unwrap();
+            let checks: Vec<bool> = ctx
+                .eval(
+                    "[expect === refreshmint.expect, expect('#total') === 'page:#total', \
+                     typeof refreshmint.assert === 'function', typeof assert === 'undefined']",
+                )
+                .unwrap();
+            assert_eq!(checks, vec![true; 4]);
+        });
+    }
+
+    #[test]
+    fn web_storage_expressions_escape_keys_and_values() {
+        assert_eq!(
+            web_storage_read_js("localStorage", Some("it's \"x\"")),
+            r#"(() => localStorage.getItem("it's \"x\""))()"#
+        );
+        assert_eq!(
+            web_storage_write_js("sessionStorage", "token", Some("a\nb")),
+            r#"(() => { sessionStorage.setItem("token", "a\nb"); })()"#
+        );
+        assert_eq!(
+            web_storage_write_js("localStorage", "token", None),
+            r#"(() => { localStorage.removeItem("token"); })()"#
+        );
+    }
+
+    #[test]
+    fn try_extract_summary_reports_count_and_date_span() {
+        let transactions: Vec<crate::extract::ExtractedTransaction> = serde_json::from_str(
+            r#"[{"tdate":"2025-02-03"},{"tdate":"2025-01-15"},{"tdate":"2025-02-28"}]"#,
+        )
+        .unwrap();
+        let summary =
+            serde_json::to_value(TryExtractSummary::from_transactions(&transactions)).unwrap();
+        assert_eq!(
+            summary,
+            serde_json::json!({"count": 3, "firstDate": "2025-01-15", "lastDate": "2025-02-28"})
+        );
+        let failed =
+            serde_json::to_value(TryExtractSummary::failed("no rows".to_string())).unwrap();
+        assert_eq!(
+            failed,
+            serde_json::json!({"count": 0, "firstDate": null, "lastDate": null, "error": "no rows"})
+        );
+    }
+
     #[test]
     fn extract_domain_https() {
         assert_eq!(extract_domain("https://example.com/path"), "example.com");
@@ -8689,7 +10604,7 @@ mod tests {
 
     #[test]
     fn missing_prompt_override_error_mentions_message_and_flag() {
-        let text = missing_prompt_override_error("OTP");
+        let text = PromptRequest::resolve(&PromptCatalog::new(), "OTP").missing_override_error();
         assert!(text.contains("OTP"));
         assert!(text.contains("--prompt"));
     }
@@ -8741,6 +10656,31 @@ mod tests {
         }
     }
 
+    #[test]
+    fn parse_balance_report_stages_balance_and_holdings() {
+        let report = serde_json::json!({
+            "label": "brokerage",
+            "date": "2026-01-31",
+            "balance": 1500.25,
+            "holdings": [
+                { "symbol": "VTI", "quantity": "5", "price": "250", "value": "1250" }
+            ]
+        });
+        let staged = parse_balance_report(&report, "2026-02-01", "s1", "2026-02-01T00:00:00Z")
+            .unwrap_or_else(|err| panic!("parse failed: {err}"));
+        assert_eq!(staged.len(), 2);
+        assert_eq!(staged[0].label.as_deref(), Some("brokerage"));
+        assert_eq!(staged[0].snapshot.amount, "1500.25");
+        assert_eq!(staged[0].snapshot.commodity, "USD");
+        assert_eq!(staged[1].snapshot.symbol.as_deref(), Some("VTI"));
+        assert_eq!(staged[1].snapshot.date, "2026-01-31");
+
+        let empty = serde_json::json!({ "label": "brokerage" });
+        assert!(parse_balance_report(&empty, "2026-02-01", "s1", "t").is_err());
+        let bad_date = serde_json::json!({ "balance": "1", "date": "01/31/2026" });
+        assert!(parse_balance_report(&bad_date, "2026-02-01", "s1", "t").is_err());
+    }
+
     #[test]
     fn test_matches_filter_metadata() {
         let mut metadata = std::collections::BTreeMap::new();
@@ -8761,6 +10701,10 @@ mod tests {
             coverage_end_date: "2026-01-31".to_string(),
             date_range_start: None,
             date_range_end: None,
+            session_date_range_start: None,
+            session_date_range_end: None,
+            suspect: None,
+            document_type: Some(crate::scrape::DocumentType::Statement),
             metadata,
         };
 
@@ -8789,6 +10733,19 @@ mod tests {
         filter.insert("version".to_string(), serde_json::Value::Number(1.into()));
         assert!(matches_filter(&info, &filter));
 
+        // Document type
+        let mut filter = std::collections::BTreeMap::new();
+        filter.insert(
+            "documentType".to_string(),
+            serde_json::Value::String("statement".to_string()),
+        );
+        assert!(matches_filter(&info, &filter));
+        filter.insert(
+            "documentType".to_string(),
+            serde_json::Value::String("tax-form".to_string()),
+        );
+        assert!(!matches_filter(&info, &filter));
+
         // Mismatch in metadata
         let mut filter = std::collections::BTreeMap::new();
         filter.insert(
@@ -8855,16 +10812,20 @@ mod tests {
             output_dir: PathBuf::new(),
             prompt_overrides: overrides,
             prompt_requires_override: true,
+            prompt_catalog: PromptCatalog::new(),
             script_options: ScriptOptions::new(),
             debug_output_sink: None,
             session_metadata: SessionMetadata::default(),
             staged_resources: Vec::new(),
+            staged_balances: Vec::new(),
             scrape_session_id: String::new(),
             extension_name: String::new(),
             account_name: String::new(),
             login_name: String::new(),
             ledger_dir: PathBuf::new(),
             prompt_ui_handler: None,
+            assertion_failure: None,
+            prompts_asked: Vec::new(),
         }
     }
 
@@ -8946,9 +10907,9 @@ mod tests {
 
     #[test]
     fn resolve_prompt_response_returns_submitted_empty_string() {
-        let value = resolve_prompt_response(Some(String::new()))
+        let value = resolve_prompt_response(Some(SecretString::default()))
             .unwrap_or_else(|err| panic!("expected prompt response value: {err}"));
-        assert_eq!(value, "");
+        assert_eq!(value.expose_secret(), "");
     }
 
     #[test]
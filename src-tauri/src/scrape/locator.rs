@@ -2,20 +2,22 @@ use std::sync::Arc;
 use tokio::sync::Mutex;
 
 use chromiumoxide::cdp::browser_protocol::dom::GetContentQuadsParams;
+use chromiumoxide::cdp::browser_protocol::page::FrameId;
 use chromiumoxide::cdp::js_protocol::runtime::{CallFunctionOnParams, EvaluateParams};
 use chromiumoxide::layout::ElementQuad;
-use rquickjs::{class::Trace, function::Opt, JsLifetime, Result as JsResult, Value};
+use rquickjs::{class::Trace, function::Opt, Class, JsLifetime, Result as JsResult, Value};
 
 use super::js_api::{
     js_err, parse_screenshot_options, resolve_screenshot_output_path, resolve_secret_if_applicable,
     run_screenshot_capture, screenshot_clip_for_object_id, scrub_known_secrets,
-    stringify_evaluation_result, wait_for_frame_execution_target, PageInner, ScreenshotClip,
+    stringify_evaluation_result, wait_for_frame_execution_target, wait_for_frame_ref, JsEvalResult,
+    PageApi, PageInner, ScreenshotClip,
 };
 
 const DEFAULT_TIMEOUT_MS: u64 = 30_000;
 const POLL_INTERVAL_MS: u64 = 100;
 
-const RESOLVER_JS: &str = r#"
+pub(crate) const RESOLVER_JS: &str = r#"
     // Shadow-piercing querySelectorAll: matches selector in root then recurses
     // into every open shadow root found in root's subtree. Mirrors Playwright's
     // _queryCSS implementation.
@@ -106,8 +108,50 @@ const RESOLVER_JS: &str = r#"
         return s.display === 'none' || s.visibility === 'hidden';
     };
 
-    const resolveLocator = async (steps) => {
-        let roots = [document];
+    // Strict-mode errors list each match with a selector that finds only it.
+    const selectorHint = (el) => {
+        const root = el.getRootNode();
+        const unique = (selector) => {
+            try {
+                return root.querySelectorAll(selector).length === 1;
+            } catch {
+                return false;
+            }
+        };
+        const tag = el.tagName.toLowerCase();
+        if (el.id && unique('#' + CSS.escape(el.id))) return '#' + CSS.escape(el.id);
+        for (const attr of ['data-testid', 'data-test', 'name', 'aria-label']) {
+            const value = el.getAttribute(attr);
+            const selector = tag + '[' + attr + '=' + JSON.stringify(value) + ']';
+            if (value && unique(selector)) return selector;
+        }
+        const role = IMPLICIT_ROLE(el);
+        const name = ACCESSIBLE_NAME(el);
+        if (role && name) return 'role=' + role + '[name=' + JSON.stringify(name) + ']';
+        return '';
+    };
+
+    const describeElement = (el) => {
+        let text = '<' + el.tagName.toLowerCase();
+        if (el.id) text += ' id="' + el.id + '"';
+        const classes = (el.getAttribute('class') || '').trim();
+        if (classes) text += ' class="' + classes.slice(0, 40) + '"';
+        text += '>';
+        const content = (el.innerText || el.textContent || '').trim().replace(/\s+/g, ' ');
+        if (content) text += ' "' + content.slice(0, 40) + '"';
+        const hint = selectorHint(el);
+        return hint ? text + ' aka ' + hint : text;
+    };
+
+    const strictModeViolation = (els) => {
+        const lines = els.slice(0, 10).map((el, i) => '    ' + (i + 1) + ') ' + describeElement(el));
+        if (els.length > 10) lines.push('    ...and ' + (els.length - 10) + ' more');
+        return 'Strict mode violation: ' + els.length + ' elements found:\n' + lines.join('\n');
+    };
+
+    // `roots` defaults to the document; `filter({ has })` resolves its inner
+    // locator below each candidate instead.
+    const resolveLocator = async (steps, roots = [document]) => {
         for (const step of steps) {
             let nextRoots = [];
             if (step.type === 'role') {
@@ -165,6 +209,130 @@ const RESOLVER_JS: &str = r#"
                         nextRoots.push(...matched);
                     }
                 }
+            } else if (step.type === 'text') {
+                const normalize = (text) => (text || '').replace(/\s+/g, ' ').trim();
+                const regex = step.pattern !== null && step.pattern !== undefined
+                    ? new RegExp(step.pattern, (step.flags || '').replace('g', ''))
+                    : null;
+                const wanted = normalize(step.text);
+                const textOf = (el) => normalize(
+                    el instanceof HTMLInputElement && ['button', 'submit', 'reset'].includes(el.type)
+                        ? el.value
+                        : el.textContent,
+                );
+                const matchesText = (el) => {
+                    if (['SCRIPT', 'STYLE', 'NOSCRIPT', 'TEMPLATE', 'HEAD'].includes(el.tagName)) return false;
+                    const text = textOf(el);
+                    if (regex) return regex.test(text);
+                    if (step.exact) return text === wanted;
+                    return text.toLowerCase().includes(wanted.toLowerCase());
+                };
+                for (const root of roots) {
+                    const candidates = collectAllDeep(root).filter(matchesText);
+                    const matchedSet = new Set(candidates);
+                    // Keep the innermost elements: drop those with a matching child.
+                    const matched = candidates.filter(el => {
+                        const children = [...el.children, ...(el.shadowRoot ? el.shadowRoot.children : [])];
+                        return !children.some(child => matchedSet.has(child));
+                    });
+                    if (step.index !== null && step.index !== undefined) {
+                        let idx = step.index;
+                        if (idx < 0) idx = matched.length + idx;
+                        if (idx >= 0 && idx < matched.length) {
+                            nextRoots.push(matched[idx]);
+                        }
+                    } else {
+                        nextRoots.push(...matched);
+                    }
+                }
+            } else if (step.type === 'label') {
+                const normalize = (text) => (text || '').replace(/\s+/g, ' ').trim();
+                const regex = step.pattern !== null && step.pattern !== undefined
+                    ? new RegExp(step.pattern, (step.flags || '').replace('g', ''))
+                    : null;
+                const wanted = normalize(step.text);
+                const matchesLabel = (label) => {
+                    const text = normalize(label);
+                    if (!text) return false;
+                    if (regex) return regex.test(text);
+                    if (step.exact) return text === wanted;
+                    return text.toLowerCase().includes(wanted.toLowerCase());
+                };
+                const labelsOf = (el) => {
+                    const labels = [el.getAttribute('aria-label')];
+                    const ids = (el.getAttribute('aria-labelledby') || '').trim().split(/\s+/).filter(Boolean);
+                    // aria-labelledby IDs refer to the element's own tree, which may be a shadow root.
+                    const scope = el.getRootNode().getElementById ? el.getRootNode() : document;
+                    for (const id of ids) {
+                        const ref = scope.getElementById(id);
+                        if (ref) labels.push(ref.innerText || ref.textContent);
+                    }
+                    if (el.labels) {
+                        for (const label of el.labels) labels.push(label.innerText || label.textContent);
+                    }
+                    return labels;
+                };
+                for (const root of roots) {
+                    const matched = collectAllDeep(root).filter(el => labelsOf(el).some(matchesLabel));
+                    if (step.index !== null && step.index !== undefined) {
+                        let idx = step.index;
+                        if (idx < 0) idx = matched.length + idx;
+                        if (idx >= 0 && idx < matched.length) {
+                            nextRoots.push(matched[idx]);
+                        }
+                    } else {
+                        nextRoots.push(...matched);
+                    }
+                }
+            } else if (step.type === 'filter') {
+                const normalize = (text) => (text || '').replace(/\s+/g, ' ').trim();
+                const textMatches = (el, filter) => {
+                    const text = normalize(el.textContent);
+                    if (filter.pattern !== null && filter.pattern !== undefined) {
+                        return new RegExp(filter.pattern, (filter.flags || '').replace('g', '')).test(text);
+                    }
+                    return text.toLowerCase().includes(normalize(filter.text).toLowerCase());
+                };
+                const matched = [];
+                for (const el of roots) {
+                    if (step.hasText && !textMatches(el, step.hasText)) continue;
+                    if (step.hasNotText && textMatches(el, step.hasNotText)) continue;
+                    if (step.has && (await resolveLocator(step.has, [el])).length === 0) continue;
+                    if (step.hasNot && (await resolveLocator(step.hasNot, [el])).length > 0) continue;
+                    matched.push(el);
+                }
+                if (step.index !== null && step.index !== undefined) {
+                    let idx = step.index;
+                    if (idx < 0) idx = matched.length + idx;
+                    if (idx >= 0 && idx < matched.length) {
+                        nextRoots.push(matched[idx]);
+                    }
+                } else {
+                    nextRoots.push(...matched);
+                }
+            } else if (step.type === 'xpath') {
+                for (const root of roots) {
+                    // Like Playwright, `//x` under a scoped root searches below that root.
+                    const expression = root.nodeType !== Node.DOCUMENT_NODE && step.selector.startsWith('/')
+                        ? '.' + step.selector
+                        : step.selector;
+                    const doc = root.ownerDocument || root;
+                    const result = doc.evaluate(expression, root, null, XPathResult.ORDERED_NODE_SNAPSHOT_TYPE, null);
+                    const matched = [];
+                    for (let i = 0; i < result.snapshotLength; i++) {
+                        const node = result.snapshotItem(i);
+                        if (node.nodeType === Node.ELEMENT_NODE) matched.push(node);
+                    }
+                    if (step.index !== null && step.index !== undefined) {
+                        let idx = step.index;
+                        if (idx < 0) idx = matched.length + idx;
+                        if (idx >= 0 && idx < matched.length) {
+                            nextRoots.push(matched[idx]);
+                        }
+                    } else {
+                        nextRoots.push(...matched);
+                    }
+                }
             } else {
                 for (const root of roots) {
                     const arr = queryAllDeep(root, step.selector);
@@ -186,6 +354,40 @@ const RESOLVER_JS: &str = r#"
     };
 "#;
 
+/// `actionabilityError(el, checks)` resolves to `''` when `el` passes every
+/// check, or to the first failing one, worded for timeout errors.
+const ACTIONABILITY_JS: &str = r#"
+    const actionabilityError = async (el, checks) => {
+        for (const check of checks) {
+            if (check === 'attached') {
+                if (!el) return 'element not found';
+                if (!el.isConnected) return 'element is detached from the document';
+            } else if (check === 'visible') {
+                const style = window.getComputedStyle(el);
+                if (!style || style.display === 'none' || style.visibility === 'hidden' || style.opacity === '0') return 'element is not visible';
+                const rect = el.getBoundingClientRect();
+                if (rect.width <= 0 || rect.height <= 0) return 'element is not visible';
+            } else if (check === 'stable') {
+                // Two boxes a frame apart must match, so animations finish first.
+                const before = el.getBoundingClientRect();
+                await new Promise((resolve) => setTimeout(resolve, 16));
+                const after = el.getBoundingClientRect();
+                if (before.x !== after.x || before.y !== after.y || before.width !== after.width || before.height !== after.height) return 'element is not stable';
+            } else if (check === 'enabled') {
+                const disabled = el.disabled === true
+                    || el.getAttribute('aria-disabled') === 'true'
+                    || !!el.closest('fieldset[disabled]');
+                if (disabled) return 'element is disabled';
+            } else if (check === 'editable') {
+                const editable = el.isContentEditable
+                    || ((el instanceof HTMLInputElement || el instanceof HTMLTextAreaElement || el instanceof HTMLSelectElement) && !el.readOnly);
+                if (!editable) return 'element is not editable';
+            }
+        }
+        return '';
+    };
+"#;
+
 #[derive(Clone, serde::Serialize, Debug, PartialEq)]
 #[serde(tag = "type", rename_all = "camelCase")]
 enum LocatorStep {
@@ -212,6 +414,263 @@ enum LocatorStep {
         selected: Option<bool>,
         index: Option<i32>,
     },
+    Xpath {
+        selector: String,
+        index: Option<i32>,
+    },
+    Text {
+        /// Plain text (used when pattern is None)
+        text: String,
+        /// Regex source (for `text=/.../`)
+        pattern: Option<String>,
+        /// Regex flags (for `text=/.../`)
+        flags: Option<String>,
+        /// true = case-sensitive full match; false = case-insensitive substring
+        exact: bool,
+        index: Option<i32>,
+    },
+    /// Form controls whose `<label>`, `aria-labelledby`, or `aria-label`
+    /// text matches, like Playwright's `getByLabel`.
+    Label {
+        text: String,
+        pattern: Option<String>,
+        flags: Option<String>,
+        exact: bool,
+        index: Option<i32>,
+    },
+    /// Keeps the previous step's matches that pass every condition, from
+    /// `locator.filter(...)`.
+    Filter {
+        #[serde(rename = "hasText")]
+        has_text: Option<TextFilter>,
+        #[serde(rename = "hasNotText")]
+        has_not_text: Option<TextFilter>,
+        /// Steps that must match below the element.
+        has: Option<Vec<LocatorStep>>,
+        /// Steps that must not match below the element.
+        #[serde(rename = "hasNot")]
+        has_not: Option<Vec<LocatorStep>>,
+        index: Option<i32>,
+    },
+}
+
+/// Text a `filter` step looks for anywhere in an element: a case-insensitive
+/// substring of `text`, or the regex `pattern`.
+#[derive(Clone, serde::Serialize, Debug, PartialEq)]
+struct TextFilter {
+    text: String,
+    pattern: Option<String>,
+    flags: Option<String>,
+}
+
+impl TextFilter {
+    fn from_value(key: &str, value: &Value<'_>) -> JsResult<Self> {
+        if let Some(text) = value.as_string() {
+            return Ok(Self {
+                text: text.to_string()?,
+                pattern: None,
+                flags: None,
+            });
+        }
+        // QuickJS RegExp objects have `source` and `flags` string properties.
+        match value.as_object() {
+            Some(regex) => Ok(Self {
+                text: String::new(),
+                pattern: Some(regex.get::<_, String>("source")?),
+                flags: Some(regex.get::<_, String>("flags").unwrap_or_default()),
+            }),
+            None => Err(js_err(format!(
+                "locator.filter: {key} must be a string or RegExp"
+            ))),
+        }
+    }
+
+    fn debug_string(&self) -> String {
+        match &self.pattern {
+            Some(pattern) => format!("/{pattern}/{}", self.flags.as_deref().unwrap_or("")),
+            None => format!("{:?}", self.text),
+        }
+    }
+}
+
+/// Parse `locator.filter({ hasText?, hasNotText?, has?, hasNot? })` into a step.
+fn parse_filter_options<'js>(options: Option<Value<'js>>) -> JsResult<LocatorStep> {
+    let Some(obj) = options.as_ref().and_then(Value::as_object) else {
+        return Err(js_err(
+            "locator.filter: options must be an object".to_string(),
+        ));
+    };
+    let present = |key: &str| -> JsResult<Option<Value<'js>>> {
+        let value: Value<'js> = obj.get(key)?;
+        Ok((!value.is_undefined() && !value.is_null()).then_some(value))
+    };
+    let text = |key: &str| -> JsResult<Option<TextFilter>> {
+        present(key)?
+            .map(|value| TextFilter::from_value(key, &value))
+            .transpose()
+    };
+    let steps = |key: &str| -> JsResult<Option<Vec<LocatorStep>>> {
+        present(key)?
+            .map(|value| {
+                Class::<Locator>::from_value(&value)
+                    .map(|locator| locator.borrow().steps.clone())
+                    .map_err(|_| js_err(format!("locator.filter: {key} must be a Locator")))
+            })
+            .transpose()
+    };
+    Ok(LocatorStep::Filter {
+        has_text: text("hasText")?,
+        has_not_text: text("hasNotText")?,
+        has: steps("has")?,
+        has_not: steps("hasNot")?,
+        index: None,
+    })
+}
+
+/// Options shared by actions that auto-wait for their element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct ActionOptions {
+    pub(crate) timeout_ms: u64,
+    /// Skip the visible/stable/enabled/receives-events checks and act as
+    /// soon as the element is attached, like Playwright's `force`.
+    pub(crate) force: bool,
+}
+
+impl Default for ActionOptions {
+    fn default() -> Self {
+        Self {
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            force: false,
+        }
+    }
+}
+
+/// Parse `{ timeout?, force? }` or a bare timeout number.
+pub(crate) fn parse_action_options(options: Option<Value<'_>>) -> ActionOptions {
+    let force = options
+        .as_ref()
+        .and_then(Value::as_object)
+        .and_then(|obj| obj.get::<_, Option<bool>>("force").ok().flatten())
+        .unwrap_or(false);
+    ActionOptions {
+        timeout_ms: parse_timeout(options),
+        force,
+    }
+}
+
+/// One `<option>` to pick in `selectOption`. A bare string matches an
+/// option's value or its label; an object matches every field it sets.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub(crate) struct SelectTarget {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value_or_label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    index: Option<i32>,
+}
+
+/// Parse `selectOption` values: a string, `{ value?, label?, index? }`, or
+/// an array of either. `null` or `[]` clears the selection.
+pub(crate) fn parse_select_targets(values: Value<'_>) -> JsResult<Vec<SelectTarget>> {
+    if values.is_null() || values.is_undefined() {
+        return Ok(Vec::new());
+    }
+    if let Some(array) = values.as_array() {
+        let mut targets = Vec::with_capacity(array.len());
+        for item in array.iter::<Value<'_>>() {
+            targets.extend(parse_select_targets(item?)?);
+        }
+        return Ok(targets);
+    }
+    if let Some(text) = values.as_string() {
+        return Ok(vec![SelectTarget {
+            value_or_label: Some(text.to_string()?),
+            ..SelectTarget::default()
+        }]);
+    }
+    if let Some(obj) = values.as_object() {
+        let target = SelectTarget {
+            value_or_label: None,
+            value: obj.get::<_, Option<String>>("value").ok().flatten(),
+            label: obj.get::<_, Option<String>>("label").ok().flatten(),
+            index: obj.get::<_, Option<i32>>("index").ok().flatten(),
+        };
+        if target == SelectTarget::default() {
+            return Err(js_err(
+                "selectOption: option must set value, label, or index".to_string(),
+            ));
+        }
+        return Ok(vec![target]);
+    }
+    Err(js_err(format!(
+        "selectOption: expected a string, {{ value, label, index }}, or an array, got {}",
+        values.type_name()
+    )))
+}
+
+/// An action that waits for its element to be actionable first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Action {
+    Click,
+    Fill,
+    Type,
+    DragAndDrop,
+    Hover,
+    Focus,
+    Blur,
+    SelectOption,
+    Check,
+    Uncheck,
+}
+
+impl Action {
+    fn name(self) -> &'static str {
+        match self {
+            Action::Click => "click",
+            Action::Fill => "fill",
+            Action::Type => "type",
+            Action::DragAndDrop => "dragAndDrop",
+            Action::Hover => "hover",
+            Action::Focus => "focus",
+            Action::Blur => "blur",
+            Action::SelectOption => "selectOption",
+            Action::Check => "check",
+            Action::Uncheck => "uncheck",
+        }
+    }
+
+    /// Checks run by `actionabilityError` before the action, in order.
+    /// Whether a click would land on the element is checked by the click
+    /// itself, after scrolling it into view.
+    fn checks(self, force: bool) -> &'static [&'static str] {
+        match (self, force) {
+            (_, true) | (Action::Focus | Action::Blur, _) => &["attached"],
+            (Action::Click | Action::DragAndDrop | Action::Check | Action::Uncheck, false) => {
+                &["attached", "visible", "stable", "enabled"]
+            }
+            (Action::Hover, false) => &["attached", "visible", "stable"],
+            (Action::SelectOption, false) => &["attached", "visible", "enabled"],
+            (Action::Fill | Action::Type, false) => &["attached", "visible", "enabled", "editable"],
+        }
+    }
+}
+
+/// Why one action attempt did not go through.
+enum AttemptError {
+    /// The element changed under us (detached, covered, scrolled away);
+    /// the action is retried until its timeout.
+    NotReady(String),
+    Fatal(rquickjs::Error),
+}
+
+impl From<rquickjs::Error> for AttemptError {
+    fn from(err: rquickjs::Error) -> Self {
+        AttemptError::Fatal(err)
+    }
 }
 
 fn parse_timeout(options: Option<Value<'_>>) -> u64 {
@@ -310,6 +769,260 @@ fn parse_role_selector(s: &str) -> Option<LocatorStep> {
     })
 }
 
+/// Parse a `text=...` selector into a `LocatorStep::Text`.
+/// `text=/re/flags` matches a regex, `text="..."` the whole normalized text
+/// (with an `i` suffix, a case-insensitive substring), and unquoted text a
+/// case-insensitive substring. Returns `None` for other selectors.
+fn parse_text_selector(s: &str) -> Option<LocatorStep> {
+    let (text, pattern, flags, exact) = parse_text_value(s.strip_prefix("text=")?)?;
+    Some(LocatorStep::Text {
+        text,
+        pattern,
+        flags,
+        exact,
+        index: None,
+    })
+}
+
+/// Parse a `label="Email"i` or `label=/^Email/` selector into a `LocatorStep::Label`.
+fn parse_label_selector(s: &str) -> Option<LocatorStep> {
+    let (text, pattern, flags, exact) = parse_text_value(s.strip_prefix("label=")?)?;
+    Some(LocatorStep::Label {
+        text,
+        pattern,
+        flags,
+        exact,
+        index: None,
+    })
+}
+
+/// Split a `text=` or `label=` value into (text, regex source, regex flags,
+/// exact). Quoted values match exactly unless followed by `i`.
+fn parse_text_value(value: &str) -> Option<(String, Option<String>, Option<String>, bool)> {
+    let rest = value.trim();
+    if let Some(regex_str) = rest.strip_prefix('/') {
+        let (src, flags) = parse_regex_literal(regex_str);
+        Some((
+            String::new(),
+            Some(src.to_string()),
+            Some(flags.to_string()),
+            false,
+        ))
+    } else if rest.starts_with('"') || rest.starts_with('\'') {
+        let (text, suffix) = parse_quoted_string(rest);
+        Some((text, None, None, suffix != "i"))
+    } else if rest.is_empty() {
+        None
+    } else {
+        Some((rest.to_string(), None, None, false))
+    }
+}
+
+/// Parse an `xpath=...` selector, or one starting with `//` or `..` as
+/// Playwright does, into a `LocatorStep::Xpath`.
+fn parse_xpath_selector(s: &str) -> Option<LocatorStep> {
+    let selector = match s.strip_prefix("xpath=") {
+        Some(rest) => rest.trim(),
+        None if s.starts_with("//") || s.starts_with("..") => s,
+        None => return None,
+    };
+    (!selector.is_empty()).then(|| LocatorStep::Xpath {
+        selector: selector.to_string(),
+        index: None,
+    })
+}
+
+/// Parse one selector into a step: a `role=`, `text=`, `label=`, or
+/// `xpath=` engine, else CSS.
+fn parse_step(selector: String) -> LocatorStep {
+    parse_role_selector(&selector)
+        .or_else(|| parse_text_selector(&selector))
+        .or_else(|| parse_label_selector(&selector))
+        .or_else(|| parse_xpath_selector(&selector))
+        .unwrap_or(LocatorStep::Css {
+            selector,
+            index: None,
+        })
+}
+
+/// Parse a selector that may chain parts with `>>`, such as
+/// `sf-login-form >> input[name="username"]`. Each part resolves below the
+/// matches of the part before it, including inside their open shadow roots,
+/// so a chain can reach elements a single CSS selector cannot. An `nth=N`
+/// part picks one match of the part before it.
+fn parse_selector(selector: &str) -> Vec<LocatorStep> {
+    let mut steps = Vec::new();
+    for part in split_selector_chain(selector) {
+        let nth = part
+            .strip_prefix("nth=")
+            .and_then(|n| n.trim().parse::<i32>().ok());
+        match nth {
+            Some(index) if !steps.is_empty() => steps = chain_nth(&steps, index),
+            _ => match parse_step(part.to_string()) {
+                LocatorStep::Css { selector, .. } => steps.extend(parse_css_selector(&selector)),
+                step => steps.push(step),
+            },
+        }
+    }
+    if steps.is_empty() {
+        steps.push(parse_step(selector.to_string()));
+    }
+    steps
+}
+
+/// Parse a CSS selector that may use Playwright's `:has-text("...")`
+/// pseudo-class, as in `#activity tr:has-text("Pending") button`, into CSS
+/// steps and `hasText` filters. Like `filter({ hasText })`, it matches a
+/// case-insensitive substring of the element's text. Only a descendant or
+/// child combinator may follow it; other selectors are left as CSS.
+fn parse_css_selector(selector: &str) -> Vec<LocatorStep> {
+    const HAS_TEXT: &str = ":has-text(";
+    let css = |selector: &str| LocatorStep::Css {
+        selector: selector.to_string(),
+        index: None,
+    };
+    let mut steps = Vec::new();
+    let mut rest = selector;
+    while let Some(start) = find_top_level(rest, HAS_TEXT) {
+        let argument = rest[start + HAS_TEXT.len()..].trim_start();
+        let (text, after) = if argument.starts_with('"') || argument.starts_with('\'') {
+            parse_quoted_string(argument)
+        } else {
+            match argument.find(')') {
+                Some(end) => (argument[..end].trim().to_string(), &argument[end..]),
+                None => return vec![css(selector)],
+            }
+        };
+        let Some(after) = after.trim_start().strip_prefix(')') else {
+            return vec![css(selector)];
+        };
+        // `tr:has-text(...)` filters the rows themselves; `tr :has-text(...)`
+        // and a bare `:has-text(...)` filter every element below.
+        let raw = &rest[..start];
+        if !(raw.is_empty() && !steps.is_empty()) {
+            let mut head = scope_combinator(raw, !steps.is_empty());
+            if head.is_empty() || head.ends_with('>') || raw.ends_with(char::is_whitespace) {
+                if !head.is_empty() {
+                    head.push(' ');
+                }
+                head.push('*');
+            }
+            steps.push(css(&head));
+        }
+        steps.push(LocatorStep::Filter {
+            has_text: Some(TextFilter {
+                text,
+                pattern: None,
+                flags: None,
+            }),
+            has_not_text: None,
+            has: None,
+            has_not: None,
+            index: None,
+        });
+        rest = after;
+        if rest.trim_start().starts_with(['+', '~']) {
+            return vec![css(selector)];
+        }
+    }
+    if steps.is_empty() {
+        return vec![css(selector)];
+    }
+    if !rest.trim().is_empty() {
+        steps.push(css(&scope_combinator(rest, true)));
+    }
+    steps
+}
+
+/// A selector that continues below a previous step's matches: a leading
+/// `>` applies to the matched element itself, as `:scope >`.
+fn scope_combinator(selector: &str, continues: bool) -> String {
+    let trimmed = selector.trim();
+    match trimmed.strip_prefix('>') {
+        Some(child) if continues => format!(":scope > {}", child.trim()).trim_end().to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Byte offset of `needle` in `selector` outside quotes and brackets.
+fn find_top_level(selector: &str, needle: &str) -> Option<usize> {
+    let bytes = selector.as_bytes();
+    let mut depth = 0usize;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'[' => depth += 1,
+            b']' => depth = depth.saturating_sub(1),
+            _ if depth == 0 && bytes[i..].starts_with(needle.as_bytes()) => return Some(i),
+            _ => {}
+        }
+        i += 1;
+    }
+    None
+}
+
+/// Split `selector` on `>>` outside quotes, brackets, parentheses, and
+/// `text=`/`label=` regex literals, dropping empty parts.
+fn split_selector_chain(selector: &str) -> Vec<&str> {
+    let bytes = selector.as_bytes();
+    let mut parts = Vec::new();
+    let mut depth = 0usize;
+    let mut start = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            quote @ (b'"' | b'\'') => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != quote {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'/' if (depth > 0 && bytes[i - 1] == b'=')
+                || selector[..i].ends_with("text=")
+                || selector[..i].ends_with("label=") =>
+            {
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'/' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'[' | b'(' => depth += 1,
+            b']' | b')' => depth = depth.saturating_sub(1),
+            b'>' if depth == 0 && bytes.get(i + 1) == Some(&b'>') => {
+                parts.push(selector[start..i].trim());
+                i += 2;
+                start = i;
+                continue;
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    parts.push(selector[start..].trim());
+    parts.retain(|part| !part.is_empty());
+    parts
+}
+
+/// Steps JSON for a page-level selector, resolved by `resolveLocator`.
+pub(crate) fn selector_steps_json(selector: &str) -> String {
+    serde_json::to_string(&parse_selector(selector)).unwrap_or_default()
+}
+
 /// Find the position of the closing `]` for an attribute value that may contain
 /// quoted strings (`"..."` or `'...'`) or regex literals (`/.../`).
 fn find_attr_close(s: &str) -> Option<usize> {
@@ -482,12 +1195,40 @@ pub(crate) fn build_role_selector(role: &str, options: Option<Value<'_>>) -> Str
     s
 }
 
+/// Build a `text=...` selector from a `getByText` string or RegExp.
+pub(crate) fn build_text_selector(text: Value<'_>, options: Option<Value<'_>>) -> String {
+    build_text_engine_selector("text", text, options)
+}
+
+/// Build a `label=...` selector from a `getByLabel` string or RegExp.
+pub(crate) fn build_label_selector(text: Value<'_>, options: Option<Value<'_>>) -> String {
+    build_text_engine_selector("label", text, options)
+}
+
+fn build_text_engine_selector(engine: &str, text: Value<'_>, options: Option<Value<'_>>) -> String {
+    // QuickJS RegExp objects have `source` and `flags` string properties.
+    if let Some(regex) = text.as_object() {
+        let source = regex.get::<_, String>("source").unwrap_or_default();
+        let flags = regex.get::<_, String>("flags").unwrap_or_default();
+        return format!("{engine}=/{source}/{flags}");
+    }
+    let text = text
+        .as_string()
+        .and_then(|text| text.to_string().ok())
+        .unwrap_or_default();
+    let exact = options
+        .as_ref()
+        .and_then(Value::as_object)
+        .and_then(|obj| obj.get::<_, bool>("exact").ok())
+        .unwrap_or(false);
+    let escaped = text.replace('\\', "\\\\").replace('"', "\\\"");
+    let suffix = if exact { "" } else { "i" };
+    format!("{engine}=\"{escaped}\"{suffix}")
+}
+
 fn chain_selector(steps: &[LocatorStep], selector: String) -> Vec<LocatorStep> {
     let mut new_steps = steps.to_vec();
-    new_steps.push(LocatorStep::Css {
-        selector,
-        index: None,
-    });
+    new_steps.extend(parse_selector(&selector));
     new_steps
 }
 
@@ -497,6 +1238,10 @@ fn chain_nth(steps: &[LocatorStep], index: i32) -> Vec<LocatorStep> {
         match last {
             LocatorStep::Css { index: idx, .. } => *idx = Some(index),
             LocatorStep::Role { index: idx, .. } => *idx = Some(index),
+            LocatorStep::Xpath { index: idx, .. } => *idx = Some(index),
+            LocatorStep::Text { index: idx, .. } => *idx = Some(index),
+            LocatorStep::Label { index: idx, .. } => *idx = Some(index),
+            LocatorStep::Filter { index: idx, .. } => *idx = Some(index),
         }
     }
     new_steps
@@ -509,6 +1254,57 @@ fn debug_selector_string(steps: &[LocatorStep]) -> String {
             let (label, index) = match step {
                 LocatorStep::Css { selector, index } => (selector.clone(), *index),
                 LocatorStep::Role { role, index, .. } => (format!("role={role}"), *index),
+                LocatorStep::Xpath { selector, index } => (format!("xpath={selector}"), *index),
+                LocatorStep::Text {
+                    text,
+                    pattern,
+                    flags,
+                    exact,
+                    index,
+                } => (
+                    debug_text_step("text", text, pattern, flags, *exact),
+                    *index,
+                ),
+                LocatorStep::Label {
+                    text,
+                    pattern,
+                    flags,
+                    exact,
+                    index,
+                } => (
+                    debug_text_step("label", text, pattern, flags, *exact),
+                    *index,
+                ),
+                LocatorStep::Filter {
+                    has_text,
+                    has_not_text,
+                    has,
+                    has_not,
+                    index,
+                } => {
+                    let parts: Vec<String> = [
+                        has_text
+                            .as_ref()
+                            .map(|t| format!("has-text={}", t.debug_string())),
+                        has_not_text
+                            .as_ref()
+                            .map(|t| format!("has-not-text={}", t.debug_string())),
+                        has.as_ref()
+                            .map(|steps| format!("has={:?}", debug_selector_string(steps))),
+                        has_not
+                            .as_ref()
+                            .map(|steps| format!("has-not={:?}", debug_selector_string(steps))),
+                    ]
+                    .into_iter()
+                    .flatten()
+                    .collect();
+                    let label = if parts.is_empty() {
+                        "filter".to_string()
+                    } else {
+                        parts.join(" >> ")
+                    };
+                    (label, *index)
+                }
             };
             let mut s = label;
             if let Some(idx) = index {
@@ -526,6 +1322,20 @@ fn debug_selector_string(steps: &[LocatorStep]) -> String {
         .join(" >> ")
 }
 
+fn debug_text_step(
+    engine: &str,
+    text: &str,
+    pattern: &Option<String>,
+    flags: &Option<String>,
+    exact: bool,
+) -> String {
+    match pattern {
+        Some(pattern) => format!("{engine}=/{pattern}/{}", flags.as_deref().unwrap_or("")),
+        None if exact => format!("{engine}={text:?}"),
+        None => format!("{engine}={text}"),
+    }
+}
+
 #[rquickjs::class]
 #[derive(Trace, Clone)]
 pub struct Locator {
@@ -533,6 +1343,20 @@ pub struct Locator {
     pub(crate) inner: Arc<Mutex<PageInner>>,
     #[qjs(skip_trace)]
     steps: Vec<LocatorStep>,
+    /// Frame from `page.frame(...)` or `page.frameLocator(...)`; `None`
+    /// follows the page's `switchToFrame` target.
+    #[qjs(skip_trace)]
+    frame: Option<FrameScope>,
+}
+
+/// The frame a locator resolves in.
+#[derive(Debug, Clone)]
+enum FrameScope {
+    /// A frame resolved once by `page.frame(...)`.
+    Id(FrameId),
+    /// A reference from `page.frameLocator(...)`, looked up again on every
+    /// action so an iframe that loads late or reloads is still found.
+    Ref(String),
 }
 
 #[allow(unsafe_code)]
@@ -542,25 +1366,50 @@ unsafe impl<'js> JsLifetime<'js> for Locator {
 
 impl Locator {
     pub(crate) fn new(inner: Arc<Mutex<PageInner>>, selector: String) -> Self {
-        let step = if let Some(role_step) = parse_role_selector(&selector) {
-            role_step
-        } else {
-            LocatorStep::Css {
-                selector,
-                index: None,
-            }
-        };
         Self {
             inner,
-            steps: vec![step],
+            steps: parse_selector(&selector),
+            frame: None,
+        }
+    }
+
+    /// Pin this locator to one frame, regardless of `switchToFrame`.
+    pub(crate) fn in_frame(mut self, frame_id: FrameId) -> Self {
+        self.frame = Some(FrameScope::Id(frame_id));
+        self
+    }
+
+    /// Resolve this locator in the frame `frame_ref` names at the time of
+    /// each action, regardless of `switchToFrame`.
+    pub(crate) fn in_frame_ref(mut self, frame_ref: String) -> Self {
+        self.frame = Some(FrameScope::Ref(frame_ref));
+        self
+    }
+
+    fn with_steps(&self, steps: Vec<LocatorStep>) -> Locator {
+        Locator {
+            inner: self.inner.clone(),
+            steps,
+            frame: self.frame.clone(),
+        }
+    }
+
+    async fn frame_target(&self, inner: &PageInner) -> JsResult<Option<FrameId>> {
+        match &self.frame {
+            Some(FrameScope::Id(frame_id)) => Ok(Some(frame_id.clone())),
+            Some(FrameScope::Ref(frame_ref)) => wait_for_frame_ref(&inner.page, frame_ref)
+                .await
+                .map(Some)
+                .map_err(js_err),
+            None => Ok(inner.target_frame_id.clone()),
         }
     }
 
     pub(crate) async fn resolve_single_element_object_id(&self) -> JsResult<String> {
         let inner = self.inner.lock().await;
-        let execution_target = if let Some(frame_id) = &inner.target_frame_id {
+        let execution_target = if let Some(frame_id) = self.frame_target(&inner).await? {
             Some(
-                wait_for_frame_execution_target(&inner.page, frame_id.clone())
+                wait_for_frame_execution_target(&inner.page, frame_id)
                     .await
                     .map_err(|e| js_err(format!("locator resolve element target failed: {e}")))?,
             )
@@ -573,7 +1422,7 @@ impl Locator {
             r#"(async (steps) => {{
                 const els = await resolveLocator(steps);
                 if (els.length === 0) throw new Error('Element not found');
-                if (els.length > 1) throw new Error('Strict mode violation: ' + els.length + ' elements found');
+                if (els.length > 1) throw new Error(strictModeViolation(els));
                 return els[0];
             }})({steps_json})"#
         );
@@ -621,10 +1470,7 @@ impl Locator {
 
     /// Create a new locator that finds elements matching `selector` relative to this locator.
     pub fn locator(&self, selector: String) -> Locator {
-        Locator {
-            inner: self.inner.clone(),
-            steps: chain_selector(&self.steps, selector),
-        }
+        self.with_steps(chain_selector(&self.steps, selector))
     }
 
     /// Create a new locator that finds elements by ARIA role relative to this locator.
@@ -637,14 +1483,23 @@ impl Locator {
         });
         let mut steps = self.steps.clone();
         steps.push(step);
-        Locator {
-            inner: self.inner.clone(),
-            steps,
-        }
+        self.with_steps(steps)
     }
 
-    /// Create a locator matching the first element.
-    pub fn first(&self) -> Locator {
+    /// Create a locator that finds elements by their text relative to this locator.
+    #[qjs(rename = "getByText")]
+    pub fn get_by_text(&self, text: Value<'_>, options: Opt<Value<'_>>) -> Locator {
+        self.locator(build_text_selector(text, options.0))
+    }
+
+    /// Create a locator that finds form controls by their label relative to this locator.
+    #[qjs(rename = "getByLabel")]
+    pub fn get_by_label(&self, text: Value<'_>, options: Opt<Value<'_>>) -> Locator {
+        self.locator(build_label_selector(text, options.0))
+    }
+
+    /// Create a locator matching the first element.
+    pub fn first(&self) -> Locator {
         self.nth(0)
     }
 
@@ -655,10 +1510,22 @@ impl Locator {
 
     /// Create a locator matching the nth element (0-based index).
     pub fn nth(&self, index: i32) -> Locator {
-        Locator {
-            inner: self.inner.clone(),
-            steps: chain_nth(&self.steps, index),
-        }
+        self.with_steps(chain_nth(&self.steps, index))
+    }
+
+    /// Narrow this locator to matches containing (or not containing) some
+    /// text or a matching inner locator.
+    pub fn filter(&self, options: Opt<Value<'_>>) -> JsResult<Locator> {
+        let mut steps = self.steps.clone();
+        steps.push(parse_filter_options(options.0)?);
+        Ok(self.with_steps(steps))
+    }
+
+    /// Return a locator for each current match, in document order. Unlike
+    /// the locator itself, the returned locators are pinned by index.
+    pub async fn all(&self) -> JsResult<Vec<Locator>> {
+        let count = self.count().await?;
+        Ok((0..count).map(|index| self.nth(index)).collect())
     }
 
     /// Count matching elements.
@@ -675,16 +1542,541 @@ impl Locator {
         Ok(count)
     }
 
-    pub(crate) async fn click_with_timeout(&self, timeout_ms: u64) -> JsResult<()> {
-        self.ensure_element_state("visible", timeout_ms).await?;
+    /// Click the element using a trusted CDP mouse click (Input.dispatchMouseEvent).
+    ///
+    /// Unlike `el.click()` via Runtime.evaluate, this produces `isTrusted: true` events,
+    /// which is required for sites that check event.isTrusted (e.g. login flows).
+    /// Waits until the element is visible, stable, enabled, and not covered.
+    pub async fn click<'js>(
+        &self,
+        ctx: rquickjs::Ctx<'js>,
+        options: Opt<Value<'js>>,
+    ) -> JsResult<()> {
+        let options = parse_action_options(options.0);
+        PageApi::new(self.inner.clone())
+            .with_login_wall_recovery(&ctx, || self.click_with_options(options))
+            .await
+    }
+
+    /// Fill the input once it is visible, enabled, and editable.
+    pub async fn fill(&self, value: String, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.fill_with_options(value, parse_action_options(options.0))
+            .await
+    }
+
+    /// Move the mouse over the element's center once it is visible and
+    /// stable, with a trusted mouse event, so hover menus open.
+    pub async fn hover(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        let (x, y) = self
+            .action_point(Action::Hover, parse_action_options(options.0))
+            .await?;
+        super::input::MouseApi::new(self.inner.clone())
+            .move_to(x, y, 1)
+            .await
+    }
+
+    /// Focus the element once it is attached.
+    pub async fn focus(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.perform(Action::Focus, parse_action_options(options.0), || {
+            self.try_focus(Action::Focus)
+        })
+        .await
+    }
+
+    /// Check a checkbox or radio button (or the control of a `<label>`)
+    /// with a trusted click once it is actionable. Does nothing if it is
+    /// already checked, and fails if the click does not check it.
+    pub async fn check(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.set_checked_with_options(true, parse_action_options(options.0))
+            .await
+    }
+
+    /// Uncheck a checkbox like `check`. Radio buttons cannot be unchecked.
+    pub async fn uncheck(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.set_checked_with_options(false, parse_action_options(options.0))
+            .await
+    }
+
+    /// Pick options of a `<select>` by value, label, or index once it is
+    /// visible and enabled, firing `input` and `change`. Waits for the
+    /// options to appear and returns the selected values.
+    #[qjs(rename = "selectOption")]
+    pub async fn select_option(
+        &self,
+        values: Value<'_>,
+        options: Opt<Value<'_>>,
+    ) -> JsResult<Vec<String>> {
+        let targets = parse_select_targets(values)?;
+        self.select_option_with_options(&targets, parse_action_options(options.0))
+            .await
+    }
+
+    /// Remove focus from the element once it is attached.
+    pub async fn blur(&self, options: Opt<Value<'_>>) -> JsResult<()> {
+        self.perform(Action::Blur, parse_action_options(options.0), || {
+            self.try_focus(Action::Blur)
+        })
+        .await
+    }
+
+    #[qjs(rename = "innerText")]
+    pub async fn inner_text(&self, options: Opt<Value<'_>>) -> JsResult<String> {
+        let timeout_ms = parse_timeout(options.0);
+        self.get_property("innerText", timeout_ms).await
+    }
+
+    #[qjs(rename = "textContent")]
+    pub async fn text_content(&self, options: Opt<Value<'_>>) -> JsResult<String> {
+        let timeout_ms = parse_timeout(options.0);
+        self.get_property("textContent", timeout_ms).await
+    }
+
+    #[qjs(rename = "inputValue")]
+    pub async fn input_value(&self, options: Opt<Value<'_>>) -> JsResult<String> {
+        let timeout_ms = parse_timeout(options.0);
+        self.get_property("value", timeout_ms).await
+    }
+
+    #[qjs(rename = "getAttribute")]
+    pub async fn get_attribute(&self, name: String, options: Opt<Value<'_>>) -> JsResult<String> {
+        let timeout_ms = parse_timeout(options.0);
+        self.ensure_element_state("attached", timeout_ms).await?;
+
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let name_json = serde_json::to_string(&name).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps, attr) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) throw new Error('Element not found');
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                return els[0].getAttribute(attr) ?? '';
+            }})({steps_json}, {name_json})"#
+        );
+        self.evaluate_internal_with_resolver(expression).await
+    }
+
+    #[qjs(rename = "isVisible")]
+    pub async fn is_visible(&self) -> JsResult<bool> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) return false;
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                const el = els[0];
+                if (!el.isConnected) return false;
+                const style = window.getComputedStyle(el);
+                if (!style || style.display === 'none' || style.visibility === 'hidden' || style.opacity === '0') return false;
+                const rect = el.getBoundingClientRect();
+                return rect.width > 0 && rect.height > 0;
+            }})({steps_json})"#
+        );
+        let res = self.evaluate_internal_with_resolver(expression).await?;
+        Ok(res == "true")
+    }
+
+    #[qjs(rename = "isEnabled")]
+    pub async fn is_enabled(&self) -> JsResult<bool> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) return false;
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                return !els[0].disabled;
+            }})({steps_json})"#
+        );
+        let res = self.evaluate_internal_with_resolver(expression).await?;
+        Ok(res == "true")
+    }
+
+    pub async fn wait_for(&self, options: Option<rquickjs::Value<'_>>) -> JsResult<()> {
+        let mut state = "visible".to_string();
+        let mut timeout = DEFAULT_TIMEOUT_MS;
+
+        if let Some(opts) = options {
+            if let Some(obj) = opts.as_object() {
+                if let Ok(Some(s)) = obj.get::<_, Option<String>>("state") {
+                    state = s;
+                }
+                if let Ok(Some(t)) = obj.get::<_, Option<u64>>("timeout") {
+                    timeout = t;
+                }
+            }
+        }
+
+        self.ensure_element_state(&state, timeout).await
+    }
+
+    pub async fn screenshot<'js>(
+        &self,
+        ctx: rquickjs::Ctx<'js>,
+        options: Opt<Value<'js>>,
+    ) -> JsResult<rquickjs::TypedArray<'js, u8>> {
+        let parsed = parse_screenshot_options(options.0.as_ref(), false)?;
+        let clip = self.screenshot_clip().await?;
+        let mut mask_clips = Vec::with_capacity(parsed.mask_locators.len());
+        for mask in &parsed.mask_locators {
+            mask_clips.push(mask.screenshot_clip().await?);
+        }
+        let path = {
+            let inner = self.inner.lock().await;
+            resolve_screenshot_output_path(&inner.download_dir, parsed.path.as_deref())?
+        };
+        let bytes =
+            run_screenshot_capture(self.inner.clone(), &parsed, Some(clip), &mask_clips, path)
+                .await?;
+        rquickjs::TypedArray::new_copy(ctx, bytes)
+            .map_err(|e| js_err(format!("Locator.screenshot failed: {e}")))
+    }
+
+    /// The element's box in viewport coordinates once it is visible, as
+    /// `{ x, y, width, height }`, for `page.mouse`.
+    #[qjs(rename = "boundingBox")]
+    pub async fn bounding_box(&self) -> JsResult<JsEvalResult> {
+        let clip = self.screenshot_clip().await?;
+        let json = serde_json::json!({
+            "x": clip.x,
+            "y": clip.y,
+            "width": clip.width,
+            "height": clip.height,
+        });
+        Ok(JsEvalResult::Json(format!("({json})")))
+    }
+}
+
+impl Locator {
+    pub(crate) async fn click_with_options(&self, options: ActionOptions) -> JsResult<()> {
+        self.perform(Action::Click, options, || self.try_click(options.force))
+            .await
+    }
+
+    pub(crate) async fn fill_with_options(
+        &self,
+        value: String,
+        options: ActionOptions,
+    ) -> JsResult<()> {
+        let actual_value = {
+            let inner = self.inner.lock().await;
+            resolve_secret_if_applicable(&inner, &value).await?
+        };
+        let value_json = serde_json::to_string(actual_value.expose_secret()).unwrap_or_default();
+        self.perform(Action::Fill, options, || self.try_fill(&value_json))
+            .await
+    }
+
+    pub(crate) async fn select_option_with_options(
+        &self,
+        targets: &[SelectTarget],
+        options: ActionOptions,
+    ) -> JsResult<Vec<String>> {
+        let targets_json = serde_json::to_string(targets).unwrap_or_default();
+        let targets_json = targets_json.as_str();
+        let selected = std::cell::RefCell::new(Vec::new());
+        let selected_ref = &selected;
+        self.perform(Action::SelectOption, options, move || async move {
+            *selected_ref.borrow_mut() = self.try_select_option(targets_json).await?;
+            Ok(())
+        })
+        .await?;
+        Ok(selected.into_inner())
+    }
+
+    pub(crate) async fn set_checked_with_options(
+        &self,
+        checked: bool,
+        options: ActionOptions,
+    ) -> JsResult<()> {
+        let action = if checked {
+            Action::Check
+        } else {
+            Action::Uncheck
+        };
+        self.perform(action, options, || async move {
+            if self.checked_state(checked).await? == checked {
+                return Ok(());
+            }
+            self.try_click(options.force).await?;
+            if self.checked_state(checked).await? != checked {
+                return Err(AttemptError::Fatal(js_err(format!(
+                    "{} failed: clicking the element did not change its state",
+                    action.name()
+                ))));
+            }
+            Ok(())
+        })
+        .await
+    }
+
+    /// Wait until the element passes the checks for `action`, without acting.
+    pub(crate) async fn wait_for_actionable(
+        &self,
+        action: Action,
+        options: ActionOptions,
+    ) -> JsResult<()> {
+        self.perform(action, options, || async { Ok(()) }).await
+    }
+
+    /// Run `attempt` once the element passes the checks for `action`,
+    /// retrying both until `attempt` succeeds or the timeout expires.
+    async fn perform<F, Fut>(
+        &self,
+        action: Action,
+        options: ActionOptions,
+        mut attempt: F,
+    ) -> JsResult<()>
+    where
+        F: FnMut() -> Fut,
+        Fut: std::future::Future<Output = Result<(), AttemptError>>,
+    {
+        let deadline =
+            tokio::time::Instant::now() + std::time::Duration::from_millis(options.timeout_ms);
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let checks_json = serde_json::to_string(action.checks(options.force)).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps, checks) => {{
+                {ACTIONABILITY_JS}
+                try {{
+                    const els = await resolveLocator(steps);
+                    if (els.length > 1) return {{ error: strictModeViolation(els) }};
+                    return {{ waiting: await actionabilityError(els[0], checks) }};
+                }} catch (err) {{
+                    return {{ waiting: String(err) }};
+                }}
+            }})({steps_json}, {checks_json})"#
+        );
+
+        loop {
+            let res = self
+                .evaluate_internal_with_resolver(expression.clone())
+                .await?;
+            let val: serde_json::Value = serde_json::from_str(&res).unwrap_or_default();
+            if let Some(err) = val.get("error").and_then(serde_json::Value::as_str) {
+                return Err(js_err(format!("{} failed: {err}", action.name())));
+            }
+            let reason = match val.get("waiting").and_then(serde_json::Value::as_str) {
+                Some("") => match attempt().await {
+                    Ok(()) => return Ok(()),
+                    Err(AttemptError::NotReady(reason)) => reason,
+                    Err(AttemptError::Fatal(err)) => return Err(err),
+                },
+                Some(reason) => reason.to_string(),
+                None => res,
+            };
+            tracing::debug!("{} waiting: {reason}", action.name());
+
+            if tokio::time::Instant::now() >= deadline {
+                return Err(js_err(format!(
+                    "TimeoutError: {} failed: timeout {}ms exceeded waiting for {}; last state: {reason}",
+                    action.name(),
+                    options.timeout_ms,
+                    debug_selector_string(&self.steps),
+                )));
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(POLL_INTERVAL_MS)).await;
+        }
+    }
+
+    /// One fill attempt; the element may have been replaced since the check.
+    async fn try_fill(&self, value_json: &str) -> Result<(), AttemptError> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps, val) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) return 'element not found';
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                const el = els[0];
+                if (!el.isConnected) return 'element is detached from the document';
+                el.scrollIntoView({{ block: 'center', inline: 'center', behavior: 'instant' }});
+                el.focus();
+                el.value = val;
+                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                return '';
+            }})({steps_json}, {value_json})"#
+        );
+
+        let result = self.evaluate_internal_with_resolver(expression).await?;
+        match serde_json::from_str::<String>(&result) {
+            Ok(reason) if !reason.is_empty() => Err(AttemptError::NotReady(reason)),
+            _ => self
+                .check_error(&result, "fill")
+                .map_err(AttemptError::Fatal),
+        }
+    }
+
+    /// Whether the checkbox or radio button is checked. A `<label>` reports
+    /// its control; anything else, or unchecking a checked radio, fails.
+    async fn checked_state(&self, want: bool) -> Result<bool, AttemptError> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps, want) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) return {{ waiting: 'element not found' }};
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                if (!els[0].isConnected) return {{ waiting: 'element is detached from the document' }};
+                const el = els[0].tagName === 'LABEL' && els[0].control ? els[0].control : els[0];
+                const isInput = el.tagName === 'INPUT' && (el.type === 'checkbox' || el.type === 'radio');
+                const role = el.getAttribute('role');
+                if (!isInput && !['checkbox', 'radio', 'switch', 'menuitemcheckbox', 'menuitemradio'].includes(role)) {{
+                    return {{ error: 'element is not a checkbox or radio button' }};
+                }}
+                const checked = isInput ? el.checked : el.getAttribute('aria-checked') === 'true';
+                const radio = isInput ? el.type === 'radio' : role === 'radio' || role === 'menuitemradio';
+                if (radio && checked && !want) return {{ error: 'cannot uncheck a radio button' }};
+                return {{ checked }};
+            }})({steps_json}, {want})"#
+        );
+
+        let result = self.evaluate_internal_with_resolver(expression).await?;
+        let val: serde_json::Value = serde_json::from_str(&result).unwrap_or_default();
+        if let Some(reason) = val.get("waiting").and_then(serde_json::Value::as_str) {
+            return Err(AttemptError::NotReady(reason.to_string()));
+        }
+        let action = if want { "check" } else { "uncheck" };
+        if let Some(err) = val.get("error").and_then(serde_json::Value::as_str) {
+            return Err(AttemptError::Fatal(js_err(format!(
+                "{action} failed: {err}"
+            ))));
+        }
+        val.get("checked")
+            .and_then(serde_json::Value::as_bool)
+            .ok_or_else(|| AttemptError::Fatal(js_err(format!("{action} failed: {result}"))))
+    }
+
+    /// One selectOption attempt. Options that are not there yet (lists
+    /// filled in after load) are waited for; a non-`<select>` element fails.
+    async fn try_select_option(&self, targets_json: &str) -> Result<Vec<String>, AttemptError> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps, targets) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) return {{ waiting: 'element not found' }};
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                const el = els[0];
+                if (!el.isConnected) return {{ waiting: 'element is detached from the document' }};
+                if (el.tagName !== 'SELECT') return {{ error: 'element is not a <select> element' }};
+                const options = Array.from(el.options);
+                const picked = [];
+                for (const target of targets) {{
+                    const option = options.find((o, i) =>
+                        (target.valueOrLabel === undefined
+                            || o.value === target.valueOrLabel || o.label === target.valueOrLabel)
+                        && (target.value === undefined || o.value === target.value)
+                        && (target.label === undefined || o.label === target.label)
+                        && (target.index === undefined || i === target.index));
+                    if (!option) return {{ waiting: 'no option matching ' + JSON.stringify(target) }};
+                    picked.push(option);
+                }}
+                if (picked.length > 1 && !el.multiple) {{
+                    return {{ error: 'element is not a <select multiple>' }};
+                }}
+                el.focus();
+                for (const o of options) o.selected = picked.includes(o);
+                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
+                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
+                return {{ selected: picked.map((o) => o.value) }};
+            }})({steps_json}, {targets_json})"#
+        );
+
+        let result = self.evaluate_internal_with_resolver(expression).await?;
+        let val: serde_json::Value = serde_json::from_str(&result).unwrap_or_default();
+        if let Some(reason) = val.get("waiting").and_then(serde_json::Value::as_str) {
+            return Err(AttemptError::NotReady(reason.to_string()));
+        }
+        if let Some(err) = val.get("error").and_then(serde_json::Value::as_str) {
+            return Err(AttemptError::Fatal(js_err(format!(
+                "selectOption failed: {err}"
+            ))));
+        }
+        match val.get("selected").and_then(serde_json::Value::as_array) {
+            Some(values) => Ok(values
+                .iter()
+                .filter_map(serde_json::Value::as_str)
+                .map(str::to_string)
+                .collect()),
+            None => Err(AttemptError::Fatal(js_err(format!(
+                "selectOption failed: {result}"
+            )))),
+        }
+    }
+
+    /// One focus or blur attempt; the element may have been replaced since
+    /// the check.
+    async fn try_focus(&self, action: Action) -> Result<(), AttemptError> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let method = action.name();
+        let expression = format!(
+            r#"(async (steps) => {{
+                const els = await resolveLocator(steps);
+                if (els.length === 0) return 'element not found';
+                if (els.length > 1) throw new Error(strictModeViolation(els));
+                const el = els[0];
+                if (!el.isConnected) return 'element is detached from the document';
+                el.{method}();
+                return '';
+            }})({steps_json})"#
+        );
+
+        let result = self.evaluate_internal_with_resolver(expression).await?;
+        match serde_json::from_str::<String>(&result) {
+            Ok(reason) if !reason.is_empty() => Err(AttemptError::NotReady(reason)),
+            _ => self
+                .check_error(&result, method)
+                .map_err(AttemptError::Fatal),
+        }
+    }
+
+    /// One click attempt on an element that passed the actionability checks.
+    /// The page can still change under us, so a detached, covered, or
+    /// off-screen element is reported as not ready rather than failing.
+    async fn try_click(&self, force: bool) -> Result<(), AttemptError> {
+        // Hold the lock for the entire click sequence.
+        let inner = self.inner.lock().await;
+        let point = self.clickable_point(&inner, force).await?;
+
+        // E. Trusted mouse click via Input.dispatchMouseEvent.
+        inner
+            .page
+            .click(point)
+            .await
+            .map_err(|e| js_err(format!("click: dispatch: {e}")))?;
+
+        Ok(())
+    }
 
-        // Hold the lock for the entire click sequence.
-        let inner = self.inner.lock().await;
+    /// Viewport coordinates of the element's center, once it is actionable
+    /// for `action`, for callers that drive the mouse themselves.
+    pub(crate) async fn action_point(
+        &self,
+        action: Action,
+        options: ActionOptions,
+    ) -> JsResult<(f64, f64)> {
+        let point = std::cell::Cell::new(None);
+        let found_point = &point;
+        self.perform(action, options, move || async move {
+            let inner = self.inner.lock().await;
+            let found = self.clickable_point(&inner, options.force).await?;
+            found_point.set(Some((found.x, found.y)));
+            Ok(())
+        })
+        .await?;
+        point
+            .get()
+            .ok_or_else(|| js_err(format!("{}: element has no position", action.name())))
+    }
 
+    /// Scroll the element into view, check that a click would land on it,
+    /// and return its center.
+    async fn clickable_point(
+        &self,
+        inner: &PageInner,
+        force: bool,
+    ) -> Result<chromiumoxide::layout::Point, AttemptError> {
         // A. Determine frame execution context.
-        let execution_target = if let Some(frame_id) = &inner.target_frame_id {
+        let execution_target = if let Some(frame_id) = self.frame_target(&inner).await? {
             Some(
-                wait_for_frame_execution_target(&inner.page, frame_id.clone())
+                wait_for_frame_execution_target(&inner.page, frame_id)
                     .await
                     .map_err(|e| js_err(format!("click: frame target: {e}")))?,
             )
@@ -698,8 +2090,8 @@ impl Locator {
             let expression = format!(
                 r#"(async (steps) => {{
                     const els = await resolveLocator(steps);
-                    if (els.length === 0) throw new Error('Element not found');
-                    if (els.length > 1) throw new Error('Strict mode violation: ' + els.length + ' elements found');
+                    if (els.length === 0) return null;
+                    if (els.length > 1) throw new Error(strictModeViolation(els));
                     return els[0];
                 }})({steps_json})"#
             );
@@ -734,7 +2126,7 @@ impl Locator {
                 .object()
                 .object_id
                 .clone()
-                .ok_or_else(|| js_err("click: element resolved to null".to_string()))?
+                .ok_or_else(|| AttemptError::NotReady("element not found".to_string()))?
         };
 
         // C. Scroll into view and check actionability (detached, visible, not occluded).
@@ -805,8 +2197,9 @@ impl Locator {
             .as_ref()
             .and_then(|v| v.as_str())
             .unwrap_or_default();
-        if !msg.is_empty() {
-            return Err(js_err(format!("click: {msg}")));
+        // `force` still needs an attached element with a box to click.
+        if !msg.is_empty() && !(force && msg.ends_with("intercepts pointer events")) {
+            return Err(AttemptError::NotReady(msg.to_string()));
         }
 
         // D. Get clickable coordinates — DOM.getContentQuads returns top-level viewport coords,
@@ -827,7 +2220,7 @@ impl Locator {
                 .await
                 .map_err(|e| js_err(format!("click: getContentQuads: {e}")))?
         };
-        let point = quads
+        quads
             .quads
             .iter()
             .filter(|q| q.inner().len() == 8)
@@ -835,171 +2228,9 @@ impl Locator {
             .filter(|q| q.quad_area() > 1.)
             .map(|q| q.quad_center())
             .next()
-            .ok_or_else(|| js_err("click: element not visible in viewport".to_string()))?;
-
-        // E. Trusted mouse click via Input.dispatchMouseEvent.
-        inner
-            .page
-            .click(point)
-            .await
-            .map_err(|e| js_err(format!("click: dispatch: {e}")))?;
-
-        Ok(())
-    }
-
-    /// Click the element using a trusted CDP mouse click (Input.dispatchMouseEvent).
-    ///
-    /// Unlike `el.click()` via Runtime.evaluate, this produces `isTrusted: true` events,
-    /// which is required for sites that check event.isTrusted (e.g. login flows).
-    pub async fn click(&self, options: Opt<Value<'_>>) -> JsResult<()> {
-        let timeout_ms = parse_timeout(options.0);
-        self.click_with_timeout(timeout_ms).await
-    }
-
-    /// Fill the input.
-    pub async fn fill(&self, value: String, options: Opt<Value<'_>>) -> JsResult<()> {
-        let timeout_ms = parse_timeout(options.0);
-        self.ensure_element_state("visible", timeout_ms).await?;
-
-        let inner = self.inner.lock().await;
-        let actual_value: String = resolve_secret_if_applicable(&inner, &value).await?;
-        drop(inner);
-
-        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
-        let value_json = serde_json::to_string(&actual_value).unwrap_or_default();
-        let expression = format!(
-            r#"(async (steps, val) => {{
-                const els = await resolveLocator(steps);
-                if (els.length === 0) return 'Element not found';
-                if (els.length > 1) return 'Strict mode violation: ' + els.length + ' elements found';
-                const el = els[0];
-                if (!el.isConnected) return 'Node is detached from document';
-                el.scrollIntoView({{ block: 'center', inline: 'center', behavior: 'instant' }});
-                el.focus();
-                el.value = val;
-                el.dispatchEvent(new Event('input', {{ bubbles: true }}));
-                el.dispatchEvent(new Event('change', {{ bubbles: true }}));
-                return '';
-            }})({steps_json}, {value_json})"#
-        );
-
-        let result = self.evaluate_internal_with_resolver(expression).await?;
-        self.check_error(&result, "fill")
-    }
-
-    #[qjs(rename = "innerText")]
-    pub async fn inner_text(&self, options: Opt<Value<'_>>) -> JsResult<String> {
-        let timeout_ms = parse_timeout(options.0);
-        self.get_property("innerText", timeout_ms).await
-    }
-
-    #[qjs(rename = "textContent")]
-    pub async fn text_content(&self, options: Opt<Value<'_>>) -> JsResult<String> {
-        let timeout_ms = parse_timeout(options.0);
-        self.get_property("textContent", timeout_ms).await
-    }
-
-    #[qjs(rename = "inputValue")]
-    pub async fn input_value(&self, options: Opt<Value<'_>>) -> JsResult<String> {
-        let timeout_ms = parse_timeout(options.0);
-        self.get_property("value", timeout_ms).await
-    }
-
-    #[qjs(rename = "getAttribute")]
-    pub async fn get_attribute(&self, name: String, options: Opt<Value<'_>>) -> JsResult<String> {
-        let timeout_ms = parse_timeout(options.0);
-        self.ensure_element_state("attached", timeout_ms).await?;
-
-        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
-        let name_json = serde_json::to_string(&name).unwrap_or_default();
-        let expression = format!(
-            r#"(async (steps, attr) => {{
-                const els = await resolveLocator(steps);
-                if (els.length === 0) throw new Error('Element not found');
-                if (els.length > 1) throw new Error('Strict mode violation: ' + els.length + ' elements found');
-                return els[0].getAttribute(attr) ?? '';
-            }})({steps_json}, {name_json})"#
-        );
-        self.evaluate_internal_with_resolver(expression).await
-    }
-
-    #[qjs(rename = "isVisible")]
-    pub async fn is_visible(&self) -> JsResult<bool> {
-        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
-        let expression = format!(
-            r#"(async (steps) => {{
-                const els = await resolveLocator(steps);
-                if (els.length === 0) return false;
-                if (els.length > 1) throw new Error('Strict mode violation: ' + els.length + ' elements found');
-                const el = els[0];
-                if (!el.isConnected) return false;
-                const style = window.getComputedStyle(el);
-                if (!style || style.display === 'none' || style.visibility === 'hidden' || style.opacity === '0') return false;
-                const rect = el.getBoundingClientRect();
-                return rect.width > 0 && rect.height > 0;
-            }})({steps_json})"#
-        );
-        let res = self.evaluate_internal_with_resolver(expression).await?;
-        Ok(res == "true")
-    }
-
-    #[qjs(rename = "isEnabled")]
-    pub async fn is_enabled(&self) -> JsResult<bool> {
-        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
-        let expression = format!(
-            r#"(async (steps) => {{
-                const els = await resolveLocator(steps);
-                if (els.length === 0) return false;
-                if (els.length > 1) throw new Error('Strict mode violation: ' + els.length + ' elements found');
-                return !els[0].disabled;
-            }})({steps_json})"#
-        );
-        let res = self.evaluate_internal_with_resolver(expression).await?;
-        Ok(res == "true")
-    }
-
-    pub async fn wait_for(&self, options: Option<rquickjs::Value<'_>>) -> JsResult<()> {
-        let mut state = "visible".to_string();
-        let mut timeout = DEFAULT_TIMEOUT_MS;
-
-        if let Some(opts) = options {
-            if let Some(obj) = opts.as_object() {
-                if let Ok(Some(s)) = obj.get::<_, Option<String>>("state") {
-                    state = s;
-                }
-                if let Ok(Some(t)) = obj.get::<_, Option<u64>>("timeout") {
-                    timeout = t;
-                }
-            }
-        }
-
-        self.ensure_element_state(&state, timeout).await
-    }
-
-    pub async fn screenshot<'js>(
-        &self,
-        ctx: rquickjs::Ctx<'js>,
-        options: Opt<Value<'js>>,
-    ) -> JsResult<rquickjs::TypedArray<'js, u8>> {
-        let parsed = parse_screenshot_options(options.0.as_ref(), false)?;
-        let clip = self.screenshot_clip().await?;
-        let mut mask_clips = Vec::with_capacity(parsed.mask_locators.len());
-        for mask in &parsed.mask_locators {
-            mask_clips.push(mask.screenshot_clip().await?);
-        }
-        let path = {
-            let inner = self.inner.lock().await;
-            resolve_screenshot_output_path(&inner.download_dir, parsed.path.as_deref())?
-        };
-        let bytes =
-            run_screenshot_capture(self.inner.clone(), &parsed, Some(clip), &mask_clips, path)
-                .await?;
-        rquickjs::TypedArray::new_copy(ctx, bytes)
-            .map_err(|e| js_err(format!("Locator.screenshot failed: {e}")))
+            .ok_or_else(|| AttemptError::NotReady("element is not in the viewport".to_string()))
     }
-}
 
-impl Locator {
     pub(crate) async fn screenshot_clip(&self) -> JsResult<ScreenshotClip> {
         self.ensure_element_state("visible", DEFAULT_TIMEOUT_MS)
             .await?;
@@ -1016,9 +2247,9 @@ impl Locator {
 
     async fn evaluate_internal(&self, expression: String) -> JsResult<String> {
         let inner = self.inner.lock().await;
-        let execution_target = if let Some(frame_id) = &inner.target_frame_id {
+        let execution_target = if let Some(frame_id) = self.frame_target(&inner).await? {
             Some(
-                wait_for_frame_execution_target(&inner.page, frame_id.clone())
+                wait_for_frame_execution_target(&inner.page, frame_id)
                     .await
                     .map_err(|e| js_err(format!("failed to get frame target: {e}")))?,
             )
@@ -1055,7 +2286,7 @@ impl Locator {
 
         let mut text =
             stringify_evaluation_result(result.value(), result.object().description.as_deref());
-        scrub_known_secrets(&inner.secret_store, &mut text);
+        scrub_known_secrets(&inner.secrets, &mut text);
         Ok(text)
     }
 
@@ -1073,7 +2304,21 @@ impl Locator {
         Ok(())
     }
 
-    async fn get_property(&self, prop: &str, timeout_ms: u64) -> JsResult<String> {
+    /// The strict mode violation message, with a hint per match, when this
+    /// locator currently matches more than one element.
+    pub(crate) async fn strict_mode_violation(&self) -> JsResult<Option<String>> {
+        let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
+        let expression = format!(
+            r#"(async (steps) => {{
+                const els = await resolveLocator(steps);
+                return els.length > 1 ? strictModeViolation(els) : '';
+            }})({steps_json})"#
+        );
+        let message = self.evaluate_internal_with_resolver(expression).await?;
+        Ok((!message.is_empty()).then_some(message))
+    }
+
+    pub(crate) async fn get_property(&self, prop: &str, timeout_ms: u64) -> JsResult<String> {
         self.ensure_element_state("attached", timeout_ms).await?;
 
         let steps_json = serde_json::to_string(&self.steps).unwrap_or_default();
@@ -1082,7 +2327,7 @@ impl Locator {
             r#"(async (steps, prop) => {{
                 const els = await resolveLocator(steps);
                 if (els.length === 0) throw new Error('Element not found');
-                if (els.length > 1) throw new Error('Strict mode violation: ' + els.length + ' elements found');
+                if (els.length > 1) throw new Error(strictModeViolation(els));
                 return els[0][prop] ?? '';
             }})({steps_json}, {prop_json})"#
         );
@@ -1111,7 +2356,7 @@ impl Locator {
                 }};
                 try {{
                     const els = await resolveLocator(steps);
-                    if (els.length > 1) return {{ error: 'Strict mode violation: ' + els.length + ' elements found' }};
+                    if (els.length > 1) return {{ error: strictModeViolation(els) }};
                     const el = els[0];
                     {state_check};
                 }} catch (err) {{
@@ -1133,7 +2378,7 @@ impl Locator {
                     if err.contains("Strict mode violation") {
                         return Err(js_err(format!("wait_for({state}) failed: {err}")));
                     }
-                    eprintln!("ensure_element_state error: {err}");
+                    tracing::debug!("ensure_element_state error: {err}");
                 }
             }
 
@@ -1468,6 +2713,266 @@ mod tests {
         assert!(json.contains("\"name\":\"Email\""));
     }
 
+    #[test]
+    fn test_parse_text_selector() {
+        let text_of = |selector: &str| match parse_text_selector(selector) {
+            Some(LocatorStep::Text {
+                text,
+                pattern,
+                flags,
+                exact,
+                ..
+            }) => (text, pattern, flags, exact),
+            other => panic!("expected text step for {selector}, got {other:?}"),
+        };
+        assert_eq!(
+            text_of("text=Download statements"),
+            ("Download statements".to_string(), None, None, false)
+        );
+        assert_eq!(
+            text_of(r#"text="Sign in""#),
+            ("Sign in".to_string(), None, None, true)
+        );
+        assert_eq!(
+            text_of(r#"text="Say \"hi\""i"#),
+            ("Say \"hi\"".to_string(), None, None, false)
+        );
+        assert_eq!(
+            text_of("text=/statement(s)?/i"),
+            (
+                String::new(),
+                Some("statement(s)?".to_string()),
+                Some("i".to_string()),
+                false
+            )
+        );
+        assert!(parse_text_selector("text=").is_none());
+        assert!(parse_text_selector("button.text").is_none());
+    }
+
+    #[test]
+    fn test_chain_and_debug_text_steps() {
+        let steps = chain_selector(
+            &[parse_step("#accounts".to_string())],
+            "text=/^Checking/".to_string(),
+        );
+        let steps = chain_nth(&steps, 1);
+        assert!(matches!(
+            &steps[1],
+            LocatorStep::Text { index: Some(1), .. }
+        ));
+        assert_eq!(
+            debug_selector_string(&steps),
+            "#accounts >> text=/^Checking/ >> nth=1"
+        );
+        let json = selector_steps_json(r#"text="Sign in""#);
+        assert!(json.contains("\"type\":\"text\""), "{json}");
+        assert!(json.contains("\"exact\":true"), "{json}");
+    }
+
+    #[test]
+    fn test_parse_and_debug_label_steps() {
+        match parse_step(r#"label="Account number"i"#.to_string()) {
+            LocatorStep::Label {
+                text,
+                pattern: None,
+                exact: false,
+                ..
+            } => assert_eq!(text, "Account number"),
+            other => panic!("expected label step, got {other:?}"),
+        }
+        let steps = chain_selector(
+            &[parse_step("form#login".to_string())],
+            "label=/^Pass(word|code)$/".to_string(),
+        );
+        let steps = chain_nth(&steps, 0);
+        assert!(matches!(
+            &steps[1],
+            LocatorStep::Label {
+                pattern: Some(_),
+                index: Some(0),
+                ..
+            }
+        ));
+        assert_eq!(
+            debug_selector_string(&steps),
+            "form#login >> label=/^Pass(word|code)$/ >> nth=0"
+        );
+        let json = selector_steps_json(r#"label="Email""#);
+        assert!(json.contains("\"type\":\"label\""), "{json}");
+        assert!(json.contains("\"exact\":true"), "{json}");
+        assert!(parse_label_selector("label=").is_none());
+    }
+
+    #[test]
+    fn test_filter_steps_serialize_and_debug() {
+        let rows = parse_step("table#activity tr".to_string());
+        let filter = LocatorStep::Filter {
+            has_text: Some(TextFilter {
+                text: "Pending".to_string(),
+                pattern: None,
+                flags: None,
+            }),
+            has_not_text: None,
+            has: Some(vec![parse_step("button.download".to_string())]),
+            has_not: None,
+            index: None,
+        };
+        let steps = chain_nth(&[rows, filter], 2);
+        assert!(matches!(
+            &steps[1],
+            LocatorStep::Filter { index: Some(2), .. }
+        ));
+        assert_eq!(
+            debug_selector_string(&steps),
+            r#"table#activity tr >> has-text="Pending" >> has="button.download" >> nth=2"#
+        );
+        let json = match serde_json::to_string(&steps) {
+            Ok(json) => json,
+            Err(err) => panic!("serialize steps: {err}"),
+        };
+        assert!(json.contains(r#""type":"filter""#), "{json}");
+        assert!(json.contains(r#""hasText":{"text":"Pending""#), "{json}");
+        assert!(json.contains(r#""hasNot":null"#), "{json}");
+        assert!(
+            json.contains(r#""has":[{"type":"css","selector":"button.download""#),
+            "{json}"
+        );
+    }
+
+    #[test]
+    fn test_parse_xpath_selector() {
+        let selector_of = |selector: &str| match parse_step(selector.to_string()) {
+            LocatorStep::Xpath { selector, .. } => selector,
+            other => panic!("expected xpath step for {selector}, got {other:?}"),
+        };
+        assert_eq!(
+            selector_of("xpath=//table[@id='tx']//tr[2]"),
+            "//table[@id='tx']//tr[2]"
+        );
+        assert_eq!(
+            selector_of("//td[contains(., 'Balance')]"),
+            "//td[contains(., 'Balance')]"
+        );
+        assert_eq!(
+            selector_of("../following-sibling::td"),
+            "../following-sibling::td"
+        );
+        assert!(parse_xpath_selector("xpath=").is_none());
+        assert!(matches!(
+            parse_step("div > a".to_string()),
+            LocatorStep::Css { .. }
+        ));
+
+        let steps = chain_nth(
+            &chain_selector(
+                &[parse_step("#main".to_string())],
+                "xpath=.//tr".to_string(),
+            ),
+            -1,
+        );
+        assert_eq!(
+            debug_selector_string(&steps),
+            "#main >> xpath=.//tr >> nth=-1"
+        );
+        assert!(selector_steps_json("//tr").contains("\"type\":\"xpath\""));
+    }
+
+    #[test]
+    fn test_parse_selector_chains() {
+        let steps = parse_selector(r#"sf-login-form >> input[name="username"]"#);
+        assert_eq!(
+            debug_selector_string(&steps),
+            r#"sf-login-form >> input[name="username"]"#
+        );
+        let steps =
+            parse_selector(r#"c-statements >> role=button[name="a >> b"] >> nth=1 >> text=/x>>y/"#);
+        assert_eq!(steps.len(), 3);
+        assert!(matches!(
+            &steps[1],
+            LocatorStep::Role { index: Some(1), .. }
+        ));
+        match &steps[2] {
+            LocatorStep::Text { pattern, .. } => assert_eq!(pattern.as_deref(), Some("x>>y")),
+            other => panic!("expected text step, got {other:?}"),
+        }
+        assert_eq!(
+            split_selector_chain("xpath=//div[@class='a>>b'] >> span"),
+            ["xpath=//div[@class='a>>b']", "span"]
+        );
+        assert_eq!(parse_selector("div > a").len(), 1);
+        let chained = chain_selector(&parse_selector("#app"), "my-card >> button".to_string());
+        assert_eq!(chained.len(), 3);
+        assert!(selector_steps_json("my-card >> text=Pay").contains("\"type\":\"text\""));
+    }
+
+    #[test]
+    fn test_parse_has_text_pseudo_class() {
+        let steps = parse_selector(r#"#activity tr:has-text("Pending") > td button"#);
+        assert_eq!(
+            debug_selector_string(&steps),
+            r#"#activity tr >> has-text="Pending" >> :scope > td button"#
+        );
+        let steps = parse_selector("button:has-text(Download CSV) >> nth=0");
+        match &steps[1] {
+            LocatorStep::Filter {
+                has_text: Some(filter),
+                index: Some(0),
+                ..
+            } => assert_eq!(filter.text, "Download CSV"),
+            other => panic!("expected has-text filter, got {other:?}"),
+        }
+        assert_eq!(
+            debug_selector_string(&parse_selector(r#":has-text("Statements")"#)),
+            r#"* >> has-text="Statements""#
+        );
+        for css in [r#"a[title=":has-text(x)"]"#, r#"li:has-text("x") + li"#] {
+            assert_eq!(debug_selector_string(&parse_selector(css)), css);
+        }
+    }
+
+    #[test]
+    fn test_action_checks() {
+        assert_eq!(
+            Action::Click.checks(false),
+            ["attached", "visible", "stable", "enabled"]
+        );
+        assert_eq!(
+            Action::Fill.checks(false),
+            ["attached", "visible", "enabled", "editable"]
+        );
+        assert_eq!(Action::Type.checks(true), ["attached"]);
+        assert_eq!(
+            Action::Hover.checks(false),
+            ["attached", "visible", "stable"]
+        );
+        assert_eq!(Action::Focus.checks(false), ["attached"]);
+        assert_eq!(Action::Check.checks(false), Action::Click.checks(false));
+        assert_eq!(Action::Uncheck.checks(true), ["attached"]);
+        assert_eq!(
+            Action::SelectOption.checks(false),
+            ["attached", "visible", "enabled"]
+        );
+        assert_eq!(ActionOptions::default().timeout_ms, DEFAULT_TIMEOUT_MS);
+    }
+
+    #[test]
+    fn test_select_target_serialization() {
+        let by_text = SelectTarget {
+            value_or_label: Some("Checking ...1234".into()),
+            ..SelectTarget::default()
+        };
+        let by_index = SelectTarget {
+            index: Some(2),
+            ..SelectTarget::default()
+        };
+        let json = match serde_json::to_string(&[by_text, by_index]) {
+            Ok(json) => json,
+            Err(err) => panic!("failed to serialize select targets: {err}"),
+        };
+        assert_eq!(json, r#"[{"valueOrLabel":"Checking ...1234"},{"index":2}]"#);
+    }
+
     #[test]
     fn test_css_step_serialization() {
         let step = LocatorStep::Css {
@@ -0,0 +1,140 @@
+//! Manifest `loginWallUrls` and automatic re-login.
+//!
+//! When a step that can navigate (`page.goto`, `page.reload`, a click) ends
+//! on a URL matching one of the manifest patterns, the session has expired.
+//! If the driver registered its login entry point with
+//! `refreshmint.setLoginHandler(login)`, the runner calls it, returns to
+//! where the step started, and repeats the step once.
+
+use std::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+
+/// Shared by every page of one scrape or debug session.
+#[derive(Debug, Default)]
+pub struct LoginWall {
+    /// Compiled manifest patterns; empty disables detection.
+    patterns: Vec<regex::Regex>,
+    /// Set while the login handler runs, so its own navigations to the
+    /// login page are not treated as another expired session.
+    in_login: AtomicBool,
+    relogins: AtomicU32,
+}
+
+/// Clears [`LoginWall`]'s in-login flag when the login handler finishes or
+/// fails.
+pub struct LoginGuard<'a> {
+    wall: &'a LoginWall,
+}
+
+impl Drop for LoginGuard<'_> {
+    fn drop(&mut self) {
+        self.wall.in_login.store(false, Ordering::SeqCst);
+    }
+}
+
+impl LoginWall {
+    /// Patterns must have passed [`normalize_login_wall_patterns`].
+    pub fn new(patterns: &[String]) -> Self {
+        Self {
+            patterns: patterns
+                .iter()
+                .filter_map(|pattern| compile_pattern(pattern).ok())
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the manifest declares any login wall.
+    pub fn is_enabled(&self) -> bool {
+        !self.patterns.is_empty()
+    }
+
+    /// Whether `url` is a login wall the runner should react to. Always
+    /// false while the login handler runs.
+    pub fn blocks(&self, url: &str) -> bool {
+        !self.in_login.load(Ordering::SeqCst) && self.matches(url)
+    }
+
+    /// Whether `url` matches one of the patterns.
+    pub fn matches(&self, url: &str) -> bool {
+        self.patterns.iter().any(|regex| regex.is_match(url))
+    }
+
+    /// Mark the login handler as running; `None` if it already is.
+    pub fn begin_login(&self) -> Option<LoginGuard<'_>> {
+        if self.in_login.swap(true, Ordering::SeqCst) {
+            return None;
+        }
+        self.relogins.fetch_add(1, Ordering::SeqCst);
+        Some(LoginGuard { wall: self })
+    }
+
+    /// How many times the login handler has run this session.
+    pub fn relogins(&self) -> u32 {
+        self.relogins.load(Ordering::SeqCst)
+    }
+}
+
+/// Validate manifest `loginWallUrls` entries: URL globs with the same
+/// syntax as `page.waitForURL` (`*` within a path segment, `**` across
+/// segments, `{a,b}` alternatives).
+pub fn normalize_login_wall_patterns(entries: &[String]) -> Result<Vec<String>, String> {
+    let mut patterns = Vec::new();
+    for entry in entries {
+        let trimmed = entry.trim();
+        if trimmed.chars().all(|c| c == '*') || compile_pattern(trimmed).is_err() {
+            return Err(format!("invalid loginWallUrls entry '{entry}'"));
+        }
+        if !patterns.iter().any(|p| p == trimmed) {
+            patterns.push(trimmed.to_string());
+        }
+    }
+    Ok(patterns)
+}
+
+fn compile_pattern(pattern: &str) -> Result<regex::Regex, regex::Error> {
+    regex::Regex::new(&super::js_api::glob_to_regex_pattern(pattern))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn patterns_use_wait_for_url_globs() {
+        let patterns = normalize_login_wall_patterns(&[
+            " https://secure.examplebank.com/auth/logon* ".to_string(),
+            "**/session-expired*".to_string(),
+            "**/session-expired*".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(
+            patterns,
+            vec![
+                "https://secure.examplebank.com/auth/logon*",
+                "**/session-expired*"
+            ]
+        );
+        let wall = LoginWall::new(&patterns);
+        assert!(wall.matches("https://secure.examplebank.com/auth/logon?reason=timeout"));
+        assert!(wall.matches("https://www.examplebank.com/app/session-expired"));
+        assert!(!wall.matches("https://secure.examplebank.com/accounts"));
+        assert!(!wall.matches("https://secure.examplebank.com/auth/logon/help"));
+        assert!(!LoginWall::default().matches("https://secure.examplebank.com/auth/logon"));
+        assert!(normalize_login_wall_patterns(&["**".to_string()]).is_err());
+        assert!(normalize_login_wall_patterns(&[" ".to_string()]).is_err());
+    }
+
+    #[test]
+    fn login_guard_suppresses_detection_until_dropped() {
+        let wall = LoginWall::new(&["https://bank.test/login*".to_string()]);
+        let url = "https://bank.test/login";
+        assert!(wall.blocks(url));
+        let guard = wall.begin_login().unwrap();
+        assert!(!wall.blocks(url));
+        assert!(wall.begin_login().is_none());
+        drop(guard);
+        assert!(wall.blocks(url));
+        assert_eq!(wall.relogins(), 1);
+    }
+}
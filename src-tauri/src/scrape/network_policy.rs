@@ -0,0 +1,470 @@
+//! Manifest `allowedDomains` enforcement and the observed-domains report.
+//!
+//! Every page the driver touches records the hosts it requests. When the
+//! manifest declares `allowedDomains`, requests are intercepted with the CDP
+//! `Fetch` domain and navigations, `fetch()`/XHR, event streams, and
+//! websockets to other hosts fail with `net::ERR_BLOCKED_BY_CLIENT`.
+//! Images, scripts, and stylesheets from other hosts (usually CDNs) are
+//! recorded but not blocked.
+//!
+//! `page.route` handlers share the same interception: requests the policy
+//! lets through are handed to the page's route dispatcher, which continues,
+//! fulfills, or aborts them.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, Mutex};
+
+use chromiumoxide::cdp::browser_protocol::fetch::EventRequestPaused;
+use tokio::sync::mpsc;
+
+/// A request paused by the `Fetch` domain, waiting for a route decision.
+pub type PausedRequest = Arc<EventRequestPaused>;
+
+/// Shared by every page of one scrape or debug session.
+#[derive(Debug, Default)]
+pub struct NetworkPolicy {
+    /// Normalized manifest entries; `None` when the manifest has no list.
+    allowed_domains: Option<Vec<String>>,
+    observed: Mutex<BTreeMap<String, DomainCounts>>,
+    installed_targets: Mutex<BTreeSet<String>>,
+    /// Targets with a running `Fetch.requestPaused` listener.
+    intercepted_targets: Mutex<BTreeSet<String>>,
+    /// `page.route` dispatchers by target id.
+    routers: Mutex<BTreeMap<String, Router>>,
+}
+
+struct Router {
+    page: chromiumoxide::Page,
+    sender: mpsc::UnboundedSender<PausedRequest>,
+}
+
+impl std::fmt::Debug for Router {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Router").finish_non_exhaustive()
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy)]
+struct DomainCounts {
+    requests: u64,
+    blocked: u64,
+}
+
+/// Hosts contacted during a scrape, for the scrape log.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkReport {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub allowed_domains: Option<Vec<String>>,
+    pub observed_domains: Vec<ObservedDomain>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ObservedDomain {
+    pub domain: String,
+    pub requests: u64,
+    /// Requests that were refused because the host is off the allowlist.
+    #[serde(default)]
+    pub blocked: u64,
+    /// Whether the host is on the allowlist (always true without one).
+    pub allowed: bool,
+}
+
+impl NetworkPolicy {
+    pub fn new(allowed_domains: Option<Vec<String>>) -> Self {
+        Self {
+            allowed_domains,
+            ..Self::default()
+        }
+    }
+
+    pub fn is_enforcing(&self) -> bool {
+        self.allowed_domains.is_some()
+    }
+
+    /// Whether a request to `url` may proceed. Non-network URLs (`about:`,
+    /// `data:`, `blob:`, ...) are always allowed.
+    pub fn allows_url(&self, url: &str) -> bool {
+        match (&self.allowed_domains, url_host(url)) {
+            (Some(allowed), Some(host)) => domain_allowed(allowed, &host),
+            _ => true,
+        }
+    }
+
+    /// Refuse a driver-initiated navigation (`goto`, popups) to an off-list
+    /// host, recording it as blocked.
+    pub fn check_navigation(&self, url: &str, action: &str) -> Result<(), String> {
+        if self.allows_url(url) {
+            return Ok(());
+        }
+        self.record(url, true);
+        Err(format!(
+            "NetworkPolicyError: {action} to \"{url}\" blocked; host is not in the manifest allowedDomains"
+        ))
+    }
+
+    fn record(&self, url: &str, blocked: bool) {
+        let Some(host) = url_host(url) else {
+            return;
+        };
+        let mut observed = self.observed.lock().unwrap_or_else(|err| err.into_inner());
+        let counts = observed.entry(host).or_default();
+        counts.requests += 1;
+        if blocked {
+            counts.blocked += 1;
+        }
+    }
+
+    pub fn report(&self) -> NetworkReport {
+        let observed = self.observed.lock().unwrap_or_else(|err| err.into_inner());
+        NetworkReport {
+            allowed_domains: self.allowed_domains.clone(),
+            observed_domains: observed
+                .iter()
+                .map(|(domain, counts)| ObservedDomain {
+                    domain: domain.clone(),
+                    requests: counts.requests,
+                    blocked: counts.blocked,
+                    allowed: self
+                        .allowed_domains
+                        .as_deref()
+                        .map_or(true, |allowed| domain_allowed(allowed, domain)),
+                })
+                .collect(),
+        }
+    }
+
+    /// Start recording (and, with an allowlist, filtering) requests made by
+    /// `page`. Safe to call repeatedly; each target is set up once.
+    pub async fn install(self: &Arc<Self>, page: &chromiumoxide::Page) -> Result<(), String> {
+        let target_id = page.target_id().as_ref().to_string();
+        {
+            let mut installed = self
+                .installed_targets
+                .lock()
+                .unwrap_or_else(|err| err.into_inner());
+            if !installed.insert(target_id) {
+                return Ok(());
+            }
+        }
+        if self.is_enforcing() {
+            self.install_interception(page).await
+        } else {
+            self.install_observer(page).await
+        }
+    }
+
+    /// Intercept every request `page` makes and send the ones the policy
+    /// allows to the returned receiver, which must continue, fulfill, or
+    /// abort each of them. Replaces any earlier router for the page.
+    pub async fn start_routing(
+        self: &Arc<Self>,
+        page: &chromiumoxide::Page,
+    ) -> Result<mpsc::UnboundedReceiver<PausedRequest>, String> {
+        let target_id = page.target_id().as_ref().to_string();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.routers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(
+                target_id.clone(),
+                Router {
+                    page: page.clone(),
+                    sender,
+                },
+            );
+        if let Err(err) = self.install_interception(page).await {
+            self.routers
+                .lock()
+                .unwrap_or_else(|err| err.into_inner())
+                .remove(&target_id);
+            return Err(err);
+        }
+        Ok(receiver)
+    }
+
+    /// Stop routing `target_id`'s requests. Its receiver ends once it has
+    /// drained what was already sent. Without an allowlist, interception is
+    /// switched off again.
+    pub async fn stop_routing(&self, target_id: &str) {
+        let router = self
+            .routers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .remove(target_id);
+        let Some(router) = router else {
+            return;
+        };
+        if !self.is_enforcing() {
+            use chromiumoxide::cdp::browser_protocol::fetch::DisableParams;
+            if let Err(err) = router.page.execute(DisableParams::default()).await {
+                tracing::debug!("failed to disable request interception: {err}");
+            }
+        }
+    }
+
+    /// Stop routing on every page; called when the driver script ends so no
+    /// request waits on a handler that can no longer run.
+    pub async fn stop_all_routing(&self) {
+        let targets: Vec<String> = self
+            .routers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .keys()
+            .cloned()
+            .collect();
+        for target_id in targets {
+            self.stop_routing(&target_id).await;
+        }
+    }
+
+    /// Hand a paused request to its page's router; false when there is none.
+    fn forward_to_router(&self, target_id: &str, ev: &PausedRequest) -> bool {
+        self.routers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .get(target_id)
+            .is_some_and(|router| router.sender.send(ev.clone()).is_ok())
+    }
+
+    async fn install_observer(self: &Arc<Self>, page: &chromiumoxide::Page) -> Result<(), String> {
+        use chromiumoxide::cdp::browser_protocol::network::{EnableParams, EventRequestWillBeSent};
+
+        let mut events = page
+            .event_listener::<EventRequestWillBeSent>()
+            .await
+            .map_err(|e| format!("failed to attach request listener: {e}"))?;
+        page.execute(EnableParams::default())
+            .await
+            .map_err(|e| format!("failed to enable Network domain: {e}"))?;
+        let policy = self.clone();
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            while let Some(ev) = events.next().await {
+                policy.record(&ev.request.url, false);
+            }
+        });
+        Ok(())
+    }
+
+    async fn install_interception(
+        self: &Arc<Self>,
+        page: &chromiumoxide::Page,
+    ) -> Result<(), String> {
+        use chromiumoxide::cdp::browser_protocol::fetch::{
+            ContinueRequestParams, EnableParams, FailRequestParams, RequestPattern, RequestStage,
+        };
+        use chromiumoxide::cdp::browser_protocol::network::ErrorReason;
+
+        let target_id = page.target_id().as_ref().to_string();
+        let already_listening = !self
+            .intercepted_targets
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .insert(target_id.clone());
+        let events = if already_listening {
+            None
+        } else {
+            Some(
+                page.event_listener::<EventRequestPaused>()
+                    .await
+                    .map_err(|e| format!("failed to attach request interception listener: {e}"))?,
+            )
+        };
+        let params = EnableParams::builder()
+            .pattern(
+                RequestPattern::builder()
+                    .url_pattern("*")
+                    .request_stage(RequestStage::Request)
+                    .build(),
+            )
+            .build();
+        page.execute(params)
+            .await
+            .map_err(|e| format!("failed to enable request interception: {e}"))?;
+        let Some(mut events) = events else {
+            return Ok(());
+        };
+        let policy = self.clone();
+        let page = page.clone();
+        tokio::spawn(async move {
+            use futures::StreamExt;
+            while let Some(ev) = events.next().await {
+                let url = &ev.request.url;
+                let blocked = policy.is_enforcing()
+                    && is_blockable_resource(&ev.resource_type)
+                    && !policy.allows_url(url);
+                if policy.is_enforcing() {
+                    // Without an allowlist the observer already counted it.
+                    policy.record(url, blocked);
+                }
+                if !blocked && policy.forward_to_router(&target_id, &ev) {
+                    continue;
+                }
+                let outcome = if blocked {
+                    tracing::warn!("blocked off-allowlist request to {url}");
+                    page.execute(FailRequestParams::new(
+                        ev.request_id.clone(),
+                        ErrorReason::BlockedByClient,
+                    ))
+                    .await
+                    .map(drop)
+                } else {
+                    page.execute(ContinueRequestParams::new(ev.request_id.clone()))
+                        .await
+                        .map(drop)
+                };
+                if let Err(err) = outcome {
+                    // The target closed or the request was already cancelled.
+                    tracing::debug!("request interception for {url} failed: {err}");
+                }
+            }
+        });
+        Ok(())
+    }
+}
+
+/// Resource types that move data to or from a host on the driver's behalf.
+fn is_blockable_resource(
+    resource_type: &chromiumoxide::cdp::browser_protocol::network::ResourceType,
+) -> bool {
+    use chromiumoxide::cdp::browser_protocol::network::ResourceType;
+    matches!(
+        resource_type,
+        ResourceType::Document
+            | ResourceType::Xhr
+            | ResourceType::Fetch
+            | ResourceType::EventSource
+            | ResourceType::WebSocket
+    )
+}
+
+/// Normalize manifest `allowedDomains` entries (`example.com`,
+/// `*.example.com`, or a URL) to bare lowercase hosts.
+pub fn normalize_allowed_domains(entries: &[String]) -> Result<Vec<String>, String> {
+    let mut domains = BTreeSet::new();
+    for entry in entries {
+        let trimmed = entry.trim();
+        let domain =
+            super::normalize_manifest_domain(trimmed.strip_prefix("*.").unwrap_or(trimmed));
+        if domain.is_empty() || domain.contains('*') {
+            return Err(format!("invalid allowedDomains entry '{entry}'"));
+        }
+        domains.insert(domain);
+    }
+    Ok(domains.into_iter().collect())
+}
+
+/// An entry allows its own host and every subdomain of it.
+fn domain_allowed(allowed: &[String], host: &str) -> bool {
+    allowed.iter().any(|domain| {
+        host == domain
+            || host
+                .strip_suffix(domain.as_str())
+                .is_some_and(|prefix| prefix.ends_with('.'))
+    })
+}
+
+/// Lowercase host of an http(s) or ws(s) URL; `None` for other schemes.
+fn url_host(url: &str) -> Option<String> {
+    let (scheme, rest) = url.split_once("://")?;
+    if !matches!(
+        scheme.to_ascii_lowercase().as_str(),
+        "http" | "https" | "ws" | "wss"
+    ) {
+        return None;
+    }
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or("");
+    let host_port = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = if let Some(bracketed) = host_port.strip_prefix('[') {
+        bracketed.split(']').next().unwrap_or("")
+    } else {
+        host_port.split(':').next().unwrap_or("")
+    };
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    (!host.is_empty()).then_some(host)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn url_host_handles_userinfo_ports_and_other_schemes() {
+        assert_eq!(
+            url_host("https://user:pw@Secure.Example.com:8443/login?x=1").as_deref(),
+            Some("secure.example.com")
+        );
+        assert_eq!(
+            url_host("wss://[::1]:9222/devtools").as_deref(),
+            Some("::1")
+        );
+        assert_eq!(
+            url_host("http://example.com#top").as_deref(),
+            Some("example.com")
+        );
+        assert_eq!(url_host("about:blank"), None);
+        assert_eq!(url_host("data:text/html,hi"), None);
+        assert_eq!(url_host("chrome-extension://abc/page.html"), None);
+    }
+
+    #[test]
+    fn allowlist_covers_subdomains_but_not_lookalikes() {
+        let allowed = normalize_allowed_domains(&[
+            "*.examplebank.com".to_string(),
+            "https://Auth.Partner.net/sso".to_string(),
+        ])
+        .unwrap();
+        assert_eq!(allowed, vec!["auth.partner.net", "examplebank.com"]);
+        assert!(normalize_allowed_domains(&["bank.*.com".to_string()]).is_err());
+        assert!(normalize_allowed_domains(&[" ".to_string()]).is_err());
+
+        let policy = NetworkPolicy::new(Some(allowed));
+        assert!(policy.allows_url("https://examplebank.com/"));
+        assert!(policy.allows_url("https://secure.examplebank.com/login"));
+        assert!(policy.allows_url("https://auth.partner.net/"));
+        assert!(!policy.allows_url("https://partner.net/"));
+        assert!(!policy.allows_url("https://notexamplebank.com/"));
+        assert!(!policy.allows_url("https://examplebank.com.evil.io/"));
+        assert!(policy.allows_url("about:blank"));
+        assert!(NetworkPolicy::default().allows_url("https://anything.io/"));
+    }
+
+    #[test]
+    fn report_counts_requests_and_blocks_per_host() {
+        let policy = NetworkPolicy::new(Some(vec!["examplebank.com".to_string()]));
+        policy.record("https://www.examplebank.com/", false);
+        policy.record("https://www.examplebank.com/api", false);
+        policy.record("https://cdn.tracker.io/pixel.gif", false);
+        let err = policy
+            .check_navigation("https://phish.io/login", "goto")
+            .unwrap_err();
+        assert!(err.starts_with("NetworkPolicyError"), "{err}");
+        assert!(policy
+            .check_navigation("https://examplebank.com/", "goto")
+            .is_ok());
+
+        let report = policy.report();
+        assert_eq!(
+            report.allowed_domains,
+            Some(vec!["examplebank.com".to_string()])
+        );
+        let summary: Vec<_> = report
+            .observed_domains
+            .iter()
+            .map(|d| (d.domain.as_str(), d.requests, d.blocked, d.allowed))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                ("cdn.tracker.io", 1, 0, false),
+                ("phish.io", 1, 1, false),
+                ("www.examplebank.com", 2, 0, true),
+            ]
+        );
+    }
+}
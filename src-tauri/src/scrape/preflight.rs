@@ -0,0 +1,415 @@
+//! Checks run before a scrape launches a browser.
+//!
+//! A scheduled scrape that is missing a keychain value or a driver script
+//! otherwise fails minutes later, after the browser has opened and the
+//! driver has reached the login page. `verify_scrape_prerequisites` finds
+//! those problems up front and says how to fix each one.
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+
+use super::{api_version, browser, load_manifest, profile, resolve_driver_script_path};
+use crate::scrape::js_api::SecretDeclarations;
+use crate::secret::{DomainEntry, SecretStore};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub enum PreflightCheckKind {
+    /// The login names an extension whose manifest and driver load.
+    Extension,
+    /// Every secret the manifest declares has a stored value.
+    Secrets,
+    /// hledger runs, so extracted entries can be read back after the scrape.
+    Hledger,
+    /// A Chrome or Chromium binary was found.
+    Browser,
+    /// The login's persistent browser profile is usable.
+    Profile,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub enum PreflightStatus {
+    Ok,
+    Failed,
+    /// Not checked because the check it depends on failed.
+    Skipped,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct PreflightCheck {
+    pub check: PreflightCheckKind,
+    pub status: PreflightStatus,
+    /// What is wrong, for failed and skipped checks.
+    pub problem: Option<String>,
+    /// What the user can do about it.
+    pub fix: Option<String>,
+}
+
+impl PreflightCheck {
+    fn ok(check: PreflightCheckKind) -> Self {
+        Self {
+            check,
+            status: PreflightStatus::Ok,
+            problem: None,
+            fix: None,
+        }
+    }
+
+    fn failed(check: PreflightCheckKind, problem: String, fix: String) -> Self {
+        Self {
+            check,
+            status: PreflightStatus::Failed,
+            problem: Some(problem),
+            fix: Some(fix),
+        }
+    }
+
+    fn skipped(check: PreflightCheckKind, problem: &str) -> Self {
+        Self {
+            check,
+            status: PreflightStatus::Skipped,
+            problem: Some(problem.to_string()),
+            fix: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ScrapePreflight {
+    pub login_name: String,
+    /// True when no check failed.
+    pub ready: bool,
+    pub checks: Vec<PreflightCheck>,
+}
+
+/// What the checks read from outside the ledger, gathered up front so the
+/// checks themselves can be tested.
+struct Environment {
+    stored_secrets: Result<Vec<DomainEntry>, String>,
+    hledger_available: bool,
+    browser: Result<PathBuf, String>,
+    profile_dir: Result<PathBuf, String>,
+}
+
+/// Check that `login_name` can be scraped without launching a browser.
+///
+/// Secrets are checked against the keychain's domain index, so no value is
+/// read and no authorization prompt is shown.
+pub fn verify_scrape_prerequisites(ledger_dir: &Path, login_name: &str) -> ScrapePreflight {
+    let env = Environment {
+        stored_secrets: SecretStore::new(format!("login/{login_name}"))
+            .list_domains()
+            .map_err(|err| err.to_string()),
+        hledger_available: std::process::Command::new(crate::binpath::hledger_path())
+            .arg("--version")
+            .output()
+            .is_ok_and(|output| output.status.success()),
+        browser: browser::find_chrome_binary().map_err(|err| err.to_string()),
+        profile_dir: profile::resolve_profile_dir(ledger_dir, login_name, None)
+            .map_err(|err| err.to_string()),
+    };
+    verify_with(ledger_dir, login_name, &env)
+}
+
+fn verify_with(ledger_dir: &Path, login_name: &str, env: &Environment) -> ScrapePreflight {
+    let mut checks = Vec::new();
+    let manifest = match load_extension(ledger_dir, login_name) {
+        Ok(manifest) => {
+            checks.push(PreflightCheck::ok(PreflightCheckKind::Extension));
+            Some(manifest)
+        }
+        Err((problem, fix)) => {
+            checks.push(PreflightCheck::failed(
+                PreflightCheckKind::Extension,
+                problem,
+                fix,
+            ));
+            None
+        }
+    };
+
+    checks.push(match &manifest {
+        Some(manifest) => check_secrets(&manifest.secrets, &env.stored_secrets),
+        None => PreflightCheck::skipped(
+            PreflightCheckKind::Secrets,
+            "the extension manifest did not load",
+        ),
+    });
+
+    checks.push(if env.hledger_available {
+        PreflightCheck::ok(PreflightCheckKind::Hledger)
+    } else {
+        PreflightCheck::failed(
+            PreflightCheckKind::Hledger,
+            "hledger was not found or did not run".to_string(),
+            "Install hledger, or reinstall refreshmint to restore the bundled copy.".to_string(),
+        )
+    });
+
+    checks.push(match &env.browser {
+        Ok(_) => PreflightCheck::ok(PreflightCheckKind::Browser),
+        Err(err) => PreflightCheck::failed(
+            PreflightCheckKind::Browser,
+            err.clone(),
+            "Install Chrome or Chromium, or set CHROME to its path.".to_string(),
+        ),
+    });
+
+    let ephemeral = manifest
+        .as_ref()
+        .is_some_and(|manifest| manifest.browser_profile == profile::ProfileMode::Ephemeral);
+    checks.push(if ephemeral {
+        PreflightCheck::ok(PreflightCheckKind::Profile)
+    } else {
+        match &env.profile_dir {
+            Ok(dir) => check_profile_dir(dir),
+            Err(err) => PreflightCheck::failed(
+                PreflightCheckKind::Profile,
+                err.clone(),
+                "Set a data directory for the user running refreshmint.".to_string(),
+            ),
+        }
+    });
+
+    ScrapePreflight {
+        login_name: login_name.to_string(),
+        ready: checks
+            .iter()
+            .all(|check| check.status != PreflightStatus::Failed),
+        checks,
+    }
+}
+
+/// Load the login's manifest the way `run_scrape_async` does, returning the
+/// problem and its fix on failure.
+fn load_extension(
+    ledger_dir: &Path,
+    login_name: &str,
+) -> Result<super::ParsedManifest, (String, String)> {
+    let extension = crate::login_config::resolve_login_extension(ledger_dir, login_name)
+        .map_err(|err| (err, "Choose an extension for the login.".to_string()))?;
+    let extension_dir = crate::account_config::resolve_extension_dir(ledger_dir, &extension);
+    let reinstall = || format!("Reinstall or update the '{extension}' extension.");
+    let manifest = load_manifest(&extension_dir).map_err(|err| {
+        (
+            format!("extension '{extension}' did not load: {err}"),
+            reinstall(),
+        )
+    })?;
+    api_version::check_compatibility(&extension, manifest.min_api_version)
+        .map_err(|err| (err, "Update refreshmint.".to_string()))?;
+    let driver_path = resolve_driver_script_path(&extension_dir, &manifest);
+    if !driver_path.exists() {
+        return Err((
+            format!("driver script not found: {}", driver_path.display()),
+            reinstall(),
+        ));
+    }
+    Ok(manifest)
+}
+
+fn check_secrets(
+    declared: &SecretDeclarations,
+    stored: &Result<Vec<DomainEntry>, String>,
+) -> PreflightCheck {
+    let stored = match stored {
+        Ok(stored) => stored,
+        Err(err) => {
+            return PreflightCheck::failed(
+                PreflightCheckKind::Secrets,
+                format!("the keychain could not be read: {err}"),
+                "Unlock the keychain and try again.".to_string(),
+            )
+        }
+    };
+    let mut missing = Vec::new();
+    for (domain, creds) in declared {
+        let entry = stored.iter().find(|entry| &entry.domain == domain);
+        let has_username = entry.is_some_and(|entry| entry.has_username);
+        let has_password = entry.is_some_and(|entry| entry.has_password);
+        if creds.username.is_some() && !has_username {
+            missing.push(format!("username for {domain}"));
+        }
+        if (creds.password.is_some() || !creds.extra_names.is_empty()) && !has_password {
+            missing.push(format!("password for {domain}"));
+        }
+    }
+    if missing.is_empty() {
+        PreflightCheck::ok(PreflightCheckKind::Secrets)
+    } else {
+        PreflightCheck::failed(
+            PreflightCheckKind::Secrets,
+            format!("missing {}", missing.join(", ")),
+            "Enter the missing credentials in the login's secrets.".to_string(),
+        )
+    }
+}
+
+/// A profile that does not exist yet is fine: the browser creates it.
+fn check_profile_dir(dir: &Path) -> PreflightCheck {
+    let clear = "Clear the login's browser profile; the next scrape starts signed out.";
+    if !dir.exists() {
+        return PreflightCheck::ok(PreflightCheckKind::Profile);
+    }
+    if !dir.is_dir() {
+        return PreflightCheck::failed(
+            PreflightCheckKind::Profile,
+            format!("browser profile {} is not a directory", dir.display()),
+            clear.to_string(),
+        );
+    }
+    if std::fs::metadata(dir).is_ok_and(|meta| meta.permissions().readonly()) {
+        return PreflightCheck::failed(
+            PreflightCheckKind::Profile,
+            format!("browser profile {} is read-only", dir.display()),
+            "Make the profile directory writable.".to_string(),
+        );
+    }
+    let local_state = dir.join("Local State");
+    if local_state.exists() {
+        let parsed = std::fs::read_to_string(&local_state)
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                serde_json::from_str::<serde_json::Value>(&text).map_err(|err| err.to_string())
+            });
+        if let Err(err) = parsed {
+            return PreflightCheck::failed(
+                PreflightCheckKind::Profile,
+                format!("browser profile state is damaged: {err}"),
+                clear.to_string(),
+            );
+        }
+    }
+    PreflightCheck::ok(PreflightCheckKind::Profile)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "refreshmint-preflight-{prefix}-{}-{now}",
+            std::process::id()
+        ));
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn env(profile_dir: PathBuf, stored: Vec<DomainEntry>) -> Environment {
+        Environment {
+            stored_secrets: Ok(stored),
+            hledger_available: true,
+            browser: Ok(PathBuf::from("/usr/bin/chromium")),
+            profile_dir: Ok(profile_dir),
+        }
+    }
+
+    fn write_login(ledger: &Path, extension: &str) {
+        crate::login_config::write_login_config(
+            ledger,
+            "bank",
+            &crate::login_config::LoginConfig {
+                extension: Some(extension.to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+    }
+
+    fn status(preflight: &ScrapePreflight, check: PreflightCheckKind) -> PreflightStatus {
+        preflight
+            .checks
+            .iter()
+            .find(|c| c.check == check)
+            .unwrap()
+            .status
+    }
+
+    #[test]
+    fn missing_secret_values_fail_before_launch() {
+        let ledger = temp_dir("secrets");
+        let extension = ledger.join("extensions").join("bank-driver");
+        fs::create_dir_all(&extension).unwrap();
+        fs::write(
+            extension.join("manifest.json"),
+            r#"{"name":"bank-driver","driver":"driver.mjs","secrets":{"bank.example.com":{"username":"user","password":"pass"}}}"#,
+        )
+        .unwrap();
+        fs::write(extension.join("driver.mjs"), "// driver").unwrap();
+        fs::create_dir_all(ledger.join("logins").join("bank")).unwrap();
+        write_login(&ledger, "bank-driver");
+
+        let stored = vec![DomainEntry {
+            domain: "bank.example.com".to_string(),
+            has_username: true,
+            has_password: false,
+        }];
+        let preflight = verify_with(&ledger, "bank", &env(ledger.join("profile"), stored));
+        assert!(!preflight.ready);
+        assert_eq!(
+            status(&preflight, PreflightCheckKind::Extension),
+            PreflightStatus::Ok
+        );
+        let secrets = &preflight.checks[1];
+        assert_eq!(secrets.status, PreflightStatus::Failed);
+        assert_eq!(
+            secrets.problem.as_deref(),
+            Some("missing password for bank.example.com")
+        );
+
+        let stored = vec![DomainEntry {
+            domain: "bank.example.com".to_string(),
+            has_username: true,
+            has_password: true,
+        }];
+        let preflight = verify_with(&ledger, "bank", &env(ledger.join("profile"), stored));
+        assert!(preflight.ready, "{preflight:?}");
+        let _ = fs::remove_dir_all(&ledger);
+    }
+
+    #[test]
+    fn missing_extension_skips_secret_check() {
+        let ledger = temp_dir("extension");
+        fs::create_dir_all(ledger.join("logins").join("bank")).unwrap();
+        write_login(&ledger, "not-installed");
+
+        let preflight = verify_with(&ledger, "bank", &env(ledger.join("profile"), Vec::new()));
+        assert!(!preflight.ready);
+        assert_eq!(
+            status(&preflight, PreflightCheckKind::Extension),
+            PreflightStatus::Failed
+        );
+        assert_eq!(
+            status(&preflight, PreflightCheckKind::Secrets),
+            PreflightStatus::Skipped
+        );
+        let _ = fs::remove_dir_all(&ledger);
+    }
+
+    #[test]
+    fn damaged_profile_state_fails() {
+        let dir = temp_dir("profile");
+        assert_eq!(check_profile_dir(&dir).status, PreflightStatus::Ok);
+        fs::write(dir.join("Local State"), "{\"profile\":").unwrap();
+        assert_eq!(check_profile_dir(&dir).status, PreflightStatus::Failed);
+        assert_eq!(
+            check_profile_dir(&dir.join("missing")).status,
+            PreflightStatus::Ok
+        );
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -1,12 +1,99 @@
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+/// Manifest `browserProfile`: whether a login's browser state outlives a run.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProfileMode {
+    /// Cookies and storage persist in the login's own profile directory.
+    #[default]
+    Persistent,
+    /// Every run starts from an empty profile that is deleted afterwards.
+    Ephemeral,
+}
+
+/// Browser profile directory of one scrape or debug session. An ephemeral
+/// profile is deleted when this is dropped, so keep it alive until the
+/// browser has exited.
+#[derive(Debug)]
+pub struct SessionProfile {
+    dir: PathBuf,
+    ephemeral: bool,
+}
+
+impl SessionProfile {
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral
+    }
+}
+
+impl Drop for SessionProfile {
+    fn drop(&mut self) {
+        if self.ephemeral {
+            if let Err(err) = std::fs::remove_dir_all(&self.dir) {
+                tracing::warn!(
+                    "failed to remove ephemeral profile {}: {err}",
+                    self.dir.display()
+                );
+            }
+        }
+    }
+}
+
+/// Resolve the browser profile for a session of `login_name`.
+///
+/// Persistent profiles are the login's directory from
+/// [`resolve_profile_dir`]. Ephemeral profiles are created empty at
+/// `<base>/ephemeral-profiles/<ledger-path-hash>/<sanitized-login>/`,
+/// replacing whatever a crashed run left there. Callers hold the login
+/// lock, so no other session of the login is using it.
+pub fn resolve_session_profile(
+    ledger_path: &Path,
+    login_name: &str,
+    profile_override: Option<&Path>,
+    mode: ProfileMode,
+) -> Result<SessionProfile, Box<dyn std::error::Error>> {
+    if mode == ProfileMode::Persistent {
+        return Ok(SessionProfile {
+            dir: resolve_profile_dir(ledger_path, login_name, profile_override)?,
+            ephemeral: false,
+        });
+    }
+
+    let base = match profile_override {
+        Some(p) => p.to_path_buf(),
+        None => {
+            let data_dir = dirs::data_dir().ok_or("could not determine data directory")?;
+            data_dir.join("refreshmint").join("Default")
+        }
+    };
+    let dir = base
+        .join("ephemeral-profiles")
+        .join(hash_path(ledger_path))
+        .join(login_dir_name(login_name));
+    if dir.exists() {
+        std::fs::remove_dir_all(&dir)?;
+    }
+    std::fs::create_dir_all(&dir)?;
+    Ok(SessionProfile {
+        dir,
+        ephemeral: true,
+    })
+}
 
 /// Resolve the browser profile directory for a given account.
 ///
 /// Default base: `dirs::data_dir()/refreshmint/Default/account-profiles/`
 /// Per-account dir: `<ledger-path-hash>/<sanitized-account>/`
 ///
+/// Names that sanitizing changes get a hash suffix, so logins such as
+/// `a/b` and `a_b` never share cookies.
+///
 /// If `profile_override` is provided, it replaces the base directory.
 pub fn resolve_profile_dir(
     ledger_path: &std::path::Path,
@@ -25,9 +112,8 @@ pub fn resolve_profile_dir(
     };
 
     let ledger_hash = hash_path(ledger_path);
-    let sanitized = sanitize_account_name(account);
 
-    Ok(base.join(ledger_hash).join(sanitized))
+    Ok(base.join(ledger_hash).join(login_dir_name(account)))
 }
 
 /// Delete the browser profile directory for a given login.
@@ -71,6 +157,18 @@ fn hash_path(path: &std::path::Path) -> String {
     format!("{:016x}", hasher.finish())
 }
 
+/// Unique directory name for a login: the sanitized name, plus a hash of
+/// the original when sanitizing changed it.
+fn login_dir_name(account: &str) -> String {
+    let sanitized = sanitize_account_name(account);
+    if sanitized == account {
+        return sanitized;
+    }
+    let mut hasher = DefaultHasher::new();
+    account.hash(&mut hasher);
+    format!("{sanitized}-{:08x}", hasher.finish() as u32)
+}
+
 fn sanitize_account_name(account: &str) -> String {
     account
         .chars()
@@ -180,6 +278,49 @@ mod tests {
         assert_eq!(sanitize_account_name("a/b:c"), "a_b_c");
     }
 
+    #[test]
+    fn test_login_dir_name_keeps_lossy_names_apart() {
+        assert_eq!(login_dir_name("chase-checking"), "chase-checking");
+        assert_ne!(login_dir_name("a/b"), login_dir_name("a_b"));
+        assert_ne!(login_dir_name("a/b"), login_dir_name("a:b"));
+        assert!(login_dir_name("a/b").starts_with("a_b-"));
+    }
+
+    #[test]
+    fn test_ephemeral_session_profile_is_fresh_and_removed() {
+        let base = std::env::temp_dir().join(format!(
+            "refreshmint-ephemeral-profile-test-{}",
+            std::process::id()
+        ));
+        let ledger = std::path::Path::new("/ledger");
+        let leftover = base
+            .join("ephemeral-profiles")
+            .join(hash_path(ledger))
+            .join("chase")
+            .join("Cookies");
+        std::fs::create_dir_all(&leftover).unwrap_or_else(|e| panic!("mkdir failed: {e}"));
+
+        let profile = resolve_session_profile(ledger, "chase", Some(&base), ProfileMode::Ephemeral)
+            .unwrap_or_else(|e| panic!("unexpected error: {e}"));
+        assert!(profile.is_ephemeral());
+        assert!(profile.dir().starts_with(base.join("ephemeral-profiles")));
+        assert!(
+            !leftover.exists(),
+            "leftover profile state should be removed"
+        );
+        let dir = profile.dir().to_path_buf();
+        assert!(dir.is_dir());
+        drop(profile);
+        assert!(!dir.exists());
+
+        let persistent =
+            resolve_session_profile(ledger, "chase", Some(&base), ProfileMode::Persistent)
+                .unwrap_or_else(|e| panic!("unexpected error: {e}"));
+        assert!(!persistent.is_ephemeral());
+        assert!(persistent.dir().ends_with("chase"));
+        let _ = std::fs::remove_dir_all(&base);
+    }
+
     #[test]
     fn test_hash_path_deterministic() {
         let p = std::path::Path::new("/some/path");
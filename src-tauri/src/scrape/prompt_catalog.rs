@@ -0,0 +1,391 @@
+//! Named prompts declared in the extension manifest (`prompts`).
+//!
+//! `refreshmint.prompt(key)` looks the key up here, so CLI runs can answer
+//! with `--prompt key=VALUE` and the UI can render the whole form before a
+//! scrape starts instead of matching on exact prompt text. Prompts that are
+//! not declared keep working as before, keyed by their message.
+//!
+//! Each scrape records the prompts it asked in the scrape log, and
+//! [`required_prompts`] uses that history to mark which ones the next run
+//! will likely ask again, so answers can be collected before launching.
+
+use std::collections::BTreeMap;
+
+/// How a prompt's answer is collected and validated.
+#[derive(
+    Debug, Default, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS,
+)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum PromptKind {
+    #[default]
+    Text,
+    /// A one-time code; surrounding whitespace and inner spaces are dropped.
+    Otp,
+    /// One of `choices`.
+    Choice,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct PromptDeclaration {
+    pub message: String,
+    #[serde(default, rename = "type")]
+    pub kind: PromptKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+}
+
+/// Manifest `prompts`, keyed by prompt key.
+pub type PromptCatalog = BTreeMap<String, PromptDeclaration>;
+
+/// Check keys, messages, and choices of a manifest `prompts` object.
+pub fn validate_catalog(catalog: &PromptCatalog) -> Result<(), String> {
+    for (key, declaration) in catalog {
+        if key.is_empty()
+            || !key
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        {
+            return Err(format!(
+                "invalid prompt key '{key}': use letters, digits, '_', '-', or '.'"
+            ));
+        }
+        if declaration.message.trim().is_empty() {
+            return Err(format!("prompt '{key}' has an empty message"));
+        }
+        match declaration.kind {
+            PromptKind::Choice if declaration.choices.is_empty() => {
+                return Err(format!("choice prompt '{key}' must list its choices"));
+            }
+            PromptKind::Text | PromptKind::Otp if !declaration.choices.is_empty() => {
+                return Err(format!(
+                    "prompt '{key}' lists choices but is not of type \"choice\""
+                ));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// What one `refreshmint.prompt(...)` call asks for.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize, ts_rs::TS)]
+#[ts(export_to = "index.ts")]
+pub struct PromptRequest {
+    /// Catalog key, when the driver passed one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub key: Option<String>,
+    pub message: String,
+    #[serde(default, rename = "type")]
+    pub kind: PromptKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub choices: Vec<String>,
+}
+
+impl PromptRequest {
+    /// Resolve the argument of `refreshmint.prompt` against the catalog; an
+    /// undeclared argument is a free-form text prompt.
+    pub fn resolve(catalog: &PromptCatalog, argument: &str) -> Self {
+        match catalog.get(argument) {
+            Some(declaration) => Self {
+                key: Some(argument.to_string()),
+                message: declaration.message.clone(),
+                kind: declaration.kind,
+                choices: declaration.choices.clone(),
+            },
+            None => Self {
+                key: None,
+                message: argument.to_string(),
+                kind: PromptKind::Text,
+                choices: Vec::new(),
+            },
+        }
+    }
+
+    /// A supplied answer: by key first, then by exact or trimmed message.
+    pub fn find_override<'a>(&self, overrides: &'a BTreeMap<String, String>) -> Option<&'a str> {
+        self.key
+            .as_deref()
+            .and_then(|key| overrides.get(key))
+            .or_else(|| overrides.get(&self.message))
+            .or_else(|| overrides.get(self.message.trim()))
+            .map(String::as_str)
+    }
+
+    /// Normalize `answer` for the prompt type, rejecting unknown choices.
+    pub fn validate_answer(&self, answer: &str) -> Result<String, String> {
+        match self.kind {
+            PromptKind::Text => Ok(answer.to_string()),
+            PromptKind::Otp => Ok(answer.split_whitespace().collect()),
+            PromptKind::Choice => {
+                let answer = answer.trim();
+                self.choices
+                    .iter()
+                    .find(|choice| choice.as_str() == answer)
+                    .or_else(|| {
+                        self.choices
+                            .iter()
+                            .find(|choice| choice.eq_ignore_ascii_case(answer))
+                    })
+                    .cloned()
+                    .ok_or_else(|| {
+                        format!(
+                            "invalid answer '{answer}' for prompt \"{}\"; expected one of: {}",
+                            self.message,
+                            self.choices.join(", ")
+                        )
+                    })
+            }
+        }
+    }
+
+    /// Error for a CLI run with no `--prompt` value for this prompt.
+    pub fn missing_override_error(&self) -> String {
+        let name = self.key.as_deref().unwrap_or(&self.message);
+        let mut text = format!(
+            "missing prompt value for refreshmint.prompt(\"{name}\"); supply --prompt \"{name}=VALUE\""
+        );
+        if !self.choices.is_empty() {
+            text.push_str(&format!(" with VALUE one of: {}", self.choices.join(", ")));
+        }
+        text
+    }
+
+    /// Line shown when reading the answer from stdin.
+    pub fn terminal_message(&self) -> String {
+        if self.choices.is_empty() {
+            self.message.clone()
+        } else {
+            format!("{} [{}]", self.message, self.choices.join("/"))
+        }
+    }
+}
+
+/// A prompt the UI can answer before a scrape starts.
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
+pub struct RequiredPrompt {
+    #[serde(flatten)]
+    pub request: PromptRequest,
+    /// Recent runs asked for it, so the next one probably will too.
+    pub likely: bool,
+}
+
+/// What the scrape log says about the login's saved session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SessionCheck {
+    /// Timestamp of the latest scrape, if the login has run before.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub last_run_at: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub last_run_succeeded: Option<bool>,
+    /// Whether recent runs had to ask for anything, i.e. the saved session
+    /// did not carry them through sign-in.
+    pub needs_prompts: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[ts(export, export_to = "index.ts")]
+pub struct RequiredPrompts {
+    pub prompts: Vec<RequiredPrompt>,
+    pub session: SessionCheck,
+}
+
+/// Combine the manifest catalog with the login's scrape log (oldest first).
+///
+/// Prompts asked by the latest successful run, or by any run after it, are
+/// `likely`. Undeclared prompts from those runs are listed after the
+/// catalog so they can be answered by message.
+pub fn required_prompts(
+    catalog: &PromptCatalog,
+    log: &[crate::operations::ScrapeLogEntry],
+) -> RequiredPrompts {
+    let recent_end = log.iter().rposition(|entry| entry.success).unwrap_or(0);
+    let asked: Vec<&PromptRequest> = log[recent_end..]
+        .iter()
+        .flat_map(|entry| &entry.prompts_asked)
+        .collect();
+
+    let mut prompts: Vec<RequiredPrompt> = catalog
+        .iter()
+        .map(|(key, declaration)| RequiredPrompt {
+            request: PromptRequest::resolve(catalog, key),
+            likely: asked.iter().any(|request| {
+                request.key.as_deref() == Some(key.as_str())
+                    || request.message == declaration.message
+            }),
+        })
+        .collect();
+    for request in &asked {
+        let undeclared = request.key.is_none() && !catalog.contains_key(&request.message);
+        if undeclared
+            && !prompts
+                .iter()
+                .any(|prompt| prompt.request.message == request.message)
+        {
+            prompts.push(RequiredPrompt {
+                request: PromptRequest::resolve(catalog, &request.message),
+                likely: true,
+            });
+        }
+    }
+
+    let latest = log.last();
+    RequiredPrompts {
+        prompts,
+        session: SessionCheck {
+            last_run_at: latest.map(|entry| entry.timestamp.clone()),
+            last_run_succeeded: latest.map(|entry| entry.success),
+            needs_prompts: !asked.is_empty(),
+        },
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn catalog() -> PromptCatalog {
+        serde_json::from_value(serde_json::json!({
+            "otp": { "message": "Enter the code we sent", "type": "otp" },
+            "mfaMethod": {
+                "message": "Where should we send the code?",
+                "type": "choice",
+                "choices": ["sms", "email"]
+            },
+            "memorable": { "message": "Memorable word" }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn catalog_validation_checks_keys_and_choices() {
+        assert!(validate_catalog(&catalog()).is_ok());
+
+        let mut bad_key = catalog();
+        bad_key.insert(
+            "one time=code".to_string(),
+            PromptDeclaration {
+                message: "Code".to_string(),
+                kind: PromptKind::Otp,
+                choices: Vec::new(),
+            },
+        );
+        assert!(validate_catalog(&bad_key).is_err());
+
+        let mut no_choices = catalog();
+        no_choices.get_mut("mfaMethod").unwrap().choices.clear();
+        assert!(validate_catalog(&no_choices).is_err());
+
+        let mut stray_choices = catalog();
+        stray_choices.get_mut("memorable").unwrap().choices = vec!["a".to_string()];
+        assert!(validate_catalog(&stray_choices).is_err());
+    }
+
+    #[test]
+    fn overrides_match_key_before_message() {
+        let catalog = catalog();
+        let request = PromptRequest::resolve(&catalog, "otp");
+        assert_eq!(request.message, "Enter the code we sent");
+        let overrides = BTreeMap::from([
+            ("Enter the code we sent".to_string(), "111111".to_string()),
+            ("otp".to_string(), "222222".to_string()),
+        ]);
+        assert_eq!(request.find_override(&overrides), Some("222222"));
+
+        let by_message =
+            BTreeMap::from([("Enter the code we sent".to_string(), "111111".to_string())]);
+        assert_eq!(request.find_override(&by_message), Some("111111"));
+
+        let free_form = PromptRequest::resolve(&catalog, " Security answer ");
+        assert_eq!(free_form.key, None);
+        let trimmed = BTreeMap::from([("Security answer".to_string(), "blue".to_string())]);
+        assert_eq!(free_form.find_override(&trimmed), Some("blue"));
+    }
+
+    #[test]
+    fn answers_are_normalized_per_type() {
+        let catalog = catalog();
+        let otp = PromptRequest::resolve(&catalog, "otp");
+        assert_eq!(otp.validate_answer(" 123 456\n").unwrap(), "123456");
+
+        let choice = PromptRequest::resolve(&catalog, "mfaMethod");
+        assert_eq!(choice.validate_answer("SMS").unwrap(), "sms");
+        let err = choice.validate_answer("voice").unwrap_err();
+        assert!(err.contains("sms, email"), "{err}");
+        assert!(choice
+            .missing_override_error()
+            .contains("--prompt \"mfaMethod=VALUE\" with VALUE one of: sms, email"));
+        assert_eq!(
+            choice.terminal_message(),
+            "Where should we send the code? [sms/email]"
+        );
+    }
+
+    #[test]
+    fn required_prompts_mark_what_recent_runs_asked() {
+        let catalog = catalog();
+        let entry =
+            |timestamp: &str, success: bool, asked: &[&str]| crate::operations::ScrapeLogEntry {
+                login_name: "bank".to_string(),
+                timestamp: timestamp.to_string(),
+                success,
+                error: None,
+                source: "manual".to_string(),
+                failure: None,
+                network: None,
+                prompts_asked: asked
+                    .iter()
+                    .map(|argument| PromptRequest::resolve(&catalog, argument))
+                    .collect(),
+                session_metadata: None,
+                suspect_documents: Vec::new(),
+                scrape_session_id: None,
+            };
+
+        let first_run = required_prompts(&catalog, &[]);
+        assert_eq!(first_run.prompts.len(), 3);
+        assert!(first_run.prompts.iter().all(|prompt| !prompt.likely));
+        assert_eq!(first_run.session, SessionCheck::default());
+
+        let log = [
+            entry("t1", true, &["memorable"]),
+            entry("t2", true, &["mfaMethod", "otp"]),
+            entry("t3", false, &["Security answer"]),
+        ];
+        let required = required_prompts(&catalog, &log);
+        let likely: Vec<&str> = required
+            .prompts
+            .iter()
+            .filter(|prompt| prompt.likely)
+            .map(|prompt| prompt.request.message.as_str())
+            .collect();
+        assert_eq!(
+            likely,
+            [
+                "Where should we send the code?",
+                "Enter the code we sent",
+                "Security answer"
+            ]
+        );
+        assert_eq!(required.prompts[3].request.key, None);
+        assert_eq!(
+            required.session,
+            SessionCheck {
+                last_run_at: Some("t3".to_string()),
+                last_run_succeeded: Some(false),
+                needs_prompts: true,
+            }
+        );
+
+        let signed_in = required_prompts(&catalog, &[entry("t4", true, &[])]);
+        assert!(!signed_in.session.needs_prompts);
+        assert!(signed_in.prompts.iter().all(|prompt| !prompt.likely));
+    }
+}
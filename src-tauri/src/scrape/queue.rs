@@ -0,0 +1,411 @@
+use std::collections::{BTreeSet, VecDeque};
+use std::sync::{Arc, Condvar, Mutex, OnceLock};
+
+/// Default number of scrape sessions allowed to run at once across all logins.
+pub const DEFAULT_MAX_CONCURRENT_SCRAPES: usize = 2;
+
+/// Callback invoked with a fresh snapshot whenever the queue changes.
+pub type QueueListener = Arc<dyn Fn(&ScrapeQueueSnapshot) + Send + Sync + 'static>;
+
+/// One queued or running scrape as reported to the UI.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ScrapeQueueEntry {
+    #[ts(type = "number")]
+    pub ticket: u64,
+    pub login_name: String,
+    pub domains: Vec<String>,
+    pub running: bool,
+    /// 1-based position among waiting scrapes; `None` once running.
+    pub position: Option<usize>,
+}
+
+/// Queue state emitted with `refreshmint://scrape-queue-changed`.
+#[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ScrapeQueueSnapshot {
+    pub max_concurrent: usize,
+    pub entries: Vec<ScrapeQueueEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct Waiter {
+    ticket: u64,
+    login_name: String,
+    domains: BTreeSet<String>,
+}
+
+#[derive(Debug)]
+struct QueueState {
+    max_concurrent: usize,
+    next_ticket: u64,
+    waiting: VecDeque<Waiter>,
+    running: Vec<Waiter>,
+}
+
+/// Admission queue for scrape sessions.
+///
+/// A waiting scrape starts only when fewer than `max_concurrent` sessions are
+/// running and no running session shares one of its domains. Scrapes that
+/// share a domain start in FIFO order; unrelated scrapes may start ahead of
+/// an earlier waiter that is blocked on its own domain.
+pub struct ScrapeQueue {
+    state: Mutex<QueueState>,
+    changed: Condvar,
+    listener: Mutex<Option<QueueListener>>,
+}
+
+/// Held for the duration of a scrape; releases the slot on drop.
+pub struct ScrapeSlot<'a> {
+    queue: &'a ScrapeQueue,
+    ticket: u64,
+}
+
+static GLOBAL_QUEUE: OnceLock<ScrapeQueue> = OnceLock::new();
+
+impl ScrapeQueue {
+    pub fn new(max_concurrent: usize) -> Self {
+        Self {
+            state: Mutex::new(QueueState {
+                max_concurrent: max_concurrent.max(1),
+                next_ticket: 1,
+                waiting: VecDeque::new(),
+                running: Vec::new(),
+            }),
+            changed: Condvar::new(),
+            listener: Mutex::new(None),
+        }
+    }
+
+    /// The process-wide queue shared by UI-triggered and scheduled scrapes.
+    pub fn global() -> &'static ScrapeQueue {
+        GLOBAL_QUEUE.get_or_init(|| ScrapeQueue::new(DEFAULT_MAX_CONCURRENT_SCRAPES))
+    }
+
+    pub fn set_listener(&self, listener: Option<QueueListener>) -> Result<(), String> {
+        let mut guard = self
+            .listener
+            .lock()
+            .map_err(|_| "failed to acquire scrape queue listener lock".to_string())?;
+        *guard = listener;
+        Ok(())
+    }
+
+    /// Change the global concurrency limit. Waiting scrapes are re-evaluated
+    /// immediately; running scrapes are never interrupted.
+    pub fn set_max_concurrent(&self, max_concurrent: usize) -> Result<(), String> {
+        if max_concurrent == 0 {
+            return Err("scrape concurrency limit must be at least 1".to_string());
+        }
+        let snapshot = {
+            let mut state = self.lock_state()?;
+            state.max_concurrent = max_concurrent;
+            snapshot_of(&state)
+        };
+        self.changed.notify_all();
+        self.notify(&snapshot);
+        Ok(())
+    }
+
+    pub fn snapshot(&self) -> Result<ScrapeQueueSnapshot, String> {
+        let state = self.lock_state()?;
+        Ok(snapshot_of(&state))
+    }
+
+    /// Block until the scrape may start.
+    ///
+    /// `domains` identifies the institution(s) the scrape talks to; an empty
+    /// set is treated as conflicting with nothing but the concurrency limit.
+    pub fn acquire(
+        &self,
+        login_name: &str,
+        domains: BTreeSet<String>,
+    ) -> Result<ScrapeSlot<'_>, String> {
+        let (ticket, snapshot) = {
+            let mut state = self.lock_state()?;
+            let ticket = state.next_ticket;
+            state.next_ticket += 1;
+            state.waiting.push_back(Waiter {
+                ticket,
+                login_name: login_name.to_string(),
+                domains,
+            });
+            (ticket, snapshot_of(&state))
+        };
+        self.notify(&snapshot);
+
+        let snapshot = {
+            let mut state = self.lock_state()?;
+            while !admissible_tickets(&state).contains(&ticket) {
+                state = self
+                    .changed
+                    .wait(state)
+                    .map_err(|_| "failed to wait on scrape queue".to_string())?;
+            }
+            if let Some(index) = state.waiting.iter().position(|w| w.ticket == ticket) {
+                if let Some(waiter) = state.waiting.remove(index) {
+                    state.running.push(waiter);
+                }
+            }
+            snapshot_of(&state)
+        };
+        // Other waiters' positions shifted.
+        self.changed.notify_all();
+        self.notify(&snapshot);
+
+        Ok(ScrapeSlot {
+            queue: self,
+            ticket,
+        })
+    }
+
+    fn release(&self, ticket: u64) {
+        let snapshot = match self.state.lock() {
+            Ok(mut state) => {
+                state.running.retain(|w| w.ticket != ticket);
+                snapshot_of(&state)
+            }
+            Err(_) => return,
+        };
+        self.changed.notify_all();
+        self.notify(&snapshot);
+    }
+
+    fn lock_state(&self) -> Result<std::sync::MutexGuard<'_, QueueState>, String> {
+        self.state
+            .lock()
+            .map_err(|_| "failed to acquire scrape queue lock".to_string())
+    }
+
+    fn notify(&self, snapshot: &ScrapeQueueSnapshot) {
+        let listener = match self.listener.lock() {
+            Ok(guard) => guard.clone(),
+            Err(_) => None,
+        };
+        if let Some(listener) = listener {
+            listener(snapshot);
+        }
+    }
+}
+
+impl Drop for ScrapeSlot<'_> {
+    fn drop(&mut self) {
+        self.queue.release(self.ticket);
+    }
+}
+
+/// Tickets of waiting scrapes that may start right now, in queue order.
+fn admissible_tickets(state: &QueueState) -> Vec<u64> {
+    let mut capacity = state.max_concurrent.saturating_sub(state.running.len());
+    let mut busy: BTreeSet<&str> = state
+        .running
+        .iter()
+        .flat_map(|w| w.domains.iter().map(String::as_str))
+        .collect();
+    let mut admitted = Vec::new();
+    for waiter in &state.waiting {
+        if capacity == 0 {
+            break;
+        }
+        let conflicts = waiter.domains.iter().any(|d| busy.contains(d.as_str()));
+        // Either way the domains are claimed: by the admitted scrape, or by
+        // an earlier waiter that later waiters on the same domain must not
+        // overtake.
+        busy.extend(waiter.domains.iter().map(String::as_str));
+        if !conflicts {
+            admitted.push(waiter.ticket);
+            capacity -= 1;
+        }
+    }
+    admitted
+}
+
+fn snapshot_of(state: &QueueState) -> ScrapeQueueSnapshot {
+    let running = state.running.iter().map(|w| ScrapeQueueEntry {
+        ticket: w.ticket,
+        login_name: w.login_name.clone(),
+        domains: w.domains.iter().cloned().collect(),
+        running: true,
+        position: None,
+    });
+    let waiting = state
+        .waiting
+        .iter()
+        .enumerate()
+        .map(|(index, w)| ScrapeQueueEntry {
+            ticket: w.ticket,
+            login_name: w.login_name.clone(),
+            domains: w.domains.iter().cloned().collect(),
+            running: false,
+            position: Some(index + 1),
+        });
+    ScrapeQueueSnapshot {
+        max_concurrent: state.max_concurrent,
+        entries: running.chain(waiting).collect(),
+    }
+}
+
+/// Domains used to serialize scrapes against the same institution.
+///
+/// Uses the manifest's declared secret domains, falling back to the extension
+/// name for extensions that declare none.
+pub fn scrape_domains(
+    declared: &super::js_api::SecretDeclarations,
+    extension_name: &str,
+) -> BTreeSet<String> {
+    let mut domains: BTreeSet<String> = declared.keys().cloned().collect();
+    if domains.is_empty() {
+        domains.insert(format!("extension:{extension_name}"));
+    }
+    domains
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    fn domains(values: &[&str]) -> BTreeSet<String> {
+        values.iter().map(|v| v.to_string()).collect()
+    }
+
+    fn waiter(ticket: u64, values: &[&str]) -> Waiter {
+        Waiter {
+            ticket,
+            login_name: format!("login-{ticket}"),
+            domains: domains(values),
+        }
+    }
+
+    #[test]
+    fn admissible_respects_global_limit() {
+        let state = QueueState {
+            max_concurrent: 2,
+            next_ticket: 4,
+            waiting: VecDeque::from(vec![
+                waiter(1, &["a.com"]),
+                waiter(2, &["b.com"]),
+                waiter(3, &["c.com"]),
+            ]),
+            running: Vec::new(),
+        };
+        assert_eq!(admissible_tickets(&state), vec![1, 2]);
+    }
+
+    #[test]
+    fn admissible_serializes_same_domain_in_fifo_order() {
+        let state = QueueState {
+            max_concurrent: 3,
+            next_ticket: 5,
+            waiting: VecDeque::from(vec![
+                waiter(2, &["bank.com"]),
+                waiter(3, &["other.com"]),
+                waiter(4, &["bank.com"]),
+            ]),
+            running: vec![waiter(1, &["bank.com"])],
+        };
+        // 2 and 4 wait on the running bank.com session; 3 may overtake.
+        assert_eq!(admissible_tickets(&state), vec![3]);
+    }
+
+    #[test]
+    fn admissible_does_not_let_later_waiter_overtake_same_domain() {
+        let state = QueueState {
+            max_concurrent: 3,
+            next_ticket: 5,
+            waiting: VecDeque::from(vec![waiter(2, &["a.com", "b.com"]), waiter(3, &["b.com"])]),
+            running: vec![waiter(1, &["a.com"])],
+        };
+        assert!(admissible_tickets(&state).is_empty());
+    }
+
+    #[test]
+    fn snapshot_reports_positions() {
+        let queue = ScrapeQueue::new(1);
+        let slot = queue
+            .acquire("first", domains(&["a.com"]))
+            .expect("first acquire");
+        {
+            let mut state = queue.lock_state().expect("lock");
+            state.waiting.push_back(waiter(10, &["b.com"]));
+        }
+        let snapshot = queue.snapshot().expect("snapshot");
+        assert_eq!(snapshot.max_concurrent, 1);
+        assert_eq!(snapshot.entries.len(), 2);
+        assert!(snapshot.entries[0].running);
+        assert_eq!(snapshot.entries[0].login_name, "first");
+        assert_eq!(snapshot.entries[1].position, Some(1));
+        drop(slot);
+    }
+
+    #[test]
+    fn set_max_concurrent_rejects_zero() {
+        let queue = ScrapeQueue::new(2);
+        assert!(queue.set_max_concurrent(0).is_err());
+        queue.set_max_concurrent(3).expect("set limit");
+        assert_eq!(queue.snapshot().expect("snapshot").max_concurrent, 3);
+    }
+
+    #[test]
+    fn same_domain_scrapes_never_overlap() {
+        let queue = Arc::new(ScrapeQueue::new(4));
+        let active = Arc::new(AtomicUsize::new(0));
+        let max_seen = Arc::new(AtomicUsize::new(0));
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                let queue = queue.clone();
+                let active = active.clone();
+                let max_seen = max_seen.clone();
+                std::thread::spawn(move || {
+                    let _slot = queue
+                        .acquire(&format!("login-{i}"), domains(&["bank.com"]))
+                        .expect("acquire");
+                    let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_seen.fetch_max(now, Ordering::SeqCst);
+                    std::thread::sleep(Duration::from_millis(10));
+                    active.fetch_sub(1, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().expect("join");
+        }
+        assert_eq!(max_seen.load(Ordering::SeqCst), 1);
+        assert!(queue.snapshot().expect("snapshot").entries.is_empty());
+    }
+
+    #[test]
+    fn listener_sees_queue_changes() {
+        let queue = ScrapeQueue::new(1);
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink = events.clone();
+        queue
+            .set_listener(Some(Arc::new(move |snapshot: &ScrapeQueueSnapshot| {
+                if let Ok(mut guard) = sink.lock() {
+                    guard.push(snapshot.entries.len());
+                }
+            })))
+            .expect("set listener");
+        let slot = queue.acquire("only", domains(&["a.com"])).expect("acquire");
+        drop(slot);
+        let events = events.lock().expect("events").clone();
+        // queued, started, released
+        assert_eq!(events, vec![1, 1, 0]);
+    }
+
+    #[test]
+    fn scrape_domains_falls_back_to_extension_name() {
+        let empty = super::super::js_api::SecretDeclarations::new();
+        assert_eq!(
+            scrape_domains(&empty, "chase"),
+            domains(&["extension:chase"])
+        );
+        let mut declared = super::super::js_api::SecretDeclarations::new();
+        declared.insert("chase.com".to_string(), Default::default());
+        assert_eq!(scrape_domains(&declared, "chase"), domains(&["chase.com"]));
+    }
+}
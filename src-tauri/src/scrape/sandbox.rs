@@ -30,7 +30,7 @@ impl Default for SandboxRunOptions {
 
 fn maybe_diag(options: SandboxRunOptions, message: &str) {
     if options.emit_diagnostics {
-        eprintln!("{message}");
+        tracing::info!("{message}");
     }
 }
 
@@ -181,6 +181,7 @@ async fn run_module_path_internal(
     let setup_result: Result<(), String> = context
         .with(|ctx| {
             init_quickjs_web_platform(&ctx)?;
+            let route_page_inner = globals.as_ref().map(|(page_inner, _)| page_inner.clone());
             if let Some((page_inner, refreshmint_inner)) = globals {
                 js_api::register_globals(&ctx, page_inner, refreshmint_inner)
                     .map_err(|e| format!("failed to register globals: {e}"))?;
@@ -192,6 +193,11 @@ async fn run_module_path_internal(
                 .map_err(|e| format!("failed to import driver module: {e}"))?;
             maybe_diag(options, "[sandbox] Driver import returned promise.");
 
+            let promise = match route_page_inner {
+                Some(page_inner) => js_api::stop_routes_when_settled(&ctx, page_inner, promise)
+                    .map_err(|e| format!("failed to chain route cleanup: {e}"))?,
+                None => promise,
+            };
             ctx.globals()
                 .set("__driver_promise__", promise)
                 .map_err(|e| format!("failed to store promise: {e}"))?;
@@ -237,7 +243,7 @@ async fn run_module_path_internal(
                         Ok(()) => "unknown JavaScript exception".to_string(),
                     };
                     if options.emit_diagnostics {
-                        eprintln!("[sandbox] Promise rejected: {msg}");
+                        tracing::warn!("Promise rejected: {msg}");
                     }
                     Err(msg)
                 }
@@ -288,6 +294,7 @@ async fn run_script_source_internal(
     let setup_result: Result<(), String> = context
         .with(|ctx| {
             init_quickjs_web_platform(&ctx)?;
+            let route_page_inner = globals.as_ref().map(|(page_inner, _)| page_inner.clone());
             if let Some((page_inner, refreshmint_inner)) = globals {
                 js_api::register_globals(&ctx, page_inner, refreshmint_inner)
                     .map_err(|e| format!("failed to register globals: {e}"))?;
@@ -321,6 +328,11 @@ async fn run_script_source_internal(
                 }
             };
 
+            let promise = match route_page_inner {
+                Some(page_inner) => js_api::stop_routes_when_settled(&ctx, page_inner, promise)
+                    .map_err(|e| format!("failed to chain route cleanup: {e}"))?,
+                None => promise,
+            };
             ctx.globals()
                 .set("__driver_promise__", promise)
                 .map_err(|e| format!("failed to store promise: {e}"))?;
@@ -362,7 +374,7 @@ async fn run_script_source_internal(
                         Ok(()) => "unknown JavaScript exception".to_string(),
                     };
                     if options.emit_diagnostics {
-                        eprintln!("[sandbox] Promise rejected: {msg}");
+                        tracing::warn!("Promise rejected: {msg}");
                     }
                     Err(msg)
                 }
@@ -396,9 +408,9 @@ async fn drive_runtime(runtime: &AsyncRuntime, options: &SandboxRunOptions) {
                             if let Some(exc) =
                                 err.clone().into_object().and_then(Exception::from_object)
                             {
-                                eprintln!("[sandbox] error executing job: {exc}");
+                                tracing::warn!("error executing job: {exc}");
                             } else {
-                                eprintln!("[sandbox] error executing job: {err:?}");
+                                tracing::warn!("error executing job: {err:?}");
                             }
                         })
                         .await;
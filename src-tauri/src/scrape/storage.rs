@@ -0,0 +1,96 @@
+//! JS-visible `refreshmint.storage`: persistent key/value state for the
+//! running login+extension, backed by [`crate::extension_storage`].
+
+use std::sync::Arc;
+
+use rquickjs::{class::Trace, JsLifetime, Result as JsResult, Value};
+use tokio::sync::Mutex;
+
+use super::js_api::{
+    js_err, rquickjs_value_to_json, scrub_known_secrets, JsEvalResult, PageInner, RefreshmintInner,
+};
+
+#[rquickjs::class(rename = "Storage")]
+#[derive(Trace)]
+pub struct StorageApi {
+    #[qjs(skip_trace)]
+    page_inner: Arc<Mutex<PageInner>>,
+    #[qjs(skip_trace)]
+    refreshmint: Arc<Mutex<RefreshmintInner>>,
+}
+
+// Safety: StorageApi only contains Arc<Mutex<...>> which is 'static.
+#[allow(unsafe_code)]
+unsafe impl<'js> JsLifetime<'js> for StorageApi {
+    type Changed<'to> = StorageApi;
+}
+
+impl StorageApi {
+    pub fn new(
+        page_inner: Arc<Mutex<PageInner>>,
+        refreshmint: Arc<Mutex<RefreshmintInner>>,
+    ) -> Self {
+        Self {
+            page_inner,
+            refreshmint,
+        }
+    }
+
+    /// `(ledger_dir, login_name, extension_name)` of the running script.
+    async fn scope(&self) -> (std::path::PathBuf, String, String) {
+        let inner = self.refreshmint.lock().await;
+        (
+            inner.ledger_dir.clone(),
+            inner.login_name.clone(),
+            inner.extension_name.clone(),
+        )
+    }
+}
+
+#[rquickjs::methods]
+impl StorageApi {
+    /// Return the stored value, or `undefined` when `key` is not set.
+    pub async fn get(&self, key: String) -> JsResult<JsEvalResult> {
+        let (ledger_dir, login_name, extension_name) = self.scope().await;
+        let value =
+            crate::extension_storage::get_value(&ledger_dir, &login_name, &extension_name, &key)
+                .map_err(|e| js_err(format!("storage.get failed: {e}")))?;
+        match value {
+            Some(value) => {
+                let json = serde_json::to_string(&value)
+                    .map_err(|e| js_err(format!("storage.get serialization: {e}")))?;
+                Ok(JsEvalResult::Json(format!("({json})")))
+            }
+            None => Ok(JsEvalResult::Undefined),
+        }
+    }
+
+    /// Store a JSON-serializable value. Values that contain a keychain
+    /// username are rejected because the store is saved in the ledger.
+    pub async fn set<'js>(&self, key: String, value: Value<'js>) -> JsResult<()> {
+        let value = rquickjs_value_to_json(&value)
+            .map_err(|e| js_err(format!("storage.set: value is not JSON-serializable: {e}")))?;
+        let text = serde_json::to_string(&value)
+            .map_err(|e| js_err(format!("storage.set serialization: {e}")))?;
+        let mut scrubbed = text.clone();
+        {
+            let page_inner = self.page_inner.lock().await;
+            scrub_known_secrets(&page_inner.secrets, &mut scrubbed);
+        }
+        if scrubbed != text {
+            return Err(js_err(format!(
+                "storage.set: value for '{key}' contains a secret; refreshmint.storage is saved in the ledger and must not hold secrets"
+            )));
+        }
+        let (ledger_dir, login_name, extension_name) = self.scope().await;
+        crate::extension_storage::set_value(&ledger_dir, &login_name, &extension_name, &key, value)
+            .map_err(|e| js_err(format!("storage.set failed: {e}")))
+    }
+
+    /// Remove `key`; resolves to whether it was set.
+    pub async fn delete(&self, key: String) -> JsResult<bool> {
+        let (ledger_dir, login_name, extension_name) = self.scope().await;
+        crate::extension_storage::delete_value(&ledger_dir, &login_name, &extension_name, &key)
+            .map_err(|e| js_err(format!("storage.delete failed: {e}")))
+    }
+}
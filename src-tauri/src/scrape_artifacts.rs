@@ -0,0 +1,320 @@
+//! Diagnostics a scrape leaves behind, for viewing in the app.
+//!
+//! Each login's artifacts live in `cache/diagnostics/<login>/`, named after
+//! the scrape session that wrote them (`<session>-<suffix>`): screenshots
+//! taken when an assertion fails, the session's report, and any HAR or
+//! console log files. [`read_scrape_artifact`] only opens files inside that
+//! directory tree, so the frontend cannot use it to read the rest of the
+//! ledger or the filesystem.
+
+use serde::Serialize;
+use std::io;
+use std::path::{Component, Path, PathBuf};
+
+use base64::Engine;
+
+use crate::operations::ScrapeLogEntry;
+
+/// Largest artifact `read_scrape_artifact` returns.
+const MAX_ARTIFACT_BYTES: u64 = 32 * 1024 * 1024;
+
+/// Length of a session ID from `generate_scrape_session_id`, `YYYYMMDD-HHMMSS`.
+const SESSION_ID_LEN: usize = 15;
+
+/// What an artifact holds, from its extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "ScrapeArtifactKind")]
+pub enum ArtifactKind {
+    Screenshot,
+    Har,
+    ConsoleLog,
+    Report,
+    Other,
+}
+
+impl ArtifactKind {
+    fn of(path: &Path) -> Self {
+        let extension = path
+            .extension()
+            .and_then(|ext| ext.to_str())
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match extension.as_str() {
+            "png" | "jpg" | "jpeg" | "webp" => ArtifactKind::Screenshot,
+            "har" => ArtifactKind::Har,
+            "log" | "txt" => ArtifactKind::ConsoleLog,
+            "json" | "jsonl" => ArtifactKind::Report,
+            _ => ArtifactKind::Other,
+        }
+    }
+
+    fn is_text(self) -> bool {
+        !matches!(self, ArtifactKind::Screenshot | ArtifactKind::Other)
+    }
+}
+
+/// One file in a login's diagnostics directory.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ScrapeArtifact {
+    /// Ledger-relative path, for `read_scrape_artifact`.
+    pub path: String,
+    pub name: String,
+    pub kind: ArtifactKind,
+    /// The scrape session that wrote it, when the name starts with one.
+    pub session_id: Option<String>,
+    #[ts(type = "number")]
+    pub size: u64,
+}
+
+/// An artifact's contents: `text` for reports, HAR files, and logs,
+/// `base64` for screenshots and anything else.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct ScrapeArtifactContent {
+    pub path: String,
+    pub kind: ArtifactKind,
+    pub mime_type: String,
+    pub text: Option<String>,
+    pub base64: Option<String>,
+}
+
+/// Ledger-relative directory holding a login's diagnostics.
+pub fn login_diagnostics_dir(login_name: &str) -> PathBuf {
+    diagnostics_root().join(login_name)
+}
+
+fn diagnostics_root() -> PathBuf {
+    Path::new("cache").join("diagnostics")
+}
+
+/// The session a `<session>-<suffix>` artifact name belongs to.
+fn session_of(name: &str) -> Option<&str> {
+    let session = name.get(..SESSION_ID_LEN)?;
+    let well_formed = session.char_indices().all(|(i, c)| match i {
+        8 => c == '-',
+        _ => c.is_ascii_digit(),
+    });
+    (well_formed && name[SESSION_ID_LEN..].starts_with('-')).then_some(session)
+}
+
+/// Artifacts for a login, newest session first, optionally only those of
+/// one session. A login that never wrote diagnostics has none.
+pub fn list_scrape_artifacts(
+    ledger_dir: &Path,
+    login_name: &str,
+    session_id: Option<&str>,
+) -> io::Result<Vec<ScrapeArtifact>> {
+    let relative_dir = login_diagnostics_dir(login_name);
+    let entries = match std::fs::read_dir(ledger_dir.join(&relative_dir)) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => return Err(err),
+    };
+
+    let mut artifacts = Vec::new();
+    for entry in entries {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+        let name = entry.file_name().to_string_lossy().into_owned();
+        let session = session_of(&name).map(str::to_string);
+        if session_id.is_some() && session.as_deref() != session_id {
+            continue;
+        }
+        let path = relative_dir.join(&name);
+        artifacts.push(ScrapeArtifact {
+            path: path.to_string_lossy().replace('\\', "/"),
+            kind: ArtifactKind::of(&path),
+            name,
+            session_id: session,
+            size: metadata.len(),
+        });
+    }
+    artifacts.sort_by(|a, b| {
+        b.session_id
+            .cmp(&a.session_id)
+            .then_with(|| a.name.cmp(&b.name))
+    });
+    Ok(artifacts)
+}
+
+/// Read one artifact by the ledger-relative path `list_scrape_artifacts`
+/// gave. Paths outside `cache/diagnostics/`, including through `..` or a
+/// symlink, are refused.
+pub fn read_scrape_artifact(
+    ledger_dir: &Path,
+    path: &str,
+) -> Result<ScrapeArtifactContent, String> {
+    let relative = Path::new(path);
+    if !relative
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+    {
+        return Err(format!(
+            "artifact path must be relative to the ledger: {path}"
+        ));
+    }
+    let root = ledger_dir
+        .join(diagnostics_root())
+        .canonicalize()
+        .map_err(|err| format!("no scrape artifacts in this ledger: {err}"))?;
+    let full = ledger_dir
+        .join(relative)
+        .canonicalize()
+        .map_err(|err| format!("artifact {path}: {err}"))?;
+    if !full.starts_with(&root) {
+        return Err(format!("not a scrape artifact: {path}"));
+    }
+    let metadata = std::fs::metadata(&full).map_err(|err| format!("artifact {path}: {err}"))?;
+    if !metadata.is_file() {
+        return Err(format!("not a scrape artifact: {path}"));
+    }
+    if metadata.len() > MAX_ARTIFACT_BYTES {
+        return Err(format!(
+            "artifact {path} is {} bytes, more than the {MAX_ARTIFACT_BYTES} shown",
+            metadata.len()
+        ));
+    }
+
+    let data = std::fs::read(&full).map_err(|err| format!("artifact {path}: {err}"))?;
+    let kind = ArtifactKind::of(&full);
+    let (text, base64) = match String::from_utf8(data) {
+        Ok(text) if kind.is_text() => (Some(text), None),
+        Ok(text) => (
+            None,
+            Some(base64::engine::general_purpose::STANDARD.encode(text.into_bytes())),
+        ),
+        Err(err) => (
+            None,
+            Some(base64::engine::general_purpose::STANDARD.encode(err.into_bytes())),
+        ),
+    };
+    Ok(ScrapeArtifactContent {
+        path: path.to_string(),
+        kind,
+        mime_type: mime_type(&full),
+        text,
+        base64,
+    })
+}
+
+fn mime_type(path: &Path) -> String {
+    let extension = path
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    match extension.as_str() {
+        "png" => "image/png".to_string(),
+        "jpg" | "jpeg" => "image/jpeg".to_string(),
+        "webp" => "image/webp".to_string(),
+        "har" => "application/json".to_string(),
+        "log" => "text/plain".to_string(),
+        _ => crate::scrape::guess_mime_type(&path.to_string_lossy()),
+    }
+}
+
+/// Save a finished run's log entry as `<session>-report.json` next to its
+/// other artifacts. Runs without a session ID have nowhere to go.
+pub fn write_session_report(ledger_dir: &Path, entry: &ScrapeLogEntry) -> io::Result<()> {
+    let Some(session_id) = &entry.scrape_session_id else {
+        return Ok(());
+    };
+    let dir = ledger_dir.join(login_diagnostics_dir(&entry.login_name));
+    let json = serde_json::to_string_pretty(entry).map_err(io::Error::other)?;
+    crate::atomic_file::write_atomic(
+        &dir.join(format!("{session_id}-report.json")),
+        json.as_bytes(),
+    )
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time should be after unix epoch")
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    #[test]
+    fn lists_artifacts_by_session_newest_first() {
+        let ledger = temp_dir("scrape-artifacts-list");
+        let dir = ledger.join(login_diagnostics_dir("bank"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("20250102-090000-093015123.png"), b"\x89PNG").unwrap();
+        std::fs::write(dir.join("20250102-090000-report.json"), b"{}").unwrap();
+        std::fs::write(dir.join("20250301-120000-report.json"), b"{}").unwrap();
+        std::fs::write(dir.join("notes.txt"), b"hi").unwrap();
+
+        let all = list_scrape_artifacts(&ledger, "bank", None).unwrap();
+        let names: Vec<&str> = all.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "20250301-120000-report.json",
+                "20250102-090000-093015123.png",
+                "20250102-090000-report.json",
+                "notes.txt",
+            ]
+        );
+        assert_eq!(all[1].kind, ArtifactKind::Screenshot);
+        assert_eq!(
+            all[1].path,
+            "cache/diagnostics/bank/20250102-090000-093015123.png"
+        );
+        assert_eq!(all[3].session_id, None);
+
+        let one = list_scrape_artifacts(&ledger, "bank", Some("20250102-090000")).unwrap();
+        assert_eq!(one.len(), 2);
+        assert!(list_scrape_artifacts(&ledger, "other", None)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn read_scrape_artifact_stays_inside_diagnostics() {
+        let ledger = temp_dir("scrape-artifacts-read");
+        let dir = ledger.join(login_diagnostics_dir("bank"));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            dir.join("20250102-090000-report.json"),
+            b"{\"success\":false}",
+        )
+        .unwrap();
+        std::fs::write(dir.join("20250102-090000-1.png"), b"\x89PNG\r\n").unwrap();
+        std::fs::write(ledger.join("general.journal"), b"secret").unwrap();
+
+        let report = read_scrape_artifact(
+            &ledger,
+            "cache/diagnostics/bank/20250102-090000-report.json",
+        )
+        .unwrap();
+        assert_eq!(report.text.as_deref(), Some("{\"success\":false}"));
+        let screenshot =
+            read_scrape_artifact(&ledger, "cache/diagnostics/bank/20250102-090000-1.png").unwrap();
+        assert_eq!(screenshot.base64.as_deref(), Some("iVBORw0K"));
+
+        for path in [
+            "general.journal",
+            "cache/diagnostics/bank/../../../general.journal",
+            "/etc/passwd",
+            "cache/diagnostics/bank",
+        ] {
+            assert!(read_scrape_artifact(&ledger, path).is_err(), "{path}");
+        }
+    }
+}
@@ -0,0 +1,372 @@
+//! Per-login scrape reliability over time.
+//!
+//! Every scrape run is added to a daily bucket in
+//! `logins/<login>/scrape-stats.json`: runs, successes, total duration, and
+//! failures by error class. Unlike the scrape log, the buckets stay small no
+//! matter how often a login is scraped. [`reliability_report`] rolls them up
+//! per day, week, or month so drivers that start failing after a bank UI
+//! change stand out.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::balances::BalancePeriod;
+use crate::operations::ScrapeLogEntry;
+
+/// Runs in the last this many days count as recent.
+const RECENT_DAYS: i64 = 14;
+
+/// Counters for one day, or for any roll-up of days.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Tally {
+    runs: u64,
+    successes: u64,
+    total_duration_ms: u64,
+    /// Failed runs by [`classify_failure`] class.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    failures: BTreeMap<String, u64>,
+}
+
+impl Tally {
+    fn add(&mut self, other: &Tally) {
+        self.runs += other.runs;
+        self.successes += other.successes;
+        self.total_duration_ms += other.total_duration_ms;
+        for (class, count) in &other.failures {
+            *self.failures.entry(class.clone()).or_default() += count;
+        }
+    }
+
+    fn success_rate(&self) -> f64 {
+        if self.runs == 0 {
+            return 0.0;
+        }
+        self.successes as f64 / self.runs as f64
+    }
+
+    fn mean_duration_ms(&self) -> u64 {
+        self.total_duration_ms.checked_div(self.runs).unwrap_or(0)
+    }
+}
+
+/// On-disk format of `scrape-stats.json`, keyed by UTC date (`YYYY-MM-DD`).
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct StatsFile {
+    #[serde(default)]
+    days: BTreeMap<String, Tally>,
+}
+
+/// Scrape totals for one day, week, or month.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "ScrapeReliabilityPoint")]
+pub struct ReliabilityPoint {
+    /// First day of the period.
+    pub period_start: String,
+    #[ts(type = "number")]
+    pub runs: u64,
+    #[ts(type = "number")]
+    pub successes: u64,
+    pub success_rate: f64,
+    #[ts(type = "number")]
+    pub mean_duration_ms: u64,
+    #[ts(type = "Record<string, number>")]
+    pub failures: BTreeMap<String, u64>,
+}
+
+/// How reliably one login has been scraping.
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts", rename = "LoginScrapeReliability")]
+pub struct LoginReliability {
+    pub login_name: String,
+    #[ts(type = "number")]
+    pub runs: u64,
+    #[ts(type = "number")]
+    pub successes: u64,
+    pub success_rate: f64,
+    #[ts(type = "number")]
+    pub mean_duration_ms: u64,
+    #[ts(type = "Record<string, number>")]
+    pub failures: BTreeMap<String, u64>,
+    /// Runs in the last 14 days.
+    #[ts(type = "number")]
+    pub recent_runs: u64,
+    /// Success rate over the last 14 days; `None` without recent runs.
+    pub recent_success_rate: Option<f64>,
+    /// Oldest period first.
+    pub points: Vec<ReliabilityPoint>,
+}
+
+/// Returns the path to `logins/<login_name>/scrape-stats.json`.
+pub fn login_scrape_stats_path(ledger_dir: &Path, login_name: &str) -> PathBuf {
+    ledger_dir
+        .join("logins")
+        .join(login_name)
+        .join("scrape-stats.json")
+}
+
+/// Group a scrape error into a coarse class for the failure breakdown:
+/// `assertion`, `session-expired`, `network-policy`, `prompt`, `selector`,
+/// `browser`, `timeout`, or `other`.
+pub fn classify_failure(error: &str, assertion: bool) -> &'static str {
+    if assertion || error.starts_with("assertion failed:") {
+        "assertion"
+    } else if error.contains("SessionExpiredError") {
+        "session-expired"
+    } else if error.contains("NetworkPolicyError") {
+        "network-policy"
+    } else if error.contains("missing prompt value") {
+        "prompt"
+    } else if error.contains("Strict mode violation") {
+        "selector"
+    } else if error.contains("TargetClosedError")
+        || error.contains("BrowserDisconnectedError")
+        || error.contains("failed to launch")
+    {
+        "browser"
+    } else if error.contains("TimeoutError") || error.contains("timed out") {
+        "timeout"
+    } else {
+        "other"
+    }
+}
+
+/// Add a finished scrape to its login's daily bucket.
+pub fn record_scrape_run(
+    ledger_dir: &Path,
+    entry: &ScrapeLogEntry,
+    duration: std::time::Duration,
+) -> io::Result<()> {
+    let path = login_scrape_stats_path(ledger_dir, &entry.login_name);
+    let mut stats = read_stats(&path)?;
+    let day = chrono::DateTime::parse_from_rfc3339(&entry.timestamp)
+        .map(|timestamp| timestamp.with_timezone(&chrono::Utc).date_naive())
+        .unwrap_or_else(|_| chrono::Utc::now().date_naive());
+    let tally = stats
+        .days
+        .entry(day.format("%Y-%m-%d").to_string())
+        .or_default();
+    tally.runs += 1;
+    tally.total_duration_ms += u64::try_from(duration.as_millis()).unwrap_or(u64::MAX);
+    if entry.success {
+        tally.successes += 1;
+    } else {
+        let class = classify_failure(
+            entry.error.as_deref().unwrap_or_default(),
+            entry.failure.is_some(),
+        );
+        *tally.failures.entry(class.to_string()).or_default() += 1;
+    }
+
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let json = serde_json::to_string_pretty(&stats).map_err(io::Error::other)?;
+    crate::atomic_file::write_atomic(&path, json.as_bytes())
+}
+
+/// Reliability of each login with recorded runs, least reliable recently
+/// first.
+pub fn reliability_report(
+    ledger_dir: &Path,
+    login_names: &[String],
+    period: BalancePeriod,
+) -> io::Result<Vec<LoginReliability>> {
+    let today = chrono::Utc::now().date_naive();
+    let mut report = Vec::new();
+    for login_name in login_names {
+        let stats = read_stats(&login_scrape_stats_path(ledger_dir, login_name))?;
+        if let Some(summary) = summarize(login_name, &stats, period, today) {
+            report.push(summary);
+        }
+    }
+    report.sort_by(|a, b| {
+        let a_rate = a.recent_success_rate.unwrap_or(f64::INFINITY);
+        let b_rate = b.recent_success_rate.unwrap_or(f64::INFINITY);
+        a_rate
+            .total_cmp(&b_rate)
+            .then_with(|| a.login_name.cmp(&b.login_name))
+    });
+    Ok(report)
+}
+
+fn summarize(
+    login_name: &str,
+    stats: &StatsFile,
+    period: BalancePeriod,
+    today: chrono::NaiveDate,
+) -> Option<LoginReliability> {
+    let recent_start = today - chrono::Duration::days(RECENT_DAYS - 1);
+    let mut total = Tally::default();
+    let mut recent = Tally::default();
+    let mut periods: BTreeMap<chrono::NaiveDate, Tally> = BTreeMap::new();
+    for (day, tally) in &stats.days {
+        let Ok(date) = chrono::NaiveDate::parse_from_str(day, "%Y-%m-%d") else {
+            continue;
+        };
+        total.add(tally);
+        if date >= recent_start {
+            recent.add(tally);
+        }
+        periods.entry(period.bucket(date)).or_default().add(tally);
+    }
+    if total.runs == 0 {
+        return None;
+    }
+    Some(LoginReliability {
+        login_name: login_name.to_string(),
+        runs: total.runs,
+        successes: total.successes,
+        success_rate: total.success_rate(),
+        mean_duration_ms: total.mean_duration_ms(),
+        recent_runs: recent.runs,
+        recent_success_rate: (recent.runs > 0).then(|| recent.success_rate()),
+        points: periods
+            .into_iter()
+            .map(|(start, tally)| ReliabilityPoint {
+                period_start: start.format("%Y-%m-%d").to_string(),
+                runs: tally.runs,
+                successes: tally.successes,
+                success_rate: tally.success_rate(),
+                mean_duration_ms: tally.mean_duration_ms(),
+                failures: tally.failures,
+            })
+            .collect(),
+        failures: total.failures,
+    })
+}
+
+fn read_stats(path: &Path) -> io::Result<StatsFile> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => serde_json::from_str(&text)
+            .map_err(|e| io::Error::other(format!("{}: invalid JSON: {e}", path.display()))),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(StatsFile::default()),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .expect("system time should be after unix epoch")
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        std::fs::create_dir_all(&dir).expect("create temp dir");
+        dir
+    }
+
+    fn run(login: &str, timestamp: &str, error: Option<&str>) -> ScrapeLogEntry {
+        ScrapeLogEntry {
+            login_name: login.to_string(),
+            timestamp: timestamp.to_string(),
+            success: error.is_none(),
+            error: error.map(str::to_string),
+            source: "auto".to_string(),
+            failure: None,
+            network: None,
+            prompts_asked: Vec::new(),
+            session_metadata: None,
+            suspect_documents: Vec::new(),
+            scrape_session_id: None,
+        }
+    }
+
+    #[test]
+    fn classify_failure_recognizes_runner_errors() {
+        let cases = [
+            (
+                "Error: TimeoutError: locator.click: timeout 30000ms exceeded",
+                "timeout",
+            ),
+            (
+                "SessionExpiredError: still on login wall",
+                "session-expired",
+            ),
+            (
+                "NetworkPolicyError: goto to \"https://x.io\" blocked",
+                "network-policy",
+            ),
+            (
+                "missing prompt value for refreshmint.prompt(\"OTP\")",
+                "prompt",
+            ),
+            ("click: Strict mode violation: 2 elements found", "selector"),
+            ("TargetClosedError: page has been closed", "browser"),
+            ("assertion failed: balance is visible", "assertion"),
+            ("ReferenceError: foo is not defined", "other"),
+        ];
+        for (error, class) in cases {
+            assert_eq!(classify_failure(error, false), class, "{error}");
+        }
+        assert_eq!(classify_failure("anything", true), "assertion");
+    }
+
+    #[test]
+    fn record_and_report_roll_up_daily_buckets() {
+        let root = temp_dir("scrape-stats");
+        let record = |entry: ScrapeLogEntry, ms: u64| {
+            record_scrape_run(&root, &entry, Duration::from_millis(ms)).unwrap();
+        };
+        record(run("chase", "2026-03-02T10:00:00.000Z", None), 1000);
+        record(
+            run("chase", "2026-03-04T10:00:00.000Z", Some("TimeoutError: x")),
+            3000,
+        );
+        record(run("chase", "2026-03-10T23:30:00.000-05:00", None), 2000);
+
+        let stats = read_stats(&login_scrape_stats_path(&root, "chase")).unwrap();
+        assert_eq!(
+            stats.days.keys().collect::<Vec<_>>(),
+            vec!["2026-03-02", "2026-03-04", "2026-03-11"]
+        );
+
+        let today = chrono::NaiveDate::from_ymd_opt(2026, 3, 20).unwrap();
+        let summary = summarize("chase", &stats, BalancePeriod::Weekly, today).unwrap();
+        assert_eq!((summary.runs, summary.successes), (3, 2));
+        assert_eq!(summary.mean_duration_ms, 2000);
+        assert_eq!(summary.failures.get("timeout"), Some(&1));
+        assert_eq!(summary.recent_runs, 1);
+        assert_eq!(summary.recent_success_rate, Some(1.0));
+        let points: Vec<_> = summary
+            .points
+            .iter()
+            .map(|p| (p.period_start.as_str(), p.runs, p.successes))
+            .collect();
+        assert_eq!(points, vec![("2026-03-02", 2, 1), ("2026-03-09", 1, 1)]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[test]
+    fn report_lists_least_reliable_logins_first() {
+        let root = temp_dir("scrape-stats-report");
+        let now = crate::operations::now_timestamp();
+        for (login, error) in [
+            ("amex", None),
+            ("chase", Some("TimeoutError: x")),
+            ("chase", None),
+        ] {
+            record_scrape_run(&root, &run(login, &now, error), Duration::ZERO).unwrap();
+        }
+        let logins = ["amex", "chase", "never-run"].map(str::to_string);
+        let report = reliability_report(&root, &logins, BalancePeriod::Daily).unwrap();
+        let order: Vec<_> = report
+            .iter()
+            .map(|r| (r.login_name.as_str(), r.recent_success_rate))
+            .collect();
+        assert_eq!(order, vec![("chase", Some(0.5)), ("amex", Some(1.0))]);
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+}
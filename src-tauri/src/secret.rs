@@ -1,4 +1,51 @@
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+use zeroize::Zeroize;
+
+/// A secret value read from the keychain or typed by the user.
+///
+/// The bytes are zeroized when the value is dropped. `Debug` prints
+/// `[REDACTED]`, and there is deliberately no `Display` or `Serialize`, so a
+/// secret cannot end up in a log line, scrape report, or command response
+/// without an explicit [`SecretString::expose_secret`].
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    pub fn new(value: String) -> Self {
+        Self(value)
+    }
+
+    /// The plaintext, for the one place that must use it (typing it into a
+    /// page, writing it to the keychain).
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(value: String) -> Self {
+        Self(value)
+    }
+}
+
+impl std::fmt::Debug for SecretString {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("SecretString([REDACTED])")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
 
 /// Per-domain credential stored as a keychain entry.
 ///
@@ -174,7 +221,7 @@ impl SecretStore {
     }
 
     /// Read the password (Data field) for a domain — triggers biometric on macOS.
-    pub fn get_password(&self, domain: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    pub fn get_password(&self, domain: &str) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
         #[cfg(target_os = "macos")]
         {
             self.get_password_macos(domain)
@@ -221,18 +268,28 @@ impl SecretStore {
     /// on macOS. Callers that need passwords for scrubbing should maintain their
     /// own cache of resolved values (see `scrub_known_secrets` in js_api.rs).
     pub fn all_usernames(&self) -> Result<Vec<String>, Box<dyn Error + Send + Sync>> {
+        Ok(self
+            .get_usernames()?
+            .into_values()
+            .filter(|u| !u.is_empty())
+            .collect())
+    }
+
+    /// Read every indexed domain's username in one pass — no biometric prompt.
+    ///
+    /// The domains index is read once; domains whose username entry is
+    /// missing or unreadable are left out.
+    pub fn get_usernames(&self) -> Result<BTreeMap<String, String>, Box<dyn Error + Send + Sync>> {
         let index = self.read_domains_index()?;
-        let mut values = Vec::new();
-        for entry in &index {
+        let mut usernames = BTreeMap::new();
+        for entry in index {
             if entry.has_username {
                 if let Ok(u) = self.get_username(&entry.domain) {
-                    if !u.is_empty() {
-                        values.push(u);
-                    }
+                    usernames.insert(entry.domain, u);
                 }
             }
         }
-        Ok(values)
+        Ok(usernames)
     }
 
     // ── macOS implementation ────────────────────────────────────────────────
@@ -336,7 +393,10 @@ impl SecretStore {
 
     /// Read kSecValueData for this domain's entry — triggers biometric on macOS.
     #[cfg(target_os = "macos")]
-    fn get_password_macos(&self, domain: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    fn get_password_macos(
+        &self,
+        domain: &str,
+    ) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
         use security_framework::item::{ItemClass, ItemSearchOptions, Limit, SearchResult};
 
         let service = self.service_for_domain(domain);
@@ -349,7 +409,7 @@ impl SecretStore {
 
         for result in results {
             if let SearchResult::Data(data) = result {
-                return Ok(String::from_utf8(data)?);
+                return Ok(SecretString::new(String::from_utf8(data)?));
             }
         }
         Err(format!("no password found for domain '{domain}'").into())
@@ -429,10 +489,13 @@ impl SecretStore {
     }
 
     #[cfg(not(target_os = "macos"))]
-    fn get_password_other(&self, domain: &str) -> Result<String, Box<dyn Error + Send + Sync>> {
+    fn get_password_other(
+        &self,
+        domain: &str,
+    ) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
         let service = self.service_for_domain(domain);
         let entry = keyring::Entry::new(&service, "_pwd")?;
-        Ok(entry.get_password()?)
+        Ok(SecretString::new(entry.get_password()?))
     }
 
     #[cfg(not(target_os = "macos"))]
@@ -489,11 +552,11 @@ impl SecretStore {
         &self,
         domain: &str,
         name: &str,
-    ) -> Result<String, Box<dyn Error + Send + Sync>> {
+    ) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
         let old_service = format!("refreshmint/{}", self.login_name);
         let account = format!("{domain}/{name}");
         let entry = keyring::Entry::new(&old_service, &account)?;
-        Ok(entry.get_password()?)
+        Ok(SecretString::new(entry.get_password()?))
     }
 
     /// Delete a single legacy secret entry.
@@ -526,6 +589,149 @@ impl SecretStore {
     }
 }
 
+/// Secret values read during one scrape session.
+///
+/// Log scrubbing and `page.fill` secret resolution go through this cache so
+/// each keychain item is read at most once per scrape: all usernames are
+/// loaded together on first use, and passwords and legacy values are kept
+/// after their first read. Reading keychain items repeatedly on macOS can
+/// re-prompt for authorization, which made long scrapes unusable.
+///
+/// Cached values are zeroized by [`SecretCache::invalidate`] and when the
+/// cache is dropped at the end of the scrape.
+pub struct SecretCache {
+    store: SecretStore,
+    cached: Mutex<CachedSecrets>,
+}
+
+#[derive(Default)]
+struct CachedSecrets {
+    /// Domain → username, once loaded.
+    usernames: Option<BTreeMap<String, SecretString>>,
+    /// Domain → password, for passwords already read.
+    passwords: BTreeMap<String, SecretString>,
+    /// Legacy `(domain, name)` pairs, once listed.
+    legacy_entries: Option<Vec<(String, String)>>,
+    /// Legacy values already read, keyed by `(domain, name)`.
+    legacy_values: BTreeMap<(String, String), SecretString>,
+}
+
+impl CachedSecrets {
+    /// Every cached value, for scrubbing.
+    fn values(&self) -> Vec<SecretString> {
+        self.usernames
+            .iter()
+            .flat_map(BTreeMap::values)
+            .chain(self.passwords.values())
+            .chain(self.legacy_values.values())
+            .filter(|value| !value.is_empty())
+            .cloned()
+            .collect()
+    }
+}
+
+impl SecretCache {
+    pub fn new(store: SecretStore) -> Self {
+        Self {
+            store,
+            cached: Mutex::new(CachedSecrets::default()),
+        }
+    }
+
+    fn lock(&self) -> MutexGuard<'_, CachedSecrets> {
+        self.cached.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn load_usernames(
+        &self,
+        cached: &mut CachedSecrets,
+    ) -> Result<(), Box<dyn Error + Send + Sync>> {
+        if cached.usernames.is_none() {
+            let usernames = self.store.get_usernames()?;
+            cached.usernames = Some(
+                usernames
+                    .into_iter()
+                    .map(|(domain, username)| (domain, SecretString::new(username)))
+                    .collect(),
+            );
+        }
+        Ok(())
+    }
+
+    /// The username for `domain` — no biometric prompt.
+    pub fn get_username(&self, domain: &str) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
+        let mut cached = self.lock();
+        self.load_usernames(&mut cached)?;
+        cached
+            .usernames
+            .as_ref()
+            .and_then(|usernames| usernames.get(domain))
+            .cloned()
+            .ok_or_else(|| format!("no username stored for domain '{domain}'").into())
+    }
+
+    /// The password for `domain`; only the first read triggers biometric on macOS.
+    pub fn get_password(&self, domain: &str) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
+        let mut cached = self.lock();
+        if let Some(password) = cached.passwords.get(domain) {
+            return Ok(password.clone());
+        }
+        let password = self.store.get_password(domain)?;
+        cached
+            .passwords
+            .insert(domain.to_string(), password.clone());
+        Ok(password)
+    }
+
+    /// Legacy `(domain, name)` pairs, listed once per session.
+    pub fn list_legacy_entries(
+        &self,
+    ) -> Result<Vec<(String, String)>, Box<dyn Error + Send + Sync>> {
+        let mut cached = self.lock();
+        if let Some(entries) = &cached.legacy_entries {
+            return Ok(entries.clone());
+        }
+        let entries = self.store.list_legacy_entries()?;
+        cached.legacy_entries = Some(entries.clone());
+        Ok(entries)
+    }
+
+    /// A legacy secret value; only the first read triggers biometric on macOS.
+    pub fn get_legacy_value(
+        &self,
+        domain: &str,
+        name: &str,
+    ) -> Result<SecretString, Box<dyn Error + Send + Sync>> {
+        let key = (domain.to_string(), name.to_string());
+        let mut cached = self.lock();
+        if let Some(value) = cached.legacy_values.get(&key) {
+            return Ok(value.clone());
+        }
+        let value = self.store.get_legacy_value(domain, name)?;
+        cached.legacy_values.insert(key, value.clone());
+        Ok(value)
+    }
+
+    /// Every known secret value to redact from script-visible text: all
+    /// stored usernames plus the passwords and legacy values read so far.
+    ///
+    /// Passwords that have not been read are not fetched, so scrubbing never
+    /// triggers biometric.
+    pub fn scrub_values(&self) -> Vec<SecretString> {
+        let mut cached = self.lock();
+        if let Err(err) = self.load_usernames(&mut cached) {
+            tracing::warn!("failed to read usernames for scrubbing: {err}");
+        }
+        cached.values()
+    }
+
+    /// Zeroize and forget every cached value, so the next access reads the
+    /// keychain again. Call after credentials change mid-session.
+    pub fn invalidate(&self) {
+        *self.lock() = CachedSecrets::default();
+    }
+}
+
 #[cfg(test)]
 #[allow(clippy::unwrap_used)]
 mod tests {
@@ -568,7 +774,7 @@ mod tests {
         assert_eq!(username, "alice");
 
         let password = store.get_password("example.com").unwrap();
-        assert_eq!(password, "hunter2");
+        assert_eq!(password.expose_secret(), "hunter2");
 
         cleanup(&store);
     }
@@ -642,11 +848,71 @@ mod tests {
         assert_eq!(username, "new_user");
 
         let password = store.get_password("d.com").unwrap();
-        assert_eq!(password, "new_pass");
+        assert_eq!(password.expose_secret(), "new_pass");
 
         let domains = store.list_domains().unwrap();
         assert_eq!(domains.len(), 1);
 
         cleanup(&store);
     }
+
+    #[test]
+    fn cache_reads_once_until_invalidated() {
+        let store = SecretStore::new(test_login());
+        if store.set_credentials("c.com", "user1", "pass1").is_err() {
+            eprintln!("skipping keyring test");
+            return;
+        }
+        let cache = SecretCache::new(SecretStore::new(store.login_name.clone()));
+        let expose = |value: SecretString| value.expose_secret().to_string();
+        assert_eq!(expose(cache.get_username("c.com").unwrap()), "user1");
+        assert_eq!(cache.scrub_values(), vec![secret("user1")]);
+        assert_eq!(expose(cache.get_password("c.com").unwrap()), "pass1");
+
+        store.set_credentials("c.com", "user2", "pass2").unwrap();
+        assert_eq!(expose(cache.get_username("c.com").unwrap()), "user1");
+        assert_eq!(expose(cache.get_password("c.com").unwrap()), "pass1");
+
+        cache.invalidate();
+        assert_eq!(expose(cache.get_username("c.com").unwrap()), "user2");
+        assert_eq!(cache.scrub_values(), vec![secret("user2")]);
+
+        cleanup(&store);
+    }
+
+    fn secret(value: &str) -> SecretString {
+        SecretString::new(value.to_string())
+    }
+
+    #[test]
+    fn cached_values_cover_everything_read() {
+        let cached = CachedSecrets {
+            usernames: Some(BTreeMap::from([
+                ("a.com".to_string(), secret("alice")),
+                ("b.com".to_string(), SecretString::default()),
+            ])),
+            passwords: BTreeMap::from([("a.com".to_string(), secret("hunter2"))]),
+            legacy_entries: Some(vec![("a.com".to_string(), "pin".to_string())]),
+            legacy_values: BTreeMap::from([(
+                ("a.com".to_string(), "pin".to_string()),
+                secret("1234"),
+            )]),
+        };
+        assert_eq!(
+            cached.values(),
+            vec![secret("alice"), secret("hunter2"), secret("1234")]
+        );
+        assert!(CachedSecrets::default().values().is_empty());
+    }
+
+    #[test]
+    fn secret_string_debug_is_redacted() {
+        let value = secret("hunter2");
+        assert_eq!(format!("{value:?}"), "SecretString([REDACTED])");
+        assert_eq!(
+            format!("{:?}", Some(&value)),
+            "Some(SecretString([REDACTED]))"
+        );
+        assert_eq!(value.expose_secret(), "hunter2");
+    }
 }
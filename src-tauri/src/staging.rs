@@ -1,15 +1,147 @@
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::{Path, PathBuf};
+
 pub const STAGING_PREFIX: &str = "Equity:Staging";
 pub const LEGACY_STAGING_PREFIX: &str = "Equity:Unreconciled";
+const STAGING_CONFIG_FILE: &str = "staging.json";
 
-pub fn canonical_staging_account(suffix: &str) -> String {
-    let trimmed = suffix.trim_matches(':').trim();
-    if trimmed.is_empty() {
-        STAGING_PREFIX.to_string()
+/// Ledger-wide counterpart settings, stored in `staging.json` at the ledger
+/// root.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct StagingConfig {
+    /// Parent account for extracted entries' counterpart postings; the login
+    /// and label are appended. Defaults to `Equity:Staging`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub counterpart_prefix: Option<String>,
+}
+
+fn staging_config_path(ledger_dir: &Path) -> PathBuf {
+    ledger_dir.join(STAGING_CONFIG_FILE)
+}
+
+pub fn get_staging_config(ledger_dir: &Path) -> io::Result<StagingConfig> {
+    Ok(
+        crate::bookkeeping::read_optional_json(&staging_config_path(ledger_dir))?
+            .unwrap_or_default(),
+    )
+}
+
+/// Save the ledger's counterpart settings after checking the prefix against
+/// the chart of accounts.
+pub fn save_staging_config(ledger_dir: &Path, config: StagingConfig) -> io::Result<StagingConfig> {
+    let config = StagingConfig {
+        counterpart_prefix: normalize_account_input(config.counterpart_prefix),
+    };
+    if let Some(prefix) = &config.counterpart_prefix {
+        validate_counterpart_account(ledger_dir, prefix)?;
+    }
+    crate::bookkeeping::write_json(&staging_config_path(ledger_dir), &config)?;
+    Ok(config)
+}
+
+/// Trimmed account name, or `None` when blank.
+pub fn normalize_account_input(account: Option<String>) -> Option<String> {
+    account
+        .map(|account| account.trim().trim_matches(':').to_string())
+        .filter(|account| !account.is_empty())
+}
+
+/// The counterpart account for a login account's extracted single-sided
+/// entries: its own `counterpartAccount`, else the ledger's prefix (or
+/// `Equity:Staging`) followed by `<login>:<label>`.
+pub fn login_account_counterpart(ledger_dir: &Path, login_name: &str, label: &str) -> String {
+    let config = crate::login_config::read_login_config(ledger_dir, login_name);
+    if let Some(account) = config
+        .accounts
+        .get(label)
+        .and_then(|account| account.counterpart_account.clone())
+    {
+        return account;
+    }
+    ledger_counterpart_account(ledger_dir, &format!("{login_name}:{label}"))
+}
+
+/// `suffix` under the ledger's counterpart prefix.
+pub fn ledger_counterpart_account(ledger_dir: &Path, suffix: &str) -> String {
+    let prefix = get_staging_config(ledger_dir)
+        .ok()
+        .and_then(|config| config.counterpart_prefix);
+    match prefix {
+        Some(prefix) => join_account(&prefix, suffix),
+        None => canonical_staging_account(suffix),
+    }
+}
+
+/// Whether `account` is the placeholder counterpart of an extracted entry:
+/// a staging account or the configured `counterpart`.
+pub fn is_counterpart_account(account: &str, counterpart: &str) -> bool {
+    is_staging_account(account) || account == counterpart
+}
+
+/// Reject counterpart accounts that aren't valid hledger names, that would
+/// move real balances (Assets or Liabilities), or whose top-level account is
+/// missing from the general journal's chart of accounts.
+pub fn validate_counterpart_account(ledger_dir: &Path, account: &str) -> io::Result<()> {
+    check_counterpart_name(account)
+        .map_err(|message| io::Error::new(io::ErrorKind::InvalidInput, message))?;
+    let journal_path = ledger_dir.join("general.journal");
+    if !journal_path.exists() {
+        return Ok(());
+    }
+    let chart = crate::report::list_accounts(&journal_path)?;
+    let top = account.split(':').next().unwrap_or(account);
+    if !chart.is_empty()
+        && !chart
+            .iter()
+            .any(|known| known.split(':').next() == Some(top))
+    {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "counterpart account '{account}': no '{top}' accounts in the chart of accounts"
+            ),
+        ));
+    }
+    Ok(())
+}
+
+fn check_counterpart_name(account: &str) -> Result<(), String> {
+    if account.contains("  ") || account.contains('\t') || account.contains('\n') {
+        return Err(format!(
+            "counterpart account '{account}' must not contain tabs, newlines, or double spaces"
+        ));
+    }
+    if account.split(':').any(|part| part.trim().is_empty()) {
+        return Err(format!(
+            "counterpart account '{account}' has an empty component"
+        ));
+    }
+    let top = account.split(':').next().unwrap_or(account);
+    if ["Assets", "Liabilities"].contains(&top) {
+        return Err(format!(
+            "counterpart account '{account}' is a balance account; use an Equity, Expenses, or Income account"
+        ));
+    }
+    Ok(())
+}
+
+fn join_account(prefix: &str, suffix: &str) -> String {
+    let suffix = suffix.trim_matches(':').trim();
+    if suffix.is_empty() {
+        prefix.to_string()
     } else {
-        format!("{STAGING_PREFIX}:{trimmed}")
+        format!("{prefix}:{suffix}")
     }
 }
 
+pub fn canonical_staging_account(suffix: &str) -> String {
+    join_account(STAGING_PREFIX, suffix)
+}
+
 pub fn canonicalize_account_name(account: &str) -> String {
     if let Some(rest) = account.strip_prefix(LEGACY_STAGING_PREFIX) {
         format!("{STAGING_PREFIX}{rest}")
@@ -43,4 +175,22 @@ mod tests {
         assert!(is_staging_account("Equity:Unreconciled:bank"));
         assert!(!is_staging_account("Equity:OpeningBalances"));
     }
+
+    #[test]
+    fn counterpart_names_must_be_valid_non_balance_accounts() {
+        assert!(check_counterpart_name("Expenses:Unknown").is_ok());
+        assert!(check_counterpart_name("Equity:Clearing:bank").is_ok());
+        assert!(check_counterpart_name("Assets:Checking").is_err());
+        assert!(check_counterpart_name("Expenses::Unknown").is_err());
+        assert!(check_counterpart_name("Expenses:Un  known").is_err());
+        assert!(is_counterpart_account(
+            "Expenses:Unknown",
+            "Expenses:Unknown"
+        ));
+        assert!(is_counterpart_account(
+            "Equity:Staging:bank:checking",
+            "Expenses:Unknown"
+        ));
+        assert!(!is_counterpart_account("Expenses:Food", "Expenses:Unknown"));
+    }
 }
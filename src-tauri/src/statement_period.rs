@@ -0,0 +1,334 @@
+//! Infer the period a document covers from its contents.
+//!
+//! Drivers normally pass `coverageEndDate` to `saveResource`. When one does
+//! not, finalization reads the document instead of stamping it with the scrape
+//! date, so incremental scrape windows and retention start from the real
+//! statement end:
+//!
+//! - OFX/QFX: the transaction list's `<DTSTART>`/`<DTEND>`, falling back to
+//!   the earliest and latest `<DTPOSTED>`.
+//! - CSV: the earliest and latest cells that hold nothing but a date.
+//! - PDF: a stated range such as `01/01/2025 - 01/31/2025` or
+//!   `January 1, 2025 through January 31, 2025`, else a date labeled as the
+//!   closing or statement date. Other dates in a statement, like the payment
+//!   due date, say nothing reliable about its period, so they are ignored.
+//!
+//! Numeric dates are read month first unless the first number cannot be a
+//! month.
+
+use chrono::{Datelike, NaiveDate};
+use regex::Regex;
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// A stated range longer than this is not a statement period.
+const MAX_PERIOD_DAYS: i64 = 400;
+
+/// Dates outside these years are taken for account numbers or amounts.
+const MIN_YEAR: i32 = 1970;
+const MAX_YEAR: i32 = 2100;
+
+const MONTHS: &str = "jan(?:uary)?|feb(?:ruary)?|mar(?:ch)?|apr(?:il)?|may|june?|july?\
+    |aug(?:ust)?|sep(?:t(?:ember)?)?|oct(?:ober)?|nov(?:ember)?|dec(?:ember)?";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DocumentPeriod {
+    pub start: Option<NaiveDate>,
+    pub end: NaiveDate,
+}
+
+/// The period of the document at `path`, if its contents state one.
+/// `filename` and `mime_type` decide how the document is read.
+pub fn infer_document_period(
+    path: &Path,
+    filename: &str,
+    mime_type: &str,
+) -> Option<DocumentPeriod> {
+    let extension = filename
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    let result = if extension == "ofx" || extension == "qfx" || mime_type.contains("ofx") {
+        std::fs::read(path).map(|bytes| ofx_period(&String::from_utf8_lossy(&bytes)))
+    } else if extension == "csv" || mime_type == "text/csv" {
+        std::fs::read(path).map(|bytes| csv_period(&bytes))
+    } else if extension == "pdf" || mime_type == "application/pdf" {
+        Ok(pdf_text(path).and_then(|text| text_period(&text)))
+    } else {
+        return None;
+    };
+    match result {
+        Ok(period) => period,
+        Err(err) => {
+            tracing::warn!("cannot read {} to infer its period: {err}", path.display());
+            None
+        }
+    }
+}
+
+fn ofx_period(text: &str) -> Option<DocumentPeriod> {
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    let tag = TAG.get_or_init(|| {
+        Regex::new(r"(?i)<(DTSTART|DTEND|DTPOSTED)>\s*(\d{4})-?(\d{2})-?(\d{2})")
+            .unwrap_or_else(|err| panic!("invalid OFX date pattern: {err}"))
+    });
+    let mut start = None;
+    let mut end = None;
+    let mut posted = Vec::new();
+    for captures in tag.captures_iter(text) {
+        let date = NaiveDate::from_ymd_opt(
+            captures[2].parse().ok()?,
+            captures[3].parse().ok()?,
+            captures[4].parse().ok()?,
+        );
+        let Some(date) = date else {
+            continue;
+        };
+        match captures[1].to_ascii_uppercase().as_str() {
+            "DTSTART" => start = start.or(Some(date)),
+            "DTEND" => end = end.or(Some(date)),
+            _ => posted.push(date),
+        }
+    }
+    match end {
+        Some(end) => Some(DocumentPeriod { start, end }),
+        None => Some(DocumentPeriod {
+            start: posted.iter().min().copied(),
+            end: posted.iter().max().copied()?,
+        }),
+    }
+}
+
+fn csv_period(bytes: &[u8]) -> Option<DocumentPeriod> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(bytes);
+    let mut dates = Vec::new();
+    for record in reader.records().flatten() {
+        dates.extend(record.iter().filter_map(parse_date));
+    }
+    Some(DocumentPeriod {
+        start: dates.iter().min().copied(),
+        end: dates.iter().max().copied()?,
+    })
+}
+
+fn pdf_text(path: &Path) -> Option<String> {
+    let document = match lopdf::Document::load(path) {
+        Ok(document) => document,
+        Err(err) => {
+            tracing::warn!("cannot open PDF {}: {err}", path.display());
+            return None;
+        }
+    };
+    let pages: Vec<u32> = document.get_pages().keys().copied().collect();
+    document.extract_text(&pages).ok()
+}
+
+/// The stated period in a statement's text.
+fn text_period(text: &str) -> Option<DocumentPeriod> {
+    static RANGE: OnceLock<Regex> = OnceLock::new();
+    static LABELED: OnceLock<Regex> = OnceLock::new();
+    let date = date_pattern();
+    let range = RANGE.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?i)({date})\s*(?:-|–|—|to|through|thru)\s*({date})"
+        ))
+        .unwrap_or_else(|err| panic!("invalid date range pattern: {err}"))
+    });
+    let labeled = LABELED.get_or_init(|| {
+        Regex::new(&format!(
+            r"(?i)(?:closing|statement|period ending|ending)\s*date\s*:?\s*({date})"
+        ))
+        .unwrap_or_else(|err| panic!("invalid labeled date pattern: {err}"))
+    });
+
+    for captures in range.captures_iter(text) {
+        let (Some(start), Some(end)) = (parse_date(&captures[1]), parse_date(&captures[2])) else {
+            continue;
+        };
+        if start <= end && (end - start).num_days() <= MAX_PERIOD_DAYS {
+            return Some(DocumentPeriod {
+                start: Some(start),
+                end,
+            });
+        }
+    }
+    labeled
+        .captures_iter(text)
+        .find_map(|captures| parse_date(&captures[1]))
+        .map(|end| DocumentPeriod { start: None, end })
+}
+
+/// Unanchored pattern for one date in any format [`parse_date`] reads.
+fn date_pattern() -> String {
+    [
+        r"\d{4}[-/.]\d{1,2}[-/.]\d{1,2}".to_string(),
+        r"\d{1,2}[-/.]\d{1,2}[-/.](?:\d{4}|\d{2})\b".to_string(),
+        format!(r"(?:{MONTHS})\.?\s+\d{{1,2}},?\s+\d{{4}}"),
+        format!(r"\d{{1,2}}\s+(?:{MONTHS})\.?,?\s+\d{{4}}"),
+    ]
+    .join("|")
+}
+
+/// Read `text` as a date if it holds nothing else, ignoring a time after it.
+fn parse_date(text: &str) -> Option<NaiveDate> {
+    let text = text.trim();
+    let (first, rest) = text.split_at(text.find(['T', ' ']).unwrap_or(text.len()));
+    let date = match parse_numeric_date(first) {
+        Some(date)
+            if rest
+                .trim_start_matches('T')
+                .trim()
+                .starts_with(|c: char| c.is_ascii_digit())
+                || rest.is_empty() =>
+        {
+            Some(date)
+        }
+        Some(_) => None,
+        None => parse_named_month_date(text),
+    }?;
+    (MIN_YEAR..=MAX_YEAR).contains(&date.year()).then_some(date)
+}
+
+/// `Jan 5, 2025`, `January 5 2025`, or `5 Jan. 2025`.
+fn parse_named_month_date(text: &str) -> Option<NaiveDate> {
+    let words: Vec<String> = text
+        .split_whitespace()
+        .map(|word| word.trim_end_matches(['.', ',']).to_ascii_lowercase())
+        .map(|word| {
+            if word == "sept" {
+                "sep".to_string()
+            } else {
+                word
+            }
+        })
+        .collect();
+    if words.len() != 3 {
+        return None;
+    }
+    let normalized = words.join(" ");
+    ["%b %d %Y", "%B %d %Y", "%d %b %Y", "%d %B %Y"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(&normalized, format).ok())
+}
+
+fn parse_numeric_date(text: &str) -> Option<NaiveDate> {
+    let parts: Vec<&str> = text.split(['-', '/', '.']).collect();
+    let [a, b, c] = parts.as_slice() else {
+        return None;
+    };
+    if !parts
+        .iter()
+        .all(|part| !part.is_empty() && part.chars().all(|ch| ch.is_ascii_digit()))
+    {
+        return None;
+    }
+    let number = |part: &str| part.parse::<u32>().ok();
+    if a.len() == 4 {
+        return NaiveDate::from_ymd_opt(a.parse().ok()?, number(b)?, number(c)?);
+    }
+    let year: i32 = match c.len() {
+        4 => c.parse().ok()?,
+        2 => 2000 + c.parse::<i32>().ok()?,
+        _ => return None,
+    };
+    let (first, second) = (number(a)?, number(b)?);
+    let (month, day) = if first > 12 {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    NaiveDate::from_ymd_opt(year, month, day)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn dates_are_read_in_common_formats() {
+        assert_eq!(parse_date("2025-01-31"), Some(date("2025-01-31")));
+        assert_eq!(
+            parse_date(" 2025-01-31T08:00:00Z "),
+            Some(date("2025-01-31"))
+        );
+        assert_eq!(parse_date("01/31/2025"), Some(date("2025-01-31")));
+        assert_eq!(parse_date("31.01.2025"), Some(date("2025-01-31")));
+        assert_eq!(parse_date("1/5/25"), Some(date("2025-01-05")));
+        assert_eq!(parse_date("Jan. 31, 2025"), Some(date("2025-01-31")));
+        assert_eq!(parse_date("September 3 2025"), Some(date("2025-09-03")));
+        assert_eq!(parse_date("3 Sept 2025"), Some(date("2025-09-03")));
+        assert_eq!(parse_date("01/31/2025 Grocer"), None);
+        assert_eq!(parse_date("12.50"), None);
+        assert_eq!(parse_date("4111-22-33"), None);
+        assert_eq!(parse_date("1234/01/01"), None);
+    }
+
+    #[test]
+    fn ofx_prefers_the_transaction_list_range() {
+        let ofx = "<OFX><DTSERVER>20250202\n<BANKTRANLIST>\n<DTSTART>20250101120000\n\
+                   <DTEND>2025-01-31\n<STMTTRN><DTPOSTED>20250115</STMTTRN>";
+        assert_eq!(
+            ofx_period(ofx),
+            Some(DocumentPeriod {
+                start: Some(date("2025-01-01")),
+                end: date("2025-01-31"),
+            })
+        );
+        let posted_only = "<STMTTRN><DTPOSTED>20250115\n<STMTTRN><DTPOSTED>20250103";
+        assert_eq!(
+            ofx_period(posted_only),
+            Some(DocumentPeriod {
+                start: Some(date("2025-01-03")),
+                end: date("2025-01-15"),
+            })
+        );
+        assert_eq!(ofx_period("<OFX></OFX>"), None);
+    }
+
+    #[test]
+    fn csv_spans_its_date_cells() {
+        let csv = "Date,Description,Amount\n01/20/2025,Coffee 01/01/2024,-4.00\n\
+                   01/03/2025,Payroll,1000\n,Pending,3\n";
+        assert_eq!(
+            csv_period(csv.as_bytes()),
+            Some(DocumentPeriod {
+                start: Some(date("2025-01-03")),
+                end: date("2025-01-20"),
+            })
+        );
+        assert_eq!(csv_period(b"a,b\n1,2\n"), None);
+    }
+
+    #[test]
+    fn statement_text_states_its_period() {
+        let text = "Payment Due Date: 03/25/2025\nStatement Period: 02/01/2025 - 02/28/2025";
+        assert_eq!(
+            text_period(text),
+            Some(DocumentPeriod {
+                start: Some(date("2025-02-01")),
+                end: date("2025-02-28"),
+            })
+        );
+        let named = "Account activity January 1, 2025 through January 31, 2025";
+        assert_eq!(text_period(named).unwrap().end, date("2025-01-31"));
+        let labeled = "Closing Date: Feb 14, 2025\nPayment due 03/11/2025";
+        assert_eq!(
+            text_period(labeled),
+            Some(DocumentPeriod {
+                start: None,
+                end: date("2025-02-14"),
+            })
+        );
+        assert_eq!(text_period("Payment due 03/11/2025"), None);
+        // A range running backwards or over a year is not a period.
+        assert_eq!(text_period("Member since 01/01/2010 - 01/01/2025"), None);
+    }
+}
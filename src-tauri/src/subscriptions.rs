@@ -0,0 +1,280 @@
+//! Recurring charge detection and subscription price-change reports.
+//!
+//! Charges in a login account journal are grouped by normalized payee and
+//! commodity. A group is recurring when it has at least three charges and
+//! most gaps between them match a weekly, monthly, quarterly, or yearly
+//! cadence. A price change is reported when the latest charge is higher than
+//! the steady amount charged before it.
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry};
+use crate::login_config;
+
+/// Fewest charges that can form a recurring series.
+const MIN_OCCURRENCES: usize = 3;
+/// Earlier charges that must agree on the amount before a change counts.
+const STEADY_CHARGES: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "lowercase")]
+#[ts(export, export_to = "index.ts")]
+pub enum RecurrencePeriod {
+    Weekly,
+    Monthly,
+    Quarterly,
+    Yearly,
+}
+
+impl RecurrencePeriod {
+    fn from_gap(days: i64) -> Option<Self> {
+        match days {
+            5..=9 => Some(Self::Weekly),
+            25..=35 => Some(Self::Monthly),
+            80..=100 => Some(Self::Quarterly),
+            350..=380 => Some(Self::Yearly),
+            _ => None,
+        }
+    }
+
+    pub fn per_year(self) -> f64 {
+        match self {
+            Self::Weekly => 52.0,
+            Self::Monthly => 12.0,
+            Self::Quarterly => 4.0,
+            Self::Yearly => 1.0,
+        }
+    }
+}
+
+/// One charge of a recurring series. `amount` is positive.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringCharge {
+    pub entry_id: String,
+    pub date: String,
+    pub description: String,
+    pub amount: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecurringSeries {
+    pub payee: String,
+    pub commodity: String,
+    pub period: RecurrencePeriod,
+    /// Charges in date order.
+    pub charges: Vec<RecurringCharge>,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SubscriptionChange {
+    pub login_name: String,
+    pub label: String,
+    pub payee: String,
+    pub description: String,
+    pub commodity: String,
+    pub period: RecurrencePeriod,
+    pub previous_amount: f64,
+    pub previous_date: String,
+    pub current_amount: f64,
+    pub current_date: String,
+    pub entry_id: String,
+    pub annualized_delta: f64,
+    pub message: String,
+}
+
+/// Find recurring charge series in one account's entries.
+pub fn detect_recurring(entries: &[AccountEntry]) -> Vec<RecurringSeries> {
+    let mut groups: BTreeMap<(String, String), Vec<RecurringCharge>> = BTreeMap::new();
+    for entry in entries {
+        let Some(amount) = entry.postings.first().and_then(|p| p.amount.as_ref()) else {
+            continue;
+        };
+        let Ok(quantity) = amount.quantity.trim().parse::<f64>() else {
+            continue;
+        };
+        let payee = crate::alerts::payee_key(&entry.description);
+        if quantity >= 0.0 || payee.is_empty() {
+            continue;
+        }
+        groups
+            .entry((payee, amount.commodity.clone()))
+            .or_default()
+            .push(RecurringCharge {
+                entry_id: entry.id.clone(),
+                date: entry.date.clone(),
+                description: entry.description.clone(),
+                amount: -quantity,
+            });
+    }
+
+    groups
+        .into_iter()
+        .filter_map(|((payee, commodity), mut charges)| {
+            charges.sort_by(|a, b| a.date.cmp(&b.date));
+            let period = series_period(&charges)?;
+            Some(RecurringSeries {
+                payee,
+                commodity,
+                period,
+                charges,
+            })
+        })
+        .collect()
+}
+
+/// The cadence shared by at least two thirds of the gaps between charges.
+fn series_period(charges: &[RecurringCharge]) -> Option<RecurrencePeriod> {
+    if charges.len() < MIN_OCCURRENCES {
+        return None;
+    }
+    let dates: Vec<chrono::NaiveDate> = charges
+        .iter()
+        .map(|charge| chrono::NaiveDate::parse_from_str(&charge.date, "%Y-%m-%d").ok())
+        .collect::<Option<_>>()?;
+    let periods: Vec<Option<RecurrencePeriod>> = dates
+        .windows(2)
+        .map(|pair| RecurrencePeriod::from_gap((pair[1] - pair[0]).num_days()))
+        .collect();
+    let (period, count) = [
+        RecurrencePeriod::Weekly,
+        RecurrencePeriod::Monthly,
+        RecurrencePeriod::Quarterly,
+        RecurrencePeriod::Yearly,
+    ]
+    .into_iter()
+    .map(|period| {
+        let count = periods.iter().filter(|p| **p == Some(period)).count();
+        (period, count)
+    })
+    .max_by_key(|(_, count)| *count)?;
+    (count * 3 >= periods.len() * 2).then_some(period)
+}
+
+/// The price change at the end of `series`, if the latest charge is higher
+/// than the steady amount charged before it.
+fn price_increase(series: &RecurringSeries) -> Option<(&RecurringCharge, &RecurringCharge)> {
+    let (current, earlier) = series.charges.split_last()?;
+    let previous = earlier.last()?;
+    let steady = earlier
+        .iter()
+        .rev()
+        .take(STEADY_CHARGES)
+        .all(|charge| (charge.amount - previous.amount).abs() < 0.005);
+    (earlier.len() >= STEADY_CHARGES && steady && current.amount - previous.amount >= 0.005)
+        .then_some((previous, current))
+}
+
+/// Recurring charges whose latest amount increased, across every login
+/// account, largest annualized increase first.
+pub fn report_subscription_changes(ledger_dir: &Path) -> io::Result<Vec<SubscriptionChange>> {
+    let mut changes = Vec::new();
+    for login in login_config::list_logins(ledger_dir)? {
+        let config = login_config::read_login_config(ledger_dir, &login);
+        for label in config.accounts.keys() {
+            let journal_path =
+                account_journal::login_account_journal_path(ledger_dir, &login, label);
+            let entries = account_journal::read_journal_at_path(&journal_path)?;
+            for series in detect_recurring(&entries) {
+                let Some((previous, current)) = price_increase(&series) else {
+                    continue;
+                };
+                let annualized_delta =
+                    (current.amount - previous.amount) * series.period.per_year();
+                changes.push(SubscriptionChange {
+                    login_name: login.clone(),
+                    label: label.clone(),
+                    payee: series.payee.clone(),
+                    description: current.description.clone(),
+                    commodity: series.commodity.clone(),
+                    period: series.period,
+                    previous_amount: previous.amount,
+                    previous_date: previous.date.clone(),
+                    current_amount: current.amount,
+                    current_date: current.date.clone(),
+                    entry_id: current.entry_id.clone(),
+                    annualized_delta,
+                    message: format!(
+                        "{} went from {:.2} to {:.2} ({:+.2} {} per year)",
+                        current.description,
+                        previous.amount,
+                        current.amount,
+                        annualized_delta,
+                        series.commodity
+                    ),
+                });
+            }
+        }
+    }
+    changes.sort_by(|a, b| b.annualized_delta.total_cmp(&a.annualized_delta));
+    Ok(changes)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn entry(date: &str, description: &str, quantity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![],
+            vec![EntryPosting {
+                account: "Liabilities:Card".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: "USD".to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    #[test]
+    fn detects_monthly_series_and_price_increase() {
+        let entries = vec![
+            entry("2025-01-15", "NETFLIX.COM 866-579", "-15.49"),
+            entry("2025-02-15", "NETFLIX.COM 866-580", "-15.49"),
+            entry("2025-02-20", "CORNER CAFE", "-4.50"),
+            entry("2025-03-15", "NETFLIX.COM 866-581", "-15.49"),
+            entry("2025-04-15", "NETFLIX.COM 866-582", "-17.99"),
+            entry("2025-04-16", "PAYROLL", "2000.00"),
+        ];
+        let series = detect_recurring(&entries);
+        assert_eq!(series.len(), 1);
+        assert_eq!(series[0].payee, "NETFLIX COM");
+        assert_eq!(series[0].period, RecurrencePeriod::Monthly);
+
+        let (previous, current) = price_increase(&series[0]).unwrap();
+        assert_eq!(previous.amount, 15.49);
+        assert_eq!(current.amount, 17.99);
+        assert_eq!(current.date, "2025-04-15");
+    }
+
+    #[test]
+    fn irregular_or_unsteady_charges_are_not_flagged() {
+        let irregular = vec![
+            entry("2025-01-03", "HARDWARE STORE", "-20.00"),
+            entry("2025-01-05", "HARDWARE STORE", "-20.00"),
+            entry("2025-03-20", "HARDWARE STORE", "-25.00"),
+        ];
+        assert!(detect_recurring(&irregular).is_empty());
+
+        let unsteady = vec![
+            entry("2025-01-01", "POWER CO", "-80.00"),
+            entry("2025-02-01", "POWER CO", "-95.00"),
+            entry("2025-03-01", "POWER CO", "-110.00"),
+        ];
+        let series = detect_recurring(&unsteady);
+        assert_eq!(series.len(), 1);
+        assert!(price_increase(&series[0]).is_none());
+    }
+}
@@ -0,0 +1,415 @@
+//! Suspicious activity heuristics across login account journals.
+//!
+//! Entries dated on or after `since` are compared with the same account's
+//! earlier entries. Each heuristic that fires adds a flag and a weight to the
+//! entry's score; the report lists flagged entries with the highest score
+//! first so the most unusual activity is reviewed first after a scrape.
+
+use serde::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::io;
+use std::path::Path;
+
+use crate::account_journal::{self, AccountEntry};
+use crate::login_config;
+
+/// Earlier entries an account needs before pattern-based heuristics apply.
+const MIN_HISTORY: usize = 10;
+/// Largest whole-dollar outflow treated as a possible card test charge.
+const TEST_CHARGE_MAX: f64 = 5.0;
+/// Fewest charges in one day that can count as a velocity spike.
+const VELOCITY_MIN_CHARGES: usize = 4;
+/// Tag keys that carry a merchant category.
+const CATEGORY_TAG_KEYS: &[&str] = &["category", "mcc", "merchantCategory"];
+/// Document metadata keys that describe where a transaction happened.
+const LOCATION_METADATA_KEYS: &[&str] = &["country", "location", "currency"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "kebab-case")]
+#[ts(export, export_to = "index.ts")]
+pub enum SuspicionKind {
+    /// A merchant category never seen before in the account.
+    UnusualCategory,
+    /// A commodity other than the account's usual one.
+    UnusualCurrency,
+    /// Document metadata (country, location, currency) never seen before.
+    UnusualLocation,
+    /// A small round-dollar charge, often used to test a stolen card.
+    TestCharge,
+    /// One of many charges on a day with far more charges than usual.
+    VelocitySpike,
+}
+
+impl SuspicionKind {
+    fn weight(self) -> u32 {
+        match self {
+            SuspicionKind::UnusualCurrency | SuspicionKind::UnusualLocation => 3,
+            SuspicionKind::UnusualCategory
+            | SuspicionKind::TestCharge
+            | SuspicionKind::VelocitySpike => 2,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SuspicionFlag {
+    pub kind: SuspicionKind,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SuspiciousEntry {
+    pub login_name: String,
+    pub label: String,
+    pub entry_id: String,
+    pub date: String,
+    pub description: String,
+    pub amount: Option<String>,
+    pub score: u32,
+    pub flags: Vec<SuspicionFlag>,
+}
+
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct SuspiciousActivityReport {
+    pub since: String,
+    pub entries: Vec<SuspiciousEntry>,
+}
+
+/// Flag entries dated on or after `since` (`YYYY-MM-DD`) in every login
+/// account, ranked by score.
+pub fn suspicious_activity_report(
+    ledger_dir: &Path,
+    since: &str,
+) -> io::Result<SuspiciousActivityReport> {
+    let mut flagged = Vec::new();
+    for login in login_config::list_logins(ledger_dir)? {
+        let config = login_config::read_login_config(ledger_dir, &login);
+        for label in config.accounts.keys() {
+            let journal_path =
+                account_journal::login_account_journal_path(ledger_dir, &login, label);
+            let entries = account_journal::read_journal_at_path(&journal_path)?;
+            if entries.is_empty() {
+                continue;
+            }
+            let documents_dir =
+                login_config::login_account_documents_dir(ledger_dir, &login, label);
+            let locations = entry_locations(&documents_dir, &entries);
+            for (index, flags) in analyze_account(&entries, &locations, since) {
+                let entry = &entries[index];
+                flagged.push(SuspiciousEntry {
+                    login_name: login.clone(),
+                    label: label.clone(),
+                    entry_id: entry.id.clone(),
+                    date: entry.date.clone(),
+                    description: entry.description.clone(),
+                    amount: entry_amount_string(entry),
+                    score: flags.iter().map(|flag| flag.kind.weight()).sum(),
+                    flags,
+                });
+            }
+        }
+    }
+    flagged.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| b.date.cmp(&a.date)));
+    Ok(SuspiciousActivityReport {
+        since: since.to_string(),
+        entries: flagged,
+    })
+}
+
+/// Run every heuristic on one account. `locations[i]` holds the location
+/// metadata of `entries[i]`. Returns `(entry index, flags)` for each entry
+/// on or after `since` with at least one flag.
+fn analyze_account(
+    entries: &[AccountEntry],
+    locations: &[Vec<(String, String)>],
+    since: &str,
+) -> Vec<(usize, Vec<SuspicionFlag>)> {
+    let (baseline, recent): (Vec<usize>, Vec<usize>) =
+        (0..entries.len()).partition(|&i| entries[i].date.as_str() < since);
+    let has_history = baseline.len() >= MIN_HISTORY;
+
+    let mut categories: BTreeSet<(String, String)> = BTreeSet::new();
+    let mut commodities: HashMap<String, usize> = HashMap::new();
+    let mut known_locations: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+    let mut baseline_charge_days: HashMap<&str, usize> = HashMap::new();
+    for &i in &baseline {
+        let entry = &entries[i];
+        categories.extend(entry_categories(entry));
+        if let Some(commodity) = entry_commodity(entry) {
+            *commodities.entry(commodity.to_string()).or_default() += 1;
+        }
+        for (key, value) in &locations[i] {
+            known_locations
+                .entry(key.clone())
+                .or_default()
+                .insert(value.clone());
+        }
+        if is_charge(entry) {
+            *baseline_charge_days.entry(entry.date.as_str()).or_default() += 1;
+        }
+    }
+    let usual_commodity = commodities
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.cmp(&a.0)))
+        .map(|(commodity, _)| commodity);
+
+    let baseline_charges: usize = baseline_charge_days.values().sum();
+    let usual_per_day = if baseline_charge_days.is_empty() {
+        0.0
+    } else {
+        baseline_charges as f64 / baseline_charge_days.len() as f64
+    };
+    let spike_threshold = VELOCITY_MIN_CHARGES.max((usual_per_day * 3.0).ceil() as usize);
+    let mut recent_charge_days: HashMap<&str, usize> = HashMap::new();
+    for &i in &recent {
+        if is_charge(&entries[i]) {
+            *recent_charge_days
+                .entry(entries[i].date.as_str())
+                .or_default() += 1;
+        }
+    }
+
+    let mut out = Vec::new();
+    for &i in &recent {
+        let entry = &entries[i];
+        let mut flags = Vec::new();
+
+        if has_history {
+            for (key, value) in entry_categories(entry) {
+                if !categories.contains(&(key.clone(), value.clone())) {
+                    flags.push(SuspicionFlag {
+                        kind: SuspicionKind::UnusualCategory,
+                        detail: format!("First {key} '{value}' in this account"),
+                    });
+                }
+            }
+            if let (Some(usual), Some(commodity)) = (&usual_commodity, entry_commodity(entry)) {
+                if commodity != usual {
+                    flags.push(SuspicionFlag {
+                        kind: SuspicionKind::UnusualCurrency,
+                        detail: format!("Amount in {commodity}; this account is usually {usual}"),
+                    });
+                }
+            }
+        }
+        for (key, value) in &locations[i] {
+            if known_locations
+                .get(key)
+                .is_some_and(|known| !known.contains(value))
+            {
+                flags.push(SuspicionFlag {
+                    kind: SuspicionKind::UnusualLocation,
+                    detail: format!("Document {key} '{value}' not seen before"),
+                });
+            }
+        }
+        if let Some(amount) = entry_amount(entry).filter(|_| is_charge(entry)) {
+            let charge = -amount;
+            if charge <= TEST_CHARGE_MAX && charge.fract().abs() < 0.005 {
+                flags.push(SuspicionFlag {
+                    kind: SuspicionKind::TestCharge,
+                    detail: format!("Small round-dollar charge of {charge:.2}"),
+                });
+            }
+            let count = recent_charge_days
+                .get(entry.date.as_str())
+                .copied()
+                .unwrap_or(0);
+            if count >= spike_threshold {
+                flags.push(SuspicionFlag {
+                    kind: SuspicionKind::VelocitySpike,
+                    detail: format!(
+                        "{count} charges on {}; usually {usual_per_day:.1} per day",
+                        entry.date
+                    ),
+                });
+            }
+        }
+
+        if !flags.is_empty() {
+            out.push((i, flags));
+        }
+    }
+    out
+}
+
+/// Location metadata for each entry, read from the sidecars of the
+/// documents its evidence points at.
+fn entry_locations(documents_dir: &Path, entries: &[AccountEntry]) -> Vec<Vec<(String, String)>> {
+    let mut cache: HashMap<String, Vec<(String, String)>> = HashMap::new();
+    entries
+        .iter()
+        .map(|entry| {
+            let mut values: BTreeSet<(String, String)> = BTreeSet::new();
+            for evidence in &entry.evidence {
+                let document = evidence.split(['#', ':']).next().unwrap_or(evidence);
+                let metadata = cache
+                    .entry(document.to_string())
+                    .or_insert_with(|| document_location(documents_dir, document));
+                values.extend(metadata.iter().cloned());
+            }
+            values.into_iter().collect()
+        })
+        .collect()
+}
+
+fn document_location(documents_dir: &Path, document: &str) -> Vec<(String, String)> {
+    let sidecar_path = documents_dir.join(format!("{document}-info.json"));
+    let Ok(text) = std::fs::read_to_string(sidecar_path) else {
+        return Vec::new();
+    };
+    let Ok(info) = serde_json::from_str::<crate::scrape::DocumentInfo>(&text) else {
+        return Vec::new();
+    };
+    LOCATION_METADATA_KEYS
+        .iter()
+        .filter_map(|key| {
+            let value = match info.metadata.get(*key)? {
+                serde_json::Value::String(value) => value.trim().to_string(),
+                value => value.to_string(),
+            };
+            (!value.is_empty()).then(|| (key.to_string(), value))
+        })
+        .collect()
+}
+
+fn entry_categories(entry: &AccountEntry) -> Vec<(String, String)> {
+    entry
+        .tags
+        .iter()
+        .filter(|(key, value)| CATEGORY_TAG_KEYS.contains(&key.as_str()) && !value.is_empty())
+        .map(|(key, value)| (key.clone(), value.trim().to_ascii_lowercase()))
+        .collect()
+}
+
+fn is_charge(entry: &AccountEntry) -> bool {
+    entry_amount(entry).is_some_and(|amount| amount < 0.0)
+}
+
+fn entry_amount(entry: &AccountEntry) -> Option<f64> {
+    entry
+        .postings
+        .first()?
+        .amount
+        .as_ref()?
+        .quantity
+        .trim()
+        .parse()
+        .ok()
+}
+
+fn entry_commodity(entry: &AccountEntry) -> Option<&str> {
+    Some(entry.postings.first()?.amount.as_ref()?.commodity.as_str())
+}
+
+fn entry_amount_string(entry: &AccountEntry) -> Option<String> {
+    let amount = entry.postings.first()?.amount.as_ref()?;
+    Some(format!("{} {}", amount.quantity, amount.commodity))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use crate::account_journal::{EntryPosting, EntryStatus, SimpleAmount};
+
+    fn entry(date: &str, description: &str, quantity: &str, commodity: &str) -> AccountEntry {
+        AccountEntry::new(
+            date.to_string(),
+            EntryStatus::Cleared,
+            description.to_string(),
+            vec![format!("{date}.csv:1:1")],
+            vec![EntryPosting {
+                account: "Assets:Checking".to_string(),
+                amount: Some(SimpleAmount {
+                    commodity: commodity.to_string(),
+                    quantity: quantity.to_string(),
+                }),
+            }],
+        )
+    }
+
+    fn with_category(mut entry: AccountEntry, category: &str) -> AccountEntry {
+        entry
+            .tags
+            .push(("category".to_string(), category.to_string()));
+        entry
+    }
+
+    fn history() -> Vec<AccountEntry> {
+        (1..=12)
+            .map(|day| {
+                with_category(
+                    entry(&format!("2025-01-{day:02}"), "GROCER", "-42.17", "USD"),
+                    "Groceries",
+                )
+            })
+            .collect()
+    }
+
+    fn kinds(flags: &[SuspicionFlag]) -> Vec<SuspicionKind> {
+        flags.iter().map(|flag| flag.kind).collect()
+    }
+
+    #[test]
+    fn flags_new_category_currency_and_test_charge() {
+        let mut entries = history();
+        entries.push(with_category(
+            entry("2025-02-03", "GROCER", "-38.10", "USD"),
+            "groceries",
+        ));
+        entries.push(with_category(
+            entry("2025-02-04", "CASINO", "-250.00", "EUR"),
+            "Gambling",
+        ));
+        entries.push(entry("2025-02-05", "ONLINE SVC", "-1.00", "USD"));
+        let locations = vec![Vec::new(); entries.len()];
+
+        let flagged = analyze_account(&entries, &locations, "2025-02-01");
+        assert_eq!(flagged.len(), 2);
+        assert_eq!(flagged[0].0, 13);
+        assert_eq!(
+            kinds(&flagged[0].1),
+            vec![
+                SuspicionKind::UnusualCategory,
+                SuspicionKind::UnusualCurrency
+            ]
+        );
+        assert_eq!(flagged[1].0, 14);
+        assert_eq!(kinds(&flagged[1].1), vec![SuspicionKind::TestCharge]);
+    }
+
+    #[test]
+    fn flags_velocity_spike_and_new_location() {
+        let mut entries = history();
+        for description in ["SHOP A", "SHOP B", "SHOP C", "SHOP D"] {
+            entries.push(entry("2025-02-10", description, "-19.99", "USD"));
+        }
+        entries.push(entry("2025-02-11", "HOTEL", "-120.55", "USD"));
+        let mut locations = vec![vec![("country".to_string(), "US".to_string())]; entries.len()];
+        *locations.last_mut().unwrap() = vec![("country".to_string(), "RO".to_string())];
+
+        let flagged = analyze_account(&entries, &locations, "2025-02-01");
+        assert_eq!(flagged.len(), 5);
+        assert!(flagged[..4]
+            .iter()
+            .all(|(_, flags)| kinds(flags) == vec![SuspicionKind::VelocitySpike]));
+        assert_eq!(kinds(&flagged[4].1), vec![SuspicionKind::UnusualLocation]);
+    }
+
+    #[test]
+    fn pattern_heuristics_need_history() {
+        let entries = vec![
+            with_category(entry("2025-01-01", "GROCER", "-42.17", "USD"), "Groceries"),
+            with_category(entry("2025-02-04", "CASINO", "-250.00", "EUR"), "Gambling"),
+        ];
+        let locations = vec![Vec::new(); entries.len()];
+        assert!(analyze_account(&entries, &locations, "2025-02-01").is_empty());
+    }
+}
@@ -0,0 +1,305 @@
+//! Year-end tax form collection across logins.
+//!
+//! Extensions opt in by declaring a `taxDocuments` entry point in their
+//! manifest. Collected forms are ordinary login-account documents with
+//! `documentType: "tax-form"`; this module groups them by tax year and
+//! compares them against the forms each extension expects to find.
+
+use serde::Serialize;
+use std::io;
+use std::path::Path;
+
+use crate::extract::DocumentWithInfo;
+use crate::scrape::{DocumentInfo, DocumentType};
+
+/// A collected tax form document.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TaxFormDocument {
+    pub label: String,
+    pub filename: String,
+    /// `metadata.formType` from the sidecar, e.g. `1099-INT`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub form_type: Option<String>,
+    pub scraped_at: String,
+}
+
+/// Tax forms collected for one login, with completeness against the
+/// extension's `expectedForms`.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct LoginTaxDocuments {
+    pub login_name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub extension: Option<String>,
+    /// Whether the login's extension declares a `taxDocuments` entry point.
+    pub supported: bool,
+    pub expected_forms: Vec<String>,
+    pub collected: Vec<TaxFormDocument>,
+    pub missing_forms: Vec<String>,
+}
+
+/// Consolidated tax form listing for one tax year.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TaxDocumentsReport {
+    pub year: i32,
+    pub logins: Vec<LoginTaxDocuments>,
+    /// True when no supported login is missing an expected form.
+    pub complete: bool,
+}
+
+/// Per-login failure from a `collect_tax_documents` run.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export_to = "index.ts")]
+pub struct TaxCollectionError {
+    pub login_name: String,
+    pub error: String,
+}
+
+/// Outcome of running the tax documents entry point for every login.
+#[derive(Debug, Clone, Serialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TaxCollectionOutcome {
+    pub attempted: Vec<String>,
+    pub errors: Vec<TaxCollectionError>,
+    pub report: TaxDocumentsReport,
+}
+
+/// Return the tax year a form belongs to.
+///
+/// Prefers the explicit `metadata.taxYear` (number or string), then the year
+/// of `dateRangeEnd`, then the year of `coverageEndDate`.
+pub fn tax_year_of(info: &DocumentInfo) -> Option<i32> {
+    if let Some(value) = info.metadata.get("taxYear") {
+        let year = match value {
+            serde_json::Value::Number(n) => n.as_i64().and_then(|n| i32::try_from(n).ok()),
+            serde_json::Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        };
+        if year.is_some() {
+            return year;
+        }
+    }
+    info.date_range_end
+        .as_deref()
+        .and_then(year_prefix)
+        .or_else(|| year_prefix(&info.coverage_end_date))
+}
+
+fn year_prefix(date: &str) -> Option<i32> {
+    date.get(..4)?.parse().ok()
+}
+
+/// List logins whose extension declares a `taxDocuments` entry point.
+pub fn tax_document_logins(ledger_dir: &Path) -> io::Result<Vec<String>> {
+    let mut logins = Vec::new();
+    for login_name in crate::login_config::list_logins(ledger_dir)? {
+        if login_tax_entry(ledger_dir, &login_name).1.is_some() {
+            logins.push(login_name);
+        }
+    }
+    Ok(logins)
+}
+
+fn login_tax_entry(
+    ledger_dir: &Path,
+    login_name: &str,
+) -> (Option<String>, Option<crate::scrape::TaxDocumentsEntry>) {
+    let Ok(extension) = crate::login_config::resolve_login_extension(ledger_dir, login_name) else {
+        return (None, None);
+    };
+    let extension_dir = crate::account_config::resolve_extension_dir(ledger_dir, &extension);
+    let entry = crate::scrape::load_manifest(&extension_dir)
+        .ok()
+        .and_then(|manifest| manifest.tax_documents);
+    (Some(extension), entry)
+}
+
+/// Build the consolidated listing of tax forms collected for `year`.
+///
+/// Includes every login that either supports tax document collection or
+/// already has a tax form for the year.
+pub fn list_tax_documents(ledger_dir: &Path, year: i32) -> io::Result<TaxDocumentsReport> {
+    let mut logins = Vec::new();
+    for login_name in crate::login_config::list_logins(ledger_dir)? {
+        let (extension, entry) = login_tax_entry(ledger_dir, &login_name);
+        let mut collected = Vec::new();
+        for label in crate::login_config::list_login_labels(ledger_dir, &login_name)? {
+            let documents =
+                crate::extract::list_documents_for_login_account(ledger_dir, &login_name, &label)?;
+            collected.extend(tax_forms_for_year(&label, documents, year));
+        }
+        if entry.is_none() && collected.is_empty() {
+            continue;
+        }
+
+        let expected_forms = entry
+            .as_ref()
+            .map(|entry| entry.expected_forms.clone())
+            .unwrap_or_default();
+        let missing_forms = missing_forms(&expected_forms, &collected);
+        logins.push(LoginTaxDocuments {
+            login_name,
+            extension,
+            supported: entry.is_some(),
+            expected_forms,
+            collected,
+            missing_forms,
+        });
+    }
+
+    let complete = logins.iter().all(|login| login.missing_forms.is_empty());
+    Ok(TaxDocumentsReport {
+        year,
+        logins,
+        complete,
+    })
+}
+
+fn tax_forms_for_year(
+    label: &str,
+    documents: Vec<DocumentWithInfo>,
+    year: i32,
+) -> Vec<TaxFormDocument> {
+    documents
+        .into_iter()
+        .filter_map(|doc| {
+            let info = doc.info?;
+            if info.document_type != Some(DocumentType::TaxForm) || tax_year_of(&info) != Some(year)
+            {
+                return None;
+            }
+            Some(TaxFormDocument {
+                label: label.to_string(),
+                filename: doc.filename,
+                form_type: info
+                    .metadata
+                    .get("formType")
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string),
+                scraped_at: info.scraped_at,
+            })
+        })
+        .collect()
+}
+
+/// Expected forms with no collected document of the same form type
+/// (compared case-insensitively).
+fn missing_forms(expected: &[String], collected: &[TaxFormDocument]) -> Vec<String> {
+    expected
+        .iter()
+        .filter(|form| {
+            !collected.iter().any(|doc| {
+                doc.form_type
+                    .as_deref()
+                    .is_some_and(|t| t.trim().eq_ignore_ascii_case(form.trim()))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn temp_dir(prefix: &str) -> PathBuf {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_else(|err| panic!("system time before epoch: {err}"))
+            .as_nanos();
+        let dir =
+            std::env::temp_dir().join(format!("refreshmint-{prefix}-{}-{now}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap_or_else(|err| panic!("create temp dir: {err}"));
+        dir
+    }
+
+    fn write(path: &Path, contents: &str) {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).unwrap_or_else(|err| panic!("create dir: {err}"));
+        }
+        fs::write(path, contents).unwrap_or_else(|err| panic!("write {}: {err}", path.display()));
+    }
+
+    fn write_tax_form(root: &Path, login: &str, name: &str, sidecar_extra: &str) {
+        let documents_dir =
+            crate::account_journal::login_account_documents_dir(root, login, "brokerage");
+        write(&documents_dir.join(name), "%PDF");
+        write(
+            &documents_dir.join(format!("{name}-info.json")),
+            &format!(
+                r#"{{"mimeType":"application/pdf","scrapedAt":"2026-02-10T00:00:00Z","extensionName":"{login}-ext","loginName":"{login}","label":"brokerage","scrapeSessionId":"s","coverageEndDate":"2026-02-10","documentType":"tax-form"{sidecar_extra}}}"#
+            ),
+        );
+    }
+
+    #[test]
+    fn tax_year_prefers_metadata_then_date_range() {
+        let mut info: DocumentInfo = serde_json::from_str(
+            r#"{"mimeType":"application/pdf","scrapedAt":"2026-02-10T00:00:00Z","extensionName":"e","loginName":"l","scrapeSessionId":"s","coverageEndDate":"2026-02-10"}"#,
+        )
+        .unwrap_or_else(|err| panic!("parse info: {err}"));
+        assert_eq!(tax_year_of(&info), Some(2026));
+        info.date_range_end = Some("2025-12-31".to_string());
+        assert_eq!(tax_year_of(&info), Some(2025));
+        info.metadata
+            .insert("taxYear".to_string(), serde_json::json!("2024"));
+        assert_eq!(tax_year_of(&info), Some(2024));
+    }
+
+    #[test]
+    fn list_tax_documents_tracks_missing_expected_forms() {
+        let root = temp_dir("tax-documents");
+        let ext_dir = root.join("extensions").join("broker-ext");
+        write(
+            &ext_dir.join("manifest.json"),
+            r#"{"name":"broker-ext","taxDocuments":{"expectedForms":["1099-DIV","1099-B"]}}"#,
+        );
+        write(
+            &crate::login_config::login_config_path(&root, "broker"),
+            r#"{"extension":"broker-ext"}"#,
+        );
+        write_tax_form(
+            &root,
+            "broker",
+            "tax-forms/2026-02-10-1099-div.pdf",
+            r#","metadata":{"taxYear":2025,"formType":"1099-div"}"#,
+        );
+        write_tax_form(
+            &root,
+            "broker",
+            "tax-forms/2025-02-10-1099-div.pdf",
+            r#","metadata":{"taxYear":2024,"formType":"1099-DIV"}"#,
+        );
+
+        let report =
+            list_tax_documents(&root, 2025).unwrap_or_else(|err| panic!("list tax docs: {err}"));
+        assert_eq!(report.year, 2025);
+        assert!(!report.complete);
+        assert_eq!(report.logins.len(), 1);
+        let login = &report.logins[0];
+        assert!(login.supported);
+        assert_eq!(login.collected.len(), 1);
+        assert_eq!(
+            login.collected[0].filename,
+            "tax-forms/2026-02-10-1099-div.pdf"
+        );
+        assert_eq!(login.missing_forms, vec!["1099-B".to_string()]);
+
+        let logins = tax_document_logins(&root).unwrap_or_else(|err| panic!("tax logins: {err}"));
+        assert_eq!(logins, vec!["broker".to_string()]);
+
+        let _ = fs::remove_dir_all(&root);
+    }
+}
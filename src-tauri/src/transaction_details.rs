@@ -0,0 +1,347 @@
+//! Per-transaction details scraped from a bank's transaction detail pages.
+//!
+//! Bank exports often truncate descriptions, while the HTML detail page for
+//! a transaction shows the full merchant name, its location, and which card
+//! member made the charge. A driver saves those records with
+//! `refreshmint.saveTransactionDetails(...)` as a JSON document with
+//! `documentType: "transaction-details"`. The document is not extractor
+//! input: extraction reads every details document of the login account and
+//! adds its fields as tags and comments on the transactions it matches.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+use crate::extract::ExtractedTransaction;
+use crate::locations;
+use crate::scrape::DocumentType;
+
+/// Tag holding the merchant's full name.
+pub const MERCHANT_TAG: &str = "merchant";
+/// Tag holding the card member who made the transaction.
+pub const CARD_MEMBER_TAG: &str = "cardMember";
+
+/// One transaction's details, matched by `fitid` (the bank's transaction ID,
+/// compared with the `bankId` tag) or else by `date` and `amount`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize, ts_rs::TS)]
+#[serde(rename_all = "camelCase")]
+#[ts(export, export_to = "index.ts")]
+pub struct TransactionDetail {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub fitid: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub date: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub amount: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub merchant: Option<String>,
+    /// Address as shown on the page; see [`crate::locations`] for the
+    /// structured fields below.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub city: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub region: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub postal_code: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub country: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub latitude: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub longitude: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub card_member: Option<String>,
+    /// Further tags to add, such as a category the bank assigned.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[ts(optional)]
+    pub comment: Option<String>,
+}
+
+impl TransactionDetail {
+    /// Tags this record adds, in a stable order.
+    fn tags(&self) -> Vec<(String, String)> {
+        let mut tags = Vec::new();
+        for (key, value) in [
+            (MERCHANT_TAG, &self.merchant),
+            (locations::LOCATION_TAG, &self.location),
+            (locations::CITY_TAG, &self.city),
+            (locations::REGION_TAG, &self.region),
+            (locations::POSTAL_CODE_TAG, &self.postal_code),
+            (locations::COUNTRY_TAG, &self.country),
+            (locations::LATITUDE_TAG, &self.latitude),
+            (locations::LONGITUDE_TAG, &self.longitude),
+            (CARD_MEMBER_TAG, &self.card_member),
+        ] {
+            if let Some(value) = value {
+                tags.push((key.to_string(), value.clone()));
+            }
+        }
+        tags.extend(
+            self.tags
+                .iter()
+                .map(|(key, value)| (key.clone(), value.clone())),
+        );
+        tags
+    }
+}
+
+/// Parse the argument of `saveTransactionDetails`: an array of records whose
+/// amounts and coordinates may be numbers or decimal strings. Every record
+/// needs a `fitid`, or a `date` and `amount`, to be matched by.
+pub fn parse_transaction_details(
+    value: &serde_json::Value,
+) -> Result<Vec<TransactionDetail>, String> {
+    let records = value
+        .as_array()
+        .ok_or_else(|| "expected an array of transaction details".to_string())?;
+    let mut details = Vec::with_capacity(records.len());
+    for (i, record) in records.iter().enumerate() {
+        let mut record = record.clone();
+        if let Some(obj) = record.as_object_mut() {
+            for key in ["fitid", "amount", "latitude", "longitude"] {
+                if let Some(serde_json::Value::Number(n)) = obj.get(key) {
+                    let text = n.to_string();
+                    obj.insert(key.to_string(), serde_json::Value::String(text));
+                }
+            }
+        }
+        let detail: TransactionDetail =
+            serde_json::from_value(record).map_err(|err| format!("details[{i}]: {err}"))?;
+        if let Some(date) = &detail.date {
+            if chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err() {
+                return Err(format!("details[{i}].date must be YYYY-MM-DD, got {date}"));
+            }
+        }
+        if let Some(amount) = &detail.amount {
+            if crate::loans::parse_cents(amount).is_none() {
+                return Err(format!("details[{i}].amount is not a number: {amount}"));
+            }
+        }
+        for (key, value) in [
+            (locations::LATITUDE_TAG, &detail.latitude),
+            (locations::LONGITUDE_TAG, &detail.longitude),
+        ] {
+            if let Some(value) = value {
+                locations::validate_coordinate(key, value)
+                    .map_err(|err| format!("details[{i}].{err}"))?;
+            }
+        }
+        if detail.fitid.is_none() && (detail.date.is_none() || detail.amount.is_none()) {
+            return Err(format!("details[{i}] needs a fitid, or a date and amount"));
+        }
+        details.push(detail);
+    }
+    Ok(details)
+}
+
+/// Every details record saved in `documents_dir`, in document order.
+/// Documents that fail to parse are skipped with a warning.
+pub fn load_transaction_details(documents_dir: &Path) -> Vec<TransactionDetail> {
+    let documents = match crate::extract::list_documents_in_dir(documents_dir) {
+        Ok(documents) => documents,
+        Err(err) => {
+            tracing::warn!(
+                "could not list documents in {}: {err}",
+                documents_dir.display()
+            );
+            return Vec::new();
+        }
+    };
+    let mut details = Vec::new();
+    for doc in crate::extract::filter_documents_by_type(documents, DocumentType::TransactionDetails)
+    {
+        let parsed = std::fs::read_to_string(documents_dir.join(&doc.filename))
+            .map_err(|err| err.to_string())
+            .and_then(|text| {
+                serde_json::from_str::<Vec<TransactionDetail>>(&text).map_err(|e| e.to_string())
+            });
+        match parsed {
+            Ok(records) => details.extend(records),
+            Err(err) => tracing::warn!("skipping transaction details {}: {err}", doc.filename),
+        }
+    }
+    details
+}
+
+/// Add the fields of each matching record to `transactions`.
+///
+/// A record matches the transaction whose `bankId` equals its `fitid`, else
+/// the only transaction on its `date` whose amount equals its `amount` in
+/// either sign (detail pages often show charges unsigned). Tags the extractor
+/// already set are kept; the record's comment is appended unless already
+/// present. Returns how many transactions were enriched.
+pub fn enrich_transactions(
+    transactions: &mut [ExtractedTransaction],
+    details: &[TransactionDetail],
+) -> usize {
+    let mut enriched = std::collections::BTreeSet::new();
+    for detail in details {
+        let Some(index) = find_match(transactions, detail) else {
+            continue;
+        };
+        let txn = &mut transactions[index];
+        let mut changed = false;
+        for (key, value) in detail.tags() {
+            if value.trim().is_empty() || txn.ttags.iter().any(|(k, _)| *k == key) {
+                continue;
+            }
+            txn.ttags.push((key, value.trim().to_string()));
+            changed = true;
+        }
+        if let Some(comment) = detail.comment.as_deref().map(str::trim) {
+            if !comment.is_empty() && !txn.tcomment.contains(comment) {
+                if !txn.tcomment.is_empty() {
+                    txn.tcomment.push('\n');
+                }
+                txn.tcomment.push_str(comment);
+                changed = true;
+            }
+        }
+        if changed {
+            enriched.insert(index);
+        }
+    }
+    enriched.len()
+}
+
+fn find_match(transactions: &[ExtractedTransaction], detail: &TransactionDetail) -> Option<usize> {
+    if let Some(fitid) = &detail.fitid {
+        if let Some(index) = transactions
+            .iter()
+            .position(|txn| txn.bank_id() == Some(fitid.as_str()))
+        {
+            return Some(index);
+        }
+    }
+    let date = detail.date.as_deref()?;
+    let cents = crate::loans::parse_cents(detail.amount.as_deref()?)?;
+    let mut candidates = transactions.iter().enumerate().filter(|(_, txn)| {
+        txn.tdate == date
+            && crate::dedup::txn_primary_amount(txn)
+                .is_some_and(|amount| ((amount * 100.0).round() as i64).abs() == cents.abs())
+    });
+    match (candidates.next(), candidates.next()) {
+        (Some((index, _)), None) => Some(index),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn txn(date: &str, amount: &str, bank_id: Option<&str>) -> ExtractedTransaction {
+        let mut ttags = vec![
+            ("evidence".to_string(), "activity.csv:1:1".to_string()),
+            ("amount".to_string(), format!("{amount} USD")),
+        ];
+        if let Some(bank_id) = bank_id {
+            ttags.push(("bankId".to_string(), bank_id.to_string()));
+        }
+        ExtractedTransaction {
+            tdate: date.to_string(),
+            tstatus: "Cleared".to_string(),
+            tdescription: "AMZN MKTP US*2K4".to_string(),
+            tcomment: String::new(),
+            ttags,
+            tpostings: None,
+        }
+    }
+
+    #[test]
+    fn parses_records_with_numeric_amounts() {
+        let details = parse_transaction_details(&serde_json::json!([
+            { "fitid": 123, "merchant": "Amazon Marketplace" },
+            { "date": "2025-03-02", "amount": -42.5, "cardMember": "J SMITH",
+              "latitude": 47.6062, "longitude": "-122.3321" }
+        ]))
+        .unwrap();
+        assert_eq!(details[0].fitid.as_deref(), Some("123"));
+        assert_eq!(details[1].amount.as_deref(), Some("-42.5"));
+        assert_eq!(details[1].card_member.as_deref(), Some("J SMITH"));
+        assert_eq!(details[1].latitude.as_deref(), Some("47.6062"));
+
+        for bad in [
+            serde_json::json!({ "fitid": "1" }),
+            serde_json::json!([{ "merchant": "Unmatched" }]),
+            serde_json::json!([{ "date": "03/02/2025", "amount": "1" }]),
+            serde_json::json!([{ "fitid": "1", "latitude": 91 }]),
+        ] {
+            assert!(parse_transaction_details(&bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn enriches_by_fitid_then_unique_date_and_amount() {
+        let mut transactions = vec![
+            txn("2025-03-01", "-12.00", Some("F1")),
+            txn("2025-03-02", "-42.50", None),
+            txn("2025-03-03", "-5.00", None),
+            txn("2025-03-03", "-5.00", None),
+        ];
+        transactions[0]
+            .ttags
+            .push(("merchant".to_string(), "From extractor".to_string()));
+        let details = vec![
+            TransactionDetail {
+                fitid: Some("F1".to_string()),
+                merchant: Some("Amazon Marketplace".to_string()),
+                location: Some("Seattle, WA".to_string()),
+                ..Default::default()
+            },
+            TransactionDetail {
+                date: Some("2025-03-02".to_string()),
+                amount: Some("42.50".to_string()),
+                card_member: Some("J SMITH".to_string()),
+                comment: Some("Order 113-55".to_string()),
+                ..Default::default()
+            },
+            TransactionDetail {
+                date: Some("2025-03-03".to_string()),
+                amount: Some("-5.00".to_string()),
+                merchant: Some("Ambiguous".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        assert_eq!(enrich_transactions(&mut transactions, &details), 2);
+        let tag = |txn: &ExtractedTransaction, key: &str| {
+            txn.ttags
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.clone())
+        };
+        assert_eq!(
+            tag(&transactions[0], "merchant").as_deref(),
+            Some("From extractor")
+        );
+        assert_eq!(
+            tag(&transactions[0], "location").as_deref(),
+            Some("Seattle, WA")
+        );
+        assert_eq!(
+            tag(&transactions[1], "cardMember").as_deref(),
+            Some("J SMITH")
+        );
+        assert_eq!(transactions[1].tcomment, "Order 113-55");
+        assert!(tag(&transactions[2], "merchant").is_none());
+        assert!(tag(&transactions[3], "merchant").is_none());
+    }
+}
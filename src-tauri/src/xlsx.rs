@@ -0,0 +1,451 @@
+//! Reading `.xlsx` workbooks as CSV-like rows.
+//!
+//! Only cell values are read: shared and inline strings, numbers, booleans,
+//! and formula results. Numbers in a date or time number format are rendered
+//! as `YYYY-MM-DD` (or `YYYY-MM-DD HH:MM:SS` when they have a time part) so
+//! extractors see the same text a CSV export would contain.
+//!
+//! Bank exports often start with a few title rows ("Account activity for
+//! ...") before the real header. [`read_sheet_rows`] drops those rows so the
+//! header is the first row, like a CSV.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::path::Path;
+
+use crate::xml_tree::{parse_xml, XmlElement};
+
+/// Rows scanned when looking for the header row.
+const HEADER_SCAN_ROWS: usize = 20;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Sheet {
+    pub name: String,
+    /// Non-empty rows with trailing empty cells removed.
+    pub rows: Vec<Vec<String>>,
+    /// Index in `rows` of the detected header row.
+    pub header_row: usize,
+}
+
+/// Read every worksheet in the workbook at `path`, in workbook order.
+pub fn read_workbook(path: &Path) -> io::Result<Vec<Sheet>> {
+    let file = std::fs::File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|err| invalid(format!("not an XLSX workbook: {}: {err}", path.display())))?;
+    let mut read_part = |name: &str| -> io::Result<Option<String>> {
+        let mut part = match archive.by_name(name) {
+            Ok(part) => part,
+            Err(zip::result::ZipError::FileNotFound) => return Ok(None),
+            Err(err) => return Err(invalid(format!("failed to read {name}: {err}"))),
+        };
+        let mut text = String::new();
+        part.read_to_string(&mut text)?;
+        Ok(Some(text))
+    };
+
+    let workbook = read_part("xl/workbook.xml")?
+        .ok_or_else(|| invalid(format!("missing xl/workbook.xml in {}", path.display())))?;
+    let workbook = parse_xml(&workbook)?;
+    let rels = match read_part("xl/_rels/workbook.xml.rels")? {
+        Some(text) => parse_xml(&text)?,
+        None => XmlElement::default(),
+    };
+    let targets: HashMap<&str, &str> = rels
+        .children_named("Relationship")
+        .filter_map(|rel| Some((rel.attr("Id")?, rel.attr("Target")?)))
+        .collect();
+    let shared_strings = match read_part("xl/sharedStrings.xml")? {
+        Some(text) => parse_xml(&text)?
+            .children_named("si")
+            .map(text_of_t_descendants)
+            .collect(),
+        None => Vec::new(),
+    };
+    let date_styles = match read_part("xl/styles.xml")? {
+        Some(text) => date_style_indexes(&parse_xml(&text)?),
+        None => Vec::new(),
+    };
+    let date1904 = workbook
+        .children_named("workbookPr")
+        .any(|pr| matches!(pr.attr("date1904"), Some("1" | "true")));
+
+    let mut sheets = Vec::new();
+    for sheet in workbook
+        .children_named("sheets")
+        .flat_map(|sheets| sheets.children_named("sheet"))
+    {
+        let name = sheet.attr("name").unwrap_or_default().to_string();
+        let Some(target) = sheet.attr("id").and_then(|id| targets.get(id)) else {
+            continue;
+        };
+        let part_name = match target.strip_prefix('/') {
+            Some(absolute) => absolute.to_string(),
+            None => format!("xl/{target}"),
+        };
+        let Some(text) = read_part(&part_name)? else {
+            continue;
+        };
+        let cells = CellContext {
+            shared_strings: &shared_strings,
+            date_styles: &date_styles,
+            date1904,
+        };
+        let rows = sheet_rows(&parse_xml(&text)?, &cells);
+        let header_row = detect_header_row(&rows);
+        sheets.push(Sheet {
+            name,
+            rows,
+            header_row,
+        });
+    }
+    Ok(sheets)
+}
+
+/// Rows of one worksheet starting at its header row.
+///
+/// `sheet` selects a worksheet by name (case-insensitive). Without it, the
+/// first worksheet with any rows is used.
+pub fn read_sheet_rows(path: &Path, sheet: Option<&str>) -> io::Result<Vec<Vec<String>>> {
+    let sheets = read_workbook(path)?;
+    let selected = match sheet.map(str::trim).filter(|name| !name.is_empty()) {
+        Some(wanted) => sheets
+            .iter()
+            .find(|s| s.name.eq_ignore_ascii_case(wanted))
+            .ok_or_else(|| {
+                let names: Vec<&str> = sheets.iter().map(|s| s.name.as_str()).collect();
+                invalid(format!(
+                    "worksheet '{wanted}' not found in {} (available: {})",
+                    path.display(),
+                    names.join(", ")
+                ))
+            })?,
+        None => match sheets.iter().find(|s| !s.rows.is_empty()) {
+            Some(sheet) => sheet,
+            None => return Ok(Vec::new()),
+        },
+    };
+    Ok(selected.rows[selected.header_row..].to_vec())
+}
+
+/// Index of the header row: the first row, among the first few, that is at
+/// least as wide as the most common row width. Title rows above a table
+/// usually have one or two cells while the header and data rows share a
+/// width.
+pub fn detect_header_row(rows: &[Vec<String>]) -> usize {
+    let filled = |row: &Vec<String>| row.iter().filter(|cell| !cell.is_empty()).count();
+    let mut width_counts: HashMap<usize, usize> = HashMap::new();
+    for row in rows {
+        *width_counts.entry(filled(row)).or_default() += 1;
+    }
+    let Some(common_width) = width_counts
+        .into_iter()
+        .max_by_key(|(width, count)| (*count, *width))
+        .map(|(width, _)| width)
+    else {
+        return 0;
+    };
+    rows.iter()
+        .take(HEADER_SCAN_ROWS)
+        .position(|row| filled(row) >= common_width.max(2))
+        .unwrap_or(0)
+}
+
+struct CellContext<'a> {
+    shared_strings: &'a [String],
+    /// Per `cellXfs` index, whether the number format is a date or time.
+    date_styles: &'a [bool],
+    date1904: bool,
+}
+
+fn sheet_rows(worksheet: &XmlElement, cells: &CellContext<'_>) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    for row in worksheet
+        .children_named("sheetData")
+        .flat_map(|data| data.children_named("row"))
+    {
+        let mut values: Vec<String> = Vec::new();
+        for cell in row.children_named("c") {
+            let column = cell
+                .attr("r")
+                .and_then(column_index)
+                .unwrap_or(values.len());
+            if column >= values.len() {
+                values.resize(column + 1, String::new());
+            }
+            values[column] = cell_value(cell, cells);
+        }
+        while values.last().is_some_and(String::is_empty) {
+            values.pop();
+        }
+        if !values.is_empty() {
+            rows.push(values);
+        }
+    }
+    rows
+}
+
+fn cell_value(cell: &XmlElement, cells: &CellContext<'_>) -> String {
+    let raw = cell
+        .children_named("v")
+        .next()
+        .map(|v| v.text.as_str())
+        .unwrap_or_default();
+    let value = match cell.attr("t").unwrap_or("n") {
+        "s" => raw
+            .trim()
+            .parse::<usize>()
+            .ok()
+            .and_then(|index| cells.shared_strings.get(index))
+            .cloned()
+            .unwrap_or_default(),
+        "inlineStr" => cell
+            .children_named("is")
+            .map(text_of_t_descendants)
+            .collect(),
+        "b" => if raw.trim() == "1" { "TRUE" } else { "FALSE" }.to_string(),
+        "n" => {
+            let is_date = cell
+                .attr("s")
+                .and_then(|s| s.parse::<usize>().ok())
+                .and_then(|style| cells.date_styles.get(style))
+                .copied()
+                .unwrap_or(false);
+            match raw.trim().parse::<f64>() {
+                Ok(serial) if is_date => {
+                    format_serial_date(serial, cells.date1904).unwrap_or_else(|| raw.to_string())
+                }
+                _ => raw.to_string(),
+            }
+        }
+        // "str" (formula string), "e" (error), and "d" (ISO date) hold text.
+        _ => raw.to_string(),
+    };
+    value.trim().to_string()
+}
+
+/// Columns Excel allows, `A` through `XFD`.
+const MAX_COLUMNS: usize = 16_384;
+
+/// Zero-based column of a cell reference such as `AB12`, or `None` past
+/// column `XFD`.
+fn column_index(reference: &str) -> Option<usize> {
+    let letters: Vec<u8> = reference
+        .bytes()
+        .take_while(u8::is_ascii_alphabetic)
+        .collect();
+    if letters.is_empty() {
+        return None;
+    }
+    let number = letters.iter().try_fold(0usize, |acc, letter| {
+        let number = acc * 26 + usize::from(letter.to_ascii_uppercase() - b'A') + 1;
+        (number <= MAX_COLUMNS).then_some(number)
+    })?;
+    Some(number - 1)
+}
+
+/// Format an Excel date serial, or `None` when it is out of chrono's range.
+fn format_serial_date(serial: f64, date1904: bool) -> Option<String> {
+    let epoch = if date1904 {
+        chrono::NaiveDate::from_ymd_opt(1904, 1, 1)
+    } else {
+        // Day 60 is the nonexistent 1900-02-29; counting from 1899-12-30
+        // is correct for every date after it.
+        chrono::NaiveDate::from_ymd_opt(1899, 12, 30)
+    }?;
+    if !serial.is_finite() {
+        return None;
+    }
+    let days = serial.floor();
+    let seconds = ((serial - days) * 86_400.0).round() as i64;
+    let datetime = epoch
+        .and_hms_opt(0, 0, 0)?
+        .checked_add_signed(chrono::TimeDelta::try_days(days as i64)?)?
+        .checked_add_signed(chrono::TimeDelta::try_seconds(seconds)?)?;
+    Some(if seconds == 0 {
+        datetime.format("%Y-%m-%d").to_string()
+    } else if days == 0.0 && !date1904 {
+        datetime.format("%H:%M:%S").to_string()
+    } else {
+        datetime.format("%Y-%m-%d %H:%M:%S").to_string()
+    })
+}
+
+/// For each `cellXfs` entry in `styles.xml`, whether it uses a date or time
+/// number format.
+fn date_style_indexes(styles: &XmlElement) -> Vec<bool> {
+    let custom_formats: HashMap<&str, &str> = styles
+        .children_named("numFmts")
+        .flat_map(|formats| formats.children_named("numFmt"))
+        .filter_map(|format| Some((format.attr("numFmtId")?, format.attr("formatCode")?)))
+        .collect();
+    styles
+        .children_named("cellXfs")
+        .flat_map(|xfs| xfs.children_named("xf"))
+        .map(|xf| {
+            let id = xf.attr("numFmtId").unwrap_or("0");
+            match id.parse::<u32>() {
+                Ok(14..=22 | 45..=47) => true,
+                _ => custom_formats
+                    .get(id)
+                    .is_some_and(|code| is_date_format_code(code)),
+            }
+        })
+        .collect()
+}
+
+/// Whether a custom number format code formats dates or times, ignoring
+/// quoted literals, escaped characters, and `[...]` sections (colors,
+/// locales, conditions).
+fn is_date_format_code(code: &str) -> bool {
+    let mut in_quotes = false;
+    let mut in_brackets = false;
+    let mut escaped = false;
+    for c in code.chars() {
+        if escaped {
+            escaped = false;
+            continue;
+        }
+        match c {
+            '\\' if !in_quotes => escaped = true,
+            '"' => in_quotes = !in_quotes,
+            '[' if !in_quotes => in_brackets = true,
+            ']' if !in_quotes => in_brackets = false,
+            'd' | 'D' | 'y' | 'Y' | 'h' | 'H' if !in_quotes && !in_brackets => return true,
+            _ => {}
+        }
+    }
+    false
+}
+
+/// Concatenated text of every descendant `<t>`, skipping phonetic runs.
+fn text_of_t_descendants(element: &XmlElement) -> String {
+    let mut text = String::new();
+    for child in &element.children {
+        match child.name.as_str() {
+            "t" => text.push_str(&child.text),
+            "rPh" => {}
+            _ => text.push_str(&text_of_t_descendants(child)),
+        }
+    }
+    text
+}
+
+fn invalid(message: String) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used, clippy::expect_used)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+
+    fn write_workbook(path: &Path, sheets: &[(&str, &str)]) {
+        let file = std::fs::File::create(path).unwrap();
+        let mut zip = zip::ZipWriter::new(file);
+        let options = SimpleFileOptions::default();
+        let mut workbook = String::from(
+            r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+        );
+        let mut rels = String::from(
+            r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        );
+        for (index, (name, _)) in sheets.iter().enumerate() {
+            let n = index + 1;
+            workbook.push_str(&format!(
+                r#"<sheet name="{name}" sheetId="{n}" r:id="rId{n}"/>"#
+            ));
+            rels.push_str(&format!(
+                r#"<Relationship Id="rId{n}" Target="worksheets/sheet{n}.xml"/>"#
+            ));
+        }
+        workbook.push_str("</sheets></workbook>");
+        rels.push_str("</Relationships>");
+        let parts = [
+            ("xl/workbook.xml", workbook),
+            ("xl/_rels/workbook.xml.rels", rels),
+            (
+                "xl/sharedStrings.xml",
+                r#"<sst><si><t>Date</t></si><si><t>Description</t></si><si><t>Amount</t></si><si><r><t>COFFEE </t></r><r><t>&amp; BAGEL</t></r></si></sst>"#.to_string(),
+            ),
+            (
+                "xl/styles.xml",
+                r#"<styleSheet><numFmts><numFmt numFmtId="164" formatCode="mm/dd/yyyy"/></numFmts><cellXfs><xf numFmtId="0"/><xf numFmtId="164"/><xf numFmtId="4"/></cellXfs></styleSheet>"#.to_string(),
+            ),
+        ];
+        for (name, text) in parts {
+            zip.start_file(name, options).unwrap();
+            zip.write_all(text.as_bytes()).unwrap();
+        }
+        for (index, (_, data)) in sheets.iter().enumerate() {
+            zip.start_file(format!("xl/worksheets/sheet{}.xml", index + 1), options)
+                .unwrap();
+            zip.write_all(
+                format!("<worksheet><sheetData>{data}</sheetData></worksheet>").as_bytes(),
+            )
+            .unwrap();
+        }
+        zip.finish().unwrap();
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap()
+            .as_nanos();
+        std::env::temp_dir().join(format!(
+            "refreshmint-{name}-{}-{now}.xlsx",
+            std::process::id()
+        ))
+    }
+
+    #[test]
+    fn reads_rows_with_dates_and_skips_title_rows() {
+        let path = temp_path("xlsx-activity");
+        let activity = concat!(
+            r#"<row r="1"><c r="A1" t="inlineStr"><is><t>Checking ...1234</t></is></c></row>"#,
+            r#"<row r="3"><c r="A3" t="s"><v>0</v></c><c r="B3" t="s"><v>1</v></c><c r="C3" t="s"><v>2</v></c></row>"#,
+            r#"<row r="4"><c r="A4" s="1"><v>45659</v></c><c r="B4" t="s"><v>3</v></c><c r="C4" s="2"><v>-4.5</v></c></row>"#,
+            r#"<row r="5"><c r="A5" s="1"><v>45660.5</v></c><c r="B5" t="str"><v>PAYROLL</v></c><c r="D5"><v>1</v></c></row>"#,
+        );
+        write_workbook(&path, &[("Summary", ""), ("Activity", activity)]);
+
+        let rows = read_sheet_rows(&path, None).unwrap();
+        assert_eq!(
+            rows,
+            vec![
+                vec!["Date", "Description", "Amount"],
+                vec!["2025-01-02", "COFFEE & BAGEL", "-4.5"],
+                vec!["2025-01-03 12:00:00", "PAYROLL", "", "1"],
+            ]
+        );
+
+        let sheets = read_workbook(&path).unwrap();
+        assert_eq!(sheets.len(), 2);
+        assert_eq!(sheets[1].header_row, 1);
+        assert_eq!(read_sheet_rows(&path, Some("activity")).unwrap(), rows);
+        assert!(read_sheet_rows(&path, Some("Missing")).is_err());
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn detects_date_formats_and_columns() {
+        assert!(is_date_format_code("yyyy-mm-dd"));
+        assert!(is_date_format_code("[$-409]h:mm AM/PM"));
+        assert!(!is_date_format_code("#,##0.00"));
+        assert!(!is_date_format_code(r#"0.00" days""#));
+        assert!(!is_date_format_code("[Red]0.00"));
+        assert_eq!(column_index("A1"), Some(0));
+        assert_eq!(column_index("AB12"), Some(27));
+        assert_eq!(column_index("XFD1"), Some(16_383));
+        assert_eq!(column_index("XFE1"), None);
+        assert_eq!(column_index("ZZZZZZZZZZZZZZZ1"), None);
+        assert_eq!(format_serial_date(1.0, true).as_deref(), Some("1904-01-02"));
+        assert_eq!(format_serial_date(1e300, false), None);
+        assert_eq!(format_serial_date(f64::NAN, false), None);
+        assert_eq!(detect_header_row(&[]), 0);
+    }
+}
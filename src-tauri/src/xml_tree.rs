@@ -0,0 +1,135 @@
+//! A minimal XML element tree for reading document formats (XLSX parts,
+//! ISO 20022 statements, GnuCash books) that are small enough to hold in
+//! memory.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use std::io;
+
+/// A parsed XML element. Names are local names (namespace prefixes dropped).
+#[derive(Debug, Default)]
+pub(crate) struct XmlElement {
+    pub(crate) name: String,
+    pub(crate) attrs: Vec<(String, String)>,
+    pub(crate) children: Vec<XmlElement>,
+    /// Text directly inside this element.
+    pub(crate) text: String,
+}
+
+impl XmlElement {
+    pub(crate) fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub(crate) fn children_named<'a>(
+        &'a self,
+        name: &'a str,
+    ) -> impl Iterator<Item = &'a XmlElement> {
+        self.children.iter().filter(move |child| child.name == name)
+    }
+
+    /// The element reached by following the first child with each name.
+    pub(crate) fn find(&self, path: &[&str]) -> Option<&XmlElement> {
+        path.iter().try_fold(self, |element, name| {
+            element.children.iter().find(|child| child.name == *name)
+        })
+    }
+
+    /// Trimmed text of the element at `path`, if present and not empty.
+    pub(crate) fn text_at(&self, path: &[&str]) -> Option<&str> {
+        self.find(path)
+            .map(|element| element.text.trim())
+            .filter(|text| !text.is_empty())
+    }
+}
+
+/// Parse `text` and return its root element.
+pub(crate) fn parse_xml(text: &str) -> io::Result<XmlElement> {
+    let xml_error = |err: &dyn std::fmt::Display| {
+        io::Error::new(io::ErrorKind::InvalidData, format!("invalid XML: {err}"))
+    };
+    let mut reader = Reader::from_str(text);
+    let mut stack = vec![XmlElement::default()];
+    loop {
+        match reader.read_event().map_err(|err| xml_error(&err))? {
+            Event::Start(start) => {
+                stack.push(start_element(&start).map_err(|err| xml_error(&err))?)
+            }
+            Event::Empty(start) => {
+                let element = start_element(&start).map_err(|err| xml_error(&err))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            Event::End(_) => {
+                let element = stack
+                    .pop()
+                    .filter(|_| !stack.is_empty())
+                    .ok_or_else(|| xml_error(&"unbalanced end tag"))?;
+                if let Some(parent) = stack.last_mut() {
+                    parent.children.push(element);
+                }
+            }
+            Event::Text(content) => {
+                let content = content.decode().map_err(|err| xml_error(&err))?;
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&content);
+                }
+            }
+            Event::CData(content) => {
+                if let Some(element) = stack.last_mut() {
+                    element.text.push_str(&String::from_utf8_lossy(&content));
+                }
+            }
+            Event::GeneralRef(reference) => {
+                let resolved = match reference
+                    .resolve_char_ref()
+                    .map_err(|err| xml_error(&err))?
+                {
+                    Some(c) => c,
+                    None => match reference.decode().map_err(|err| xml_error(&err))?.as_ref() {
+                        "amp" => '&',
+                        "lt" => '<',
+                        "gt" => '>',
+                        "quot" => '"',
+                        "apos" => '\'',
+                        other => return Err(xml_error(&format!("unknown entity &{other};"))),
+                    },
+                };
+                if let Some(element) = stack.last_mut() {
+                    element.text.push(resolved);
+                }
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    let mut root = stack.pop().ok_or_else(|| xml_error(&"empty document"))?;
+    if !stack.is_empty() {
+        return Err(xml_error(&"unclosed element"));
+    }
+    if root.children.is_empty() {
+        return Err(xml_error(&"no root element"));
+    }
+    Ok(root.children.swap_remove(0))
+}
+
+fn start_element(start: &BytesStart<'_>) -> Result<XmlElement, quick_xml::Error> {
+    let mut attrs = Vec::new();
+    for attr in start.attributes() {
+        let attr = attr?;
+        attrs.push((
+            String::from_utf8_lossy(attr.key.local_name().as_ref()).into_owned(),
+            attr.unescape_value()?.into_owned(),
+        ));
+    }
+    Ok(XmlElement {
+        name: String::from_utf8_lossy(start.local_name().as_ref()).into_owned(),
+        attrs,
+        children: Vec::new(),
+        text: String::new(),
+    })
+}
@@ -228,6 +228,236 @@ try {
 }
 "##;
 
+const STRICT_SELECTORS_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("strict selectors test start");
+  const html = encodeURIComponent(`
+    <button id="first" title="a" onclick="window.clicks = (window.clicks || []).concat('first')">Sign in</button>
+    <button data-testid="second" title="b" onclick="window.clicks = (window.clicks || []).concat('second')">Sign in</button>
+  `);
+  await page.goto(`data:text/html,${html}`);
+
+  // 1. Page-level actions and reads are strict by default, and the error
+  //    lists each match with a selector that finds only it.
+  for (const [name, action] of [
+    ["click", () => page.click("button", { timeout: 1000 })],
+    ["innerText", () => page.innerText("button")],
+    ["locator.click", () => page.locator("button").click({ timeout: 1000 })],
+  ]) {
+    let message = "";
+    try {
+      await action();
+    } catch (e) {
+      message = String(e && e.message ? e.message : e);
+    }
+    if (!message.includes("Strict mode violation: 2 elements found")) {
+      throw new Error(`${name}: expected a strict mode violation, got: ${message}`);
+    }
+    if (!message.includes("aka #first") || !message.includes('aka button[data-testid="second"]')) {
+      throw new Error(`${name}: expected selector hints, got: ${message}`);
+    }
+  }
+  const clicksAfterErrors = await page.evaluate("JSON.stringify(window.clicks || [])");
+  if (clicksAfterErrors !== "[]") throw new Error(`Ambiguous clicks acted: ${clicksAfterErrors}`);
+
+  // 2. locator.all() returns one locator per match, in document order.
+  const all = await page.locator("button").all();
+  if (all.length !== 2) throw new Error(`Expected 2 locators, got ${all.length}`);
+  const titles = [];
+  for (const locator of all) titles.push(await locator.getAttribute("title"));
+  if (titles.join() !== "a,b") throw new Error(`Unexpected titles: ${JSON.stringify(titles)}`);
+  await all[1].click();
+
+  // 3. setStrictSelectors(false) makes page-level methods use the first match.
+  await page.setStrictSelectors(false);
+  await page.click("button");
+  const clicks = await page.evaluate("JSON.stringify(window.clicks)");
+  if (clicks !== '["second","first"]') throw new Error(`Unexpected clicks: ${clicks}`);
+
+  await refreshmint.saveResource("strict_selectors.bin", [111, 107]);
+  refreshmint.log("strict selectors test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("strict selectors test error: " + msg);
+  throw e;
+}
+"##;
+
+const HOVER_FOCUS_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("hover focus test start");
+  const html = encodeURIComponent(`
+    <style>
+      #menu { display: none; }
+      #trigger:hover + #menu { display: block; }
+    </style>
+    <button id="trigger">Menu</button>
+    <div id="menu">Statements</div>
+    <input id="name" onfocus="window.events.push('focus')" onblur="window.events.push('blur')" />
+    <button id="other">Other</button>
+    <script>window.events = [];</script>
+  `);
+  await page.goto(`data:text/html,${html}`);
+  const hovered = (id) => page.evaluate(`document.getElementById('${id}').matches(':hover')`);
+  const activeId = () => page.evaluate("document.activeElement ? document.activeElement.id : ''");
+
+  // 1. hover moves the mouse, so CSS :hover rules apply.
+  await page.locator("#trigger").hover();
+  if ((await hovered("trigger")) !== true) throw new Error("#trigger is not :hover after hover()");
+  if (!(await page.locator("#menu").isVisible())) throw new Error("hover menu did not open");
+  await page.locator("#other").hover();
+  if ((await hovered("trigger")) !== false) throw new Error("#trigger still :hover after moving away");
+  if ((await hovered("other")) !== true) throw new Error("#other is not :hover");
+
+  // 2. focus and blur move document.activeElement and fire their events.
+  await page.locator("#name").focus();
+  if ((await activeId()) !== "name") throw new Error(`activeElement after focus: ${await activeId()}`);
+  await page.locator("#name").blur();
+  if ((await activeId()) === "name") throw new Error("#name still active after blur()");
+  const events = await page.evaluate("JSON.stringify(window.events)");
+  if (events !== '["focus","blur"]') throw new Error(`Unexpected focus events: ${events}`);
+
+  await refreshmint.saveResource("hover_focus.bin", [111, 107]);
+  refreshmint.log("hover focus test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("hover focus test error: " + msg);
+  throw e;
+}
+"##;
+
+const CHECK_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("check test start");
+  const html = encodeURIComponent(`
+    <input type="checkbox" id="terms" checked onclick="window.clicks++" />
+    <input type="checkbox" id="locked" disabled />
+    <input type="radio" name="account" id="checking" checked />
+    <input type="radio" name="account" id="savings" />
+    <script>window.clicks = 0;</script>
+  `);
+  await page.goto(`data:text/html,${html}`);
+  const checked = (id) => page.evaluate(`document.getElementById('${id}').checked`);
+  const failure = async (action) => {
+    try {
+      await action();
+    } catch (e) {
+      return String(e && e.message ? e.message : e);
+    }
+    return "";
+  };
+
+  // 1. Checking an already checked box does not click it.
+  await page.locator("#terms").check();
+  if ((await page.evaluate("window.clicks")) !== 0) throw new Error("check() clicked a checked box");
+  if ((await checked("terms")) !== true) throw new Error("#terms lost its check");
+  await page.locator("#terms").uncheck();
+  if ((await checked("terms")) !== false) throw new Error("uncheck() left #terms checked");
+  if ((await page.evaluate("window.clicks")) !== 1) throw new Error("uncheck() did not click once");
+
+  // 2. A disabled box fails the actionability checks.
+  const disabledError = await failure(() => page.locator("#locked").check({ timeout: 500 }));
+  if (!disabledError.includes("TimeoutError") || !disabledError.includes("element is disabled")) {
+    throw new Error(`Expected a disabled timeout, got: ${disabledError}`);
+  }
+  if ((await checked("locked")) !== false) throw new Error("#locked was checked");
+
+  // 3. A radio button can be checked but not unchecked.
+  await page.locator("#savings").check();
+  if ((await checked("savings")) !== true || (await checked("checking")) !== false) {
+    throw new Error("check() did not switch the radio group");
+  }
+  const radioError = await failure(() => page.locator("#savings").uncheck({ timeout: 500 }));
+  if (!radioError.includes("cannot uncheck a radio button")) {
+    throw new Error(`Expected a radio uncheck error, got: ${radioError}`);
+  }
+  if ((await checked("savings")) !== true) throw new Error("#savings was unchecked");
+
+  await refreshmint.saveResource("check.bin", [111, 107]);
+  refreshmint.log("check test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("check test error: " + msg);
+  throw e;
+}
+"##;
+
+const WAIT_FOR_FUNCTION_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("waitForFunction test start");
+  const html = encodeURIComponent(`
+    <script>
+      setTimeout(() => { window.statement = { rows: 3 }; }, 300);
+      setTimeout(() => { document.title = "loaded"; }, 300);
+    </script>
+  `);
+  await page.goto(`data:text/html,${html}`);
+
+  // 1. Resolves with the value once the expression becomes truthy.
+  const rows = await page.waitForFunction("window.statement && window.statement.rows", 5000);
+  if (rows !== 3) throw new Error(`Expected 3 rows, got ${JSON.stringify(rows)}`);
+
+  // 2. A function expression is called on each poll.
+  const loaded = await page.waitForFunction("() => document.title === 'loaded'", 5000);
+  if (loaded !== true) throw new Error(`Expected true, got ${JSON.stringify(loaded)}`);
+
+  // 3. A predicate that never holds rejects with a TimeoutError.
+  let message = "";
+  try {
+    await page.waitForFunction("window.never === true", 300);
+  } catch (e) {
+    message = String(e && e.message ? e.message : e);
+  }
+  if (!message.includes("TimeoutError") || !message.includes("300ms")) {
+    throw new Error(`Expected a timeout, got: ${message}`);
+  }
+
+  await refreshmint.saveResource("wait_for_function.bin", [111, 107]);
+  refreshmint.log("waitForFunction test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("waitForFunction test error: " + msg);
+  throw e;
+}
+"##;
+
+const TO_HAVE_VALUE_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("toHaveValue test start");
+  const html = encodeURIComponent(`
+    <input id="amount" value="" />
+    <script>
+      setTimeout(() => { document.getElementById('amount').value = '42.00'; }, 500);
+    </script>
+  `);
+  await page.goto(`data:text/html,${html}`);
+  if (expect !== refreshmint.expect) throw new Error("global expect is not refreshmint.expect");
+
+  // 1. toHaveValue keeps polling until the delayed value appears.
+  await expect(page.locator("#amount")).toHaveValue("42.00", { timeout: 5000 });
+  await expect("#amount").toHaveValue(/^42\./);
+  await expect("#amount").not.toHaveValue("0.00");
+
+  // 2. A value that never appears fails with what was expected and seen.
+  let message = "";
+  try {
+    await expect("#amount").toHaveValue("43.00", { timeout: 300 });
+  } catch (e) {
+    message = String(e && e.message ? e.message : e);
+  }
+  if (!message.includes('expected value "43.00"') || !message.includes('"42.00"')) {
+    throw new Error(`Expected a value mismatch, got: ${message}`);
+  }
+
+  await refreshmint.saveResource("to_have_value.bin", [111, 107]);
+  refreshmint.log("toHaveValue test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("toHaveValue test error: " + msg);
+  throw e;
+}
+"##;
+
 struct TestSandbox {
     root: PathBuf,
 }
@@ -254,6 +484,38 @@ impl Drop for TestSandbox {
     }
 }
 
+/// Run `driver_source` as the test extension and return its output directory.
+fn run_driver(sandbox: &TestSandbox, driver_source: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let ledger_dir = sandbox.path().join("ledger.refreshmint");
+    let extension_dir = ledger_dir.join("extensions").join(EXTENSION_NAME);
+    fs::create_dir_all(&extension_dir)?;
+    fs::write(
+        extension_dir.join("manifest.json"),
+        format!("{{\"name\":\"{EXTENSION_NAME}\"}}"),
+    )?;
+    fs::write(extension_dir.join("driver.mjs"), driver_source)?;
+
+    let config = ScrapeConfig {
+        login_name: LOGIN_NAME.to_string(),
+        extension_name: EXTENSION_NAME.to_string(),
+        ledger_dir: ledger_dir.clone(),
+        profile_override: Some(sandbox.path().join("profile")),
+        prompt_overrides: app_lib::scrape::js_api::PromptOverrides::new(),
+        headless: false,
+        prompt_requires_override: false,
+        prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
+    };
+    scrape::run_scrape(config)?;
+
+    Ok(ledger_dir
+        .join("cache")
+        .join("extensions")
+        .join(EXTENSION_NAME)
+        .join("output"))
+}
+
 #[test]
 #[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
 fn locator_api_works() -> Result<(), Box<dyn Error>> {
@@ -289,6 +551,8 @@ fn locator_api_works() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -345,6 +609,8 @@ fn get_by_role_api_works() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -400,6 +666,8 @@ fn shadow_dom_api_works() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -419,3 +687,78 @@ fn shadow_dom_api_works() -> Result<(), Box<dyn Error>> {
 
     Ok(())
 }
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn strict_selectors_report_matches() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping strict selectors test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("strict-selectors")?;
+    let output_dir = run_driver(&sandbox, STRICT_SELECTORS_DRIVER_SOURCE)?;
+    assert_eq!(fs::read(output_dir.join("strict_selectors.bin"))?, b"ok");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn hover_focus_and_blur_change_page_state() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping hover/focus test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("hover-focus")?;
+    let output_dir = run_driver(&sandbox, HOVER_FOCUS_DRIVER_SOURCE)?;
+    assert_eq!(fs::read(output_dir.join("hover_focus.bin"))?, b"ok");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn check_and_uncheck_respect_state() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping check test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("check")?;
+    let output_dir = run_driver(&sandbox, CHECK_DRIVER_SOURCE)?;
+    assert_eq!(fs::read(output_dir.join("check.bin"))?, b"ok");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn wait_for_function_polls_until_truthy() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping waitForFunction test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("wait-for-function")?;
+    let output_dir = run_driver(&sandbox, WAIT_FOR_FUNCTION_DRIVER_SOURCE)?;
+    assert_eq!(fs::read(output_dir.join("wait_for_function.bin"))?, b"ok");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn to_have_value_waits_for_delayed_value() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping toHaveValue test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("to-have-value")?;
+    let output_dir = run_driver(&sandbox, TO_HAVE_VALUE_DRIVER_SOURCE)?;
+    assert_eq!(fs::read(output_dir.join("to_have_value.bin"))?, b"ok");
+
+    Ok(())
+}
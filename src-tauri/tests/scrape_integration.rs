@@ -92,6 +92,40 @@ try {
 }
 "##;
 
+const POPUP_DOWNLOAD_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("popup download test start");
+  await page.goto(__OPENER_URL__);
+  const [popup] = await Promise.all([
+    page.waitForEvent("popup", 10000),
+    page.click("#open"),
+  ]);
+  await popup.waitForLoadState("domcontentloaded", 10000);
+
+  // 1. A popup downloads into its own directory.
+  const [download] = await Promise.all([
+    popup.waitForDownload(10000),
+    popup.click("#download"),
+  ]);
+  await refreshmint.saveDownloadedResource(download.path, "popup_download.txt");
+
+  // 2. Closing the popup leaves the opener open and usable.
+  if (await popup.isClosed()) throw new Error("popup reported closed before close()");
+  await popup.close();
+  if (!(await popup.isClosed())) throw new Error("popup not closed after close()");
+  if (await page.isClosed()) throw new Error("opener closed with its popup");
+  const openerUrl = await page.url();
+  if (openerUrl !== __OPENER_URL__) throw new Error("opener moved to " + openerUrl);
+
+  await refreshmint.saveResource("popup_download.bin", [111, 107]);
+  refreshmint.log("popup download test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("popup download test error: " + msg);
+  throw e;
+}
+"##;
+
 const OVERLAY_DRIVER_SOURCE: &str = r##"
 try {
   refreshmint.log("integration overlay start");
@@ -214,6 +248,107 @@ try {
 }
 "##;
 
+const FRAME_HANDLE_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("frame handle test start");
+  await page.goto(__FRAME_URL__);
+
+  // 1. Actions through a frame handle run in the iframe without
+  //    switchToFrame.
+  const login = await page.frame("logonbox");
+  await login.fill("#user", "alice");
+  await login.click("#submit");
+  const result = await login.evaluate("document.getElementById('result').textContent");
+  if (result !== "submitted alice") {
+    throw new Error("click through frame handle did not submit: " + result);
+  }
+  const value = await login.locator("#user").inputValue();
+  if (value !== "alice") throw new Error("frame locator read " + value);
+
+  // 2. Page methods still run in the main frame.
+  if (await page.isVisible("#user")) {
+    throw new Error("#user should not be visible from the main frame");
+  }
+  if (!(await page.isVisible("#main"))) {
+    throw new Error("#main should be visible from the main frame");
+  }
+
+  await refreshmint.saveResource("frame_handle.bin", [111, 107]);
+  refreshmint.log("frame handle test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("frame handle test error: " + msg);
+  throw e;
+}
+"##;
+
+const NESTED_FRAME_LOCATOR_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("nested frame locator test start");
+  await page.goto(__FRAME_URL__);
+
+  // 1. A frame locator finds an iframe nested inside another by name.
+  const mfa = page.frameLocator("inner");
+  await mfa.locator("#code").fill("123456");
+  await mfa.click("#verify");
+  await mfa.waitForSelector("#verified", 5000);
+  const verified = await mfa.locator("#verified").textContent();
+  if (verified !== "verified 123456") throw new Error("unexpected result: " + verified);
+
+  // 2. Looking the frame up by URL reaches the same document.
+  const code = await page.frameLocator("nested-inner.html").locator("#code").inputValue();
+  if (code !== "123456") throw new Error("frame located by URL read " + code);
+
+  // 3. Page methods still run in the main frame.
+  if (await page.isVisible("#code")) throw new Error("#code should not be visible from the main frame");
+
+  await refreshmint.saveResource("nested_frame_locator.bin", [111, 107]);
+  refreshmint.log("nested frame locator test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("nested frame locator test error: " + msg);
+  throw e;
+}
+"##;
+
+const XPATH_SELECTOR_DRIVER_SOURCE: &str = r##"
+try {
+  refreshmint.log("xpath selector test start");
+  await page.goto(__FRAME_URL__);
+
+  // 1. page.$ and page.$$
+  const heading = await page.$("xpath=//h1[@id='title']");
+  if (!heading) throw new Error("$ found no heading");
+  const title = await heading.textContent();
+  if (title !== "Activity") throw new Error("$ read " + title);
+  const missing = await page.$("xpath=//h2");
+  if (missing !== null) throw new Error("$ matched a missing element");
+  const cells = await page.$$("xpath=//table[@id='activity']//td[1]");
+  if (cells.length !== 2) throw new Error("$$ found " + cells.length + " cells");
+  const firstDate = await cells[0].textContent();
+  if (firstDate !== "2025-01-02") throw new Error("$$ first cell " + firstDate);
+
+  // 2. extractTable
+  const table = await page.extractTable("xpath=//table[@id='activity']");
+  if (table.headers.join() !== "Date,Amount") throw new Error("headers " + JSON.stringify(table.headers));
+  if (table.rows.length !== 2 || table.rows[1][1] !== "-12.00") {
+    throw new Error("rows " + JSON.stringify(table.rows));
+  }
+
+  // 3. frameFill
+  await page.frameFill("logonbox", "xpath=//input[@name='user']", "alice");
+  const user = await page.frame("logonbox").then(frame => frame.locator("#user").inputValue());
+  if (user !== "alice") throw new Error("frameFill wrote " + user);
+
+  await refreshmint.saveResource("xpath_selectors.bin", [111, 107]);
+  refreshmint.log("xpath selector test done");
+} catch (e) {
+  const msg = (e && (e.stack || e.message)) ? (e.stack || e.message) : String(e);
+  refreshmint.log("xpath selector test error: " + msg);
+  throw e;
+}
+"##;
+
 const GOTO_DRIVER_SOURCE: &str = r##"
 try {
   refreshmint.log("integration goto start");
@@ -808,6 +943,38 @@ fn file_url(path: &Path) -> Result<String, Box<dyn Error>> {
     }
 }
 
+/// Run `driver_source` as the smoke extension and return its output directory.
+fn run_driver(sandbox: &TestSandbox, driver_source: &str) -> Result<PathBuf, Box<dyn Error>> {
+    let ledger_dir = sandbox.path().join("ledger.refreshmint");
+    let extension_dir = ledger_dir.join("extensions").join(EXTENSION_NAME);
+    fs::create_dir_all(&extension_dir)?;
+    fs::write(
+        extension_dir.join("manifest.json"),
+        format!("{{\"name\":\"{EXTENSION_NAME}\"}}"),
+    )?;
+    fs::write(extension_dir.join("driver.mjs"), driver_source)?;
+
+    let config = ScrapeConfig {
+        login_name: LOGIN_NAME.to_string(),
+        extension_name: EXTENSION_NAME.to_string(),
+        ledger_dir: ledger_dir.clone(),
+        profile_override: Some(sandbox.path().join("profile")),
+        prompt_overrides: app_lib::scrape::js_api::PromptOverrides::new(),
+        headless: false,
+        prompt_requires_override: false,
+        prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
+    };
+    scrape::run_scrape(config)?;
+
+    Ok(ledger_dir
+        .join("cache")
+        .join("extensions")
+        .join(EXTENSION_NAME)
+        .join("output"))
+}
+
 impl Drop for TestSandbox {
     fn drop(&mut self) {
         let _ = fs::remove_dir_all(&self.root);
@@ -1001,6 +1168,8 @@ fn scrape_smoke_driver_writes_output() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1064,6 +1233,8 @@ fn scrape_popup_wait_for_event_switches_tab() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1112,6 +1283,8 @@ fn scrape_screenshot_api_returns_bytes_and_writes_paths() -> Result<(), Box<dyn
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1186,6 +1359,8 @@ fn scrape_popup_waiter_rejects_when_popup_closes() -> Result<(), Box<dyn Error>>
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1202,6 +1377,38 @@ fn scrape_popup_waiter_rejects_when_popup_closes() -> Result<(), Box<dyn Error>>
     Ok(())
 }
 
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn scrape_popup_downloads_and_closes() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping popup download test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("scrape-popup-download")?;
+    let popup_url = write_fixture_file(
+        &sandbox,
+        "popup-download.html",
+        "<!doctype html><html><body><a id=\"download\" href=\"data:text/plain,hello%20popup\" download=\"statement.txt\">Download</a></body></html>",
+    )?;
+    let opener_html = format!(
+        "<!doctype html><html><body><button id=\"open\" type=\"button\">Open Popup</button><script>document.getElementById('open').addEventListener('click', () => window.open({}, '_blank'));</script></body></html>",
+        serde_json::to_string(&popup_url)?,
+    );
+    let opener_url = write_fixture_file(&sandbox, "popup-download-opener.html", &opener_html)?;
+    let driver = POPUP_DOWNLOAD_DRIVER_SOURCE
+        .replace("__OPENER_URL__", &serde_json::to_string(&opener_url)?);
+
+    let output_dir = run_driver(&sandbox, &driver)?;
+    assert_eq!(fs::read(output_dir.join("popup_download.bin"))?, b"ok");
+    assert_eq!(
+        fs::read_to_string(output_dir.join("popup_download.txt"))?,
+        "hello popup"
+    );
+
+    Ok(())
+}
+
 #[test]
 #[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
 fn scrape_click_reports_overlay_interception() -> Result<(), Box<dyn Error>> {
@@ -1237,6 +1444,8 @@ fn scrape_click_reports_overlay_interception() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1296,6 +1505,8 @@ fn scrape_goto_handles_same_url_and_hash_navigation() -> Result<(), Box<dyn Erro
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1360,6 +1571,8 @@ fn scrape_frame_methods_switch_context() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1418,6 +1631,8 @@ fn scrape_frame_methods_switch_context_cross_origin_oopif() -> Result<(), Box<dy
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     scrape::run_scrape(config)?;
@@ -1434,6 +1649,98 @@ fn scrape_frame_methods_switch_context_cross_origin_oopif() -> Result<(), Box<dy
     Ok(())
 }
 
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn scrape_frame_handle_acts_in_its_frame() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping frame handle test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("scrape-frame-handle")?;
+    let child_url = write_fixture_file(
+        &sandbox,
+        "frame-handle-child.html",
+        "<!doctype html><html><body><input id=\"user\"><button id=\"submit\" onclick=\"document.getElementById('result').textContent = 'submitted ' + document.getElementById('user').value\">OK</button><div id=\"result\"></div></body></html>",
+    )?;
+    let frame_html = format!(
+        "<!doctype html><html><body><div id=\"main\">Main</div><iframe name=\"logonbox\" src={}></iframe></body></html>",
+        serde_json::to_string(&child_url)?,
+    );
+    let frame_url = write_fixture_file(&sandbox, "frame-handle.html", &frame_html)?;
+    let driver =
+        FRAME_HANDLE_DRIVER_SOURCE.replace("__FRAME_URL__", &serde_json::to_string(&frame_url)?);
+
+    let output_dir = run_driver(&sandbox, &driver)?;
+    assert_eq!(fs::read(output_dir.join("frame_handle.bin"))?, b"ok");
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn scrape_frame_locator_resolves_nested_iframes() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping nested frame locator test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("scrape-nested-frame-locator")?;
+    let inner_url = write_fixture_file(
+        &sandbox,
+        "nested-inner.html",
+        "<!doctype html><html><body><input id=\"code\"><button id=\"verify\" onclick=\"const done = document.createElement('div'); done.id = 'verified'; done.textContent = 'verified ' + document.getElementById('code').value; document.body.append(done)\">Verify</button></body></html>",
+    )?;
+    let outer_html = format!(
+        "<!doctype html><html><body><div>Outer</div><iframe name=\"inner\" src={}></iframe></body></html>",
+        serde_json::to_string(&inner_url)?,
+    );
+    let outer_url = write_fixture_file(&sandbox, "nested-outer.html", &outer_html)?;
+    let main_html = format!(
+        "<!doctype html><html><body><div id=\"main\">Main</div><iframe name=\"outer\" src={}></iframe></body></html>",
+        serde_json::to_string(&outer_url)?,
+    );
+    let main_url = write_fixture_file(&sandbox, "nested-main.html", &main_html)?;
+    let driver = NESTED_FRAME_LOCATOR_DRIVER_SOURCE
+        .replace("__FRAME_URL__", &serde_json::to_string(&main_url)?);
+
+    let output_dir = run_driver(&sandbox, &driver)?;
+    assert_eq!(
+        fs::read(output_dir.join("nested_frame_locator.bin"))?,
+        b"ok"
+    );
+
+    Ok(())
+}
+
+#[test]
+#[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
+fn scrape_xpath_selectors_work_in_every_entry_point() -> Result<(), Box<dyn Error>> {
+    if scrape::browser::find_chrome_binary().is_err() {
+        eprintln!("skipping xpath selector test: Chrome/Edge binary not found");
+        return Ok(());
+    }
+
+    let sandbox = TestSandbox::new("scrape-xpath")?;
+    let child_url = write_fixture_file(
+        &sandbox,
+        "xpath-child.html",
+        "<!doctype html><html><body><input id=\"user\" name=\"user\"></body></html>",
+    )?;
+    let main_html = format!(
+        "<!doctype html><html><body><h1 id=\"title\">Activity</h1><table id=\"activity\"><tr><th>Date</th><th>Amount</th></tr><tr><td>2025-01-02</td><td>-4.50</td></tr><tr><td>2025-01-03</td><td>-12.00</td></tr></table><iframe name=\"logonbox\" src={}></iframe></body></html>",
+        serde_json::to_string(&child_url)?,
+    );
+    let main_url = write_fixture_file(&sandbox, "xpath-main.html", &main_html)?;
+    let driver =
+        XPATH_SELECTOR_DRIVER_SOURCE.replace("__FRAME_URL__", &serde_json::to_string(&main_url)?);
+
+    let output_dir = run_driver(&sandbox, &driver)?;
+    assert_eq!(fs::read(output_dir.join("xpath_selectors.bin"))?, b"ok");
+
+    Ok(())
+}
+
 #[test]
 #[ignore = "requires a local Chrome/Edge install; run periodically with --ignored"]
 fn scrape_network_request_response_api_works() -> Result<(), Box<dyn Error>> {
@@ -1474,6 +1781,8 @@ fn scrape_network_request_response_api_works() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     eprintln!("network scrape sandbox: {}", sandbox.path().display());
@@ -1550,6 +1859,8 @@ fn scrape_network_matchers_work() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     eprintln!(
@@ -1629,6 +1940,8 @@ fn scrape_network_wait_for_event_aliases_work() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     let (result_tx, result_rx) = mpsc::channel();
@@ -1704,6 +2017,8 @@ fn scrape_network_wait_for_event_options_work() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     let (result_tx, result_rx) = mpsc::channel();
@@ -1777,6 +2092,8 @@ fn scrape_network_lifecycle_events_work() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     let (result_tx, result_rx) = mpsc::channel();
@@ -1854,6 +2171,8 @@ fn scrape_network_redirects_work() -> Result<(), Box<dyn Error>> {
         headless: false,
         prompt_requires_override: false,
         prompt_ui_handler: None,
+        mode: scrape::ScrapeMode::Accounts,
+        report: None,
     };
 
     let (result_tx, result_rx) = mpsc::channel();
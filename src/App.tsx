@@ -28,6 +28,7 @@ import {
     getLoginAccountUnposted,
     suggestCategories,
     postLoginAccountEntry,
+    postLoginAccountEntrySplit,
     postLoginAccountTransfer,
     type CategoryResult,
     openLedger,
@@ -37,7 +38,21 @@ import {
     type AmountTotal,
     type LedgerView,
     setLoginAccount,
+    type Alert,
+    acknowledgeAlert,
+    type Digest,
+    generateDueDigests,
+    type PromptKind,
+    getRequiredPrompts,
+    type RequiredPrompt,
+    verifyScrapePrerequisites,
+    type UncommittedChanges,
+    getUncommittedChanges,
+    commitPendingChanges,
+    type UnknownAccountId,
+    assignAccountId,
 } from './tauri-commands.ts';
+import { describePreflightFailures } from './scrapeLog.ts';
 import { PipelineTab } from './tabs/PipelineTab.tsx';
 import { ReportsTab } from './tabs/ReportsTab.tsx';
 import { ScrapeTab } from './tabs/ScrapeTab.tsx';
@@ -82,12 +97,33 @@ function App() {
     );
     const [autoEtlStatus, setAutoEtlStatus] = useState<string | null>(null);
     const [autoEtlErrors, setAutoEtlErrors] = useState<string | null>(null);
+    const [raisedAlerts, setRaisedAlerts] = useState<Alert[]>([]);
+    const [readyDigests, setReadyDigests] = useState<Digest[]>([]);
+    const [uncommitted, setUncommitted] = useState<UncommittedChanges | null>(
+        null,
+    );
     const [promptRequest, setPromptRequest] = useState<{
         message: string;
+        type: PromptKind;
+        choices: string[];
+        /** Heading; defaults to "Scraper prompt". */
+        title?: string;
+        /** Receives the answer instead of the running scrape driver. */
+        onAnswer?: (answer: string | null) => void;
     } | null>(null);
     const [scrapeLogVersion, setScrapeLogVersion] = useState(0);
     const [loginAccounts, setLoginAccounts] = useState<LoginAccountRef[]>([]);
 
+    function answerPromptRequest(answer: string | null) {
+        const onAnswer = promptRequest?.onAnswer;
+        setPromptRequest(null);
+        if (onAnswer !== undefined) {
+            onAnswer(answer);
+        } else {
+            void invoke('submit_prompt_answer', { answer });
+        }
+    }
+
     function handleSelectAccount(accountName: string) {
         setTransactionsTabSession((current) => ({
             ...current,
@@ -329,10 +365,108 @@ function App() {
     // Listen for prompt requests from the Rust scrape driver and surface them
     // as a blocking modal so the user can supply MFA codes etc.
     useEffect(() => {
-        const unlisten = listen<{ message: string }>(
-            'refreshmint://prompt-requested',
+        const unlisten = listen<{
+            message: string;
+            type: PromptKind;
+            choices?: string[];
+        }>('refreshmint://prompt-requested', (event) => {
+            setPromptRequest((current) => {
+                // A running scrape takes priority over pre-run questions,
+                // which are then left to be asked during the scrape.
+                current?.onAnswer?.(null);
+                return {
+                    message: event.payload.message,
+                    type: event.payload.type,
+                    choices: event.payload.choices ?? [],
+                };
+            });
+        });
+        return () => {
+            unlisten
+                .then((fn) => {
+                    fn();
+                })
+                .catch(() => {});
+        };
+    }, []);
+
+    // Surface alerts raised by rules after extraction.
+    useEffect(() => {
+        const unlisten = listen<Alert[]>(
+            'refreshmint://alerts-raised',
+            (event) => {
+                setRaisedAlerts((current) => [...current, ...event.payload]);
+            },
+        );
+        return () => {
+            unlisten
+                .then((fn) => {
+                    fn();
+                })
+                .catch(() => {});
+        };
+    }, []);
+
+    // Ask which label an account number extraction has not seen belongs to.
+    // Unanswered numbers stay queued for the login.
+    useEffect(() => {
+        if (ledger === null) return;
+        const ledgerPath = ledger.path;
+        const unlisten = listen<UnknownAccountId[]>(
+            'refreshmint://unknown-account-ids',
+            (event) => {
+                for (const unknown of event.payload) {
+                    void getLoginConfig(ledgerPath, unknown.loginName).then(
+                        (config) => {
+                            const labels = Object.keys(
+                                normalizeLoginConfig(config).accounts,
+                            ).filter((label) => label !== '_default');
+                            setPromptRequest((current) => {
+                                if (current !== null) {
+                                    return current;
+                                }
+                                return {
+                                    message: `Which account of ${unknown.loginName} is ${unknown.accountId}, seen in ${unknown.document}?`,
+                                    type: 'choice',
+                                    choices: labels,
+                                    title: 'New account number',
+                                    onAnswer: (label) => {
+                                        if (label === null || label === '') {
+                                            return;
+                                        }
+                                        void assignAccountId(
+                                            ledgerPath,
+                                            unknown.loginName,
+                                            unknown.accountId,
+                                            label,
+                                        ).catch((error: unknown) => {
+                                            setAutoEtlErrors(
+                                                `Assigning ${unknown.accountId} failed: ${String(error)}`,
+                                            );
+                                        });
+                                    },
+                                };
+                            });
+                        },
+                    );
+                }
+            },
+        );
+        return () => {
+            unlisten
+                .then((fn) => {
+                    fn();
+                })
+                .catch(() => {});
+        };
+    }, [ledger]);
+
+    // Surface spending digests as they are generated.
+    useEffect(() => {
+        const unlisten = listen<Digest[]>(
+            'refreshmint://digests-ready',
             (event) => {
-                setPromptRequest({ message: event.payload.message });
+                setReadyDigests((current) => [...current, ...event.payload]);
             },
         );
         return () => {
@@ -344,6 +478,32 @@ function App() {
         };
     }, []);
 
+    // Show a save button while a batched or manual commit policy holds
+    // changes back. Re-check after every ledger refresh and once a minute.
+    useEffect(() => {
+        setUncommitted(null);
+        if (ledger === null) return;
+        const ledgerPath = ledger.path;
+        const check = () => {
+            getUncommittedChanges(ledgerPath)
+                .then(setUncommitted)
+                .catch(() => {});
+        };
+        check();
+        const id = window.setInterval(check, 60 * 1000);
+        return () => {
+            window.clearInterval(id);
+        };
+    }, [ledger]);
+
+    // Generate weekly/monthly digests that came due while the app was closed.
+    useEffect(() => {
+        setReadyDigests([]);
+        if (ledgerPath !== null) {
+            void generateDueDigests(ledgerPath).catch(() => {});
+        }
+    }, [ledgerPath]);
+
     // Keep autoEtlForLoginRef current so Effect 2's async chain always sees
     // the latest loginAccounts and loginConfigsByName without adding them to
     // Effect 2's dependency array.
@@ -426,6 +586,34 @@ function App() {
                                     );
                                     posted = true;
                                 }
+                            } else if (glAccount && suggestion?.loanSplit) {
+                                await postLoginAccountEntrySplit(
+                                    ledgerPath,
+                                    loginName,
+                                    label,
+                                    entry.id,
+                                    suggestion.loanSplit.counterparts,
+                                );
+                                posted = true;
+                            } else if (glAccount && suggestion?.paycheckSplit) {
+                                await postLoginAccountEntrySplit(
+                                    ledgerPath,
+                                    loginName,
+                                    label,
+                                    entry.id,
+                                    suggestion.paycheckSplit.counterparts,
+                                );
+                                posted = true;
+                            } else if (glAccount && suggestion?.invoiceMatch) {
+                                await postLoginAccountEntry(
+                                    ledgerPath,
+                                    loginName,
+                                    label,
+                                    entry.id,
+                                    suggestion.invoiceMatch.receivableAccount,
+                                    null,
+                                );
+                                posted = true;
                             } else if (glAccount) {
                                 await postLoginAccountEntry(
                                     ledgerPath,
@@ -481,7 +669,46 @@ function App() {
         setAutoScrapeActive(loginName);
         setAutoScrapeQueue(rest);
         const timestamp = new Date().toISOString();
-        void runScrapeForLogin(ledger.path, loginName, 'auto', headlessScrape)
+        const askBeforeScrape = (prompt: RequiredPrompt) =>
+            new Promise<string | null>((resolve) => {
+                setPromptRequest((current) => {
+                    // Never cover a prompt from a scrape that is running.
+                    if (current !== null) {
+                        resolve(null);
+                        return current;
+                    }
+                    return {
+                        message: prompt.message,
+                        type: prompt.type,
+                        choices: prompt.choices ?? [],
+                        title: `Before scraping ${loginName}`,
+                        onAnswer: resolve,
+                    };
+                });
+            });
+        // Check secrets, extension, and profile before a browser opens.
+        void verifyScrapePrerequisites(ledger.path, loginName)
+            .then((preflight) => {
+                if (!preflight.ready) {
+                    throw new Error(
+                        `preflight failed: ${describePreflightFailures(preflight)}`,
+                    );
+                }
+                return collectLikelyPromptAnswers(
+                    ledger.path,
+                    loginName,
+                    askBeforeScrape,
+                );
+            })
+            .then((prompts) =>
+                runScrapeForLogin(
+                    ledger.path,
+                    loginName,
+                    'auto',
+                    headlessScrape,
+                    prompts,
+                ),
+            )
             .then(async () => {
                 localStorage.setItem(`lastScrape:${loginName}`, timestamp);
                 await autoEtlForLoginRef.current?.(loginName);
@@ -1217,6 +1444,89 @@ function App() {
                         </div>
                     )}
 
+                    {raisedAlerts.length > 0 && ledger !== null && (
+                        <div className="auto-scrape-banner">
+                            <span>
+                                {raisedAlerts.length === 1
+                                    ? `Alert: ${raisedAlerts[0]?.message ?? ''}`
+                                    : `${raisedAlerts.length} new alerts: ${raisedAlerts[0]?.message ?? ''}`}
+                            </span>
+                            <button
+                                type="button"
+                                className="ghost-button"
+                                onClick={() => {
+                                    const ledgerPath = ledger.path;
+                                    const ids = raisedAlerts.map((a) => a.id);
+                                    setRaisedAlerts([]);
+                                    void Promise.all(
+                                        ids.map((id) =>
+                                            acknowledgeAlert(ledgerPath, id),
+                                        ),
+                                    ).catch(() => {});
+                                }}
+                            >
+                                Acknowledge
+                            </button>
+                        </div>
+                    )}
+
+                    {uncommitted !== null &&
+                        uncommitted.policy.mode !== 'perOperation' &&
+                        uncommitted.paths.length > 0 && (
+                            <div className="auto-scrape-banner">
+                                <span>
+                                    {uncommitted.operations.length === 1
+                                        ? `Unsaved change: ${uncommitted.operations[0] ?? ''}`
+                                        : `${uncommitted.operations.length} unsaved changes`}
+                                </span>
+                                <button
+                                    type="button"
+                                    className="ghost-button"
+                                    onClick={() => {
+                                        void commitPendingChanges(
+                                            ledger.path,
+                                            '',
+                                        )
+                                            .then(setUncommitted)
+                                            .catch((error: unknown) => {
+                                                setAutoEtlErrors(
+                                                    `Save failed: ${String(error)}`,
+                                                );
+                                            });
+                                    }}
+                                >
+                                    Save
+                                </button>
+                            </div>
+                        )}
+
+                    {readyDigests.map((digest) => (
+                        <div key={digest.id} className="auto-scrape-banner">
+                            <span>
+                                {`${digest.cadence === 'weekly' ? 'Weekly' : 'Monthly'} digest from ${digest.periodStart}: spent ${digest.totalSpending.toFixed(2)} ${digest.commodity}`}
+                                {digest.topCategories[0] !== undefined &&
+                                    `, most on ${digest.topCategories[0].account}`}
+                                {digest.unusualSpending.length > 0 &&
+                                    `, ${digest.unusualSpending.length} above usual`}
+                                {digest.unposted.length > 0 &&
+                                    `, ${digest.unposted.reduce((sum, u) => sum + u.count, 0)} unposted`}
+                            </span>
+                            <button
+                                type="button"
+                                className="ghost-button"
+                                onClick={() => {
+                                    setReadyDigests((current) =>
+                                        current.filter(
+                                            (d) => d.id !== digest.id,
+                                        ),
+                                    );
+                                }}
+                            >
+                                Dismiss
+                            </button>
+                        </div>
+                    ))}
+
                     {activeTab === 'accounts' ? (
                         <div className="table-wrap">
                             <AccountsTable
@@ -1408,20 +1718,44 @@ function App() {
                         role="dialog"
                         aria-modal="true"
                     >
-                        <h3>Scraper prompt</h3>
+                        <h3>{promptRequest.title ?? 'Scraper prompt'}</h3>
                         <p>{promptRequest.message}</p>
+                        {promptRequest.choices.length > 0 ? (
+                            <div className="txn-actions">
+                                {promptRequest.choices.map((choice) => (
+                                    <button
+                                        key={choice}
+                                        type="button"
+                                        className="secondary-button"
+                                        onClick={() => {
+                                            answerPromptRequest(choice);
+                                        }}
+                                    >
+                                        {choice}
+                                    </button>
+                                ))}
+                            </div>
+                        ) : null}
                         <input
                             ref={promptInputRef}
                             type="text"
+                            hidden={promptRequest.choices.length > 0}
+                            inputMode={
+                                promptRequest.type === 'otp'
+                                    ? 'numeric'
+                                    : undefined
+                            }
+                            autoComplete={
+                                promptRequest.type === 'otp'
+                                    ? 'one-time-code'
+                                    : 'off'
+                            }
                             autoFocus
                             onKeyDown={(e) => {
                                 if (e.key === 'Enter') {
-                                    const val =
-                                        promptInputRef.current?.value ?? '';
-                                    setPromptRequest(null);
-                                    void invoke('submit_prompt_answer', {
-                                        answer: val,
-                                    });
+                                    answerPromptRequest(
+                                        promptInputRef.current?.value ?? '',
+                                    );
                                 }
                             }}
                         />
@@ -1429,13 +1763,11 @@ function App() {
                             <button
                                 type="button"
                                 className="primary-button"
+                                hidden={promptRequest.choices.length > 0}
                                 onClick={() => {
-                                    const val =
-                                        promptInputRef.current?.value ?? '';
-                                    setPromptRequest(null);
-                                    void invoke('submit_prompt_answer', {
-                                        answer: val,
-                                    });
+                                    answerPromptRequest(
+                                        promptInputRef.current?.value ?? '',
+                                    );
                                 }}
                             >
                                 Submit
@@ -1444,10 +1776,7 @@ function App() {
                                 type="button"
                                 className="ghost-button"
                                 onClick={() => {
-                                    setPromptRequest(null);
-                                    void invoke('submit_prompt_answer', {
-                                        answer: null,
-                                    });
+                                    answerPromptRequest(null);
                                 }}
                             >
                                 Cancel
@@ -1492,6 +1821,33 @@ function App() {
     );
 }
 
+/**
+ * Before an unattended scrape, ask for the answers recent runs of the login
+ * needed so the run does not stall mid-way. One-time codes are skipped since
+ * they are only sent after the scrape starts signing in.
+ */
+async function collectLikelyPromptAnswers(
+    ledgerPath: string,
+    loginName: string,
+    ask: (prompt: RequiredPrompt) => Promise<string | null>,
+): Promise<Record<string, string>> {
+    const answers: Record<string, string> = {};
+    let prompts: RequiredPrompt[];
+    try {
+        ({ prompts } = await getRequiredPrompts(ledgerPath, loginName));
+    } catch {
+        return answers;
+    }
+    for (const prompt of prompts) {
+        if (!prompt.likely || prompt.type === 'otp') continue;
+        const answer = await ask(prompt);
+        if (answer !== null && answer.trim().length > 0) {
+            answers[prompt.key ?? prompt.message] = answer;
+        }
+    }
+    return answers;
+}
+
 function normalizeStyle(style: AmountStyleHint | null) {
     if (style === null) {
         return { side: 'R' as const, spaced: true };
@@ -1,9 +1,74 @@
+import type { ScrapePreflight } from './tauri-commands.ts';
+
+/** Page context captured when a driver assertion failed. */
+export interface ScrapeAssertionFailure {
+    message: string;
+    target?: string;
+    expected?: string;
+    actual?: string;
+    url?: string;
+    screenshot?: string; // ledger-relative path
+    snapshotExcerpt?: string;
+}
+
+/** One host requested during a scrape. */
+export interface ObservedDomain {
+    domain: string;
+    requests: number;
+    blocked: number;
+    allowed: boolean;
+}
+
+export interface ScrapeNetworkReport {
+    allowedDomains?: string[];
+    observedDomains: ObservedDomain[];
+}
+
+/** A prompt the driver asked for; answers are never logged. */
+export interface ScrapePromptAsked {
+    key?: string;
+    message: string;
+    type: 'text' | 'otp' | 'choice';
+    choices?: string[];
+}
+
+/** The window the driver declared with `setSessionMetadata`. */
+export interface ScrapeSessionMetadata {
+    dateRangeStart?: string;
+    dateRangeEnd?: string;
+}
+
+/** A saved resource that looks like an error page or an empty export. */
+export interface ScrapeSuspectDocument {
+    filename: string;
+    reason: string;
+}
+
 export interface ScrapeLogEntry {
     loginName: string;
     timestamp: string; // ISO
     success: boolean;
     error?: string;
     source: 'manual' | 'auto';
+    failure?: ScrapeAssertionFailure;
+    network?: ScrapeNetworkReport;
+    promptsAsked?: ScrapePromptAsked[];
+    sessionMetadata?: ScrapeSessionMetadata;
+    suspectDocuments?: ScrapeSuspectDocument[];
+    /** Prefix of this run's files from `listScrapeArtifacts`. */
+    scrapeSessionId?: string;
+}
+
+/** The problem and fix of each failed preflight check, for a status line. */
+export function describePreflightFailures(preflight: ScrapePreflight): string {
+    return preflight.checks
+        .filter((check) => check.status === 'failed')
+        .map((check) =>
+            [check.problem, check.fix]
+                .filter((part) => part !== null)
+                .join('. '),
+        )
+        .join(' ');
 }
 
 const MAX_PER_LOGIN = 100;
@@ -9,6 +9,7 @@ import {
 import { listen } from '@tauri-apps/api/event';
 import {
     type AccountJournalEntry,
+    type AccountType,
     type CategoryResult,
     getLoginAccountJournal,
     getLoginAccountUnposted,
@@ -28,6 +29,7 @@ import {
     postLoginAccountTransfer,
     runLoginAccountExtraction,
     setLoginAccount,
+    setLoginAccountType,
     startLockMetadataWatch,
     stopLockMetadataWatch,
     suggestCategories,
@@ -41,6 +43,7 @@ import {
     type PipelineBulkStats,
     type PipelineTabSession,
     type SplitDraftRow,
+    ACCOUNT_TYPES,
     createEmptyPipelineBulkSummary,
     normalizeLoginConfig,
     suggestGlAccountName,
@@ -63,6 +66,34 @@ interface PipelineTabProps {
     ) => void;
 }
 
+/** Tooltip text saying why a category was suggested. */
+function describeCategorySuggestion(suggestion: CategoryResult): string {
+    const explanation = suggestion.explanation;
+    if (!explanation) {
+        return '';
+    }
+    const reasons = [
+        `${Math.round(explanation.classifierConfidence * 100)}% confidence`,
+    ];
+    if (explanation.matchedRule) {
+        reasons.push(`rule "${explanation.matchedRule.keyword}"`);
+    }
+    if (explanation.payeeHistory) {
+        const { payee, suggestedCount, totalCount } = explanation.payeeHistory;
+        reasons.push(
+            `${payee}: ${suggestedCount} of ${totalCount} posted here`,
+        );
+    }
+    if (explanation.mcc) {
+        const { code, category } = explanation.mcc;
+        reasons.push(`MCC ${code} (${category})`);
+    }
+    if (explanation.feedbackCount > 0) {
+        reasons.push(`chosen ${explanation.feedbackCount}× before`);
+    }
+    return reasons.join('; ');
+}
+
 export function PipelineTab({
     ledger,
     isActive,
@@ -98,6 +129,7 @@ export function PipelineTab({
     );
     const [documentRows, setDocumentRows] = useState<string[][]>([]);
     const [isLoadingDocumentRows, setIsLoadingDocumentRows] = useState(false);
+    const [documentRowsHasMore, setDocumentRowsHasMore] = useState(false);
     const [isLoadingDocuments, setIsLoadingDocuments] = useState(false);
     const [lightboxSrc, setLightboxSrc] = useState<string | null>(null);
     const [lightboxFilename, setLightboxFilename] = useState<string | null>(
@@ -362,12 +394,21 @@ export function PipelineTab({
     const pipelineGlAccount = useMemo<string | null>(() => {
         if (!selectedLoginAccount) return null;
         return (
-            loginConfigsByName[selectedLoginAccount.loginName]?.accounts[
+            loginConfigsByName[selectedLoginAccount.loginName]?.accounts?.[
                 selectedLoginAccount.label
             ]?.glAccount ?? null
         );
     }, [selectedLoginAccount, loginConfigsByName]);
 
+    const pipelineAccountType = useMemo<AccountType | null>(() => {
+        if (!selectedLoginAccount) return null;
+        return (
+            loginConfigsByName[selectedLoginAccount.loginName]?.accounts?.[
+                selectedLoginAccount.label
+            ]?.accountType ?? null
+        );
+    }, [selectedLoginAccount, loginConfigsByName]);
+
     const glLockStatus = lockStatusSnapshot?.gl ?? {
         locked: false,
         metadata: null,
@@ -471,6 +512,7 @@ export function PipelineTab({
         }
         setEvidenceRowsDocument('');
         setDocumentRows([]);
+        setDocumentRowsHasMore(false);
         setPipelineSelectedEntryIds(new Set());
         setPipelineCategorySuggestions({});
         setPipelineGlAccountDraft(
@@ -599,8 +641,11 @@ export function PipelineTab({
             filename.toLowerCase().endsWith(ext),
         );
     }
-    function isCsvDocument(filename: string): boolean {
-        return filename.toLowerCase().endsWith('.csv');
+    // Documents the row preview can show. Keep aligned with
+    // read_login_account_document_csv_rows in src-tauri/src/extract.rs.
+    function isTabularDocument(filename: string): boolean {
+        const lower = filename.toLowerCase();
+        return lower.endsWith('.csv') || lower.endsWith('.xlsx');
     }
     function isPdfDocument(filename: string): boolean {
         return filename.toLowerCase().endsWith('.pdf');
@@ -628,7 +673,7 @@ export function PipelineTab({
             } finally {
                 setLightboxLoading(false);
             }
-        } else if (isCsvDocument(doc.filename)) {
+        } else if (isTabularDocument(doc.filename)) {
             await handleLoadDocumentRows(doc.filename);
             setPipelineSubTab('evidence-rows');
         } else {
@@ -658,20 +703,39 @@ export function PipelineTab({
         }
         setIsLoadingDocumentRows(true);
         try {
-            const rows = await readLoginAccountDocumentRows(
+            const page = await readLoginAccountDocumentRows(
                 ledgerPath,
                 selectedLoginAccount.loginName,
                 selectedLoginAccount.label,
                 documentName,
             );
-            setDocumentRows(rows);
+            setDocumentRows(page.rows);
+            setDocumentRowsHasMore(page.hasMore);
         } catch {
             setDocumentRows([]);
+            setDocumentRowsHasMore(false);
         } finally {
             setIsLoadingDocumentRows(false);
         }
     }
 
+    async function handleLoadMoreDocumentRows() {
+        if (!evidenceRowsDocument || !selectedLoginAccount) return;
+        try {
+            const page = await readLoginAccountDocumentRows(
+                ledgerPath,
+                selectedLoginAccount.loginName,
+                selectedLoginAccount.label,
+                evidenceRowsDocument,
+                { offset: documentRows.length },
+            );
+            setDocumentRows([...documentRows, ...page.rows]);
+            setDocumentRowsHasMore(page.hasMore);
+        } catch (e) {
+            setPipelineStatus(`Failed to load more rows: ${String(e)}`);
+        }
+    }
+
     async function handlePipelineExtraction(documentName: string) {
         if (selectedLoginAccount === null || !documentName) return;
         const { loginName, label } = selectedLoginAccount;
@@ -748,6 +812,29 @@ export function PipelineTab({
         }
     }
 
+    async function handleSavePipelineAccountType(
+        accountType: AccountType | null,
+    ) {
+        if (!selectedLoginAccount) return;
+        const { loginName, label } = selectedLoginAccount;
+        try {
+            await setLoginAccountType(
+                ledgerPath,
+                loginName,
+                label,
+                accountType,
+            );
+            onLoginConfigChanged();
+            if (pipelineGlAccount === null || pipelineGlAccount === '') {
+                setPipelineGlAccountDraft(
+                    suggestGlAccountName(label, accountType),
+                );
+            }
+        } catch (error) {
+            setPipelineStatus(`Failed to save account type: ${String(error)}`);
+        }
+    }
+
     async function doPipelinePostForAccount(
         loginName: string,
         label: string,
@@ -778,6 +865,40 @@ export function PipelineTab({
             return `Transfer posted: ${entryId} ↔ ${suggestion.transferMatch.entryId} (${glId})`;
         }
 
+        if (suggestion?.loanSplit) {
+            const glId = await postLoginAccountEntrySplit(
+                ledgerPath,
+                loginName,
+                label,
+                entryId,
+                suggestion.loanSplit.counterparts,
+            );
+            return `Loan payment ${suggestion.loanSplit.paymentNumber} split: ${entryId} to ${glId}`;
+        }
+
+        if (suggestion?.paycheckSplit) {
+            const glId = await postLoginAccountEntrySplit(
+                ledgerPath,
+                loginName,
+                label,
+                entryId,
+                suggestion.paycheckSplit.counterparts,
+            );
+            return `Paycheck ${suggestion.paycheckSplit.payDate} split: ${entryId} to ${glId}`;
+        }
+
+        if (suggestion?.invoiceMatch) {
+            const glId = await postLoginAccountEntry(
+                ledgerPath,
+                loginName,
+                label,
+                entryId,
+                suggestion.invoiceMatch.receivableAccount,
+                null,
+            );
+            return `Invoice ${suggestion.invoiceMatch.number} payment: ${entryId} to ${glId}`;
+        }
+
         const glId = await postLoginAccountEntry(
             ledgerPath,
             loginName,
@@ -1529,9 +1650,7 @@ export function PipelineTab({
                                     </option>
                                     {documents
                                         .filter((d) =>
-                                            d.filename
-                                                .toLowerCase()
-                                                .endsWith('.csv'),
+                                            isTabularDocument(d.filename),
                                         )
                                         .map((d) => (
                                             <option
@@ -1621,6 +1740,16 @@ export function PipelineTab({
                                         })}
                                     </tbody>
                                 </table>
+                                {documentRowsHasMore && (
+                                    <button
+                                        type="button"
+                                        onClick={() => {
+                                            void handleLoadMoreDocumentRows();
+                                        }}
+                                    >
+                                        Load more rows
+                                    </button>
+                                )}
                             </div>
                         )}
                     </>
@@ -1645,6 +1774,7 @@ export function PipelineTab({
                                                 type="text"
                                                 placeholder={suggestGlAccountName(
                                                     selectedLoginAccount.label,
+                                                    pipelineAccountType,
                                                 )}
                                                 value={pipelineGlAccountDraft}
                                                 onChange={(e) => {
@@ -1672,6 +1802,31 @@ export function PipelineTab({
                                             </button>
                                         </>
                                     )}
+                                    <span className="pipeline-gl-account-label">
+                                        Type:
+                                    </span>
+                                    <select
+                                        value={pipelineAccountType ?? ''}
+                                        disabled={selectedLoginLocked}
+                                        onChange={(e) => {
+                                            const value = e.target.value;
+                                            void handleSavePipelineAccountType(
+                                                value === ''
+                                                    ? null
+                                                    : (value as AccountType),
+                                            );
+                                        }}
+                                    >
+                                        <option value="">Unspecified</option>
+                                        {ACCOUNT_TYPES.map((accountType) => (
+                                            <option
+                                                key={accountType}
+                                                value={accountType}
+                                            >
+                                                {accountType}
+                                            </option>
+                                        ))}
+                                    </select>
                                 </div>
                                 <div className="pipeline-actions">
                                     <button
@@ -1848,6 +2003,19 @@ export function PipelineTab({
                                                                             posted
                                                                         </span>
                                                                     )}
+                                                                    {isUnposted &&
+                                                                        suggestion?.suggested && (
+                                                                            <span
+                                                                                className="status-chip"
+                                                                                title={describeCategorySuggestion(
+                                                                                    suggestion,
+                                                                                )}
+                                                                            >
+                                                                                {
+                                                                                    suggestion.suggested
+                                                                                }
+                                                                            </span>
+                                                                        )}
                                                                 </div>
                                                             </td>
                                                             <td>
@@ -1885,19 +2053,40 @@ export function PipelineTab({
                                                                                     selectedLoginLocked
                                                                                 }
                                                                                 onClick={() => {
+                                                                                    const suggestedLegs =
+                                                                                        suggestion
+                                                                                            ?.loanSplit
+                                                                                            ?.counterparts ??
+                                                                                        suggestion
+                                                                                            ?.paycheckSplit
+                                                                                            ?.counterparts ??
+                                                                                        null;
                                                                                     setSplitDraftRows(
-                                                                                        [
-                                                                                            {
-                                                                                                account:
-                                                                                                    '',
-                                                                                                amount: '',
-                                                                                            },
-                                                                                            {
-                                                                                                account:
-                                                                                                    '',
-                                                                                                amount: '',
-                                                                                            },
-                                                                                        ],
+                                                                                        suggestedLegs !==
+                                                                                            null
+                                                                                            ? suggestedLegs.map(
+                                                                                                  (
+                                                                                                      leg,
+                                                                                                  ) => ({
+                                                                                                      account:
+                                                                                                          leg.account,
+                                                                                                      amount:
+                                                                                                          leg.amount ??
+                                                                                                          '',
+                                                                                                  }),
+                                                                                              )
+                                                                                            : [
+                                                                                                  {
+                                                                                                      account:
+                                                                                                          '',
+                                                                                                      amount: '',
+                                                                                                  },
+                                                                                                  {
+                                                                                                      account:
+                                                                                                          '',
+                                                                                                      amount: '',
+                                                                                                  },
+                                                                                              ],
                                                                                     );
                                                                                     setSplitModalEntryId(
                                                                                         entry.id,
@@ -56,6 +56,7 @@ export function ReportsTab({ ledger, accounts }: Props) {
     const [realOnly, setRealOnly] = useState(false);
     const [showEmpty, setShowEmpty] = useState(false);
     const [depth, setDepth] = useState('');
+    const [grouped, setGrouped] = useState(false);
 
     // Valuation
     const [valueCost, setValueCost] = useState(false);
@@ -216,14 +217,14 @@ export function ReportsTab({ ledger, accounts }: Props) {
         setResult(null);
         try {
             const args = buildArgs();
-            const res = await runHledgerReport(ledger, command, args);
+            const res = await runHledgerReport(ledger, command, args, grouped);
             setResult(res);
         } catch (e) {
             setError(String(e));
         } finally {
             setRunning(false);
         }
-    }, [ledger, command, buildArgs]);
+    }, [ledger, command, buildArgs, grouped]);
 
     const isBalanceFamily = BALANCE_FAMILY.includes(command);
     const isRegisterFamily = REGISTER_FAMILY.includes(command);
@@ -484,6 +485,16 @@ export function ReportsTab({ ledger, accounts }: Props) {
                                 />
                                 <span>Show empty (-E)</span>
                             </label>
+                            <label className="checkbox-field">
+                                <input
+                                    type="checkbox"
+                                    checked={grouped}
+                                    onChange={(e) => {
+                                        setGrouped(e.target.checked);
+                                    }}
+                                />
+                                <span>Report groups</span>
+                            </label>
                             <label className="checkbox-field">
                                 <span>Depth</span>
                                 <input
@@ -11,12 +11,15 @@ import {
     type LedgerView,
     type LoginConfig,
     type MigrationOutcome,
+    type RequiredPrompts,
+    cancelJob,
     createLogin,
     deleteLogin,
     deleteLoginAccount,
     getLoginAccountJournal,
     getLoginAccountUnposted,
     getLoginConfig,
+    getRequiredPrompts,
     getLoginUsername,
     getScrapeDebugSessionSocket,
     listLoginAccountDocuments,
@@ -39,8 +42,10 @@ import {
     setLoginUsername,
     startScrapeDebugSessionForLogin,
     stopScrapeDebugSession,
+    suggestLoginAccountGlAccount,
     syncLoginSecretsForExtension,
     unpostLoginAccountEntry,
+    verifyScrapePrerequisites,
 } from '../tauri-commands.ts';
 import {
     type LoginAccountMapping,
@@ -49,7 +54,10 @@ import {
     type TransferDraft,
     normalizeLoginConfig,
 } from '../types.ts';
-import { type ScrapeLogEntry } from '../scrapeLog.ts';
+import {
+    type ScrapeLogEntry,
+    describePreflightFailures,
+} from '../scrapeLog.ts';
 
 interface ScrapeTabProps {
     ledger: LedgerView | null;
@@ -130,6 +138,11 @@ export function ScrapeTab({
     const [scrapeLogEntries, setScrapeLogEntries] = useState<ScrapeLogEntry[]>(
         [],
     );
+    const [requiredPrompts, setRequiredPrompts] =
+        useState<RequiredPrompts | null>(null);
+    const [promptAnswers, setPromptAnswers] = useState<Record<string, string>>(
+        {},
+    );
     const [scrapeDebugSocket, setScrapeDebugSocket] = useState<string | null>(
         null,
     );
@@ -178,6 +191,7 @@ export function ScrapeTab({
     const [pipelineStatus, setPipelineStatus] = useState<string | null>(null);
     const [isLoadingDocuments, setIsLoadingDocuments] = useState(false);
     const [isRunningExtraction, setIsRunningExtraction] = useState(false);
+    const [extractionJobId, setExtractionJobId] = useState<number | null>(null);
     const [isLoadingAccountJournal, setIsLoadingAccountJournal] =
         useState(false);
     const [isLoadingUnposted, setIsLoadingUnposted] = useState(false);
@@ -193,6 +207,9 @@ export function ScrapeTab({
         entryId2: '',
     });
     const [isPostingTransfer, setIsPostingTransfer] = useState(false);
+    const [suggestedGlAccount, setSuggestedGlAccount] = useState<
+        string | null
+    >(null);
 
     const secretDomainRef = useRef('');
     const ledgerPath = ledger?.path ?? null;
@@ -337,6 +354,31 @@ export function ScrapeTab({
         };
     }, [activeScrapeLoginName, scrapeLogVersion, ledger]);
 
+    useEffect(() => {
+        setPromptAnswers({});
+    }, [activeScrapeLoginName]);
+
+    // Load the prompts the next scrape may ask so answers can be given up
+    // front. Reloaded after each scrape, since the log decides which are
+    // likely.
+    useEffect(() => {
+        if (activeScrapeLoginName === null || !ledger) {
+            setRequiredPrompts(null);
+            return;
+        }
+        let cancelled = false;
+        getRequiredPrompts(ledger.path, activeScrapeLoginName)
+            .then((required) => {
+                if (!cancelled) setRequiredPrompts(required);
+            })
+            .catch(() => {
+                if (!cancelled) setRequiredPrompts(null);
+            });
+        return () => {
+            cancelled = true;
+        };
+    }, [activeScrapeLoginName, ledger, scrapeLogVersion]);
+
     // List scrape extensions when ledger changes.
     useEffect(() => {
         if (ledgerPath === null) {
@@ -657,6 +699,40 @@ export function ScrapeTab({
         };
     }, [activeScrapeLoginName, ledgerPath]);
 
+    // Propose a GL account for the mapping being edited from the login's
+    // institution metadata.
+    useEffect(() => {
+        const label = (editingMappingLabel ?? '').trim();
+        if (
+            ledgerPath === null ||
+            activeScrapeLoginName === null ||
+            label.length === 0
+        ) {
+            setSuggestedGlAccount(null);
+            return;
+        }
+
+        let cancelled = false;
+        const timer = window.setTimeout(() => {
+            void suggestLoginAccountGlAccount(
+                ledgerPath,
+                activeScrapeLoginName,
+                label,
+            )
+                .then((suggestion) => {
+                    if (!cancelled) setSuggestedGlAccount(suggestion);
+                })
+                .catch(() => {
+                    if (!cancelled) setSuggestedGlAccount(null);
+                });
+        }, 100);
+
+        return () => {
+            cancelled = true;
+            window.clearTimeout(timer);
+        };
+    }, [activeScrapeLoginName, editingMappingLabel, ledgerPath]);
+
     // Load documents/journal/unposted for the currently selected scrape account mapping.
     useEffect(() => {
         if (ledgerPath === null) {
@@ -1456,6 +1532,14 @@ export function ScrapeTab({
                 loginName,
                 label,
                 documentNames,
+                {
+                    onStart: setExtractionJobId,
+                    onProgress: (progress) => {
+                        setPipelineStatus(
+                            `${progress.message} (${Math.min(progress.done + 1, progress.total)} of ${progress.total})...`,
+                        );
+                    },
+                },
             );
             await refreshAccountPipelineData(
                 loginName,
@@ -1469,6 +1553,7 @@ export function ScrapeTab({
             setPipelineStatus(`Extraction failed: ${String(error)}`);
         } finally {
             setIsRunningExtraction(false);
+            setExtractionJobId(null);
         }
     }
 
@@ -1649,15 +1734,40 @@ export function ScrapeTab({
         }
 
         setIsRunningScrape(true);
+        setScrapeStatus(`Checking ${loginName} before scraping...`);
+        try {
+            const preflight = await verifyScrapePrerequisites(
+                ledger.path,
+                loginName,
+            );
+            if (!preflight.ready) {
+                setScrapeStatus(
+                    `Scrape not started: ${describePreflightFailures(preflight)}`,
+                );
+                setIsRunningScrape(false);
+                return;
+            }
+        } catch (error) {
+            setScrapeStatus(`Preflight check failed: ${String(error)}`);
+            setIsRunningScrape(false);
+            return;
+        }
         setScrapeStatus(`Running scrape for ${loginName}...`);
         const timestamp = new Date().toISOString();
+        const prompts = Object.fromEntries(
+            Object.entries(promptAnswers).filter(
+                ([, value]) => value.trim().length > 0,
+            ),
+        );
         try {
             await runScrapeForLogin(
                 ledger.path,
                 loginName,
                 'manual',
                 headlessScrape,
+                prompts,
             );
+            setPromptAnswers({});
             localStorage.setItem(`lastScrape:${loginName}`, timestamp);
             setScrapeStatus(`Scrape completed for ${loginName}.`);
             await onScrapeComplete(loginName);
@@ -2215,7 +2325,7 @@ export function ScrapeTab({
                                                     value={
                                                         editingMappingGlAccountDraft
                                                     }
-                                                    placeholder="Assets:Bank:Checking (blank = ignored)"
+                                                    placeholder={`${suggestedGlAccount ?? 'Assets:Bank:Checking'} (blank = ignored)`}
                                                     list="scrape-account-options"
                                                     onChange={(event) => {
                                                         onEditingMappingGlAccountDraftChange(
@@ -2290,6 +2400,89 @@ export function ScrapeTab({
                         run scrape or start a debug session.
                     </p>
                 )}
+                {hasActiveScrapeLogin &&
+                requiredPrompts !== null &&
+                requiredPrompts.prompts.length > 0 ? (
+                    <fieldset>
+                        <legend>Prompt answers</legend>
+                        <p className="hint">
+                            Answers filled in here are used instead of asking
+                            during the scrape. Leave blank to be asked.
+                            {requiredPrompts.session.needsPrompts
+                                ? ' Recent scrapes asked for the fields ' +
+                                  'marked (likely).'
+                                : ''}
+                        </p>
+                        <div className="txn-grid">
+                            {requiredPrompts.prompts.map((prompt) => {
+                                const answerKey = prompt.key ?? prompt.message;
+                                const answer = promptAnswers[answerKey] ?? '';
+                                return (
+                                    <label className="field" key={answerKey}>
+                                        <span>
+                                            {prompt.message}
+                                            {prompt.likely ? ' (likely)' : ''}
+                                        </span>
+                                        {prompt.type === 'choice' ? (
+                                            <select
+                                                value={answer}
+                                                onChange={(event) => {
+                                                    const value =
+                                                        event.target.value;
+                                                    setPromptAnswers(
+                                                        (current) => ({
+                                                            ...current,
+                                                            [answerKey]: value,
+                                                        }),
+                                                    );
+                                                }}
+                                            >
+                                                <option value="">
+                                                    Ask during scrape
+                                                </option>
+                                                {(prompt.choices ?? []).map(
+                                                    (choice) => (
+                                                        <option
+                                                            key={choice}
+                                                            value={choice}
+                                                        >
+                                                            {choice}
+                                                        </option>
+                                                    ),
+                                                )}
+                                            </select>
+                                        ) : (
+                                            <input
+                                                type="text"
+                                                inputMode={
+                                                    prompt.type === 'otp'
+                                                        ? 'numeric'
+                                                        : undefined
+                                                }
+                                                autoComplete={
+                                                    prompt.type === 'otp'
+                                                        ? 'one-time-code'
+                                                        : 'off'
+                                                }
+                                                value={answer}
+                                                onChange={(event) => {
+                                                    const value =
+                                                        event.target.value;
+                                                    setPromptAnswers(
+                                                        (current) => ({
+                                                            ...current,
+                                                            [answerKey]: value,
+                                                        }),
+                                                    );
+                                                }}
+                                            />
+                                        )}
+                                    </label>
+                                );
+                            })}
+                        </div>
+                    </fieldset>
+                ) : null}
                 <div className="txn-actions">
                     <button
                         type="button"
@@ -2365,7 +2558,16 @@ export function ScrapeTab({
                                         <td>
                                             {entry.success ? 'OK' : 'Failed'}
                                         </td>
-                                        <td>{entry.error ?? ''}</td>
+                                        <td>
+                                            {entry.error ?? ''}
+                                            {entry.failure?.screenshot !==
+                                                undefined && (
+                                                <div className="hint">
+                                                    Screenshot:{' '}
+                                                    {entry.failure.screenshot}
+                                                </div>
+                                            )}
+                                        </td>
                                     </tr>
                                 ))}
                             </tbody>
@@ -2803,6 +3005,17 @@ export function ScrapeTab({
                                 ? 'Running extraction...'
                                 : `Run extraction (${selectedDocumentNames.length > 0 ? selectedDocumentNames.length : documents.length})`}
                         </button>
+                        {extractionJobId !== null && (
+                            <button
+                                type="button"
+                                className="ghost-button"
+                                onClick={() => {
+                                    void cancelJob(extractionJobId);
+                                }}
+                            >
+                                Cancel extraction
+                            </button>
+                        )}
                     </div>
                     {isLoadingDocuments ? (
                         <p className="status">Loading documents...</p>
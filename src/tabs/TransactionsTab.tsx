@@ -6,9 +6,12 @@ import {
     type AmountTotal,
     type GlCategoryResult,
     type LedgerView,
+    listPayees,
     mergeGlTransfer,
     type NewTransactionInput,
+    type PayeeSummary,
     queryTransactions,
+    queryTransactionsPage,
     recategorizeGlTransaction,
     suggestGlCategories,
     type TransactionRow,
@@ -322,6 +325,10 @@ export function TransactionsTab({
         null,
     );
     const [queryError, setQueryError] = useState<string | null>(null);
+    const [queryTotal, setQueryTotal] = useState(0);
+    const [queryCapped, setQueryCapped] = useState(false);
+    const [queryHasMore, setQueryHasMore] = useState(false);
+    const [isLoadingMore, setIsLoadingMore] = useState(false);
     const [isNewTxnExpandedOverride, setIsNewTxnExpandedOverride] = useState<
         boolean | null
     >(session.isNewTxnExpandedOverride);
@@ -345,6 +352,8 @@ export function TransactionsTab({
         newAccount: string;
     } | null>(null);
 
+    const [payeeOptions, setPayeeOptions] = useState<PayeeSummary[]>([]);
+
     const searchInputRef = useRef<HTMLInputElement>(null);
     const similarSearchInputRef = useRef<HTMLInputElement>(null);
     const hasSeenLedgerRef = useRef(false);
@@ -417,6 +426,21 @@ export function TransactionsTab({
         setBookkeepingFilter('all');
     }, [ledgerPath]);
 
+    // Reload payee autocompletion whenever the ledger view refreshes.
+    useEffect(() => {
+        let cancelled = false;
+        listPayees(ledger.path)
+            .then((payees) => {
+                if (!cancelled) setPayeeOptions(payees);
+            })
+            .catch(() => {
+                if (!cancelled) setPayeeOptions([]);
+            });
+        return () => {
+            cancelled = true;
+        };
+    }, [ledger]);
+
     // Apply cross-tab search navigation (e.g. from PipelineTab)
     useEffect(() => {
         if (pendingSearch !== null) {
@@ -451,17 +475,26 @@ export function TransactionsTab({
         if (!q) {
             setQueryResults(null);
             setQueryError(null);
+            setQueryTotal(0);
+            setQueryCapped(false);
+            setQueryHasMore(false);
             return;
         }
         const timer = setTimeout(() => {
             void (async () => {
                 try {
-                    const rows = await queryTransactions(ledgerPath, q);
-                    setQueryResults(rows);
+                    const page = await queryTransactionsPage(ledgerPath, q);
+                    setQueryResults(page.rows);
+                    setQueryTotal(page.total);
+                    setQueryCapped(page.capped);
+                    setQueryHasMore(page.hasMore);
                     setQueryError(null);
                 } catch (err) {
                     setQueryError(String(err));
                     setQueryResults(null);
+                    setQueryTotal(0);
+                    setQueryCapped(false);
+                    setQueryHasMore(false);
                 }
             })();
         }, 300);
@@ -470,6 +503,28 @@ export function TransactionsTab({
         };
     }, [transactionsSearch, ledger.transactions, ledgerPath]);
 
+    const loadMoreQueryResults = async () => {
+        const q = transactionsSearch.trim();
+        if (!q || queryResults === null) {
+            return;
+        }
+        setIsLoadingMore(true);
+        try {
+            const page = await queryTransactionsPage(ledgerPath, q, {
+                offset: queryResults.length,
+            });
+            setQueryResults([...queryResults, ...page.rows]);
+            setQueryTotal(page.total);
+            setQueryCapped(page.capped);
+            setQueryHasMore(page.hasMore);
+            setQueryError(null);
+        } catch (err) {
+            setQueryError(String(err));
+        } finally {
+            setIsLoadingMore(false);
+        }
+    };
+
     const activeRecategorizeTabId = activeRecategorizeTab?.id ?? null;
     const activeRecategorizeSearchQuery =
         activeRecategorizeTab?.plan.searchQuery ?? '';
@@ -1615,6 +1670,9 @@ export function TransactionsTab({
                             setBookkeepingFilter('all');
                             setQueryResults(null);
                             setQueryError(null);
+                            setQueryTotal(0);
+                            setQueryCapped(false);
+                            setQueryHasMore(false);
                         }}
                     >
                         Clear filter
@@ -1624,6 +1682,30 @@ export function TransactionsTab({
             {queryError !== null && (
                 <div className="query-error">{queryError}</div>
             )}
+            {queryCapped && (
+                <p className="hint">
+                    {queryTotal.toLocaleString()} transactions match, more than
+                    can be listed. Refine your query to narrow the results.
+                </p>
+            )}
+            {queryResults !== null && queryHasMore && (
+                <div className="filter-info">
+                    <span className="hint">
+                        Showing {queryResults.length.toLocaleString()} of{' '}
+                        {queryTotal.toLocaleString()} matches.
+                    </span>
+                    <button
+                        className="ghost-button"
+                        type="button"
+                        disabled={isLoadingMore}
+                        onClick={() => {
+                            void loadMoreQueryResults();
+                        }}
+                    >
+                        {isLoadingMore ? 'Loading…' : 'Load more'}
+                    </button>
+                </div>
+            )}
             <section className="txn-form">
                 <button
                     className="txn-form-toggle"
@@ -1771,6 +1853,7 @@ export function TransactionsTab({
                                         <span>Description</span>
                                         <input
                                             type="text"
+                                            list="payee-options"
                                             value={transactionDraft.description}
                                             placeholder="Description"
                                             onChange={(event) => {
@@ -1808,6 +1891,14 @@ export function TransactionsTab({
                                         />
                                     </label>
                                 </div>
+                                <datalist id="payee-options">
+                                    {payeeOptions.map((payee) => (
+                                        <option
+                                            key={payee.key}
+                                            value={payee.name}
+                                        />
+                                    ))}
+                                </datalist>
                                 <div className="txn-postings">
                                     <datalist id="account-options">
                                         {ledger.accounts
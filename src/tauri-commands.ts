@@ -1,265 +1,143 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen } from '@tauri-apps/api/event';
 import type { ScrapeLogEntry } from './scrapeLog.ts';
-
-export interface LedgerView {
-    path: string;
-    accounts: AccountRow[];
-    transactions: TransactionRow[];
-    glAccountConflicts: GlAccountConflict[];
-}
-
-export interface GlAccountConflict {
-    glAccount: string;
-    entries: GlAccountConflictEntry[];
-}
-
-export interface GlAccountConflictEntry {
-    loginName: string;
-    label: string;
-}
-
-export interface AccountRow {
-    name: string;
-    totals: AmountTotal[] | null;
-    unpostedCount: number;
-}
-
-export interface ReconciliationSession {
-    id: string;
-    glAccount: string;
-    statementStartDate: string | null;
-    statementEndDate: string;
-    statementStartingBalance: string | null;
-    statementEndingBalance: string;
-    currency: string | null;
-    status: 'draft' | 'finalized' | 'reopened';
-    reconciledTxnIds: string[];
-    notes: string | null;
-    createdAt: string;
-    updatedAt: string;
-}
-
-export interface NewReconciliationSessionInput {
-    glAccount: string;
-    statementStartDate: string | null;
-    statementEndDate: string;
-    statementStartingBalance: string | null;
-    statementEndingBalance: string;
-    currency: string | null;
-    reconciledTxnIds: string[];
-    notes: string | null;
-}
-
-export interface UpdateReconciliationSessionInput extends NewReconciliationSessionInput {
-    id: string;
-}
-
-export type TypedRefKind = 'gl-txn' | 'login-entry' | 'document';
-
-export interface TypedRef {
-    kind: TypedRefKind;
-    id?: string | null;
-    locator?: string | null;
-    entryId?: string | null;
-    loginName?: string | null;
-    label?: string | null;
-    filename?: string | null;
-}
-
-export type LinkKind = 'evidence-link' | 'settlement-link' | 'source-link';
-
-export interface LinkRecord {
-    id: string;
-    kind: LinkKind;
-    leftRef: TypedRef;
-    rightRef: TypedRef;
-    amount: string | null;
-    notes: string | null;
-    createdAt: string;
-    updatedAt: string;
-}
-
-export interface NewLinkRecordInput {
-    kind: LinkKind;
-    leftRef: TypedRef;
-    rightRef: TypedRef;
-    amount: string | null;
-    notes: string | null;
-}
-
-export type PeriodCloseStatus = 'draft' | 'soft-closed' | 'reopened';
-
-export interface PeriodClose {
-    periodId: string;
-    status: PeriodCloseStatus;
-    closedAt: string | null;
-    closedBy: string | null;
-    notes: string | null;
-    reconciliationSessionIds: string[];
-    adjustmentTxnIds: string[];
-}
-
-export interface UpsertPeriodCloseInput {
-    periodId: string;
-    status: PeriodCloseStatus;
-    closedBy: string | null;
-    notes: string | null;
-    reconciliationSessionIds: string[];
-    adjustmentTxnIds: string[];
-}
-
-export interface TransactionRow {
-    id: string;
-    date: string;
-    description: string;
-    descriptionRaw: string;
-    comment: string;
-    evidence: string[];
-    accounts: string;
-    totals: AmountTotal[] | null;
-    postings: PostingRow[];
-    bookkeeping: TransactionBookkeeping;
-}
-
-export interface TransactionBookkeeping {
-    generated: boolean;
-    reconciledSessionIds: string[];
-    linkedRecordIds: string[];
-    settlementLinkIds: string[];
-    softClosedPeriodId: string | null;
-}
-
-export interface AmountTotal {
-    commodity: string;
-    mantissa: string;
-    scale: number;
-    style: AmountStyleHint | null;
-}
-
-export interface AmountStyleHint {
-    side: 'L' | 'R';
-    spaced: boolean;
-}
-
-export interface PostingRow {
-    account: string;
-    amount: string | null;
-    comment: string;
-    totals: AmountTotal[] | null;
-}
+import type {
+    AccountConfig,
+    AccountCoverage,
+    AccountJournalEntry,
+    AccountType,
+    Alert,
+    AlertRule,
+    AllocateEnvelopeInput,
+    AmortizationRow,
+    BalanceHistoryPoint,
+    BookFormat,
+    BulkEntryOperation,
+    BulkUpdateResult,
+    CategoryFeedback,
+    CategoryResult,
+    ClassificationQueueItem,
+    ClassifyDecision,
+    ClassifyOutcome,
+    CommitPolicy,
+    CommitSigning,
+    CostBasisConfig,
+    DescriptionCleaning,
+    Digest,
+    DigestCadence,
+    DocumentRowsPage,
+    DocumentRowsRequest,
+    DocumentType,
+    DocumentWithInfo,
+    DomainSecretEntry,
+    EntryFilter,
+    EntryIdMigrationOutcome,
+    EntryQuery,
+    EntryRef,
+    EntryReviewState,
+    EnvelopeDefinition,
+    EnvelopeReport,
+    GlCategoryResult,
+    GlLayout,
+    GlLayoutConversion,
+    HledgerReportResult,
+    HledgerTiming,
+    ImportReport,
+    InstitutionInfo,
+    Invoice,
+    JobInfo,
+    JobProgress,
+    JournalExport,
+    JournalExportFormat,
+    JournalExportOptions,
+    JsApiVersionInfo,
+    LabelRule,
+    LedgerExport,
+    LedgerHistoryVerification,
+    LedgerTemplateId,
+    LedgerTemplateInfo,
+    LedgerView,
+    LinkRecord,
+    LintOptions,
+    LintReport,
+    Loan,
+    LoanBalanceReport,
+    LocationSpending,
+    LockStatusSnapshot,
+    LogLevels,
+    LogRecord,
+    LoginConfig,
+    LoginExtractionSupport,
+    LoginScrapeReliability,
+    MigrationOutcome,
+    NewLinkRecordInput,
+    NewReconciliationSessionInput,
+    NewTransactionInput,
+    OnboardingStatus,
+    OpeningBalance,
+    OpeningBalanceInput,
+    PaperTrailOptions,
+    PaperTrailReport,
+    PayeeDetail,
+    PayeeSummary,
+    PaystubDocument,
+    PerformanceStats,
+    PeriodClose,
+    PostedEntryConflict,
+    PriceBackfillResult,
+    RealizedGainsReport,
+    ReceivablesReport,
+    ReconciliationSession,
+    RecordInvoiceInput,
+    RedactedExport,
+    RedactionOptions,
+    RelabelReport,
+    ReportGroup,
+    RequiredPrompts,
+    ScrapeArtifact,
+    ScrapeArtifactContent,
+    ScrapePreflight,
+    ScrapeQueueSnapshot,
+    SecretSyncResult,
+    SplitCounterpart,
+    SplitPart,
+    StagingConfig,
+    SubscriptionChange,
+    SuspiciousActivityReport,
+    TaxCollectionOutcome,
+    TaxDocumentsReport,
+    TransactionPage,
+    TransactionProvenance,
+    TransactionRow,
+    UncommittedChanges,
+    UnknownAccountId,
+    UnpostedTransferResult,
+    UpdateReconciliationSessionInput,
+    UpsertLoanInput,
+    UpsertPeriodCloseInput,
+} from './bindings/index.ts';
+import type { JsonValue } from './bindings/serde_json/JsonValue.ts';
+
+export type * from './bindings/index.ts';
 
 /** The placeholder counterpart account used for uncategorized GL transactions. */
 export const UNCATEGORIZED_GL_ACCOUNT = 'Expenses:Unknown';
 
-/** Per-domain credential status returned by list/sync commands. */
-export interface DomainSecretEntry {
-    domain: string;
-    hasUsername: boolean;
-    hasPassword: boolean;
-}
-
-export interface SecretSyncResult {
-    /** All domains declared in the extension manifest. */
-    required: DomainSecretEntry[];
-    /** Required domains missing a username. */
-    missingUsername: string[];
-    /** Required domains missing a password. */
-    missingPassword: string[];
-    /** Domains in the store that are not declared by the manifest. */
-    extras: string[];
-}
-
-export interface MigratedAccount {
-    accountName: string;
-    loginName: string;
-    label: string;
-}
-
-export interface MigrationOutcome {
-    dryRun: boolean;
-    migrated: MigratedAccount[];
-    skipped: string[];
-    warnings: string[];
-}
-
-export interface DocumentInfo {
-    mimeType: string;
-    originalUrl?: string;
-    scrapedAt: string;
-    extensionName: string;
-    accountName?: string;
-    loginName?: string;
-    label?: string;
-    scrapeSessionId: string;
-    coverageEndDate: string;
-    dateRangeStart?: string;
-    dateRangeEnd?: string;
-}
-
-export interface DocumentWithInfo {
-    filename: string;
-    info: DocumentInfo | null;
-}
-
-export interface AccountJournalEntry {
-    id: string;
-    date: string;
-    bankStatus: 'pending' | 'posted' | 'unknown';
-    statusMarker: '' | '!' | '*';
-    description: string;
-    comment: string;
-    evidence: string[];
-    posted: string | null;
-    isTransfer: boolean;
-    /** Quantity of the first posting (no commodity symbol), e.g. "-21.32". */
-    amount: string | null;
-    /** All tags on the entry as [key, value] pairs. */
-    tags: [string, string][];
-}
-
-export type LockMetadataResource =
-    | { kind: 'login'; loginName: string }
-    | { kind: 'gl' };
-
-export interface LockMetadata {
-    version: number;
-    owner: string;
-    purpose: string;
-    startedAt: string;
-    pid?: number | null;
-    resource: LockMetadataResource;
-}
-
-export interface LockStatus {
-    locked: boolean;
-    metadata: LockMetadata | null;
-}
-
-export interface LockStatusSnapshot {
-    gl: LockStatus;
-    logins: Record<string, LockStatus>;
-}
-
-export interface LoginExtractionSupport {
-    supported: boolean;
-    reason:
-        | 'missing-extension'
-        | 'missing-extractor'
-        | 'broken-extractor'
-        | null;
+export async function newLedger(
+    ledger: string | null,
+    template?: LedgerTemplateId | null,
+): Promise<void> {
+    return invoke('new_ledger', { ledger, template: template ?? null });
 }
 
-export interface NewTransactionInput {
-    date: string;
-    description: string;
-    comment: string | null;
-    postings: NewPostingInput[];
+export async function listLedgerTemplates(): Promise<LedgerTemplateInfo[]> {
+    return invoke('list_ledger_templates');
 }
 
-export interface NewPostingInput {
-    account: string;
-    amount: string | null;
-    comment: string | null;
+export async function getOnboardingStatus(
+    ledger: string,
+): Promise<OnboardingStatus> {
+    return invoke('get_onboarding_status', { ledger });
 }
 
 export async function openLedger(ledger: string): Promise<LedgerView> {
@@ -294,6 +172,55 @@ export async function validateTransactionText(
     await invoke('validate_transaction_text', { ledger, transaction });
 }
 
+export async function addTransactionsText(
+    ledger: string,
+    transactions: string[],
+): Promise<LedgerView> {
+    return invoke('add_transactions_text', { ledger, transactions });
+}
+
+/** Returns one error message (or null) per transaction. */
+export async function validateTransactionsText(
+    ledger: string,
+    transactions: string[],
+): Promise<(string | null)[]> {
+    return invoke('validate_transactions_text', { ledger, transactions });
+}
+
+export async function getHledgerTimings(): Promise<HledgerTiming[]> {
+    return invoke('get_hledger_timings');
+}
+
+export async function resetHledgerTimings(): Promise<void> {
+    await invoke('reset_hledger_timings');
+}
+
+export async function getPerformanceStats(): Promise<PerformanceStats> {
+    return invoke('get_performance_stats');
+}
+
+export async function resetPerformanceStats(): Promise<void> {
+    await invoke('reset_performance_stats');
+}
+
+export async function getRecentLogs(
+    limit?: number,
+    minLevel?: string,
+): Promise<LogRecord[]> {
+    return invoke('get_recent_logs', {
+        limit: limit ?? null,
+        minLevel: minLevel ?? null,
+    });
+}
+
+export async function getLogLevels(): Promise<LogLevels> {
+    return invoke('get_log_levels');
+}
+
+export async function setLogLevels(levels: LogLevels): Promise<LogLevels> {
+    return invoke('set_log_levels', { levels });
+}
+
 export async function listReconciliationSessions(
     ledger: string,
 ): Promise<ReconciliationSession[]> {
@@ -370,6 +297,10 @@ export async function listScrapeExtensions(ledger: string): Promise<string[]> {
     return invoke('list_scrape_extensions', { ledger });
 }
 
+export async function getJsApiVersion(): Promise<JsApiVersionInfo> {
+    return invoke('get_js_api_version');
+}
+
 export async function loadScrapeExtension(
     ledger: string,
     source: string,
@@ -434,19 +365,65 @@ export async function runScrape(
     await runScrapeForLogin(ledger, account);
 }
 
+/**
+ * `dateFrom`/`dateTo` (YYYY-MM-DD) keep documents whose period, or failing
+ * that their scrape session's window, overlaps the range.
+ */
 export async function listDocuments(
     ledger: string,
     accountName: string,
+    documentType?: DocumentType,
+    dateFrom?: string,
+    dateTo?: string,
 ): Promise<DocumentWithInfo[]> {
-    return invoke('list_documents', { ledger, accountName });
+    return invoke('list_documents', {
+        ledger,
+        accountName,
+        documentType: documentType ?? null,
+        dateFrom: dateFrom ?? null,
+        dateTo: dateTo ?? null,
+    });
 }
 
 export async function listLoginAccountDocuments(
     ledger: string,
     loginName: string,
     label: string,
+    documentType?: DocumentType,
+    dateFrom?: string,
+    dateTo?: string,
 ): Promise<DocumentWithInfo[]> {
-    return invoke('list_login_account_documents', { ledger, loginName, label });
+    return invoke('list_login_account_documents', {
+        ledger,
+        loginName,
+        label,
+        documentType: documentType ?? null,
+        dateFrom: dateFrom ?? null,
+        dateTo: dateTo ?? null,
+    });
+}
+
+export async function pruneLoginAccountDocuments(
+    ledger: string,
+    loginName: string,
+    label: string,
+    dryRun: boolean,
+): Promise<string[]> {
+    return invoke('prune_login_account_documents', {
+        ledger,
+        loginName,
+        label,
+        dryRun,
+    });
+}
+
+/** Move `_default` documents that the login's label rules now label. */
+export async function relabelDocuments(
+    ledger: string,
+    loginName: string,
+    dryRun: boolean,
+): Promise<RelabelReport> {
+    return invoke('relabel_documents', { ledger, loginName, dryRun });
 }
 
 export async function readAttachmentDataUrl(
@@ -461,12 +438,16 @@ export async function readLoginAccountDocumentRows(
     loginName: string,
     label: string,
     documentName: string,
-): Promise<string[][]> {
+    page?: DocumentRowsRequest,
+): Promise<DocumentRowsPage> {
     return invoke('read_login_account_document_rows', {
         ledger,
         loginName,
         label,
         documentName,
+        offset: page?.offset ?? null,
+        limit: page?.limit ?? null,
+        columns: page?.columns ?? null,
     });
 }
 
@@ -488,59 +469,88 @@ export async function runExtraction(
     ledger: string,
     accountName: string,
     documentNames: string[],
+    callbacks?: JobCallbacks,
 ): Promise<number> {
-    return invoke('run_extraction', {
-        ledger,
-        accountName,
-        documentNames,
-    });
+    const newCount = await runJob(
+        'run_extraction',
+        { ledger, accountName, documentNames },
+        callbacks,
+    );
+    return newCount as number;
 }
 
+/** Documents marked `suspect` at download are skipped unless `includeSuspect`. */
 export async function runLoginAccountExtraction(
     ledger: string,
     loginName: string,
     label: string,
     documentNames: string[],
+    callbacks?: JobCallbacks,
+    includeSuspect?: boolean,
 ): Promise<number> {
-    return invoke('run_login_account_extraction', {
-        ledger,
-        loginName,
-        label,
-        documentNames,
-    });
+    const newCount = await runJob(
+        'run_login_account_extraction',
+        {
+            ledger,
+            loginName,
+            label,
+            documentNames,
+            includeSuspect: includeSuspect ?? null,
+        },
+        callbacks,
+    );
+    return newCount as number;
 }
 
 export async function getAccountJournal(
     ledger: string,
     accountName: string,
+    filter?: EntryFilter,
 ): Promise<AccountJournalEntry[]> {
-    return invoke('get_account_journal', { ledger, accountName });
+    return invoke('get_account_journal', {
+        ledger,
+        accountName,
+        filter: filter ?? null,
+    });
 }
 
 export async function getLoginAccountJournal(
     ledger: string,
     loginName: string,
     label: string,
+    filter?: EntryFilter,
 ): Promise<AccountJournalEntry[]> {
-    return invoke('get_login_account_journal', { ledger, loginName, label });
+    return invoke('get_login_account_journal', {
+        ledger,
+        loginName,
+        label,
+        filter: filter ?? null,
+    });
 }
 
 export async function getUnposted(
     ledger: string,
     accountName: string,
+    filter?: EntryFilter,
 ): Promise<AccountJournalEntry[]> {
-    return invoke('get_unposted', { ledger, accountName });
+    return invoke('get_unposted', {
+        ledger,
+        accountName,
+        filter: filter ?? null,
+    });
 }
 
 export async function getLoginAccountUnposted(
     ledger: string,
     loginName: string,
     label: string,
+    filter?: EntryFilter,
 ): Promise<AccountJournalEntry[]> {
     return invoke('get_login_account_unposted', {
         ledger,
         loginName,
         label,
+        filter: filter ?? null,
     });
 }
 
@@ -578,11 +588,6 @@ export async function postLoginAccountEntry(
     });
 }
 
-export interface SplitCounterpart {
-    account: string;
-    amount: string | null;
-}
-
 export async function postLoginAccountEntrySplit(
     ledger: string,
     loginName: string,
@@ -629,6 +634,84 @@ export async function unpostLoginAccountEntry(
     });
 }
 
+/** A null or blank note clears it. */
+export async function setEntryNote(
+    ledger: string,
+    loginName: string,
+    label: string,
+    entryId: string,
+    note: string | null,
+): Promise<void> {
+    await invoke('set_entry_note', { ledger, loginName, label, entryId, note });
+}
+
+export async function setEntryReviewState(
+    ledger: string,
+    loginName: string,
+    label: string,
+    entryId: string,
+    state: EntryReviewState | null,
+): Promise<void> {
+    await invoke('set_entry_review_state', {
+        ledger,
+        loginName,
+        label,
+        entryId,
+        state,
+    });
+}
+
+/** Pass entry ids or a query; `dryRun` only counts the affected entries. */
+export async function bulkUpdateEntries(
+    ledger: string,
+    loginName: string,
+    label: string,
+    queryOrIds: string[] | EntryQuery,
+    operations: BulkEntryOperation[],
+    dryRun = false,
+): Promise<BulkUpdateResult> {
+    return invoke('bulk_update_entries', {
+        ledger,
+        loginName,
+        label,
+        queryOrIds,
+        operations,
+        dryRun,
+    });
+}
+
+/** Replace an unposted entry with parts whose amounts sum to it. */
+export async function splitLoginAccountEntry(
+    ledger: string,
+    loginName: string,
+    label: string,
+    entryId: string,
+    parts: SplitPart[],
+): Promise<AccountJournalEntry[]> {
+    return invoke('split_login_account_entry', {
+        ledger,
+        loginName,
+        label,
+        entryId,
+        parts,
+    });
+}
+
+/** `entryId` is the original entry's id, as in the parts' `splitFrom` tag. */
+export async function mergeLoginAccountEntrySplit(
+    ledger: string,
+    loginName: string,
+    label: string,
+    entryId: string,
+): Promise<void> {
+    await invoke('merge_login_account_entry_split', {
+        ledger,
+        loginName,
+        label,
+        entryId,
+    });
+}
+
 export async function postTransfer(
     ledger: string,
     account1: string,
@@ -645,12 +728,6 @@ export async function postTransfer(
     });
 }
 
-export interface UnpostedTransferResult {
-    loginName: string;
-    label: string;
-    entry: AccountJournalEntry;
-}
-
 export async function getUnpostedEntriesForTransfer(
     ledger: string,
     excludeLogin: string,
@@ -694,21 +771,26 @@ export async function syncGlTransaction(
     return invoke('sync_gl_transaction', { ledger, loginName, label, entryId });
 }
 
-export interface TransferMatch {
-    accountLocator: string;
-    entryId: string;
-    matchedAmount: string;
+export async function listPostedEntryConflicts(
+    ledger: string,
+    loginName: string,
+    label: string,
+): Promise<PostedEntryConflict[]> {
+    return invoke('list_posted_entry_conflicts', { ledger, loginName, label });
 }
 
-export interface CategoryResult {
-    /** Suggested counterpart account, or null if confidence < 0.5. */
-    suggested: string | null;
-    /** True if the entry's posting amount differs from the GL transaction. */
-    amountChanged: boolean;
-    /** True if the entry's status differs from the GL transaction. */
-    statusChanged: boolean;
-    /** Auto-detected transfer match, or null if none / ambiguous. */
-    transferMatch: TransferMatch | null;
+export async function acceptPostedEntryChanges(
+    ledger: string,
+    loginName: string,
+    label: string,
+    entryId: string,
+): Promise<string> {
+    return invoke('accept_posted_entry_changes', {
+        ledger,
+        loginName,
+        label,
+        entryId,
+    });
 }
 
 export async function suggestCategories(
@@ -719,19 +801,47 @@ export async function suggestCategories(
     return invoke('suggest_categories', { ledger, loginName, label });
 }
 
-export interface GlTransferMatch {
-    /** GL transaction ID of the matched counterpart. */
-    txnId: string;
-    description: string;
-    date: string;
-    matchedAmount: string;
+/**
+ * Record the account chosen for an entry after seeing its suggestion, so
+ * later suggestions learn from it.
+ */
+export async function recordCategoryFeedback(
+    ledger: string,
+    loginName: string,
+    label: string,
+    entryId: string,
+    suggested: string | null,
+    account: string,
+): Promise<CategoryFeedback> {
+    return invoke('record_category_feedback', {
+        ledger,
+        loginName,
+        label,
+        entryId,
+        suggested,
+        account,
+    });
+}
+
+export async function getClassificationQueue(
+    ledger: string,
+): Promise<ClassificationQueueItem[]> {
+    return invoke('get_classification_queue', { ledger });
+}
+
+export async function classifyNext(
+    ledger: string,
+    entryRef: EntryRef,
+    decision: ClassifyDecision,
+): Promise<ClassifyOutcome> {
+    return invoke('classify_next', { ledger, entryRef, decision });
 }
 
-export interface GlCategoryResult {
-    /** ML-suggested replacement for `Expenses:Unknown`, or null. */
-    suggested: string | null;
-    /** Auto-detected transfer pair among other Expenses:Unknown GL txns. */
-    transferMatch: GlTransferMatch | null;
+export async function getTransactionProvenance(
+    ledger: string,
+    txnId: string,
+): Promise<TransactionProvenance> {
+    return invoke('get_transaction_provenance', { ledger, txnId });
 }
 
 export async function suggestGlCategories(
@@ -762,19 +872,6 @@ export async function mergeGlTransfer(
     return invoke('merge_gl_transfer', { ledger, txnId1, txnId2 });
 }
 
-export interface AccountConfig {
-    extension?: string;
-}
-
-export interface LoginAccountConfig {
-    glAccount?: string | null;
-}
-
-export interface LoginConfig {
-    extension?: string;
-    accounts: Record<string, LoginAccountConfig>;
-}
-
 export async function getAccountConfig(
     ledger: string,
     accountName: string,
@@ -837,60 +934,167 @@ export async function setLoginAccount(
     await invoke('set_login_account', { ledger, loginName, label, glAccount });
 }
 
-export async function removeLoginAccount(
+export async function setLoginAccountType(
     ledger: string,
     loginName: string,
     label: string,
+    accountType: AccountType | null,
 ): Promise<void> {
-    await deleteLoginAccount(ledger, loginName, label);
+    await invoke('set_login_account_type', {
+        ledger,
+        loginName,
+        label,
+        accountType,
+    });
 }
 
-export async function deleteLoginAccount(
+/** Set the institution account ids whose transactions route to `label`. */
+/**
+ * Set the account extracted single-sided entries balance against; `null`
+ * restores the ledger default. Returns the account now in effect.
+ */
+export async function setLoginAccountCounterpart(
     ledger: string,
     loginName: string,
     label: string,
-): Promise<void> {
-    await invoke('delete_login_account', { ledger, loginName, label });
-}
-
-export async function listLoginSecrets(
-    loginName: string,
-): Promise<DomainSecretEntry[]> {
-    return invoke('list_login_secrets', { loginName });
+    counterpartAccount: string | null,
+): Promise<string> {
+    return invoke('set_login_account_counterpart', {
+        ledger,
+        loginName,
+        label,
+        counterpartAccount,
+    });
 }
 
-export async function syncLoginSecretsForExtension(
+export async function setLoginAccountIds(
     ledger: string,
     loginName: string,
-    extension: string,
-): Promise<SecretSyncResult> {
-    return invoke('sync_login_secrets_for_extension', {
+    label: string,
+    accountIds: string[],
+): Promise<string[]> {
+    return invoke('set_login_account_ids', {
         ledger,
         loginName,
-        extension,
+        label,
+        accountIds,
     });
 }
 
-/** Store username + password together (one biometric prompt on macOS). */
-export async function setLoginCredentials(
+/** Account ids extraction found that no label of the login claims. */
+export async function listUnknownAccountIds(
+    ledger: string,
     loginName: string,
-    domain: string,
-    username: string,
-    password: string,
-): Promise<void> {
-    await invoke('set_login_credentials', {
-        loginName,
-        domain,
-        username,
-        password,
-    });
+): Promise<UnknownAccountId[]> {
+    return invoke('list_unknown_account_ids', { ledger, loginName });
 }
 
-/** Store only the username (no biometric prompt on macOS). */
-export async function setLoginUsername(
+/** Route an account id to `label` and drop it from the unknown list. */
+export async function assignAccountId(
+    ledger: string,
     loginName: string,
-    domain: string,
-    username: string,
+    accountId: string,
+    label: string,
+): Promise<string[]> {
+    return invoke('assign_account_id', {
+        ledger,
+        loginName,
+        accountId,
+        label,
+    });
+}
+
+export async function getLoginInstitution(
+    ledger: string,
+    loginName: string,
+): Promise<InstitutionInfo | null> {
+    return invoke('get_login_institution', { ledger, loginName });
+}
+
+/** Returns the stored metadata after trimming, or `null` when cleared. */
+export async function setLoginInstitution(
+    ledger: string,
+    loginName: string,
+    institution: InstitutionInfo | null,
+): Promise<InstitutionInfo | null> {
+    return invoke('set_login_institution', { ledger, loginName, institution });
+}
+
+export async function setLoginLabelRules(
+    ledger: string,
+    loginName: string,
+    rules: LabelRule[],
+): Promise<LabelRule[]> {
+    return invoke('set_login_label_rules', { ledger, loginName, rules });
+}
+
+/** GL account name proposed from the institution and account type. */
+export async function suggestLoginAccountGlAccount(
+    ledger: string,
+    loginName: string,
+    label: string,
+): Promise<string> {
+    return invoke('suggest_login_account_gl_account', {
+        ledger,
+        loginName,
+        label,
+    });
+}
+
+export async function removeLoginAccount(
+    ledger: string,
+    loginName: string,
+    label: string,
+): Promise<void> {
+    await deleteLoginAccount(ledger, loginName, label);
+}
+
+export async function deleteLoginAccount(
+    ledger: string,
+    loginName: string,
+    label: string,
+): Promise<void> {
+    await invoke('delete_login_account', { ledger, loginName, label });
+}
+
+export async function listLoginSecrets(
+    loginName: string,
+): Promise<DomainSecretEntry[]> {
+    return invoke('list_login_secrets', { loginName });
+}
+
+export async function syncLoginSecretsForExtension(
+    ledger: string,
+    loginName: string,
+    extension: string,
+): Promise<SecretSyncResult> {
+    return invoke('sync_login_secrets_for_extension', {
+        ledger,
+        loginName,
+        extension,
+    });
+}
+
+/** Store username + password together (one biometric prompt on macOS). */
+export async function setLoginCredentials(
+    loginName: string,
+    domain: string,
+    username: string,
+    password: string,
+): Promise<void> {
+    await invoke('set_login_credentials', {
+        loginName,
+        domain,
+        username,
+        password,
+    });
+}
+
+/** Store only the username (no biometric prompt on macOS). */
+export async function setLoginUsername(
+    loginName: string,
+    domain: string,
+    username: string,
 ): Promise<void> {
     await invoke('set_login_username', { loginName, domain, username });
 }
@@ -934,20 +1138,516 @@ export async function clearLoginProfile(
     await invoke('clear_login_profile', { ledger, loginName });
 }
 
+/** Driver storage entries keyed by extension name, then by key. */
+export type LoginStorage = Record<string, Record<string, unknown>>;
+
+export async function getLoginStorage(
+    ledger: string,
+    loginName: string,
+): Promise<LoginStorage> {
+    return invoke<LoginStorage>('get_login_storage', { ledger, loginName });
+}
+
+/**
+ * Clear one key (with `extension`), one extension's storage, or all driver
+ * storage for the login. Returns the number of entries removed.
+ */
+export async function clearLoginStorage(
+    ledger: string,
+    loginName: string,
+    extension?: string,
+    key?: string,
+): Promise<number> {
+    return invoke<number>('clear_login_storage', {
+        ledger,
+        loginName,
+        extension: extension ?? null,
+        key: key ?? null,
+    });
+}
+
 export async function runScrapeForLogin(
     ledger: string,
     loginName: string,
     source: 'manual' | 'auto' = 'manual',
     headless = false,
+    prompts?: Record<string, string>,
 ): Promise<void> {
     await invoke('run_scrape_for_login', {
         ledger,
         loginName,
         source,
         headless,
+        prompts: prompts ?? null,
+    });
+}
+
+/** Checks a scrape's prerequisites without launching a browser. */
+export async function verifyScrapePrerequisites(
+    ledger: string,
+    loginName: string,
+): Promise<ScrapePreflight> {
+    return invoke('verify_scrape_prerequisites', { ledger, loginName });
+}
+
+export async function getRequiredPrompts(
+    ledger: string,
+    loginName: string,
+): Promise<RequiredPrompts> {
+    return invoke('get_required_prompts', { ledger, loginName });
+}
+
+export async function collectTaxDocuments(
+    ledger: string,
+    year: number,
+    headless = false,
+): Promise<TaxCollectionOutcome> {
+    return invoke('collect_tax_documents', { ledger, year, headless });
+}
+
+export async function listTaxDocuments(
+    ledger: string,
+    year: number,
+): Promise<TaxDocumentsReport> {
+    return invoke('list_tax_documents', { ledger, year });
+}
+
+export async function listPaystubs(ledger: string): Promise<PaystubDocument[]> {
+    return invoke('list_paystubs', { ledger });
+}
+
+export async function getCostBasisConfig(
+    ledger: string,
+): Promise<CostBasisConfig> {
+    return invoke('get_cost_basis_config', { ledger });
+}
+
+export async function saveCostBasisConfig(
+    ledger: string,
+    config: CostBasisConfig,
+): Promise<CostBasisConfig> {
+    return invoke('save_cost_basis_config', { ledger, config });
+}
+
+export async function reportRealizedGains(
+    ledger: string,
+    year?: number,
+): Promise<RealizedGainsReport> {
+    return invoke('report_realized_gains', { ledger, year: year ?? null });
+}
+
+export async function listInvoices(ledger: string): Promise<Invoice[]> {
+    return invoke('list_invoices', { ledger });
+}
+
+export async function recordInvoice(
+    ledger: string,
+    invoice: RecordInvoiceInput,
+): Promise<Invoice> {
+    return invoke('record_invoice', { ledger, invoice });
+}
+
+export async function reportAccountsReceivable(
+    ledger: string,
+    asOf?: string,
+): Promise<ReceivablesReport> {
+    return invoke('report_accounts_receivable', {
+        ledger,
+        asOf: asOf ?? null,
+    });
+}
+
+export async function exportEntriesToLedger(
+    ledger: string,
+    query: string,
+    targetLedger: string,
+): Promise<LedgerExport> {
+    return invoke('export_entries_to_ledger', { ledger, query, targetLedger });
+}
+
+/** Copy the ledger to `options.target` with sensitive data redacted. */
+export async function exportRedactedLedger(
+    ledger: string,
+    options: RedactionOptions,
+): Promise<RedactedExport> {
+    return invoke('export_redacted_ledger', { ledger, options });
+}
+
+/** Write account statements for a period as a printable HTML or PDF file. */
+export async function writePaperTrailReport(
+    ledger: string,
+    options: PaperTrailOptions,
+): Promise<PaperTrailReport> {
+    return invoke('write_paper_trail_report', { ledger, options });
+}
+
+/** Write one login account's journal as CSV or JSON, with evidence links. */
+export async function exportAccountJournal(
+    ledger: string,
+    loginName: string,
+    label: string,
+    format: JournalExportFormat,
+    options: JournalExportOptions,
+): Promise<JournalExport> {
+    return invoke('export_account_journal', {
+        ledger,
+        loginName,
+        label,
+        format,
+        options,
+    });
+}
+
+/** Work out a login account's opening balance without recording it. */
+export async function planOpeningBalance(
+    ledger: string,
+    loginName: string,
+    label: string,
+    input: OpeningBalanceInput,
+): Promise<OpeningBalance> {
+    return invoke('plan_opening_balance', { ledger, loginName, label, input });
+}
+
+/** Post the opening-balance transaction and set the account's coverage start. */
+export async function recordOpeningBalance(
+    ledger: string,
+    loginName: string,
+    label: string,
+    input: OpeningBalanceInput,
+): Promise<OpeningBalance> {
+    return invoke('record_opening_balance', {
+        ledger,
+        loginName,
+        label,
+        input,
+    });
+}
+
+export async function reportBalanceHistory(
+    ledger: string,
+    account: string,
+    period: 'daily' | 'weekly' | 'monthly' = 'daily',
+): Promise<BalanceHistoryPoint[]> {
+    return invoke('report_balance_history', { ledger, account, period });
+}
+
+export async function listLoans(ledger: string): Promise<Loan[]> {
+    return invoke('list_loans', { ledger });
+}
+
+export async function upsertLoan(
+    ledger: string,
+    loan: UpsertLoanInput,
+): Promise<Loan> {
+    return invoke('upsert_loan', { ledger, loan });
+}
+
+export async function deleteLoan(ledger: string, id: string): Promise<void> {
+    await invoke('delete_loan', { ledger, id });
+}
+
+export async function getLoanSchedule(
+    ledger: string,
+    id: string,
+): Promise<AmortizationRow[]> {
+    return invoke('get_loan_schedule', { ledger, id });
+}
+
+export async function reportLoanBalance(
+    ledger: string,
+    id: string,
+    asOf: string | null = null,
+): Promise<LoanBalanceReport> {
+    return invoke('report_loan_balance', { ledger, id, asOf });
+}
+
+export async function listAlertRules(ledger: string): Promise<AlertRule[]> {
+    return invoke('list_alert_rules', { ledger });
+}
+
+export async function saveAlertRules(
+    ledger: string,
+    rules: AlertRule[],
+): Promise<AlertRule[]> {
+    return invoke('save_alert_rules', { ledger, rules });
+}
+
+export async function listAlerts(
+    ledger: string,
+    includeClosed = false,
+): Promise<Alert[]> {
+    return invoke('list_alerts', { ledger, includeClosed });
+}
+
+export async function acknowledgeAlert(
+    ledger: string,
+    id: string,
+): Promise<Alert> {
+    return invoke('acknowledge_alert', { ledger, id });
+}
+
+export async function dismissAlert(ledger: string, id: string): Promise<Alert> {
+    return invoke('dismiss_alert', { ledger, id });
+}
+
+/** Rank entries since `since` (default: 30 days ago) by fraud heuristics. */
+export async function reportSuspiciousActivity(
+    ledger: string,
+    since?: string,
+): Promise<SuspiciousActivityReport> {
+    return invoke('report_suspicious_activity', {
+        ledger,
+        since: since ?? null,
+    });
+}
+
+export async function reportSubscriptionChanges(
+    ledger: string,
+): Promise<SubscriptionChange[]> {
+    return invoke('report_subscription_changes', { ledger });
+}
+
+export async function getDescriptionCleaning(
+    ledger: string,
+): Promise<DescriptionCleaning> {
+    return invoke('get_description_cleaning', { ledger });
+}
+
+export async function setDescriptionCleaning(
+    ledger: string,
+    cleaning: DescriptionCleaning,
+): Promise<void> {
+    return invoke('set_description_cleaning', { ledger, cleaning });
+}
+
+/** What `cleaning` would make of `descriptions`, without saving it. */
+export async function previewDescriptionCleaning(
+    cleaning: DescriptionCleaning,
+    descriptions: string[],
+): Promise<string[]> {
+    return invoke('preview_description_cleaning', { cleaning, descriptions });
+}
+
+/** Expense totals per tagged location between optional `YYYY-MM-DD` dates. */
+export async function reportSpendingByLocation(
+    ledger: string,
+    since?: string,
+    until?: string,
+): Promise<LocationSpending[]> {
+    return invoke('report_spending_by_location', {
+        ledger,
+        since: since ?? null,
+        until: until ?? null,
+    });
+}
+
+export async function listPayees(
+    ledger: string,
+    query?: string,
+): Promise<PayeeSummary[]> {
+    return invoke('list_payees', { ledger, query: query ?? null });
+}
+
+export async function getPayee(
+    ledger: string,
+    payee: string,
+): Promise<PayeeDetail> {
+    return invoke('get_payee', { ledger, payee });
+}
+
+/**
+ * Write `P` directives for `commodity` in `quote` (default USD) to
+ * prices.journal. Omitted dates default to the span of GL postings in the
+ * commodity; `provider` is `ecb` (default) or `frankfurter`.
+ */
+export async function backfillPrices(
+    ledger: string,
+    commodity: string,
+    options: {
+        quote?: string;
+        start?: string;
+        end?: string;
+        provider?: string;
+    } = {},
+): Promise<PriceBackfillResult> {
+    return invoke('backfill_prices', { ledger, commodity, ...options });
+}
+
+export async function lintLedger(
+    ledger: string,
+    options: LintOptions = {},
+): Promise<LintReport> {
+    return invoke('lint_ledger', { ledger, options });
+}
+
+export async function listDigests(ledger: string): Promise<Digest[]> {
+    return invoke('list_digests', { ledger });
+}
+
+export async function generateDigest(
+    ledger: string,
+    cadence: DigestCadence,
+    asOf?: string,
+    commodity?: string,
+): Promise<Digest> {
+    return invoke('generate_digest', {
+        ledger,
+        cadence,
+        asOf: asOf ?? null,
+        commodity: commodity ?? null,
     });
 }
 
+export async function generateDueDigests(
+    ledger: string,
+    commodity?: string,
+): Promise<Digest[]> {
+    return invoke('generate_due_digests', {
+        ledger,
+        commodity: commodity ?? null,
+    });
+}
+
+export async function getScrapeQueue(): Promise<ScrapeQueueSnapshot> {
+    return invoke('get_scrape_queue');
+}
+
+export async function setScrapeConcurrencyLimit(limit: number): Promise<void> {
+    await invoke('set_scrape_concurrency_limit', { limit });
+}
+
+export interface JobCallbacks {
+    /** Called with the job id once the job has started. */
+    onStart?: (jobId: number) => void;
+    onProgress?: (progress: JobProgress) => void;
+}
+
+/**
+ * Start a job with `command` and resolve with its result once it finishes.
+ * Rejects with the job's error, or with `cancelled` after `cancelJob`.
+ */
+async function runJob(
+    command: string,
+    args: Record<string, unknown>,
+    callbacks?: JobCallbacks,
+): Promise<JsonValue> {
+    let jobId: number | null = null;
+    // Events that arrive before `invoke` returns the id.
+    const early: JobInfo[] = [];
+    let resolveFinished: ((job: JobInfo) => void) | undefined;
+    const finished = new Promise<JobInfo>((resolve) => {
+        resolveFinished = resolve;
+    });
+    const update = (job: JobInfo) => {
+        if (job.state !== 'running') {
+            resolveFinished?.(job);
+        } else if (job.progress !== null) {
+            callbacks?.onProgress?.(job.progress);
+        }
+    };
+    const unlisten = await listen<JobInfo>(
+        'refreshmint://job-changed',
+        (event) => {
+            if (jobId === null) {
+                early.push(event.payload);
+            } else if (event.payload.id === jobId) {
+                update(event.payload);
+            }
+        },
+    );
+    try {
+        const id = await invoke<number>(command, args);
+        jobId = id;
+        callbacks?.onStart?.(id);
+        early.filter((job) => job.id === id).forEach(update);
+        const job = await finished;
+        if (job.state === 'succeeded') {
+            return job.result;
+        }
+        throw new Error(job.error ?? job.state);
+    } finally {
+        unlisten();
+    }
+}
+
+export async function listJobs(): Promise<JobInfo[]> {
+    return invoke('list_jobs');
+}
+
+export async function cancelJob(id: number): Promise<void> {
+    await invoke('cancel_job', { id });
+}
+
+export async function getCommitPolicy(ledger: string): Promise<CommitPolicy> {
+    return invoke('get_commit_policy', { ledger });
+}
+
+export async function saveCommitPolicy(
+    ledger: string,
+    policy: CommitPolicy,
+): Promise<CommitPolicy> {
+    return invoke('save_commit_policy', { ledger, policy });
+}
+
+export async function getStagingConfig(
+    ledger: string,
+): Promise<StagingConfig> {
+    return invoke('get_staging_config', { ledger });
+}
+
+export async function saveStagingConfig(
+    ledger: string,
+    config: StagingConfig,
+): Promise<StagingConfig> {
+    return invoke('save_staging_config', { ledger, config });
+}
+
+export async function getGlLayout(ledger: string): Promise<GlLayout> {
+    return invoke('get_gl_layout', { ledger });
+}
+
+/** Move GL transactions into `layout`'s files and post there from now on. */
+export async function convertGlLayout(
+    ledger: string,
+    layout: GlLayout,
+): Promise<GlLayoutConversion> {
+    return invoke('convert_gl_layout', { ledger, layout });
+}
+
+export async function getUncommittedChanges(
+    ledger: string,
+): Promise<UncommittedChanges> {
+    return invoke('get_uncommitted_changes', { ledger });
+}
+
+/** Commit changes held back by a batched or manual commit policy. */
+export async function commitPendingChanges(
+    ledger: string,
+    message: string,
+): Promise<UncommittedChanges> {
+    return invoke('commit_pending_changes', { ledger, message });
+}
+
+export async function getCommitSigning(
+    ledger: string,
+): Promise<CommitSigning | null> {
+    return invoke('get_commit_signing', { ledger });
+}
+
+/** Pass `null` to stop signing commits. */
+export async function saveCommitSigning(
+    ledger: string,
+    signing: CommitSigning | null,
+): Promise<CommitSigning | null> {
+    return invoke('save_commit_signing', { ledger, signing });
+}
+
+export async function verifyLedgerHistory(
+    ledger: string,
+): Promise<LedgerHistoryVerification> {
+    return invoke('verify_ledger_history', { ledger });
+}
+
 export async function getScrapeLog(
     ledger: string,
     loginName: string,
@@ -955,11 +1655,84 @@ export async function getScrapeLog(
     return invoke('get_scrape_log', { ledger, loginName });
 }
 
+/** Failure screenshots, reports, and logs a login's scrapes left behind. */
+export async function listScrapeArtifacts(
+    ledger: string,
+    loginName: string,
+    sessionId?: string,
+): Promise<ScrapeArtifact[]> {
+    return invoke('list_scrape_artifacts', {
+        ledger,
+        loginName,
+        sessionId: sessionId ?? null,
+    });
+}
+
+/** Read an artifact by the ledger-relative path `listScrapeArtifacts` gave. */
+export async function readScrapeArtifact(
+    ledger: string,
+    path: string,
+): Promise<ScrapeArtifactContent> {
+    return invoke('read_scrape_artifact', { ledger, path });
+}
+
+export async function reportScrapeReliability(
+    ledger: string,
+    period: 'daily' | 'weekly' | 'monthly' = 'weekly',
+    loginName?: string,
+): Promise<LoginScrapeReliability[]> {
+    return invoke('report_scrape_reliability', {
+        ledger,
+        period,
+        loginName: loginName ?? null,
+    });
+}
+
+/** Gaps and overlaps in each login account's document coverage. */
+export async function reportCoverageGaps(
+    ledger: string,
+    loginName?: string,
+): Promise<AccountCoverage[]> {
+    return invoke('report_coverage_gaps', {
+        ledger,
+        loginName: loginName ?? null,
+    });
+}
+
 export async function migrateLedger(
     ledger: string,
     dryRun: boolean,
+    callbacks?: JobCallbacks,
 ): Promise<MigrationOutcome> {
-    return invoke('migrate_ledger', { ledger, dryRun });
+    const outcome = await runJob(
+        'migrate_ledger',
+        { ledger, dryRun },
+        callbacks,
+    );
+    return outcome as MigrationOutcome;
+}
+
+export async function importBook(
+    ledger: string,
+    format: BookFormat,
+    path: string,
+    startDate: string | null,
+    dryRun: boolean,
+    callbacks?: JobCallbacks,
+): Promise<ImportReport> {
+    const report = await runJob(
+        'import_book',
+        { ledger, format, path, startDate, dryRun },
+        callbacks,
+    );
+    return report as ImportReport;
+}
+
+export async function migrateEntryIds(
+    ledger: string,
+    dryRun?: boolean,
+): Promise<EntryIdMigrationOutcome> {
+    return invoke('migrate_entry_ids', { ledger, dryRun: dryRun ?? null });
 }
 
 export async function repairLoginAccountLabels(
@@ -976,6 +1749,18 @@ export async function queryTransactions(
     return invoke<TransactionRow[]>('query_transactions', { ledger, query });
 }
 
+export async function queryTransactionsPage(
+    ledger: string,
+    query: string,
+    options: { offset?: number; limit?: number; rowCap?: number } = {},
+): Promise<TransactionPage> {
+    return invoke<TransactionPage>('query_transactions_page', {
+        ledger,
+        query,
+        ...options,
+    });
+}
+
 export async function queryReconciliationCandidates(
     ledger: string,
     glAccount: string,
@@ -990,15 +1775,52 @@ export async function queryReconciliationCandidates(
     });
 }
 
-export interface HledgerReportResult {
-    rows: string[][]; // all rows including header; empty for text commands
-    text: string | null; // plain text output for stats/activity
-}
-
 export async function runHledgerReport(
     ledger: string,
     command: string,
     args: string[],
+    grouped = false,
 ): Promise<HledgerReportResult> {
-    return invoke('run_hledger_report', { ledger, command, args });
+    return invoke('run_hledger_report', { ledger, command, args, grouped });
+}
+
+export async function listReportGroups(ledger: string): Promise<ReportGroup[]> {
+    return invoke('list_report_groups', { ledger });
+}
+
+export async function upsertReportGroup(
+    ledger: string,
+    name: string,
+    accounts: string[],
+): Promise<ReportGroup[]> {
+    return invoke('upsert_report_group', { ledger, name, accounts });
+}
+
+export async function getEnvelopes(
+    ledger: string,
+    month: string | null = null,
+    commodity: string | null = null,
+): Promise<EnvelopeReport> {
+    return invoke('get_envelopes', { ledger, month, commodity });
+}
+
+export async function defineEnvelope(
+    ledger: string,
+    envelope: EnvelopeDefinition,
+): Promise<EnvelopeDefinition> {
+    return invoke('define_envelope', { ledger, envelope });
+}
+
+export async function allocateEnvelope(
+    ledger: string,
+    allocation: AllocateEnvelopeInput,
+): Promise<void> {
+    await invoke('allocate_envelope', { ledger, allocation });
+}
+
+export async function deleteReportGroup(
+    ledger: string,
+    name: string,
+): Promise<ReportGroup[]> {
+    return invoke('delete_report_group', { ledger, name });
 }
@@ -1,4 +1,6 @@
 import type {
+    AccountType,
+    InstitutionInfo,
     LoginConfig,
     LoginAccountConfig,
     LockStatus,
@@ -168,16 +170,35 @@ export function normalizeLoginAccountConfig(
     if (!isRecord(value)) {
         return {};
     }
+    const config: LoginAccountConfig = {};
     const glAccount = value['glAccount'];
     if (typeof glAccount === 'string' || glAccount === null) {
-        return { glAccount };
+        config.glAccount = glAccount;
     }
-    return {};
+    const accountType = value['accountType'];
+    if (
+        typeof accountType === 'string' &&
+        (ACCOUNT_TYPES as readonly string[]).includes(accountType)
+    ) {
+        config.accountType = accountType as AccountType;
+    }
+    const accountIds = value['accountIds'];
+    if (Array.isArray(accountIds)) {
+        config.accountIds = accountIds.filter(
+            (id): id is string => typeof id === 'string',
+        );
+    }
+    return config;
 }
 
+/** A login config whose `accounts` map is always present. */
+export type NormalizedLoginConfig = Omit<LoginConfig, 'accounts'> & {
+    accounts: Record<string, LoginAccountConfig>;
+};
+
 export function normalizeLoginConfig(
     value: LoginConfig | null | undefined,
-): LoginConfig {
+): NormalizedLoginConfig {
     if (!isRecord(value)) {
         return { accounts: {} };
     }
@@ -189,16 +210,53 @@ export function normalizeLoginConfig(
             accounts[label] = normalizeLoginAccountConfig(accountConfig);
         }
     }
-    const normalized: LoginConfig = { accounts };
+    const normalized: NormalizedLoginConfig = { accounts };
     if (typeof extension === 'string') {
         normalized.extension = extension;
     }
+    const institution = value['institution'];
+    if (isRecord(institution)) {
+        normalized.institution = institution as InstitutionInfo;
+    }
     return normalized;
 }
 
-export function suggestGlAccountName(label: string): string {
+export const ACCOUNT_TYPES: readonly AccountType[] = [
+    'bank',
+    'card',
+    'brokerage',
+    'retirement',
+    'benefits',
+];
+
+export function suggestGlAccountName(
+    label: string,
+    accountType?: AccountType | null,
+): string {
     const lc = label.toLowerCase();
     const name = label.charAt(0).toUpperCase() + label.slice(1);
+    switch (accountType) {
+        case 'bank':
+            return /savings/.test(lc)
+                ? `Assets:Savings:${name}`
+                : `Assets:Checking:${name}`;
+        case 'card':
+            return `Liabilities:CreditCard:${name}`;
+        case 'brokerage':
+            return `Assets:Investments:${name}`;
+        case 'retirement':
+            return `Assets:Retirement:${name}`;
+        case 'benefits':
+            return `Assets:Benefits:${name}`;
+        default:
+            break;
+    }
+    if (/\b(401k|403b|ira)\b|retire/.test(lc)) {
+        return `Assets:Retirement:${name}`;
+    }
+    if (/\b(hsa|fsa)\b/.test(lc)) {
+        return `Assets:Benefits:${name}`;
+    }
     if (/credit|card|visa|mastercard|amex|discover/.test(lc)) {
         return `Liabilities:CreditCard:${name}`;
     }